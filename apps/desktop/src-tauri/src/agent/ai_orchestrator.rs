@@ -333,6 +333,7 @@ impl AIOrchestrator {
                     target_files: vec![],
                     constraints: vec![],
                     context: "".to_string(),
+                    edit_regions: vec![],
                 };
 
                 let result = self.code_generator.generate_code(request).await?;
@@ -1,10 +1,14 @@
 use super::*;
+use crate::audit::{self, Actor, AuditCategory, AuditEntry, AuditOutcome};
+use crate::notifications::Notification;
+use crate::security::{CommandValidator, SafetyLevel};
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{self, json};
 use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::{oneshot, Mutex as TokioMutex};
 
@@ -158,7 +162,7 @@ impl ApprovalManager {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ApprovalScopeType {
     Terminal,
@@ -196,24 +200,122 @@ pub struct ApprovalRequestPayload {
     pub action_signature: String,
 }
 
+impl ApprovalRequestPayload {
+    /// A shell command the command validator rates dangerous; approving it
+    /// needs re-authentication
+    pub fn is_dangerous_command(&self) -> bool {
+        let Some(command) = self.scope.command.as_deref() else {
+            return false;
+        };
+        matches!(self.scope.scope_type, ApprovalScopeType::Terminal)
+            && matches!(
+                CommandValidator::new().validate_command(command, &[]),
+                Ok(SafetyLevel::Dangerous | SafetyLevel::Blocked)
+            )
+    }
+
+    /// Below high risk, so a policy may approve it without asking
+    pub fn is_auto_approvable(&self) -> bool {
+        matches!(self.risk_level.to_lowercase().as_str(), "low" | "medium")
+            && !self.is_dangerous_command()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ApprovalResolution {
-    Approved { trust: bool },
-    Rejected { reason: Option<String> },
+    Approved {
+        trust: bool,
+    },
+    Rejected {
+        reason: Option<String>,
+    },
+    /// The action is not run, but the run carries on without it
+    Skipped {
+        reason: Option<String>,
+    },
+}
+
+/// What happens to an approval request nobody decides on in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutAction {
+    /// Reject the action
+    #[default]
+    Fail,
+    /// Skip the action and continue the run
+    Skip,
+}
+
+/// How approval requests for a tool category, or a single tool, are handled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalPolicy {
+    pub id: String,
+    /// Category the policy covers; every category when unset
+    #[serde(default)]
+    pub category: Option<ApprovalScopeType>,
+    /// Tool the policy covers; wins over category policies
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    /// Approve without asking. Never applies to high-risk requests or
+    /// dangerous shell commands.
+    #[serde(default)]
+    pub auto_approve: bool,
+    /// Seconds to wait for a decision; waits indefinitely when unset
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub on_timeout: TimeoutAction,
+    /// Seconds after which an undecided request is escalated with another
+    /// notification
+    #[serde(default)]
+    pub escalate_after_secs: Option<u64>,
+}
+
+impl ApprovalPolicy {
+    fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            return Err(anyhow!("Approval policy needs an id"));
+        }
+        if self.timeout_secs == Some(0) || self.escalate_after_secs == Some(0) {
+            return Err(anyhow!("Approval timeouts must be at least one second"));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, request: &ApprovalRequestPayload) -> bool {
+        self.tool_name
+            .as_deref()
+            .is_none_or(|tool| tool == request.tool_name)
+            && self
+                .category
+                .as_ref()
+                .is_none_or(|category| *category == request.scope.scope_type)
+    }
+
+    /// Tool policies beat category policies, which beat catch-alls
+    fn specificity(&self) -> u8 {
+        u8::from(self.tool_name.is_some()) * 2 + u8::from(self.category.is_some())
+    }
 }
 
 pub struct ApprovalController {
     pending: TokioMutex<HashMap<String, oneshot::Sender<ApprovalResolution>>>,
+    pending_requests: TokioMutex<HashMap<String, ApprovalRequestPayload>>,
     trust_store: TokioMutex<TrustedWorkflowStore>,
+    policies: TokioMutex<ApprovalPolicyStore>,
     current_hash: TokioMutex<Option<String>>,
 }
 
 impl ApprovalController {
     pub fn new(data_dir: PathBuf) -> Result<Self> {
         let trust_store = TrustedWorkflowStore::load(data_dir.join("trusted_workflows.json"))?;
+        let policies = ApprovalPolicyStore::load(data_dir.join("approval_policies.json"))?;
         Ok(Self {
             pending: TokioMutex::new(HashMap::new()),
+            pending_requests: TokioMutex::new(HashMap::new()),
             trust_store: TokioMutex::new(trust_store),
+            policies: TokioMutex::new(policies),
             current_hash: TokioMutex::new(None),
         })
     }
@@ -243,23 +345,63 @@ impl ApprovalController {
             }
         }
 
+        let policy = self.policies.lock().await.policy_for(&payload);
+        if let Some(policy) = policy
+            .as_ref()
+            .filter(|policy| policy.auto_approve && payload.is_auto_approvable())
+        {
+            tracing::info!(
+                "[Approval] Policy {} auto-approved {} for action {}",
+                policy.id,
+                payload.tool_name,
+                payload.action_id
+            );
+            audit::record(
+                app_handle,
+                AuditEntry::new(Actor::system(), AuditCategory::Permission, "approval_auto")
+                    .target(payload.tool_name.clone())
+                    .detail(format!("Policy {}", policy.id)),
+            );
+            return Ok(ApprovalResolution::Approved { trust: false });
+        }
+
         let (tx, rx) = oneshot::channel();
         {
             let mut pending = self.pending.lock().await;
             pending.insert(payload.action_id.clone(), tx);
         }
+        self.pending_requests
+            .lock()
+            .await
+            .insert(payload.action_id.clone(), payload.clone());
 
         self.emit_status(app_handle, "paused", &payload.reason)?;
 
         if let Err(error) = app_handle.emit("agent:permission_required", &payload) {
             let mut pending = self.pending.lock().await;
             pending.remove(&payload.action_id);
+            self.pending_requests
+                .lock()
+                .await
+                .remove(&payload.action_id);
             return Err(anyhow!("Failed to emit approval request: {}", error));
         }
+        crate::notifications::notify(
+            app_handle,
+            Notification::approval(&payload.action_id, &payload.title, &payload.description),
+        );
 
         let action_signature = payload.action_signature.clone();
 
-        match rx.await {
+        let Some(received) = self
+            .wait_for_decision(app_handle, &payload, rx, policy.as_ref())
+            .await
+        else {
+            let policy = policy.expect("only a policy sets a timeout");
+            return Ok(self.time_out(app_handle, &payload, &policy).await);
+        };
+
+        match received {
             Ok(resolution) => {
                 if let (ApprovalResolution::Approved { trust }, Some(hash)) =
                     (&resolution, payload.workflow_hash.as_deref())
@@ -273,6 +415,10 @@ impl ApprovalController {
             }
             Err(_) => {
                 self.pending.lock().await.remove(&payload.action_id);
+                self.pending_requests
+                    .lock()
+                    .await
+                    .remove(&payload.action_id);
                 Err(anyhow!(
                     "Approval channel dropped for {}",
                     payload.action_id
@@ -281,6 +427,110 @@ impl ApprovalController {
         }
     }
 
+    /// The decision, or `None` when the policy's timeout passed without one.
+    /// Escalates once if the policy asks for it.
+    async fn wait_for_decision(
+        &self,
+        app_handle: &AppHandle,
+        payload: &ApprovalRequestPayload,
+        mut rx: oneshot::Receiver<ApprovalResolution>,
+        policy: Option<&ApprovalPolicy>,
+    ) -> Option<std::result::Result<ApprovalResolution, oneshot::error::RecvError>> {
+        let timeout = policy
+            .and_then(|policy| policy.timeout_secs)
+            .map(Duration::from_secs);
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+
+        let escalate_after = policy
+            .and_then(|policy| policy.escalate_after_secs)
+            .map(Duration::from_secs)
+            .filter(|after| timeout.is_none_or(|timeout| *after < timeout));
+        if let Some(after) = escalate_after {
+            match tokio::time::timeout(after, &mut rx).await {
+                Ok(received) => return Some(received),
+                Err(_) => self.escalate(app_handle, payload, after),
+            }
+        }
+
+        match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, rx).await.ok(),
+            None => Some(rx.await),
+        }
+    }
+
+    fn escalate(&self, app_handle: &AppHandle, payload: &ApprovalRequestPayload, waited: Duration) {
+        tracing::warn!(
+            "[Approval] {} still undecided after {}s, escalating",
+            payload.action_id,
+            waited.as_secs()
+        );
+        let _ = app_handle.emit(
+            "agent:approval_escalated",
+            json!({ "id": payload.action_id, "waitedSecs": waited.as_secs() }),
+        );
+        crate::notifications::notify(
+            app_handle,
+            Notification::approval(
+                &payload.action_id,
+                format!("Still waiting: {}", payload.title),
+                format!(
+                    "{} (waiting {} min)",
+                    payload.description,
+                    waited.as_secs().div_ceil(60)
+                ),
+            ),
+        );
+    }
+
+    /// Drop an expired request and apply the policy's timeout action
+    async fn time_out(
+        &self,
+        app_handle: &AppHandle,
+        payload: &ApprovalRequestPayload,
+        policy: &ApprovalPolicy,
+    ) -> ApprovalResolution {
+        self.pending.lock().await.remove(&payload.action_id);
+        self.pending_requests
+            .lock()
+            .await
+            .remove(&payload.action_id);
+
+        let reason = format!(
+            "No decision within {}s (policy {})",
+            policy.timeout_secs.unwrap_or_default(),
+            policy.id
+        );
+        tracing::warn!("[Approval] {}: {}", payload.action_id, reason);
+        let _ = app_handle.emit(
+            "agent:approval_timeout",
+            json!({
+                "id": payload.action_id,
+                "action": policy.on_timeout,
+                "reason": reason,
+            }),
+        );
+        audit::record(
+            app_handle,
+            AuditEntry::new(
+                Actor::system(),
+                AuditCategory::Permission,
+                "approval_timed_out",
+            )
+            .target(payload.tool_name.clone())
+            .outcome(AuditOutcome::Denied)
+            .detail(reason.clone()),
+        );
+
+        match policy.on_timeout {
+            TimeoutAction::Fail => ApprovalResolution::Rejected {
+                reason: Some(reason),
+            },
+            TimeoutAction::Skip => ApprovalResolution::Skipped {
+                reason: Some(reason),
+            },
+        }
+    }
+
     pub async fn resolve(&self, action_id: &str, resolution: ApprovalResolution) -> Result<()> {
         let sender = {
             let mut pending = self.pending.lock().await;
@@ -288,12 +538,27 @@ impl ApprovalController {
                 .remove(action_id)
                 .ok_or_else(|| anyhow!("Approval {} not pending", action_id))?
         };
+        self.pending_requests.lock().await.remove(action_id);
 
         sender
             .send(resolution)
             .map_err(|_| anyhow!("Failed to send approval resolution for {}", action_id))
     }
 
+    pub async fn pending_request(&self, action_id: &str) -> Option<ApprovalRequestPayload> {
+        self.pending_requests.lock().await.get(action_id).cloned()
+    }
+
+    /// Snapshot of approval requests still waiting for a decision
+    pub async fn pending_requests(&self) -> Vec<ApprovalRequestPayload> {
+        self.pending_requests
+            .lock()
+            .await
+            .values()
+            .cloned()
+            .collect()
+    }
+
     pub async fn is_action_trusted(
         &self,
         workflow_hash: Option<&str>,
@@ -321,6 +586,22 @@ impl ApprovalController {
             .map_err(|e| anyhow!("Failed to emit status update: {}", e))
     }
 
+    /// Policies, most specific first
+    pub async fn policies(&self) -> Vec<ApprovalPolicy> {
+        self.policies.lock().await.sorted()
+    }
+
+    /// Add a policy or replace the one with the same id
+    pub async fn set_policy(&self, policy: ApprovalPolicy) -> Result<()> {
+        policy.validate()?;
+        self.policies.lock().await.upsert(policy)
+    }
+
+    /// Whether a policy with the id existed
+    pub async fn remove_policy(&self, id: &str) -> Result<bool> {
+        self.policies.lock().await.remove(id)
+    }
+
     pub async fn set_current_hash(&self, hash: Option<String>) {
         let mut guard = self.current_hash.lock().await;
         *guard = hash;
@@ -390,3 +671,66 @@ impl TrustedWorkflowStore {
             .with_context(|| format!("Failed to write {:?}", self.path))
     }
 }
+
+#[derive(Debug)]
+struct ApprovalPolicyStore {
+    path: PathBuf,
+    policies: Vec<ApprovalPolicy>,
+}
+
+impl ApprovalPolicyStore {
+    fn load(path: PathBuf) -> Result<Self> {
+        let policies = if path.exists() {
+            let contents =
+                fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+            if contents.trim().is_empty() {
+                Vec::new()
+            } else {
+                serde_json::from_str(&contents)
+                    .with_context(|| format!("Failed to parse {:?}", path))?
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, policies })
+    }
+
+    fn policy_for(&self, request: &ApprovalRequestPayload) -> Option<ApprovalPolicy> {
+        self.policies
+            .iter()
+            .filter(|policy| policy.matches(request))
+            .max_by_key(|policy| policy.specificity())
+            .cloned()
+    }
+
+    fn sorted(&self) -> Vec<ApprovalPolicy> {
+        let mut policies = self.policies.clone();
+        policies.sort_by_key(|policy| std::cmp::Reverse(policy.specificity()));
+        policies
+    }
+
+    fn upsert(&mut self, policy: ApprovalPolicy) -> Result<()> {
+        match self.policies.iter_mut().find(|p| p.id == policy.id) {
+            Some(existing) => *existing = policy,
+            None => self.policies.push(policy),
+        }
+        self.persist()
+    }
+
+    fn remove(&mut self, id: &str) -> Result<bool> {
+        let before = self.policies.len();
+        self.policies.retain(|policy| policy.id != id);
+        if self.policies.len() == before {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    fn persist(&self) -> Result<()> {
+        let serialized = serde_json::to_string_pretty(&self.policies)?;
+        fs::write(&self.path, serialized)
+            .with_context(|| format!("Failed to write {:?}", self.path))
+    }
+}
@@ -8,6 +8,10 @@
 /// - Pattern-aware code creation
 use crate::agent::context_manager::{Constraint, ContextManager};
 use crate::agent::intelligent_file_access::IntelligentFileAccess;
+use crate::agent::symbol_context::{
+    collect_symbol_context, EditRegion, SymbolContextConfig, SymbolContextStats,
+};
+use crate::commands::lsp::LSPState;
 use crate::mcp::McpToolRegistry;
 use crate::router::LLMRouter;
 use anyhow::Result;
@@ -24,6 +28,9 @@ pub struct CodeGenRequest {
     pub target_files: Vec<PathBuf>,
     pub constraints: Vec<Constraint>,
     pub context: String, // Additional context
+    /// Lines being changed; when empty, each target file is scanned in full
+    #[serde(default)]
+    pub edit_regions: Vec<EditRegion>,
 }
 
 /// Generated code file
@@ -56,6 +63,9 @@ pub struct CodeGenResult {
     pub changes_summary: String,
     pub validation_errors: Vec<String>,
     pub suggestions: Vec<String>,
+    /// Language server symbols added to the prompt, if any were found
+    #[serde(default)]
+    pub symbol_context: Option<SymbolContextStats>,
 }
 
 /// AI-native code generator
@@ -64,6 +74,8 @@ pub struct CodeGenerator {
     mcp_registry: Option<McpToolRegistry>,
     llm_router: Option<Arc<LLMRouter>>,
     file_access: IntelligentFileAccess,
+    lsp_state: Option<Arc<LSPState>>,
+    symbol_context_config: SymbolContextConfig,
 }
 
 impl CodeGenerator {
@@ -76,6 +88,8 @@ impl CodeGenerator {
                 // Fallback if initialization fails
                 IntelligentFileAccess::default()
             }),
+            lsp_state: None,
+            symbol_context_config: SymbolContextConfig::default(),
         }
     }
 
@@ -88,6 +102,15 @@ impl CodeGenerator {
         self.llm_router = Some(router);
     }
 
+    /// Use running language servers to add symbol signatures to prompts
+    pub fn set_lsp_state(&mut self, lsp_state: Arc<LSPState>) {
+        self.lsp_state = Some(lsp_state);
+    }
+
+    pub fn set_symbol_context_config(&mut self, config: SymbolContextConfig) {
+        self.symbol_context_config = config;
+    }
+
     /// Generate code based on request
     pub async fn generate_code(&self, request: CodeGenRequest) -> Result<CodeGenResult> {
         // Build context prompt
@@ -98,10 +121,22 @@ impl CodeGenerator {
         // Analyze existing code if target files exist
         let existing_code = self.analyze_existing_code(&request.target_files).await?;
 
+        // Look up signatures of the symbols used in the edited code
+        let (symbol_context, symbol_stats) = self
+            .build_symbol_context(&request, &existing_code)
+            .await
+            .unwrap_or_default();
+
         // Generate code using LLM (via MCP or direct)
         let generated_code = if let Some(ref router) = self.llm_router {
-            self.generate_with_llm(router, &request, &context_prompt, &existing_code)
-                .await?
+            self.generate_with_llm(
+                router,
+                &request,
+                &context_prompt,
+                &existing_code,
+                &symbol_context,
+            )
+            .await?
         } else {
             // Fallback: use MCP tools for code generation
             self.generate_with_mcp(&request, &context_prompt, &existing_code)
@@ -125,9 +160,53 @@ impl CodeGenerator {
             changes_summary,
             validation_errors,
             suggestions,
+            symbol_context: symbol_stats,
         })
     }
 
+    /// Symbol context block for the prompt, or `None` when disabled or no
+    /// language server is running
+    async fn build_symbol_context(
+        &self,
+        request: &CodeGenRequest,
+        existing_code: &HashMap<PathBuf, String>,
+    ) -> Option<(String, Option<SymbolContextStats>)> {
+        if !self.symbol_context_config.enabled {
+            return None;
+        }
+        let lsp_state = self.lsp_state.as_ref()?;
+
+        let regions = if request.edit_regions.is_empty() {
+            request
+                .target_files
+                .iter()
+                .map(|path| EditRegion {
+                    path: path.clone(),
+                    start_line: 0,
+                    end_line: u32::MAX,
+                })
+                .collect()
+        } else {
+            request.edit_regions.clone()
+        };
+
+        let (block, stats) = collect_symbol_context(
+            lsp_state,
+            &regions,
+            existing_code,
+            &self.symbol_context_config,
+        )
+        .await;
+        tracing::debug!(
+            "[CodeGenerator] Symbol context for {}: {} of {} symbols resolved, ~{} tokens",
+            request.task_id,
+            stats.symbols_resolved,
+            stats.symbols_queried,
+            stats.estimated_tokens
+        );
+        Some((block, Some(stats)))
+    }
+
     /// Analyze existing code in target files (with intelligent fallback to screenshots)
     async fn analyze_existing_code(&self, files: &[PathBuf]) -> Result<HashMap<PathBuf, String>> {
         let mut code_map = HashMap::new();
@@ -186,6 +265,7 @@ impl CodeGenerator {
         request: &CodeGenRequest,
         context_prompt: &str,
         existing_code: &HashMap<PathBuf, String>,
+        symbol_context: &str,
     ) -> Result<Vec<GeneratedFile>> {
         tracing::info!(
             "[CodeGenerator] Generating code with LLM for task: {}",
@@ -234,6 +314,8 @@ impl CodeGenerator {
             ));
         }
 
+        prompt.push_str(symbol_context);
+
         prompt.push_str("\n## Generation Instructions\n\n");
         prompt.push_str("Generate code that:\n");
         prompt.push_str("1. Implements the requested functionality\n");
@@ -241,6 +323,9 @@ impl CodeGenerator {
         prompt.push_str("3. Integrates seamlessly with existing code\n");
         prompt.push_str("4. Includes comprehensive tests\n");
         prompt.push_str("5. Has proper documentation\n");
+        if !symbol_context.is_empty() {
+            prompt.push_str("6. Only calls APIs shown in the symbol context or existing code\n");
+        }
         prompt.push_str("\n## Output Format\n\n");
         prompt.push_str("Return JSON array with this structure:\n");
         prompt.push_str("[\n  {\n    \"path\": \"file/path\",\n    \"content\": \"file content\",\n    \"file_type\": \"source|test|config|documentation|type_definition\",\n    \"dependencies\": [\"dep1\"],\n    \"exports\": [\"export1\"]\n  }\n]\n");
@@ -456,6 +541,7 @@ impl CodeGenerator {
             target_files: files,
             constraints,
             context: "Refactoring existing code while maintaining functionality".to_string(),
+            edit_regions: Vec::new(),
         };
 
         // Generate refactored code
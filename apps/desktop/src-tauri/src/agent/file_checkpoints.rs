@@ -0,0 +1,472 @@
+//! Snapshots of files taken before agents change them
+//!
+//! Before an agent tool writes or deletes a file, the file's prior content
+//! goes into a content-addressed blob store (one copy per distinct content)
+//! and a row in `agent_file_snapshots` keyed by the run. A run's changes can
+//! then be reviewed as diffs against those snapshots, and rolling the run back
+//! restores every file it touched to its state before the run's first change,
+//! all or nothing.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+
+use crate::db::Pool;
+
+/// Agent tools that write or delete a file, with the parameter naming it
+const CHECKPOINTED_TOOLS: &[(&str, &str)] = &[
+    ("file_write", "path"),
+    ("file_delete", "path"),
+    ("api_download", "save_path"),
+    ("cloud_download", "local_path"),
+];
+
+/// Files larger than this are not diffed in change reviews
+const MAX_DIFF_BYTES: usize = 1024 * 1024;
+
+/// The file a tool call is about to change, if the tool changes files
+pub fn target_path(tool_name: &str, params: &Value) -> Option<PathBuf> {
+    CHECKPOINTED_TOOLS
+        .iter()
+        .find(|(tool, _)| *tool == tool_name)
+        .and_then(|(_, key)| params.get(*key))
+        .and_then(Value::as_str)
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    /// Changed and changed back, or the write failed
+    Unchanged,
+}
+
+/// A file a run touched, compared with its state before the run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: String,
+    pub kind: FileChangeKind,
+    /// Tools that changed the file, in order
+    pub tools: Vec<String>,
+    /// Unified diff for text files
+    pub diff: Option<String>,
+    pub rolled_back: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackReport {
+    pub run_id: String,
+    /// Files put back to their earlier content
+    pub restored: Vec<String>,
+    /// Files the run created, now removed
+    pub removed: Vec<String>,
+}
+
+/// Earliest snapshot of each file a run touched
+struct Snapshot {
+    path: PathBuf,
+    blob_hash: Option<String>,
+    tools: Vec<String>,
+    rolled_back: bool,
+}
+
+/// Content-addressed store of pre-change file content. Managed as app state.
+#[derive(Clone)]
+pub struct CheckpointStore {
+    pool: Pool,
+    blob_dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(pool: Pool, root: impl Into<PathBuf>) -> Self {
+        Self {
+            pool,
+            blob_dir: root.into().join("blobs"),
+        }
+    }
+
+    /// Store under the app data directory
+    pub fn open(pool: Pool) -> Result<Self> {
+        Ok(Self::new(
+            pool,
+            crate::utils::app_data_dir()?.join("file_checkpoints"),
+        ))
+    }
+
+    pub fn from_app(app: &tauri::AppHandle) -> Option<Self> {
+        use tauri::Manager;
+        app.try_state::<CheckpointStore>()
+            .map(|store| store.inner().clone())
+    }
+
+    /// Snapshot the file `tool_name` is about to change for `run_id`. Does
+    /// nothing for tools that do not change files.
+    pub async fn before_tool(&self, run_id: &str, tool_name: &str, params: &Value) -> Result<()> {
+        let Some(path) = target_path(tool_name, params) else {
+            return Ok(());
+        };
+        let store = self.clone();
+        let run_id = run_id.to_string();
+        let tool_name = tool_name.to_string();
+        self.pool
+            .run(move |conn| store.snapshot(conn, &run_id, &tool_name, &path))
+            .await
+    }
+
+    /// [`Self::changes`] on the store's pool
+    pub async fn run_changes(&self, run_id: &str) -> Result<Vec<FileChange>> {
+        let store = self.clone();
+        let run_id = run_id.to_string();
+        self.pool
+            .run(move |conn| store.changes(conn, &run_id))
+            .await
+    }
+
+    /// [`Self::rollback`] on the store's pool
+    pub async fn rollback_run(&self, run_id: &str) -> Result<RollbackReport> {
+        let store = self.clone();
+        let run_id = run_id.to_string();
+        self.pool
+            .run(move |conn| store.rollback(conn, &run_id))
+            .await
+    }
+
+    /// Record the current content of `path` (or that it does not exist)
+    pub fn snapshot(
+        &self,
+        conn: &Connection,
+        run_id: &str,
+        tool_name: &str,
+        path: &Path,
+    ) -> Result<()> {
+        let path = std::path::absolute(path)?;
+        if path.is_dir() {
+            return Err(anyhow!("{} is a directory", path.display()));
+        }
+        let blob_hash = if path.exists() {
+            let content = std::fs::read(&path)
+                .with_context(|| format!("Failed to snapshot {}", path.display()))?;
+            Some(self.put_blob(&content)?)
+        } else {
+            None
+        };
+        conn.execute(
+            "INSERT INTO agent_file_snapshots (run_id, path, tool_name, blob_hash, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run_id,
+                path.to_string_lossy(),
+                tool_name,
+                blob_hash,
+                Utc::now().timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.blob_dir.join(&hash[..2]).join(hash)
+    }
+
+    fn put_blob(&self, content: &[u8]) -> Result<String> {
+        let hash = hex::encode(Sha256::digest(content));
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            let dir = path.parent().expect("blob path has a parent");
+            std::fs::create_dir_all(dir)?;
+            // Write then rename so a crash never leaves a truncated blob
+            let partial = dir.join(format!("{}.partial", hash));
+            std::fs::write(&partial, content)?;
+            std::fs::rename(&partial, &path)?;
+        }
+        Ok(hash)
+    }
+
+    fn get_blob(&self, hash: &str) -> Result<Vec<u8>> {
+        std::fs::read(self.blob_path(hash))
+            .with_context(|| format!("Snapshot {} is missing from the store", hash))
+    }
+
+    /// With `pending_only`, snapshots an earlier rollback already undid are skipped
+    fn snapshots(
+        &self,
+        conn: &Connection,
+        run_id: &str,
+        pending_only: bool,
+    ) -> Result<Vec<Snapshot>> {
+        let mut stmt = conn.prepare(
+            "SELECT path, blob_hash, tool_name, rolled_back_at IS NOT NULL
+             FROM agent_file_snapshots
+             WHERE run_id = ?1 AND (?2 = 0 OR rolled_back_at IS NULL)
+             ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map(params![run_id, pending_only], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, bool>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut snapshots: Vec<Snapshot> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (path, blob_hash, tool, rolled_back) in rows {
+            match index.get(&path) {
+                Some(&i) => snapshots[i].tools.push(tool),
+                None => {
+                    index.insert(path.clone(), snapshots.len());
+                    snapshots.push(Snapshot {
+                        path: PathBuf::from(path),
+                        blob_hash,
+                        tools: vec![tool],
+                        rolled_back,
+                    });
+                }
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// Every file the run touched, compared with its state before the run
+    pub fn changes(&self, conn: &Connection, run_id: &str) -> Result<Vec<FileChange>> {
+        self.snapshots(conn, run_id, false)?
+            .into_iter()
+            .map(|snapshot| {
+                let before = snapshot
+                    .blob_hash
+                    .as_deref()
+                    .map(|hash| self.get_blob(hash))
+                    .transpose()?;
+                let after = std::fs::read(&snapshot.path).ok();
+                let kind = match (&before, &after) {
+                    (None, Some(_)) => FileChangeKind::Created,
+                    (Some(_), None) => FileChangeKind::Deleted,
+                    (Some(before), Some(after)) if before != after => FileChangeKind::Modified,
+                    _ => FileChangeKind::Unchanged,
+                };
+                let diff = match kind {
+                    FileChangeKind::Unchanged => None,
+                    _ => text_diff(
+                        &snapshot.path,
+                        before.as_deref().unwrap_or_default(),
+                        after.as_deref().unwrap_or_default(),
+                    ),
+                };
+                Ok(FileChange {
+                    path: snapshot.path.to_string_lossy().into_owned(),
+                    kind,
+                    tools: snapshot.tools,
+                    diff,
+                    rolled_back: snapshot.rolled_back,
+                })
+            })
+            .collect()
+    }
+
+    /// Put every file the run touched back the way it was before the run.
+    /// Either all files are restored or, if one fails, the ones already
+    /// restored are put back and nothing is marked rolled back.
+    pub fn rollback(&self, conn: &mut Connection, run_id: &str) -> Result<RollbackReport> {
+        let snapshots = self.snapshots(conn, run_id, true)?;
+
+        // Load everything first so a missing blob fails before any file changes
+        let mut plan = Vec::with_capacity(snapshots.len());
+        for snapshot in &snapshots {
+            let before = snapshot
+                .blob_hash
+                .as_deref()
+                .map(|hash| self.get_blob(hash))
+                .transpose()?;
+            plan.push((snapshot.path.as_path(), before));
+        }
+
+        let mut report = RollbackReport {
+            run_id: run_id.to_string(),
+            ..Default::default()
+        };
+        let mut undo: Vec<(&Path, Option<Vec<u8>>)> = Vec::with_capacity(plan.len());
+        for (path, before) in &plan {
+            let current = std::fs::read(path).ok();
+            if let Err(e) = restore_file(path, before.as_deref()) {
+                for (path, content) in undo.iter().rev() {
+                    if let Err(undo_err) = restore_file(path, content.as_deref()) {
+                        tracing::error!(
+                            "[Checkpoints] Failed to undo partial rollback of {}: {}",
+                            path.display(),
+                            undo_err
+                        );
+                    }
+                }
+                return Err(e.context(format!(
+                    "Rollback of run {} failed at {}; no files were changed",
+                    run_id,
+                    path.display()
+                )));
+            }
+            undo.push((path, current));
+            let display = path.to_string_lossy().into_owned();
+            match before {
+                Some(_) => report.restored.push(display),
+                None => report.removed.push(display),
+            }
+        }
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE agent_file_snapshots SET rolled_back_at = ?2
+             WHERE run_id = ?1 AND rolled_back_at IS NULL",
+            params![run_id, Utc::now().timestamp_millis()],
+        )?;
+        tx.commit()?;
+        Ok(report)
+    }
+}
+
+/// Write `content` to `path`, or remove the file when it should not exist
+fn restore_file(path: &Path, content: Option<&[u8]>) -> Result<()> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        None if path.exists() => std::fs::remove_file(path)?,
+        None => {}
+    }
+    Ok(())
+}
+
+fn text_diff(path: &Path, before: &[u8], after: &[u8]) -> Option<String> {
+    if before.len() > MAX_DIFF_BYTES || after.len() > MAX_DIFF_BYTES {
+        return None;
+    }
+    let before = std::str::from_utf8(before).ok()?;
+    let after = std::str::from_utf8(after).ok()?;
+    let name = path.to_string_lossy();
+    Some(
+        TextDiff::from_lines(before, after)
+            .unified_diff()
+            .header(&format!("a/{}", name), &format!("b/{}", name))
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use serde_json::json;
+
+    fn setup() -> (tempfile::TempDir, CheckpointStore, Connection) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let store = CheckpointStore::new(Pool::in_memory().unwrap(), dir.path().join("store"));
+        (dir, store, conn)
+    }
+
+    #[test]
+    fn test_target_path() {
+        assert_eq!(
+            target_path("file_write", &json!({ "path": "/tmp/a.txt" })),
+            Some(PathBuf::from("/tmp/a.txt"))
+        );
+        assert_eq!(
+            target_path("file_read", &json!({ "path": "/tmp/a.txt" })),
+            None
+        );
+        assert_eq!(target_path("file_delete", &json!({})), None);
+    }
+
+    #[test]
+    fn test_changes_and_rollback() {
+        let (dir, store, mut conn) = setup();
+        let edited = dir.path().join("edited.txt");
+        let created = dir.path().join("created.txt");
+        let deleted = dir.path().join("deleted.txt");
+        std::fs::write(&edited, "one\ntwo\n").unwrap();
+        std::fs::write(&deleted, "keep me\n").unwrap();
+
+        store
+            .snapshot(&conn, "run-1", "file_write", &edited)
+            .unwrap();
+        std::fs::write(&edited, "one\nthree\n").unwrap();
+        store
+            .snapshot(&conn, "run-1", "file_write", &edited)
+            .unwrap();
+        std::fs::write(&edited, "one\nfour\n").unwrap();
+        store
+            .snapshot(&conn, "run-1", "file_write", &created)
+            .unwrap();
+        std::fs::write(&created, "new\n").unwrap();
+        store
+            .snapshot(&conn, "run-1", "file_delete", &deleted)
+            .unwrap();
+        std::fs::remove_file(&deleted).unwrap();
+
+        let changes = store.changes(&conn, "run-1").unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].kind, FileChangeKind::Modified);
+        assert_eq!(changes[0].tools, vec!["file_write", "file_write"]);
+        let diff = changes[0].diff.as_deref().unwrap();
+        assert!(diff.contains("-two") && diff.contains("+four"));
+        assert_eq!(changes[1].kind, FileChangeKind::Created);
+        assert_eq!(changes[2].kind, FileChangeKind::Deleted);
+
+        let report = store.rollback(&mut conn, "run-1").unwrap();
+        assert_eq!(report.restored.len(), 2);
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "one\ntwo\n");
+        assert_eq!(std::fs::read_to_string(&deleted).unwrap(), "keep me\n");
+        assert!(!created.exists());
+        assert!(store
+            .changes(&conn, "run-1")
+            .unwrap()
+            .iter()
+            .all(|change| change.rolled_back && change.kind == FileChangeKind::Unchanged));
+
+        // A second rollback has nothing left to do
+        let again = store.rollback(&mut conn, "run-1").unwrap();
+        assert!(again.restored.is_empty() && again.removed.is_empty());
+    }
+
+    #[test]
+    fn test_rollback_is_all_or_nothing() {
+        let (dir, store, mut conn) = setup();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        std::fs::write(&first, "first\n").unwrap();
+        std::fs::write(&second, "second\n").unwrap();
+        store
+            .snapshot(&conn, "run-2", "file_write", &first)
+            .unwrap();
+        store
+            .snapshot(&conn, "run-2", "file_write", &second)
+            .unwrap();
+        std::fs::write(&first, "changed\n").unwrap();
+        std::fs::write(&second, "changed\n").unwrap();
+
+        // Losing a blob must fail the rollback before any file is touched
+        std::fs::remove_dir_all(dir.path().join("store").join("blobs")).unwrap();
+        assert!(store.rollback(&mut conn, "run-2").is_err());
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "changed\n");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "changed\n");
+    }
+}
@@ -272,6 +272,7 @@ Provide your analysis in a clear, structured format."#
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let preferences = crate::router::RouterPreferences {
@@ -294,7 +295,7 @@ Provide your analysis in a clear, structured format."#
 
         // Invoke LLM
         let outcome = router
-            .invoke_candidate(&candidates[0], &llm_request)
+            .invoke_with_failover(&candidates, &llm_request)
             .await?;
         let analysis = outcome.response.content;
 
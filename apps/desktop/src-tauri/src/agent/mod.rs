@@ -6,11 +6,13 @@ pub mod code_generator;
 pub mod context_compactor;
 pub mod context_manager;
 pub mod executor;
+pub mod file_checkpoints;
 pub mod intelligent_file_access;
 pub mod planner;
 pub mod prompt_engineer;
 pub mod rag_system;
 pub mod runtime;
+pub mod symbol_context;
 pub mod vision;
 
 #[cfg(test)]
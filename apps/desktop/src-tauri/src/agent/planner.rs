@@ -96,7 +96,9 @@ Return ONLY the JSON array, no other text."#,
 
     async fn plan_with_cloud_llm(&self, prompt: &str) -> Result<String> {
         // Use router to get LLM response
-        use crate::router::{ChatMessage, LLMRequest, RouterPreferences, RoutingStrategy};
+        use crate::router::{
+            ChatMessage, LLMRequest, ResponseFormat, RouterPreferences, RoutingStrategy,
+        };
 
         let request = LLMRequest {
             messages: vec![ChatMessage {
@@ -112,6 +114,25 @@ Return ONLY the JSON array, no other text."#,
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: Some(ResponseFormat::JsonSchema {
+                name: "task_plan".to_string(),
+                schema: json!({
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "string" },
+                            "action": { "type": "object" },
+                            "description": { "type": "string" },
+                            "expected_result": { "type": "string" },
+                            "timeout": { "type": "integer" },
+                            "retry_on_failure": { "type": "boolean" }
+                        },
+                        "required": ["id", "action", "description"]
+                    }
+                }),
+                strict: false,
+            }),
         };
 
         let preferences = RouterPreferences {
@@ -127,7 +148,7 @@ Return ONLY the JSON array, no other text."#,
 
         if !candidates.is_empty() {
             let router = self.router.lock().await;
-            if let Ok(outcome) = router.invoke_candidate(&candidates[0], &request).await {
+            if let Ok(outcome) = router.invoke_with_failover(&candidates, &request).await {
                 return Ok(outcome.response.content);
             }
         }
@@ -1043,3 +1043,976 @@ Do not repeat the error message."#,
         });
     }
 }
+
+/// Lifecycle of a recorded run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+impl RunStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Running => "running",
+            RunStatus::Completed => "completed",
+            RunStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "completed" => RunStatus::Completed,
+            "failed" => RunStatus::Failed,
+            _ => RunStatus::Running,
+        }
+    }
+}
+
+/// Setting holding the budget runs get unless they ask for their own
+const RUN_BUDGET_SETTING: &str = "agent.run_budget";
+
+/// Limits on what a single run may spend before it has to ask to continue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunBudget {
+    /// LLM spend in USD
+    pub max_cost: Option<f64>,
+    pub max_tool_calls: Option<u32>,
+    pub max_duration_secs: Option<u64>,
+}
+
+impl RunBudget {
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .max_cost
+            .is_some_and(|cost| !(cost > 0.0 && cost.is_finite()))
+        {
+            return Err(anyhow!("Run cost budget must be above $0"));
+        }
+        if self.max_tool_calls == Some(0) || self.max_duration_secs == Some(0) {
+            return Err(anyhow!("Run budgets must allow at least one step"));
+        }
+        Ok(())
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_cost.is_none() && self.max_tool_calls.is_none() && self.max_duration_secs.is_none()
+    }
+
+    /// The first limit `usage` has reached
+    pub fn exceeded(&self, usage: &BudgetUsage) -> Option<BudgetLimit> {
+        if self.max_cost.is_some_and(|max| usage.cost >= max) {
+            Some(BudgetLimit::Cost)
+        } else if self
+            .max_tool_calls
+            .is_some_and(|max| usage.tool_calls >= max)
+        {
+            Some(BudgetLimit::ToolCalls)
+        } else if self
+            .max_duration_secs
+            .is_some_and(|max| usage.elapsed_secs >= max)
+        {
+            Some(BudgetLimit::Duration)
+        } else {
+            None
+        }
+    }
+
+    /// Grant another `allowance` worth of `limit` on top of `usage`
+    fn extend(&mut self, limit: BudgetLimit, allowance: &RunBudget, usage: &BudgetUsage) {
+        match limit {
+            BudgetLimit::Cost => {
+                self.max_cost = allowance.max_cost.map(|extra| usage.cost + extra);
+            }
+            BudgetLimit::ToolCalls => {
+                self.max_tool_calls = allowance
+                    .max_tool_calls
+                    .map(|extra| usage.tool_calls.saturating_add(extra));
+            }
+            BudgetLimit::Duration => {
+                self.max_duration_secs = allowance
+                    .max_duration_secs
+                    .map(|extra| usage.elapsed_secs.saturating_add(extra));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    Cost,
+    ToolCalls,
+    Duration,
+}
+
+/// What a run has spent so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetUsage {
+    pub cost: f64,
+    pub tool_calls: u32,
+    pub elapsed_secs: u64,
+}
+
+/// A run that reached one of its limits
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetOverrun {
+    pub limit: BudgetLimit,
+    pub budget: RunBudget,
+    pub usage: BudgetUsage,
+}
+
+impl std::fmt::Display for BudgetOverrun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.limit {
+            BudgetLimit::Cost => write!(
+                f,
+                "Run cost budget of ${:.2} reached (${:.2} spent)",
+                self.budget.max_cost.unwrap_or_default(),
+                self.usage.cost
+            ),
+            BudgetLimit::ToolCalls => write!(
+                f,
+                "Run budget of {} tool calls reached",
+                self.budget.max_tool_calls.unwrap_or_default()
+            ),
+            BudgetLimit::Duration => write!(
+                f,
+                "Run time budget of {}s reached ({}s elapsed)",
+                self.budget.max_duration_secs.unwrap_or_default(),
+                self.usage.elapsed_secs
+            ),
+        }
+    }
+}
+
+/// Budget use across recent runs, for the metrics dashboard
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetMetrics {
+    pub runs: u64,
+    /// Runs that had at least one limit
+    pub budgeted_runs: u64,
+    /// Runs that reached a limit at least once
+    pub over_budget_runs: u64,
+    pub total_cost: f64,
+    pub total_tool_calls: u64,
+    pub avg_cost_per_run: f64,
+}
+
+/// Something that happened during a recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum TraceEvent {
+    /// Steps the planner produced, as the planner's JSON
+    Plan { steps: serde_json::Value },
+    /// One plan step's tool call
+    ToolCall {
+        step_id: String,
+        tool_name: String,
+        input: serde_json::Value,
+        output: Option<serde_json::Value>,
+        error: Option<String>,
+        duration_ms: u64,
+        /// A replay returned the recorded output instead of running the tool
+        #[serde(default)]
+        mocked: bool,
+        /// Copy of the screenshot the tool took, kept with the trace
+        screenshot: Option<String>,
+    },
+    /// A request to an LLM and its response
+    LlmCall {
+        provider: Option<String>,
+        model: String,
+        request: serde_json::Value,
+        response: Option<String>,
+        error: Option<String>,
+        prompt_tokens: u32,
+        completion_tokens: u32,
+        cost: f64,
+    },
+    /// The run reached a budget limit and the user was asked whether to go on
+    Budget {
+        overrun: BudgetOverrun,
+        approved: bool,
+    },
+}
+
+impl TraceEvent {
+    fn kind(&self) -> &'static str {
+        match self {
+            TraceEvent::Plan { .. } => "plan",
+            TraceEvent::ToolCall { .. } => "tool_call",
+            TraceEvent::LlmCall { .. } => "llm_call",
+            TraceEvent::Budget { .. } => "budget",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    pub seq: i64,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: TraceEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub run_id: String,
+    pub goal: serde_json::Value,
+    /// Run this one replayed
+    pub replay_of: Option<String>,
+    pub status: RunStatus,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub budget: RunBudget,
+    /// Spend so far, or in total once the run finished
+    pub usage: BudgetUsage,
+    /// Times the run reached a limit
+    pub budget_overruns: u32,
+}
+
+/// A run and everything recorded during it, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunTrace {
+    #[serde(flatten)]
+    pub run: RunSummary,
+    pub events: Vec<TraceEntry>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+impl RunTrace {
+    /// Steps of the recorded plan, as the planner's JSON
+    pub fn plan_steps(&self) -> Option<&serde_json::Value> {
+        self.events.iter().find_map(|entry| match &entry.event {
+            TraceEvent::Plan { steps } => Some(steps),
+            _ => None,
+        })
+    }
+
+    /// The last recorded outcome of each step, keyed by step id
+    pub fn step_outcomes(&self) -> HashMap<String, std::result::Result<serde_json::Value, String>> {
+        let mut outcomes = HashMap::new();
+        for entry in &self.events {
+            if let TraceEvent::ToolCall {
+                step_id,
+                output,
+                error,
+                ..
+            } = &entry.event
+            {
+                let outcome = match error {
+                    Some(error) => Err(error.clone()),
+                    None => Ok(output.clone().unwrap_or(serde_json::Value::Null)),
+                };
+                outcomes.insert(step_id.clone(), outcome);
+            }
+        }
+        outcomes
+    }
+}
+
+fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+const RUN_SUMMARY_COLUMNS: &str = "run_id, goal, replay_of, status, error, started_at, \
+     finished_at, budget, cost, tool_calls, budget_overruns";
+
+fn run_summary_from_row(row: &rusqlite::Row) -> rusqlite::Result<RunSummary> {
+    let goal: String = row.get(1)?;
+    let status: String = row.get(3)?;
+    let started_at = millis_to_datetime(row.get(5)?);
+    let finished_at = row.get::<_, Option<i64>>(6)?.map(millis_to_datetime);
+    let budget = row
+        .get::<_, Option<String>>(7)?
+        .and_then(|budget| serde_json::from_str(&budget).ok())
+        .unwrap_or_default();
+    let elapsed = finished_at.unwrap_or_else(Utc::now) - started_at;
+    Ok(RunSummary {
+        run_id: row.get(0)?,
+        goal: serde_json::from_str(&goal).unwrap_or(serde_json::Value::Null),
+        replay_of: row.get(2)?,
+        status: RunStatus::parse(&status),
+        error: row.get(4)?,
+        started_at,
+        finished_at,
+        budget,
+        usage: BudgetUsage {
+            cost: row.get(8)?,
+            tool_calls: row.get(9)?,
+            elapsed_secs: elapsed.num_seconds().max(0) as u64,
+        },
+        budget_overruns: row.get(10)?,
+    })
+}
+
+pub fn begin_run(
+    conn: &rusqlite::Connection,
+    run_id: &str,
+    goal: &serde_json::Value,
+    replay_of: Option<&str>,
+    budget: &RunBudget,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO agent_runs (run_id, goal, replay_of, status, started_at, budget)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            run_id,
+            goal.to_string(),
+            replay_of,
+            RunStatus::Running.as_str(),
+            Utc::now().timestamp_millis(),
+            serde_json::to_string(budget)?,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn append_event(conn: &rusqlite::Connection, run_id: &str, entry: &TraceEntry) -> Result<()> {
+    conn.execute(
+        "INSERT INTO agent_run_events (run_id, seq, at, kind, payload)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            run_id,
+            entry.seq,
+            entry.at.timestamp_millis(),
+            entry.event.kind(),
+            serde_json::to_string(&entry.event)?,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn finish_run(
+    conn: &rusqlite::Connection,
+    run_id: &str,
+    status: RunStatus,
+    error: Option<&str>,
+    usage: &BudgetUsage,
+    budget_overruns: u32,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE agent_runs
+         SET status = ?2, error = ?3, finished_at = ?4, cost = ?5, tool_calls = ?6,
+             budget_overruns = ?7
+         WHERE run_id = ?1",
+        rusqlite::params![
+            run_id,
+            status.as_str(),
+            error,
+            Utc::now().timestamp_millis(),
+            usage.cost,
+            usage.tool_calls,
+            budget_overruns,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Recorded runs, newest first
+pub fn list_runs(conn: &rusqlite::Connection, limit: usize) -> Result<Vec<RunSummary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM agent_runs ORDER BY started_at DESC, rowid DESC LIMIT ?1",
+        RUN_SUMMARY_COLUMNS
+    ))?;
+    let runs = stmt
+        .query_map([limit as i64], run_summary_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(runs)
+}
+
+/// Budget use of finished runs started since `since`
+pub fn budget_metrics(conn: &rusqlite::Connection, since: DateTime<Utc>) -> Result<BudgetMetrics> {
+    let mut stmt = conn.prepare(
+        "SELECT budget, cost, tool_calls, budget_overruns FROM agent_runs
+         WHERE started_at >= ?1 AND finished_at IS NOT NULL",
+    )?;
+    let rows = stmt
+        .query_map([since.timestamp_millis()], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut metrics = BudgetMetrics::default();
+    for (budget, cost, tool_calls, overruns) in rows {
+        let budget: RunBudget = budget
+            .and_then(|budget| serde_json::from_str(&budget).ok())
+            .unwrap_or_default();
+        metrics.runs += 1;
+        metrics.budgeted_runs += u64::from(!budget.is_unlimited());
+        metrics.over_budget_runs += u64::from(overruns > 0);
+        metrics.total_cost += cost;
+        metrics.total_tool_calls += u64::from(tool_calls);
+    }
+    if metrics.runs > 0 {
+        metrics.avg_cost_per_run = metrics.total_cost / metrics.runs as f64;
+    }
+    Ok(metrics)
+}
+
+/// Budget a run gets unless it asks for its own
+pub fn default_budget(conn: &rusqlite::Connection) -> Result<RunBudget> {
+    match crate::db::repository::get_setting(conn, RUN_BUDGET_SETTING) {
+        Ok(setting) => Ok(serde_json::from_str(&setting.value)?),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(RunBudget::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn set_default_budget(conn: &rusqlite::Connection, budget: &RunBudget) -> Result<()> {
+    budget.validate()?;
+    crate::db::repository::set_setting(
+        conn,
+        RUN_BUDGET_SETTING.to_string(),
+        serde_json::to_string(budget)?,
+        false,
+    )?;
+    Ok(())
+}
+
+pub fn load_trace(conn: &rusqlite::Connection, run_id: &str) -> Result<Option<RunTrace>> {
+    use rusqlite::OptionalExtension;
+
+    let Some(run) = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM agent_runs WHERE run_id = ?1",
+                RUN_SUMMARY_COLUMNS
+            ),
+            [run_id],
+            run_summary_from_row,
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT seq, at, payload FROM agent_run_events WHERE run_id = ?1 ORDER BY seq")?;
+    let rows = stmt
+        .query_map([run_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut trace = RunTrace {
+        run,
+        events: Vec::with_capacity(rows.len()),
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        cost: 0.0,
+    };
+    for (seq, at, payload) in rows {
+        let event: TraceEvent = serde_json::from_str(&payload)?;
+        if let TraceEvent::LlmCall {
+            prompt_tokens,
+            completion_tokens,
+            cost,
+            ..
+        } = &event
+        {
+            trace.prompt_tokens += u64::from(*prompt_tokens);
+            trace.completion_tokens += u64::from(*completion_tokens);
+            trace.cost += cost;
+        }
+        trace.events.push(TraceEntry {
+            seq,
+            at: millis_to_datetime(at),
+            event,
+        });
+    }
+    Ok(Some(trace))
+}
+
+tokio::task_local! {
+    static ACTIVE_RUN: ActiveRun;
+}
+
+struct ActiveRun {
+    run_id: String,
+    next_seq: std::sync::atomic::AtomicI64,
+    events: tokio::sync::mpsc::UnboundedSender<TraceEntry>,
+    started: std::time::Instant,
+    /// The run's budget as given; each approved overrun grants this again
+    allowance: RunBudget,
+    spend: parking_lot::Mutex<RunSpend>,
+}
+
+#[derive(Default)]
+struct RunSpend {
+    budget: RunBudget,
+    cost: f64,
+    tool_calls: u32,
+    overruns: u32,
+}
+
+impl ActiveRun {
+    fn usage(&self, spend: &RunSpend) -> BudgetUsage {
+        BudgetUsage {
+            cost: spend.cost,
+            tool_calls: spend.tool_calls,
+            elapsed_secs: self.started.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Persists runs as replayable traces. Managed as app state; work inside
+/// [`RunRecorder::record`] reports through [`record_event`] and friends
+/// without the run being threaded through every call.
+#[derive(Clone)]
+pub struct RunRecorder {
+    pool: crate::db::Pool,
+    /// Budgets for runs about to start, in place of the default
+    budgets: Arc<parking_lot::Mutex<HashMap<String, RunBudget>>>,
+}
+
+impl RunRecorder {
+    pub fn new(pool: crate::db::Pool) -> Self {
+        Self {
+            pool,
+            budgets: Arc::default(),
+        }
+    }
+
+    pub fn from_app(app: &tauri::AppHandle) -> Option<Self> {
+        use tauri::Manager;
+        app.try_state::<RunRecorder>()
+            .map(|recorder| recorder.inner().clone())
+    }
+
+    /// Run `work` as `run_id`, recording its events and outcome. Recording
+    /// failures are logged and never fail the run.
+    pub async fn record<T, E, F>(
+        &self,
+        run_id: &str,
+        goal: serde_json::Value,
+        replay_of: Option<String>,
+        work: F,
+    ) -> std::result::Result<T, E>
+    where
+        E: std::fmt::Display,
+        F: std::future::Future<Output = std::result::Result<T, E>>,
+    {
+        let id = run_id.to_string();
+        let requested = self.budgets.lock().remove(run_id);
+        let started = self
+            .pool
+            .run(move |conn| {
+                let budget = match requested {
+                    Some(budget) => budget,
+                    None => default_budget(conn).unwrap_or_else(|e| {
+                        tracing::warn!("[Recorder] Ignoring default run budget: {}", e);
+                        RunBudget::default()
+                    }),
+                };
+                begin_run(conn, &id, &goal, replay_of.as_deref(), &budget)?;
+                Ok::<_, anyhow::Error>(budget)
+            })
+            .await;
+        let budget = match started {
+            Ok(budget) => budget,
+            Err(e) => {
+                tracing::warn!("[Recorder] Not recording run {}: {}", run_id, e);
+                return work.await;
+            }
+        };
+
+        let (events, mut pending) = tokio::sync::mpsc::unbounded_channel::<TraceEntry>();
+        let pool = self.pool.clone();
+        let writer_run_id = run_id.to_string();
+        let writer = tokio::spawn(async move {
+            while let Some(entry) = pending.recv().await {
+                let run_id = writer_run_id.clone();
+                let seq = entry.seq;
+                let written = pool
+                    .run(move |conn| append_event(conn, &run_id, &entry))
+                    .await;
+                if let Err(e) = written {
+                    tracing::warn!("[Recorder] Failed to record event {}: {}", seq, e);
+                }
+            }
+        });
+
+        let active = ActiveRun {
+            run_id: run_id.to_string(),
+            next_seq: std::sync::atomic::AtomicI64::new(1),
+            events,
+            started: std::time::Instant::now(),
+            allowance: budget,
+            spend: parking_lot::Mutex::new(RunSpend {
+                budget,
+                ..RunSpend::default()
+            }),
+        };
+        let (result, usage, overruns) = ACTIVE_RUN
+            .scope(active, async {
+                let result = work.await;
+                let (usage, overruns) = ACTIVE_RUN.with(|run| {
+                    let spend = run.spend.lock();
+                    (run.usage(&spend), spend.overruns)
+                });
+                (result, usage, overruns)
+            })
+            .await;
+        // The sender went away with the scope, so this drains and stops
+        let _ = writer.await;
+
+        let (status, error) = match &result {
+            Ok(_) => (RunStatus::Completed, None),
+            Err(e) => (RunStatus::Failed, Some(e.to_string())),
+        };
+        let id = run_id.to_string();
+        let finished = self
+            .pool
+            .run(move |conn| finish_run(conn, &id, status, error.as_deref(), &usage, overruns))
+            .await;
+        if let Err(e) = finished {
+            tracing::warn!("[Recorder] Failed to finish run {}: {}", run_id, e);
+        }
+        result
+    }
+
+    pub async fn trace(&self, run_id: &str) -> Result<Option<RunTrace>> {
+        let run_id = run_id.to_string();
+        self.pool.run(move |conn| load_trace(conn, &run_id)).await
+    }
+
+    pub async fn runs(&self, limit: usize) -> Result<Vec<RunSummary>> {
+        self.pool.run(move |conn| list_runs(conn, limit)).await
+    }
+
+    /// Give the run `run_id` its own budget; call before the run starts
+    pub fn set_run_budget(&self, run_id: &str, budget: RunBudget) -> Result<()> {
+        budget.validate()?;
+        self.budgets.lock().insert(run_id.to_string(), budget);
+        Ok(())
+    }
+
+    pub async fn default_budget(&self) -> Result<RunBudget> {
+        self.pool.run(|conn| default_budget(conn)).await
+    }
+
+    pub async fn set_default_budget(&self, budget: RunBudget) -> Result<()> {
+        self.pool
+            .run(move |conn| set_default_budget(conn, &budget))
+            .await
+    }
+
+    pub async fn budget_metrics(&self, since: DateTime<Utc>) -> Result<BudgetMetrics> {
+        self.pool.run(move |conn| budget_metrics(conn, since)).await
+    }
+}
+
+/// Run being recorded by the current task, if any
+pub fn recording_run_id() -> Option<String> {
+    ACTIVE_RUN.try_with(|run| run.run_id.clone()).ok()
+}
+
+/// Add an event to the current task's run; a no-op outside a recorded run
+pub fn record_event(event: TraceEvent) {
+    let _ = ACTIVE_RUN.try_with(|run| {
+        let entry = TraceEntry {
+            seq: run
+                .next_seq
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            at: Utc::now(),
+            event,
+        };
+        let _ = run.events.send(entry);
+    });
+}
+
+/// What the current task's run has spent against its budget
+pub fn budget_usage() -> Option<(RunBudget, BudgetUsage)> {
+    ACTIVE_RUN
+        .try_with(|run| {
+            let spend = run.spend.lock();
+            (spend.budget, run.usage(&spend))
+        })
+        .ok()
+}
+
+/// The limit the current task's run has reached, if any. Checked before
+/// each step.
+pub fn budget_overrun() -> Option<BudgetOverrun> {
+    let (budget, usage) = budget_usage()?;
+    budget.exceeded(&usage).map(|limit| BudgetOverrun {
+        limit,
+        budget,
+        usage,
+    })
+}
+
+/// Record the user's answer to an overrun. Approving grants the run another
+/// allowance of the limit it reached.
+pub fn resolve_budget_overrun(overrun: &BudgetOverrun, approved: bool) {
+    let _ = ACTIVE_RUN.try_with(|run| {
+        let mut spend = run.spend.lock();
+        spend.overruns += 1;
+        if approved {
+            let usage = run.usage(&spend);
+            spend.budget.extend(overrun.limit, &run.allowance, &usage);
+        }
+    });
+    record_event(TraceEvent::Budget {
+        overrun: *overrun,
+        approved,
+    });
+}
+
+/// Record a plan step's tool call. Screenshots the tool saved are copied
+/// next to the trace, since tools write them to the temp directory.
+pub fn record_tool_call(
+    step_id: &str,
+    tool_name: &str,
+    input: serde_json::Value,
+    outcome: &std::result::Result<serde_json::Value, String>,
+    duration_ms: u64,
+    mocked: bool,
+) {
+    let Some(run_id) = recording_run_id() else {
+        return;
+    };
+    if !mocked {
+        let _ = ACTIVE_RUN.try_with(|run| run.spend.lock().tool_calls += 1);
+    }
+    let screenshot = match outcome {
+        Ok(output) if !mocked => output
+            .get("screenshot_path")
+            .and_then(|path| path.as_str())
+            .and_then(|path| keep_screenshot(&run_id, step_id, path)),
+        _ => None,
+    };
+    record_event(TraceEvent::ToolCall {
+        step_id: step_id.to_string(),
+        tool_name: tool_name.to_string(),
+        input,
+        output: outcome.as_ref().ok().cloned(),
+        error: outcome.as_ref().err().cloned(),
+        duration_ms,
+        mocked,
+        screenshot,
+    });
+}
+
+fn keep_screenshot(run_id: &str, step_id: &str, path: &str) -> Option<String> {
+    let source = std::path::Path::new(path);
+    let extension = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("png");
+    let kept = (|| -> Result<std::path::PathBuf> {
+        let dir = crate::utils::app_data_dir()?
+            .join("run_traces")
+            .join(run_id);
+        std::fs::create_dir_all(&dir)?;
+        let target = dir.join(format!("{}.{}", step_id, extension));
+        std::fs::copy(source, &target)?;
+        Ok(target)
+    })();
+    match kept {
+        Ok(target) => Some(target.to_string_lossy().into_owned()),
+        Err(e) => {
+            tracing::warn!("[Recorder] Failed to keep screenshot {}: {}", path, e);
+            Some(path.to_string())
+        }
+    }
+}
+
+/// Record an LLM request and its response, tokens and cost
+pub fn record_llm_call(
+    request: &crate::router::LLMRequest,
+    outcome: &Result<crate::router::llm_router::RouteOutcome>,
+) {
+    if recording_run_id().is_none() {
+        return;
+    }
+    let request_json = serde_json::to_value(request).unwrap_or(serde_json::Value::Null);
+    if let Ok(outcome) = outcome {
+        let _ = ACTIVE_RUN.try_with(|run| run.spend.lock().cost += outcome.cost);
+    }
+    let event = match outcome {
+        Ok(outcome) => TraceEvent::LlmCall {
+            provider: Some(outcome.provider.as_string().to_string()),
+            model: outcome.model.clone(),
+            request: request_json,
+            response: Some(outcome.response.content.clone()),
+            error: None,
+            prompt_tokens: outcome.prompt_tokens,
+            completion_tokens: outcome.completion_tokens,
+            cost: outcome.cost,
+        },
+        Err(e) => TraceEvent::LlmCall {
+            provider: None,
+            model: request.model.clone(),
+            request: request_json,
+            response: None,
+            error: Some(e.to_string()),
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+        },
+    };
+    record_event(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use crate::db::Pool;
+
+    fn pool() -> Pool {
+        let pool = Pool::in_memory().unwrap();
+        run_migrations(&pool.get().unwrap()).unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_record_run_trace() {
+        let recorder = RunRecorder::new(pool());
+        let result: std::result::Result<(), String> = recorder
+            .record(
+                "goal_1",
+                serde_json::json!({ "description": "demo" }),
+                None,
+                async {
+                    record_event(TraceEvent::Plan {
+                        steps: serde_json::json!([{ "id": "step_1", "tool_id": "file_read" }]),
+                    });
+                    record_event(TraceEvent::LlmCall {
+                        provider: Some("openai".to_string()),
+                        model: "gpt-4o".to_string(),
+                        request: serde_json::json!({ "messages": [] }),
+                        response: Some("ok".to_string()),
+                        error: None,
+                        prompt_tokens: 10,
+                        completion_tokens: 5,
+                        cost: 0.25,
+                    });
+                    record_tool_call(
+                        "step_1",
+                        "file_read",
+                        serde_json::json!({ "path": "a.txt" }),
+                        &Err("not found".to_string()),
+                        12,
+                        false,
+                    );
+                    Err("step failed".to_string())
+                },
+            )
+            .await;
+        assert!(result.is_err());
+
+        let trace = recorder.trace("goal_1").await.unwrap().unwrap();
+        assert_eq!(trace.run.status, RunStatus::Failed);
+        assert_eq!(trace.run.error.as_deref(), Some("step failed"));
+        assert!(trace.run.finished_at.is_some());
+        assert_eq!(
+            trace.events.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(trace.prompt_tokens, 10);
+        assert_eq!(trace.completion_tokens, 5);
+        assert!(trace.plan_steps().is_some());
+        assert_eq!(
+            trace.step_outcomes().get("step_1"),
+            Some(&Err("not found".to_string()))
+        );
+
+        let runs = recorder.runs(10).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(recorder.trace("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_budget_overrun() {
+        let recorder = RunRecorder::new(pool());
+        recorder
+            .set_run_budget(
+                "goal_2",
+                RunBudget {
+                    max_tool_calls: Some(1),
+                    ..RunBudget::default()
+                },
+            )
+            .unwrap();
+        let result: std::result::Result<(), String> = recorder
+            .record("goal_2", serde_json::Value::Null, None, async {
+                assert!(budget_overrun().is_none());
+                record_tool_call(
+                    "step_1",
+                    "file_read",
+                    serde_json::Value::Null,
+                    &Ok(serde_json::Value::Null),
+                    1,
+                    false,
+                );
+                let overrun = budget_overrun().expect("tool call budget reached");
+                assert_eq!(overrun.limit, BudgetLimit::ToolCalls);
+                resolve_budget_overrun(&overrun, true);
+                assert!(budget_overrun().is_none());
+                assert_eq!(budget_usage().unwrap().0.max_tool_calls, Some(2));
+                Ok(())
+            })
+            .await;
+        assert!(result.is_ok());
+
+        let trace = recorder.trace("goal_2").await.unwrap().unwrap();
+        assert_eq!(trace.run.budget.max_tool_calls, Some(1));
+        assert_eq!(trace.run.usage.tool_calls, 1);
+        assert_eq!(trace.run.budget_overruns, 1);
+        assert!(trace
+            .events
+            .iter()
+            .any(|entry| matches!(entry.event, TraceEvent::Budget { approved: true, .. })));
+
+        let metrics = recorder
+            .budget_metrics(Utc::now() - chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(metrics.runs, 1);
+        assert_eq!(metrics.budgeted_runs, 1);
+        assert_eq!(metrics.over_budget_runs, 1);
+        assert!(RunBudget {
+            max_cost: Some(0.0),
+            ..RunBudget::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_events_outside_a_run_are_ignored() {
+        assert!(recording_run_id().is_none());
+        record_event(TraceEvent::Plan {
+            steps: serde_json::Value::Null,
+        });
+    }
+}
@@ -0,0 +1,447 @@
+/// Symbol context - live type information for code generation prompts
+///
+/// Queries running language servers for hover and definition info of the
+/// symbols referenced in the region being edited and renders it as a compact
+/// prompt block, so generated code calls real signatures instead of guessed ones.
+use crate::commands::lsp::{detect_language, LSPState};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+const MAX_SIGNATURE_CHARS: usize = 240;
+
+const IGNORED_WORDS: &[&str] = &[
+    "and",
+    "as",
+    "async",
+    "await",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "crate",
+    "def",
+    "default",
+    "defer",
+    "delete",
+    "do",
+    "dyn",
+    "elif",
+    "else",
+    "enum",
+    "Err",
+    "export",
+    "extends",
+    "false",
+    "fn",
+    "for",
+    "from",
+    "func",
+    "function",
+    "go",
+    "if",
+    "impl",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "is",
+    "lambda",
+    "let",
+    "loop",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "new",
+    "None",
+    "not",
+    "null",
+    "Ok",
+    "Option",
+    "or",
+    "package",
+    "pass",
+    "pub",
+    "ref",
+    "Result",
+    "return",
+    "self",
+    "Self",
+    "Some",
+    "static",
+    "str",
+    "String",
+    "struct",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "unsafe",
+    "use",
+    "var",
+    "Vec",
+    "void",
+    "where",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Toggle and size limits for the symbol context block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolContextConfig {
+    pub enabled: bool,
+    /// Approximate token budget for the rendered block
+    pub token_budget: usize,
+    /// Upper bound on language server lookups per request
+    pub max_symbols: usize,
+}
+
+impl Default for SymbolContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            token_budget: 800,
+            max_symbols: 32,
+        }
+    }
+}
+
+/// Lines of a file that a generation request is going to change (0-based, inclusive)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditRegion {
+    pub path: PathBuf,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+/// An identifier in the edit region worth asking the language server about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolReference {
+    pub name: String,
+    pub line: u32,
+    /// UTF-16 column, as LSP positions expect
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub signature: String,
+    /// `path:line` of the definition
+    pub definition: Option<String>,
+}
+
+/// How much the symbol context contributed to a prompt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolContextStats {
+    pub symbols_queried: usize,
+    pub symbols_resolved: usize,
+    pub symbols_included: usize,
+    pub estimated_tokens: usize,
+}
+
+/// Look up the symbols used in each region and render the prompt block.
+///
+/// Regions whose language has no running server are skipped, and lookup
+/// failures only shrink the block; an empty string means nothing was found.
+pub async fn collect_symbol_context(
+    lsp: &LSPState,
+    regions: &[EditRegion],
+    sources: &HashMap<PathBuf, String>,
+    config: &SymbolContextConfig,
+) -> (String, SymbolContextStats) {
+    let mut stats = SymbolContextStats::default();
+    let mut symbols: Vec<SymbolInfo> = Vec::new();
+    let mut seen = HashSet::new();
+
+    for region in regions {
+        if stats.symbols_queried >= config.max_symbols {
+            break;
+        }
+        let Some(source) = sources.get(&region.path) else {
+            continue;
+        };
+        let Some(language) = detect_language(&region.path) else {
+            continue;
+        };
+        let Some(client) = lsp.client(language).await else {
+            continue;
+        };
+        let mut client = client.lock().await;
+
+        let uri = format!("file://{}", region.path.display());
+        if let Err(e) = client.text_document_did_open(&uri, language, source).await {
+            tracing::debug!("[SymbolContext] Failed to open {}: {}", uri, e);
+            continue;
+        }
+
+        let references = symbol_references(
+            source,
+            region.start_line,
+            region.end_line,
+            config.max_symbols - stats.symbols_queried,
+        );
+        for reference in references {
+            if !seen.insert(reference.name.clone()) {
+                continue;
+            }
+            stats.symbols_queried += 1;
+
+            let hover = match client
+                .text_document_hover(&uri, reference.line, reference.character)
+                .await
+            {
+                Ok(Some(hover)) => hover,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::debug!("[SymbolContext] Hover failed for {}: {}", reference.name, e);
+                    continue;
+                }
+            };
+            let Some(signature) = compact_hover(&hover.contents) else {
+                continue;
+            };
+            let definition = client
+                .text_document_definition(&uri, reference.line, reference.character)
+                .await
+                .ok()
+                .and_then(|locations| locations.into_iter().next())
+                .filter(|location| !location.uri.is_empty())
+                .map(|location| {
+                    format!(
+                        "{}:{}",
+                        location.uri.trim_start_matches("file://"),
+                        location.range.start.line + 1
+                    )
+                });
+
+            stats.symbols_resolved += 1;
+            symbols.push(SymbolInfo {
+                name: reference.name,
+                signature,
+                definition,
+            });
+        }
+    }
+
+    let (block, included) = render_symbol_block(&symbols, config.token_budget);
+    stats.symbols_included = included;
+    stats.estimated_tokens = estimate_tokens(&block);
+    (block, stats)
+}
+
+/// Identifiers in `start_line..=end_line` that look like calls, paths or
+/// types, in order of first use
+pub fn symbol_references(
+    source: &str,
+    start_line: u32,
+    end_line: u32,
+    max: usize,
+) -> Vec<SymbolReference> {
+    let mut references = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index as u32;
+        if line_number < start_line {
+            continue;
+        }
+        if line_number > end_line || references.len() >= max {
+            break;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut in_string: Option<char> = None;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if let Some(quote) = in_string {
+                if c == '\\' {
+                    i += 1;
+                } else if c == quote {
+                    in_string = None;
+                }
+                i += 1;
+                continue;
+            }
+            // A lone ' is a Rust lifetime rather than the start of a literal
+            if c == '"' || c == '`' || (c == '\'' && chars[i + 1..].contains(&'\'')) {
+                in_string = Some(c);
+                i += 1;
+                continue;
+            }
+            if c == '/' && chars.get(i + 1) == Some(&'/') || c == '#' {
+                break;
+            }
+            if !(c.is_alphabetic() || c == '_') {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let before = chars[..start]
+                .iter()
+                .rev()
+                .find(|c| !c.is_whitespace())
+                .copied();
+            let after = chars[i..].iter().find(|c| !c.is_whitespace()).copied();
+
+            let is_member = before == Some('.') || (before == Some(':') && start >= 2);
+            let is_call_or_path = matches!(after, Some('(' | '.' | ':' | '!' | '<'));
+            let is_type = name.starts_with(|c: char| c.is_uppercase());
+            if name.chars().count() < 2
+                || IGNORED_WORDS.contains(&name.as_str())
+                || !(is_member || is_call_or_path || is_type)
+                || !seen.insert(name.clone())
+            {
+                continue;
+            }
+
+            references.push(SymbolReference {
+                name,
+                line: line_number,
+                character: chars[..start].iter().map(|c| c.len_utf16() as u32).sum(),
+            });
+            if references.len() >= max {
+                break;
+            }
+        }
+    }
+
+    references
+}
+
+/// Reduce hover Markdown to a one-line signature, preferring the first code block
+pub fn compact_hover(contents: &str) -> Option<String> {
+    let text = match contents.find("```") {
+        Some(start) => {
+            let body = &contents[start + 3..];
+            let body = body.split_once('\n').map_or(body, |(_, rest)| rest);
+            body.split("```").next().unwrap_or(body)
+        }
+        None => contents.split("\n\n").find(|p| !p.trim().is_empty())?,
+    };
+
+    let signature = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if signature.is_empty() {
+        return None;
+    }
+    if signature.chars().count() > MAX_SIGNATURE_CHARS {
+        let truncated: String = signature.chars().take(MAX_SIGNATURE_CHARS).collect();
+        return Some(format!("{}…", truncated));
+    }
+    Some(signature)
+}
+
+/// Render resolved symbols until the token budget is reached.
+///
+/// Returns the block (empty when no symbol fits) and how many symbols it holds.
+pub fn render_symbol_block(symbols: &[SymbolInfo], token_budget: usize) -> (String, usize) {
+    let mut block = String::from(
+        "\n\n## Symbol Context\n\n\
+         Signatures reported by the language server for symbols used in the code being edited. \
+         Prefer these APIs over guessing; do not invent methods that are not listed here or in the existing code.\n\n",
+    );
+    let header_tokens = estimate_tokens(&block);
+    let mut tokens = header_tokens;
+    let mut included = 0;
+
+    for symbol in symbols {
+        let line = match &symbol.definition {
+            Some(definition) => format!(
+                "- `{}`: `{}` ({})\n",
+                symbol.name, symbol.signature, definition
+            ),
+            None => format!("- `{}`: `{}`\n", symbol.name, symbol.signature),
+        };
+        let line_tokens = estimate_tokens(&line);
+        if tokens + line_tokens > token_budget {
+            break;
+        }
+        tokens += line_tokens;
+        block.push_str(&line);
+        included += 1;
+    }
+
+    if included == 0 {
+        return (String::new(), 0);
+    }
+    (block, included)
+}
+
+/// Rough approximation: ~4 characters per token
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_references_skip_keywords_strings_and_locals() {
+        let source = "fn main() {\n    let count = 1;\n    let client = HttpClient::new(\"Fake::thing()\");\n    client.send_request(count); // Ignored::call()\n}\n";
+        let names: Vec<String> = symbol_references(source, 1, 3, 10)
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["HttpClient", "client", "send_request"]);
+
+        let refs = symbol_references(source, 3, 3, 10);
+        assert_eq!(refs[0].name, "client");
+        assert_eq!((refs[0].line, refs[0].character), (3, 4));
+        assert_eq!(symbol_references(source, 0, 10, 1).len(), 1);
+
+        let refs = symbol_references("fn parse<'a>(input: &'a str) -> Token<'a> {}", 0, 0, 10);
+        assert_eq!(refs[0].name, "parse");
+        assert_eq!(refs[1].name, "Token");
+    }
+
+    #[test]
+    fn test_compact_hover_prefers_code_block() {
+        let hover = "```rust\npub fn send_request(\n    &mut self,\n    body: &str,\n) -> Result<()>\n```\n\nSends a request.";
+        assert_eq!(
+            compact_hover(hover).unwrap(),
+            "pub fn send_request( &mut self, body: &str, ) -> Result<()>"
+        );
+        assert_eq!(compact_hover("Plain docs\n\nMore").unwrap(), "Plain docs");
+        assert!(compact_hover("  ").is_none());
+    }
+
+    #[test]
+    fn test_render_symbol_block_respects_budget() {
+        let symbols: Vec<SymbolInfo> = (0..50)
+            .map(|i| SymbolInfo {
+                name: format!("function_{}", i),
+                signature: format!("fn function_{}(input: &str) -> usize", i),
+                definition: Some(format!("src/lib.rs:{}", i + 1)),
+            })
+            .collect();
+
+        let (block, included) = render_symbol_block(&symbols, 200);
+        assert!(included > 0 && included < symbols.len());
+        assert!(estimate_tokens(&block) <= 200);
+        assert!(
+            block.contains("`function_0`: `fn function_0(input: &str) -> usize` (src/lib.rs:1)")
+        );
+
+        assert_eq!(render_symbol_block(&symbols, 10), (String::new(), 0));
+    }
+}
@@ -72,4 +72,91 @@ mod tests {
         let approved: Vec<_> = history.iter().filter(|(_, approved)| *approved).collect();
         assert_eq!(approved.len(), 2);
     }
+
+    use crate::agent::approval::{
+        ApprovalController, ApprovalPolicy, ApprovalRequestPayload, ApprovalScope,
+        ApprovalScopeType, TimeoutAction,
+    };
+
+    fn policy(id: &str, category: Option<ApprovalScopeType>, tool: Option<&str>) -> ApprovalPolicy {
+        ApprovalPolicy {
+            id: id.to_string(),
+            category,
+            tool_name: tool.map(str::to_string),
+            auto_approve: false,
+            timeout_secs: Some(60),
+            on_timeout: TimeoutAction::Skip,
+            escalate_after_secs: None,
+        }
+    }
+
+    fn request(
+        risk: &str,
+        scope_type: ApprovalScopeType,
+        command: Option<&str>,
+    ) -> ApprovalRequestPayload {
+        ApprovalRequestPayload {
+            action_id: "a1".to_string(),
+            tool_name: "file_write".to_string(),
+            title: String::new(),
+            description: String::new(),
+            reason: String::new(),
+            risk_level: risk.to_string(),
+            scope: ApprovalScope {
+                scope_type,
+                command: command.map(str::to_string),
+                cwd: None,
+                path: None,
+                domain: None,
+                description: None,
+                risk: risk.to_string(),
+            },
+            workflow_hash: None,
+            action_signature: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approval_policies_persist_most_specific_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let controller = ApprovalController::new(dir.path().to_path_buf()).unwrap();
+        controller
+            .set_policy(policy("all", None, None))
+            .await
+            .unwrap();
+        controller
+            .set_policy(policy("writes", None, Some("file_write")))
+            .await
+            .unwrap();
+        controller
+            .set_policy(policy("fs", Some(ApprovalScopeType::Filesystem), None))
+            .await
+            .unwrap();
+        assert!(controller
+            .set_policy(policy(" ", None, None))
+            .await
+            .is_err());
+
+        let reloaded = ApprovalController::new(dir.path().to_path_buf()).unwrap();
+        let ids: Vec<String> = reloaded
+            .policies()
+            .await
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec!["writes", "fs", "all"]);
+
+        assert!(reloaded.remove_policy("fs").await.unwrap());
+        assert!(!reloaded.remove_policy("fs").await.unwrap());
+        assert_eq!(reloaded.policies().await.len(), 2);
+    }
+
+    #[test]
+    fn test_only_lower_risk_requests_are_auto_approvable() {
+        assert!(request("medium", ApprovalScopeType::Filesystem, None).is_auto_approvable());
+        assert!(!request("high", ApprovalScopeType::Filesystem, None).is_auto_approvable());
+        assert!(
+            !request("medium", ApprovalScopeType::Terminal, Some("rm -rf /")).is_auto_approvable()
+        );
+    }
 }
@@ -1,5 +1,5 @@
 // API Tools Implementation for AGI Executor
-// This file contains the implementation of api_call, api_upload, and api_download tools
+// This file contains the implementation of api_call, api_upload, api_download and web_fetch tools
 
 use anyhow::{anyhow, Result};
 use serde_json::{json, Value};
@@ -209,6 +209,44 @@ pub async fn execute_api_download(
     }))
 }
 
+/// Fetch a web page as Markdown, indexing it into a collection if one is given
+pub async fn execute_web_fetch(
+    app_handle: &tauri::AppHandle,
+    parameters: &HashMap<String, Value>,
+) -> Result<Value> {
+    let url = parameters
+        .get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing url parameter"))?;
+    let collection_id = parameters
+        .get("collection_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let mut options = crate::web::FetchOptions::default();
+    if let Some(max_bytes) = parameters.get("max_bytes").and_then(|v| v.as_u64()) {
+        options.max_bytes = max_bytes as usize;
+    }
+
+    let result = crate::commands::fetch_and_index(app_handle, url, &options, collection_id)
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    let page = result.page;
+
+    Ok(json!({
+        "success": true,
+        "url": page.final_url,
+        "title": page.title,
+        "byline": page.byline,
+        "site_name": page.site_name,
+        "excerpt": page.excerpt,
+        "word_count": page.word_count,
+        "content": page.markdown,
+        "fetched_at": page.fetched_at,
+        "collection_source_id": result.source.map(|s| s.id),
+        "usage": crate::agi::ToolUsage::units(&[("pages", 1.0), ("words", page.word_count as f64)]),
+    }))
+}
+
 /// Helper function to parse authentication from parameters
 fn parse_auth_from_parameters(parameters: &HashMap<String, Value>) -> Result<crate::api::AuthType> {
     use crate::api::AuthType;
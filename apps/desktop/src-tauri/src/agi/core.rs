@@ -1,6 +1,8 @@
 use super::*;
-use crate::agi::planner::Plan;
+use crate::agent::runtime::{self, RunRecorder, TraceEvent};
+use crate::agi::planner::{Plan, PlanStep};
 use crate::automation::AutomationService;
+use crate::permissions::RunPermissions;
 use crate::router::LLMRouter;
 use anyhow::{anyhow, Result};
 use chrono::Utc;
@@ -28,6 +30,13 @@ impl Default for PlanStepRuntimeState {
     }
 }
 
+/// A recorded plan to run again instead of planning
+struct Replay {
+    replay_of: String,
+    plan: Plan,
+    mocked: HashMap<String, std::result::Result<serde_json::Value, String>>,
+}
+
 /// AGI Core - The central intelligence that coordinates all systems
 pub struct AGICore {
     config: AGIConfig,
@@ -114,6 +123,10 @@ impl AGICore {
         Arc::clone(&self.knowledge_base)
     }
 
+    pub fn executor(&self) -> Arc<AGIExecutor> {
+        Arc::clone(&self.executor)
+    }
+
     /// Create AGI Core with process reasoning and outcome tracking enabled
     pub fn with_process_reasoning(
         config: AGIConfig,
@@ -279,6 +292,17 @@ impl AGICore {
 
     /// Submit a goal for the AGI to achieve
     pub async fn submit_goal(&self, goal: Goal) -> Result<String> {
+        self.submit_goal_with_permissions(goal, RunPermissions::default())
+            .await
+    }
+
+    /// Submit a goal whose tool calls are checked against a specific
+    /// permission profile or employee
+    pub async fn submit_goal_with_permissions(
+        &self,
+        goal: Goal,
+        permissions: RunPermissions,
+    ) -> Result<String> {
         tracing::info!("[AGI] New goal submitted: {}", goal.description);
 
         // Emit goal submitted event
@@ -307,6 +331,7 @@ impl AGICore {
             available_resources: self.resource_manager.get_state().await?,
             tool_results: Vec::new(),
             context_memory: Vec::new(),
+            permissions,
         };
 
         self.execution_contexts
@@ -315,21 +340,84 @@ impl AGICore {
             .insert(goal.id.clone(), context);
 
         // Start planning and execution in background
-        let goal_id = goal.id.clone();
-        let core_clone = self.clone_for_execution();
+        self.spawn_run(&goal, None);
+
+        Ok(goal.id)
+    }
+
+    /// Run a recorded plan again as a new goal, skipping the planner. Steps
+    /// listed in `mocked` return their recorded outcome instead of running.
+    pub async fn replay_plan(
+        &self,
+        goal: Goal,
+        steps: Vec<PlanStep>,
+        mocked: HashMap<String, std::result::Result<serde_json::Value, String>>,
+        permissions: RunPermissions,
+        replay_of: String,
+    ) -> Result<String> {
+        tracing::info!("[AGI] Replaying run {} as {}", replay_of, goal.id);
+
+        let context = ExecutionContext {
+            goal: goal.clone(),
+            current_state: HashMap::new(),
+            available_resources: self.resource_manager.get_state().await?,
+            tool_results: Vec::new(),
+            context_memory: Vec::new(),
+            permissions,
+        };
+        self.execution_contexts
+            .lock()
+            .map_err(|_| anyhow!("Failed to acquire execution contexts lock"))?
+            .insert(goal.id.clone(), context);
+
+        let plan = Plan {
+            goal_id: goal.id.clone(),
+            estimated_duration: Duration::from_secs(steps.len() as u64),
+            estimated_resources: ResourceUsage {
+                cpu_percent: 0.0,
+                memory_mb: 0,
+                network_mb: 0.0,
+            },
+            steps,
+        };
+        self.spawn_run(
+            &goal,
+            Some(Replay {
+                replay_of,
+                plan,
+                mocked,
+            }),
+        );
+
+        Ok(goal.id)
+    }
+
+    /// Plan (or replay) and execute a goal in the background, recording the
+    /// run's trace when a recorder is available
+    fn spawn_run(&self, goal: &Goal, replay: Option<Replay>) {
+        let mut core = self.clone_for_execution();
         // Restore app handle for event emission (clone separately to avoid Send issues)
-        let app_handle_clone = self.app_handle.clone();
-        let mut core_with_app = core_clone;
-        core_with_app.app_handle = app_handle_clone;
-        let goal_id_for_spawn = goal_id.clone();
+        core.app_handle = self.app_handle.clone();
+        let recorder = self.app_handle.as_ref().and_then(RunRecorder::from_app);
+        let goal_id = goal.id.clone();
+        let goal_json = serde_json::to_value(goal).unwrap_or_default();
         // Use tokio::spawn instead of tauri::async_runtime::spawn to avoid Send issues
         tokio::spawn(async move {
-            if let Err(e) = core_with_app.achieve_goal(goal_id_for_spawn).await {
+            let replay_of = replay.as_ref().map(|replay| replay.replay_of.clone());
+            let run = async {
+                match replay {
+                    Some(replay) => core.run_replay(&goal_id, replay).await,
+                    None => core.achieve_goal(goal_id.clone()).await,
+                }
+            };
+            let result = match recorder {
+                Some(recorder) => recorder.record(&goal_id, goal_json, replay_of, run).await,
+                None => run.await,
+            };
+            if let Err(e) = result {
                 tracing::error!("[AGI] Goal execution failed: {}", e);
             }
         });
-
-        Ok(goal.id)
     }
 
     /// Submit a goal for parallel execution with multiple agents (Cursor 2.0-style)
@@ -341,6 +429,7 @@ impl AGICore {
     /// # Arguments
     /// * `goal` - The goal to achieve
     /// * `num_agents` - Number of parallel agents to spawn (default: 8)
+    /// * `permissions` - Permission profile every agent's tool calls are checked against
     ///
     /// # Returns
     /// The best ScoredResult from all parallel executions
@@ -348,6 +437,7 @@ impl AGICore {
         &self,
         goal: Goal,
         num_agents: usize,
+        permissions: RunPermissions,
     ) -> Result<crate::agi::ScoredResult> {
         tracing::info!(
             "[AGI] Parallel goal submitted: {} (agents: {})",
@@ -375,6 +465,7 @@ impl AGICore {
             available_resources: self.resource_manager.get_state().await?,
             tool_results: Vec::new(),
             context_memory: Vec::new(),
+            permissions: permissions.clone(),
         };
 
         // Generate parallel plans with different strategies
@@ -400,7 +491,7 @@ impl AGICore {
         tracing::info!("[AGI] Executing {} plans in parallel", plans.len());
         let results = self
             .executor
-            .execute_plans_parallel(plans, &sandbox_manager, &goal)
+            .execute_plans_parallel(plans, &sandbox_manager, &goal, &permissions)
             .await?;
 
         // Emit execution completed event
@@ -484,15 +575,18 @@ impl AGICore {
         Ok(())
     }
 
-    /// Achieve a specific goal
-    async fn achieve_goal(&self, goal_id: String) -> Result<()> {
-        let mut context = self
-            .execution_contexts
+    fn context_for(&self, goal_id: &str) -> Result<ExecutionContext> {
+        self.execution_contexts
             .lock()
             .unwrap()
-            .get(&goal_id)
-            .ok_or_else(|| anyhow!("Goal {} not found", goal_id))?
-            .clone();
+            .get(goal_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Goal {} not found", goal_id))
+    }
+
+    /// Achieve a specific goal
+    async fn achieve_goal(&self, goal_id: String) -> Result<()> {
+        let context = self.context_for(&goal_id)?;
 
         tracing::info!("[AGI] Achieving goal: {}", context.goal.description);
 
@@ -501,6 +595,30 @@ impl AGICore {
 
         tracing::info!("[AGI] Plan created with {} steps", plan.steps.len());
 
+        self.run_plan(&goal_id, context, plan, &HashMap::new())
+            .await
+    }
+
+    async fn run_replay(&self, goal_id: &str, replay: Replay) -> Result<()> {
+        let context = self.context_for(goal_id)?;
+        self.run_plan(goal_id, context, replay.plan, &replay.mocked)
+            .await
+    }
+
+    /// Execute a plan's steps in order. Steps with an entry in `mocked`
+    /// return it instead of running their tool.
+    async fn run_plan(
+        &self,
+        goal_id: &str,
+        mut context: ExecutionContext,
+        plan: Plan,
+        mocked: &HashMap<String, std::result::Result<serde_json::Value, String>>,
+    ) -> Result<()> {
+        let goal_id = goal_id.to_string();
+        runtime::record_event(TraceEvent::Plan {
+            steps: serde_json::to_value(&plan.steps)?,
+        });
+
         let workflow_hash = compute_plan_workflow_hash(&context.goal, &plan);
         let plan_created_at = Utc::now().timestamp_millis();
         let mut step_states = vec![PlanStepRuntimeState::default(); plan.steps.len()];
@@ -544,6 +662,24 @@ impl AGICore {
                 }),
             );
 
+            // Pause for the user's go-ahead once the run is over budget
+            if let Some(overrun) = runtime::budget_overrun() {
+                if !self
+                    .approve_budget_overrun(&goal_id, &overrun, &context)
+                    .await
+                {
+                    self.emit_event(
+                        "agi:goal:budget_stopped",
+                        json!({
+                            "goal_id": goal_id,
+                            "step_index": index,
+                            "overrun": overrun,
+                        }),
+                    );
+                    return Err(anyhow!("Run stopped: {}", overrun));
+                }
+            }
+
             // Check resources before execution
             if !self
                 .resource_manager
@@ -571,11 +707,27 @@ impl AGICore {
 
             // Execute step
             let start = std::time::Instant::now();
-            let execution = self.executor.execute_step(step, &context).await;
+            let recorded = mocked.get(&step.id);
+            let execution = match recorded {
+                Some(outcome) => outcome.clone(),
+                None => self
+                    .executor
+                    .execute_step(step, &context)
+                    .await
+                    .map_err(|err| err.to_string()),
+            };
             let execution_time = start.elapsed();
+            runtime::record_tool_call(
+                &step.id,
+                &step.tool_id,
+                serde_json::to_value(&step.parameters)?,
+                &execution,
+                execution_time.as_millis() as u64,
+                recorded.is_some(),
+            );
             let (success, step_value, error_text) = match execution {
                 Ok(value) => (true, value, None),
-                Err(err) => (false, serde_json::Value::Null, Some(err.to_string())),
+                Err(err) => (false, serde_json::Value::Null, Some(err)),
             };
 
             // Release resources
@@ -651,6 +803,8 @@ impl AGICore {
                 "completed_steps": index + 1,
                 "total_steps": plan.steps.len(),
                 "progress_percent": ((index + 1) as f64 / plan.steps.len() as f64 * 100.0) as u32,
+                "budget": runtime::budget_usage()
+                    .map(|(budget, usage)| json!({ "limits": budget, "usage": usage })),
             }));
 
             // Check if goal is achieved
@@ -689,6 +843,73 @@ impl AGICore {
         Ok(())
     }
 
+    /// Ask the user whether a run that reached a budget limit may go on. The
+    /// answer is recorded in the run's trace.
+    async fn approve_budget_overrun(
+        &self,
+        goal_id: &str,
+        overrun: &runtime::BudgetOverrun,
+        context: &ExecutionContext,
+    ) -> bool {
+        use crate::agent::approval::{
+            ApprovalController, ApprovalRequestPayload, ApprovalResolution, ApprovalScope,
+            ApprovalScopeType,
+        };
+        use tauri::Manager;
+
+        tracing::warn!("[AGI] Goal {}: {}", goal_id, overrun);
+        self.emit_event(
+            "agi:goal:budget_exceeded",
+            json!({
+                "goal_id": goal_id,
+                "overrun": overrun,
+            }),
+        );
+
+        let approvals = self
+            .app_handle
+            .as_ref()
+            .and_then(|app| Some((app, app.try_state::<ApprovalController>()?)));
+        let approved = match approvals {
+            Some((app, approvals)) => {
+                let payload = ApprovalRequestPayload {
+                    action_id: uuid::Uuid::new_v4().to_string(),
+                    tool_name: "run_budget".to_string(),
+                    title: "Continue over budget?".to_string(),
+                    description: format!(
+                        "The agent working on \"{}\" reached its budget",
+                        context.goal.description
+                    ),
+                    reason: overrun.to_string(),
+                    // Never auto-approved by a policy
+                    risk_level: "high".to_string(),
+                    scope: ApprovalScope {
+                        scope_type: ApprovalScopeType::Unknown,
+                        command: None,
+                        cwd: None,
+                        path: None,
+                        domain: None,
+                        description: Some("Run budget".to_string()),
+                        risk: "high".to_string(),
+                    },
+                    workflow_hash: None,
+                    action_signature: format!("run_budget:{}", goal_id),
+                };
+                match approvals.request_approval(app, payload).await {
+                    Ok(resolution) => matches!(resolution, ApprovalResolution::Approved { .. }),
+                    Err(e) => {
+                        tracing::warn!("[AGI] Budget approval failed: {}", e);
+                        false
+                    }
+                }
+            }
+            None => false,
+        };
+
+        runtime::resolve_budget_overrun(overrun, approved);
+        approved
+    }
+
     /// Check if goal has been achieved
     async fn check_goal_achieved(&self, context: &ExecutionContext) -> Result<bool> {
         // Check success criteria
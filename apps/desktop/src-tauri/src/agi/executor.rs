@@ -1,19 +1,31 @@
 use super::*;
+use crate::agent::file_checkpoints::CheckpointStore;
 use crate::agi::api_tools_impl;
 use crate::agi::outcome_tracker::OutcomeTracker;
 use crate::agi::planner::PlanStep;
 use crate::agi::process_reasoning::ProcessReasoning;
+use crate::audit::{self, Actor, AuditCategory, AuditEntry, AuditOutcome};
 use crate::automation::AutomationService;
+use crate::browser::healing::{act_with_healing, ElementQuery, HealedSelector};
+use crate::cache::warmup::{self, PatternKind};
 use crate::cache::ToolResultCache;
 use crate::calendar::EventDateTime;
+use crate::permissions::profiles::{
+    self, DecisionOutcome, PermissionProfile, RunPermissions, ToolCategory, ToolDecision,
+    ToolDecisionRecord,
+};
 use crate::router::{ChatMessage, LLMRequest, LLMRouter, RouterPreferences, RoutingStrategy};
-use crate::security::ToolExecutionGuard;
+use crate::security::{guardrails, shell_guard, ToolExecutionGuard};
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Context state key naming the skill or workflow a tool call runs in, so
+/// selector healing is attributed to it
+const WORKFLOW_ID_KEY: &str = "workflow_id";
+
 /// AGI Executor - executes plan steps using tools
 pub struct AGIExecutor {
     tool_registry: Arc<ToolRegistry>,
@@ -108,6 +120,405 @@ impl AGIExecutor {
         self.tool_cache.prune_expired()
     }
 
+    /// Run a read-only tool on behalf of cache warmup. `Ok(false)` when a
+    /// fresh result was already cached.
+    pub async fn warm_tool(
+        &self,
+        tool_name: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<bool> {
+        if !warmup::WARMABLE_TOOLS.contains(&tool_name) {
+            return Err(anyhow!("Tool '{}' is not warmable", tool_name));
+        }
+        if self.tool_cache.contains(tool_name, parameters) {
+            return Ok(false);
+        }
+
+        let context = ExecutionContext {
+            goal: Goal {
+                id: format!("cache-warmup-{}", uuid::Uuid::new_v4()),
+                description: "Cache warmup".to_string(),
+                priority: Priority::Low,
+                deadline: None,
+                constraints: Vec::new(),
+                success_criteria: Vec::new(),
+            },
+            current_state: HashMap::new(),
+            available_resources: self._resource_manager.get_state().await?,
+            tool_results: Vec::new(),
+            context_memory: Vec::new(),
+            permissions: Default::default(),
+        };
+        let result = self
+            .execute_tool_impl(tool_name, parameters, &context)
+            .await?;
+        self.tool_cache.set(tool_name, parameters, result)?;
+        Ok(true)
+    }
+
+    /// Run one tool for a caller outside the agent loop, such as an MCP
+    /// client. The call goes through the same permission checks, guardrails
+    /// and audit as a goal's tool calls.
+    pub async fn call_tool(
+        &self,
+        tool_id: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+        caller: &str,
+        permissions: RunPermissions,
+    ) -> Result<serde_json::Value> {
+        let tool = self
+            .tool_registry
+            .get_tool(tool_id)
+            .ok_or_else(|| anyhow!("Tool '{}' not found", tool_id))?;
+        let context = ExecutionContext {
+            goal: Goal {
+                id: format!("external-{}", uuid::Uuid::new_v4()),
+                description: format!("{} called {}", caller, tool.name),
+                priority: Priority::Medium,
+                deadline: None,
+                constraints: Vec::new(),
+                success_criteria: Vec::new(),
+            },
+            current_state: HashMap::new(),
+            available_resources: self._resource_manager.get_state().await?,
+            tool_results: Vec::new(),
+            context_memory: Vec::new(),
+            permissions,
+        };
+        self.execute_tool(&tool, parameters, &context).await
+    }
+
+    /// Pass the shared database to `f`, if the app has one
+    fn with_app_db(&self, f: impl FnOnce(&rusqlite::Connection)) {
+        use tauri::Manager;
+        let Some(db) = self
+            .app_handle
+            .as_ref()
+            .and_then(|app| app.try_state::<crate::commands::AppDatabase>())
+        else {
+            return;
+        };
+        if let Ok(conn) = db.conn.lock() {
+            f(&conn);
+        };
+    }
+
+    /// Check a tool call against the run's permission profile, asking the
+    /// user when the profile says so. Every decision is audited. `Ok(false)`
+    /// when the approval was skipped and the call should not run.
+    async fn authorize_tool(
+        &self,
+        tool_name: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+        context: &ExecutionContext,
+    ) -> Result<bool> {
+        use tauri::Manager;
+
+        let Some(category) = ToolCategory::of(tool_name) else {
+            return Ok(true);
+        };
+        let pool = self
+            .app_handle
+            .as_ref()
+            .and_then(|app| app.try_state::<crate::db::Pool>())
+            .map(|pool| pool.inner().clone());
+
+        let run = context.permissions.clone();
+        let profile = match &pool {
+            Some(pool) => {
+                pool.run(move |conn| -> Result<_> { profiles::resolve_profile(conn, &run) })
+                    .await
+            }
+            // Sandboxed parallel runs have no database; built-in profiles still apply
+            None => {
+                let id = run
+                    .profile_id
+                    .as_deref()
+                    .unwrap_or(profiles::DEFAULT_PROFILE);
+                profiles::builtin_profile(id)
+                    .ok_or_else(|| anyhow!("Unknown permission profile '{}'", id))
+            }
+        };
+
+        let mut skipped = false;
+        let (profile_id, outcome, reason) = match profile {
+            Ok(profile) => {
+                let (outcome, reason) = match profile.decision(category) {
+                    ToolDecision::Allow => (DecisionOutcome::Allowed, None),
+                    ToolDecision::Deny => (
+                        DecisionOutcome::Denied,
+                        Some(format!(
+                            "Permission profile '{}' does not allow {}",
+                            profile.name,
+                            category.label()
+                        )),
+                    ),
+                    ToolDecision::Ask => {
+                        let (outcome, reason, skip) = self
+                            .ask_tool_permission(tool_name, parameters, category, &profile, context)
+                            .await;
+                        skipped = skip;
+                        (outcome, reason)
+                    }
+                };
+                (profile.id, outcome, reason)
+            }
+            Err(e) => (
+                context.permissions.profile_id.clone().unwrap_or_default(),
+                DecisionOutcome::Denied,
+                Some(format!("Failed to resolve permission profile: {}", e)),
+            ),
+        };
+
+        let record = ToolDecisionRecord {
+            id: 0,
+            run_id: context.goal.id.clone(),
+            user_employee_id: context.permissions.user_employee_id.clone(),
+            profile_id,
+            tool_name: tool_name.to_string(),
+            category,
+            outcome,
+            reason: reason.clone(),
+            created_at: Utc::now(),
+        };
+        tracing::info!(
+            "[Executor] Permission profile '{}' {} '{}' for goal {}",
+            record.profile_id,
+            outcome.as_str(),
+            tool_name,
+            record.run_id
+        );
+        if let Some(app) = self.app_handle.as_ref() {
+            let actor = match &record.user_employee_id {
+                Some(employee_id) => Actor::employee(employee_id.clone()),
+                None => Actor::agent(record.run_id.clone()),
+            };
+            let entry = AuditEntry::new(actor, AuditCategory::Permission, "tool_permission")
+                .target(tool_name)
+                .outcome(if outcome.permits() {
+                    AuditOutcome::Success
+                } else {
+                    AuditOutcome::Denied
+                })
+                .detail(format!(
+                    "{} by profile '{}'{}",
+                    outcome.as_str(),
+                    record.profile_id,
+                    reason
+                        .as_deref()
+                        .map(|r| format!(": {}", r))
+                        .unwrap_or_default()
+                ));
+            audit::record(app, entry);
+        }
+        if let Some(pool) = pool {
+            let recorded = pool
+                .run(move |conn| -> Result<_> { profiles::record_decision(conn, &record) })
+                .await;
+            if let Err(e) = recorded {
+                tracing::warn!(
+                    "[Executor] Failed to audit permission decision for '{}': {}",
+                    tool_name,
+                    e
+                );
+            }
+        }
+
+        if outcome.permits() {
+            Ok(true)
+        } else if skipped {
+            Ok(false)
+        } else {
+            Err(anyhow!(
+                "{}",
+                reason.unwrap_or_else(|| format!("Tool '{}' was not permitted", tool_name))
+            ))
+        }
+    }
+
+    /// The user's decision, and whether they (or a timeout policy) skipped the call
+    async fn ask_tool_permission(
+        &self,
+        tool_name: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+        category: ToolCategory,
+        profile: &PermissionProfile,
+        context: &ExecutionContext,
+    ) -> (DecisionOutcome, Option<String>, bool) {
+        use crate::agent::approval::{
+            ApprovalController, ApprovalRequestPayload, ApprovalResolution, ApprovalScope,
+            ApprovalScopeType,
+        };
+        use tauri::Manager;
+
+        let Some(app) = self.app_handle.as_ref() else {
+            return (
+                DecisionOutcome::Rejected,
+                Some(format!(
+                    "Nobody to approve {} for this run",
+                    category.label()
+                )),
+                false,
+            );
+        };
+        let Some(approvals) = app.try_state::<ApprovalController>() else {
+            return (
+                DecisionOutcome::Rejected,
+                Some("Approvals are unavailable".to_string()),
+                false,
+            );
+        };
+
+        let param = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+        let scope_type = match category {
+            ToolCategory::Shell => ApprovalScopeType::Terminal,
+            ToolCategory::FilesystemWrite => ApprovalScopeType::Filesystem,
+            ToolCategory::Browser => ApprovalScopeType::Browser,
+            ToolCategory::UiAutomation => ApprovalScopeType::Ui,
+            _ => ApprovalScopeType::Unknown,
+        };
+        let payload = ApprovalRequestPayload {
+            action_id: uuid::Uuid::new_v4().to_string(),
+            tool_name: tool_name.to_string(),
+            title: format!("Allow {}?", tool_name),
+            description: format!(
+                "The agent working on \"{}\" wants to run {}",
+                context.goal.description, tool_name
+            ),
+            reason: format!(
+                "Permission profile '{}' asks before {}",
+                profile.name,
+                category.label()
+            ),
+            risk_level: category.risk().to_string(),
+            scope: ApprovalScope {
+                scope_type,
+                command: param("command"),
+                cwd: param("cwd"),
+                path: param("path"),
+                domain: param("url")
+                    .and_then(|url| url::Url::parse(&url).ok())
+                    .and_then(|url| url.host_str().map(str::to_string)),
+                description: Some(category.label().to_string()),
+                risk: category.risk().to_string(),
+            },
+            workflow_hash: None,
+            action_signature: format!("{}:{}", profile.id, tool_name),
+        };
+
+        match approvals.request_approval(app, payload).await {
+            Ok(ApprovalResolution::Approved { .. }) => (DecisionOutcome::Approved, None, false),
+            Ok(ApprovalResolution::Rejected { reason }) => (
+                DecisionOutcome::Rejected,
+                Some(reason.unwrap_or_else(|| format!("{} was rejected", tool_name))),
+                false,
+            ),
+            Ok(ApprovalResolution::Skipped { reason }) => (
+                DecisionOutcome::Rejected,
+                Some(reason.unwrap_or_else(|| format!("{} was skipped", tool_name))),
+                true,
+            ),
+            Err(e) => (
+                DecisionOutcome::Rejected,
+                Some(format!("Approval failed: {}", e)),
+                false,
+            ),
+        }
+    }
+
+    /// Run a saved skill's steps in order, each through the usual permission
+    /// checks, stopping at the first failure
+    async fn run_skill(
+        &self,
+        parameters: &HashMap<String, serde_json::Value>,
+        context: &ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let skill_id = parameters
+            .get("skill_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Missing skill_id parameter"))?;
+        let args = parameters
+            .get("args")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        let library = super::skills::global_skill_library();
+        let skill = library
+            .get(skill_id)
+            .await?
+            .ok_or_else(|| anyhow!("Skill {} not found", skill_id))?;
+        let steps = skill.instantiate(&args)?;
+        let mut skill_context = context.clone();
+        skill_context
+            .current_state
+            .insert(WORKFLOW_ID_KEY.to_string(), json!(skill.id));
+        let mut upgraded = skill.clone();
+
+        let mut outputs = Vec::with_capacity(steps.len());
+        for step in &steps {
+            let tool = self
+                .tool_registry
+                .get_tool(&step.tool_id)
+                .ok_or_else(|| anyhow!("Tool {} not found", step.tool_id))?;
+            let started = std::time::Instant::now();
+            // Boxed: the skill's steps come back through execute_tool
+            let outcome = Box::pin(self.execute_tool(&tool, &step.parameters, &skill_context))
+                .await
+                .map_err(|e| e.to_string());
+            crate::agent::runtime::record_tool_call(
+                &format!("{}/{}", skill.id, step.id),
+                &step.tool_id,
+                serde_json::to_value(&step.parameters)?,
+                &outcome,
+                started.elapsed().as_millis() as u64,
+                false,
+            );
+            let output = outcome
+                .map_err(|e| anyhow!("Skill '{}' failed at step {}: {}", skill.name, step.id, e))?;
+
+            // Store a healed selector so later runs skip the stale one
+            let healed = output
+                .get("healed")
+                .and_then(|h| serde_json::from_value::<HealedSelector>(h.clone()).ok());
+            if let Some(healed) = healed {
+                let stored = upgraded
+                    .steps
+                    .iter_mut()
+                    .find(|s| s.id == step.id)
+                    .and_then(|s| s.parameters.get_mut("selector"))
+                    .filter(|selector| selector.as_str() == Some(healed.from.as_str()));
+                if let Some(selector) = stored {
+                    *selector = json!(healed.selector);
+                    if let Err(e) = library.save(upgraded.clone()).await {
+                        tracing::warn!(
+                            "Failed to store healed selector for skill {}: {}",
+                            skill.id,
+                            e
+                        );
+                    }
+                }
+            }
+            outputs.push(json!({
+                "step_id": step.id,
+                "tool_id": step.tool_id,
+                "output": output,
+            }));
+        }
+        library.record_use(&skill.id).await;
+
+        Ok(json!({
+            "skill_id": skill.id,
+            "skill": skill.name,
+            "steps": outputs,
+        }))
+    }
+
     fn normalized_step_id(step_id: &str) -> String {
         if step_id.trim().is_empty() {
             uuid::Uuid::new_v4().to_string()
@@ -149,6 +560,137 @@ impl AGIExecutor {
         crate::productivity::TaskStatus::from_notion_status(status)
     }
 
+    /// The run's own page in the browser pool when it is one of several
+    /// parallel orchestrator agents; held until the lease is dropped
+    async fn pooled_page(
+        &self,
+        context: &ExecutionContext,
+    ) -> Result<Option<crate::browser::PoolLease>> {
+        use crate::browser::BrowserPool;
+        use crate::orchestration::blackboard::blackboards;
+        use tauri::Manager;
+
+        if blackboards().for_goal(&context.goal.id).is_none() {
+            return Ok(None);
+        }
+        let Some(pool) = self
+            .app_handle
+            .as_ref()
+            .and_then(|app| app.try_state::<Arc<BrowserPool>>())
+        else {
+            return Ok(None);
+        };
+        let lease = pool
+            .inner()
+            .lease(&context.goal.id)
+            .await
+            .map_err(|e| anyhow!("Failed to get a pooled browser: {}", e))?;
+        Ok(Some(lease))
+    }
+
+    /// CDP client for `tab_id`, or for the first open tab. Parallel agents
+    /// get their pooled page instead, with the lease to hold while using it.
+    async fn browser_tab_client(
+        &self,
+        tab_id: Option<&str>,
+        context: &ExecutionContext,
+    ) -> Result<(
+        String,
+        Arc<crate::browser::CdpClient>,
+        Option<crate::browser::PoolLease>,
+    )> {
+        use crate::commands::BrowserStateWrapper;
+        use tauri::Manager;
+
+        if let Some(lease) = self.pooled_page(context).await? {
+            let client = lease.client();
+            self.capture_downloads(lease.target_id(), &client).await;
+            return Ok((lease.target_id().to_string(), client, Some(lease)));
+        }
+
+        let app = self
+            .app_handle
+            .as_ref()
+            .ok_or_else(|| anyhow!("App handle not available for browser automation"))?;
+        let browser_state = app.state::<BrowserStateWrapper>();
+        let browser_guard = browser_state.inner().0.lock().await;
+
+        // Determine which tab to use
+        let target_tab_id = match tab_id {
+            Some(tid) => tid.to_string(),
+            None => {
+                let tab_manager = browser_guard.tab_manager.lock().await;
+                let tabs = tab_manager
+                    .list_tabs()
+                    .await
+                    .map_err(|e| anyhow!("Failed to list tabs: {}", e))?;
+                tabs.first().map(|tab| tab.id.clone()).ok_or_else(|| {
+                    anyhow!("No browser tabs available. Please navigate to a URL first using browser_navigate.")
+                })?
+            }
+        };
+
+        let cdp_client = browser_guard
+            .get_cdp_client(&target_tab_id)
+            .await
+            .map_err(|e| anyhow!("Failed to get CDP client: {}", e))?;
+        drop(browser_guard);
+        self.capture_downloads(&target_tab_id, &cdp_client).await;
+        Ok((target_tab_id, cdp_client, None))
+    }
+
+    /// Save the tab's downloads where `browser_wait_for_download` can find them
+    async fn capture_downloads(&self, tab_id: &str, cdp_client: &crate::browser::CdpClient) {
+        use crate::browser::DownloadManager;
+        use tauri::Manager;
+
+        let Some(downloads) = self
+            .app_handle
+            .as_ref()
+            .and_then(|app| app.try_state::<Arc<DownloadManager>>())
+        else {
+            return;
+        };
+        if let Err(e) = downloads
+            .inner()
+            .ensure(tab_id, cdp_client.ws_url().to_string())
+            .await
+        {
+            tracing::warn!(
+                "[Executor] Downloads of tab {} are not captured: {}",
+                tab_id,
+                e
+            );
+        }
+    }
+
+    /// Run `act` on the call's element, healing the selector if the element is
+    /// gone. Returns the selector that worked and the heal, if there was one.
+    async fn act_on_element<F, Fut>(
+        &self,
+        tool_id: &str,
+        cdp_client: &crate::browser::CdpClient,
+        parameters: &HashMap<String, serde_json::Value>,
+        context: &ExecutionContext,
+        act: F,
+    ) -> crate::error::Result<(String, Option<HealedSelector>)>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = crate::error::Result<()>>,
+    {
+        let query = ElementQuery::from_parameters(parameters)
+            .ok_or_else(|| crate::error::Error::Other("Missing selector parameter".into()))?;
+        let workflow_id = context
+            .current_state
+            .get(WORKFLOW_ID_KEY)
+            .and_then(|v| v.as_str());
+        let healed = act_with_healing(cdp_client, &query, tool_id, workflow_id, act).await?;
+        let selector = healed
+            .as_ref()
+            .map_or(query.selector, |h| h.selector.clone());
+        Ok((selector, healed))
+    }
+
     /// Execute a plan step
     pub async fn execute_step(
         &self,
@@ -227,19 +769,85 @@ impl AGIExecutor {
     ) -> Result<serde_json::Value> {
         let tool_name = tool.id.as_str();
 
+        // Before the cache, so a cached result is never handed to a run that may not call the tool
+        if !self.authorize_tool(tool_name, parameters, _context).await? {
+            return Ok(json!({ "skipped": true, "tool": tool_name }));
+        }
+
+        let guardrails = guardrails::shared(self.app_handle.as_ref());
+        let run_id = _context.goal.id.as_str();
+        guardrails
+            .check_egress(
+                self.app_handle.as_ref(),
+                run_id,
+                tool_name,
+                &serde_json::to_value(parameters)?,
+            )
+            .map_err(|e| anyhow!(e))?;
+
         // Check cache before executing
-        if let Some(cached_result) = self.tool_cache.get(tool_name, parameters) {
+        if let Some(mut cached_result) = self.tool_cache.get(tool_name, parameters) {
             tracing::info!(
                 "[Executor] Using cached result for tool '{}' (cache hit)",
                 tool_name
             );
+            let key = ToolResultCache::generate_cache_key(tool_name, parameters);
+            self.with_app_db(|conn| warmup::record_warm_hit(conn, PatternKind::Tool, &key));
+            guardrails.screen_value(
+                self.app_handle.as_ref(),
+                run_id,
+                tool_name,
+                &mut cached_result,
+            );
             return Ok(cached_result);
         }
 
-        // Execute tool
-        let result = self
+        // Keep the prior content of any file the tool changes so the run can be rolled back
+        if let Some(store) = self.app_handle.as_ref().and_then(CheckpointStore::from_app) {
+            store
+                .before_tool(run_id, tool_name, &serde_json::to_value(parameters)?)
+                .await
+                .map_err(|e| e.context("Failed to snapshot file before changing it"))?;
+        }
+
+        // Execute tool, recording the outcome for reliability scoring
+        let started = std::time::Instant::now();
+        let outcome = self
             .execute_tool_impl(tool_name, parameters, _context)
-            .await?;
+            .await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        if let Some(app) = self.app_handle.as_ref() {
+            let actor = match &_context.permissions.user_employee_id {
+                Some(employee_id) => Actor::employee(employee_id.clone()),
+                None => Actor::agent(run_id),
+            };
+            if let Some(entry) =
+                AuditEntry::for_tool(actor, tool_name, &serde_json::to_value(parameters)?)
+            {
+                audit::record(app, entry.result(&outcome));
+            }
+        }
+        super::tool_reliability::global_tool_reliability().record(
+            tool_name,
+            super::tool_reliability::call_method(parameters),
+            outcome.is_ok(),
+            duration_ms,
+            outcome.as_ref().err().map(|e| e.to_string()),
+        );
+        super::tool_costs::global_tool_costs().record(
+            tool_name,
+            outcome.is_ok(),
+            duration_ms,
+            outcome.as_ref().ok().and_then(ToolUsage::from_result),
+        );
+        let mut result = outcome?;
+
+        if warmup::WARMABLE_TOOLS.contains(&tool_name) {
+            let key = ToolResultCache::generate_cache_key(tool_name, parameters);
+            self.with_app_db(|conn| {
+                warmup::record_tool_call(conn, &key, tool_name, parameters, chrono::Local::now())
+            });
+        }
 
         // Cache the result (cache will determine if it should be cached based on TTL)
         if let Err(e) = self.tool_cache.set(tool_name, parameters, result.clone()) {
@@ -250,6 +858,8 @@ impl AGIExecutor {
             );
         }
 
+        // The cache keeps the raw result; the model only sees the screened one
+        guardrails.screen_value(self.app_handle.as_ref(), run_id, tool_name, &mut result);
         Ok(result)
     }
 
@@ -481,6 +1091,15 @@ impl AGIExecutor {
                     .as_str()
                     .ok_or_else(|| anyhow::anyhow!("Missing url parameter"))?;
 
+                if let Some(lease) = self.pooled_page(_context).await? {
+                    lease
+                        .client()
+                        .navigate(url)
+                        .await
+                        .map_err(|e| anyhow!("Failed to navigate: {}", e))?;
+                    return Ok(json!({ "success": true, "url": url, "tab_id": lease.target_id() }));
+                }
+
                 if let Some(ref app) = self.app_handle {
                     use crate::commands::BrowserStateWrapper;
                     use tauri::Manager;
@@ -524,51 +1143,202 @@ impl AGIExecutor {
                     .ok_or_else(|| anyhow!("Missing selector parameter"))?;
                 let tab_id = parameters.get("tab_id").and_then(|v| v.as_str());
 
-                if let Some(ref app) = self.app_handle {
-                    use crate::browser::{ClickOptions, DomOperations};
-                    use crate::commands::BrowserStateWrapper;
-                    use tauri::Manager;
-
-                    let browser_state = app.state::<BrowserStateWrapper>();
-                    let browser_guard = browser_state.inner().0.lock().await;
-                    let tab_manager = browser_guard.tab_manager.lock().await;
+                use crate::browser::{ClickOptions, DomOperations};
+                let (target_tab_id, cdp_client, _lease) =
+                    self.browser_tab_client(tab_id, _context).await?;
 
-                    // Determine which tab to use
-                    let target_tab_id = if let Some(tid) = tab_id {
-                        tid.to_string()
-                    } else {
-                        // Use first available tab
-                        let tabs = tab_manager
-                            .list_tabs()
-                            .await
-                            .map_err(|e| anyhow!("Failed to list tabs: {}", e))?;
-                        if tabs.is_empty() {
-                            return Err(anyhow!("No browser tabs available. Please navigate to a URL first using browser_navigate."));
+                // Click the element
+                let (used, healed) = self
+                    .act_on_element("browser_click", &cdp_client, parameters, _context, |sel| {
+                        let client = Arc::clone(&cdp_client);
+                        async move {
+                            DomOperations::click_with_cdp(client, &sel, ClickOptions::default())
+                                .await
                         }
-                        tabs[0].id.clone()
-                    };
+                    })
+                    .await
+                    .map_err(|e| anyhow!("Failed to click element '{}': {}", selector, e))?;
 
-                    // Get CDP client for the tab
-                    let cdp_client = browser_guard
-                        .get_cdp_client(&target_tab_id)
-                        .await
-                        .map_err(|e| anyhow!("Failed to get CDP client: {}", e))?;
+                Ok(json!({
+                    "success": true,
+                    "action": "clicked",
+                    "selector": used,
+                    "healed": healed,
+                    "tab_id": target_tab_id
+                }))
+            }
+            "browser_type" => {
+                let selector = parameters
+                    .get("selector")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing selector parameter"))?;
+                let text = parameters
+                    .get("text")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing text parameter"))?;
+                let clear_first = parameters
+                    .get("clear_first")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let tab_id = parameters.get("tab_id").and_then(|v| v.as_str());
 
-                    // Click the element
-                    let options = ClickOptions::default();
-                    DomOperations::click_with_cdp(cdp_client, selector, options)
-                        .await
-                        .map_err(|e| anyhow!("Failed to click element '{}': {}", selector, e))?;
+                let (target_tab_id, cdp_client, _lease) =
+                    self.browser_tab_client(tab_id, _context).await?;
+                let (used, healed) = self
+                    .act_on_element("browser_type", &cdp_client, parameters, _context, |sel| {
+                        let client = Arc::clone(&cdp_client);
+                        let text = text.to_string();
+                        async move { client.type_into_element(&sel, &text, clear_first).await }
+                    })
+                    .await
+                    .map_err(|e| anyhow!("Failed to type into '{}': {}", selector, e))?;
 
-                    Ok(json!({
-                        "success": true,
-                        "action": "clicked",
-                        "selector": selector,
-                        "tab_id": target_tab_id
-                    }))
-                } else {
-                    Err(anyhow!("App handle not available for browser click"))
+                // The text is left out; it may be a password
+                Ok(json!({
+                    "success": true,
+                    "action": "typed",
+                    "selector": used,
+                    "healed": healed,
+                    "tab_id": target_tab_id
+                }))
+            }
+            "browser_select" => {
+                let selector = parameters
+                    .get("selector")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing selector parameter"))?;
+                let value = parameters
+                    .get("value")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing value parameter"))?;
+                let tab_id = parameters.get("tab_id").and_then(|v| v.as_str());
+
+                let (target_tab_id, cdp_client, _lease) =
+                    self.browser_tab_client(tab_id, _context).await?;
+                let (used, healed) = self
+                    .act_on_element("browser_select", &cdp_client, parameters, _context, |sel| {
+                        let client = Arc::clone(&cdp_client);
+                        let value = value.to_string();
+                        async move { client.select_option(&sel, &value).await }
+                    })
+                    .await
+                    .map_err(|e| {
+                        anyhow!("Failed to select '{}' in '{}': {}", value, selector, e)
+                    })?;
+
+                Ok(json!({
+                    "success": true,
+                    "action": "selected",
+                    "selector": used,
+                    "healed": healed,
+                    "value": value,
+                    "tab_id": target_tab_id
+                }))
+            }
+            "browser_check" => {
+                let selector = parameters
+                    .get("selector")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing selector parameter"))?;
+                let checked = parameters
+                    .get("checked")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let tab_id = parameters.get("tab_id").and_then(|v| v.as_str());
+
+                let (target_tab_id, cdp_client, _lease) =
+                    self.browser_tab_client(tab_id, _context).await?;
+                let (used, healed) = self
+                    .act_on_element("browser_check", &cdp_client, parameters, _context, |sel| {
+                        let client = Arc::clone(&cdp_client);
+                        async move { client.set_checked(&sel, checked).await }
+                    })
+                    .await
+                    .map_err(|e| anyhow!("Failed to set '{}' checked: {}", selector, e))?;
+
+                let action = if checked { "checked" } else { "unchecked" };
+                Ok(json!({
+                    "success": true,
+                    "action": action,
+                    "selector": used,
+                    "healed": healed,
+                    "tab_id": target_tab_id
+                }))
+            }
+            "browser_press_key" => {
+                let key = parameters
+                    .get("key")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing key parameter"))?;
+                let selector = parameters.get("selector").and_then(|v| v.as_str());
+                let tab_id = parameters.get("tab_id").and_then(|v| v.as_str());
+
+                let (target_tab_id, cdp_client, _lease) =
+                    self.browser_tab_client(tab_id, _context).await?;
+                let mut healed = None;
+                if let Some(selector) = selector {
+                    healed = self
+                        .act_on_element(
+                            "browser_press_key",
+                            &cdp_client,
+                            parameters,
+                            _context,
+                            |sel| {
+                                let client = Arc::clone(&cdp_client);
+                                async move { client.focus_element(&sel).await }
+                            },
+                        )
+                        .await
+                        .map_err(|e| anyhow!("Failed to focus '{}': {}", selector, e))?
+                        .1;
                 }
+                cdp_client
+                    .press_key(key)
+                    .await
+                    .map_err(|e| anyhow!("Failed to press {}: {}", key, e))?;
+
+                Ok(json!({
+                    "success": true,
+                    "action": "pressed",
+                    "key": key,
+                    "healed": healed,
+                    "tab_id": target_tab_id
+                }))
+            }
+            "browser_wait_for_download" => {
+                let tab_id = parameters.get("tab_id").and_then(|v| v.as_str());
+                let options = crate::browser::WaitForDownload {
+                    timeout_ms: parameters.get("timeout_ms").and_then(|v| v.as_u64()),
+                    file_name: parameters
+                        .get("file_name")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                    sha256: parameters
+                        .get("sha256")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                };
+
+                use crate::browser::DownloadManager;
+                use tauri::Manager;
+                let (target_tab_id, _cdp_client, _lease) =
+                    self.browser_tab_client(tab_id, _context).await?;
+                let downloads = self
+                    .app_handle
+                    .as_ref()
+                    .and_then(|app| app.try_state::<Arc<DownloadManager>>())
+                    .ok_or_else(|| anyhow!("Download capture is not available"))?;
+                let download = downloads
+                    .inner()
+                    .wait(&target_tab_id, options)
+                    .await
+                    .map_err(|e| anyhow!("Failed to wait for download: {}", e))?;
+
+                Ok(json!({
+                    "success": true,
+                    "download": download,
+                    "tab_id": target_tab_id
+                }))
             }
             "browser_extract" => {
                 let selector = parameters
@@ -694,6 +1464,17 @@ impl AGIExecutor {
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| anyhow::anyhow!("Missing code parameter"))?;
 
+                let verdict = shell_guard::guard_for(self.app_handle.as_ref())?.check(code);
+                shell_guard::enforce(
+                    self.app_handle.as_ref(),
+                    "code_execute",
+                    code,
+                    None,
+                    &verdict,
+                )
+                .await
+                .map_err(|e| anyhow!(e))?;
+
                 if let Some(ref app) = self.app_handle {
                     use crate::terminal::SessionManager;
                     use crate::terminal::ShellType;
@@ -815,6 +1596,13 @@ impl AGIExecutor {
                     Err(anyhow!("App handle not available for API call"))
                 }
             }
+            "web_fetch" => {
+                if let Some(ref app) = self.app_handle {
+                    api_tools_impl::execute_web_fetch(app, parameters).await
+                } else {
+                    Err(anyhow!("App handle not available for web fetch"))
+                }
+            }
             "api_upload" => {
                 if let Some(ref app) = self.app_handle {
                     api_tools_impl::execute_api_upload(app, parameters).await
@@ -863,6 +1651,64 @@ impl AGIExecutor {
                     json!({ "success": true, "note": "Requires LLM router access for code analysis" }),
                 )
             }
+            "code_impact_analysis" => {
+                let path = parameters
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing path parameter"))?;
+
+                if let Some(ref app) = self.app_handle {
+                    use tauri::Manager;
+
+                    let graph_state = app.state::<crate::codebase::DependencyGraphState>();
+                    let graph = graph_state.0.read();
+                    let graph = graph.as_ref().ok_or_else(|| {
+                        anyhow!("Workspace not indexed; run workspace indexing first")
+                    })?;
+                    Ok(serde_json::to_value(
+                        graph.impact(std::path::Path::new(path)),
+                    )?)
+                } else {
+                    Err(anyhow!("App handle not available for impact analysis"))
+                }
+            }
+            "dependency_scan" => {
+                let path = parameters
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing path parameter"))?
+                    .to_string();
+
+                let report = tokio::task::spawn_blocking(move || {
+                    crate::codebase::dependency_scan::scan_workspace(
+                        std::path::Path::new(&path),
+                        &Default::default(),
+                    )
+                })
+                .await??;
+                Ok(serde_json::to_value(report)?)
+            }
+            "project_detect_tasks" | "project_run_task" => {
+                if let Some(ref app) = self.app_handle {
+                    crate::codebase::project_tasks::execute_tool(app, tool_name, parameters).await
+                } else {
+                    Err(anyhow!("App handle not available for project tasks"))
+                }
+            }
+            "slack_reply" | "slack_react" | "slack_open_thread" => {
+                if let Some(ref app) = self.app_handle {
+                    crate::messaging::slack_events::execute_tool(app, tool_name, parameters).await
+                } else {
+                    Err(anyhow!("App handle not available for Slack"))
+                }
+            }
+            "messaging_send" | "messaging_list_threads" | "messaging_poll" => {
+                if let Some(ref app) = self.app_handle {
+                    crate::messaging::manager::execute_tool(app, tool_name, parameters).await
+                } else {
+                    Err(anyhow!("App handle not available for messaging"))
+                }
+            }
             "llm_reason" => {
                 let prompt = parameters
                     .get("prompt")
@@ -896,13 +1742,14 @@ impl AGIExecutor {
                     stream: false,
                     tools: None,
                     tool_choice: None,
+                    response_format: None,
                 };
 
                 let router = self.router.lock().await;
                 let candidates = router.candidates(&request, &preferences);
 
                 if !candidates.is_empty() {
-                    match router.invoke_candidate(&candidates[0], &request).await {
+                    match router.invoke_with_failover(&candidates, &request).await {
                         Ok(outcome) => {
                             drop(router);
                             Ok(json!({
@@ -969,22 +1816,20 @@ impl AGIExecutor {
                         body_text: Some(body.to_string()),
                         body_html: None,
                         attachments: vec![],
+                        send_at: None,
                     };
 
-                    // Send via email_send command
-                    use crate::commands::email::email_send;
-                    let message_id = email_send(app.clone(), send_request)
-                        .await
+                    // Queue through the outbox, which delivers and retries. The
+                    // tool call itself is audited by `execute_tool`.
+                    let queued = crate::commands::email::queue_email(app, &send_request)
                         .map_err(|e| anyhow!("Email send failed: {}", e))?;
 
-                    tracing::info!(
-                        "[Executor] Email sent successfully: message_id={}",
-                        message_id
-                    );
+                    tracing::info!("[Executor] Email queued: outbox_id={}", queued.id);
 
                     Ok(json!({
                         "success": true,
-                        "message_id": message_id,
+                        "outbox_id": queued.id,
+                        "status": queued.status,
                         "to": to,
                         "subject": subject,
                         "from": account.email
@@ -1384,10 +2229,15 @@ impl AGIExecutor {
                         .unwrap_or_default();
 
                     // Create task via ProductivityState
-                    let manager_arc = productivity_state.manager();
-                    let manager = manager_arc.lock().await;
+                    let manager = productivity_state.manager();
+                    let account = manager
+                        .resolve_account(
+                            &provider,
+                            parameters.get("account_id").and_then(|v| v.as_str()),
+                        )
+                        .map_err(|e| anyhow!("Failed to create productivity task: {}", e))?;
                     let task_id = manager
-                        .create_task(provider, task)
+                        .create_task(&account.account_id, task)
                         .await
                         .map_err(|e| {
                             anyhow!("Failed to create productivity task: {}. Ensure the provider account is connected via productivity_connect.", e)
@@ -1403,6 +2253,7 @@ impl AGIExecutor {
                     Ok(json!({
                         "success": true,
                         "task_id": task_id,
+                        "account_id": account.account_id,
                         "provider": provider_str,
                         "title": title
                     }))
@@ -1624,6 +2475,12 @@ impl AGIExecutor {
                     Err(anyhow!("App handle not available for transaction rollback"))
                 }
             }
+            tool if crate::orchestration::blackboard::BLACKBOARD_TOOLS.contains(&tool) => {
+                crate::orchestration::blackboard::run_tool(tool, parameters, &_context.goal.id)
+                    .await
+                    .map_err(|e| anyhow!(e))
+            }
+            super::skills::RUN_SKILL_TOOL => self.run_skill(parameters, _context).await,
             _ => Err(anyhow!("Unknown tool: {}", tool_name)),
         };
 
@@ -1674,6 +2531,7 @@ impl AGIExecutor {
         plans: Vec<planner::Plan>,
         sandbox_manager: &crate::agi::SandboxManager,
         goal: &Goal,
+        permissions: &RunPermissions,
     ) -> Result<Vec<crate::agi::ExecutionResult>> {
         use tokio::time::Instant;
 
@@ -1694,6 +2552,7 @@ impl AGIExecutor {
             let sandbox_id = sandbox.id.clone();
             let plan_id = plan.goal_id.clone();
             let goal_clone = goal.clone();
+            let permissions = permissions.clone();
 
             let handle = tokio::spawn(async move {
                 let start_time = Instant::now();
@@ -1710,6 +2569,7 @@ impl AGIExecutor {
                     },
                     tool_results: Vec::new(),
                     context_memory: Vec::new(),
+                    permissions,
                 };
 
                 // Create executor with shared cache
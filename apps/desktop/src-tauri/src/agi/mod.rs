@@ -14,7 +14,10 @@ pub mod process_ontology;
 pub mod process_reasoning;
 pub mod resources;
 pub mod sandbox;
+pub mod skills;
 pub mod templates;
+pub mod tool_costs;
+pub mod tool_reliability;
 pub mod tools;
 
 #[cfg(test)]
@@ -37,10 +40,15 @@ pub use process_ontology::{ProcessOntology, ProcessTemplate};
 pub use process_reasoning::{Outcome, OutcomeScore, ProcessReasoning, ProcessType, Strategy};
 pub use resources::ResourceManager;
 pub use sandbox::{Sandbox, SandboxManager};
+pub use skills::{global_skill_library, Skill, SkillLibrary, SkillMatch, SkillParameter};
 pub use templates::{
     get_builtin_templates, AgentTemplate, DifficultyLevel, TemplateCategory, TemplateManager,
     WorkflowDefinition, WorkflowStep,
 };
+pub use tool_costs::{
+    global_tool_costs, ToolCostBreakdown, ToolCostLedger, ToolCostModel, ToolPricing, ToolUsage,
+};
+pub use tool_reliability::{global_tool_reliability, ToolReliabilityStats, ToolReliabilityTracker};
 pub use tools::{Tool, ToolCapability, ToolRegistry, ToolResult};
 
 use serde::{Deserialize, Serialize};
@@ -133,6 +141,9 @@ pub struct ExecutionContext {
     pub available_resources: ResourceState,
     pub tool_results: Vec<ToolExecutionResult>,
     pub context_memory: Vec<ContextEntry>,
+    /// Permission profile the run's tool calls are checked against
+    #[serde(default)]
+    pub permissions: crate::permissions::RunPermissions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,5 +1,6 @@
 use super::*;
 use crate::automation::AutomationService;
+use crate::orchestration::blackboard::blackboards;
 use crate::router::LLMRouter;
 use anyhow::{anyhow, Result};
 use parking_lot::RwLock;
@@ -263,19 +264,32 @@ impl AgentOrchestrator {
             goal_id
         );
 
+        self.refresh_tray(&agents);
         Ok(agent_id)
     }
 
     /// Spawn multiple agents in parallel
     pub async fn spawn_parallel(&self, goals: Vec<Goal>) -> Result<Vec<String>> {
-        let mut agent_ids = Vec::new();
+        Ok(self.spawn_run(goals).await?.1)
+    }
+
+    /// Spawn agents for one run that share a blackboard. Returns the run id
+    /// and the agent ids.
+    pub async fn spawn_run(&self, goals: Vec<Goal>) -> Result<(String, Vec<String>)> {
+        let run_id = format!("run_{}", &Uuid::new_v4().to_string()[..8]);
+        blackboards().open(&run_id, self.app_handle.clone());
+        // Joined before spawning, since an agent starts working immediately
+        for goal in &goals {
+            blackboards().join(&run_id, &goal.id);
+        }
 
+        let mut agent_ids = Vec::new();
         for goal in goals {
             let agent_id = self.spawn_agent(goal).await?;
             agent_ids.push(agent_id);
         }
 
-        Ok(agent_ids)
+        Ok((run_id, agent_ids))
     }
 
     /// Get status of a specific agent
@@ -339,6 +353,7 @@ impl AgentOrchestrator {
 
             // Stop the agent's core
             agent.core.stop();
+            self.release_browser(&agent.goal.id);
 
             // Update status
             agent.status.status = AgentState::Failed;
@@ -360,6 +375,7 @@ impl AgentOrchestrator {
                 );
             }
 
+            self.refresh_tray(&agents);
             Ok(())
         } else {
             Err(anyhow!("Agent {} not found", id))
@@ -398,6 +414,7 @@ impl AgentOrchestrator {
                 );
             }
 
+            self.refresh_tray(&agents);
             Ok(())
         } else {
             Err(anyhow!("Agent {} not found", id))
@@ -436,12 +453,57 @@ impl AgentOrchestrator {
                 );
             }
 
+            self.refresh_tray(&agents);
             Ok(())
         } else {
             Err(anyhow!("Agent {} not found", id))
         }
     }
 
+    /// Pause every running agent; returns how many were paused
+    pub async fn pause_all_agents(&self) -> usize {
+        let mut paused = 0;
+        for id in self.agent_ids_in(AgentState::Running).await {
+            match self.pause_agent(&id).await {
+                Ok(()) => paused += 1,
+                Err(e) => tracing::warn!("[Orchestrator] Failed to pause agent {}: {}", id, e),
+            }
+        }
+        paused
+    }
+
+    /// Resume every paused agent; returns how many were resumed
+    pub async fn resume_all_agents(&self) -> usize {
+        let mut resumed = 0;
+        for id in self.agent_ids_in(AgentState::Paused).await {
+            match self.resume_agent(&id).await {
+                Ok(()) => resumed += 1,
+                Err(e) => tracing::warn!("[Orchestrator] Failed to resume agent {}: {}", id, e),
+            }
+        }
+        resumed
+    }
+
+    async fn agent_ids_in(&self, state: AgentState) -> Vec<String> {
+        let agents = self.agents.lock().await;
+        agents
+            .iter()
+            .filter(|(_, agent)| agent.status.status == state)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Report the number of running agents to the system tray
+    fn refresh_tray(&self, agents: &HashMap<String, AgentInstance>) {
+        if let Some(ref app) = self.app_handle {
+            let running = agents
+                .values()
+                .filter(|agent| agent.status.status == AgentState::Running)
+                .count();
+            crate::tray::update_tray(app, |status| status.running_agents = running);
+        }
+    }
+
     /// Cancel all agents
     pub async fn cancel_all_agents(&self) -> Result<()> {
         let agent_ids: Vec<String> = {
@@ -493,7 +555,9 @@ impl AgentOrchestrator {
 
                         // Remove completed agent
                         let mut agents = self.agents.lock().await;
-                        agents.remove(agent_id);
+                        if let Some(agent) = agents.remove(agent_id) {
+                            self.release_browser(&agent.goal.id);
+                        }
                     }
                 }
             }
@@ -515,6 +579,27 @@ impl AgentOrchestrator {
         self.knowledge_base.clone()
     }
 
+    /// Close the agent's pooled browser context, if it has one
+    fn release_browser(&self, goal_id: &str) {
+        use crate::browser::BrowserPool;
+        use tauri::Manager;
+
+        let Some(pool) = self
+            .app_handle
+            .as_ref()
+            .and_then(|app| app.try_state::<Arc<BrowserPool>>())
+        else {
+            return;
+        };
+        let pool = Arc::clone(pool.inner());
+        let goal_id = goal_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = pool.release(&goal_id).await {
+                tracing::warn!("[Orchestrator] Failed to release browser context: {}", e);
+            }
+        });
+    }
+
     /// Cleanup completed agents
     pub async fn cleanup_completed(&self) -> Result<usize> {
         let mut agents = self.agents.lock().await;
@@ -527,6 +612,7 @@ impl AgentOrchestrator {
                 if agent.status.status == AgentState::Completed
                     || agent.status.status == AgentState::Failed
                 {
+                    self.release_browser(&agent.goal.id);
                     agents.remove(&agent_id);
                     removed += 1;
                 }
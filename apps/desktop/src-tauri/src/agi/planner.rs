@@ -2,8 +2,11 @@ use super::*;
 use crate::agi::knowledge::KnowledgeEntry;
 use crate::agi::process_ontology::ProcessOntology;
 use crate::agi::process_reasoning::ProcessReasoning;
-use crate::router::{ChatMessage, LLMRequest, LLMRouter, RouterPreferences, RoutingStrategy};
+use crate::router::{
+    ChatMessage, LLMRequest, LLMRouter, ResponseFormat, RouterPreferences, RoutingStrategy,
+};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
 use std::time::Duration;
@@ -26,7 +29,7 @@ pub struct Plan {
     pub estimated_resources: ResourceUsage,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanStep {
     pub id: String,
     pub tool_id: String,
@@ -99,12 +102,36 @@ impl AGIPlanner {
         // Get relevant knowledge
         let knowledge = self.knowledge_base.get_relevant_knowledge(goal, 10).await?;
 
-        // Suggest tools
-        let suggested_tools: Vec<_> = self.tool_registry.suggest_tools(&goal.description);
+        // Suggest tools, demoting ones that keep failing on this machine
+        let mut suggested_tools: Vec<_> = self.tool_registry.suggest_tools(&goal.description);
+        rank_by_reliability(&mut suggested_tools);
+
+        // Saved skills replace whole sequences of steps with one
+        let skills = match super::skills::global_skill_library()
+            .search(&goal.description, 3)
+            .await
+        {
+            Ok(matches) => matches
+                .into_iter()
+                .filter(|m| m.score >= super::skills::MIN_PLANNER_SIMILARITY)
+                .map(|m| m.skill)
+                .collect(),
+            Err(e) => {
+                tracing::debug!("[Planner] No skills offered: {}", e);
+                Vec::new()
+            }
+        };
 
         // Use LLM to create plan with process-aware context
         let plan_json = self
-            .plan_with_llm(goal, context, &knowledge, &suggested_tools, &best_practices)
+            .plan_with_llm(
+                goal,
+                context,
+                &knowledge,
+                &suggested_tools,
+                &best_practices,
+                &skills,
+            )
             .await?;
 
         // Parse plan
@@ -118,6 +145,7 @@ impl AGIPlanner {
         knowledge: &[KnowledgeEntry],
         tools: &[Tool],
         best_practices: &[String],
+        skills: &[super::skills::Skill],
     ) -> Result<String> {
         let knowledge_summary: Vec<String> = knowledge
             .iter()
@@ -125,9 +153,13 @@ impl AGIPlanner {
             .take(5)
             .collect();
 
+        let reliability = super::tool_reliability::global_tool_reliability();
         let tools_summary: Vec<String> = tools
             .iter()
-            .map(|t| format!("- {}: {}", t.id, t.description))
+            .map(|t| match reliability.planner_hint(&t.id) {
+                Some(hint) => format!("- {}: {} ({})", t.id, t.description, hint),
+                None => format!("- {}: {}", t.id, t.description),
+            })
             .take(10)
             .collect();
 
@@ -144,6 +176,20 @@ impl AGIPlanner {
             String::new()
         };
 
+        let skills_section = if !skills.is_empty() {
+            format!(
+                "\nSaved Skills (a proven sequence of steps; use one as a single step with tool_id \"{}\" and parameters {{ \"skill_id\": ..., \"args\": {{ ... }} }} instead of planning those steps):\n{}\n",
+                super::skills::RUN_SKILL_TOOL,
+                skills
+                    .iter()
+                    .map(|skill| skill.planner_summary())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        } else {
+            String::new()
+        };
+
         let prompt = format!(
             r#"You are an AGI (Artificial General Intelligence) planning system. Create a detailed execution plan to achieve the following goal.
 
@@ -153,7 +199,7 @@ Success Criteria: {}
 
 Available Tools:
 {}
-
+{}
 Relevant Knowledge:
 {}
 {}
@@ -162,6 +208,8 @@ Current Context:
 - Memory Usage: {}MB
 - Previous Steps: {}
 
+Tool reliability figures reflect recent results on this machine. When several tools or targeting methods can accomplish a step, prefer the more reliable one (for example, target UI elements by element_id or text rather than raw coordinates when those score higher).
+
 Create a step-by-step plan. For each step, specify:
 1. Tool ID to use
 2. Parameters for the tool
@@ -202,6 +250,7 @@ Return ONLY the JSON array."#,
             goal.priority,
             goal.success_criteria.join(", "),
             tools_summary.join("\n"),
+            skills_section,
             knowledge_summary.join("\n"),
             best_practices_section,
             context.available_resources.cpu_usage_percent,
@@ -232,6 +281,11 @@ Return ONLY the JSON array."#,
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: Some(ResponseFormat::JsonSchema {
+                name: "execution_plan".to_string(),
+                schema: plan_schema(),
+                strict: false,
+            }),
         };
 
         let router = self.router.lock().await;
@@ -240,7 +294,7 @@ Return ONLY the JSON array."#,
 
         if !candidates.is_empty() {
             let router = self.router.lock().await;
-            if let Ok(outcome) = router.invoke_candidate(&candidates[0], &request).await {
+            if let Ok(outcome) = router.invoke_with_failover(&candidates, &request).await {
                 return Ok(outcome.response.content);
             }
         }
@@ -328,14 +382,16 @@ Return ONLY the JSON array."#,
             let tool_duration = match step.tool_id.as_str() {
                 "file_read" | "file_write" | "file_list" => 2,
                 "ui_click" | "ui_type" | "ui_screenshot" => 3,
-                "browser_navigate" | "browser_click" | "browser_extract" => 5,
+                "browser_navigate" | "browser_click" | "browser_type" | "browser_select"
+                | "browser_check" | "browser_press_key" | "browser_extract" => 5,
                 "code_execute" | "code_analyze" => 10,
                 "db_query"
                 | "db_execute"
                 | "db_transaction_begin"
                 | "db_transaction_commit"
                 | "db_transaction_rollback" => 8,
-                "api_call" | "api_upload" | "api_download" => 6,
+                "api_call" | "api_upload" | "api_download" | "web_fetch" => 6,
+                "browser_wait_for_download" => 30,
                 "document_read" | "document_search" | "image_ocr" => 7,
                 "llm_reason" => 15, // LLM calls are typically slower
                 _ => 5,             // default for unknown tools
@@ -624,6 +680,7 @@ Your response:"#,
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let router = self.router.lock().await;
@@ -632,7 +689,7 @@ Your response:"#,
 
         if !candidates.is_empty() {
             let router = self.router.lock().await;
-            if let Ok(outcome) = router.invoke_candidate(&candidates[0], &request).await {
+            if let Ok(outcome) = router.invoke_with_failover(&candidates, &request).await {
                 let response = outcome.response.content.trim().to_lowercase();
                 tracing::debug!("[Planner] LLM evaluation response: {}", response);
 
@@ -798,6 +855,7 @@ Return ONLY a JSON array of steps with this structure:
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let router = self.router.lock().await;
@@ -806,7 +864,7 @@ Return ONLY a JSON array of steps with this structure:
 
         if !candidates.is_empty() {
             let router = self.router.lock().await;
-            if let Ok(outcome) = router.invoke_candidate(&candidates[0], &request).await {
+            if let Ok(outcome) = router.invoke_with_failover(&candidates, &request).await {
                 return Ok(outcome.response.content);
             }
         }
@@ -814,3 +872,56 @@ Return ONLY a JSON array of steps with this structure:
         self.generate_basic_plan(goal, tools, &[]).await
     }
 }
+
+/// Below this score a tool is moved behind the other suggestions
+const UNRELIABLE_THRESHOLD: f64 = 0.5;
+
+/// Stable reorder that keeps relevance order but pushes tools with a poor
+/// track record to the end, least reliable last
+fn rank_by_reliability(tools: &mut [Tool]) {
+    let reliability = super::tool_reliability::global_tool_reliability();
+    let scores: HashMap<String, f64> = tools
+        .iter()
+        .filter_map(|t| reliability.score(&t.id).map(|s| (t.id.clone(), s)))
+        .collect();
+
+    tools.sort_by(|a, b| {
+        let score_a = scores.get(&a.id).copied().unwrap_or(1.0);
+        let score_b = scores.get(&b.id).copied().unwrap_or(1.0);
+        match (
+            score_a < UNRELIABLE_THRESHOLD,
+            score_b < UNRELIABLE_THRESHOLD,
+        ) {
+            (false, false) => std::cmp::Ordering::Equal,
+            (true, false) => std::cmp::Ordering::Greater,
+            (false, true) => std::cmp::Ordering::Less,
+            (true, true) => score_b.total_cmp(&score_a),
+        }
+    });
+}
+
+/// Shape of the plan requested in `plan_with_llm`
+fn plan_schema() -> serde_json::Value {
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "tool_id": { "type": "string" },
+                "description": { "type": "string" },
+                "parameters": { "type": "object" },
+                "estimated_resources": {
+                    "type": "object",
+                    "properties": {
+                        "cpu_percent": { "type": "number" },
+                        "memory_mb": { "type": "number" },
+                        "network_mb": { "type": "number" }
+                    }
+                },
+                "dependencies": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["id", "tool_id", "description", "parameters"]
+        }
+    })
+}
@@ -365,6 +365,7 @@ Return ONLY the process type name (e.g., "code_review", "customer_support", etc.
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let router = self.router.lock().await;
@@ -373,7 +374,7 @@ Return ONLY the process type name (e.g., "code_review", "customer_support", etc.
 
         if !candidates.is_empty() {
             let router = self.router.lock().await;
-            if let Ok(outcome) = router.invoke_candidate(&candidates[0], &request).await {
+            if let Ok(outcome) = router.invoke_with_failover(&candidates, &request).await {
                 let response = outcome.response.content.trim();
                 if let Some(process_type) = ProcessType::from_str(response) {
                     return Ok(process_type);
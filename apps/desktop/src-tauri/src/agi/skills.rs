@@ -0,0 +1,756 @@
+//! Skill library
+//!
+//! A skill is a named, parameterized sequence of plan steps saved from a
+//! successful run, e.g. "export Salesforce report". Skills are embedded so
+//! the planner can find the ones relevant to a goal and use each as a single
+//! `run_skill` step instead of planning the whole sequence again.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::agent::runtime::RunTrace;
+use crate::agi::planner::PlanStep;
+use crate::db::pool::Pool;
+use crate::embeddings::{cosine_similarity, EmbeddingConfig, EmbeddingGenerator, Vector};
+
+/// Tool that runs a saved skill as one step
+pub const RUN_SKILL_TOOL: &str = "run_skill";
+/// Skills below this similarity to the goal are not offered to the planner
+pub const MIN_PLANNER_SIMILARITY: f32 = 0.55;
+/// How long to stop asking for embeddings after the generator fails
+const GENERATOR_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Used when a run leaves the parameter out; required when unset
+    #[serde(default)]
+    pub default: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Skill {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Vec<SkillParameter>,
+    /// Steps whose parameters refer to skill parameters as `{{name}}`
+    pub steps: Vec<PlanStep>,
+    /// What must be true before the skill runs, e.g. "Salesforce is open"
+    #[serde(default)]
+    pub preconditions: Vec<String>,
+    /// Integrations that must be connected, e.g. "salesforce"
+    #[serde(default)]
+    pub integrations: Vec<String>,
+    /// Run the skill was saved from
+    #[serde(default)]
+    pub source_run: Option<String>,
+    #[serde(default)]
+    pub use_count: u32,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillMatch {
+    pub skill: Skill,
+    /// Cosine similarity, or keyword overlap when embeddings are unavailable
+    pub score: f32,
+}
+
+impl Skill {
+    /// A skill from the steps of `trace` that succeeded. Each parameter's
+    /// default is the value the run used; where it appears in a step it is
+    /// replaced by the parameter.
+    pub fn from_run(
+        trace: &RunTrace,
+        name: String,
+        description: String,
+        parameters: Vec<SkillParameter>,
+    ) -> Result<Self> {
+        let planned: Vec<PlanStep> = trace
+            .plan_steps()
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()?
+            .ok_or_else(|| anyhow!("Run {} has no recorded plan", trace.run.run_id))?;
+        let outcomes = trace.step_outcomes();
+        let mut steps: Vec<PlanStep> = planned
+            .into_iter()
+            .filter(|step| matches!(outcomes.get(&step.id), Some(Ok(_))))
+            .filter(|step| step.tool_id != RUN_SKILL_TOOL)
+            .collect();
+        if steps.is_empty() {
+            return Err(anyhow!(
+                "Run {} has no successful steps to save",
+                trace.run.run_id
+            ));
+        }
+
+        for parameter in &parameters {
+            let Some(Value::String(literal)) = &parameter.default else {
+                continue;
+            };
+            if literal.is_empty() {
+                continue;
+            }
+            let placeholder = format!("{{{{{}}}}}", parameter.name);
+            for step in &mut steps {
+                for value in step.parameters.values_mut() {
+                    replace_literal(value, literal, &placeholder);
+                }
+            }
+        }
+
+        let now = Utc::now();
+        Ok(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            description,
+            parameters,
+            steps,
+            preconditions: Vec::new(),
+            integrations: Vec::new(),
+            source_run: Some(trace.run.run_id.clone()),
+            use_count: 0,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Skill needs a name"));
+        }
+        if self.steps.is_empty() {
+            return Err(anyhow!("Skill '{}' has no steps", self.name));
+        }
+        if self.steps.iter().any(|step| step.tool_id == RUN_SKILL_TOOL) {
+            return Err(anyhow!("Skills cannot run other skills"));
+        }
+        let mut seen = Vec::new();
+        for parameter in &self.parameters {
+            if parameter.name.trim().is_empty() || seen.contains(&parameter.name.as_str()) {
+                return Err(anyhow!(
+                    "Skill parameter names must be unique and not empty"
+                ));
+            }
+            seen.push(&parameter.name);
+        }
+        Ok(())
+    }
+
+    /// The skill's steps with `args` (or parameter defaults) filled in
+    pub fn instantiate(&self, args: &serde_json::Map<String, Value>) -> Result<Vec<PlanStep>> {
+        let mut values = HashMap::new();
+        for parameter in &self.parameters {
+            let value = args
+                .get(&parameter.name)
+                .or(parameter.default.as_ref())
+                .ok_or_else(|| {
+                    anyhow!("Skill '{}' needs parameter '{}'", self.name, parameter.name)
+                })?;
+            values.insert(parameter.name.as_str(), value);
+        }
+
+        let mut steps = self.steps.clone();
+        for step in &mut steps {
+            for value in step.parameters.values_mut() {
+                fill_placeholders(value, &values);
+            }
+        }
+        Ok(steps)
+    }
+
+    /// One line per skill for the planner prompt
+    pub fn planner_summary(&self) -> String {
+        let mut summary = format!("- {} \"{}\": {}", self.id, self.name, self.description);
+        if !self.parameters.is_empty() {
+            let parameters: Vec<String> = self
+                .parameters
+                .iter()
+                .map(|p| match &p.default {
+                    Some(default) => format!("{} (default {})", p.name, default),
+                    None => format!("{} (required)", p.name),
+                })
+                .collect();
+            summary.push_str(&format!(" Parameters: {}.", parameters.join(", ")));
+        }
+        if !self.preconditions.is_empty() {
+            summary.push_str(&format!(
+                " Preconditions: {}.",
+                self.preconditions.join("; ")
+            ));
+        }
+        if !self.integrations.is_empty() {
+            summary.push_str(&format!(" Needs: {}.", self.integrations.join(", ")));
+        }
+        summary
+    }
+
+    fn search_text(&self) -> String {
+        let mut text = format!("{}\n{}", self.name, self.description);
+        for precondition in &self.preconditions {
+            text.push('\n');
+            text.push_str(precondition);
+        }
+        text
+    }
+}
+
+fn replace_literal(value: &mut Value, literal: &str, placeholder: &str) {
+    match value {
+        Value::String(text) if text.contains(literal) => {
+            *text = text.replace(literal, placeholder);
+        }
+        Value::Array(items) => {
+            for item in items {
+                replace_literal(item, literal, placeholder);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                replace_literal(item, literal, placeholder);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A string that is exactly `{{name}}` takes the argument as is, keeping
+/// its JSON type; placeholders inside longer strings are spliced in as text
+fn fill_placeholders(value: &mut Value, args: &HashMap<&str, &Value>) {
+    match value {
+        Value::String(text) => {
+            if let Some(arg) = text
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .and_then(|name| args.get(name))
+            {
+                *value = (*arg).clone();
+                return;
+            }
+            for (name, arg) in args {
+                let placeholder = format!("{{{{{}}}}}", name);
+                if text.contains(&placeholder) {
+                    let arg = match arg {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    *text = text.replace(&placeholder, &arg);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                fill_placeholders(item, args);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                fill_placeholders(item, args);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn save_skill(
+    conn: &Connection,
+    skill: &Skill,
+    embedding: Option<(&str, &[f32])>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO agent_skills
+            (id, name, description, definition, embedding, embedding_model, use_count,
+             created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            description = excluded.description,
+            definition = excluded.definition,
+            embedding = excluded.embedding,
+            embedding_model = excluded.embedding_model,
+            updated_at = excluded.updated_at",
+        params![
+            skill.id,
+            skill.name,
+            skill.description,
+            serde_json::to_string(skill)?,
+            embedding.map(|(_, vector)| encode_vector(vector)),
+            embedding.map(|(model, _)| model),
+            skill.use_count,
+            skill.created_at.timestamp_millis(),
+            skill.updated_at.timestamp_millis(),
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(ref failure, _)
+            if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            anyhow!("A skill named '{}' already exists", skill.name)
+        }
+        e => e.into(),
+    })?;
+    Ok(())
+}
+
+fn skill_from_row(definition: String, use_count: u32) -> Result<Skill> {
+    let mut skill: Skill = serde_json::from_str(&definition)?;
+    skill.use_count = use_count;
+    Ok(skill)
+}
+
+pub fn load_skill(conn: &Connection, id: &str) -> Result<Option<Skill>> {
+    conn.query_row(
+        "SELECT definition, use_count FROM agent_skills WHERE id = ?1 OR name = ?1",
+        [id],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)),
+    )
+    .optional()?
+    .map(|(definition, use_count)| skill_from_row(definition, use_count))
+    .transpose()
+}
+
+/// Every skill, most used first
+pub fn list_skills(conn: &Connection) -> Result<Vec<Skill>> {
+    let mut stmt = conn
+        .prepare("SELECT definition, use_count FROM agent_skills ORDER BY use_count DESC, name")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    rows.into_iter()
+        .map(|(definition, use_count)| skill_from_row(definition, use_count))
+        .collect()
+}
+
+pub fn delete_skill(conn: &Connection, id: &str) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM agent_skills WHERE id = ?1", [id])? > 0)
+}
+
+/// Skills closest to `query`. Skills embedded with `query_vector`'s model
+/// are ranked by cosine similarity, the rest by keyword overlap.
+pub fn rank_skills(
+    conn: &Connection,
+    query: &str,
+    query_vector: Option<(&str, &[f32])>,
+    limit: usize,
+) -> Result<Vec<SkillMatch>> {
+    let mut stmt =
+        conn.prepare("SELECT definition, use_count, embedding, embedding_model FROM agent_skills")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, Option<Vec<u8>>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut matches = Vec::new();
+    for (definition, use_count, embedding, model) in rows {
+        let skill = skill_from_row(definition, use_count)?;
+        let score = match (query_vector, embedding, model) {
+            (Some((query_model, vector)), Some(bytes), Some(model)) if model == query_model => {
+                cosine_similarity(vector, &decode_vector(&bytes))
+            }
+            _ => keyword_score(query, &skill.search_text()),
+        };
+        if score > 0.0 {
+            matches.push(SkillMatch { skill, score });
+        }
+    }
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Share of the query's words found in `text`
+fn keyword_score(query: &str, text: &str) -> f32 {
+    let text = text.to_lowercase();
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let found = words
+        .iter()
+        .filter(|word| text.contains(word.as_str()))
+        .count();
+    found as f32 / words.len() as f32
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Saved skills, shared by the planner, the executor and commands
+pub struct SkillLibrary {
+    pool: RwLock<Option<Pool>>,
+    generator: tokio::sync::Mutex<Option<Arc<EmbeddingGenerator>>>,
+    unavailable_until: Mutex<Option<Instant>>,
+}
+
+impl Default for SkillLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkillLibrary {
+    pub fn new() -> Self {
+        Self {
+            pool: RwLock::new(None),
+            generator: tokio::sync::Mutex::new(None),
+            unavailable_until: Mutex::new(None),
+        }
+    }
+
+    pub fn attach_database(&self, pool: Pool) -> Result<()> {
+        pool.get()?
+            .query_row("SELECT COUNT(*) FROM agent_skills", [], |row| {
+                row.get::<_, i64>(0)
+            })?;
+        *self.pool.write() = Some(pool);
+        Ok(())
+    }
+
+    fn pool(&self) -> Result<Pool> {
+        self.pool
+            .read()
+            .clone()
+            .ok_or_else(|| anyhow!("Skill library is not ready yet"))
+    }
+
+    /// Save a new skill or replace one with the same id
+    pub async fn save(&self, mut skill: Skill) -> Result<Skill> {
+        skill.validate()?;
+        skill.updated_at = Utc::now();
+        let embedding = self.embed(&skill.search_text()).await;
+        let pool = self.pool()?;
+        let saved = skill.clone();
+        pool.run(move |conn| {
+            save_skill(
+                conn,
+                &saved,
+                embedding
+                    .as_ref()
+                    .map(|(model, vector)| (model.as_str(), vector.as_slice())),
+            )
+        })
+        .await?;
+        Ok(skill)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Skill>> {
+        let id = id.to_string();
+        self.pool()?.run(move |conn| load_skill(conn, &id)).await
+    }
+
+    pub async fn list(&self) -> Result<Vec<Skill>> {
+        self.pool()?.run(|conn| list_skills(conn)).await
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let id = id.to_string();
+        self.pool()?.run(move |conn| delete_skill(conn, &id)).await
+    }
+
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SkillMatch>> {
+        let pool = self.pool()?;
+        let embedding = self.embed(query).await;
+        let query = query.to_string();
+        pool.run(move |conn| {
+            rank_skills(
+                conn,
+                &query,
+                embedding
+                    .as_ref()
+                    .map(|(model, vector)| (model.as_str(), vector.as_slice())),
+                limit,
+            )
+        })
+        .await
+    }
+
+    /// Count a run of the skill; failures are only logged
+    pub async fn record_use(&self, id: &str) {
+        let Ok(pool) = self.pool() else {
+            return;
+        };
+        let id = id.to_string();
+        let counted = pool
+            .run(move |conn| {
+                conn.execute(
+                    "UPDATE agent_skills SET use_count = use_count + 1 WHERE id = ?1",
+                    [&id],
+                )
+                .map_err(anyhow::Error::from)
+            })
+            .await;
+        if let Err(e) = counted {
+            tracing::warn!("[Skills] Failed to count skill use: {}", e);
+        }
+    }
+
+    /// The text's embedding and model id, or `None` while the embedding
+    /// backend is unavailable
+    async fn embed(&self, text: &str) -> Option<(String, Vector)> {
+        if self
+            .unavailable_until
+            .lock()
+            .is_some_and(|until| Instant::now() < until)
+        {
+            return None;
+        }
+
+        let generator = {
+            let mut slot = self.generator.lock().await;
+            if slot.is_none() {
+                let config = EmbeddingConfig {
+                    timeout: Duration::from_secs(5),
+                    ..EmbeddingConfig::default()
+                };
+                match EmbeddingGenerator::new(config).await {
+                    Ok(generator) => *slot = Some(Arc::new(generator)),
+                    Err(e) => {
+                        self.mark_unavailable(&e);
+                        return None;
+                    }
+                }
+            }
+            slot.clone()?
+        };
+
+        match generator.generate(text).await {
+            Ok(vector) => Some((generator.model().id().to_string(), vector)),
+            Err(e) => {
+                self.mark_unavailable(&e);
+                None
+            }
+        }
+    }
+
+    fn mark_unavailable(&self, error: &anyhow::Error) {
+        tracing::debug!(
+            "[Skills] Searching by keyword for {}s: {}",
+            GENERATOR_RETRY_AFTER.as_secs(),
+            error
+        );
+        *self.unavailable_until.lock() = Some(Instant::now() + GENERATOR_RETRY_AFTER);
+    }
+}
+
+static GLOBAL_SKILL_LIBRARY: Lazy<SkillLibrary> = Lazy::new(SkillLibrary::new);
+
+pub fn global_skill_library() -> &'static SkillLibrary {
+    &GLOBAL_SKILL_LIBRARY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::runtime::{RunStatus, RunSummary, TraceEntry, TraceEvent};
+    use crate::agi::ResourceUsage;
+    use serde_json::json;
+
+    fn step(id: &str, tool_id: &str, parameters: Value) -> PlanStep {
+        PlanStep {
+            id: id.to_string(),
+            tool_id: tool_id.to_string(),
+            description: format!("{} step", tool_id),
+            parameters: serde_json::from_value(parameters).unwrap(),
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 1,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        }
+    }
+
+    fn trace() -> RunTrace {
+        let steps = vec![
+            step(
+                "step_1",
+                "browser_navigate",
+                json!({ "url": "https://crm.example.com/reports/Q3" }),
+            ),
+            step("step_2", "browser_click", json!({ "selector": "#export" })),
+            step("step_3", "file_read", json!({ "path": "missing.csv" })),
+        ];
+        let entry = |seq, event| TraceEntry {
+            seq,
+            at: Utc::now(),
+            event,
+        };
+        let tool_call = |step_id: &str, error: Option<&str>| TraceEvent::ToolCall {
+            step_id: step_id.to_string(),
+            tool_name: String::new(),
+            input: Value::Null,
+            output: error.is_none().then_some(Value::Null),
+            error: error.map(str::to_string),
+            duration_ms: 1,
+            mocked: false,
+            screenshot: None,
+        };
+        RunTrace {
+            run: RunSummary {
+                run_id: "goal_1".to_string(),
+                goal: Value::Null,
+                replay_of: None,
+                status: RunStatus::Completed,
+                error: None,
+                started_at: Utc::now(),
+                finished_at: Some(Utc::now()),
+                budget: Default::default(),
+                usage: Default::default(),
+                budget_overruns: 0,
+            },
+            events: vec![
+                entry(
+                    1,
+                    TraceEvent::Plan {
+                        steps: serde_json::to_value(&steps).unwrap(),
+                    },
+                ),
+                entry(2, tool_call("step_1", None)),
+                entry(3, tool_call("step_2", None)),
+                entry(4, tool_call("step_3", Some("not found"))),
+            ],
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_skill_from_run_is_parameterized() {
+        let skill = Skill::from_run(
+            &trace(),
+            "Export CRM report".to_string(),
+            "Export a CRM report as CSV".to_string(),
+            vec![SkillParameter {
+                name: "report".to_string(),
+                description: None,
+                default: Some(json!("Q3")),
+            }],
+        )
+        .unwrap();
+        // The failed step is left out
+        assert_eq!(skill.steps.len(), 2);
+        assert_eq!(
+            skill.steps[0].parameters["url"],
+            json!("https://crm.example.com/reports/{{report}}")
+        );
+
+        let steps = skill
+            .instantiate(json!({ "report": "Q4" }).as_object().unwrap())
+            .unwrap();
+        assert_eq!(
+            steps[0].parameters["url"],
+            json!("https://crm.example.com/reports/Q4")
+        );
+        // Defaults fill parameters a run leaves out
+        let steps = skill.instantiate(&serde_json::Map::new()).unwrap();
+        assert_eq!(
+            steps[0].parameters["url"],
+            json!("https://crm.example.com/reports/Q3")
+        );
+    }
+
+    #[test]
+    fn test_whole_placeholder_keeps_argument_type() {
+        let mut skill = Skill::from_run(
+            &trace(),
+            "Open".to_string(),
+            "Open a page".to_string(),
+            vec![],
+        )
+        .unwrap();
+        skill.parameters.push(SkillParameter {
+            name: "limit".to_string(),
+            description: None,
+            default: None,
+        });
+        skill.steps[1]
+            .parameters
+            .insert("count".to_string(), json!("{{limit}}"));
+
+        assert!(skill.instantiate(&serde_json::Map::new()).is_err());
+        let steps = skill
+            .instantiate(json!({ "limit": 5 }).as_object().unwrap())
+            .unwrap();
+        assert_eq!(steps[1].parameters["count"], json!(5));
+    }
+
+    #[test]
+    fn test_rank_skills() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        let mut export = Skill::from_run(
+            &trace(),
+            "Export Salesforce report".to_string(),
+            "Download a report from Salesforce as CSV".to_string(),
+            vec![],
+        )
+        .unwrap();
+        export.integrations.push("salesforce".to_string());
+        save_skill(&conn, &export, Some(("model-a", &[1.0, 0.0]))).unwrap();
+        let invoice = Skill::from_run(
+            &trace(),
+            "Send invoice".to_string(),
+            "Email an invoice to a customer".to_string(),
+            vec![],
+        )
+        .unwrap();
+        save_skill(&conn, &invoice, None).unwrap();
+
+        let by_vector = rank_skills(&conn, "anything", Some(("model-a", &[0.9, 0.1])), 5).unwrap();
+        assert_eq!(by_vector[0].skill.id, export.id);
+        assert!(by_vector[0].score > 0.9);
+
+        let by_keyword = rank_skills(&conn, "send the invoice", None, 5).unwrap();
+        assert_eq!(by_keyword[0].skill.id, invoice.id);
+
+        let mut duplicate = invoice.clone();
+        duplicate.id = "other".to_string();
+        assert!(save_skill(&conn, &duplicate, None).is_err());
+        assert_eq!(
+            load_skill(&conn, "Send invoice").unwrap().unwrap().id,
+            invoice.id
+        );
+        assert!(delete_skill(&conn, &invoice.id).unwrap());
+        assert_eq!(list_skills(&conn).unwrap().len(), 1);
+    }
+}
@@ -1,3 +1,4 @@
+use super::requirements::TemplateRequirements;
 use super::template_manager::{
     AgentTemplate, DifficultyLevel, TemplateCategory, WorkflowDefinition, WorkflowStep,
 };
@@ -151,6 +152,11 @@ fn create_accounts_payable_agent() -> AgentTemplate {
     ])
     .with_estimated_duration(300000) // 5 minutes
     .with_difficulty(DifficultyLevel::Medium)
+    .with_requirements(TemplateRequirements {
+        integrations: vec!["email".to_string()],
+        tools: Vec::new(),
+        permissions: vec!["file:read".to_string(), "database:read".to_string()],
+    })
 }
 
 /// 2. Customer Support Agent
@@ -510,6 +516,11 @@ fn create_email_management_agent() -> AgentTemplate {
     ])
     .with_estimated_duration(600000) // 10 minutes
     .with_difficulty(DifficultyLevel::Easy)
+    .with_requirements(TemplateRequirements {
+        integrations: vec!["email".to_string()],
+        tools: Vec::new(),
+        permissions: Vec::new(),
+    })
 }
 
 /// 5. Social Media Agent
@@ -867,6 +878,11 @@ fn create_code_review_agent() -> AgentTemplate {
     ])
     .with_estimated_duration(300000) // 5 minutes
     .with_difficulty(DifficultyLevel::Hard)
+    .with_requirements(TemplateRequirements {
+        integrations: vec!["github".to_string()],
+        tools: Vec::new(),
+        permissions: vec!["terminal:execute".to_string()],
+    })
 }
 
 /// 8. Testing Agent
@@ -1197,6 +1213,11 @@ fn create_deployment_agent() -> AgentTemplate {
     ])
     .with_estimated_duration(600000) // 10 minutes
     .with_difficulty(DifficultyLevel::Hard)
+    .with_requirements(TemplateRequirements {
+        integrations: Vec::new(),
+        tools: Vec::new(),
+        permissions: vec!["terminal:execute".to_string()],
+    })
 }
 
 /// 11. Meeting Scheduler Agent
@@ -1320,6 +1341,11 @@ fn create_meeting_scheduler_agent() -> AgentTemplate {
     ])
     .with_estimated_duration(120000) // 2 minutes
     .with_difficulty(DifficultyLevel::Easy)
+    .with_requirements(TemplateRequirements {
+        integrations: vec!["calendar".to_string(), "email".to_string()],
+        tools: Vec::new(),
+        permissions: Vec::new(),
+    })
 }
 
 /// 12. Expense Report Agent
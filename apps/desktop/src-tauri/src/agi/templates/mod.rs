@@ -1,5 +1,7 @@
 pub mod builtin_templates;
+pub mod requirements;
 pub mod template_manager;
 
 pub use builtin_templates::*;
+pub use requirements::*;
 pub use template_manager::*;
@@ -0,0 +1,398 @@
+//! Template dependency declarations and checks.
+//!
+//! A template can declare the integrations, tools and permissions it needs.
+//! Installing or executing a template verifies them first, so a template
+//! whose integration is not connected fails up front with a setup hint
+//! instead of failing silently halfway through its workflow.
+
+use super::AgentTemplate;
+use crate::agi::tools::ToolRegistry;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateRequirements {
+    /// Connected accounts, e.g. `email`, `calendar`, `slack`, `github`
+    #[serde(default)]
+    pub integrations: Vec<String>,
+    /// Tool ids that must be available in addition to the template's own tools
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// RBAC permission names, e.g. `file:write`, `terminal:execute`
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl TemplateRequirements {
+    pub fn is_empty(&self) -> bool {
+        self.integrations.is_empty() && self.tools.is_empty() && self.permissions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequirementKind {
+    Integration,
+    Tool,
+    Permission,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingRequirement {
+    pub kind: RequirementKind,
+    pub name: String,
+    /// What the user can do to satisfy it
+    pub hint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementsReport {
+    pub template_id: String,
+    pub satisfied: bool,
+    pub missing: Vec<MissingRequirement>,
+}
+
+impl RequirementsReport {
+    /// One actionable message listing everything that is missing
+    pub fn error_message(&self, template_name: &str) -> String {
+        let lines: Vec<String> = self
+            .missing
+            .iter()
+            .map(|m| {
+                let kind = match m.kind {
+                    RequirementKind::Integration => "integration",
+                    RequirementKind::Tool => "tool",
+                    RequirementKind::Permission => "permission",
+                };
+                format!("- missing {} '{}': {}", kind, m.name, m.hint)
+            })
+            .collect();
+        format!(
+            "Template '{}' cannot run until its requirements are met:\n{}",
+            template_name,
+            lines.join("\n")
+        )
+    }
+}
+
+const MESSAGING_ACCOUNTS: &str = "SELECT id, COALESCE(workspace_name, platform)
+     FROM messaging_connections WHERE platform = ?1 AND is_active = 1";
+const OAUTH_ACCOUNTS: &str = "SELECT id, provider_user_id FROM oauth_providers WHERE provider = ?1";
+const CLOUD_ACCOUNTS: &str =
+    "SELECT id, COALESCE(label, provider) FROM cloud_accounts WHERE provider = ?1";
+
+/// A connected account that can satisfy an integration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrationAccount {
+    pub id: String,
+    pub label: String,
+}
+
+/// How to find an integration's connected accounts: a query for `(id,
+/// label)` rows with at most one parameter
+struct IntegrationCheck {
+    sql: &'static str,
+    param: Option<&'static str>,
+    hint: &'static str,
+}
+
+fn integration_check(name: &str) -> Option<IntegrationCheck> {
+    let (sql, param, hint) = match name {
+        "email" => (
+            "SELECT CAST(id AS TEXT), email FROM email_accounts",
+            None,
+            "Connect an email account in Settings > Integrations > Email.",
+        ),
+        "calendar" => (
+            "SELECT id, COALESCE(account_email, display_name, provider) FROM calendar_accounts",
+            None,
+            "Connect Google Calendar or Outlook in Settings > Integrations > Calendar.",
+        ),
+        "google_calendar" => (
+            "SELECT id, COALESCE(account_email, display_name, provider)
+             FROM calendar_accounts WHERE provider = ?1",
+            Some("google"),
+            "Connect Google Calendar in Settings > Integrations > Calendar.",
+        ),
+        "outlook_calendar" => (
+            "SELECT id, COALESCE(account_email, display_name, provider)
+             FROM calendar_accounts WHERE provider = ?1",
+            Some("outlook"),
+            "Connect Outlook Calendar in Settings > Integrations > Calendar.",
+        ),
+        "slack" => (
+            MESSAGING_ACCOUNTS,
+            Some("slack"),
+            "Connect a Slack workspace in Settings > Integrations > Messaging.",
+        ),
+        "teams" => (
+            MESSAGING_ACCOUNTS,
+            Some("teams"),
+            "Connect Microsoft Teams in Settings > Integrations > Messaging.",
+        ),
+        "whatsapp" => (
+            MESSAGING_ACCOUNTS,
+            Some("whatsapp"),
+            "Connect WhatsApp Business in Settings > Integrations > Messaging.",
+        ),
+        "github" => (
+            OAUTH_ACCOUNTS,
+            Some("github"),
+            "Sign in with GitHub in Settings > Account > Connected accounts.",
+        ),
+        "google" => (
+            OAUTH_ACCOUNTS,
+            Some("google"),
+            "Sign in with Google in Settings > Account > Connected accounts.",
+        ),
+        "microsoft" => (
+            OAUTH_ACCOUNTS,
+            Some("microsoft"),
+            "Sign in with Microsoft in Settings > Account > Connected accounts.",
+        ),
+        "google_drive" => (
+            CLOUD_ACCOUNTS,
+            Some("google_drive"),
+            "Connect Google Drive in Settings > Integrations > Cloud storage.",
+        ),
+        "dropbox" => (
+            CLOUD_ACCOUNTS,
+            Some("dropbox"),
+            "Connect Dropbox in Settings > Integrations > Cloud storage.",
+        ),
+        "one_drive" => (
+            CLOUD_ACCOUNTS,
+            Some("one_drive"),
+            "Connect OneDrive in Settings > Integrations > Cloud storage.",
+        ),
+        _ => return None,
+    };
+    Some(IntegrationCheck { sql, param, hint })
+}
+
+fn connected_accounts(
+    conn: &Connection,
+    check: &IntegrationCheck,
+) -> rusqlite::Result<Vec<IntegrationAccount>> {
+    let mut stmt = conn.prepare(check.sql)?;
+    let account = |row: &rusqlite::Row| {
+        Ok(IntegrationAccount {
+            id: row.get(0)?,
+            label: row.get(1)?,
+        })
+    };
+    match check.param {
+        Some(param) => stmt.query_map([param], account)?.collect(),
+        None => stmt.query_map([], account)?.collect(),
+    }
+}
+
+/// Accounts connected for an integration, or `None` when there is no
+/// connector for it
+pub fn integration_accounts(
+    conn: &Connection,
+    name: &str,
+) -> rusqlite::Result<Option<Vec<IntegrationAccount>>> {
+    integration_check(name)
+        .map(|check| connected_accounts(conn, &check))
+        .transpose()
+}
+
+/// Ids of the built-in tool catalogue
+fn builtin_tools() -> &'static HashSet<String> {
+    static TOOLS: OnceLock<HashSet<String>> = OnceLock::new();
+    TOOLS.get_or_init(|| {
+        let registry = match ToolRegistry::new() {
+            Ok(registry) => registry,
+            Err(_) => return HashSet::new(),
+        };
+        if let Err(e) = registry.register_builtin_tools() {
+            tracing::warn!("Failed to load tool catalogue: {}", e);
+        }
+        registry
+            .list_tools()
+            .into_iter()
+            .map(|tool| tool.id)
+            .collect()
+    })
+}
+
+/// Permission state for `user_id`: `None` when the permission does not
+/// exist. Local use without an account has no roles and is not restricted.
+fn permission_granted(
+    conn: &Connection,
+    user_id: &str,
+    permission: &str,
+) -> rusqlite::Result<Option<bool>> {
+    let known: Option<String> = conn
+        .query_row(
+            "SELECT id FROM permissions WHERE name = ?1",
+            [permission],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(permission_id) = known else {
+        return Ok(None);
+    };
+
+    let user_override: Option<i64> = conn
+        .query_row(
+            "SELECT granted FROM user_permissions WHERE user_id = ?1 AND permission_id = ?2",
+            params![user_id, permission_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(granted) = user_override {
+        return Ok(Some(granted != 0));
+    }
+
+    let role: Option<String> = conn
+        .query_row("SELECT role FROM users WHERE id = ?1", [user_id], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    let Some(role) = role else {
+        return Ok(Some(true));
+    };
+
+    let granted: Option<i64> = conn
+        .query_row(
+            "SELECT granted FROM role_permissions WHERE role = ?1 AND permission_id = ?2",
+            params![role, permission_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(Some(granted.unwrap_or(0) != 0))
+}
+
+/// Check everything `template` declares for `user_id`
+pub fn check_requirements(
+    conn: &Connection,
+    user_id: &str,
+    template: &AgentTemplate,
+) -> rusqlite::Result<RequirementsReport> {
+    let missing = missing_requirements(conn, user_id, &template.requirements)?;
+    Ok(RequirementsReport {
+        template_id: template.id.clone(),
+        satisfied: missing.is_empty(),
+        missing,
+    })
+}
+
+/// The integrations, tools and permissions in `requirements` that `user_id`
+/// does not have
+pub fn missing_requirements(
+    conn: &Connection,
+    user_id: &str,
+    requirements: &TemplateRequirements,
+) -> rusqlite::Result<Vec<MissingRequirement>> {
+    let mut missing = Vec::new();
+
+    for name in &requirements.integrations {
+        let hint = match integration_check(name) {
+            Some(check) => {
+                if !connected_accounts(conn, &check)?.is_empty() {
+                    continue;
+                }
+                check.hint.to_string()
+            }
+            None => format!(
+                "AGI Workforce has no '{}' connector yet; connect it through an MCP server.",
+                name
+            ),
+        };
+        missing.push(MissingRequirement {
+            kind: RequirementKind::Integration,
+            name: name.clone(),
+            hint,
+        });
+    }
+
+    let tools = builtin_tools();
+    for name in &requirements.tools {
+        if tools.contains(name) {
+            continue;
+        }
+        let hint = if name.starts_with("mcp_") {
+            "Install and start the MCP server that provides this tool.".to_string()
+        } else {
+            "This tool is not available in this version of AGI Workforce.".to_string()
+        };
+        missing.push(MissingRequirement {
+            kind: RequirementKind::Tool,
+            name: name.clone(),
+            hint,
+        });
+    }
+
+    for name in &requirements.permissions {
+        let hint = match permission_granted(conn, user_id, name)? {
+            Some(true) => continue,
+            Some(false) => "Ask a workspace admin to grant this permission.".to_string(),
+            None => "This permission does not exist; check the template definition.".to_string(),
+        };
+        missing.push(MissingRequirement {
+            kind: RequirementKind::Permission,
+            name: name.clone(),
+            hint,
+        });
+    }
+
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agi::templates::TemplateCategory;
+
+    #[test]
+    fn test_reports_missing_integrations_with_hints() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+
+        let template = AgentTemplate::new(
+            "t".to_string(),
+            "Inbox helper".to_string(),
+            TemplateCategory::Operations,
+            String::new(),
+        )
+        .with_requirements(TemplateRequirements {
+            integrations: vec!["email".to_string(), "slack".to_string()],
+            tools: vec!["file_read".to_string(), "mcp_jira_create".to_string()],
+            permissions: vec!["file:read".to_string(), "file:teleport".to_string()],
+        });
+
+        let report = check_requirements(&conn, "default_user", &template).unwrap();
+        assert!(!report.satisfied);
+        let names: Vec<&str> = report.missing.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["email", "slack", "mcp_jira_create", "file:teleport"]
+        );
+        assert!(report
+            .error_message(&template.name)
+            .contains("Settings > Integrations > Email"));
+
+        conn.execute(
+            "INSERT INTO messaging_connections (id, user_id, platform, credentials, created_at)
+             VALUES ('c', 'default_user', 'slack', '{}', 0)",
+            [],
+        )
+        .unwrap();
+        let report = check_requirements(&conn, "default_user", &template).unwrap();
+        assert!(!report.missing.iter().any(|m| m.name == "slack"));
+        assert_eq!(
+            integration_accounts(&conn, "slack").unwrap(),
+            Some(vec![IntegrationAccount {
+                id: "c".to_string(),
+                label: "slack".to_string(),
+            }])
+        );
+        assert_eq!(integration_accounts(&conn, "jira").unwrap(), None);
+    }
+}
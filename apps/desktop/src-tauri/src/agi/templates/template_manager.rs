@@ -1,7 +1,11 @@
-use rusqlite::{Connection, Result};
+use super::{
+    check_requirements, integration_accounts, missing_requirements, IntegrationAccount,
+    MissingRequirement, RequirementsReport, TemplateRequirements,
+};
+use crate::db::pool::{Pool, PooledConnection};
+use rusqlite::{Result, Row};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
 
 /// Template category for organizing templates
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -118,6 +122,9 @@ pub struct AgentTemplate {
     pub difficulty_level: DifficultyLevel,
     pub install_count: i64,
     pub created_at: i64,
+    /// Integrations, tools and permissions checked before install and run
+    #[serde(default)]
+    pub requirements: TemplateRequirements,
 }
 
 impl AgentTemplate {
@@ -140,6 +147,7 @@ impl AgentTemplate {
             difficulty_level: DifficultyLevel::Medium,
             install_count: 0,
             created_at: chrono::Utc::now().timestamp(),
+            requirements: TemplateRequirements::default(),
         }
     }
 
@@ -177,65 +185,76 @@ impl AgentTemplate {
         self.difficulty_level = difficulty;
         self
     }
+
+    pub fn with_requirements(mut self, requirements: TemplateRequirements) -> Self {
+        self.requirements = requirements;
+        self
+    }
+}
+
+/// Map a row selected with the standard agent_templates column list
+fn template_from_row(row: &Row<'_>) -> Result<AgentTemplate> {
+    let tools_json: String = row.get(5)?;
+    let workflow_json: String = row.get(6)?;
+    let prompts_json: String = row.get(7)?;
+    let criteria_json: String = row.get(8)?;
+    let category_str: String = row.get(2)?;
+    let difficulty_str: String = row.get(10)?;
+    let requirements_json: String = row.get(13)?;
+
+    Ok(AgentTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        category: TemplateCategory::from_str(&category_str).unwrap_or(TemplateCategory::Operations),
+        description: row.get(3)?,
+        icon: row.get(4)?,
+        tools: serde_json::from_str(&tools_json).unwrap_or_default(),
+        workflow: serde_json::from_str(&workflow_json).unwrap_or(WorkflowDefinition {
+            steps: Vec::new(),
+            parallel_execution: false,
+            failure_strategy: "stop".to_string(),
+        }),
+        default_prompts: serde_json::from_str(&prompts_json).unwrap_or_default(),
+        success_criteria: serde_json::from_str(&criteria_json).unwrap_or_default(),
+        estimated_duration_ms: row.get(9)?,
+        difficulty_level: DifficultyLevel::from_str(&difficulty_str)
+            .unwrap_or(DifficultyLevel::Medium),
+        install_count: row.get(11)?,
+        created_at: row.get(12)?,
+        requirements: serde_json::from_str(&requirements_json).unwrap_or_default(),
+    })
 }
 
 /// Template manager for storing and retrieving templates
 pub struct TemplateManager {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
 }
 
 impl TemplateManager {
-    pub fn new(db: Arc<Mutex<Connection>>) -> Result<Self> {
+    pub fn new(db: Pool) -> Result<Self> {
         Ok(Self { db })
     }
 
+    fn conn(&self) -> Result<PooledConnection> {
+        self.db
+            .get()
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    }
+
     /// Get all available templates
     pub fn get_all_templates(&self) -> Result<Vec<AgentTemplate>> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         let mut stmt = conn.prepare(
             "SELECT id, name, category, description, icon, tools, workflow,
                     default_prompts, success_criteria, estimated_duration_ms,
-                    difficulty_level, install_count, created_at
+                    difficulty_level, install_count, created_at, requirements
              FROM agent_templates
              ORDER BY install_count DESC, name ASC",
         )?;
 
         let templates = stmt
-            .query_map([], |row| {
-                let tools_json: String = row.get(5)?;
-                let workflow_json: String = row.get(6)?;
-                let prompts_json: String = row.get(7)?;
-                let criteria_json: String = row.get(8)?;
-                let category_str: String = row.get(2)?;
-                let difficulty_str: String = row.get(10)?;
-
-                Ok(AgentTemplate {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    category: TemplateCategory::from_str(&category_str)
-                        .unwrap_or(TemplateCategory::Operations),
-                    description: row.get(3)?,
-                    icon: row.get(4)?,
-                    tools: serde_json::from_str(&tools_json).unwrap_or_default(),
-                    workflow: serde_json::from_str(&workflow_json).unwrap_or(WorkflowDefinition {
-                        steps: Vec::new(),
-                        parallel_execution: false,
-                        failure_strategy: "stop".to_string(),
-                    }),
-                    default_prompts: serde_json::from_str(&prompts_json).unwrap_or_default(),
-                    success_criteria: serde_json::from_str(&criteria_json).unwrap_or_default(),
-                    estimated_duration_ms: row.get(9)?,
-                    difficulty_level: DifficultyLevel::from_str(&difficulty_str)
-                        .unwrap_or(DifficultyLevel::Medium),
-                    install_count: row.get(11)?,
-                    created_at: row.get(12)?,
-                })
-            })?
+            .query_map([], template_from_row)?
             .collect::<Result<Vec<_>>>()?;
 
         Ok(templates)
@@ -243,16 +262,12 @@ impl TemplateManager {
 
     /// Get template by ID
     pub fn get_template_by_id(&self, id: &str) -> Result<Option<AgentTemplate>> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         let mut stmt = conn.prepare(
             "SELECT id, name, category, description, icon, tools, workflow,
                     default_prompts, success_criteria, estimated_duration_ms,
-                    difficulty_level, install_count, created_at
+                    difficulty_level, install_count, created_at, requirements
              FROM agent_templates
              WHERE id = ?1",
         )?;
@@ -260,45 +275,14 @@ impl TemplateManager {
         let mut rows = stmt.query([id])?;
 
         if let Some(row) = rows.next()? {
-            let tools_json: String = row.get(5)?;
-            let workflow_json: String = row.get(6)?;
-            let prompts_json: String = row.get(7)?;
-            let criteria_json: String = row.get(8)?;
-            let category_str: String = row.get(2)?;
-            let difficulty_str: String = row.get(10)?;
-
-            Ok(Some(AgentTemplate {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                category: TemplateCategory::from_str(&category_str)
-                    .unwrap_or(TemplateCategory::Operations),
-                description: row.get(3)?,
-                icon: row.get(4)?,
-                tools: serde_json::from_str(&tools_json).unwrap_or_default(),
-                workflow: serde_json::from_str(&workflow_json).unwrap_or(WorkflowDefinition {
-                    steps: Vec::new(),
-                    parallel_execution: false,
-                    failure_strategy: "stop".to_string(),
-                }),
-                default_prompts: serde_json::from_str(&prompts_json).unwrap_or_default(),
-                success_criteria: serde_json::from_str(&criteria_json).unwrap_or_default(),
-                estimated_duration_ms: row.get(9)?,
-                difficulty_level: DifficultyLevel::from_str(&difficulty_str)
-                    .unwrap_or(DifficultyLevel::Medium),
-                install_count: row.get(11)?,
-                created_at: row.get(12)?,
-            }))
+            Ok(Some(template_from_row(row)?))
         } else {
             Ok(None)
         }
     }
 
     pub fn uninstall_template(&self, user_id: &str, template_id: &str) -> Result<()> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         conn.execute(
             "DELETE FROM template_installs WHERE user_id = ?1 AND template_id = ?2",
@@ -313,52 +297,19 @@ impl TemplateManager {
         &self,
         category: TemplateCategory,
     ) -> Result<Vec<AgentTemplate>> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         let mut stmt = conn.prepare(
             "SELECT id, name, category, description, icon, tools, workflow,
                     default_prompts, success_criteria, estimated_duration_ms,
-                    difficulty_level, install_count, created_at
+                    difficulty_level, install_count, created_at, requirements
              FROM agent_templates
              WHERE category = ?1
              ORDER BY install_count DESC, name ASC",
         )?;
 
         let templates = stmt
-            .query_map([category.as_str()], |row| {
-                let tools_json: String = row.get(5)?;
-                let workflow_json: String = row.get(6)?;
-                let prompts_json: String = row.get(7)?;
-                let criteria_json: String = row.get(8)?;
-                let category_str: String = row.get(2)?;
-                let difficulty_str: String = row.get(10)?;
-
-                Ok(AgentTemplate {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    category: TemplateCategory::from_str(&category_str)
-                        .unwrap_or(TemplateCategory::Operations),
-                    description: row.get(3)?,
-                    icon: row.get(4)?,
-                    tools: serde_json::from_str(&tools_json).unwrap_or_default(),
-                    workflow: serde_json::from_str(&workflow_json).unwrap_or(WorkflowDefinition {
-                        steps: Vec::new(),
-                        parallel_execution: false,
-                        failure_strategy: "stop".to_string(),
-                    }),
-                    default_prompts: serde_json::from_str(&prompts_json).unwrap_or_default(),
-                    success_criteria: serde_json::from_str(&criteria_json).unwrap_or_default(),
-                    estimated_duration_ms: row.get(9)?,
-                    difficulty_level: DifficultyLevel::from_str(&difficulty_str)
-                        .unwrap_or(DifficultyLevel::Medium),
-                    install_count: row.get(11)?,
-                    created_at: row.get(12)?,
-                })
-            })?
+            .query_map([category.as_str()], template_from_row)?
             .collect::<Result<Vec<_>>>()?;
 
         Ok(templates)
@@ -366,11 +317,7 @@ impl TemplateManager {
 
     /// Install a template for a user
     pub fn install_template(&self, user_id: &str, template_id: &str) -> Result<()> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         // Insert install record
         conn.execute(
@@ -394,16 +341,12 @@ impl TemplateManager {
 
     /// Get installed templates for a user
     pub fn get_installed_templates(&self, user_id: &str) -> Result<Vec<AgentTemplate>> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         let mut stmt = conn.prepare(
             "SELECT t.id, t.name, t.category, t.description, t.icon, t.tools, t.workflow,
                     t.default_prompts, t.success_criteria, t.estimated_duration_ms,
-                    t.difficulty_level, t.install_count, t.created_at
+                    t.difficulty_level, t.install_count, t.created_at, t.requirements
              FROM agent_templates t
              INNER JOIN template_installs i ON t.id = i.template_id
              WHERE i.user_id = ?1
@@ -411,36 +354,7 @@ impl TemplateManager {
         )?;
 
         let templates = stmt
-            .query_map([user_id], |row| {
-                let tools_json: String = row.get(5)?;
-                let workflow_json: String = row.get(6)?;
-                let prompts_json: String = row.get(7)?;
-                let criteria_json: String = row.get(8)?;
-                let category_str: String = row.get(2)?;
-                let difficulty_str: String = row.get(10)?;
-
-                Ok(AgentTemplate {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    category: TemplateCategory::from_str(&category_str)
-                        .unwrap_or(TemplateCategory::Operations),
-                    description: row.get(3)?,
-                    icon: row.get(4)?,
-                    tools: serde_json::from_str(&tools_json).unwrap_or_default(),
-                    workflow: serde_json::from_str(&workflow_json).unwrap_or(WorkflowDefinition {
-                        steps: Vec::new(),
-                        parallel_execution: false,
-                        failure_strategy: "stop".to_string(),
-                    }),
-                    default_prompts: serde_json::from_str(&prompts_json).unwrap_or_default(),
-                    success_criteria: serde_json::from_str(&criteria_json).unwrap_or_default(),
-                    estimated_duration_ms: row.get(9)?,
-                    difficulty_level: DifficultyLevel::from_str(&difficulty_str)
-                        .unwrap_or(DifficultyLevel::Medium),
-                    install_count: row.get(11)?,
-                    created_at: row.get(12)?,
-                })
-            })?
+            .query_map([user_id], template_from_row)?
             .collect::<Result<Vec<_>>>()?;
 
         Ok(templates)
@@ -448,54 +362,21 @@ impl TemplateManager {
 
     /// Search templates by query (searches name and description)
     pub fn search_templates(&self, query: &str) -> Result<Vec<AgentTemplate>> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         let search_pattern = format!("%{}%", query.to_lowercase());
 
         let mut stmt = conn.prepare(
             "SELECT id, name, category, description, icon, tools, workflow,
                     default_prompts, success_criteria, estimated_duration_ms,
-                    difficulty_level, install_count, created_at
+                    difficulty_level, install_count, created_at, requirements
              FROM agent_templates
              WHERE LOWER(name) LIKE ?1 OR LOWER(description) LIKE ?1
              ORDER BY install_count DESC, name ASC",
         )?;
 
         let templates = stmt
-            .query_map([&search_pattern], |row| {
-                let tools_json: String = row.get(5)?;
-                let workflow_json: String = row.get(6)?;
-                let prompts_json: String = row.get(7)?;
-                let criteria_json: String = row.get(8)?;
-                let category_str: String = row.get(2)?;
-                let difficulty_str: String = row.get(10)?;
-
-                Ok(AgentTemplate {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    category: TemplateCategory::from_str(&category_str)
-                        .unwrap_or(TemplateCategory::Operations),
-                    description: row.get(3)?,
-                    icon: row.get(4)?,
-                    tools: serde_json::from_str(&tools_json).unwrap_or_default(),
-                    workflow: serde_json::from_str(&workflow_json).unwrap_or(WorkflowDefinition {
-                        steps: Vec::new(),
-                        parallel_execution: false,
-                        failure_strategy: "stop".to_string(),
-                    }),
-                    default_prompts: serde_json::from_str(&prompts_json).unwrap_or_default(),
-                    success_criteria: serde_json::from_str(&criteria_json).unwrap_or_default(),
-                    estimated_duration_ms: row.get(9)?,
-                    difficulty_level: DifficultyLevel::from_str(&difficulty_str)
-                        .unwrap_or(DifficultyLevel::Medium),
-                    install_count: row.get(11)?,
-                    created_at: row.get(12)?,
-                })
-            })?
+            .query_map([&search_pattern], template_from_row)?
             .collect::<Result<Vec<_>>>()?;
 
         Ok(templates)
@@ -503,23 +384,20 @@ impl TemplateManager {
 
     /// Save a template to the database
     pub fn save_template(&self, template: &AgentTemplate) -> Result<()> {
-        let conn = self.db.lock().map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to lock database",
-            )))
-        })?;
+        let conn = self.conn()?;
 
         let tools_json = serde_json::to_string(&template.tools).unwrap_or_default();
         let workflow_json = serde_json::to_string(&template.workflow).unwrap_or_default();
         let prompts_json = serde_json::to_string(&template.default_prompts).unwrap_or_default();
         let criteria_json = serde_json::to_string(&template.success_criteria).unwrap_or_default();
+        let requirements_json = serde_json::to_string(&template.requirements).unwrap_or_default();
 
         conn.execute(
             "INSERT OR REPLACE INTO agent_templates
              (id, name, category, description, icon, tools, workflow,
               default_prompts, success_criteria, estimated_duration_ms,
-              difficulty_level, install_count, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+              difficulty_level, install_count, created_at, requirements)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             rusqlite::params![
                 template.id,
                 template.name,
@@ -534,6 +412,54 @@ impl TemplateManager {
                 template.difficulty_level.as_str(),
                 template.install_count,
                 template.created_at,
+                requirements_json,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Verify the integrations, tools and permissions `template` declares
+    pub fn check_requirements(
+        &self,
+        user_id: &str,
+        template: &AgentTemplate,
+    ) -> Result<RequirementsReport> {
+        let conn = self.conn()?;
+
+        check_requirements(&conn, user_id, template)
+    }
+
+    /// The parts of `requirements` that `user_id` does not have
+    pub fn missing_requirements(
+        &self,
+        user_id: &str,
+        requirements: &TemplateRequirements,
+    ) -> Result<Vec<MissingRequirement>> {
+        let conn = self.conn()?;
+
+        missing_requirements(&conn, user_id, requirements)
+    }
+
+    /// Connected accounts for an integration; `None` when it has no connector
+    pub fn integration_accounts(&self, name: &str) -> Result<Option<Vec<IntegrationAccount>>> {
+        let conn = self.conn()?;
+
+        integration_accounts(&conn, name)
+    }
+
+    pub fn update_requirements(
+        &self,
+        template_id: &str,
+        requirements: &TemplateRequirements,
+    ) -> Result<()> {
+        let conn = self.conn()?;
+
+        conn.execute(
+            "UPDATE agent_templates SET requirements = ?1 WHERE id = ?2",
+            rusqlite::params![
+                serde_json::to_string(requirements).unwrap_or_default(),
+                template_id
             ],
         )?;
 
@@ -547,6 +473,10 @@ impl TemplateManager {
             let exists = self.get_template_by_id(&template.id)?.is_some();
             if !exists {
                 self.save_template(&template)?;
+            } else {
+                // Built-ins own their dependency declarations; refresh them
+                // for databases seeded before templates declared any
+                self.update_requirements(&template.id, &template.requirements)?;
             }
         }
         Ok(())
@@ -27,6 +27,7 @@ mod tests {
             },
             tool_results: vec![],
             context_memory: vec![],
+            permissions: Default::default(),
         };
 
         assert_eq!(context.goal.id, "exec-goal-1");
@@ -98,6 +99,7 @@ mod tests {
             },
             tool_results: vec![],
             context_memory: vec![],
+            permissions: Default::default(),
         };
 
         context
@@ -154,6 +156,7 @@ mod tests {
             },
             tool_results: vec![],
             context_memory: vec![],
+            permissions: Default::default(),
         };
 
         // Add multiple tool results
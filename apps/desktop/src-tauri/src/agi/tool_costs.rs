@@ -0,0 +1,435 @@
+//! Tool cost accounting
+//!
+//! Some tools spend real money: OCR and vision APIs, hosted browser sessions,
+//! MCP servers fronting paid APIs. Every tool call is written to a ledger
+//! with its duration, the resources it reports and what it cost, so tool
+//! spend can be shown next to LLM spend. A call's cost is the amount the
+//! executor reports, or else what the tool's cost model charges for its
+//! usage; tools without either cost nothing.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::models::CostTimeseriesPoint;
+use crate::db::pool::{Pool, PooledConnection};
+
+/// Key under which executors report usage, in a tool's JSON result or in
+/// the metadata of a router tool result
+pub const USAGE_KEY: &str = "usage";
+
+/// Resources a single tool call consumed, as reported by its executor
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolUsage {
+    /// Cost in USD when the executor knows it, e.g. from an API's billing
+    /// headers; overrides the tool's cost model
+    pub cost: Option<f64>,
+    /// Metered quantities such as `pages`, `images` or `api_calls`
+    pub units: HashMap<String, f64>,
+    /// Service the cost was incurred with, e.g. an MCP server
+    pub source: Option<String>,
+}
+
+impl ToolUsage {
+    pub fn units(units: &[(&str, f64)]) -> Self {
+        Self {
+            units: units
+                .iter()
+                .map(|(name, amount)| (name.to_string(), *amount))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Usage reported under [`USAGE_KEY`] in a tool result, if any
+    pub fn from_result(result: &Value) -> Option<Self> {
+        serde_json::from_value(result.get(USAGE_KEY)?.clone()).ok()
+    }
+}
+
+/// Prices a tool call from its duration and reported usage
+pub trait ToolCostModel: Send + Sync {
+    fn cost(&self, duration_ms: u64, usage: &ToolUsage) -> f64;
+}
+
+/// Flat prices in USD: per call, per second of execution and per unit of
+/// each metered resource
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolPricing {
+    pub per_call: f64,
+    pub per_second: f64,
+    pub per_unit: HashMap<String, f64>,
+}
+
+impl ToolCostModel for ToolPricing {
+    fn cost(&self, duration_ms: u64, usage: &ToolUsage) -> f64 {
+        let metered: f64 = usage
+            .units
+            .iter()
+            .filter_map(|(unit, amount)| self.per_unit.get(unit).map(|price| price * amount))
+            .sum();
+        self.per_call + self.per_second * duration_ms as f64 / 1000.0 + metered
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCostBreakdown {
+    pub tool_id: String,
+    pub calls: usize,
+    pub failures: usize,
+    pub total_cost: f64,
+    pub avg_duration_ms: f64,
+    /// Metered units summed over the calls
+    pub units: HashMap<String, f64>,
+}
+
+/// Per-tool cost models plus the ledger they write to
+pub struct ToolCostLedger {
+    models: RwLock<HashMap<String, Arc<dyn ToolCostModel>>>,
+    pool: RwLock<Option<Pool>>,
+}
+
+impl Default for ToolCostLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolCostLedger {
+    pub fn new() -> Self {
+        Self {
+            models: RwLock::new(HashMap::new()),
+            pool: RwLock::new(None),
+        }
+    }
+
+    /// Write the ledger to the given database and load the configured prices
+    pub fn attach_database(&self, pool: Pool) -> Result<()> {
+        let conn = pool.get()?;
+        let pricing = {
+            let mut stmt = conn.prepare("SELECT tool_id, pricing FROM tool_pricing")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        {
+            let mut models = self.models.write();
+            for (tool_id, json) in pricing {
+                match serde_json::from_str::<ToolPricing>(&json) {
+                    Ok(pricing) => {
+                        models.insert(tool_id, Arc::new(pricing));
+                    }
+                    Err(e) => tracing::warn!("Ignoring invalid pricing for {}: {}", tool_id, e),
+                }
+            }
+        }
+
+        *self.pool.write() = Some(pool);
+        Ok(())
+    }
+
+    /// A connection to the attached database; `None` until one is attached
+    fn conn(&self) -> Result<Option<PooledConnection>> {
+        match self.pool.read().as_ref() {
+            Some(pool) => Ok(Some(pool.get()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Hook a cost model in for a tool, replacing any configured pricing
+    pub fn register_model(&self, tool_id: &str, model: Arc<dyn ToolCostModel>) {
+        self.models.write().insert(tool_id.to_string(), model);
+    }
+
+    /// Set a tool's flat prices, or clear them with `None`
+    pub fn set_pricing(&self, tool_id: &str, pricing: Option<ToolPricing>) -> Result<()> {
+        if let Some(conn) = self.conn()? {
+            match &pricing {
+                Some(pricing) => conn.execute(
+                    "INSERT INTO tool_pricing (tool_id, pricing, updated_at)
+                     VALUES (?1, ?2, ?3)
+                     ON CONFLICT(tool_id) DO UPDATE SET
+                        pricing = excluded.pricing,
+                        updated_at = excluded.updated_at",
+                    params![
+                        tool_id,
+                        serde_json::to_string(pricing)?,
+                        Utc::now().timestamp()
+                    ],
+                )?,
+                None => conn.execute(
+                    "DELETE FROM tool_pricing WHERE tool_id = ?1",
+                    params![tool_id],
+                )?,
+            };
+        }
+
+        let mut models = self.models.write();
+        match pricing {
+            Some(pricing) => models.insert(tool_id.to_string(), Arc::new(pricing)),
+            None => models.remove(tool_id),
+        };
+        Ok(())
+    }
+
+    /// Flat prices configured for each tool
+    pub fn pricing(&self) -> Result<HashMap<String, ToolPricing>> {
+        let Some(conn) = self.conn()? else {
+            return Ok(HashMap::new());
+        };
+        let mut stmt = conn.prepare("SELECT tool_id, pricing FROM tool_pricing")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut pricing = HashMap::new();
+        for row in rows {
+            let (tool_id, json) = row?;
+            if let Ok(parsed) = serde_json::from_str(&json) {
+                pricing.insert(tool_id, parsed);
+            }
+        }
+        Ok(pricing)
+    }
+
+    /// Record a tool call in the ledger, returning what it cost
+    pub fn record(
+        &self,
+        tool_id: &str,
+        success: bool,
+        duration_ms: u64,
+        usage: Option<ToolUsage>,
+    ) -> f64 {
+        let usage = usage.unwrap_or_default();
+        let cost = match usage.cost {
+            Some(cost) => cost.max(0.0),
+            None => self
+                .models
+                .read()
+                .get(tool_id)
+                .map(|model| model.cost(duration_ms, &usage).max(0.0))
+                .unwrap_or(0.0),
+        };
+
+        if let Err(e) = self.insert_event(tool_id, success, duration_ms, cost, &usage) {
+            tracing::warn!("Failed to record tool cost: {}", e);
+        }
+        cost
+    }
+
+    fn insert_event(
+        &self,
+        tool_id: &str,
+        success: bool,
+        duration_ms: u64,
+        cost: f64,
+        usage: &ToolUsage,
+    ) -> Result<()> {
+        let Some(conn) = self.conn()? else {
+            return Ok(());
+        };
+        let units = serde_json::to_string(&usage.units).unwrap_or_else(|_| "{}".into());
+        conn.execute(
+            "INSERT INTO tool_cost_events
+                (tool_id, success, duration_ms, cost, units, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                tool_id,
+                success as i64,
+                duration_ms as i64,
+                cost,
+                units,
+                usage.source,
+                to_sqlite_timestamp(Utc::now()),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// Total tool spend since `since`
+pub fn tool_cost_since(conn: &Connection, since: DateTime<Utc>) -> rusqlite::Result<f64> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(cost), 0.0) FROM tool_cost_events WHERE created_at >= ?1",
+        params![to_sqlite_timestamp(since)],
+        |row| row.get(0),
+    )
+}
+
+/// Spend, calls and resource usage per tool between `start` and `end`,
+/// most expensive first
+pub fn tool_cost_breakdown(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> rusqlite::Result<Vec<ToolCostBreakdown>> {
+    let mut stmt = conn.prepare(
+        "SELECT tool_id, success, duration_ms, cost, units
+         FROM tool_cost_events
+         WHERE created_at >= ?1 AND created_at <= ?2",
+    )?;
+    let rows = stmt.query_map(
+        params![to_sqlite_timestamp(start), to_sqlite_timestamp(end)],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)? != 0,
+                row.get::<_, i64>(2)?.max(0),
+                row.get::<_, f64>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        },
+    )?;
+
+    let mut by_tool: HashMap<String, (ToolCostBreakdown, i64)> = HashMap::new();
+    for row in rows {
+        let (tool_id, success, duration_ms, cost, units) = row?;
+        let (entry, total_duration) = by_tool.entry(tool_id.clone()).or_insert_with(|| {
+            (
+                ToolCostBreakdown {
+                    tool_id,
+                    calls: 0,
+                    failures: 0,
+                    total_cost: 0.0,
+                    avg_duration_ms: 0.0,
+                    units: HashMap::new(),
+                },
+                0,
+            )
+        });
+        entry.calls += 1;
+        if !success {
+            entry.failures += 1;
+        }
+        entry.total_cost += cost;
+        *total_duration += duration_ms;
+        let units: HashMap<String, f64> = units
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        for (unit, amount) in units {
+            *entry.units.entry(unit).or_insert(0.0) += amount;
+        }
+    }
+
+    let mut breakdown: Vec<ToolCostBreakdown> = by_tool
+        .into_values()
+        .map(|(mut entry, total_duration)| {
+            entry.avg_duration_ms = total_duration as f64 / entry.calls as f64;
+            entry
+        })
+        .collect();
+    breakdown.sort_by(|a, b| {
+        b.total_cost
+            .total_cmp(&a.total_cost)
+            .then(b.calls.cmp(&a.calls))
+            .then(a.tool_id.cmp(&b.tool_id))
+    });
+    Ok(breakdown)
+}
+
+/// Daily tool spend over the last `days` days, oldest first
+pub fn tool_cost_timeseries(
+    conn: &Connection,
+    days: i64,
+) -> rusqlite::Result<Vec<CostTimeseriesPoint>> {
+    let cutoff = Utc::now() - Duration::days(days.max(1) - 1);
+    let mut stmt = conn.prepare(
+        "SELECT DATE(created_at) AS bucket, COALESCE(SUM(cost), 0.0)
+         FROM tool_cost_events
+         WHERE created_at >= DATE(?1)
+         GROUP BY bucket ORDER BY bucket ASC",
+    )?;
+    let rows = stmt.query_map(params![to_sqlite_timestamp(cutoff)], |row| {
+        Ok(CostTimeseriesPoint {
+            date: row.get(0)?,
+            total_cost: row.get(1)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Same format as `messages.created_at`, so tool and LLM spend bucket alike
+fn to_sqlite_timestamp(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+static GLOBAL_TOOL_COSTS: Lazy<ToolCostLedger> = Lazy::new(ToolCostLedger::new);
+
+/// Process-wide ledger shared by executors and commands
+pub fn global_tool_costs() -> &'static ToolCostLedger {
+    &GLOBAL_TOOL_COSTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger() -> ToolCostLedger {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let ledger = ToolCostLedger::new();
+        ledger.attach_database(pool).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn test_reported_cost_overrides_pricing() {
+        let ledger = ledger();
+        ledger
+            .set_pricing(
+                "image_ocr",
+                Some(ToolPricing {
+                    per_call: 0.001,
+                    per_unit: HashMap::from([("pages".to_string(), 0.0015)]),
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+
+        let priced = ledger.record(
+            "image_ocr",
+            true,
+            800,
+            Some(ToolUsage::units(&[("pages", 2.0)])),
+        );
+        assert!((priced - 0.004).abs() < 1e-9);
+        let reported = ToolUsage {
+            cost: Some(0.05),
+            ..Default::default()
+        };
+        assert_eq!(ledger.record("image_ocr", true, 800, Some(reported)), 0.05);
+        assert_eq!(ledger.record("file_read", true, 3, None), 0.0);
+
+        let conn = ledger.conn().unwrap().unwrap();
+        let conn = &*conn;
+        let now = Utc::now();
+        let breakdown = tool_cost_breakdown(conn, now - Duration::hours(1), now).unwrap();
+        assert_eq!(breakdown[0].tool_id, "image_ocr");
+        assert_eq!(breakdown[0].calls, 2);
+        assert!((breakdown[0].total_cost - 0.054).abs() < 1e-9);
+        assert_eq!(breakdown[0].units.get("pages"), Some(&2.0));
+        assert_eq!(breakdown[1].tool_id, "file_read");
+        assert!((tool_cost_since(conn, now - Duration::hours(1)).unwrap() - 0.054).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_usage_read_from_tool_result() {
+        let result = serde_json::json!({
+            "text": "scanned",
+            "usage": { "cost": 0.02, "units": { "images": 1.0 }, "source": "vision-api" }
+        });
+        let usage = ToolUsage::from_result(&result).unwrap();
+        assert_eq!(usage.cost, Some(0.02));
+        assert_eq!(usage.units.get("images"), Some(&1.0));
+        assert_eq!(usage.source.as_deref(), Some("vision-api"));
+        assert!(ToolUsage::from_result(&serde_json::json!({ "text": "x" })).is_none());
+    }
+}
@@ -0,0 +1,466 @@
+//! Tool reliability telemetry
+//!
+//! Records the outcome and latency of every tool call so the planner can
+//! learn which tools (and which targeting methods, e.g. UI Automation vs raw
+//! coordinates) actually work on this machine. Samples are kept in a rolling
+//! in-memory window per tool and mirrored to SQLite so scores survive restarts.
+
+use anyhow::Result;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::db::pool::{Pool, PooledConnection};
+
+/// Samples retained per tool for scoring
+const WINDOW_SIZE: usize = 200;
+/// Weight multiplier applied per step back in history
+const DECAY: f64 = 0.97;
+/// Minimum number of samples before a score influences planning
+const MIN_SAMPLES: usize = 3;
+/// Persisted events older than this are pruned
+const RETENTION_DAYS: i64 = 30;
+/// Inserts between prunes of the persisted events
+const PRUNE_EVERY: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallSample {
+    pub tool_id: String,
+    pub method: Option<String>,
+    pub success: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    pub recorded_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolReliabilityStats {
+    pub tool_id: String,
+    /// Targeting method, or None for the aggregate across all methods
+    pub method: Option<String>,
+    pub total_calls: usize,
+    pub successes: usize,
+    pub failures: usize,
+    pub success_rate: f64,
+    /// Recency-weighted success estimate in [0, 1]
+    pub reliability_score: f64,
+    pub avg_latency_ms: f64,
+    pub p95_latency_ms: u64,
+    pub last_error: Option<String>,
+    pub last_used_at: Option<i64>,
+}
+
+/// Rolling per-tool reliability tracker
+pub struct ToolReliabilityTracker {
+    samples: Mutex<HashMap<String, VecDeque<ToolCallSample>>>,
+    pool: RwLock<Option<Pool>>,
+    inserts_since_prune: AtomicUsize,
+}
+
+impl Default for ToolReliabilityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolReliabilityTracker {
+    pub fn new() -> Self {
+        Self {
+            samples: Mutex::new(HashMap::new()),
+            pool: RwLock::new(None),
+            inserts_since_prune: AtomicUsize::new(0),
+        }
+    }
+
+    /// Persist samples to the given database and load the recent history
+    pub fn attach_database(&self, pool: Pool) -> Result<()> {
+        let conn = pool.get()?;
+        prune(&conn)?;
+
+        let loaded = {
+            let mut stmt = conn.prepare(
+                "SELECT tool_id, method, success, latency_ms, error, recorded_at
+                 FROM tool_reliability_events
+                 ORDER BY recorded_at ASC, id ASC",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(ToolCallSample {
+                    tool_id: row.get(0)?,
+                    method: row.get(1)?,
+                    success: row.get::<_, i64>(2)? != 0,
+                    latency_ms: row.get::<_, i64>(3)?.max(0) as u64,
+                    error: row.get(4)?,
+                    recorded_at: row.get(5)?,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        {
+            let mut samples = self.samples.lock();
+            for sample in loaded {
+                push_sample(&mut samples, sample);
+            }
+        }
+
+        *self.pool.write() = Some(pool);
+        Ok(())
+    }
+
+    /// A connection to the attached database; `None` until one is attached
+    fn conn(&self) -> Result<Option<PooledConnection>> {
+        match self.pool.read().as_ref() {
+            Some(pool) => Ok(Some(pool.get()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Record the outcome of a tool call
+    pub fn record(
+        &self,
+        tool_id: &str,
+        method: Option<String>,
+        success: bool,
+        latency_ms: u64,
+        error: Option<String>,
+    ) {
+        let sample = ToolCallSample {
+            tool_id: tool_id.to_string(),
+            method,
+            success,
+            latency_ms,
+            error,
+            recorded_at: Utc::now().timestamp(),
+        };
+
+        if let Err(e) = self.persist(&sample) {
+            tracing::warn!("Failed to persist tool reliability sample: {}", e);
+        }
+
+        push_sample(&mut self.samples.lock(), sample);
+    }
+
+    fn persist(&self, sample: &ToolCallSample) -> Result<()> {
+        let Some(conn) = self.conn()? else {
+            return Ok(());
+        };
+        conn.execute(
+            "INSERT INTO tool_reliability_events
+                (tool_id, method, success, latency_ms, error, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                sample.tool_id,
+                sample.method,
+                sample.success as i64,
+                sample.latency_ms as i64,
+                sample.error,
+                sample.recorded_at,
+            ],
+        )?;
+        if self.inserts_since_prune.fetch_add(1, Ordering::Relaxed) + 1 >= PRUNE_EVERY {
+            self.inserts_since_prune.store(0, Ordering::Relaxed);
+            prune(&conn)?;
+        }
+        Ok(())
+    }
+
+    /// Reliability score for a tool, or None if there is too little data
+    pub fn score(&self, tool_id: &str) -> Option<f64> {
+        let samples = self.samples.lock();
+        let window = samples.get(tool_id)?;
+        if window.len() < MIN_SAMPLES {
+            return None;
+        }
+        Some(weighted_score(window.iter()))
+    }
+
+    /// Statistics for every tool, plus a row per targeting method
+    pub fn stats(&self) -> Vec<ToolReliabilityStats> {
+        let samples = self.samples.lock();
+        let mut tool_ids: Vec<&String> = samples.keys().collect();
+        tool_ids.sort();
+
+        tool_ids
+            .into_iter()
+            .flat_map(|tool_id| stats_for_window(tool_id, &samples[tool_id]))
+            .collect()
+    }
+
+    /// Statistics for a single tool
+    pub fn stats_for_tool(&self, tool_id: &str) -> Vec<ToolReliabilityStats> {
+        let samples = self.samples.lock();
+        samples
+            .get(tool_id)
+            .map(|window| stats_for_window(tool_id, window))
+            .unwrap_or_default()
+    }
+
+    /// Short planner-facing summary such as "92% reliable; uia_element 98%, coordinates 61%"
+    pub fn planner_hint(&self, tool_id: &str) -> Option<String> {
+        let stats = self.stats_for_tool(tool_id);
+        let overall = stats.iter().find(|s| s.method.is_none())?;
+        if overall.total_calls < MIN_SAMPLES {
+            return None;
+        }
+
+        let mut hint = format!(
+            "{:.0}% reliable over {} calls",
+            overall.reliability_score * 100.0,
+            overall.total_calls
+        );
+        let methods: Vec<String> = stats
+            .iter()
+            .filter(|s| s.total_calls >= MIN_SAMPLES)
+            .filter_map(|s| {
+                s.method
+                    .as_ref()
+                    .map(|m| format!("{} {:.0}%", m, s.reliability_score * 100.0))
+            })
+            .collect();
+        if !methods.is_empty() {
+            hint.push_str("; ");
+            hint.push_str(&methods.join(", "));
+        }
+        Some(hint)
+    }
+
+    /// Clear history for one tool, or all tools when None
+    pub fn reset(&self, tool_id: Option<&str>) -> Result<()> {
+        if let Some(conn) = self.conn()? {
+            match tool_id {
+                Some(id) => conn.execute(
+                    "DELETE FROM tool_reliability_events WHERE tool_id = ?1",
+                    params![id],
+                )?,
+                None => conn.execute("DELETE FROM tool_reliability_events", [])?,
+            };
+        }
+
+        let mut samples = self.samples.lock();
+        match tool_id {
+            Some(id) => {
+                samples.remove(id);
+            }
+            None => samples.clear(),
+        }
+        Ok(())
+    }
+}
+
+/// Classify how a UI tool located its target so different paths are scored separately
+pub fn call_method(parameters: &HashMap<String, serde_json::Value>) -> Option<String> {
+    let target = parameters.get("target")?.as_object()?;
+    let method = if target.contains_key("element_id") {
+        "uia_element"
+    } else if target.contains_key("text") {
+        "uia_text"
+    } else if target.contains_key("image") || target.contains_key("template") {
+        "image_match"
+    } else if target.contains_key("coordinates") {
+        "coordinates"
+    } else {
+        return None;
+    };
+    Some(method.to_string())
+}
+
+/// Drop events past the retention period and those beyond each tool's
+/// scoring window, which are never loaded
+fn prune(conn: &rusqlite::Connection) -> Result<()> {
+    let cutoff = Utc::now().timestamp() - RETENTION_DAYS * 86_400;
+    conn.execute(
+        "DELETE FROM tool_reliability_events
+         WHERE recorded_at < ?1
+            OR id IN (
+                SELECT id FROM (
+                    SELECT id, ROW_NUMBER() OVER (
+                        PARTITION BY tool_id ORDER BY recorded_at DESC, id DESC
+                    ) AS position
+                    FROM tool_reliability_events
+                )
+                WHERE position > ?2
+            )",
+        params![cutoff, WINDOW_SIZE as i64],
+    )?;
+    Ok(())
+}
+
+fn push_sample(samples: &mut HashMap<String, VecDeque<ToolCallSample>>, sample: ToolCallSample) {
+    let window = samples.entry(sample.tool_id.clone()).or_default();
+    window.push_back(sample);
+    while window.len() > WINDOW_SIZE {
+        window.pop_front();
+    }
+}
+
+/// Recency-weighted success rate with a uniform prior, so a couple of early
+/// failures do not permanently bury a tool
+fn weighted_score<'a>(samples: impl DoubleEndedIterator<Item = &'a ToolCallSample>) -> f64 {
+    let mut weight = 1.0;
+    let mut successes = 0.0;
+    let mut total = 0.0;
+    for sample in samples.rev() {
+        if sample.success {
+            successes += weight;
+        }
+        total += weight;
+        weight *= DECAY;
+    }
+    (successes + 1.0) / (total + 2.0)
+}
+
+fn stats_for_window(tool_id: &str, window: &VecDeque<ToolCallSample>) -> Vec<ToolReliabilityStats> {
+    let all: Vec<&ToolCallSample> = window.iter().collect();
+    let mut stats = vec![summarize(tool_id, None, &all)];
+
+    let mut methods: Vec<&String> = window.iter().filter_map(|s| s.method.as_ref()).collect();
+    methods.sort();
+    methods.dedup();
+    for method in methods {
+        let subset: Vec<&ToolCallSample> = window
+            .iter()
+            .filter(|s| s.method.as_ref() == Some(method))
+            .collect();
+        stats.push(summarize(tool_id, Some(method.clone()), &subset));
+    }
+
+    stats
+}
+
+fn summarize(
+    tool_id: &str,
+    method: Option<String>,
+    samples: &[&ToolCallSample],
+) -> ToolReliabilityStats {
+    let total_calls = samples.len();
+    let successes = samples.iter().filter(|s| s.success).count();
+
+    let mut latencies: Vec<u64> = samples.iter().map(|s| s.latency_ms).collect();
+    latencies.sort_unstable();
+    let avg_latency_ms = if total_calls > 0 {
+        latencies.iter().sum::<u64>() as f64 / total_calls as f64
+    } else {
+        0.0
+    };
+    let p95_latency_ms = if latencies.is_empty() {
+        0
+    } else {
+        let idx = ((latencies.len() as f64) * 0.95).ceil() as usize;
+        latencies[idx.saturating_sub(1).min(latencies.len() - 1)]
+    };
+
+    ToolReliabilityStats {
+        tool_id: tool_id.to_string(),
+        method,
+        total_calls,
+        successes,
+        failures: total_calls - successes,
+        success_rate: if total_calls > 0 {
+            successes as f64 / total_calls as f64
+        } else {
+            0.0
+        },
+        reliability_score: weighted_score(samples.iter().copied()),
+        avg_latency_ms,
+        p95_latency_ms,
+        last_error: samples
+            .iter()
+            .rev()
+            .find_map(|s| if s.success { None } else { s.error.clone() }),
+        last_used_at: samples.last().map(|s| s.recorded_at),
+    }
+}
+
+static GLOBAL_TOOL_RELIABILITY: Lazy<ToolReliabilityTracker> =
+    Lazy::new(ToolReliabilityTracker::new);
+
+/// Process-wide tracker shared by executors, the planner and commands
+pub fn global_tool_reliability() -> &'static ToolReliabilityTracker {
+    &GLOBAL_TOOL_RELIABILITY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_failures_lower_score() {
+        let tracker = ToolReliabilityTracker::new();
+        for _ in 0..10 {
+            tracker.record("ui_click", Some("uia_element".into()), true, 40, None);
+        }
+        for _ in 0..5 {
+            tracker.record(
+                "ui_click",
+                Some("coordinates".into()),
+                false,
+                120,
+                Some("missed".into()),
+            );
+        }
+
+        let stats = tracker.stats_for_tool("ui_click");
+        let uia = stats
+            .iter()
+            .find(|s| s.method.as_deref() == Some("uia_element"))
+            .unwrap();
+        let coords = stats
+            .iter()
+            .find(|s| s.method.as_deref() == Some("coordinates"))
+            .unwrap();
+        assert!(uia.reliability_score > coords.reliability_score);
+        assert_eq!(coords.last_error.as_deref(), Some("missed"));
+
+        let overall = stats.iter().find(|s| s.method.is_none()).unwrap();
+        assert_eq!(overall.total_calls, 15);
+        // Failures are the most recent samples, so the weighted score is below the raw rate
+        assert!(overall.reliability_score < overall.success_rate);
+    }
+
+    #[test]
+    fn test_persisted_samples_reload() {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let tracker = ToolReliabilityTracker::new();
+        tracker.attach_database(pool.clone()).unwrap();
+        tracker.record("file_read", None, true, 5, None);
+        tracker.record("file_read", None, false, 7, Some("not found".into()));
+
+        drop(tracker);
+        let reloaded = ToolReliabilityTracker::new();
+        reloaded.attach_database(pool).unwrap();
+        let stats = reloaded.stats_for_tool("file_read");
+        assert_eq!(stats[0].total_calls, 2);
+        assert_eq!(stats[0].failures, 1);
+        assert!(reloaded.score("file_read").is_none());
+    }
+
+    #[test]
+    fn test_persisted_events_are_capped_per_tool() {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let tracker = ToolReliabilityTracker::new();
+        tracker.attach_database(pool.clone()).unwrap();
+        for _ in 0..WINDOW_SIZE + PRUNE_EVERY {
+            tracker.record("file_read", None, true, 5, None);
+        }
+        tracker.record("file_write", None, true, 5, None);
+
+        let count = |tool_id: &str| -> i64 {
+            pool.get()
+                .unwrap()
+                .query_row(
+                    "SELECT COUNT(*) FROM tool_reliability_events WHERE tool_id = ?1",
+                    params![tool_id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(count("file_read"), WINDOW_SIZE as i64);
+        assert_eq!(count("file_write"), 1);
+    }
+}
@@ -88,6 +88,12 @@ impl ToolRegistry {
         _automation: Arc<AutomationService>,
         _router: Arc<tokio::sync::Mutex<LLMRouter>>,
     ) -> Result<()> {
+        self.register_builtin_tools()
+    }
+
+    /// Register the built-in tool catalogue. It needs no services, so callers
+    /// that only ask which tools exist can build a registry without them.
+    pub fn register_builtin_tools(&self) -> Result<()> {
         // File Operations
         self.register_tool(Tool {
             id: "file_read".to_string(),
@@ -300,6 +306,227 @@ impl ToolRegistry {
                     description: "CSS selector for the element to click".to_string(),
                     default: None,
                 },
+                ToolParameter {
+                    name: "fallbacks".to_string(),
+                    parameter_type: ParameterType::Array,
+                    required: false,
+                    description: "Other ways to find the element (test id, label, role and name, text), tried when the selector no longer matches".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "description".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "What the element is, e.g. 'button \"Export\"'".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "tab_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Tab ID (uses first tab if not provided)".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 50,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "browser_type".to_string(),
+            name: "Type Into Browser Element".to_string(),
+            description: "Type text into an input, textarea or editable element in the browser".to_string(),
+            capabilities: vec![ToolCapability::BrowserAutomation],
+            parameters: vec![
+                ToolParameter {
+                    name: "selector".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "CSS selector for the field".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "fallbacks".to_string(),
+                    parameter_type: ParameterType::Array,
+                    required: false,
+                    description: "Other ways to find the element (test id, label, role and name, text), tried when the selector no longer matches".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "description".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "What the element is, e.g. 'button \"Export\"'".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "text".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Text to enter".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "clear_first".to_string(),
+                    parameter_type: ParameterType::Boolean,
+                    required: false,
+                    description: "Clear the field before typing (defaults to false)".to_string(),
+                    default: Some(serde_json::json!(false)),
+                },
+                ToolParameter {
+                    name: "tab_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Tab ID (uses first tab if not provided)".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 50,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "browser_select".to_string(),
+            name: "Select Browser Option".to_string(),
+            description: "Choose an option in a dropdown in the browser".to_string(),
+            capabilities: vec![ToolCapability::BrowserAutomation],
+            parameters: vec![
+                ToolParameter {
+                    name: "selector".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "CSS selector for the select element".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "fallbacks".to_string(),
+                    parameter_type: ParameterType::Array,
+                    required: false,
+                    description: "Other ways to find the element (test id, label, role and name, text), tried when the selector no longer matches".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "description".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "What the element is, e.g. 'button \"Export\"'".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "value".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Value of the option to choose".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "tab_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Tab ID (uses first tab if not provided)".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 50,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "browser_check".to_string(),
+            name: "Check Browser Checkbox".to_string(),
+            description: "Check or uncheck a checkbox or radio button in the browser".to_string(),
+            capabilities: vec![ToolCapability::BrowserAutomation],
+            parameters: vec![
+                ToolParameter {
+                    name: "selector".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "CSS selector for the checkbox or radio button".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "fallbacks".to_string(),
+                    parameter_type: ParameterType::Array,
+                    required: false,
+                    description: "Other ways to find the element (test id, label, role and name, text), tried when the selector no longer matches".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "description".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "What the element is, e.g. 'button \"Export\"'".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "checked".to_string(),
+                    parameter_type: ParameterType::Boolean,
+                    required: false,
+                    description: "Whether it should end up checked (defaults to true)".to_string(),
+                    default: Some(serde_json::json!(true)),
+                },
+                ToolParameter {
+                    name: "tab_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Tab ID (uses first tab if not provided)".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 50,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "browser_press_key".to_string(),
+            name: "Press Key In Browser".to_string(),
+            description: "Press a key such as Enter or Escape in the browser, optionally after focusing an element".to_string(),
+            capabilities: vec![ToolCapability::BrowserAutomation],
+            parameters: vec![
+                ToolParameter {
+                    name: "key".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Key to press: Enter, Tab, Escape, Backspace, ArrowUp, ArrowDown, or a letter or digit".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "selector".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "CSS selector for the element to focus first".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "fallbacks".to_string(),
+                    parameter_type: ParameterType::Array,
+                    required: false,
+                    description: "Other ways to find the element (test id, label, role and name, text), tried when the selector no longer matches".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "description".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "What the element is, e.g. 'button \"Export\"'".to_string(),
+                    default: None,
+                },
                 ToolParameter {
                     name: "tab_id".to_string(),
                     parameter_type: ParameterType::String,
@@ -359,6 +586,52 @@ impl ToolRegistry {
             dependencies: vec![],
         })?;
 
+        self.register_tool(Tool {
+            id: "browser_wait_for_download".to_string(),
+            name: "Wait for Browser Download".to_string(),
+            description:
+                "Wait for the next file the browser downloads and return its path and SHA-256"
+                    .to_string(),
+            capabilities: vec![ToolCapability::BrowserAutomation, ToolCapability::FileRead],
+            parameters: vec![
+                ToolParameter {
+                    name: "timeout_ms".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "How long to wait (defaults to 60000)".to_string(),
+                    default: Some(serde_json::json!(60000)),
+                },
+                ToolParameter {
+                    name: "file_name".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Only wait for a file whose name contains this".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "sha256".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Expected SHA-256 of the file; a mismatch fails the call"
+                        .to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "tab_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Tab ID (uses first tab if not provided)".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 20,
+                network_mb: 5.0,
+            },
+            dependencies: vec!["browser_click".to_string()],
+        })?;
+
         // Code Execution
         self.register_tool(Tool {
             id: "code_execute".to_string(),
@@ -587,6 +860,42 @@ impl ToolRegistry {
             dependencies: vec![],
         })?;
 
+        self.register_tool(Tool {
+            id: "web_fetch".to_string(),
+            name: "Fetch Web Page".to_string(),
+            description: "Fetch a web page and return its main content as Markdown with title and final URL for citation; respects robots.txt. Optionally index it into a project collection".to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::TextProcessing],
+            parameters: vec![
+                ToolParameter {
+                    name: "url".to_string(),
+                    parameter_type: ParameterType::URL,
+                    required: true,
+                    description: "Page URL (http or https)".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "collection_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Project collection to index the page into".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "max_bytes".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Largest page body to download".to_string(),
+                    default: Some(serde_json::Value::Number(serde_json::Number::from(2 * 1024 * 1024))),
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 3.0,
+                memory_mb: 20,
+                network_mb: 2.0,
+            },
+            dependencies: vec![],
+        })?;
+
         self.register_tool(Tool {
             id: "api_upload".to_string(),
             name: "Upload File via API".to_string(),
@@ -638,130 +947,499 @@ impl ToolRegistry {
         })?;
 
         self.register_tool(Tool {
-            id: "api_download".to_string(),
-            name: "Download File via API".to_string(),
-            description: "Download a file from a URL with authentication support".to_string(),
-            capabilities: vec![ToolCapability::APICall, ToolCapability::NetworkOperation, ToolCapability::FileWrite],
+            id: "api_download".to_string(),
+            name: "Download File via API".to_string(),
+            description: "Download a file from a URL with authentication support".to_string(),
+            capabilities: vec![ToolCapability::APICall, ToolCapability::NetworkOperation, ToolCapability::FileWrite],
+            parameters: vec![
+                ToolParameter {
+                    name: "url".to_string(),
+                    parameter_type: ParameterType::URL,
+                    required: true,
+                    description: "File download URL".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "save_path".to_string(),
+                    parameter_type: ParameterType::FilePath,
+                    required: true,
+                    description: "Local path to save the downloaded file".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "auth".to_string(),
+                    parameter_type: ParameterType::Object,
+                    required: false,
+                    description: "Authentication: {type: 'bearer'|'basic'|'apikey', token/username/password/key/header}".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 100,
+                network_mb: 50.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        // Image Processing
+        self.register_tool(Tool {
+            id: "image_ocr".to_string(),
+            name: "OCR Image".to_string(),
+            description: "Extract text from image using OCR".to_string(),
+            capabilities: vec![
+                ToolCapability::ImageProcessing,
+                ToolCapability::TextProcessing,
+            ],
+            parameters: vec![ToolParameter {
+                name: "image_path".to_string(),
+                parameter_type: ParameterType::FilePath,
+                required: true,
+                description: "Path to image file".to_string(),
+                default: None,
+            }],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 30.0,
+                memory_mb: 200,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "image_analyze".to_string(),
+            name: "Analyze Image with AI".to_string(),
+            description: "Analyze an image using vision-capable AI models to answer questions or describe content".to_string(),
+            capabilities: vec![
+                ToolCapability::ImageProcessing,
+                ToolCapability::Planning,
+            ],
+            parameters: vec![
+                ToolParameter {
+                    name: "image_path".to_string(),
+                    parameter_type: ParameterType::FilePath,
+                    required: true,
+                    description: "Path to image file (PNG, JPEG, WEBP)".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "question".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Question to ask about the image or description request".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "detail".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Detail level: 'low', 'high', or 'auto' (default: 'auto')".to_string(),
+                    default: Some(serde_json::json!("auto")),
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 20.0,
+                memory_mb: 150,
+                network_mb: 5.0, // API call to vision model
+            },
+            dependencies: vec![],
+        })?;
+
+        // Code Analysis
+        self.register_tool(Tool {
+            id: "code_analyze".to_string(),
+            name: "Analyze Code".to_string(),
+            description: "Analyze code structure and semantics".to_string(),
+            capabilities: vec![ToolCapability::CodeAnalysis, ToolCapability::TextProcessing],
+            parameters: vec![
+                ToolParameter {
+                    name: "code".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Code to analyze".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "language".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Programming language".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 15.0,
+                memory_mb: 150,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        // Dependency impact analysis
+        self.register_tool(Tool {
+            id: "code_impact_analysis".to_string(),
+            name: "Code Impact Analysis".to_string(),
+            description: "List the files that depend on a workspace file, directly or transitively, and the import cycles it is part of. Consult before large refactors".to_string(),
+            capabilities: vec![ToolCapability::CodeAnalysis],
+            parameters: vec![ToolParameter {
+                name: "path".to_string(),
+                parameter_type: ParameterType::FilePath,
+                required: true,
+                description: "File that is about to change".to_string(),
+                default: None,
+            }],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 5.0,
+                memory_mb: 20,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "dependency_scan".to_string(),
+            name: "Dependency Scan".to_string(),
+            description: "Check a workspace's Cargo, npm and Python dependencies against the local vulnerability database and flag licenses that conflict with the project's license".to_string(),
+            capabilities: vec![ToolCapability::CodeAnalysis],
+            parameters: vec![ToolParameter {
+                name: "path".to_string(),
+                parameter_type: ParameterType::FilePath,
+                required: true,
+                description: "Workspace root".to_string(),
+                default: None,
+            }],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 10.0,
+                memory_mb: 50,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "project_detect_tasks".to_string(),
+            name: "Detect Project Tasks".to_string(),
+            description: "List the build, test and run tasks a workspace defines (Cargo, npm scripts, Makefile targets, Gradle)".to_string(),
+            capabilities: vec![ToolCapability::CodeAnalysis],
+            parameters: vec![ToolParameter {
+                name: "path".to_string(),
+                parameter_type: ParameterType::FilePath,
+                required: true,
+                description: "Workspace root".to_string(),
+                default: None,
+            }],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 2.0,
+                memory_mb: 10,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "project_run_task".to_string(),
+            name: "Run Project Task".to_string(),
+            description: "Run a workspace task by name from project_detect_tasks, or by kind: build, test, run or lint".to_string(),
+            capabilities: vec![ToolCapability::CodeExecution, ToolCapability::SystemOperation],
+            parameters: vec![
+                ToolParameter {
+                    name: "path".to_string(),
+                    parameter_type: ParameterType::FilePath,
+                    required: true,
+                    description: "Workspace root".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "name".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Task name (e.g. cargo:test) or kind (build, test, run, lint)".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "timeout_secs".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Stop the task after this many seconds (default 600)".to_string(),
+                    default: Some(serde_json::json!(600)),
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 50.0,
+                memory_mb: 500,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        // Slack tools for answering inbound messaging://slack-event messages
+        self.register_tool(Tool {
+            id: "slack_reply".to_string(),
+            name: "Slack Reply".to_string(),
+            description:
+                "Post a message to a Slack channel, or reply in a thread when thread_ts is given"
+                    .to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::APICall],
+            parameters: vec![
+                ToolParameter {
+                    name: "connection_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Slack connection; optional when only one workspace is connected"
+                        .to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "channel".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Channel ID from the Slack event".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "text".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Message text (Slack mrkdwn)".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "thread_ts".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Timestamp of the thread's parent message".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.1,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "slack_react".to_string(),
+            name: "Slack React".to_string(),
+            description: "Add an emoji reaction to a Slack message".to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::APICall],
+            parameters: vec![
+                ToolParameter {
+                    name: "connection_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Slack connection; optional when only one workspace is connected"
+                        .to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "channel".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Channel ID from the Slack event".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "ts".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Timestamp of the message to react to".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "emoji".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Emoji name, e.g. eyes or white_check_mark".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.1,
+            },
+            dependencies: vec![],
+        })?;
+
+        self.register_tool(Tool {
+            id: "slack_open_thread".to_string(),
+            name: "Slack Open Thread".to_string(),
+            description: "Read a Slack thread's parent message and replies".to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::APICall],
+            parameters: vec![
+                ToolParameter {
+                    name: "connection_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Slack connection; optional when only one workspace is connected"
+                        .to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "channel".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Channel ID from the Slack event".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "thread_ts".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Timestamp of the thread's parent message".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "limit".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Maximum messages to return (default 50)".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.1,
+            },
+            dependencies: vec![],
+        })?;
+
+        // Cross-platform messaging tools (Slack, Teams, WhatsApp)
+        self.register_tool(Tool {
+            id: "messaging_send".to_string(),
+            name: "Messaging Send".to_string(),
+            description:
+                "Send a message on Slack, Teams or WhatsApp, in a thread when thread_id is given"
+                    .to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::APICall],
             parameters: vec![
                 ToolParameter {
-                    name: "url".to_string(),
-                    parameter_type: ParameterType::URL,
+                    name: "connection_id".to_string(),
+                    parameter_type: ParameterType::String,
                     required: true,
-                    description: "File download URL".to_string(),
+                    description: "Messaging connection (account) to use".to_string(),
                     default: None,
                 },
                 ToolParameter {
-                    name: "save_path".to_string(),
-                    parameter_type: ParameterType::FilePath,
+                    name: "channel_id".to_string(),
+                    parameter_type: ParameterType::String,
                     required: true,
-                    description: "Local path to save the downloaded file".to_string(),
+                    description:
+                        "Slack channel ID, Teams team_id/channel_id, or WhatsApp phone number"
+                            .to_string(),
                     default: None,
                 },
                 ToolParameter {
-                    name: "auth".to_string(),
-                    parameter_type: ParameterType::Object,
+                    name: "text".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Message text".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "thread_id".to_string(),
+                    parameter_type: ParameterType::String,
                     required: false,
-                    description: "Authentication: {type: 'bearer'|'basic'|'apikey', token/username/password/key/header}".to_string(),
+                    description: "Thread to reply in, from a previous send or poll".to_string(),
                     default: None,
                 },
             ],
             estimated_resources: ResourceUsage {
-                cpu_percent: 5.0,
-                memory_mb: 100,
-                network_mb: 50.0,
-            },
-            dependencies: vec![],
-        })?;
-
-        // Image Processing
-        self.register_tool(Tool {
-            id: "image_ocr".to_string(),
-            name: "OCR Image".to_string(),
-            description: "Extract text from image using OCR".to_string(),
-            capabilities: vec![
-                ToolCapability::ImageProcessing,
-                ToolCapability::TextProcessing,
-            ],
-            parameters: vec![ToolParameter {
-                name: "image_path".to_string(),
-                parameter_type: ParameterType::FilePath,
-                required: true,
-                description: "Path to image file".to_string(),
-                default: None,
-            }],
-            estimated_resources: ResourceUsage {
-                cpu_percent: 30.0,
-                memory_mb: 200,
-                network_mb: 0.0,
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.1,
             },
             dependencies: vec![],
         })?;
 
         self.register_tool(Tool {
-            id: "image_analyze".to_string(),
-            name: "Analyze Image with AI".to_string(),
-            description: "Analyze an image using vision-capable AI models to answer questions or describe content".to_string(),
-            capabilities: vec![
-                ToolCapability::ImageProcessing,
-                ToolCapability::Planning,
-            ],
+            id: "messaging_list_threads".to_string(),
+            name: "Messaging List Threads".to_string(),
+            description: "List recent conversations across messaging platforms, newest first"
+                .to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::APICall],
             parameters: vec![
                 ToolParameter {
-                    name: "image_path".to_string(),
-                    parameter_type: ParameterType::FilePath,
-                    required: true,
-                    description: "Path to image file (PNG, JPEG, WEBP)".to_string(),
+                    name: "connection_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Only list threads of this messaging connection".to_string(),
                     default: None,
                 },
                 ToolParameter {
-                    name: "question".to_string(),
+                    name: "platform".to_string(),
                     parameter_type: ParameterType::String,
-                    required: true,
-                    description: "Question to ask about the image or description request".to_string(),
+                    required: false,
+                    description: "slack, teams or whatsapp".to_string(),
                     default: None,
                 },
                 ToolParameter {
-                    name: "detail".to_string(),
-                    parameter_type: ParameterType::String,
+                    name: "limit".to_string(),
+                    parameter_type: ParameterType::Integer,
                     required: false,
-                    description: "Detail level: 'low', 'high', or 'auto' (default: 'auto')".to_string(),
-                    default: Some(serde_json::json!("auto")),
+                    description: "Maximum threads to return (default 20)".to_string(),
+                    default: None,
                 },
             ],
             estimated_resources: ResourceUsage {
-                cpu_percent: 20.0,
-                memory_mb: 150,
-                network_mb: 5.0, // API call to vision model
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.1,
             },
             dependencies: vec![],
         })?;
 
-        // Code Analysis
         self.register_tool(Tool {
-            id: "code_analyze".to_string(),
-            name: "Analyze Code".to_string(),
-            description: "Analyze code structure and semantics".to_string(),
-            capabilities: vec![ToolCapability::CodeAnalysis, ToolCapability::TextProcessing],
+            id: "messaging_poll".to_string(),
+            name: "Messaging Poll".to_string(),
+            description: "Get inbound messages received since the cursor of the previous poll"
+                .to_string(),
+            capabilities: vec![ToolCapability::NetworkOperation, ToolCapability::APICall],
             parameters: vec![
                 ToolParameter {
-                    name: "code".to_string(),
+                    name: "connection_id".to_string(),
                     parameter_type: ParameterType::String,
                     required: true,
-                    description: "Code to analyze".to_string(),
+                    description: "Messaging connection (account) to use".to_string(),
                     default: None,
                 },
                 ToolParameter {
-                    name: "language".to_string(),
+                    name: "channel_id".to_string(),
                     parameter_type: ParameterType::String,
-                    required: true,
-                    description: "Programming language".to_string(),
+                    required: false,
+                    description: "Channel to fetch new Slack or Teams messages from".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "thread_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Only return messages in this thread".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "cursor".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "cursor from the previous poll; omit for the latest messages"
+                        .to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "limit".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Maximum messages to return (default 50)".to_string(),
                     default: None,
                 },
             ],
             estimated_resources: ResourceUsage {
-                cpu_percent: 15.0,
-                memory_mb: 150,
-                network_mb: 0.0,
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.1,
             },
             dependencies: vec![],
         })?;
@@ -1021,6 +1699,14 @@ impl ToolRegistry {
                     description: "Productivity provider (notion, trello, asana)".to_string(),
                     default: None,
                 },
+                ToolParameter {
+                    name: "account_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Connected account; optional when the provider has only one"
+                        .to_string(),
+                    default: None,
+                },
                 ToolParameter {
                     name: "title".to_string(),
                     parameter_type: ParameterType::String,
@@ -1260,6 +1946,14 @@ impl ToolRegistry {
                         .to_string(),
                     default: Some(serde_json::json!(60000)),
                 },
+                ToolParameter {
+                    name: "dry_run".to_string(),
+                    parameter_type: ParameterType::Boolean,
+                    required: false,
+                    description: "Return what would execute and whether it needs approval, without running it"
+                        .to_string(),
+                    default: Some(serde_json::json!(false)),
+                },
             ],
             estimated_resources: ResourceUsage {
                 cpu_percent: 10.0,
@@ -1458,6 +2152,185 @@ impl ToolRegistry {
             dependencies: vec!["browser_navigate".to_string(), "ui_click".to_string()],
         })?;
 
+        // Blackboard shared by agents of the same orchestrated run
+        self.register_tool(Tool {
+            id: "blackboard_write".to_string(),
+            name: "Write to Blackboard".to_string(),
+            description: "Share a value with the other agents of this run under a key".to_string(),
+            capabilities: vec![ToolCapability::DataAnalysis],
+            parameters: vec![
+                ToolParameter {
+                    name: "key".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Key to write".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "value".to_string(),
+                    parameter_type: ParameterType::Object,
+                    required: true,
+                    description: "JSON value to store".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "mode".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description:
+                        "\"replace\" (last write wins) or \"append\" (keep a list of values)"
+                            .to_string(),
+                    default: Some(serde_json::json!("replace")),
+                },
+                ToolParameter {
+                    name: "expected_version".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Only write if the entry is still at this version (0 when new)"
+                        .to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+        self.register_tool(Tool {
+            id: "blackboard_read".to_string(),
+            name: "Read from Blackboard".to_string(),
+            description: "Read a value another agent of this run shared, or list everything shared when no key is given".to_string(),
+            capabilities: vec![ToolCapability::DataAnalysis],
+            parameters: vec![
+                ToolParameter {
+                    name: "key".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "Key to read".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "after_version".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Only return the entry once it is newer than this version".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "wait_secs".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Seconds to wait for another agent to write it if it is missing".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+        self.register_tool(Tool {
+            id: "blackboard_put_artifact".to_string(),
+            name: "Share Artifact".to_string(),
+            description:
+                "Share a document (notes, draft, report) with the other agents of this run"
+                    .to_string(),
+            capabilities: vec![ToolCapability::DataAnalysis],
+            parameters: vec![
+                ToolParameter {
+                    name: "name".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Artifact name".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "content".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Artifact content".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "content_type".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    description: "MIME type of the content".to_string(),
+                    default: Some(serde_json::json!("text/markdown")),
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+        self.register_tool(Tool {
+            id: "blackboard_get_artifact".to_string(),
+            name: "Get Shared Artifact".to_string(),
+            description: "Read a document another agent of this run shared".to_string(),
+            capabilities: vec![ToolCapability::DataAnalysis],
+            parameters: vec![
+                ToolParameter {
+                    name: "name".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Artifact name".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "wait_secs".to_string(),
+                    parameter_type: ParameterType::Integer,
+                    required: false,
+                    description: "Seconds to wait for another agent to write it if it is missing"
+                        .to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 1.0,
+                memory_mb: 5,
+                network_mb: 0.0,
+            },
+            dependencies: vec![],
+        })?;
+
+        // Saved skills run as a single step
+        self.register_tool(Tool {
+            id: super::skills::RUN_SKILL_TOOL.to_string(),
+            name: "Run Skill".to_string(),
+            description: "Run a saved skill (a learned sequence of steps) with the given arguments"
+                .to_string(),
+            capabilities: vec![ToolCapability::Learning],
+            parameters: vec![
+                ToolParameter {
+                    name: "skill_id".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    description: "Id or name of the skill".to_string(),
+                    default: None,
+                },
+                ToolParameter {
+                    name: "args".to_string(),
+                    parameter_type: ParameterType::Object,
+                    required: false,
+                    description: "Values for the skill's parameters".to_string(),
+                    default: None,
+                },
+            ],
+            estimated_resources: ResourceUsage {
+                cpu_percent: 10.0,
+                memory_mb: 100,
+                network_mb: 1.0,
+            },
+            dependencies: vec![],
+        })?;
+
         Ok(())
     }
 
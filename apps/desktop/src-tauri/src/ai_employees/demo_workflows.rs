@@ -341,16 +341,28 @@ pub fn code_reviewer_demo() -> DemoWorkflow {
     step1_input.insert("pr_number".to_string(), "123".to_string());
     step1_input.insert("repo".to_string(), "company/backend".to_string());
 
+    let mut step2_input = HashMap::new();
+    step2_input.insert("path".to_string(), "company/backend".to_string());
+
     DemoWorkflow {
         title: "Review Pull Request".to_string(),
-        steps: vec![DemoStep {
-            description: "Analyze code changes".to_string(),
-            tool: "code_analyzer".to_string(),
-            input: step1_input,
-            expected_result:
-                "Found: 2 style issues, 1 security concern (SQL injection risk), 3 suggestions"
+        steps: vec![
+            DemoStep {
+                description: "Analyze code changes".to_string(),
+                tool: "code_analyzer".to_string(),
+                input: step1_input,
+                expected_result:
+                    "Found: 2 style issues, 1 security concern (SQL injection risk), 3 suggestions"
+                        .to_string(),
+            },
+            DemoStep {
+                description: "Scan dependencies for vulnerabilities and licenses".to_string(),
+                tool: "dependency_scan".to_string(),
+                input: step2_input,
+                expected_result: "1 high severity advisory in a new dependency, licenses OK"
                     .to_string(),
-        }],
+            },
+        ],
         sample_input: "PR #123: Add user authentication endpoint (150 lines changed)".to_string(),
         expected_output: "Review posted with 6 comments: 2 blocking issues, 4 suggestions"
             .to_string(),
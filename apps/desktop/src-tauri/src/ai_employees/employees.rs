@@ -360,6 +360,7 @@ fn create_code_reviewer() -> AIEmployee {
         description: "Reviews pull requests for code quality, security issues, style violations, and suggests improvements following best practices.".to_string(),
         capabilities: vec![
             "Review PRs for quality and security".to_string(),
+            "Scan dependencies for vulnerabilities and license conflicts".to_string(),
             "Check code style compliance".to_string(),
             "Suggest performance improvements".to_string(),
             "Identify potential bugs".to_string(),
@@ -544,6 +545,7 @@ fn create_research_assistant() -> AIEmployee {
         description: "Conducts research on topics, summarizes findings, cites sources, and compiles comprehensive reports.".to_string(),
         capabilities: vec![
             "Search multiple sources".to_string(),
+            "Read and cite live web pages".to_string(),
             "Summarize key findings".to_string(),
             "Cite sources properly".to_string(),
             "Compile research reports".to_string(),
@@ -552,7 +554,7 @@ fn create_research_assistant() -> AIEmployee {
         estimated_time_saved_per_run: 90, // 90 minutes per research task
         estimated_cost_saved_per_run: 75.00, // $50/hr rate
         demo_workflow: Some(super::demo_workflows::research_assistant_demo()),
-        required_integrations: vec!["web_search".to_string()],
+        required_integrations: vec!["web_search".to_string(), "web_fetch".to_string()],
         template_id: Some("research-assistant-template".to_string()),
         is_verified: true,
         usage_count: 0,
@@ -1,8 +1,8 @@
 use super::*;
 use crate::agi::tools::ToolRegistry;
+use crate::db::Pool;
 use crate::router::{LLMRouter, Provider};
 use chrono::Utc;
-use rusqlite::Connection;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -10,18 +10,14 @@ use uuid::Uuid;
 
 /// AI Employee Executor manages task execution and demo mode
 pub struct AIEmployeeExecutor {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
     llm_router: Arc<Mutex<LLMRouter>>,
     tools: Arc<ToolRegistry>,
 }
 
 impl AIEmployeeExecutor {
     /// Create a new executor instance
-    pub fn new(
-        db: Arc<Mutex<Connection>>,
-        llm_router: Arc<Mutex<LLMRouter>>,
-        tools: Arc<ToolRegistry>,
-    ) -> Self {
+    pub fn new(db: Pool, llm_router: Arc<Mutex<LLMRouter>>, tools: Arc<ToolRegistry>) -> Self {
         Self {
             db,
             llm_router,
@@ -31,10 +27,9 @@ impl AIEmployeeExecutor {
 
     /// Hire an employee for a user
     pub async fn hire(&self, employee_id: &str, user_id: &str) -> Result<String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         // Check if employee exists
         let exists: bool = conn
@@ -85,10 +80,9 @@ impl AIEmployeeExecutor {
 
     /// Fire (deactivate) an employee
     pub async fn fire(&self, user_employee_id: &str) -> Result<()> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         conn.execute(
             "UPDATE user_employees SET is_active = 0 WHERE id = ?1",
@@ -149,10 +143,9 @@ impl AIEmployeeExecutor {
         }
 
         // Store task in database
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let input_json = serde_json::to_string(&input_data).unwrap_or_default();
 
@@ -172,8 +165,8 @@ impl AIEmployeeExecutor {
 
         // Update status to Running
         {
-            let conn = self.db.lock().map_err(|e| {
-                EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e))
+            let conn = self.db.get().map_err(|e| {
+                EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
             })?;
 
             conn.execute(
@@ -185,8 +178,8 @@ impl AIEmployeeExecutor {
 
         // Load task details
         let (task_type, input_json, user_employee_id, employee_id) = {
-            let conn = self.db.lock().map_err(|e| {
-                EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e))
+            let conn = self.db.get().map_err(|e| {
+                EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
             })?;
 
             let result: std::result::Result<(String, String, String, String), rusqlite::Error> =
@@ -220,8 +213,8 @@ impl AIEmployeeExecutor {
 
         // Calculate estimated time/cost saved (retrieve from employee definition)
         let (time_saved, cost_saved) = {
-            let conn = self.db.lock().map_err(|e| {
-                EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e))
+            let conn = self.db.get().map_err(|e| {
+                EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
             })?;
 
             let result: std::result::Result<(i64, f64), rusqlite::Error> = conn.query_row(
@@ -235,8 +228,8 @@ impl AIEmployeeExecutor {
 
         // Update task as completed
         {
-            let conn = self.db.lock().map_err(|e| {
-                EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e))
+            let conn = self.db.get().map_err(|e| {
+                EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
             })?;
 
             let output_json = serde_json::to_string(&output).unwrap_or_default();
@@ -274,8 +267,8 @@ impl AIEmployeeExecutor {
 
         // Load employee details
         let (demo_json, estimated_time, estimated_cost) = {
-            let conn = self.db.lock().map_err(|e| {
-                EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e))
+            let conn = self.db.get().map_err(|e| {
+                EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
             })?;
 
             let result: std::result::Result<(Option<String>, i64, f64), rusqlite::Error> = conn.query_row(
@@ -340,10 +333,9 @@ impl AIEmployeeExecutor {
 
     /// Get task status
     pub async fn get_task_status(&self, task_id: &str) -> Result<EmployeeTask> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let result: std::result::Result<EmployeeTask, rusqlite::Error> = conn.query_row(
             "SELECT id, user_employee_id, task_type, input_data, output_data, time_saved_minutes, cost_saved_usd, started_at, completed_at, status
@@ -389,10 +381,9 @@ impl AIEmployeeExecutor {
 
     /// List all tasks for a user employee
     pub async fn list_tasks(&self, user_employee_id: &str) -> Result<Vec<EmployeeTask>> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let mut stmt = conn
             .prepare(
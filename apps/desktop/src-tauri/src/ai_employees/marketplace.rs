@@ -1,24 +1,22 @@
 use super::*;
-use rusqlite::Connection;
-use std::sync::{Arc, Mutex};
+use crate::db::Pool;
 
 /// Employee Marketplace for browsing, searching, and publishing employees
 pub struct EmployeeMarketplace {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
 }
 
 impl EmployeeMarketplace {
     /// Create a new marketplace instance
-    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self { db }
     }
 
     /// Get featured employees (top-rated, most-used)
     pub fn get_featured_employees(&self) -> Result<Vec<AIEmployee>> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let mut stmt = conn
             .prepare(
@@ -49,10 +47,9 @@ impl EmployeeMarketplace {
         query: &str,
         filters: EmployeeFilters,
     ) -> Result<Vec<AIEmployee>> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let mut sql = String::from(
             "SELECT id, name, role, description, capabilities, estimated_time_saved, estimated_cost_saved,
@@ -127,10 +124,9 @@ impl EmployeeMarketplace {
 
     /// Get employee by ID
     pub fn get_employee_by_id(&self, employee_id: &str) -> Result<AIEmployee> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let result = conn.query_row(
             "SELECT id, name, role, description, capabilities, estimated_time_saved, estimated_cost_saved,
@@ -145,10 +141,9 @@ impl EmployeeMarketplace {
 
     /// Get statistics for an employee
     pub fn get_employee_stats(&self, employee_id: &str) -> Result<EmployeeStats> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         // Get aggregate stats
         let (total_hires, total_tasks, total_time_mins, total_cost, avg_rating): (
@@ -193,10 +188,9 @@ impl EmployeeMarketplace {
 
     /// Publish a new employee (created by user)
     pub fn publish_employee(&self, employee: AIEmployee, creator_id: &str) -> Result<String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let capabilities_json = serde_json::to_string(&employee.capabilities).unwrap_or_default();
         let demo_json = employee
@@ -238,10 +232,9 @@ impl EmployeeMarketplace {
 
     /// Update an existing employee configuration
     pub fn update_employee(&self, employee_id: &str, employee: AIEmployee) -> Result<()> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         // First verify the employee exists
         let exists: bool = conn
@@ -302,10 +295,9 @@ impl EmployeeMarketplace {
 
     /// Delete a custom employee
     pub fn delete_employee(&self, employee_id: &str) -> Result<()> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         // First verify the employee exists
         let exists: bool = conn
@@ -337,10 +329,9 @@ impl EmployeeMarketplace {
         creator_id: &str,
         is_public: bool,
     ) -> Result<String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         // First verify the employee exists and belongs to the creator
         let (exists, current_creator): (bool, Option<String>) = conn
@@ -385,10 +376,9 @@ impl EmployeeMarketplace {
 
     /// Get all employees by category
     pub fn get_employees_by_category(&self, category: &str) -> Result<Vec<AIEmployee>> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let mut stmt = conn
             .prepare(
@@ -415,10 +405,9 @@ impl EmployeeMarketplace {
 
     /// Get user's hired employees
     pub fn get_user_employees(&self, user_id: &str) -> Result<Vec<UserEmployee>> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let mut stmt = conn
             .prepare(
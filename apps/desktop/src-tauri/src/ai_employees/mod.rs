@@ -2,6 +2,7 @@ pub mod demo_workflows;
 pub mod employees;
 pub mod executor;
 pub mod marketplace;
+pub mod pipelines;
 pub mod registry;
 
 use serde::{Deserialize, Serialize};
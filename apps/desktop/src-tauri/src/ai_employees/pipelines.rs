@@ -0,0 +1,796 @@
+use super::executor::AIEmployeeExecutor;
+use super::*;
+use crate::db::pool::{Pool, PooledConnection};
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// A chain of hired employees where each stage's output feeds the next
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmployeePipeline {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub stages: Vec<PipelineStage>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// One step of a pipeline, run by a hired employee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStage {
+    /// Unique within the pipeline; later stages reference it as `$stages.<name>`
+    pub name: String,
+    pub user_employee_id: String,
+    pub task_type: String,
+    /// Task input key -> `$input.<path>`, `$previous.<path>`, `$stages.<name>.<path>`
+    /// or a literal value. When empty the previous stage's output (or the pipeline
+    /// input for the first stage) is handed over as-is.
+    #[serde(default)]
+    pub input_mapping: HashMap<String, Value>,
+    #[serde(default)]
+    pub budget: StageBudget,
+    /// Pause the run before this stage until it is approved
+    #[serde(default)]
+    pub requires_approval: bool,
+}
+
+/// Limits a stage must stay within for the run to continue
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StageBudget {
+    pub max_duration_secs: Option<u64>,
+    /// Checked against the `cost_usd` the stage's task reports in its output
+    pub max_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineRunStatus {
+    Running,
+    AwaitingApproval,
+    Completed,
+    Failed,
+    Rejected,
+}
+
+impl PipelineRunStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::AwaitingApproval => "awaiting_approval",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StageRunStatus {
+    Pending,
+    AwaitingApproval,
+    Running,
+    Completed,
+    Failed,
+    Rejected,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageApproval {
+    pub approved: bool,
+    pub note: Option<String>,
+    pub decided_at: i64,
+}
+
+/// Per-stage progress and metrics within a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageRun {
+    pub name: String,
+    pub status: StageRunStatus,
+    pub task_id: Option<String>,
+    pub input: Option<HashMap<String, Value>>,
+    pub output: Option<HashMap<String, Value>>,
+    pub approval: Option<StageApproval>,
+    pub started_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub execution_time_seconds: f64,
+    pub time_saved_minutes: u64,
+    pub cost_saved_usd: f64,
+    pub cost_usd: Option<f64>,
+    /// Set when the stage failed because it ran over its budget
+    pub budget_exceeded: Option<String>,
+    pub error: Option<String>,
+}
+
+impl StageRun {
+    fn pending(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: StageRunStatus::Pending,
+            task_id: None,
+            input: None,
+            output: None,
+            approval: None,
+            started_at: None,
+            completed_at: None,
+            execution_time_seconds: 0.0,
+            time_saved_minutes: 0,
+            cost_saved_usd: 0.0,
+            cost_usd: None,
+            budget_exceeded: None,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PipelineRunMetrics {
+    pub stages_completed: usize,
+    pub stages_total: usize,
+    pub execution_time_seconds: f64,
+    pub time_saved_minutes: u64,
+    pub cost_saved_usd: f64,
+    pub cost_usd: f64,
+}
+
+/// A single execution of a pipeline, tracked across all of its stages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineRun {
+    pub id: String,
+    pub pipeline_id: String,
+    pub status: PipelineRunStatus,
+    pub input: HashMap<String, Value>,
+    pub current_stage: usize,
+    pub stages: Vec<StageRun>,
+    pub metrics: PipelineRunMetrics,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub updated_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+impl PipelineRun {
+    fn refresh_metrics(&mut self) {
+        let mut metrics = PipelineRunMetrics {
+            stages_total: self.stages.len(),
+            ..Default::default()
+        };
+        for stage in &self.stages {
+            if stage.status == StageRunStatus::Completed {
+                metrics.stages_completed += 1;
+            }
+            metrics.execution_time_seconds += stage.execution_time_seconds;
+            metrics.time_saved_minutes += stage.time_saved_minutes;
+            metrics.cost_saved_usd += stage.cost_saved_usd;
+            metrics.cost_usd += stage.cost_usd.unwrap_or(0.0);
+        }
+        self.metrics = metrics;
+    }
+
+    fn finish(&mut self, status: PipelineRunStatus, error: Option<String>) {
+        for stage in self.stages.iter_mut().skip(self.current_stage + 1) {
+            stage.status = StageRunStatus::Skipped;
+        }
+        self.status = status;
+        self.error = error;
+        self.completed_at = Some(Utc::now().timestamp());
+    }
+}
+
+/// Creates pipelines and drives their runs through the employee executor
+pub struct EmployeePipelineManager {
+    db: Pool,
+    executor: Arc<AIEmployeeExecutor>,
+}
+
+impl EmployeePipelineManager {
+    pub fn new(db: Pool, executor: Arc<AIEmployeeExecutor>) -> Self {
+        Self { db, executor }
+    }
+
+    fn conn(&self) -> Result<PooledConnection> {
+        self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })
+    }
+
+    /// Validate and store a new pipeline
+    pub fn create(
+        &self,
+        user_id: &str,
+        name: &str,
+        description: Option<String>,
+        stages: Vec<PipelineStage>,
+    ) -> Result<EmployeePipeline> {
+        if name.trim().is_empty() {
+            return Err(EmployeeError::InvalidConfig(
+                "Pipeline name cannot be empty".to_string(),
+            ));
+        }
+        validate_stages(&stages).map_err(EmployeeError::InvalidConfig)?;
+
+        let conn = self.conn()?;
+        for stage in &stages {
+            let hired: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM user_employees WHERE id = ?1 AND user_id = ?2 AND is_active = 1",
+                    [&stage.user_employee_id, user_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| EmployeeError::DatabaseError(e.to_string()))?;
+            if !hired {
+                return Err(EmployeeError::NotFound(format!(
+                    "Hired employee {} for stage '{}'",
+                    stage.user_employee_id, stage.name
+                )));
+            }
+        }
+
+        let now = Utc::now().timestamp();
+        let pipeline = EmployeePipeline {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: name.trim().to_string(),
+            description,
+            stages,
+            created_at: now,
+            updated_at: now,
+        };
+        let stages_json = serde_json::to_string(&pipeline.stages)
+            .map_err(|e| EmployeeError::InvalidConfig(e.to_string()))?;
+
+        conn.execute(
+            "INSERT INTO employee_pipelines (id, user_id, name, description, stages, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                pipeline.id,
+                pipeline.user_id,
+                pipeline.name,
+                pipeline.description,
+                stages_json,
+                now,
+                now
+            ],
+        )
+        .map_err(|e| EmployeeError::DatabaseError(e.to_string()))?;
+
+        Ok(pipeline)
+    }
+
+    pub fn get(&self, pipeline_id: &str) -> Result<EmployeePipeline> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT id, user_id, name, description, stages, created_at, updated_at
+             FROM employee_pipelines WHERE id = ?1",
+            [pipeline_id],
+            |row| {
+                let stages_json: String = row.get(4)?;
+                Ok(EmployeePipeline {
+                    id: row.get(0)?,
+                    user_id: row.get(1)?,
+                    name: row.get(2)?,
+                    description: row.get(3)?,
+                    stages: serde_json::from_str(&stages_json).unwrap_or_default(),
+                    created_at: row.get(5)?,
+                    updated_at: row.get(6)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| EmployeeError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| EmployeeError::NotFound(format!("Pipeline {}", pipeline_id)))
+    }
+
+    /// Start a run and execute stages until it finishes or reaches an approval gate
+    pub async fn execute(
+        &self,
+        pipeline_id: &str,
+        input: HashMap<String, Value>,
+    ) -> Result<PipelineRun> {
+        let pipeline = self.get(pipeline_id)?;
+        let now = Utc::now().timestamp();
+        let mut run = PipelineRun {
+            id: Uuid::new_v4().to_string(),
+            pipeline_id: pipeline.id.clone(),
+            status: PipelineRunStatus::Running,
+            input,
+            current_stage: 0,
+            stages: pipeline
+                .stages
+                .iter()
+                .map(|stage| StageRun::pending(&stage.name))
+                .collect(),
+            metrics: PipelineRunMetrics::default(),
+            error: None,
+            started_at: now,
+            updated_at: now,
+            completed_at: None,
+        };
+        run.refresh_metrics();
+
+        {
+            let conn = self.conn()?;
+            conn.execute(
+                "INSERT INTO employee_pipeline_runs (id, pipeline_id, status, input, current_stage, stages, error, started_at, updated_at, completed_at)
+                 VALUES (?1, ?2, ?3, ?4, 0, ?5, NULL, ?6, ?6, NULL)",
+                params![
+                    run.id,
+                    run.pipeline_id,
+                    run.status.as_str(),
+                    serde_json::to_string(&run.input).unwrap_or_default(),
+                    serde_json::to_string(&run.stages).unwrap_or_default(),
+                    now
+                ],
+            )
+            .map_err(|e| EmployeeError::DatabaseError(e.to_string()))?;
+        }
+
+        self.advance(&pipeline, run).await
+    }
+
+    /// Decide the approval gate a run is waiting on, resuming it when approved
+    pub async fn approve(
+        &self,
+        run_id: &str,
+        approved: bool,
+        note: Option<String>,
+    ) -> Result<PipelineRun> {
+        let mut run = self.status(run_id)?;
+        if run.status != PipelineRunStatus::AwaitingApproval {
+            return Err(EmployeeError::InvalidConfig(format!(
+                "Pipeline run {} is not awaiting approval",
+                run_id
+            )));
+        }
+        let pipeline = self.get(&run.pipeline_id)?;
+
+        let index = run.current_stage;
+        let stage = &mut run.stages[index];
+        stage.approval = Some(StageApproval {
+            approved,
+            note,
+            decided_at: Utc::now().timestamp(),
+        });
+
+        if !approved {
+            stage.status = StageRunStatus::Rejected;
+            let error = format!("Stage '{}' was rejected", stage.name);
+            run.finish(PipelineRunStatus::Rejected, Some(error));
+            self.save_run(&mut run)?;
+            return Ok(run);
+        }
+
+        stage.status = StageRunStatus::Pending;
+        run.status = PipelineRunStatus::Running;
+        self.advance(&pipeline, run).await
+    }
+
+    pub fn status(&self, run_id: &str) -> Result<PipelineRun> {
+        let conn = self.conn()?;
+        let run = conn
+            .query_row(
+                "SELECT id, pipeline_id, status, input, current_stage, stages, error, started_at, updated_at, completed_at
+                 FROM employee_pipeline_runs WHERE id = ?1",
+                [run_id],
+                |row| {
+                    let status: String = row.get(2)?;
+                    let input: String = row.get(3)?;
+                    let stages: String = row.get(5)?;
+                    let current_stage: i64 = row.get(4)?;
+                    Ok(PipelineRun {
+                        id: row.get(0)?,
+                        pipeline_id: row.get(1)?,
+                        status: serde_json::from_value(Value::String(status))
+                            .unwrap_or(PipelineRunStatus::Failed),
+                        input: serde_json::from_str(&input).unwrap_or_default(),
+                        current_stage: current_stage as usize,
+                        stages: serde_json::from_str(&stages).unwrap_or_default(),
+                        metrics: PipelineRunMetrics::default(),
+                        error: row.get(6)?,
+                        started_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                        completed_at: row.get(9)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| EmployeeError::DatabaseError(e.to_string()))?;
+
+        let mut run =
+            run.ok_or_else(|| EmployeeError::NotFound(format!("Pipeline run {}", run_id)))?;
+        run.refresh_metrics();
+        Ok(run)
+    }
+
+    async fn advance(
+        &self,
+        pipeline: &EmployeePipeline,
+        mut run: PipelineRun,
+    ) -> Result<PipelineRun> {
+        while run.current_stage < pipeline.stages.len() {
+            let index = run.current_stage;
+            let stage = &pipeline.stages[index];
+
+            let approved = run.stages[index]
+                .approval
+                .as_ref()
+                .is_some_and(|approval| approval.approved);
+            if stage.requires_approval && !approved {
+                run.stages[index].status = StageRunStatus::AwaitingApproval;
+                run.status = PipelineRunStatus::AwaitingApproval;
+                self.save_run(&mut run)?;
+                return Ok(run);
+            }
+
+            let input = match resolve_stage_input(stage, &run.input, &run.stages[..index]) {
+                Ok(input) => input,
+                Err(error) => {
+                    run.stages[index].status = StageRunStatus::Failed;
+                    run.stages[index].error = Some(error.clone());
+                    run.finish(PipelineRunStatus::Failed, Some(error));
+                    self.save_run(&mut run)?;
+                    return Ok(run);
+                }
+            };
+
+            run.stages[index].status = StageRunStatus::Running;
+            run.stages[index].input = Some(input.clone());
+            run.stages[index].started_at = Some(Utc::now().timestamp());
+            self.save_run(&mut run)?;
+
+            self.run_stage(stage, &mut run.stages[index], input).await;
+
+            let stage_run = &run.stages[index];
+            if stage_run.status != StageRunStatus::Completed {
+                let error = format!(
+                    "Stage '{}' failed: {}",
+                    stage.name,
+                    stage_run.error.as_deref().unwrap_or("unknown error")
+                );
+                run.finish(PipelineRunStatus::Failed, Some(error));
+                self.save_run(&mut run)?;
+                return Ok(run);
+            }
+
+            run.current_stage += 1;
+            self.save_run(&mut run)?;
+        }
+
+        run.current_stage = pipeline.stages.len().saturating_sub(1);
+        run.finish(PipelineRunStatus::Completed, None);
+        self.save_run(&mut run)?;
+        Ok(run)
+    }
+
+    async fn run_stage(
+        &self,
+        stage: &PipelineStage,
+        stage_run: &mut StageRun,
+        input: HashMap<String, Value>,
+    ) {
+        let task = match self
+            .executor
+            .assign_task(&stage.user_employee_id, stage.task_type.clone(), input)
+            .await
+        {
+            Ok(task) => task,
+            Err(e) => {
+                stage_run.status = StageRunStatus::Failed;
+                stage_run.error = Some(e.to_string());
+                stage_run.completed_at = Some(Utc::now().timestamp());
+                return;
+            }
+        };
+        stage_run.task_id = Some(task.id.clone());
+
+        let started = Instant::now();
+        let execution = self.executor.execute_task(&task.id);
+        let outcome = match stage.budget.max_duration_secs {
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), execution)
+                .await
+                .map_err(|_| secs),
+            None => Ok(execution.await),
+        };
+        stage_run.execution_time_seconds = started.elapsed().as_secs_f64();
+        stage_run.completed_at = Some(Utc::now().timestamp());
+
+        let result = match outcome {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                stage_run.status = StageRunStatus::Failed;
+                stage_run.error = Some(e.to_string());
+                return;
+            }
+            Err(secs) => {
+                let exceeded = format!("exceeded the {}s time budget", secs);
+                stage_run.status = StageRunStatus::Failed;
+                stage_run.budget_exceeded = Some(exceeded.clone());
+                stage_run.error = Some(exceeded);
+                return;
+            }
+        };
+
+        stage_run.time_saved_minutes = result.time_saved_minutes;
+        stage_run.cost_saved_usd = result.cost_saved_usd;
+        stage_run.cost_usd = result.output.get("cost_usd").and_then(Value::as_f64);
+        stage_run.output = Some(result.output);
+
+        if result.status != TaskStatus::Completed {
+            stage_run.status = StageRunStatus::Failed;
+            stage_run.error = Some(
+                result
+                    .error
+                    .unwrap_or_else(|| format!("Task ended as {:?}", result.status)),
+            );
+        } else if let Some(exceeded) = check_budget(&stage.budget, stage_run) {
+            stage_run.status = StageRunStatus::Failed;
+            stage_run.budget_exceeded = Some(exceeded.clone());
+            stage_run.error = Some(exceeded);
+        } else {
+            stage_run.status = StageRunStatus::Completed;
+        }
+    }
+
+    fn save_run(&self, run: &mut PipelineRun) -> Result<()> {
+        run.updated_at = Utc::now().timestamp();
+        run.refresh_metrics();
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE employee_pipeline_runs
+             SET status = ?1, current_stage = ?2, stages = ?3, error = ?4, updated_at = ?5, completed_at = ?6
+             WHERE id = ?7",
+            params![
+                run.status.as_str(),
+                run.current_stage as i64,
+                serde_json::to_string(&run.stages).unwrap_or_default(),
+                run.error,
+                run.updated_at,
+                run.completed_at,
+                run.id
+            ],
+        )
+        .map_err(|e| EmployeeError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Check stage names and that mappings only reference earlier stages
+pub fn validate_stages(stages: &[PipelineStage]) -> std::result::Result<(), String> {
+    if stages.is_empty() {
+        return Err("A pipeline needs at least one stage".to_string());
+    }
+
+    for (index, stage) in stages.iter().enumerate() {
+        let name = stage.name.trim();
+        if name.is_empty() || name.contains('.') {
+            return Err(format!("Stage {} needs a name without dots", index + 1));
+        }
+        if stages[..index]
+            .iter()
+            .any(|earlier| earlier.name == stage.name)
+        {
+            return Err(format!("Duplicate stage name '{}'", stage.name));
+        }
+        if stage.task_type.trim().is_empty() {
+            return Err(format!("Stage '{}' needs a task type", stage.name));
+        }
+
+        for source in stage.input_mapping.values() {
+            let Some(reference) = source.as_str().and_then(|s| s.strip_prefix('$')) else {
+                continue;
+            };
+            let mut parts = reference.splitn(3, '.');
+            match parts.next() {
+                Some("input") => {}
+                Some("previous") if index > 0 => {}
+                Some("previous") => {
+                    return Err(format!(
+                        "Stage '{}' references $previous but is the first stage",
+                        stage.name
+                    ))
+                }
+                Some("stages") => {
+                    let target = parts.next().unwrap_or_default();
+                    if !stages[..index].iter().any(|earlier| earlier.name == target) {
+                        return Err(format!(
+                            "Stage '{}' references '{}', which is not an earlier stage",
+                            stage.name, target
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "Stage '{}' has an unknown reference '{}'",
+                        stage.name, source
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a stage's task input from the pipeline input and earlier stage outputs
+pub fn resolve_stage_input(
+    stage: &PipelineStage,
+    pipeline_input: &HashMap<String, Value>,
+    completed: &[StageRun],
+) -> std::result::Result<HashMap<String, Value>, String> {
+    let output_of = |stage_run: &StageRun| {
+        Value::Object(
+            stage_run
+                .output
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        )
+    };
+    let input = Value::Object(pipeline_input.clone().into_iter().collect());
+
+    if stage.input_mapping.is_empty() {
+        let handoff = completed.last().map(output_of).unwrap_or(input);
+        return Ok(match handoff {
+            Value::Object(map) => map.into_iter().collect(),
+            _ => HashMap::new(),
+        });
+    }
+
+    let mut resolved = HashMap::new();
+    for (key, source) in &stage.input_mapping {
+        let Some(reference) = source.as_str().and_then(|s| s.strip_prefix('$')) else {
+            resolved.insert(key.clone(), source.clone());
+            continue;
+        };
+
+        let (root, path) = reference.split_once('.').unwrap_or((reference, ""));
+        let (base, path) = match root {
+            "input" => (input.clone(), path),
+            "previous" => (
+                completed
+                    .last()
+                    .map(output_of)
+                    .ok_or_else(|| format!("No previous stage for '{}'", source))?,
+                path,
+            ),
+            "stages" => {
+                let (name, rest) = path.split_once('.').unwrap_or((path, ""));
+                let stage_run = completed
+                    .iter()
+                    .find(|stage_run| stage_run.name == name)
+                    .ok_or_else(|| format!("Stage '{}' has not run", name))?;
+                (output_of(stage_run), rest)
+            }
+            _ => return Err(format!("Unknown reference '{}'", source)),
+        };
+
+        let value = lookup_path(&base, path)
+            .ok_or_else(|| format!("'{}' did not resolve to a value", source))?;
+        resolved.insert(key.clone(), value.clone());
+    }
+
+    Ok(resolved)
+}
+
+fn lookup_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| match current {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+/// Describe how a finished stage went over its budget, if it did
+pub fn check_budget(budget: &StageBudget, stage_run: &StageRun) -> Option<String> {
+    if let Some(max) = budget.max_duration_secs {
+        if stage_run.execution_time_seconds > max as f64 {
+            return Some(format!("exceeded the {}s time budget", max));
+        }
+    }
+    if let (Some(max), Some(cost)) = (budget.max_cost_usd, stage_run.cost_usd) {
+        if cost > max {
+            return Some(format!("cost ${:.2} exceeded the ${:.2} budget", cost, max));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stage(name: &str, mapping: Value) -> PipelineStage {
+        PipelineStage {
+            name: name.to_string(),
+            user_employee_id: format!("ue-{}", name),
+            task_type: name.to_string(),
+            input_mapping: serde_json::from_value(mapping).unwrap(),
+            budget: StageBudget::default(),
+            requires_approval: false,
+        }
+    }
+
+    fn completed(name: &str, output: Value) -> StageRun {
+        let mut run = StageRun::pending(name);
+        run.status = StageRunStatus::Completed;
+        run.output = Some(serde_json::from_value(output).unwrap());
+        run
+    }
+
+    #[test]
+    fn test_validate_stages() {
+        let qualify = stage("qualify", json!({ "leads": "$input.leads" }));
+        let campaign = stage("campaign", json!({ "leads": "$stages.qualify.qualified" }));
+        assert!(validate_stages(&[qualify.clone(), campaign.clone()]).is_ok());
+
+        assert!(validate_stages(&[]).is_err());
+        assert!(validate_stages(&[campaign.clone(), qualify.clone()]).is_err());
+        assert!(validate_stages(&[qualify.clone(), qualify.clone()]).is_err());
+        assert!(validate_stages(&[stage("first", json!({ "x": "$previous.result" }))]).is_err());
+        assert!(validate_stages(&[stage("first", json!({ "x": "$env.HOME" }))]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_stage_input() {
+        let input: HashMap<String, Value> =
+            serde_json::from_value(json!({ "leads": [{ "email": "a@example.com" }] })).unwrap();
+        let done = vec![completed(
+            "qualify",
+            json!({ "qualified": ["a@example.com"], "score": 0.9 }),
+        )];
+
+        let campaign = stage(
+            "campaign",
+            json!({
+                "recipients": "$stages.qualify.qualified",
+                "first": "$input.leads.0.email",
+                "score": "$previous.score",
+                "template": "welcome",
+            }),
+        );
+        let resolved = resolve_stage_input(&campaign, &input, &done).unwrap();
+        assert_eq!(resolved["recipients"], json!(["a@example.com"]));
+        assert_eq!(resolved["first"], json!("a@example.com"));
+        assert_eq!(resolved["score"], json!(0.9));
+        assert_eq!(resolved["template"], json!("welcome"));
+
+        // An empty mapping hands over the previous output, or the pipeline input
+        let passthrough = stage("campaign", json!({}));
+        let resolved = resolve_stage_input(&passthrough, &input, &done).unwrap();
+        assert_eq!(resolved["score"], json!(0.9));
+        let resolved = resolve_stage_input(&passthrough, &input, &[]).unwrap();
+        assert!(resolved.contains_key("leads"));
+
+        let missing = stage("campaign", json!({ "x": "$previous.nope" }));
+        assert!(resolve_stage_input(&missing, &input, &done).is_err());
+    }
+
+    #[test]
+    fn test_check_budget() {
+        let budget = StageBudget {
+            max_duration_secs: Some(10),
+            max_cost_usd: Some(0.5),
+        };
+        let mut run = completed("qualify", json!({}));
+        run.execution_time_seconds = 2.0;
+        assert!(check_budget(&budget, &run).is_none());
+
+        run.cost_usd = Some(0.75);
+        assert!(check_budget(&budget, &run).unwrap().contains("budget"));
+
+        run.cost_usd = None;
+        run.execution_time_seconds = 12.0;
+        assert!(check_budget(&budget, &run).unwrap().contains("10s"));
+    }
+}
@@ -1,16 +1,15 @@
 use super::employees::get_pre_built_employees;
 use super::*;
-use rusqlite::Connection;
-use std::sync::{Arc, Mutex};
+use crate::db::Pool;
 
 /// AI Employee Registry manages the collection of pre-built employees
 pub struct AIEmployeeRegistry {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
 }
 
 impl AIEmployeeRegistry {
     /// Create a new registry instance
-    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self { db }
     }
 
@@ -31,10 +30,9 @@ impl AIEmployeeRegistry {
 
     /// Register a single employee
     fn register_employee(&self, employee: AIEmployee) -> Result<()> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         // Check if already exists
         let exists: bool = conn
@@ -129,10 +127,9 @@ impl AIEmployeeRegistry {
 
     /// Get count of registered employees
     pub fn count(&self) -> Result<usize> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let count: i64 = conn
             .query_row("SELECT COUNT(*) FROM ai_employees", [], |row| row.get(0))
@@ -143,10 +140,9 @@ impl AIEmployeeRegistry {
 
     /// Get all employees
     pub fn get_all(&self) -> Result<Vec<AIEmployee>> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| EmployeeError::DatabaseError(format!("Failed to acquire lock: {}", e)))?;
+        let conn = self.db.get().map_err(|e| {
+            EmployeeError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
 
         let mut stmt = conn
             .prepare(
@@ -8,6 +8,7 @@ use std::time::Duration;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
+use crate::cancellation::cancellable;
 use crate::error::{Error, Result};
 
 /// HTTP methods
@@ -202,9 +203,8 @@ impl ApiClient {
         }
 
         // Execute request
-        let response = req_builder
-            .send()
-            .await
+        let response = cancellable(req_builder.send())
+            .await?
             .map_err(|e| Error::Other(format!("Failed to send request: {}", e)))?;
 
         let duration_ms = start.elapsed().as_millis();
@@ -214,9 +214,8 @@ impl ApiClient {
         let headers = self.extract_headers(&response);
         let success = status.is_success();
 
-        let body = response
-            .text()
-            .await
+        let body = cancellable(response.text())
+            .await?
             .map_err(|e| Error::Other(format!("Failed to read response body: {}", e)))?;
 
         tracing::info!(
@@ -338,9 +337,8 @@ impl ApiClient {
         };
 
         // Execute request
-        let response = req_builder
-            .send()
-            .await
+        let response = cancellable(req_builder.send())
+            .await?
             .map_err(|e| Error::Other(format!("Failed to upload file: {}", e)))?;
 
         let duration_ms = start.elapsed().as_millis();
@@ -350,9 +348,8 @@ impl ApiClient {
         let headers = self.extract_headers(&response);
         let success = status.is_success();
 
-        let body = response
-            .text()
-            .await
+        let body = cancellable(response.text())
+            .await?
             .map_err(|e| Error::Other(format!("Failed to read response body: {}", e)))?;
 
         tracing::info!(
@@ -403,9 +400,8 @@ impl ApiClient {
         };
 
         // Execute request
-        let response = req_builder
-            .send()
-            .await
+        let response = cancellable(req_builder.send())
+            .await?
             .map_err(|e| Error::Other(format!("Failed to download file: {}", e)))?;
 
         let status = response.status();
@@ -431,9 +427,8 @@ impl ApiClient {
         let file_size = response.content_length().unwrap_or(0);
 
         // Read response body as bytes
-        let bytes = response
-            .bytes()
-            .await
+        let bytes = cancellable(response.bytes())
+            .await?
             .map_err(|e| Error::Other(format!("Failed to read response bytes: {}", e)))?;
 
         // Write to file
@@ -441,13 +436,21 @@ impl ApiClient {
             .await
             .map_err(|e| Error::Other(format!("Failed to create file: {}", e)))?;
 
-        file.write_all(&bytes)
-            .await
-            .map_err(|e| Error::Other(format!("Failed to write file: {}", e)))?;
-
-        file.flush()
-            .await
-            .map_err(|e| Error::Other(format!("Failed to flush file: {}", e)))?;
+        let written = cancellable(async {
+            file.write_all(&bytes).await?;
+            file.flush().await
+        })
+        .await;
+        match written {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(Error::Other(format!("Failed to write file: {}", e))),
+            Err(cancelled) => {
+                // Don't leave a truncated download behind
+                drop(file);
+                let _ = tokio::fs::remove_file(save_path).await;
+                return Err(cancelled.into());
+            }
+        }
 
         let duration_ms = start.elapsed().as_millis();
 
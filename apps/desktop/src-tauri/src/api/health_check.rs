@@ -0,0 +1,653 @@
+//! Declarative health checks for custom HTTP integrations.
+//!
+//! A check names an endpoint, the status codes it should answer with and
+//! JSONPath assertions on its body. The [`HealthCheckScheduler`] runs each
+//! enabled check on its own interval and feeds the outcome into the
+//! credential health registry, so a failing integration shows up next to
+//! expiring accounts and raises one notification until it recovers.
+
+use chrono::Utc;
+use futures::future::join_all;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+use super::client::{ApiClient, ApiRequest, ApiResponse, HttpMethod, RetryConfig};
+use super::response_parser::{ParsedResponse, ResponseParser};
+use crate::credential_health::{self, monitor::HEALTH_EVENT, CredentialCheck, CredentialSource};
+use crate::error::{Error, Result};
+use crate::notifications::{self, Notification, NotificationCategory};
+
+/// Shortest allowed time between two runs of a check
+pub const MIN_INTERVAL_SECS: u64 = 30;
+const MAX_TIMEOUT_MS: u64 = 60_000;
+/// How often the scheduler looks for checks that are due
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssertionOp {
+    /// The path resolves to a non-null value
+    Exists,
+    Equals,
+    NotEquals,
+    /// Substring of a string, or element of an array
+    Contains,
+    GreaterThan,
+    LessThan,
+}
+
+/// A condition on one value of a JSON response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonPathAssertion {
+    /// Dotted path such as `data.services[0].state`; a leading `$.` is ignored
+    pub path: String,
+    pub op: AssertionOp,
+    /// Compared against the value at `path`; unused for `exists`
+    #[serde(default)]
+    pub value: JsonValue,
+}
+
+/// A health check as submitted by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewHealthCheck {
+    pub name: String,
+    #[serde(default = "default_method")]
+    pub method: HttpMethod,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Accepted status codes; empty accepts any 2xx
+    #[serde(default)]
+    pub expected_status: Vec<u16>,
+    #[serde(default)]
+    pub assertions: Vec<JsonPathAssertion>,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_method() -> HttpMethod {
+    HttpMethod::Get
+}
+
+fn default_interval_secs() -> u64 {
+    5 * 60
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl NewHealthCheck {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Other("Health check name is required".to_string()));
+        }
+        let url = url::Url::parse(&self.url)
+            .map_err(|e| Error::Other(format!("Invalid health check URL: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Error::Other(
+                "Health check URL must use http or https".to_string(),
+            ));
+        }
+        if self.interval_secs < MIN_INTERVAL_SECS {
+            return Err(Error::Other(format!(
+                "Health checks run at most every {} seconds",
+                MIN_INTERVAL_SECS
+            )));
+        }
+        if self.timeout_ms == 0 || self.timeout_ms > MAX_TIMEOUT_MS {
+            return Err(Error::Other(format!(
+                "Timeout must be between 1 and {} ms",
+                MAX_TIMEOUT_MS
+            )));
+        }
+        if let Some(status) = self
+            .expected_status
+            .iter()
+            .find(|status| !(100..600).contains(*status))
+        {
+            return Err(Error::Other(format!("Invalid status code {}", status)));
+        }
+        for assertion in &self.assertions {
+            if assertion.path.trim().is_empty() {
+                return Err(Error::Other("Assertion path is required".to_string()));
+            }
+            if matches!(
+                assertion.op,
+                AssertionOp::GreaterThan | AssertionOp::LessThan
+            ) && !assertion.value.is_number()
+            {
+                return Err(Error::Other(format!(
+                    "Assertion on `{}` needs a numeric value",
+                    assertion.path
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A stored health check with the outcome of its last run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub id: String,
+    pub name: String,
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>,
+    pub expected_status: Vec<u16>,
+    pub assertions: Vec<JsonPathAssertion>,
+    pub interval_secs: u64,
+    pub timeout_ms: u64,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+    /// HTTP status of the last run; `None` if the request itself failed
+    pub last_status: Option<u16>,
+    pub last_latency_ms: Option<u64>,
+    /// Why the last run failed; `None` after a passing run
+    pub last_error: Option<String>,
+}
+
+impl HealthCheck {
+    pub fn is_due(&self, now: i64) -> bool {
+        self.enabled
+            && self
+                .last_run_at
+                .is_none_or(|last| last + self.interval_secs as i64 <= now)
+    }
+}
+
+/// Outcome of one run of a check
+#[derive(Debug, Clone)]
+pub struct HealthCheckRun {
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub outcome: std::result::Result<(), String>,
+}
+
+/// Whether `response` satisfies the check's status and body assertions
+pub fn evaluate(check: &HealthCheck, response: &ApiResponse) -> std::result::Result<(), String> {
+    let status_ok = if check.expected_status.is_empty() {
+        (200..300).contains(&response.status)
+    } else {
+        check.expected_status.contains(&response.status)
+    };
+    if !status_ok {
+        return Err(format!("Unexpected status {}", response.status));
+    }
+    if check.assertions.is_empty() {
+        return Ok(());
+    }
+
+    let content_type = response
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str());
+    let parsed = ResponseParser::parse(&response.body, content_type)
+        .map_err(|e| format!("Unreadable response body: {}", e))?;
+    for assertion in &check.assertions {
+        check_assertion(assertion, &parsed)?;
+    }
+    Ok(())
+}
+
+fn check_assertion(
+    assertion: &JsonPathAssertion,
+    parsed: &ParsedResponse,
+) -> std::result::Result<(), String> {
+    let path = assertion.path.trim();
+    let path = path.strip_prefix("$.").unwrap_or(path);
+    let actual = ResponseParser::extract_json_path(parsed, path)
+        .ok()
+        .filter(|value| !value.is_null());
+    let Some(actual) = actual else {
+        return Err(format!("`{}` is missing", assertion.path));
+    };
+
+    let expected = &assertion.value;
+    let passed = match assertion.op {
+        AssertionOp::Exists => true,
+        AssertionOp::Equals => actual == *expected,
+        AssertionOp::NotEquals => actual != *expected,
+        AssertionOp::Contains => match (&actual, expected) {
+            (JsonValue::String(text), JsonValue::String(needle)) => text.contains(needle.as_str()),
+            (JsonValue::Array(items), needle) => items.contains(needle),
+            _ => false,
+        },
+        AssertionOp::GreaterThan => compare(&actual, expected).is_some_and(|(a, b)| a > b),
+        AssertionOp::LessThan => compare(&actual, expected).is_some_and(|(a, b)| a < b),
+    };
+    if passed {
+        Ok(())
+    } else {
+        Err(format!(
+            "`{}` is {}, expected {} {}",
+            assertion.path,
+            actual,
+            serde_json::to_value(assertion.op)
+                .ok()
+                .and_then(|op| op.as_str().map(|op| op.replace('_', " ")))
+                .unwrap_or_default(),
+            expected
+        ))
+    }
+}
+
+fn compare(actual: &JsonValue, expected: &JsonValue) -> Option<(f64, f64)> {
+    Some((actual.as_f64()?, expected.as_f64()?))
+}
+
+/// Send the check's request once and evaluate the response
+pub async fn run(client: &ApiClient, check: &HealthCheck) -> HealthCheckRun {
+    let request = ApiRequest {
+        method: check.method.clone(),
+        url: check.url.clone(),
+        headers: check.headers.clone(),
+        body: check.body.clone(),
+        timeout_ms: Some(check.timeout_ms),
+        ..ApiRequest::default()
+    };
+    match client.execute(request).await {
+        Ok(response) => HealthCheckRun {
+            status: Some(response.status),
+            latency_ms: Some(response.duration_ms as u64),
+            outcome: evaluate(check, &response),
+        },
+        Err(e) => HealthCheckRun {
+            status: None,
+            latency_ms: None,
+            outcome: Err(e.to_string()),
+        },
+    }
+}
+
+pub fn create(conn: &Connection, new: NewHealthCheck, now: i64) -> Result<HealthCheck> {
+    new.validate()?;
+    let check = HealthCheck {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: new.name.trim().to_string(),
+        method: new.method,
+        url: new.url,
+        headers: new.headers,
+        body: new.body,
+        expected_status: new.expected_status,
+        assertions: new.assertions,
+        interval_secs: new.interval_secs,
+        timeout_ms: new.timeout_ms,
+        enabled: new.enabled,
+        created_at: now,
+        last_run_at: None,
+        last_status: None,
+        last_latency_ms: None,
+        last_error: None,
+    };
+    conn.execute(
+        "INSERT INTO health_checks
+            (id, name, method, url, headers, body, expected_status, assertions,
+             interval_secs, timeout_ms, enabled, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            check.id,
+            check.name,
+            check.method.to_string(),
+            check.url,
+            serde_json::to_string(&check.headers)?,
+            check.body,
+            serde_json::to_string(&check.expected_status)?,
+            serde_json::to_string(&check.assertions)?,
+            check.interval_secs as i64,
+            check.timeout_ms as i64,
+            check.enabled,
+            check.created_at
+        ],
+    )?;
+    Ok(check)
+}
+
+/// Every check, oldest first
+pub fn list(conn: &Connection) -> Result<Vec<HealthCheck>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, method, url, headers, body, expected_status, assertions,
+                interval_secs, timeout_ms, enabled, created_at, last_run_at,
+                last_status, last_latency_ms, last_error
+         FROM health_checks ORDER BY created_at, name",
+    )?;
+    let checks = stmt
+        .query_map([], read_check)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(checks)
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<HealthCheck>> {
+    let check = conn
+        .query_row(
+            "SELECT id, name, method, url, headers, body, expected_status, assertions,
+                    interval_secs, timeout_ms, enabled, created_at, last_run_at,
+                    last_status, last_latency_ms, last_error
+             FROM health_checks WHERE id = ?1",
+            [id],
+            read_check,
+        )
+        .optional()?;
+    Ok(check)
+}
+
+/// Remove a check and its entry in the health registry
+pub fn delete(conn: &Connection, id: &str) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM health_checks WHERE id = ?1", [id])? > 0;
+    let remaining: Vec<String> = list(conn)?.into_iter().map(|check| check.id).collect();
+    credential_health::retain(conn, CredentialSource::HealthCheck, &remaining)?;
+    Ok(deleted)
+}
+
+/// Store a run on the check and in the health registry
+pub fn record_run(
+    conn: &Connection,
+    check: &HealthCheck,
+    run: &HealthCheckRun,
+    now: i64,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE health_checks
+         SET last_run_at = ?1, last_status = ?2, last_latency_ms = ?3, last_error = ?4
+         WHERE id = ?5",
+        params![
+            now,
+            run.status,
+            run.latency_ms.map(|ms| ms as i64),
+            run.outcome.as_ref().err(),
+            check.id
+        ],
+    )?;
+    credential_health::record(
+        conn,
+        &CredentialCheck {
+            source: CredentialSource::HealthCheck,
+            account_id: check.id.clone(),
+            provider: url::Url::parse(&check.url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| check.url.clone()),
+            label: Some(check.name.clone()),
+            expires_at: None,
+            outcome: run.outcome.clone(),
+        },
+        now,
+    )
+}
+
+fn read_check(row: &Row) -> rusqlite::Result<HealthCheck> {
+    fn json<T: serde::de::DeserializeOwned + Default>(value: String) -> T {
+        serde_json::from_str(&value).unwrap_or_default()
+    }
+
+    Ok(HealthCheck {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        method: serde_json::from_value(JsonValue::String(row.get(2)?)).unwrap_or(HttpMethod::Get),
+        url: row.get(3)?,
+        headers: json(row.get(4)?),
+        body: row.get(5)?,
+        expected_status: json(row.get(6)?),
+        assertions: json(row.get(7)?),
+        interval_secs: row.get::<_, i64>(8)? as u64,
+        timeout_ms: row.get::<_, i64>(9)? as u64,
+        enabled: row.get(10)?,
+        created_at: row.get(11)?,
+        last_run_at: row.get(12)?,
+        last_status: row.get(13)?,
+        last_latency_ms: row.get::<_, Option<i64>>(14)?.map(|ms| ms as u64),
+        last_error: row.get(15)?,
+    })
+}
+
+/// Owns the task that runs due checks
+#[derive(Default)]
+pub struct HealthCheckScheduler {
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl HealthCheckScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, app: &AppHandle) {
+        let app = app.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            let client = match ApiClient::with_retry_config(RetryConfig {
+                max_retries: 0,
+                ..RetryConfig::default()
+            }) {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("Health checks disabled: {}", e);
+                    return;
+                }
+            };
+            loop {
+                if let Err(e) = run_due(&app, &client).await {
+                    warn!("Health check run failed: {}", e);
+                }
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+        if let Some(previous) = self.worker.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
+/// Run every check that is due, record the results and notify about checks
+/// that started failing
+async fn run_due(app: &AppHandle, client: &ApiClient) -> Result<()> {
+    let due: Vec<HealthCheck> = {
+        let conn = open_connection(app)?;
+        let now = Utc::now().timestamp();
+        list(&conn)?
+            .into_iter()
+            .filter(|check| check.is_due(now))
+            .collect()
+    };
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let runs = join_all(due.iter().map(|check| run(client, check))).await;
+
+    let now = Utc::now().timestamp();
+    let conn = open_connection(app)?;
+    for (check, run) in due.iter().zip(&runs) {
+        record_run(&conn, check, run, now)?;
+    }
+    for health in
+        credential_health::take_notifications(&conn, now, &[CredentialSource::HealthCheck])?
+    {
+        let label = health.label.unwrap_or(health.provider);
+        notifications::notify(
+            app,
+            Notification::new(
+                NotificationCategory::IntegrationHealth,
+                format!("{} is failing its health check", label),
+                health
+                    .last_error
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            )
+            .with_target(format!(
+                "{}:{}",
+                CredentialSource::HealthCheck.as_str(),
+                health.account_id
+            )),
+        );
+    }
+
+    let overview = credential_health::overview(&conn, now)?;
+    if let Err(e) = app.emit(HEALTH_EVENT, &overview) {
+        warn!("Failed to emit credential health event: {}", e);
+    }
+    Ok(())
+}
+
+fn open_connection(app: &AppHandle) -> Result<Connection> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Generic(format!("Failed to get app data dir: {}", e)))?
+        .join("agiworkforce.db");
+
+    Connection::open(db_path).map_err(|e| Error::Generic(format!("Database error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_check(assertions: Vec<JsonPathAssertion>) -> NewHealthCheck {
+        NewHealthCheck {
+            name: "Billing API".to_string(),
+            method: HttpMethod::Get,
+            url: "https://billing.example.com/health".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            expected_status: Vec::new(),
+            assertions,
+            interval_secs: 60,
+            timeout_ms: 5_000,
+            enabled: true,
+        }
+    }
+
+    fn assertion(path: &str, op: AssertionOp, value: JsonValue) -> JsonPathAssertion {
+        JsonPathAssertion {
+            path: path.to_string(),
+            op,
+            value,
+        }
+    }
+
+    fn response(status: u16, body: &str) -> ApiResponse {
+        ApiResponse {
+            status,
+            headers: HashMap::from([("content-type".to_string(), "application/json".to_string())]),
+            body: body.to_string(),
+            duration_ms: 12,
+            success: (200..300).contains(&status),
+        }
+    }
+
+    fn conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_evaluate_status_and_assertions() {
+        let conn = conn();
+        let mut check = create(
+            &conn,
+            new_check(vec![
+                assertion("$.status", AssertionOp::Equals, "ok".into()),
+                assertion("queue.depth", AssertionOp::LessThan, 100.into()),
+                assertion("regions", AssertionOp::Contains, "eu".into()),
+            ]),
+            0,
+        )
+        .unwrap();
+
+        let body = r#"{"status": "ok", "queue": {"depth": 3}, "regions": ["us", "eu"]}"#;
+        assert!(evaluate(&check, &response(200, body)).is_ok());
+        assert_eq!(
+            evaluate(&check, &response(503, body)).unwrap_err(),
+            "Unexpected status 503"
+        );
+
+        let degraded = r#"{"status": "ok", "queue": {"depth": 250}, "regions": ["eu"]}"#;
+        assert_eq!(
+            evaluate(&check, &response(200, degraded)).unwrap_err(),
+            "`queue.depth` is 250, expected less than 100"
+        );
+        assert_eq!(
+            evaluate(&check, &response(200, r#"{"queue": {"depth": 1}}"#)).unwrap_err(),
+            "`$.status` is missing"
+        );
+
+        check.expected_status = vec![503];
+        check.assertions.clear();
+        assert!(evaluate(&check, &response(503, "")).is_ok());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(new_check(Vec::new()).validate().is_ok());
+        let mut check = new_check(Vec::new());
+        check.url = "ftp://example.com".to_string();
+        assert!(check.validate().is_err());
+        let mut check = new_check(Vec::new());
+        check.interval_secs = 5;
+        assert!(check.validate().is_err());
+        let check = new_check(vec![assertion(
+            "latency",
+            AssertionOp::GreaterThan,
+            "fast".into(),
+        )]);
+        assert!(check.validate().is_err());
+    }
+
+    #[test]
+    fn test_storage_and_registry() {
+        let conn = conn();
+        let check = create(&conn, new_check(Vec::new()), 1_000).unwrap();
+        assert!(check.is_due(1_000));
+
+        let failing = HealthCheckRun {
+            status: Some(500),
+            latency_ms: Some(40),
+            outcome: Err("Unexpected status 500".to_string()),
+        };
+        record_run(&conn, &check, &failing, 1_000).unwrap();
+        let stored = get(&conn, &check.id).unwrap().unwrap();
+        assert_eq!(stored.last_status, Some(500));
+        assert_eq!(stored.last_error.as_deref(), Some("Unexpected status 500"));
+        assert!(!stored.is_due(1_030));
+        assert!(stored.is_due(1_060));
+
+        let health = credential_health::load(&conn, 1_000).unwrap();
+        assert_eq!(health.len(), 1);
+        assert_eq!(health[0].provider, "billing.example.com");
+        assert_eq!(
+            health[0].status,
+            credential_health::CredentialStatus::Failing
+        );
+        assert_eq!(
+            credential_health::take_notifications(&conn, 1_000, &[CredentialSource::HealthCheck])
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(delete(&conn, &check.id).unwrap());
+        assert!(list(&conn).unwrap().is_empty());
+        assert!(credential_health::load(&conn, 1_000).unwrap().is_empty());
+    }
+}
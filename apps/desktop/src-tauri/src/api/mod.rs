@@ -1,9 +1,11 @@
 pub mod client;
+pub mod health_check;
 pub mod oauth;
 pub mod request_template;
 pub mod response_parser;
 
 pub use client::{ApiClient, ApiRequest, ApiResponse, AuthType, HttpMethod};
+pub use health_check::{HealthCheck, HealthCheckScheduler, JsonPathAssertion, NewHealthCheck};
 pub use oauth::{OAuth2Client, OAuth2Config, PkceChallenge, TokenResponse};
 pub use request_template::{RequestTemplate, TemplateEngine, TemplateVariable};
 pub use response_parser::{ParsedResponse, ResponseFormat, ResponseParser};
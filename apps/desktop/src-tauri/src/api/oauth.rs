@@ -1,4 +1,4 @@
-use base64::{engine::general_purpose::STANDARD, Engine};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -6,12 +6,13 @@ use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::error::{Error, Result};
+use crate::security::SecretString;
 
 /// OAuth 2.0 configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuth2Config {
     pub client_id: String,
-    pub client_secret: Option<String>,
+    pub client_secret: Option<SecretString>,
     pub auth_url: String,
     pub token_url: String,
     pub redirect_uri: String,
@@ -22,10 +23,10 @@ pub struct OAuth2Config {
 /// OAuth 2.0 token response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenResponse {
-    pub access_token: String,
+    pub access_token: SecretString,
     pub token_type: String,
     pub expires_in: Option<u64>,
-    pub refresh_token: Option<String>,
+    pub refresh_token: Option<SecretString>,
     pub scope: Option<String>,
     #[serde(skip)]
     pub expires_at: Option<u64>,
@@ -75,11 +76,11 @@ impl PkceChallenge {
             })
             .collect();
 
-        // Generate code challenge (SHA256 hash of verifier, base64 URL encoded)
+        // Generate code challenge (SHA256 hash of verifier, base64url without padding)
         let mut hasher = Sha256::new();
         hasher.update(code_verifier.as_bytes());
         let hash = hasher.finalize();
-        let code_challenge = STANDARD.encode(hash);
+        let code_challenge = URL_SAFE_NO_PAD.encode(hash);
 
         Self {
             code_verifier,
@@ -180,7 +181,7 @@ impl OAuth2Client {
 
         // Add client secret if available (for confidential clients)
         if let Some(ref secret) = self.config.client_secret {
-            params.insert("client_secret", secret);
+            params.insert("client_secret", secret.expose_secret());
         }
 
         // Add PKCE code verifier if using PKCE
@@ -230,7 +231,7 @@ impl OAuth2Client {
 
         // Add client secret if available
         if let Some(ref secret) = self.config.client_secret {
-            params.insert("client_secret", secret);
+            params.insert("client_secret", secret.expose_secret());
         }
 
         // Send request
@@ -281,7 +282,7 @@ impl OAuth2Client {
         let mut params = HashMap::new();
         params.insert("grant_type", "client_credentials");
         params.insert("client_id", &self.config.client_id);
-        params.insert("client_secret", client_secret);
+        params.insert("client_secret", client_secret.expose_secret());
 
         // Add scopes if present
         if let Some(ref scopes) = scope_string {
@@ -331,14 +332,17 @@ mod tests {
         // Verify code verifier length
         assert_eq!(challenge.code_verifier.len(), 64);
 
-        // Verify code challenge is base64 encoded
-        assert!(!challenge.code_challenge.is_empty());
+        // Verify code challenge is base64url encoded, as S256 requires
+        assert_eq!(challenge.code_challenge.len(), 43);
+        assert!(!challenge
+            .code_challenge
+            .contains(|c| matches!(c, '+' | '/' | '=')));
     }
 
     #[test]
     fn test_token_expiration() {
         let mut token = TokenResponse {
-            access_token: "test_token".to_string(),
+            access_token: "test_token".into(),
             token_type: "Bearer".to_string(),
             expires_in: Some(3600),
             refresh_token: None,
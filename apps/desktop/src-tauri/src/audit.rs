@@ -0,0 +1,591 @@
+//! Append-only audit trail of agent and user actions
+//!
+//! Automation, filesystem writes, email sends, payments, permission grants
+//! and remote API calls are recorded in the `audit` table with who acted,
+//! what they did to which target, a hash of the parameters (never the
+//! parameters themselves) and the outcome. Database triggers refuse updates
+//! and deletes.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::db::Pool;
+use crate::security::AuthenticatedUser;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorKind {
+    User,
+    Agent,
+    Employee,
+    System,
+}
+
+impl ActorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActorKind::User => "user",
+            ActorKind::Agent => "agent",
+            ActorKind::Employee => "employee",
+            ActorKind::System => "system",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(ActorKind::User),
+            "agent" => Some(ActorKind::Agent),
+            "employee" => Some(ActorKind::Employee),
+            "system" => Some(ActorKind::System),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Actor {
+    pub kind: ActorKind,
+    /// User id, agent run id or employee id
+    pub id: Option<String>,
+}
+
+impl Actor {
+    pub fn user(user: &AuthenticatedUser) -> Self {
+        Self {
+            kind: ActorKind::User,
+            id: Some(user.user_id.clone().unwrap_or_else(|| "local".to_string())),
+        }
+    }
+
+    pub fn agent(run_id: impl Into<String>) -> Self {
+        Self {
+            kind: ActorKind::Agent,
+            id: Some(run_id.into()),
+        }
+    }
+
+    pub fn employee(employee_id: impl Into<String>) -> Self {
+        Self {
+            kind: ActorKind::Employee,
+            id: Some(employee_id.into()),
+        }
+    }
+
+    pub fn system() -> Self {
+        Self {
+            kind: ActorKind::System,
+            id: None,
+        }
+    }
+
+    /// A script or device calling the remote control API
+    pub fn api_client(client_id: impl Into<String>) -> Self {
+        Self {
+            kind: ActorKind::System,
+            id: Some(format!("api_client:{}", client_id.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditCategory {
+    Automation,
+    Filesystem,
+    Email,
+    Payment,
+    Permission,
+    RemoteApi,
+}
+
+impl AuditCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditCategory::Automation => "automation",
+            AuditCategory::Filesystem => "filesystem",
+            AuditCategory::Email => "email",
+            AuditCategory::Payment => "payment",
+            AuditCategory::Permission => "permission",
+            AuditCategory::RemoteApi => "remote_api",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "automation" => Some(AuditCategory::Automation),
+            "filesystem" => Some(AuditCategory::Filesystem),
+            "email" => Some(AuditCategory::Email),
+            "payment" => Some(AuditCategory::Payment),
+            "permission" => Some(AuditCategory::Permission),
+            "remote_api" => Some(AuditCategory::RemoteApi),
+            _ => None,
+        }
+    }
+
+    /// Category of an agent tool, if its calls are audited
+    pub fn of_tool(tool_name: &str) -> Option<Self> {
+        match tool_name {
+            "file_write"
+            | "file_delete"
+            | "document_create_word"
+            | "document_create_excel"
+            | "document_create_pdf" => Some(AuditCategory::Filesystem),
+            "email_send" => Some(AuditCategory::Email),
+            "terminal_execute" | "code_execute" | "project_run_task" => {
+                Some(AuditCategory::Automation)
+            }
+            name if name.starts_with("ui_")
+                || name.starts_with("automation_")
+                || name.starts_with("browser_") =>
+            {
+                Some(AuditCategory::Automation)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    Denied,
+}
+
+impl AuditOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+            AuditOutcome::Denied => "denied",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "success" => Some(AuditOutcome::Success),
+            "failure" => Some(AuditOutcome::Failure),
+            "denied" => Some(AuditOutcome::Denied),
+            _ => None,
+        }
+    }
+}
+
+/// Tool parameters naming what a call acts on, in order of preference
+const TOOL_TARGET_KEYS: &[&str] = &["path", "to", "url", "cwd", "selector", "element_id"];
+
+/// An action to record
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub actor: Actor,
+    pub category: AuditCategory,
+    pub action: String,
+    pub target: Option<String>,
+    pub params_hash: Option<String>,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(actor: Actor, category: AuditCategory, action: impl Into<String>) -> Self {
+        Self {
+            actor,
+            category,
+            action: action.into(),
+            target: None,
+            params_hash: None,
+            outcome: AuditOutcome::Success,
+            detail: None,
+        }
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn params(mut self, params: &Value) -> Self {
+        self.params_hash = Some(params_hash(params));
+        self
+    }
+
+    pub fn outcome(mut self, outcome: AuditOutcome) -> Self {
+        self.outcome = outcome;
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Entry for an agent tool call, if calls to the tool are audited
+    pub fn for_tool(actor: Actor, tool_name: &str, params: &Value) -> Option<Self> {
+        let category = AuditCategory::of_tool(tool_name)?;
+        let mut entry = Self::new(actor, category, tool_name).params(params);
+        entry.target = TOOL_TARGET_KEYS
+            .iter()
+            .find_map(|key| params.get(*key))
+            .map(|target| match target {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            });
+        Some(entry)
+    }
+
+    /// Outcome from a result, with the error as the detail
+    pub fn result<T, E: std::fmt::Display>(self, result: &std::result::Result<T, E>) -> Self {
+        match result {
+            Ok(_) => self.outcome(AuditOutcome::Success),
+            Err(e) => self.outcome(AuditOutcome::Failure).detail(e.to_string()),
+        }
+    }
+}
+
+/// SHA-256 of the parameters with object keys sorted, so equal parameters
+/// hash equally whatever order they were built in
+pub fn params_hash(params: &Value) -> String {
+    fn canonical(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| (k.clone(), canonical(v)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+            other => other.clone(),
+        }
+    }
+    hex::encode(Sha256::digest(canonical(params).to_string().as_bytes()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub actor_kind: ActorKind,
+    pub actor_id: Option<String>,
+    pub category: AuditCategory,
+    pub action: String,
+    pub target: Option<String>,
+    pub params_hash: Option<String>,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+}
+
+impl AuditRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let invalid = |index: usize, value: String| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                format!("unknown value '{}'", value).into(),
+            )
+        };
+        let actor_kind: String = row.get(2)?;
+        let category: String = row.get(4)?;
+        let outcome: String = row.get(8)?;
+        Ok(Self {
+            id: row.get(0)?,
+            created_at: Utc
+                .timestamp_millis_opt(row.get(1)?)
+                .single()
+                .unwrap_or_default(),
+            actor_kind: ActorKind::parse(&actor_kind).ok_or_else(|| invalid(2, actor_kind))?,
+            actor_id: row.get(3)?,
+            category: AuditCategory::parse(&category).ok_or_else(|| invalid(4, category))?,
+            action: row.get(5)?,
+            target: row.get(6)?,
+            params_hash: row.get(7)?,
+            outcome: AuditOutcome::parse(&outcome).ok_or_else(|| invalid(8, outcome))?,
+            detail: row.get(9)?,
+        })
+    }
+}
+
+pub fn append(conn: &Connection, entry: &AuditEntry) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO audit
+            (created_at, actor_kind, actor_id, category, action, target, params_hash, outcome, detail)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            Utc::now().timestamp_millis(),
+            entry.actor.kind.as_str(),
+            entry.actor.id,
+            entry.category.as_str(),
+            entry.action,
+            entry.target,
+            entry.params_hash,
+            entry.outcome.as_str(),
+            entry.detail,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuditQuery {
+    pub actor_kind: Option<ActorKind>,
+    pub actor_id: Option<String>,
+    pub category: Option<AuditCategory>,
+    pub action: Option<String>,
+    /// Substring of the target
+    pub target: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+const DEFAULT_QUERY_LIMIT: usize = 500;
+
+/// Matching records, newest first
+pub fn query(conn: &Connection, filters: &AuditQuery) -> Result<Vec<AuditRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, created_at, actor_kind, actor_id, category, action, target, params_hash, outcome, detail
+         FROM audit
+         WHERE (?1 IS NULL OR actor_kind = ?1)
+           AND (?2 IS NULL OR actor_id = ?2)
+           AND (?3 IS NULL OR category = ?3)
+           AND (?4 IS NULL OR action = ?4)
+           AND (?5 IS NULL OR instr(target, ?5) > 0)
+           AND (?6 IS NULL OR outcome = ?6)
+           AND (?7 IS NULL OR created_at >= ?7)
+           AND (?8 IS NULL OR created_at <= ?8)
+         ORDER BY id DESC
+         LIMIT ?9 OFFSET ?10",
+    )?;
+    let records = stmt
+        .query_map(
+            params![
+                filters.actor_kind.map(ActorKind::as_str),
+                filters.actor_id,
+                filters.category.map(AuditCategory::as_str),
+                filters.action,
+                filters.target,
+                filters.outcome.map(AuditOutcome::as_str),
+                filters.since.map(|t| t.timestamp_millis()),
+                filters.until.map(|t| t.timestamp_millis()),
+                filters.limit.unwrap_or(DEFAULT_QUERY_LIMIT) as i64,
+                filters.offset.unwrap_or(0) as i64,
+            ],
+            AuditRecord::from_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(records)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+}
+
+pub fn export(records: &[AuditRecord], format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Jsonl => {
+            let mut out = String::new();
+            for record in records {
+                out.push_str(&serde_json::to_string(record)?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record([
+                "id",
+                "created_at",
+                "actor_kind",
+                "actor_id",
+                "category",
+                "action",
+                "target",
+                "params_hash",
+                "outcome",
+                "detail",
+            ])?;
+            for record in records {
+                writer.write_record([
+                    record.id.to_string(),
+                    record.created_at.to_rfc3339(),
+                    record.actor_kind.as_str().to_string(),
+                    record.actor_id.clone().unwrap_or_default(),
+                    record.category.as_str().to_string(),
+                    record.action.clone(),
+                    record.target.clone().unwrap_or_default(),
+                    record.params_hash.clone().unwrap_or_default(),
+                    record.outcome.as_str().to_string(),
+                    record.detail.clone().unwrap_or_default(),
+                ])?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| anyhow!("Failed to write CSV: {}", e))?;
+            Ok(String::from_utf8(bytes)?)
+        }
+    }
+}
+
+/// Writes audit entries through the shared pool. Managed as app state.
+#[derive(Clone)]
+pub struct AuditLogger {
+    pool: Pool,
+}
+
+impl AuditLogger {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// Record an entry. Failures are logged rather than returned so auditing
+    /// never breaks the action itself.
+    pub async fn log(&self, entry: AuditEntry) {
+        let action = entry.action.clone();
+        let result = self
+            .pool
+            .run(move |conn| -> Result<_> { append(conn, &entry) })
+            .await;
+        if let Err(e) = result {
+            tracing::warn!("[Audit] Failed to record '{}': {}", action, e);
+        }
+    }
+}
+
+/// Record an entry in the background through the app's logger
+pub fn record(app: &AppHandle, entry: AuditEntry) {
+    if let Some(logger) = app.try_state::<AuditLogger>() {
+        let logger = logger.inner().clone();
+        tauri::async_runtime::spawn(async move { logger.log(entry).await });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use serde_json::json;
+
+    fn conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let conn = conn();
+        let user = AuthenticatedUser::local_owner();
+        append(
+            &conn,
+            &AuditEntry::new(Actor::user(&user), AuditCategory::Filesystem, "file_write")
+                .target("/tmp/a.txt")
+                .params(&json!({ "path": "/tmp/a.txt", "content": "secret" })),
+        )
+        .unwrap();
+        append(
+            &conn,
+            &AuditEntry::new(Actor::agent("run-1"), AuditCategory::Email, "email_send")
+                .target("bob@example.com")
+                .outcome(AuditOutcome::Failure)
+                .detail("SMTP timeout"),
+        )
+        .unwrap();
+
+        let all = query(&conn, &AuditQuery::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].action, "email_send");
+        assert_eq!(all[1].actor_id.as_deref(), Some("local"));
+        assert_eq!(all[1].params_hash.as_ref().unwrap().len(), 64);
+
+        let failures = query(
+            &conn,
+            &AuditQuery {
+                outcome: Some(AuditOutcome::Failure),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].actor_kind, ActorKind::Agent);
+
+        let by_target = query(
+            &conn,
+            &AuditQuery {
+                target: Some("a.txt".to_string()),
+                category: Some(AuditCategory::Filesystem),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(by_target.len(), 1);
+    }
+
+    #[test]
+    fn test_table_is_append_only() {
+        let conn = conn();
+        append(
+            &conn,
+            &AuditEntry::new(Actor::system(), AuditCategory::Permission, "grant"),
+        )
+        .unwrap();
+        assert!(conn
+            .execute("UPDATE audit SET outcome = 'denied'", [])
+            .is_err());
+        assert!(conn.execute("DELETE FROM audit", []).is_err());
+        assert_eq!(query(&conn, &AuditQuery::default()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_params_hash_ignores_key_order() {
+        let a = json!({ "to": "a@example.com", "subject": "Hi", "nested": { "x": 1, "y": 2 } });
+        let b = json!({ "nested": { "y": 2, "x": 1 }, "subject": "Hi", "to": "a@example.com" });
+        assert_eq!(params_hash(&a), params_hash(&b));
+        assert_ne!(
+            params_hash(&a),
+            params_hash(&json!({ "to": "b@example.com" }))
+        );
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let conn = conn();
+        append(
+            &conn,
+            &AuditEntry::new(
+                Actor::employee("emp-1"),
+                AuditCategory::Payment,
+                "update_subscription",
+            )
+            .target("sub_123, \"pro\""),
+        )
+        .unwrap();
+        let records = query(&conn, &AuditQuery::default()).unwrap();
+
+        let jsonl = export(&records, ExportFormat::Jsonl).unwrap();
+        let parsed: AuditRecord = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(parsed.action, "update_subscription");
+
+        let csv = export(&records, ExportFormat::Csv).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let row = reader.records().next().unwrap().unwrap();
+        assert_eq!(&row[2], "employee");
+        assert_eq!(&row[6], "sub_123, \"pro\"");
+    }
+}
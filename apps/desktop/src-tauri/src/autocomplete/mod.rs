@@ -0,0 +1,426 @@
+//! Autocomplete for the chat input: slash commands, workflows, AI employees,
+//! workspace files and `@`-mentions of contacts.
+//!
+//! Like the launcher palette, candidates are loaded into an
+//! [`AutocompleteIndex`] once and matched in memory on every keystroke.
+//! Suggestions the user accepts are counted in `autocomplete_usage`, and
+//! frequently used ones rank above equally good fuzzy matches. A query stops
+//! scanning at its deadline and returns the best matches found so far, so a
+//! large workspace never stalls typing.
+
+use crate::launcher::fuzzy_match;
+use parking_lot::RwLock;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Rebuild the index at most this often while the user keeps typing
+const INDEX_TTL: Duration = Duration::from_secs(30);
+/// Workspace files beyond this many are not offered
+pub const MAX_FILES: usize = 20_000;
+/// Matches on the detail (email, description, role) rank below label matches
+const DETAIL_PENALTY: i32 = 24;
+/// Score added per doubling of the usage count
+const USAGE_WEIGHT: f32 = 6.0;
+/// Candidates matched between deadline checks
+const DEADLINE_CHECK_EVERY: usize = 256;
+
+/// Built-in chat commands, typed as `/name`
+const SLASH_COMMANDS: &[(&str, &str)] = &[
+    ("new", "Start a new conversation"),
+    ("clear", "Clear the current conversation"),
+    ("workflow", "Run a workflow"),
+    ("employee", "Hand the task to an AI employee"),
+    ("file", "Attach a workspace file"),
+    ("search", "Search messages, documents and email"),
+    ("model", "Switch the model for this conversation"),
+    ("help", "Show available commands"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    SlashCommand,
+    Workflow,
+    Employee,
+    File,
+    Contact,
+}
+
+impl SuggestionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionKind::SlashCommand => "slash_command",
+            SuggestionKind::Workflow => "workflow",
+            SuggestionKind::Employee => "employee",
+            SuggestionKind::File => "file",
+            SuggestionKind::Contact => "contact",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "slash_command" => Some(SuggestionKind::SlashCommand),
+            "workflow" => Some(SuggestionKind::Workflow),
+            "employee" => Some(SuggestionKind::Employee),
+            "file" => Some(SuggestionKind::File),
+            "contact" => Some(SuggestionKind::Contact),
+            _ => None,
+        }
+    }
+
+    /// Kinds offered for a prefix when the caller does not restrict them:
+    /// `/` completes commands, `@` mentions contacts and employees, and
+    /// anything else completes workflows and files.
+    pub fn for_prefix(prefix: &str) -> Vec<Self> {
+        match prefix.chars().next() {
+            Some('/') => vec![SuggestionKind::SlashCommand],
+            Some('@') => vec![SuggestionKind::Contact, SuggestionKind::Employee],
+            _ => vec![SuggestionKind::Workflow, SuggestionKind::File],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub kind: SuggestionKind,
+    /// Command name, row id, relative path or email
+    pub id: String,
+    pub label: String,
+    pub detail: Option<String>,
+    /// Text that replaces the prefix in the chat input
+    pub insert_text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    #[serde(flatten)]
+    pub candidate: Candidate,
+    pub score: i32,
+    /// Times the user picked this suggestion
+    pub usage_count: u32,
+    /// Char indices in `label` to highlight
+    pub matches: Vec<usize>,
+}
+
+pub struct AutocompleteIndex {
+    candidates: Vec<Candidate>,
+    usage: HashMap<(SuggestionKind, String), u32>,
+    built_at: Instant,
+}
+
+impl AutocompleteIndex {
+    /// Collect every candidate. `files` are workspace-relative paths; the
+    /// rest is read from the database.
+    pub fn build(conn: &Connection, files: Vec<String>) -> rusqlite::Result<Self> {
+        let mut candidates: Vec<Candidate> = SLASH_COMMANDS
+            .iter()
+            .map(|(name, description)| Candidate {
+                kind: SuggestionKind::SlashCommand,
+                id: name.to_string(),
+                label: format!("/{}", name),
+                detail: Some(description.to_string()),
+                insert_text: format!("/{} ", name),
+            })
+            .collect();
+
+        let mut stmt = conn.prepare("SELECT id, name, description FROM workflow_definitions")?;
+        let workflows = stmt.query_map([], |row| {
+            let name: String = row.get(1)?;
+            Ok(Candidate {
+                kind: SuggestionKind::Workflow,
+                id: row.get(0)?,
+                insert_text: name.clone(),
+                label: name,
+                detail: row.get(2)?,
+            })
+        })?;
+        candidates.extend(workflows.collect::<rusqlite::Result<Vec<_>>>()?);
+
+        let mut stmt = conn.prepare("SELECT id, name, role FROM ai_employees")?;
+        let employees = stmt.query_map([], |row| {
+            let name: String = row.get(1)?;
+            Ok(Candidate {
+                kind: SuggestionKind::Employee,
+                id: row.get(0)?,
+                insert_text: format!("@{}", name),
+                label: name,
+                detail: row.get(2)?,
+            })
+        })?;
+        candidates.extend(employees.collect::<rusqlite::Result<Vec<_>>>()?);
+
+        let mut stmt = conn
+            .prepare("SELECT email, display_name, first_name, last_name, company FROM contacts")?;
+        let contacts = stmt.query_map([], |row| {
+            let email: String = row.get(0)?;
+            let full_name = match (
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ) {
+                (Some(first), Some(last)) => Some(format!("{} {}", first, last)),
+                (first, last) => first.or(last),
+            };
+            let label = row
+                .get::<_, Option<String>>(1)?
+                .or(full_name)
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| email.clone());
+            let company: Option<String> = row.get(4)?;
+            Ok(Candidate {
+                kind: SuggestionKind::Contact,
+                insert_text: format!("@{}", email),
+                detail: Some(match company {
+                    Some(company) if !company.is_empty() => format!("{} · {}", email, company),
+                    _ => email.clone(),
+                }),
+                id: email,
+                label,
+            })
+        })?;
+        candidates.extend(contacts.collect::<rusqlite::Result<Vec<_>>>()?);
+
+        candidates.extend(files.into_iter().take(MAX_FILES).map(|path| Candidate {
+            kind: SuggestionKind::File,
+            insert_text: path.clone(),
+            label: path.clone(),
+            detail: None,
+            id: path,
+        }));
+
+        Ok(Self {
+            candidates,
+            usage: load_usage(conn)?,
+            built_at: Instant::now(),
+        })
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.built_at.elapsed() > INDEX_TTL
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Best `limit` suggestions of `kinds` for `prefix`, matching until
+    /// `deadline`. A leading `/` or `@` selects the kind and is not matched.
+    /// Usage breaks ties and lifts frequent picks; an empty prefix returns
+    /// the most used suggestions.
+    pub fn query(
+        &self,
+        prefix: &str,
+        kinds: &[SuggestionKind],
+        limit: usize,
+        deadline: Instant,
+    ) -> Vec<Suggestion> {
+        let query = prefix.trim_start_matches(['/', '@']);
+        let mut suggestions = Vec::new();
+
+        for (scanned, candidate) in self
+            .candidates
+            .iter()
+            .filter(|candidate| kinds.contains(&candidate.kind))
+            .enumerate()
+        {
+            if scanned % DEADLINE_CHECK_EVERY == 0 && scanned > 0 && Instant::now() >= deadline {
+                tracing::debug!(scanned, "Autocomplete query hit its deadline");
+                break;
+            }
+
+            let (score, matches) = match fuzzy_match(query, &candidate.label) {
+                Some(found) => (found.score, found.positions),
+                None => {
+                    let Some(found) = candidate
+                        .detail
+                        .as_deref()
+                        .and_then(|detail| fuzzy_match(query, detail))
+                    else {
+                        continue;
+                    };
+                    (found.score - DETAIL_PENALTY, Vec::new())
+                }
+            };
+            let usage_count = self
+                .usage
+                .get(&(candidate.kind, candidate.id.clone()))
+                .copied()
+                .unwrap_or(0);
+            suggestions.push(Suggestion {
+                candidate: candidate.clone(),
+                score: score + usage_boost(usage_count),
+                usage_count,
+                matches,
+            });
+        }
+
+        // Stable sort keeps index order among equal scores
+        suggestions.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.usage_count.cmp(&a.usage_count))
+        });
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+fn usage_boost(count: u32) -> i32 {
+    ((count as f32 + 1.0).log2() * USAGE_WEIGHT).round() as i32
+}
+
+fn load_usage(conn: &Connection) -> rusqlite::Result<HashMap<(SuggestionKind, String), u32>> {
+    let mut stmt = conn.prepare("SELECT kind, value, count FROM autocomplete_usage")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, u32>(2)?,
+        ))
+    })?;
+
+    let mut usage = HashMap::new();
+    for row in rows {
+        let (kind, value, count) = row?;
+        if let Some(kind) = SuggestionKind::parse(&kind) {
+            usage.insert((kind, value), count);
+        }
+    }
+    Ok(usage)
+}
+
+/// Count an accepted suggestion so it ranks higher next time
+pub fn record_usage(conn: &Connection, kind: SuggestionKind, id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO autocomplete_usage (kind, value, count, last_used_at)
+         VALUES (?1, ?2, 1, ?3)
+         ON CONFLICT(kind, value) DO UPDATE SET
+             count = count + 1,
+             last_used_at = excluded.last_used_at",
+        params![kind.as_str(), id, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Cached autocomplete index, shared by the autocomplete commands
+#[derive(Default)]
+pub struct AutocompleteState {
+    index: RwLock<Option<Arc<AutocompleteIndex>>>,
+    rebuilding: AtomicBool,
+}
+
+impl AutocompleteState {
+    /// The cached index if it is still fresh
+    pub fn cached(&self) -> Option<Arc<AutocompleteIndex>> {
+        self.index
+            .read()
+            .as_ref()
+            .filter(|index| !index.is_stale())
+            .cloned()
+    }
+
+    /// The cached index even if stale, to answer while a rebuild runs
+    pub fn latest(&self) -> Option<Arc<AutocompleteIndex>> {
+        self.index.read().clone()
+    }
+
+    /// Claim the single rebuild slot; false if a rebuild is already running
+    pub fn begin_rebuild(&self) -> bool {
+        !self.rebuilding.swap(true, Ordering::AcqRel)
+    }
+
+    /// Cache a rebuilt index and release the rebuild slot
+    pub fn store(&self, index: AutocompleteIndex) -> Arc<AutocompleteIndex> {
+        let index = Arc::new(index);
+        *self.index.write() = Some(index.clone());
+        self.rebuilding.store(false, Ordering::Release);
+        index
+    }
+
+    /// Release the rebuild slot after a failed rebuild
+    pub fn abandon_rebuild(&self) {
+        self.rebuilding.store(false, Ordering::Release);
+    }
+
+    pub fn invalidate(&self) {
+        self.index.write().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations;
+
+    fn far_deadline() -> Instant {
+        Instant::now() + Duration::from_secs(60)
+    }
+
+    #[test]
+    fn test_kinds_follow_trigger_and_usage_boosts_rank() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::run_migrations(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO workflow_definitions (id, user_id, name, description, nodes, edges)
+                 VALUES ('wf-1', 'u', 'Invoice approval', 'Route invoices to finance', '[]', '[]');
+             INSERT INTO contacts (email, display_name, company, created_at, updated_at)
+                 VALUES ('ana@example.com', 'Ana Lopez', 'Acme', 0, 0),
+                        ('andy@example.com', 'Andy Lee', NULL, 0, 0);",
+        )
+        .unwrap();
+        let files = vec!["src/invoice.rs".to_string(), "README.md".to_string()];
+
+        let index = AutocompleteIndex::build(&conn, files.clone()).unwrap();
+        let results = index.query("/cl", &SuggestionKind::for_prefix("/cl"), 5, far_deadline());
+        assert_eq!(results[0].candidate.insert_text, "/clear ");
+
+        let results = index.query("inv", &SuggestionKind::for_prefix("inv"), 5, far_deadline());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].candidate.kind, SuggestionKind::Workflow);
+        assert_eq!(results[0].matches, vec![0, 1, 2]);
+
+        let mentions = SuggestionKind::for_prefix("@an");
+        let results = index.query("@an", &mentions, 5, far_deadline());
+        assert_eq!(results[0].candidate.id, "ana@example.com");
+        assert_eq!(results[0].candidate.insert_text, "@ana@example.com");
+        assert_eq!(
+            results[0].candidate.detail.as_deref(),
+            Some("ana@example.com · Acme")
+        );
+
+        for _ in 0..3 {
+            record_usage(&conn, SuggestionKind::Contact, "andy@example.com").unwrap();
+        }
+        let index = AutocompleteIndex::build(&conn, files).unwrap();
+        let results = index.query("@an", &mentions, 5, far_deadline());
+        assert_eq!(results[0].candidate.id, "andy@example.com");
+        assert_eq!(results[0].usage_count, 3);
+    }
+
+    #[test]
+    fn test_query_stops_at_deadline() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::run_migrations(&conn).unwrap();
+        let files: Vec<String> = (0..MAX_FILES + 10)
+            .map(|i| format!("src/module_{}.rs", i))
+            .collect();
+
+        let index = AutocompleteIndex::build(&conn, files).unwrap();
+        assert_eq!(index.len(), SLASH_COMMANDS.len() + MAX_FILES);
+
+        let kinds = [SuggestionKind::File];
+        let partial = index.query("mod", &kinds, usize::MAX, Instant::now());
+        assert_eq!(partial.len(), DEADLINE_CHECK_EVERY);
+        let full = index.query("mod", &kinds, usize::MAX, far_deadline());
+        assert_eq!(full.len(), MAX_FILES);
+    }
+}
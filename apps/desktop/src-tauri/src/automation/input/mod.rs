@@ -4,8 +4,10 @@ mod clipboard;
 mod keyboard;
 #[cfg(windows)]
 mod mouse;
+#[cfg(not(windows))]
+mod portable;
 
-#[cfg(test)]
+#[cfg(all(test, windows))]
 mod tests;
 
 #[cfg(windows)]
@@ -14,3 +16,5 @@ pub use clipboard::ClipboardManager;
 pub use keyboard::KeyboardSimulator;
 #[cfg(windows)]
 pub use mouse::{MouseButton, MouseSimulator};
+#[cfg(not(windows))]
+pub use portable::{ClipboardManager, KeyboardSimulator, MouseButton, MouseSimulator};
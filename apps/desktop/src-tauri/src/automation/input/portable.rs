@@ -0,0 +1,387 @@
+//! Keyboard, mouse and clipboard backends for macOS and Linux.
+//!
+//! These mirror the Windows `SendInput` simulators so callers can stay
+//! platform-neutral. Key codes are still Windows virtual-key codes; they are
+//! translated to enigo keys here. Each call opens a short-lived enigo handle
+//! because the underlying X11/Quartz connections are not `Send`.
+
+use crate::automation::uia::BoundingRectangle;
+use anyhow::{anyhow, Result};
+use enigo::{Axis, Button, Coordinate, Direction, Enigo, Key, Keyboard, Mouse, Settings};
+use std::time::Duration;
+
+const VK_BACK: u16 = 0x08;
+const VK_TAB: u16 = 0x09;
+const VK_RETURN: u16 = 0x0D;
+const VK_SHIFT: u16 = 0x10;
+const VK_CONTROL: u16 = 0x11;
+const VK_MENU: u16 = 0x12;
+const VK_ESCAPE: u16 = 0x1B;
+const VK_SPACE: u16 = 0x20;
+const VK_PRIOR: u16 = 0x21;
+const VK_NEXT: u16 = 0x22;
+const VK_END: u16 = 0x23;
+const VK_HOME: u16 = 0x24;
+const VK_LEFT: u16 = 0x25;
+const VK_UP: u16 = 0x26;
+const VK_RIGHT: u16 = 0x27;
+const VK_DOWN: u16 = 0x28;
+const VK_DELETE: u16 = 0x2E;
+const VK_LWIN: u16 = 0x5B;
+const VK_F1: u16 = 0x70;
+const VK_F12: u16 = 0x7B;
+
+fn open() -> Result<Enigo> {
+    Enigo::new(&Settings::default()).map_err(|err| anyhow!("Input backend unavailable: {err}"))
+}
+
+fn input_err(err: enigo::InputError) -> anyhow::Error {
+    anyhow!("Failed to simulate input: {err}")
+}
+
+/// Translate a Windows virtual-key code into the equivalent enigo key.
+fn map_virtual_key(virtual_key: u16) -> Result<Key> {
+    let key = match virtual_key {
+        VK_BACK => Key::Backspace,
+        VK_TAB => Key::Tab,
+        VK_RETURN => Key::Return,
+        VK_SHIFT => Key::Shift,
+        VK_CONTROL => Key::Control,
+        VK_MENU => Key::Alt,
+        VK_ESCAPE => Key::Escape,
+        VK_SPACE => Key::Space,
+        VK_PRIOR => Key::PageUp,
+        VK_NEXT => Key::PageDown,
+        VK_END => Key::End,
+        VK_HOME => Key::Home,
+        VK_LEFT => Key::LeftArrow,
+        VK_UP => Key::UpArrow,
+        VK_RIGHT => Key::RightArrow,
+        VK_DOWN => Key::DownArrow,
+        VK_DELETE => Key::Delete,
+        VK_LWIN => Key::Meta,
+        VK_F1..=VK_F12 => [
+            Key::F1,
+            Key::F2,
+            Key::F3,
+            Key::F4,
+            Key::F5,
+            Key::F6,
+            Key::F7,
+            Key::F8,
+            Key::F9,
+            Key::F10,
+            Key::F11,
+            Key::F12,
+        ][(virtual_key - VK_F1) as usize],
+        // Letters and digits share their ASCII codes with the virtual-key table.
+        0x30..=0x39 | 0x41..=0x5A => Key::Unicode((virtual_key as u8 as char).to_ascii_lowercase()),
+        other => return Err(anyhow!("Unsupported virtual key: 0x{other:02X}")),
+    };
+    Ok(key)
+}
+
+pub struct KeyboardSimulator {
+    typing_delay_ms: u64,
+}
+
+impl KeyboardSimulator {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            typing_delay_ms: 10, // Default 10ms delay between keystrokes
+        })
+    }
+
+    /// Set typing speed (delay in milliseconds between keystrokes)
+    pub fn set_typing_speed(&mut self, delay_ms: u64) {
+        self.typing_delay_ms = delay_ms;
+    }
+
+    pub async fn send_text(&self, text: &str) -> Result<()> {
+        self.send_text_with_delay(text, self.typing_delay_ms).await
+    }
+
+    /// Send text with custom delay between keystrokes
+    pub async fn send_text_with_delay(&self, text: &str, delay_ms: u64) -> Result<()> {
+        if delay_ms == 0 {
+            return open()?.text(text).map_err(input_err);
+        }
+        for ch in text.chars() {
+            self.send_unicode(ch)?;
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+        Ok(())
+    }
+
+    /// Press a key down (without releasing)
+    pub fn key_down(&self, virtual_key: u8) -> Result<()> {
+        let key = map_virtual_key(virtual_key as u16)?;
+        open()?.key(key, Direction::Press).map_err(input_err)
+    }
+
+    /// Release a key
+    pub fn key_up(&self, virtual_key: u8) -> Result<()> {
+        let key = map_virtual_key(virtual_key as u16)?;
+        open()?.key(key, Direction::Release).map_err(input_err)
+    }
+
+    pub fn press_key(&self, virtual_key: u16) -> Result<()> {
+        let key = map_virtual_key(virtual_key)?;
+        open()?.key(key, Direction::Click).map_err(input_err)
+    }
+
+    pub fn hotkey(&self, modifiers: &[u16], key: u16) -> Result<()> {
+        let modifiers = modifiers
+            .iter()
+            .map(|modifier| map_virtual_key(*modifier))
+            .collect::<Result<Vec<_>>>()?;
+        let key = map_virtual_key(key)?;
+        let mut enigo = open()?;
+
+        for modifier in &modifiers {
+            enigo.key(*modifier, Direction::Press).map_err(input_err)?;
+        }
+        let result = enigo.key(key, Direction::Click).map_err(input_err);
+        // Always release modifiers, even if the main key failed.
+        for modifier in modifiers.iter().rev() {
+            let _ = enigo.key(*modifier, Direction::Release);
+        }
+        result
+    }
+
+    pub fn send_unicode(&self, ch: char) -> Result<()> {
+        let mut enigo = open()?;
+        match ch {
+            '\r' | '\n' => enigo.key(Key::Return, Direction::Click),
+            '\t' => enigo.key(Key::Tab, Direction::Click),
+            _ => enigo.text(&ch.to_string()),
+        }
+        .map_err(input_err)
+    }
+
+    pub fn modifier_key(name: &str) -> Option<u16> {
+        match name.to_lowercase().as_str() {
+            "ctrl" | "control" => Some(VK_CONTROL),
+            "alt" => Some(VK_MENU),
+            "shift" => Some(VK_SHIFT),
+            "meta" | "super" | "cmd" | "command" | "win" => Some(VK_LWIN),
+            _ => None,
+        }
+    }
+
+    /// Press a key by name (e.g., "Enter", "Escape", "Tab")
+    pub async fn press_key_by_name(&self, key_name: &str) -> Result<()> {
+        let virtual_key = match key_name.to_lowercase().as_str() {
+            "enter" | "return" => VK_RETURN,
+            "escape" | "esc" => VK_ESCAPE,
+            "tab" => VK_TAB,
+            "backspace" | "back" => VK_BACK,
+            "delete" | "del" => VK_DELETE,
+            "space" => VK_SPACE,
+            "up" | "arrowup" => VK_UP,
+            "down" | "arrowdown" => VK_DOWN,
+            "left" | "arrowleft" => VK_LEFT,
+            "right" | "arrowright" => VK_RIGHT,
+            "home" => VK_HOME,
+            "end" => VK_END,
+            "pageup" | "pgup" => VK_PRIOR,
+            "pagedown" | "pgdown" => VK_NEXT,
+            name => match name
+                .strip_prefix('f')
+                .and_then(|n| n.parse::<u16>().ok())
+                .filter(|n| (1..=12).contains(n))
+            {
+                Some(n) => VK_F1 + n - 1,
+                None => return Err(anyhow!("Unsupported key name: {}", key_name)),
+            },
+        };
+
+        self.press_key(virtual_key)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl From<MouseButton> for Button {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => Button::Left,
+            MouseButton::Right => Button::Right,
+            MouseButton::Middle => Button::Middle,
+        }
+    }
+}
+
+pub struct MouseSimulator;
+
+impl MouseSimulator {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub fn move_to(&self, x: i32, y: i32) -> Result<()> {
+        open()?.move_mouse(x, y, Coordinate::Abs).map_err(input_err)
+    }
+
+    /// Move cursor smoothly to target position with animation
+    pub async fn move_to_smooth(&self, x: i32, y: i32, duration_ms: u32) -> Result<()> {
+        let (from_x, from_y) = open()?.location().map_err(input_err)?;
+        let dx = x - from_x;
+        let dy = y - from_y;
+
+        if dx == 0 && dy == 0 {
+            return Ok(());
+        }
+
+        let duration_ms = duration_ms.max(10);
+        let steps = ((duration_ms as f64 / 16.0).ceil() as usize).max(2); // ~60fps
+        let step_delay = duration_ms / steps as u32;
+
+        for i in 1..=steps {
+            let t = i as f64 / steps as f64;
+            // Ease-out cubic for natural deceleration
+            let ease_t = 1.0 - (1.0 - t).powi(3);
+            self.move_to(
+                from_x + (dx as f64 * ease_t) as i32,
+                from_y + (dy as f64 * ease_t) as i32,
+            )?;
+            if i < steps {
+                tokio::time::sleep(Duration::from_millis(step_delay as u64)).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn double_click(&self, x: i32, y: i32) -> Result<()> {
+        self.click(x, y, MouseButton::Left)?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.click(x, y, MouseButton::Left)
+    }
+
+    pub fn click(&self, x: i32, y: i32, button: MouseButton) -> Result<()> {
+        let mut enigo = open()?;
+        enigo.move_mouse(x, y, Coordinate::Abs).map_err(input_err)?;
+        enigo
+            .button(button.into(), Direction::Click)
+            .map_err(input_err)
+    }
+
+    pub fn click_rect_center(&self, rect: &BoundingRectangle, button: MouseButton) -> Result<()> {
+        let x = (rect.left + rect.width / 2.0).round() as i32;
+        let y = (rect.top + rect.height / 2.0).round() as i32;
+        self.click(x, y, button)
+    }
+
+    pub fn drag(&self, start: (i32, i32), end: (i32, i32)) -> Result<()> {
+        let mut enigo = open()?;
+        enigo
+            .move_mouse(start.0, start.1, Coordinate::Abs)
+            .map_err(input_err)?;
+        enigo
+            .button(Button::Left, Direction::Press)
+            .map_err(input_err)?;
+        let moved = enigo
+            .move_mouse(end.0, end.1, Coordinate::Abs)
+            .map_err(input_err);
+        enigo
+            .button(Button::Left, Direction::Release)
+            .map_err(input_err)?;
+        moved
+    }
+
+    /// Drag with an animated path between the two points.
+    pub async fn drag_and_drop(
+        &self,
+        from_x: i32,
+        from_y: i32,
+        to_x: i32,
+        to_y: i32,
+        duration_ms: u32,
+    ) -> Result<()> {
+        self.move_to(from_x, from_y)?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        open()?
+            .button(Button::Left, Direction::Press)
+            .map_err(input_err)?;
+
+        let steps = ((duration_ms as f64 / 100.0).ceil() as i32).max(5);
+        let step_delay = duration_ms / steps as u32;
+        let mut moved = Ok(());
+        for i in 1..=steps {
+            let x = from_x + (to_x - from_x) * i / steps;
+            let y = from_y + (to_y - from_y) * i / steps;
+            moved = self.move_to(x, y);
+            if moved.is_err() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(step_delay as u64)).await;
+        }
+
+        open()?
+            .button(Button::Left, Direction::Release)
+            .map_err(input_err)?;
+        moved
+    }
+
+    /// Scroll the wheel; positive values scroll up like the Windows backend.
+    pub fn scroll(&self, delta: i32) -> Result<()> {
+        // enigo treats positive lengths as scrolling down.
+        open()?.scroll(-delta, Axis::Vertical).map_err(input_err)
+    }
+
+    /// Scroll up (positive delta)
+    pub fn scroll_up(&self, amount: i32) -> Result<()> {
+        self.scroll(amount)
+    }
+
+    /// Scroll down (negative delta)
+    pub fn scroll_down(&self, amount: i32) -> Result<()> {
+        self.scroll(-amount)
+    }
+
+    /// Drag from one point to another (alias for drag_and_drop with default duration)
+    pub fn drag_to(&self, from_x: i32, from_y: i32, to_x: i32, to_y: i32) -> Result<()> {
+        self.drag((from_x, from_y), (to_x, to_y))
+    }
+}
+
+pub struct ClipboardManager;
+
+impl ClipboardManager {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub fn get_text(&self) -> Result<String> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .map_err(|err| anyhow!("Failed to read clipboard: {err}"))
+    }
+
+    pub fn set_text(&self, text: &str) -> Result<()> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|err| anyhow!("Failed to write clipboard: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_virtual_keys_to_portable_keys() {
+        assert!(matches!(map_virtual_key(VK_RETURN).unwrap(), Key::Return));
+        assert!(matches!(map_virtual_key(0x41).unwrap(), Key::Unicode('a')));
+        assert!(matches!(map_virtual_key(0x37).unwrap(), Key::Unicode('7')));
+        assert!(matches!(map_virtual_key(VK_F1 + 4).unwrap(), Key::F5));
+        assert!(map_virtual_key(0xFF).is_err());
+        assert_eq!(KeyboardSimulator::modifier_key("Cmd"), Some(VK_LWIN));
+    }
+}
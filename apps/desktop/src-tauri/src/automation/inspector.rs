@@ -1,10 +1,14 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+#[cfg(windows)]
 use windows::Win32::Foundation::POINT;
+#[cfg(windows)]
 use windows::Win32::UI::Accessibility::IUIAutomationElement;
 
-use super::uia::{read_bstr, BoundingRectangle, UIAutomationService};
+#[cfg(windows)]
+use super::uia::read_bstr;
+use super::uia::{BoundingRectangle, UIAutomationService};
 
 /// Detailed element information with all properties
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,26 +65,6 @@ impl InspectorService {
         })
     }
 
-    /// Get detailed information about element at specific coordinates
-    pub fn inspect_element_at_point(&self, x: i32, y: i32) -> Result<DetailedElementInfo> {
-        let point = POINT { x, y };
-
-        let element = unsafe {
-            self.uia
-                .automation()
-                .ElementFromPoint(point)
-                .map_err(|err| anyhow!("ElementFromPoint failed: {err:?}"))?
-        };
-
-        self.get_detailed_info(&element)
-    }
-
-    /// Get detailed information about a cached element by ID
-    pub fn inspect_element_by_id(&self, element_id: &str) -> Result<DetailedElementInfo> {
-        let element = self.uia.get_element(element_id)?;
-        self.get_detailed_info(&element)
-    }
-
     /// Find element by selector
     pub fn find_element_by_selector(&self, selector: &ElementSelector) -> Result<Option<String>> {
         match selector.selector_type {
@@ -126,6 +110,29 @@ impl InspectorService {
             }
         }
     }
+}
+
+#[cfg(windows)]
+impl InspectorService {
+    /// Get detailed information about element at specific coordinates
+    pub fn inspect_element_at_point(&self, x: i32, y: i32) -> Result<DetailedElementInfo> {
+        let point = POINT { x, y };
+
+        let element = unsafe {
+            self.uia
+                .automation()
+                .ElementFromPoint(point)
+                .map_err(|err| anyhow!("ElementFromPoint failed: {err:?}"))?
+        };
+
+        self.get_detailed_info(&element)
+    }
+
+    /// Get detailed information about a cached element by ID
+    pub fn inspect_element_by_id(&self, element_id: &str) -> Result<DetailedElementInfo> {
+        let element = self.uia.get_element(element_id)?;
+        self.get_detailed_info(&element)
+    }
 
     /// Generate selector for an element
     pub fn generate_selector(&self, element_id: &str) -> Result<Vec<ElementSelector>> {
@@ -343,3 +350,123 @@ impl InspectorService {
         })
     }
 }
+
+#[cfg(not(windows))]
+impl InspectorService {
+    /// Get detailed information about element at specific coordinates
+    pub fn inspect_element_at_point(&self, x: i32, y: i32) -> Result<DetailedElementInfo> {
+        let element_id = self.uia.element_at_point(x, y)?;
+        self.inspect_element_by_id(&element_id)
+    }
+
+    /// Get detailed information about an element by ID
+    pub fn inspect_element_by_id(&self, element_id: &str) -> Result<DetailedElementInfo> {
+        let details = self.uia.details(element_id)?;
+        let info = details.info;
+
+        let mut properties = HashMap::new();
+        properties.insert("name".to_string(), serde_json::json!(info.name));
+        properties.insert("class_name".to_string(), serde_json::json!(info.class_name));
+        properties.insert(
+            "control_type".to_string(),
+            serde_json::json!(info.control_type),
+        );
+        if let Some(ref aid) = details.automation_id {
+            properties.insert("automation_id".to_string(), serde_json::json!(aid));
+        }
+        properties.insert(
+            "is_enabled".to_string(),
+            serde_json::json!(details.is_enabled),
+        );
+        properties.insert(
+            "is_offscreen".to_string(),
+            serde_json::json!(details.is_offscreen),
+        );
+        properties.insert(
+            "has_keyboard_focus".to_string(),
+            serde_json::json!(details.has_keyboard_focus),
+        );
+
+        let (parent, children) = self.get_element_tree(element_id)?;
+
+        Ok(DetailedElementInfo {
+            id: info.id,
+            name: info.name,
+            class_name: info.class_name,
+            control_type: info.control_type,
+            bounding_rect: info.bounding_rect,
+            properties,
+            automation_id: details.automation_id,
+            parent,
+            children,
+            is_enabled: details.is_enabled,
+            is_offscreen: details.is_offscreen,
+            has_keyboard_focus: details.has_keyboard_focus,
+        })
+    }
+
+    /// Generate selector for an element
+    pub fn generate_selector(&self, element_id: &str) -> Result<Vec<ElementSelector>> {
+        let details = self.uia.details(element_id)?;
+        let mut selectors = Vec::new();
+
+        if let Some(automation_id) = details.automation_id {
+            selectors.push(ElementSelector {
+                selector_type: SelectorType::AutomationId,
+                value: automation_id,
+            });
+        }
+
+        if !details.info.name.is_empty() {
+            selectors.push(ElementSelector {
+                selector_type: SelectorType::Name,
+                value: details.info.name,
+            });
+        }
+
+        if !details.info.class_name.is_empty() {
+            selectors.push(ElementSelector {
+                selector_type: SelectorType::ClassName,
+                value: details.info.class_name,
+            });
+        }
+
+        if let Some(rect) = details.info.bounding_rect {
+            let x = (rect.left + rect.width / 2.0).round() as i32;
+            let y = (rect.top + rect.height / 2.0).round() as i32;
+            selectors.push(ElementSelector {
+                selector_type: SelectorType::Coordinates,
+                value: format!("{},{}", x, y),
+            });
+        }
+
+        Ok(selectors)
+    }
+
+    /// Get element tree (parent and children)
+    pub fn get_element_tree(
+        &self,
+        element_id: &str,
+    ) -> Result<(Option<BasicElementInfo>, Vec<BasicElementInfo>)> {
+        let parent = self.uia.parent_of(element_id).ok().map(basic_info);
+        let children = self
+            .uia
+            .children_of(element_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(basic_info)
+            .collect();
+
+        Ok((parent, children))
+    }
+}
+
+#[cfg(not(windows))]
+fn basic_info(info: super::uia::UIElementInfo) -> BasicElementInfo {
+    BasicElementInfo {
+        id: info.id,
+        name: info.name,
+        class_name: info.class_name,
+        control_type: info.control_type,
+    }
+}
@@ -94,6 +94,13 @@ impl UIAutomationService {
             .map_err(|err| anyhow!("SetForegroundWindow failed: {err:?}"))
     }
 
+    pub fn is_enabled(&self, element_id: &str) -> Result<bool> {
+        let element = self.get_element(element_id)?;
+        unsafe { element.CurrentIsEnabled() }
+            .map(|enabled| enabled.as_bool())
+            .map_err(|err| anyhow!("CurrentIsEnabled failed: {err:?}"))
+    }
+
     /// Get a cell value from a grid or table element.
     ///
     /// # Arguments
@@ -0,0 +1,619 @@
+//! AT-SPI backed [`UIAutomationService`] for Linux desktops.
+//!
+//! Talks to the accessibility bus directly over D-Bus. Element ids are the
+//! `bus_name|object_path` pair of the accessible object, so they resolve
+//! without a cache. Where no AT-SPI registry is running (including macOS),
+//! `new()` fails just like a missing UIA provider does on Windows.
+
+use super::element_tree::{BoundingRectangle, ElementQuery, UIElementInfo};
+use super::patterns::PatternCapabilities;
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use zbus::blocking::Connection;
+use zbus::zvariant::{DynamicType, OwnedObjectPath, OwnedValue, Type, Value};
+
+const REGISTRY: &str = "org.a11y.atspi.Registry";
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const NULL_PATH: &str = "/org/a11y/atspi/null";
+
+const ACCESSIBLE: &str = "org.a11y.atspi.Accessible";
+const ACTION: &str = "org.a11y.atspi.Action";
+const COMPONENT: &str = "org.a11y.atspi.Component";
+const EDITABLE_TEXT: &str = "org.a11y.atspi.EditableText";
+const SELECTION: &str = "org.a11y.atspi.Selection";
+const TABLE: &str = "org.a11y.atspi.Table";
+const TEXT: &str = "org.a11y.atspi.Text";
+const VALUE: &str = "org.a11y.atspi.Value";
+const PROPERTIES: &str = "org.freedesktop.DBus.Properties";
+
+// AtspiStateType bit positions.
+const STATE_ENABLED: u32 = 8;
+const STATE_EXPANDABLE: u32 = 9;
+const STATE_EXPANDED: u32 = 10;
+const STATE_FOCUSED: u32 = 12;
+const STATE_SHOWING: u32 = 25;
+
+/// ATSPI_COORD_TYPE_SCREEN
+const COORD_SCREEN: u32 = 0;
+/// ATSPI_SCROLL_ANYWHERE
+const SCROLL_ANYWHERE: u32 = 6;
+
+/// Upper bound on accessibles visited by a single search so queries against
+/// very large trees (browsers, IDEs) stay responsive.
+const MAX_VISITED: usize = 5_000;
+
+type ObjectRef = (String, OwnedObjectPath);
+
+/// Properties the inspector needs beyond [`UIElementInfo`].
+#[derive(Debug, Clone)]
+pub(crate) struct ElementDetails {
+    pub info: UIElementInfo,
+    pub automation_id: Option<String>,
+    pub is_enabled: bool,
+    pub is_offscreen: bool,
+    pub has_keyboard_focus: bool,
+}
+
+pub struct UIAutomationService {
+    conn: Connection,
+}
+
+impl UIAutomationService {
+    pub fn new() -> Result<Self> {
+        let address = match std::env::var("AT_SPI_BUS_ADDRESS") {
+            Ok(address) if !address.is_empty() => address,
+            _ => Connection::session()
+                .context("D-Bus session bus unavailable")?
+                .call_method(
+                    Some("org.a11y.Bus"),
+                    "/org/a11y/bus",
+                    Some("org.a11y.Bus"),
+                    "GetAddress",
+                    &(),
+                )
+                .context("AT-SPI accessibility bus is not running")?
+                .body()
+                .deserialize::<String>()?,
+        };
+
+        let conn = zbus::blocking::connection::Builder::address(address.as_str())?
+            .build()
+            .context("Failed to connect to the accessibility bus")?;
+
+        Ok(Self { conn })
+    }
+
+    pub fn list_windows(&self) -> Result<Vec<UIElementInfo>> {
+        let mut results = Vec::new();
+        for (bus, path) in self.top_level_windows()? {
+            if let Ok(info) = self.describe(&bus, &path) {
+                results.push(info);
+            }
+        }
+        Ok(results)
+    }
+
+    pub fn find_elements(
+        &self,
+        parent_id: Option<String>,
+        query: &ElementQuery,
+    ) -> Result<Vec<UIElementInfo>> {
+        let root = if let Some(parent_id) = parent_id {
+            parse_id(&parent_id)?
+        } else if let Some(window_name) = &query.window {
+            let id = self
+                .find_window(window_name, query.window_class.as_deref())?
+                .ok_or_else(|| anyhow!("Window '{window_name}' not found"))?;
+            parse_id(&id)?
+        } else {
+            (REGISTRY.to_string(), ROOT_PATH.to_string())
+        };
+
+        let max_results = query.max_results.unwrap_or(50);
+        let mut results = Vec::new();
+        let mut queue = VecDeque::from([root]);
+        let mut visited = 0;
+
+        while let Some((bus, path)) = queue.pop_front() {
+            if results.len() >= max_results || visited >= MAX_VISITED {
+                break;
+            }
+            visited += 1;
+
+            if self.matches(&bus, &path, query) {
+                if let Ok(info) = self.describe(&bus, &path) {
+                    results.push(info);
+                }
+            }
+
+            for (child_bus, child_path) in self.children(&bus, &path).unwrap_or_default() {
+                queue.push_back((child_bus, child_path.to_string()));
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Find a top-level window by title (and optionally role), returning its id.
+    pub fn find_window(&self, title: &str, class_name: Option<&str>) -> Result<Option<String>> {
+        for (bus, path) in self.top_level_windows()? {
+            let name: String = self
+                .property(&bus, &path, ACCESSIBLE, "Name")
+                .unwrap_or_default();
+            if name != title {
+                continue;
+            }
+            if let Some(class_name) = class_name {
+                if self.role_name(&bus, &path).ok().as_deref() != Some(class_name) {
+                    continue;
+                }
+            }
+            return Ok(Some(element_id(&bus, &path)));
+        }
+        Ok(None)
+    }
+
+    pub fn check_patterns(&self, element_id: &str) -> Result<PatternCapabilities> {
+        let (bus, path) = parse_id(element_id)?;
+        let interfaces: Vec<String> = self.call(&bus, &path, ACCESSIBLE, "GetInterfaces", &())?;
+        let has = |iface: &str| interfaces.iter().any(|i| i == iface);
+        let states = self.states(&bus, &path).unwrap_or_default();
+        let role = self.role_name(&bus, &path).unwrap_or_default();
+
+        Ok(PatternCapabilities {
+            invoke: has(ACTION),
+            value: has(EDITABLE_TEXT) || has(VALUE),
+            toggle: has(ACTION) && is_toggle_role(&role),
+            text: has(TEXT),
+            grid: has(TABLE),
+            table: has(TABLE),
+            scroll: has(COMPONENT),
+            expand_collapse: has_state(states, STATE_EXPANDABLE),
+            selection: has(SELECTION),
+        })
+    }
+
+    pub fn invoke(&self, element_id: &str) -> Result<()> {
+        let (bus, path) = parse_id(element_id)?;
+        self.do_action(&bus, &path, 0)
+    }
+
+    pub fn set_value(&self, element_id: &str, value: &str) -> Result<()> {
+        let (bus, path) = parse_id(element_id)?;
+        if let Ok(done) =
+            self.call::<_, bool>(&bus, &path, EDITABLE_TEXT, "SetTextContents", &value)
+        {
+            return if done {
+                Ok(())
+            } else {
+                Err(anyhow!("Element rejected the new text"))
+            };
+        }
+
+        // Sliders, spin buttons and other numeric controls expose Value instead.
+        let number: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Element does not support text input"))?;
+        self.call::<_, ()>(
+            &bus,
+            &path,
+            PROPERTIES,
+            "Set",
+            &(VALUE, "CurrentValue", Value::from(number)),
+        )
+    }
+
+    pub fn get_value(&self, element_id: &str) -> Result<String> {
+        let (bus, path) = parse_id(element_id)?;
+        self.read_value(&bus, &path)
+    }
+
+    pub fn toggle(&self, element_id: &str) -> Result<()> {
+        self.invoke(element_id)
+    }
+
+    pub fn bounding_rect(&self, element_id: &str) -> Result<Option<BoundingRectangle>> {
+        let (bus, path) = parse_id(element_id)?;
+        self.extents(&bus, &path)
+    }
+
+    pub fn set_focus(&self, element_id: &str) -> Result<()> {
+        let (bus, path) = parse_id(element_id)?;
+        if self.call::<_, bool>(&bus, &path, COMPONENT, "GrabFocus", &())? {
+            Ok(())
+        } else {
+            Err(anyhow!("Element refused keyboard focus"))
+        }
+    }
+
+    pub fn focus_window(&self, element_id: &str) -> Result<()> {
+        self.set_focus(element_id)
+            .map_err(|err| anyhow!("Failed to focus window: {err}"))
+    }
+
+    pub fn is_enabled(&self, element_id: &str) -> Result<bool> {
+        let (bus, path) = parse_id(element_id)?;
+        Ok(has_state(self.states(&bus, &path)?, STATE_ENABLED))
+    }
+
+    pub fn get_table_cell(&self, element_id: &str, row: i32, column: i32) -> Result<String> {
+        let (bus, path) = parse_id(element_id)?;
+        let (cell_bus, cell_path): ObjectRef =
+            self.call(&bus, &path, TABLE, "GetAccessibleAt", &(row, column))?;
+        if cell_path.as_str() == NULL_PATH {
+            return Err(anyhow!("No cell at row {row}, column {column}"));
+        }
+        self.read_value(&cell_bus, cell_path.as_str())
+    }
+
+    pub fn scroll_to_element(&self, element_id: &str) -> Result<()> {
+        let (bus, path) = parse_id(element_id)?;
+        if self.call::<_, bool>(&bus, &path, COMPONENT, "ScrollTo", &SCROLL_ANYWHERE)? {
+            Ok(())
+        } else {
+            Err(anyhow!("Element could not be scrolled into view"))
+        }
+    }
+
+    pub fn expand_tree_node(&self, element_id: &str, expand: bool) -> Result<()> {
+        let (bus, path) = parse_id(element_id)?;
+        let states = self.states(&bus, &path)?;
+        if !has_state(states, STATE_EXPANDABLE) {
+            return Err(anyhow!("Element cannot be expanded or collapsed"));
+        }
+        if has_state(states, STATE_EXPANDED) == expand {
+            return Ok(());
+        }
+
+        let count: i32 = self.property(&bus, &path, ACTION, "NActions")?;
+        let index = (0..count)
+            .find(|index| {
+                self.call::<_, String>(&bus, &path, ACTION, "GetName", index)
+                    .map(|name| {
+                        let name = name.to_lowercase();
+                        name.contains("expand") || name.contains("collapse")
+                    })
+                    .unwrap_or(false)
+            })
+            .unwrap_or(0);
+        self.do_action(&bus, &path, index)
+    }
+
+    pub fn get_grid_row_count(&self, element_id: &str) -> Result<i32> {
+        let (bus, path) = parse_id(element_id)?;
+        self.property(&bus, &path, TABLE, "NRows")
+    }
+
+    pub fn get_grid_column_count(&self, element_id: &str) -> Result<i32> {
+        let (bus, path) = parse_id(element_id)?;
+        self.property(&bus, &path, TABLE, "NColumns")
+    }
+
+    /// Element ids resolve directly on the bus, so there is nothing to expire.
+    pub fn clear_expired_cache(&self) {}
+
+    /// Element ids resolve directly on the bus, so there is nothing to clear.
+    pub fn clear_cache(&self) {}
+
+    pub(crate) fn details(&self, element_id: &str) -> Result<ElementDetails> {
+        let (bus, path) = parse_id(element_id)?;
+        let info = self.describe(&bus, &path)?;
+        let states = self.states(&bus, &path).unwrap_or_default();
+        let automation_id = self
+            .attributes(&bus, &path)
+            .ok()
+            .and_then(|mut attributes| attributes.remove("id"))
+            .filter(|id| !id.is_empty());
+
+        Ok(ElementDetails {
+            info,
+            automation_id,
+            is_enabled: has_state(states, STATE_ENABLED),
+            is_offscreen: !has_state(states, STATE_SHOWING),
+            has_keyboard_focus: has_state(states, STATE_FOCUSED),
+        })
+    }
+
+    /// Deepest accessible under a screen coordinate.
+    pub(crate) fn element_at_point(&self, x: i32, y: i32) -> Result<String> {
+        for (bus, path) in self.top_level_windows()? {
+            let states = self.states(&bus, &path).unwrap_or_default();
+            let contains = self
+                .extents(&bus, &path)
+                .ok()
+                .flatten()
+                .map(|rect| rect_contains(&rect, x, y))
+                .unwrap_or(false);
+            if !has_state(states, STATE_SHOWING) || !contains {
+                continue;
+            }
+
+            let mut current = (bus, path);
+            loop {
+                let next: Result<ObjectRef> = self.call(
+                    &current.0,
+                    &current.1,
+                    COMPONENT,
+                    "GetAccessibleAtPoint",
+                    &(x, y, COORD_SCREEN),
+                );
+                match next {
+                    Ok((bus, path)) if path.as_str() != NULL_PATH && path.as_str() != current.1 => {
+                        current = (bus, path.to_string());
+                    }
+                    _ => break,
+                }
+            }
+            return Ok(element_id(&current.0, &current.1));
+        }
+        Err(anyhow!("No accessible element at ({x}, {y})"))
+    }
+
+    pub(crate) fn parent_of(&self, element_id: &str) -> Result<UIElementInfo> {
+        let (bus, path) = parse_id(element_id)?;
+        let (parent_bus, parent_path): ObjectRef =
+            self.property(&bus, &path, ACCESSIBLE, "Parent")?;
+        if parent_path.as_str() == NULL_PATH {
+            return Err(anyhow!("Element has no parent"));
+        }
+        self.describe(&parent_bus, parent_path.as_str())
+    }
+
+    pub(crate) fn children_of(&self, element_id: &str) -> Result<Vec<UIElementInfo>> {
+        let (bus, path) = parse_id(element_id)?;
+        Ok(self
+            .children(&bus, &path)?
+            .into_iter()
+            .filter_map(|(bus, path)| self.describe(&bus, path.as_str()).ok())
+            .collect())
+    }
+
+    fn describe(&self, bus: &str, path: &str) -> Result<UIElementInfo> {
+        let name: String = self
+            .property(bus, path, ACCESSIBLE, "Name")
+            .unwrap_or_default();
+        let role = self.role_name(bus, path)?;
+        Ok(UIElementInfo {
+            id: element_id(bus, path),
+            name,
+            control_type: control_type_for_role(&role),
+            class_name: role,
+            bounding_rect: self.extents(bus, path).ok().flatten(),
+        })
+    }
+
+    fn matches(&self, bus: &str, path: &str, query: &ElementQuery) -> bool {
+        if let Some(expected) = &query.name {
+            let name: String = self
+                .property(bus, path, ACCESSIBLE, "Name")
+                .unwrap_or_default();
+            if &name != expected {
+                return false;
+            }
+        }
+
+        if query.class_name.is_some() || query.control_type.is_some() {
+            let Ok(role) = self.role_name(bus, path) else {
+                return false;
+            };
+            if query
+                .class_name
+                .as_ref()
+                .is_some_and(|class| class != &role)
+            {
+                return false;
+            }
+            if let Some(control_type) = &query.control_type {
+                if normalize(&control_type_for_role(&role)) != normalize(control_type) {
+                    return false;
+                }
+            }
+        }
+
+        if let Some(expected) = &query.automation_id {
+            let id = self
+                .attributes(bus, path)
+                .ok()
+                .and_then(|mut attributes| attributes.remove("id"));
+            if id.as_ref() != Some(expected) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn top_level_windows(&self) -> Result<Vec<(String, String)>> {
+        let mut windows = Vec::new();
+        for (app_bus, app_path) in self.children(REGISTRY, ROOT_PATH)? {
+            for (bus, path) in self
+                .children(&app_bus, app_path.as_str())
+                .unwrap_or_default()
+            {
+                windows.push((bus, path.to_string()));
+            }
+        }
+        Ok(windows)
+    }
+
+    fn children(&self, bus: &str, path: &str) -> Result<Vec<ObjectRef>> {
+        self.call(bus, path, ACCESSIBLE, "GetChildren", &())
+    }
+
+    fn role_name(&self, bus: &str, path: &str) -> Result<String> {
+        self.call(bus, path, ACCESSIBLE, "GetRoleName", &())
+    }
+
+    fn states(&self, bus: &str, path: &str) -> Result<u64> {
+        let words: Vec<u32> = self.call(bus, path, ACCESSIBLE, "GetState", &())?;
+        Ok(state_bits(&words))
+    }
+
+    fn attributes(&self, bus: &str, path: &str) -> Result<HashMap<String, String>> {
+        self.call(bus, path, ACCESSIBLE, "GetAttributes", &())
+    }
+
+    fn extents(&self, bus: &str, path: &str) -> Result<Option<BoundingRectangle>> {
+        let (x, y, width, height): (i32, i32, i32, i32) =
+            self.call(bus, path, COMPONENT, "GetExtents", &COORD_SCREEN)?;
+        if width <= 0 && height <= 0 {
+            return Ok(None);
+        }
+        Ok(Some(BoundingRectangle {
+            left: x as f64,
+            top: y as f64,
+            width: width.max(0) as f64,
+            height: height.max(0) as f64,
+        }))
+    }
+
+    fn read_value(&self, bus: &str, path: &str) -> Result<String> {
+        if let Ok(count) = self.property::<i32>(bus, path, TEXT, "CharacterCount") {
+            return self.call(bus, path, TEXT, "GetText", &(0i32, count));
+        }
+        if let Ok(value) = self.property::<f64>(bus, path, VALUE, "CurrentValue") {
+            return Ok(value.to_string());
+        }
+        self.property(bus, path, ACCESSIBLE, "Name")
+    }
+
+    fn do_action(&self, bus: &str, path: &str, index: i32) -> Result<()> {
+        if self.call::<_, bool>(bus, path, ACTION, "DoAction", &index)? {
+            Ok(())
+        } else {
+            Err(anyhow!("Element did not perform action {index}"))
+        }
+    }
+
+    fn call<B, T>(&self, bus: &str, path: &str, iface: &str, method: &str, body: &B) -> Result<T>
+    where
+        B: Serialize + DynamicType,
+        T: DeserializeOwned + Type,
+    {
+        let reply = self
+            .conn
+            .call_method(Some(bus), path, Some(iface), method, body)
+            .with_context(|| format!("{iface}.{method} failed"))?;
+        reply
+            .body()
+            .deserialize::<T>()
+            .with_context(|| format!("Unexpected reply from {iface}.{method}"))
+    }
+
+    fn property<T>(&self, bus: &str, path: &str, iface: &str, name: &str) -> Result<T>
+    where
+        T: TryFrom<OwnedValue>,
+        T::Error: std::fmt::Display,
+    {
+        let value: OwnedValue = self.call(bus, path, PROPERTIES, "Get", &(iface, name))?;
+        T::try_from(value).map_err(|err| anyhow!("Unexpected type for {iface}.{name}: {err}"))
+    }
+}
+
+fn element_id(bus: &str, path: &str) -> String {
+    format!("{bus}|{path}")
+}
+
+fn parse_id(id: &str) -> Result<(String, String)> {
+    id.split_once('|')
+        .filter(|(bus, path)| !bus.is_empty() && path.starts_with('/'))
+        .map(|(bus, path)| (bus.to_string(), path.to_string()))
+        .ok_or_else(|| anyhow!("Unknown element id: {id}"))
+}
+
+/// AT-SPI reports states as two 32-bit words.
+fn state_bits(words: &[u32]) -> u64 {
+    let low = words.first().copied().unwrap_or(0) as u64;
+    let high = words.get(1).copied().unwrap_or(0) as u64;
+    low | (high << 32)
+}
+
+fn has_state(states: u64, state: u32) -> bool {
+    states & (1 << state) != 0
+}
+
+fn rect_contains(rect: &BoundingRectangle, x: i32, y: i32) -> bool {
+    let (x, y) = (x as f64, y as f64);
+    x >= rect.left && x < rect.left + rect.width && y >= rect.top && y < rect.top + rect.height
+}
+
+fn is_toggle_role(role: &str) -> bool {
+    matches!(
+        role,
+        "check box" | "toggle button" | "radio button" | "check menu item" | "switch"
+    )
+}
+
+fn normalize(control_type: &str) -> String {
+    control_type
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Map AT-SPI role names onto the UIA control type names used by the rest of
+/// the app, so queries like `control_type: "button"` work on every platform.
+fn control_type_for_role(role: &str) -> String {
+    let mapped = match role {
+        "push button" | "toggle button" | "button" => "Button",
+        "entry" | "text" | "password text" | "editbar" => "Edit",
+        "check box" | "switch" => "CheckBox",
+        "combo box" => "ComboBox",
+        "list item" => "ListItem",
+        "menu item" | "check menu item" | "radio menu item" => "MenuItem",
+        "table cell" => "DataItem",
+        "label" | "static" => "Text",
+        "frame" | "window" | "dialog" => "Window",
+        other => {
+            return other
+                .split_whitespace()
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                        .unwrap_or_default()
+                })
+                .collect();
+        }
+    };
+    mapped.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_ids_round_trip() {
+        let id = element_id(":1.42", "/org/a11y/atspi/accessible/17");
+        assert_eq!(
+            parse_id(&id).unwrap(),
+            (
+                ":1.42".to_string(),
+                "/org/a11y/atspi/accessible/17".to_string()
+            )
+        );
+        assert!(parse_id("123-456").is_err());
+    }
+
+    #[test]
+    fn maps_roles_to_control_types() {
+        assert_eq!(control_type_for_role("push button"), "Button");
+        assert_eq!(control_type_for_role("frame"), "Window");
+        assert_eq!(control_type_for_role("tree table"), "TreeTable");
+        assert_eq!(normalize("Check Box"), normalize("CheckBox"));
+    }
+
+    #[test]
+    fn combines_state_words() {
+        let states = state_bits(&[1 << STATE_ENABLED, 1]);
+        assert!(has_state(states, STATE_ENABLED));
+        assert!(has_state(states, 32));
+        assert!(!has_state(states, STATE_FOCUSED));
+    }
+}
@@ -1,5 +1,7 @@
+#[cfg(windows)]
 use super::*;
 use serde::{Deserialize, Serialize};
+#[cfg(windows)]
 use windows::Win32::UI::Accessibility::{
     IUIAutomationCondition, TreeScope_Children, TreeScope_Subtree, UIA_AutomationIdPropertyId,
     UIA_ButtonControlTypeId, UIA_CheckBoxControlTypeId, UIA_ClassNamePropertyId,
@@ -44,6 +46,7 @@ pub struct ElementQuery {
     pub max_results: Option<usize>,
 }
 
+#[cfg(windows)]
 impl UIAutomationService {
     pub fn list_windows(&self) -> Result<Vec<UIElementInfo>> {
         let desktop = self.root_element()?;
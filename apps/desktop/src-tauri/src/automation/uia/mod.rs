@@ -1,48 +1,67 @@
+#[cfg(windows)]
 use anyhow::{anyhow, Result};
+#[cfg(windows)]
 use std::collections::HashMap;
+#[cfg(windows)]
 use std::sync::{Mutex, OnceLock};
+#[cfg(windows)]
 use std::time::{Duration, Instant};
+#[cfg(windows)]
 use windows::core::{Interface, BSTR, VARIANT};
+#[cfg(windows)]
 use windows::Win32::System::Com::{
     CoCreateInstance, CoInitializeEx, CoInitializeSecurity, CLSCTX_INPROC_SERVER,
     COINIT_APARTMENTTHREADED, EOAC_NONE, RPC_C_AUTHN_LEVEL_DEFAULT, RPC_C_IMP_LEVEL_IDENTIFY,
     SAFEARRAY,
 };
+#[cfg(windows)]
 use windows::Win32::System::Ole::{
     SafeArrayAccessData, SafeArrayDestroy, SafeArrayGetLBound, SafeArrayGetUBound,
     SafeArrayUnaccessData,
 };
+#[cfg(windows)]
 use windows::Win32::UI::Accessibility::{CUIAutomation, IUIAutomation, IUIAutomationElement};
 
+#[cfg(windows)]
 mod actions;
+#[cfg(not(windows))]
+mod atspi;
 mod element_tree;
 mod patterns;
 mod wait;
 
-#[cfg(test)]
+#[cfg(all(test, windows))]
 mod tests;
 
+#[cfg(not(windows))]
+pub use atspi::UIAutomationService;
 pub use element_tree::{BoundingRectangle, ElementQuery, UIElementInfo};
 pub use patterns::PatternCapabilities;
 pub use wait::WaitConfig;
 
+#[cfg(windows)]
 static COM_INITIALIZED: OnceLock<()> = OnceLock::new();
 
+#[cfg(windows)]
 #[derive(Clone)]
 struct CachedElement {
     element: IUIAutomationElement,
     cached_at: Instant,
 }
 
+#[cfg(windows)]
 pub struct UIAutomationService {
     automation: IUIAutomation,
     cache: Mutex<HashMap<String, CachedElement>>,
     cache_ttl: Duration,
 }
 
+#[cfg(windows)]
 unsafe impl Send for UIAutomationService {}
+#[cfg(windows)]
 unsafe impl Sync for UIAutomationService {}
 
+#[cfg(windows)]
 impl UIAutomationService {
     pub fn new() -> Result<Self> {
         // Thread-safe COM initialization using OnceLock
@@ -145,6 +164,7 @@ impl UIAutomationService {
 //     }
 // }
 
+#[cfg(windows)]
 pub(super) fn read_bstr<F>(mut f: F) -> Option<String>
 where
     F: FnMut() -> Option<BSTR>,
@@ -152,6 +172,7 @@ where
     f().map(|b| b.to_string())
 }
 
+#[cfg(windows)]
 pub(super) fn safe_array_to_runtime_id(array: *mut SAFEARRAY) -> Result<String> {
     unsafe {
         if array.is_null() {
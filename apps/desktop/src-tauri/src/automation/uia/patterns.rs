@@ -1,5 +1,7 @@
+#[cfg(windows)]
 use super::*;
 use serde::{Deserialize, Serialize};
+#[cfg(windows)]
 use windows::Win32::UI::Accessibility::{
     IUIAutomationElement, IUIAutomationExpandCollapsePattern, IUIAutomationGridPattern,
     IUIAutomationInvokePattern, IUIAutomationScrollPattern, IUIAutomationSelectionItemPattern,
@@ -22,6 +24,7 @@ pub struct PatternCapabilities {
     pub selection: bool,
 }
 
+#[cfg(windows)]
 impl PatternCapabilities {
     pub fn from_element(element: &IUIAutomationElement) -> PatternCapabilities {
         PatternCapabilities {
@@ -38,6 +41,7 @@ impl PatternCapabilities {
     }
 }
 
+#[cfg(windows)]
 pub(super) fn get_invoke_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationInvokePattern> {
@@ -48,6 +52,7 @@ pub(super) fn get_invoke_pattern(
     }
 }
 
+#[cfg(windows)]
 pub(super) fn get_value_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationValuePattern> {
@@ -58,6 +63,7 @@ pub(super) fn get_value_pattern(
     }
 }
 
+#[cfg(windows)]
 pub(super) fn get_toggle_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationTogglePattern> {
@@ -68,6 +74,7 @@ pub(super) fn get_toggle_pattern(
     }
 }
 
+#[cfg(windows)]
 pub(super) fn get_selection_item_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationSelectionItemPattern> {
@@ -78,6 +85,7 @@ pub(super) fn get_selection_item_pattern(
     }
 }
 
+#[cfg(windows)]
 pub(super) fn get_text_pattern(element: &IUIAutomationElement) -> Option<IUIAutomationTextPattern> {
     unsafe {
         element
@@ -95,6 +103,7 @@ pub(super) fn get_text_pattern(element: &IUIAutomationElement) -> Option<IUIAuto
 /// - Spreadsheet applications (Excel)
 /// - Data grids
 /// - Calendars
+#[cfg(windows)]
 pub(super) fn get_grid_pattern(element: &IUIAutomationElement) -> Option<IUIAutomationGridPattern> {
     unsafe {
         element
@@ -110,6 +119,7 @@ pub(super) fn get_grid_pattern(element: &IUIAutomationElement) -> Option<IUIAuto
 /// - Excel spreadsheets
 /// - Data tables with headers
 /// - Grid controls with header information
+#[cfg(windows)]
 pub(super) fn get_table_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationTablePattern> {
@@ -129,6 +139,7 @@ pub(super) fn get_table_pattern(
 /// - Document viewers
 ///
 /// Note: Many applications support this pattern for scrolling elements into view.
+#[cfg(windows)]
 pub(super) fn get_scroll_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationScrollPattern> {
@@ -147,6 +158,7 @@ pub(super) fn get_scroll_pattern(
 /// - Menu items with submenus
 /// - Combo boxes
 /// - Accordion controls
+#[cfg(windows)]
 pub(super) fn get_expand_collapse_pattern(
     element: &IUIAutomationElement,
 ) -> Option<IUIAutomationExpandCollapsePattern> {
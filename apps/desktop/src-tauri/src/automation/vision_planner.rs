@@ -152,6 +152,7 @@ impl ActionPlanner {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         // Get candidates (prefer vision-capable models)
@@ -170,7 +171,7 @@ impl ActionPlanner {
         }
 
         let outcome = router
-            .invoke_candidate(&candidates[0], &request)
+            .invoke_with_failover(&candidates, &request)
             .await
             .context("Vision LLM request failed")?;
 
@@ -31,6 +31,12 @@ pub struct PaymentMethodInfo;
 
 use std::sync::{Arc, Mutex};
 
+#[cfg(feature = "billing")]
+use crate::audit::{self, Actor, AuditCategory, AuditEntry};
+use crate::security::{AdminUser, AuthenticatedUser};
+#[cfg(feature = "billing")]
+use crate::security::{ReauthManager, SensitiveAction};
+
 #[cfg(feature = "billing")]
 /// Billing state wrapper for Tauri
 pub struct BillingState {
@@ -128,6 +134,7 @@ impl Default for BillingStateWrapper {
 /// Both systems can coexist - MCP tools are available to the agent via the MCP tool registry.
 #[tauri::command]
 pub async fn billing_initialize(
+    _admin: AdminUser,
     stripe_api_key: String,
     webhook_secret: String,
     state: State<'_, BillingStateWrapper>,
@@ -148,6 +155,7 @@ pub async fn billing_initialize(
 /// Create a new customer
 #[tauri::command]
 pub async fn stripe_create_customer(
+    _admin: AdminUser,
     email: String,
     name: Option<String>,
     state: State<'_, BillingStateWrapper>,
@@ -171,6 +179,7 @@ pub async fn stripe_create_customer(
 /// Get customer by email
 #[tauri::command]
 pub fn stripe_get_customer_by_email(
+    _user: AuthenticatedUser,
     email: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<Option<CustomerInfo>, String> {
@@ -188,10 +197,30 @@ pub fn stripe_get_customer_by_email(
         .map_err(|e| format!("Failed to get customer: {}", e))
 }
 
+#[cfg(feature = "billing")]
+/// Record a billing change made by an admin in the audit trail
+fn audit_payment<T>(
+    app: &tauri::AppHandle,
+    admin: &AdminUser,
+    action: &str,
+    target: &str,
+    result: &Result<T, String>,
+) {
+    audit::record(
+        app,
+        AuditEntry::new(Actor::user(&admin.0), AuditCategory::Payment, action)
+            .target(target)
+            .result(result),
+    );
+}
+
 #[cfg(feature = "billing")]
 /// Create a subscription
 #[tauri::command]
 pub async fn stripe_create_subscription(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     customer_stripe_id: String,
     price_id: String,
     trial_days: Option<u32>,
@@ -199,6 +228,10 @@ pub async fn stripe_create_subscription(
     billing_interval: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<SubscriptionInfo, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -208,7 +241,7 @@ pub async fn stripe_create_subscription(
         .stripe_service()
         .map_err(|e| format!("Stripe service not initialized: {}", e))?;
 
-    service
+    let result = service
         .create_subscription(
             &customer_stripe_id,
             &price_id,
@@ -217,13 +250,22 @@ pub async fn stripe_create_subscription(
             &billing_interval,
         )
         .await
-        .map_err(|e| format!("Failed to create subscription: {}", e))
+        .map_err(|e| format!("Failed to create subscription: {}", e));
+    audit_payment(
+        &app,
+        &admin,
+        "create_subscription",
+        &customer_stripe_id,
+        &result,
+    );
+    result
 }
 
 #[cfg(feature = "billing")]
 /// Get subscription details
 #[tauri::command]
 pub async fn stripe_get_subscription(
+    _user: AuthenticatedUser,
     stripe_subscription_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<SubscriptionInfo, String> {
@@ -246,11 +288,18 @@ pub async fn stripe_get_subscription(
 /// Update subscription (upgrade/downgrade)
 #[tauri::command]
 pub async fn stripe_update_subscription(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     stripe_subscription_id: String,
     new_price_id: String,
     new_plan_name: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<SubscriptionInfo, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -260,19 +309,34 @@ pub async fn stripe_update_subscription(
         .stripe_service()
         .map_err(|e| format!("Stripe service not initialized: {}", e))?;
 
-    service
+    let result = service
         .update_subscription(&stripe_subscription_id, &new_price_id, &new_plan_name)
         .await
-        .map_err(|e| format!("Failed to update subscription: {}", e))
+        .map_err(|e| format!("Failed to update subscription: {}", e));
+    audit_payment(
+        &app,
+        &admin,
+        "update_subscription",
+        &stripe_subscription_id,
+        &result,
+    );
+    result
 }
 
 #[cfg(feature = "billing")]
 /// Cancel subscription
 #[tauri::command]
 pub async fn stripe_cancel_subscription(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     stripe_subscription_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -282,16 +346,25 @@ pub async fn stripe_cancel_subscription(
         .stripe_service()
         .map_err(|e| format!("Stripe service not initialized: {}", e))?;
 
-    service
+    let result = service
         .cancel_subscription(&stripe_subscription_id)
         .await
-        .map_err(|e| format!("Failed to cancel subscription: {}", e))
+        .map_err(|e| format!("Failed to cancel subscription: {}", e));
+    audit_payment(
+        &app,
+        &admin,
+        "cancel_subscription",
+        &stripe_subscription_id,
+        &result,
+    );
+    result
 }
 
 #[cfg(feature = "billing")]
 /// Get invoices for customer
 #[tauri::command]
 pub async fn stripe_get_invoices(
+    _user: AuthenticatedUser,
     customer_stripe_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<Vec<InvoiceInfo>, String> {
@@ -314,6 +387,7 @@ pub async fn stripe_get_invoices(
 /// Get usage statistics
 #[tauri::command]
 pub fn stripe_get_usage(
+    _user: AuthenticatedUser,
     customer_id: String,
     period_start: i64,
     period_end: i64,
@@ -337,6 +411,7 @@ pub fn stripe_get_usage(
 /// Track usage event
 #[tauri::command]
 pub fn stripe_track_usage(
+    _admin: AdminUser,
     customer_id: String,
     usage_type: String,
     count: u64,
@@ -370,10 +445,17 @@ pub fn stripe_track_usage(
 /// Create Stripe billing portal session
 #[tauri::command]
 pub async fn stripe_create_portal_session(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     customer_stripe_id: String,
     return_url: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<String, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -393,6 +475,7 @@ pub async fn stripe_create_portal_session(
 /// Get active subscription for customer
 #[tauri::command]
 pub fn stripe_get_active_subscription(
+    _user: AuthenticatedUser,
     customer_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<Option<SubscriptionInfo>, String> {
@@ -414,6 +497,7 @@ pub fn stripe_get_active_subscription(
 /// Process webhook event
 #[tauri::command]
 pub async fn stripe_process_webhook(
+    _admin: AdminUser,
     payload: String,
     signature: String,
     state: State<'_, BillingStateWrapper>,
@@ -437,6 +521,7 @@ pub async fn stripe_process_webhook(
 /// Get payment methods for a customer
 #[tauri::command]
 pub async fn stripe_get_payment_methods(
+    _user: AuthenticatedUser,
     customer_stripe_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<Vec<PaymentMethodInfo>, String> {
@@ -459,10 +544,17 @@ pub async fn stripe_get_payment_methods(
 /// Attach a payment method to a customer
 #[tauri::command]
 pub async fn stripe_attach_payment_method(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     customer_stripe_id: String,
     payment_method_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<PaymentMethodInfo, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -472,20 +564,35 @@ pub async fn stripe_attach_payment_method(
         .stripe_service()
         .map_err(|e| format!("Stripe service not initialized: {}", e))?;
 
-    service
+    let result = service
         .attach_payment_method(&customer_stripe_id, &payment_method_id)
         .await
-        .map_err(|e| format!("Failed to attach payment method: {}", e))
+        .map_err(|e| format!("Failed to attach payment method: {}", e));
+    audit_payment(
+        &app,
+        &admin,
+        "attach_payment_method",
+        &payment_method_id,
+        &result,
+    );
+    result
 }
 
 #[cfg(feature = "billing")]
 /// Set default payment method for a customer
 #[tauri::command]
 pub async fn stripe_set_default_payment_method(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     customer_stripe_id: String,
     payment_method_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -495,19 +602,34 @@ pub async fn stripe_set_default_payment_method(
         .stripe_service()
         .map_err(|e| format!("Stripe service not initialized: {}", e))?;
 
-    service
+    let result = service
         .set_default_payment_method(&customer_stripe_id, &payment_method_id)
         .await
-        .map_err(|e| format!("Failed to set default payment method: {}", e))
+        .map_err(|e| format!("Failed to set default payment method: {}", e));
+    audit_payment(
+        &app,
+        &admin,
+        "set_default_payment_method",
+        &payment_method_id,
+        &result,
+    );
+    result
 }
 
 #[cfg(feature = "billing")]
 /// Create a Setup Intent for adding a payment method
 #[tauri::command]
 pub async fn stripe_create_setup_intent(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     customer_stripe_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<String, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -527,9 +649,16 @@ pub async fn stripe_create_setup_intent(
 /// Detach (delete) a payment method
 #[tauri::command]
 pub async fn stripe_delete_payment_method(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     payment_method_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -539,16 +668,25 @@ pub async fn stripe_delete_payment_method(
         .stripe_service()
         .map_err(|e| format!("Stripe service not initialized: {}", e))?;
 
-    service
+    let result = service
         .detach_payment_method(&payment_method_id)
         .await
-        .map_err(|e| format!("Failed to delete payment method: {}", e))
+        .map_err(|e| format!("Failed to delete payment method: {}", e));
+    audit_payment(
+        &app,
+        &admin,
+        "detach_payment_method",
+        &payment_method_id,
+        &result,
+    );
+    result
 }
 
 #[cfg(feature = "billing")]
 /// Send invoice via email
 #[tauri::command]
 pub async fn send_invoice_email(
+    _admin: AdminUser,
     invoice_id: String,
     recipient_email: String,
     subject: String,
@@ -636,6 +774,7 @@ const BILLING_DISABLED_MSG: &str = "Billing feature is not enabled";
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn billing_initialize(
+    _admin: AdminUser,
     _stripe_api_key: String,
     _webhook_secret: String,
     _state: tauri::State<'_, BillingStateWrapper>,
@@ -647,6 +786,7 @@ pub async fn billing_initialize(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_create_customer(
+    _admin: AdminUser,
     _email: String,
     _name: Option<String>,
     _state: tauri::State<'_, BillingStateWrapper>,
@@ -657,6 +797,7 @@ pub async fn stripe_create_customer(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub fn stripe_get_customer_by_email(
+    _user: AuthenticatedUser,
     _email: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<Option<CustomerInfo>, String> {
@@ -666,6 +807,7 @@ pub fn stripe_get_customer_by_email(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_create_subscription(
+    _admin: AdminUser,
     _customer_id: String,
     _price_id: String,
     _trial_days: Option<u32>,
@@ -679,6 +821,7 @@ pub async fn stripe_create_subscription(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub fn stripe_get_subscription(
+    _user: AuthenticatedUser,
     _subscription_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<Option<SubscriptionInfo>, String> {
@@ -688,6 +831,7 @@ pub fn stripe_get_subscription(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_update_subscription(
+    _admin: AdminUser,
     _subscription_id: String,
     _new_price_id: String,
     _proration_behavior: Option<String>,
@@ -699,6 +843,7 @@ pub async fn stripe_update_subscription(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_cancel_subscription(
+    _admin: AdminUser,
     _subscription_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<(), String> {
@@ -708,6 +853,7 @@ pub async fn stripe_cancel_subscription(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_get_invoices(
+    _user: AuthenticatedUser,
     _customer_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<Vec<InvoiceInfo>, String> {
@@ -717,6 +863,7 @@ pub async fn stripe_get_invoices(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub fn stripe_get_usage(
+    _user: AuthenticatedUser,
     _customer_id: String,
     _period_start: i64,
     _period_end: i64,
@@ -728,6 +875,7 @@ pub fn stripe_get_usage(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub fn stripe_track_usage(
+    _admin: AdminUser,
     _customer_id: String,
     _usage_type: String,
     _count: u64,
@@ -742,6 +890,7 @@ pub fn stripe_track_usage(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_create_portal_session(
+    _admin: AdminUser,
     _customer_stripe_id: String,
     _return_url: String,
     _state: tauri::State<'_, BillingStateWrapper>,
@@ -752,6 +901,7 @@ pub async fn stripe_create_portal_session(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub fn stripe_get_active_subscription(
+    _user: AuthenticatedUser,
     _customer_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<Option<SubscriptionInfo>, String> {
@@ -761,6 +911,7 @@ pub fn stripe_get_active_subscription(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_process_webhook(
+    _admin: AdminUser,
     _payload: String,
     _signature: String,
     _state: tauri::State<'_, BillingStateWrapper>,
@@ -771,6 +922,7 @@ pub async fn stripe_process_webhook(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_create_setup_intent(
+    _admin: AdminUser,
     _customer_stripe_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<String, String> {
@@ -780,6 +932,7 @@ pub async fn stripe_create_setup_intent(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_get_payment_methods(
+    _user: AuthenticatedUser,
     _customer_stripe_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<Vec<PaymentMethodInfo>, String> {
@@ -789,6 +942,7 @@ pub async fn stripe_get_payment_methods(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_attach_payment_method(
+    _admin: AdminUser,
     _customer_stripe_id: String,
     _payment_method_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
@@ -799,6 +953,7 @@ pub async fn stripe_attach_payment_method(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_set_default_payment_method(
+    _admin: AdminUser,
     _customer_stripe_id: String,
     _payment_method_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
@@ -809,6 +964,7 @@ pub async fn stripe_set_default_payment_method(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn stripe_delete_payment_method(
+    _admin: AdminUser,
     _payment_method_id: String,
     _state: tauri::State<'_, BillingStateWrapper>,
 ) -> Result<(), String> {
@@ -818,6 +974,7 @@ pub async fn stripe_delete_payment_method(
 #[cfg(not(feature = "billing"))]
 #[tauri::command]
 pub async fn send_invoice_email(
+    _admin: AdminUser,
     _invoice_id: String,
     _recipient_email: String,
     _subject: String,
@@ -0,0 +1,127 @@
+//! Runs workflows, indexes folders and sends chat prompts from a terminal,
+//! using the desktop app's data without opening its window.
+
+use agiworkforce_desktop::cli::{self, Core};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(
+    name = "agiworkforce-cli",
+    version,
+    about = "Run AGI Workforce workflows, indexing and chat from a terminal"
+)]
+struct Args {
+    /// Data directory of the desktop app; defaults to the app's own
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a saved workflow and print the execution as JSON
+    ///
+    /// Exits with 0 when the run completes, 1 when it fails or is cancelled,
+    /// 2 when it is paused and 124 when it outlasts --timeout.
+    Run {
+        workflow_id: String,
+        /// Workflow input; values that parse as JSON are passed as such
+        #[arg(short, long = "input", value_name = "KEY=VALUE")]
+        inputs: Vec<String>,
+        /// Print the predicted path, duration and cost without running anything
+        #[arg(long)]
+        simulate: bool,
+        /// Cancel the run if it has not settled after this many seconds
+        #[arg(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+    },
+    /// Index a folder's files for semantic search
+    Index { folder: PathBuf },
+    /// Send a prompt to the configured LLM providers and print the reply
+    Chat {
+        prompt: String,
+        /// Provider to use, e.g. openai, anthropic or ollama
+        #[arg(long)]
+        provider: Option<String>,
+        #[arg(long)]
+        model: Option<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .init();
+
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the async runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(run(args)) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<ExitCode> {
+    let data_dir = match args.data_dir {
+        Some(dir) => dir,
+        None => cli::default_data_dir()?,
+    };
+    let core = Core::open(data_dir)?;
+
+    match args.command {
+        Command::Run {
+            workflow_id,
+            inputs,
+            simulate,
+            timeout,
+        } => {
+            let inputs = cli::parse_inputs(&inputs)?;
+            let outcome = core
+                .run_workflow(
+                    workflow_id,
+                    inputs,
+                    simulate,
+                    timeout.map(Duration::from_secs),
+                )
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&outcome)?);
+            Ok(ExitCode::from(outcome.exit_code()))
+        }
+        Command::Index { folder } => {
+            let indexed = core.index_folder(&folder).await?;
+            println!("Indexed {} files in {}", indexed, folder.display());
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Chat {
+            prompt,
+            provider,
+            model,
+        } => {
+            let reply = core.chat(&prompt, provider.as_deref(), model).await?;
+            println!("{}", reply);
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
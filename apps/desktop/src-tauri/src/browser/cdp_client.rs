@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::sync::Mutex;
 use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
 use url::Url;
@@ -14,6 +15,7 @@ pub struct CdpClient {
     ws_url: String,
     message_id: Arc<AtomicU64>,
     connection: Arc<Mutex<Option<CdpConnection>>>,
+    event_subscribers: Arc<parking_lot::Mutex<Vec<UnboundedSender<CdpEvent>>>>,
 }
 
 struct CdpConnection {
@@ -44,6 +46,25 @@ struct CdpError {
     message: String,
 }
 
+/// Notification pushed by the browser, e.g. `Page.frameNavigated`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CdpEvent {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl CdpEvent {
+    /// Parse a raw message; command responses (which carry an `id`) are not events
+    fn parse(text: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(text).ok()?;
+        if value.get("id").is_some() {
+            return None;
+        }
+        serde_json::from_value(value).ok()
+    }
+}
+
 impl CdpClient {
     /// Create a new CDP client
     pub fn new(ws_url: String) -> Self {
@@ -51,9 +72,23 @@ impl CdpClient {
             ws_url,
             message_id: Arc::new(AtomicU64::new(1)),
             connection: Arc::new(Mutex::new(None)),
+            event_subscribers: Arc::new(parking_lot::Mutex::new(Vec::new())),
         }
     }
 
+    /// DevTools WebSocket URL this client connects to
+    pub fn ws_url(&self) -> &str {
+        &self.ws_url
+    }
+
+    /// Receive every event the page emits from now on.
+    /// Domains still have to be enabled, e.g. with `Page.enable`.
+    pub fn subscribe_events(&self) -> UnboundedReceiver<CdpEvent> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.event_subscribers.lock().push(tx);
+        rx
+    }
+
     /// Connect to the browser via WebSocket
     pub async fn connect(&self) -> Result<()> {
         let url = Url::parse(&self.ws_url)
@@ -85,11 +120,20 @@ impl CdpClient {
             }
         });
 
-        // Spawn receiver task
+        // Spawn receiver task; events go to subscribers, everything else to send_command
+        let subscribers = Arc::clone(&self.event_subscribers);
         tokio::spawn(async move {
             while let Some(result) = read.next().await {
                 match result {
                     Ok(msg) => {
+                        if let WsMessage::Text(text) = &msg {
+                            if let Some(event) = CdpEvent::parse(text) {
+                                subscribers
+                                    .lock()
+                                    .retain(|tx| tx.send(event.clone()).is_ok());
+                                continue;
+                            }
+                        }
                         if response_tx.send(msg).is_err() {
                             break;
                         }
@@ -373,6 +417,55 @@ impl CdpClient {
         Ok(())
     }
 
+    /// Press and release a key in the focused element, e.g. `Enter` or `a`
+    pub async fn press_key(&self, key: &str) -> Result<()> {
+        let (code, key_code, text) = match key {
+            "Enter" => ("Enter".to_string(), 13, Some("\r".to_string())),
+            "Tab" => ("Tab".to_string(), 9, None),
+            "Escape" => ("Escape".to_string(), 27, None),
+            "Backspace" => ("Backspace".to_string(), 8, None),
+            "ArrowUp" => ("ArrowUp".to_string(), 38, None),
+            "ArrowDown" => ("ArrowDown".to_string(), 40, None),
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_alphanumeric() => (
+                        if c.is_ascii_digit() {
+                            format!("Digit{}", c)
+                        } else {
+                            format!("Key{}", c.to_ascii_uppercase())
+                        },
+                        c.to_ascii_uppercase() as i64,
+                        Some(c.to_string()),
+                    ),
+                    _ => return Err(Error::Other(format!("Unsupported key: {}", key))),
+                }
+            }
+        };
+
+        let mut down = json!({
+            "type": if text.is_some() { "keyDown" } else { "rawKeyDown" },
+            "key": key,
+            "code": code,
+            "windowsVirtualKeyCode": key_code,
+        });
+        if let Some(text) = text {
+            down["text"] = json!(text);
+        }
+        self.send_command("Input.dispatchKeyEvent", down).await?;
+        self.send_command(
+            "Input.dispatchKeyEvent",
+            json!({
+                "type": "keyUp",
+                "key": key,
+                "code": code,
+                "windowsVirtualKeyCode": key_code,
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
     /// Focus element
     pub async fn focus_element(&self, selector: &str) -> Result<()> {
         let script = format!(
@@ -526,4 +619,17 @@ mod tests {
         // Mock test - actual test would connect to real browser
         assert!(client.connection.lock().await.is_none());
     }
+
+    #[test]
+    fn test_event_parsing_skips_responses() {
+        let event = CdpEvent::parse(
+            r#"{"method":"Page.frameNavigated","params":{"frame":{"url":"https://a.test/"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(event.method, "Page.frameNavigated");
+        assert_eq!(event.params["frame"]["url"], "https://a.test/");
+
+        assert!(CdpEvent::parse(r#"{"id":3,"result":{}}"#).is_none());
+        assert!(CdpEvent::parse("not json").is_none());
+    }
 }
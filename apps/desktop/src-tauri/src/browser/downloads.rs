@@ -0,0 +1,720 @@
+//! Downloads started in automated tabs
+//!
+//! Each tab's downloads go to a directory of its own, reported through CDP
+//! download events and re-emitted as `browser:download-progress`. A finished
+//! file is renamed to its suggested name and checksummed; waiters claim
+//! finished downloads one at a time, oldest first. When the session names a
+//! project, every finished download is also ingested into that project's
+//! knowledge base so agents can search the reports they pulled.
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use super::cdp_client::{CdpClient, CdpEvent};
+use crate::error::{Error, Result};
+use crate::projects::{is_archive, is_supported, ProjectManager};
+
+/// Event carrying a `DownloadRecord` whenever a download progresses or ends
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "browser:download-progress";
+/// Progress of one download is reported at most this often
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const DEFAULT_WAIT: Duration = Duration::from_secs(60);
+const MAX_WAIT: Duration = Duration::from_secs(30 * 60);
+
+type ProgressCallback = Arc<dyn Fn(&DownloadRecord) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    InProgress,
+    Completed,
+    Canceled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadRecord {
+    /// The browser's download GUID
+    pub id: String,
+    pub tab_id: String,
+    pub url: String,
+    pub file_name: String,
+    /// Set once the file is complete
+    pub path: Option<String>,
+    pub state: DownloadState,
+    pub received_bytes: u64,
+    /// 0 when the server did not send a length
+    pub total_bytes: u64,
+    pub sha256: Option<String>,
+    /// Knowledge base document ID, or the source group of an ingested archive
+    pub knowledge_id: Option<String>,
+    pub ingest_error: Option<String>,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    #[serde(skip)]
+    directory: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DownloadOptions {
+    /// Defaults to a directory of the tab's own under the app's downloads
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// Project whose knowledge base ingests finished downloads
+    #[serde(default)]
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WaitForDownload {
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Only downloads whose file name contains this, ignoring case
+    #[serde(default)]
+    pub file_name: Option<String>,
+    /// Expected SHA-256 of the file, in hex
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadSessionInfo {
+    pub tab_id: String,
+    pub directory: String,
+    pub project_id: Option<String>,
+    pub downloads: Vec<DownloadRecord>,
+}
+
+/// What an event changed
+#[derive(Debug)]
+enum Update {
+    None,
+    Progress(DownloadRecord),
+    /// All bytes arrived; the file still has to be placed and checked
+    Transferred(DownloadRecord),
+}
+
+/// Downloads of one tab
+struct DownloadSession {
+    tab_id: String,
+    directory: Mutex<PathBuf>,
+    project_id: Mutex<Option<String>>,
+    downloads: Mutex<Vec<DownloadRecord>>,
+    claimed: Mutex<HashSet<String>>,
+    reported: Mutex<HashMap<String, Instant>>,
+    changed: Notify,
+}
+
+impl DownloadSession {
+    fn new(tab_id: &str, directory: PathBuf, project_id: Option<String>) -> Self {
+        Self {
+            tab_id: tab_id.to_string(),
+            directory: Mutex::new(directory),
+            project_id: Mutex::new(project_id),
+            downloads: Mutex::new(Vec::new()),
+            claimed: Mutex::new(HashSet::new()),
+            reported: Mutex::new(HashMap::new()),
+            changed: Notify::new(),
+        }
+    }
+
+    fn info(&self) -> DownloadSessionInfo {
+        DownloadSessionInfo {
+            tab_id: self.tab_id.clone(),
+            directory: self.directory.lock().to_string_lossy().into_owned(),
+            project_id: self.project_id.lock().clone(),
+            downloads: self.downloads.lock().clone(),
+        }
+    }
+
+    /// Apply a `downloadWillBegin` or `downloadProgress` event from the
+    /// Browser domain, or from the Page domain on older browsers
+    fn handle_event(&self, event: &CdpEvent) -> Update {
+        let params = &event.params;
+        let Some(guid) = params["guid"].as_str() else {
+            return Update::None;
+        };
+        let mut downloads = self.downloads.lock();
+
+        if event.method.ends_with(".downloadWillBegin") {
+            if downloads.iter().any(|d| d.id == guid) {
+                return Update::None;
+            }
+            let record = DownloadRecord {
+                id: guid.to_string(),
+                tab_id: self.tab_id.clone(),
+                url: params["url"].as_str().unwrap_or_default().to_string(),
+                file_name: safe_file_name(params["suggestedFilename"].as_str().unwrap_or("")),
+                path: None,
+                state: DownloadState::InProgress,
+                received_bytes: 0,
+                total_bytes: 0,
+                sha256: None,
+                knowledge_id: None,
+                ingest_error: None,
+                error: None,
+                started_at: Utc::now().timestamp(),
+                finished_at: None,
+                directory: self.directory.lock().clone(),
+            };
+            downloads.push(record.clone());
+            return Update::Progress(record);
+        }
+
+        if !event.method.ends_with(".downloadProgress") {
+            return Update::None;
+        }
+        let Some(record) = downloads.iter_mut().find(|d| d.id == guid) else {
+            return Update::None;
+        };
+        if record.state != DownloadState::InProgress {
+            return Update::None;
+        }
+        record.received_bytes = params["receivedBytes"].as_f64().unwrap_or(0.0) as u64;
+        record.total_bytes = params["totalBytes"].as_f64().unwrap_or(0.0) as u64;
+
+        match params["state"].as_str() {
+            Some("completed") => Update::Transferred(record.clone()),
+            Some("canceled") => {
+                record.state = DownloadState::Canceled;
+                record.finished_at = Some(Utc::now().timestamp());
+                let ended = record.clone();
+                drop(downloads);
+                self.changed.notify_waiters();
+                Update::Progress(ended)
+            }
+            _ => {
+                let mut reported = self.reported.lock();
+                let due = reported
+                    .get(guid)
+                    .is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL);
+                if !due {
+                    return Update::None;
+                }
+                reported.insert(guid.to_string(), Instant::now());
+                Update::Progress(record.clone())
+            }
+        }
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut DownloadRecord)) -> Option<DownloadRecord> {
+        let updated = {
+            let mut downloads = self.downloads.lock();
+            let record = downloads.iter_mut().find(|d| d.id == id)?;
+            apply(record);
+            record.clone()
+        };
+        self.reported.lock().remove(id);
+        self.changed.notify_waiters();
+        Some(updated)
+    }
+
+    /// The oldest finished download matching `options` that no waiter has taken
+    fn claim(&self, options: &WaitForDownload) -> Option<DownloadRecord> {
+        let wanted = options.file_name.as_deref().map(str::to_lowercase);
+        let downloads = self.downloads.lock();
+        let mut claimed = self.claimed.lock();
+        let record = downloads.iter().find(|d| {
+            d.state != DownloadState::InProgress
+                && !claimed.contains(&d.id)
+                && wanted
+                    .as_deref()
+                    .is_none_or(|w| d.file_name.to_lowercase().contains(w))
+        })?;
+        claimed.insert(record.id.clone());
+        Some(record.clone())
+    }
+
+    async fn wait(&self, options: &WaitForDownload) -> Result<DownloadRecord> {
+        let timeout = options
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_WAIT)
+            .min(MAX_WAIT);
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        let record = loop {
+            // Registered before checking, so a change in between is not missed
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if let Some(record) = self.claim(options) {
+                break record;
+            }
+            if tokio::time::timeout_at(deadline, changed).await.is_err() {
+                let pending = self
+                    .downloads
+                    .lock()
+                    .iter()
+                    .filter(|d| d.state == DownloadState::InProgress)
+                    .count();
+                return Err(Error::Other(format!(
+                    "No download finished within {}s ({} still in progress)",
+                    timeout.as_secs(),
+                    pending
+                )));
+            }
+        };
+
+        match record.state {
+            DownloadState::Canceled => Err(Error::Other(format!(
+                "Download of {} was canceled",
+                record.file_name
+            ))),
+            DownloadState::Failed => Err(Error::Other(format!(
+                "Download of {} failed: {}",
+                record.file_name,
+                record.error.as_deref().unwrap_or("unknown error")
+            ))),
+            _ => {
+                if let Some(expected) = &options.sha256 {
+                    let actual = record.sha256.as_deref().unwrap_or_default();
+                    if !actual.eq_ignore_ascii_case(expected.trim()) {
+                        return Err(Error::Other(format!(
+                            "Checksum mismatch for {}: expected {}, got {}",
+                            record.file_name, expected, actual
+                        )));
+                    }
+                }
+                Ok(record)
+            }
+        }
+    }
+}
+
+struct ActiveSession {
+    client: Arc<CdpClient>,
+    session: Arc<DownloadSession>,
+    listener: JoinHandle<()>,
+}
+
+/// Download capture for automated tabs
+pub struct DownloadManager {
+    root: PathBuf,
+    knowledge_db: PathBuf,
+    on_progress: ProgressCallback,
+    sessions: tokio::sync::Mutex<HashMap<String, ActiveSession>>,
+}
+
+impl DownloadManager {
+    /// `root` holds per-tab download directories; `knowledge_db` is the
+    /// projects database whose knowledge bases ingest finished downloads
+    pub fn new(
+        root: impl Into<PathBuf>,
+        knowledge_db: impl Into<PathBuf>,
+        on_progress: impl Fn(&DownloadRecord) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            root: root.into(),
+            knowledge_db: knowledge_db.into(),
+            on_progress: Arc::new(on_progress),
+            sessions: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Capture the tab's downloads, or change where they go if already captured
+    pub async fn enable(
+        &self,
+        tab_id: &str,
+        ws_url: String,
+        options: DownloadOptions,
+    ) -> Result<DownloadSessionInfo> {
+        let directory = options
+            .directory
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.root.join(safe_file_name(tab_id)));
+        tokio::fs::create_dir_all(&directory).await?;
+
+        let mut sessions = self.sessions.lock().await;
+        if let Some(active) = sessions.get(tab_id) {
+            set_download_behavior(&active.client, &directory).await?;
+            *active.session.directory.lock() = directory;
+            *active.session.project_id.lock() = options.project_id;
+            return Ok(active.session.info());
+        }
+
+        // A connection of its own: download events go to the session that enabled them
+        let client = Arc::new(CdpClient::new(ws_url));
+        client.connect().await?;
+        let events = client.subscribe_events();
+        set_download_behavior(&client, &directory).await?;
+
+        let session = Arc::new(DownloadSession::new(tab_id, directory, options.project_id));
+        let listener = tokio::spawn(listen(
+            events,
+            Arc::clone(&session),
+            self.knowledge_db.clone(),
+            Arc::clone(&self.on_progress),
+        ));
+        let info = session.info();
+        sessions.insert(
+            tab_id.to_string(),
+            ActiveSession {
+                client,
+                session,
+                listener,
+            },
+        );
+        tracing::info!(
+            "[Browser] Capturing downloads of tab {} into {}",
+            tab_id,
+            info.directory
+        );
+        Ok(info)
+    }
+
+    /// Capture the tab's downloads with the default directory unless already captured
+    pub async fn ensure(&self, tab_id: &str, ws_url: String) -> Result<()> {
+        if self.sessions.lock().await.contains_key(tab_id) {
+            return Ok(());
+        }
+        self.enable(tab_id, ws_url, DownloadOptions::default())
+            .await
+            .map(|_| ())
+    }
+
+    /// Stop capturing; files already downloaded are kept
+    pub async fn disable(&self, tab_id: &str) -> Result<()> {
+        let Some(active) = self.sessions.lock().await.remove(tab_id) else {
+            return Ok(());
+        };
+        active.listener.abort();
+        // Best effort: the tab may already be gone
+        let _ = active
+            .client
+            .send_command(
+                "Browser.setDownloadBehavior",
+                json!({ "behavior": "default" }),
+            )
+            .await;
+        Ok(())
+    }
+
+    pub async fn session(&self, tab_id: &str) -> Option<DownloadSessionInfo> {
+        self.sessions
+            .lock()
+            .await
+            .get(tab_id)
+            .map(|active| active.session.info())
+    }
+
+    /// Wait for the tab's next finished download, checking its checksum if one is given
+    pub async fn wait(&self, tab_id: &str, options: WaitForDownload) -> Result<DownloadRecord> {
+        let session = self
+            .sessions
+            .lock()
+            .await
+            .get(tab_id)
+            .map(|active| Arc::clone(&active.session))
+            .ok_or_else(|| Error::Other(format!("Downloads of tab {} are not captured", tab_id)))?;
+        session.wait(&options).await
+    }
+}
+
+async fn set_download_behavior(client: &CdpClient, directory: &Path) -> Result<()> {
+    let path = directory.to_string_lossy();
+    let named_by_id = client
+        .send_command(
+            "Browser.setDownloadBehavior",
+            json!({ "behavior": "allowAndName", "downloadPath": path, "eventsEnabled": true }),
+        )
+        .await;
+    if named_by_id.is_err() {
+        // Older browsers only have the page-level variant, which keeps suggested names
+        client.send_command("Page.enable", json!({})).await?;
+        client
+            .send_command(
+                "Page.setDownloadBehavior",
+                json!({ "behavior": "allow", "downloadPath": path }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+async fn listen(
+    mut events: tokio::sync::mpsc::UnboundedReceiver<CdpEvent>,
+    session: Arc<DownloadSession>,
+    knowledge_db: PathBuf,
+    on_progress: ProgressCallback,
+) {
+    while let Some(event) = events.recv().await {
+        match session.handle_event(&event) {
+            Update::None => {}
+            Update::Progress(record) => on_progress(&record),
+            Update::Transferred(record) => {
+                on_progress(&record);
+                tokio::spawn(finish(
+                    Arc::clone(&session),
+                    record,
+                    knowledge_db.clone(),
+                    Arc::clone(&on_progress),
+                ));
+            }
+        }
+    }
+}
+
+/// Place, checksum and ingest a download whose bytes have all arrived
+async fn finish(
+    session: Arc<DownloadSession>,
+    record: DownloadRecord,
+    knowledge_db: PathBuf,
+    on_progress: ProgressCallback,
+) {
+    let project_id = session.project_id.lock().clone();
+    let id = record.id.clone();
+    let finished = tokio::task::spawn_blocking(move || {
+        let path = place_file(&record.directory, &record.id, &record.file_name)?;
+        let sha256 = sha256_file(&path)?;
+        let ingested = project_id.map(|project_id| ingest(&knowledge_db, &project_id, &path));
+        Ok::<_, std::io::Error>((path, sha256, ingested))
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|placed| placed.map_err(|e| e.to_string()));
+
+    let updated = session.update(&id, |record| {
+        record.finished_at = Some(Utc::now().timestamp());
+        match finished {
+            Ok((path, sha256, ingested)) => {
+                tracing::info!("[Browser] Downloaded {}", path.display());
+                record.state = DownloadState::Completed;
+                record.path = Some(path.to_string_lossy().into_owned());
+                record.sha256 = Some(sha256);
+                match ingested {
+                    Some(Ok(knowledge_id)) => record.knowledge_id = Some(knowledge_id),
+                    Some(Err(e)) => record.ingest_error = Some(e),
+                    None => {}
+                }
+            }
+            Err(e) => {
+                tracing::warn!("[Browser] Download of {} failed: {}", record.file_name, e);
+                record.state = DownloadState::Failed;
+                record.error = Some(e);
+            }
+        }
+    });
+    if let Some(record) = updated {
+        on_progress(&record);
+    }
+}
+
+fn ingest(
+    knowledge_db: &Path,
+    project_id: &str,
+    path: &Path,
+) -> std::result::Result<String, String> {
+    let manager = ProjectManager::new(knowledge_db.to_path_buf(), knowledge_db.to_path_buf())
+        .map_err(|e| format!("Failed to open knowledge base: {:#}", e))?;
+    let file = path.to_string_lossy();
+    let ingested = if is_archive(path) {
+        manager
+            .add_archive(project_id, &file, |_| {})
+            .map(|report| report.source_group)
+    } else if is_supported(path) {
+        manager
+            .add_document(project_id, &file)
+            .map(|document| document.id)
+    } else {
+        return Err("File type cannot be added to a knowledge base".to_string());
+    };
+    ingested.map_err(|e| format!("{:#}", e))
+}
+
+/// Move a file the browser saved under its download ID to its suggested name
+fn place_file(directory: &Path, id: &str, file_name: &str) -> std::io::Result<PathBuf> {
+    let by_id = directory.join(id);
+    if by_id.is_file() {
+        let target = unique_path(directory, file_name);
+        std::fs::rename(&by_id, &target)?;
+        return Ok(target);
+    }
+    let named = directory.join(file_name);
+    if named.is_file() {
+        return Ok(named);
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("{} not found in {}", file_name, directory.display()),
+    ))
+}
+
+/// `report.pdf`, else `report (1).pdf`, `report (2).pdf`, ...
+fn unique_path(directory: &Path, file_name: &str) -> PathBuf {
+    let candidate = directory.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let name = Path::new(file_name);
+    let stem = name
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_name);
+    let extension = name
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{}", e))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| directory.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|path| !path.exists())
+        .expect("some suffix is free")
+}
+
+/// A suggested name usable as a file name on every platform
+fn safe_file_name(suggested: &str) -> String {
+    let base = suggested.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.').to_string();
+    if cleaned.is_empty() {
+        "download".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(method: &str, params: serde_json::Value) -> CdpEvent {
+        CdpEvent {
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    #[test]
+    fn test_events_track_a_download() {
+        let session = DownloadSession::new("tab-1", PathBuf::from("/tmp/dl"), None);
+        let begun = session.handle_event(&event(
+            "Browser.downloadWillBegin",
+            json!({ "guid": "g1", "url": "https://portal/export", "suggestedFilename": "Q3: report.pdf" }),
+        ));
+        assert!(matches!(begun, Update::Progress(ref r) if r.file_name == "Q3_ report.pdf"));
+
+        let progress =
+            json!({ "guid": "g1", "totalBytes": 100, "receivedBytes": 40, "state": "inProgress" });
+        assert!(matches!(
+            session.handle_event(&event("Browser.downloadProgress", progress.clone())),
+            Update::Progress(ref r) if r.received_bytes == 40
+        ));
+        // Throttled
+        assert!(matches!(
+            session.handle_event(&event("Browser.downloadProgress", progress)),
+            Update::None
+        ));
+        assert!(matches!(
+            session.handle_event(&event(
+                "Page.downloadProgress",
+                json!({ "guid": "g1", "totalBytes": 100, "receivedBytes": 100, "state": "completed" }),
+            )),
+            Update::Transferred(ref r) if r.received_bytes == 100
+        ));
+
+        session.handle_event(&event(
+            "Browser.downloadWillBegin",
+            json!({ "guid": "g2", "url": "https://portal/other", "suggestedFilename": "" }),
+        ));
+        session.handle_event(&event(
+            "Browser.downloadProgress",
+            json!({ "guid": "g2", "totalBytes": 0, "receivedBytes": 0, "state": "canceled" }),
+        ));
+        let info = session.info();
+        assert_eq!(info.downloads[1].file_name, "download");
+        assert_eq!(info.downloads[1].state, DownloadState::Canceled);
+    }
+
+    #[test]
+    fn test_place_file_renames_and_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("report.pdf"), b"older").unwrap();
+        std::fs::write(dir.path().join("g1"), b"abc").unwrap();
+
+        let placed = place_file(dir.path(), "g1", "report.pdf").unwrap();
+        assert_eq!(placed, dir.path().join("report (1).pdf"));
+        assert!(!dir.path().join("g1").exists());
+        assert_eq!(
+            sha256_file(&placed).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(place_file(dir.path(), "g2", "missing.csv").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_waiters_claim_finished_downloads_in_order() {
+        let session = Arc::new(DownloadSession::new("tab-1", PathBuf::new(), None));
+        for (guid, name) in [("g1", "invoice.pdf"), ("g2", "report.csv")] {
+            session.handle_event(&event(
+                "Browser.downloadWillBegin",
+                json!({ "guid": guid, "url": "https://portal", "suggestedFilename": name }),
+            ));
+        }
+
+        let waiter = {
+            let session = Arc::clone(&session);
+            tokio::spawn(async move {
+                let options = WaitForDownload {
+                    file_name: Some("REPORT".to_string()),
+                    sha256: Some("AB12".to_string()),
+                    ..Default::default()
+                };
+                session.wait(&options).await
+            })
+        };
+        tokio::task::yield_now().await;
+        session.update("g2", |r| {
+            r.state = DownloadState::Completed;
+            r.sha256 = Some("ab12".to_string());
+        });
+        assert_eq!(waiter.await.unwrap().unwrap().id, "g2");
+
+        session.update("g1", |r| r.state = DownloadState::Completed);
+        let next = session.wait(&WaitForDownload::default()).await.unwrap();
+        assert_eq!(next.id, "g1");
+
+        let timed_out = session
+            .wait(&WaitForDownload {
+                timeout_ms: Some(10),
+                ..Default::default()
+            })
+            .await;
+        assert!(timed_out.is_err());
+    }
+}
@@ -0,0 +1,701 @@
+//! Self-healing element lookup for browser tools.
+//!
+//! When an action's selector stops matching, the element is looked up again
+//! with the target's recorded fallbacks (test id, label, role and name, text,
+//! ...), then by the label text next to a form control, and finally with the
+//! strategies the semantic layer derives from the target's description. The
+//! element found gets a fresh unique selector that the caller retries with and
+//! stores in place of the old one. Every lookup is tracked per workflow so
+//! selector reliability can be followed over time.
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Future;
+
+use super::cdp_client::CdpClient;
+use super::semantic::{SelectorStrategy, SemanticElementFinder};
+use crate::db::pool::{Pool, PooledConnection};
+use crate::error::{Error, Result};
+
+/// Lookups retained in memory for statistics
+const WINDOW_SIZE: usize = 5_000;
+/// Persisted events older than this are pruned on startup
+const RETENTION_DAYS: i64 = 30;
+/// Words the semantic layer turns into strategies that match any element of that kind
+const ELEMENT_KIND_WORDS: [&str; 9] = [
+    "button", "link", "input", "field", "textbox", "checkbox", "radio", "select", "dropdown",
+];
+
+/// Page helpers shared with the recorder: naming, roles and unique selectors
+pub(super) const ELEMENT_JS: &str = r#"
+  const clean = (s) => (s || '').replace(/\s+/g, ' ').trim();
+  const quotable = (s) => !!s && !/["'\\\n]/.test(s);
+  const unique = (selector) => {
+    try { return document.querySelectorAll(selector).length === 1; } catch (e) { return false; }
+  };
+  const stableId = (id) => quotable(id) && !/\d{3,}/.test(id) && !id.startsWith(':');
+
+  const inputType = (el) => el.tagName === 'INPUT' ? (el.getAttribute('type') || 'text').toLowerCase() : null;
+  const roleOf = (el) => {
+    const explicit = el.getAttribute('role');
+    if (explicit) return explicit;
+    switch (el.tagName) {
+      case 'A': return el.hasAttribute('href') ? 'link' : null;
+      case 'BUTTON': return 'button';
+      case 'SELECT': return 'combobox';
+      case 'TEXTAREA': return 'textbox';
+      case 'INPUT': {
+        const t = inputType(el);
+        if (t === 'checkbox' || t === 'radio') return t;
+        if (['button', 'submit', 'reset', 'image'].includes(t)) return 'button';
+        return 'textbox';
+      }
+      default: return null;
+    }
+  };
+  const labelOf = (el) => {
+    if (el.labels && el.labels.length) return clean(el.labels[0].innerText);
+    const ids = el.getAttribute('aria-labelledby');
+    const labelled = ids && document.getElementById(ids.split(' ')[0]);
+    return labelled ? clean(labelled.innerText) : '';
+  };
+  const nameOf = (el) => clean(el.getAttribute('aria-label')) || labelOf(el)
+    || (['button', 'submit', 'reset'].includes(inputType(el)) ? clean(el.value) : '')
+    || (['A', 'BUTTON'].includes(el.tagName) || el.getAttribute('role') ? clean(el.innerText) : '')
+    || clean(el.getAttribute('title')) || clean(el.getAttribute('alt'));
+
+  const cssPath = (el) => {
+    if (el.id && stableId(el.id)) {
+      const byId = /^[A-Za-z_][\w-]*$/.test(el.id) ? '#' + el.id : '[id="' + el.id + '"]';
+      if (unique(byId)) return byId;
+    }
+    const tag = el.tagName.toLowerCase();
+    for (const attr of ['data-testid', 'data-test', 'data-qa', 'name', 'aria-label', 'placeholder']) {
+      const value = el.getAttribute(attr);
+      if (quotable(value)) {
+        const selector = tag + '[' + attr + '="' + value + '"]';
+        if (unique(selector)) return selector;
+      }
+    }
+    const parts = [];
+    for (let node = el; node && node.nodeType === 1 && node !== document.documentElement; node = node.parentElement) {
+      let part = node.tagName.toLowerCase();
+      if (node.id && stableId(node.id) && /^[A-Za-z_][\w-]*$/.test(node.id)) {
+        part = '#' + node.id;
+      } else if (node.parentElement) {
+        const same = Array.from(node.parentElement.children).filter((c) => c.tagName === node.tagName);
+        if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(node) + 1) + ')';
+      }
+      parts.unshift(part);
+      const selector = parts.join(' > ');
+      if (unique(selector)) return selector;
+    }
+    return parts.join(' > ');
+  };
+  const xpathOf = (el) => {
+    const segments = [];
+    for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+      let index = 1;
+      for (let s = node.previousElementSibling; s; s = s.previousElementSibling) {
+        if (s.tagName === node.tagName) index++;
+      }
+      segments.unshift(node.tagName.toLowerCase() + '[' + index + ']');
+    }
+    return '/' + segments.join('/');
+  };
+"#;
+
+/// Finds the element for `query` and returns `{ selector, strategy }` or null
+const HEAL_JS: &str = r#"
+  const lower = (s) => clean(s).toLowerCase();
+  const visible = (el) => {
+    const rect = el.getBoundingClientRect();
+    return rect.width > 0 && rect.height > 0;
+  };
+  const all = (selector) => {
+    try { return Array.from(document.querySelectorAll(selector)); } catch (e) { return []; }
+  };
+  const byText = (text) => {
+    const wanted = lower(text);
+    const matches = all('body *').filter((el) => lower(el.innerText) === wanted);
+    // The innermost match is the element itself rather than a wrapper
+    return matches.filter((el) => !matches.some((other) => other !== el && el.contains(other)));
+  };
+  const candidates = (strategy) => {
+    const [kind, value] = Object.entries(strategy)[0];
+    switch (kind) {
+      case 'DataTestId': return all('[data-testid]').filter((el) => el.getAttribute('data-testid') === value);
+      case 'AriaLabel': return all('[aria-label]').filter((el) => lower(el.getAttribute('aria-label')) === lower(value));
+      case 'Role': return all('body *').filter((el) => roleOf(el) === value[0] && lower(nameOf(el)).includes(lower(value[1])));
+      case 'Text': return byText(value);
+      case 'Placeholder': return all('[placeholder]').filter((el) => el.getAttribute('placeholder') === value);
+      case 'Css': return all(value);
+      case 'XPath': {
+        try {
+          const node = document.evaluate(value, document, null, XPathResult.FIRST_ORDERED_NODE_TYPE, null).singleNodeValue;
+          return node && node.nodeType === 1 ? [node] : [];
+        } catch (e) { return []; }
+      }
+      default: return [];
+    }
+  };
+  const byLabel = (label) => {
+    const wanted = lower(label);
+    const controls = 'input, select, textarea, button, [contenteditable="true"]';
+    const found = [];
+    for (const el of all('label, span, div, td, th, dt, p')) {
+      if (lower(el.innerText) !== wanted) continue;
+      const control = el.control || (el.htmlFor && document.getElementById(el.htmlFor));
+      if (control) { found.push(control); continue; }
+      // Otherwise the first control after the label within a few ancestors
+      let scope = el;
+      for (let depth = 0; depth < 3 && scope; depth++, scope = scope.parentElement) {
+        const next = Array.from(scope.querySelectorAll(controls))
+          .find((c) => el.compareDocumentPosition(c) & Node.DOCUMENT_POSITION_FOLLOWING);
+        if (next) { found.push(next); break; }
+      }
+    }
+    return found;
+  };
+  const pick = (elements) => elements.find(visible) || elements[0] || null;
+  const kinds = {
+    DataTestId: 'data_test_id', AriaLabel: 'aria_label', Role: 'role', Text: 'text',
+    Placeholder: 'placeholder', Css: 'css', XPath: 'xpath',
+  };
+
+  for (const strategy of query.fallbacks) {
+    const el = pick(candidates(strategy));
+    if (el) return { selector: cssPath(el), strategy: kinds[Object.keys(strategy)[0]] };
+  }
+  if (query.label) {
+    const el = pick(byLabel(query.label));
+    if (el) return { selector: cssPath(el), strategy: 'nearby_label' };
+  }
+  for (const strategy of query.semantic) {
+    const el = pick(candidates(strategy));
+    if (el) return { selector: cssPath(el), strategy: 'semantic' };
+  }
+  return null;
+"#;
+
+/// What an element-targeting tool call was asked to act on
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ElementQuery {
+    pub selector: String,
+    /// Most reliable first
+    #[serde(default)]
+    pub fallbacks: Vec<SelectorStrategy>,
+    /// e.g. `button "Export"`
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl ElementQuery {
+    /// Read `selector`, `fallbacks` and `description` from a tool call's parameters
+    pub fn from_parameters(parameters: &HashMap<String, Value>) -> Option<Self> {
+        let selector = parameters.get("selector")?.as_str()?.to_string();
+        let fallbacks = parameters
+            .get("fallbacks")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let description = parameters
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        Some(Self {
+            selector,
+            fallbacks,
+            description,
+        })
+    }
+
+    /// The quoted name in descriptions such as `textbox "Email"`, else the recorded role name
+    fn label(&self) -> Option<String> {
+        let quoted = self.description.as_deref().and_then(|d| {
+            let start = d.find('"')? + 1;
+            let end = start + d[start..].find('"')?;
+            Some(d[start..end].trim().to_string())
+        });
+        quoted.filter(|l| !l.is_empty()).or_else(|| {
+            self.fallbacks.iter().find_map(|s| match s {
+                SelectorStrategy::Role(_, name) if !name.is_empty() => Some(name.clone()),
+                _ => None,
+            })
+        })
+    }
+
+    /// Strategies the semantic layer derives from the description that were not recorded.
+    /// Its CSS guesses and bare element-kind words match too broadly to act on.
+    fn semantic_strategies(&self) -> Vec<SelectorStrategy> {
+        let Some(description) = self.description.as_deref() else {
+            return Vec::new();
+        };
+        SemanticElementFinder::from_natural_language(&description.replace('"', " "))
+            .strategies
+            .into_iter()
+            .filter(|s| !self.fallbacks.contains(s))
+            .filter(|s| match s {
+                SelectorStrategy::Css(_) | SelectorStrategy::XPath(_) => false,
+                SelectorStrategy::DataTestId(v)
+                | SelectorStrategy::AriaLabel(v)
+                | SelectorStrategy::Text(v)
+                | SelectorStrategy::Placeholder(v)
+                | SelectorStrategy::Role(_, v) => !ELEMENT_KIND_WORDS.contains(&v.as_str()),
+            })
+            .collect()
+    }
+
+    fn heal_script(&self) -> String {
+        let query = json!({
+            "fallbacks": self.fallbacks,
+            "label": self.label(),
+            "semantic": self.semantic_strategies(),
+        });
+        format!(
+            "(() => {{\n{}\n  const query = {};\n{}}})()",
+            ELEMENT_JS, query, HEAL_JS
+        )
+    }
+}
+
+/// How a lost element was found again
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HealingStrategy {
+    DataTestId,
+    AriaLabel,
+    Role,
+    Text,
+    Placeholder,
+    Css,
+    #[serde(rename = "xpath")]
+    XPath,
+    NearbyLabel,
+    Semantic,
+}
+
+impl HealingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealingStrategy::DataTestId => "data_test_id",
+            HealingStrategy::AriaLabel => "aria_label",
+            HealingStrategy::Role => "role",
+            HealingStrategy::Text => "text",
+            HealingStrategy::Placeholder => "placeholder",
+            HealingStrategy::Css => "css",
+            HealingStrategy::XPath => "xpath",
+            HealingStrategy::NearbyLabel => "nearby_label",
+            HealingStrategy::Semantic => "semantic",
+        }
+    }
+}
+
+/// A selector that replaced one which stopped matching
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HealedSelector {
+    pub from: String,
+    pub selector: String,
+    pub strategy: HealingStrategy,
+}
+
+#[derive(Deserialize)]
+struct HealResult {
+    selector: String,
+    strategy: HealingStrategy,
+}
+
+/// Find the element `query` describes after its selector stopped matching
+pub async fn heal(client: &CdpClient, query: &ElementQuery) -> Result<Option<HealedSelector>> {
+    let found = client.evaluate(&query.heal_script()).await?;
+    if found.is_null() {
+        return Ok(None);
+    }
+    let found: HealResult = serde_json::from_value(found)?;
+    Ok(Some(HealedSelector {
+        from: query.selector.clone(),
+        selector: found.selector,
+        strategy: found.strategy,
+    }))
+}
+
+/// Run `act` on the query's selector. When it fails because the element is
+/// gone, heal the selector and run `act` once more on the healed one. Every
+/// lookup is recorded against `workflow_id`.
+pub async fn act_with_healing<F, Fut>(
+    client: &CdpClient,
+    query: &ElementQuery,
+    tool_id: &str,
+    workflow_id: Option<&str>,
+    act: F,
+) -> Result<Option<HealedSelector>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let tracker = global_selector_healing();
+    let event = |outcome, healed: Option<&HealedSelector>| HealingEvent {
+        workflow_id: workflow_id.map(str::to_string),
+        tool_id: tool_id.to_string(),
+        selector: query.selector.clone(),
+        outcome,
+        strategy: healed.map(|h| h.strategy),
+        healed_selector: healed.map(|h| h.selector.clone()),
+        recorded_at: Utc::now().timestamp(),
+    };
+
+    let error = match act(query.selector.clone()).await {
+        Ok(()) => {
+            tracker.record(event(LookupOutcome::Found, None));
+            return Ok(None);
+        }
+        Err(e) => e,
+    };
+    // The element is there, so the action itself failed; healing cannot help
+    if client
+        .element_exists(&query.selector)
+        .await
+        .unwrap_or(false)
+    {
+        tracker.record(event(LookupOutcome::Found, None));
+        return Err(error);
+    }
+
+    let healed = match heal(client, query).await {
+        Ok(Some(healed)) => healed,
+        Ok(None) => {
+            tracker.record(event(LookupOutcome::Failed, None));
+            return Err(Error::Other(format!(
+                "Element not found: {} ({})",
+                query.selector, error
+            )));
+        }
+        Err(e) => {
+            tracker.record(event(LookupOutcome::Failed, None));
+            return Err(Error::Other(format!(
+                "Element not found: {} ({}); healing failed: {}",
+                query.selector, error, e
+            )));
+        }
+    };
+
+    if let Err(e) = act(healed.selector.clone()).await {
+        tracker.record(event(LookupOutcome::Failed, Some(&healed)));
+        return Err(e);
+    }
+    tracing::info!(
+        "[Browser] Healed selector '{}' -> '{}' via {}",
+        healed.from,
+        healed.selector,
+        healed.strategy.as_str()
+    );
+    tracker.record(event(LookupOutcome::Healed, Some(&healed)));
+    Ok(Some(healed))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupOutcome {
+    /// The stored selector matched
+    Found,
+    /// The stored selector missed and healing found the element
+    Healed,
+    Failed,
+}
+
+impl LookupOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LookupOutcome::Found => "found",
+            LookupOutcome::Healed => "healed",
+            LookupOutcome::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealingEvent {
+    /// None for calls made outside a skill or workflow
+    pub workflow_id: Option<String>,
+    pub tool_id: String,
+    pub selector: String,
+    pub outcome: LookupOutcome,
+    pub strategy: Option<HealingStrategy>,
+    pub healed_selector: Option<String>,
+    pub recorded_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealingStats {
+    pub workflow_id: Option<String>,
+    pub lookups: usize,
+    pub found: usize,
+    pub healed: usize,
+    pub failed: usize,
+    /// Share of lookups that reached the element, healed or not
+    pub success_rate: f64,
+    /// Share of lookups whose stored selector had gone stale
+    pub heal_rate: f64,
+    /// Successful heals per strategy
+    pub strategies: BTreeMap<String, usize>,
+    pub last_healed_at: Option<i64>,
+}
+
+/// Rolling record of element lookups and how stale selectors were healed
+pub struct SelectorHealingTracker {
+    events: Mutex<VecDeque<HealingEvent>>,
+    pool: RwLock<Option<Pool>>,
+}
+
+impl Default for SelectorHealingTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelectorHealingTracker {
+    pub fn new() -> Self {
+        Self {
+            events: Mutex::new(VecDeque::new()),
+            pool: RwLock::new(None),
+        }
+    }
+
+    /// Persist events to the given database and load the recent history
+    pub fn attach_database(&self, pool: Pool) -> anyhow::Result<()> {
+        let conn = pool.get()?;
+        let cutoff = Utc::now().timestamp() - RETENTION_DAYS * 86_400;
+        conn.execute(
+            "DELETE FROM selector_healing_events WHERE recorded_at < ?1",
+            params![cutoff],
+        )?;
+
+        let loaded = {
+            let mut stmt = conn.prepare(
+                "SELECT workflow_id, tool_id, selector, outcome, strategy, healed_selector, recorded_at
+                 FROM selector_healing_events
+                 ORDER BY recorded_at ASC, id ASC",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                let outcome: String = row.get(3)?;
+                let strategy: Option<String> = row.get(4)?;
+                Ok(HealingEvent {
+                    workflow_id: row.get(0)?,
+                    tool_id: row.get(1)?,
+                    selector: row.get(2)?,
+                    outcome: serde_json::from_value(json!(outcome))
+                        .unwrap_or(LookupOutcome::Failed),
+                    strategy: strategy.and_then(|s| serde_json::from_value(json!(s)).ok()),
+                    healed_selector: row.get(5)?,
+                    recorded_at: row.get(6)?,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        {
+            let mut events = self.events.lock();
+            for event in loaded {
+                push_event(&mut events, event);
+            }
+        }
+
+        *self.pool.write() = Some(pool);
+        Ok(())
+    }
+
+    /// A connection to the attached database; `None` until one is attached
+    fn conn(&self) -> anyhow::Result<Option<PooledConnection>> {
+        match self.pool.read().as_ref() {
+            Some(pool) => Ok(Some(pool.get()?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn record(&self, event: HealingEvent) {
+        if let Err(e) = self.persist(&event) {
+            tracing::warn!("Failed to persist selector healing event: {}", e);
+        }
+        push_event(&mut self.events.lock(), event);
+    }
+
+    fn persist(&self, event: &HealingEvent) -> anyhow::Result<()> {
+        let Some(conn) = self.conn()? else {
+            return Ok(());
+        };
+        conn.execute(
+            "INSERT INTO selector_healing_events
+                (workflow_id, tool_id, selector, outcome, strategy, healed_selector, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                event.workflow_id,
+                event.tool_id,
+                event.selector,
+                event.outcome.as_str(),
+                event.strategy.map(|s| s.as_str()),
+                event.healed_selector,
+                event.recorded_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Statistics per workflow, or for one workflow when given
+    pub fn stats(&self, workflow_id: Option<&str>) -> Vec<HealingStats> {
+        let events = self.events.lock();
+        let mut grouped: BTreeMap<Option<&str>, Vec<&HealingEvent>> = BTreeMap::new();
+        for event in events.iter() {
+            let id = event.workflow_id.as_deref();
+            if workflow_id.is_none() || id == workflow_id {
+                grouped.entry(id).or_default().push(event);
+            }
+        }
+        grouped
+            .into_iter()
+            .map(|(id, events)| summarize(id, &events))
+            .collect()
+    }
+}
+
+fn push_event(events: &mut VecDeque<HealingEvent>, event: HealingEvent) {
+    events.push_back(event);
+    while events.len() > WINDOW_SIZE {
+        events.pop_front();
+    }
+}
+
+fn summarize(workflow_id: Option<&str>, events: &[&HealingEvent]) -> HealingStats {
+    let count = |outcome| events.iter().filter(|e| e.outcome == outcome).count();
+    let lookups = events.len();
+    let (found, healed, failed) = (
+        count(LookupOutcome::Found),
+        count(LookupOutcome::Healed),
+        count(LookupOutcome::Failed),
+    );
+    let rate = |n: usize| {
+        if lookups > 0 {
+            n as f64 / lookups as f64
+        } else {
+            0.0
+        }
+    };
+
+    let mut strategies = BTreeMap::new();
+    for event in events.iter().filter(|e| e.outcome == LookupOutcome::Healed) {
+        if let Some(strategy) = event.strategy {
+            *strategies.entry(strategy.as_str().to_string()).or_insert(0) += 1;
+        }
+    }
+
+    HealingStats {
+        workflow_id: workflow_id.map(str::to_string),
+        lookups,
+        found,
+        healed,
+        failed,
+        success_rate: rate(found + healed),
+        heal_rate: rate(healed + failed),
+        strategies,
+        last_healed_at: events
+            .iter()
+            .rev()
+            .find(|e| e.outcome == LookupOutcome::Healed)
+            .map(|e| e.recorded_at),
+    }
+}
+
+static GLOBAL_SELECTOR_HEALING: Lazy<SelectorHealingTracker> =
+    Lazy::new(SelectorHealingTracker::new);
+
+/// Process-wide tracker shared by the executor and commands
+pub fn global_selector_healing() -> &'static SelectorHealingTracker {
+    &GLOBAL_SELECTOR_HEALING
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(workflow_id: Option<&str>, outcome: LookupOutcome) -> HealingEvent {
+        HealingEvent {
+            workflow_id: workflow_id.map(str::to_string),
+            tool_id: "browser_click".to_string(),
+            selector: "#export".to_string(),
+            outcome,
+            strategy: (outcome == LookupOutcome::Healed).then_some(HealingStrategy::Role),
+            healed_selector: None,
+            recorded_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_heal_script_carries_query() {
+        let query = ElementQuery {
+            selector: "#old-email".to_string(),
+            fallbacks: vec![
+                SelectorStrategy::DataTestId("email".to_string()),
+                SelectorStrategy::Role("textbox".to_string(), "Email".to_string()),
+            ],
+            description: Some("textbox \"Work email\"".to_string()),
+        };
+        assert_eq!(query.label().as_deref(), Some("Work email"));
+
+        let semantic = query.semantic_strategies();
+        assert!(!semantic.is_empty());
+        assert!(semantic
+            .iter()
+            .all(|s| !matches!(s, SelectorStrategy::Css(_))));
+        assert!(!semantic.contains(&SelectorStrategy::Text("textbox".to_string())));
+
+        let script = query.heal_script();
+        assert!(script.contains(r#""label":"Work email""#));
+        assert!(script.contains(r#"{"DataTestId":"email"}"#));
+        assert!(script.contains("const cssPath"));
+    }
+
+    #[test]
+    fn test_stats_per_workflow() {
+        let tracker = SelectorHealingTracker::new();
+        for outcome in [
+            LookupOutcome::Found,
+            LookupOutcome::Found,
+            LookupOutcome::Healed,
+            LookupOutcome::Failed,
+        ] {
+            tracker.record(event(Some("skill-1"), outcome));
+        }
+        tracker.record(event(None, LookupOutcome::Found));
+
+        assert_eq!(tracker.stats(None).len(), 2);
+        let stats = tracker.stats(Some("skill-1"));
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].lookups, 4);
+        assert_eq!(stats[0].healed, 1);
+        assert_eq!(stats[0].success_rate, 0.75);
+        assert_eq!(stats[0].heal_rate, 0.5);
+        assert_eq!(stats[0].strategies.get("role"), Some(&1));
+        assert_eq!(stats[0].last_healed_at, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn test_persisted_events_reload() {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let tracker = SelectorHealingTracker::new();
+        tracker.attach_database(pool.clone()).unwrap();
+        let mut healed = event(Some("skill-1"), LookupOutcome::Healed);
+        healed.recorded_at = Utc::now().timestamp();
+        tracker.record(healed);
+
+        drop(tracker);
+        let reloaded = SelectorHealingTracker::new();
+        reloaded.attach_database(pool).unwrap();
+        let stats = reloaded.stats(Some("skill-1"));
+        assert_eq!(stats[0].healed, 1);
+        assert_eq!(stats[0].strategies.get("role"), Some(&1));
+    }
+}
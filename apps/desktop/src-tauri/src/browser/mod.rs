@@ -2,18 +2,36 @@
 pub mod advanced;
 pub mod cdp_client;
 pub mod dom_operations;
+pub mod downloads;
 pub mod extension_bridge;
+pub mod healing;
 pub mod playwright_bridge;
+pub mod pool;
+pub mod profiles;
+pub mod recorder;
 pub mod semantic;
 pub mod tab_manager;
 
 // Re-exports for convenience
 // Note: Cookie is exported from advanced, not extension_bridge to avoid ambiguity
 pub use advanced::*;
-pub use cdp_client::CdpClient;
+pub use cdp_client::{CdpClient, CdpEvent};
 pub use dom_operations::*;
+pub use downloads::{
+    DownloadManager, DownloadOptions, DownloadRecord, DownloadSessionInfo, DownloadState,
+    WaitForDownload, DOWNLOAD_PROGRESS_EVENT,
+};
 pub use extension_bridge::ExtensionBridge;
+pub use healing::{
+    global_selector_healing, ElementQuery, HealedSelector, HealingStats, HealingStrategy,
+    SelectorHealingTracker,
+};
 pub use playwright_bridge::*;
+pub use pool::{BrowserPool, BrowserPoolConfig, BrowserPoolStats, PoolLease, PooledContextStats};
+pub use profiles::{
+    BrowserProfile, BrowserProfileInput, BrowserProfileManager, ProfileSession, ProxySettings,
+};
+pub use recorder::{BrowserRecorder, RecordedAction, RecordedScript, RecordedStep, RecordedTarget};
 pub use semantic::*;
 pub use tab_manager::*;
 
@@ -50,9 +68,7 @@ impl BrowserState {
         }
 
         // Create new CDP client for this tab
-        // In production, get the actual WebSocket URL from the browser
-        let ws_url = format!("ws://localhost:9222/devtools/page/{}", tab_id);
-        let client = Arc::new(CdpClient::new(ws_url));
+        let client = Arc::new(CdpClient::new(page_ws_url(tab_id)));
 
         // Connect to the browser
         client.connect().await?;
@@ -62,3 +78,9 @@ impl BrowserState {
         Ok(client)
     }
 }
+
+/// DevTools WebSocket URL of a tab in the automation browser
+pub fn page_ws_url(tab_id: &str) -> String {
+    // In production, get the actual WebSocket URL from the browser
+    format!("ws://localhost:9222/devtools/page/{}", tab_id)
+}
@@ -61,6 +61,9 @@ pub struct BrowserHandle {
     pub id: String,
     pub browser_type: BrowserType,
     pub ws_endpoint: String,
+    /// Set when the browser runs a named profile
+    #[serde(default)]
+    pub profile_id: Option<String>,
 }
 
 /// Playwright server configuration
@@ -184,6 +187,7 @@ impl PlaywrightBridge {
             id: browser_id.clone(),
             browser_type: browser_type.clone(),
             ws_endpoint: ws_endpoint.clone(),
+            profile_id: None,
         };
 
         // Store browser handle
@@ -206,6 +210,16 @@ impl PlaywrightBridge {
         Ok(())
     }
 
+    /// Track a browser started elsewhere, such as a re-attached profile
+    pub async fn register_browser(&self, handle: BrowserHandle) {
+        self.browsers.lock().await.insert(handle.id.clone(), handle);
+    }
+
+    /// Stop tracking a browser that was closed elsewhere
+    pub async fn unregister_browser(&self, id: &str) {
+        self.browsers.lock().await.remove(id);
+    }
+
     /// Get all active browser instances
     pub async fn list_browsers(&self) -> Result<Vec<BrowserHandle>> {
         let browsers = self.browsers.lock().await;
@@ -237,38 +251,7 @@ impl PlaywrightBridge {
         // Add custom args
         args.extend(options.args.clone());
 
-        // Determine executable path
-        let exe = match browser_type {
-            BrowserType::Chromium => {
-                // Try to find Chrome/Chromium
-                #[cfg(windows)]
-                {
-                    // Check common Chrome installation paths on Windows
-                    let possible_paths = [
-                        r"C:\Program Files\Google\Chrome\Application\chrome.exe",
-                        r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
-                        r"C:\Users\%USERNAME%\AppData\Local\Google\Chrome\Application\chrome.exe",
-                    ];
-
-                    possible_paths
-                        .iter()
-                        .find(|p| std::path::Path::new(p).exists())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| "chrome".to_string())
-                }
-
-                #[cfg(not(windows))]
-                {
-                    "chromium".to_string()
-                }
-            }
-            BrowserType::Firefox => "firefox".to_string(),
-            BrowserType::Webkit => {
-                return Err(Error::Other(
-                    "WebKit browser not yet supported on this platform".to_string(),
-                ))
-            }
-        };
+        let exe = browser_executable(browser_type)?;
 
         Ok((exe, args))
     }
@@ -296,6 +279,42 @@ impl PlaywrightBridge {
     }
 }
 
+/// Path or name of the executable that runs `browser_type`
+pub fn browser_executable(browser_type: &BrowserType) -> Result<String> {
+    let exe = match browser_type {
+        BrowserType::Chromium => {
+            // Try to find Chrome/Chromium
+            #[cfg(windows)]
+            {
+                // Check common Chrome installation paths on Windows
+                let possible_paths = [
+                    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+                    r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+                    r"C:\Users\%USERNAME%\AppData\Local\Google\Chrome\Application\chrome.exe",
+                ];
+
+                possible_paths
+                    .iter()
+                    .find(|p| std::path::Path::new(p).exists())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "chrome".to_string())
+            }
+
+            #[cfg(not(windows))]
+            {
+                "chromium".to_string()
+            }
+        }
+        BrowserType::Firefox => "firefox".to_string(),
+        BrowserType::Webkit => {
+            return Err(Error::Other(
+                "WebKit browser not yet supported on this platform".to_string(),
+            ))
+        }
+    };
+    Ok(exe)
+}
+
 impl Drop for PlaywrightBridge {
     fn drop(&mut self) {
         // Ensure server is stopped when bridge is dropped
@@ -0,0 +1,634 @@
+//! Pool of isolated headless browser contexts for parallel agents
+//!
+//! One headless Chromium, started on first use, serves the pool. Each agent
+//! gets a browser context of its own (separate cookies, storage and cache)
+//! holding a single page, so parallel agents never see each other's sessions.
+//! Leases bound how many browser operations run at once; agents waiting for a
+//! lease are served round-robin so a busy agent cannot starve the others.
+//! Contexts unused for the idle timeout are closed, and the browser is shut
+//! down with the last of them.
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+use tokio::sync::oneshot;
+
+use super::cdp_client::CdpClient;
+use super::playwright_bridge::{browser_executable, BrowserType};
+use super::profiles::{devtools_endpoint, free_port};
+use crate::error::{Error, Result};
+
+/// How long the pool's browser gets to open its DevTools port
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
+/// How often the reaper looks for idle contexts, at most
+const MAX_REAP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserPoolConfig {
+    /// Browser operations that may run at once across all agents
+    pub max_concurrency: usize,
+    /// Contexts unused this long are closed
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for BrowserPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: std::thread::available_parallelism()
+                .map(|n| n.get().clamp(2, 8))
+                .unwrap_or(4),
+            idle_timeout_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PooledContextStats {
+    pub agent_id: String,
+    /// Page target; usable as `tab_id`
+    pub target_id: String,
+    pub active_leases: usize,
+    pub total_leases: u64,
+    pub created_at: i64,
+    pub last_used_at: i64,
+    pub idle_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserPoolStats {
+    pub max_concurrency: usize,
+    pub idle_timeout_secs: u64,
+    pub browser_running: bool,
+    pub active_leases: usize,
+    /// Lease requests waiting for a free slot, per agent
+    pub waiting: BTreeMap<String, usize>,
+    pub contexts: Vec<PooledContextStats>,
+    pub leases_granted: u64,
+    /// Leases that had to wait for a slot
+    pub leases_queued: u64,
+    pub contexts_reaped: u64,
+}
+
+/// Slots for concurrent operations, handed to waiting agents in turn
+struct Scheduler {
+    state: Mutex<SchedulerState>,
+}
+
+struct SchedulerState {
+    capacity: usize,
+    active: usize,
+    waiting: HashMap<String, VecDeque<oneshot::Sender<()>>>,
+    /// Agents with waiting requests, in the order they are served
+    turns: VecDeque<String>,
+}
+
+impl SchedulerState {
+    /// The next waiter, taking one request per agent in rotation
+    fn next_waiter(&mut self) -> Option<oneshot::Sender<()>> {
+        let agent_id = self.turns.pop_front()?;
+        let queue = self.waiting.get_mut(&agent_id)?;
+        let waiter = queue.pop_front();
+        if queue.is_empty() {
+            self.waiting.remove(&agent_id);
+        } else {
+            self.turns.push_back(agent_id);
+        }
+        waiter
+    }
+}
+
+/// A slot held until dropped
+struct Permit {
+    scheduler: Arc<Scheduler>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+/// A queued request; a slot handed to it after it was abandoned is passed on
+struct Waiting {
+    rx: oneshot::Receiver<()>,
+    scheduler: Arc<Scheduler>,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if self.rx.try_recv().is_ok() {
+            self.scheduler.release();
+        }
+    }
+}
+
+impl Scheduler {
+    fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(SchedulerState {
+                capacity: capacity.max(1),
+                active: 0,
+                waiting: HashMap::new(),
+                turns: VecDeque::new(),
+            }),
+        })
+    }
+
+    /// Wait for a slot; returns whether the request had to queue
+    async fn acquire(self: &Arc<Self>, agent_id: &str) -> (Permit, bool) {
+        let rx = {
+            let mut state = self.state.lock();
+            if state.active < state.capacity && state.turns.is_empty() {
+                state.active += 1;
+                None
+            } else {
+                let (tx, rx) = oneshot::channel();
+                let queue = state.waiting.entry(agent_id.to_string()).or_default();
+                if queue.is_empty() {
+                    state.turns.push_back(agent_id.to_string());
+                }
+                state
+                    .waiting
+                    .get_mut(agent_id)
+                    .expect("queue was just created")
+                    .push_back(tx);
+                Some(rx)
+            }
+        };
+
+        let queued = rx.is_some();
+        if let Some(rx) = rx {
+            let mut waiting = Waiting {
+                rx,
+                scheduler: Arc::clone(self),
+            };
+            // The sender is only dropped after a send, so this always succeeds
+            let _ = (&mut waiting.rx).await;
+        }
+        (
+            Permit {
+                scheduler: Arc::clone(self),
+            },
+            queued,
+        )
+    }
+
+    /// Hand a freed slot to the next waiter, or return it to the pool
+    fn release(&self) {
+        let mut state = self.state.lock();
+        while let Some(waiter) = state.next_waiter() {
+            if waiter.send(()).is_ok() {
+                return;
+            }
+        }
+        state.active = state.active.saturating_sub(1);
+    }
+
+    fn active(&self) -> usize {
+        self.state.lock().active
+    }
+
+    fn waiting(&self) -> BTreeMap<String, usize> {
+        self.state
+            .lock()
+            .waiting
+            .iter()
+            .map(|(agent_id, queue)| (agent_id.clone(), queue.len()))
+            .collect()
+    }
+}
+
+struct PooledBrowser {
+    child: Child,
+    port: u16,
+    client: CdpClient,
+    /// Removed when the browser shuts down
+    _user_data_dir: tempfile::TempDir,
+}
+
+struct PooledContext {
+    context_id: String,
+    target_id: String,
+    client: Arc<CdpClient>,
+    active_leases: usize,
+    total_leases: u64,
+    created_at: i64,
+    last_used_at: i64,
+    last_used: Instant,
+}
+
+/// Managed headless browser contexts, one per agent
+pub struct BrowserPool {
+    config: BrowserPoolConfig,
+    scheduler: Arc<Scheduler>,
+    /// Serializes launching, creating and closing
+    browser: tokio::sync::Mutex<Option<PooledBrowser>>,
+    contexts: Mutex<HashMap<String, PooledContext>>,
+    browser_running: AtomicBool,
+    leases_granted: AtomicU64,
+    leases_queued: AtomicU64,
+    contexts_reaped: AtomicU64,
+    http: reqwest::Client,
+}
+
+/// An agent's page, usable until dropped
+pub struct PoolLease {
+    pool: Arc<BrowserPool>,
+    agent_id: String,
+    target_id: String,
+    client: Arc<CdpClient>,
+    _permit: Permit,
+}
+
+impl PoolLease {
+    /// Page target; usable as `tab_id`
+    pub fn target_id(&self) -> &str {
+        &self.target_id
+    }
+
+    pub fn client(&self) -> Arc<CdpClient> {
+        Arc::clone(&self.client)
+    }
+}
+
+impl Drop for PoolLease {
+    fn drop(&mut self) {
+        if let Some(context) = self.pool.contexts.lock().get_mut(&self.agent_id) {
+            context.active_leases = context.active_leases.saturating_sub(1);
+            context.last_used = Instant::now();
+            context.last_used_at = Utc::now().timestamp();
+        }
+    }
+}
+
+impl BrowserPool {
+    pub fn new(config: BrowserPoolConfig) -> Self {
+        Self {
+            scheduler: Scheduler::new(config.max_concurrency),
+            config,
+            browser: tokio::sync::Mutex::new(None),
+            contexts: Mutex::new(HashMap::new()),
+            browser_running: AtomicBool::new(false),
+            leases_granted: AtomicU64::new(0),
+            leases_queued: AtomicU64::new(0),
+            contexts_reaped: AtomicU64::new(0),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Wait for a free slot, then return the agent's page, creating its
+    /// context (and the browser) if needed
+    pub async fn lease(self: &Arc<Self>, agent_id: &str) -> Result<PoolLease> {
+        let (permit, queued) = self.scheduler.acquire(agent_id).await;
+        if queued {
+            self.leases_queued.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut browser = self.browser.lock().await;
+        if browser.as_mut().is_some_and(|b| !is_alive(&mut b.child)) {
+            tracing::warn!("[Browser] Pool browser exited; restarting it");
+            *browser = None;
+            self.contexts.lock().clear();
+            self.browser_running.store(false, Ordering::Relaxed);
+        }
+
+        let existing = self
+            .contexts
+            .lock()
+            .get(agent_id)
+            .map(|c| (c.target_id.clone(), Arc::clone(&c.client)));
+        let (target_id, client) = match existing {
+            Some(found) => found,
+            None => {
+                if browser.is_none() {
+                    *browser = Some(self.launch().await?);
+                    self.browser_running.store(true, Ordering::Relaxed);
+                }
+                let running = browser.as_ref().expect("browser was just launched");
+                let context = create_context(running).await?;
+                tracing::info!(
+                    "[Browser] Pool context {} created for agent {}",
+                    context.context_id,
+                    agent_id
+                );
+                let found = (context.target_id.clone(), Arc::clone(&context.client));
+                self.contexts.lock().insert(agent_id.to_string(), context);
+                found
+            }
+        };
+        drop(browser);
+
+        if let Some(context) = self.contexts.lock().get_mut(agent_id) {
+            context.active_leases += 1;
+            context.total_leases += 1;
+            context.last_used = Instant::now();
+            context.last_used_at = Utc::now().timestamp();
+        }
+        self.leases_granted.fetch_add(1, Ordering::Relaxed);
+
+        Ok(PoolLease {
+            pool: Arc::clone(self),
+            agent_id: agent_id.to_string(),
+            target_id,
+            client,
+            _permit: permit,
+        })
+    }
+
+    /// Close the agent's context, e.g. when its run ends
+    pub async fn release(&self, agent_id: &str) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        let Some(context) = self.contexts.lock().remove(agent_id) else {
+            return Ok(());
+        };
+        if let Some(running) = browser.as_ref() {
+            dispose_context(running, &context).await;
+        }
+        tracing::info!("[Browser] Pool context for agent {} released", agent_id);
+        self.shutdown_if_unused(&mut browser).await;
+        Ok(())
+    }
+
+    /// Close contexts unused for the idle timeout; returns how many were closed
+    pub async fn reap_idle(&self) -> usize {
+        let idle_timeout = Duration::from_secs(self.config.idle_timeout_secs);
+        let mut browser = self.browser.lock().await;
+        let idle: Vec<PooledContext> = {
+            let mut contexts = self.contexts.lock();
+            let agent_ids: Vec<String> = contexts
+                .iter()
+                .filter(|(_, c)| c.active_leases == 0 && c.last_used.elapsed() >= idle_timeout)
+                .map(|(agent_id, _)| agent_id.clone())
+                .collect();
+            agent_ids
+                .iter()
+                .filter_map(|agent_id| contexts.remove(agent_id))
+                .collect()
+        };
+
+        if let Some(running) = browser.as_ref() {
+            for context in &idle {
+                dispose_context(running, context).await;
+            }
+        }
+        if !idle.is_empty() {
+            tracing::info!("[Browser] Reaped {} idle pool contexts", idle.len());
+            self.contexts_reaped
+                .fetch_add(idle.len() as u64, Ordering::Relaxed);
+        }
+        self.shutdown_if_unused(&mut browser).await;
+        idle.len()
+    }
+
+    /// Reap idle contexts in the background for as long as the pool lives
+    pub fn spawn_reaper(self: &Arc<Self>) -> tauri::async_runtime::JoinHandle<()> {
+        let pool = Arc::downgrade(self);
+        let interval = Duration::from_secs(self.config.idle_timeout_secs / 4)
+            .clamp(Duration::from_secs(1), MAX_REAP_INTERVAL);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let Some(pool) = pool.upgrade() else {
+                    return;
+                };
+                pool.reap_idle().await;
+            }
+        })
+    }
+
+    pub fn stats(&self) -> BrowserPoolStats {
+        let mut contexts: Vec<PooledContextStats> = self
+            .contexts
+            .lock()
+            .iter()
+            .map(|(agent_id, c)| PooledContextStats {
+                agent_id: agent_id.clone(),
+                target_id: c.target_id.clone(),
+                active_leases: c.active_leases,
+                total_leases: c.total_leases,
+                created_at: c.created_at,
+                last_used_at: c.last_used_at,
+                idle_secs: if c.active_leases > 0 {
+                    0
+                } else {
+                    c.last_used.elapsed().as_secs()
+                },
+            })
+            .collect();
+        contexts.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+
+        BrowserPoolStats {
+            max_concurrency: self.config.max_concurrency.max(1),
+            idle_timeout_secs: self.config.idle_timeout_secs,
+            browser_running: self.browser_running.load(Ordering::Relaxed),
+            active_leases: self.scheduler.active(),
+            waiting: self.scheduler.waiting(),
+            contexts,
+            leases_granted: self.leases_granted.load(Ordering::Relaxed),
+            leases_queued: self.leases_queued.load(Ordering::Relaxed),
+            contexts_reaped: self.contexts_reaped.load(Ordering::Relaxed),
+        }
+    }
+
+    async fn launch(&self) -> Result<PooledBrowser> {
+        let port = free_port()?;
+        let user_data_dir = tempfile::Builder::new()
+            .prefix("agiworkforce-pool-")
+            .tempdir()?;
+        let exe = browser_executable(&BrowserType::Chromium)?;
+        let mut child = Command::new(exe)
+            .args([
+                "--headless=new".to_string(),
+                format!("--remote-debugging-port={}", port),
+                format!("--user-data-dir={}", user_data_dir.path().display()),
+                "--no-first-run".to_string(),
+                "--no-default-browser-check".to_string(),
+                "about:blank".to_string(),
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let deadline = tokio::time::Instant::now() + STARTUP_TIMEOUT;
+        let ws_endpoint = loop {
+            if let Some(ws_endpoint) = devtools_endpoint(&self.http, port).await {
+                break ws_endpoint;
+            }
+            if tokio::time::Instant::now() >= deadline || !is_alive(&mut child) {
+                let _ = child.start_kill();
+                return Err(Error::Other(format!(
+                    "Pool browser did not start within {}s",
+                    STARTUP_TIMEOUT.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        };
+
+        let client = CdpClient::new(ws_endpoint);
+        client.connect().await?;
+        tracing::info!("[Browser] Pool browser started on port {}", port);
+        Ok(PooledBrowser {
+            child,
+            port,
+            client,
+            _user_data_dir: user_data_dir,
+        })
+    }
+
+    async fn shutdown_if_unused(&self, browser: &mut Option<PooledBrowser>) {
+        if !self.contexts.lock().is_empty() {
+            return;
+        }
+        if let Some(mut running) = browser.take() {
+            let _ = running
+                .client
+                .send_command("Browser.close", json!({}))
+                .await;
+            let _ = running.child.start_kill();
+            self.browser_running.store(false, Ordering::Relaxed);
+            tracing::info!("[Browser] Pool browser shut down");
+        }
+    }
+}
+
+fn is_alive(child: &mut Child) -> bool {
+    matches!(child.try_wait(), Ok(None))
+}
+
+async fn create_context(browser: &PooledBrowser) -> Result<PooledContext> {
+    let created = browser
+        .client
+        .send_command("Target.createBrowserContext", json!({}))
+        .await?;
+    let context_id = created["browserContextId"]
+        .as_str()
+        .ok_or_else(|| Error::Other("Browser did not return a context id".to_string()))?
+        .to_string();
+
+    let target = browser
+        .client
+        .send_command(
+            "Target.createTarget",
+            json!({ "url": "about:blank", "browserContextId": context_id }),
+        )
+        .await?;
+    let target_id = target["targetId"]
+        .as_str()
+        .ok_or_else(|| Error::Other("Browser did not return a target id".to_string()))?
+        .to_string();
+
+    let client = CdpClient::new(format!(
+        "ws://127.0.0.1:{}/devtools/page/{}",
+        browser.port, target_id
+    ));
+    client.connect().await?;
+
+    let now = Utc::now().timestamp();
+    Ok(PooledContext {
+        context_id,
+        target_id,
+        client: Arc::new(client),
+        active_leases: 0,
+        total_leases: 0,
+        created_at: now,
+        last_used_at: now,
+        last_used: Instant::now(),
+    })
+}
+
+async fn dispose_context(browser: &PooledBrowser, context: &PooledContext) {
+    let disposed = browser
+        .client
+        .send_command(
+            "Target.disposeBrowserContext",
+            json!({ "browserContextId": context.context_id }),
+        )
+        .await;
+    if let Err(e) = disposed {
+        tracing::warn!(
+            "[Browser] Failed to close pool context {}: {}",
+            context.context_id,
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_waiting_agents_take_turns() {
+        let scheduler = Scheduler::new(1);
+        let (held, queued) = scheduler.acquire("a").await;
+        assert!(!queued);
+
+        // Agent a queues three requests before b queues one
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for agent_id in ["a", "a", "a", "b"] {
+            let scheduler = Arc::clone(&scheduler);
+            let order = Arc::clone(&order);
+            tasks.push(tokio::spawn(async move {
+                let (_permit, queued) = scheduler.acquire(agent_id).await;
+                assert!(queued);
+                order.lock().push(agent_id);
+            }));
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(scheduler.waiting().get("a"), Some(&3));
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*order.lock(), vec!["a", "b", "a", "a"]);
+        assert_eq!(scheduler.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_wait_frees_its_slot() {
+        let scheduler = Scheduler::new(1);
+        let (held, _) = scheduler.acquire("a").await;
+
+        let waiter = {
+            let scheduler = Arc::clone(&scheduler);
+            tokio::spawn(async move {
+                scheduler.acquire("b").await;
+            })
+        };
+        tokio::task::yield_now().await;
+        waiter.abort();
+        let _ = waiter.await;
+
+        drop(held);
+        assert_eq!(scheduler.active(), 0);
+        let (_permit, queued) = scheduler.acquire("c").await;
+        assert!(!queued);
+    }
+
+    #[tokio::test]
+    async fn test_stats_without_browser() {
+        let pool = Arc::new(BrowserPool::new(BrowserPoolConfig {
+            max_concurrency: 2,
+            idle_timeout_secs: 60,
+        }));
+        assert_eq!(pool.reap_idle().await, 0);
+        pool.release("agent_1").await.unwrap();
+
+        let stats = pool.stats();
+        assert_eq!(stats.max_concurrency, 2);
+        assert!(!stats.browser_running);
+        assert!(stats.contexts.is_empty());
+        assert_eq!(stats.leases_granted, 0);
+    }
+}
@@ -0,0 +1,1016 @@
+//! Record what the user does in a controlled tab as a replayable script.
+//!
+//! A page script reports clicks, typing, selections and key presses through a
+//! CDP binding, and `Page.frameNavigated` events report navigations. Every
+//! target keeps the CSS selector it was recorded with plus semantic fallbacks
+//! (test id, label, role and name, text, ...) so a replay can still find the
+//! element when the page's markup changes. A finished script converts to a
+//! skill or a draft workflow; neither is saved until the caller stores it.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use super::cdp_client::{CdpClient, CdpEvent};
+use super::healing::ELEMENT_JS;
+use super::semantic::SelectorStrategy;
+use crate::agi::planner::PlanStep;
+use crate::agi::skills::{Skill, SkillParameter};
+use crate::agi::ResourceUsage;
+use crate::error::{Error, Result};
+use crate::orchestration::conversation_workflow::WorkflowParameter;
+use crate::orchestration::workflow_engine::{
+    NodePosition, ToolNodeData, WorkflowDefinition, WorkflowEdge, WorkflowNode, WorkflowTrigger,
+};
+
+/// Name of the CDP binding the page script reports through
+const BINDING: &str = "__agiRecorderEmit";
+/// A navigation this soon after a user action was caused by it
+const CAUSED_NAVIGATION_MS: i64 = 5_000;
+/// Longer names and texts make brittle fallbacks
+const MAX_HINT_LEN: usize = 80;
+
+const NODE_X: f64 = 250.0;
+const NODE_Y_START: f64 = 100.0;
+const NODE_Y_STEP: f64 = 150.0;
+
+/// Installed in every document of the recorded tab
+static PAGE_SCRIPT: Lazy<String> = Lazy::new(|| {
+    format!(
+        "(() => {{\n  if (window.__agiRecorderInstalled) return;\n  window.__agiRecorderInstalled = true;\n{}{}}})()",
+        ELEMENT_JS, LISTENERS_JS
+    )
+});
+
+/// Reports the user's actions through the binding
+const LISTENERS_JS: &str = r#"
+  const emit = (payload) => {
+    try { window.__agiRecorderEmit(JSON.stringify(payload)); } catch (e) {}
+  };
+  const describe = (el) => ({
+    selector: cssPath(el),
+    tag: el.tagName.toLowerCase(),
+    inputType: inputType(el),
+    testId: el.getAttribute('data-testid'),
+    ariaLabel: el.getAttribute('aria-label'),
+    role: roleOf(el),
+    name: nameOf(el) || null,
+    text: ['A', 'BUTTON'].includes(el.tagName) ? clean(el.innerText) || null : null,
+    placeholder: el.getAttribute('placeholder'),
+    xpath: xpathOf(el),
+  });
+  const isTextField = (el) => el.tagName === 'TEXTAREA' || el.isContentEditable
+    || (el.tagName === 'INPUT' && !['checkbox', 'radio', 'button', 'submit', 'reset', 'image', 'file', 'range', 'color'].includes(inputType(el)));
+  const actionable = (el) => (el.closest && el.closest(
+    'a, button, input, select, textarea, label, summary, [role], [onclick], [data-testid]')) || el;
+
+  document.addEventListener('click', (e) => {
+    if (!e.isTrusted || !(e.target instanceof Element)) return;
+    emit({ kind: 'click', target: describe(actionable(e.target)) });
+  }, true);
+  document.addEventListener('input', (e) => {
+    const el = e.target;
+    if (!e.isTrusted || !(el instanceof Element) || !isTextField(el)) return;
+    const secret = inputType(el) === 'password';
+    const value = el.isContentEditable ? el.innerText : el.value;
+    emit({ kind: 'input', target: describe(el), value: secret ? null : value, secret });
+  }, true);
+  document.addEventListener('change', (e) => {
+    const el = e.target;
+    if (!e.isTrusted || !(el instanceof Element)) return;
+    if (el.tagName === 'SELECT') {
+      emit({ kind: 'select', target: describe(el), value: el.value });
+    } else if (['checkbox', 'radio'].includes(inputType(el))) {
+      emit({ kind: 'check', target: describe(el), checked: el.checked });
+    }
+  }, true);
+  document.addEventListener('keydown', (e) => {
+    if (!e.isTrusted || !['Enter', 'Escape'].includes(e.key)) return;
+    const el = e.target instanceof Element && e.target !== document.body ? e.target : null;
+    emit({ kind: 'key', key: e.key, target: el ? describe(el) : null });
+  }, true);
+"#;
+
+/// An element as it looked while recording
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedTarget {
+    /// CSS selector that matched only this element
+    pub selector: String,
+    /// Other ways to find the element, most reliable first
+    pub fallbacks: Vec<SelectorStrategy>,
+    /// e.g. `button "Export"`
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RecordedAction {
+    Navigate {
+        url: String,
+    },
+    /// Navigation caused by the step before it; replays wait for it instead
+    WaitForNavigation {
+        url: String,
+    },
+    Click {
+        target: RecordedTarget,
+    },
+    /// `text` is empty when `secret` is set (password fields)
+    Type {
+        target: RecordedTarget,
+        text: String,
+        secret: bool,
+    },
+    Select {
+        target: RecordedTarget,
+        value: String,
+    },
+    Check {
+        target: RecordedTarget,
+        checked: bool,
+    },
+    PressKey {
+        target: Option<RecordedTarget>,
+        key: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedStep {
+    /// Unix milliseconds
+    pub at: i64,
+    #[serde(flatten)]
+    pub action: RecordedAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedScript {
+    pub id: String,
+    pub tab_id: String,
+    pub start_url: String,
+    pub steps: Vec<RecordedStep>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+/// What the page script reports
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum CapturedEvent {
+    Click {
+        target: CapturedElement,
+    },
+    Input {
+        target: CapturedElement,
+        value: Option<String>,
+        #[serde(default)]
+        secret: bool,
+    },
+    Select {
+        target: CapturedElement,
+        value: String,
+    },
+    Check {
+        target: CapturedElement,
+        checked: bool,
+    },
+    Key {
+        key: String,
+        target: Option<CapturedElement>,
+    },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CapturedElement {
+    selector: String,
+    tag: String,
+    input_type: Option<String>,
+    test_id: Option<String>,
+    aria_label: Option<String>,
+    role: Option<String>,
+    name: Option<String>,
+    text: Option<String>,
+    placeholder: Option<String>,
+    xpath: Option<String>,
+}
+
+impl CapturedElement {
+    /// Clicks on form fields only focus or toggle them; the input, select
+    /// and check steps that follow carry the intent
+    fn is_form_field(&self) -> bool {
+        match self.tag.as_str() {
+            "select" | "textarea" => true,
+            "input" => !matches!(
+                self.input_type.as_deref(),
+                Some("button" | "submit" | "reset" | "image")
+            ),
+            _ => false,
+        }
+    }
+
+    fn into_target(self) -> RecordedTarget {
+        // Fallback values are embedded in quoted scripts by `to_selector_script`
+        let hint = |value: Option<String>| {
+            value.map(|v| v.trim().to_string()).filter(|v| {
+                !v.is_empty()
+                    && v.chars().count() <= MAX_HINT_LEN
+                    && !v.contains(['\'', '"', '\\', '\n'])
+            })
+        };
+        let name = hint(self.name);
+        let mut fallbacks = Vec::new();
+        if let Some(id) = hint(self.test_id) {
+            fallbacks.push(SelectorStrategy::DataTestId(id));
+        }
+        if let Some(label) = hint(self.aria_label) {
+            fallbacks.push(SelectorStrategy::AriaLabel(label));
+        }
+        if let (Some(role), Some(name)) = (self.role.clone(), name.clone()) {
+            fallbacks.push(SelectorStrategy::Role(role, name));
+        }
+        if let Some(text) = hint(self.text) {
+            fallbacks.push(SelectorStrategy::Text(text));
+        }
+        if let Some(placeholder) = hint(self.placeholder) {
+            fallbacks.push(SelectorStrategy::Placeholder(placeholder));
+        }
+        if let Some(xpath) = hint(self.xpath) {
+            fallbacks.push(SelectorStrategy::XPath(xpath));
+        }
+
+        let kind = self.role.unwrap_or(self.tag);
+        let description = match name {
+            Some(name) => format!("{} \"{}\"", kind, name),
+            None => kind,
+        };
+        RecordedTarget {
+            selector: self.selector,
+            fallbacks,
+            description,
+        }
+    }
+}
+
+impl RecordedScript {
+    pub fn new(id: String, tab_id: String, start_url: String, started_at: i64) -> Self {
+        let mut script = Self {
+            id,
+            tab_id,
+            start_url: start_url.clone(),
+            steps: Vec::new(),
+            started_at,
+            finished_at: None,
+        };
+        // Replays start from the page the recording started on
+        script.record_navigation(start_url, started_at);
+        script
+    }
+
+    fn record(&mut self, event: CapturedEvent, at: i64) {
+        let action = match event {
+            CapturedEvent::Click { target } => {
+                if target.is_form_field() {
+                    return;
+                }
+                RecordedAction::Click {
+                    target: target.into_target(),
+                }
+            }
+            CapturedEvent::Input {
+                target,
+                value,
+                secret,
+            } => RecordedAction::Type {
+                target: target.into_target(),
+                text: if secret {
+                    String::new()
+                } else {
+                    value.unwrap_or_default()
+                },
+                secret,
+            },
+            CapturedEvent::Select { target, value } => RecordedAction::Select {
+                target: target.into_target(),
+                value,
+            },
+            CapturedEvent::Check { target, checked } => RecordedAction::Check {
+                target: target.into_target(),
+                checked,
+            },
+            CapturedEvent::Key { key, target } => RecordedAction::PressKey {
+                target: target.map(CapturedElement::into_target),
+                key,
+            },
+        };
+
+        // Keystrokes into one field, or repeated changes to one control,
+        // collapse into the final value
+        if let Some(last) = self.steps.last_mut() {
+            let same_field = match (&last.action, &action) {
+                (
+                    RecordedAction::Type { target: a, .. },
+                    RecordedAction::Type { target: b, .. },
+                )
+                | (
+                    RecordedAction::Select { target: a, .. },
+                    RecordedAction::Select { target: b, .. },
+                )
+                | (
+                    RecordedAction::Check { target: a, .. },
+                    RecordedAction::Check { target: b, .. },
+                ) => a.selector == b.selector,
+                _ => false,
+            };
+            if same_field {
+                *last = RecordedStep { at, action };
+                return;
+            }
+        }
+        self.steps.push(RecordedStep { at, action });
+    }
+
+    fn record_navigation(&mut self, url: String, at: i64) {
+        if url.is_empty() || url.starts_with("about:") || url.starts_with("chrome-error:") {
+            return;
+        }
+        let action = match self.steps.last_mut() {
+            Some(last) if at - last.at <= CAUSED_NAVIGATION_MS => match &mut last.action {
+                // A redirect: keep where it ended up
+                RecordedAction::Navigate { url: previous }
+                | RecordedAction::WaitForNavigation { url: previous } => {
+                    *previous = url;
+                    last.at = at;
+                    return;
+                }
+                _ => RecordedAction::WaitForNavigation { url },
+            },
+            Some(RecordedStep {
+                action:
+                    RecordedAction::Navigate { url: previous }
+                    | RecordedAction::WaitForNavigation { url: previous },
+                ..
+            }) if *previous == url => return,
+            _ => RecordedAction::Navigate { url },
+        };
+        self.steps.push(RecordedStep { at, action });
+    }
+
+    fn handle_event(&mut self, event: &CdpEvent, at: i64) {
+        match event.method.as_str() {
+            "Runtime.bindingCalled" if event.params["name"] == BINDING => {
+                let Some(payload) = event.params["payload"].as_str() else {
+                    return;
+                };
+                match serde_json::from_str::<CapturedEvent>(payload) {
+                    Ok(captured) => self.record(captured, at),
+                    Err(e) => tracing::debug!("Ignoring recorder payload: {}", e),
+                }
+            }
+            // Only the main frame has no parent
+            "Page.frameNavigated" if event.params["frame"].get("parentId").is_none() => {
+                if let Some(url) = event.params["frame"]["url"].as_str() {
+                    self.record_navigation(url.to_string(), at);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The agent tool calls that replay the script, with the step each came from.
+    /// Typed text becomes a parameter so a skill or workflow can be re-run with
+    /// other values; password fields become parameters without a default.
+    fn tool_calls(&self) -> (Vec<ToolCall>, Vec<WorkflowParameter>) {
+        let mut calls = Vec::new();
+        let mut parameters: Vec<WorkflowParameter> = Vec::new();
+
+        for step in &self.steps {
+            let mut input = Map::new();
+            let (tool, description) = match &step.action {
+                RecordedAction::Navigate { url } => {
+                    input.insert("url".to_string(), json!(url));
+                    ("browser_navigate", format!("Open {}", url))
+                }
+                // The next step's element lookup waits for the page
+                RecordedAction::WaitForNavigation { .. } => continue,
+                RecordedAction::Click { target } => {
+                    insert_target(&mut input, target);
+                    ("browser_click", format!("Click {}", target.description))
+                }
+                RecordedAction::Type {
+                    target,
+                    text,
+                    secret,
+                } => {
+                    insert_target(&mut input, target);
+                    let base = if *secret {
+                        "password".to_string()
+                    } else {
+                        parameter_base_name(target)
+                    };
+                    let name = unique_name(&base, &parameters);
+                    parameters.push(WorkflowParameter {
+                        name: name.clone(),
+                        default: if *secret { Value::Null } else { json!(text) },
+                        description: format!("Text typed into {}", target.description),
+                        used_by: Vec::new(),
+                    });
+                    input.insert("text".to_string(), json!(format!("{{{{{}}}}}", name)));
+                    input.insert("clear_first".to_string(), json!(true));
+                    ("browser_type", format!("Type into {}", target.description))
+                }
+                RecordedAction::Select { target, value } => {
+                    insert_target(&mut input, target);
+                    input.insert("value".to_string(), json!(value));
+                    (
+                        "browser_select",
+                        format!("Choose {} in {}", value, target.description),
+                    )
+                }
+                RecordedAction::Check { target, checked } => {
+                    insert_target(&mut input, target);
+                    input.insert("checked".to_string(), json!(checked));
+                    let verb = if *checked { "Check" } else { "Uncheck" };
+                    ("browser_check", format!("{} {}", verb, target.description))
+                }
+                RecordedAction::PressKey { target, key } => {
+                    if let Some(target) = target {
+                        insert_target(&mut input, target);
+                    }
+                    input.insert("key".to_string(), json!(key));
+                    let on = target
+                        .as_ref()
+                        .map(|t| format!(" in {}", t.description))
+                        .unwrap_or_default();
+                    ("browser_press_key", format!("Press {}{}", key, on))
+                }
+            };
+
+            let id = format!("step_{}", calls.len() + 1);
+            if let RecordedAction::Type { .. } = step.action {
+                if let Some(parameter) = parameters.last_mut() {
+                    parameter.used_by.push(id.clone());
+                }
+            }
+            calls.push(ToolCall {
+                id,
+                tool: tool.to_string(),
+                description,
+                input,
+            });
+        }
+        (calls, parameters)
+    }
+
+    /// A skill that replays the script. Fails when nothing replayable was recorded
+    pub fn to_skill(&self, name: String, description: String) -> Result<Skill> {
+        let (calls, parameters) = self.tool_calls();
+        if calls.is_empty() {
+            return Err(Error::Other("The recording has no steps".to_string()));
+        }
+        // Skill steps run in order, so they need no dependencies
+        let steps = calls
+            .into_iter()
+            .map(|call| PlanStep {
+                estimated_resources: estimated_resources(&call.tool),
+                id: call.id,
+                tool_id: call.tool,
+                description: call.description,
+                parameters: call.input.into_iter().collect(),
+                dependencies: Vec::new(),
+            })
+            .collect();
+
+        let now = chrono::Utc::now();
+        Ok(Skill {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            description,
+            parameters: parameters
+                .into_iter()
+                .map(|p| SkillParameter {
+                    name: p.name,
+                    description: Some(p.description),
+                    default: (!p.default.is_null()).then_some(p.default),
+                })
+                .collect(),
+            steps,
+            preconditions: Vec::new(),
+            integrations: Vec::new(),
+            source_run: None,
+            use_count: 0,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// A draft workflow of tool nodes run one after another
+    pub fn to_workflow(&self, name: String) -> Result<WorkflowDefinition> {
+        let (calls, parameters) = self.tool_calls();
+        if calls.is_empty() {
+            return Err(Error::Other("The recording has no steps".to_string()));
+        }
+        let node_ids: Vec<String> = calls.iter().map(|call| call.id.clone()).collect();
+        let nodes = calls
+            .into_iter()
+            .enumerate()
+            .map(|(index, call)| WorkflowNode::ToolNode {
+                id: call.id,
+                position: NodePosition {
+                    x: NODE_X,
+                    y: NODE_Y_START + NODE_Y_STEP * index as f64,
+                },
+                data: ToolNodeData {
+                    label: call.description,
+                    tool_name: call.tool,
+                    tool_input: call.input.into_iter().collect(),
+                    timeout_seconds: None,
+                },
+            })
+            .collect();
+        let edges = node_ids
+            .windows(2)
+            .enumerate()
+            .map(|(index, pair)| WorkflowEdge {
+                id: format!("edge_{}", index + 1),
+                source: pair[0].clone(),
+                target: pair[1].clone(),
+                source_handle: None,
+                target_handle: None,
+                condition: None,
+                label: None,
+            })
+            .collect();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("source".to_string(), Value::from("browser_recording"));
+        metadata.insert("recording_id".to_string(), Value::from(self.id.clone()));
+        metadata.insert("parameters".to_string(), serde_json::to_value(&parameters)?);
+
+        let now = chrono::Utc::now().timestamp();
+        Ok(WorkflowDefinition {
+            id: String::new(),
+            user_id: "default_user".to_string(),
+            name: name.trim().to_string(),
+            description: Some(format!(
+                "Recorded in the browser starting at {}",
+                self.start_url
+            )),
+            nodes,
+            edges,
+            triggers: vec![WorkflowTrigger::Manual],
+            metadata,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+struct ToolCall {
+    id: String,
+    tool: String,
+    description: String,
+    input: Map<String, Value>,
+}
+
+fn insert_target(input: &mut Map<String, Value>, target: &RecordedTarget) {
+    input.insert("selector".to_string(), json!(target.selector));
+    input.insert("fallbacks".to_string(), json!(target.fallbacks));
+    input.insert("description".to_string(), json!(target.description));
+}
+
+/// Matches what the tools are registered with
+fn estimated_resources(tool: &str) -> ResourceUsage {
+    if tool == "browser_navigate" {
+        ResourceUsage {
+            cpu_percent: 15.0,
+            memory_mb: 200,
+            network_mb: 5.0,
+        }
+    } else {
+        ResourceUsage {
+            cpu_percent: 5.0,
+            memory_mb: 50,
+            network_mb: 0.0,
+        }
+    }
+}
+
+/// `email` for a field labelled "Email", `text` when it has no name
+fn parameter_base_name(target: &RecordedTarget) -> String {
+    let label = target.fallbacks.iter().find_map(|strategy| match strategy {
+        SelectorStrategy::Role(_, name)
+        | SelectorStrategy::AriaLabel(name)
+        | SelectorStrategy::Placeholder(name) => Some(name.as_str()),
+        _ => None,
+    });
+    let slug = label
+        .unwrap_or_default()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(4)
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("_");
+    if slug.is_empty() || slug.starts_with(|c: char| c.is_ascii_digit()) {
+        "text".to_string()
+    } else {
+        slug
+    }
+}
+
+fn unique_name(base: &str, taken: &[WorkflowParameter]) -> String {
+    let is_taken = |name: &str| taken.iter().any(|p| p.name == name);
+    if !is_taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|name| !is_taken(name))
+        .expect("unbounded range")
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+struct ActiveRecording {
+    client: Arc<CdpClient>,
+    script: Arc<parking_lot::Mutex<RecordedScript>>,
+    script_identifier: Option<String>,
+    listener: JoinHandle<()>,
+}
+
+/// Recordings in progress, by recording ID
+#[derive(Default)]
+pub struct BrowserRecorder {
+    active: tokio::sync::Mutex<HashMap<String, ActiveRecording>>,
+}
+
+impl BrowserRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start recording the page at `ws_url` (a tab's DevTools WebSocket).
+    /// Returns the recording ID
+    pub async fn start(&self, tab_id: &str, ws_url: String) -> Result<String> {
+        let mut active = self.active.lock().await;
+        if let Some((id, _)) = active
+            .iter()
+            .find(|(_, recording)| recording.script.lock().tab_id == tab_id)
+        {
+            return Err(Error::Other(format!(
+                "Tab {} is already being recorded ({})",
+                tab_id, id
+            )));
+        }
+
+        // A connection of its own, so events are not shared with automation
+        let client = Arc::new(CdpClient::new(ws_url));
+        client.connect().await?;
+        let mut events = client.subscribe_events();
+
+        client.send_command("Page.enable", json!({})).await?;
+        client.send_command("Runtime.enable", json!({})).await?;
+        client
+            .send_command("Runtime.addBinding", json!({ "name": BINDING }))
+            .await?;
+        let added = client
+            .send_command(
+                "Page.addScriptToEvaluateOnNewDocument",
+                json!({ "source": PAGE_SCRIPT.as_str() }),
+            )
+            .await?;
+        client.evaluate(&PAGE_SCRIPT).await?;
+        let start_url = client.get_url().await.unwrap_or_default();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let script = Arc::new(parking_lot::Mutex::new(RecordedScript::new(
+            id.clone(),
+            tab_id.to_string(),
+            start_url,
+            now_ms(),
+        )));
+        let listener = {
+            let script = Arc::clone(&script);
+            tokio::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    script.lock().handle_event(&event, now_ms());
+                }
+            })
+        };
+
+        tracing::info!("Recording browser tab {} as {}", tab_id, id);
+        active.insert(
+            id.clone(),
+            ActiveRecording {
+                client,
+                script,
+                script_identifier: added["identifier"].as_str().map(str::to_string),
+                listener,
+            },
+        );
+        Ok(id)
+    }
+
+    /// The steps recorded so far
+    pub async fn snapshot(&self, recording_id: &str) -> Option<RecordedScript> {
+        let active = self.active.lock().await;
+        active
+            .get(recording_id)
+            .map(|recording| recording.script.lock().clone())
+    }
+
+    /// Stop recording and return the finished script
+    pub async fn stop(&self, recording_id: &str) -> Result<RecordedScript> {
+        let recording = self
+            .active
+            .lock()
+            .await
+            .remove(recording_id)
+            .ok_or_else(|| Error::Other(format!("No recording {}", recording_id)))?;
+        recording.listener.abort();
+
+        // Best effort: the tab may already be gone
+        if let Some(identifier) = &recording.script_identifier {
+            let _ = recording
+                .client
+                .send_command(
+                    "Page.removeScriptToEvaluateOnNewDocument",
+                    json!({ "identifier": identifier }),
+                )
+                .await;
+        }
+        let _ = recording
+            .client
+            .send_command("Runtime.removeBinding", json!({ "name": BINDING }))
+            .await;
+
+        let mut script = recording.script.lock().clone();
+        script.finished_at = Some(now_ms());
+        tracing::info!(
+            "Recorded {} steps in browser tab {}",
+            script.steps.len(),
+            script.tab_id
+        );
+        Ok(script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(selector: &str, tag: &str, role: &str, name: &str) -> CapturedElement {
+        CapturedElement {
+            selector: selector.to_string(),
+            tag: tag.to_string(),
+            role: Some(role.to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn input(selector: &str, name: &str, value: &str) -> CapturedEvent {
+        CapturedEvent::Input {
+            target: CapturedElement {
+                input_type: Some("text".to_string()),
+                ..element(selector, "input", "textbox", name)
+            },
+            value: Some(value.to_string()),
+            secret: false,
+        }
+    }
+
+    fn script() -> RecordedScript {
+        RecordedScript::new(
+            "rec".to_string(),
+            "tab".to_string(),
+            "https://app.test/login".to_string(),
+            0,
+        )
+    }
+
+    fn actions(script: &RecordedScript) -> Vec<&RecordedAction> {
+        script.steps.iter().map(|step| &step.action).collect()
+    }
+
+    #[test]
+    fn test_typing_collapses_and_navigation_after_click_is_waited_for() {
+        let mut script = script();
+        script.record(
+            CapturedEvent::Click {
+                target: element("#email", "input", "textbox", "Email"),
+            },
+            100,
+        );
+        script.record(input("#email", "Email", "a"), 200);
+        script.record(input("#email", "Email", "ada@example.com"), 300);
+        script.record(
+            CapturedEvent::Input {
+                target: CapturedElement {
+                    input_type: Some("password".to_string()),
+                    ..element("#password", "input", "textbox", "Password")
+                },
+                value: None,
+                secret: true,
+            },
+            400,
+        );
+        script.record(
+            CapturedEvent::Click {
+                target: CapturedElement {
+                    test_id: Some("sign-in".to_string()),
+                    text: Some("Sign in".to_string()),
+                    ..element("form > button", "button", "button", "Sign in")
+                },
+            },
+            500,
+        );
+        script.handle_event(
+            &CdpEvent {
+                method: "Page.frameNavigated".to_string(),
+                params: json!({ "frame": { "url": "https://app.test/home" } }),
+            },
+            1_000,
+        );
+        // Sub-frame navigations are not part of the script
+        script.handle_event(
+            &CdpEvent {
+                method: "Page.frameNavigated".to_string(),
+                params: json!({ "frame": { "parentId": "main", "url": "https://ads.test/" } }),
+            },
+            1_100,
+        );
+        script.record_navigation("https://app.test/reports".to_string(), 60_000);
+
+        let steps = actions(&script);
+        assert_eq!(steps.len(), 6);
+        assert_eq!(
+            steps[0],
+            &RecordedAction::Navigate {
+                url: "https://app.test/login".to_string()
+            }
+        );
+        match steps[1] {
+            RecordedAction::Type {
+                target,
+                text,
+                secret,
+            } => {
+                assert_eq!(target.selector, "#email");
+                assert_eq!(text, "ada@example.com");
+                assert!(!secret);
+                assert_eq!(target.description, "textbox \"Email\"");
+            }
+            other => panic!("unexpected step {:?}", other),
+        }
+        assert!(
+            matches!(steps[2], RecordedAction::Type { secret: true, text, .. } if text.is_empty())
+        );
+        match steps[3] {
+            RecordedAction::Click { target } => assert_eq!(
+                target.fallbacks,
+                vec![
+                    SelectorStrategy::DataTestId("sign-in".to_string()),
+                    SelectorStrategy::Role("button".to_string(), "Sign in".to_string()),
+                    SelectorStrategy::Text("Sign in".to_string()),
+                ]
+            ),
+            other => panic!("unexpected step {:?}", other),
+        }
+        assert_eq!(
+            steps[4],
+            &RecordedAction::WaitForNavigation {
+                url: "https://app.test/home".to_string()
+            }
+        );
+        assert_eq!(
+            steps[5],
+            &RecordedAction::Navigate {
+                url: "https://app.test/reports".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_payload_parsing_and_unsafe_hints() {
+        let mut script = script();
+        let payload = json!({
+            "kind": "click",
+            "target": {
+                "selector": "a.export",
+                "tag": "a",
+                "role": "link",
+                "name": "Don't export",
+                "ariaLabel": "Export",
+                "xpath": "/html[1]/body[1]/a[1]"
+            }
+        });
+        script.handle_event(
+            &CdpEvent {
+                method: "Runtime.bindingCalled".to_string(),
+                params: json!({ "name": BINDING, "payload": payload.to_string() }),
+            },
+            10,
+        );
+        match actions(&script)[1] {
+            RecordedAction::Click { target } => {
+                assert_eq!(
+                    target.fallbacks,
+                    vec![
+                        SelectorStrategy::AriaLabel("Export".to_string()),
+                        SelectorStrategy::XPath("/html[1]/body[1]/a[1]".to_string()),
+                    ]
+                );
+                assert_eq!(target.description, "link");
+            }
+            other => panic!("unexpected step {:?}", other),
+        }
+
+        // Serialized steps are flat and tagged by action
+        let value = serde_json::to_value(&script.steps[0]).unwrap();
+        assert_eq!(value["action"], "navigate");
+        assert_eq!(value["url"], "https://app.test/login");
+        let parsed: RecordedStep = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, script.steps[0]);
+    }
+
+    #[test]
+    fn test_script_to_skill_and_workflow() {
+        let mut script = script();
+        script.record(input("#email", "Email", "ada@example.com"), 100);
+        script.record(
+            CapturedEvent::Input {
+                target: CapturedElement {
+                    input_type: Some("password".to_string()),
+                    ..element("#password", "input", "textbox", "Password")
+                },
+                value: None,
+                secret: true,
+            },
+            200,
+        );
+        script.record(
+            CapturedEvent::Key {
+                key: "Enter".to_string(),
+                target: Some(element("#password", "input", "textbox", "Password")),
+            },
+            300,
+        );
+        script.record_navigation("https://app.test/home".to_string(), 400);
+        script.record(
+            CapturedEvent::Check {
+                target: element("#remember", "input", "checkbox", "Remember me"),
+                checked: true,
+            },
+            10_000,
+        );
+
+        let skill = script
+            .to_skill("Sign in".to_string(), "Sign in to the app".to_string())
+            .unwrap();
+        skill.validate().unwrap();
+        let tools: Vec<&str> = skill.steps.iter().map(|s| s.tool_id.as_str()).collect();
+        assert_eq!(
+            tools,
+            vec![
+                "browser_navigate",
+                "browser_type",
+                "browser_type",
+                "browser_press_key",
+                "browser_check"
+            ]
+        );
+        assert_eq!(skill.steps[1].parameters["text"], json!("{{email}}"));
+        assert_eq!(skill.parameters.len(), 2);
+        assert_eq!(skill.parameters[0].default, Some(json!("ada@example.com")));
+        assert_eq!(skill.parameters[1].name, "password");
+        assert_eq!(skill.parameters[1].default, None);
+
+        // The password has to be supplied on each run
+        assert!(skill.instantiate(&Map::new()).is_err());
+        let mut args = Map::new();
+        args.insert("password".to_string(), json!("hunter2"));
+        let steps = skill.instantiate(&args).unwrap();
+        assert_eq!(steps[2].parameters["text"], json!("hunter2"));
+
+        let workflow = script.to_workflow("Sign in".to_string()).unwrap();
+        assert_eq!(workflow.nodes.len(), 5);
+        assert_eq!(workflow.edges.len(), 4);
+        assert_eq!(workflow.edges[0].source, "step_1");
+        assert_eq!(workflow.edges[0].target, "step_2");
+        assert_eq!(workflow.metadata["source"], "browser_recording");
+        assert_eq!(
+            workflow.metadata["parameters"][1]["used_by"],
+            json!(["step_3"])
+        );
+
+        let empty = RecordedScript::new(String::new(), String::new(), "about:blank".into(), 0);
+        assert!(empty.to_skill("x".into(), "y".into()).is_err());
+    }
+}
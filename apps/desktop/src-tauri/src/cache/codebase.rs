@@ -17,8 +17,9 @@
  * - Integrates with file watcher for real-time invalidation
  * - Consumed by AGI planner for faster codebase understanding
  */
+use crate::db::Pool;
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -205,14 +206,14 @@ pub struct CacheStats {
 
 /// Codebase analysis cache
 pub struct CodebaseCache {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
     hit_count: Arc<Mutex<u64>>,
     miss_count: Arc<Mutex<u64>>,
 }
 
 impl CodebaseCache {
     /// Create a new cache instance using the provided database connection
-    pub fn new(db: Arc<Mutex<Connection>>) -> Result<Self> {
+    pub fn new(db: Pool) -> Result<Self> {
         Ok(Self {
             db,
             hit_count: Arc::new(Mutex::new(0)),
@@ -232,8 +233,8 @@ impl CodebaseCache {
     {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let cache_key = Self::generate_cache_key(project_path, cache_type, file_hash);
         let now = Self::current_timestamp();
@@ -282,8 +283,8 @@ impl CodebaseCache {
     {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let cache_key = Self::generate_cache_key(project_path, cache_type, file_hash);
         let now = Self::current_timestamp();
@@ -312,8 +313,8 @@ impl CodebaseCache {
     pub fn invalidate_file(&self, file_path: &Path) -> Result<usize> {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let file_path_str = file_path.to_string_lossy().to_string();
 
@@ -333,8 +334,8 @@ impl CodebaseCache {
     pub fn invalidate_project(&self, project_path: &Path) -> Result<usize> {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let project_path_str = project_path.to_string_lossy().to_string();
 
@@ -352,8 +353,8 @@ impl CodebaseCache {
     pub fn invalidate_type(&self, cache_type: CacheType) -> Result<usize> {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let deleted = db
             .execute(
@@ -369,8 +370,8 @@ impl CodebaseCache {
     pub fn clear_expired(&self) -> Result<usize> {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let now = Self::current_timestamp();
 
@@ -388,8 +389,8 @@ impl CodebaseCache {
     pub fn clear_all(&self) -> Result<usize> {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         let deleted = db
             .execute("DELETE FROM codebase_cache", [])
@@ -412,8 +413,8 @@ impl CodebaseCache {
     pub fn get_stats(&self) -> Result<CacheStats> {
         let db = self
             .db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+            .get()
+            .map_err(|e| anyhow::anyhow!("Failed to get database connection: {}", e))?;
 
         // Total entries
         let total_entries: usize =
@@ -522,11 +523,11 @@ impl CodebaseCache {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rusqlite::Connection;
-    use std::sync::{Arc, Mutex};
+    use crate::db::Pool;
 
     fn setup_test_cache() -> Result<CodebaseCache> {
-        let conn = Connection::open_in_memory()?;
+        let pool = Pool::in_memory()?;
+        let conn = pool.get()?;
 
         // Create schema
         conn.execute(
@@ -557,7 +558,7 @@ mod tests {
             [],
         )?;
 
-        CodebaseCache::new(Arc::new(Mutex::new(conn)))
+        CodebaseCache::new(pool.clone())
     }
 
     #[test]
@@ -106,6 +106,7 @@ impl LLMResponseCache {
                 cached: true,
                 tool_calls: None,
                 finish_reason: None,
+                routing: None,
             };
 
             Ok(Some(response))
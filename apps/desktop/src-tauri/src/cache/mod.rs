@@ -6,6 +6,7 @@
  * - Tool result caching (in-memory LRU cache for tool executions)
  * - LLM response caching (implemented in router/cache_manager.rs)
  * - Compilation results
+ * - Scheduled warmup of the LLM and tool caches from usage patterns
  *
  * This module provides caching for both codebase analysis and tool execution results
  * to achieve <30 second task completion times for the AGI system.
@@ -13,6 +14,7 @@
 pub mod codebase;
 pub mod llm_responses;
 pub mod tool_results;
+pub mod warmup;
 pub mod watcher_integration;
 
 // Re-export codebase cache types
@@ -30,3 +32,6 @@ pub use llm_responses::{CachedLLMResponse, LLMResponseCache};
 
 // Re-export tool results cache types
 pub use tool_results::{ToolCacheStats, ToolCacheTTLConfig, ToolResultCache, ToolResultCacheEntry};
+
+// Re-export cache warmup types
+pub use warmup::{CacheWarmupScheduler, UsagePattern, WarmupReport, WarmupRun};
@@ -31,12 +31,21 @@ impl Default for ToolCacheTTLConfig {
         // Browser operations
         configs.insert("browser_navigate".to_string(), Duration::from_secs(0)); // Never cache navigation
         configs.insert("browser_click".to_string(), Duration::from_secs(0)); // Never cache actions
+        configs.insert("browser_type".to_string(), Duration::from_secs(0)); // Never cache actions
+        configs.insert("browser_select".to_string(), Duration::from_secs(0)); // Never cache actions
+        configs.insert("browser_check".to_string(), Duration::from_secs(0)); // Never cache actions
+        configs.insert("browser_press_key".to_string(), Duration::from_secs(0)); // Never cache actions
         configs.insert("browser_extract".to_string(), Duration::from_secs(60)); // 1 minute
+        configs.insert(
+            "browser_wait_for_download".to_string(),
+            Duration::from_secs(0),
+        ); // Each call claims a different download
 
         // API calls: 1 minute
         configs.insert("api_call".to_string(), Duration::from_secs(60));
         configs.insert("api_upload".to_string(), Duration::from_secs(0)); // Never cache uploads
         configs.insert("api_download".to_string(), Duration::from_secs(120)); // 2 minutes
+        configs.insert("web_fetch".to_string(), Duration::from_secs(0)); // May index into a collection
 
         // Database operations: 2 minutes
         configs.insert("db_query".to_string(), Duration::from_secs(120));
@@ -80,6 +89,17 @@ impl Default for ToolCacheTTLConfig {
             Duration::from_secs(0),
         );
 
+        // Skills run other tools, some of which act
+        configs.insert(
+            crate::agi::skills::RUN_SKILL_TOOL.to_string(),
+            Duration::from_secs(0),
+        );
+
+        // Blackboard: shared run state that other agents change at any time
+        for tool in crate::orchestration::blackboard::BLACKBOARD_TOOLS {
+            configs.insert(tool.to_string(), Duration::from_secs(0));
+        }
+
         Self {
             configs,
             default_ttl: Duration::from_secs(60), // Default: 1 minute
@@ -279,6 +299,18 @@ impl ToolResultCache {
         None
     }
 
+    /// Whether a fresh result is cached, without counting a hit or miss
+    pub fn contains(
+        &self,
+        tool_name: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> bool {
+        let cache_key = Self::generate_cache_key(tool_name, parameters);
+        self.entries
+            .get(&cache_key)
+            .is_some_and(|entry| !entry.is_expired(self.start_instant))
+    }
+
     /// Store a result in the cache
     pub fn set(
         &self,
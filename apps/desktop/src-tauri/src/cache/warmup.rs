@@ -0,0 +1,758 @@
+//! Usage-driven cache warmup.
+//!
+//! Every LLM request and read-only tool call is tallied per hour of day.
+//! Prompts and calls that keep coming back on different days are replayed
+//! shortly before the hour they are usually made, while the app is idle, so
+//! the real request finds a fresh cache entry. The first hit on a warmed
+//! entry is credited to warmup in `cache_get_analytics`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Timelike, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::{AppDatabase, LLMState};
+use crate::router::semantic_cache::CacheHitRate;
+use crate::router::{LLMRequest, Provider, RouteCandidate};
+
+/// Uses needed before a prompt or tool call counts as a pattern
+pub const MIN_USES: u32 = 3;
+/// Distinct days it must have been used on, so one busy afternoon is not a habit
+pub const MIN_ACTIVE_DAYS: u32 = 2;
+/// Requests replayed per run, which bounds what warmup can spend
+pub const MAX_WARM_PER_RUN: usize = 5;
+/// Patterns kept; the least recently used are forgotten first
+const MAX_PATTERNS: i64 = 500;
+/// Larger requests are not recorded; they are unlikely to repeat verbatim
+const MAX_PAYLOAD_BYTES: usize = 16 * 1024;
+/// A pattern warmed this recently is left alone
+const WARM_COOLDOWN_SECS: i64 = 3 * 3600;
+/// How long without a request before the app counts as idle
+const IDLE_AFTER_SECS: i64 = 120;
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+const TICK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const ENABLED_SETTING: &str = "cache.warmup_enabled";
+
+/// Tools without side effects whose results live long enough to be worth
+/// fetching ahead of time
+pub const WARMABLE_TOOLS: &[&str] = &[
+    "file_read",
+    "document_read",
+    "document_search",
+    "code_analyze",
+    "calendar_list_events",
+    "email_fetch",
+];
+
+tokio::task_local! {
+    static WARMING: ();
+}
+
+static LAST_ACTIVITY: AtomicI64 = AtomicI64::new(0);
+
+/// Run `future` as warmup: its cache lookups and requests are not counted
+/// as user activity or usage
+pub async fn warming<F: std::future::Future>(future: F) -> F::Output {
+    WARMING.scope((), future).await
+}
+
+pub fn is_warming() -> bool {
+    WARMING.try_with(|_| ()).is_ok()
+}
+
+/// No user-driven request for a while
+pub fn is_idle(now: i64) -> bool {
+    now - LAST_ACTIVITY.load(Ordering::Relaxed) >= IDLE_AFTER_SECS
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    Llm,
+    Tool,
+}
+
+impl PatternKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PatternKind::Llm => "llm",
+            PatternKind::Tool => "tool",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "llm" => Some(PatternKind::Llm),
+            "tool" => Some(PatternKind::Tool),
+            _ => None,
+        }
+    }
+}
+
+/// What is needed to replay a pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WarmupPayload {
+    Llm {
+        provider: Provider,
+        request: LLMRequest,
+    },
+    Tool {
+        tool: String,
+        parameters: HashMap<String, serde_json::Value>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsagePattern {
+    pub kind: PatternKind,
+    /// Cache key the pattern resolves to
+    pub key: String,
+    /// Start of the last user message, or the tool name
+    pub label: String,
+    pub uses: u32,
+    pub active_days: u32,
+    /// Local hour of day it is most often used at
+    pub peak_hour: u32,
+    pub last_used_at: i64,
+    pub last_warmed_at: Option<i64>,
+    pub warm_hits: u64,
+}
+
+impl UsagePattern {
+    pub fn is_frequent(&self) -> bool {
+        self.uses >= MIN_USES && self.active_days >= MIN_ACTIVE_DAYS
+    }
+
+    /// LLM responses outlive an hour, so they are warmed from the hour
+    /// before the peak; tool results expire within minutes and only during it
+    fn is_due(&self, now: DateTime<Local>) -> bool {
+        let hour = now.hour();
+        let in_window = match self.kind {
+            PatternKind::Llm => self.peak_hour == hour || self.peak_hour == (hour + 1) % 24,
+            PatternKind::Tool => self.peak_hour == hour,
+        };
+        let ts = now.timestamp();
+        let cooled_down = self
+            .last_warmed_at
+            .is_none_or(|warmed| ts - warmed >= WARM_COOLDOWN_SECS);
+        self.is_frequent() && in_window && cooled_down
+    }
+}
+
+/// Effect of warmup on cache hit rates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmupReport {
+    pub enabled: bool,
+    pub tracked_patterns: usize,
+    /// Most used recurring patterns
+    pub frequent_patterns: Vec<UsagePattern>,
+    pub last_warmed_at: Option<i64>,
+    /// Entries warmup has filled
+    pub warmed_entries: u64,
+    /// LLM hits that found an entry warmup filled
+    pub llm_warm_hits: u64,
+    pub tool_warm_hits: u64,
+    pub hit_rate_without_warmup: f64,
+    pub hit_rate_with_warmup: f64,
+    /// Hit rate gained through warmup, in percentage points
+    pub hit_rate_improvement: f64,
+}
+
+/// Outcome of one warmup run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmupRun {
+    pub llm_warmed: usize,
+    pub tools_warmed: usize,
+    /// Patterns whose cache entry was still fresh
+    pub already_fresh: usize,
+    pub failed: usize,
+}
+
+/// Remember an LLM request. Requests with tools or images, and any made
+/// by warmup itself, are ignored.
+pub fn record_llm_request(
+    conn: &Connection,
+    cache_key: &str,
+    provider: Provider,
+    model: &str,
+    request: &LLMRequest,
+    now: DateTime<Local>,
+) {
+    if is_warming() {
+        return;
+    }
+    LAST_ACTIVITY.store(now.timestamp(), Ordering::Relaxed);
+
+    let replayable = request.tools.is_none()
+        && request
+            .messages
+            .iter()
+            .all(|m| m.multimodal_content.is_none() && m.tool_calls.is_none());
+    if !replayable {
+        return;
+    }
+    let label = request
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.as_str())
+        .unwrap_or_default();
+    let mut request = request.clone();
+    request.model = model.to_string();
+    request.stream = false;
+
+    record(
+        conn,
+        PatternKind::Llm,
+        cache_key,
+        label,
+        &WarmupPayload::Llm { provider, request },
+        now,
+    );
+}
+
+/// Remember a successful call to one of [`WARMABLE_TOOLS`]
+pub fn record_tool_call(
+    conn: &Connection,
+    cache_key: &str,
+    tool: &str,
+    parameters: &HashMap<String, serde_json::Value>,
+    now: DateTime<Local>,
+) {
+    if is_warming() || !WARMABLE_TOOLS.contains(&tool) {
+        return;
+    }
+    record(
+        conn,
+        PatternKind::Tool,
+        cache_key,
+        tool,
+        &WarmupPayload::Tool {
+            tool: tool.to_string(),
+            parameters: parameters.clone(),
+        },
+        now,
+    );
+}
+
+fn record(
+    conn: &Connection,
+    kind: PatternKind,
+    key: &str,
+    label: &str,
+    payload: &WarmupPayload,
+    now: DateTime<Local>,
+) {
+    if let Err(e) = try_record(conn, kind, key, label, payload, now) {
+        tracing::debug!("Failed to record usage pattern: {}", e);
+    }
+}
+
+fn try_record(
+    conn: &Connection,
+    kind: PatternKind,
+    key: &str,
+    label: &str,
+    payload: &WarmupPayload,
+    now: DateTime<Local>,
+) -> Result<()> {
+    let payload = serde_json::to_string(payload)?;
+    if payload.len() > MAX_PAYLOAD_BYTES {
+        return Ok(());
+    }
+    let label: String = label.trim().chars().take(80).collect();
+    let today = now.format("%Y-%m-%d").to_string();
+
+    let existing: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT hour_counts, last_day FROM cache_warmup_patterns
+             WHERE kind = ?1 AND pattern_key = ?2",
+            params![kind.as_str(), key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (mut hours, new_day) = match &existing {
+        Some((hours, last_day)) => (
+            parse_hours(hours),
+            last_day.as_deref() != Some(today.as_str()),
+        ),
+        None => (vec![0; 24], true),
+    };
+    hours[now.hour() as usize] += 1;
+    let hours = serde_json::to_string(&hours)?;
+
+    conn.execute(
+        "INSERT INTO cache_warmup_patterns
+            (kind, pattern_key, label, payload, uses, active_days, last_day, hour_counts,
+             first_used_at, last_used_at)
+         VALUES (?1, ?2, ?3, ?4, 1, 1, ?5, ?6, ?7, ?7)
+         ON CONFLICT(kind, pattern_key) DO UPDATE SET
+            label = excluded.label,
+            payload = excluded.payload,
+            uses = uses + 1,
+            active_days = active_days + ?8,
+            last_day = excluded.last_day,
+            hour_counts = excluded.hour_counts,
+            last_used_at = excluded.last_used_at",
+        params![
+            kind.as_str(),
+            key,
+            label,
+            payload,
+            today,
+            hours,
+            now.timestamp(),
+            new_day as i64,
+        ],
+    )?;
+
+    if existing.is_none() {
+        conn.execute(
+            "DELETE FROM cache_warmup_patterns WHERE rowid IN (
+                SELECT rowid FROM cache_warmup_patterns
+                ORDER BY last_used_at DESC LIMIT -1 OFFSET ?1
+            )",
+            params![MAX_PATTERNS],
+        )?;
+    }
+    Ok(())
+}
+
+/// Credit a cache hit to warmup when it is the first use of a warmed entry
+pub fn record_warm_hit(conn: &Connection, kind: PatternKind, key: &str) {
+    if is_warming() {
+        return;
+    }
+    if let Err(e) = conn.execute(
+        "UPDATE cache_warmup_patterns SET warm_hits = warm_hits + 1, warm_pending = 0
+         WHERE kind = ?1 AND pattern_key = ?2 AND warm_pending = 1",
+        params![kind.as_str(), key],
+    ) {
+        tracing::debug!("Failed to record warmup hit: {}", e);
+    }
+}
+
+/// Note that warmup has just filled the cache entry for a pattern
+pub fn mark_warmed(conn: &Connection, kind: PatternKind, key: &str, now: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE cache_warmup_patterns
+         SET last_warmed_at = ?3, warm_pending = 1, warm_runs = warm_runs + 1
+         WHERE kind = ?1 AND pattern_key = ?2",
+        params![kind.as_str(), key, now],
+    )?;
+    Ok(())
+}
+
+/// Every tracked pattern, most used first
+pub fn patterns(conn: &Connection) -> Result<Vec<UsagePattern>> {
+    let mut stmt = conn.prepare(
+        "SELECT kind, pattern_key, label, uses, active_days, hour_counts, last_used_at,
+                last_warmed_at, warm_hits
+         FROM cache_warmup_patterns
+         ORDER BY uses DESC, last_used_at DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            UsagePattern {
+                kind: PatternKind::Llm,
+                key: row.get(1)?,
+                label: row.get(2)?,
+                uses: row.get(3)?,
+                active_days: row.get(4)?,
+                peak_hour: peak_hour(&parse_hours(&row.get::<_, String>(5)?)),
+                last_used_at: row.get(6)?,
+                last_warmed_at: row.get(7)?,
+                warm_hits: row.get::<_, i64>(8)? as u64,
+            },
+        ))
+    })?;
+
+    let mut patterns = Vec::new();
+    for row in rows {
+        let (kind, mut pattern) = row?;
+        if let Some(kind) = PatternKind::parse(&kind) {
+            pattern.kind = kind;
+            patterns.push(pattern);
+        }
+    }
+    Ok(patterns)
+}
+
+/// Frequent patterns due for warming at `now`, with their replay payloads
+pub fn due(
+    conn: &Connection,
+    now: DateTime<Local>,
+    limit: usize,
+) -> Result<Vec<(UsagePattern, WarmupPayload)>> {
+    let mut due = Vec::new();
+    for pattern in patterns(conn)? {
+        if due.len() >= limit {
+            break;
+        }
+        if !pattern.is_due(now) {
+            continue;
+        }
+        let payload: String = conn.query_row(
+            "SELECT payload FROM cache_warmup_patterns WHERE kind = ?1 AND pattern_key = ?2",
+            params![pattern.kind.as_str(), pattern.key],
+            |row| row.get(0),
+        )?;
+        match serde_json::from_str(&payload) {
+            Ok(payload) => due.push((pattern, payload)),
+            Err(e) => tracing::debug!("Skipping unreadable warmup pattern: {}", e),
+        }
+    }
+    Ok(due)
+}
+
+pub fn report(conn: &Connection, hit_rate: &CacheHitRate) -> Result<WarmupReport> {
+    let patterns = patterns(conn)?;
+    let (warmed_entries, last_warmed_at): (i64, Option<i64>) = conn.query_row(
+        "SELECT COALESCE(SUM(warm_runs), 0), MAX(last_warmed_at) FROM cache_warmup_patterns",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let warm_hits = |kind: PatternKind| {
+        patterns
+            .iter()
+            .filter(|p| p.kind == kind)
+            .map(|p| p.warm_hits)
+            .sum::<u64>()
+    };
+    let llm_warm_hits = warm_hits(PatternKind::Llm);
+
+    // Without warmup, each credited hit would have been a miss
+    let hits = hit_rate.exact_hits + hit_rate.semantic_hits;
+    let hit_rate_without_warmup = if hit_rate.lookups == 0 {
+        0.0
+    } else {
+        hits.saturating_sub(llm_warm_hits) as f64 / hit_rate.lookups as f64
+    };
+
+    Ok(WarmupReport {
+        enabled: is_enabled(conn),
+        tracked_patterns: patterns.len(),
+        frequent_patterns: patterns
+            .iter()
+            .filter(|p| p.is_frequent())
+            .take(10)
+            .cloned()
+            .collect(),
+        last_warmed_at,
+        warmed_entries: warmed_entries as u64,
+        llm_warm_hits,
+        tool_warm_hits: warm_hits(PatternKind::Tool),
+        hit_rate_without_warmup,
+        hit_rate_with_warmup: hit_rate.hit_rate,
+        hit_rate_improvement: (hit_rate.hit_rate - hit_rate_without_warmup) * 100.0,
+    })
+}
+
+pub fn is_enabled(conn: &Connection) -> bool {
+    crate::db::repository::get_setting(conn, ENABLED_SETTING)
+        .map(|setting| setting.value != "false")
+        .unwrap_or(true)
+}
+
+pub fn set_enabled(conn: &Connection, enabled: bool) -> Result<()> {
+    crate::db::repository::set_setting(
+        conn,
+        ENABLED_SETTING.to_string(),
+        enabled.to_string(),
+        false,
+    )?;
+    Ok(())
+}
+
+fn parse_hours(value: &str) -> Vec<u32> {
+    let mut hours: Vec<u32> = serde_json::from_str(value).unwrap_or_default();
+    hours.resize(24, 0);
+    hours
+}
+
+fn peak_hour(hours: &[u32]) -> u32 {
+    hours
+        .iter()
+        .enumerate()
+        .max_by_key(|(hour, count)| (**count, std::cmp::Reverse(*hour)))
+        .map(|(hour, _)| hour as u32)
+        .unwrap_or(0)
+}
+
+/// Warms the caches at startup and then every [`TICK_INTERVAL`], whenever
+/// the app is idle
+#[derive(Default)]
+pub struct CacheWarmupScheduler {
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CacheWarmupScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, app: &AppHandle) {
+        let app = app.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(STARTUP_DELAY).await;
+            loop {
+                if is_idle(Utc::now().timestamp()) {
+                    match run(&app, false).await {
+                        Ok(run) if run.llm_warmed + run.tools_warmed > 0 => {
+                            tracing::info!(
+                                "Cache warmup filled {} LLM and {} tool entries",
+                                run.llm_warmed,
+                                run.tools_warmed
+                            );
+                        }
+                        Ok(_) => {}
+                        Err(e) => tracing::warn!("Cache warmup failed: {}", e),
+                    }
+                }
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+        if let Some(previous) = self.worker.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
+/// Warm the patterns that are due. `force` runs even with warmup disabled,
+/// for an explicit request from the user.
+pub async fn run(app: &AppHandle, force: bool) -> Result<WarmupRun> {
+    let db = app.state::<AppDatabase>().conn.clone();
+    let due = {
+        let conn = db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+        if !force && !is_enabled(&conn) {
+            return Ok(WarmupRun::default());
+        }
+        due(&conn, Local::now(), MAX_WARM_PER_RUN)?
+    };
+
+    let mut run = WarmupRun::default();
+    for (pattern, payload) in due {
+        let warmed = match payload {
+            WarmupPayload::Llm { provider, request } => {
+                warming(warm_llm(app, &pattern.key, provider, request)).await
+            }
+            WarmupPayload::Tool { tool, parameters } => {
+                warming(crate::commands::agi::warm_tool(&tool, &parameters))
+                    .await
+                    .map_err(anyhow::Error::msg)
+            }
+        };
+
+        match warmed {
+            Ok(Some(true)) => {
+                let conn = db
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+                mark_warmed(&conn, pattern.kind, &pattern.key, Utc::now().timestamp())?;
+                match pattern.kind {
+                    PatternKind::Llm => run.llm_warmed += 1,
+                    PatternKind::Tool => run.tools_warmed += 1,
+                }
+            }
+            Ok(Some(false)) => run.already_fresh += 1,
+            // Nothing to warm with, e.g. the agent is not running
+            Ok(None) => {}
+            Err(e) => {
+                tracing::debug!("Warming {} failed: {}", pattern.label, e);
+                run.failed += 1;
+            }
+        }
+    }
+    Ok(run)
+}
+
+/// Replay a request unless its cache entry is still fresh. `Some(true)`
+/// when the exact entry was filled.
+async fn warm_llm(
+    app: &AppHandle,
+    key: &str,
+    provider: Provider,
+    request: LLMRequest,
+) -> Result<Option<bool>> {
+    let Some(llm) = app.try_state::<LLMState>() else {
+        return Ok(None);
+    };
+    let db = app.state::<AppDatabase>().conn.clone();
+    let is_fresh = || -> Result<bool> {
+        let conn = db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+        Ok(llm
+            .cache_manager
+            .fetch(&conn, key)?
+            .is_some_and(|entry| entry.expires_at > Utc::now()))
+    };
+    if is_fresh()? {
+        return Ok(Some(false));
+    }
+
+    let candidate = RouteCandidate {
+        provider,
+        model: request.model.clone(),
+        reason: "cache warmup",
+    };
+    {
+        let router = llm.router.lock().await;
+        if !router.has_provider(provider) {
+            return Ok(None);
+        }
+        router.invoke_candidate(&candidate, &request).await?;
+    }
+    // A semantic hit answers without filling the exact entry
+    Ok(Some(is_fresh()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use crate::router::ChatMessage;
+    use chrono::TimeZone;
+
+    fn request(prompt: &str) -> LLMRequest {
+        LLMRequest {
+            messages: vec![ChatMessage {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                multimodal_content: None,
+            }],
+            model: String::new(),
+            temperature: Some(0.0),
+            max_tokens: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+        }
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, day, hour, 5, 0).unwrap()
+    }
+
+    #[test]
+    fn recurring_morning_prompt_is_due_before_its_hour() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let digest = request("Summarize my unread email");
+
+        for (day, hour) in [(2, 8), (3, 8), (4, 9), (5, 8)] {
+            record_llm_request(
+                &conn,
+                "digest",
+                Provider::OpenAI,
+                "gpt-4o",
+                &digest,
+                at(day, hour),
+            );
+        }
+        // Asked often, but all on one day
+        for hour in [10, 11, 12, 13] {
+            record_llm_request(
+                &conn,
+                "burst",
+                Provider::OpenAI,
+                "gpt-4o",
+                &request("x"),
+                at(5, hour),
+            );
+        }
+
+        let all = patterns(&conn).unwrap();
+        let digest_pattern = all.iter().find(|p| p.key == "digest").unwrap();
+        assert_eq!(digest_pattern.uses, 4);
+        assert_eq!(digest_pattern.active_days, 4);
+        assert_eq!(digest_pattern.peak_hour, 8);
+        assert_eq!(digest_pattern.label, "Summarize my unread email");
+        assert!(!all.iter().find(|p| p.key == "burst").unwrap().is_frequent());
+
+        let due_early = due(&conn, at(6, 7), 5).unwrap();
+        assert_eq!(due_early.len(), 1);
+        match &due_early[0].1 {
+            WarmupPayload::Llm { provider, request } => {
+                assert_eq!(*provider, Provider::OpenAI);
+                assert_eq!(request.model, "gpt-4o");
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+        assert!(due(&conn, at(6, 15), 5).unwrap().is_empty());
+
+        // Warmed patterns cool down
+        mark_warmed(&conn, PatternKind::Llm, "digest", at(6, 7).timestamp()).unwrap();
+        assert!(due(&conn, at(6, 8), 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_first_hit_after_warmup_is_credited() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let digest = request("Summarize my unread email");
+        record_llm_request(
+            &conn,
+            "digest",
+            Provider::OpenAI,
+            "gpt-4o",
+            &digest,
+            at(2, 8),
+        );
+
+        record_warm_hit(&conn, PatternKind::Llm, "digest");
+        mark_warmed(&conn, PatternKind::Llm, "digest", at(3, 7).timestamp()).unwrap();
+        record_warm_hit(&conn, PatternKind::Llm, "digest");
+        record_warm_hit(&conn, PatternKind::Llm, "digest");
+
+        let hit_rate = CacheHitRate {
+            lookups: 10,
+            exact_hits: 4,
+            misses: 6,
+            hit_rate: 0.4,
+            ..CacheHitRate::default()
+        };
+        let report = report(&conn, &hit_rate).unwrap();
+        assert_eq!(report.llm_warm_hits, 1);
+        assert_eq!(report.warmed_entries, 1);
+        assert!((report.hit_rate_without_warmup - 0.3).abs() < 1e-9);
+        assert!((report.hit_rate_improvement - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn requests_with_tools_are_not_recorded() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let mut with_tools = request("What's on my calendar?");
+        with_tools.tools = Some(Vec::new());
+        record_llm_request(
+            &conn,
+            "tools",
+            Provider::OpenAI,
+            "gpt-4o",
+            &with_tools,
+            at(2, 8),
+        );
+        record_tool_call(&conn, "write", "file_write", &HashMap::new(), at(2, 8));
+
+        assert!(patterns(&conn).unwrap().is_empty());
+    }
+}
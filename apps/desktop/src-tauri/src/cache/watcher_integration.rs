@@ -84,12 +84,13 @@ pub fn handle_directory_change(cache: Arc<CodebaseCache>, dir_path: &Path) -> Re
 mod tests {
     use super::*;
     use crate::cache::{CacheType, CodebaseCache, FileTree};
-    use rusqlite::Connection;
+    use crate::db::Pool;
     use std::path::PathBuf;
-    use std::sync::{Arc, Mutex};
+    use std::sync::Arc;
 
     fn setup_test_cache() -> Result<Arc<CodebaseCache>> {
-        let conn = Connection::open_in_memory()?;
+        let pool = Pool::in_memory()?;
+        let conn = pool.get()?;
 
         // Create schema
         conn.execute(
@@ -110,7 +111,7 @@ mod tests {
             [],
         )?;
 
-        let cache = CodebaseCache::new(Arc::new(Mutex::new(conn)))?;
+        let cache = CodebaseCache::new(pool.clone())?;
         Ok(Arc::new(cache))
     }
 
@@ -466,6 +466,43 @@ impl CalendarManager {
         Ok(())
     }
 
+    /// Refresh an account's access token and make one read-only call to
+    /// confirm the account still works
+    ///
+    /// Restored tokens carry no expiry, so one with a refresh token is
+    /// treated as expired to exercise the refresh.
+    pub async fn check_account(&self, account_id: &str) -> Result<()> {
+        self.ensure_client_loaded(account_id)?;
+
+        let mut client = {
+            let entry = self
+                .clients
+                .get(account_id)
+                .ok_or_else(|| Error::Other("Account not found".to_string()))?;
+            entry.value().clone()
+        };
+        if let Some(mut token) = client.token() {
+            if token.expires_at.is_none() && token.refresh_token.is_some() {
+                token.expires_at = Some(0);
+                client.set_token(token);
+            }
+        }
+
+        client.ensure_valid_token().await?;
+        client.list_calendars().await?;
+
+        if let Some(token) = client.token() {
+            if let Some(mut entry) = self.clients.get_mut(account_id) {
+                entry.value_mut().set_token(token.clone());
+            }
+            if let Some(mut info) = self.accounts.get_mut(account_id) {
+                info.token = token;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Remove an account/client from memory
     pub fn remove_account(&self, account_id: &str) {
         self.clients.remove(account_id);
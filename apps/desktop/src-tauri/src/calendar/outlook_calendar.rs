@@ -28,7 +28,7 @@ impl OutlookCalendarClient {
     pub fn new(client_id: String, client_secret: String, redirect_uri: String) -> Self {
         let oauth_config = OAuth2Config {
             client_id,
-            client_secret: Some(client_secret),
+            client_secret: Some(client_secret.into()),
             auth_url: MICROSOFT_AUTH_URL.to_string(),
             token_url: MICROSOFT_TOKEN_URL.to_string(),
             redirect_uri,
@@ -92,7 +92,7 @@ impl OutlookCalendarClient {
     fn get_access_token(&self) -> Result<&str> {
         self.token
             .as_ref()
-            .map(|t| t.access_token.as_str())
+            .map(|t| t.access_token.expose_secret())
             .ok_or_else(|| Error::Other("Not authenticated".to_string()))
     }
 
@@ -105,7 +105,7 @@ impl OutlookCalendarClient {
                 if let Some(ref refresh_token) = token.refresh_token {
                     let new_token = self
                         .oauth_client
-                        .refresh_token(refresh_token)
+                        .refresh_token(refresh_token.expose_secret())
                         .await?
                         .with_expiration();
                     self.token = Some(new_token);
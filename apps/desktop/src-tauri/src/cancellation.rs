@@ -0,0 +1,296 @@
+//! Cancellable operations.
+//!
+//! Long-running commands register an operation under an ID the frontend
+//! can pass to `operation_cancel`. The command body runs inside the
+//! operation's scope, and layers below it (the LLM router, embeddings,
+//! document extraction, the API client) pick the operation's token up
+//! through [`cancellable`] and [`check`] without it being threaded through
+//! every signature. Once cancelled, those layers get a short grace period to
+//! unwind and undo partial work before the command future is dropped.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+
+pub const OPERATION_STARTED_EVENT: &str = "operation:started";
+pub const OPERATION_FINISHED_EVENT: &str = "operation:finished";
+
+/// How long a cancelled operation gets to clean up before it is dropped
+const CLEANUP_GRACE: Duration = Duration::from_secs(2);
+
+tokio::task_local! {
+    static CURRENT: CancellationToken;
+}
+
+/// Returned by work that stopped because its operation was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Operation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl From<Cancelled> for crate::error::Error {
+    fn from(_: Cancelled) -> Self {
+        crate::error::Error::Cancelled
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+    pub id: String,
+    /// What the operation is doing, e.g. `document_extract_text`
+    pub kind: String,
+    pub started_at: DateTime<Utc>,
+    pub cancelled: bool,
+}
+
+struct Entry {
+    info: OperationInfo,
+    token: CancellationToken,
+    /// Tells an operation apart from a later one that reused its ID
+    seq: u64,
+}
+
+#[derive(Default)]
+pub struct OperationRegistry {
+    operations: Mutex<HashMap<String, Entry>>,
+    next_seq: AtomicU64,
+}
+
+impl OperationRegistry {
+    /// Register an operation, under `id` when the caller picked one so it
+    /// can cancel before the command returns
+    pub fn begin(&'static self, kind: &str, id: Option<String>) -> Operation {
+        let id = id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let token = CancellationToken::new();
+        let info = OperationInfo {
+            id: id.clone(),
+            kind: kind.to_string(),
+            started_at: Utc::now(),
+            cancelled: false,
+        };
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let entry = Entry {
+            info,
+            token: token.clone(),
+            seq,
+        };
+        if let Some(previous) = self.operations.lock().insert(id.clone(), entry) {
+            // An ID reused while still running cancels the older operation
+            previous.token.cancel();
+        }
+
+        Operation {
+            id,
+            token,
+            seq,
+            registry: self,
+        }
+    }
+
+    /// Cancel a running operation; false when no operation has that ID
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.operations.lock().get_mut(id) {
+            Some(entry) => {
+                entry.info.cancelled = true;
+                entry.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every running operation, returning how many there were
+    pub fn cancel_all(&self) -> usize {
+        let mut operations = self.operations.lock();
+        for entry in operations.values_mut() {
+            entry.info.cancelled = true;
+            entry.token.cancel();
+        }
+        operations.len()
+    }
+
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let mut operations: Vec<OperationInfo> = self
+            .operations
+            .lock()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        operations.sort_by_key(|info| info.started_at);
+        operations
+    }
+
+    fn finish(&self, id: &str, seq: u64) {
+        let mut operations = self.operations.lock();
+        // Leave a newer operation registered under the same ID alone
+        if operations.get(id).is_some_and(|entry| entry.seq == seq) {
+            operations.remove(id);
+        }
+    }
+}
+
+/// A registered operation; unregisters itself when dropped
+pub struct Operation {
+    id: String,
+    token: CancellationToken,
+    seq: u64,
+    registry: &'static OperationRegistry,
+}
+
+impl Operation {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Run `work` in this operation's scope. Once the operation is
+    /// cancelled, `work` gets [`CLEANUP_GRACE`] to notice and clean up; its
+    /// output is returned if it finishes in time, `Cancelled` otherwise.
+    pub async fn run<F: Future>(self, work: F) -> Result<F::Output, Cancelled> {
+        let token = self.token.clone();
+        let scoped = CURRENT.scope(token.clone(), work);
+        tokio::pin!(scoped);
+
+        tokio::select! {
+            biased;
+            output = &mut scoped => return Ok(output),
+            _ = token.cancelled() => {}
+        }
+        tracing::info!("Operation {} cancelled", self.id);
+        tokio::time::timeout(CLEANUP_GRACE, &mut scoped)
+            .await
+            .map_err(|_| Cancelled)
+    }
+
+    /// Like [`Operation::run`], announcing the operation to the frontend with
+    /// `operation:started` and `operation:finished` events
+    pub async fn run_with_events<F: Future>(
+        self,
+        app: &AppHandle,
+        work: F,
+    ) -> Result<F::Output, Cancelled> {
+        let id = self.id.clone();
+        let kind = self
+            .registry
+            .operations
+            .lock()
+            .get(&id)
+            .map(|entry| entry.info.kind.clone())
+            .unwrap_or_default();
+        let _ = app.emit(
+            OPERATION_STARTED_EVENT,
+            serde_json::json!({ "id": id, "kind": kind }),
+        );
+        let result = self.run(work).await;
+        let _ = app.emit(
+            OPERATION_FINISHED_EVENT,
+            serde_json::json!({ "id": id, "kind": kind, "cancelled": result.is_err() }),
+        );
+        result
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        self.registry.finish(&self.id, self.seq);
+    }
+}
+
+static OPERATIONS: Lazy<OperationRegistry> = Lazy::new(OperationRegistry::default);
+
+/// Operations registered by commands across the app
+pub fn global_operations() -> &'static OperationRegistry {
+    &OPERATIONS
+}
+
+/// Token of the operation the current task runs in, if any
+pub fn current() -> Option<CancellationToken> {
+    CURRENT.try_with(CancellationToken::clone).ok()
+}
+
+/// `Err(Cancelled)` once the current operation has been cancelled; for loops
+/// to call between units of work
+pub fn check() -> Result<(), Cancelled> {
+    match current() {
+        Some(token) if token.is_cancelled() => Err(Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Await `work`, stopping early if the current operation is cancelled.
+/// Outside an operation this is just `work.await`.
+pub async fn cancellable<F: Future>(work: F) -> Result<F::Output, Cancelled> {
+    match current() {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(Cancelled),
+            output = work => Ok(output),
+        },
+        None => Ok(work.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> &'static OperationRegistry {
+        Box::leak(Box::default())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reaches_nested_work() {
+        let registry = registry();
+        let operation = registry.begin("test", Some("op-1".into()));
+        assert_eq!(registry.list().len(), 1);
+
+        let cleaned_up = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = cleaned_up.clone();
+        let work = async move {
+            let result = cancellable(tokio::time::sleep(Duration::from_secs(30))).await;
+            if result.is_err() {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+            result
+        };
+        let handle = tokio::spawn(operation.run(work));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(registry.cancel("op-1"));
+        // The work noticed the cancel and returned its own error
+        assert_eq!(handle.await.unwrap(), Ok(Err(Cancelled)));
+        assert!(cleaned_up.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(registry.list().is_empty());
+        assert!(!registry.cancel("op-1"));
+    }
+
+    #[tokio::test]
+    async fn test_outside_an_operation_nothing_is_cancelled() {
+        assert!(check().is_ok());
+        assert_eq!(cancellable(async { 7 }).await, Ok(7));
+
+        let registry = registry();
+        let operation = registry.begin("test", None);
+        let result = operation.run(async { check().map(|_| 1) }).await;
+        assert_eq!(result, Ok(Ok(1)));
+    }
+}
@@ -0,0 +1,472 @@
+//! Headless access to the app's core for the `agiworkforce-cli` binary.
+//!
+//! [`Core`] opens the desktop app's database and secrets vault without a
+//! window, so workflows, folder indexing and chat prompts run from a terminal
+//! or CI job against the user's data and stored provider keys. Provider keys
+//! can also come from the environment, e.g. `OPENAI_API_KEY`.
+
+use crate::commands::WorkflowEngineState;
+use crate::db::{migrations, Pool};
+use crate::embeddings::{EmbeddingConfig, EmbeddingService};
+use crate::orchestration::{WorkflowExecution, WorkflowSimulation, WorkflowStatus};
+use crate::router::llm_router::RouterPreferences;
+use crate::router::providers::{
+    anthropic::AnthropicProvider, deepseek::DeepSeekProvider, google::GoogleProvider,
+    mistral::MistralProvider, ollama::OllamaProvider, openai::OpenAIProvider, qwen::QwenProvider,
+    xai::XAIProvider,
+};
+use crate::router::{LLMRouter, Provider};
+use crate::security::vault::{SecretId, Vault};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Tauri identifier; the app keeps its data in a directory of this name
+pub const APP_IDENTIFIER: &str = "com.agiworkforce.desktop";
+/// Overrides where the CLI looks for the app's data
+pub const DATA_DIR_ENV: &str = "AGIWORKFORCE_DATA_DIR";
+
+/// Vault actor recorded for key reads
+const VAULT_ACTOR: &str = "cli";
+const WORKFLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Providers that need an API key, with the variable that can supply it
+const KEYED_PROVIDERS: &[(&str, &str)] = &[
+    ("openai", "OPENAI_API_KEY"),
+    ("anthropic", "ANTHROPIC_API_KEY"),
+    ("google", "GOOGLE_API_KEY"),
+    ("xai", "XAI_API_KEY"),
+    ("deepseek", "DEEPSEEK_API_KEY"),
+    ("qwen", "DASHSCOPE_API_KEY"),
+    ("mistral", "MISTRAL_API_KEY"),
+];
+
+/// Data directory of the desktop app: [`DATA_DIR_ENV`] when set, else the
+/// one Tauri picks for [`APP_IDENTIFIER`]
+pub fn default_data_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| anyhow!("Failed to find the data directory; set {}", DATA_DIR_ENV))
+}
+
+/// `key=value` pairs as workflow inputs. Values that parse as JSON are
+/// taken as such, anything else as a string
+pub fn parse_inputs(pairs: &[String]) -> Result<HashMap<String, Value>> {
+    pairs
+        .iter()
+        .map(|pair| {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected key=value, got '{}'", pair))?;
+            let key = key.trim();
+            if key.is_empty() {
+                bail!("Input '{}' has no name", pair);
+            }
+            let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.into()));
+            Ok((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// How a workflow run from the CLI ended
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum WorkflowOutcome {
+    /// Completed, failed or cancelled
+    Finished(WorkflowExecution),
+    /// Paused from the app, which can resume it where it stopped
+    Paused(WorkflowExecution),
+    /// Still running at the deadline, so cancelled
+    TimedOut(WorkflowExecution),
+    Simulated(WorkflowSimulation),
+}
+
+impl WorkflowOutcome {
+    /// Process exit status: 0 when the run completed (or was simulated), 1
+    /// when it failed or was cancelled, 2 when it was paused and 124, as
+    /// with `timeout(1)`, when it ran out of time
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            WorkflowOutcome::Finished(e) if e.status != WorkflowStatus::Completed => 1,
+            WorkflowOutcome::Finished(_) | WorkflowOutcome::Simulated(_) => 0,
+            WorkflowOutcome::Paused(_) => 2,
+            WorkflowOutcome::TimedOut(_) => 124,
+        }
+    }
+}
+
+/// The app's database and vault, opened without the GUI
+pub struct Core {
+    pool: Pool,
+    vault: Vault,
+}
+
+impl Core {
+    /// Open the app data in `data_dir`, applying pending migrations the way
+    /// the app does on startup
+    pub fn open(data_dir: impl Into<PathBuf>) -> Result<Self> {
+        let data_dir = data_dir.into();
+        std::fs::create_dir_all(&data_dir)
+            .with_context(|| format!("Failed to create data directory {}", data_dir.display()))?;
+        let db_path = data_dir.join("agiworkforce.db");
+
+        let conn = crate::db::pool::open(&db_path).context("Failed to open database")?;
+        migrations::run_migrations_with_backup(&conn, &data_dir.join("backups"))
+            .context("Failed to run migrations")?;
+        drop(conn);
+
+        let pool = Pool::open(&db_path).context("Failed to open database pool")?;
+        let vault = Vault::open(pool.clone(), &data_dir.join("vault.key"))
+            .context("Failed to open secrets vault")?;
+        Ok(Self { pool, vault })
+    }
+
+    /// Key for `provider` from its environment variable, else from the vault
+    fn provider_key(&self, provider: &str) -> Option<String> {
+        let from_env = KEYED_PROVIDERS
+            .iter()
+            .find(|(name, _)| *name == provider)
+            .and_then(|(_, var)| std::env::var(var).ok())
+            .filter(|key| !key.trim().is_empty());
+        from_env.or_else(
+            || match self.vault.get(&SecretId::provider(provider), VAULT_ACTOR) {
+                Ok(key) => key.map(|key| key.expose_secret().to_string()),
+                Err(e) => {
+                    tracing::warn!("Failed to read {} API key: {}", provider, e);
+                    None
+                }
+            },
+        )
+    }
+
+    /// Router with every provider a key is available for, plus local Ollama
+    fn llm_router(&self) -> LLMRouter {
+        let mut router = LLMRouter::new();
+        for (provider, _) in KEYED_PROVIDERS {
+            let Some(key) = self.provider_key(provider) else {
+                continue;
+            };
+            match *provider {
+                "openai" => router.set_openai(Box::new(OpenAIProvider::new(key))),
+                "anthropic" => router.set_anthropic(Box::new(AnthropicProvider::new(key))),
+                "google" => router.set_google(Box::new(GoogleProvider::new(key))),
+                "xai" => router.set_xai(Box::new(XAIProvider::new(Some(key)))),
+                "deepseek" => router.set_deepseek(Box::new(DeepSeekProvider::new(Some(key)))),
+                "qwen" => router.set_qwen(Box::new(QwenProvider::new(Some(key)))),
+                "mistral" => router.set_mistral(Box::new(MistralProvider::new(Some(key)))),
+                _ => {}
+            }
+        }
+        router.set_ollama(Box::new(OllamaProvider::new(
+            std::env::var("OLLAMA_HOST").ok(),
+        )));
+        router
+    }
+
+    /// Send one prompt and return the reply
+    pub async fn chat(
+        &self,
+        prompt: &str,
+        provider: Option<&str>,
+        model: Option<String>,
+    ) -> Result<String> {
+        let provider = provider
+            .map(|name| {
+                Provider::from_string(name).ok_or_else(|| anyhow!("Unknown provider: {}", name))
+            })
+            .transpose()?;
+        let preferences = RouterPreferences {
+            provider,
+            model,
+            ..RouterPreferences::default()
+        };
+        self.llm_router()
+            .send_message(prompt, Some(preferences))
+            .await
+    }
+
+    /// Run a workflow until it completes, fails, is cancelled or paused, or
+    /// with `simulate` predict its run. A run still going after `timeout` is
+    /// cancelled.
+    pub async fn run_workflow(
+        &self,
+        workflow_id: String,
+        inputs: HashMap<String, Value>,
+        simulate: bool,
+        timeout: Option<Duration>,
+    ) -> Result<WorkflowOutcome> {
+        let state = WorkflowEngineState::new(self.pool.clone());
+        if simulate {
+            return state
+                .executor
+                .simulate_workflow(&workflow_id, inputs)
+                .map(WorkflowOutcome::Simulated)
+                .map_err(|e| anyhow!(e));
+        }
+        crate::kill_switch::ensure_allowed("Running workflows").map_err(|e| anyhow!(e))?;
+        let (execution_id, run) = state
+            .executor
+            .start_workflow(workflow_id, inputs)
+            .map_err(|e| anyhow!(e))?;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        // The run is a task of this process, so stay until it settles
+        loop {
+            // Checked before reading the status, which a finished task has
+            // already written
+            let task_ended = run.is_finished();
+            let execution = state
+                .engine
+                .get_execution_status(&execution_id)
+                .map_err(|e| anyhow!(e))?;
+            match execution.status {
+                WorkflowStatus::Completed | WorkflowStatus::Failed | WorkflowStatus::Cancelled => {
+                    return Ok(WorkflowOutcome::Finished(execution));
+                }
+                // Whoever resumes it continues from the current node
+                WorkflowStatus::Paused => {
+                    run.abort();
+                    return Ok(WorkflowOutcome::Paused(execution));
+                }
+                WorkflowStatus::Pending | WorkflowStatus::Running => {}
+            }
+
+            if task_ended {
+                let error = match run.await {
+                    Err(e) if e.is_panic() => "The workflow executor crashed".to_string(),
+                    _ => "The workflow executor stopped without recording an outcome".to_string(),
+                };
+                state
+                    .engine
+                    .update_execution_status(
+                        &execution_id,
+                        WorkflowStatus::Failed,
+                        execution.current_node_id,
+                        Some(error),
+                    )
+                    .map_err(|e| anyhow!(e))?;
+                return Ok(WorkflowOutcome::Finished(
+                    state
+                        .engine
+                        .get_execution_status(&execution_id)
+                        .map_err(|e| anyhow!(e))?,
+                ));
+            }
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                run.abort();
+                state
+                    .executor
+                    .cancel_execution(&execution_id)
+                    .map_err(|e| anyhow!(e))?;
+                return Ok(WorkflowOutcome::TimedOut(
+                    state
+                        .engine
+                        .get_execution_status(&execution_id)
+                        .map_err(|e| anyhow!(e))?,
+                ));
+            }
+            tokio::time::sleep(WORKFLOW_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Embed the files of `folder` for semantic search. The index is kept in
+    /// the folder's `.agi` directory; returns how many files it holds
+    pub async fn index_folder(&self, folder: &Path) -> Result<usize> {
+        let folder = folder
+            .canonicalize()
+            .with_context(|| format!("Failed to open {}", folder.display()))?;
+        if !folder.is_dir() {
+            bail!("{} is not a folder", folder.display());
+        }
+        let config = EmbeddingConfig {
+            openai_api_key: self.provider_key("openai").map(Into::into),
+            ..EmbeddingConfig::default()
+        };
+        let service = EmbeddingService::new(folder, config)
+            .await
+            .context("Failed to start the embedding service")?;
+        let indexer = service.indexer();
+        let indexer = indexer.lock().await;
+        indexer
+            .index_workspace()
+            .await
+            .context("Failed to index folder")?;
+        Ok(indexer.indexed_count().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_inputs_parse_json_values_and_fall_back_to_strings() {
+        let inputs = parse_inputs(&[
+            "count=3".to_string(),
+            "tags=[\"a\",\"b\"]".to_string(),
+            "name=weekly report".to_string(),
+            "query=a=b".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(inputs["count"], json!(3));
+        assert_eq!(inputs["tags"], json!(["a", "b"]));
+        assert_eq!(inputs["name"], json!("weekly report"));
+        assert_eq!(inputs["query"], json!("a=b"));
+
+        assert!(parse_inputs(&["missing".to_string()]).is_err());
+        assert!(parse_inputs(&["=1".to_string()]).is_err());
+    }
+
+    /// Save a workflow whose `nodes` run one after another
+    fn save_workflow(core: &Core, id: &str, nodes: Vec<Value>) {
+        let edges: Vec<Value> = nodes
+            .windows(2)
+            .map(|pair| {
+                let (source, target) = (pair[0]["id"].as_str(), pair[1]["id"].as_str());
+                json!({
+                    "id": format!("{}-{}", source.unwrap(), target.unwrap()),
+                    "source": source,
+                    "target": target,
+                })
+            })
+            .collect();
+        let definition = serde_json::from_value(json!({
+            "id": id,
+            "user_id": "user-1",
+            "name": id,
+            "nodes": nodes,
+            "edges": edges,
+            "triggers": [],
+            "metadata": {},
+            "created_at": 0,
+            "updated_at": 0,
+        }))
+        .unwrap();
+        WorkflowEngineState::new(core.pool.clone())
+            .engine
+            .create_workflow(definition)
+            .unwrap();
+    }
+
+    fn node(id: &str, kind: &str, data: Value) -> Value {
+        let mut data = data;
+        data["label"] = json!(id);
+        json!({ "id": id, "type": kind, "position": { "x": 0, "y": 0 }, "data": data })
+    }
+
+    fn wait(id: &str, seconds: u64) -> Value {
+        node(
+            id,
+            "wait",
+            json!({ "wait_type": "duration", "duration_seconds": seconds }),
+        )
+    }
+
+    fn tool(id: &str) -> Value {
+        node(id, "tool", json!({ "tool_name": id, "tool_input": {} }))
+    }
+
+    async fn run(core: &Core, workflow_id: &str, timeout: Option<Duration>) -> WorkflowOutcome {
+        core.run_workflow(workflow_id.to_string(), HashMap::new(), false, timeout)
+            .await
+            .unwrap()
+    }
+
+    fn status(outcome: &WorkflowOutcome) -> WorkflowStatus {
+        match outcome {
+            WorkflowOutcome::Finished(execution)
+            | WorkflowOutcome::Paused(execution)
+            | WorkflowOutcome::TimedOut(execution) => execution.status.clone(),
+            WorkflowOutcome::Simulated(_) => panic!("expected a run"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_workflow_reports_completed_and_failed_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let core = Core::open(dir.path()).unwrap();
+
+        save_workflow(&core, "steps", vec![tool("first"), tool("second")]);
+        let outcome = run(&core, "steps", None).await;
+        assert_eq!(status(&outcome), WorkflowStatus::Completed);
+        assert_eq!(outcome.exit_code(), 0);
+
+        // A sub-workflow deleted after the parent was saved fails the run
+        save_workflow(&core, "child", vec![tool("inner")]);
+        save_workflow(
+            &core,
+            "parent",
+            vec![node(
+                "nested",
+                "sub_workflow",
+                json!({ "workflow_id": "child" }),
+            )],
+        );
+        WorkflowEngineState::new(core.pool.clone())
+            .engine
+            .delete_workflow("child")
+            .unwrap();
+        let outcome = run(&core, "parent", None).await;
+        assert_eq!(status(&outcome), WorkflowStatus::Failed);
+        assert_eq!(outcome.exit_code(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_workflow_stops_at_a_pause() {
+        let dir = tempfile::tempdir().unwrap();
+        let core = Core::open(dir.path()).unwrap();
+        save_workflow(&core, "slow", vec![wait("hold", 30), tool("after")]);
+
+        // Pause from outside the run, the way the app does
+        let pool = core.pool.clone();
+        tokio::spawn(async move {
+            let state = WorkflowEngineState::new(pool.clone());
+            loop {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let running: Option<String> = pool
+                    .get()
+                    .unwrap()
+                    .query_row(
+                        "SELECT id FROM workflow_executions WHERE status = 'running'",
+                        [],
+                        |row| row.get(0),
+                    )
+                    .ok();
+                if let Some(id) = running {
+                    state.executor.pause_execution(&id).unwrap();
+                    break;
+                }
+            }
+        });
+
+        let outcome = run(&core, "slow", Some(Duration::from_secs(20))).await;
+        assert_eq!(status(&outcome), WorkflowStatus::Paused);
+        assert_eq!(outcome.exit_code(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_workflow_gives_up_on_timeouts_and_dead_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let core = Core::open(dir.path()).unwrap();
+
+        save_workflow(&core, "slow", vec![wait("hold", 30), tool("after")]);
+        let outcome = run(&core, "slow", Some(Duration::from_millis(300))).await;
+        assert_eq!(status(&outcome), WorkflowStatus::Cancelled);
+        assert_eq!(outcome.exit_code(), 124);
+
+        // Without a start node the executor gives up after marking the run
+        // as running; the CLI must not wait on it forever
+        save_workflow(&core, "empty", Vec::new());
+        let outcome = run(&core, "empty", None).await;
+        assert_eq!(status(&outcome), WorkflowStatus::Failed);
+        assert_eq!(outcome.exit_code(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Result};
+#[cfg(windows)]
 use clipboard_win::{formats, get_clipboard_string, set_clipboard};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
@@ -149,7 +150,7 @@ impl ClipboardMonitor {
         db_path: &PathBuf,
     ) -> Result<()> {
         // Try to get text from clipboard
-        if let Ok(current_text) = get_clipboard_string() {
+        if let Ok(current_text) = read_clipboard_text() {
             let mut last = last_content.lock().await;
 
             if last.as_ref() != Some(&current_text) {
@@ -330,18 +331,44 @@ impl ClipboardMonitor {
     }
 
     pub async fn set_clipboard_text(&self, text: &str) -> Result<()> {
-        set_clipboard(formats::Unicode, text)?;
-        Ok(())
+        write_clipboard_text(text)
     }
 
     pub async fn get_current_clipboard(&self) -> Result<Option<String>> {
-        match get_clipboard_string() {
+        match read_clipboard_text() {
             Ok(text) => Ok(Some(text)),
             Err(_) => Ok(None),
         }
     }
 }
 
+/// Text currently on the system clipboard
+#[cfg(windows)]
+pub fn read_clipboard_text() -> Result<String> {
+    Ok(get_clipboard_string()?)
+}
+
+#[cfg(windows)]
+fn write_clipboard_text(text: &str) -> Result<()> {
+    set_clipboard(formats::Unicode, text)?;
+    Ok(())
+}
+
+/// Text currently on the system clipboard
+#[cfg(not(windows))]
+pub fn read_clipboard_text() -> Result<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|err| anyhow!("Failed to read clipboard: {err}"))
+}
+
+#[cfg(not(windows))]
+fn write_clipboard_text(text: &str) -> Result<()> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|err| anyhow!("Failed to write clipboard: {err}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,6 +2,7 @@ use crate::{
     api::oauth::{OAuth2Client, OAuth2Config, PkceChallenge, TokenResponse},
     cloud::{CloudFile, ListOptions, ShareLink},
     error::{Error, Result},
+    security::SecretString,
 };
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -24,7 +25,11 @@ pub struct DropboxClient {
 
 impl DropboxClient {
     // Updated Nov 16, 2025: Return Result instead of panicking on HTTP client construction failure
-    pub fn new(client_id: String, client_secret: String, redirect_uri: String) -> Result<Self> {
+    pub fn new(
+        client_id: String,
+        client_secret: SecretString,
+        redirect_uri: String,
+    ) -> Result<Self> {
         let oauth_config = OAuth2Config {
             client_id,
             client_secret: Some(client_secret),
@@ -67,10 +72,10 @@ impl DropboxClient {
         Ok(())
     }
 
-    async fn ensure_token(&mut self) -> Result<String> {
+    pub(super) async fn ensure_token(&mut self) -> Result<String> {
         if let Some(token) = &self.token {
             if !token.is_expired() {
-                return Ok(token.access_token.clone());
+                return Ok(token.access_token.expose_secret().to_string());
             }
         }
 
@@ -82,13 +87,38 @@ impl DropboxClient {
                 Error::Other("Dropbox refresh token missing; re-authenticate".to_string())
             })?;
 
-        let refreshed = self
+        let mut refreshed = self
             .oauth_client
-            .refresh_token(&refresh)
+            .refresh_token(refresh.expose_secret())
             .await
             .map_err(|e| Error::Other(format!("Failed to refresh Dropbox token: {}", e)))?;
-        self.token = Some(refreshed.clone());
-        Ok(refreshed.access_token)
+        if refreshed.refresh_token.is_none() {
+            // Providers may omit the refresh token on refresh; keep the one we have
+            refreshed.refresh_token = Some(refresh);
+        }
+        let access_token = refreshed.access_token.expose_secret().to_string();
+        self.token = Some(refreshed);
+        Ok(access_token)
+    }
+
+    /// Current refresh token, if the account has been authorized
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.token
+            .as_ref()
+            .and_then(|token| token.refresh_token.as_ref())
+            .map(SecretString::expose_secret)
+    }
+
+    /// Restore a persisted session; the access token is refreshed on first use
+    pub fn restore_refresh_token(&mut self, refresh_token: SecretString) {
+        self.token = Some(TokenResponse {
+            access_token: SecretString::default(),
+            token_type: "Bearer".to_string(),
+            expires_in: None,
+            refresh_token: Some(refresh_token),
+            scope: None,
+            expires_at: Some(0),
+        });
     }
 
     pub async fn list(&mut self, options: ListOptions) -> Result<Vec<CloudFile>> {
@@ -518,7 +548,7 @@ impl DropboxClient {
         let response = self
             .client
             .post(format!("{DROPBOX_API_URL}/users/get_current_account"))
-            .bearer_auth(&token.access_token)
+            .bearer_auth(token.access_token.expose_secret())
             .send()
             .await
             .map_err(|e| Error::Other(format!("Dropbox account lookup failed: {}", e)))?;
@@ -1,13 +1,16 @@
 mod dropbox;
 mod google_drive;
 mod one_drive;
+mod webdav;
 
 pub use dropbox::DropboxClient;
 pub use google_drive::GoogleDriveClient;
 pub use one_drive::OneDriveClient;
+pub use webdav::{WebDavAuth, WebDavClient, WebDavConfig};
 
 use crate::api::oauth::PkceChallenge;
 use crate::error::{Error, Result};
+use crate::security::{SecretManager, SecretString};
 use dashmap::DashMap;
 use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
@@ -22,6 +25,8 @@ pub enum CloudProvider {
     GoogleDrive,
     Dropbox,
     OneDrive,
+    #[serde(rename = "webdav")]
+    WebDav,
 }
 
 /// Summary information for a connected cloud account
@@ -64,22 +69,51 @@ pub struct ListOptions {
 }
 
 /// OAuth configuration required to connect a provider
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloudOAuthConfig {
     pub provider: CloudProvider,
     pub client_id: String,
-    pub client_secret: Option<String>,
+    pub client_secret: Option<SecretString>,
     pub redirect_uri: String,
 }
 
+/// How an account authenticates with its provider
+#[derive(Debug, Clone)]
+pub enum CloudConnection {
+    OAuth(CloudOAuthConfig),
+    WebDav(WebDavConfig),
+}
+
+impl CloudConnection {
+    pub fn provider(&self) -> CloudProvider {
+        match self {
+            CloudConnection::OAuth(config) => config.provider,
+            CloudConnection::WebDav(_) => CloudProvider::WebDav,
+        }
+    }
+}
+
+/// Credentials kept in the secret vault so an account survives restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCredentials {
+    client_secret: Option<SecretString>,
+    refresh_token: SecretString,
+}
+
+/// WebDAV password kept in the secret vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredWebDavCredentials {
+    password: String,
+}
+
 struct PendingAuth {
-    provider: CloudProvider,
+    config: CloudOAuthConfig,
     client: CloudClient,
     pkce: Option<PkceChallenge>,
 }
 
 struct AccountEntry {
-    provider: CloudProvider,
+    connection: CloudConnection,
     label: Option<String>,
     client: Arc<Mutex<CloudClient>>,
 }
@@ -88,6 +122,7 @@ struct AccountEntry {
 pub struct CloudStorageManager {
     accounts: DashMap<String, AccountEntry>,
     pending: DashMap<String, PendingAuth>,
+    secrets: Option<Arc<SecretManager>>,
 }
 
 impl Default for CloudStorageManager {
@@ -101,6 +136,15 @@ impl CloudStorageManager {
         Self {
             accounts: DashMap::new(),
             pending: DashMap::new(),
+            secrets: None,
+        }
+    }
+
+    /// Create a manager that persists refresh tokens through the secret vault
+    pub fn with_secrets(secrets: Arc<SecretManager>) -> Self {
+        Self {
+            secrets: Some(secrets),
+            ..Self::new()
         }
     }
 
@@ -113,7 +157,7 @@ impl CloudStorageManager {
         self.pending.insert(
             state.clone(),
             PendingAuth {
-                provider: config.provider,
+                config,
                 client,
                 pkce,
             },
@@ -143,13 +187,16 @@ impl CloudStorageManager {
                 None
             }
         };
-        let provider = pending.provider;
+        if let Some(refresh_token) = pending.client.refresh_token() {
+            self.store_credentials(&account_id, &pending.config, refresh_token.to_string());
+        }
+
         let client = Arc::new(Mutex::new(pending.client));
 
         self.accounts.insert(
             account_id.clone(),
             AccountEntry {
-                provider,
+                connection: CloudConnection::OAuth(pending.config),
                 label,
                 client,
             },
@@ -158,21 +205,122 @@ impl CloudStorageManager {
         Ok(account_id)
     }
 
+    /// Connect a WebDAV server, verifying the credentials before persisting them
+    pub async fn connect_webdav(
+        &self,
+        config: WebDavConfig,
+        password: String,
+        label: Option<String>,
+    ) -> Result<String> {
+        let mut client = WebDavClient::new(&config, password.clone())?;
+        client.check().await?;
+
+        let account_id = Uuid::new_v4().to_string();
+        let label = label
+            .filter(|label| !label.trim().is_empty())
+            .or_else(|| Some(client.account_label()));
+        if let Some(secrets) = &self.secrets {
+            let raw = serde_json::to_string(&StoredWebDavCredentials { password })
+                .map_err(|e| Error::Other(format!("Failed to serialize credentials: {}", e)))?;
+            secrets
+                .store_secret(&credentials_key(&account_id), &raw)
+                .map_err(|e| Error::Other(format!("Failed to persist credentials: {}", e)))?;
+        }
+
+        self.accounts.insert(
+            account_id.clone(),
+            AccountEntry {
+                connection: CloudConnection::WebDav(config),
+                label,
+                client: Arc::new(Mutex::new(CloudClient::WebDav(client))),
+            },
+        );
+
+        Ok(account_id)
+    }
+
+    /// Re-register a persisted account using credentials from the secret vault
+    ///
+    /// No network call is made here; the access token is refreshed lazily the
+    /// first time the account is used.
+    pub fn restore_account(
+        &self,
+        account_id: String,
+        connection: CloudConnection,
+        label: Option<String>,
+    ) -> Result<()> {
+        let secrets = self
+            .secrets
+            .as_ref()
+            .ok_or_else(|| Error::Other("Secret storage unavailable".to_string()))?;
+        let raw = secrets
+            .get_secret(&credentials_key(&account_id))
+            .map_err(|e| Error::Other(format!("Cloud credentials unavailable: {}", e)))?;
+
+        let (connection, client) = match connection {
+            CloudConnection::OAuth(mut config) => {
+                let credentials: StoredCredentials = serde_json::from_str(&raw)
+                    .map_err(|e| Error::Other(format!("Corrupt cloud credentials: {}", e)))?;
+                config.client_secret = credentials.client_secret;
+                let mut client = CloudClient::from_oauth_config(&config)?;
+                client.restore_refresh_token(credentials.refresh_token);
+                (CloudConnection::OAuth(config), client)
+            }
+            CloudConnection::WebDav(config) => {
+                let credentials: StoredWebDavCredentials = serde_json::from_str(&raw)
+                    .map_err(|e| Error::Other(format!("Corrupt cloud credentials: {}", e)))?;
+                let client = WebDavClient::new(&config, credentials.password)?;
+                (CloudConnection::WebDav(config), CloudClient::WebDav(client))
+            }
+        };
+
+        self.accounts.insert(
+            account_id,
+            AccountEntry {
+                connection,
+                label,
+                client: Arc::new(Mutex::new(client)),
+            },
+        );
+
+        Ok(())
+    }
+
     /// Disconnect an account and drop its credentials
     pub fn disconnect(&self, account_id: &str) -> Result<()> {
         self.accounts
             .remove(account_id)
             .ok_or_else(|| Error::Other("Account not found".to_string()))?;
+
+        if let Some(secrets) = &self.secrets {
+            if let Err(e) = secrets.delete_secret(&credentials_key(account_id)) {
+                tracing::warn!("Failed to remove cloud credentials: {}", e);
+            }
+        }
         Ok(())
     }
 
+    /// Connection settings (without secrets) and label for an account
+    pub fn account_connection(
+        &self,
+        account_id: &str,
+    ) -> Option<(CloudConnection, Option<String>)> {
+        self.accounts.get(account_id).map(|entry| {
+            let mut connection = entry.connection.clone();
+            if let CloudConnection::OAuth(config) = &mut connection {
+                config.client_secret = None;
+            }
+            (connection, entry.label.clone())
+        })
+    }
+
     /// List all connected accounts
     pub fn list_accounts(&self) -> Vec<CloudAccount> {
         self.accounts
             .iter()
             .map(|entry| CloudAccount {
                 account_id: entry.key().clone(),
-                provider: entry.value().provider,
+                provider: entry.value().connection.provider(),
                 label: entry.value().label.clone(),
             })
             .collect()
@@ -189,11 +337,61 @@ impl CloudStorageManager {
             .ok_or_else(|| Error::Other("Account not found".to_string()))?;
 
         let client = Arc::clone(&entry.client);
+        let connection = entry.connection.clone();
         drop(entry);
 
         let mut guard = client.lock().await;
-        f(&mut guard).await
+        let previous_refresh = guard.refresh_token().map(str::to_string);
+        let result = f(&mut guard).await;
+
+        // Providers may rotate refresh tokens; keep the vault in sync
+        if let (CloudConnection::OAuth(config), Some(current)) =
+            (&connection, guard.refresh_token())
+        {
+            if previous_refresh.as_deref() != Some(current) {
+                self.store_credentials(account_id, config, current.to_string());
+            }
+        }
+
+        result
+    }
+
+    /// Refresh the account's access token if needed, confirming its refresh
+    /// token (or WebDAV password) is still accepted
+    pub async fn check_account(&self, account_id: &str) -> Result<()> {
+        self.with_client(account_id, |client| {
+            Box::pin(async move { client.check().await })
+        })
+        .await
     }
+
+    fn store_credentials(
+        &self,
+        account_id: &str,
+        config: &CloudOAuthConfig,
+        refresh_token: String,
+    ) {
+        let Some(secrets) = &self.secrets else {
+            return;
+        };
+
+        let credentials = StoredCredentials {
+            client_secret: config.client_secret.clone(),
+            refresh_token: refresh_token.into(),
+        };
+        match serde_json::to_string(&credentials) {
+            Ok(raw) => {
+                if let Err(e) = secrets.store_secret(&credentials_key(account_id), &raw) {
+                    tracing::warn!("Failed to persist cloud credentials: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize cloud credentials: {}", e),
+        }
+    }
+}
+
+fn credentials_key(account_id: &str) -> String {
+    format!("cloud_account.{}", account_id)
 }
 
 /// Unified cloud client over provider-specific implementations
@@ -201,6 +399,7 @@ pub enum CloudClient {
     Google(GoogleDriveClient),
     Dropbox(DropboxClient),
     OneDrive(OneDriveClient),
+    WebDav(WebDavClient),
 }
 
 impl CloudClient {
@@ -227,6 +426,9 @@ impl CloudClient {
                 })?,
                 config.redirect_uri.clone(),
             )?)),
+            CloudProvider::WebDav => Err(Error::Other(
+                "WebDAV accounts connect with a server URL and password, not OAuth".to_string(),
+            )),
         }
     }
 
@@ -235,6 +437,8 @@ impl CloudClient {
             CloudClient::Google(client) => client.get_authorization_url(state),
             CloudClient::Dropbox(client) => client.get_authorization_url(state),
             CloudClient::OneDrive(client) => client.get_authorization_url(state),
+            // Never pending: `from_oauth_config` rejects WebDAV
+            CloudClient::WebDav(_) => (String::new(), None),
         }
     }
 
@@ -243,6 +447,36 @@ impl CloudClient {
             CloudClient::Google(client) => client.authorize_with_code(code, verifier).await,
             CloudClient::Dropbox(client) => client.authorize_with_code(code).await,
             CloudClient::OneDrive(client) => client.authorize_with_code(code, verifier).await,
+            CloudClient::WebDav(_) => {
+                Err(Error::Other("WebDAV accounts do not use OAuth".to_string()))
+            }
+        }
+    }
+
+    fn refresh_token(&self) -> Option<&str> {
+        match self {
+            CloudClient::Google(client) => client.refresh_token(),
+            CloudClient::Dropbox(client) => client.refresh_token(),
+            CloudClient::OneDrive(client) => client.refresh_token(),
+            CloudClient::WebDav(_) => None,
+        }
+    }
+
+    fn restore_refresh_token(&mut self, refresh_token: SecretString) {
+        match self {
+            CloudClient::Google(client) => client.restore_refresh_token(refresh_token),
+            CloudClient::Dropbox(client) => client.restore_refresh_token(refresh_token),
+            CloudClient::OneDrive(client) => client.restore_refresh_token(refresh_token),
+            CloudClient::WebDav(_) => {}
+        }
+    }
+
+    async fn check(&mut self) -> Result<()> {
+        match self {
+            CloudClient::Google(client) => client.ensure_token().await.map(|_| ()),
+            CloudClient::Dropbox(client) => client.ensure_token().await.map(|_| ()),
+            CloudClient::OneDrive(client) => client.ensure_token().await.map(|_| ()),
+            CloudClient::WebDav(client) => client.check().await,
         }
     }
 
@@ -251,6 +485,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.get_account_email().await,
             CloudClient::Dropbox(client) => client.get_account_name().await,
             CloudClient::OneDrive(client) => client.get_account_display_name().await,
+            CloudClient::WebDav(client) => Ok(Some(client.account_label())),
         }
     }
 
@@ -259,6 +494,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.list(options).await,
             CloudClient::Dropbox(client) => client.list(options).await,
             CloudClient::OneDrive(client) => client.list(options).await,
+            CloudClient::WebDav(client) => client.list(options).await,
         }
     }
 
@@ -267,6 +503,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.upload(local_path, remote_path).await,
             CloudClient::Dropbox(client) => client.upload(local_path, remote_path).await,
             CloudClient::OneDrive(client) => client.upload(local_path, remote_path).await,
+            CloudClient::WebDav(client) => client.upload(local_path, remote_path).await,
         }
     }
 
@@ -275,6 +512,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.download(remote_path, local_path).await,
             CloudClient::Dropbox(client) => client.download(remote_path, local_path).await,
             CloudClient::OneDrive(client) => client.download(remote_path, local_path).await,
+            CloudClient::WebDav(client) => client.download(remote_path, local_path).await,
         }
     }
 
@@ -283,6 +521,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.delete(remote_path).await,
             CloudClient::Dropbox(client) => client.delete(remote_path).await,
             CloudClient::OneDrive(client) => client.delete(remote_path).await,
+            CloudClient::WebDav(client) => client.delete(remote_path).await,
         }
     }
 
@@ -291,6 +530,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.create_folder(folder_path).await,
             CloudClient::Dropbox(client) => client.create_folder(folder_path).await,
             CloudClient::OneDrive(client) => client.create_folder(folder_path).await,
+            CloudClient::WebDav(client) => client.create_folder(folder_path).await,
         }
     }
 
@@ -299,6 +539,7 @@ impl CloudClient {
             CloudClient::Google(client) => client.share_link(remote_path, allow_edit).await,
             CloudClient::Dropbox(client) => client.share_link(remote_path, allow_edit).await,
             CloudClient::OneDrive(client) => client.share_link(remote_path, allow_edit).await,
+            CloudClient::WebDav(client) => client.share_link(remote_path, allow_edit).await,
         }
     }
 }
@@ -312,4 +553,71 @@ mod tests {
         let manager = CloudStorageManager::new();
         assert_eq!(manager.list_accounts().len(), 0);
     }
+
+    #[test]
+    fn test_restore_account_from_vault() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        let secrets = Arc::new(SecretManager::new(Arc::new(std::sync::Mutex::new(conn))));
+        let manager = CloudStorageManager::with_secrets(secrets);
+
+        let config = CloudOAuthConfig {
+            provider: CloudProvider::Dropbox,
+            client_id: "client".to_string(),
+            client_secret: Some("secret".into()),
+            redirect_uri: "http://localhost/callback".to_string(),
+        };
+        manager.store_credentials("acct-1", &config, "refresh".to_string());
+
+        let restored_config = CloudOAuthConfig {
+            client_secret: None,
+            ..config
+        };
+        manager
+            .restore_account(
+                "acct-1".to_string(),
+                CloudConnection::OAuth(restored_config.clone()),
+                Some("me@example.com".to_string()),
+            )
+            .unwrap();
+
+        let accounts = manager.list_accounts();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].provider, CloudProvider::Dropbox);
+
+        // Disconnecting removes the vault entry, so the account cannot be restored again
+        manager.disconnect("acct-1").unwrap();
+        assert!(manager
+            .restore_account(
+                "acct-1".to_string(),
+                CloudConnection::OAuth(restored_config),
+                None
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_restore_webdav_account() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        let secrets = Arc::new(SecretManager::new(Arc::new(std::sync::Mutex::new(conn))));
+        secrets
+            .store_secret(&credentials_key("dav-1"), r#"{"password":"hunter2"}"#)
+            .unwrap();
+        let manager = CloudStorageManager::with_secrets(secrets);
+
+        let config = WebDavConfig {
+            url: "https://cloud.example.com/remote.php/dav/files/alice".to_string(),
+            username: "alice".to_string(),
+            auth: WebDavAuth::Basic,
+            accept_invalid_certs: false,
+        };
+        manager
+            .restore_account("dav-1".to_string(), CloudConnection::WebDav(config), None)
+            .unwrap();
+
+        assert_eq!(manager.list_accounts()[0].provider, CloudProvider::WebDav);
+        let (connection, _) = manager.account_connection("dav-1").unwrap();
+        assert!(matches!(connection, CloudConnection::WebDav(ref c) if c.username == "alice"));
+    }
 }
@@ -0,0 +1,548 @@
+use crate::{
+    cloud::{CloudFile, ListOptions, ShareLink},
+    error::{Error, Result},
+};
+use bytes::Bytes;
+use digest_auth::{AuthContext, HttpMethod, WwwAuthenticateHeader};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, WWW_AUTHENTICATE};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use roxmltree::{Document as XmlDocument, Node};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+const DAV_NAMESPACE: &str = "DAV:";
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:displayname/>
+    <d:resourcetype/>
+    <d:getcontentlength/>
+    <d:getcontenttype/>
+    <d:getlastmodified/>
+  </d:prop>
+</d:propfind>"#;
+
+/// HTTP authentication scheme used by a WebDAV server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebDavAuth {
+    #[default]
+    Basic,
+    Digest,
+}
+
+/// Connection settings for a WebDAV server (Nextcloud, ownCloud, Synology, ...)
+///
+/// The password is not part of the settings; it lives in the secret vault.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// Root of the user's files, e.g. `https://cloud.example.com/remote.php/dav/files/alice/`
+    pub url: String,
+    pub username: String,
+    #[serde(default)]
+    pub auth: WebDavAuth,
+    /// Trust self-signed or otherwise invalid TLS certificates
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+}
+
+pub struct WebDavClient {
+    client: Client,
+    base_url: Url,
+    username: String,
+    password: String,
+    auth: WebDavAuth,
+    /// Last digest challenge; reused until the server issues a new one
+    digest: Option<WwwAuthenticateHeader>,
+}
+
+impl WebDavClient {
+    pub fn new(config: &WebDavConfig, password: String) -> Result<Self> {
+        let mut base_url = Url::parse(config.url.trim())
+            .map_err(|e| Error::Other(format!("Invalid WebDAV URL: {}", e)))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(Error::Other(
+                "WebDAV URL must use http or https".to_string(),
+            ));
+        }
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .danger_accept_invalid_certs(config.accept_invalid_certs)
+            .build()
+            .map_err(|e| {
+                Error::Other(format!("Failed to construct HTTP client for WebDAV: {}", e))
+            })?;
+
+        Ok(Self {
+            client,
+            base_url,
+            username: config.username.clone(),
+            password,
+            auth: config.auth,
+            digest: None,
+        })
+    }
+
+    pub fn account_label(&self) -> String {
+        match self.base_url.host_str() {
+            Some(host) => format!("{}@{}", self.username, host),
+            None => self.username.clone(),
+        }
+    }
+
+    /// Confirm the server is reachable and accepts the credentials
+    pub async fn check(&mut self) -> Result<()> {
+        let url = self.base_url.clone();
+        let response = self
+            .send(
+                propfind(),
+                &url,
+                Some("0"),
+                Some(Bytes::from_static(PROPFIND_BODY.as_bytes())),
+            )
+            .await?;
+        ensure_success(response, "check").await.map(|_| ())
+    }
+
+    /// List a folder; `search` filters the folder's entries by name
+    pub async fn list(&mut self, options: ListOptions) -> Result<Vec<CloudFile>> {
+        let folder = normalize_path(options.folder_path.as_deref().unwrap_or("/"));
+        let url = self.resolve(&folder, true);
+
+        let response = self
+            .send(
+                propfind(),
+                &url,
+                Some("1"),
+                Some(Bytes::from_static(PROPFIND_BODY.as_bytes())),
+            )
+            .await?;
+        let response = ensure_success(response, "list").await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| Error::Other(format!("Failed to read WebDAV listing: {}", e)))?;
+
+        let search = options.search.as_deref().map(str::to_lowercase);
+        let files = parse_multistatus(&body, &self.base_url)?
+            .into_iter()
+            .filter(|file| file.path != folder)
+            .filter(|file| options.include_folders || !file.is_folder)
+            .filter(|file| match &search {
+                Some(term) => file.name.to_lowercase().contains(term.as_str()),
+                None => true,
+            })
+            .collect();
+
+        Ok(files)
+    }
+
+    pub async fn upload(&mut self, local_path: &str, remote_path: &str) -> Result<String> {
+        let path = normalize_path(remote_path);
+        let data = Bytes::from(
+            tokio::fs::read(local_path)
+                .await
+                .map_err(|e| Error::Other(format!("Failed to read {}: {}", local_path, e)))?,
+        );
+        let url = self.resolve(&path, false);
+
+        let mut response = self
+            .send(Method::PUT, &url, None, Some(data.clone()))
+            .await?;
+        // 409 means a parent collection is missing
+        if response.status() == StatusCode::CONFLICT {
+            self.create_parents(&path).await?;
+            response = self.send(Method::PUT, &url, None, Some(data)).await?;
+        }
+        ensure_success(response, "upload").await?;
+
+        Ok(path)
+    }
+
+    pub async fn download(&mut self, remote_path: &str, local_path: &str) -> Result<()> {
+        let url = self.resolve(&normalize_path(remote_path), false);
+        let response = self.send(Method::GET, &url, None, None).await?;
+        let response = ensure_success(response, "download").await?;
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::Other(format!("Failed to read WebDAV download: {}", e)))?;
+        tokio::fs::write(local_path, bytes)
+            .await
+            .map_err(|e| Error::Other(format!("Failed to write {}: {}", local_path, e)))?;
+        Ok(())
+    }
+
+    pub async fn delete(&mut self, remote_path: &str) -> Result<()> {
+        let path = normalize_path(remote_path);
+        if path == "/" {
+            return Err(Error::Other(
+                "Refusing to delete the WebDAV root folder".to_string(),
+            ));
+        }
+        let url = self.resolve(&path, false);
+        let response = self.send(Method::DELETE, &url, None, None).await?;
+        ensure_success(response, "delete").await.map(|_| ())
+    }
+
+    pub async fn create_folder(&mut self, folder_path: &str) -> Result<String> {
+        let path = normalize_path(folder_path);
+        let url = self.resolve(&path, true);
+
+        let mut response = self.send(mkcol(), &url, None, None).await?;
+        if response.status() == StatusCode::CONFLICT {
+            self.create_parents(&path).await?;
+            response = self.send(mkcol(), &url, None, None).await?;
+        }
+        ensure_success(response, "create folder").await?;
+
+        Ok(path)
+    }
+
+    pub async fn share_link(&mut self, _remote_path: &str, _allow_edit: bool) -> Result<ShareLink> {
+        Err(Error::Other(
+            "Share links are not part of the WebDAV protocol".to_string(),
+        ))
+    }
+
+    /// Create every missing ancestor collection of `path`
+    async fn create_parents(&mut self, path: &str) -> Result<()> {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut current = String::new();
+        for segment in segments.iter().take(segments.len().saturating_sub(1)) {
+            current.push('/');
+            current.push_str(segment);
+            let url = self.resolve(&current, true);
+            let response = self.send(mkcol(), &url, None, None).await?;
+            // 405 Method Not Allowed: the collection already exists
+            if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+                ensure_success(response, "create folder").await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Absolute URL for a normalized remote path
+    fn resolve(&self, path: &str, collection: bool) -> Url {
+        let mut url = self.base_url.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments
+                .pop_if_empty()
+                .extend(path.split('/').filter(|s| !s.is_empty()));
+            if collection {
+                segments.push("");
+            }
+        }
+        url
+    }
+
+    /// Send a request, answering a digest challenge once if the server asks for one
+    async fn send(
+        &mut self,
+        method: Method,
+        url: &Url,
+        depth: Option<&str>,
+        body: Option<Bytes>,
+    ) -> Result<Response> {
+        let mut challenged = false;
+        loop {
+            let mut request = self.client.request(method.clone(), url.clone());
+            if let Some(depth) = depth {
+                request = request
+                    .header("Depth", depth)
+                    .header(CONTENT_TYPE, "application/xml; charset=utf-8");
+            }
+            if let Some(body) = &body {
+                request = request.body(body.clone());
+            }
+            let request = self.authorize(request, &method, url)?;
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| Error::Other(format!("WebDAV {} request failed: {}", method, e)))?;
+
+            if response.status() == StatusCode::UNAUTHORIZED
+                && self.auth == WebDavAuth::Digest
+                && !challenged
+            {
+                let challenge = response
+                    .headers()
+                    .get_all(WWW_AUTHENTICATE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .find(|value| {
+                        value
+                            .trim_start()
+                            .to_ascii_lowercase()
+                            .starts_with("digest")
+                    });
+                if let Some(challenge) = challenge {
+                    self.digest = Some(digest_auth::parse(challenge).map_err(|e| {
+                        Error::Other(format!("Invalid WebDAV digest challenge: {}", e))
+                    })?);
+                    challenged = true;
+                    continue;
+                }
+            }
+
+            return Ok(response);
+        }
+    }
+
+    fn authorize(
+        &mut self,
+        request: RequestBuilder,
+        method: &Method,
+        url: &Url,
+    ) -> Result<RequestBuilder> {
+        match self.auth {
+            WebDavAuth::Basic => Ok(request.basic_auth(&self.username, Some(&self.password))),
+            WebDavAuth::Digest => {
+                let Some(challenge) = self.digest.as_mut() else {
+                    return Ok(request);
+                };
+                let context = AuthContext::new_with_method(
+                    self.username.as_str(),
+                    self.password.as_str(),
+                    &url[url::Position::BeforePath..],
+                    None::<&[u8]>,
+                    HttpMethod::from(method.as_str()),
+                );
+                let header = challenge
+                    .respond(&context)
+                    .map_err(|e| Error::Other(format!("WebDAV digest auth failed: {}", e)))?;
+                Ok(request.header(AUTHORIZATION, header.to_header_string()))
+            }
+        }
+    }
+}
+
+fn propfind() -> Method {
+    Method::from_bytes(b"PROPFIND").expect("PROPFIND is a valid method")
+}
+
+fn mkcol() -> Method {
+    Method::from_bytes(b"MKCOL").expect("MKCOL is a valid method")
+}
+
+async fn ensure_success(response: Response, action: &str) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(Error::Other(format!(
+            "WebDAV {} failed: server rejected the credentials",
+            action
+        )));
+    }
+
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unable to read response body".to_string());
+    Err(Error::Other(format!(
+        "WebDAV {} failed: {} - {}",
+        action, status, body
+    )))
+}
+
+/// Remote paths are absolute, `/`-separated and relative to the configured URL
+fn normalize_path(path: &str) -> String {
+    let segments: Vec<&str> = path
+        .trim()
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+/// Map an `href` from a multistatus response back to a path under `base_url`
+fn href_to_path(href: &str, base_url: &Url) -> Option<String> {
+    let url = base_url.join(href.trim()).ok()?;
+    let decoded = urlencoding::decode(url.path()).ok()?;
+    let base = urlencoding::decode(base_url.path()).ok()?;
+    let relative = decoded
+        .strip_prefix(base.trim_end_matches('/'))
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))?;
+    Some(normalize_path(relative))
+}
+
+fn is_dav(node: &Node<'_, '_>, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node.tag_name().namespace() == Some(DAV_NAMESPACE)
+}
+
+fn dav_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is_dav(child, name))
+}
+
+fn parse_multistatus(xml: &str, base_url: &Url) -> Result<Vec<CloudFile>> {
+    let doc = XmlDocument::parse(xml)
+        .map_err(|e| Error::Other(format!("Invalid WebDAV response: {}", e)))?;
+
+    let mut files = Vec::new();
+    for response in doc.descendants().filter(|node| is_dav(node, "response")) {
+        let Some(path) = dav_child(response, "href")
+            .and_then(|href| href.text())
+            .and_then(|href| href_to_path(href, base_url))
+        else {
+            continue;
+        };
+
+        // Only the propstat carrying the found (200) properties is of interest
+        let prop = response
+            .children()
+            .filter(|node| is_dav(node, "propstat"))
+            .find(|propstat| {
+                dav_child(*propstat, "status")
+                    .and_then(|status| status.text())
+                    .map(|status| status.contains(" 200"))
+                    .unwrap_or(true)
+            })
+            .and_then(|propstat| dav_child(propstat, "prop"));
+        let text = |name: &str| {
+            prop.and_then(|prop| dav_child(prop, name))
+                .and_then(|node| node.text())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        let is_folder = prop
+            .and_then(|prop| dav_child(prop, "resourcetype"))
+            .map(|resourcetype| dav_child(resourcetype, "collection").is_some())
+            .unwrap_or(false);
+        let name = text("displayname")
+            .map(str::to_string)
+            .or_else(|| path.rsplit('/').next().map(str::to_string))
+            .unwrap_or_default();
+
+        files.push(CloudFile {
+            id: path.clone(),
+            name,
+            mime_type: text("getcontenttype").map(str::to_string),
+            size: if is_folder {
+                None
+            } else {
+                text("getcontentlength").and_then(|size| size.parse().ok())
+            },
+            modified_at: text("getlastmodified").map(|value| {
+                chrono::DateTime::parse_from_rfc2822(value)
+                    .map(|date| date.to_rfc3339())
+                    .unwrap_or_else(|_| value.to_string())
+            }),
+            path,
+            is_folder,
+            share_link: None,
+        });
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTISTATUS: &str = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+  <d:response>
+    <d:href>/remote.php/dav/files/alice/Docs/</d:href>
+    <d:propstat>
+      <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/alice/Docs/Q3%20report.pdf</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:resourcetype/>
+        <d:getcontentlength>2048</d:getcontentlength>
+        <d:getcontenttype>application/pdf</d:getcontenttype>
+        <d:getlastmodified>Tue, 01 Oct 2024 09:30:00 GMT</d:getlastmodified>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop><d:displayname/></d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>https://cloud.example.com/remote.php/dav/files/alice/Docs/Archive/</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:displayname>Archive</d:displayname>
+        <d:resourcetype><d:collection/></d:resourcetype>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+    fn base() -> Url {
+        Url::parse("https://cloud.example.com/remote.php/dav/files/alice/").unwrap()
+    }
+
+    #[test]
+    fn test_parse_multistatus() {
+        let files = parse_multistatus(MULTISTATUS, &base()).unwrap();
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].path, "/Docs");
+        assert!(files[0].is_folder);
+
+        let report = &files[1];
+        assert_eq!(report.path, "/Docs/Q3 report.pdf");
+        assert_eq!(report.name, "Q3 report.pdf");
+        assert_eq!(report.size, Some(2048));
+        assert_eq!(report.mime_type.as_deref(), Some("application/pdf"));
+        assert_eq!(
+            report.modified_at.as_deref(),
+            Some("2024-10-01T09:30:00+00:00")
+        );
+        assert!(!report.is_folder);
+
+        assert_eq!(files[2].path, "/Docs/Archive");
+        assert_eq!(files[2].name, "Archive");
+        assert!(files[2].is_folder);
+    }
+
+    #[test]
+    fn test_resolve_encodes_segments() {
+        let config = WebDavConfig {
+            url: "https://nas.local:5006/home".to_string(),
+            username: "bob".to_string(),
+            auth: WebDavAuth::Digest,
+            accept_invalid_certs: true,
+        };
+        let client = WebDavClient::new(&config, "secret".to_string()).unwrap();
+
+        assert_eq!(client.account_label(), "bob@nas.local");
+        assert_eq!(
+            client.resolve("/Photos/a b#1.jpg", false).as_str(),
+            "https://nas.local:5006/home/Photos/a%20b%231.jpg"
+        );
+        assert_eq!(
+            client.resolve("/", true).as_str(),
+            "https://nas.local:5006/home/"
+        );
+        assert_eq!(
+            href_to_path("/home/Photos/a%20b%231.jpg", &client.base_url).as_deref(),
+            Some("/Photos/a b#1.jpg")
+        );
+        assert_eq!(href_to_path("/other/file.txt", &client.base_url), None);
+        assert_eq!(href_to_path("/homework/file.txt", &client.base_url), None);
+        assert_eq!(normalize_path("Photos//2024/"), "/Photos/2024");
+    }
+}
@@ -0,0 +1,976 @@
+//! Incremental file dependency graph for a workspace.
+//!
+//! The graph is built when the workspace is indexed and then patched one file
+//! at a time from file watcher events, so reverse-dependency, cycle and
+//! impact queries stay current without walking the tree again. Imports are
+//! resolved against the files already in the graph: relative imports for
+//! TypeScript/JavaScript, `mod`/`use crate::`/`use super::` for Rust and
+//! package imports for Python. Anything that does not resolve to a workspace
+//! file (packages, std) is kept as a raw import only.
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use crate::filesystem::paths::strip_extended;
+use crate::filesystem::watcher::FileEvent;
+
+/// Directories never worth indexing
+const IGNORED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+const TS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+/// Share of the workspace depending on a file that makes changing it risky
+const HIGH_RISK_SHARE: f64 = 0.2;
+const HIGH_RISK_DEPENDENTS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceLanguage {
+    Rust,
+    TypeScript,
+    Python,
+}
+
+impl SourceLanguage {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(SourceLanguage::Rust),
+            "py" => Some(SourceLanguage::Python),
+            ext if TS_EXTENSIONS.contains(&ext) => Some(SourceLanguage::TypeScript),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileNode {
+    pub path: PathBuf,
+    pub language: SourceLanguage,
+    /// Import specifiers as written
+    pub imports: Vec<String>,
+    pub exports: Vec<String>,
+    /// Workspace files the imports resolve to
+    pub dependencies: BTreeSet<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImpactRisk {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactedFile {
+    pub path: PathBuf,
+    /// 1 for direct dependents, 2 for their dependents, ...
+    pub depth: usize,
+}
+
+/// What a change to one file can break
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactAnalysis {
+    pub path: PathBuf,
+    pub indexed: bool,
+    pub exports: Vec<String>,
+    pub dependencies: Vec<PathBuf>,
+    pub direct_dependents: Vec<PathBuf>,
+    /// Every file that depends on it directly or indirectly, nearest first
+    pub transitive_dependents: Vec<ImpactedFile>,
+    /// Import cycles the file is part of
+    pub cycles: Vec<Vec<PathBuf>>,
+    pub risk: ImpactRisk,
+}
+
+pub struct ImportGraph {
+    root: PathBuf,
+    files: HashMap<PathBuf, FileNode>,
+    dependents: HashMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl ImportGraph {
+    /// Parse every supported file under `root`
+    pub fn build(root: &Path) -> Self {
+        let mut graph = Self {
+            root: normalize(root),
+            files: HashMap::new(),
+            dependents: HashMap::new(),
+        };
+
+        let mut sources = Vec::new();
+        collect_sources(&graph.root, &mut sources);
+        for path in sources {
+            if let Some(node) = parse_file(&path) {
+                graph.files.insert(path, node);
+            }
+        }
+
+        let paths: Vec<PathBuf> = graph.files.keys().cloned().collect();
+        for path in paths {
+            graph.link(&path);
+        }
+        graph
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &FileNode> {
+        self.files.values()
+    }
+
+    pub fn file(&self, path: &Path) -> Option<&FileNode> {
+        self.files.get(&normalize(path))
+    }
+
+    /// Apply a watcher event; returns whether the graph changed
+    pub fn apply_event(&mut self, event: &FileEvent) -> bool {
+        match event {
+            FileEvent::Created(paths) | FileEvent::Modified(paths) => paths
+                .iter()
+                .fold(false, |changed, path| self.update_file(path) || changed),
+            FileEvent::Deleted(paths) => paths
+                .iter()
+                .fold(false, |changed, path| self.remove_path(path) || changed),
+            FileEvent::Renamed { from, to } => {
+                let removed = self.remove_path(from);
+                self.update_file(to) || removed
+            }
+        }
+    }
+
+    /// Re-parse one file, or drop it if it no longer exists
+    pub fn update_file(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        if !self.tracks(&path) {
+            return false;
+        }
+        if !path.is_file() {
+            return self.remove_path(&path);
+        }
+        let Some(node) = parse_file(&path) else {
+            return false;
+        };
+
+        let is_new = !self.files.contains_key(&path);
+        self.unlink(&path);
+        self.files.insert(path.clone(), node);
+        self.link(&path);
+
+        // A new file can satisfy imports that resolved to nothing before
+        if is_new {
+            self.relink_all();
+        }
+        true
+    }
+
+    /// Drop a file, or every file under a removed directory
+    pub fn remove_path(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|file| file.starts_with(&path))
+            .cloned()
+            .collect();
+        if removed.is_empty() {
+            return false;
+        }
+
+        let mut affected = BTreeSet::new();
+        for file in &removed {
+            self.unlink(file);
+            self.files.remove(file);
+            if let Some(dependents) = self.dependents.remove(file) {
+                affected.extend(dependents);
+            }
+        }
+
+        // Importers may now resolve to another candidate, e.g. `index.ts`
+        for file in affected {
+            if self.files.contains_key(&file) {
+                self.unlink(&file);
+                self.link(&file);
+            }
+        }
+        true
+    }
+
+    pub fn dependencies(&self, path: &Path) -> Vec<PathBuf> {
+        self.files
+            .get(&normalize(path))
+            .map(|node| node.dependencies.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Files importing `path`, with their depth when `transitive`
+    pub fn dependents(&self, path: &Path, transitive: bool) -> Vec<ImpactedFile> {
+        let start = normalize(path);
+        let mut seen = BTreeSet::from([start.clone()]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut found = Vec::new();
+
+        while let Some((current, depth)) = queue.pop_front() {
+            if depth > 0 && !transitive {
+                break;
+            }
+            for dependent in self.dependents.get(&current).into_iter().flatten() {
+                if seen.insert(dependent.clone()) {
+                    found.push(ImpactedFile {
+                        path: dependent.clone(),
+                        depth: depth + 1,
+                    });
+                    queue.push_back((dependent.clone(), depth + 1));
+                }
+            }
+        }
+        found
+    }
+
+    /// Import cycles, one strongly connected component each
+    pub fn cycles(&self) -> Vec<Vec<PathBuf>> {
+        let mut cycles: Vec<Vec<PathBuf>> = strongly_connected(&self.files)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self.files[&component[0]]
+                        .dependencies
+                        .contains(&component[0])
+            })
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    pub fn impact(&self, path: &Path) -> ImpactAnalysis {
+        let path = normalize(path);
+        let node = self.files.get(&path);
+        let transitive = self.dependents(&path, true);
+        let cycles: Vec<Vec<PathBuf>> = self
+            .cycles()
+            .into_iter()
+            .filter(|cycle| cycle.contains(&path))
+            .collect();
+
+        let share = transitive.len() as f64 / self.files.len().max(1) as f64;
+        let risk = if transitive.len() > HIGH_RISK_DEPENDENTS || share >= HIGH_RISK_SHARE {
+            ImpactRisk::High
+        } else if transitive.len() > 2 || !cycles.is_empty() {
+            ImpactRisk::Medium
+        } else {
+            ImpactRisk::Low
+        };
+
+        ImpactAnalysis {
+            indexed: node.is_some(),
+            exports: node.map(|node| node.exports.clone()).unwrap_or_default(),
+            dependencies: self.dependencies(&path),
+            direct_dependents: transitive
+                .iter()
+                .filter(|file| file.depth == 1)
+                .map(|file| file.path.clone())
+                .collect(),
+            transitive_dependents: transitive,
+            cycles,
+            risk,
+            path,
+        }
+    }
+
+    fn tracks(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        SourceLanguage::from_path(path).is_some()
+            && !relative.components().any(|component| match component {
+                Component::Normal(name) => is_ignored(&name.to_string_lossy()),
+                _ => false,
+            })
+    }
+
+    fn link(&mut self, path: &Path) {
+        let Some(node) = self.files.get(path) else {
+            return;
+        };
+        let dependencies: BTreeSet<PathBuf> = node
+            .imports
+            .iter()
+            .filter_map(|import| self.resolve(path, node.language, import))
+            .collect();
+
+        for dependency in &dependencies {
+            self.dependents
+                .entry(dependency.clone())
+                .or_default()
+                .insert(path.to_path_buf());
+        }
+        if let Some(node) = self.files.get_mut(path) {
+            node.dependencies = dependencies;
+        }
+    }
+
+    fn unlink(&mut self, path: &Path) {
+        let Some(node) = self.files.get(path) else {
+            return;
+        };
+        for dependency in &node.dependencies {
+            if let Some(dependents) = self.dependents.get_mut(dependency) {
+                dependents.remove(path);
+                if dependents.is_empty() {
+                    self.dependents.remove(dependency);
+                }
+            }
+        }
+    }
+
+    fn relink_all(&mut self) {
+        self.dependents.clear();
+        let paths: Vec<PathBuf> = self.files.keys().cloned().collect();
+        for path in paths {
+            self.link(&path);
+        }
+    }
+
+    fn resolve(&self, from: &Path, language: SourceLanguage, import: &str) -> Option<PathBuf> {
+        let candidates = match language {
+            SourceLanguage::TypeScript => ts_candidates(from, import),
+            SourceLanguage::Rust => self.rust_candidates(from, import),
+            SourceLanguage::Python => python_candidates(&self.root, from, import),
+        };
+        candidates
+            .into_iter()
+            .find(|candidate| candidate != from && self.files.contains_key(candidate))
+    }
+
+    fn rust_candidates(&self, from: &Path, import: &str) -> Vec<PathBuf> {
+        let own_dir = rust_module_dir(from);
+
+        if let Some(name) = import.strip_prefix("mod ") {
+            return rust_module_files(&own_dir, &[name]);
+        }
+
+        let segments: Vec<&str> = import.split("::").collect();
+        let (base, rest) = match segments.first().copied() {
+            Some("crate") => {
+                let Some(crate_root) = from.ancestors().skip(1).find(|dir| {
+                    self.files.contains_key(&dir.join("lib.rs"))
+                        || self.files.contains_key(&dir.join("main.rs"))
+                }) else {
+                    return Vec::new();
+                };
+                (crate_root.to_path_buf(), &segments[1..])
+            }
+            Some("self") => (own_dir.clone(), &segments[1..]),
+            Some("super") => {
+                let mut dir = own_dir.clone();
+                let mut rest = &segments[..];
+                while rest.first() == Some(&"super") {
+                    match dir.parent() {
+                        Some(parent) => dir = parent.to_path_buf(),
+                        None => return Vec::new(),
+                    }
+                    rest = &rest[1..];
+                }
+                (dir, rest)
+            }
+            _ => return Vec::new(),
+        };
+
+        // Longest module path first; the tail may name items, not modules.
+        // Items of the parent or crate root are left unresolved since every
+        // child module would otherwise form a cycle with its parent
+        (1..=rest.len())
+            .rev()
+            .flat_map(|len| rust_module_files(&base, &rest[..len]))
+            .collect()
+    }
+}
+
+/// Shared handle to the workspace's graph, built by `workspace_index`
+#[derive(Default)]
+pub struct DependencyGraphState(pub Arc<RwLock<Option<ImportGraph>>>);
+
+impl DependencyGraphState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Keep the workspace graph in step with a file watcher event
+pub fn apply_file_event(app: &AppHandle, event: &FileEvent) {
+    if let Some(state) = app.try_state::<DependencyGraphState>() {
+        if let Some(graph) = state.0.write().as_mut() {
+            graph.apply_event(event);
+        }
+    }
+}
+
+fn collect_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_ignored(&name) {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => collect_sources(&path, sources),
+            Ok(kind) if kind.is_file() && SourceLanguage::from_path(&path).is_some() => {
+                sources.push(path)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_ignored(name: &str) -> bool {
+    name.starts_with('.') || IGNORED_DIRS.contains(&name)
+}
+
+fn parse_file(path: &Path) -> Option<FileNode> {
+    let language = SourceLanguage::from_path(path)?;
+    let content = std::fs::read_to_string(path).ok()?;
+    let (imports, exports) = match language {
+        SourceLanguage::Rust => (rust_imports(&content), rust_exports(&content)),
+        SourceLanguage::TypeScript => (ts_imports(&content), ts_exports(&content)),
+        SourceLanguage::Python => (python_imports(&content), python_exports(&content)),
+    };
+    Some(FileNode {
+        path: path.to_path_buf(),
+        language,
+        imports,
+        exports,
+        dependencies: BTreeSet::new(),
+    })
+}
+
+fn ts_imports(content: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    for line in content.lines().map(str::trim) {
+        let is_import =
+            line.starts_with("import ") || (line.starts_with("export ") && line.contains(" from "));
+        let specifier = if is_import {
+            line.rsplit(" from ")
+                .next()
+                .filter(|_| line.contains(" from "))
+                .or_else(|| line.strip_prefix("import "))
+                .and_then(quoted)
+        } else {
+            // `require('./x')` and `import('./x')`
+            ["require(", "import("].iter().find_map(|call| {
+                line.find(call)
+                    .and_then(|start| quoted(&line[start + call.len()..]))
+            })
+        };
+        if let Some(specifier) = specifier {
+            imports.push(specifier);
+        }
+    }
+    imports
+}
+
+fn ts_exports(content: &str) -> Vec<String> {
+    let mut exports = Vec::new();
+    for line in content.lines() {
+        let Some(rest) = line.strip_prefix("export ") else {
+            continue;
+        };
+        if rest.starts_with("default") {
+            exports.push("default".to_string());
+        } else if rest.starts_with('*') {
+            exports.push("*".to_string());
+        } else if let Some(list) = rest.strip_prefix('{') {
+            let list = list.split('}').next().unwrap_or_default();
+            exports.extend(
+                list.split(',')
+                    .filter_map(|item| item.split(" as ").last())
+                    .map(|name| name.trim().trim_start_matches("type ").to_string())
+                    .filter(|name| !name.is_empty()),
+            );
+        } else {
+            let words: Vec<&str> = rest
+                .split(|c: char| c.is_whitespace() || c == '(' || c == '<' || c == '=' || c == ':')
+                .filter(|word| !word.is_empty())
+                .collect();
+            if let Some(name) = words.iter().position(|word| {
+                matches!(
+                    *word,
+                    "function"
+                        | "function*"
+                        | "class"
+                        | "const"
+                        | "let"
+                        | "var"
+                        | "interface"
+                        | "type"
+                        | "enum"
+                        | "namespace"
+                )
+            }) {
+                if let Some(name) = words.get(name + 1) {
+                    exports.push(name.trim_start_matches('*').to_string());
+                }
+            }
+        }
+    }
+    exports
+}
+
+/// `mod x;` declarations plus `use` paths, with one level of `{}` groups expanded
+fn rust_imports(content: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut statement = String::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if statement.is_empty() {
+            let declaration = trimmed
+                .strip_prefix("pub ")
+                .or_else(|| trimmed.strip_prefix("pub(crate) "))
+                .unwrap_or(trimmed);
+            if let Some(name) = declaration
+                .strip_prefix("mod ")
+                .and_then(|rest| rest.strip_suffix(';'))
+            {
+                imports.push(format!("mod {}", name.trim()));
+                continue;
+            }
+            match declaration.strip_prefix("use ") {
+                Some(rest) => statement.push_str(rest),
+                None => continue,
+            }
+        } else {
+            statement.push_str(trimmed);
+        }
+
+        if let Some(end) = statement.find(';') {
+            imports.extend(expand_use(&statement[..end]));
+            statement.clear();
+        }
+    }
+    imports
+}
+
+fn expand_use(path: &str) -> Vec<String> {
+    let path: String = path.chars().filter(|c| !c.is_whitespace()).collect();
+    match path.split_once('{') {
+        Some((prefix, group)) => {
+            let group = group.strip_suffix('}').unwrap_or(group);
+            let mut depth = 0;
+            let mut items = vec![String::new()];
+            for c in group.chars() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    ',' if depth == 0 => {
+                        items.push(String::new());
+                        continue;
+                    }
+                    _ => {}
+                }
+                if let Some(item) = items.last_mut() {
+                    item.push(c);
+                }
+            }
+            items
+                .into_iter()
+                .filter(|item| !item.is_empty())
+                .map(|item| {
+                    let item = item.split('{').next().unwrap_or_default();
+                    let item = item.trim_end_matches("::");
+                    if item == "self" {
+                        prefix.trim_end_matches("::").to_string()
+                    } else {
+                        format!("{}{}", prefix, item)
+                    }
+                })
+                .collect()
+        }
+        None => vec![path.split(" as ").next().unwrap_or(&path).to_string()],
+    }
+}
+
+fn rust_exports(content: &str) -> Vec<String> {
+    let mut exports = Vec::new();
+    for line in content.lines() {
+        let Some(rest) = line.strip_prefix("pub ") else {
+            continue;
+        };
+        let words: Vec<&str> = rest
+            .split(|c: char| c.is_whitespace() || c == '(' || c == '<' || c == ':' || c == ';')
+            .filter(|word| !word.is_empty())
+            .collect();
+        if let Some(position) = words.iter().position(|word| {
+            matches!(
+                *word,
+                "fn" | "struct" | "enum" | "trait" | "type" | "const" | "static" | "mod"
+            )
+        }) {
+            if let Some(name) = words.get(position + 1) {
+                exports.push(name.to_string());
+            }
+        }
+    }
+    exports
+}
+
+fn python_imports(content: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    for line in content.lines() {
+        if let Some(rest) = line.strip_prefix("from ") {
+            let Some((module, names)) = rest.split_once(" import ") else {
+                continue;
+            };
+            let module = module.trim();
+            // `from . import x` pulls in sibling modules
+            if module.chars().all(|c| c == '.') {
+                imports.extend(
+                    names
+                        .trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+                        .split(',')
+                        .filter_map(|name| name.split_whitespace().next())
+                        .map(|name| format!("{}{}", module, name)),
+                );
+            } else {
+                imports.push(module.to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            imports.extend(
+                rest.split(',')
+                    .filter_map(|module| module.split_whitespace().next())
+                    .map(str::to_string),
+            );
+        }
+    }
+    imports
+}
+
+fn python_exports(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let rest = line
+                .strip_prefix("def ")
+                .or_else(|| line.strip_prefix("async def "))
+                .or_else(|| line.strip_prefix("class "))?;
+            let name = rest.split(|c: char| c == '(' || c == ':').next()?.trim();
+            (!name.starts_with('_')).then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn ts_candidates(from: &Path, import: &str) -> Vec<PathBuf> {
+    if !import.starts_with('.') {
+        return Vec::new();
+    }
+    let Some(dir) = from.parent() else {
+        return Vec::new();
+    };
+    let base = lexical_join(dir, import);
+
+    let mut candidates = vec![base.clone()];
+    // ESM TypeScript imports name the compiled `.js` file
+    if let Some(stem) = import
+        .strip_suffix(".js")
+        .or_else(|| import.strip_suffix(".jsx"))
+    {
+        let stem = lexical_join(dir, stem);
+        candidates.push(stem.with_extension("ts"));
+        candidates.push(stem.with_extension("tsx"));
+    }
+    for ext in TS_EXTENSIONS {
+        candidates.push(append_extension(&base, ext));
+    }
+    for ext in TS_EXTENSIONS {
+        candidates.push(base.join(format!("index.{}", ext)));
+    }
+    candidates
+}
+
+fn python_candidates(root: &Path, from: &Path, import: &str) -> Vec<PathBuf> {
+    let dots = import.chars().take_while(|c| *c == '.').count();
+    let module = &import[dots..];
+
+    let bases: Vec<PathBuf> = if dots > 0 {
+        let mut dir = from.parent().map(Path::to_path_buf);
+        for _ in 1..dots {
+            dir = dir.and_then(|dir| dir.parent().map(Path::to_path_buf));
+        }
+        dir.into_iter().collect()
+    } else {
+        // Absolute imports are relative to the workspace or the importer
+        let mut bases = vec![root.to_path_buf()];
+        bases.extend(from.parent().map(Path::to_path_buf));
+        bases
+    };
+
+    let segments: Vec<&str> = module.split('.').filter(|s| !s.is_empty()).collect();
+    let mut candidates = Vec::new();
+    for base in bases {
+        let mut path = base.clone();
+        for segment in &segments {
+            path.push(segment);
+        }
+        candidates.push(path.with_extension("py"));
+        candidates.push(path.join("__init__.py"));
+    }
+    candidates
+}
+
+/// Directory holding a Rust file's child modules
+fn rust_module_dir(file: &Path) -> PathBuf {
+    let parent = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    match file.file_name().and_then(|name| name.to_str()) {
+        Some("mod.rs" | "lib.rs" | "main.rs") => parent,
+        _ => match file.file_stem() {
+            Some(stem) => parent.join(stem),
+            None => parent,
+        },
+    }
+}
+
+fn rust_module_files(dir: &Path, segments: &[&str]) -> Vec<PathBuf> {
+    let mut path = dir.to_path_buf();
+    for segment in segments {
+        path.push(segment.trim_start_matches("r#"));
+    }
+    vec![path.with_extension("rs"), path.join("mod.rs")]
+}
+
+fn quoted(text: &str) -> Option<String> {
+    let start = text.find(['\'', '"', '`'])?;
+    let quote = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    let end = rest.find(quote)?;
+    Some(rest[..end].to_string())
+}
+
+fn append_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut text = path.as_os_str().to_os_string();
+    text.push(".");
+    text.push(ext);
+    PathBuf::from(text)
+}
+
+fn lexical_join(dir: &Path, relative: &str) -> PathBuf {
+    let mut joined = dir.to_path_buf();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::ParentDir => {
+                joined.pop();
+            }
+            Component::Normal(name) => joined.push(name),
+            _ => {}
+        }
+    }
+    joined
+}
+
+/// Canonical form used as the graph key; deleted files keep their canonical
+/// parent so they still match
+fn normalize(path: &Path) -> PathBuf {
+    if let Ok(canonical) = std::fs::canonicalize(path) {
+        return strip_extended(&canonical);
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => match std::fs::canonicalize(parent) {
+            Ok(parent) => strip_extended(&parent).join(name),
+            Err(_) => path.to_path_buf(),
+        },
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Tarjan's algorithm without recursion, so deep import chains cannot
+/// overflow the stack
+fn strongly_connected(files: &HashMap<PathBuf, FileNode>) -> Vec<Vec<PathBuf>> {
+    let mut paths: Vec<&PathBuf> = files.keys().collect();
+    paths.sort();
+    let position: HashMap<&PathBuf, usize> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (*path, i))
+        .collect();
+    let edges: Vec<Vec<usize>> = paths
+        .iter()
+        .map(|path| {
+            files[*path]
+                .dependencies
+                .iter()
+                .filter_map(|dependency| position.get(dependency).copied())
+                .collect()
+        })
+        .collect();
+
+    let count = paths.len();
+    let mut index = vec![usize::MAX; count];
+    let mut low = vec![0; count];
+    let mut on_stack = vec![false; count];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for start in 0..count {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        let mut call_stack = vec![(start, 0)];
+        index[start] = next_index;
+        low[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some(&(node, edge)) = call_stack.last() {
+            if let Some(&child) = edges[node].get(edge) {
+                if let Some(top) = call_stack.last_mut() {
+                    top.1 += 1;
+                }
+                if index[child] == usize::MAX {
+                    index[child] = next_index;
+                    low[child] = next_index;
+                    next_index += 1;
+                    stack.push(child);
+                    on_stack[child] = true;
+                    call_stack.push((child, 0));
+                } else if on_stack[child] {
+                    low[node] = low[node].min(index[child]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(paths[member].clone());
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        normalize(&path)
+    }
+
+    fn paths(files: &[ImpactedFile]) -> Vec<PathBuf> {
+        files.iter().map(|file| file.path.clone()).collect()
+    }
+
+    #[test]
+    fn test_resolves_imports_and_reverse_dependencies() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let util = write(root, "web/util.ts", "export function slug() {}\n");
+        let api = write(
+            root,
+            "web/api/index.ts",
+            "import { slug } from '../util';\nexport const get = 1;\n",
+        );
+        let app = write(root, "web/app.tsx", "import { get } from './api';\n");
+        let lib = write(root, "core/src/lib.rs", "pub mod config;\nmod db;\n");
+        let config = write(root, "core/src/config.rs", "pub struct Config;\n");
+        let db = write(
+            root,
+            "core/src/db/mod.rs",
+            "use crate::{\n    config::Config,\n    other,\n};\n",
+        );
+        write(
+            root,
+            "node_modules/pkg/index.ts",
+            "import '../../web/util';\n",
+        );
+
+        let graph = ImportGraph::build(root);
+
+        assert_eq!(graph.dependencies(&app), vec![api.clone()]);
+        assert_eq!(paths(&graph.dependents(&util, false)), vec![api.clone()]);
+        assert_eq!(
+            paths(&graph.dependents(&util, true)),
+            vec![api.clone(), app.clone()]
+        );
+        assert_eq!(graph.dependencies(&lib), vec![config.clone(), db.clone()]);
+        assert_eq!(graph.dependencies(&db), vec![config.clone()]);
+        assert_eq!(graph.file(&util).unwrap().exports, vec!["slug"]);
+        assert_eq!(graph.files().count(), 6);
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn test_cycles_and_impact() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let a = write(root, "a.ts", "import './b';\n");
+        let b = write(root, "b.ts", "import './c';\n");
+        let c = write(root, "c.ts", "import './a';\n");
+        write(root, "d.ts", "import './c';\n");
+
+        let graph = ImportGraph::build(root);
+        assert_eq!(graph.cycles(), vec![vec![a.clone(), b.clone(), c.clone()]]);
+
+        let impact = graph.impact(&c);
+        assert!(impact.indexed);
+        assert_eq!(impact.direct_dependents.len(), 2);
+        assert_eq!(impact.transitive_dependents.len(), 3);
+        assert_eq!(impact.cycles.len(), 1);
+        assert_eq!(impact.risk, ImpactRisk::High);
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let main = write(root, "main.ts", "import { run } from './task';\n");
+        let mut graph = ImportGraph::build(root);
+        assert!(graph.dependencies(&main).is_empty());
+
+        // A new file satisfies the dangling import
+        let task = write(root, "task.ts", "export const run = 1;\n");
+        assert!(graph.apply_event(&FileEvent::Created(vec![task.clone()])));
+        assert_eq!(graph.dependencies(&main), vec![task.clone()]);
+
+        // Editing the importer drops the edge
+        std::fs::write(&main, "console.log('no imports');\n").unwrap();
+        assert!(graph.apply_event(&FileEvent::Modified(vec![main.clone()])));
+        assert!(graph.dependents(&task, true).is_empty());
+
+        std::fs::write(&main, "import { run } from './task';\n").unwrap();
+        graph.apply_event(&FileEvent::Modified(vec![main.clone()]));
+        std::fs::remove_file(&task).unwrap();
+        assert!(graph.apply_event(&FileEvent::Deleted(vec![task.clone()])));
+        assert!(graph.file(&task).is_none());
+        assert!(graph.dependencies(&main).is_empty());
+
+        // Files outside the workspace or in ignored directories are skipped
+        let ignored = write(root, "dist/out.js", "import '../main';\n");
+        assert!(!graph.apply_event(&FileEvent::Created(vec![ignored])));
+    }
+}
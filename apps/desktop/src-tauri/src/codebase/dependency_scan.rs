@@ -0,0 +1,1792 @@
+//! Dependency vulnerability and license scanning.
+//!
+//! A scan collects the packages a workspace depends on from its manifests
+//! (`Cargo.toml`, `package.json`, `requirements*.txt`) and, where present,
+//! their lockfiles, so transitive packages are covered at the versions that
+//! are actually installed. Each package is checked against a local
+//! [`AdvisoryDatabase`] of OSV advisories, which scans never touch the network
+//! for; it is refreshed from osv.dev or imported from an OSV export when
+//! online access is not available. Licenses come from lockfiles and installed
+//! package metadata and are checked against a [`LicensePolicy`].
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::review::{Severity, SeverityCounts};
+
+const OSV_API: &str = "https://api.osv.dev/v1";
+/// Queries per `querybatch` request, the limit osv.dev accepts
+const OSV_BATCH_SIZE: usize = 1000;
+const MAX_MANIFEST_DEPTH: usize = 8;
+/// Directories holding installed or generated code rather than manifests
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".venv",
+    "venv",
+    "env",
+    "__pycache__",
+    ".tox",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Ecosystem {
+    #[serde(rename = "crates.io")]
+    CratesIo,
+    #[serde(rename = "npm")]
+    Npm,
+    #[serde(rename = "PyPI")]
+    PyPI,
+}
+
+impl Ecosystem {
+    /// Name used by OSV
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Ecosystem::CratesIo => "crates.io",
+            Ecosystem::Npm => "npm",
+            Ecosystem::PyPI => "PyPI",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "crates.io" => Some(Ecosystem::CratesIo),
+            "npm" => Some(Ecosystem::Npm),
+            "PyPI" => Some(Ecosystem::PyPI),
+            _ => None,
+        }
+    }
+
+    /// Package names as OSV and the registries compare them
+    fn normalize(self, name: &str) -> String {
+        match self {
+            Ecosystem::PyPI => name.to_lowercase().replace(['_', '.'], "-"),
+            _ => name.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    pub ecosystem: Ecosystem,
+    /// Installed version from the lockfile, or the lowest version the
+    /// requirement allows when there is none
+    pub version: Option<String>,
+    pub requirement: Option<String>,
+    /// Whether `version` comes from a lockfile or an exact pin
+    pub resolved: bool,
+    /// Declared in a manifest, as opposed to pulled in by another package
+    pub direct: bool,
+    pub dev: bool,
+    pub license: Option<String>,
+    /// Manifest or lockfile it was found in, relative to the workspace
+    pub source: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AffectedRange {
+    /// `None` means every version before the end of the range
+    pub introduced: Option<String>,
+    pub fixed: Option<String>,
+    pub last_affected: Option<String>,
+}
+
+impl AffectedRange {
+    fn contains(&self, version: &str) -> bool {
+        let after_start = self
+            .introduced
+            .as_deref()
+            .filter(|introduced| *introduced != "0")
+            .is_none_or(|introduced| compare_versions(version, introduced) != Ordering::Less);
+        let before_end = match (&self.fixed, &self.last_affected) {
+            (Some(fixed), _) => compare_versions(version, fixed) == Ordering::Less,
+            (None, Some(last)) => compare_versions(version, last) != Ordering::Greater,
+            (None, None) => true,
+        };
+        after_start && before_end
+    }
+}
+
+/// An advisory for one package, as stored in the local database
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Advisory {
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub severity: Severity,
+    pub ecosystem: Ecosystem,
+    pub package: String,
+    #[serde(default)]
+    pub ranges: Vec<AffectedRange>,
+    /// Versions listed as affected outside of any range
+    #[serde(default)]
+    pub versions: Vec<String>,
+    pub url: String,
+}
+
+impl Advisory {
+    pub fn affects(&self, version: &str) -> bool {
+        self.versions.iter().any(|affected| affected == version)
+            || self.ranges.iter().any(|range| range.contains(version))
+    }
+
+    /// Lowest fixed version above `version`
+    fn fixed_after(&self, version: &str) -> Option<String> {
+        self.ranges
+            .iter()
+            .filter_map(|range| range.fixed.as_deref())
+            .filter(|fixed| compare_versions(fixed, version) == Ordering::Greater)
+            .min_by(|a, b| compare_versions(a, b))
+            .map(str::to_string)
+    }
+
+    /// Convert an OSV record into one advisory per affected package we can scan
+    pub fn from_osv(record: &Value) -> Vec<Advisory> {
+        let Some(id) = record.get("id").and_then(Value::as_str) else {
+            return Vec::new();
+        };
+        if record.get("withdrawn").and_then(Value::as_str).is_some() {
+            return Vec::new();
+        }
+
+        let strings = |value: Option<&Value>| -> Vec<String> {
+            value
+                .and_then(Value::as_array)
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let summary = record
+            .get("summary")
+            .and_then(Value::as_str)
+            .or_else(|| {
+                record
+                    .get("details")
+                    .and_then(Value::as_str)
+                    .and_then(|details| details.lines().find(|line| !line.trim().is_empty()))
+            })
+            .unwrap_or(id)
+            .trim()
+            .to_string();
+        let url = record
+            .get("references")
+            .and_then(Value::as_array)
+            .and_then(|references| {
+                references
+                    .iter()
+                    .find(|reference| {
+                        reference.get("type").and_then(Value::as_str) == Some("ADVISORY")
+                    })
+                    .or_else(|| references.first())
+            })
+            .and_then(|reference| reference.get("url"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("https://osv.dev/vulnerability/{}", id));
+        let record_severity = osv_severity(record);
+
+        let mut advisories = Vec::new();
+        for affected in record
+            .get("affected")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let package = affected.get("package");
+            let Some(ecosystem) = package
+                .and_then(|package| package.get("ecosystem"))
+                .and_then(Value::as_str)
+                .and_then(Ecosystem::parse)
+            else {
+                continue;
+            };
+            let Some(name) = package
+                .and_then(|package| package.get("name"))
+                .and_then(Value::as_str)
+            else {
+                continue;
+            };
+
+            let mut ranges = Vec::new();
+            for range in affected
+                .get("ranges")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if range.get("type").and_then(Value::as_str) == Some("GIT") {
+                    continue;
+                }
+                let mut current: Option<AffectedRange> = None;
+                for event in range
+                    .get("events")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    let field = |key: &str| event.get(key).and_then(Value::as_str);
+                    if let Some(introduced) = field("introduced") {
+                        if let Some(open) = current.take() {
+                            ranges.push(open);
+                        }
+                        current = Some(AffectedRange {
+                            introduced: Some(introduced.to_string()),
+                            ..Default::default()
+                        });
+                    } else if let Some(fixed) = field("fixed") {
+                        let mut range = current.take().unwrap_or_default();
+                        range.fixed = Some(fixed.to_string());
+                        ranges.push(range);
+                    } else if let Some(last) = field("last_affected") {
+                        let mut range = current.take().unwrap_or_default();
+                        range.last_affected = Some(last.to_string());
+                        ranges.push(range);
+                    }
+                }
+                ranges.extend(current);
+            }
+
+            advisories.push(Advisory {
+                id: id.to_string(),
+                aliases: strings(record.get("aliases")),
+                summary: summary.clone(),
+                severity: osv_severity(affected)
+                    .or(record_severity)
+                    .unwrap_or(Severity::Medium),
+                ecosystem,
+                package: ecosystem.normalize(name),
+                ranges,
+                versions: strings(affected.get("versions")),
+                url: url.clone(),
+            });
+        }
+        advisories
+    }
+}
+
+/// Severity from GitHub's `database_specific.severity` or a CVSS v3 vector
+fn osv_severity(value: &Value) -> Option<Severity> {
+    let labelled = value
+        .get("database_specific")
+        .and_then(|specific| specific.get("severity"))
+        .and_then(Value::as_str)
+        .map(|label| match label.to_lowercase().as_str() {
+            "moderate" => Severity::Medium,
+            other => Severity::parse(other),
+        });
+    labelled.or_else(|| {
+        value
+            .get("severity")
+            .and_then(Value::as_array)?
+            .iter()
+            .filter_map(|entry| entry.get("score").and_then(Value::as_str))
+            .find_map(cvss3_base_score)
+            .map(|score| match score {
+                s if s >= 9.0 => Severity::Critical,
+                s if s >= 7.0 => Severity::High,
+                s if s >= 4.0 => Severity::Medium,
+                s if s > 0.0 => Severity::Low,
+                _ => Severity::Info,
+            })
+    })
+}
+
+/// Base score of a `CVSS:3.x/...` vector, per the CVSS v3.1 specification
+fn cvss3_base_score(vector: &str) -> Option<f64> {
+    if !vector.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: HashMap<&str, &str> = vector
+        .split('/')
+        .skip(1)
+        .filter_map(|metric| metric.split_once(':'))
+        .collect();
+    let changed = *metrics.get("S")? == "C";
+
+    let attack_vector = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        _ => 0.2,
+    };
+    let complexity = if *metrics.get("AC")? == "L" {
+        0.77
+    } else {
+        0.44
+    };
+    let privileges = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        (_, false) => 0.27,
+        (_, true) => 0.5,
+    };
+    let interaction = if *metrics.get("UI")? == "N" {
+        0.85
+    } else {
+        0.62
+    };
+    let impact_of = |key: &str| -> Option<f64> {
+        Some(match *metrics.get(key)? {
+            "H" => 0.56,
+            "L" => 0.22,
+            _ => 0.0,
+        })
+    };
+    let base = 1.0 - (1.0 - impact_of("C")?) * (1.0 - impact_of("I")?) * (1.0 - impact_of("A")?);
+
+    let impact = if changed {
+        7.52 * (base - 0.029) - 3.25 * (base - 0.02).powi(15)
+    } else {
+        6.42 * base
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * attack_vector * complexity * privileges * interaction;
+    let score = if changed {
+        (1.08 * (impact + exploitability)).min(10.0)
+    } else {
+        (impact + exploitability).min(10.0)
+    };
+    Some((score * 10.0 - 1e-9).ceil() / 10.0)
+}
+
+/// Advisories cached on disk so scans work offline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdvisoryDatabase {
+    pub updated_at: Option<i64>,
+    pub advisories: Vec<Advisory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvisoryDatabaseStatus {
+    pub path: PathBuf,
+    pub updated_at: Option<i64>,
+    pub advisories: usize,
+    /// Advisories added or replaced by the last refresh
+    pub refreshed: usize,
+}
+
+impl AdvisoryDatabase {
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(crate::utils::app_data_dir()?.join("advisories.json"))
+    }
+
+    /// Load the database; a missing file is an empty database
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Invalid advisory database {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// Add advisories, replacing stored versions of the same advisory and package
+    pub fn merge(&mut self, advisories: Vec<Advisory>) -> usize {
+        let mut index: HashMap<(String, Ecosystem, String), usize> = self
+            .advisories
+            .iter()
+            .enumerate()
+            .map(|(i, a)| ((a.id.clone(), a.ecosystem, a.package.clone()), i))
+            .collect();
+        let count = advisories.len();
+        for advisory in advisories {
+            let key = (
+                advisory.id.clone(),
+                advisory.ecosystem,
+                advisory.package.clone(),
+            );
+            match index.get(&key) {
+                Some(&i) => self.advisories[i] = advisory,
+                None => {
+                    index.insert(key, self.advisories.len());
+                    self.advisories.push(advisory);
+                }
+            }
+        }
+        self.updated_at = Some(chrono::Utc::now().timestamp());
+        count
+    }
+
+    pub fn status(&self, path: &Path, refreshed: usize) -> AdvisoryDatabaseStatus {
+        AdvisoryDatabaseStatus {
+            path: path.to_path_buf(),
+            updated_at: self.updated_at,
+            advisories: self.advisories.len(),
+            refreshed,
+        }
+    }
+
+    fn by_package(&self) -> HashMap<(Ecosystem, &str), Vec<&Advisory>> {
+        let mut map: HashMap<(Ecosystem, &str), Vec<&Advisory>> = HashMap::new();
+        for advisory in &self.advisories {
+            map.entry((advisory.ecosystem, advisory.package.as_str()))
+                .or_default()
+                .push(advisory);
+        }
+        map
+    }
+}
+
+/// Read OSV records from a JSON file (one record, an array, or a saved
+/// [`AdvisoryDatabase`]) or a zip of records such as osv.dev's `all.zip` exports
+pub fn import_advisories(path: &Path) -> Result<Vec<Advisory>> {
+    let is_zip = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        let value: Value = serde_json::from_slice(&std::fs::read(path)?)
+            .with_context(|| format!("Invalid advisory file {}", path.display()))?;
+        if value.get("advisories").is_some() {
+            let database: AdvisoryDatabase = serde_json::from_value(value)?;
+            return Ok(database.advisories);
+        }
+        return Ok(match value {
+            Value::Array(records) => records.iter().flat_map(Advisory::from_osv).collect(),
+            record => Advisory::from_osv(&record),
+        });
+    }
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)
+        .with_context(|| format!("Invalid advisory archive {}", path.display()))?;
+    let mut advisories = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.name().ends_with(".json") {
+            continue;
+        }
+        let mut text = String::new();
+        entry.read_to_string(&mut text)?;
+        match serde_json::from_str::<Value>(&text) {
+            Ok(record) => advisories.extend(Advisory::from_osv(&record)),
+            Err(e) => tracing::debug!("Skipping {} in advisory archive: {}", entry.name(), e),
+        }
+    }
+    Ok(advisories)
+}
+
+/// Fetch every OSV advisory for the given packages, regardless of version, so
+/// later scans stay accurate after upgrades without going online
+pub async fn fetch_osv_advisories(
+    client: &reqwest::Client,
+    dependencies: &[Dependency],
+) -> Result<Vec<Advisory>> {
+    let packages: Vec<(Ecosystem, String)> = dependencies
+        .iter()
+        .map(|dep| (dep.ecosystem, dep.name.clone()))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut ids = HashSet::new();
+    for batch in packages.chunks(OSV_BATCH_SIZE) {
+        let queries: Vec<Value> = batch
+            .iter()
+            .map(|(ecosystem, name)| {
+                serde_json::json!({ "package": { "name": name, "ecosystem": ecosystem.as_str() } })
+            })
+            .collect();
+        let response: Value = client
+            .post(format!("{}/querybatch", OSV_API))
+            .json(&serde_json::json!({ "queries": queries }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        for result in response
+            .get("results")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            for vuln in result
+                .get("vulns")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(id) = vuln.get("id").and_then(Value::as_str) {
+                    ids.insert(id.to_string());
+                }
+            }
+        }
+    }
+
+    let mut advisories = Vec::new();
+    for id in ids {
+        let record: Value = client
+            .get(format!("{}/vulns/{}", OSV_API, id))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        advisories.extend(Advisory::from_osv(&record));
+    }
+    Ok(advisories)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseVerdict {
+    Allowed,
+    /// Needs a human decision: weak copyleft, unrecognized, or missing
+    Review,
+    Denied,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LicenseClass {
+    Permissive,
+    WeakCopyleft,
+    StrongCopyleft,
+    Unknown,
+}
+
+const PERMISSIVE: &[&str] = &[
+    "MIT",
+    "MIT-0",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "0BSD",
+    "ISC",
+    "Zlib",
+    "Unlicense",
+    "CC0-1.0",
+    "BSL-1.0",
+    "Unicode-DFS-2016",
+    "Unicode-3.0",
+    "Python-2.0",
+    "PSF-2.0",
+    "WTFPL",
+    "BlueOak-1.0.0",
+    "CC-BY-4.0",
+];
+const WEAK_COPYLEFT: &[&str] = &["LGPL", "MPL", "EPL", "CDDL", "CPL", "EUPL", "OSL"];
+const STRONG_COPYLEFT: &[&str] = &["GPL", "AGPL", "SSPL", "CC-BY-SA"];
+
+fn classify_license(id: &str) -> LicenseClass {
+    let id = id.trim();
+    if PERMISSIVE
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(id))
+    {
+        return LicenseClass::Permissive;
+    }
+    let upper = id.to_uppercase();
+    // Check LGPL before GPL, which it contains
+    if WEAK_COPYLEFT.iter().any(|family| upper.starts_with(family)) {
+        LicenseClass::WeakCopyleft
+    } else if STRONG_COPYLEFT
+        .iter()
+        .any(|family| upper.starts_with(family))
+    {
+        LicenseClass::StrongCopyleft
+    } else {
+        LicenseClass::Unknown
+    }
+}
+
+/// Which dependency licenses a workspace accepts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LicensePolicy {
+    /// Whether the workspace is distributed under a proprietary license, which
+    /// rules out strong copyleft dependencies. Detected from the workspace's own
+    /// license when unset; a workspace without one is treated as proprietary
+    #[serde(default)]
+    pub proprietary: Option<bool>,
+    /// SPDX ids that are always accepted
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// SPDX ids that are always rejected
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Skip findings for packages whose license could not be determined
+    #[serde(default)]
+    pub ignore_unknown: bool,
+}
+
+impl LicensePolicy {
+    fn judge_id(&self, id: &str, proprietary: bool) -> (LicenseVerdict, String) {
+        let (base, exception) = match id.split_once(" WITH ") {
+            Some((base, exception)) => (base.trim(), Some(exception.trim())),
+            None => (id.trim(), None),
+        };
+        let listed = |list: &[String]| {
+            list.iter()
+                .any(|entry| entry.eq_ignore_ascii_case(id) || entry.eq_ignore_ascii_case(base))
+        };
+        if listed(&self.deny) {
+            return (
+                LicenseVerdict::Denied,
+                format!("{} is on the deny list", id),
+            );
+        }
+        if listed(&self.allow) {
+            return (LicenseVerdict::Allowed, String::new());
+        }
+
+        let mut class = classify_license(base);
+        // Linking exceptions such as Classpath-exception-2.0 weaken the copyleft
+        if class == LicenseClass::StrongCopyleft && exception.is_some() {
+            class = LicenseClass::WeakCopyleft;
+        }
+        match class {
+            LicenseClass::Permissive => (LicenseVerdict::Allowed, String::new()),
+            LicenseClass::StrongCopyleft if proprietary => (
+                LicenseVerdict::Denied,
+                format!("{} is strong copyleft and this project is proprietary", id),
+            ),
+            LicenseClass::StrongCopyleft => (LicenseVerdict::Allowed, String::new()),
+            LicenseClass::WeakCopyleft if proprietary => (
+                LicenseVerdict::Review,
+                format!("{} is weak copyleft; check how the package is linked", id),
+            ),
+            LicenseClass::WeakCopyleft => (LicenseVerdict::Allowed, String::new()),
+            LicenseClass::Unknown => (
+                LicenseVerdict::Review,
+                format!("{} is not a recognized license", id),
+            ),
+        }
+    }
+
+    /// Judge an SPDX expression. An `OR` is satisfied by its best alternative
+    /// and an `AND` is limited by its worst part
+    pub fn judge(&self, expression: &str, proprietary: bool) -> (LicenseVerdict, String) {
+        let cleaned = expression.replace(['(', ')'], " ");
+        let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+        // npm and older Cargo manifests separate alternatives with `/`
+        let cleaned = cleaned.replace('/', " OR ");
+
+        cleaned
+            .split(" OR ")
+            .map(|alternative| {
+                alternative
+                    .split(" AND ")
+                    .map(|id| self.judge_id(id, proprietary))
+                    .max_by_key(|(verdict, _)| *verdict)
+                    .unwrap_or((
+                        LicenseVerdict::Review,
+                        "Empty license expression".to_string(),
+                    ))
+            })
+            .min_by_key(|(verdict, _)| *verdict)
+            .unwrap_or((
+                LicenseVerdict::Review,
+                "Empty license expression".to_string(),
+            ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VulnerabilityFinding {
+    pub package: String,
+    pub ecosystem: Ecosystem,
+    pub version: String,
+    pub direct: bool,
+    pub advisory_id: String,
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub severity: Severity,
+    /// Lowest version that fixes the advisory, when one exists
+    pub fixed_version: Option<String>,
+    pub url: String,
+    /// Matched against a requirement's lower bound instead of an installed version
+    pub unresolved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseFinding {
+    pub package: String,
+    pub ecosystem: Ecosystem,
+    pub version: Option<String>,
+    pub direct: bool,
+    pub license: Option<String>,
+    pub verdict: LicenseVerdict,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyScanReport {
+    pub root: PathBuf,
+    /// Manifests and lockfiles read, relative to `root`
+    pub manifests: Vec<PathBuf>,
+    pub project_license: Option<String>,
+    pub proprietary: bool,
+    pub dependencies: Vec<Dependency>,
+    /// Most severe first
+    pub vulnerabilities: Vec<VulnerabilityFinding>,
+    /// Only dependencies whose license is not plainly allowed
+    pub licenses: Vec<LicenseFinding>,
+    pub counts: SeverityCounts,
+    pub advisory_db_updated_at: Option<i64>,
+    pub notes: Vec<String>,
+    pub scanned_at: i64,
+}
+
+impl DependencyScanReport {
+    /// Whether the scan found nothing at or above `threshold` and no denied license
+    pub fn passes(&self, threshold: Severity) -> bool {
+        self.vulnerabilities
+            .iter()
+            .all(|finding| finding.severity < threshold)
+            && self
+                .licenses
+                .iter()
+                .all(|finding| finding.verdict != LicenseVerdict::Denied)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("## Dependency scan\n\n");
+        out.push_str(&format!(
+            "{} dependencies from {} manifest(s). Project license: {}{}.\n\n",
+            self.dependencies.len(),
+            self.manifests.len(),
+            self.project_license.as_deref().unwrap_or("none found"),
+            if self.proprietary {
+                " (proprietary)"
+            } else {
+                ""
+            }
+        ));
+
+        if self.vulnerabilities.is_empty() {
+            out.push_str("No known vulnerabilities.\n\n");
+        } else {
+            out.push_str("### Vulnerabilities\n\n| Severity | Package | Advisory | Fixed in |\n|---|---|---|---|\n");
+            for finding in &self.vulnerabilities {
+                out.push_str(&format!(
+                    "| {} | {} {} ({}){} | [{}]({}) {} | {} |\n",
+                    finding.severity.label(),
+                    finding.package,
+                    finding.version,
+                    finding.ecosystem.as_str(),
+                    if finding.direct { "" } else { ", transitive" },
+                    finding.advisory_id,
+                    finding.url,
+                    finding.summary.replace('|', "\\|"),
+                    finding.fixed_version.as_deref().unwrap_or("-"),
+                ));
+            }
+            out.push('\n');
+        }
+
+        if !self.licenses.is_empty() {
+            out.push_str("### Licenses\n\n");
+            for finding in &self.licenses {
+                let verdict = match finding.verdict {
+                    LicenseVerdict::Denied => "Denied",
+                    LicenseVerdict::Review => "Review",
+                    LicenseVerdict::Allowed => "Allowed",
+                };
+                out.push_str(&format!(
+                    "- **{}** {} {}: {}\n",
+                    verdict,
+                    finding.package,
+                    finding.version.as_deref().unwrap_or(""),
+                    finding.reason
+                ));
+            }
+            out.push('\n');
+        }
+
+        for note in &self.notes {
+            out.push_str(&format!("> {}\n", note));
+        }
+        out
+    }
+}
+
+/// Scan a workspace against the local advisory database and a license policy
+pub fn scan(
+    root: &Path,
+    database: &AdvisoryDatabase,
+    policy: &LicensePolicy,
+) -> Result<DependencyScanReport> {
+    if !root.is_dir() {
+        return Err(anyhow!("{} is not a directory", root.display()));
+    }
+    let mut notes = Vec::new();
+    let (mut dependencies, manifests) = collect_dependencies(root, &mut notes)?;
+    fill_installed_licenses(root, &mut dependencies);
+
+    let project_license = project_license(root);
+    let proprietary = policy.proprietary.unwrap_or_else(|| {
+        project_license.as_deref().is_none_or(|license| {
+            let lower = license.to_lowercase();
+            lower.contains("unlicensed") || lower.contains("proprietary")
+        })
+    });
+
+    if database.updated_at.is_none() {
+        notes.push(
+            "The advisory database is empty; refresh it to check for vulnerabilities".to_string(),
+        );
+    }
+    let advisories = database.by_package();
+    let mut vulnerabilities = Vec::new();
+    for dep in &dependencies {
+        let Some(version) = &dep.version else {
+            continue;
+        };
+        let normalized = dep.ecosystem.normalize(&dep.name);
+        for advisory in advisories
+            .get(&(dep.ecosystem, normalized.as_str()))
+            .into_iter()
+            .flatten()
+        {
+            if !advisory.affects(version) {
+                continue;
+            }
+            vulnerabilities.push(VulnerabilityFinding {
+                package: dep.name.clone(),
+                ecosystem: dep.ecosystem,
+                version: version.clone(),
+                direct: dep.direct,
+                advisory_id: advisory.id.clone(),
+                aliases: advisory.aliases.clone(),
+                summary: advisory.summary.clone(),
+                severity: advisory.severity,
+                fixed_version: advisory.fixed_after(version),
+                url: advisory.url.clone(),
+                unresolved: !dep.resolved,
+            });
+        }
+    }
+    vulnerabilities.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.package.cmp(&b.package))
+    });
+    if vulnerabilities.iter().any(|finding| finding.unresolved) {
+        notes.push(
+            "Some packages have no lockfile entry and were checked at the lowest version their requirement allows".to_string(),
+        );
+    }
+
+    let mut licenses = Vec::new();
+    for dep in &dependencies {
+        let (verdict, reason) = match &dep.license {
+            Some(license) => policy.judge(license, proprietary),
+            None if policy.ignore_unknown => continue,
+            None => (
+                LicenseVerdict::Review,
+                "License could not be determined; install the package or check its registry page"
+                    .to_string(),
+            ),
+        };
+        if verdict != LicenseVerdict::Allowed {
+            licenses.push(LicenseFinding {
+                package: dep.name.clone(),
+                ecosystem: dep.ecosystem,
+                version: dep.version.clone(),
+                direct: dep.direct,
+                license: dep.license.clone(),
+                verdict,
+                reason,
+            });
+        }
+    }
+    licenses.sort_by(|a, b| {
+        b.verdict
+            .cmp(&a.verdict)
+            .then_with(|| a.package.cmp(&b.package))
+    });
+
+    let mut counts = SeverityCounts::default();
+    for finding in &vulnerabilities {
+        counts.add(finding.severity);
+    }
+
+    Ok(DependencyScanReport {
+        root: root.to_path_buf(),
+        manifests,
+        project_license,
+        proprietary,
+        dependencies,
+        vulnerabilities,
+        licenses,
+        counts,
+        advisory_db_updated_at: database.updated_at,
+        notes,
+        scanned_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Scan a workspace against the advisory database in the app data directory
+pub fn scan_workspace(root: &Path, policy: &LicensePolicy) -> Result<DependencyScanReport> {
+    let database = AdvisoryDatabase::load(&AdvisoryDatabase::default_path()?)?;
+    scan(root, &database, policy)
+}
+
+/// Collect dependencies from every manifest under `root`. Returns them with
+/// the manifest and lockfile paths that were read
+pub fn collect_dependencies(
+    root: &Path,
+    notes: &mut Vec<String>,
+) -> Result<(Vec<Dependency>, Vec<PathBuf>)> {
+    let mut collected: BTreeMap<(Ecosystem, String, Option<String>), Dependency> = BTreeMap::new();
+    let mut manifests = Vec::new();
+    let mut add = |dep: Dependency| {
+        let key = (dep.ecosystem, dep.name.clone(), dep.version.clone());
+        match collected.get_mut(&key) {
+            Some(existing) => {
+                existing.direct |= dep.direct;
+                existing.dev &= dep.dev;
+                existing.resolved |= dep.resolved;
+                if existing.license.is_none() {
+                    existing.license = dep.license;
+                }
+                if existing.requirement.is_none() {
+                    existing.requirement = dep.requirement;
+                }
+            }
+            None => {
+                collected.insert(key, dep);
+            }
+        }
+    };
+
+    let entries = WalkDir::new(root)
+        .max_depth(MAX_MANIFEST_DEPTH)
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.file_type().is_dir()
+                && entry.depth() > 0
+                && entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| SKIPPED_DIRS.contains(&name)))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy();
+        let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        let dir = path.parent().unwrap_or(root);
+
+        let parsed = if name == "Cargo.toml" {
+            parse_cargo_manifest(path, &relative).map(|direct| {
+                let lockfile = find_upwards(dir, root, "Cargo.lock");
+                resolve_with_lockfile(direct, lockfile, root, &mut manifests, parse_cargo_lock)
+            })
+        } else if name == "package.json" {
+            parse_package_json(path, &relative).map(|direct| {
+                let lockfile = dir.join("package-lock.json");
+                let lockfile = lockfile.is_file().then_some(lockfile);
+                resolve_with_lockfile(direct, lockfile, root, &mut manifests, parse_package_lock)
+            })
+        } else if name.starts_with("requirements") && name.ends_with(".txt") {
+            parse_requirements(path, &relative)
+        } else {
+            continue;
+        };
+
+        match parsed {
+            Ok(deps) => {
+                manifests.push(relative);
+                deps.into_iter().for_each(&mut add);
+            }
+            Err(e) => notes.push(format!("Skipped {}: {}", relative.display(), e)),
+        }
+    }
+
+    manifests.sort();
+    manifests.dedup();
+    Ok((collected.into_values().collect(), manifests))
+}
+
+/// Replace a manifest's declared dependencies with the full locked set, marking
+/// declared ones as direct. Falls back to the declarations without a lockfile
+fn resolve_with_lockfile(
+    direct: Vec<Dependency>,
+    lockfile: Option<PathBuf>,
+    root: &Path,
+    manifests: &mut Vec<PathBuf>,
+    parse: fn(&Path, &Path) -> Result<Vec<Dependency>>,
+) -> Vec<Dependency> {
+    let Some(lockfile) = lockfile else {
+        return direct;
+    };
+    let relative = lockfile
+        .strip_prefix(root)
+        .unwrap_or(&lockfile)
+        .to_path_buf();
+    let locked = match parse(&lockfile, &relative) {
+        Ok(locked) => locked,
+        Err(e) => {
+            tracing::warn!("Failed to read {}: {}", lockfile.display(), e);
+            return direct;
+        }
+    };
+    manifests.push(relative);
+
+    let declared: HashMap<&str, &Dependency> =
+        direct.iter().map(|dep| (dep.name.as_str(), dep)).collect();
+    let mut resolved: Vec<Dependency> = locked
+        .into_iter()
+        .map(|mut dep| {
+            if let Some(declared) = declared.get(dep.name.as_str()) {
+                dep.direct = true;
+                dep.dev = declared.dev;
+                dep.requirement = declared.requirement.clone();
+            }
+            dep
+        })
+        .collect();
+    // Declared packages missing from the lockfile (it is stale) still count
+    let locked_names: HashSet<String> = resolved.iter().map(|dep| dep.name.clone()).collect();
+    resolved.extend(
+        direct
+            .into_iter()
+            .filter(|dep| !locked_names.contains(&dep.name)),
+    );
+    resolved
+}
+
+fn find_upwards(start: &Path, root: &Path, name: &str) -> Option<PathBuf> {
+    let mut dir = Some(start);
+    while let Some(current) = dir {
+        let candidate = current.join(name);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if current == root {
+            break;
+        }
+        dir = current.parent();
+    }
+    None
+}
+
+fn declared(
+    name: &str,
+    ecosystem: Ecosystem,
+    requirement: Option<&str>,
+    dev: bool,
+    source: &Path,
+) -> Dependency {
+    let (version, resolved) = match requirement.map(str::trim) {
+        Some(requirement) => lower_bound(requirement),
+        None => (None, false),
+    };
+    Dependency {
+        name: name.to_string(),
+        ecosystem,
+        version,
+        requirement: requirement.map(|r| r.trim().to_string()),
+        resolved,
+        direct: true,
+        dev,
+        license: None,
+        source: source.to_path_buf(),
+    }
+}
+
+/// Lowest version a requirement allows, and whether it pins an exact version
+fn lower_bound(requirement: &str) -> (Option<String>, bool) {
+    let first = requirement
+        .split("||")
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let exact = (first.starts_with("==") && !first.contains('*')) || first.starts_with('=');
+    let version = first
+        .trim_start_matches(['^', '~', '=', '>', '<', ' ', 'v'])
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .replace(['x', 'X', '*'], "0");
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        (Some(version.trim_end_matches('.').to_string()), exact)
+    } else {
+        (None, false)
+    }
+}
+
+fn parse_cargo_manifest(path: &Path, relative: &Path) -> Result<Vec<Dependency>> {
+    let manifest: toml::Value = toml::from_str(&std::fs::read_to_string(path)?)?;
+    let mut tables = cargo_sections(&manifest);
+    if let Some(workspace) = manifest.get("workspace") {
+        tables.extend(cargo_sections(workspace));
+    }
+    if let Some(targets) = manifest.get("target").and_then(|t| t.as_table()) {
+        tables.extend(targets.values().flat_map(cargo_sections));
+    }
+
+    let mut deps = Vec::new();
+    for (section, dev) in tables {
+        let Some(section) = section.as_table() else {
+            continue;
+        };
+        for (key, spec) in section {
+            let (name, requirement) = match spec {
+                toml::Value::String(requirement) => (key.as_str(), Some(requirement.as_str())),
+                toml::Value::Table(spec) => {
+                    // Path and git dependencies are not published packages
+                    if spec.contains_key("path") || spec.contains_key("git") {
+                        continue;
+                    }
+                    (
+                        spec.get("package")
+                            .and_then(|p| p.as_str())
+                            .unwrap_or(key.as_str()),
+                        spec.get("version").and_then(|v| v.as_str()),
+                    )
+                }
+                _ => continue,
+            };
+            deps.push(declared(
+                name,
+                Ecosystem::CratesIo,
+                requirement,
+                dev,
+                relative,
+            ));
+        }
+    }
+    Ok(deps)
+}
+
+/// Dependency tables of a manifest, package or `[target.*]` table, with
+/// whether they hold dev-dependencies
+fn cargo_sections(table: &toml::Value) -> Vec<(&toml::Value, bool)> {
+    [
+        ("dependencies", false),
+        ("dev-dependencies", true),
+        ("build-dependencies", false),
+    ]
+    .into_iter()
+    .filter_map(|(key, dev)| Some((table.get(key)?, dev)))
+    .collect()
+}
+
+fn parse_cargo_lock(path: &Path, relative: &Path) -> Result<Vec<Dependency>> {
+    let lock: toml::Value = toml::from_str(&std::fs::read_to_string(path)?)?;
+    Ok(lock
+        .get("package")
+        .and_then(|packages| packages.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|package| {
+            package
+                .get("source")
+                .and_then(|s| s.as_str())
+                .is_some_and(|source| source.starts_with("registry+"))
+        })
+        .filter_map(|package| {
+            Some(Dependency {
+                name: package.get("name")?.as_str()?.to_string(),
+                ecosystem: Ecosystem::CratesIo,
+                version: Some(package.get("version")?.as_str()?.to_string()),
+                requirement: None,
+                resolved: true,
+                direct: false,
+                dev: false,
+                license: None,
+                source: relative.to_path_buf(),
+            })
+        })
+        .collect())
+}
+
+fn parse_package_json(path: &Path, relative: &Path) -> Result<Vec<Dependency>> {
+    let manifest: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    let mut deps = Vec::new();
+    for (key, dev) in [
+        ("dependencies", false),
+        ("optionalDependencies", false),
+        ("devDependencies", true),
+    ] {
+        for (name, requirement) in manifest
+            .get(key)
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            deps.push(declared(
+                name,
+                Ecosystem::Npm,
+                requirement.as_str(),
+                dev,
+                relative,
+            ));
+        }
+    }
+    Ok(deps)
+}
+
+fn parse_package_lock(path: &Path, relative: &Path) -> Result<Vec<Dependency>> {
+    let lock: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    let locked = |name: &str, entry: &Value| {
+        Some(Dependency {
+            name: name.to_string(),
+            ecosystem: Ecosystem::Npm,
+            version: Some(entry.get("version")?.as_str()?.to_string()),
+            requirement: None,
+            resolved: true,
+            direct: false,
+            dev: entry.get("dev").and_then(Value::as_bool).unwrap_or(false),
+            license: entry.get("license").and_then(license_field),
+            source: relative.to_path_buf(),
+        })
+    };
+
+    // Lockfile v2 and v3 list every installed path under `packages`
+    if let Some(packages) = lock.get("packages").and_then(Value::as_object) {
+        return Ok(packages
+            .iter()
+            .filter(|(_, entry)| entry.get("link").and_then(Value::as_bool) != Some(true))
+            .filter_map(|(path, entry)| {
+                let (_, name) = path.rsplit_once("node_modules/")?;
+                locked(name, entry)
+            })
+            .collect());
+    }
+
+    // Lockfile v1 nests dependencies of dependencies
+    let mut deps = Vec::new();
+    let mut pending: Vec<&Value> = lock.get("dependencies").into_iter().collect();
+    while let Some(level) = pending.pop() {
+        for (name, entry) in level.as_object().into_iter().flatten() {
+            deps.extend(locked(name, entry));
+            pending.extend(entry.get("dependencies"));
+        }
+    }
+    Ok(deps)
+}
+
+fn parse_requirements(path: &Path, relative: &Path) -> Result<Vec<Dependency>> {
+    let dev = relative
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.contains("dev") || name.contains("test"));
+    let text = std::fs::read_to_string(path)?;
+
+    Ok(text
+        .lines()
+        .filter_map(|line| {
+            let line = line.split(" #").next().unwrap_or_default();
+            let line = line.split(';').next().unwrap_or_default().trim();
+            // Options, includes, URLs and local paths are not registry packages
+            if line.is_empty()
+                || line.starts_with(['#', '-', '.', '/'])
+                || line.contains("://")
+                || line.contains(" @ ")
+            {
+                return None;
+            }
+            let split = line
+                .find(['=', '<', '>', '~', '!', '[', ' '])
+                .unwrap_or(line.len());
+            let name = line[..split].trim();
+            let rest = line[split..].trim();
+            let requirement = match rest.find(']') {
+                Some(end) if rest.starts_with('[') => rest[end + 1..].trim(),
+                _ => rest,
+            };
+            (!name.is_empty()).then(|| {
+                declared(
+                    name,
+                    Ecosystem::PyPI,
+                    (!requirement.is_empty()).then_some(requirement),
+                    dev,
+                    relative,
+                )
+            })
+        })
+        .collect())
+}
+
+/// A `license` field in `package.json` form: an SPDX string or `{ "type": .. }`
+fn license_field(value: &Value) -> Option<String> {
+    match value {
+        Value::String(license) => Some(license.clone()),
+        Value::Object(_) => value
+            .get("type")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        Value::Array(licenses) => {
+            let ids: Vec<String> = licenses.iter().filter_map(license_field).collect();
+            (!ids.is_empty()).then(|| ids.join(" OR "))
+        }
+        _ => None,
+    }
+}
+
+/// Look up licenses of packages that are installed locally: `node_modules`,
+/// the Cargo registry cache, and a virtualenv in the workspace
+fn fill_installed_licenses(root: &Path, dependencies: &mut [Dependency]) {
+    let cargo_sources: Vec<PathBuf> = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+        .and_then(|home| std::fs::read_dir(home.join("registry").join("src")).ok())
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default();
+    let python_metadata = python_license_index(root);
+
+    for dep in dependencies.iter_mut().filter(|dep| dep.license.is_none()) {
+        dep.license = match dep.ecosystem {
+            Ecosystem::Npm => {
+                let manifest_dir = dep
+                    .source
+                    .parent()
+                    .map(|dir| root.join(dir))
+                    .unwrap_or_else(|| root.to_path_buf());
+                std::fs::read(
+                    manifest_dir
+                        .join("node_modules")
+                        .join(&dep.name)
+                        .join("package.json"),
+                )
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+                .and_then(|manifest| {
+                    manifest
+                        .get("license")
+                        .or_else(|| manifest.get("licenses"))
+                        .and_then(license_field)
+                })
+            }
+            Ecosystem::CratesIo => dep.version.as_ref().and_then(|version| {
+                cargo_sources.iter().find_map(|registry| {
+                    let manifest = registry
+                        .join(format!("{}-{}", dep.name, version))
+                        .join("Cargo.toml");
+                    let manifest: toml::Value =
+                        toml::from_str(&std::fs::read_to_string(manifest).ok()?).ok()?;
+                    let package = manifest.get("package")?;
+                    package
+                        .get("license")
+                        .and_then(|l| l.as_str())
+                        .map(str::to_string)
+                        .or_else(|| {
+                            package
+                                .get("license-file")
+                                .map(|_| "LicenseRef-file".to_string())
+                        })
+                })
+            }),
+            Ecosystem::PyPI => python_metadata
+                .get(&Ecosystem::PyPI.normalize(&dep.name))
+                .cloned(),
+        };
+    }
+}
+
+/// Licenses of packages installed in a virtualenv inside the workspace, by
+/// normalized package name
+fn python_license_index(root: &Path) -> HashMap<String, String> {
+    let mut index = HashMap::new();
+    for venv in [".venv", "venv", "env"] {
+        let venv = root.join(venv);
+        if !venv.is_dir() {
+            continue;
+        }
+        let dist_infos = WalkDir::new(&venv)
+            .max_depth(5)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.file_type().is_dir()
+                    && entry.file_name().to_string_lossy().ends_with(".dist-info")
+            });
+        for dist_info in dist_infos {
+            let Ok(metadata) = std::fs::read_to_string(dist_info.path().join("METADATA")) else {
+                continue;
+            };
+            let mut name = None;
+            let mut expression = None;
+            let mut license = None;
+            let mut classifiers = Vec::new();
+            // Headers end at the first blank line; the description follows
+            for line in metadata.lines().take_while(|line| !line.is_empty()) {
+                if let Some(value) = line.strip_prefix("Name: ") {
+                    name = Some(value.trim().to_string());
+                } else if let Some(value) = line.strip_prefix("License-Expression: ") {
+                    expression = Some(value.trim().to_string());
+                } else if let Some(value) = line.strip_prefix("License: ") {
+                    // Some packages paste the whole license text here
+                    if value.len() < 60 && !value.trim().eq_ignore_ascii_case("UNKNOWN") {
+                        license = Some(value.trim().to_string());
+                    }
+                } else if let Some(value) = line.strip_prefix("Classifier: License :: ") {
+                    classifiers.extend(license_from_classifier(value));
+                }
+            }
+            let found = expression
+                .or_else(|| (!classifiers.is_empty()).then(|| classifiers.join(" OR ")))
+                .or(license);
+            if let (Some(name), Some(found)) = (name, found) {
+                index.insert(Ecosystem::PyPI.normalize(&name), found);
+            }
+        }
+    }
+    index
+}
+
+fn license_from_classifier(classifier: &str) -> Option<String> {
+    let name = classifier.rsplit(" :: ").next()?.trim();
+    let id = match name {
+        "MIT License" => "MIT",
+        "Apache Software License" => "Apache-2.0",
+        "BSD License" => "BSD-3-Clause",
+        "ISC License (ISCL)" => "ISC",
+        "Python Software Foundation License" => "PSF-2.0",
+        "Mozilla Public License 2.0 (MPL 2.0)" => "MPL-2.0",
+        "GNU General Public License v2 (GPLv2)" => "GPL-2.0",
+        "GNU General Public License v3 (GPLv3)" => "GPL-3.0",
+        "GNU General Public License v2 or later (GPLv2+)" => "GPL-2.0-or-later",
+        "GNU General Public License v3 or later (GPLv3+)" => "GPL-3.0-or-later",
+        "GNU Lesser General Public License v2 (LGPLv2)" => "LGPL-2.0",
+        "GNU Lesser General Public License v3 (LGPLv3)" => "LGPL-3.0",
+        "GNU Lesser General Public License v2 or later (LGPLv2+)" => "LGPL-2.0-or-later",
+        "GNU Lesser General Public License v3 or later (LGPLv3+)" => "LGPL-3.0-or-later",
+        "GNU Affero General Public License v3" => "AGPL-3.0",
+        "The Unlicense (Unlicense)" => "Unlicense",
+        _ => return None,
+    };
+    Some(id.to_string())
+}
+
+/// The workspace's own license from its root manifest or LICENSE file
+fn project_license(root: &Path) -> Option<String> {
+    let cargo = std::fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|text| toml::from_str::<toml::Value>(&text).ok())
+        .and_then(|manifest| {
+            let package = manifest.get("package");
+            let workspace = manifest.get("workspace").and_then(|w| w.get("package"));
+            package
+                .or(workspace)?
+                .get("license")?
+                .as_str()
+                .map(str::to_string)
+        });
+    let npm = || {
+        std::fs::read(root.join("package.json"))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            .and_then(|manifest| manifest.get("license").and_then(license_field))
+    };
+    let license_file = || {
+        ["LICENSE", "LICENSE.md", "LICENSE.txt", "COPYING"]
+            .iter()
+            .find_map(|name| std::fs::read_to_string(root.join(name)).ok())
+            .and_then(|text| {
+                let head: String = text.chars().take(2000).collect::<String>().to_uppercase();
+                let id = if head.contains("GNU AFFERO GENERAL PUBLIC LICENSE") {
+                    "AGPL-3.0"
+                } else if head.contains("GNU LESSER GENERAL PUBLIC LICENSE") {
+                    "LGPL-3.0"
+                } else if head.contains("GNU GENERAL PUBLIC LICENSE") {
+                    "GPL-3.0"
+                } else if head.contains("MIT LICENSE")
+                    || head.contains("PERMISSION IS HEREBY GRANTED, FREE OF CHARGE")
+                {
+                    "MIT"
+                } else if head.contains("APACHE LICENSE") {
+                    "Apache-2.0"
+                } else if head.contains("MOZILLA PUBLIC LICENSE") {
+                    "MPL-2.0"
+                } else if head.contains("ALL RIGHTS RESERVED") {
+                    "Proprietary"
+                } else {
+                    return None;
+                };
+                Some(id.to_string())
+            })
+    };
+    cargo.or_else(npm).or_else(license_file)
+}
+
+/// Compare versions segment by segment, numerically where possible. Handles
+/// SemVer and the common PEP 440 forms; a pre-release sorts before its release
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    /// Release numbers, pre-release tag, and post-release tag
+    fn split(version: &str) -> (Vec<u64>, Option<String>, Option<String>) {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split('+').next().unwrap_or_default();
+        let version = version.split_once('!').map_or(version, |(_, rest)| rest);
+        let mut release = Vec::new();
+        let mut rest = version;
+        loop {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits == 0 {
+                break;
+            }
+            release.push(rest[..digits].parse().unwrap_or(u64::MAX));
+            rest = &rest[digits..];
+            match rest.strip_prefix('.') {
+                Some(next) if next.starts_with(|c: char| c.is_ascii_digit()) => rest = next,
+                _ => break,
+            }
+        }
+        let suffix = rest.trim_start_matches(['.', '-', '_']).to_lowercase();
+        if suffix.is_empty() {
+            (release, None, None)
+        } else if suffix.starts_with("post") {
+            (release, None, Some(suffix))
+        } else {
+            (release, Some(suffix), None)
+        }
+    }
+
+    let (release_a, pre_a, post_a) = split(a);
+    let (release_b, pre_b, post_b) = split(b);
+    for i in 0..release_a.len().max(release_b.len()) {
+        let x = release_a.get(i).copied().unwrap_or(0);
+        let y = release_b.get(i).copied().unwrap_or(0);
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+    let pre = match (pre_a, pre_b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => compare_identifiers(&x, &y),
+    };
+    pre.then_with(|| match (post_a, post_b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(x), Some(y)) => compare_identifiers(&x, &y),
+    })
+}
+
+/// Compare pre-release tags like `alpha.10` and `rc1`, numbers numerically
+fn compare_identifiers(a: &str, b: &str) -> Ordering {
+    let parts = |s: &str| -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        for c in s.chars() {
+            if c == '.' || c == '-' {
+                parts.extend((!current.is_empty()).then(|| std::mem::take(&mut current)));
+                continue;
+            }
+            if !current.is_empty()
+                && current.chars().last().is_some_and(|l| l.is_ascii_digit()) != c.is_ascii_digit()
+            {
+                parts.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+        parts.extend((!current.is_empty()).then_some(current));
+        parts
+    };
+    let (a, b) = (parts(a), parts(b));
+    for (x, y) in a.iter().zip(&b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_semver_and_pep440_versions() {
+        assert_eq!(compare_versions("1.2.10", "1.2.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(
+            compare_versions("1.0.0-alpha.2", "1.0.0-alpha.10"),
+            Ordering::Less
+        );
+        assert_eq!(compare_versions("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("2.0rc1", "2.0"), Ordering::Less);
+        assert_eq!(compare_versions("2.0.post1", "2.0"), Ordering::Greater);
+    }
+
+    #[test]
+    fn matches_osv_ranges_and_scores_cvss() {
+        let record = serde_json::json!({
+            "id": "RUSTSEC-2024-0001",
+            "aliases": ["CVE-2024-0001"],
+            "summary": "Overflow in parser",
+            "severity": [{ "type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H" }],
+            "affected": [{
+                "package": { "ecosystem": "crates.io", "name": "parser" },
+                "ranges": [{ "type": "SEMVER", "events": [
+                    { "introduced": "0" }, { "fixed": "0.4.2" },
+                    { "introduced": "0.5.0" }, { "fixed": "0.5.1" }
+                ]}]
+            }]
+        });
+        let advisories = Advisory::from_osv(&record);
+        assert_eq!(advisories.len(), 1);
+        let advisory = &advisories[0];
+        assert_eq!(advisory.severity, Severity::Critical);
+        assert!(advisory.affects("0.3.0"));
+        assert!(!advisory.affects("0.4.2"));
+        assert!(advisory.affects("0.5.0"));
+        assert!(!advisory.affects("0.5.1"));
+        assert_eq!(advisory.fixed_after("0.3.0").as_deref(), Some("0.4.2"));
+        assert_eq!(
+            cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"),
+            Some(6.1)
+        );
+    }
+
+    #[test]
+    fn judges_license_expressions() {
+        let policy = LicensePolicy::default();
+        assert_eq!(
+            policy.judge("MIT OR GPL-3.0", true).0,
+            LicenseVerdict::Allowed
+        );
+        assert_eq!(policy.judge("GPL-3.0-only", true).0, LicenseVerdict::Denied);
+        assert_eq!(
+            policy.judge("GPL-3.0-only", false).0,
+            LicenseVerdict::Allowed
+        );
+        assert_eq!(
+            policy.judge("MIT AND LGPL-2.1", true).0,
+            LicenseVerdict::Review
+        );
+        assert_eq!(
+            policy.judge("GPL-2.0 WITH Classpath-exception-2.0", true).0,
+            LicenseVerdict::Review
+        );
+        let strict = LicensePolicy {
+            deny: vec!["MPL-2.0".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(strict.judge("MPL-2.0", false).0, LicenseVerdict::Denied);
+    }
+
+    #[test]
+    fn scans_a_workspace_with_lockfiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nlicense = \"Proprietary\"\n\n[dependencies]\nparser = \"0.4\"\nlocal = { path = \"../local\" }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("Cargo.lock"),
+            "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"parser\"\nversion = \"0.4.1\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"helper\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(root.join("web")).unwrap();
+        std::fs::write(
+            root.join("web/package.json"),
+            r#"{ "dependencies": { "left-pad": "^1.3.0" } }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("web/package-lock.json"),
+            r#"{ "lockfileVersion": 3, "packages": {
+                "": { "name": "web" },
+                "node_modules/left-pad": { "version": "1.3.0", "license": "GPL-3.0" }
+            } }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("requirements.txt"),
+            "requests[socks]==2.31.0 ; python_version > '3'\n-r other.txt\n",
+        )
+        .unwrap();
+
+        let mut database = AdvisoryDatabase::default();
+        database.merge(Advisory::from_osv(&serde_json::json!({
+            "id": "GHSA-xxxx",
+            "summary": "Bad parser",
+            "database_specific": { "severity": "MODERATE" },
+            "affected": [{
+                "package": { "ecosystem": "crates.io", "name": "parser" },
+                "ranges": [{ "type": "SEMVER", "events": [{ "introduced": "0" }, { "fixed": "0.4.2" }] }]
+            }]
+        })));
+
+        let report = scan(root, &database, &LicensePolicy::default()).unwrap();
+        assert!(report.proprietary);
+        assert_eq!(report.manifests.len(), 5);
+
+        let parser = report
+            .dependencies
+            .iter()
+            .find(|d| d.name == "parser")
+            .unwrap();
+        assert!(parser.direct && parser.resolved);
+        assert_eq!(parser.version.as_deref(), Some("0.4.1"));
+        let helper = report
+            .dependencies
+            .iter()
+            .find(|d| d.name == "helper")
+            .unwrap();
+        assert!(!helper.direct);
+        assert!(!report.dependencies.iter().any(|d| d.name == "local"));
+        let requests = report
+            .dependencies
+            .iter()
+            .find(|d| d.name == "requests")
+            .unwrap();
+        assert_eq!(requests.version.as_deref(), Some("2.31.0"));
+        assert!(requests.resolved);
+
+        assert_eq!(report.vulnerabilities.len(), 1);
+        assert_eq!(report.vulnerabilities[0].severity, Severity::Medium);
+        assert_eq!(
+            report.vulnerabilities[0].fixed_version.as_deref(),
+            Some("0.4.2")
+        );
+
+        let left_pad = report
+            .licenses
+            .iter()
+            .find(|f| f.package == "left-pad")
+            .unwrap();
+        assert_eq!(left_pad.verdict, LicenseVerdict::Denied);
+        assert!(!report.passes(Severity::Critical));
+    }
+}
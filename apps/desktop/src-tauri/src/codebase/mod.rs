@@ -2,9 +2,15 @@
  * Codebase Analysis Module
  * Workspace indexing, semantic search, and symbol resolution
  */
+pub mod dependency_graph;
+pub mod dependency_scan;
 pub mod indexer;
+pub mod project_tasks;
+pub mod review;
 
+pub use dependency_graph::{DependencyGraphState, ImpactAnalysis, ImpactRisk, ImportGraph};
 pub use indexer::{CodebaseIndexer, IndexStats, Symbol, SymbolKind};
+pub use project_tasks::TaskRunner;
 
 use anyhow::Result;
 use std::path::PathBuf;
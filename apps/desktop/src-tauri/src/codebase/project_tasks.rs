@@ -0,0 +1,728 @@
+//! Build and run task detection.
+//!
+//! Finds the build, test and run commands a workspace already defines —
+//! Cargo targets, `package.json` scripts, Makefile targets and Gradle tasks —
+//! so the UI and agents can offer "build project" or "run tests" without
+//! hand-written commands.
+//!
+//! Tasks run as a program plus arguments, never through a shell string, in
+//! the directory of the manifest that defined them. The command validator
+//! vets them first, the workspace's env files are injected, and secret values
+//! are masked in the output streamed as [`TASK_OUTPUT_EVENT`].
+
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, Notify};
+use walkdir::WalkDir;
+
+use crate::security::{CommandValidator, SafetyLevel};
+use crate::terminal::{EnvMasker, WorkspaceEnvManager};
+
+pub const TASK_STARTED_EVENT: &str = "project://task-started";
+pub const TASK_OUTPUT_EVENT: &str = "project://task-output";
+pub const TASK_FINISHED_EVENT: &str = "project://task-finished";
+
+/// Manifests deeper than this below the workspace root are ignored
+const MAX_DEPTH: usize = 3;
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".gradle",
+    ".venv",
+    "vendor",
+];
+/// Output lines kept for the run summary
+const OUTPUT_TAIL: usize = 200;
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+static MAKE_TARGET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Za-z0-9][A-Za-z0-9_.-]*)\s*:([^=]|$)").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskKind {
+    Build,
+    Test,
+    Run,
+    Lint,
+    Other,
+}
+
+impl TaskKind {
+    fn from_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        let head = name.split([':', '-', '_']).next().unwrap_or_default();
+        match head {
+            "build" | "compile" | "assemble" | "bundle" | "package" | "all" => Self::Build,
+            "test" | "tests" | "check" | "e2e" | "coverage" => Self::Test,
+            "run" | "start" | "dev" | "serve" | "preview" | "watch" => Self::Run,
+            "lint" | "clippy" | "fmt" | "format" | "typecheck" => Self::Lint,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskSource {
+    Cargo,
+    Npm,
+    Make,
+    Gradle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTask {
+    /// Unique within the workspace, e.g. `cargo:test` or `web/npm:dev`
+    pub name: String,
+    pub kind: TaskKind,
+    pub source: TaskSource,
+    pub program: String,
+    pub args: Vec<String>,
+    /// Directory the task runs in, relative to the workspace root
+    pub cwd: String,
+    /// The script body for npm scripts
+    pub description: Option<String>,
+}
+
+impl ProjectTask {
+    fn new(
+        dir: &str,
+        source: TaskSource,
+        task: &str,
+        kind: TaskKind,
+        program: &str,
+        args: &[&str],
+    ) -> Self {
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir)
+        };
+        Self {
+            name: format!("{}{}:{}", prefix, source_label(source), task),
+            kind,
+            source,
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            cwd: dir.to_string(),
+            description: None,
+        }
+    }
+
+    /// The command line, for display
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn source_label(source: TaskSource) -> &'static str {
+    match source {
+        TaskSource::Cargo => "cargo",
+        TaskSource::Npm => "npm",
+        TaskSource::Make => "make",
+        TaskSource::Gradle => "gradle",
+    }
+}
+
+/// Detect every task defined under `root`. Root tasks come first
+pub fn detect_tasks(root: &Path) -> Result<Vec<ProjectTask>> {
+    if !root.is_dir() {
+        bail!("Workspace {} does not exist", root.display());
+    }
+
+    let mut tasks = Vec::new();
+    let mut cargo_workspaces: Vec<PathBuf> = Vec::new();
+    let walker = WalkDir::new(root)
+        .max_depth(MAX_DEPTH)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !(e.file_type().is_dir()
+                    && SKIPPED_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+        });
+
+    for entry in walker
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+    {
+        let dir = entry.path();
+        let rel = dir
+            .strip_prefix(root)
+            .unwrap_or(dir)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        // Members are built by their workspace root
+        if !cargo_workspaces.iter().any(|ws| dir.starts_with(ws)) {
+            if let Some((found, is_workspace)) = cargo_tasks(dir, &rel) {
+                tasks.extend(found);
+                if is_workspace {
+                    cargo_workspaces.push(dir.to_path_buf());
+                }
+            }
+        }
+        tasks.extend(npm_tasks(dir, &rel));
+        tasks.extend(make_tasks(dir, &rel));
+        tasks.extend(gradle_tasks(dir, &rel));
+    }
+
+    tasks.sort_by_key(|t| t.cwd.matches('/').count() + usize::from(!t.cwd.is_empty()));
+    Ok(tasks)
+}
+
+fn cargo_tasks(dir: &Path, rel: &str) -> Option<(Vec<ProjectTask>, bool)> {
+    let manifest: toml::Value = std::fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()?
+        .parse()
+        .ok()?;
+    let is_workspace = manifest.get("workspace").is_some();
+    let has_binary = manifest.get("bin").is_some() || dir.join("src/main.rs").is_file();
+
+    let mut tasks = vec![
+        ProjectTask::new(
+            rel,
+            TaskSource::Cargo,
+            "build",
+            TaskKind::Build,
+            "cargo",
+            &["build"],
+        ),
+        ProjectTask::new(
+            rel,
+            TaskSource::Cargo,
+            "test",
+            TaskKind::Test,
+            "cargo",
+            &["test"],
+        ),
+        ProjectTask::new(
+            rel,
+            TaskSource::Cargo,
+            "check",
+            TaskKind::Lint,
+            "cargo",
+            &["check"],
+        ),
+        ProjectTask::new(
+            rel,
+            TaskSource::Cargo,
+            "clippy",
+            TaskKind::Lint,
+            "cargo",
+            &["clippy", "--all-targets"],
+        ),
+    ];
+    if has_binary && manifest.get("package").is_some() {
+        tasks.push(ProjectTask::new(
+            rel,
+            TaskSource::Cargo,
+            "run",
+            TaskKind::Run,
+            "cargo",
+            &["run"],
+        ));
+    }
+    Some((tasks, is_workspace))
+}
+
+fn npm_tasks(dir: &Path, rel: &str) -> Vec<ProjectTask> {
+    let Some(package) = std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+    else {
+        return Vec::new();
+    };
+    let Some(scripts) = package.get("scripts").and_then(|s| s.as_object()) else {
+        return Vec::new();
+    };
+
+    let manager = if dir.join("pnpm-lock.yaml").is_file() {
+        "pnpm"
+    } else if dir.join("yarn.lock").is_file() {
+        "yarn"
+    } else if dir.join("bun.lockb").is_file() || dir.join("bun.lock").is_file() {
+        "bun"
+    } else {
+        "npm"
+    };
+    let program = windows_shim(manager);
+
+    scripts
+        .iter()
+        // Lifecycle hooks run as part of their main script
+        .filter(|(name, _)| !name.starts_with("pre") && !name.starts_with("post"))
+        .map(|(name, body)| {
+            let mut task = ProjectTask::new(
+                rel,
+                TaskSource::Npm,
+                name,
+                TaskKind::from_name(name),
+                &program,
+                &["run", name],
+            );
+            task.description = body.as_str().map(str::to_string);
+            task
+        })
+        .collect()
+}
+
+fn make_tasks(dir: &Path, rel: &str) -> Vec<ProjectTask> {
+    let Some(makefile) = ["Makefile", "makefile", "GNUmakefile"]
+        .iter()
+        .find_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut seen = Vec::new();
+    makefile
+        .lines()
+        .filter_map(|line| MAKE_TARGET.captures(line))
+        .filter_map(|caps| {
+            let target = caps.get(1)?.as_str();
+            // Skip file targets such as `main.o`
+            if target.contains('.') || seen.iter().any(|t| t == target) {
+                return None;
+            }
+            seen.push(target.to_string());
+            Some(ProjectTask::new(
+                rel,
+                TaskSource::Make,
+                target,
+                TaskKind::from_name(target),
+                "make",
+                &[target],
+            ))
+        })
+        .collect()
+}
+
+fn gradle_tasks(dir: &Path, rel: &str) -> Vec<ProjectTask> {
+    let Some(build_file) = ["build.gradle.kts", "build.gradle"]
+        .iter()
+        .find_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+    else {
+        return Vec::new();
+    };
+
+    let program = if dir.join("gradlew").is_file() || dir.join("gradlew.bat").is_file() {
+        if cfg!(windows) {
+            dir.join("gradlew.bat").to_string_lossy().to_string()
+        } else {
+            dir.join("gradlew").to_string_lossy().to_string()
+        }
+    } else {
+        windows_shim("gradle")
+    };
+
+    let mut tasks = vec![
+        ProjectTask::new(
+            rel,
+            TaskSource::Gradle,
+            "build",
+            TaskKind::Build,
+            &program,
+            &["build"],
+        ),
+        ProjectTask::new(
+            rel,
+            TaskSource::Gradle,
+            "test",
+            TaskKind::Test,
+            &program,
+            &["test"],
+        ),
+    ];
+    if build_file.contains("application") {
+        tasks.push(ProjectTask::new(
+            rel,
+            TaskSource::Gradle,
+            "run",
+            TaskKind::Run,
+            &program,
+            &["run"],
+        ));
+    }
+    tasks
+}
+
+/// Node package managers and Gradle install `.cmd` launchers on Windows,
+/// which process spawning does not find without the extension
+fn windows_shim(program: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.cmd", program)
+    } else {
+        program.to_string()
+    }
+}
+
+/// Find a task by exact name, or the first task of a kind (`build`, `test`,
+/// `run`, `lint`) so callers can ask for "the build" without knowing the tool
+pub fn resolve_task<'a>(tasks: &'a [ProjectTask], name: &str) -> Option<&'a ProjectTask> {
+    tasks.iter().find(|t| t.name == name).or_else(|| {
+        let kind: TaskKind = serde_json::from_value(serde_json::json!(name)).ok()?;
+        tasks.iter().find(|t| t.kind == kind)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskRunStatus {
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStartedEvent {
+    pub run_id: String,
+    pub task: ProjectTask,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskOutputEvent {
+    pub run_id: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub run_id: String,
+    pub task: ProjectTask,
+    pub status: TaskRunStatus,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// The last lines of combined output, secrets masked
+    pub output_tail: Vec<String>,
+}
+
+/// Tracks running tasks so they can be cancelled
+#[derive(Default)]
+pub struct TaskRunner {
+    running: Mutex<HashMap<String, Arc<Notify>>>,
+}
+
+impl TaskRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop a running task; returns whether it was running
+    pub fn cancel(&self, run_id: &str) -> bool {
+        match self.running.lock().get(run_id) {
+            Some(cancel) => {
+                cancel.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn running(&self) -> Vec<String> {
+        self.running.lock().keys().cloned().collect()
+    }
+
+    /// Run `task` from the workspace at `root` to completion, streaming its
+    /// output to the frontend
+    pub async fn run(
+        &self,
+        app: &AppHandle,
+        root: &Path,
+        task: ProjectTask,
+        timeout: Option<Duration>,
+    ) -> Result<TaskRun> {
+        // npm scripts are shell snippets, so vet the script body as well
+        let validator = CommandValidator::new();
+        let commands = std::iter::once((task.program.as_str(), task.args.as_slice())).chain(
+            task.description
+                .as_deref()
+                .map(|body| (body, &[] as &[String])),
+        );
+        for (program, args) in commands {
+            if validator
+                .validate_command(program, args)
+                .map_err(|e| anyhow!("{}", e))?
+                == SafetyLevel::Blocked
+            {
+                bail!("Task {} runs a blocked command", task.name);
+            }
+        }
+
+        let cwd = root.join(&task.cwd);
+        let mut cmd = Command::new(&task.program);
+        cmd.args(&task.args)
+            .current_dir(&cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let masker = match app
+            .try_state::<WorkspaceEnvManager>()
+            .and_then(|manager| manager.resolve_for(&cwd))
+        {
+            Some(env) => {
+                cmd.envs(env.vars);
+                env.masker
+            }
+            None => EnvMasker::default(),
+        };
+
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to start {}", task.command_line()))?;
+
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(Notify::new());
+        self.running
+            .lock()
+            .insert(run_id.clone(), Arc::clone(&cancel));
+        tracing::info!("Running task {} ({})", task.name, run_id);
+        let _ = app.emit(
+            TASK_STARTED_EVENT,
+            TaskStartedEvent {
+                run_id: run_id.clone(),
+                task: task.clone(),
+            },
+        );
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, OutputStream::Stdout, tx.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, OutputStream::Stderr, tx);
+        }
+
+        let start = Instant::now();
+        let deadline = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(deadline);
+
+        let mut tail: VecDeque<String> = VecDeque::with_capacity(OUTPUT_TAIL);
+        let mut on_line = |stream: OutputStream, line: String| {
+            let line = masker.mask(&line);
+            let _ = app.emit(
+                TASK_OUTPUT_EVENT,
+                TaskOutputEvent {
+                    run_id: run_id.clone(),
+                    stream,
+                    line: line.clone(),
+                },
+            );
+            if tail.len() == OUTPUT_TAIL {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        };
+
+        let outcome = loop {
+            tokio::select! {
+                Some((stream, line)) = rx.recv() => on_line(stream, line),
+                status = child.wait() => break status.map(Some),
+                _ = cancel.notified() => break Ok(None),
+                _ = &mut deadline => break Err(std::io::ErrorKind::TimedOut.into()),
+            }
+        };
+        let (status, exit_code) = match outcome {
+            Ok(Some(exit)) => {
+                // Drain what the pipes still hold; background processes the
+                // task left behind may keep them open, so don't wait forever
+                let drain = async {
+                    while let Some((stream, line)) = rx.recv().await {
+                        on_line(stream, line);
+                    }
+                };
+                let _ = tokio::time::timeout(DRAIN_TIMEOUT, drain).await;
+                let status = if exit.success() {
+                    TaskRunStatus::Succeeded
+                } else {
+                    TaskRunStatus::Failed
+                };
+                (status, exit.code())
+            }
+            Ok(None) => {
+                let _ = child.kill().await;
+                (TaskRunStatus::Cancelled, None)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                let _ = child.kill().await;
+                (TaskRunStatus::TimedOut, None)
+            }
+            Err(e) => {
+                self.running.lock().remove(&run_id);
+                return Err(anyhow!("Failed to wait for task {}: {}", task.name, e));
+            }
+        };
+        self.running.lock().remove(&run_id);
+
+        let run = TaskRun {
+            run_id,
+            task,
+            status,
+            exit_code,
+            duration_ms: start.elapsed().as_millis() as u64,
+            output_tail: tail.into(),
+        };
+        tracing::info!(
+            "Task {} finished: {:?} in {} ms",
+            run.task.name,
+            run.status,
+            run.duration_ms
+        );
+        let _ = app.emit(TASK_FINISHED_EVENT, &run);
+        Ok(run)
+    }
+}
+
+fn forward_lines<R>(
+    reader: R,
+    stream: OutputStream,
+    tx: mpsc::UnboundedSender<(OutputStream, String)>,
+) where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if tx.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Tasks started by agents stop after this long unless told otherwise
+const AGENT_TASK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Run the `project_detect_tasks` or `project_run_task` agent tool
+pub async fn execute_tool(
+    app: &AppHandle,
+    tool_id: &str,
+    args: &HashMap<String, serde_json::Value>,
+) -> Result<serde_json::Value> {
+    let root = PathBuf::from(
+        args.get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Missing path parameter"))?,
+    );
+    let detect_root = root.clone();
+    let tasks = tokio::task::spawn_blocking(move || detect_tasks(&detect_root)).await??;
+
+    if tool_id == "project_detect_tasks" {
+        return Ok(serde_json::to_value(tasks)?);
+    }
+
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Missing name parameter"))?;
+    let task = resolve_task(&tasks, name)
+        .cloned()
+        .ok_or_else(|| anyhow!("No task named {} in {}", name, root.display()))?;
+    let timeout = args
+        .get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map_or(AGENT_TASK_TIMEOUT, Duration::from_secs);
+
+    let runner = app
+        .try_state::<TaskRunner>()
+        .ok_or_else(|| anyhow!("Task runner is not initialized"))?;
+    let run = runner.run(app, &root, task, Some(timeout)).await?;
+    Ok(serde_json::to_value(run)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_tasks_across_build_systems() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/core\"]\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("crates/core/src")).unwrap();
+        std::fs::write(
+            root.join("crates/core/Cargo.toml"),
+            "[package]\nname = \"core\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("web")).unwrap();
+        std::fs::write(
+            root.join("web/package.json"),
+            r#"{"scripts": {"dev": "vite", "build": "vite build", "prebuild": "tsc", "test": "vitest"}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("web/pnpm-lock.yaml"), "").unwrap();
+        std::fs::write(
+            root.join("Makefile"),
+            ".PHONY: all test\nall: build\nVAR := 1\nmain.o: main.c\ntest:\n\tcargo test\n",
+        )
+        .unwrap();
+
+        let tasks = detect_tasks(root).unwrap();
+        let names: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+
+        assert!(names.contains(&"cargo:build"));
+        assert!(!names.contains(&"cargo:run"));
+        assert!(!names.iter().any(|n| n.starts_with("crates/core")));
+        assert!(names.contains(&"make:all"));
+        assert!(names.contains(&"make:test"));
+        assert!(!names
+            .iter()
+            .any(|n| n.contains("main.o") || n.contains("VAR")));
+        assert!(names.contains(&"web/npm:dev"));
+        assert!(!names.contains(&"web/npm:prebuild"));
+
+        let dev = tasks.iter().find(|t| t.name == "web/npm:dev").unwrap();
+        assert_eq!(dev.kind, TaskKind::Run);
+        assert_eq!(dev.program, windows_shim("pnpm"));
+        assert_eq!(dev.args, vec!["run", "dev"]);
+        assert_eq!(dev.cwd, "web");
+
+        // Root tasks win when resolving by kind
+        assert_eq!(resolve_task(&tasks, "test").unwrap().name, "cargo:test");
+        assert_eq!(resolve_task(&tasks, "run").unwrap().name, "web/npm:dev");
+        assert_eq!(
+            resolve_task(&tasks, "make:all").unwrap().source,
+            TaskSource::Make
+        );
+        assert!(resolve_task(&tasks, "deploy").is_none());
+    }
+}
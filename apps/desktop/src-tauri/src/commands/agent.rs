@@ -1,10 +1,12 @@
 use crate::agent::{
-    approval::{ApprovalController, ApprovalResolution},
+    approval::{ApprovalController, ApprovalPolicy, ApprovalResolution},
     AgentConfig, AutonomousAgent, Task,
 };
+use crate::audit::{self, Actor, AuditCategory, AuditEntry, AuditOutcome};
 use crate::automation::AutomationService;
 use crate::commands::llm::LLMState;
 use crate::router::LLMRouter;
+use crate::security::{AdminUser, AuthenticatedUser, ReauthManager, SensitiveAction};
 use anyhow::Result;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
@@ -147,29 +149,135 @@ pub async fn agent_stop() -> Result<(), String> {
     Ok(())
 }
 
+fn parse_decision(
+    decision: &str,
+    trust: Option<bool>,
+    reason: Option<String>,
+) -> Result<ApprovalResolution, String> {
+    match decision.to_lowercase().as_str() {
+        "approve" | "approved" => Ok(ApprovalResolution::Approved {
+            trust: trust.unwrap_or(false),
+        }),
+        "reject" | "rejected" => Ok(ApprovalResolution::Rejected { reason }),
+        "skip" | "skipped" => Ok(ApprovalResolution::Skipped { reason }),
+        other => Err(format!("Invalid approval decision: {}", other)),
+    }
+}
+
 #[tauri::command]
 pub async fn agent_resolve_approval(
     app_handle: tauri::AppHandle,
+    user: AuthenticatedUser,
+    reauth: State<'_, ReauthManager>,
     approval_state: State<'_, ApprovalController>,
     approval_id: String,
     decision: String,
     trust: Option<bool>,
     reason: Option<String>,
 ) -> Result<(), String> {
-    let normalized = decision.to_lowercase();
-    let resolution = match normalized.as_str() {
-        "approve" | "approved" => ApprovalResolution::Approved {
-            trust: trust.unwrap_or(false),
-        },
-        "reject" | "rejected" => ApprovalResolution::Rejected { reason },
-        other => return Err(format!("Invalid approval decision: {}", other)),
-    };
+    let resolution = parse_decision(&decision, trust, reason)?;
 
+    let pending = approval_state.pending_request(&approval_id).await;
+    if matches!(resolution, ApprovalResolution::Approved { .. })
+        && pending
+            .as_ref()
+            .is_some_and(|request| request.is_dangerous_command())
+    {
+        reauth
+            .require(&app_handle, &user, SensitiveAction::ApproveDangerousCommand)
+            .await?;
+    }
+
+    apply_resolution(
+        &app_handle,
+        &user,
+        &approval_state,
+        &approval_id,
+        pending.map(|request| request.tool_name),
+        resolution,
+    )
+    .await
+}
+
+/// Apply one decision to several pending approvals. Without ids every pending
+/// approval is decided. Returns the ids that were resolved.
+#[tauri::command]
+pub async fn approval_resolve_batch(
+    app_handle: tauri::AppHandle,
+    user: AuthenticatedUser,
+    reauth: State<'_, ReauthManager>,
+    approval_state: State<'_, ApprovalController>,
+    approval_ids: Option<Vec<String>>,
+    decision: String,
+    reason: Option<String>,
+) -> Result<Vec<String>, String> {
+    let resolution = parse_decision(&decision, None, reason)?;
+
+    let mut pending = approval_state.pending_requests().await;
+    if let Some(ids) = &approval_ids {
+        pending.retain(|request| ids.contains(&request.action_id));
+    }
+    // One re-authentication covers every dangerous command in the batch
+    if matches!(resolution, ApprovalResolution::Approved { .. })
+        && pending.iter().any(|request| request.is_dangerous_command())
+    {
+        reauth
+            .require(&app_handle, &user, SensitiveAction::ApproveDangerousCommand)
+            .await?;
+    }
+
+    let mut resolved = Vec::with_capacity(pending.len());
+    for request in pending {
+        // A request may time out or be decided elsewhere while the batch runs
+        match apply_resolution(
+            &app_handle,
+            &user,
+            &approval_state,
+            &request.action_id,
+            Some(request.tool_name),
+            resolution.clone(),
+        )
+        .await
+        {
+            Ok(()) => resolved.push(request.action_id),
+            Err(e) => tracing::warn!("Skipped {} in approval batch: {}", request.action_id, e),
+        }
+    }
+    Ok(resolved)
+}
+
+async fn apply_resolution(
+    app_handle: &tauri::AppHandle,
+    user: &AuthenticatedUser,
+    approval_state: &ApprovalController,
+    approval_id: &str,
+    tool_name: Option<String>,
+    resolution: ApprovalResolution,
+) -> Result<(), String> {
     approval_state
-        .resolve(&approval_id, resolution.clone())
+        .resolve(approval_id, resolution.clone())
         .await
         .map_err(|e| format!("Failed to resolve approval: {}", e))?;
 
+    let (action, outcome) = match &resolution {
+        ApprovalResolution::Approved { trust } => (
+            if *trust {
+                "approval_trusted"
+            } else {
+                "approval_granted"
+            },
+            AuditOutcome::Success,
+        ),
+        ApprovalResolution::Rejected { .. } => ("approval_rejected", AuditOutcome::Denied),
+        ApprovalResolution::Skipped { .. } => ("approval_skipped", AuditOutcome::Denied),
+    };
+    audit::record(
+        app_handle,
+        AuditEntry::new(Actor::user(user), AuditCategory::Permission, action)
+            .target(tool_name.unwrap_or_else(|| approval_id.to_string()))
+            .outcome(outcome),
+    );
+
     match &resolution {
         ApprovalResolution::Approved { .. } => {
             let _ = app_handle.emit(
@@ -229,6 +337,25 @@ pub async fn agent_resolve_approval(
                 }),
             );
         }
+        ApprovalResolution::Skipped { reason } => {
+            let _ = app_handle.emit(
+                "approval:skipped",
+                json!({
+                    "id": approval_id,
+                    "reason": reason
+                }),
+            );
+            let _ = app_handle.emit(
+                "agent:action_update",
+                json!({
+                    "action": {
+                        "id": approval_id,
+                        "status": "skipped",
+                        "requiresApproval": false
+                    }
+                }),
+            );
+        }
     }
 
     Ok(())
@@ -252,3 +379,60 @@ pub async fn agent_list_trusted_workflows(
         .await
         .map_err(|e| format!("Failed to list trusted workflows: {}", e))
 }
+
+/// Approval policies, most specific first
+#[tauri::command]
+pub async fn approval_list_policies(
+    approval_state: State<'_, ApprovalController>,
+) -> Result<Vec<ApprovalPolicy>, String> {
+    Ok(approval_state.policies().await)
+}
+
+/// Add or replace an approval policy. Policies that auto-approve are an
+/// admin decision.
+#[tauri::command]
+pub async fn approval_set_policy(
+    app_handle: tauri::AppHandle,
+    admin: AdminUser,
+    approval_state: State<'_, ApprovalController>,
+    policy: ApprovalPolicy,
+) -> Result<(), String> {
+    let entry = AuditEntry::new(
+        Actor::user(&admin.0),
+        AuditCategory::Permission,
+        "set_approval_policy",
+    )
+    .target(policy.id.clone())
+    .detail(serde_json::to_string(&policy).unwrap_or_default());
+    approval_state
+        .set_policy(policy)
+        .await
+        .map_err(|e| format!("Failed to save approval policy: {}", e))?;
+    audit::record(&app_handle, entry);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn approval_delete_policy(
+    app_handle: tauri::AppHandle,
+    admin: AdminUser,
+    approval_state: State<'_, ApprovalController>,
+    id: String,
+) -> Result<bool, String> {
+    let removed = approval_state
+        .remove_policy(&id)
+        .await
+        .map_err(|e| format!("Failed to delete approval policy: {}", e))?;
+    if removed {
+        audit::record(
+            &app_handle,
+            AuditEntry::new(
+                Actor::user(&admin.0),
+                AuditCategory::Permission,
+                "delete_approval_policy",
+            )
+            .target(id),
+        );
+    }
+    Ok(removed)
+}
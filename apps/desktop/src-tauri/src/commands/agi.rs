@@ -1,13 +1,24 @@
+use crate::agent::file_checkpoints::{CheckpointStore, FileChange, RollbackReport};
+use crate::agent::runtime::{BudgetMetrics, RunBudget, RunRecorder, RunSummary, RunTrace};
+use crate::agi::planner::PlanStep;
 use crate::agi::{
-    AGIConfig, AGICore, AgentOrchestrator, AgentResult, AgentStatus, ExecutionContext, Goal,
-    Priority, ScoredResult,
+    global_tool_reliability, AGIConfig, AGICore, AgentOrchestrator, AgentResult, AgentStatus,
+    ExecutionContext, Goal, Priority, ScoredResult, ToolReliabilityStats,
 };
+use crate::audit::{self, Actor, AuditCategory, AuditEntry};
 use crate::automation::AutomationService;
 use crate::commands::llm::LLMState;
+use crate::db::Pool;
+use crate::orchestration::blackboard::{
+    blackboards, Artifact, BlackboardEntry, BlackboardSnapshot, WriteMode,
+};
+use crate::permissions::profiles::{self, RunPermissions};
 use crate::router::LLMRouter;
+use crate::security::AuthenticatedUser;
 use anyhow::Result;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex as TokioMutex;
@@ -18,6 +29,15 @@ pub struct SubmitGoalRequest {
     pub priority: Option<String>,
     pub deadline: Option<u64>,
     pub success_criteria: Option<Vec<String>>,
+    /// Permission profile for the run's tool calls; overrides the employee's
+    #[serde(default)]
+    pub permission_profile: Option<String>,
+    /// Hired employee (`user_employees.id`) the run works for
+    #[serde(default)]
+    pub user_employee_id: Option<String>,
+    /// Limits for this run; the default run budget applies when unset
+    #[serde(default)]
+    pub budget: Option<RunBudget>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,6 +57,12 @@ pub struct SubmitParallelGoalRequest {
     pub deadline: Option<u64>,
     pub success_criteria: Option<Vec<String>>,
     pub num_agents: Option<usize>, // Number of parallel agents (default: 8)
+    /// Permission profile for the run's tool calls; overrides the employee's
+    #[serde(default)]
+    pub permission_profile: Option<String>,
+    /// Hired employee (`user_employees.id`) the run works for
+    #[serde(default)]
+    pub user_employee_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,6 +73,11 @@ pub struct SubmitParallelGoalResponse {
 // Global AGI instance - use parking_lot::Mutex for outer, Arc<TokioMutex> for inner
 static AGI_CORE: Mutex<Option<Arc<TokioMutex<AGICore>>>> = Mutex::new(None);
 
+/// Whether `agi_init` has run
+pub(crate) fn agi_initialized() -> bool {
+    AGI_CORE.lock().is_some()
+}
+
 /// Initialize the AGI system
 #[tauri::command]
 pub async fn agi_init(
@@ -55,6 +86,8 @@ pub async fn agi_init(
     llm_state: State<'_, LLMState>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("The AGI loop")?;
+
     // Get router from LLM state
     let router = llm_state.router.lock().await;
     // Create a new router instance for AGI (since we can't clone)
@@ -96,7 +129,28 @@ pub async fn agi_init(
 
 /// Submit a goal to the AGI
 #[tauri::command]
-pub async fn agi_submit_goal(request: SubmitGoalRequest) -> Result<SubmitGoalResponse, String> {
+pub async fn agi_submit_goal(
+    pool: State<'_, Pool>,
+    recorder: State<'_, RunRecorder>,
+    request: SubmitGoalRequest,
+) -> Result<SubmitGoalResponse, String> {
+    submit_goal(&pool, &recorder, request).await
+}
+
+/// Start a goal in the running agent loop; shared with the remote API
+pub(crate) async fn submit_goal(
+    pool: &Pool,
+    recorder: &RunRecorder,
+    request: SubmitGoalRequest,
+) -> Result<SubmitGoalResponse, String> {
+    crate::kill_switch::ensure_allowed("Submitting goals")?;
+    let permissions = run_permissions(
+        pool,
+        request.permission_profile.clone(),
+        request.user_employee_id.clone(),
+    )
+    .await?;
+
     let agi_arc = {
         let agi_guard = AGI_CORE.lock();
         agi_guard
@@ -123,10 +177,15 @@ pub async fn agi_submit_goal(request: SubmitGoalRequest) -> Result<SubmitGoalRes
     };
 
     let goal_id = goal.id.clone();
+    if let Some(budget) = request.budget {
+        recorder
+            .set_run_budget(&goal_id, budget)
+            .map_err(|e| format!("Invalid run budget: {}", e))?;
+    }
 
     // Now we can safely await with tokio::Mutex
     let agi = agi_arc.lock().await;
-    agi.submit_goal(goal)
+    agi.submit_goal_with_permissions(goal, permissions)
         .await
         .map_err(|e| format!("Failed to submit goal: {}", e))?;
 
@@ -139,8 +198,15 @@ pub async fn agi_submit_goal(request: SubmitGoalRequest) -> Result<SubmitGoalRes
 /// Returns the best result after comparing all executions.
 #[tauri::command]
 pub async fn agi_submit_goal_parallel(
+    pool: State<'_, Pool>,
     request: SubmitParallelGoalRequest,
 ) -> Result<SubmitParallelGoalResponse, String> {
+    let permissions = run_permissions(
+        &pool,
+        request.permission_profile.clone(),
+        request.user_employee_id.clone(),
+    )
+    .await?;
     let agi_arc = {
         let agi_guard = AGI_CORE.lock();
         agi_guard
@@ -171,13 +237,31 @@ pub async fn agi_submit_goal_parallel(
     // Now we can safely await with tokio::Mutex
     let agi = agi_arc.lock().await;
     let best_result = agi
-        .submit_goal_parallel(goal, num_agents)
+        .submit_goal_parallel(goal, num_agents, permissions)
         .await
         .map_err(|e| format!("Failed to execute parallel goal: {}", e))?;
 
     Ok(SubmitParallelGoalResponse { best_result })
 }
 
+/// Check that the run's profile and employee exist before it starts
+async fn run_permissions(
+    pool: &Pool,
+    profile_id: Option<String>,
+    user_employee_id: Option<String>,
+) -> Result<RunPermissions, String> {
+    let permissions = RunPermissions {
+        profile_id,
+        user_employee_id,
+    };
+    let checked = permissions.clone();
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::resolve_profile(conn, &checked).map_err(|e| e.to_string())
+    })
+    .await?;
+    Ok(permissions)
+}
+
 /// Get goal status
 #[tauri::command]
 pub async fn agi_get_goal_status(goal_id: String) -> Result<GoalStatusResponse, String> {
@@ -229,6 +313,228 @@ pub async fn agi_stop() -> Result<(), String> {
     Ok(())
 }
 
+/// Recorded runs, newest first
+#[tauri::command]
+pub async fn agent_list_runs(
+    recorder: State<'_, RunRecorder>,
+    limit: Option<usize>,
+) -> Result<Vec<RunSummary>, String> {
+    recorder
+        .runs(limit.unwrap_or(50))
+        .await
+        .map_err(|e| format!("Failed to list runs: {}", e))
+}
+
+/// Plan, tool calls with inputs, outputs and screenshots, and LLM calls with
+/// token counts and costs recorded for a run
+#[tauri::command]
+pub async fn agent_get_run_trace(
+    recorder: State<'_, RunRecorder>,
+    run_id: String,
+) -> Result<Option<RunTrace>, String> {
+    recorder
+        .trace(&run_id)
+        .await
+        .map_err(|e| format!("Failed to load run trace: {}", e))
+}
+
+/// Budget runs get unless they are submitted with their own
+#[tauri::command]
+pub async fn agent_get_default_run_budget(
+    recorder: State<'_, RunRecorder>,
+) -> Result<RunBudget, String> {
+    recorder
+        .default_budget()
+        .await
+        .map_err(|e| format!("Failed to load run budget: {}", e))
+}
+
+/// Set the cost, tool call and wall-clock limits new runs get. A run that
+/// reaches one pauses and asks whether to continue.
+#[tauri::command]
+pub async fn agent_set_default_run_budget(
+    app: tauri::AppHandle,
+    user: AuthenticatedUser,
+    recorder: State<'_, RunRecorder>,
+    budget: RunBudget,
+) -> Result<(), String> {
+    let result = recorder.set_default_budget(budget).await;
+    audit::record(
+        &app,
+        AuditEntry::new(
+            Actor::user(&user),
+            AuditCategory::Automation,
+            "set_run_budget",
+        )
+        .detail(serde_json::to_string(&budget).unwrap_or_default())
+        .result(&result),
+    );
+    result.map_err(|e| format!("Failed to save run budget: {}", e))
+}
+
+/// Cost, tool calls and budget overruns of runs from the last `days` days
+#[tauri::command]
+pub async fn agent_get_budget_metrics(
+    recorder: State<'_, RunRecorder>,
+    days: Option<u32>,
+) -> Result<BudgetMetrics, String> {
+    let since = chrono::Utc::now() - chrono::Duration::days(i64::from(days.unwrap_or(30)));
+    recorder
+        .budget_metrics(since)
+        .await
+        .map_err(|e| format!("Failed to load budget metrics: {}", e))
+}
+
+/// Files a run changed, with diffs against their content before the run
+#[tauri::command]
+pub async fn agent_get_run_file_changes(
+    store: State<'_, CheckpointStore>,
+    run_id: String,
+) -> Result<Vec<FileChange>, String> {
+    store
+        .run_changes(&run_id)
+        .await
+        .map_err(|e| format!("Failed to load run file changes: {}", e))
+}
+
+/// Restore every file a failed or rejected run touched to its state before
+/// the run. No file is changed unless all of them can be restored.
+#[tauri::command]
+pub async fn agent_rollback_run(
+    app: tauri::AppHandle,
+    user: AuthenticatedUser,
+    store: State<'_, CheckpointStore>,
+    run_id: String,
+) -> Result<RollbackReport, String> {
+    let result = store.rollback_run(&run_id).await;
+    audit::record(
+        &app,
+        AuditEntry::new(
+            Actor::user(&user),
+            AuditCategory::Filesystem,
+            "rollback_run",
+        )
+        .target(run_id)
+        .result(&result),
+    );
+    result.map_err(|e| format!("Failed to roll back run: {}", e))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayRunRequest {
+    /// Return every step's recorded output instead of running its tool
+    pub mock_all_tools: bool,
+    /// Tools whose recorded output is returned instead of running them
+    pub mock_tools: Vec<String>,
+    /// Permission profile for the replay; the default profile when unset
+    pub permission_profile: Option<String>,
+    pub user_employee_id: Option<String>,
+}
+
+/// Re-execute a recorded run's plan with the same inputs as a new run,
+/// optionally against mocked tools
+#[tauri::command]
+pub async fn agent_replay_run(
+    pool: State<'_, Pool>,
+    recorder: State<'_, RunRecorder>,
+    run_id: String,
+    request: Option<ReplayRunRequest>,
+) -> Result<SubmitGoalResponse, String> {
+    crate::kill_switch::ensure_allowed("Replaying runs")?;
+    let request = request.unwrap_or_default();
+    let permissions = run_permissions(
+        &pool,
+        request.permission_profile.clone(),
+        request.user_employee_id.clone(),
+    )
+    .await?;
+
+    let trace = recorder
+        .trace(&run_id)
+        .await
+        .map_err(|e| format!("Failed to load run trace: {}", e))?
+        .ok_or_else(|| format!("No trace recorded for run {}", run_id))?;
+    let steps: Vec<PlanStep> = trace
+        .plan_steps()
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| format!("Recorded plan is unreadable: {}", e))?
+        .ok_or_else(|| format!("Run {} has no recorded plan", run_id))?;
+
+    let outcomes = trace.step_outcomes();
+    let mut mocked = HashMap::new();
+    for step in &steps {
+        if !request.mock_all_tools && !request.mock_tools.contains(&step.tool_id) {
+            continue;
+        }
+        let outcome = outcomes.get(&step.id).cloned().ok_or_else(|| {
+            format!(
+                "Step {} was never recorded, so {} cannot be mocked",
+                step.id, step.tool_id
+            )
+        })?;
+        mocked.insert(step.id.clone(), outcome);
+    }
+
+    let mut goal: Goal = serde_json::from_value(trace.run.goal.clone())
+        .map_err(|e| format!("Recorded goal is unreadable: {}", e))?;
+    goal.id = format!("goal_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+
+    let agi_arc = {
+        let agi_guard = AGI_CORE.lock();
+        agi_guard
+            .as_ref()
+            .ok_or_else(|| "AGI not initialized".to_string())?
+            .clone()
+    }; // Drop the guard immediately
+
+    let agi = agi_arc.lock().await;
+    let goal_id = agi
+        .replay_plan(goal, steps, mocked, permissions, run_id)
+        .await
+        .map_err(|e| format!("Failed to replay run: {}", e))?;
+
+    Ok(SubmitGoalResponse { goal_id })
+}
+
+/// Fill the agent's tool cache for a recurring call. `Ok(None)` when the
+/// agent has not been started.
+pub(crate) async fn warm_tool(
+    tool: &str,
+    parameters: &HashMap<String, serde_json::Value>,
+) -> Result<Option<bool>, String> {
+    let Some(agi_arc) = AGI_CORE.lock().as_ref().cloned() else {
+        return Ok(None);
+    };
+    let executor = agi_arc.lock().await.executor();
+    executor
+        .warm_tool(tool, parameters)
+        .await
+        .map(Some)
+        .map_err(|e| format!("Failed to warm {}: {}", tool, e))
+}
+
+/// Run one tool for `caller` under `permissions`. `Ok(None)` when the agent
+/// has not been started.
+pub(crate) async fn call_tool(
+    tool: &str,
+    parameters: &HashMap<String, serde_json::Value>,
+    caller: &str,
+    permissions: RunPermissions,
+) -> Result<Option<serde_json::Value>, String> {
+    let Some(agi_arc) = AGI_CORE.lock().as_ref().cloned() else {
+        return Ok(None);
+    };
+    let executor = agi_arc.lock().await.executor();
+    executor
+        .call_tool(tool, parameters, caller, permissions)
+        .await
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Parallel Agent Orchestration Commands
 // ============================================================================
@@ -262,6 +568,8 @@ pub struct SpawnParallelAgentsRequest {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpawnParallelAgentsResponse {
+    /// Run whose blackboard the agents share
+    pub run_id: String,
     pub agent_ids: Vec<String>,
 }
 
@@ -273,6 +581,8 @@ pub async fn orchestrator_init(
     llm_state: State<'_, LLMState>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("The AGI loop")?;
+
     // Get router from LLM state
     let router = llm_state.router.lock().await;
     // Create a new router instance for orchestrator
@@ -387,12 +697,57 @@ pub async fn orchestrator_spawn_parallel(
     }
 
     let orchestrator = orchestrator_arc.lock().await;
-    let agent_ids = orchestrator
-        .spawn_parallel(goals)
+    let (run_id, agent_ids) = orchestrator
+        .spawn_run(goals)
         .await
         .map_err(|e| format!("Failed to spawn parallel agents: {}", e))?;
 
-    Ok(SpawnParallelAgentsResponse { agent_ids })
+    Ok(SpawnParallelAgentsResponse { run_id, agent_ids })
+}
+
+/// Entries and artifacts the agents of an orchestrated run have shared
+#[tauri::command]
+pub async fn blackboard_get(run_id: String) -> Result<BlackboardSnapshot, String> {
+    blackboards()
+        .get(&run_id)
+        .map(|board| board.snapshot())
+        .ok_or_else(|| format!("No blackboard for run {}", run_id))
+}
+
+#[tauri::command]
+pub async fn blackboard_get_artifact(run_id: String, name: String) -> Result<Artifact, String> {
+    blackboards()
+        .get(&run_id)
+        .ok_or_else(|| format!("No blackboard for run {}", run_id))?
+        .artifact(&name)
+        .ok_or_else(|| format!("No artifact named {}", name))
+}
+
+/// Write an entry for the run's agents, e.g. to steer them mid-run
+#[tauri::command]
+pub async fn blackboard_write(
+    run_id: String,
+    key: String,
+    value: serde_json::Value,
+    mode: Option<WriteMode>,
+    expected_version: Option<u64>,
+) -> Result<BlackboardEntry, String> {
+    blackboards()
+        .get(&run_id)
+        .ok_or_else(|| format!("No blackboard for run {}", run_id))?
+        .write(
+            &key,
+            value,
+            mode.unwrap_or_default(),
+            expected_version,
+            "user",
+        )
+}
+
+/// Drop a finished run's blackboard
+#[tauri::command]
+pub async fn blackboard_close(run_id: String) -> Result<bool, String> {
+    Ok(blackboards().close(&run_id))
 }
 
 /// Get status of a specific agent
@@ -467,6 +822,36 @@ pub async fn orchestrator_cancel_all() -> Result<(), String> {
         .map_err(|e| format!("Failed to cancel all agents: {}", e))
 }
 
+/// Pause every running agent; returns how many were paused
+#[tauri::command]
+pub async fn orchestrator_pause_all() -> Result<usize, String> {
+    let orchestrator_arc = {
+        let guard = ORCHESTRATOR.lock();
+        guard
+            .as_ref()
+            .ok_or_else(|| "Orchestrator not initialized".to_string())?
+            .clone()
+    };
+
+    let orchestrator = orchestrator_arc.lock().await;
+    Ok(orchestrator.pause_all_agents().await)
+}
+
+/// Resume every paused agent; returns how many were resumed
+#[tauri::command]
+pub async fn orchestrator_resume_all() -> Result<usize, String> {
+    let orchestrator_arc = {
+        let guard = ORCHESTRATOR.lock();
+        guard
+            .as_ref()
+            .ok_or_else(|| "Orchestrator not initialized".to_string())?
+            .clone()
+    };
+
+    let orchestrator = orchestrator_arc.lock().await;
+    Ok(orchestrator.resume_all_agents().await)
+}
+
 /// Wait for all agents to complete and return results
 #[tauri::command]
 pub async fn orchestrator_wait_all() -> Result<Vec<AgentResult>, String> {
@@ -742,3 +1127,23 @@ pub async fn get_knowledge_by_category(
 
     Ok(filtered)
 }
+
+/// Get per-tool success rates, latencies and reliability scores recorded on this machine
+#[tauri::command]
+pub async fn tools_get_reliability_stats(
+    tool_id: Option<String>,
+) -> Result<Vec<ToolReliabilityStats>, String> {
+    let tracker = global_tool_reliability();
+    Ok(match tool_id {
+        Some(id) => tracker.stats_for_tool(&id),
+        None => tracker.stats(),
+    })
+}
+
+/// Clear recorded reliability history for one tool, or all tools
+#[tauri::command]
+pub async fn tools_reset_reliability_stats(tool_id: Option<String>) -> Result<(), String> {
+    global_tool_reliability()
+        .reset(tool_id.as_deref())
+        .map_err(|e| format!("Failed to reset tool reliability stats: {}", e))
+}
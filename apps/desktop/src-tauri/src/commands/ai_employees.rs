@@ -9,6 +9,7 @@ pub struct AIEmployeeState {
     pub executor: Arc<executor::AIEmployeeExecutor>,
     pub marketplace: Arc<Mutex<marketplace::EmployeeMarketplace>>,
     pub registry: Arc<Mutex<registry::AIEmployeeRegistry>>,
+    pub pipelines: Arc<pipelines::EmployeePipelineManager>,
 }
 
 /// Get all available AI employees
@@ -128,6 +129,7 @@ pub async fn ai_employees_execute_task(
     task_id: String,
     state: State<'_, AIEmployeeState>,
 ) -> StdResult<TaskResult, String> {
+    crate::kill_switch::ensure_allowed("Executing employee tasks")?;
     state
         .executor
         .execute_task(&task_id)
@@ -167,6 +169,7 @@ pub async fn ai_employees_run_demo(
     employee_id: String,
     state: State<'_, AIEmployeeState>,
 ) -> StdResult<DemoResult, String> {
+    crate::kill_switch::ensure_allowed("Running employee demos")?;
     state
         .executor
         .run_demo(&employee_id)
@@ -247,3 +250,60 @@ pub async fn ai_employees_initialize(
     registry.initialize().map_err(|e| e.to_string())?;
     registry.count().map_err(|e| e.to_string())
 }
+
+/// Create a pipeline that chains hired employees
+#[tauri::command]
+pub async fn employee_pipelines_create(
+    user_id: String,
+    name: String,
+    description: Option<String>,
+    stages: Vec<pipelines::PipelineStage>,
+    state: State<'_, AIEmployeeState>,
+) -> StdResult<pipelines::EmployeePipeline, String> {
+    state
+        .pipelines
+        .create(&user_id, &name, description, stages)
+        .map_err(|e| e.to_string())
+}
+
+/// Run a pipeline until it completes, fails or reaches an approval gate
+#[tauri::command]
+pub async fn employee_pipelines_execute(
+    pipeline_id: String,
+    input: HashMap<String, serde_json::Value>,
+    state: State<'_, AIEmployeeState>,
+) -> StdResult<pipelines::PipelineRun, String> {
+    crate::kill_switch::ensure_allowed("Executing employee pipelines")?;
+    state
+        .pipelines
+        .execute(&pipeline_id, input)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get a pipeline run with per-stage metrics
+#[tauri::command]
+pub async fn employee_pipelines_status(
+    run_id: String,
+    state: State<'_, AIEmployeeState>,
+) -> StdResult<pipelines::PipelineRun, String> {
+    state.pipelines.status(&run_id).map_err(|e| e.to_string())
+}
+
+/// Approve or reject the stage a pipeline run is waiting on
+#[tauri::command]
+pub async fn employee_pipelines_approve(
+    run_id: String,
+    approved: bool,
+    note: Option<String>,
+    state: State<'_, AIEmployeeState>,
+) -> StdResult<pipelines::PipelineRun, String> {
+    if approved {
+        crate::kill_switch::ensure_allowed("Executing employee pipelines")?;
+    }
+    state
+        .pipelines
+        .approve(&run_id, approved, note)
+        .await
+        .map_err(|e| e.to_string())
+}
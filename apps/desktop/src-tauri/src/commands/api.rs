@@ -1,11 +1,12 @@
 use std::collections::HashMap;
-use tauri::State;
+use tauri::{AppHandle, State};
 use tokio::sync::Mutex;
 
 use crate::api::{
     ApiClient, ApiRequest, ApiResponse, OAuth2Client, OAuth2Config, PkceChallenge, RequestTemplate,
     ResponseParser, TokenResponse,
 };
+use crate::cancellation::global_operations;
 
 /// State for managing API clients
 pub struct ApiState {
@@ -38,10 +39,13 @@ impl ApiState {
     }
 }
 
-/// Execute an API request
+/// Execute an API request. Runs as a cancellable operation under
+/// `operation_id` when given.
 #[tauri::command]
 pub async fn api_request(
     request: ApiRequest,
+    operation_id: Option<String>,
+    app: AppHandle,
     state: State<'_, ApiState>,
 ) -> Result<ApiResponse, String> {
     tracing::info!(
@@ -50,10 +54,11 @@ pub async fn api_request(
         request.url
     );
 
-    state
-        .client
-        .execute(request)
+    global_operations()
+        .begin("api_request", operation_id)
+        .run_with_events(&app, state.client.execute(request))
         .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| format!("API request failed: {}", e))
 }
 
@@ -0,0 +1,37 @@
+use tauri::State;
+
+use crate::audit::{self, AuditQuery, AuditRecord, ExportFormat};
+use crate::db::Pool;
+use crate::security::AdminUser;
+
+/// Audit records matching the filters, newest first
+#[tauri::command]
+pub async fn audit_query(
+    _admin: AdminUser,
+    pool: State<'_, Pool>,
+    filters: Option<AuditQuery>,
+) -> Result<Vec<AuditRecord>, String> {
+    let filters = filters.unwrap_or_default();
+    pool.run(move |conn| audit::query(conn, &filters))
+        .await
+        .map_err(|e| format!("Failed to query audit log: {}", e))
+}
+
+/// Matching records as JSON Lines or CSV for compliance review. Without an
+/// explicit limit every matching record is exported.
+#[tauri::command]
+pub async fn audit_export(
+    _admin: AdminUser,
+    pool: State<'_, Pool>,
+    filters: Option<AuditQuery>,
+    format: ExportFormat,
+) -> Result<String, String> {
+    let mut filters = filters.unwrap_or_default();
+    filters.limit = filters.limit.or(Some(i64::MAX as usize));
+    pool.run(move |conn| {
+        let records = audit::query(conn, &filters)?;
+        audit::export(&records, format)
+    })
+    .await
+    .map_err(|e| format!("Failed to export audit log: {}", e))
+}
@@ -0,0 +1,139 @@
+use crate::autocomplete::{
+    self, AutocompleteIndex, AutocompleteState, Suggestion, SuggestionKind, MAX_FILES,
+};
+use crate::commands::{AppDatabase, WorkspaceIndexState};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Mutex;
+
+/// Time a keystroke may spend on suggestions, including an index rebuild
+const LATENCY_BUDGET: Duration = Duration::from_millis(50);
+const DEFAULT_RESULT_LIMIT: usize = 8;
+
+/// Suggestions for the word being typed in the chat input. `kinds` defaults
+/// to what the prefix's trigger character implies (`/` or `@`).
+///
+/// Answers within [`LATENCY_BUDGET`]: a stale index keeps serving while its
+/// replacement builds in the background, and matching stops at the deadline.
+#[tauri::command]
+pub async fn autocomplete_query(
+    prefix: String,
+    kinds: Option<Vec<SuggestionKind>>,
+    limit: Option<usize>,
+    app: AppHandle,
+    state: State<'_, AutocompleteState>,
+) -> Result<Vec<Suggestion>, String> {
+    let deadline = Instant::now() + LATENCY_BUDGET;
+    let kinds = kinds
+        .filter(|kinds| !kinds.is_empty())
+        .unwrap_or_else(|| SuggestionKind::for_prefix(&prefix));
+
+    let index = match state.cached() {
+        Some(index) => Some(index),
+        None if state.begin_rebuild() => {
+            let rebuild = tauri::async_runtime::spawn(rebuild_index(app));
+            match tokio::time::timeout_at(deadline.into(), rebuild).await {
+                Ok(result) => {
+                    Some(result.map_err(|e| format!("Autocomplete task failed: {}", e))??)
+                }
+                Err(_) => state.latest(),
+            }
+        }
+        None => state.latest(),
+    };
+
+    Ok(index
+        .map(|index| {
+            index.query(
+                &prefix,
+                &kinds,
+                limit.unwrap_or(DEFAULT_RESULT_LIMIT),
+                deadline,
+            )
+        })
+        .unwrap_or_default())
+}
+
+/// Record that the user accepted a suggestion, so it ranks higher next time
+#[tauri::command]
+pub async fn autocomplete_record_usage(
+    kind: SuggestionKind,
+    id: String,
+    db: State<'_, AppDatabase>,
+    state: State<'_, AutocompleteState>,
+) -> Result<(), String> {
+    let conn = db.conn.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = conn
+            .lock()
+            .map_err(|e| format!("Failed to lock database: {}", e))?;
+        autocomplete::record_usage(&conn, kind, &id)
+            .map_err(|e| format!("Failed to record autocomplete usage: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Autocomplete task failed: {}", e))??;
+
+    state.invalidate();
+    Ok(())
+}
+
+/// Build the index and cache it. Runs as its own task so it still completes
+/// when the query that started it has given up waiting.
+async fn rebuild_index(app: AppHandle) -> Result<Arc<AutocompleteIndex>, String> {
+    let files = workspace_files(&app);
+
+    let conn = app.state::<AppDatabase>().conn.clone();
+    let built = tokio::task::spawn_blocking(move || {
+        let conn = conn
+            .lock()
+            .map_err(|e| format!("Database lock poisoned: {}", e))?;
+        AutocompleteIndex::build(&conn, files)
+            .map_err(|e| format!("Failed to build autocomplete index: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Autocomplete index task failed: {}", e))
+    .and_then(|result| result);
+
+    let state = app.state::<AutocompleteState>();
+    match built {
+        Ok(index) => {
+            tracing::debug!(candidates = index.len(), "Autocomplete index rebuilt");
+            Ok(state.store(index))
+        }
+        Err(e) => {
+            state.abandon_rebuild();
+            Err(e)
+        }
+    }
+}
+
+/// Relative paths from the workspace index. Skipped rather than awaited
+/// while indexing holds the lock.
+fn workspace_files(app: &AppHandle) -> Vec<String> {
+    let Some(state) = app.try_state::<Arc<Mutex<WorkspaceIndexState>>>() else {
+        return Vec::new();
+    };
+    let Ok(state) = state.try_lock() else {
+        return Vec::new();
+    };
+    let Ok(index) = state.index.try_lock() else {
+        return Vec::new();
+    };
+    let Some(index) = index.as_ref() else {
+        return Vec::new();
+    };
+
+    index
+        .files
+        .iter()
+        .take(MAX_FILES)
+        .map(|file| {
+            file.path
+                .strip_prefix(&index.root_path)
+                .unwrap_or(&file.path)
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect()
+}
@@ -16,6 +16,9 @@ pub struct SubmitTaskRequest {
     pub description: Option<String>,
     pub priority: String, // "Low", "Normal", or "High"
     pub payload: Option<String>,
+    /// Conversation the task belongs to; it then moves with a session handoff
+    #[serde(default)]
+    pub conversation_id: Option<i64>,
 }
 
 /// Task filter request for background tasks
@@ -32,6 +35,7 @@ pub async fn bg_submit_task(
     request: SubmitTaskRequest,
     state: State<'_, TaskManagerState>,
 ) -> Result<String, String> {
+    crate::kill_switch::ensure_allowed("Submitting background tasks")?;
     let priority = match request.priority.as_str() {
         "Low" => Priority::Low,
         "Normal" => Priority::Normal,
@@ -39,9 +43,17 @@ pub async fn bg_submit_task(
         _ => Priority::Normal,
     };
 
+    let mut task = Task::new(request.name, request.description, priority);
+    if let Some(payload) = request.payload {
+        task = task.with_payload(payload);
+    }
+    if let Some(conversation_id) = request.conversation_id {
+        task = task.with_conversation(conversation_id);
+    }
+
     state
         .0
-        .submit(request.name, request.description, priority, request.payload)
+        .submit_task(task)
         .await
         .map_err(|e| format!("Failed to submit task: {}", e))
 }
@@ -78,6 +90,7 @@ pub async fn bg_resume_task(
     task_id: String,
     state: State<'_, TaskManagerState>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Resuming background tasks")?;
     state
         .0
         .resume(&task_id)
@@ -124,6 +137,7 @@ pub async fn bg_list_tasks(
     let filter = TaskFilter {
         status,
         priority,
+        conversation_id: None,
         limit: request.limit,
     };
 
@@ -142,5 +156,6 @@ pub async fn bg_get_task_stats(
     state
         .0
         .stats()
+        .await
         .map_err(|e| format!("Failed to get task stats: {}", e))
 }
@@ -6,12 +6,17 @@ use tauri::{AppHandle, Emitter, State};
 use tokio::sync::{Mutex, MutexGuard};
 use uuid::Uuid;
 
+use crate::agi::skills::Skill;
 use crate::browser::advanced::Cookie;
 use crate::browser::{
-    AdvancedBrowserOps, BrowserOptions, BrowserState, BrowserType, ClickOptions, DomOperations,
-    ElementState, ExecuteOptions, FormField, ImageFormat, NavigationOptions, ScreenshotOptions,
-    TypeOptions,
+    global_selector_healing, page_ws_url, AdvancedBrowserOps, BrowserHandle, BrowserOptions,
+    BrowserPool, BrowserPoolStats, BrowserProfile, BrowserProfileInput, BrowserProfileManager,
+    BrowserRecorder, BrowserState, BrowserType, ClickOptions, DomOperations, DownloadManager,
+    DownloadOptions, DownloadRecord, DownloadSessionInfo, ElementState, ExecuteOptions, FormField,
+    HealingStats, ImageFormat, NavigationOptions, ProxySettings, RecordedScript, ScreenshotOptions,
+    TypeOptions, WaitForDownload,
 };
+use crate::orchestration::WorkflowDefinition;
 
 /// Browser state wrapper for Tauri
 pub struct BrowserStateWrapper(pub Arc<Mutex<BrowserState>>);
@@ -55,6 +60,7 @@ pub async fn browser_launch(
     headless: bool,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<String, String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!(
         "Launching {} browser (headless: {})",
         browser_type,
@@ -185,6 +191,7 @@ pub async fn browser_navigate(
     url: String,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Navigating tab {} to {}", tab_id, url);
 
     // Validate tab_id
@@ -341,6 +348,7 @@ pub async fn browser_click(
     selector: String,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Clicking element {} in tab {}", selector, tab_id);
 
     // Validate inputs
@@ -410,6 +418,7 @@ pub async fn browser_type(
     text: String,
     _state: State<'_, BrowserStateWrapper>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Typing text into {} in tab {}", selector, tab_id);
 
     let options = TypeOptions::default();
@@ -618,6 +627,7 @@ pub async fn browser_evaluate(
     script: String,
     _state: State<'_, BrowserStateWrapper>,
 ) -> Result<serde_json::Value, String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     // Validate inputs
     if tab_id.trim().is_empty() {
         return Err("Tab ID cannot be empty".to_string());
@@ -717,6 +727,7 @@ pub async fn browser_execute_async_js(
     retry_count: Option<u32>,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<serde_json::Value, String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Executing async JS in tab: {}", tab_id);
 
     let browser_state = state.inner().lock().await;
@@ -782,6 +793,7 @@ pub async fn browser_fill_form(
     fields: Vec<FormField>,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Filling form with {} fields", fields.len());
 
     let browser_state = state.inner().lock().await;
@@ -803,6 +815,7 @@ pub async fn browser_drag_and_drop(
     target_selector: String,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Dragging {} to {}", source_selector, target_selector);
 
     let browser_state = state.inner().lock().await;
@@ -825,6 +838,7 @@ pub async fn browser_upload_file(
     file_path: String,
     state: State<'_, BrowserStateWrapper>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
     tracing::info!("Uploading file {} to {}", file_path, selector);
 
     // Validate inputs
@@ -1647,3 +1661,241 @@ pub async fn find_by_role(
         .cloned()
         .ok_or_else(|| "Result is not an array".to_string())
 }
+
+/// Create a named browser profile with its own cookies, storage, extensions and proxy
+#[tauri::command]
+pub async fn browser_create_profile(
+    input: BrowserProfileInput,
+    profiles: State<'_, Arc<BrowserProfileManager>>,
+) -> Result<BrowserProfile, String> {
+    profiles
+        .create(input)
+        .await
+        .map_err(|e| format!("Failed to create browser profile: {}", e))
+}
+
+/// List browser profiles
+#[tauri::command]
+pub async fn browser_list_profiles(
+    profiles: State<'_, Arc<BrowserProfileManager>>,
+) -> Result<Vec<BrowserProfile>, String> {
+    profiles
+        .list()
+        .await
+        .map_err(|e| format!("Failed to list browser profiles: {}", e))
+}
+
+/// Set or clear a profile's proxy; applies from the next launch
+#[tauri::command]
+pub async fn browser_set_profile_proxy(
+    profile_id: String,
+    proxy: Option<ProxySettings>,
+    profiles: State<'_, Arc<BrowserProfileManager>>,
+) -> Result<BrowserProfile, String> {
+    profiles
+        .set_proxy(&profile_id, proxy)
+        .await
+        .map_err(|e| format!("Failed to update profile proxy: {}", e))
+}
+
+/// Delete a profile along with its cookies and storage
+#[tauri::command]
+pub async fn browser_delete_profile(
+    profile_id: String,
+    state: State<'_, BrowserStateWrapper>,
+    profiles: State<'_, Arc<BrowserProfileManager>>,
+) -> Result<(), String> {
+    let profile = profiles.get(&profile_id).await.map_err(|e| e.to_string())?;
+    profiles
+        .delete(&profile.id)
+        .await
+        .map_err(|e| format!("Failed to delete browser profile: {}", e))?;
+
+    let browser_state = state.inner().lock().await;
+    browser_state
+        .playwright
+        .lock()
+        .await
+        .unregister_browser(&profile.id)
+        .await;
+    Ok(())
+}
+
+/// Launch a profile's browser, or re-attach to it if it is already running
+#[tauri::command]
+pub async fn browser_launch_profile(
+    profile_id: String,
+    headless: Option<bool>,
+    state: State<'_, BrowserStateWrapper>,
+    profiles: State<'_, Arc<BrowserProfileManager>>,
+) -> Result<BrowserHandle, String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
+    let handle = profiles
+        .launch(&profile_id, headless.unwrap_or(false))
+        .await
+        .map_err(|e| format!("Failed to launch browser profile: {}", e))?;
+
+    let browser_state = state.inner().lock().await;
+    browser_state
+        .playwright
+        .lock()
+        .await
+        .register_browser(handle.clone())
+        .await;
+    Ok(handle)
+}
+
+/// Close a profile's browser; its cookies and storage are kept
+#[tauri::command]
+pub async fn browser_stop_profile(
+    profile_id: String,
+    state: State<'_, BrowserStateWrapper>,
+    profiles: State<'_, Arc<BrowserProfileManager>>,
+) -> Result<(), String> {
+    let profile = profiles.get(&profile_id).await.map_err(|e| e.to_string())?;
+    profiles
+        .stop(&profile.id)
+        .await
+        .map_err(|e| format!("Failed to stop browser profile: {}", e))?;
+
+    let browser_state = state.inner().lock().await;
+    browser_state
+        .playwright
+        .lock()
+        .await
+        .unregister_browser(&profile.id)
+        .await;
+    Ok(())
+}
+
+/// Start recording what the user does in a tab; returns the recording ID
+#[tauri::command]
+pub async fn browser_start_recording(
+    tab_id: String,
+    recorder: State<'_, BrowserRecorder>,
+) -> Result<String, String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
+    recorder
+        .start(&tab_id, page_ws_url(&tab_id))
+        .await
+        .map_err(|e| format!("Failed to start recording: {}", e))
+}
+
+/// The steps a recording has captured so far
+#[tauri::command]
+pub async fn browser_recording_snapshot(
+    recording_id: String,
+    recorder: State<'_, BrowserRecorder>,
+) -> Result<RecordedScript, String> {
+    recorder
+        .snapshot(&recording_id)
+        .await
+        .ok_or_else(|| format!("No recording {}", recording_id))
+}
+
+#[tauri::command]
+pub async fn browser_stop_recording(
+    recording_id: String,
+    recorder: State<'_, BrowserRecorder>,
+) -> Result<RecordedScript, String> {
+    recorder
+        .stop(&recording_id)
+        .await
+        .map_err(|e| format!("Failed to stop recording: {}", e))
+}
+
+/// Turn a recorded script into a skill; save it with `skill_save`
+#[tauri::command]
+pub async fn browser_recording_to_skill(
+    script: RecordedScript,
+    name: String,
+    description: String,
+) -> Result<Skill, String> {
+    script
+        .to_skill(name, description)
+        .map_err(|e| e.to_string())
+}
+
+/// Turn a recorded script into a draft workflow; save it with `create_workflow`
+#[tauri::command]
+pub async fn browser_recording_to_workflow(
+    script: RecordedScript,
+    name: String,
+) -> Result<WorkflowDefinition, String> {
+    script.to_workflow(name).map_err(|e| e.to_string())
+}
+
+/// Selector lookup and healing statistics per workflow, or for one workflow
+#[tauri::command]
+pub async fn browser_healing_stats(
+    workflow_id: Option<String>,
+) -> Result<Vec<HealingStats>, String> {
+    Ok(global_selector_healing().stats(workflow_id.as_deref()))
+}
+
+/// Contexts, leases and queued requests of the parallel-agent browser pool
+#[tauri::command]
+pub async fn browser_pool_stats(
+    pool: State<'_, Arc<BrowserPool>>,
+) -> Result<BrowserPoolStats, String> {
+    Ok(pool.stats())
+}
+
+/// Save the tab's downloads to `directory` (or a directory of its own) and,
+/// with `project_id`, add each finished file to that project's knowledge base
+#[tauri::command]
+pub async fn browser_enable_downloads(
+    tab_id: String,
+    directory: Option<String>,
+    project_id: Option<String>,
+    downloads: State<'_, Arc<DownloadManager>>,
+) -> Result<DownloadSessionInfo, String> {
+    crate::kill_switch::ensure_allowed("Browser automation")?;
+    downloads
+        .enable(
+            &tab_id,
+            page_ws_url(&tab_id),
+            DownloadOptions {
+                directory,
+                project_id,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to enable downloads: {}", e))
+}
+
+/// Where the tab's downloads go and what it has downloaded so far
+#[tauri::command]
+pub async fn browser_list_downloads(
+    tab_id: String,
+    downloads: State<'_, Arc<DownloadManager>>,
+) -> Result<DownloadSessionInfo, String> {
+    downloads
+        .session(&tab_id)
+        .await
+        .ok_or_else(|| format!("Downloads of tab {} are not captured", tab_id))
+}
+
+/// Wait for the tab's next finished download
+#[tauri::command]
+pub async fn browser_wait_for_download(
+    tab_id: String,
+    options: Option<WaitForDownload>,
+    downloads: State<'_, Arc<DownloadManager>>,
+) -> Result<DownloadRecord, String> {
+    downloads
+        .wait(&tab_id, options.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn browser_disable_downloads(
+    tab_id: String,
+    downloads: State<'_, Arc<DownloadManager>>,
+) -> Result<(), String> {
+    downloads
+        .disable(&tab_id)
+        .await
+        .map_err(|e| format!("Failed to disable downloads: {}", e))
+}
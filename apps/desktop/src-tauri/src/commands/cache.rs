@@ -1,7 +1,9 @@
 use super::{llm::LLMState, AppDatabase};
+use crate::cache::warmup::{self, WarmupReport, WarmupRun};
+use crate::router::semantic_cache::{self, CacheHitRate};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 
 /// Statistics for a specific cache type
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +45,10 @@ pub struct CacheSettings {
     pub ttl_seconds: Option<u64>,
     pub max_entries: Option<usize>,
     pub enabled: Option<bool>,
+    /// Minimum cosine similarity for serving a deterministic request from a similar prompt
+    pub semantic_threshold: Option<f32>,
+    /// Pre-warm recurring prompts and tool calls while the app is idle
+    pub warmup_enabled: Option<bool>,
 }
 
 /// Cache analytics data
@@ -52,6 +58,8 @@ pub struct CacheAnalytics {
     pub provider_breakdown: Vec<ProviderCacheBreakdown>,
     pub total_cost_saved: f64,
     pub total_tokens_saved: u64,
+    pub hit_rate: CacheHitRate,
+    pub warmup: WarmupReport,
 }
 
 /// Information about a frequently cached query
@@ -213,18 +221,40 @@ pub async fn cache_get_size(db: State<'_, AppDatabase>) -> Result<f64, String> {
 #[tauri::command]
 pub async fn cache_configure(
     settings: CacheSettings,
-    _llm_state: State<'_, LLMState>,
+    db: State<'_, AppDatabase>,
+    llm_state: State<'_, LLMState>,
 ) -> Result<(), String> {
     // Note: Current CacheManager doesn't support runtime reconfiguration
     // This is a placeholder for future implementation
 
     tracing::info!(
-        "Cache configuration request received: ttl={:?}s, max_entries={:?}, enabled={:?}",
+        "Cache configuration request received: ttl={:?}s, max_entries={:?}, enabled={:?}, semantic_threshold={:?}, warmup_enabled={:?}",
         settings.ttl_seconds,
         settings.max_entries,
-        settings.enabled
+        settings.enabled,
+        settings.semantic_threshold,
+        settings.warmup_enabled
     );
 
+    if let Some(enabled) = settings.warmup_enabled {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        warmup::set_enabled(&conn, enabled)
+            .map_err(|e| format!("Failed to save warmup setting: {}", e))?;
+    }
+
+    if let Some(threshold) = settings.semantic_threshold {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(format!(
+                "Invalid semantic threshold: {}. Must be between 0.0 and 1.0",
+                threshold
+            ));
+        }
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        semantic_cache::save_threshold(&conn, threshold)
+            .map_err(|e| format!("Failed to save semantic threshold: {}", e))?;
+        llm_state.semantic_cache.set_threshold(threshold);
+    }
+
     // TODO: Implement runtime cache configuration
     // This would require making LLMState's cache_manager mutable or
     // refactoring CacheManager to support runtime configuration updates
@@ -232,16 +262,37 @@ pub async fn cache_configure(
     Ok(())
 }
 
-/// Warm up cache with common queries
+/// Warm up the cache with the given queries, then with every recurring
+/// prompt and tool call that is due
 #[tauri::command]
-pub async fn cache_warmup(queries: Vec<String>) -> Result<(), String> {
-    // Placeholder for future cache warmup implementation
+pub async fn cache_warmup(
+    queries: Option<Vec<String>>,
+    app: AppHandle,
+    llm_state: State<'_, LLMState>,
+) -> Result<WarmupRun, String> {
+    let queries = queries.unwrap_or_default();
     tracing::info!("Cache warmup requested for {} queries", queries.len());
 
-    // TODO: Implement cache warmup by pre-computing responses for common queries
-    // This would involve calling the LLM router for each query and storing the results
+    let mut run = WarmupRun::default();
+    for query in &queries {
+        let router = llm_state.router.lock().await;
+        match warmup::warming(router.send_message(query, None)).await {
+            Ok(_) => run.llm_warmed += 1,
+            Err(e) => {
+                tracing::warn!("Failed to warm cache for query: {}", e);
+                run.failed += 1;
+            }
+        }
+    }
 
-    Ok(())
+    let patterns = warmup::run(&app, true)
+        .await
+        .map_err(|e| format!("Cache warmup failed: {}", e))?;
+    run.llm_warmed += patterns.llm_warmed;
+    run.tools_warmed += patterns.tools_warmed;
+    run.already_fresh += patterns.already_fresh;
+    run.failed += patterns.failed;
+    Ok(run)
 }
 
 /// Export cache entries for backup
@@ -361,11 +412,19 @@ pub async fn cache_get_analytics(db: State<'_, AppDatabase>) -> Result<CacheAnal
         )
         .map_err(|e| format!("Failed to calculate total tokens saved: {}", e))?;
 
+    let hit_rate = semantic_cache::hit_rate(&conn)
+        .map_err(|e| format!("Failed to calculate cache hit rate: {}", e))?;
+
+    let warmup = warmup::report(&conn, &hit_rate)
+        .map_err(|e| format!("Failed to build warmup report: {}", e))?;
+
     Ok(CacheAnalytics {
         most_cached_queries: most_cached,
         provider_breakdown,
         total_cost_saved,
         total_tokens_saved: total_tokens_saved as u64,
+        hit_rate,
+        warmup,
     })
 }
 
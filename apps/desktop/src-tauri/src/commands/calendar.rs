@@ -174,7 +174,7 @@ pub async fn calendar_list_calendars(
 
     let calendars = state.manager.list_calendars(&account_id).await?;
 
-    persist_account(&state, &app, &account_id)?;
+    persist_account(&state.manager, &app, &account_id)?;
 
     Ok(calendars)
 }
@@ -195,7 +195,7 @@ pub async fn calendar_list_events(
 
     let response = state.manager.list_events(&account_id, &request).await?;
 
-    persist_account(&state, &app, &account_id)?;
+    persist_account(&state.manager, &app, &account_id)?;
 
     Ok(response)
 }
@@ -222,7 +222,7 @@ pub async fn calendar_create_event(
 
     let event = state.manager.create_event(&account_id, &request).await?;
 
-    persist_account(&state, &app, &account_id)?;
+    persist_account(&state.manager, &app, &account_id)?;
 
     app.emit("calendar:event_created", &event)
         .map_err(|e| Error::Other(format!("Failed to emit event: {}", e)))?;
@@ -253,7 +253,7 @@ pub async fn calendar_update_event(
         .update_event(&account_id, &calendar_id, &event_id, &request)
         .await?;
 
-    persist_account(&state, &app, &account_id)?;
+    persist_account(&state.manager, &app, &account_id)?;
 
     app.emit("calendar:event_updated", &event)
         .map_err(|e| Error::Other(format!("Failed to emit event: {}", e)))?;
@@ -287,7 +287,7 @@ pub async fn calendar_delete_event(
         .delete_event(&account_id, &calendar_id, &event_id)
         .await?;
 
-    persist_account(&state, &app, &account_id)?;
+    persist_account(&state.manager, &app, &account_id)?;
 
     #[derive(Serialize)]
     struct DeletedEvent {
@@ -534,12 +534,13 @@ fn delete_calendar_account(conn: &Connection, account_id: &str) -> Result<()> {
     Ok(())
 }
 
-fn persist_account(
-    state: &State<'_, CalendarState>,
+/// Save the account's current token, e.g. after it was refreshed
+pub(crate) fn persist_account(
+    manager: &CalendarManager,
     app: &AppHandle,
     account_id: &str,
 ) -> Result<()> {
-    if let Some(info) = state.manager.account_info(account_id) {
+    if let Some(info) = manager.account_info(account_id) {
         let conn = open_connection(app)?;
         let updated_at = Utc::now().timestamp();
         insert_calendar_account(&conn, account_id, &info, updated_at)?;
@@ -1,21 +1,25 @@
 use super::llm::LLMState;
 use crate::agent::approval::ApprovalController;
-// TODO: Re-enable auto-compaction once ContextManager API is compatible with chat.rs
-// The deleted agent/context_compactor used std::sync::Mutex, but agi::ContextManager
-// requires tokio::sync::Mutex. Need to either:
-// 1. Change LLMState to use tokio::sync::Mutex, or
-// 2. Create an adapter/wrapper, or
-// 3. Port ContextCompactor functionality to a chat-specific helper
-// use crate::agi::ContextManager;
+use crate::agi::tool_costs::{self, global_tool_costs, ToolCostBreakdown, ToolPricing};
+use crate::cancellation::{cancellable, Cancelled};
+use crate::db::branches::{self, ConversationBranch};
 use crate::db::models::{
     Conversation, ConversationCostBreakdown, CostTimeseriesPoint, Message, MessageRole,
-    ProviderCostBreakdown,
+    ProviderCostBreakdown, ToolCallRecord,
 };
+use crate::db::pagination::{Page, PageRequest};
 use crate::db::repository;
+use crate::db::Pool;
+use crate::orchestration::conversation_workflow::{self, ConversationWorkflowDraft};
 use crate::router::{
     cache_manager::{CacheManager, CacheRecord},
+    context_window::{ContextUsage, ContextWindowManager},
+    cost_calculator::CostCalculator,
+    cost_forecast::{self, CostForecast, SectionTokens},
     llm_router::{CostPriority, RouteOutcome, RouterContext, RouterPreferences, RoutingStrategy},
-    ChatMessage as RouterChatMessage, LLMRequest, LLMResponse, Provider,
+    token_counter::TokenCounter,
+    tool_executor::ToolExecutor,
+    ChatMessage as RouterChatMessage, LLMRequest, LLMResponse, Provider, ToolDefinition,
 };
 use chrono::{Datelike, Duration as ChronoDuration, TimeZone, Utc};
 use futures_util::StreamExt;
@@ -31,86 +35,76 @@ pub struct AppDatabase {
     pub conn: Arc<Mutex<Connection>>,
 }
 
-/* TODO: Re-enable auto-compaction once ContextManager API is compatible
-/// Auto-compact conversation history if needed (like Cursor/Claude Code)
-async fn auto_compact_conversation(db: &AppDatabase, conversation_id: i64) -> Result<(), String> {
-    let messages = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-        repository::list_messages(&conn, conversation_id)
-            .map_err(|e| format!("Failed to list messages: {}", e))?
-    };
-
-    // Auto-compact context if needed
-    let compactor = ContextCompactor::with_default_config();
-
-    if compactor.should_compact(&messages) {
-        info!(
-            "Auto-compacting conversation {} ({} messages, {} tokens)",
+/// Conversation history to send, kept inside the model's context window by
+/// folding older turns into the conversation's rolling summary. `fixed` are
+/// the other parts of the prompt the history has to leave room for.
+async fn windowed_history(
+    pool: &Pool,
+    llm_state: &LLMState,
+    conversation_id: i64,
+    history: &[Message],
+    model: &str,
+    fixed: &[&str],
+    tools: Option<&Vec<ToolDefinition>>,
+) -> Vec<RouterChatMessage> {
+    let tool_tokens = tools
+        .and_then(|tools| serde_json::to_string(tools).ok())
+        .map(|json| TokenCounter::estimate_completion_tokens(&json))
+        .unwrap_or(0);
+    let fixed_tokens = fixed
+        .iter()
+        .map(|text| TokenCounter::estimate_completion_tokens(text))
+        .sum::<u32>()
+        + tool_tokens;
+
+    match ContextWindowManager::default()
+        .window(
+            pool,
+            &llm_state.router,
             conversation_id,
-            messages.len(),
-            ContextCompactor::calculate_tokens(&messages)
-        );
-
-        match compactor.compact_if_needed(&messages).await {
-            Ok(Some(compaction_result)) => {
-                info!(
-                    "Compaction result: {} messages compacted, {} -> {} tokens",
-                    compaction_result.messages_compacted,
-                    compaction_result.tokens_before,
-                    compaction_result.tokens_after
-                );
-
-                // Generate summary
-                let summary = compactor
-                    .generate_summary(
-                        &messages[..messages.len() - compaction_result.messages_compacted],
-                    )
-                    .await
-                    .unwrap_or_else(|_| "Context was compacted".to_string());
-
-                // Replace old messages with summary in database
-                let keep_count = compactor.config.keep_recent.min(messages.len());
-                let old_count = messages.len() - keep_count;
-
-                if old_count > 0 {
-                    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-                    // Delete old messages (except recent ones)
-                    let old_messages: Vec<i64> =
-                        messages[..old_count].iter().map(|m| m.id).collect();
-
-                    for msg_id in old_messages {
-                        let _ = repository::delete_message(&conn, msg_id);
-                    }
-
-                    // Insert summary message
-                    let summary_msg = Message {
-                        id: 0,
-                        conversation_id,
-                        role: MessageRole::System,
-                        content: format!("[Compacted Context]\n\n{}", summary),
-                        tokens: Some(compaction_result.tokens_after as i32),
-                        cost: None,
-                        provider: None,
-                        model: None,
-                        created_at: Utc::now(),
-                    };
-                    let _summary_id = repository::create_message(&conn, &summary_msg)
-                        .map_err(|e| format!("Failed to create summary message: {}", e))?;
-                }
-            }
-            Ok(None) => {
-                // No compaction needed
-            }
-            Err(e) => {
-                warn!("Compaction failed: {}, continuing with full history", e);
-            }
+            history,
+            model,
+            fixed_tokens,
+        )
+        .await
+    {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!(
+                "Context window unavailable for conversation {}, sending full history: {}",
+                conversation_id, e
+            );
+            history
+                .iter()
+                .map(|message| RouterChatMessage {
+                    role: message.role.as_str().to_string(),
+                    content: message.content.clone(),
+                    tool_calls: None,
+                    tool_call_id: None,
+                    multimodal_content: None,
+                })
+                .collect()
         }
     }
+}
 
+/// Store tool outputs as system messages so the follow-up request sees them
+fn save_tool_results(
+    conn: &Connection,
+    conversation_id: i64,
+    tool_results: Vec<(String, String)>,
+) -> Result<(), String> {
+    for (tool_call_id, result_content) in tool_results {
+        let tool_result_msg = Message::new(
+            conversation_id,
+            MessageRole::System,
+            format!("Tool result [{}]: {}", tool_call_id, result_content),
+        );
+        repository::create_message(conn, &tool_result_msg)
+            .map_err(|e| format!("Failed to save tool result: {}", e))?;
+    }
     Ok(())
 }
-*/
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateConversationRequest {
@@ -156,6 +150,15 @@ pub struct ChatSendMessageRequest {
     pub workflow_hash: Option<String>,
     #[serde(default, alias = "taskMetadata")]
     pub task_metadata: Option<TaskMetadata>,
+    /// Project collection to pull context from for this message
+    #[serde(default, alias = "collectionId")]
+    pub collection_id: Option<String>,
+    /// ID to pass to `operation_cancel` to stop the reply
+    #[serde(default, alias = "operationId")]
+    pub operation_id: Option<String>,
+    /// MCP resources to read into the message's context
+    #[serde(default, alias = "mcpResources")]
+    pub mcp_resources: Vec<crate::commands::mcp::McpResourceRef>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -180,6 +183,32 @@ pub struct ChatSendMessageResponse {
     pub last_message: Option<String>,
 }
 
+/// Retrieved excerpts from the collection the request names and the MCP
+/// resources it attaches, if any
+async fn request_context(
+    app_handle: &tauri::AppHandle,
+    request: &ChatSendMessageRequest,
+    content: &str,
+) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(collection_id) = request.collection_id.as_deref() {
+        parts.extend(
+            crate::commands::project_collections::collection_chat_context(
+                app_handle,
+                collection_id,
+                content,
+            )
+            .await,
+        );
+    }
+    if !request.mcp_resources.is_empty() {
+        parts.extend(
+            crate::commands::mcp::resource_chat_context(app_handle, &request.mcp_resources).await,
+        );
+    }
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
 fn router_context_from_metadata(metadata: &TaskMetadata) -> RouterContext {
     RouterContext {
         intents: metadata.intents.clone(),
@@ -196,6 +225,124 @@ fn parse_cost_priority(source: Option<&str>) -> CostPriority {
     }
 }
 
+/// Assistant replies used to bracket the expected reply length
+const RECENT_REPLIES_FOR_FORECAST: usize = 10;
+
+/// System prompt sent ahead of the conversation history on the streaming path
+const CHAT_SYSTEM_PROMPT: &str = "You are AGI Workforce, an intelligent AI assistant with access to powerful automation tools.
+
+TOOL USAGE - CRITICAL:
+You have access to tools for file operations, web searches, terminal commands, screenshots, and more. 
+When a user requests an action, automatically identify and call the appropriate tools:
+
+Common patterns:
+- \"read [file]\" or \"show me [file]\" → Use file_read tool
+- \"create [file]\" or \"write to [file]\" → Use file_write tool  
+- \"search for [query]\" or \"find information about [topic]\" → Use web_search tool
+- \"read [URL]\" or \"summarize this page\" → Use web_fetch tool and cite the returned url
+- \"run [command]\" or \"execute [command]\" → Use terminal_execute tool
+- \"take a screenshot\" or \"show me the screen\" → Use screenshot_capture tool
+- \"list files in [directory]\" → Use file_list tool
+
+IMPORTANT: You should proactively use tools when they would help answer the user's question. 
+Don't wait for explicit \"use tool\" commands. Be intelligent and helpful.
+
+THINKING PROCESS:
+For complex problems, show your reasoning using <thinking> tags before the final answer:
+<thinking>
+1. Analyze the user's request → Identify required tools
+2. Plan tool execution → Determine parameters
+3. Review tool results → Synthesize response
+</thinking>
+
+RESPONSE STYLE:
+- Be concise and clear
+- Explain what tools you're using and why
+- Synthesize tool results into helpful answers
+- If a tool fails, explain the error and suggest alternatives
+
+Remember: You are an autonomous agent. Use tools proactively to provide the best assistance.";
+
+/// Provider the client asked for, if any
+fn requested_provider(request: &ChatSendMessageRequest) -> Option<&str> {
+    request
+        .provider_override
+        .as_deref()
+        .or(request.provider.as_deref())
+}
+
+/// Model configured in settings for `provider`, or for the default provider
+async fn default_model(
+    settings_state: &crate::commands::settings::SettingsState,
+    provider: Option<&str>,
+) -> String {
+    let settings = settings_state.settings.lock().await;
+    let provider_name = provider
+        .map(str::to_string)
+        .unwrap_or_else(|| settings.llm_config.default_provider.clone());
+    match provider_name.as_str() {
+        "openai" => settings.llm_config.default_models.openai.clone(),
+        "anthropic" => settings.llm_config.default_models.anthropic.clone(),
+        "google" => settings.llm_config.default_models.google.clone(),
+        "ollama" => settings.llm_config.default_models.ollama.clone(),
+        "xai" => settings.llm_config.default_models.xai.clone(),
+        "deepseek" => settings.llm_config.default_models.deepseek.clone(),
+        "qwen" => settings.llm_config.default_models.qwen.clone(),
+        "mistral" => settings.llm_config.default_models.mistral.clone(),
+        "moonshot" => settings.llm_config.default_models.moonshot.clone(),
+        _ => settings.llm_config.default_models.openai.clone(),
+    }
+}
+
+/// Tool definitions from the AGI registry plus any running MCP servers, and
+/// the executor that runs them. Both are `None` when tools are disabled.
+fn chat_tool_definitions(
+    app_handle: &tauri::AppHandle,
+    enable_tools: bool,
+    conversation_mode: Option<String>,
+) -> (Option<Vec<ToolDefinition>>, Option<ToolExecutor>) {
+    use crate::agi::tools::ToolRegistry;
+    use crate::commands::McpState;
+
+    if !enable_tools {
+        return (None, None);
+    }
+
+    match ToolRegistry::new() {
+        Ok(registry) => {
+            let tool_registry = Arc::new(registry);
+            let mut tool_executor =
+                ToolExecutor::with_app_handle(tool_registry.clone(), app_handle.clone());
+
+            // 🔒 Set conversation mode for security checks
+            tool_executor.set_conversation_mode(conversation_mode);
+
+            let mut tool_defs = tool_executor.get_tool_definitions(None);
+
+            // ✅ Add MCP tools if available
+            if let Some(mcp_state) = app_handle.try_state::<McpState>() {
+                let mcp_tools = mcp_state.registry.get_all_tool_definitions();
+                if !mcp_tools.is_empty() {
+                    tracing::info!(
+                        "[Chat] Adding {} MCP tools to function definitions",
+                        mcp_tools.len()
+                    );
+                    tool_defs.extend(mcp_tools);
+                }
+            }
+
+            // TODO: AI Employees integration (future feature)
+            // AI employee tools will be added here when the marketplace feature is ready
+
+            (Some(tool_defs), Some(tool_executor))
+        }
+        Err(e) => {
+            tracing::warn!("[Chat] Failed to initialize tool registry: {}", e);
+            (None, None)
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 struct StreamStartPayload {
     conversation_id: i64,
@@ -256,6 +403,16 @@ pub fn chat_get_conversations(db: State<AppDatabase>) -> Result<Vec<Conversation
         .map_err(|e| format!("Failed to list conversations: {}", e))
 }
 
+/// Conversations a page at a time; sortable by `updatedAt`, `createdAt` or `title`
+#[tauri::command]
+pub fn chat_get_conversations_page(
+    db: State<AppDatabase>,
+    page: Option<PageRequest>,
+) -> Result<Page<Conversation>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    repository::page_conversations(&conn, &page.unwrap_or_default())
+}
+
 // Updated Nov 16, 2025: Added input validation for conversation ID
 #[tauri::command]
 pub fn chat_get_conversation(db: State<AppDatabase>, id: i64) -> Result<Conversation, String> {
@@ -479,6 +636,265 @@ pub fn chat_delete_message(db: State<AppDatabase>, id: i64) -> Result<(), String
         .map_err(|e| format!("Failed to delete message {}: {}", id, e))
 }
 
+#[derive(Debug, Serialize)]
+pub struct RegenerateMessageResponse {
+    pub message: Message,
+    pub branch_id: i64,
+    /// The conversation's messages with the new reply on the active branch
+    pub messages: Vec<Message>,
+}
+
+/// Ask the model to answer again in place of an assistant message. The new
+/// reply becomes a sibling of the original on its own branch, which is made
+/// active; the original branch stays reachable through `chat_switch_branch`.
+#[tauri::command]
+pub async fn chat_regenerate_message(
+    pool: State<'_, Pool>,
+    llm_state: State<'_, LLMState>,
+    settings_state: State<'_, crate::commands::settings::SettingsState>,
+    message_id: i64,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<RegenerateMessageResponse, String> {
+    if message_id <= 0 {
+        return Err(format!(
+            "Invalid message ID: {}. ID must be positive",
+            message_id
+        ));
+    }
+
+    let (original, history) = pool
+        .run(move |conn| -> Result<_, String> {
+            let original = repository::get_message(conn, message_id)
+                .map_err(|e| format!("Message {} not found: {}", message_id, e))?;
+            if original.role != MessageRole::Assistant {
+                return Err("Only assistant messages can be regenerated".to_string());
+            }
+            let mut history = repository::list_message_path(conn, message_id)
+                .map_err(|e| format!("Failed to load message history: {}", e))?;
+            history.pop();
+            // The rolling summary may cover messages that are not on this branch
+            ContextWindowManager::clear_summary(conn, original.conversation_id)
+                .map_err(|e| format!("Failed to reset conversation summary: {}", e))?;
+            Ok((original, history))
+        })
+        .await?;
+    if history.is_empty() {
+        return Err("Nothing precedes this message to answer".to_string());
+    }
+
+    let provider = provider
+        .or_else(|| original.provider.clone())
+        .filter(|p| !p.trim().is_empty());
+    let model = match model
+        .or_else(|| original.model.clone())
+        .filter(|m| !m.trim().is_empty())
+    {
+        Some(model) => model,
+        None => default_model(&settings_state, provider.as_deref()).await,
+    };
+
+    let messages = windowed_history(
+        &pool,
+        &llm_state,
+        original.conversation_id,
+        &history,
+        &model,
+        &[],
+        None,
+    )
+    .await;
+    let llm_request = LLMRequest {
+        messages,
+        model: model.clone(),
+        temperature: None,
+        max_tokens: None,
+        stream: false,
+        tools: None,
+        tool_choice: None,
+        response_format: None,
+    };
+    let preferences = RouterPreferences {
+        provider: provider.as_deref().and_then(Provider::from_string),
+        model: Some(model),
+        strategy: RoutingStrategy::Auto,
+        context: None,
+    };
+
+    // The response cache is skipped: a regenerated reply should differ
+    let candidates = {
+        let router = llm_state.router.lock().await;
+        router.candidates(&llm_request, &preferences)
+    };
+    if candidates.is_empty() {
+        return Err("No LLM providers are configured.".to_string());
+    }
+    let mut outcome = None;
+    let mut last_error = None;
+    for candidate in candidates {
+        let res = {
+            let router = llm_state.router.lock().await;
+            router.invoke_candidate(&candidate, &llm_request).await
+        };
+        match res {
+            Ok(route_outcome) => {
+                outcome = Some(route_outcome);
+                break;
+            }
+            Err(e) => {
+                warn!(
+                    "Regeneration with {} failed: {}",
+                    candidate.provider.as_string(),
+                    e
+                );
+                last_error = Some(e);
+            }
+        }
+    }
+    let outcome = outcome.ok_or_else(|| {
+        format!(
+            "Failed to regenerate message: {}",
+            last_error
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| "no provider responded".to_string())
+        )
+    })?;
+
+    let mut reply = Message::new(
+        original.conversation_id,
+        MessageRole::Assistant,
+        outcome.response.content.clone(),
+    )
+    .with_source(
+        Some(outcome.provider.as_string().to_string()),
+        Some(outcome.model.clone()),
+    );
+    reply.tokens = Some((outcome.prompt_tokens + outcome.completion_tokens) as i32);
+    reply.cost = Some(outcome.cost);
+
+    pool.run(move |conn| {
+        let (id, branch_id) = branches::add_sibling(conn, &original, &reply)
+            .map_err(|e| format!("Failed to save regenerated message: {}", e))?;
+        let message = repository::get_message(conn, id)
+            .map_err(|e| format!("Failed to retrieve message {}: {}", id, e))?;
+        let messages = repository::list_messages(conn, original.conversation_id)
+            .map_err(|e| format!("Failed to list messages: {}", e))?;
+
+        Ok(RegenerateMessageResponse {
+            message,
+            branch_id,
+            messages,
+        })
+    })
+    .await
+}
+
+/// Show another branch of the conversation, returning its messages
+#[tauri::command]
+pub fn chat_switch_branch(
+    db: State<AppDatabase>,
+    conversation_id: i64,
+    branch_id: i64,
+) -> Result<Vec<Message>, String> {
+    if conversation_id <= 0 {
+        return Err(format!(
+            "Invalid conversation ID: {}. ID must be positive",
+            conversation_id
+        ));
+    }
+
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+    branches::switch_branch(&conn, conversation_id, branch_id).map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!(
+            "Conversation {} has no branch {}",
+            conversation_id, branch_id
+        ),
+        e => format!("Failed to switch branch: {}", e),
+    })?;
+    ContextWindowManager::clear_summary(&conn, conversation_id)
+        .map_err(|e| format!("Failed to reset conversation summary: {}", e))?;
+    repository::list_messages(&conn, conversation_id).map_err(|e| {
+        format!(
+            "Failed to list messages for conversation {}: {}",
+            conversation_id, e
+        )
+    })
+}
+
+/// Branches of a conversation, each with how it differs from the reply it
+/// was regenerated alongside
+#[tauri::command]
+pub fn chat_list_branches(
+    db: State<AppDatabase>,
+    conversation_id: i64,
+) -> Result<Vec<ConversationBranch>, String> {
+    if conversation_id <= 0 {
+        return Err(format!(
+            "Invalid conversation ID: {}. ID must be positive",
+            conversation_id
+        ));
+    }
+
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+    branches::list_branches(&conn, conversation_id).map_err(|e| {
+        format!(
+            "Failed to list branches for conversation {}: {}",
+            conversation_id, e
+        )
+    })
+}
+
+/// Draft a reusable workflow from the tool calls made in a conversation. The
+/// draft is returned for editing and saved separately with `create_workflow`
+#[tauri::command]
+pub fn chat_convert_to_workflow(
+    db: State<AppDatabase>,
+    conversation_id: i64,
+) -> Result<ConversationWorkflowDraft, String> {
+    if conversation_id <= 0 {
+        return Err(format!(
+            "Invalid conversation ID: {}. ID must be positive",
+            conversation_id
+        ));
+    }
+
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to acquire database lock: {}", e))?;
+    let conversation = repository::get_conversation(&conn, conversation_id)
+        .map_err(|e| format!("Failed to get conversation {}: {}", conversation_id, e))?;
+    let user_messages: Vec<String> = repository::list_messages(&conn, conversation_id)
+        .map_err(|e| {
+            format!(
+                "Failed to list messages for conversation {}: {}",
+                conversation_id, e
+            )
+        })?
+        .into_iter()
+        .filter(|message| message.role == MessageRole::User)
+        .map(|message| message.content)
+        .collect();
+    let tool_calls: Vec<ToolCallRecord> = repository::list_tool_calls(&conn, conversation_id)
+        .map_err(|e| format!("Failed to list tool calls: {}", e))?
+        .into_iter()
+        .map(|(_, call)| call)
+        .collect();
+
+    conversation_workflow::draft_from_tool_calls(
+        conversation_id,
+        &conversation.title,
+        &user_messages,
+        &tool_calls,
+    )
+}
+
 // Updated Nov 16, 2025: Added input validation for conversation ID
 #[tauri::command]
 pub fn chat_get_conversation_stats(
@@ -515,9 +931,31 @@ pub fn chat_get_conversation_stats(
     })
 }
 
+/// How much of the model's context window a conversation uses, and how
+/// much of it has been folded into the rolling summary
+#[tauri::command]
+pub async fn chat_get_context_usage(
+    pool: State<'_, Pool>,
+    settings_state: State<'_, crate::commands::settings::SettingsState>,
+    conversation_id: i64,
+    model: Option<String>,
+) -> Result<ContextUsage, String> {
+    let model = match model {
+        Some(model) => model,
+        None => default_model(&settings_state, None).await,
+    };
+    pool.run(move |conn| {
+        let history = repository::list_messages(conn, conversation_id)
+            .map_err(|e| format!("Failed to list messages: {}", e))?;
+        ContextWindowManager::usage(conn, conversation_id, &history, &model)
+            .map_err(|e| format!("Failed to read conversation summary: {}", e))
+    })
+    .await
+}
+
 /// Handle streaming chat messages with real SSE streaming
 async fn chat_send_message_streaming(
-    db: State<'_, AppDatabase>,
+    pool: State<'_, Pool>,
     llm_state: State<'_, LLMState>,
     settings_state: State<'_, crate::commands::settings::SettingsState>,
     app_handle: tauri::AppHandle,
@@ -563,99 +1001,33 @@ async fn chat_send_message_streaming(
     }
 
     // Create conversation and user message
-    let (conversation_id, _user_message_id, assistant_message_id) = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-        let conversation_id = match request.conversation_id {
-            Some(id) => {
-                repository::get_conversation(&conn, id)
-                    .map_err(|e| format!("Conversation not found: {}", e))?;
-                id
-            }
-            None => repository::create_conversation(&conn, "New Conversation".to_string())
-                .map_err(|e| format!("Failed to create conversation: {}", e))?,
-        };
-
-        let user_msg = Message::new(conversation_id, MessageRole::User, trimmed_content.clone());
-        let user_msg_id = repository::create_message(&conn, &user_msg)
-            .map_err(|e| format!("Failed to create user message: {}", e))?;
-
-        // Create placeholder assistant message
-        let assistant_msg = Message::new(conversation_id, MessageRole::Assistant, String::new());
-        let assistant_msg_id = repository::create_message(&conn, &assistant_msg)
-            .map_err(|e| format!("Failed to create assistant message: {}", e))?;
-
-        (conversation_id, user_msg_id, assistant_msg_id)
-    };
-
-    // TODO: Re-enable auto-compaction once ContextManager API is compatible
-    // auto_compact_conversation(&db, conversation_id)
-    //     .await
-    //     .unwrap_or_else(|e| warn!("Auto-compaction failed: {}", e));
-
-    // Get conversation history (after compaction)
-    let history = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-        repository::list_messages(&conn, conversation_id)
-            .map_err(|e| format!("Failed to list messages: {}", e))?
-    };
-
-    // Construct messages for the router
-    let mut router_messages: Vec<RouterChatMessage> = Vec::new();
-
-    // Add System Prompt with Tool Usage + Thinking Process instructions
-    router_messages.push(RouterChatMessage {
-        role: "system".to_string(),
-        content: "You are AGI Workforce, an intelligent AI assistant with access to powerful automation tools.
-
-TOOL USAGE - CRITICAL:
-You have access to tools for file operations, web searches, terminal commands, screenshots, and more. 
-When a user requests an action, automatically identify and call the appropriate tools:
-
-Common patterns:
-- \"read [file]\" or \"show me [file]\" → Use file_read tool
-- \"create [file]\" or \"write to [file]\" → Use file_write tool  
-- \"search for [query]\" or \"find information about [topic]\" → Use web_search tool
-- \"run [command]\" or \"execute [command]\" → Use terminal_execute tool
-- \"take a screenshot\" or \"show me the screen\" → Use screenshot_capture tool
-- \"list files in [directory]\" → Use file_list tool
-
-IMPORTANT: You should proactively use tools when they would help answer the user's question. 
-Don"
-.to_string() + "'t wait for explicit \"use tool\" commands. Be intelligent and helpful.
-
-THINKING PROCESS:
-For complex problems, show your reasoning using <thinking> tags before the final answer:
-<thinking>
-1. Analyze the user's request → Identify required tools
-2. Plan tool execution → Determine parameters
-3. Review tool results → Synthesize response
-</thinking>
+    let requested_conversation = request.conversation_id;
+    let user_content = trimmed_content.clone();
+    let (conversation_id, _user_message_id, assistant_message_id) = pool
+        .run(move |conn| -> Result<_, String> {
+            let conversation_id = match requested_conversation {
+                Some(id) => {
+                    repository::get_conversation(conn, id)
+                        .map_err(|e| format!("Conversation not found: {}", e))?;
+                    id
+                }
+                None => repository::create_conversation(conn, "New Conversation".to_string())
+                    .map_err(|e| format!("Failed to create conversation: {}", e))?,
+            };
 
-RESPONSE STYLE:
-- Be concise and clear
-- Explain what tools you're using and why
-- Synthesize tool results into helpful answers
-- If a tool fails, explain the error and suggest alternatives
+            let user_msg = Message::new(conversation_id, MessageRole::User, user_content);
+            let user_msg_id = repository::create_message(conn, &user_msg)
+                .map_err(|e| format!("Failed to create user message: {}", e))?;
 
-Remember: You are an autonomous agent. Use tools proactively to provide the best assistance.",
-        tool_calls: None,
-        tool_call_id: None,
-        multimodal_content: None,
-    });
+            // Create placeholder assistant message
+            let assistant_msg =
+                Message::new(conversation_id, MessageRole::Assistant, String::new());
+            let assistant_msg_id = repository::create_message(conn, &assistant_msg)
+                .map_err(|e| format!("Failed to create assistant message: {}", e))?;
 
-    router_messages.extend(
-        history
-            .iter()
-            .filter(|m| m.id != assistant_message_id) // Exclude placeholder
-            .map(|message| RouterChatMessage {
-                role: message.role.as_str().to_string(),
-                content: message.content.clone(),
-                tool_calls: None,
-                tool_call_id: None,
-                multimodal_content: None,
-            }),
-    );
+            Ok((conversation_id, user_msg_id, assistant_msg_id))
+        })
+        .await?;
 
     let provider_override = request
         .provider_override
@@ -671,73 +1043,62 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
         .map(router_context_from_metadata);
 
     // Get default model from settings if not provided
-    let model = if let Some(model) = model_override.clone() {
-        model
-    } else {
-        let settings = settings_state.settings.lock().await;
-        let provider_name = request
-            .provider_override
-            .as_ref()
-            .or(request.provider.as_ref())
-            .cloned()
-            .unwrap_or_else(|| settings.llm_config.default_provider.clone());
-        match provider_name.as_str() {
-            "openai" => settings.llm_config.default_models.openai.clone(),
-            "anthropic" => settings.llm_config.default_models.anthropic.clone(),
-            "google" => settings.llm_config.default_models.google.clone(),
-            "ollama" => settings.llm_config.default_models.ollama.clone(),
-            "xai" => settings.llm_config.default_models.xai.clone(),
-            "deepseek" => settings.llm_config.default_models.deepseek.clone(),
-            "qwen" => settings.llm_config.default_models.qwen.clone(),
-            "mistral" => settings.llm_config.default_models.mistral.clone(),
-            "moonshot" => settings.llm_config.default_models.moonshot.clone(),
-            _ => settings.llm_config.default_models.openai.clone(),
-        }
+    let model = match model_override.clone() {
+        Some(model) => model,
+        None => default_model(&settings_state, requested_provider(&request)).await,
     };
 
-    // ✅ Add tool definitions from AGI registry + MCP tools + AI Employees (same as non-streaming)
-    let (tool_definitions, tool_executor) = if request.enable_tools.unwrap_or(true) {
-        use crate::agi::tools::ToolRegistry;
-        use crate::commands::McpState;
-        use crate::router::tool_executor::ToolExecutor;
-        use std::sync::Arc;
-
-        match ToolRegistry::new() {
-            Ok(registry) => {
-                let tool_registry = Arc::new(registry);
-                let mut tool_executor =
-                    ToolExecutor::with_app_handle(tool_registry.clone(), app_handle.clone());
-
-                // 🔒 Set conversation mode for security checks
-                tool_executor.set_conversation_mode(request.conversation_mode.clone());
-
-                let mut tool_defs = tool_executor.get_tool_definitions(None);
-
-                // ✅ Add MCP tools if available
-                if let Some(mcp_state) = app_handle.try_state::<McpState>() {
-                    let mcp_tools = mcp_state.registry.get_all_tool_definitions();
-                    if !mcp_tools.is_empty() {
-                        tracing::info!(
-                            "[Chat Streaming] Adding {} MCP tools to function definitions",
-                            mcp_tools.len()
-                        );
-                        tool_defs.extend(mcp_tools);
-                    }
-                }
+    // ✅ Add tool definitions from AGI registry + MCP tools
+    let (tool_definitions, tool_executor) = chat_tool_definitions(
+        &app_handle,
+        request.enable_tools.unwrap_or(true),
+        request.conversation_mode.clone(),
+    );
 
-                // TODO: AI Employees integration (future feature)
-                // AI employee tools will be added here when the marketplace feature is ready
+    let history: Vec<Message> = pool
+        .run(move |conn| {
+            repository::list_messages(conn, conversation_id)
+                .map_err(|e| format!("Failed to list messages: {}", e))
+        })
+        .await?
+        .into_iter()
+        .filter(|m| m.id != assistant_message_id) // Exclude placeholder
+        .collect();
+    let context = request_context(&app_handle, &request, &trimmed_content).await;
 
-                (Some(tool_defs), Some(tool_executor))
-            }
-            Err(e) => {
-                tracing::warn!("[Chat Streaming] Failed to initialize tool registry: {}", e);
-                (None, None)
-            }
-        }
-    } else {
-        (None, None)
-    };
+    // Construct messages for the router: system prompt with tool usage and
+    // thinking instructions, retrieved context, then the windowed history
+    let mut router_messages: Vec<RouterChatMessage> = Vec::new();
+    router_messages.push(RouterChatMessage {
+        role: "system".to_string(),
+        content: CHAT_SYSTEM_PROMPT.to_string(),
+        tool_calls: None,
+        tool_call_id: None,
+        multimodal_content: None,
+    });
+    let mut fixed = vec![CHAT_SYSTEM_PROMPT];
+    if let Some(context) = &context {
+        router_messages.push(RouterChatMessage {
+            role: "system".to_string(),
+            content: context.clone(),
+            tool_calls: None,
+            tool_call_id: None,
+            multimodal_content: None,
+        });
+        fixed.push(context.as_str());
+    }
+    router_messages.extend(
+        windowed_history(
+            &pool,
+            &llm_state,
+            conversation_id,
+            &history,
+            &model,
+            &fixed,
+            tool_definitions.as_ref(),
+        )
+        .await,
+    );
 
     let has_tools = tool_definitions.is_some();
     let tool_defs_for_follow_up = tool_definitions.clone();
@@ -755,6 +1116,7 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
         } else {
             None
         },
+        response_format: None,
     };
 
     let preferences = RouterPreferences {
@@ -787,7 +1149,16 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
     let mut accumulated_content = String::new();
     let _total_tokens: Option<i32> = None;
 
-    while let Some(chunk_result) = stream.next().await {
+    let mut cancelled = false;
+    loop {
+        let chunk_result = match cancellable(stream.next()).await {
+            Ok(Some(chunk_result)) => chunk_result,
+            Ok(None) => break,
+            Err(_) => {
+                cancelled = true;
+                break;
+            }
+        };
         match chunk_result {
             Ok(chunk) => {
                 if !chunk.content.is_empty() {
@@ -830,12 +1201,37 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
         warn!("Failed to emit stream end event: {}", error);
     }
 
+    if cancelled {
+        // Keep what streamed before the cancel; an empty reply is removed
+        let cleanup = pool
+            .run(move |conn| {
+                if accumulated_content.is_empty() {
+                    repository::delete_message(conn, assistant_message_id)
+                } else {
+                    repository::update_message_content(
+                        conn,
+                        assistant_message_id,
+                        accumulated_content,
+                    )
+                    .map(|_| ())
+                }
+                .map_err(|e| e.to_string())
+            })
+            .await;
+        if let Err(e) = cleanup {
+            warn!("Failed to clean up cancelled reply: {}", e);
+        }
+        return Err(Cancelled.to_string());
+    }
+
     // Update assistant message with final content
-    let mut assistant_msg = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-        repository::update_message_content(&conn, assistant_message_id, accumulated_content.clone())
-            .map_err(|e| format!("Failed to update assistant message: {}", e))?
-    };
+    let final_content = accumulated_content.clone();
+    let mut assistant_msg = pool
+        .run(move |conn| {
+            repository::update_message_content(conn, assistant_message_id, final_content)
+                .map_err(|e| format!("Failed to update assistant message: {}", e))
+        })
+        .await?;
 
     // ✅ Check for tool calls after streaming completes
     // Since streaming doesn't include tool calls in chunks, we make a follow-up non-streaming request
@@ -860,6 +1256,7 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
             stream: false, // Non-streaming to get tool calls
             tools: tool_defs_for_follow_up.clone(),
             tool_choice: Some(crate::router::ToolChoice::Auto),
+            response_format: None,
         };
 
         let candidates = {
@@ -882,6 +1279,7 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
 
                         // Execute tool calls
                         let mut tool_results = Vec::new();
+                        let mut tool_records = Vec::new();
                         for tool_call in tool_calls {
                             tracing::info!(
                                 "[Chat Streaming] Executing tool: {} ({})",
@@ -895,6 +1293,12 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
                                     let duration = start_time.elapsed().as_millis() as u64;
                                     let formatted = executor.format_tool_result(tool_call, &result);
                                     tool_results.push((tool_call.id.clone(), formatted));
+                                    tool_records.push(ToolCallRecord::new(
+                                        &tool_call.id,
+                                        &tool_call.name,
+                                        &tool_call.arguments,
+                                        None,
+                                    ));
                                     tracing::info!(
                                         "[Chat Streaming] Tool {} succeeded",
                                         tool_call.name
@@ -925,6 +1329,12 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
                                     let duration = start_time.elapsed().as_millis() as u64;
                                     let error_msg = format!("Tool execution failed: {}", e);
                                     tool_results.push((tool_call.id.clone(), error_msg.clone()));
+                                    tool_records.push(ToolCallRecord::new(
+                                        &tool_call.id,
+                                        &tool_call.name,
+                                        &tool_call.arguments,
+                                        Some(e.to_string()),
+                                    ));
                                     tracing::error!(
                                         "[Chat Streaming] Tool {} failed: {}",
                                         tool_call.name,
@@ -955,35 +1365,37 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
                             }
                         }
 
-                        // Add tool results to conversation
-                        for (tool_call_id, result_content) in tool_results {
-                            let conn = db.conn.lock().map_err(|e| e.to_string())?;
-                            let tool_result_msg = Message::new(
-                                conversation_id,
-                                MessageRole::System,
-                                format!("Tool result [{}]: {}", tool_call_id, result_content),
-                            );
-                            repository::create_message(&conn, &tool_result_msg)
-                                .map_err(|e| format!("Failed to save tool result: {}", e))?;
+                        let message_id = assistant_message_id;
+                        if let Err(e) = pool
+                            .run(move |conn| {
+                                repository::set_message_tool_calls(conn, message_id, &tool_records)
+                                    .map_err(|e| e.to_string())
+                            })
+                            .await
+                        {
+                            warn!("Failed to record tool calls: {}", e);
                         }
 
-                        // Continue conversation with tool results (make another request)
-                        let updated_history = {
-                            let conn = db.conn.lock().map_err(|e| e.to_string())?;
-                            repository::list_messages(&conn, conversation_id)
-                                .map_err(|e| format!("Failed to list messages: {}", e))?
-                        };
-
-                        let updated_messages: Vec<RouterChatMessage> = updated_history
-                            .iter()
-                            .map(|message| RouterChatMessage {
-                                role: message.role.as_str().to_string(),
-                                content: message.content.clone(),
-                                tool_calls: None,
-                                tool_call_id: None,
-                                multimodal_content: None,
+                        // Add tool results to conversation, then continue it with
+                        // them (make another request)
+                        let updated_history = pool
+                            .run(move |conn| {
+                                save_tool_results(conn, conversation_id, tool_results)?;
+                                repository::list_messages(conn, conversation_id)
+                                    .map_err(|e| format!("Failed to list messages: {}", e))
                             })
-                            .collect();
+                            .await?;
+
+                        let updated_messages = windowed_history(
+                            &pool,
+                            &llm_state,
+                            conversation_id,
+                            &updated_history,
+                            &llm_request.model,
+                            &[],
+                            tool_defs_for_follow_up.as_ref(),
+                        )
+                        .await;
 
                         let final_request = LLMRequest {
                             messages: updated_messages,
@@ -993,6 +1405,7 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
                             stream: false,
                             tools: tool_defs_for_follow_up.clone(),
                             tool_choice: Some(crate::router::ToolChoice::Auto),
+                            response_format: None,
                         };
 
                         // Get final response with tool results
@@ -1001,13 +1414,19 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
                             router.invoke_candidate(candidate, &final_request).await
                         } {
                             // Update assistant message with final response
-                            let conn = db.conn.lock().map_err(|e| e.to_string())?;
-                            assistant_msg = repository::update_message_content(
-                                &conn,
-                                assistant_message_id,
-                                final_outcome.response.content.clone(),
-                            )
-                            .map_err(|e| format!("Failed to update assistant message: {}", e))?;
+                            let content = final_outcome.response.content.clone();
+                            assistant_msg = pool
+                                .run(move |conn| {
+                                    repository::update_message_content(
+                                        conn,
+                                        assistant_message_id,
+                                        content,
+                                    )
+                                    .map_err(|e| {
+                                        format!("Failed to update assistant message: {}", e)
+                                    })
+                                })
+                                .await?;
                         }
                     }
                 }
@@ -1016,16 +1435,17 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
     }
 
     // Fetch final conversation state
-    let (conversation, user_msg, messages) = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-        let conversation = repository::get_conversation(&conn, conversation_id)
-            .map_err(|e| format!("Failed to get conversation: {}", e))?;
-        let user_msg = repository::get_message(&conn, _user_message_id)
-            .map_err(|e| format!("Failed to get user message: {}", e))?;
-        let messages = repository::list_messages(&conn, conversation_id)
-            .map_err(|e| format!("Failed to list messages: {}", e))?;
-        (conversation, user_msg, messages)
-    };
+    let (conversation, user_msg, messages) = pool
+        .run(move |conn| -> Result<_, String> {
+            let conversation = repository::get_conversation(conn, conversation_id)
+                .map_err(|e| format!("Failed to get conversation: {}", e))?;
+            let user_msg = repository::get_message(conn, _user_message_id)
+                .map_err(|e| format!("Failed to get user message: {}", e))?;
+            let messages = repository::list_messages(conn, conversation_id)
+                .map_err(|e| format!("Failed to list messages: {}", e))?;
+            Ok((conversation, user_msg, messages))
+        })
+        .await?;
 
     let total_tokens_sum: i32 = messages.iter().filter_map(|m| m.tokens).sum();
     let total_cost: f64 = messages.iter().filter_map(|m| m.cost).sum();
@@ -1063,7 +1483,7 @@ Remember: You are an autonomous agent. Use tools proactively to provide the best
 
 #[tauri::command]
 pub async fn chat_send_message(
-    db: State<'_, AppDatabase>,
+    pool: State<'_, Pool>,
     llm_state: State<'_, LLMState>,
     settings_state: State<'_, crate::commands::settings::SettingsState>,
     #[cfg_attr(not(feature = "billing"), allow(unused_variables))] billing_state: State<
@@ -1091,11 +1511,33 @@ pub async fn chat_send_message(
 
     let stream_mode = request.stream.unwrap_or(false);
 
-    // Use separate streaming path if requested
-    if stream_mode {
-        return chat_send_message_streaming(db, llm_state, settings_state, app_handle, request)
-            .await;
-    }
+    // The reply is a cancellable operation, under the ID the frontend picked
+    // when it sent one
+    let operation = crate::cancellation::global_operations()
+        .begin("chat_send_message", request.operation_id.clone());
+    let app = app_handle.clone();
+    let reply = async move {
+        // Use separate streaming path if requested
+        if stream_mode {
+            chat_send_message_streaming(pool, llm_state, settings_state, app_handle, request).await
+        } else {
+            chat_send_message_complete(pool, llm_state, settings_state, app_handle, request).await
+        }
+    };
+    operation
+        .run_with_events(&app, reply)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+async fn chat_send_message_complete(
+    pool: State<'_, Pool>,
+    llm_state: State<'_, LLMState>,
+    settings_state: State<'_, crate::commands::settings::SettingsState>,
+    app_handle: tauri::AppHandle,
+    request: ChatSendMessageRequest,
+) -> Result<ChatSendMessageResponse, String> {
+    let stream_mode = request.stream.unwrap_or(false);
     let task_start = std::time::Instant::now();
     if let Some(approval_state) = app_handle.try_state::<ApprovalController>() {
         approval_state
@@ -1136,32 +1578,29 @@ pub async fn chat_send_message(
         }
     }
 
-    let (conversation_id, user_message) = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-        let conversation_id = match request.conversation_id {
-            Some(id) => {
-                repository::get_conversation(&conn, id)
-                    .map_err(|e| format!("Conversation not found: {}", e))?;
-                id
-            }
-            None => repository::create_conversation(&conn, "New Conversation".to_string())
-                .map_err(|e| format!("Failed to create conversation: {}", e))?,
-        };
-
-        let message = Message::new(conversation_id, MessageRole::User, trimmed_content.clone());
-        let message_id = repository::create_message(&conn, &message)
-            .map_err(|e| format!("Failed to create message: {}", e))?;
-        let message = repository::get_message(&conn, message_id)
-            .map_err(|e| format!("Failed to retrieve message: {}", e))?;
+    let requested_conversation = request.conversation_id;
+    let user_content = trimmed_content.clone();
+    let (conversation_id, user_message) = pool
+        .run(move |conn| -> Result<_, String> {
+            let conversation_id = match requested_conversation {
+                Some(id) => {
+                    repository::get_conversation(conn, id)
+                        .map_err(|e| format!("Conversation not found: {}", e))?;
+                    id
+                }
+                None => repository::create_conversation(conn, "New Conversation".to_string())
+                    .map_err(|e| format!("Failed to create conversation: {}", e))?,
+            };
 
-        (conversation_id, message)
-    };
+            let message = Message::new(conversation_id, MessageRole::User, user_content);
+            let message_id = repository::create_message(conn, &message)
+                .map_err(|e| format!("Failed to create message: {}", e))?;
+            let message = repository::get_message(conn, message_id)
+                .map_err(|e| format!("Failed to retrieve message: {}", e))?;
 
-    // TODO: Re-enable auto-compaction once ContextManager API is compatible
-    // auto_compact_conversation(&db, conversation_id)
-    //     .await
-    //     .unwrap_or_else(|e| warn!("Auto-compaction failed: {}", e));
+            Ok((conversation_id, message))
+        })
+        .await?;
 
     // 🔔 Emit agent status: Analyzing request
     let _ = app_handle.emit(
@@ -1175,67 +1614,12 @@ pub async fn chat_send_message(
         }),
     );
 
-    // Get conversation history (after compaction)
-    let history = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-        repository::list_messages(&conn, conversation_id)
-            .map_err(|e| format!("Failed to list messages: {}", e))?
-    };
-
-    let router_messages: Vec<RouterChatMessage> = history
-        .iter()
-        .map(|message| RouterChatMessage {
-            role: message.role.as_str().to_string(),
-            content: message.content.clone(),
-            tool_calls: None,
-            tool_call_id: None,
-            multimodal_content: None,
-        })
-        .collect();
-
-    // ✅ Add tool definitions from AGI registry + MCP tools + AI Employees
-    let (tool_definitions, _tool_executor) = if request.enable_tools.unwrap_or(true) {
-        use crate::agi::tools::ToolRegistry;
-        use crate::commands::McpState;
-        use crate::router::tool_executor::ToolExecutor;
-        use std::sync::Arc;
-
-        match ToolRegistry::new() {
-            Ok(registry) => {
-                let tool_registry = Arc::new(registry);
-                let mut tool_executor =
-                    ToolExecutor::with_app_handle(tool_registry.clone(), app_handle.clone());
-
-                // 🔒 Set conversation mode for security checks
-                tool_executor.set_conversation_mode(request.conversation_mode.clone());
-
-                let mut tool_defs = tool_executor.get_tool_definitions(None);
-
-                // ✅ Add MCP tools if available
-                if let Some(mcp_state) = app_handle.try_state::<McpState>() {
-                    let mcp_tools = mcp_state.registry.get_all_tool_definitions();
-                    if !mcp_tools.is_empty() {
-                        tracing::info!(
-                            "[Chat] Adding {} MCP tools to function definitions",
-                            mcp_tools.len()
-                        );
-                        tool_defs.extend(mcp_tools);
-                    }
-                }
-
-                // TODO: AI Employees integration (future feature)
-                // AI employee tools will be added here when the marketplace feature is ready
-
-                (Some(tool_defs), Some(tool_executor))
-            }
-            Err(e) => {
-                tracing::warn!("[Chat] Failed to initialize tool registry: {}", e);
-                (None, None)
-            }
-        }
-    } else {
-        (None, None)
-    };
+    // ✅ Add tool definitions from AGI registry + MCP tools
+    let (tool_definitions, _tool_executor) = chat_tool_definitions(
+        &app_handle,
+        request.enable_tools.unwrap_or(true),
+        request.conversation_mode.clone(),
+    );
 
     let has_tools = tool_definitions.is_some();
     let tool_defs_for_follow_up = tool_definitions.clone(); // Clone for potential follow-up request
@@ -1266,30 +1650,42 @@ pub async fn chat_send_message(
         .map(router_context_from_metadata);
 
     // Get default model from settings if not provided
-    let model = if let Some(model) = model_override.clone() {
-        model
-    } else {
-        let settings = settings_state.settings.lock().await;
-        let provider_name = request
-            .provider_override
-            .as_ref()
-            .or(request.provider.as_ref())
-            .cloned()
-            .unwrap_or_else(|| settings.llm_config.default_provider.clone());
-        match provider_name.as_str() {
-            "openai" => settings.llm_config.default_models.openai.clone(),
-            "anthropic" => settings.llm_config.default_models.anthropic.clone(),
-            "google" => settings.llm_config.default_models.google.clone(),
-            "ollama" => settings.llm_config.default_models.ollama.clone(),
-            "xai" => settings.llm_config.default_models.xai.clone(),
-            "deepseek" => settings.llm_config.default_models.deepseek.clone(),
-            "qwen" => settings.llm_config.default_models.qwen.clone(),
-            "mistral" => settings.llm_config.default_models.mistral.clone(),
-            "moonshot" => settings.llm_config.default_models.moonshot.clone(),
-            _ => settings.llm_config.default_models.openai.clone(),
-        }
+    let model = match model_override.clone() {
+        Some(model) => model,
+        None => default_model(&settings_state, requested_provider(&request)).await,
     };
 
+    let history = pool
+        .run(move |conn| {
+            repository::list_messages(conn, conversation_id)
+                .map_err(|e| format!("Failed to list messages: {}", e))
+        })
+        .await?;
+    let context = request_context(&app_handle, &request, &trimmed_content).await;
+    let fixed: Vec<&str> = context.iter().map(String::as_str).collect();
+    let mut router_messages = windowed_history(
+        &pool,
+        &llm_state,
+        conversation_id,
+        &history,
+        &model,
+        &fixed,
+        tool_definitions.as_ref(),
+    )
+    .await;
+    if let Some(context) = context {
+        router_messages.insert(
+            0,
+            RouterChatMessage {
+                role: "system".to_string(),
+                content: context,
+                tool_calls: None,
+                tool_call_id: None,
+                multimodal_content: None,
+            },
+        );
+    }
+
     let llm_request = LLMRequest {
         messages: router_messages,
         model,
@@ -1302,6 +1698,7 @@ pub async fn chat_send_message(
         } else {
             None
         },
+        response_format: None,
     };
 
     let preferences = RouterPreferences {
@@ -1333,13 +1730,16 @@ pub async fn chat_send_message(
             llm_request.max_tokens,
         );
 
-        if let Some(entry) = {
-            let conn = db.conn.lock().map_err(|e| e.to_string())?;
-            llm_state
-                .cache_manager
-                .fetch(&conn, &cache_key)
-                .map_err(|e| format!("Failed to read cache: {}", e))?
-        } {
+        let cache_manager = llm_state.cache_manager.clone();
+        let lookup_key = cache_key.clone();
+        if let Some(entry) = pool
+            .run(move |conn| {
+                cache_manager
+                    .fetch(conn, &lookup_key)
+                    .map_err(|e| format!("Failed to read cache: {}", e))
+            })
+            .await?
+        {
             if let Some(provider) = Provider::from_string(&entry.provider) {
                 let tokens = entry.tokens.map(|t| t as u32);
                 let response = LLMResponse {
@@ -1352,6 +1752,7 @@ pub async fn chat_send_message(
                     cached: true,
                     tool_calls: None,
                     finish_reason: None,
+                    routing: None,
                 };
                 outcome = Some(RouteOutcome {
                     provider,
@@ -1373,34 +1774,44 @@ pub async fn chat_send_message(
             Ok(mut route_outcome) => {
                 route_outcome.response.cached = false;
                 {
-                    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-                    let expires_at = llm_state.cache_manager.default_expiry();
-                    llm_state
-                        .cache_manager
-                        .upsert(
-                            &conn,
-                            CacheRecord {
-                                cache_key: &cache_key,
-                                provider: route_outcome.provider,
-                                model: &route_outcome.model,
-                                prompt_hash: &prompt_hash,
-                                response: &route_outcome.response.content,
-                                tokens: route_outcome.response.tokens,
-                                cost: route_outcome.response.cost,
-                                temperature: llm_request.temperature,
-                                max_tokens: llm_request.max_tokens,
-                                expires_at,
-                            },
-                        )
-                        .map_err(|e| format!("Failed to store cache entry: {}", e))?;
+                    let cache_manager = llm_state.cache_manager.clone();
+                    let cache_key = cache_key.clone();
+                    let prompt_hash = prompt_hash.clone();
+                    let provider = route_outcome.provider;
+                    let model = route_outcome.model.clone();
+                    let response = route_outcome.response.content.clone();
+                    let tokens = route_outcome.response.tokens;
+                    let cost = route_outcome.response.cost;
+                    let temperature = llm_request.temperature;
+                    let max_tokens = llm_request.max_tokens;
+                    pool.run(move |conn| {
+                        let expires_at = cache_manager.default_expiry();
+                        cache_manager
+                            .upsert(
+                                conn,
+                                CacheRecord {
+                                    cache_key: &cache_key,
+                                    provider,
+                                    model: &model,
+                                    prompt_hash: &prompt_hash,
+                                    response: &response,
+                                    tokens,
+                                    cost,
+                                    temperature,
+                                    max_tokens,
+                                    expires_at,
+                                },
+                            )
+                            .map_err(|e| format!("Failed to store cache entry: {}", e))
+                    })
+                    .await?;
                 }
 
                 // ✅ Handle tool calls in response
                 if let Some(tool_calls) = &route_outcome.response.tool_calls {
                     if let Some(ref executor) = _tool_executor {
                         // Save assistant message with tool calls
-                        let _assistant_msg_with_tools = {
-                            let conn = db.conn.lock().map_err(|e| e.to_string())?;
+                        let assistant_msg_with_tools = {
                             let mut assistant = Message::new(
                                 conversation_id,
                                 MessageRole::Assistant,
@@ -1418,17 +1829,21 @@ pub async fn chat_send_message(
                                 assistant.cost = Some(cost);
                             }
 
-                            let msg_id =
-                                repository::create_message(&conn, &assistant).map_err(|e| {
-                                    format!("Failed to create assistant message: {}", e)
-                                })?;
-                            repository::get_message(&conn, msg_id).map_err(|e| {
-                                format!("Failed to retrieve assistant message: {}", e)
-                            })?
+                            pool.run(move |conn| {
+                                let msg_id =
+                                    repository::create_message(conn, &assistant).map_err(|e| {
+                                        format!("Failed to create assistant message: {}", e)
+                                    })?;
+                                repository::get_message(conn, msg_id).map_err(|e| {
+                                    format!("Failed to retrieve assistant message: {}", e)
+                                })
+                            })
+                            .await?
                         };
 
                         // Execute all tool calls
                         let mut tool_results = Vec::new();
+                        let mut tool_records = Vec::new();
                         for tool_call in tool_calls {
                             tracing::info!(
                                 "[Chat] Executing tool: {} ({})",
@@ -1440,26 +1855,37 @@ pub async fn chat_send_message(
                                 Ok(result) => {
                                     let formatted = executor.format_tool_result(tool_call, &result);
                                     tool_results.push((tool_call.id.clone(), formatted));
+                                    tool_records.push(ToolCallRecord::new(
+                                        &tool_call.id,
+                                        &tool_call.name,
+                                        &tool_call.arguments,
+                                        None,
+                                    ));
                                     tracing::info!("[Chat] Tool {} succeeded", tool_call.name);
                                 }
                                 Err(e) => {
                                     let error_msg = format!("Tool execution failed: {}", e);
                                     tool_results.push((tool_call.id.clone(), error_msg));
+                                    tool_records.push(ToolCallRecord::new(
+                                        &tool_call.id,
+                                        &tool_call.name,
+                                        &tool_call.arguments,
+                                        Some(e.to_string()),
+                                    ));
                                     tracing::error!("[Chat] Tool {} failed: {}", tool_call.name, e);
                                 }
                             }
                         }
 
-                        // Add tool results to conversation
-                        for (tool_call_id, result_content) in tool_results {
-                            let conn = db.conn.lock().map_err(|e| e.to_string())?;
-                            let tool_result_msg = Message::new(
-                                conversation_id,
-                                MessageRole::System, // Tool results as system messages
-                                format!("Tool result [{}]: {}", tool_call_id, result_content),
-                            );
-                            repository::create_message(&conn, &tool_result_msg)
-                                .map_err(|e| format!("Failed to save tool result: {}", e))?;
+                        let message_id = assistant_msg_with_tools.id;
+                        if let Err(e) = pool
+                            .run(move |conn| {
+                                repository::set_message_tool_calls(conn, message_id, &tool_records)
+                                    .map_err(|e| e.to_string())
+                            })
+                            .await
+                        {
+                            warn!("Failed to record tool calls: {}", e);
                         }
 
                         // Continue conversation with tool results (non-streaming for now)
@@ -1468,22 +1894,24 @@ pub async fn chat_send_message(
                             tool_calls.len()
                         );
 
-                        let updated_history = {
-                            let conn = db.conn.lock().map_err(|e| e.to_string())?;
-                            repository::list_messages(&conn, conversation_id)
-                                .map_err(|e| format!("Failed to list messages: {}", e))?
-                        };
-
-                        let updated_messages: Vec<RouterChatMessage> = updated_history
-                            .iter()
-                            .map(|message| RouterChatMessage {
-                                role: message.role.as_str().to_string(),
-                                content: message.content.clone(),
-                                tool_calls: None,
-                                tool_call_id: None,
-                                multimodal_content: None,
+                        let updated_history = pool
+                            .run(move |conn| {
+                                save_tool_results(conn, conversation_id, tool_results)?;
+                                repository::list_messages(conn, conversation_id)
+                                    .map_err(|e| format!("Failed to list messages: {}", e))
                             })
-                            .collect();
+                            .await?;
+
+                        let updated_messages = windowed_history(
+                            &pool,
+                            &llm_state,
+                            conversation_id,
+                            &updated_history,
+                            &llm_request.model,
+                            &[],
+                            tool_defs_for_follow_up.as_ref(),
+                        )
+                        .await;
 
                         let follow_up_request = LLMRequest {
                             messages: updated_messages,
@@ -1493,6 +1921,7 @@ pub async fn chat_send_message(
                             stream: false,
                             tools: tool_defs_for_follow_up.clone(),
                             tool_choice: Some(crate::router::ToolChoice::Auto),
+                            response_format: None,
                         };
 
                         // Make follow-up request
@@ -1525,66 +1954,67 @@ pub async fn chat_send_message(
             .unwrap_or_else(|| "All providers failed with unknown errors.".to_string())
     })?;
 
-    let (conversation, assistant_message, stats, last_message) = {
-        let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-        let mut assistant = Message::new(
-            conversation_id,
-            MessageRole::Assistant,
-            outcome.response.content.clone(),
-        )
-        .with_source(
-            Some(outcome.provider.as_string().to_string()),
-            Some(outcome.model.clone()),
-        );
+    let mut assistant = Message::new(
+        conversation_id,
+        MessageRole::Assistant,
+        outcome.response.content.clone(),
+    )
+    .with_source(
+        Some(outcome.provider.as_string().to_string()),
+        Some(outcome.model.clone()),
+    );
 
-        if let Some(tokens) = outcome.response.tokens {
-            assistant.tokens = Some(tokens as i32);
-        }
-        if let Some(cost) = outcome.response.cost {
-            assistant.cost = Some(cost);
-        }
+    if let Some(tokens) = outcome.response.tokens {
+        assistant.tokens = Some(tokens as i32);
+    }
+    if let Some(cost) = outcome.response.cost {
+        assistant.cost = Some(cost);
+    }
 
-        let assistant_id = repository::create_message(&conn, &assistant)
-            .map_err(|e| format!("Failed to create assistant message: {}", e))?;
-        let assistant_message = repository::get_message(&conn, assistant_id)
-            .map_err(|e| format!("Failed to retrieve assistant message: {}", e))?;
-
-        let mut conversation = repository::get_conversation(&conn, conversation_id)
-            .map_err(|e| format!("Failed to load conversation: {}", e))?;
-
-        if conversation.title == "New Conversation" {
-            let new_title = trimmed_content
-                .split('\n')
-                .next()
-                .unwrap_or_default()
-                .chars()
-                .take(50)
-                .collect::<String>()
-                .trim()
-                .to_string();
-
-            if !new_title.is_empty() && new_title != conversation.title {
-                repository::update_conversation_title(&conn, conversation_id, new_title.clone())
-                    .map_err(|e| format!("Failed to update conversation title: {}", e))?;
-                conversation = repository::get_conversation(&conn, conversation_id)
-                    .map_err(|e| format!("Failed to refresh conversation: {}", e))?;
+    let title_source = trimmed_content.clone();
+    let (conversation, assistant_message, stats, last_message) = pool
+        .run(move |conn| -> Result<_, String> {
+            let assistant_id = repository::create_message(conn, &assistant)
+                .map_err(|e| format!("Failed to create assistant message: {}", e))?;
+            let assistant_message = repository::get_message(conn, assistant_id)
+                .map_err(|e| format!("Failed to retrieve assistant message: {}", e))?;
+
+            let mut conversation = repository::get_conversation(conn, conversation_id)
+                .map_err(|e| format!("Failed to load conversation: {}", e))?;
+
+            if conversation.title == "New Conversation" {
+                let new_title = title_source
+                    .split('\n')
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(50)
+                    .collect::<String>()
+                    .trim()
+                    .to_string();
+
+                if !new_title.is_empty() && new_title != conversation.title {
+                    repository::update_conversation_title(conn, conversation_id, new_title.clone())
+                        .map_err(|e| format!("Failed to update conversation title: {}", e))?;
+                    conversation = repository::get_conversation(conn, conversation_id)
+                        .map_err(|e| format!("Failed to refresh conversation: {}", e))?;
+                }
             }
-        }
 
-        let messages = repository::list_messages(&conn, conversation_id)
-            .map_err(|e| format!("Failed to list messages: {}", e))?;
+            let messages = repository::list_messages(conn, conversation_id)
+                .map_err(|e| format!("Failed to list messages: {}", e))?;
 
-        let stats = ConversationStats {
-            message_count: messages.len(),
-            total_tokens: messages.iter().filter_map(|m| m.tokens).sum(),
-            total_cost: messages.iter().filter_map(|m| m.cost).sum(),
-        };
+            let stats = ConversationStats {
+                message_count: messages.len(),
+                total_tokens: messages.iter().filter_map(|m| m.tokens).sum(),
+                total_cost: messages.iter().filter_map(|m| m.cost).sum(),
+            };
 
-        let last_message = messages.last().map(|m| m.content.clone());
+            let last_message = messages.last().map(|m| m.content.clone());
 
-        (conversation, assistant_message, stats, last_message)
-    };
+            Ok((conversation, assistant_message, stats, last_message))
+        })
+        .await?;
 
     if stream_mode {
         let start_payload = StreamStartPayload {
@@ -1713,12 +2143,112 @@ fn split_stream_chunks(content: &str) -> Vec<String> {
 
 #[derive(Debug, Serialize)]
 pub struct CostOverviewResponse {
+    /// LLM and tool spend together
     pub today_total: f64,
     pub month_total: f64,
+    /// The part of the totals spent by tool calls
+    pub tool_today_total: f64,
+    pub tool_month_total: f64,
     pub monthly_budget: Option<f64>,
     pub remaining_budget: Option<f64>,
 }
 
+/// Forecast what sending `draft_message` would cost: the prompt is assembled
+/// as the streaming send path builds it, counted per section and priced for
+/// `model` (or the configured default).
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn chat_estimate_cost(
+    pool: State<'_, Pool>,
+    settings_state: State<'_, crate::commands::settings::SettingsState>,
+    app_handle: tauri::AppHandle,
+    conversation_id: Option<i64>,
+    draft_message: String,
+    model: Option<String>,
+    provider: Option<String>,
+    enable_tools: Option<bool>,
+) -> Result<CostForecast, String> {
+    let draft = draft_message.trim();
+    if draft.is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+
+    let provider_name = match provider {
+        Some(provider) => provider,
+        None => settings_state
+            .settings
+            .lock()
+            .await
+            .llm_config
+            .default_provider
+            .clone(),
+    };
+    let provider = Provider::from_string(&provider_name)
+        .ok_or_else(|| format!("Unknown provider: {}", provider_name))?;
+    let model = match model {
+        Some(model) => model,
+        None => default_model(&settings_state, Some(&provider_name)).await,
+    };
+
+    let history = match conversation_id {
+        Some(id) => {
+            pool.run(move |conn| {
+                repository::list_messages(conn, id)
+                    .map_err(|e| format!("Failed to list messages: {}", e))
+            })
+            .await?
+        }
+        None => Vec::new(),
+    };
+
+    let (tool_definitions, _) =
+        chat_tool_definitions(&app_handle, enable_tools.unwrap_or(true), None);
+    let tools_json = match &tool_definitions {
+        Some(tools) => serde_json::to_string(tools).map_err(|e| e.to_string())?,
+        None => String::new(),
+    };
+
+    let count = |text: &str| TokenCounter::estimate_section_tokens(provider, text);
+    let system_tokens = count(CHAT_SYSTEM_PROMPT);
+    let tools_tokens = count(&tools_json);
+    let message_tokens: Vec<u32> = history.iter().map(|m| count(&m.content)).collect();
+    let history_tokens: u32 = message_tokens.iter().sum();
+
+    // The previous request ended with the last user message; everything up
+    // to it is a prefix the provider has just seen
+    let cacheable_tokens = match history.iter().rposition(|m| m.role == MessageRole::User) {
+        Some(last_user) => {
+            system_tokens + tools_tokens + message_tokens[..=last_user].iter().sum::<u32>()
+        }
+        None => 0,
+    };
+
+    let recent_replies: Vec<u32> = history
+        .iter()
+        .zip(&message_tokens)
+        .filter(|(m, _)| m.role == MessageRole::Assistant)
+        .rev()
+        .take(RECENT_REPLIES_FOR_FORECAST)
+        .map(|(m, &estimated)| m.tokens.map_or(estimated, |t| t.max(0) as u32))
+        .collect();
+
+    let sections = vec![
+        SectionTokens::new("system", system_tokens),
+        SectionTokens::new("tools", tools_tokens),
+        SectionTokens::new("history", history_tokens),
+        SectionTokens::new("draft", count(draft)),
+    ];
+
+    Ok(cost_forecast::forecast(
+        &CostCalculator::new(),
+        provider,
+        &model,
+        sections,
+        cacheable_tokens,
+        &recent_replies,
+    ))
+}
+
 #[tauri::command]
 pub fn chat_get_cost_overview(db: State<AppDatabase>) -> Result<CostOverviewResponse, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
@@ -1737,6 +2267,12 @@ pub fn chat_get_cost_overview(db: State<AppDatabase>) -> Result<CostOverviewResp
         .map_err(|e| format!("Failed to compute today's cost: {}", e))?;
     let month_total = repository::sum_cost_since(&conn, month_start)
         .map_err(|e| format!("Failed to compute monthly cost: {}", e))?;
+    let tool_today_total = tool_costs::tool_cost_since(&conn, today_start)
+        .map_err(|e| format!("Failed to compute today's tool cost: {}", e))?;
+    let tool_month_total = tool_costs::tool_cost_since(&conn, month_start)
+        .map_err(|e| format!("Failed to compute monthly tool cost: {}", e))?;
+    let today_total = today_total + tool_today_total;
+    let month_total = month_total + tool_month_total;
 
     let monthly_budget = repository::get_setting(&conn, "billing.monthly_budget")
         .ok()
@@ -1746,6 +2282,8 @@ pub fn chat_get_cost_overview(db: State<AppDatabase>) -> Result<CostOverviewResp
     Ok(CostOverviewResponse {
         today_total,
         month_total,
+        tool_today_total,
+        tool_month_total,
         monthly_budget,
         remaining_budget,
     })
@@ -1756,6 +2294,9 @@ pub struct CostAnalyticsResponse {
     pub timeseries: Vec<CostTimeseriesPoint>,
     pub providers: Vec<ProviderCostBreakdown>,
     pub top_conversations: Vec<ConversationCostBreakdown>,
+    /// Daily tool spend; tools have no provider or model, so filters don't apply
+    pub tool_timeseries: Vec<CostTimeseriesPoint>,
+    pub tools: Vec<ToolCostBreakdown>,
 }
 
 // Updated Nov 16, 2025: Added input validation for days parameter
@@ -1819,17 +2360,60 @@ pub fn chat_get_cost_analytics(
         model_ref,
     )
     .map_err(|e| format!("Failed to load top conversations: {}", e))?;
+    let tool_timeseries = tool_costs::tool_cost_timeseries(&conn, window)
+        .map_err(|e| format!("Failed to load tool cost timeseries: {}", e))?;
+    let tools = tool_costs::tool_cost_breakdown(&conn, start, end)
+        .map_err(|e| format!("Failed to load tool cost breakdown: {}", e))?;
 
     Ok(CostAnalyticsResponse {
         timeseries,
         providers,
         top_conversations,
+        tool_timeseries,
+        tools,
     })
 }
 
+/// Flat prices configured per tool
+#[tauri::command]
+pub fn chat_get_tool_pricing() -> Result<std::collections::HashMap<String, ToolPricing>, String> {
+    global_tool_costs()
+        .pricing()
+        .map_err(|e| format!("Failed to load tool pricing: {}", e))
+}
+
+/// Set what calls to a tool cost, or clear its pricing with `None`
+#[tauri::command]
+pub fn chat_set_tool_pricing(tool_id: String, pricing: Option<ToolPricing>) -> Result<(), String> {
+    let tool_id = tool_id.trim();
+    if tool_id.is_empty() {
+        return Err("Tool id cannot be empty".to_string());
+    }
+    if let Some(pricing) = &pricing {
+        let prices = [pricing.per_call, pricing.per_second]
+            .into_iter()
+            .chain(pricing.per_unit.values().copied());
+        for price in prices {
+            if !price.is_finite() || price < 0.0 {
+                return Err(format!(
+                    "Invalid price: {}. Prices must be non-negative",
+                    price
+                ));
+            }
+        }
+    }
+    global_tool_costs()
+        .set_pricing(tool_id, pricing)
+        .map_err(|e| format!("Failed to save tool pricing: {}", e))
+}
+
 // Updated Nov 16, 2025: Added input validation for budget amount
 #[tauri::command]
-pub fn chat_set_monthly_budget(db: State<AppDatabase>, amount: Option<f64>) -> Result<(), String> {
+pub fn chat_set_monthly_budget(
+    db: State<AppDatabase>,
+    llm_state: State<LLMState>,
+    amount: Option<f64>,
+) -> Result<(), String> {
     // Validate amount if provided
     if let Some(value) = amount {
         if value < 0.0 {
@@ -1862,6 +2446,13 @@ pub fn chat_set_monthly_budget(db: State<AppDatabase>, amount: Option<f64>) -> R
         None => repository::delete_setting(&conn, "billing.monthly_budget")
             .map_err(|e| format!("Failed to clear monthly budget: {}", e))?,
     }
+    drop(conn);
+
+    // The router enforces the monthly budget as a hard cap
+    llm_state
+        .budget_guard
+        .reload_limits()
+        .map_err(|e| format!("Failed to apply monthly budget: {}", e))?;
 
     Ok(())
 }
@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
-use tauri::{AppHandle, Emitter, State};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::{
-    cloud::{CloudAccount, CloudOAuthConfig, CloudStorageManager, ListOptions, ShareLink},
-    error::Result,
+    cloud::{
+        CloudAccount, CloudConnection, CloudOAuthConfig, CloudProvider, CloudStorageManager,
+        ListOptions, ShareLink, WebDavConfig,
+    },
+    error::{Error, Result},
+    security::SecretManager,
 };
 
 /// Shared application state for cloud storage operations
@@ -24,6 +30,13 @@ impl CloudState {
             manager: Arc::new(CloudStorageManager::new()),
         }
     }
+
+    /// Create state whose accounts persist refresh tokens in the secret vault
+    pub fn with_secrets(secrets: Arc<SecretManager>) -> Self {
+        Self {
+            manager: Arc::new(CloudStorageManager::with_secrets(secrets)),
+        }
+    }
 }
 
 /// Request payload for completing OAuth flow
@@ -33,6 +46,15 @@ pub struct CloudCompleteOAuthRequest {
     pub code: String,
 }
 
+/// Request payload for connecting a WebDAV server
+#[derive(Debug, serde::Deserialize)]
+pub struct CloudWebDavRequest {
+    #[serde(flatten)]
+    pub config: WebDavConfig,
+    pub password: String,
+    pub label: Option<String>,
+}
+
 /// Response payload containing authorization URL and state token
 #[derive(Debug, serde::Serialize)]
 pub struct CloudAuthorizationResponse {
@@ -119,6 +141,36 @@ pub async fn cloud_complete_oauth(
         .manager
         .complete_oauth(&request.state, &request.code)
         .await?;
+
+    if let Some((connection, label)) = state.manager.account_connection(&account_id) {
+        let conn = open_connection(&app)?;
+        insert_cloud_account(&conn, &account_id, &connection, label.as_deref())?;
+    }
+
+    let _ = app.emit("cloud:connected", &account_id);
+
+    Ok(CloudAccountResponse { account_id })
+}
+
+/// Connect a WebDAV server (Nextcloud, ownCloud, Synology, ...) with a password
+#[tauri::command]
+pub async fn cloud_connect_webdav(
+    request: CloudWebDavRequest,
+    state: State<'_, CloudState>,
+    app: AppHandle,
+) -> Result<CloudAccountResponse> {
+    tracing::info!("Connecting WebDAV server {}", request.config.url);
+
+    let account_id = state
+        .manager
+        .connect_webdav(request.config, request.password, request.label)
+        .await?;
+
+    if let Some((connection, label)) = state.manager.account_connection(&account_id) {
+        let conn = open_connection(&app)?;
+        insert_cloud_account(&conn, &account_id, &connection, label.as_deref())?;
+    }
+
     let _ = app.emit("cloud:connected", &account_id);
 
     Ok(CloudAccountResponse { account_id })
@@ -134,6 +186,10 @@ pub async fn cloud_disconnect(
     tracing::info!("Disconnecting cloud account {}", account_id);
 
     state.manager.disconnect(&account_id)?;
+
+    let conn = open_connection(&app)?;
+    delete_cloud_account(&conn, &account_id)?;
+
     let _ = app.emit("cloud:disconnected", &account_id);
     Ok(())
 }
@@ -314,3 +370,146 @@ pub async fn cloud_share(
         })
         .await
 }
+
+/// Load persisted cloud accounts (without secrets) for restoration at startup
+pub fn load_persisted_cloud_accounts(
+    conn: &Connection,
+) -> Result<Vec<(String, CloudConnection, Option<String>)>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, provider, label, client_id, redirect_uri, settings
+             FROM cloud_accounts
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| Error::Generic(format!("Database error: {}", e)))?;
+
+    let accounts = stmt
+        .query_map([], |row| {
+            let provider_raw: String = row.get(1)?;
+            let provider = provider_from_str(&provider_raw).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    1,
+                    rusqlite::types::Type::Text,
+                    Box::new(Error::Other(format!("Unknown provider {}", provider_raw))),
+                )
+            })?;
+
+            let connection = match provider {
+                CloudProvider::WebDav => {
+                    let settings: Option<String> = row.get(5)?;
+                    let config = serde_json::from_str(settings.as_deref().unwrap_or_default())
+                        .map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(
+                                5,
+                                rusqlite::types::Type::Text,
+                                Box::new(e),
+                            )
+                        })?;
+                    CloudConnection::WebDav(config)
+                }
+                _ => CloudConnection::OAuth(CloudOAuthConfig {
+                    provider,
+                    client_id: row.get(3)?,
+                    client_secret: None,
+                    redirect_uri: row.get(4)?,
+                }),
+            };
+
+            Ok((
+                row.get::<_, String>(0)?,
+                connection,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })
+        .map_err(|e| Error::Generic(format!("Database error: {}", e)))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| Error::Generic(format!("Database error: {}", e)))?;
+
+    Ok(accounts)
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<Connection> {
+    let db_path = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Generic(format!("Failed to get app data dir: {}", e)))?
+        .join("agiworkforce.db");
+
+    Connection::open(db_path).map_err(|e| Error::Generic(format!("Database error: {}", e)))
+}
+
+fn insert_cloud_account(
+    conn: &Connection,
+    account_id: &str,
+    connection: &CloudConnection,
+    label: Option<&str>,
+) -> Result<()> {
+    let now = Utc::now().timestamp();
+    let (client_id, redirect_uri, settings) = match connection {
+        CloudConnection::OAuth(config) => (
+            Some(config.client_id.as_str()),
+            Some(config.redirect_uri.as_str()),
+            None,
+        ),
+        CloudConnection::WebDav(config) => (
+            None,
+            None,
+            Some(
+                serde_json::to_string(config)
+                    .map_err(|e| Error::Generic(format!("Serialization error: {}", e)))?,
+            ),
+        ),
+    };
+
+    conn.execute(
+        "INSERT INTO cloud_accounts (id, provider, label, client_id, redirect_uri, settings, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            provider = excluded.provider,
+            label = excluded.label,
+            client_id = excluded.client_id,
+            redirect_uri = excluded.redirect_uri,
+            settings = excluded.settings,
+            updated_at = excluded.updated_at",
+        params![
+            account_id,
+            provider_to_string(connection.provider()),
+            label,
+            client_id,
+            redirect_uri,
+            settings,
+            now
+        ],
+    )
+    .map_err(|e| Error::Generic(format!("Database error: {}", e)))?;
+
+    Ok(())
+}
+
+fn delete_cloud_account(conn: &Connection, account_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM cloud_accounts WHERE id = ?1",
+        params![account_id],
+    )
+    .map_err(|e| Error::Generic(format!("Database error: {}", e)))?;
+    Ok(())
+}
+
+fn provider_to_string(provider: CloudProvider) -> &'static str {
+    match provider {
+        CloudProvider::GoogleDrive => "google_drive",
+        CloudProvider::Dropbox => "dropbox",
+        CloudProvider::OneDrive => "one_drive",
+        CloudProvider::WebDav => "webdav",
+    }
+}
+
+fn provider_from_str(value: &str) -> Option<CloudProvider> {
+    match value {
+        "google_drive" => Some(CloudProvider::GoogleDrive),
+        "dropbox" => Some(CloudProvider::Dropbox),
+        "one_drive" => Some(CloudProvider::OneDrive),
+        "webdav" => Some(CloudProvider::WebDav),
+        _ => None,
+    }
+}
@@ -114,6 +114,7 @@ Respond ONLY with the modified code. Do not include explanations or markdown for
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -125,7 +126,7 @@ Respond ONLY with the modified code. Do not include explanations or markdown for
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
@@ -260,6 +261,7 @@ Format your response as JSON:
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -271,7 +273,7 @@ Format your response as JSON:
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
@@ -0,0 +1,205 @@
+/**
+ * Code Review Commands
+ * Review git diffs with rule checks and the LLM, and post results to GitHub
+ */
+use tauri::{AppHandle, Manager, State};
+
+use crate::codebase::review::{self, DiffChunk, ReviewRange, ReviewReport};
+use crate::commands::llm::LLMState;
+use crate::commands::{EmbeddingServiceState, SettingsServiceState};
+use crate::filesystem::decode_path;
+
+/// Upper bound on LLM requests per review; the rest of the diff is rule-checked only
+const MAX_LLM_CHUNKS: usize = 20;
+const CONTEXT_SNIPPETS: usize = 3;
+const CONTEXT_SNIPPET_CHARS: usize = 1500;
+
+/// Review a diff. `range` defaults to uncommitted changes and `use_llm` to true
+#[tauri::command]
+pub async fn code_review_run(
+    repo_path: String,
+    range: Option<ReviewRange>,
+    use_llm: Option<bool>,
+    app_handle: AppHandle,
+    llm_state: State<'_, LLMState>,
+) -> Result<ReviewReport, String> {
+    let repo = decode_path(&repo_path);
+    let range = range.unwrap_or(ReviewRange::Uncommitted);
+    tracing::info!("Reviewing {:?} in {}", range, repo.display());
+
+    let diff = {
+        let repo = repo.clone();
+        let range = range.clone();
+        tokio::task::spawn_blocking(move || review::load_diff(&repo, &range))
+            .await
+            .map_err(|e| format!("Review task failed: {}", e))?
+            .map_err(|e| e.to_string())?
+    };
+    let files = review::parse_diff(&diff);
+    let mut findings = review::rule_findings(&repo, &files);
+    let mut notes = Vec::new();
+    let mut llm_reviewed = false;
+
+    if use_llm.unwrap_or(true) {
+        let chunks = review::chunk_diff(&files, review::CHUNK_CHARS);
+        if chunks.len() > MAX_LLM_CHUNKS {
+            let skipped: Vec<String> = chunks[MAX_LLM_CHUNKS..]
+                .iter()
+                .flat_map(|chunk| chunk.files.iter().cloned())
+                .collect();
+            notes.push(format!(
+                "Diff too large for a full LLM review; only rule checks ran for: {}",
+                skipped.join(", ")
+            ));
+        }
+
+        for chunk in chunks.iter().take(MAX_LLM_CHUNKS) {
+            let context = related_code(&app_handle, chunk).await;
+            let prompt = review::llm_prompt(chunk, &context);
+            let response = {
+                let router = llm_state.router.lock().await;
+                router.send_message(&prompt, None).await
+            };
+
+            match response.and_then(|response| review::parse_llm_findings(&response, chunk, &files))
+            {
+                Ok(chunk_findings) => {
+                    llm_reviewed = true;
+                    findings.extend(chunk_findings);
+                }
+                Err(e) => {
+                    tracing::warn!("LLM review of {:?} failed: {}", chunk.files, e);
+                    notes.push(format!(
+                        "LLM review failed for {}: {}",
+                        chunk.files.join(", "),
+                        e
+                    ));
+                }
+            }
+        }
+    }
+
+    let mut report = ReviewReport::new(range, &files, findings);
+    report.llm_reviewed = llm_reviewed;
+    report.notes = notes;
+    Ok(report)
+}
+
+/// Render a report as Markdown
+#[tauri::command]
+pub async fn code_review_to_markdown(report: ReviewReport) -> Result<String, String> {
+    Ok(report.to_markdown())
+}
+
+/// Post a report as a review on a GitHub pull request of the `origin` remote.
+/// Uses `token`, or the GitHub key stored in settings. Returns the review URL
+#[tauri::command]
+pub async fn code_review_post_github(
+    repo_path: String,
+    pull_request: u64,
+    report: ReviewReport,
+    token: Option<String>,
+    settings_state: State<'_, SettingsServiceState>,
+) -> Result<String, String> {
+    let token = match token.filter(|token| !token.trim().is_empty()) {
+        Some(token) => token,
+        None => settings_state
+            .service
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .get_api_key("github")
+            .map_err(|_| "No GitHub token configured".to_string())?,
+    };
+    let (owner, name) =
+        review::github_repository(&decode_path(&repo_path)).map_err(|e| e.to_string())?;
+
+    let response = reqwest::Client::new()
+        .post(format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}/reviews",
+            owner, name, pull_request
+        ))
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "AGI-Workforce")
+        .json(&report.to_github_review())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to post review: {}", e))?;
+
+    let status = response.status();
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid GitHub response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "GitHub rejected the review ({}): {}",
+            status,
+            body.get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error")
+        ));
+    }
+
+    Ok(body
+        .get("html_url")
+        .and_then(|url| url.as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Snippets from the semantic index related to a chunk, excluding the files
+/// under review
+async fn related_code(app_handle: &AppHandle, chunk: &DiffChunk) -> String {
+    let Some(state) = app_handle.try_state::<EmbeddingServiceState>() else {
+        return String::new();
+    };
+    let service = state.0.lock().await;
+
+    let embedding = {
+        let generator = service.generator();
+        let generator = generator.lock().await;
+        match generator.generate(&review::context_query(chunk)).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                tracing::debug!("No review context: {}", e);
+                return String::new();
+            }
+        }
+    };
+    let results = {
+        let similarity = service.similarity();
+        let similarity = similarity.lock().await;
+        match similarity.search(embedding, CONTEXT_SNIPPETS * 3) {
+            Ok(results) => results,
+            Err(e) => {
+                tracing::debug!("No review context: {}", e);
+                return String::new();
+            }
+        }
+    };
+
+    results
+        .into_iter()
+        .filter(|result| {
+            let path = result.metadata.file_path.replace('\\', "/");
+            !chunk.files.iter().any(|file| path.ends_with(file.as_str()))
+        })
+        .take(CONTEXT_SNIPPETS)
+        .map(|result| {
+            let content: String = result
+                .metadata
+                .content
+                .chars()
+                .take(CONTEXT_SNIPPET_CHARS)
+                .collect();
+            format!(
+                "#### {} (lines {}-{})\n{}\n",
+                result.metadata.file_path,
+                result.metadata.start_line,
+                result.metadata.end_line,
+                content
+            )
+        })
+        .collect()
+}
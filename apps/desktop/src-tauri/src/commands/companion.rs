@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use tauri::{AppHandle, State};
+
+use crate::sync::{
+    CompanionApprovalDecision, CompanionChannelConfig, CompanionPairing, CompanionSync,
+};
+
+/// Shared state for the mobile companion sync
+pub struct CompanionSyncState(pub Arc<CompanionSync>);
+
+/// Pair a companion device; returns the pairing key to import on the phone
+#[tauri::command]
+pub async fn companion_pair(
+    state: State<'_, CompanionSyncState>,
+) -> Result<CompanionPairing, String> {
+    state
+        .0
+        .pair()
+        .map_err(|e| format!("Failed to pair companion: {}", e))
+}
+
+/// Remove the companion pairing and stop publishing state
+#[tauri::command]
+pub async fn companion_unpair(state: State<'_, CompanionSyncState>) -> Result<(), String> {
+    state
+        .0
+        .unpair()
+        .await
+        .map_err(|e| format!("Failed to unpair companion: {}", e))
+}
+
+/// Choose where sealed companion state is published
+#[tauri::command]
+pub async fn companion_set_channel(
+    state: State<'_, CompanionSyncState>,
+    channel: CompanionChannelConfig,
+) -> Result<(), String> {
+    state
+        .0
+        .set_channel(channel)
+        .await
+        .map_err(|e| format!("Failed to set companion channel: {}", e))
+}
+
+/// Publish the current state immediately; returns whether anything was sent
+#[tauri::command]
+pub async fn companion_publish(
+    app_handle: AppHandle,
+    state: State<'_, CompanionSyncState>,
+) -> Result<bool, String> {
+    state
+        .0
+        .publish(&app_handle, true)
+        .await
+        .map_err(|e| format!("Failed to publish companion state: {}", e))
+}
+
+/// Apply an approval decision made on the companion after validating its token
+#[tauri::command]
+pub async fn companion_submit_approval(
+    app_handle: AppHandle,
+    state: State<'_, CompanionSyncState>,
+    decision: CompanionApprovalDecision,
+) -> Result<(), String> {
+    state
+        .0
+        .verify_decision(&decision)
+        .map_err(|e| format!("Rejected companion approval: {}", e))?;
+    state
+        .0
+        .apply_decision(&app_handle, &decision)
+        .await
+        .map_err(|e| e.to_string())
+}
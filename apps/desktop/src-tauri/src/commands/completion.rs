@@ -53,6 +53,7 @@ pub async fn get_code_completion(
         stream: false, // No streaming for completions (need full response fast)
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -139,6 +140,7 @@ pub async fn get_inline_completion(
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -149,7 +151,7 @@ pub async fn get_inline_completion(
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("Inline completion failed: {}", e))?;
 
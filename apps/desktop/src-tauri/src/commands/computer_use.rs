@@ -7,7 +7,6 @@ use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
 
-#[cfg(target_os = "windows")]
 use crate::automation::screen;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +71,7 @@ impl Default for ComputerUseState {
 pub async fn computer_use_start_session(
     state: State<'_, Arc<Mutex<ComputerUseState>>>,
 ) -> Result<String, String> {
+    crate::kill_switch::ensure_allowed("Computer use")?;
     let computer_state = state.lock().await;
     let session_id = uuid::Uuid::new_v4().to_string();
 
@@ -99,34 +99,26 @@ pub async fn computer_use_capture_screen(
 ) -> Result<ScreenCapture, String> {
     tracing::info!("Capturing screen");
 
-    #[cfg(target_os = "windows")]
-    {
-        let screenshot =
-            capture_screenshot().map_err(|e| format!("Failed to capture screenshot: {}", e))?;
-
-        let capture = ScreenCapture {
-            image_data: screenshot.image_data,
-            width: screenshot.width,
-            height: screenshot.height,
-            timestamp: current_timestamp(),
-        };
-
-        // Add to current session
-        let computer_state = state.lock().await;
-        if let Some(session_id) = computer_state.current_session.lock().await.as_ref() {
-            let mut sessions = computer_state.sessions.lock().await;
-            if let Some(session) = sessions.iter_mut().find(|s| &s.id == session_id) {
-                session.screenshots.push(capture.clone());
-            }
-        }
+    let screenshot =
+        capture_screenshot().map_err(|e| format!("Failed to capture screenshot: {}", e))?;
 
-        Ok(capture)
-    }
+    let capture = ScreenCapture {
+        image_data: screenshot.image_data,
+        width: screenshot.width,
+        height: screenshot.height,
+        timestamp: current_timestamp(),
+    };
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        Err("Screen capture not supported on this platform".to_string())
+    // Add to current session
+    let computer_state = state.lock().await;
+    if let Some(session_id) = computer_state.current_session.lock().await.as_ref() {
+        let mut sessions = computer_state.sessions.lock().await;
+        if let Some(session) = sessions.iter_mut().find(|s| &s.id == session_id) {
+            session.screenshots.push(capture.clone());
+        }
     }
+
+    Ok(capture)
 }
 
 /// Perform mouse click
@@ -136,32 +128,25 @@ pub async fn computer_use_click(
     y: i32,
     state: State<'_, Arc<Mutex<ComputerUseState>>>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Computer use")?;
     tracing::info!("Clicking at ({}, {})", x, y);
 
-    #[cfg(target_os = "windows")]
-    {
-        click(x, y).map_err(|e| format!("Failed to click: {}", e))?;
-
-        // Record action
-        let computer_state = state.lock().await;
-        record_action(
-            &computer_state,
-            ComputerAction {
-                action_type: ActionType::Click,
-                coordinates: Some((x, y)),
-                text: None,
-                key: None,
-            },
-        )
-        .await;
-
-        Ok(())
-    }
+    click(x, y).map_err(|e| format!("Failed to click: {}", e))?;
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        Err("Mouse control not supported on this platform".to_string())
-    }
+    // Record action
+    let computer_state = state.lock().await;
+    record_action(
+        &computer_state,
+        ComputerAction {
+            action_type: ActionType::Click,
+            coordinates: Some((x, y)),
+            text: None,
+            key: None,
+        },
+    )
+    .await;
+
+    Ok(())
 }
 
 /// Move mouse
@@ -171,32 +156,25 @@ pub async fn computer_use_move_mouse(
     y: i32,
     state: State<'_, Arc<Mutex<ComputerUseState>>>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Computer use")?;
     tracing::info!("Moving mouse to ({}, {})", x, y);
 
-    #[cfg(target_os = "windows")]
-    {
-        move_to(x, y).map_err(|e| format!("Failed to move mouse: {}", e))?;
-
-        // Record action
-        let computer_state = state.lock().await;
-        record_action(
-            &computer_state,
-            ComputerAction {
-                action_type: ActionType::MoveMouse,
-                coordinates: Some((x, y)),
-                text: None,
-                key: None,
-            },
-        )
-        .await;
-
-        Ok(())
-    }
+    move_to(x, y).map_err(|e| format!("Failed to move mouse: {}", e))?;
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        Err("Mouse control not supported on this platform".to_string())
-    }
+    // Record action
+    let computer_state = state.lock().await;
+    record_action(
+        &computer_state,
+        ComputerAction {
+            action_type: ActionType::MoveMouse,
+            coordinates: Some((x, y)),
+            text: None,
+            key: None,
+        },
+    )
+    .await;
+
+    Ok(())
 }
 
 /// Type text
@@ -205,32 +183,25 @@ pub async fn computer_use_type_text(
     text: String,
     state: State<'_, Arc<Mutex<ComputerUseState>>>,
 ) -> Result<(), String> {
+    crate::kill_switch::ensure_allowed("Computer use")?;
     tracing::info!("Typing text: {}", text);
 
-    #[cfg(target_os = "windows")]
-    {
-        type_text(&text).map_err(|e| format!("Failed to type text: {}", e))?;
-
-        // Record action
-        let computer_state = state.lock().await;
-        record_action(
-            &computer_state,
-            ComputerAction {
-                action_type: ActionType::Type,
-                coordinates: None,
-                text: Some(text),
-                key: None,
-            },
-        )
-        .await;
-
-        Ok(())
-    }
+    type_text(&text).map_err(|e| format!("Failed to type text: {}", e))?;
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        Err("Keyboard control not supported on this platform".to_string())
-    }
+    // Record action
+    let computer_state = state.lock().await;
+    record_action(
+        &computer_state,
+        ComputerAction {
+            action_type: ActionType::Type,
+            coordinates: None,
+            text: Some(text),
+            key: None,
+        },
+    )
+    .await;
+
+    Ok(())
 }
 
 /// Get session history
@@ -312,7 +283,6 @@ fn current_timestamp() -> u64 {
         .as_secs()
 }
 
-#[cfg(target_os = "windows")]
 fn capture_screenshot() -> Result<ScreenCapture, anyhow::Error> {
     use base64::{engine::general_purpose, Engine as _};
     use image::ImageEncoder;
@@ -342,7 +312,6 @@ fn capture_screenshot() -> Result<ScreenCapture, anyhow::Error> {
     })
 }
 
-#[cfg(target_os = "windows")]
 fn click(x: i32, y: i32) -> Result<(), anyhow::Error> {
     use enigo::{Enigo, Mouse, Settings};
     let mut enigo = Enigo::new(&Settings::default())?;
@@ -351,7 +320,6 @@ fn click(x: i32, y: i32) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-#[cfg(target_os = "windows")]
 fn move_to(x: i32, y: i32) -> Result<(), anyhow::Error> {
     use enigo::{Enigo, Mouse, Settings};
     let mut enigo = Enigo::new(&Settings::default())?;
@@ -359,7 +327,6 @@ fn move_to(x: i32, y: i32) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-#[cfg(target_os = "windows")]
 fn type_text(text: &str) -> Result<(), anyhow::Error> {
     use enigo::{Enigo, Keyboard, Settings};
     let mut enigo = Enigo::new(&Settings::default())?;
@@ -0,0 +1,16 @@
+use tauri::AppHandle;
+
+use crate::credential_health::{monitor, CredentialHealthOverview};
+use crate::error::Result;
+
+/// Expiry and last-check status of every connected account, as of the last check
+#[tauri::command]
+pub fn credentials_health_overview(app: AppHandle) -> Result<CredentialHealthOverview> {
+    monitor::load_overview(&app)
+}
+
+/// Re-check every connected account now; results are also emitted as `credentials://health`
+#[tauri::command]
+pub async fn credentials_health_check(app: AppHandle) -> Result<CredentialHealthOverview> {
+    monitor::check_all(&app).await
+}
@@ -1,11 +1,18 @@
 use std::collections::HashMap;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::Mutex;
 
+use crate::commands::AppDatabase;
+use crate::db::backup::{self, BackupInfo, BackupSchedule};
+use crate::db::migrations::{self, MigrationPlan, SchemaVersionInfo};
+use crate::db::Pool;
+
 use crate::database::{
-    ConnectionConfig, DeleteQuery, InsertQuery, MongoClient, PoolConfig, QueryBuilder,
-    QueryValidation, RedisClient, SelectQuery, SqlClient, SqlSecurityValidator, UpdateQuery,
+    ConnectionConfig, DeleteQuery, ImportMode, ImportSummary, InsertQuery, MongoClient, PoolConfig,
+    QueryBuilder, QueryValidation, RedisClient, SelectQuery, SqlClient, SqlSecurityValidator,
+    UpdateQuery,
 };
+use crate::document::TabularReadOptions;
 
 /// State for managing database clients
 pub struct DatabaseState {
@@ -771,3 +778,201 @@ mod tests {
         drop(state);
     }
 }
+
+// File import
+
+/// Import a CSV or Parquet file into a SQLite table
+///
+/// The table goes into the SQLite database behind `connection_id` when given,
+/// otherwise into `imports.db` in the app data directory.
+#[tauri::command]
+pub async fn db_import_file(
+    app: AppHandle,
+    file_path: String,
+    table_name: String,
+    mode: Option<ImportMode>,
+    options: Option<TabularReadOptions>,
+    connection_id: Option<String>,
+    state: State<'_, Mutex<DatabaseState>>,
+) -> Result<ImportSummary, String> {
+    let database_path = match connection_id {
+        Some(connection_id) => {
+            let state = state.lock().await;
+            state
+                .sql_client
+                .sqlite_path(&connection_id)
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        None => {
+            let data_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            std::fs::create_dir_all(&data_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+            data_dir.join("imports.db").to_string_lossy().into_owned()
+        }
+    };
+
+    let summary = tokio::task::spawn_blocking(move || {
+        let data = crate::document::tabular::read_table(
+            std::path::Path::new(&file_path),
+            &options.unwrap_or_default(),
+        )?;
+        let mut conn = rusqlite::Connection::open(&database_path)?;
+        crate::database::import::import_table(
+            &mut conn,
+            &table_name,
+            &data,
+            mode.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+    .map_err(|e| format!("Import failed: {}", e))?;
+
+    tracing::info!(
+        "Imported {} rows into {} ({})",
+        summary.rows_imported,
+        summary.table,
+        summary.database_path
+    );
+    Ok(summary)
+}
+
+// Application schema commands
+
+/// Report the local schema version along with applied and pending migrations
+#[tauri::command]
+pub async fn db_get_schema_version(
+    db: State<'_, AppDatabase>,
+) -> Result<SchemaVersionInfo, String> {
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    migrations::schema_version_info(&conn)
+        .map_err(|e| format!("Failed to read schema version: {}", e))
+}
+
+/// Migrate the local schema up or down to `target_version` (latest by default).
+/// A backup is taken first unless this is a dry run or nothing would change.
+/// A dry run lists the SQL each step would execute.
+#[tauri::command]
+pub async fn db_migrate_schema(
+    app: AppHandle,
+    db: State<'_, AppDatabase>,
+    target_version: Option<i32>,
+    dry_run: Option<bool>,
+) -> Result<MigrationPlan, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let mut conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+
+    if dry_run {
+        return migrations::preview_migrations(&mut conn, target_version)
+            .map_err(|e| format!("Migration dry run failed: {:#}", e));
+    }
+
+    let plan = migrations::plan_migrations(&conn, target_version).map_err(|e| e.to_string())?;
+    let backup_path = if !plan.steps.is_empty() {
+        let backup_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?
+            .join("backups");
+        let path = backup::backup_database(&conn, &backup_dir, "pre-migration")
+            .map_err(|e| format!("Failed to back up database: {}", e))?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let mut plan = migrations::migrate_to(&conn, target_version, false)
+        .map_err(|e| format!("Migration failed: {:#}", e))?;
+    plan.backup_path = backup_path;
+
+    tracing::info!(
+        "Schema migrated from v{} to v{} ({} steps)",
+        plan.current_version,
+        plan.target_version,
+        plan.steps.len()
+    );
+    Ok(plan)
+}
+
+// Backups
+
+/// Back up the app database to a timestamped file, zstd-compressed unless
+/// `compress` is false
+#[tauri::command]
+pub async fn db_backup_create(
+    app: AppHandle,
+    pool: State<'_, Pool>,
+    compress: Option<bool>,
+) -> Result<BackupInfo, String> {
+    let backup_dir = backup::backup_dir(&app).map_err(|e| e.to_string())?;
+    let compress = compress.unwrap_or(true);
+    pool.run(move |conn| -> Result<_, String> {
+        backup::create_backup(conn, &backup_dir, "manual", compress)
+            .map_err(|e| format!("Failed to back up database: {:#}", e))
+    })
+    .await
+}
+
+/// Backups on disk, newest first
+#[tauri::command]
+pub async fn db_backup_list(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let backup_dir = backup::backup_dir(&app).map_err(|e| e.to_string())?;
+    backup::list_backups(&backup_dir).map_err(|e| format!("Failed to list backups: {}", e))
+}
+
+/// Replace the app database with a backup. The current data is backed up
+/// first so the restore can be undone.
+#[tauri::command]
+pub async fn db_backup_restore(
+    app: AppHandle,
+    pool: State<'_, Pool>,
+    path: String,
+) -> Result<BackupInfo, String> {
+    let backup_dir = backup::backup_dir(&app).map_err(|e| e.to_string())?;
+    pool.run(move |conn| -> Result<_, String> {
+        let undo = backup::create_backup(conn, &backup_dir, "pre-restore", true)
+            .map_err(|e| format!("Failed to back up database: {:#}", e))?;
+        backup::restore_backup(conn, std::path::Path::new(&path))
+            .map_err(|e| format!("Restore failed: {:#}", e))?;
+        Ok(undo)
+    })
+    .await
+}
+
+/// Run `PRAGMA integrity_check`; an empty list means the database is sound
+#[tauri::command]
+pub async fn db_integrity_check(pool: State<'_, Pool>) -> Result<Vec<String>, String> {
+    pool.run(|conn| -> Result<_, String> {
+        backup::integrity_check(conn).map_err(|e| format!("Integrity check failed: {}", e))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn db_backup_get_schedule(pool: State<'_, Pool>) -> Result<BackupSchedule, String> {
+    pool.run(|conn| -> Result<_, String> { Ok(backup::schedule(conn)) })
+        .await
+}
+
+/// Change how often scheduled backups run; an interval of 0 turns them off
+#[tauri::command]
+pub async fn db_backup_set_schedule(
+    pool: State<'_, Pool>,
+    schedule: BackupSchedule,
+) -> Result<(), String> {
+    pool.run(move |conn| -> Result<_, String> {
+        backup::set_schedule(conn, schedule)
+            .map_err(|e| format!("Failed to save backup schedule: {}", e))
+    })
+    .await
+}
@@ -132,6 +132,7 @@ Provide solutions in JSON format:
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -143,7 +144,7 @@ Provide solutions in JSON format:
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
@@ -204,6 +205,7 @@ Provide analysis in JSON format:
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -215,7 +217,7 @@ Provide analysis in JSON format:
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
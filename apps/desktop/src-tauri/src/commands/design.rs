@@ -97,6 +97,7 @@ pub async fn design_generate_css(
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -108,7 +109,7 @@ pub async fn design_generate_css(
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
@@ -222,6 +223,7 @@ Ensure colors have proper contrast ratios (WCAG AA minimum):
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -233,7 +235,7 @@ Ensure colors have proper contrast ratios (WCAG AA minimum):
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
@@ -292,6 +294,7 @@ Return as JSON array:
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -303,7 +306,7 @@ Return as JSON array:
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
@@ -404,6 +407,7 @@ Return as JSON:
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -415,7 +419,7 @@ Return as JSON:
     }
 
     let outcome = router
-        .invoke_candidate(&candidates[0], &llm_request)
+        .invoke_with_failover(&candidates, &llm_request)
         .await
         .map_err(|e| format!("LLM request failed: {}", e))?;
 
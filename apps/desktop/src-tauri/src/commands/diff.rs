@@ -62,6 +62,7 @@ Modified Code:"#,
         max_tokens: Some(2000),
         temperature: Some(0.2),
         stream: false,
+        response_format: None,
     };
 
     let router = router_state.lock().await;
@@ -1,10 +1,15 @@
 use std::sync::Arc;
-use tauri::{command, State};
+use tauri::{command, AppHandle, Emitter, Manager, State};
 
+use crate::cancellation::global_operations;
+use crate::commands::AppDatabase;
 use crate::document::{
     DocumentContent,
+    DocumentConversion,
     DocumentManager,
     DocumentMetadata,
+    // Templates
+    DocumentTemplate,
     // Creation types
     ExcelDocumentConfig,
     ExcelDocumentCreator,
@@ -13,11 +18,15 @@ use crate::document::{
     PdfDocumentConfig,
     PdfDocumentCreator,
     SearchResult,
+    TabularPreview,
+    TabularReadOptions,
+    TemplateStore,
     WordContent,
     WordDocumentConfig,
     WordDocumentCreator,
 };
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::projects::{ArchiveIngestReport, ProjectManager};
 
 pub struct DocumentState {
     pub manager: Arc<DocumentManager>,
@@ -37,22 +46,56 @@ impl DocumentState {
     }
 }
 
-/// Read a document and extract its content
+/// Read a document and extract its content. Runs as a cancellable
+/// operation under `operation_id` when given.
 #[command]
 pub async fn document_read(
     file_path: String,
+    operation_id: Option<String>,
+    app: AppHandle,
     state: State<'_, DocumentState>,
+    db: State<'_, AppDatabase>,
 ) -> Result<DocumentContent> {
-    state.manager.read_document(&file_path).await
+    let content = global_operations()
+        .begin("document_read", operation_id)
+        .run_with_events(&app, state.manager.read_document(&file_path))
+        .await??;
+    // A cancelled read never reaches the search index
+    index_for_search(&db, &file_path, &content.text);
+    Ok(content)
 }
 
-/// Extract plain text from a document
+/// Extract plain text from a document. Runs as a cancellable operation
+/// under `operation_id` when given.
 #[command]
 pub async fn document_extract_text(
     file_path: String,
+    operation_id: Option<String>,
+    app: AppHandle,
     state: State<'_, DocumentState>,
+    db: State<'_, AppDatabase>,
 ) -> Result<String> {
-    state.manager.extract_text(&file_path).await
+    let text = global_operations()
+        .begin("document_extract_text", operation_id)
+        .run_with_events(&app, state.manager.extract_text(&file_path))
+        .await??;
+    index_for_search(&db, &file_path, &text);
+    Ok(text)
+}
+
+/// Keep the global search index in step with documents the user has opened
+fn index_for_search(db: &AppDatabase, file_path: &str, text: &str) {
+    let title = std::path::Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(file_path);
+    let result = match db.conn.lock() {
+        Ok(conn) => crate::search::index_document(&conn, file_path, title, text),
+        Err(e) => Err(anyhow::anyhow!("Failed to lock database: {}", e)),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to index {} for search: {}", file_path, e);
+    }
 }
 
 /// Get metadata from a document
@@ -172,3 +215,156 @@ pub async fn document_create_pdf_simple(
     creator.create_simple(&output_path, title, author, paragraphs)?;
     Ok(output_path)
 }
+
+// ====================
+// Document Template Commands
+// ====================
+
+fn template_store(app: &AppHandle) -> Result<TemplateStore> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Generic(format!("Failed to get app data dir: {}", e)))?
+        .join("document_templates");
+    Ok(TemplateStore::new(dir))
+}
+
+/// Store a .docx or .xlsx file as a reusable template
+#[command]
+pub async fn document_template_save(
+    app: AppHandle,
+    name: String,
+    source_path: String,
+) -> Result<DocumentTemplate> {
+    template_store(&app)?.save(&name, &source_path)
+}
+
+/// List stored templates with the placeholders each expects
+#[command]
+pub async fn document_template_list(app: AppHandle) -> Result<Vec<DocumentTemplate>> {
+    template_store(&app)?.list()
+}
+
+/// Delete a stored template
+#[command]
+pub async fn document_template_delete(app: AppHandle, name: String) -> Result<()> {
+    template_store(&app)?.delete(&name)
+}
+
+/// Render a template with JSON data into a Word, Excel or PDF document
+///
+/// `template` is the name of a stored template or a path to a .docx/.xlsx file.
+/// The output format follows the extension of `output_path`.
+#[command]
+pub async fn document_render_template(
+    app: AppHandle,
+    template: String,
+    data: serde_json::Value,
+    output_path: String,
+) -> Result<String> {
+    let template_path = if std::path::Path::new(&template).is_file() {
+        template
+    } else {
+        template_store(&app)?
+            .resolve(&template)?
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    let output = output_path.clone();
+    tokio::task::spawn_blocking(move || {
+        crate::document::template::render_template(&template_path, &data, &output)
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Template rendering task failed: {}", e)))??;
+
+    Ok(output_path)
+}
+
+// ====================
+// Document Conversion Commands
+// ====================
+
+/// Convert between Word, PDF and Markdown
+///
+/// Both formats are detected from the file extensions, falling back to the
+/// input file's contents when its extension is missing or unknown.
+#[command]
+pub async fn document_convert(
+    input_path: String,
+    output_path: String,
+) -> Result<DocumentConversion> {
+    tokio::task::spawn_blocking(move || {
+        crate::document::markdown::convert(&input_path, &output_path)
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Document conversion task failed: {}", e)))?
+}
+
+/// Read a Word, PDF or Markdown file as structured Markdown for LLM context
+#[command]
+pub async fn document_to_markdown(file_path: String) -> Result<String> {
+    tokio::task::spawn_blocking(move || crate::document::markdown::to_markdown(&file_path))
+        .await
+        .map_err(|e| Error::Generic(format!("Document conversion task failed: {}", e)))?
+}
+
+// ====================
+// Tabular Data Commands
+// ====================
+
+/// Preview a CSV or Parquet file with its inferred schema
+///
+/// CSV delimiter, encoding and header row are sniffed unless set in `options`.
+#[command]
+pub async fn document_read_tabular(
+    file_path: String,
+    options: Option<TabularReadOptions>,
+) -> Result<TabularPreview> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        crate::document::tabular::preview(std::path::Path::new(&file_path), &options)
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Tabular read task failed: {}", e)))?
+}
+
+// ====================
+// Archive Ingestion Commands
+// ====================
+
+/// Ingest every PDF, DOCX, Markdown, text and HTML file in a zip archive into
+/// a project's knowledge base.
+///
+/// Emits `knowledge:ingest-progress` after each entry. Entries that fail or
+/// are skipped are listed in the report rather than aborting the import.
+#[command]
+pub async fn document_ingest_archive(
+    app: AppHandle,
+    project_id: String,
+    archive_path: String,
+) -> Result<ArchiveIngestReport> {
+    if !crate::projects::is_archive(std::path::Path::new(&archive_path)) {
+        return Err(Error::Generic(format!(
+            "Not a zip archive: {}",
+            archive_path
+        )));
+    }
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Generic(format!("Failed to get app data dir: {}", e)))?
+        .join("projects.db");
+
+    tokio::task::spawn_blocking(move || {
+        let manager = ProjectManager::new(db_path.clone(), db_path)
+            .map_err(|e| Error::Generic(format!("Failed to open knowledge base: {:#}", e)))?;
+        manager
+            .add_archive(&project_id, &archive_path, |progress| {
+                let _ = app.emit("knowledge:ingest-progress", progress);
+            })
+            .map_err(|e| Error::Generic(format!("Archive ingestion failed: {:#}", e)))
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Archive ingestion task failed: {}", e)))?
+}
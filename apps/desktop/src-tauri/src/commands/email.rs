@@ -6,14 +6,19 @@ use serde::{Deserialize, Serialize};
 use tauri::{command, AppHandle, Manager};
 use tracing::info;
 
+use crate::audit::{self, Actor, AuditCategory, AuditEntry};
 use crate::communications::{
     contacts::ContactManager,
+    email_outbox::{self, EmailOutbox, OutboxEntry, OutboxStatus},
     email_parser,
+    email_rules::{self, EmailRule, EmailRuleInput},
+    email_sync::{self, EmailSyncManager, EmailSyncStatus},
     imap_client::ImapClient,
     smtp_client::{OutgoingEmail, SmtpClient},
     Contact, Email, EmailAccount, EmailAddress, EmailFilter,
 };
 use crate::error::{Error, Result};
+use crate::security::AuthenticatedUser;
 use mailparse::parse_mail;
 
 const DEFAULT_FOLDER: &str = "INBOX";
@@ -107,7 +112,7 @@ impl EmailAccountRecord {
 }
 
 /// Request payload for sending email.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendEmailRequest {
     pub account_id: i64,
     pub to: Vec<EmailAddress>,
@@ -121,6 +126,9 @@ pub struct SendEmailRequest {
     pub body_html: Option<String>,
     #[serde(default)]
     pub attachments: Vec<String>,
+    /// Unix timestamp to send at; sends right away when unset
+    #[serde(default)]
+    pub send_at: Option<i64>,
 }
 
 /// Connect to an email account and persist configuration.
@@ -176,6 +184,10 @@ pub async fn email_connect(
 
     info!("Email account {} stored with id {}", email, account_id);
 
+    if let Some(sync) = app_handle.try_state::<EmailSyncManager>() {
+        sync.start(&app_handle, account_id);
+    }
+
     Ok(record.into_account())
 }
 
@@ -208,6 +220,9 @@ pub async fn email_list_accounts(app_handle: AppHandle) -> Result<Vec<EmailAccou
 #[command]
 pub async fn email_remove_account(app_handle: AppHandle, account_id: i64) -> Result<()> {
     info!("Removing email account {}", account_id);
+    if let Some(sync) = app_handle.try_state::<EmailSyncManager>() {
+        sync.stop(account_id);
+    }
     let conn = open_connection(&app_handle)?;
     conn.execute(
         "DELETE FROM email_accounts WHERE id = ?1",
@@ -279,6 +294,102 @@ pub async fn email_fetch_inbox(
     Ok(emails)
 }
 
+/// Read messages from the local cache kept by background sync; works offline.
+#[command]
+pub async fn email_list_cached(
+    app_handle: AppHandle,
+    account_id: i64,
+    folder: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<Email>> {
+    let conn = open_connection(&app_handle)?;
+    email_sync::cached_emails(
+        &conn,
+        account_id,
+        folder.as_deref().unwrap_or(DEFAULT_FOLDER),
+        limit.unwrap_or(50),
+    )
+}
+
+/// Start (or restart) background sync for an account.
+#[command]
+pub async fn email_sync_start(app_handle: AppHandle, account_id: i64) -> Result<()> {
+    let conn = open_connection(&app_handle)?;
+    fetch_account(&conn, account_id)?;
+    app_handle
+        .state::<EmailSyncManager>()
+        .start(&app_handle, account_id);
+    Ok(())
+}
+
+/// Stop background sync for an account.
+#[command]
+pub async fn email_sync_stop(app_handle: AppHandle, account_id: i64) -> Result<bool> {
+    Ok(app_handle.state::<EmailSyncManager>().stop(account_id))
+}
+
+/// Background sync state of every account being synced.
+#[command]
+pub async fn email_sync_status(app_handle: AppHandle) -> Result<Vec<EmailSyncStatus>> {
+    Ok(app_handle.state::<EmailSyncManager>().statuses())
+}
+
+/// Email rules in evaluation order; `account_id` narrows to the rules that
+/// apply to that account.
+#[command]
+pub async fn email_rules_list(
+    app_handle: AppHandle,
+    account_id: Option<i64>,
+) -> Result<Vec<EmailRule>> {
+    let conn = open_connection(&app_handle)?;
+    email_rules::list_rules(&conn, account_id)
+}
+
+/// Create an email rule.
+#[command]
+pub async fn email_rules_create(app_handle: AppHandle, rule: EmailRuleInput) -> Result<EmailRule> {
+    let conn = open_connection(&app_handle)?;
+    email_rules::create_rule(&conn, rule)
+}
+
+/// Replace an email rule's settings.
+#[command]
+pub async fn email_rules_update(
+    app_handle: AppHandle,
+    id: String,
+    rule: EmailRuleInput,
+) -> Result<EmailRule> {
+    let conn = open_connection(&app_handle)?;
+    email_rules::update_rule(&conn, &id, rule)
+}
+
+/// Delete an email rule.
+#[command]
+pub async fn email_rules_delete(app_handle: AppHandle, id: String) -> Result<bool> {
+    let conn = open_connection(&app_handle)?;
+    email_rules::delete_rule(&conn, &id)
+}
+
+/// Cached inbox messages a rule would match, without running its actions.
+/// Category conditions need the LLM and are ignored here.
+#[command]
+pub async fn email_rules_test(
+    app_handle: AppHandle,
+    rule: EmailRuleInput,
+    account_id: i64,
+    limit: Option<usize>,
+) -> Result<Vec<Email>> {
+    rule.validate()?;
+    let conn = open_connection(&app_handle)?;
+    let emails = email_sync::cached_emails(
+        &conn,
+        account_id,
+        email_sync::SYNC_FOLDER,
+        limit.unwrap_or(200),
+    )?;
+    Ok(email_rules::preview_matches(&rule, emails))
+}
+
 /// Mark a message as read/unread.
 #[command]
 pub async fn email_mark_read(
@@ -361,11 +472,133 @@ pub async fn email_download_attachment(
     Ok(file_path)
 }
 
-/// Send an email using the configured SMTP account.
+/// Queue an email for delivery through the outbox, at `send_at` if set.
+/// Delivery progress is reported through `email://outbox` events.
 #[command]
-pub async fn email_send(app_handle: AppHandle, request: SendEmailRequest) -> Result<String> {
+pub async fn email_send(
+    app_handle: AppHandle,
+    user: AuthenticatedUser,
+    request: SendEmailRequest,
+) -> Result<OutboxEntry> {
+    let queued = queue_email(&app_handle, &request);
+    audit_send(&app_handle, Actor::user(&user), &request, &queued);
+    queued
+}
+
+/// Validate and enqueue a message without auditing it. Callers record the
+/// send under their own actor.
+pub(crate) fn queue_email(
+    app_handle: &AppHandle,
+    request: &SendEmailRequest,
+) -> Result<OutboxEntry> {
+    validate_send_request(request)?;
+    let conn = open_connection(app_handle)?;
+    fetch_account(&conn, request.account_id)?;
+
+    let entry = email_outbox::enqueue(&conn, request)?;
+    info!(
+        "Queued email {} for account {} at {}",
+        entry.id, entry.account_id, entry.send_at
+    );
+    email_outbox::emit(app_handle, &entry);
+    if let Some(outbox) = app_handle.try_state::<EmailOutbox>() {
+        outbox.wake();
+    }
+
+    Ok(entry)
+}
+
+/// Record a queued send in the audit trail, keyed by its recipients
+pub(crate) fn audit_send(
+    app_handle: &AppHandle,
+    actor: Actor,
+    request: &SendEmailRequest,
+    result: &Result<OutboxEntry>,
+) {
+    let recipients = request
+        .to
+        .iter()
+        .chain(&request.cc)
+        .chain(&request.bcc)
+        .map(|recipient| recipient.email.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut entry = AuditEntry::new(actor, AuditCategory::Email, "email_send")
+        .target(recipients)
+        .result(result);
+    if let Ok(params) = serde_json::to_value(request) {
+        entry = entry.params(&params);
+    }
+    audit::record(app_handle, entry);
+}
+
+/// List outbox entries, newest first.
+#[command]
+pub async fn email_outbox_list(
+    app_handle: AppHandle,
+    account_id: Option<i64>,
+    status: Option<OutboxStatus>,
+    limit: Option<usize>,
+) -> Result<Vec<OutboxEntry>> {
     let conn = open_connection(&app_handle)?;
-    let record = fetch_account(&conn, request.account_id)?;
+    email_outbox::list_entries(&conn, account_id, status, limit.unwrap_or(100))
+}
+
+/// Cancel a queued email. Returns false if it is already being sent or done.
+#[command]
+pub async fn email_outbox_cancel(app_handle: AppHandle, id: String) -> Result<bool> {
+    let conn = open_connection(&app_handle)?;
+    let cancelled = email_outbox::cancel(&conn, &id)?;
+    if cancelled {
+        if let Some(entry) = email_outbox::get_entry(&conn, &id)? {
+            email_outbox::emit(&app_handle, &entry);
+        }
+    }
+    Ok(cancelled)
+}
+
+/// Reject requests that could never be delivered before they are queued.
+fn validate_send_request(request: &SendEmailRequest) -> Result<()> {
+    let recipients = request
+        .to
+        .iter()
+        .chain(&request.cc)
+        .chain(&request.bcc)
+        .chain(&request.reply_to);
+    let mut any = false;
+    for recipient in recipients {
+        any = true;
+        recipient.email.parse::<lettre::Address>().map_err(|err| {
+            Error::EmailSend(format!(
+                "Invalid email address '{}': {}",
+                recipient.email, err
+            ))
+        })?;
+    }
+    if !any {
+        return Err(Error::EmailSend(
+            "At least one recipient (To/CC/BCC) is required".to_string(),
+        ));
+    }
+    if let Some(missing) = request
+        .attachments
+        .iter()
+        .find(|path| !std::path::Path::new(path).is_file())
+    {
+        return Err(Error::EmailSend(format!(
+            "Attachment '{}' does not exist",
+            missing
+        )));
+    }
+    Ok(())
+}
+
+/// Send a message over the account's SMTP server. Used by the outbox worker.
+pub(crate) async fn deliver(app_handle: &AppHandle, request: &SendEmailRequest) -> Result<String> {
+    let record = {
+        let conn = open_connection(app_handle)?;
+        fetch_account(&conn, request.account_id)?
+    };
     let password = decode_password(&record.password)?;
 
     let smtp = SmtpClient::new(
@@ -379,14 +612,14 @@ pub async fn email_send(app_handle: AppHandle, request: SendEmailRequest) -> Res
 
     let outgoing = OutgoingEmail {
         from: EmailAddress::new(record.email.clone(), record.display_name.clone()),
-        to: request.to,
-        cc: request.cc,
-        bcc: request.bcc,
-        reply_to: request.reply_to,
-        subject: request.subject,
-        body_text: request.body_text,
-        body_html: request.body_html,
-        attachments: request.attachments,
+        to: request.to.clone(),
+        cc: request.cc.clone(),
+        bcc: request.bcc.clone(),
+        reply_to: request.reply_to.clone(),
+        subject: request.subject.clone(),
+        body_text: request.body_text.clone(),
+        body_html: request.body_html.clone(),
+        attachments: request.attachments.clone(),
     };
 
     smtp.send(outgoing).await
@@ -471,7 +704,28 @@ pub async fn contact_export_vcard(app_handle: AppHandle, file_path: String) -> R
     manager.export_vcard(&file_path).await
 }
 
-fn open_connection(app_handle: &AppHandle) -> Result<Connection> {
+/// Open an IMAP session for a stored account.
+pub(crate) async fn connect_account_imap(
+    app_handle: &AppHandle,
+    account_id: i64,
+) -> Result<ImapClient> {
+    let record = {
+        let conn = open_connection(app_handle)?;
+        fetch_account(&conn, account_id)?
+    };
+    let password = decode_password(&record.password)?;
+
+    ImapClient::connect(
+        &record.imap_host,
+        record.imap_port,
+        &record.email,
+        &password,
+        record.imap_use_tls,
+    )
+    .await
+}
+
+pub(crate) fn open_connection(app_handle: &AppHandle) -> Result<Connection> {
     let db_path = app_handle
         .path()
         .app_data_dir()
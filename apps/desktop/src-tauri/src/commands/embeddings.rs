@@ -6,13 +6,16 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 pub use crate::embeddings::{
-    __cmd__generate_code_embeddings, __cmd__get_embedding_stats, __cmd__get_indexing_progress,
-    __cmd__index_file, __cmd__index_workspace, __cmd__on_file_changed, __cmd__on_file_deleted,
-    __cmd__semantic_search_codebase,
+    __cmd__generate_code_embeddings, __cmd__get_embedding_model_status, __cmd__get_embedding_stats,
+    __cmd__get_indexing_progress, __cmd__hybrid_search_codebase, __cmd__index_file,
+    __cmd__index_workspace, __cmd__on_file_changed, __cmd__on_file_deleted,
+    __cmd__rebuild_embedding_index, __cmd__semantic_search_codebase, __cmd__set_embedding_model,
 };
 pub use crate::embeddings::{
-    generate_code_embeddings, get_embedding_stats, get_indexing_progress, index_file,
-    index_workspace, on_file_changed, on_file_deleted, semantic_search_codebase, EmbeddingService,
+    generate_code_embeddings, get_embedding_model_status, get_embedding_stats,
+    get_indexing_progress, hybrid_search_codebase, index_file, index_workspace, on_file_changed,
+    on_file_deleted, rebuild_embedding_index, semantic_search_codebase, set_embedding_model,
+    EmbeddingService,
 };
 
 /// Embedding service state wrapper
@@ -1,6 +1,9 @@
+use crate::audit::{self, Actor, AuditCategory, AuditEntry, AuditOutcome};
 use crate::commands::AppDatabase;
 use crate::db::models::PermissionType;
+use crate::filesystem::{decode_path, portable_path, resolve_path};
 use crate::security::permissions::PermissionManager;
+use crate::security::AuthenticatedUser;
 use base64::{engine::general_purpose, Engine as _};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -76,9 +79,10 @@ fn validate_path_security(path: &str) -> Result<(), String> {
     if path.is_empty() {
         return Err("Path cannot be empty".to_string());
     }
-    if path.len() > 4096 {
+    // Extended-length paths allow up to 32,767 UTF-16 units on Windows
+    if path.len() > 32_767 {
         return Err(format!(
-            "Path too long: {} characters. Maximum is 4096",
+            "Path too long: {} characters. Maximum is 32767",
             path.len()
         ));
     }
@@ -180,6 +184,7 @@ async fn log_file_operation(
     success: bool,
     error: Option<String>,
     db: &AppDatabase,
+    user: &AuthenticatedUser,
 ) -> Result<(), String> {
     let conn = db
         .conn
@@ -214,6 +219,24 @@ async fn log_file_operation(
     )
     .map_err(|e| format!("Failed to log audit entry: {}", e))?;
 
+    if !matches!(operation, FileOperation::Read) {
+        let mut entry = AuditEntry::new(
+            Actor::user(user),
+            AuditCategory::Filesystem,
+            format!("file_{}", operation.as_str()),
+        )
+        .target(path)
+        .outcome(if success {
+            AuditOutcome::Success
+        } else {
+            AuditOutcome::Failure
+        });
+        if let Some(error) = error {
+            entry = entry.detail(error);
+        }
+        audit::append(&conn, &entry).map_err(|e| format!("Failed to log audit entry: {}", e))?;
+    }
+
     Ok(())
 }
 
@@ -257,6 +280,7 @@ async fn _confirm_dangerous_operation(
 /// Read file contents
 #[tauri::command]
 pub async fn file_read(
+    user: AuthenticatedUser,
     path: String,
     state: tauri::State<'_, AppDatabase>,
 ) -> Result<String, String> {
@@ -266,7 +290,7 @@ pub async fn file_read(
     validate_path_security(&path)?;
 
     // Check file size before reading (prevent OOM)
-    match fs::metadata(&path) {
+    match fs::metadata(resolve_path(&path)) {
         Ok(metadata) => {
             if metadata.len() > 100_000_000 {
                 return Err(format!(
@@ -290,15 +314,16 @@ pub async fn file_read(
             false,
             Some(error.clone()),
             &state,
+            &user,
         )
         .await?;
         return Err(error);
     }
 
     // Read file
-    match fs::read_to_string(&path) {
+    match fs::read_to_string(resolve_path(&path)) {
         Ok(content) => {
-            log_file_operation(&path, FileOperation::Read, true, None, &state).await?;
+            log_file_operation(&path, FileOperation::Read, true, None, &state, &user).await?;
             info!("Successfully read file: {}", path);
             Ok(content)
         }
@@ -310,6 +335,7 @@ pub async fn file_read(
                 false,
                 Some(error.clone()),
                 &state,
+                &user,
             )
             .await?;
             Err(error)
@@ -321,6 +347,7 @@ pub async fn file_read(
 /// Write file contents
 #[tauri::command]
 pub async fn file_write(
+    user: AuthenticatedUser,
     path: String,
     content: String,
     state: tauri::State<'_, AppDatabase>,
@@ -347,13 +374,14 @@ pub async fn file_write(
             false,
             Some(error.clone()),
             &state,
+            &user,
         )
         .await?;
         return Err(error);
     }
 
     // Create parent directory if it doesn't exist
-    if let Some(parent) = Path::new(&path).parent() {
+    if let Some(parent) = resolve_path(&path).parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)
                 .map_err(|e| format!("Failed to create parent directory: {}", e))?;
@@ -361,9 +389,9 @@ pub async fn file_write(
     }
 
     // Write file
-    match fs::write(&path, content) {
+    match fs::write(resolve_path(&path), content) {
         Ok(_) => {
-            log_file_operation(&path, FileOperation::Write, true, None, &state).await?;
+            log_file_operation(&path, FileOperation::Write, true, None, &state, &user).await?;
             info!("Successfully wrote file: {}", path);
             Ok(())
         }
@@ -375,6 +403,7 @@ pub async fn file_write(
                 false,
                 Some(error.clone()),
                 &state,
+                &user,
             )
             .await?;
             Err(error)
@@ -385,14 +414,18 @@ pub async fn file_write(
 // Updated Nov 16, 2025: Added comprehensive input validation
 /// Delete file
 #[tauri::command]
-pub async fn file_delete(path: String, state: tauri::State<'_, AppDatabase>) -> Result<(), String> {
+pub async fn file_delete(
+    user: AuthenticatedUser,
+    path: String,
+    state: tauri::State<'_, AppDatabase>,
+) -> Result<(), String> {
     debug!("Deleting file: {}", path);
 
     // Validate path security
     validate_path_security(&path)?;
 
     // Verify it's a file, not a directory
-    match fs::metadata(&path) {
+    match fs::metadata(resolve_path(&path)) {
         Ok(metadata) => {
             if !metadata.is_file() {
                 return Err(format!(
@@ -415,15 +448,16 @@ pub async fn file_delete(path: String, state: tauri::State<'_, AppDatabase>) ->
             false,
             Some(error.clone()),
             &state,
+            &user,
         )
         .await?;
         return Err(error);
     }
 
     // Delete file
-    match fs::remove_file(&path) {
+    match fs::remove_file(resolve_path(&path)) {
         Ok(_) => {
-            log_file_operation(&path, FileOperation::Delete, true, None, &state).await?;
+            log_file_operation(&path, FileOperation::Delete, true, None, &state, &user).await?;
             info!("Successfully deleted file: {}", path);
             Ok(())
         }
@@ -435,6 +469,7 @@ pub async fn file_delete(path: String, state: tauri::State<'_, AppDatabase>) ->
                 false,
                 Some(error.clone()),
                 &state,
+                &user,
             )
             .await?;
             Err(error)
@@ -446,6 +481,7 @@ pub async fn file_delete(path: String, state: tauri::State<'_, AppDatabase>) ->
 /// Rename/move file
 #[tauri::command]
 pub async fn file_rename(
+    user: AuthenticatedUser,
     old_path: String,
     new_path: String,
     state: tauri::State<'_, AppDatabase>,
@@ -457,12 +493,12 @@ pub async fn file_rename(
     validate_path_security(&new_path)?;
 
     // Verify source exists and is a file
-    if !Path::new(&old_path).exists() {
+    if !resolve_path(&old_path).exists() {
         return Err(format!("Source file does not exist: {}", old_path));
     }
 
     // Prevent overwriting existing files
-    if Path::new(&new_path).exists() {
+    if resolve_path(&new_path).exists() {
         return Err(format!(
             "Destination already exists: {}. Cannot overwrite",
             new_path
@@ -478,10 +514,10 @@ pub async fn file_rename(
     }
 
     // Rename file
-    match fs::rename(&old_path, &new_path) {
+    match fs::rename(resolve_path(&old_path), resolve_path(&new_path)) {
         Ok(_) => {
-            log_file_operation(&old_path, FileOperation::Delete, true, None, &state).await?;
-            log_file_operation(&new_path, FileOperation::Write, true, None, &state).await?;
+            log_file_operation(&old_path, FileOperation::Delete, true, None, &state, &user).await?;
+            log_file_operation(&new_path, FileOperation::Write, true, None, &state, &user).await?;
             info!("Successfully renamed file: {} -> {}", old_path, new_path);
             Ok(())
         }
@@ -496,6 +532,7 @@ pub async fn file_rename(
 /// Copy file
 #[tauri::command]
 pub async fn file_copy(
+    user: AuthenticatedUser,
     src: String,
     dest: String,
     state: tauri::State<'_, AppDatabase>,
@@ -507,7 +544,7 @@ pub async fn file_copy(
     validate_path_security(&dest)?;
 
     // Check source exists and is a file
-    match fs::metadata(&src) {
+    match fs::metadata(resolve_path(&src)) {
         Ok(metadata) => {
             if !metadata.is_file() {
                 return Err(format!("Source is not a file: {}", src));
@@ -524,7 +561,7 @@ pub async fn file_copy(
     }
 
     // Prevent overwriting without warning
-    if Path::new(&dest).exists() {
+    if resolve_path(&dest).exists() {
         return Err(format!(
             "Destination already exists: {}. Cannot overwrite",
             dest
@@ -540,9 +577,9 @@ pub async fn file_copy(
     }
 
     // Copy file
-    match fs::copy(&src, &dest) {
+    match fs::copy(resolve_path(&src), resolve_path(&dest)) {
         Ok(_) => {
-            log_file_operation(&dest, FileOperation::Write, true, None, &state).await?;
+            log_file_operation(&dest, FileOperation::Write, true, None, &state, &user).await?;
             info!("Successfully copied file: {} -> {}", src, dest);
             Ok(())
         }
@@ -556,6 +593,7 @@ pub async fn file_copy(
 /// Move file (copy + delete)
 #[tauri::command]
 pub async fn file_move(
+    user: AuthenticatedUser,
     src: String,
     dest: String,
     state: tauri::State<'_, AppDatabase>,
@@ -571,19 +609,21 @@ pub async fn file_move(
     }
 
     // Try rename first (faster if on same filesystem)
-    match fs::rename(&src, &dest) {
+    match fs::rename(resolve_path(&src), resolve_path(&dest)) {
         Ok(_) => {
-            log_file_operation(&src, FileOperation::Delete, true, None, &state).await?;
-            log_file_operation(&dest, FileOperation::Write, true, None, &state).await?;
+            log_file_operation(&src, FileOperation::Delete, true, None, &state, &user).await?;
+            log_file_operation(&dest, FileOperation::Write, true, None, &state, &user).await?;
             info!("Successfully moved file: {} -> {}", src, dest);
             Ok(())
         }
         Err(_) => {
             // Fall back to copy + delete
-            fs::copy(&src, &dest).map_err(|e| format!("Failed to copy file: {}", e))?;
-            fs::remove_file(&src).map_err(|e| format!("Failed to delete source file: {}", e))?;
-            log_file_operation(&src, FileOperation::Delete, true, None, &state).await?;
-            log_file_operation(&dest, FileOperation::Write, true, None, &state).await?;
+            fs::copy(resolve_path(&src), resolve_path(&dest))
+                .map_err(|e| format!("Failed to copy file: {}", e))?;
+            fs::remove_file(resolve_path(&src))
+                .map_err(|e| format!("Failed to delete source file: {}", e))?;
+            log_file_operation(&src, FileOperation::Delete, true, None, &state, &user).await?;
+            log_file_operation(&dest, FileOperation::Write, true, None, &state, &user).await?;
             info!("Successfully moved file: {} -> {}", src, dest);
             Ok(())
         }
@@ -593,24 +633,24 @@ pub async fn file_move(
 // Updated Nov 16, 2025: Added input validation
 /// Check if file exists
 #[tauri::command]
-pub async fn file_exists(path: String) -> Result<bool, String> {
+pub async fn file_exists(_user: AuthenticatedUser, path: String) -> Result<bool, String> {
     // Validate path security
     validate_path_security(&path)?;
 
-    Ok(Path::new(&path).exists())
+    Ok(resolve_path(&path).exists())
 }
 
 // Updated Nov 16, 2025: Added input validation
 /// Get file metadata
 #[tauri::command]
-pub async fn file_metadata(path: String) -> Result<FileMetadata, String> {
+pub async fn file_metadata(_user: AuthenticatedUser, path: String) -> Result<FileMetadata, String> {
     debug!("Getting metadata for: {}", path);
 
     // Validate path security
     validate_path_security(&path)?;
 
-    let metadata =
-        fs::metadata(&path).map_err(|e| format!("Failed to get metadata for '{}': {}", path, e))?;
+    let metadata = fs::metadata(resolve_path(&path))
+        .map_err(|e| format!("Failed to get metadata for '{}': {}", path, e))?;
 
     let created = metadata
         .created()
@@ -643,14 +683,18 @@ pub async fn file_metadata(path: String) -> Result<FileMetadata, String> {
 // Updated Nov 16, 2025: Added comprehensive input validation
 /// Create directory (including parent directories)
 #[tauri::command]
-pub async fn dir_create(path: String, state: tauri::State<'_, AppDatabase>) -> Result<(), String> {
+pub async fn dir_create(
+    user: AuthenticatedUser,
+    path: String,
+    state: tauri::State<'_, AppDatabase>,
+) -> Result<(), String> {
     debug!("Creating directory: {}", path);
 
     // Validate path security
     validate_path_security(&path)?;
 
     // Check if already exists
-    if Path::new(&path).exists() {
+    if resolve_path(&path).exists() {
         return Err(format!("Path already exists: {}", path));
     }
 
@@ -660,9 +704,9 @@ pub async fn dir_create(path: String, state: tauri::State<'_, AppDatabase>) -> R
     }
 
     // Create directory
-    match fs::create_dir_all(&path) {
+    match fs::create_dir_all(resolve_path(&path)) {
         Ok(_) => {
-            log_file_operation(&path, FileOperation::Write, true, None, &state).await?;
+            log_file_operation(&path, FileOperation::Write, true, None, &state, &user).await?;
             info!("Successfully created directory: {}", path);
             Ok(())
         }
@@ -677,6 +721,7 @@ pub async fn dir_create(path: String, state: tauri::State<'_, AppDatabase>) -> R
 /// List directory contents
 #[tauri::command]
 pub async fn dir_list(
+    user: AuthenticatedUser,
     path: String,
     state: tauri::State<'_, AppDatabase>,
 ) -> Result<Vec<DirEntry>, String> {
@@ -686,7 +731,7 @@ pub async fn dir_list(
     validate_path_security(&path)?;
 
     // Verify it's a directory
-    match fs::metadata(&path) {
+    match fs::metadata(resolve_path(&path)) {
         Ok(metadata) => {
             if !metadata.is_dir() {
                 return Err(format!("Path is not a directory: {}", path));
@@ -701,7 +746,8 @@ pub async fn dir_list(
     }
 
     // Read directory
-    let entries = fs::read_dir(&path).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let entries = fs::read_dir(resolve_path(&path))
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
 
     let mut results = Vec::new();
 
@@ -721,7 +767,7 @@ pub async fn dir_list(
 
         results.push(DirEntry {
             name: entry.file_name().to_string_lossy().to_string(),
-            path: path_buf.to_string_lossy().to_string(),
+            path: portable_path(&path_buf),
             is_file: metadata.is_file(),
             is_dir: metadata.is_dir(),
             size: metadata.len(),
@@ -729,7 +775,7 @@ pub async fn dir_list(
         });
     }
 
-    log_file_operation(&path, FileOperation::Read, true, None, &state).await?;
+    log_file_operation(&path, FileOperation::Read, true, None, &state, &user).await?;
     Ok(results)
 }
 
@@ -737,6 +783,7 @@ pub async fn dir_list(
 /// Delete directory
 #[tauri::command]
 pub async fn dir_delete(
+    user: AuthenticatedUser,
     path: String,
     recursive: bool,
     state: tauri::State<'_, AppDatabase>,
@@ -747,7 +794,7 @@ pub async fn dir_delete(
     validate_path_security(&path)?;
 
     // Verify it's a directory
-    match fs::metadata(&path) {
+    match fs::metadata(resolve_path(&path)) {
         Ok(metadata) => {
             if !metadata.is_dir() {
                 return Err(format!(
@@ -771,14 +818,14 @@ pub async fn dir_delete(
 
     // Delete directory
     let result = if recursive {
-        fs::remove_dir_all(&path)
+        fs::remove_dir_all(resolve_path(&path))
     } else {
-        fs::remove_dir(&path)
+        fs::remove_dir(resolve_path(&path))
     };
 
     match result {
         Ok(_) => {
-            log_file_operation(&path, FileOperation::Delete, true, None, &state).await?;
+            log_file_operation(&path, FileOperation::Delete, true, None, &state, &user).await?;
             info!("Successfully deleted directory: {}", path);
             Ok(())
         }
@@ -793,6 +840,7 @@ pub async fn dir_delete(
 /// Traverse directory with glob pattern
 #[tauri::command]
 pub async fn dir_traverse(
+    user: AuthenticatedUser,
     path: String,
     glob_pattern: String,
     state: tauri::State<'_, AppDatabase>,
@@ -817,7 +865,7 @@ pub async fn dir_traverse(
     }
 
     // Verify path is a directory
-    match fs::metadata(&path) {
+    match fs::metadata(resolve_path(&path)) {
         Ok(metadata) => {
             if !metadata.is_dir() {
                 return Err(format!("Path is not a directory: {}", path));
@@ -831,11 +879,13 @@ pub async fn dir_traverse(
         return Err("Permission denied".to_string());
     }
 
-    // Build full glob pattern
+    // Build full glob pattern; the root is escaped so brackets and `?` in
+    // directory names are matched literally
+    let root = glob::Pattern::escape(&decode_path(&path).to_string_lossy());
     let full_pattern = if glob_pattern.is_empty() {
-        format!("{}/**/*", path)
+        format!("{}/**/*", root)
     } else {
-        format!("{}/{}", path, glob_pattern)
+        format!("{}/{}", root, glob_pattern)
     };
 
     // Execute glob with result limit to prevent DoS
@@ -852,7 +902,7 @@ pub async fn dir_traverse(
 
                 match entry {
                     Ok(path_buf) => {
-                        results.push(path_buf.to_string_lossy().to_string());
+                        results.push(portable_path(&path_buf));
                     }
                     Err(e) => {
                         warn!("Glob error: {}", e);
@@ -865,7 +915,7 @@ pub async fn dir_traverse(
         }
     }
 
-    log_file_operation(&path, FileOperation::Read, true, None, &state).await?;
+    log_file_operation(&path, FileOperation::Read, true, None, &state, &user).await?;
     info!("Found {} files matching pattern", results.len());
     Ok(results)
 }
@@ -930,6 +980,7 @@ fn detect_language(path: &str) -> Option<String> {
 /// Read file content with metadata for LLM context
 #[tauri::command]
 pub async fn fs_read_file_content(
+    user: AuthenticatedUser,
     file_path: String,
     state: tauri::State<'_, AppDatabase>,
 ) -> Result<FileContextContent, String> {
@@ -944,13 +995,14 @@ pub async fn fs_read_file_content(
             false,
             Some(error.clone()),
             &state,
+            &user,
         )
         .await?;
         return Err(error);
     }
 
     // Read file
-    let content = match fs::read_to_string(&file_path) {
+    let content = match fs::read_to_string(resolve_path(&file_path)) {
         Ok(content) => content,
         Err(e) => {
             let error = format!("Failed to read file: {}", e);
@@ -960,6 +1012,7 @@ pub async fn fs_read_file_content(
                 false,
                 Some(error.clone()),
                 &state,
+                &user,
             )
             .await?;
             return Err(error);
@@ -967,8 +1020,8 @@ pub async fn fs_read_file_content(
     };
 
     // Get file size
-    let metadata =
-        fs::metadata(&file_path).map_err(|e| format!("Failed to get file metadata: {}", e))?;
+    let metadata = fs::metadata(resolve_path(&file_path))
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
     let size = metadata.len();
 
     // Count lines
@@ -984,7 +1037,7 @@ pub async fn fs_read_file_content(
         content.clone()
     };
 
-    log_file_operation(&file_path, FileOperation::Read, true, None, &state).await?;
+    log_file_operation(&file_path, FileOperation::Read, true, None, &state, &user).await?;
 
     Ok(FileContextContent {
         content,
@@ -1011,6 +1064,7 @@ pub struct WorkspaceFile {
 /// Get list of files in workspace directory (non-recursive)
 #[tauri::command]
 pub async fn fs_get_workspace_files(
+    _user: AuthenticatedUser,
     workspace_path: String,
     state: tauri::State<'_, AppDatabase>,
 ) -> Result<Vec<WorkspaceFile>, String> {
@@ -1020,7 +1074,7 @@ pub async fn fs_get_workspace_files(
     validate_path_security(&workspace_path)?;
 
     // Verify it's a directory
-    match fs::metadata(&workspace_path) {
+    match fs::metadata(resolve_path(&workspace_path)) {
         Ok(metadata) => {
             if !metadata.is_dir() {
                 return Err(format!("Path is not a directory: {}", workspace_path));
@@ -1035,18 +1089,18 @@ pub async fn fs_get_workspace_files(
     }
 
     // Read directory
-    let entries =
-        fs::read_dir(&workspace_path).map_err(|e| format!("Failed to read directory: {}", e))?;
+    let entries = fs::read_dir(resolve_path(&workspace_path))
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
 
     let mut files = Vec::new();
 
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
         let path = entry.path();
-        let path_str = path.to_str().unwrap_or("").to_string();
+        let path_str = portable_path(&path);
 
         // Skip hidden files and common ignored directories
-        let name = entry.file_name().to_str().unwrap_or("").to_string();
+        let name = entry.file_name().to_string_lossy().to_string();
         if name.starts_with('.')
             || name == "node_modules"
             || name == "target"
@@ -1071,7 +1125,7 @@ pub async fn fs_get_workspace_files(
             .map(|s| s.to_string());
 
         let language = if is_file {
-            detect_language(&path_str)
+            detect_language(&path.to_string_lossy())
         } else {
             None
         };
@@ -1116,17 +1170,23 @@ mod tests {
         let file_path = dir.path().join("test.txt");
 
         // File doesn't exist yet
-        assert!(!file_exists(file_path.to_str().unwrap().to_string())
-            .await
-            .unwrap());
+        assert!(!file_exists(
+            AuthenticatedUser::local_owner(),
+            file_path.to_str().unwrap().to_string(),
+        )
+        .await
+        .unwrap());
 
         // Create file
         fs::write(&file_path, "test").unwrap();
 
         // File exists now
-        assert!(file_exists(file_path.to_str().unwrap().to_string())
-            .await
-            .unwrap());
+        assert!(file_exists(
+            AuthenticatedUser::local_owner(),
+            file_path.to_str().unwrap().to_string(),
+        )
+        .await
+        .unwrap());
     }
 
     #[tokio::test]
@@ -1138,9 +1198,12 @@ mod tests {
         fs::write(&file_path, "test content").unwrap();
 
         // Get metadata
-        let metadata = file_metadata(file_path.to_str().unwrap().to_string())
-            .await
-            .unwrap();
+        let metadata = file_metadata(
+            AuthenticatedUser::local_owner(),
+            file_path.to_str().unwrap().to_string(),
+        )
+        .await
+        .unwrap();
 
         assert!(metadata.is_file);
         assert!(!metadata.is_dir);
@@ -1162,59 +1225,93 @@ mod tests {
 
 /// Read a text file and return its content
 #[tauri::command]
-pub async fn file_read_text(file_path: String) -> Result<String, String> {
+pub async fn file_read_text(_user: AuthenticatedUser, file_path: String) -> Result<String, String> {
     validate_path_security(&file_path)?;
 
-    fs::read_to_string(&file_path).map_err(|e| format!("Failed to read file: {}", e))
+    fs::read_to_string(resolve_path(&file_path)).map_err(|e| format!("Failed to read file: {}", e))
 }
 
 /// Write text to a file
 #[tauri::command]
-pub async fn file_write_text(file_path: String, content: String) -> Result<(), String> {
+pub async fn file_write_text(
+    app: AppHandle,
+    user: AuthenticatedUser,
+    file_path: String,
+    content: String,
+) -> Result<(), String> {
     validate_path_security(&file_path)?;
 
-    // Create parent directory if needed
-    if let Some(parent) = Path::new(&file_path).parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
-
-    fs::write(&file_path, content).map_err(|e| format!("Failed to write file: {}", e))
+    let result = write_with_parents(&file_path, content.as_bytes());
+    audit_write(&app, &user, &file_path, &result);
+    result
 }
 
 /// Read binary file as base64
 #[tauri::command]
-pub async fn file_read_binary(file_path: String) -> Result<String, String> {
+pub async fn file_read_binary(
+    _user: AuthenticatedUser,
+    file_path: String,
+) -> Result<String, String> {
     validate_path_security(&file_path)?;
 
-    let data = fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let data =
+        fs::read(resolve_path(&file_path)).map_err(|e| format!("Failed to read file: {}", e))?;
 
     Ok(general_purpose::STANDARD.encode(&data))
 }
 
 /// Write binary file from base64
 #[tauri::command]
-pub async fn file_write_binary(file_path: String, base64_content: String) -> Result<(), String> {
+pub async fn file_write_binary(
+    app: AppHandle,
+    user: AuthenticatedUser,
+    file_path: String,
+    base64_content: String,
+) -> Result<(), String> {
     validate_path_security(&file_path)?;
 
     let data = general_purpose::STANDARD
         .decode(&base64_content)
         .map_err(|e| format!("Failed to decode base64: {}", e))?;
 
-    // Create parent directory if needed
-    if let Some(parent) = Path::new(&file_path).parent() {
+    let result = write_with_parents(&file_path, &data);
+    audit_write(&app, &user, &file_path, &result);
+    result
+}
+
+/// Write a file, creating its parent directory if needed
+fn write_with_parents(file_path: &str, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = resolve_path(file_path).parent() {
         fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
 
-    fs::write(&file_path, data).map_err(|e| format!("Failed to write file: {}", e))
+    fs::write(resolve_path(file_path), data).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+fn audit_write(
+    app: &AppHandle,
+    user: &AuthenticatedUser,
+    file_path: &str,
+    result: &Result<(), String>,
+) {
+    audit::record(
+        app,
+        AuditEntry::new(Actor::user(user), AuditCategory::Filesystem, "file_write")
+            .target(file_path)
+            .result(result),
+    );
 }
 
 /// Get simple file metadata
 #[tauri::command]
-pub async fn file_get_metadata(file_path: String) -> Result<FileMetadata, String> {
+pub async fn file_get_metadata(
+    _user: AuthenticatedUser,
+    file_path: String,
+) -> Result<FileMetadata, String> {
     validate_path_security(&file_path)?;
 
-    let metadata =
-        fs::metadata(&file_path).map_err(|e| format!("Failed to get metadata: {}", e))?;
+    let metadata = fs::metadata(resolve_path(&file_path))
+        .map_err(|e| format!("Failed to get metadata: {}", e))?;
 
     let created = metadata
         .created()
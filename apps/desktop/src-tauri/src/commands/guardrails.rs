@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::db::Pool;
+use crate::security::guardrails::{self, GuardrailConfig, GuardrailReport, Guardrails};
+use crate::security::AdminUser;
+
+#[tauri::command]
+pub async fn guardrails_get_config(
+    guardrails: State<'_, Arc<Guardrails>>,
+) -> Result<GuardrailConfig, String> {
+    Ok(guardrails.config())
+}
+
+#[tauri::command]
+pub async fn guardrails_set_config(
+    _admin: AdminUser,
+    pool: State<'_, Pool>,
+    guardrails: State<'_, Arc<Guardrails>>,
+    config: GuardrailConfig,
+) -> Result<(), String> {
+    let saved = config.clone();
+    pool.run(move |conn| -> Result<_, String> {
+        guardrails::save_config(conn, &saved).map_err(|e| e.to_string())
+    })
+    .await?;
+    guardrails.set_config(config);
+    Ok(())
+}
+
+/// Reports of recent runs, newest first
+#[tauri::command]
+pub async fn guardrails_list_reports(
+    guardrails: State<'_, Arc<Guardrails>>,
+) -> Result<Vec<GuardrailReport>, String> {
+    Ok(guardrails.reports())
+}
+
+#[tauri::command]
+pub async fn guardrails_get_report(
+    guardrails: State<'_, Arc<Guardrails>>,
+    run_id: String,
+) -> Result<Option<GuardrailReport>, String> {
+    Ok(guardrails.report(&run_id))
+}
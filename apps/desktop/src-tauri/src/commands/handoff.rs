@@ -0,0 +1,90 @@
+use std::fs;
+
+use tauri::State;
+
+use crate::commands::AppDatabase;
+use crate::db::Pool;
+use crate::sync::{
+    open_bundle, restore_session, seal_bundle, snapshot_session, unfinished_tasks,
+    HandoffExportSummary, HandoffImportSummary,
+};
+use crate::tasks::persistence::TaskPersistence;
+
+/// Export a conversation and in-progress task state to an encrypted handoff bundle
+#[tauri::command]
+pub async fn session_handoff_export(
+    db: State<'_, AppDatabase>,
+    pool: State<'_, Pool>,
+    conversation_id: i64,
+    passphrase: String,
+    output_path: String,
+    include_tasks: Option<bool>,
+) -> Result<HandoffExportSummary, String> {
+    let tasks = if include_tasks.unwrap_or(true) {
+        unfinished_tasks(&TaskPersistence::new(pool.inner().clone()), conversation_id)
+            .await
+            .map_err(|e| format!("Failed to load tasks: {}", e))?
+    } else {
+        Vec::new()
+    };
+
+    let mut snapshot = {
+        let conn = db
+            .conn
+            .lock()
+            .map_err(|e| format!("Failed to lock database: {}", e))?;
+        snapshot_session(&conn, conversation_id)
+            .map_err(|e| format!("Failed to snapshot session: {}", e))?
+    };
+    snapshot.tasks = tasks;
+
+    let (bundle, payload_sha256) = seal_bundle(&snapshot, &passphrase)
+        .map_err(|e| format!("Failed to seal handoff bundle: {}", e))?;
+    fs::write(&output_path, &bundle)
+        .map_err(|e| format!("Failed to write handoff bundle: {}", e))?;
+
+    tracing::info!(
+        "Exported session handoff for conversation {} to {}",
+        conversation_id,
+        output_path
+    );
+
+    Ok(HandoffExportSummary {
+        conversation_id,
+        messages: snapshot.messages.len(),
+        checkpoints: snapshot.checkpoints.len(),
+        captures: snapshot.captures.len(),
+        tasks: snapshot.tasks.len(),
+        payload_sha256,
+        bundle_size: bundle.len(),
+    })
+}
+
+/// Import a handoff bundle as a new conversation with remapped IDs
+#[tauri::command]
+pub async fn session_handoff_import(
+    db: State<'_, AppDatabase>,
+    input_path: String,
+    passphrase: String,
+) -> Result<HandoffImportSummary, String> {
+    let bundle =
+        fs::read(&input_path).map_err(|e| format!("Failed to read handoff bundle: {}", e))?;
+    let (snapshot, exported_at) = open_bundle(&bundle, &passphrase)
+        .map_err(|e| format!("Failed to open handoff bundle: {}", e))?;
+
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let summary = restore_session(&conn, &snapshot, exported_at)
+        .map_err(|e| format!("Failed to import session: {}", e))?;
+
+    tracing::info!(
+        "Imported session handoff as conversation {} ({} messages, {} tasks)",
+        summary.conversation_id,
+        summary.message_ids.len(),
+        summary.task_ids.len()
+    );
+
+    Ok(summary)
+}
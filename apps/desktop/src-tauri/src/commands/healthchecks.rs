@@ -0,0 +1,42 @@
+use chrono::Utc;
+use tauri::State;
+
+use crate::api::health_check::{self, HealthCheck, NewHealthCheck};
+use crate::commands::AppDatabase;
+
+/// Define a health check for a custom integration; it runs on its interval
+/// and reports into the credential health overview
+#[tauri::command]
+pub async fn healthchecks_create(
+    definition: NewHealthCheck,
+    db: State<'_, AppDatabase>,
+) -> Result<HealthCheck, String> {
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    health_check::create(&conn, definition, Utc::now().timestamp()).map_err(|e| e.to_string())
+}
+
+/// Every health check with the outcome of its last run
+#[tauri::command]
+pub async fn healthchecks_list(db: State<'_, AppDatabase>) -> Result<Vec<HealthCheck>, String> {
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    health_check::list(&conn).map_err(|e| format!("Failed to list health checks: {}", e))
+}
+
+#[tauri::command]
+pub async fn healthchecks_delete(id: String, db: State<'_, AppDatabase>) -> Result<(), String> {
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    match health_check::delete(&conn, &id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Health check {} not found", id)),
+        Err(e) => Err(format!("Failed to delete health check: {}", e)),
+    }
+}
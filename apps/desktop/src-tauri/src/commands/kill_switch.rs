@@ -0,0 +1,25 @@
+use crate::kill_switch::{self, KillSwitchReport, KillSwitchStatus};
+use tauri::AppHandle;
+
+/// Immediately stop all running automation and block new runs until re-armed.
+#[tauri::command]
+pub async fn kill_switch_engage(
+    initiator: Option<String>,
+    reason: Option<String>,
+    app: AppHandle,
+) -> Result<KillSwitchReport, String> {
+    let initiator = initiator.unwrap_or_else(|| "user".to_string());
+    Ok(kill_switch::engage(&app, &initiator, reason).await)
+}
+
+/// Allow automation to run again after the kill switch was engaged.
+#[tauri::command]
+pub fn kill_switch_rearm(initiator: Option<String>, app: AppHandle) -> KillSwitchStatus {
+    let initiator = initiator.unwrap_or_else(|| "user".to_string());
+    kill_switch::rearm(&app, &initiator)
+}
+
+#[tauri::command]
+pub fn kill_switch_status() -> KillSwitchStatus {
+    kill_switch::status()
+}
@@ -0,0 +1,92 @@
+use crate::commands::{AppDatabase, ShortcutsState};
+use crate::launcher::{self, LauncherState, PaletteIndex, PaletteKind, PaletteResult};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Mutex;
+
+const DEFAULT_RESULT_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteOpenEvent {
+    pub kind: PaletteKind,
+    pub id: String,
+}
+
+/// Ranked palette matches for `query`. Called on every keystroke; the index
+/// is cached so only a stale or invalidated index touches the database.
+#[tauri::command]
+pub async fn palette_query(
+    query: String,
+    limit: Option<usize>,
+    launcher: State<'_, LauncherState>,
+    db: State<'_, AppDatabase>,
+    shortcuts: State<'_, Arc<Mutex<ShortcutsState>>>,
+) -> Result<Vec<PaletteResult>, String> {
+    let index = match launcher.cached() {
+        Some(index) => index,
+        None => launcher.store(build_index(&db, &shortcuts).await?),
+    };
+    Ok(index.query(&query, limit.unwrap_or(DEFAULT_RESULT_LIMIT)))
+}
+
+/// Toggle the launcher window. Opening it refreshes the palette index.
+#[tauri::command]
+pub fn launcher_toggle(app: AppHandle, launcher: State<LauncherState>) -> Result<bool, String> {
+    let visible = launcher::toggle_launcher(&app).map_err(|e| e.to_string())?;
+    if visible {
+        launcher.invalidate();
+    }
+    Ok(visible)
+}
+
+#[tauri::command]
+pub fn launcher_hide(app: AppHandle) -> Result<(), String> {
+    launcher::hide_launcher(&app).map_err(|e| e.to_string())
+}
+
+/// Act on a selected palette item: commands go through the shortcut action
+/// pipeline, everything else is opened in the main window.
+#[tauri::command]
+pub fn palette_open(kind: PaletteKind, id: String, app: AppHandle) -> Result<(), String> {
+    launcher::hide_launcher(&app).map_err(|e| e.to_string())?;
+
+    if kind == PaletteKind::Command {
+        return app
+            .emit("shortcut_action", id)
+            .map_err(|e| format!("Failed to emit event: {}", e));
+    }
+
+    let main = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    crate::window::show_window(&main).map_err(|e| e.to_string())?;
+    main.emit("palette://open", PaletteOpenEvent { kind, id })
+        .map_err(|e| format!("Failed to emit event: {}", e))
+}
+
+async fn build_index(
+    db: &AppDatabase,
+    shortcuts: &Arc<Mutex<ShortcutsState>>,
+) -> Result<PaletteIndex, String> {
+    let shortcuts: Vec<_> = {
+        let state = shortcuts.lock().await;
+        let registered = state.shortcuts.lock().await;
+        registered.values().cloned().collect()
+    };
+
+    let conn = db.conn.clone();
+    let index = tokio::task::spawn_blocking(move || {
+        let conn = conn
+            .lock()
+            .map_err(|e| format!("Database lock poisoned: {}", e))?;
+        PaletteIndex::build(&conn, &shortcuts)
+            .map_err(|e| format!("Failed to build palette index: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Palette index task failed: {}", e))??;
+
+    tracing::debug!(items = index.len(), "Palette index rebuilt");
+    Ok(index)
+}
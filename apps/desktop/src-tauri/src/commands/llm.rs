@@ -1,13 +1,24 @@
+use crate::error::LLMError;
 use crate::router::providers::{
-    anthropic::AnthropicProvider, deepseek::DeepSeekProvider, google::GoogleProvider,
-    mistral::MistralProvider, ollama::OllamaProvider, openai::OpenAIProvider, qwen::QwenProvider,
+    anthropic::AnthropicProvider,
+    azure_openai::AzureOpenAIProvider,
+    bedrock::{self, AwsCredentials, BedrockProvider},
+    deepseek::DeepSeekProvider,
+    google::GoogleProvider,
+    mistral::MistralProvider,
+    ollama::OllamaProvider,
+    openai::OpenAIProvider,
+    qwen::QwenProvider,
     xai::XAIProvider,
 };
 use crate::router::{
     cache_manager::CacheManager,
     llm_router::{RouterContext, RouterPreferences, RoutingStrategy},
-    ChatMessage, LLMRequest, LLMResponse, LLMRouter, Provider,
+    structured_output::StructuredOutputError,
+    BudgetGuard, BudgetLimits, BudgetStatus, ChatMessage, LLMRequest, LLMResponse, LLMRouter,
+    Provider, ProviderHealth, ProviderHealthMonitor, ResponseFormat, SemanticCache,
 };
+use crate::security::SecretString;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
@@ -21,11 +32,30 @@ pub struct LLMSendMessageRequest {
     pub provider: Option<String>,
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Settings for providers that need more than an API key and base URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    /// Azure OpenAI deployment name
+    pub deployment: Option<String>,
+    /// Azure OpenAI `api-version`
+    pub api_version: Option<String>,
+    /// AWS region for Bedrock; falls back to `AWS_REGION`
+    pub region: Option<String>,
+    /// AWS access key id for Bedrock; the secret key is passed as `api_key`
+    pub access_key_id: Option<String>,
+    pub session_token: Option<String>,
 }
 
 pub struct LLMState {
     pub router: Arc<Mutex<LLMRouter>>,
     pub cache_manager: CacheManager,
+    pub budget_guard: Arc<BudgetGuard>,
+    pub health_monitor: Arc<ProviderHealthMonitor>,
+    pub semantic_cache: Arc<SemanticCache>,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,9 +73,31 @@ impl Default for LLMState {
 
 impl LLMState {
     pub fn new() -> Self {
+        let budget_guard = Arc::new(BudgetGuard::new());
+        let health_monitor = Arc::new(ProviderHealthMonitor::new());
+        let semantic_cache = Arc::new(SemanticCache::new());
+        let mut router = LLMRouter::new();
+        router.set_budget_guard(budget_guard.clone());
+        router.set_health_monitor(health_monitor.clone());
+        router.set_semantic_cache(semantic_cache.clone());
+
         Self {
-            router: Arc::new(Mutex::new(LLMRouter::new())),
+            router: Arc::new(Mutex::new(router)),
             cache_manager: CacheManager::new(Duration::from_secs(60 * 60 * 24), 512),
+            budget_guard,
+            health_monitor,
+            semantic_cache,
+        }
+    }
+
+    /// Turn on exact and semantic response caching backed by the app database
+    pub fn enable_cache(&self, db: Arc<std::sync::Mutex<rusqlite::Connection>>) {
+        if let Ok(conn) = db.lock() {
+            self.semantic_cache.load_threshold(&conn);
+        }
+        match self.router.try_lock() {
+            Ok(mut router) => router.set_cache(self.cache_manager.clone(), db),
+            Err(_) => tracing::warn!("LLM router busy; response cache not enabled"),
         }
     }
 }
@@ -117,6 +169,7 @@ pub async fn llm_send_message(
         stream: false,
         tools: None,
         tool_choice: None,
+        response_format: request.response_format,
     };
 
     let preferences = RouterPreferences {
@@ -141,63 +194,36 @@ pub async fn llm_send_message(
         return Err("No LLM providers are configured.".to_string());
     }
 
-    let mut last_error: Option<anyhow::Error> = None;
-    let mut error_messages: Vec<String> = Vec::new();
-
-    for candidate in candidates {
-        let res = {
-            let router = state.router.lock().await;
-            router.invoke_candidate(&candidate, &llm_request).await
-        };
-        match res {
-            Ok(mut outcome) => {
-                outcome.response.cached = false;
-                return Ok(outcome.response);
-            }
-            Err(err) => {
-                let error_msg = err.to_string();
-                error_messages.push(format!("{}: {}", candidate.provider.as_string(), error_msg));
-
-                // Check for specific error types
-                if error_msg.contains("401")
-                    || error_msg.contains("Unauthorized")
-                    || error_msg.contains("invalid_api_key")
-                {
-                    return Err(format!(
-                        "API key authentication failed for {}. Please check your API key in Settings > API Keys.",
-                        candidate.provider.as_string()
-                    ));
-                }
-                if error_msg.contains("decode")
-                    || error_msg.contains("deserialize")
-                    || error_msg.contains("JSON")
-                {
-                    return Err(format!(
-                        "Error decoding response from {}: {}. This may indicate an API issue or invalid response format.",
-                        candidate.provider.as_string(),
-                        error_msg
-                    ));
-                }
+    let res = {
+        let router = state.router.lock().await;
+        router.invoke_with_failover(&candidates, &llm_request).await
+    };
 
-                last_error = Some(err);
-            }
+    match res {
+        Ok(mut outcome) => {
+            outcome.response.cached = false;
+            Ok(outcome.response)
         }
-    }
+        Err(err) => {
+            let error_msg = err.to_string();
+
+            if err.downcast_ref::<StructuredOutputError>().is_some() {
+                return Err(error_msg);
+            }
 
-    // Return detailed error message
-    if let Some(err) = last_error {
-        let mut error_text = format!(
-            "All providers failed. Errors: {}",
-            error_messages.join("; ")
-        );
-        let err_str = err.to_string();
-        if !err_str.is_empty() {
-            error_text = format!("{} Last error: {}", error_text, err_str);
+            match err.downcast_ref::<LLMError>() {
+                Some(LLMError::AuthenticationError(_)) => Err(format!(
+                    "API key authentication failed. Please check your API keys in Settings > API Keys. ({})",
+                    error_msg
+                )),
+                Some(LLMError::InvalidResponse(_)) => Err(format!(
+                    "Error decoding provider response: {}. This may indicate an API issue or invalid response format.",
+                    error_msg
+                )),
+                _ => Err(format!("All providers failed. {}", error_msg)),
+            }
         }
-        return Err(error_text);
     }
-
-    Err("All providers failed with unknown errors.".to_string())
 }
 
 // Updated Nov 16, 2025: Added input validation for API keys
@@ -206,8 +232,11 @@ pub async fn llm_configure_provider(
     provider: String,
     api_key: Option<String>,
     base_url: Option<String>,
+    config: Option<ProviderConfig>,
     state: State<'_, LLMState>,
 ) -> Result<(), String> {
+    let config = config.unwrap_or_default();
+
     // Validate provider name
     if provider.trim().is_empty() {
         return Err("Provider name cannot be empty".to_string());
@@ -316,6 +345,54 @@ pub async fn llm_configure_provider(
                 Err("Mistral requires an API key".to_string())
             }
         }
+        "azure_openai" | "azure" => {
+            let (Some(key), Some(endpoint)) = (api_key, base_url) else {
+                return Err(
+                    "Azure OpenAI requires an API key and the resource endpoint as base URL"
+                        .to_string(),
+                );
+            };
+            let deployment = config
+                .deployment
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty())
+                .ok_or_else(|| "Azure OpenAI requires a deployment name".to_string())?;
+            router.set_azure_openai(Box::new(AzureOpenAIProvider::new(
+                key.trim().to_string(),
+                endpoint,
+                deployment,
+                config.api_version,
+            )));
+            Ok(())
+        }
+        "bedrock" | "aws_bedrock" => {
+            let credentials = match (config.access_key_id, api_key) {
+                (Some(access_key_id), Some(secret)) => AwsCredentials {
+                    access_key_id: access_key_id.trim().to_string(),
+                    secret_access_key: secret.trim().into(),
+                    session_token: config.session_token.map(SecretString::from),
+                },
+                (None, None) => AwsCredentials::from_env().ok_or_else(|| {
+                    "Bedrock requires AWS credentials: pass an access key id and secret, or set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY".to_string()
+                })?,
+                _ => {
+                    return Err(
+                        "Bedrock requires both an access key id and a secret access key"
+                            .to_string(),
+                    )
+                }
+            };
+            let region = config
+                .region
+                .or_else(bedrock::region_from_env)
+                .ok_or_else(|| "Bedrock requires an AWS region".to_string())?;
+            let mut provider = BedrockProvider::new(credentials, region);
+            if let Some(url) = base_url {
+                provider = provider.with_base_url(url);
+            }
+            router.set_bedrock(Box::new(provider));
+            Ok(())
+        }
         _ => Err(format!("Unknown provider: {}", provider)),
     }
 }
@@ -625,3 +702,46 @@ pub async fn router_suggestions(
         reason: suggestion.reason,
     })
 }
+
+/// Current spend against the configured daily and monthly caps
+#[tauri::command]
+pub async fn llm_get_budget_status(state: State<'_, LLMState>) -> Result<BudgetStatus, String> {
+    Ok(state.budget_guard.status())
+}
+
+/// Latency, error rate and rate-limit health per provider over the recent window
+#[tauri::command]
+pub async fn llm_get_provider_health(
+    state: State<'_, LLMState>,
+) -> Result<Vec<ProviderHealth>, String> {
+    Ok(state.health_monitor.report())
+}
+
+/// Configure soft (downgrade) and hard (reject) spend caps enforced by the router
+#[tauri::command]
+pub async fn llm_set_budget_limits(
+    state: State<'_, LLMState>,
+    limits: BudgetLimits,
+) -> Result<(), String> {
+    for cap in [
+        limits.daily_soft_cap,
+        limits.daily_hard_cap,
+        limits.monthly_soft_cap,
+        limits.monthly_hard_cap,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !(0.0..=1_000_000.0).contains(&cap) {
+            return Err(format!(
+                "Invalid budget cap: {}. Caps must be between $0 and $1,000,000",
+                cap
+            ));
+        }
+    }
+
+    state
+        .budget_guard
+        .set_limits(limits)
+        .map_err(|e| format!("Failed to save budget limits: {}", e))
+}
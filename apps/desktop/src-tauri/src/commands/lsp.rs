@@ -358,11 +358,15 @@ impl LSPClient {
 
         if let Some(result) = response.get("result") {
             if !result.is_null() {
-                let hover: Hover =
-                    serde_json::from_value(result.clone()).unwrap_or_else(|_| Hover {
-                        contents: String::new(),
-                        range: None,
-                    });
+                let hover = Hover {
+                    contents: result
+                        .get("contents")
+                        .map(hover_contents_text)
+                        .unwrap_or_default(),
+                    range: result
+                        .get("range")
+                        .and_then(|range| serde_json::from_value(range.clone()).ok()),
+                };
                 return Ok(Some(hover));
             }
         }
@@ -655,6 +659,57 @@ impl LSPState {
             clients: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Running server for a language, if one was started
+    pub async fn client(&self, language: &str) -> Option<Arc<Mutex<LSPClient>>> {
+        self.clients.lock().await.get(language).cloned()
+    }
+}
+
+/// Flatten the `MarkedString | MarkedString[] | MarkupContent` forms of
+/// hover contents into Markdown
+fn hover_contents_text(contents: &serde_json::Value) -> String {
+    match contents {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(hover_contents_text)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        serde_json::Value::Object(map) => {
+            let value = map
+                .get("value")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            match map.get("language").and_then(|l| l.as_str()) {
+                Some(language) => format!("```{}\n{}\n```", language, value),
+                None => value.to_string(),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// Language server key for a file, based on its extension
+pub fn detect_language(path: &std::path::Path) -> Option<&'static str> {
+    let language = match path.extension()?.to_str()? {
+        "rs" => "rust",
+        "ts" | "tsx" => "typescript",
+        "js" | "jsx" => "javascript",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        "cpp" | "cc" | "cxx" => "cpp",
+        "c" | "h" => "c",
+        "json" => "json",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "less" => "less",
+        _ => return None,
+    };
+    Some(language)
 }
 
 fn get_lsp_command(language: &str) -> Result<(String, Vec<String>), String> {
@@ -927,22 +982,7 @@ pub async fn lsp_detect_language(file_path: String) -> Result<String, String> {
         .and_then(|e| e.to_str())
         .ok_or("Could not determine file extension")?;
 
-    let language = match extension {
-        "rs" => "rust",
-        "ts" | "tsx" => "typescript",
-        "js" | "jsx" => "javascript",
-        "py" => "python",
-        "go" => "go",
-        "java" => "java",
-        "cpp" | "cc" | "cxx" => "cpp",
-        "c" | "h" => "c",
-        "json" => "json",
-        "html" | "htm" => "html",
-        "css" => "css",
-        "scss" => "scss",
-        "less" => "less",
-        _ => return Err(format!("Unsupported file extension: {}", extension)),
-    };
-
-    Ok(language.to_string())
+    detect_language(path)
+        .map(str::to_string)
+        .ok_or_else(|| format!("Unsupported file extension: {}", extension))
 }
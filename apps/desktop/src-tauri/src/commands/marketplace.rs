@@ -1,16 +1,15 @@
+use crate::db::Pool;
 use crate::orchestration::workflow_engine::WorkflowDefinition;
 use crate::workflows::{
     get_all_templates, PublishedWorkflow, SharePlatform, SortOption, WorkflowCategory,
     WorkflowComment, WorkflowFilters, WorkflowMarketplace, WorkflowPublisher, WorkflowSocial,
     WorkflowStats, WorkflowTemplate,
 };
-use rusqlite::Connection;
-use std::sync::{Arc, Mutex};
 use tauri::State;
 
 /// State for marketplace operations
 pub struct MarketplaceState {
-    pub db: Arc<Mutex<Connection>>,
+    pub db: Pool,
 }
 
 /// Publish a workflow to the marketplace
@@ -27,49 +26,47 @@ pub async fn publish_workflow_to_marketplace(
     state: State<'_, MarketplaceState>,
 ) -> Result<PublishedWorkflow, String> {
     // First, get the workflow from workflow_definitions table
-    let db = state
+    let lookup_id = workflow_id.clone();
+    let workflow: WorkflowDefinition = state
         .db
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-    let workflow: WorkflowDefinition = {
-        let mut stmt = db.prepare(
-            "SELECT id, user_id, name, description, nodes, edges, triggers, metadata, created_at, updated_at
-             FROM workflow_definitions WHERE id = ?1"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        stmt.query_row(rusqlite::params![&workflow_id], |row| {
-            let nodes_json: String = row.get(4)?;
-            let edges_json: String = row.get(5)?;
-            let triggers_json: String = row.get(6)?;
-            let metadata_json: String = row.get(7)?;
-
-            let nodes =
-                serde_json::from_str(&nodes_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
-            let edges =
-                serde_json::from_str(&edges_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
-            let triggers =
-                serde_json::from_str(&triggers_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
-            let metadata =
-                serde_json::from_str(&metadata_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
-
-            Ok(WorkflowDefinition {
-                id: row.get(0)?,
-                user_id: row.get(1)?,
-                name: row.get(2)?,
-                description: row.get(3)?,
-                nodes,
-                edges,
-                triggers,
-                metadata,
-                created_at: row.get(8)?,
-                updated_at: row.get(9)?,
+        .run(move |conn| -> Result<_, String> {
+            let mut stmt = conn.prepare(
+                "SELECT id, user_id, name, description, nodes, edges, triggers, metadata, created_at, updated_at
+                 FROM workflow_definitions WHERE id = ?1"
+            ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+            let workflow = stmt.query_row(rusqlite::params![&lookup_id], |row| {
+                let nodes_json: String = row.get(4)?;
+                let edges_json: String = row.get(5)?;
+                let triggers_json: String = row.get(6)?;
+                let metadata_json: String = row.get(7)?;
+
+                let nodes =
+                    serde_json::from_str(&nodes_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+                let edges =
+                    serde_json::from_str(&edges_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+                let triggers =
+                    serde_json::from_str(&triggers_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+                let metadata =
+                    serde_json::from_str(&metadata_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+                Ok(WorkflowDefinition {
+                    id: row.get(0)?,
+                    user_id: row.get(1)?,
+                    name: row.get(2)?,
+                    description: row.get(3)?,
+                    nodes,
+                    edges,
+                    triggers,
+                    metadata,
+                    created_at: row.get(8)?,
+                    updated_at: row.get(9)?,
+                })
             })
+            .map_err(|e| format!("Workflow not found: {}", e))?;
+            Ok(workflow)
         })
-        .map_err(|e| format!("Workflow not found: {}", e))?
-    };
-
-    drop(db); // Release lock before publishing
+        .await?;
 
     let category_enum = WorkflowCategory::from_str(&category);
     let publisher = WorkflowPublisher::new(state.db.clone());
@@ -85,7 +82,7 @@ pub async fn publish_workflow_to_marketplace(
         thumbnail_url,
     };
 
-    publisher.publish_workflow(request)
+    publisher.publish_workflow(request).await
 }
 
 /// Unpublish a workflow from the marketplace
@@ -96,7 +93,7 @@ pub async fn unpublish_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<(), String> {
     let publisher = WorkflowPublisher::new(state.db.clone());
-    publisher.unpublish_workflow(&workflow_id, &user_id)
+    publisher.unpublish_workflow(&workflow_id, &user_id).await
 }
 
 /// Get featured workflows
@@ -106,7 +103,7 @@ pub async fn get_featured_workflows(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<PublishedWorkflow>, String> {
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    marketplace.get_featured_workflows(limit)
+    marketplace.get_featured_workflows(limit).await
 }
 
 /// Get trending workflows
@@ -116,7 +113,7 @@ pub async fn get_trending_workflows(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<PublishedWorkflow>, String> {
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    marketplace.get_trending_workflows(limit)
+    marketplace.get_trending_workflows(limit).await
 }
 
 /// Search workflows with filters
@@ -153,7 +150,7 @@ pub async fn search_marketplace_workflows(
     };
 
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    marketplace.search_workflows(filters, limit, offset)
+    marketplace.search_workflows(filters, limit, offset).await
 }
 
 /// Get workflow by share URL
@@ -166,8 +163,8 @@ pub async fn get_workflow_by_share_url(
     let publisher = WorkflowPublisher::new(state.db.clone());
 
     // Increment view count
-    let workflow = marketplace.get_workflow_by_share_url(&share_url)?;
-    let _ = publisher.increment_view_count(&workflow.id);
+    let workflow = marketplace.get_workflow_by_share_url(&share_url).await?;
+    let _ = publisher.increment_view_count(&workflow.id).await;
 
     Ok(workflow)
 }
@@ -179,7 +176,7 @@ pub async fn get_creator_workflows(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<PublishedWorkflow>, String> {
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    marketplace.get_creator_workflows(&creator_id)
+    marketplace.get_creator_workflows(&creator_id).await
 }
 
 /// Get user's published workflows
@@ -189,7 +186,7 @@ pub async fn get_my_published_workflows(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<PublishedWorkflow>, String> {
     let publisher = WorkflowPublisher::new(state.db.clone());
-    publisher.get_user_published_workflows(&user_id)
+    publisher.get_user_published_workflows(&user_id).await
 }
 
 /// Get workflows by category
@@ -201,7 +198,9 @@ pub async fn get_workflows_by_category(
 ) -> Result<Vec<PublishedWorkflow>, String> {
     let category_enum = WorkflowCategory::from_str(&category);
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    marketplace.get_workflows_by_category(category_enum, limit)
+    marketplace
+        .get_workflows_by_category(category_enum, limit)
+        .await
 }
 
 /// Get category counts for navigation
@@ -210,7 +209,7 @@ pub async fn get_category_counts(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<(String, u64)>, String> {
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    let counts = marketplace.get_category_counts()?;
+    let counts = marketplace.get_category_counts().await?;
 
     // Convert enum to string
     Ok(counts
@@ -226,7 +225,7 @@ pub async fn get_popular_tags(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<(String, u64)>, String> {
     let marketplace = WorkflowMarketplace::new(state.db.clone());
-    marketplace.get_popular_tags(limit)
+    marketplace.get_popular_tags(limit).await
 }
 
 /// Clone a workflow to user's workspace
@@ -238,7 +237,9 @@ pub async fn clone_marketplace_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<String, String> {
     let publisher = WorkflowPublisher::new(state.db.clone());
-    publisher.clone_workflow(&workflow_id, &user_id, &user_name)
+    publisher
+        .clone_workflow(&workflow_id, &user_id, &user_name)
+        .await
 }
 
 /// Fork a workflow (editable copy with link to original)
@@ -250,7 +251,9 @@ pub async fn fork_marketplace_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<String, String> {
     let publisher = WorkflowPublisher::new(state.db.clone());
-    publisher.fork_workflow(&workflow_id, &user_id, &user_name)
+    publisher
+        .fork_workflow(&workflow_id, &user_id, &user_name)
+        .await
 }
 
 /// Rate a workflow
@@ -263,7 +266,9 @@ pub async fn rate_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<(), String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.rate_workflow(&workflow_id, &user_id, rating, comment)
+    social
+        .rate_workflow(&workflow_id, &user_id, rating, comment)
+        .await
 }
 
 /// Get user's rating for a workflow
@@ -274,7 +279,7 @@ pub async fn get_user_workflow_rating(
     state: State<'_, MarketplaceState>,
 ) -> Result<Option<u8>, String> {
     let social = WorkflowSocial::new(state.db.clone());
-    let rating = social.get_user_rating(&workflow_id, &user_id)?;
+    let rating = social.get_user_rating(&workflow_id, &user_id).await?;
     Ok(rating.map(|r| r.rating))
 }
 
@@ -288,7 +293,9 @@ pub async fn comment_on_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<String, String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.comment_on_workflow(&workflow_id, &user_id, &user_name, comment)
+    social
+        .comment_on_workflow(&workflow_id, &user_id, &user_name, comment)
+        .await
 }
 
 /// Get workflow comments
@@ -300,7 +307,9 @@ pub async fn get_workflow_comments(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<WorkflowComment>, String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.get_workflow_comments(&workflow_id, limit, offset)
+    social
+        .get_workflow_comments(&workflow_id, limit, offset)
+        .await
 }
 
 /// Delete a comment
@@ -311,7 +320,7 @@ pub async fn delete_workflow_comment(
     state: State<'_, MarketplaceState>,
 ) -> Result<(), String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.delete_comment(&comment_id, &user_id)
+    social.delete_comment(&comment_id, &user_id).await
 }
 
 /// Favorite a workflow
@@ -322,7 +331,7 @@ pub async fn favorite_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<(), String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.favorite_workflow(&workflow_id, &user_id)
+    social.favorite_workflow(&workflow_id, &user_id).await
 }
 
 /// Unfavorite a workflow
@@ -333,7 +342,7 @@ pub async fn unfavorite_workflow(
     state: State<'_, MarketplaceState>,
 ) -> Result<(), String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.unfavorite_workflow(&workflow_id, &user_id)
+    social.unfavorite_workflow(&workflow_id, &user_id).await
 }
 
 /// Check if workflow is favorited
@@ -344,7 +353,7 @@ pub async fn is_workflow_favorited(
     state: State<'_, MarketplaceState>,
 ) -> Result<bool, String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.is_favorited(&workflow_id, &user_id)
+    social.is_favorited(&workflow_id, &user_id).await
 }
 
 /// Get user's favorited workflows
@@ -354,14 +363,14 @@ pub async fn get_user_favorites(
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<PublishedWorkflow>, String> {
     let social = WorkflowSocial::new(state.db.clone());
-    let workflow_ids = social.get_user_favorites(&user_id)?;
+    let workflow_ids = social.get_user_favorites(&user_id).await?;
 
     // Get full workflow details for each favorited workflow
     let marketplace = WorkflowMarketplace::new(state.db.clone());
     let mut workflows = Vec::new();
 
     for workflow_id in workflow_ids {
-        if let Ok(workflow) = marketplace.get_workflow_by_id(&workflow_id) {
+        if let Ok(workflow) = marketplace.get_workflow_by_id(&workflow_id).await {
             workflows.push(workflow);
         }
     }
@@ -375,49 +384,49 @@ pub async fn get_user_clones(
     user_id: String,
     state: State<'_, MarketplaceState>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    let db = state
+    state
         .db
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-    let mut stmt = db
-        .prepare(
-            "SELECT
-            wc.id as clone_id,
-            wc.workflow_id,
-            pw.title as workflow_title,
-            pw.description as workflow_description,
-            pw.category,
-            pw.creator_name,
-            wc.cloned_at,
-            pw.clone_count as original_clone_count,
-            pw.avg_rating as original_avg_rating
-         FROM workflow_clones wc
-         JOIN published_workflows pw ON wc.workflow_id = pw.id
-         WHERE wc.cloner_id = ?1
-         ORDER BY wc.cloned_at DESC",
-        )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let clones = stmt
-        .query_map(rusqlite::params![&user_id], |row| {
-            Ok(serde_json::json!({
-                "clone_id": row.get::<_, String>(0)?,
-                "workflow_id": row.get::<_, String>(1)?,
-                "workflow_title": row.get::<_, String>(2)?,
-                "workflow_description": row.get::<_, String>(3)?,
-                "category": row.get::<_, String>(4)?,
-                "creator_name": row.get::<_, String>(5)?,
-                "cloned_at": row.get::<_, i64>(6)?,
-                "original_clone_count": row.get::<_, i64>(7)?,
-                "original_avg_rating": row.get::<_, f64>(8)?,
-            }))
+        .run(move |conn| {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT
+                wc.id as clone_id,
+                wc.workflow_id,
+                pw.title as workflow_title,
+                pw.description as workflow_description,
+                pw.category,
+                pw.creator_name,
+                wc.cloned_at,
+                pw.clone_count as original_clone_count,
+                pw.avg_rating as original_avg_rating
+             FROM workflow_clones wc
+             JOIN published_workflows pw ON wc.workflow_id = pw.id
+             WHERE wc.cloner_id = ?1
+             ORDER BY wc.cloned_at DESC",
+                )
+                .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+            let clones = stmt
+                .query_map(rusqlite::params![&user_id], |row| {
+                    Ok(serde_json::json!({
+                        "clone_id": row.get::<_, String>(0)?,
+                        "workflow_id": row.get::<_, String>(1)?,
+                        "workflow_title": row.get::<_, String>(2)?,
+                        "workflow_description": row.get::<_, String>(3)?,
+                        "category": row.get::<_, String>(4)?,
+                        "creator_name": row.get::<_, String>(5)?,
+                        "cloned_at": row.get::<_, i64>(6)?,
+                        "original_clone_count": row.get::<_, i64>(7)?,
+                        "original_avg_rating": row.get::<_, f64>(8)?,
+                    }))
+                })
+                .map_err(|e| format!("Failed to query clones: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to collect results: {}", e))?;
+
+            Ok(clones)
         })
-        .map_err(|e| format!("Failed to query clones: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
-
-    Ok(clones)
+        .await
 }
 
 /// Generate share link for a workflow
@@ -437,7 +446,7 @@ pub async fn share_workflow(
     };
 
     let social = WorkflowSocial::new(state.db.clone());
-    social.share_workflow(&workflow_id, platform_enum)
+    social.share_workflow(&workflow_id, platform_enum).await
 }
 
 /// Get workflow statistics
@@ -447,7 +456,7 @@ pub async fn get_workflow_stats(
     state: State<'_, MarketplaceState>,
 ) -> Result<WorkflowStats, String> {
     let social = WorkflowSocial::new(state.db.clone());
-    social.get_workflow_stats(&workflow_id)
+    social.get_workflow_stats(&workflow_id).await
 }
 
 /// Get all pre-built workflow templates
@@ -1,12 +1,18 @@
 use crate::mcp::{
-    emit_mcp_event, McpClient, McpEvent, McpHealthMonitor, McpServersConfig, McpToolRegistry,
+    catalog, emit_mcp_event, find_catalog_entry, format_resource_context, http_transport,
+    install_catalog_server, CatalogCredential, CatalogEntry, McpClient, McpError, McpEvent,
+    McpHealthMonitor, McpOAuth, McpPrompt, McpResource, McpServerClient, McpServerClientCreated,
+    McpServerClientInput, McpServerConfig, McpServerStatus, McpServersConfig, McpSupervisor,
+    McpToolDefinition, McpToolRegistry, McpToolServer, PromptGetResult, ResourceReadResult,
+    ResultProcessingSettings, ResultSummarizer, ServerHealth,
 };
+use crate::security::vault::{mcp_secret_id, Vault};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{Manager, State};
 
 /// MCP state managed by Tauri
 pub struct McpState {
@@ -14,6 +20,7 @@ pub struct McpState {
     pub registry: Arc<McpToolRegistry>,
     pub config: Arc<Mutex<McpServersConfig>>,
     pub health_monitor: Arc<McpHealthMonitor>,
+    pub supervisor: Arc<McpSupervisor>,
 }
 
 impl Default for McpState {
@@ -28,12 +35,14 @@ impl McpState {
         let registry = Arc::new(McpToolRegistry::new(client.clone()));
         let config = Arc::new(Mutex::new(McpServersConfig::default()));
         let health_monitor = Arc::new(McpHealthMonitor::new(client.clone()));
+        let supervisor = Arc::new(McpSupervisor::new(client.clone(), config.clone()));
 
         Self {
             client,
             registry,
             config,
             health_monitor,
+            supervisor,
         }
     }
 
@@ -42,6 +51,95 @@ impl McpState {
         let monitor = self.health_monitor.clone();
         monitor.start_monitoring(std::time::Duration::from_secs(30), app_handle);
     }
+
+    /// Restart crashed servers in the background
+    pub fn start_supervisor(&self, app_handle: tauri::AppHandle) {
+        self.supervisor.clone().start(app_handle);
+    }
+
+    /// Sign in to remote servers with tokens kept in the vault
+    pub fn enable_oauth(&self, vault: Arc<Vault>) {
+        self.client.set_oauth(Arc::new(McpOAuth::new(vault)));
+    }
+
+    /// Pass resource and prompt changes that servers announce on to the frontend
+    pub fn forward_notifications(&self, app_handle: tauri::AppHandle) {
+        let mut notifications = self.client.subscribe_notifications();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let notification = match notifications.recv().await {
+                    Ok(notification) => notification,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("[MCP] Dropped {} server notifications", skipped);
+                        continue;
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                let server_name = notification.server_name;
+                let event = match notification.method.as_str() {
+                    "notifications/resources/updated" => {
+                        let Some(uri) = notification
+                            .params
+                            .as_ref()
+                            .and_then(|params| params.get("uri"))
+                            .and_then(Value::as_str)
+                        else {
+                            continue;
+                        };
+                        McpEvent::ResourceUpdated {
+                            server_name,
+                            uri: uri.to_string(),
+                        }
+                    }
+                    "notifications/resources/list_changed" => {
+                        McpEvent::ResourcesChanged { server_name }
+                    }
+                    "notifications/prompts/list_changed" => {
+                        McpEvent::PromptsChanged { server_name }
+                    }
+                    _ => continue,
+                };
+                emit_mcp_event(&app_handle, event);
+            }
+        });
+    }
+
+    /// Let result processing rules summarize oversized results with the LLM router
+    pub fn enable_result_summaries(&self, app_handle: tauri::AppHandle) {
+        self.registry
+            .result_processor()
+            .set_summarizer(Arc::new(LlmResultSummarizer { app_handle }));
+    }
+}
+
+/// Summarizes oversized tool results through the LLM router
+struct LlmResultSummarizer {
+    app_handle: tauri::AppHandle,
+}
+
+#[async_trait::async_trait]
+impl ResultSummarizer for LlmResultSummarizer {
+    async fn summarize(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        text: &str,
+        max_chars: usize,
+    ) -> anyhow::Result<String> {
+        let llm_state = self
+            .app_handle
+            .try_state::<crate::commands::llm::LLMState>()
+            .ok_or_else(|| anyhow::anyhow!("LLM state not available"))?;
+        let prompt = format!(
+            "The tool '{}' from MCP server '{}' returned the output below. Summarize it in at \
+             most {} characters for an assistant that is using it to complete a task. Keep \
+             identifiers, numbers, names, errors and anything needed to act on the result.\n\n{}",
+            tool_name, server_name, max_chars, text
+        );
+
+        let router = llm_state.router.lock().await;
+        router.send_message(&prompt, None).await
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +149,9 @@ pub struct McpServerInfo {
     pub connected: bool,
     pub tool_count: usize,
     pub command: String,
+    /// Endpoint of a remote server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,6 +167,7 @@ pub struct McpToolInfo {
 #[tauri::command]
 pub async fn mcp_initialize(
     state: State<'_, McpState>,
+    vault: State<'_, Arc<Vault>>,
     app: tauri::AppHandle,
 ) -> Result<String, String> {
     tracing::info!("Initializing MCP system");
@@ -90,10 +192,14 @@ pub async fn mcp_initialize(
 
     // Inject credentials from credential manager
     config
-        .inject_credentials()
+        .inject_credentials(&vault)
         .map_err(|e| format!("Failed to inject credentials: {}", e))?;
 
     // Store configuration
+    state
+        .registry
+        .result_processor()
+        .set_settings(config.result_processing.clone());
     *state.config.lock() = config.clone();
 
     // Connect to enabled servers
@@ -134,6 +240,14 @@ pub async fn mcp_initialize(
                 }
                 Err(e) => {
                     tracing::warn!("Failed to connect to MCP server '{}': {}", name, e);
+                    if matches!(e, McpError::AuthorizationRequired(_)) {
+                        emit_mcp_event(
+                            &app,
+                            McpEvent::AuthorizationRequired {
+                                server_name: name.clone(),
+                            },
+                        );
+                    }
                     emit_mcp_event(
                         &app,
                         McpEvent::ServerConnectionChanged {
@@ -156,7 +270,8 @@ pub async fn mcp_initialize(
         },
     );
 
-    // Start health monitoring
+    // Start health monitoring and crash supervision
+    state.start_supervisor(app.clone());
     state.start_health_monitoring(app);
 
     Ok(format!(
@@ -180,7 +295,11 @@ pub async fn mcp_list_servers(state: State<'_, McpState>) -> Result<Vec<McpServe
             enabled: server_config.enabled,
             connected: connected.contains(name),
             tool_count: stats.get(name).copied().unwrap_or(0),
-            command: format!("{} {}", server_config.command, server_config.args.join(" ")),
+            command: match &server_config.url {
+                Some(url) => url.clone(),
+                None => format!("{} {}", server_config.command, server_config.args.join(" ")),
+            },
+            url: server_config.url.clone(),
         })
         .collect();
 
@@ -201,6 +320,8 @@ pub async fn mcp_connect_server(
         .ok_or_else(|| format!("Server '{}' not found in configuration", name))?
         .clone();
 
+    // Reconnecting by hand lifts a quarantine
+    state.supervisor.reset(&name);
     state
         .client
         .connect_server(name.clone(), server_config)
@@ -216,6 +337,7 @@ pub async fn mcp_disconnect_server(
     state: State<'_, McpState>,
     name: String,
 ) -> Result<String, String> {
+    state.supervisor.reset(&name);
     state
         .client
         .disconnect_server(&name)
@@ -311,6 +433,7 @@ pub async fn mcp_get_config(state: State<'_, McpState>) -> Result<Value, String>
 #[tauri::command]
 pub async fn mcp_update_config(
     state: State<'_, McpState>,
+    vault: State<'_, Arc<Vault>>,
     new_config: Value,
 ) -> Result<String, String> {
     let mut parsed_config: McpServersConfig =
@@ -318,7 +441,7 @@ pub async fn mcp_update_config(
 
     // Inject credentials
     parsed_config
-        .inject_credentials()
+        .inject_credentials(&vault)
         .map_err(|e| format!("Failed to inject credentials: {}", e))?;
 
     // Save to file
@@ -330,11 +453,46 @@ pub async fn mcp_update_config(
         .map_err(|e| format!("Failed to save config: {}", e))?;
 
     // Update state
+    state
+        .registry
+        .result_processor()
+        .set_settings(parsed_config.result_processing.clone());
     *state.config.lock() = parsed_config;
 
     Ok("Configuration updated successfully".to_string())
 }
 
+/// Get the tool result processing rules
+#[tauri::command]
+pub async fn mcp_get_result_processing(
+    state: State<'_, McpState>,
+) -> Result<ResultProcessingSettings, String> {
+    Ok(state.registry.result_processor().settings())
+}
+
+/// Replace the tool result processing rules and persist them with the server config
+#[tauri::command]
+pub async fn mcp_set_result_processing(
+    state: State<'_, McpState>,
+    settings: ResultProcessingSettings,
+) -> Result<(), String> {
+    let config = {
+        let mut config = state.config.lock();
+        config.result_processing = settings.clone();
+        config.clone()
+    };
+
+    let config_path = McpServersConfig::default_config_path()
+        .map_err(|e| format!("Failed to get config path: {}", e))?;
+    config
+        .save_to_file(&config_path)
+        .await
+        .map_err(|e| format!("Failed to save config: {}", e))?;
+
+    state.registry.result_processor().set_settings(settings);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn mcp_enable_server(state: State<'_, McpState>, name: String) -> Result<String, String> {
     set_server_enabled(state, name, true).await
@@ -379,6 +537,7 @@ async fn set_server_enabled(
         .await
         .map_err(|e| format!("Failed to save MCP config: {}", e))?;
 
+    state.supervisor.reset(trimmed);
     if enabled {
         state
             .client
@@ -417,19 +576,16 @@ pub async fn mcp_get_server_logs(
     Ok(vec![])
 }
 
-/// Store a credential in Windows Credential Manager
+/// Store a credential for an MCP server in the vault
 #[tauri::command]
 pub async fn mcp_store_credential(
+    vault: State<'_, Arc<Vault>>,
     server_name: String,
     key: String,
     value: String,
 ) -> Result<String, String> {
-    let service = format!("agiworkforce-mcp-{}", server_name);
-    let entry = keyring::Entry::new(&service, &key)
-        .map_err(|e| format!("Failed to create credential entry: {}", e))?;
-
-    entry
-        .set_password(&value)
+    vault
+        .put(&mcp_secret_id(&server_name, &key), &value, "mcp")
         .map_err(|e| format!("Failed to store credential: {}", e))?;
 
     Ok(format!("Credential stored for {} / {}", server_name, key))
@@ -449,6 +605,15 @@ pub async fn mcp_get_health(
     Ok(state.health_monitor.get_all_health())
 }
 
+/// Supervision state of servers that crashed recently, are waiting to be
+/// restarted or are quarantined
+#[tauri::command]
+pub async fn mcp_get_server_statuses(
+    state: State<'_, McpState>,
+) -> Result<Vec<crate::mcp::ServerStatusEvent>, String> {
+    Ok(state.supervisor.statuses())
+}
+
 /// Check health of a specific MCP server
 #[tauri::command]
 pub async fn mcp_check_server_health(
@@ -458,3 +623,491 @@ pub async fn mcp_check_server_health(
     let health = state.health_monitor.check_server_health(&server_name).await;
     Ok(health)
 }
+
+#[derive(Debug, Serialize)]
+pub struct McpCatalogListing {
+    #[serde(flatten)]
+    pub entry: CatalogEntry,
+    pub installed: bool,
+    pub connected: bool,
+    /// Required credential keys not yet in the vault
+    pub missing_credentials: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum McpCatalogInstallResult {
+    /// Nothing was installed; ask the user for these and install again
+    NeedsCredentials { missing: Vec<CatalogCredential> },
+    Installed {
+        server_name: String,
+        health: ServerHealth,
+        /// Why connecting after the install failed
+        error: Option<String>,
+    },
+}
+
+/// List the curated MCP servers that can be installed in one step
+#[tauri::command]
+pub async fn mcp_catalog_list(
+    state: State<'_, McpState>,
+    vault: State<'_, Arc<Vault>>,
+) -> Result<Vec<McpCatalogListing>, String> {
+    let installed: HashSet<String> = state.config.lock().mcp_servers.keys().cloned().collect();
+    let connected: HashSet<String> = state.client.get_connected_servers().into_iter().collect();
+
+    Ok(catalog()
+        .iter()
+        .map(|entry| McpCatalogListing {
+            installed: installed.contains(&entry.id),
+            connected: connected.contains(&entry.id),
+            missing_credentials: entry
+                .missing_credentials(&HashMap::new(), &vault)
+                .into_iter()
+                .map(|credential| credential.key)
+                .collect(),
+            entry: entry.clone(),
+        })
+        .collect())
+}
+
+/// Install a catalog server, store its credentials in the vault, add it to
+/// the config and connect to it. Returns the missing credentials instead when
+/// required ones were neither given nor stored
+#[tauri::command]
+pub async fn mcp_catalog_install(
+    state: State<'_, McpState>,
+    vault: State<'_, Arc<Vault>>,
+    app: tauri::AppHandle,
+    id: String,
+    credentials: Option<HashMap<String, String>>,
+) -> Result<McpCatalogInstallResult, String> {
+    let entry =
+        find_catalog_entry(&id).ok_or_else(|| format!("'{}' is not in the MCP catalog", id))?;
+    let credentials = credentials.unwrap_or_default();
+    if let Some(key) = credentials.keys().find(|key| {
+        !entry
+            .credentials
+            .iter()
+            .any(|credential| &credential.key == *key)
+    }) {
+        return Err(format!(
+            "{} does not use a credential named {}",
+            entry.name, key
+        ));
+    }
+    let missing = entry.missing_credentials(&credentials, &vault);
+    if !missing.is_empty() {
+        return Ok(McpCatalogInstallResult::NeedsCredentials { missing });
+    }
+
+    for (key, value) in credentials
+        .iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+    {
+        vault
+            .put(&mcp_secret_id(&entry.id, key), value.trim(), "mcp")
+            .map_err(|e| format!("Failed to store credential: {}", e))?;
+    }
+
+    let server_config = install_catalog_server(entry)
+        .await
+        .map_err(|e| e.to_string())?;
+    let snapshot = {
+        let mut config = state.config.lock();
+        config
+            .mcp_servers
+            .insert(entry.id.clone(), server_config.clone());
+        config.clone()
+    };
+    let config_path = McpServersConfig::default_config_path()
+        .map_err(|e| format!("Failed to get config path: {}", e))?;
+    snapshot
+        .save_to_file(&config_path)
+        .await
+        .map_err(|e| format!("Failed to save MCP config: {}", e))?;
+
+    // Connect with the credentials filled in
+    let mut connect_config = McpServersConfig {
+        mcp_servers: HashMap::from([(entry.id.clone(), server_config)]),
+        result_processing: ResultProcessingSettings::default(),
+    };
+    connect_config
+        .inject_credentials(&vault)
+        .map_err(|e| format!("Failed to inject credentials: {}", e))?;
+    let server_config = connect_config
+        .mcp_servers
+        .remove(&entry.id)
+        .ok_or_else(|| format!("Server '{}' not found in configuration", entry.id))?;
+    state
+        .config
+        .lock()
+        .mcp_servers
+        .insert(entry.id.clone(), server_config.clone());
+
+    state.supervisor.reset(&entry.id);
+    let error = state
+        .client
+        .connect_server(entry.id.clone(), server_config)
+        .await
+        .err()
+        .map(|e| e.to_string());
+    emit_mcp_event(
+        &app,
+        McpEvent::ServerConnectionChanged {
+            server_name: entry.id.clone(),
+            connected: error.is_none(),
+            error: error.clone(),
+        },
+    );
+
+    let health = state.health_monitor.check_server_health(&entry.id).await;
+    Ok(McpCatalogInstallResult::Installed {
+        server_name: entry.id.clone(),
+        health,
+        error,
+    })
+}
+
+/// Whether the app's own MCP server is listening, and its registered clients
+#[tauri::command]
+pub async fn mcp_server_status(
+    server: State<'_, Arc<McpToolServer>>,
+) -> Result<McpServerStatus, String> {
+    server.status().await
+}
+
+/// Tools outside MCP clients can be allowed to call
+#[tauri::command]
+pub async fn mcp_server_list_tools(
+    server: State<'_, Arc<McpToolServer>>,
+) -> Result<Vec<McpToolDefinition>, String> {
+    Ok(server.exposed_tools())
+}
+
+/// Register an outside MCP client. Its token is only returned here
+#[tauri::command]
+pub async fn mcp_server_create_client(
+    server: State<'_, Arc<McpToolServer>>,
+    input: McpServerClientInput,
+) -> Result<McpServerClientCreated, String> {
+    server.inner().create_client(input).await
+}
+
+#[tauri::command]
+pub async fn mcp_server_update_client(
+    server: State<'_, Arc<McpToolServer>>,
+    id: String,
+    input: McpServerClientInput,
+) -> Result<McpServerClient, String> {
+    server.inner().update_client(id, input).await
+}
+
+#[tauri::command]
+pub async fn mcp_server_delete_client(
+    server: State<'_, Arc<McpToolServer>>,
+    id: String,
+) -> Result<bool, String> {
+    server.delete_client(id).await
+}
+
+/// Longest text taken from one resource attached to a chat message
+const RESOURCE_CONTEXT_MAX_CHARS: usize = 20_000;
+
+/// A resource to attach to a chat message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResourceRef {
+    #[serde(alias = "serverName")]
+    pub server_name: String,
+    pub uri: String,
+}
+
+/// Context for a chat message from the resources it attaches; resources that
+/// fail to load are skipped
+pub(crate) async fn resource_chat_context(
+    app: &tauri::AppHandle,
+    resources: &[McpResourceRef],
+) -> Option<String> {
+    let state = app.try_state::<McpState>()?;
+    let mut read = Vec::new();
+    for resource in resources {
+        match state
+            .client
+            .read_resource(&resource.server_name, &resource.uri)
+            .await
+        {
+            Ok(result) => read.push((resource.server_name.clone(), result)),
+            Err(e) => tracing::warn!(
+                "Failed to read MCP resource {} from '{}': {}",
+                resource.uri,
+                resource.server_name,
+                e
+            ),
+        }
+    }
+    (!read.is_empty()).then(|| format_resource_context(&read, RESOURCE_CONTEXT_MAX_CHARS))
+}
+
+/// Resources of one server, or of every connected server
+#[tauri::command]
+pub async fn mcp_list_resources(
+    state: State<'_, McpState>,
+    server_name: Option<String>,
+) -> Result<Vec<McpResource>, String> {
+    state
+        .client
+        .list_resources(server_name.as_deref())
+        .await
+        .map_err(|e| format!("Failed to list resources: {}", e))
+}
+
+#[tauri::command]
+pub async fn mcp_read_resource(
+    state: State<'_, McpState>,
+    server_name: String,
+    uri: String,
+) -> Result<ResourceReadResult, String> {
+    state
+        .client
+        .read_resource(&server_name, &uri)
+        .await
+        .map_err(|e| format!("Failed to read resource: {}", e))
+}
+
+/// Emit `mcp://resource-updated` whenever the resource changes
+#[tauri::command]
+pub async fn mcp_subscribe_resource(
+    state: State<'_, McpState>,
+    server_name: String,
+    uri: String,
+) -> Result<(), String> {
+    state
+        .client
+        .subscribe_resource(&server_name, &uri)
+        .await
+        .map_err(|e| format!("Failed to subscribe to resource: {}", e))
+}
+
+#[tauri::command]
+pub async fn mcp_unsubscribe_resource(
+    state: State<'_, McpState>,
+    server_name: String,
+    uri: String,
+) -> Result<(), String> {
+    state
+        .client
+        .unsubscribe_resource(&server_name, &uri)
+        .await
+        .map_err(|e| format!("Failed to unsubscribe from resource: {}", e))
+}
+
+/// Prompt templates of one server, or of every connected server
+#[tauri::command]
+pub async fn mcp_list_prompts(
+    state: State<'_, McpState>,
+    server_name: Option<String>,
+) -> Result<Vec<McpPrompt>, String> {
+    state
+        .client
+        .list_prompts(server_name.as_deref())
+        .await
+        .map_err(|e| format!("Failed to list prompts: {}", e))
+}
+
+/// Render a server's prompt template with its arguments
+#[tauri::command]
+pub async fn mcp_get_prompt(
+    state: State<'_, McpState>,
+    server_name: String,
+    name: String,
+    arguments: Option<HashMap<String, String>>,
+) -> Result<PromptGetResult, String> {
+    state
+        .client
+        .get_prompt(&server_name, &name, arguments.unwrap_or_default())
+        .await
+        .map_err(|e| format!("Failed to get prompt: {}", e))
+}
+
+#[derive(Debug, Serialize)]
+pub struct McpRemoteServerAdded {
+    pub server_name: String,
+    pub connected: bool,
+    /// Sign in with `mcp_start_oauth` to connect
+    pub authorization_required: bool,
+    pub error: Option<String>,
+}
+
+/// Add a remote server reached over Streamable HTTP and connect to it
+#[tauri::command]
+pub async fn mcp_add_remote_server(
+    state: State<'_, McpState>,
+    app: tauri::AppHandle,
+    name: String,
+    url: String,
+) -> Result<McpRemoteServerAdded, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Server name cannot be empty".to_string());
+    }
+    let url = http_transport::validate_url(url.trim()).map_err(|e| e.to_string())?;
+
+    let server_config = McpServerConfig {
+        command: String::new(),
+        args: Vec::new(),
+        url: Some(url),
+        env: HashMap::new(),
+        enabled: true,
+    };
+    let snapshot = {
+        let mut config = state.config.lock();
+        if config.mcp_servers.contains_key(&name) {
+            return Err(format!("Server '{}' already exists", name));
+        }
+        config
+            .mcp_servers
+            .insert(name.clone(), server_config.clone());
+        config.clone()
+    };
+    let config_path = McpServersConfig::default_config_path()
+        .map_err(|e| format!("Failed to get config path: {}", e))?;
+    snapshot
+        .save_to_file(&config_path)
+        .await
+        .map_err(|e| format!("Failed to save MCP config: {}", e))?;
+
+    let result = state
+        .client
+        .connect_server(name.clone(), server_config)
+        .await;
+    let authorization_required = matches!(result, Err(McpError::AuthorizationRequired(_)));
+    if authorization_required {
+        emit_mcp_event(
+            &app,
+            McpEvent::AuthorizationRequired {
+                server_name: name.clone(),
+            },
+        );
+    }
+    let error = result.err().map(|e| e.to_string());
+    emit_mcp_event(
+        &app,
+        McpEvent::ServerConnectionChanged {
+            server_name: name.clone(),
+            connected: error.is_none(),
+            error: error.clone(),
+        },
+    );
+
+    Ok(McpRemoteServerAdded {
+        server_name: name,
+        connected: error.is_none(),
+        authorization_required,
+        error,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct McpOAuthStartResponse {
+    pub auth_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct McpOAuthCompleteRequest {
+    pub state: String,
+    pub code: String,
+}
+
+fn remote_server_url(state: &McpState, server_name: &str) -> Result<String, String> {
+    state
+        .config
+        .lock()
+        .mcp_servers
+        .get(server_name)
+        .ok_or_else(|| format!("Server '{}' not found in configuration", server_name))?
+        .url
+        .clone()
+        .ok_or_else(|| format!("Server '{}' is not a remote server", server_name))
+}
+
+fn oauth(state: &McpState) -> Result<Arc<McpOAuth>, String> {
+    state
+        .client
+        .oauth()
+        .ok_or_else(|| "MCP authorization is not available".to_string())
+}
+
+/// Begin signing in to a remote server. Registers the app with the server's
+/// authorization server when needed; open `auth_url` and pass the code the
+/// redirect receives to `mcp_complete_oauth`
+#[tauri::command]
+pub async fn mcp_start_oauth(
+    state: State<'_, McpState>,
+    server_name: String,
+    redirect_uri: String,
+) -> Result<McpOAuthStartResponse, String> {
+    let url = remote_server_url(&state, &server_name)?;
+    let (auth_url, oauth_state) = oauth(&state)?
+        .start(&server_name, &url, &redirect_uri)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(McpOAuthStartResponse {
+        auth_url,
+        state: oauth_state,
+    })
+}
+
+/// Finish signing in to a remote server and connect to it
+#[tauri::command]
+pub async fn mcp_complete_oauth(
+    state: State<'_, McpState>,
+    app: tauri::AppHandle,
+    request: McpOAuthCompleteRequest,
+) -> Result<String, String> {
+    let server_name = oauth(&state)?
+        .complete(&request.state, &request.code)
+        .await
+        .map_err(|e| e.to_string())?;
+    let server_config = state
+        .config
+        .lock()
+        .mcp_servers
+        .get(&server_name)
+        .cloned()
+        .ok_or_else(|| format!("Server '{}' not found in configuration", server_name))?;
+
+    // Replace a session that failed or was started before signing in
+    let _ = state.client.disconnect_server(&server_name).await;
+    state.supervisor.reset(&server_name);
+    let result = state
+        .client
+        .connect_server(server_name.clone(), server_config)
+        .await;
+    emit_mcp_event(
+        &app,
+        McpEvent::ServerConnectionChanged {
+            server_name: server_name.clone(),
+            connected: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        },
+    );
+    result.map_err(|e| format!("Signed in, but failed to connect: {}", e))?;
+
+    Ok(format!("Connected to server '{}'", server_name))
+}
+
+/// Forget a remote server's tokens and disconnect from it
+#[tauri::command]
+pub async fn mcp_sign_out(state: State<'_, McpState>, server_name: String) -> Result<(), String> {
+    remote_server_url(&state, &server_name)?;
+    oauth(&state)?
+        .forget(&server_name)
+        .map_err(|e| e.to_string())?;
+    state.supervisor.reset(&server_name);
+    if let Err(e) = state.client.disconnect_server(&server_name).await {
+        tracing::debug!("Server '{}' was not connected: {}", server_name, e);
+    }
+    Ok(())
+}
@@ -6,11 +6,11 @@ use crate::api_integrations::veo3::{
     Veo3Client, VideoGenerationRequest, VideoResolution, VideoStatus,
 };
 use crate::api_integrations::{APIError, RequestConfig};
-use keyring::Entry;
+use crate::security::vault::{SecretId, Vault};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::Instant;
-
-const KEYRING_SERVICE: &str = "AGIWorkforce";
+use tauri::State;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -77,12 +77,13 @@ pub struct MediaVideoResponse {
 /// Generate images with a concrete provider (Imagen/DALL-E/SDXL)
 #[tauri::command]
 pub async fn media_generate_image(
+    vault: State<'_, Arc<Vault>>,
     request: MediaImageRequest,
 ) -> Result<MediaImageResponse, String> {
     let provider = map_image_provider(request.provider.as_deref());
     let provider_str = provider_to_label(&provider);
 
-    let api_key = resolve_api_key(provider_hint(&provider))
+    let api_key = resolve_api_key(&vault, provider_hint(&provider))
         .map_err(|e| format!("API key for {} missing: {}", provider_str, e))?;
 
     let client = ImageGenerationClient::new(
@@ -141,6 +142,7 @@ pub async fn media_generate_image(
 /// Generate video via Veo 3.1 (Google DeepMind) with optional Pro/Max gating
 #[tauri::command]
 pub async fn media_generate_video(
+    vault: State<'_, Arc<Vault>>,
     request: MediaVideoRequest,
 ) -> Result<MediaVideoResponse, String> {
     if let Some(plan) = request.plan.as_deref() {
@@ -149,8 +151,8 @@ pub async fn media_generate_video(
         }
     }
 
-    let api_key =
-        resolve_api_key("google").map_err(|e| format!("API key for Veo/Google missing: {}", e))?;
+    let api_key = resolve_api_key(&vault, "google")
+        .map_err(|e| format!("API key for Veo/Google missing: {}", e))?;
 
     let client = Veo3Client::new(RequestConfig {
         api_key,
@@ -236,7 +238,7 @@ fn provider_to_label(provider: &ImageProvider) -> &'static str {
     }
 }
 
-fn resolve_api_key(provider: &str) -> Result<String, APIError> {
+fn resolve_api_key(vault: &Vault, provider: &str) -> Result<String, APIError> {
     let env_keys: Vec<String> = match provider {
         "openai" => vec!["OPENAI_API_KEY".to_string()],
         "stability" => vec!["STABILITY_API_KEY".to_string(), "STABILITY_KEY".to_string()],
@@ -257,13 +259,12 @@ fn resolve_api_key(provider: &str) -> Result<String, APIError> {
         }
     }
 
-    // Fallback to keyring (shared with settings module)
-    let entry = Entry::new(KEYRING_SERVICE, &format!("api_key_{}", provider))
-        .map_err(|e| APIError::APIError(format!("Keyring unavailable: {}", e)))?;
-
-    entry
-        .get_password()
-        .map_err(|_| APIError::MissingAPIKey(provider.to_string()))
+    // Fall back to the key saved in settings
+    vault
+        .get(&SecretId::provider(provider), "media")
+        .map_err(|e| APIError::APIError(format!("Vault unavailable: {}", e)))?
+        .map(|key| key.expose_secret().to_string())
+        .ok_or_else(|| APIError::MissingAPIKey(provider.to_string()))
 }
 
 fn estimate_image_cost(provider: &ImageProvider, count: u32) -> Option<f64> {
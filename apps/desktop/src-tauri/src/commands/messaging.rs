@@ -1,12 +1,18 @@
 use crate::commands::AppDatabase;
+use crate::messaging::manager::{
+    MessagingPollRequest, MessagingPollResult, MessagingSendRequest, MessagingSendResult,
+    MessagingThreadSummary,
+};
+use crate::messaging::slack_events::SlackListenerStatus;
+use crate::messaging::whatsapp::WhatsAppWebhook;
 use crate::messaging::{
-    MessagingConnection, MessagingPlatform, MessagingRouter, SendMessageRequest,
-    SendMessageResponse, SlackClient, SlackConfig, TeamsClient, TeamsConfig, UnifiedMessage,
-    WhatsAppClient,
+    MessagingConnection, MessagingManager, MessagingPlatform, MessagingRouter, SendMessageRequest,
+    SendMessageResponse, SlackClient, SlackConfig, SlackEventManager, TeamsClient, TeamsConfig,
+    UnifiedMessage, WhatsAppClient,
 };
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,71 +42,78 @@ pub struct ConnectTeamsRequest {
     pub workspace_name: Option<String>,
 }
 
-/// Connect to Slack workspace
+/// Connect to Slack workspace. Tokens are kept in the secret store, keyed by
+/// the Slack workspace, and a Socket Mode listener starts when an app-level
+/// token is given. Reconnecting a workspace updates its existing connection
 #[tauri::command]
 pub async fn connect_slack(
     request: ConnectSlackRequest,
+    app: AppHandle,
     db: State<'_, AppDatabase>,
+    slack: State<'_, SlackEventManager>,
 ) -> Result<MessagingConnection, String> {
-    // Validate Slack credentials by creating a client
+    // Validate Slack credentials and identify the workspace they belong to
     let config = SlackConfig {
         bot_token: request.bot_token.clone(),
         app_token: request.app_token.clone(),
         signing_secret: request.signing_secret.clone(),
     };
 
-    SlackClient::new(config).map_err(|e| format!("Failed to create Slack client: {}", e))?;
-
-    // Generate connection ID
-    let connection_id = Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().timestamp();
+    let client = SlackClient::new(config.clone())
+        .map_err(|e| format!("Failed to create Slack client: {}", e))?;
+    let auth = client
+        .auth_test()
+        .await
+        .map_err(|e| format!("Failed to authenticate with Slack: {}", e))?;
+
+    let workspace_id = request.workspace_id.or(Some(auth.team_id.clone()));
+    let workspace_name = request.workspace_name.or(auth.team);
+    let credentials = slack
+        .store_credentials(&auth.team_id, &config)
+        .map_err(|e| e.to_string())?;
+
+    let connection = {
+        let conn = db
+            .conn
+            .lock()
+            .map_err(|e| format!("Database lock error: {}", e))?;
+        let connection_id = connection_id_for(
+            &conn,
+            &request.user_id,
+            &MessagingPlatform::Slack,
+            workspace_id.as_deref(),
+        )?;
+        save_connection(
+            &conn,
+            MessagingConnection {
+                id: connection_id,
+                user_id: request.user_id,
+                platform: MessagingPlatform::Slack,
+                workspace_id,
+                workspace_name,
+                is_active: true,
+                created_at: chrono::Utc::now().timestamp(),
+                last_used_at: None,
+            },
+            &credentials,
+        )?
+    };
 
-    // Encrypt credentials (in production, use proper encryption)
-    let credentials_json = serde_json::json!({
-        "bot_token": request.bot_token,
-        "app_token": request.app_token,
-        "signing_secret": request.signing_secret,
-    })
-    .to_string();
+    if !config.app_token.is_empty() {
+        if let Err(e) = slack.start(&app, &connection.id) {
+            tracing::warn!("Failed to start Slack listener: {}", e);
+        }
+    }
 
-    // Store connection in database
-    db.conn
-        .lock()
-        .map_err(|e| format!("Database lock error: {}", e))?
-        .execute(
-            "INSERT INTO messaging_connections
-            (id, user_id, platform, workspace_id, workspace_name, credentials, is_active, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                connection_id,
-                request.user_id,
-                "slack",
-                request.workspace_id,
-                request.workspace_name,
-                credentials_json,
-                1,
-                now,
-            ],
-        )
-        .map_err(|e| format!("Failed to store connection: {}", e))?;
-
-    Ok(MessagingConnection {
-        id: connection_id,
-        user_id: request.user_id,
-        platform: MessagingPlatform::Slack,
-        workspace_id: request.workspace_id,
-        workspace_name: request.workspace_name,
-        is_active: true,
-        created_at: now,
-        last_used_at: None,
-    })
+    Ok(connection)
 }
 
-/// Connect to WhatsApp Business API
+/// Connect to WhatsApp Business API. Each phone number is one connection
 #[tauri::command]
 pub async fn connect_whatsapp(
     request: ConnectWhatsAppRequest,
     db: State<'_, AppDatabase>,
+    messaging: State<'_, MessagingManager>,
 ) -> Result<MessagingConnection, String> {
     // Validate WhatsApp credentials
     WhatsAppClient::new(
@@ -109,51 +122,51 @@ pub async fn connect_whatsapp(
     )
     .map_err(|e| format!("Failed to create WhatsApp client: {}", e))?;
 
-    let connection_id = Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().timestamp();
-
-    let credentials_json = serde_json::json!({
-        "phone_number_id": request.phone_number_id,
-        "access_token": request.access_token,
-        "verify_token": request.verify_token,
-    })
-    .to_string();
-
-    db.conn
-        .lock()
-        .map_err(|e| format!("Database lock error: {}", e))?
-        .execute(
-            "INSERT INTO messaging_connections
-            (id, user_id, platform, credentials, is_active, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                connection_id,
-                request.user_id,
-                "whatsapp",
-                credentials_json,
-                1,
-                now,
-            ],
+    let connection_id = connection_id_for(
+        &*db.conn
+            .lock()
+            .map_err(|e| format!("Database lock error: {}", e))?,
+        &request.user_id,
+        &MessagingPlatform::WhatsApp,
+        Some(&request.phone_number_id),
+    )?;
+
+    let credentials_json = messaging
+        .store_credentials(
+            &MessagingPlatform::WhatsApp,
+            &connection_id,
+            &serde_json::json!({
+                "phone_number_id": request.phone_number_id,
+                "access_token": request.access_token,
+                "verify_token": request.verify_token,
+            }),
         )
-        .map_err(|e| format!("Failed to store connection: {}", e))?;
-
-    Ok(MessagingConnection {
-        id: connection_id,
-        user_id: request.user_id,
-        platform: MessagingPlatform::WhatsApp,
-        workspace_id: None,
-        workspace_name: Some("WhatsApp Business".to_string()),
-        is_active: true,
-        created_at: now,
-        last_used_at: None,
-    })
+        .map_err(|e| e.to_string())?;
+
+    save_connection(
+        &*db.conn
+            .lock()
+            .map_err(|e| format!("Database lock error: {}", e))?,
+        MessagingConnection {
+            id: connection_id,
+            user_id: request.user_id,
+            platform: MessagingPlatform::WhatsApp,
+            workspace_id: Some(request.phone_number_id),
+            workspace_name: Some("WhatsApp Business".to_string()),
+            is_active: true,
+            created_at: chrono::Utc::now().timestamp(),
+            last_used_at: None,
+        },
+        &credentials_json,
+    )
 }
 
-/// Connect to Microsoft Teams
+/// Connect to Microsoft Teams. Each tenant is one connection
 #[tauri::command]
 pub async fn connect_teams(
     request: ConnectTeamsRequest,
     db: State<'_, AppDatabase>,
+    messaging: State<'_, MessagingManager>,
 ) -> Result<MessagingConnection, String> {
     // Validate Teams credentials
     let config = TeamsConfig {
@@ -171,46 +184,108 @@ pub async fn connect_teams(
         .await
         .map_err(|e| format!("Failed to authenticate with Teams: {}", e))?;
 
-    let connection_id = Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().timestamp();
+    let connection_id = connection_id_for(
+        &*db.conn
+            .lock()
+            .map_err(|e| format!("Database lock error: {}", e))?,
+        &request.user_id,
+        &MessagingPlatform::Teams,
+        Some(&request.tenant_id),
+    )?;
+    // Drop a cached client holding the previous credentials
+    messaging.forget(&connection_id);
+
+    let credentials_json = messaging
+        .store_credentials(
+            &MessagingPlatform::Teams,
+            &connection_id,
+            &serde_json::json!({
+                "tenant_id": request.tenant_id,
+                "client_id": request.client_id,
+                "client_secret": request.client_secret,
+            }),
+        )
+        .map_err(|e| e.to_string())?;
+
+    save_connection(
+        &*db.conn
+            .lock()
+            .map_err(|e| format!("Database lock error: {}", e))?,
+        MessagingConnection {
+            id: connection_id,
+            user_id: request.user_id,
+            platform: MessagingPlatform::Teams,
+            workspace_id: Some(request.tenant_id),
+            workspace_name: request.workspace_name,
+            is_active: true,
+            created_at: chrono::Utc::now().timestamp(),
+            last_used_at: None,
+        },
+        &credentials_json,
+    )
+}
 
-    let credentials_json = serde_json::json!({
-        "tenant_id": request.tenant_id,
-        "client_id": request.client_id,
-        "client_secret": request.client_secret,
-    })
-    .to_string();
+/// The user's existing connection to a workspace, or a new connection ID
+fn connection_id_for(
+    conn: &Connection,
+    user_id: &str,
+    platform: &MessagingPlatform,
+    workspace_id: Option<&str>,
+) -> Result<String, String> {
+    let Some(workspace_id) = workspace_id else {
+        return Ok(Uuid::new_v4().to_string());
+    };
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM messaging_connections
+             WHERE user_id = ?1 AND platform = ?2 AND workspace_id = ?3
+             ORDER BY is_active DESC, created_at DESC
+             LIMIT 1",
+            params![user_id, platform.as_str(), workspace_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to look up connection: {}", e))?;
+    Ok(existing.unwrap_or_else(|| Uuid::new_v4().to_string()))
+}
 
-    db.conn
-        .lock()
-        .map_err(|e| format!("Database lock error: {}", e))?
-        .execute(
-            "INSERT INTO messaging_connections
-            (id, user_id, platform, workspace_id, workspace_name, credentials, is_active, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                connection_id,
-                request.user_id,
-                "teams",
-                Some(request.tenant_id.clone()),
-                request.workspace_name,
-                credentials_json,
-                1,
-                now,
-            ],
+/// Insert or reactivate a connection, keeping the original `created_at`
+fn save_connection(
+    conn: &Connection,
+    mut connection: MessagingConnection,
+    credentials: &str,
+) -> Result<MessagingConnection, String> {
+    conn.execute(
+        "INSERT INTO messaging_connections
+        (id, user_id, platform, workspace_id, workspace_name, credentials, is_active, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7)
+        ON CONFLICT(id) DO UPDATE SET
+            workspace_name = COALESCE(excluded.workspace_name, workspace_name),
+            credentials = excluded.credentials,
+            is_active = 1",
+        params![
+            connection.id,
+            connection.user_id,
+            connection.platform.as_str(),
+            connection.workspace_id,
+            connection.workspace_name,
+            credentials,
+            connection.created_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to store connection: {}", e))?;
+
+    let (workspace_name, created_at, last_used_at) = conn
+        .query_row(
+            "SELECT workspace_name, created_at, last_used_at FROM messaging_connections WHERE id = ?1",
+            params![connection.id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
-        .map_err(|e| format!("Failed to store connection: {}", e))?;
-
-    Ok(MessagingConnection {
-        id: connection_id,
-        user_id: request.user_id,
-        platform: MessagingPlatform::Teams,
-        workspace_id: Some(request.tenant_id),
-        workspace_name: request.workspace_name,
-        is_active: true,
-        created_at: now,
-        last_used_at: None,
-    })
+        .map_err(|e| format!("Failed to read connection: {}", e))?;
+    connection.workspace_name = workspace_name;
+    connection.created_at = created_at;
+    connection.last_used_at = last_used_at;
+    Ok(connection)
 }
 
 /// Send a message through any messaging platform
@@ -220,6 +295,7 @@ pub async fn send_message(
     channel_id: String,
     text: String,
     db: State<'_, AppDatabase>,
+    slack: State<'_, SlackEventManager>,
 ) -> Result<SendMessageResponse, String> {
     // Get connection details
     let conn = db
@@ -245,23 +321,9 @@ pub async fn send_message(
 
     match platform {
         MessagingPlatform::Slack => {
-            let creds: serde_json::Value = serde_json::from_str(&credentials)
-                .map_err(|e| format!("Invalid credentials: {}", e))?;
-
-            let config = SlackConfig {
-                bot_token: creds["bot_token"]
-                    .as_str()
-                    .ok_or("Missing bot_token")?
-                    .to_string(),
-                app_token: creds["app_token"]
-                    .as_str()
-                    .ok_or("Missing app_token")?
-                    .to_string(),
-                signing_secret: creds["signing_secret"]
-                    .as_str()
-                    .ok_or("Missing signing_secret")?
-                    .to_string(),
-            };
+            let config = slack
+                .config_from_credentials(&credentials)
+                .map_err(|e| e.to_string())?;
 
             let client = SlackClient::new(config)
                 .map_err(|e| format!("Failed to create Slack client: {}", e))?;
@@ -417,7 +479,12 @@ pub async fn get_messaging_history(
 pub async fn disconnect_platform(
     connection_id: String,
     db: State<'_, AppDatabase>,
+    slack: State<'_, SlackEventManager>,
+    messaging: State<'_, MessagingManager>,
 ) -> Result<(), String> {
+    slack.stop(&connection_id);
+    messaging.forget(&connection_id);
+
     db.conn
         .lock()
         .map_err(|e| format!("Database lock error: {}", e))?
@@ -474,6 +541,88 @@ pub async fn list_messaging_connections(
     Ok(connections)
 }
 
+/// Start (or restart) the Socket Mode listener for a Slack connection
+#[tauri::command]
+pub async fn slack_events_start(
+    connection_id: String,
+    app: AppHandle,
+    slack: State<'_, SlackEventManager>,
+) -> Result<(), String> {
+    slack
+        .start(&app, &connection_id)
+        .map_err(|e| format!("Failed to start Slack listener: {}", e))
+}
+
+/// Stop a Slack connection's listener; returns whether one was running
+#[tauri::command]
+pub async fn slack_events_stop(
+    connection_id: String,
+    slack: State<'_, SlackEventManager>,
+) -> Result<bool, String> {
+    Ok(slack.stop(&connection_id))
+}
+
+/// Listener state of every Slack connection being listened to
+#[tauri::command]
+pub async fn slack_events_status(
+    slack: State<'_, SlackEventManager>,
+) -> Result<Vec<SlackListenerStatus>, String> {
+    Ok(slack.statuses())
+}
+
+/// Send a message on any connected platform, in a thread when `thread_id` is set
+#[tauri::command]
+pub async fn messaging_send(
+    request: MessagingSendRequest,
+    db: State<'_, AppDatabase>,
+    messaging: State<'_, MessagingManager>,
+) -> Result<MessagingSendResult, String> {
+    messaging
+        .send(&db, request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recent conversations across connections, newest first
+#[tauri::command]
+pub async fn messaging_list_threads(
+    connection_id: Option<String>,
+    platform: Option<String>,
+    limit: Option<usize>,
+    db: State<'_, AppDatabase>,
+    messaging: State<'_, MessagingManager>,
+) -> Result<Vec<MessagingThreadSummary>, String> {
+    messaging
+        .list_threads(&db, connection_id.as_deref(), platform.as_deref(), limit)
+        .map_err(|e| e.to_string())
+}
+
+/// Inbound messages since the previous poll's cursor
+#[tauri::command]
+pub async fn messaging_poll(
+    request: MessagingPollRequest,
+    app: AppHandle,
+    messaging: State<'_, MessagingManager>,
+) -> Result<MessagingPollResult, String> {
+    messaging
+        .poll(&app, request)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Record the messages in a WhatsApp Business webhook delivery; returns how
+/// many were new
+#[tauri::command]
+pub async fn messaging_whatsapp_webhook(
+    payload: WhatsAppWebhook,
+    app: AppHandle,
+    messaging: State<'_, MessagingManager>,
+) -> Result<usize, String> {
+    messaging
+        .record_whatsapp_webhook(&app, &payload)
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
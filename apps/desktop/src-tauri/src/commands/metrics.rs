@@ -135,8 +135,8 @@ pub async fn get_milestones(
 ) -> Result<Vec<MilestoneData>, String> {
     let db_conn = collector.0.db_conn();
     let conn = db_conn
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
+        .get()
+        .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
     let mut stmt = conn
         .prepare(
@@ -172,8 +172,8 @@ pub async fn share_milestone(
 ) -> Result<(), String> {
     let db_conn = collector.0.db_conn();
     let conn = db_conn
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
+        .get()
+        .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
     conn.execute(
         "UPDATE user_milestones SET shared = 1 WHERE id = ?1",
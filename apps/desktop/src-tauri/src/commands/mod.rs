@@ -4,6 +4,8 @@ pub mod ai_employees;
 pub mod ai_native;
 pub mod analytics;
 pub mod api;
+pub mod audit;
+pub mod autocomplete;
 pub mod automation;
 pub mod automation_enhanced;
 pub mod background_tasks;
@@ -15,8 +17,11 @@ pub mod chat;
 pub mod checkpoints;
 pub mod cloud;
 pub mod code_editing;
+pub mod code_review;
+pub mod companion;
 pub mod completion;
 pub mod computer_use;
+pub mod credential_health;
 pub mod database;
 pub mod debugging;
 pub mod design;
@@ -29,7 +34,12 @@ pub mod file_watcher;
 pub mod git;
 pub mod github;
 pub mod governance;
+pub mod guardrails;
+pub mod handoff;
+pub mod healthchecks;
 pub mod hooks;
+pub mod kill_switch;
+pub mod launcher;
 pub mod llm;
 pub mod lsp;
 pub mod marketplace;
@@ -38,18 +48,30 @@ pub mod media;
 pub mod messaging;
 pub mod metrics;
 pub mod migration;
+pub mod notifications;
 pub mod ocr;
 pub mod onboarding;
 pub mod operations;
 pub mod orchestration;
+pub mod permission_profiles;
+pub mod platform;
+pub mod privacy;
 pub mod process_reasoning;
 pub mod productivity;
+pub mod project_collections;
 pub mod prompt_enhancement;
+pub mod readiness;
 pub mod realtime;
+pub mod reauth;
+pub mod remote_api;
+pub mod safe_mode;
+pub mod search;
 pub mod security;
 pub mod settings;
 pub mod settings_v2;
+pub mod shell_guard;
 pub mod shortcuts;
+pub mod skills;
 pub mod subscription;
 pub mod task_persistence;
 pub mod teams;
@@ -57,8 +79,11 @@ pub mod templates;
 pub mod terminal;
 pub mod tray;
 pub mod tutorials;
+pub mod vault;
 pub mod vision;
 pub mod voice;
+pub mod web;
+pub mod webhooks;
 pub mod window;
 pub mod workspace;
 
@@ -68,6 +93,8 @@ pub use ai_employees::*;
 pub use ai_native::*;
 pub use analytics::*;
 pub use api::*;
+pub use audit::*;
+pub use autocomplete::*;
 pub use automation::*;
 pub use automation_enhanced::*;
 pub use background_tasks::*;
@@ -79,8 +106,11 @@ pub use chat::*;
 pub use checkpoints::*;
 pub use cloud::*;
 pub use code_editing::*;
+pub use code_review::*;
+pub use companion::*;
 pub use completion::*;
 pub use computer_use::*;
+pub use credential_health::*;
 pub use database::*;
 pub use debugging::*;
 pub use design::*;
@@ -93,7 +123,12 @@ pub use file_watcher::*;
 pub use git::*;
 pub use github::*;
 pub use governance::*;
+pub use guardrails::*;
+pub use handoff::*;
+pub use healthchecks::*;
 pub use hooks::*;
+pub use kill_switch::*;
+pub use launcher::*;
 pub use llm::*;
 pub use lsp::*;
 pub use marketplace::*;
@@ -102,18 +137,30 @@ pub use media::*;
 pub use messaging::*;
 pub use metrics::*;
 pub use migration::*;
+pub use notifications::*;
 pub use ocr::*;
 pub use onboarding::*;
 pub use operations::*;
 pub use orchestration::*;
+pub use permission_profiles::*;
+pub use platform::*;
+pub use privacy::*;
 pub use process_reasoning::*;
 pub use productivity::*;
+pub use project_collections::*;
 pub use prompt_enhancement::*;
+pub use readiness::*;
 pub use realtime::*;
+pub use reauth::*;
+pub use remote_api::*;
+pub use safe_mode::*;
+pub use search::*;
 pub use security::*;
 pub use settings::*;
 pub use settings_v2::*;
+pub use shell_guard::*;
 pub use shortcuts::*;
+pub use skills::*;
 pub use subscription::*;
 pub use task_persistence::*;
 pub use teams::*;
@@ -121,7 +168,10 @@ pub use templates::*;
 pub use terminal::*;
 pub use tray::*;
 pub use tutorials::*;
+pub use vault::*;
 pub use vision::*;
 pub use voice::*;
+pub use web::*;
+pub use webhooks::*;
 pub use window::*;
 pub use workspace::*;
@@ -0,0 +1,32 @@
+use crate::commands::SettingsServiceState;
+use crate::notifications::{self, NotificationActivation};
+use crate::settings::NotificationPreferences;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn notifications_get_preferences(app: AppHandle) -> Result<NotificationPreferences, String> {
+    Ok(notifications::load_preferences(&app))
+}
+
+#[tauri::command]
+pub fn notifications_set_preferences(
+    preferences: NotificationPreferences,
+    state: State<SettingsServiceState>,
+) -> Result<(), String> {
+    let service = state
+        .service
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    notifications::save_preferences(&service, &preferences)
+        .map_err(|e| format!("Failed to save notification preferences: {}", e))
+}
+
+/// Run a notification action from the in-app notification list, for
+/// platforms whose native notifications have no buttons
+#[tauri::command]
+pub async fn notifications_dispatch_action(
+    app: AppHandle,
+    activation: NotificationActivation,
+) -> Result<(), String> {
+    notifications::dispatch_action(&app, activation).await
+}
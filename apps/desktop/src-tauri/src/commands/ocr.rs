@@ -462,16 +462,25 @@ pub async fn ocr_process_with_boxes(
         "Processing OCR with bounding boxes for image: {}",
         image_path
     );
+    recognize_image(&image_path, language, preprocess.unwrap_or(false))
+}
+
+/// Run OCR on an image file without storing the result
+#[cfg(feature = "ocr")]
+pub(crate) fn recognize_image(
+    image_path: &str,
+    language: Option<String>,
+    preprocess: bool,
+) -> Result<OCRResult, String> {
     let start = Instant::now();
 
     let lang = language.unwrap_or_else(|| "eng".to_string());
-    let should_preprocess = preprocess.unwrap_or(false);
 
     // Preprocess image if requested
-    let processing_path = if should_preprocess {
-        preprocess_image(&image_path)?
+    let processing_path = if preprocess {
+        preprocess_image(image_path)?
     } else {
-        image_path.clone()
+        image_path.to_string()
     };
 
     // Initialize Tesseract
@@ -496,7 +505,7 @@ pub async fn ocr_process_with_boxes(
     let processing_time = start.elapsed().as_millis() as u64;
 
     // Clean up preprocessed image if it was created
-    if should_preprocess && processing_path != image_path {
+    if processing_path != image_path {
         let _ = std::fs::remove_file(&processing_path);
     }
 
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, State};
 
 use crate::commands::AppDatabase;
+use crate::security::{AuthenticatedUser, ReauthManager, SensitiveAction};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -138,9 +139,18 @@ pub async fn reset_onboarding(db: State<'_, AppDatabase>) -> Result<(), String>
 
 /// Export user data (for GDPR compliance)
 #[tauri::command]
-pub async fn export_user_data(db: State<'_, AppDatabase>) -> Result<String, String> {
+pub async fn export_user_data(
+    app: AppHandle,
+    user: AuthenticatedUser,
+    reauth: State<'_, ReauthManager>,
+    db: State<'_, AppDatabase>,
+) -> Result<String, String> {
     use serde_json::json;
 
+    reauth
+        .require(&app, &user, SensitiveAction::ExportUserData)
+        .await?;
+
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
     // Export conversations
@@ -259,3 +259,17 @@ pub async fn list_active_agents(app_handle: AppHandle) -> Result<Vec<serde_json:
         Ok(Vec::new()) // Return empty list if orchestrator not initialized
     }
 }
+
+/// Cancel a long-running command by the operation ID it was started with.
+/// Returns false when no such operation is running.
+#[tauri::command]
+pub fn operation_cancel(id: String) -> bool {
+    tracing::info!("[Commands] Cancelling operation: {}", id);
+    crate::cancellation::global_operations().cancel(&id)
+}
+
+/// List long-running commands that can be cancelled
+#[tauri::command]
+pub fn operation_list() -> Vec<crate::cancellation::OperationInfo> {
+    crate::cancellation::global_operations().list()
+}
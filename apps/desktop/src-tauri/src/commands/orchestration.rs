@@ -1,7 +1,14 @@
+use super::TemplateManagerState;
+use crate::db::pagination::{Page, PageRequest};
+use crate::db::Pool;
 use crate::orchestration::{
+    export_bundle, import_bundle, signing_key, BundleEncoding, BundleImportOptions,
+    BundleImportReport, RegisteredTrigger, TriggerInput, TriggerRegistry, WorkflowArtifact,
     WorkflowDefinition, WorkflowEngine, WorkflowExecution, WorkflowExecutionLog, WorkflowExecutor,
-    WorkflowScheduler,
+    WorkflowScheduler, WorkflowSimulation,
 };
+use crate::security::SecretManager;
+use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -12,23 +19,44 @@ pub struct WorkflowEngineState {
     pub engine: Arc<WorkflowEngine>,
     pub executor: Arc<WorkflowExecutor>,
     pub scheduler: Arc<WorkflowScheduler>,
+    pub triggers: Arc<TriggerRegistry>,
+    /// Holds the key workflow bundles are signed with
+    pub secrets: Option<Arc<SecretManager>>,
 }
 
 impl WorkflowEngineState {
-    pub fn new(db_path: String) -> Self {
-        let engine = Arc::new(WorkflowEngine::new(db_path));
+    pub fn new(pool: Pool) -> Self {
+        let engine = Arc::new(WorkflowEngine::new(
+            pool.path().to_string_lossy().to_string(),
+        ));
         let executor = Arc::new(WorkflowExecutor::new(Arc::clone(&engine)));
         let scheduler = Arc::new(WorkflowScheduler::new(
             Arc::clone(&engine),
             Arc::clone(&executor),
         ));
+        let triggers = Arc::new(TriggerRegistry::new(pool, Arc::clone(&executor)));
 
         Self {
             engine,
             executor,
             scheduler,
+            triggers,
+            secrets: None,
         }
     }
+
+    pub fn with_secrets(mut self, secrets: Arc<SecretManager>) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    fn bundle_signing_key(&self) -> Result<Vec<u8>, String> {
+        let secrets = self
+            .secrets
+            .as_ref()
+            .ok_or("Secret storage is not available")?;
+        signing_key(secrets)
+    }
 }
 
 /// Create a new workflow
@@ -53,7 +81,9 @@ pub fn update_workflow(
 /// Delete a workflow
 #[tauri::command]
 pub fn delete_workflow(id: String, state: State<WorkflowEngineState>) -> Result<(), String> {
-    state.engine.delete_workflow(&id)
+    state.engine.delete_workflow(&id)?;
+    state.triggers.delete_for_workflow(&id)?;
+    Ok(())
 }
 
 /// Get a workflow by ID
@@ -74,14 +104,58 @@ pub fn get_user_workflows(
     state.engine.get_user_workflows(&user_id)
 }
 
-/// Execute a workflow
+/// Get a user's workflows a page at a time
+#[tauri::command]
+pub fn get_user_workflows_page(
+    user_id: String,
+    page: Option<PageRequest>,
+    state: State<WorkflowEngineState>,
+) -> Result<Page<WorkflowDefinition>, String> {
+    state
+        .engine
+        .get_user_workflows_page(&user_id, &page.unwrap_or_default())
+}
+
+/// What `execute_workflow` returns: the started execution's id, or with
+/// `simulate` the predicted run
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum WorkflowRun {
+    Started(String),
+    Simulated(WorkflowSimulation),
+}
+
+/// Execute a workflow, or with `simulate` predict its path, duration and cost
+/// without running anything
 #[tauri::command]
 pub async fn execute_workflow(
     workflow_id: String,
     inputs: HashMap<String, Value>,
+    simulate: Option<bool>,
     state: State<'_, WorkflowEngineState>,
-) -> Result<String, String> {
-    state.executor.execute_workflow(workflow_id, inputs).await
+) -> Result<WorkflowRun, String> {
+    run_workflow(&state, workflow_id, inputs, simulate.unwrap_or(false)).await
+}
+
+/// Start or simulate a workflow; shared with the remote API
+pub(crate) async fn run_workflow(
+    state: &WorkflowEngineState,
+    workflow_id: String,
+    inputs: HashMap<String, Value>,
+    simulate: bool,
+) -> Result<WorkflowRun, String> {
+    if simulate {
+        return state
+            .executor
+            .simulate_workflow(&workflow_id, inputs)
+            .map(WorkflowRun::Simulated);
+    }
+    crate::kill_switch::ensure_allowed("Running workflows")?;
+    state
+        .executor
+        .execute_workflow(workflow_id, inputs)
+        .await
+        .map(WorkflowRun::Started)
 }
 
 /// Pause a workflow execution
@@ -129,6 +203,15 @@ pub fn get_execution_logs(
     state.engine.get_execution_logs(&execution_id)
 }
 
+/// Get the files an execution's steps produced
+#[tauri::command]
+pub fn workflow_get_artifacts(
+    execution_id: String,
+    state: State<WorkflowEngineState>,
+) -> Result<Vec<WorkflowArtifact>, String> {
+    state.engine.artifacts().list(&execution_id)
+}
+
 /// Schedule a workflow with cron expression
 #[tauri::command]
 pub fn schedule_workflow(
@@ -156,6 +239,93 @@ pub async fn trigger_workflow_on_event(
         .await
 }
 
+/// Triggers of a workflow, or of every workflow
+#[tauri::command]
+pub fn workflow_trigger_list(
+    workflow_id: Option<String>,
+    state: State<WorkflowEngineState>,
+) -> Result<Vec<RegisteredTrigger>, String> {
+    state.triggers.list(workflow_id.as_deref())
+}
+
+/// Start a workflow from a file, email, webhook, clipboard or hotkey source
+#[tauri::command]
+pub fn workflow_trigger_create(
+    trigger: TriggerInput,
+    state: State<WorkflowEngineState>,
+) -> Result<RegisteredTrigger, String> {
+    state.triggers.create(trigger)
+}
+
+#[tauri::command]
+pub fn workflow_trigger_update(
+    id: String,
+    trigger: TriggerInput,
+    state: State<WorkflowEngineState>,
+) -> Result<RegisteredTrigger, String> {
+    state.triggers.update(&id, trigger)
+}
+
+/// Stop or resume listening to a trigger's source
+#[tauri::command]
+pub fn workflow_trigger_set_enabled(
+    id: String,
+    enabled: bool,
+    state: State<WorkflowEngineState>,
+) -> Result<RegisteredTrigger, String> {
+    state.triggers.set_enabled(&id, enabled)
+}
+
+#[tauri::command]
+pub fn workflow_trigger_delete(
+    id: String,
+    state: State<WorkflowEngineState>,
+) -> Result<bool, String> {
+    state.triggers.delete(&id)
+}
+
+/// Package a workflow with its sub-workflows, agent templates, required
+/// integrations and credential placeholders into one signed file
+#[tauri::command]
+pub fn workflow_export_bundle(
+    workflow_id: String,
+    encoding: Option<BundleEncoding>,
+    state: State<WorkflowEngineState>,
+    templates: State<TemplateManagerState>,
+) -> Result<String, String> {
+    let key = state.bundle_signing_key()?;
+    let manager = templates.manager.lock().map_err(|e| e.to_string())?;
+    export_bundle(
+        &state.engine,
+        &manager,
+        &key,
+        &workflow_id,
+        encoding.unwrap_or_default(),
+    )
+}
+
+/// Validate a bundle file, map its integrations to local accounts and,
+/// unless only checking, import it. Missing prerequisites are reported
+#[tauri::command]
+pub fn workflow_import_bundle(
+    contents: String,
+    user_id: String,
+    options: Option<BundleImportOptions>,
+    state: State<WorkflowEngineState>,
+    templates: State<TemplateManagerState>,
+) -> Result<BundleImportReport, String> {
+    let key = state.bundle_signing_key()?;
+    let manager = templates.manager.lock().map_err(|e| e.to_string())?;
+    import_bundle(
+        &state.engine,
+        &manager,
+        &key,
+        &user_id,
+        &contents,
+        &options.unwrap_or_default(),
+    )
+}
+
 /// Get next scheduled execution time for a cron expression
 #[tauri::command]
 pub fn get_next_execution_time(
@@ -171,9 +341,10 @@ mod tests {
 
     #[test]
     fn test_workflow_engine_state_creation() {
-        let state = WorkflowEngineState::new(":memory:".to_string());
+        let state = WorkflowEngineState::new(Pool::in_memory().unwrap());
         assert!(Arc::strong_count(&state.engine) >= 1);
         assert!(Arc::strong_count(&state.executor) >= 1);
         assert!(Arc::strong_count(&state.scheduler) >= 1);
+        assert!(Arc::strong_count(&state.triggers) >= 1);
     }
 }
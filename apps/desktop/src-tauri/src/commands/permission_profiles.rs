@@ -0,0 +1,125 @@
+use tauri::State;
+
+use crate::audit::{self, Actor, AuditCategory, AuditEntry};
+use crate::db::Pool;
+use crate::permissions::profiles::{self, PermissionProfile, ToolDecisionRecord};
+use crate::security::AuthenticatedUser;
+
+const DEFAULT_DECISION_LOG_LIMIT: usize = 200;
+
+/// Built-in profiles followed by the user's own
+#[tauri::command]
+pub async fn permission_profiles_list(
+    pool: State<'_, Pool>,
+) -> Result<Vec<PermissionProfile>, String> {
+    pool.run(|conn| -> Result<_, String> {
+        profiles::list_profiles(conn).map_err(|e| format!("Failed to list profiles: {}", e))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn permission_profiles_save(
+    pool: State<'_, Pool>,
+    profile: PermissionProfile,
+) -> Result<(), String> {
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::save_profile(conn, &profile).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Returns whether the profile existed. Employees using it fall back to the
+/// default profile.
+#[tauri::command]
+pub async fn permission_profiles_delete(
+    pool: State<'_, Pool>,
+    profile_id: String,
+) -> Result<bool, String> {
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::delete_profile(conn, &profile_id).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn permission_profiles_get_default(pool: State<'_, Pool>) -> Result<String, String> {
+    pool.run(|conn| -> Result<_, String> { Ok(profiles::default_profile_id(conn)) })
+        .await
+}
+
+/// Profile for agent runs that name neither a profile nor an employee with one
+#[tauri::command]
+pub async fn permission_profiles_set_default(
+    user: AuthenticatedUser,
+    pool: State<'_, Pool>,
+    profile_id: String,
+) -> Result<(), String> {
+    let entry = AuditEntry::new(
+        Actor::user(&user),
+        AuditCategory::Permission,
+        "set_default_profile",
+    )
+    .target(profile_id.clone());
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::set_default_profile(conn, &profile_id).map_err(|e| e.to_string())?;
+        audit::append(conn, &entry).map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
+
+/// Profile assigned to a hired employee; `None` when it uses the default
+#[tauri::command]
+pub async fn ai_employees_get_permission_profile(
+    pool: State<'_, Pool>,
+    user_employee_id: String,
+) -> Result<Option<String>, String> {
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::employee_profile_id(conn, &user_employee_id).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Assign a profile to a hired employee, or pass `None` to use the default
+#[tauri::command]
+pub async fn ai_employees_set_permission_profile(
+    user: AuthenticatedUser,
+    pool: State<'_, Pool>,
+    user_employee_id: String,
+    profile_id: Option<String>,
+) -> Result<(), String> {
+    let entry = AuditEntry::new(
+        Actor::user(&user),
+        AuditCategory::Permission,
+        "assign_employee_profile",
+    )
+    .target(user_employee_id.clone())
+    .detail(format!(
+        "profile: {}",
+        profile_id.as_deref().unwrap_or("default")
+    ));
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::assign_employee_profile(conn, &user_employee_id, profile_id.as_deref())
+            .map_err(|e| e.to_string())?;
+        audit::append(conn, &entry).map_err(|e| e.to_string())?;
+        Ok(())
+    })
+    .await
+}
+
+/// Allow, deny and approval decisions on agent tool calls, newest first,
+/// optionally for one run (goal id)
+#[tauri::command]
+pub async fn permission_decisions_log(
+    pool: State<'_, Pool>,
+    run_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ToolDecisionRecord>, String> {
+    let limit = limit.unwrap_or(DEFAULT_DECISION_LOG_LIMIT);
+    pool.run(move |conn| -> Result<_, String> {
+        profiles::decision_log(conn, run_id.as_deref(), limit)
+            .map_err(|e| format!("Failed to read permission decisions: {}", e))
+    })
+    .await
+}
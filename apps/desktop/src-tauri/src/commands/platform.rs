@@ -0,0 +1,9 @@
+use crate::platform::{self, PlatformCapabilities};
+
+/// Report which subsystems and backends are usable on this machine.
+#[tauri::command]
+pub async fn platform_capabilities() -> Result<PlatformCapabilities, String> {
+    tokio::task::spawn_blocking(platform::detect)
+        .await
+        .map_err(|e| format!("Failed to probe platform capabilities: {}", e))
+}
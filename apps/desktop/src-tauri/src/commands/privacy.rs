@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use keyring::Entry;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::{AppDatabase, EmbeddingServiceState, McpState, TelemetryState};
+use crate::db::retention::{self, PurgeReport, RetentionPolicy};
+use crate::db::Pool;
+use crate::router::Provider;
+use crate::security::{SecretManager, Vault};
+
+/// Keyring service shared by provider API keys and the settings master key
+const KEYRING_SERVICE: &str = "AGIWorkforce";
+/// Keyring names outside the LLM providers: media generation keys and the
+/// key that encrypts settings
+const EXTRA_KEYRING_NAMES: [&str; 3] = [
+    "api_key_stability",
+    "api_key_midjourney",
+    "encryption_master_key",
+];
+/// App data subdirectories holding user files
+const USER_DATA_DIRS: [&str; 4] = ["backups", "cache", "captures", "github_repos"];
+/// Databases kept beside the main one
+const SIDE_DATABASES: [&str; 2] = ["projects.db", "imports.db"];
+
+/// What `privacy_purge_all` removed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyPurgeReport {
+    pub database_rows: usize,
+    pub embeddings: usize,
+    pub secrets: usize,
+    pub directories: usize,
+    /// Steps that failed; everything else was still removed
+    pub errors: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn retention_get_policy(pool: State<'_, Pool>) -> Result<RetentionPolicy, String> {
+    pool.run(|conn| -> Result<_, String> { Ok(retention::policy(conn)) })
+        .await
+}
+
+#[tauri::command]
+pub async fn retention_set_policy(
+    pool: State<'_, Pool>,
+    policy: RetentionPolicy,
+) -> Result<(), String> {
+    if [
+        policy.messages_days,
+        policy.telemetry_days,
+        policy.automation_history_days,
+        policy.overlay_events_days,
+    ]
+    .contains(&Some(0))
+    {
+        return Err("Retention must be at least one day".to_string());
+    }
+    pool.run(move |conn| -> Result<_, String> {
+        retention::set_policy(conn, &policy)
+            .map_err(|e| format!("Failed to save retention policy: {}", e))
+    })
+    .await
+}
+
+/// Apply the retention policy now instead of waiting for the daily purge
+#[tauri::command]
+pub async fn retention_purge_now(pool: State<'_, Pool>) -> Result<PurgeReport, String> {
+    pool.run(|conn| -> Result<_, String> {
+        let policy = retention::policy(conn);
+        retention::purge_expired(conn, &policy, chrono::Utc::now())
+            .map_err(|e| format!("Failed to purge expired data: {:#}", e))
+    })
+    .await
+}
+
+/// Delete all user data: database rows, embeddings, cached and captured
+/// files, and secrets in the OS keyring.
+///
+/// Keeps going past individual failures so as much as possible is removed,
+/// and lists them in the report.
+#[tauri::command]
+pub async fn privacy_purge_all(
+    app: AppHandle,
+    db: State<'_, AppDatabase>,
+    pool: State<'_, Pool>,
+) -> Result<PrivacyPurgeReport, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let mut report = PrivacyPurgeReport::default();
+
+    // Secrets first: their database copies are about to go with the rows
+    let secret_manager = SecretManager::new(db.conn.clone());
+    match tokio::task::spawn_blocking(move || secret_manager.delete_all_secrets()).await {
+        Ok(Ok(removed)) => report.secrets += removed,
+        Ok(Err(e)) => report.errors.push(format!("secrets: {}", e)),
+        Err(e) => report.errors.push(format!("secrets: {}", e)),
+    }
+    if let Some(vault) = app.try_state::<Arc<Vault>>() {
+        let vault = vault.inner().clone();
+        match tokio::task::spawn_blocking(move || vault.delete_all("privacy")).await {
+            Ok(Ok(removed)) => report.secrets += removed,
+            Ok(Err(e)) => report.errors.push(format!("vault: {}", e)),
+            Err(e) => report.errors.push(format!("vault: {}", e)),
+        }
+    }
+    // Entries from before the vault, in case they were never migrated
+    let mut keyring_entries: Vec<(String, String)> = Provider::ALL
+        .iter()
+        .map(|provider| format!("api_key_{}", provider.as_string()))
+        .chain(EXTRA_KEYRING_NAMES.iter().map(|name| name.to_string()))
+        .map(|name| (KEYRING_SERVICE.to_string(), name))
+        .collect();
+    if let Some(mcp) = app.try_state::<McpState>() {
+        let config = mcp.config.lock();
+        for (server, server_config) in &config.mcp_servers {
+            let service = format!("agiworkforce-mcp-{}", server);
+            for key in server_config.env.keys() {
+                keyring_entries.push((service.clone(), key.clone()));
+            }
+        }
+    }
+    report.secrets += keyring_entries
+        .iter()
+        .filter(|(service, name)| {
+            Entry::new(service, name)
+                .and_then(|entry| entry.delete_password())
+                .is_ok()
+        })
+        .count();
+
+    match app.try_state::<EmbeddingServiceState>() {
+        Some(embeddings) => {
+            let service = embeddings.0.lock().await;
+            let similarity = service.similarity();
+            let cleared = similarity.lock().await.clear();
+            match cleared {
+                Ok(removed) => report.embeddings = removed,
+                Err(e) => report.errors.push(format!("embeddings: {:#}", e)),
+            }
+            if let Err(e) = service.cache().lock().await.clear() {
+                report.errors.push(format!("embedding cache: {:#}", e));
+            }
+        }
+        // Not running (safe mode or failed to start), so the files are free
+        None => {
+            let store = app_data_dir.join(".agi").join("embeddings.db");
+            for path in [store.with_extension("hnsw"), store] {
+                if let Err(e) = remove_database_file(&path) {
+                    report.errors.push(format!("{}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+
+    if let Some(telemetry) = app.try_state::<TelemetryState>() {
+        if let Err(e) = telemetry.collector.read().await.delete_all_data().await {
+            report.errors.push(format!("telemetry: {:#}", e));
+        }
+    }
+
+    let mut directories: Vec<PathBuf> = USER_DATA_DIRS
+        .iter()
+        .map(|dir| app_data_dir.join(dir))
+        .collect();
+    if let Ok(cache_dir) = app.path().app_cache_dir() {
+        directories.push(cache_dir);
+    }
+    for dir in directories.iter().filter(|dir| dir.exists()) {
+        match std::fs::remove_dir_all(dir) {
+            Ok(()) => report.directories += 1,
+            Err(e) => report.errors.push(format!("{}: {}", dir.display(), e)),
+        }
+    }
+
+    for name in SIDE_DATABASES {
+        let path = app_data_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        let wiped = tokio::task::spawn_blocking(move || -> anyhow::Result<usize> {
+            let mut conn = Connection::open(&path)?;
+            Ok(retention::wipe_database(&mut conn)?.rows)
+        })
+        .await;
+        match wiped {
+            Ok(Ok(rows)) => report.database_rows += rows,
+            Ok(Err(e)) => report.errors.push(format!("{}: {:#}", name, e)),
+            Err(e) => report.errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    // Last, so nothing above writes new rows behind the wipe
+    match pool
+        .run(|conn| -> Result<_, String> {
+            retention::wipe_database(conn).map_err(|e| format!("database: {:#}", e))
+        })
+        .await
+    {
+        Ok(wiped) => report.database_rows += wiped.rows,
+        Err(e) => report.errors.push(e),
+    }
+
+    tracing::info!(
+        "Privacy purge removed {} rows, {} embeddings, {} secrets and {} directories ({} errors)",
+        report.database_rows,
+        report.embeddings,
+        report.secrets,
+        report.directories,
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+/// Remove a SQLite file together with its WAL and shared-memory files
+fn remove_database_file(path: &Path) -> std::io::Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        match std::fs::remove_file(&file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -1,25 +1,94 @@
-use crate::error::Result;
-use crate::productivity::{ProductivityManager, Provider, Task};
+use crate::commands::AppDatabase;
+use crate::error::{Error, Result};
+use crate::productivity::aggregator::{
+    self, ProviderSyncReport, TaskFilter, UnifiedBoard, UnifiedTask, UnifiedTaskSearch,
+};
+use crate::productivity::oauth::StoredCredentials;
+use crate::productivity::{
+    accounts, ProductivityAccount, ProductivityManager, ProductivityOAuth, ProductivityOAuthConfig,
+    Provider, Task,
+};
+use crate::security::SecretManager;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tauri::State;
-use tokio::sync::Mutex;
 
 /// State wrapper for ProductivityManager
 pub struct ProductivityState {
-    manager: Arc<Mutex<ProductivityManager>>,
+    manager: Arc<ProductivityManager>,
+    oauth: Arc<ProductivityOAuth>,
 }
 
 impl ProductivityState {
     pub fn new() -> Self {
         Self {
-            manager: Arc::new(Mutex::new(ProductivityManager::new())),
+            manager: Arc::new(ProductivityManager::new()),
+            oauth: Arc::new(ProductivityOAuth::new()),
         }
     }
 
-    pub fn manager(&self) -> Arc<Mutex<ProductivityManager>> {
+    /// Create state whose credentials persist in the secret vault
+    ///
+    /// Accounts connected in a previous session are reconnected immediately,
+    /// after moving credentials saved by single-account builds into accounts.
+    pub fn with_secrets(secrets: Arc<SecretManager>, conn: &Connection) -> Self {
+        let oauth = ProductivityOAuth::with_secrets(secrets);
+        let manager = ProductivityManager::new();
+
+        let (migrated, mut failures) =
+            oauth.migrate_legacy(|account| accounts::save_account(conn, account));
+        if !migrated.is_empty() {
+            tracing::info!(
+                "Migrated {} productivity connection(s) to accounts",
+                migrated.len()
+            );
+        }
+
+        match accounts::load_accounts(conn) {
+            Ok(persisted) => {
+                let (restored, restore_failures) = oauth.restore(&manager, persisted);
+                failures.extend(restore_failures);
+                if restored > 0 {
+                    tracing::info!("Restored {} productivity account(s)", restored);
+                }
+            }
+            Err(e) => failures.push(format!("Failed to load productivity accounts: {}", e)),
+        }
+        for failure in failures {
+            tracing::warn!("{}", failure);
+        }
+
+        Self {
+            manager: Arc::new(manager),
+            oauth: Arc::new(oauth),
+        }
+    }
+
+    pub fn manager(&self) -> Arc<ProductivityManager> {
         Arc::clone(&self.manager)
     }
+
+    /// Refresh the account's token if needed and confirm the provider still
+    /// accepts it. Returns when the credentials expire, if they do
+    pub async fn check_account(&self, account_id: &str) -> Result<Option<i64>> {
+        self.oauth
+            .refresh_if_needed(&self.manager, account_id)
+            .await?;
+        self.manager.verify_account(account_id).await?;
+        Ok(self.oauth.expires_at(account_id))
+    }
+
+    /// Refresh the account's access token if it is about to expire
+    async fn refresh(&self, account_id: &str) {
+        if let Err(e) = self
+            .oauth
+            .refresh_if_needed(&self.manager, account_id)
+            .await
+        {
+            tracing::warn!("Failed to refresh access token for {}: {}", account_id, e);
+        }
+    }
 }
 
 impl Default for ProductivityState {
@@ -33,25 +102,50 @@ impl Default for ProductivityState {
 pub struct ConnectRequest {
     pub provider: Provider,
     pub credentials: serde_json::Value,
+    pub label: Option<String>,
 }
 
 /// Response from connecting to a provider
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectResponse {
     pub account_id: String,
+    pub account: ProductivityAccount,
     pub success: bool,
 }
 
-/// Request to list tasks from productivity providers
+/// Response payload containing the consent URL and state token
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ListProductivityTasksRequest {
+pub struct ProductivityAuthorizationResponse {
+    pub auth_url: String,
+    pub state: String,
+}
+
+/// Request to complete a provider consent flow
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductivityCompleteOAuthRequest {
+    pub state: String,
+    /// Authorization code, or the token from the return URL fragment for Trello
+    pub code: String,
+}
+
+/// Response from completing a provider consent flow
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductivityOAuthResponse {
     pub provider: Provider,
+    pub account_id: String,
+    pub account: ProductivityAccount,
+}
+
+/// Request to list tasks from a productivity account
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListProductivityTasksRequest {
+    pub account_id: String,
 }
 
 /// Request to create a task
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTaskRequest {
-    pub provider: Provider,
+    pub account_id: String,
     pub task: Task,
 }
 
@@ -62,7 +156,10 @@ pub struct CreateTaskResponse {
     pub success: bool,
 }
 
-/// Connect to a productivity provider
+/// Connect an account of a productivity provider
+///
+/// Each call adds a new account, so several workspaces of the same provider
+/// can be connected side by side.
 ///
 /// # Examples
 ///
@@ -72,7 +169,8 @@ pub struct CreateTaskResponse {
 ///   provider: 'notion',
 ///   credentials: {
 ///     token: 'secret_xxxxxxxxxxxx'
-///   }
+///   },
+///   label: 'Acme workspace'
 /// });
 /// ```
 ///
@@ -99,53 +197,171 @@ pub struct CreateTaskResponse {
 #[tauri::command]
 pub async fn productivity_connect(
     state: State<'_, ProductivityState>,
+    db: State<'_, AppDatabase>,
     provider: Provider,
     credentials: serde_json::Value,
+    label: Option<String>,
 ) -> Result<ConnectResponse> {
     tracing::info!("Connecting to {:?} provider", provider);
 
-    let mut manager = state.manager.lock().await;
-    let account_id = manager.connect(provider, credentials).await?;
+    let mut stored = StoredCredentials::from_token(&provider, &credentials)?;
+    let account = state.manager.connect(provider, credentials, label).await?;
+    stored.remote_id = account.remote_id.clone();
+    state.oauth.save(&account.account_id, stored);
+    save_account(&db, &account)?;
 
-    tracing::info!("Successfully connected, account_id: {}", account_id);
+    tracing::info!("Successfully connected, account_id: {}", account.account_id);
 
     Ok(ConnectResponse {
-        account_id,
+        account_id: account.account_id.clone(),
+        account,
         success: true,
     })
 }
 
-/// List all tasks from a productivity provider
+/// Start a browser consent flow for a productivity provider
+///
+/// # Examples
+///
+/// ```javascript
+/// const { auth_url, state } = await invoke('productivity_start_oauth', {
+///   config: {
+///     provider: 'asana',
+///     client_id: 'your_client_id',
+///     client_secret: 'your_client_secret',
+///     redirect_uri: 'http://localhost:5173/oauth/callback',
+///     label: 'Marketing'
+///   }
+/// });
+/// ```
+#[tauri::command]
+pub async fn productivity_start_oauth(
+    state: State<'_, ProductivityState>,
+    config: ProductivityOAuthConfig,
+) -> Result<ProductivityAuthorizationResponse> {
+    tracing::info!("Starting OAuth for {:?} provider", config.provider);
+
+    let (auth_url, oauth_state) = state.oauth.start(config)?;
+
+    Ok(ProductivityAuthorizationResponse {
+        auth_url,
+        state: oauth_state,
+    })
+}
+
+/// Complete a consent flow and connect a new account
+///
+/// # Examples
+///
+/// ```javascript
+/// const result = await invoke('productivity_complete_oauth', {
+///   request: { state, code }
+/// });
+/// ```
+#[tauri::command]
+pub async fn productivity_complete_oauth(
+    state: State<'_, ProductivityState>,
+    db: State<'_, AppDatabase>,
+    request: ProductivityCompleteOAuthRequest,
+) -> Result<ProductivityOAuthResponse> {
+    tracing::info!("Completing productivity OAuth for state {}", request.state);
+
+    let account = state
+        .oauth
+        .complete(&state.manager, &request.state, &request.code)
+        .await?;
+    save_account(&db, &account)?;
+
+    tracing::info!(
+        "Connected {:?}, account_id: {}",
+        account.provider,
+        account.account_id
+    );
+
+    Ok(ProductivityOAuthResponse {
+        provider: account.provider.clone(),
+        account_id: account.account_id.clone(),
+        account,
+    })
+}
+
+/// List connected productivity accounts
+///
+/// # Examples
+///
+/// ```javascript
+/// const accounts = await invoke('productivity_list_accounts');
+/// ```
+#[tauri::command]
+pub async fn productivity_list_accounts(
+    state: State<'_, ProductivityState>,
+) -> Result<Vec<ProductivityAccount>> {
+    let mut accounts = state.manager.list_accounts();
+    accounts.sort_by(|a, b| {
+        accounts::provider_key(&a.provider)
+            .cmp(accounts::provider_key(&b.provider))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    Ok(accounts)
+}
+
+/// Disconnect an account and forget its stored credentials
+#[tauri::command]
+pub async fn productivity_disconnect(
+    state: State<'_, ProductivityState>,
+    db: State<'_, AppDatabase>,
+    account_id: String,
+) -> Result<()> {
+    tracing::info!("Disconnecting productivity account {}", account_id);
+
+    state.manager.disconnect(&account_id)?;
+    state.oauth.forget(&account_id);
+    let conn = lock_db(&db)?;
+    accounts::delete_account(&conn, &account_id)
+}
+
+fn lock_db(db: &AppDatabase) -> Result<std::sync::MutexGuard<'_, Connection>> {
+    db.conn
+        .lock()
+        .map_err(|e| Error::Database(format!("Failed to lock database: {}", e)))
+}
+
+fn save_account(db: &AppDatabase, account: &ProductivityAccount) -> Result<()> {
+    let conn = lock_db(db)?;
+    accounts::save_account(&conn, account)
+}
+
+/// List all tasks from a productivity account
 ///
 /// # Examples
 ///
 /// ```javascript
 /// const tasks = await invoke('productivity_list_tasks', {
-///   provider: 'notion'
+///   accountId: 'account_id_here'
 /// });
 /// ```
 #[tauri::command]
 pub async fn productivity_list_tasks(
     state: State<'_, ProductivityState>,
-    provider: Provider,
+    account_id: String,
 ) -> Result<Vec<Task>> {
-    tracing::info!("Listing tasks from {:?} provider", provider);
+    tracing::info!("Listing tasks from account {}", account_id);
 
-    let manager = state.manager.lock().await;
-    let tasks = manager.list_tasks(provider).await?;
+    state.refresh(&account_id).await;
+    let tasks = state.manager.list_tasks(&account_id).await?;
 
     tracing::info!("Retrieved {} tasks", tasks.len());
 
     Ok(tasks)
 }
 
-/// Create a new task in a productivity provider
+/// Create a new task in a productivity account
 ///
 /// # Examples
 ///
 /// ```javascript
 /// const result = await invoke('productivity_create_task', {
-///   provider: 'trello',
+///   accountId: 'account_id_here',
 ///   task: {
 ///     title: 'New Task',
 ///     description: 'Task description',
@@ -157,13 +373,13 @@ pub async fn productivity_list_tasks(
 #[tauri::command]
 pub async fn productivity_create_task(
     state: State<'_, ProductivityState>,
-    provider: Provider,
+    account_id: String,
     task: Task,
 ) -> Result<CreateTaskResponse> {
-    tracing::info!("Creating task in {:?} provider: {}", provider, task.title);
+    tracing::info!("Creating task in account {}: {}", account_id, task.title);
 
-    let manager = state.manager.lock().await;
-    let task_id = manager.create_task(provider, task).await?;
+    state.refresh(&account_id).await;
+    let task_id = state.manager.create_task(&account_id, task).await?;
 
     tracing::info!("Successfully created task, id: {}", task_id);
 
@@ -178,30 +394,26 @@ pub async fn productivity_create_task(
 /// # Examples
 ///
 /// ```javascript
-/// const pages = await invoke('productivity_notion_list_pages');
+/// const pages = await invoke('productivity_notion_list_pages', {
+///   accountId: 'account_id_here'
+/// });
 /// ```
 #[tauri::command]
 pub async fn productivity_notion_list_pages(
     state: State<'_, ProductivityState>,
+    account_id: String,
 ) -> Result<Vec<serde_json::Value>> {
     tracing::info!("Listing Notion pages");
 
-    let manager = state.manager.lock().await;
-
-    // Access the Notion client directly
-    if let Some(notion_client) = manager.notion_client() {
-        let client = notion_client.lock().await;
-        let pages = client.list_pages().await?;
-        let pages_json: Vec<serde_json::Value> = pages
-            .iter()
-            .map(|p| serde_json::to_value(p).unwrap_or(serde_json::Value::Null))
-            .collect();
-        Ok(pages_json)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Notion client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.notion_client(&account_id)?;
+    let client = client.lock().await;
+    let pages = client.list_pages().await?;
+    let pages_json: Vec<serde_json::Value> = pages
+        .iter()
+        .map(|p| serde_json::to_value(p).unwrap_or(serde_json::Value::Null))
+        .collect();
+    Ok(pages_json)
 }
 
 /// Query a Notion database
@@ -210,6 +422,7 @@ pub async fn productivity_notion_list_pages(
 ///
 /// ```javascript
 /// const results = await invoke('productivity_notion_query_database', {
+///   accountId: 'account_id_here',
 ///   databaseId: 'database_id_here',
 ///   filter: {
 ///     property: 'Status',
@@ -220,23 +433,18 @@ pub async fn productivity_notion_list_pages(
 #[tauri::command]
 pub async fn productivity_notion_query_database(
     state: State<'_, ProductivityState>,
+    account_id: String,
     database_id: String,
     filter: Option<serde_json::Value>,
     sorts: Option<Vec<serde_json::Value>>,
 ) -> Result<Vec<serde_json::Value>> {
     tracing::info!("Querying Notion database: {}", database_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(notion_client) = manager.notion_client() {
-        let client = notion_client.lock().await;
-        let results = client.query_database(&database_id, filter, sorts).await?;
-        Ok(results)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Notion client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.notion_client(&account_id)?;
+    let client = client.lock().await;
+    let results = client.query_database(&database_id, filter, sorts).await?;
+    Ok(results)
 }
 
 /// Create a row in a Notion database
@@ -245,6 +453,7 @@ pub async fn productivity_notion_query_database(
 ///
 /// ```javascript
 /// const pageId = await invoke('productivity_notion_create_database_row', {
+///   accountId: 'account_id_here',
 ///   databaseId: 'database_id_here',
 ///   properties: {
 ///     Name: {
@@ -259,22 +468,17 @@ pub async fn productivity_notion_query_database(
 #[tauri::command]
 pub async fn productivity_notion_create_database_row(
     state: State<'_, ProductivityState>,
+    account_id: String,
     database_id: String,
     properties: serde_json::Value,
 ) -> Result<String> {
     tracing::info!("Creating row in Notion database: {}", database_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(notion_client) = manager.notion_client() {
-        let client = notion_client.lock().await;
-        let page_id = client.create_database_row(&database_id, properties).await?;
-        Ok(page_id)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Notion client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.notion_client(&account_id)?;
+    let client = client.lock().await;
+    let page_id = client.create_database_row(&database_id, properties).await?;
+    Ok(page_id)
 }
 
 /// List Trello boards
@@ -282,29 +486,26 @@ pub async fn productivity_notion_create_database_row(
 /// # Examples
 ///
 /// ```javascript
-/// const boards = await invoke('productivity_trello_list_boards');
+/// const boards = await invoke('productivity_trello_list_boards', {
+///   accountId: 'account_id_here'
+/// });
 /// ```
 #[tauri::command]
 pub async fn productivity_trello_list_boards(
     state: State<'_, ProductivityState>,
+    account_id: String,
 ) -> Result<Vec<serde_json::Value>> {
     tracing::info!("Listing Trello boards");
 
-    let manager = state.manager.lock().await;
-
-    if let Some(trello_client) = manager.trello_client() {
-        let client = trello_client.lock().await;
-        let boards = client.list_boards().await?;
-        let boards_json: Vec<serde_json::Value> = boards
-            .iter()
-            .map(|b| serde_json::to_value(b).unwrap_or(serde_json::Value::Null))
-            .collect();
-        Ok(boards_json)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Trello client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.trello_client(&account_id)?;
+    let client = client.lock().await;
+    let boards = client.list_boards().await?;
+    let boards_json: Vec<serde_json::Value> = boards
+        .iter()
+        .map(|b| serde_json::to_value(b).unwrap_or(serde_json::Value::Null))
+        .collect();
+    Ok(boards_json)
 }
 
 /// List cards in a Trello board
@@ -313,34 +514,30 @@ pub async fn productivity_trello_list_boards(
 ///
 /// ```javascript
 /// const cards = await invoke('productivity_trello_list_cards', {
+///   accountId: 'account_id_here',
 ///   boardId: 'board_id_here'
 /// });
 /// ```
 #[tauri::command]
 pub async fn productivity_trello_list_cards(
     state: State<'_, ProductivityState>,
+    account_id: String,
     board_id: String,
 ) -> Result<Vec<Task>> {
     tracing::info!("Listing cards in Trello board: {}", board_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(trello_client) = manager.trello_client() {
-        let client = trello_client.lock().await;
-        let cards = client.list_board_cards(&board_id).await?;
+    state.refresh(&account_id).await;
+    let client = state.manager.trello_client(&account_id)?;
+    let client = client.lock().await;
+    let cards = client.list_board_cards(&board_id).await?;
 
-        let mut tasks = Vec::new();
-        for card in cards {
-            let task = client.card_to_task(&card).await;
-            tasks.push(task);
-        }
-
-        Ok(tasks)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Trello client not connected".to_string(),
-        ))
+    let mut tasks = Vec::new();
+    for card in cards {
+        let task = client.card_to_task(&card).await;
+        tasks.push(task);
     }
+
+    Ok(tasks)
 }
 
 /// Create a card in Trello
@@ -349,6 +546,7 @@ pub async fn productivity_trello_list_cards(
 ///
 /// ```javascript
 /// const cardId = await invoke('productivity_trello_create_card', {
+///   accountId: 'account_id_here',
 ///   listId: 'list_id_here',
 ///   name: 'New Card',
 ///   description: 'Card description'
@@ -357,25 +555,20 @@ pub async fn productivity_trello_list_cards(
 #[tauri::command]
 pub async fn productivity_trello_create_card(
     state: State<'_, ProductivityState>,
+    account_id: String,
     list_id: String,
     name: String,
     description: Option<String>,
 ) -> Result<String> {
     tracing::info!("Creating Trello card: {}", name);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(trello_client) = manager.trello_client() {
-        let client = trello_client.lock().await;
-        let card_id = client
-            .create_card(&list_id, &name, description.as_deref(), None)
-            .await?;
-        Ok(card_id)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Trello client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.trello_client(&account_id)?;
+    let client = client.lock().await;
+    let card_id = client
+        .create_card(&list_id, &name, description.as_deref(), None)
+        .await?;
+    Ok(card_id)
 }
 
 /// Move a Trello card to a different list
@@ -384,6 +577,7 @@ pub async fn productivity_trello_create_card(
 ///
 /// ```javascript
 /// await invoke('productivity_trello_move_card', {
+///   accountId: 'account_id_here',
 ///   cardId: 'card_id_here',
 ///   listId: 'target_list_id_here'
 /// });
@@ -391,22 +585,17 @@ pub async fn productivity_trello_create_card(
 #[tauri::command]
 pub async fn productivity_trello_move_card(
     state: State<'_, ProductivityState>,
+    account_id: String,
     card_id: String,
     list_id: String,
 ) -> Result<()> {
     tracing::info!("Moving Trello card {} to list {}", card_id, list_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(trello_client) = manager.trello_client() {
-        let client = trello_client.lock().await;
-        client.move_card(&card_id, &list_id).await?;
-        Ok(())
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Trello client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.trello_client(&account_id)?;
+    let client = client.lock().await;
+    client.move_card(&card_id, &list_id).await?;
+    Ok(())
 }
 
 /// Add a comment to a Trello card
@@ -415,6 +604,7 @@ pub async fn productivity_trello_move_card(
 ///
 /// ```javascript
 /// await invoke('productivity_trello_add_comment', {
+///   accountId: 'account_id_here',
 ///   cardId: 'card_id_here',
 ///   text: 'This is a comment'
 /// });
@@ -422,22 +612,17 @@ pub async fn productivity_trello_move_card(
 #[tauri::command]
 pub async fn productivity_trello_add_comment(
     state: State<'_, ProductivityState>,
+    account_id: String,
     card_id: String,
     text: String,
 ) -> Result<String> {
     tracing::info!("Adding comment to Trello card: {}", card_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(trello_client) = manager.trello_client() {
-        let client = trello_client.lock().await;
-        let comment_id = client.add_comment(&card_id, &text).await?;
-        Ok(comment_id)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Trello client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.trello_client(&account_id)?;
+    let client = client.lock().await;
+    let comment_id = client.add_comment(&card_id, &text).await?;
+    Ok(comment_id)
 }
 
 /// List Asana projects
@@ -446,31 +631,27 @@ pub async fn productivity_trello_add_comment(
 ///
 /// ```javascript
 /// const projects = await invoke('productivity_asana_list_projects', {
+///   accountId: 'account_id_here',
 ///   workspaceId: 'workspace_id_here'
 /// });
 /// ```
 #[tauri::command]
 pub async fn productivity_asana_list_projects(
     state: State<'_, ProductivityState>,
+    account_id: String,
     workspace_id: String,
 ) -> Result<Vec<serde_json::Value>> {
     tracing::info!("Listing Asana projects in workspace: {}", workspace_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(asana_client) = manager.asana_client() {
-        let client = asana_client.lock().await;
-        let projects = client.list_projects(&workspace_id).await?;
-        let projects_json: Vec<serde_json::Value> = projects
-            .iter()
-            .map(|p| serde_json::to_value(p).unwrap_or(serde_json::Value::Null))
-            .collect();
-        Ok(projects_json)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Asana client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.asana_client(&account_id)?;
+    let client = client.lock().await;
+    let projects = client.list_projects(&workspace_id).await?;
+    let projects_json: Vec<serde_json::Value> = projects
+        .iter()
+        .map(|p| serde_json::to_value(p).unwrap_or(serde_json::Value::Null))
+        .collect();
+    Ok(projects_json)
 }
 
 /// List tasks in an Asana project
@@ -479,33 +660,29 @@ pub async fn productivity_asana_list_projects(
 ///
 /// ```javascript
 /// const tasks = await invoke('productivity_asana_list_project_tasks', {
+///   accountId: 'account_id_here',
 ///   projectId: 'project_id_here'
 /// });
 /// ```
 #[tauri::command]
 pub async fn productivity_asana_list_project_tasks(
     state: State<'_, ProductivityState>,
+    account_id: String,
     project_id: String,
 ) -> Result<Vec<Task>> {
     tracing::info!("Listing tasks in Asana project: {}", project_id);
 
-    let manager = state.manager.lock().await;
+    state.refresh(&account_id).await;
+    let client = state.manager.asana_client(&account_id)?;
+    let client = client.lock().await;
+    let asana_tasks = client.list_project_tasks(&project_id).await?;
 
-    if let Some(asana_client) = manager.asana_client() {
-        let client = asana_client.lock().await;
-        let asana_tasks = client.list_project_tasks(&project_id).await?;
+    let tasks: Vec<Task> = asana_tasks
+        .iter()
+        .map(|t| client.asana_task_to_task(t))
+        .collect();
 
-        let tasks: Vec<Task> = asana_tasks
-            .iter()
-            .map(|t| client.asana_task_to_task(t))
-            .collect();
-
-        Ok(tasks)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Asana client not connected".to_string(),
-        ))
-    }
+    Ok(tasks)
 }
 
 /// Create a task in Asana
@@ -514,6 +691,7 @@ pub async fn productivity_asana_list_project_tasks(
 ///
 /// ```javascript
 /// const taskId = await invoke('productivity_asana_create_task', {
+///   accountId: 'account_id_here',
 ///   name: 'New Task',
 ///   notes: 'Task description',
 ///   workspaceId: 'workspace_id_here',
@@ -523,6 +701,7 @@ pub async fn productivity_asana_list_project_tasks(
 #[tauri::command]
 pub async fn productivity_asana_create_task(
     state: State<'_, ProductivityState>,
+    account_id: String,
     name: String,
     notes: Option<String>,
     workspace_id: Option<String>,
@@ -531,26 +710,20 @@ pub async fn productivity_asana_create_task(
 ) -> Result<String> {
     tracing::info!("Creating Asana task: {}", name);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(asana_client) = manager.asana_client() {
-        let client = asana_client.lock().await;
-        let task_id = client
-            .create_task_raw(
-                &name,
-                notes.as_deref(),
-                workspace_id.as_deref(),
-                project_id.as_deref(),
-                assignee_id.as_deref(),
-                None,
-            )
-            .await?;
-        Ok(task_id)
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Asana client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.asana_client(&account_id)?;
+    let client = client.lock().await;
+    let task_id = client
+        .create_task_raw(
+            &name,
+            notes.as_deref(),
+            workspace_id.as_deref(),
+            project_id.as_deref(),
+            assignee_id.as_deref(),
+            None,
+        )
+        .await?;
+    Ok(task_id)
 }
 
 /// Assign an Asana task to a user
@@ -559,6 +732,7 @@ pub async fn productivity_asana_create_task(
 ///
 /// ```javascript
 /// await invoke('productivity_asana_assign_task', {
+///   accountId: 'account_id_here',
 ///   taskId: 'task_id_here',
 ///   assigneeId: 'user_id_here'
 /// });
@@ -566,22 +740,17 @@ pub async fn productivity_asana_create_task(
 #[tauri::command]
 pub async fn productivity_asana_assign_task(
     state: State<'_, ProductivityState>,
+    account_id: String,
     task_id: String,
     assignee_id: String,
 ) -> Result<()> {
     tracing::info!("Assigning Asana task {} to {}", task_id, assignee_id);
 
-    let manager = state.manager.lock().await;
-
-    if let Some(asana_client) = manager.asana_client() {
-        let client = asana_client.lock().await;
-        client.assign_task(&task_id, &assignee_id).await?;
-        Ok(())
-    } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Asana client not connected".to_string(),
-        ))
-    }
+    state.refresh(&account_id).await;
+    let client = state.manager.asana_client(&account_id)?;
+    let client = client.lock().await;
+    client.assign_task(&task_id, &assignee_id).await?;
+    Ok(())
 }
 
 /// Mark an Asana task as complete
@@ -590,6 +759,7 @@ pub async fn productivity_asana_assign_task(
 ///
 /// ```javascript
 /// await invoke('productivity_asana_mark_complete', {
+///   accountId: 'account_id_here',
 ///   taskId: 'task_id_here',
 ///   completed: true
 /// });
@@ -597,20 +767,100 @@ pub async fn productivity_asana_assign_task(
 #[tauri::command]
 pub async fn productivity_asana_mark_complete(
     state: State<'_, ProductivityState>,
+    account_id: String,
     task_id: String,
     completed: bool,
 ) -> Result<()> {
     tracing::info!("Marking Asana task {} as complete: {}", task_id, completed);
 
-    let manager = state.manager.lock().await;
+    state.refresh(&account_id).await;
+    let client = state.manager.asana_client(&account_id)?;
+    let client = client.lock().await;
+    client.mark_task_complete(&task_id, completed).await?;
+    Ok(())
+}
 
-    if let Some(asana_client) = manager.asana_client() {
-        let client = asana_client.lock().await;
-        client.mark_task_complete(&task_id, completed).await?;
-        Ok(())
+/// Sync the task cache if needed, then run the filter against it
+async fn search_unified(
+    state: &ProductivityState,
+    db: &AppDatabase,
+    filter: &TaskFilter,
+    refresh: Option<bool>,
+) -> Result<(Vec<UnifiedTask>, Option<i64>, Vec<ProviderSyncReport>)> {
+    let last_synced_at = {
+        let conn = lock_db(db)?;
+        aggregator::last_synced_at(&conn)?
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let sync = if aggregator::needs_refresh(refresh, last_synced_at, now) {
+        for account in state.manager.list_accounts() {
+            state.refresh(&account.account_id).await;
+        }
+        aggregator::sync_all(&state.manager, &db.conn).await?
     } else {
-        Err(crate::error::AGIError::ConfigurationError(
-            "Asana client not connected".to_string(),
-        ))
-    }
+        Vec::new()
+    };
+
+    let conn = lock_db(db)?;
+    let tasks = aggregator::search_cached(&conn, filter)?;
+    let last_synced_at = aggregator::last_synced_at(&conn)?;
+    Ok((tasks, last_synced_at, sync))
+}
+
+/// Search tasks across all connected accounts
+///
+/// Results come from a local cache that is refreshed when older than five minutes,
+/// or on demand with `refresh: true`.
+///
+/// # Examples
+///
+/// ```javascript
+/// const result = await invoke('productivity_search_all', {
+///   filter: { query: 'roadmap', statuses: ['todo', 'in_progress'], assignee: 'dana' },
+///   refresh: false
+/// });
+/// ```
+#[tauri::command]
+pub async fn productivity_search_all(
+    state: State<'_, ProductivityState>,
+    db: State<'_, AppDatabase>,
+    filter: Option<TaskFilter>,
+    refresh: Option<bool>,
+) -> Result<UnifiedTaskSearch> {
+    let filter = filter.unwrap_or_default();
+    let (tasks, last_synced_at, sync) = search_unified(&state, &db, &filter, refresh).await?;
+
+    Ok(UnifiedTaskSearch {
+        tasks,
+        last_synced_at,
+        sync,
+    })
+}
+
+/// Get tasks from all connected accounts grouped into status columns
+///
+/// # Examples
+///
+/// ```javascript
+/// const board = await invoke('productivity_get_unified_board', {
+///   filter: { providers: ['trello', 'asana'] }
+/// });
+/// ```
+#[tauri::command]
+pub async fn productivity_get_unified_board(
+    state: State<'_, ProductivityState>,
+    db: State<'_, AppDatabase>,
+    filter: Option<TaskFilter>,
+    refresh: Option<bool>,
+) -> Result<UnifiedBoard> {
+    let filter = filter.unwrap_or_default();
+    let (tasks, last_synced_at, sync) = search_unified(&state, &db, &filter, refresh).await?;
+
+    Ok(UnifiedBoard {
+        total: tasks.len(),
+        columns: aggregator::build_board(tasks),
+        last_synced_at,
+        sync,
+    })
 }
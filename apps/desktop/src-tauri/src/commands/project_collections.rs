@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+use tauri::{command, AppHandle, Manager};
+
+use crate::commands::EmbeddingServiceState;
+use crate::error::{Error, Result};
+use crate::projects::{
+    format_context, CollectionMatch, CollectionSource, CollectionSourceKind, ProjectCollection,
+    ProjectCollections, SourceDocument,
+};
+use crate::web::{fetch_page, FetchOptions, WebPage};
+
+/// Chunks sent to the embedding model per request
+const EMBED_BATCH: usize = 64;
+const DEFAULT_TOP_K: usize = 5;
+
+fn collections_db(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Generic(format!("Failed to get app data dir: {}", e)))?
+        .join("projects.db"))
+}
+
+/// Run `f` against the collections store on the blocking pool
+async fn with_collections<T, F>(app: &AppHandle, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&ProjectCollections) -> anyhow::Result<T> + Send + 'static,
+{
+    let db_path = collections_db(app)?;
+    tokio::task::spawn_blocking(move || {
+        let collections = ProjectCollections::new(db_path)
+            .map_err(|e| Error::Generic(format!("Failed to open collections: {:#}", e)))?;
+        f(&collections).map_err(|e| Error::Generic(format!("{:#}", e)))
+    })
+    .await
+    .map_err(|e| Error::Generic(format!("Collection task failed: {}", e)))?
+}
+
+/// Embed `texts` with the active model, returning its id alongside the vectors
+async fn embed(app: &AppHandle, texts: &[&str]) -> Result<(String, Vec<Vec<f32>>)> {
+    let state = app
+        .try_state::<EmbeddingServiceState>()
+        .ok_or_else(|| Error::Generic("Embedding service is not ready yet".to_string()))?;
+    let service = state.0.lock().await;
+    let generator = service.generator();
+    let generator = generator.lock().await;
+
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(EMBED_BATCH) {
+        embeddings.extend(
+            generator
+                .generate_batch(batch)
+                .await
+                .map_err(|e| Error::Generic(format!("Embedding failed: {}", e)))?,
+        );
+    }
+    Ok((generator.model().id().to_string(), embeddings))
+}
+
+#[command]
+pub async fn project_collection_create(
+    app: AppHandle,
+    project_id: String,
+    name: String,
+    description: Option<String>,
+) -> Result<ProjectCollection> {
+    with_collections(&app, move |c| {
+        c.create_collection(&project_id, &name, description)
+    })
+    .await
+}
+
+#[command]
+pub async fn project_collection_list(
+    app: AppHandle,
+    project_id: String,
+) -> Result<Vec<ProjectCollection>> {
+    with_collections(&app, move |c| c.list_collections(&project_id)).await
+}
+
+#[command]
+pub async fn project_collection_list_sources(
+    app: AppHandle,
+    collection_id: String,
+) -> Result<Vec<CollectionSource>> {
+    with_collections(&app, move |c| c.list_sources(&collection_id)).await
+}
+
+#[command]
+pub async fn project_collection_delete(app: AppHandle, collection_id: String) -> Result<()> {
+    with_collections(&app, move |c| c.delete_collection(&collection_id)).await
+}
+
+/// Add a file, a folder of documents or a web page to a collection, then
+/// chunk and embed it with the active embedding model. A source that fails
+/// is kept with its error so the user can see what went wrong.
+#[command]
+pub async fn project_collection_add_source(
+    app: AppHandle,
+    collection_id: String,
+    kind: CollectionSourceKind,
+    location: String,
+) -> Result<CollectionSource> {
+    let source = {
+        let location = location.clone();
+        with_collections(&app, move |c| c.add_source(&collection_id, kind, &location)).await?
+    };
+    let documents = match kind {
+        CollectionSourceKind::Url => fetch_page(&location, &FetchOptions::default())
+            .await
+            .map(|page| vec![page_document(&page)])
+            .map_err(|e| Error::Generic(format!("Failed to fetch {}: {:#}", location, e))),
+        _ => with_collections(&app, move |c| c.read_source(kind, &location)).await,
+    };
+    ingest(&app, source, documents).await
+}
+
+/// Index a page that has already been fetched, as a URL source of the
+/// collection
+pub async fn add_web_page(
+    app: &AppHandle,
+    collection_id: String,
+    page: &WebPage,
+) -> Result<CollectionSource> {
+    let location = page.final_url.clone();
+    let source = with_collections(app, move |c| {
+        c.add_source(&collection_id, CollectionSourceKind::Url, &location)
+    })
+    .await?;
+    ingest(app, source, Ok(vec![page_document(page)])).await
+}
+
+fn page_document(page: &WebPage) -> SourceDocument {
+    SourceDocument {
+        document: page.final_url.clone(),
+        title: page.title.clone(),
+        content: page.markdown.clone(),
+    }
+}
+
+/// Chunk and embed the documents read for `source`, marking it failed if
+/// reading or embedding went wrong, and return its updated record
+async fn ingest(
+    app: &AppHandle,
+    source: CollectionSource,
+    documents: Result<Vec<SourceDocument>>,
+) -> Result<CollectionSource> {
+    let stored = match documents {
+        Ok(documents) => ingest_documents(app, &source, documents).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = stored {
+        let source_id = source.id.clone();
+        let message = e.to_string();
+        with_collections(app, move |c| c.fail_source(&source_id, &message)).await?;
+        return Err(e);
+    }
+
+    let collection_id = source.collection_id.clone();
+    let source_id = source.id.clone();
+    with_collections(app, move |c| c.list_sources(&collection_id))
+        .await?
+        .into_iter()
+        .find(|s| s.id == source_id)
+        .ok_or_else(|| Error::Generic(format!("Source {} disappeared", source_id)))
+}
+
+async fn ingest_documents(
+    app: &AppHandle,
+    source: &CollectionSource,
+    documents: Vec<SourceDocument>,
+) -> Result<()> {
+    let chunks = with_collections(app, move |c| c.chunk(&documents)).await?;
+
+    let texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+    let (model, embeddings) = if texts.is_empty() {
+        (String::new(), Vec::new())
+    } else {
+        embed(app, &texts).await?
+    };
+
+    let source_id = source.id.clone();
+    let stored = with_collections(app, move |c| {
+        c.store_chunks(&source_id, &model, &chunks, &embeddings)
+    })
+    .await?;
+    tracing::info!(
+        "Added {} chunks from {} to collection {}",
+        stored,
+        source.location,
+        source.collection_id
+    );
+    Ok(())
+}
+
+/// The `top_k` chunks most relevant to `query`, each citing its source
+#[command]
+pub async fn project_collection_query(
+    app: AppHandle,
+    collection_id: String,
+    query: String,
+    top_k: Option<usize>,
+) -> Result<Vec<CollectionMatch>> {
+    query_collection(&app, collection_id, &query, top_k.unwrap_or(DEFAULT_TOP_K)).await
+}
+
+async fn query_collection(
+    app: &AppHandle,
+    collection_id: String,
+    query: &str,
+    top_k: usize,
+) -> Result<Vec<CollectionMatch>> {
+    let (model, mut embeddings) = embed(app, &[query]).await?;
+    let embedding = embeddings
+        .pop()
+        .ok_or_else(|| Error::Generic("Embedding model returned no vector".to_string()))?;
+    with_collections(app, move |c| {
+        c.query(&collection_id, &model, &embedding, top_k)
+    })
+    .await
+}
+
+/// System-prompt block with the collection's best matches for a chat message.
+/// Retrieval problems are logged and yield `None` so the message still sends.
+pub async fn collection_chat_context(
+    app: &AppHandle,
+    collection_id: &str,
+    message: &str,
+) -> Option<String> {
+    match query_collection(app, collection_id.to_string(), message, DEFAULT_TOP_K).await {
+        Ok(matches) if !matches.is_empty() => Some(format_context(&matches)),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!("No context from collection {}: {}", collection_id, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use crate::readiness::{self, ReadinessReport};
+
+/// Current state of every backend subsystem; transitions are also emitted as `app://readiness`
+#[tauri::command]
+pub fn app_get_readiness() -> ReadinessReport {
+    readiness::snapshot()
+}
@@ -1,4 +1,4 @@
-use crate::realtime::{PresenceManager, UserActivity, UserPresence};
+use crate::realtime::{PresenceManager, PresencePolicy, UserActivity, UserPresence};
 use std::sync::Arc;
 use tauri::State;
 
@@ -26,15 +26,41 @@ pub async fn connect_websocket(
     Ok(format!("ws://127.0.0.1:{}", state.websocket_port))
 }
 
+/// Team members' presence as `viewer_id` is allowed to see it
 #[tauri::command]
 pub async fn get_team_presence(
     state: State<'_, RealtimeState>,
     team_id: String,
+    viewer_id: String,
 ) -> Result<Vec<UserPresence>, String> {
-    let presence = state.presence.get_team_presence(&team_id);
+    let presence = state.presence.get_team_presence(&team_id, &viewer_id);
     Ok(presence)
 }
 
+#[tauri::command]
+pub async fn get_presence_policy(
+    state: State<'_, RealtimeState>,
+    team_id: String,
+    user_id: String,
+) -> Result<PresencePolicy, String> {
+    state.presence.presence_policy(&team_id, &user_id)
+}
+
+/// Set who in a team can see `user_id`'s presence and how much of it.
+/// `None` falls back to the team's default.
+#[tauri::command]
+pub async fn set_presence_policy(
+    state: State<'_, RealtimeState>,
+    actor_id: String,
+    team_id: String,
+    user_id: String,
+    policy: Option<PresencePolicy>,
+) -> Result<(), String> {
+    state
+        .presence
+        .set_presence_policy(&actor_id, &team_id, &user_id, policy)
+}
+
 #[tauri::command]
 pub async fn update_user_activity(
     state: State<'_, RealtimeState>,
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, State};
+
+use crate::db::Pool;
+use crate::security::reauth::{self, ReauthManager, ReauthStatus, SensitiveAction};
+use crate::security::AuthenticatedUser;
+
+/// Confirm the user's identity for an action ahead of time, e.g. before a
+/// multi-step billing change. Resolves once Windows Hello or the password
+/// prompt succeeds, or immediately within the grace period.
+#[tauri::command]
+pub async fn reauth_confirm(
+    app: AppHandle,
+    user: AuthenticatedUser,
+    reauth: State<'_, ReauthManager>,
+    action: SensitiveAction,
+) -> Result<(), String> {
+    reauth
+        .require(&app, &user, action)
+        .await
+        .map_err(Into::into)
+}
+
+/// Answer a `reauth:password_required` prompt; `None` cancels it
+#[tauri::command]
+pub async fn reauth_submit_password(
+    reauth: State<'_, ReauthManager>,
+    request_id: String,
+    password: Option<String>,
+) -> Result<(), String> {
+    reauth
+        .submit_password(&request_id, password)
+        .await
+        .map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn reauth_status(reauth: State<'_, ReauthManager>) -> Result<ReauthStatus, String> {
+    reauth.status().await.map_err(Into::into)
+}
+
+/// How long a successful check covers the same kind of action; zero asks
+/// every time
+#[tauri::command]
+pub async fn reauth_set_grace_period(
+    _user: AuthenticatedUser,
+    pool: State<'_, Pool>,
+    seconds: u64,
+) -> Result<(), String> {
+    pool.run(move |conn| -> Result<_, String> {
+        reauth::set_grace_period(conn, Duration::from_secs(seconds)).map_err(Into::into)
+    })
+    .await
+}
+
+/// Set or change the local re-authentication password used when Windows
+/// Hello is unavailable and nobody has an account
+#[tauri::command]
+pub async fn reauth_set_password(
+    _user: AuthenticatedUser,
+    pool: State<'_, Pool>,
+    current_password: Option<String>,
+    new_password: String,
+) -> Result<(), String> {
+    pool.run(move |conn| -> Result<_, String> {
+        reauth::set_local_password(conn, current_password.as_deref(), &new_password)
+            .map_err(Into::into)
+    })
+    .await
+}
@@ -0,0 +1,48 @@
+use crate::remote_api::{
+    ApiClient, ApiClientCreated, ApiClientInput, RemoteApiConfig, RemoteApiServer, RemoteApiStatus,
+};
+use std::sync::Arc;
+use tauri::State;
+
+/// Remote API settings, where it is listening, and its registered clients
+#[tauri::command]
+pub async fn remote_api_status(
+    server: State<'_, Arc<RemoteApiServer>>,
+) -> Result<RemoteApiStatus, String> {
+    server.status().await
+}
+
+/// Turn the remote API on or off, or move it to another port
+#[tauri::command]
+pub async fn remote_api_configure(
+    server: State<'_, Arc<RemoteApiServer>>,
+    config: RemoteApiConfig,
+) -> Result<RemoteApiStatus, String> {
+    server.inner().configure(config).await
+}
+
+/// Register a remote API client. Its token is only returned here
+#[tauri::command]
+pub async fn remote_api_create_client(
+    server: State<'_, Arc<RemoteApiServer>>,
+    input: ApiClientInput,
+) -> Result<ApiClientCreated, String> {
+    server.create_client(input).await
+}
+
+#[tauri::command]
+pub async fn remote_api_update_client(
+    server: State<'_, Arc<RemoteApiServer>>,
+    id: String,
+    input: ApiClientInput,
+) -> Result<ApiClient, String> {
+    server.update_client(id, input).await
+}
+
+#[tauri::command]
+pub async fn remote_api_delete_client(
+    server: State<'_, Arc<RemoteApiServer>>,
+    id: String,
+) -> Result<bool, String> {
+    server.delete_client(id).await
+}
@@ -0,0 +1,198 @@
+use crate::commands::AppDatabase;
+use crate::db::migrations;
+use crate::safe_mode::{self, SafeModeSource, DISABLED_SUBSYSTEMS};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeModeStatus {
+    pub enabled: bool,
+    pub source: Option<SafeModeSource>,
+    pub disabled_subsystems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeModeDiagnostics {
+    /// `["ok"]` when SQLite finds no problems
+    pub integrity: Vec<String>,
+    pub schema_version: i32,
+    pub latest_schema_version: i32,
+    pub database_bytes: Option<u64>,
+    /// Tasks that would resume on the next normal start
+    pub pending_tasks: i64,
+    pub llm_cache_entries: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairAction {
+    /// Rebuild every index
+    Reindex,
+    /// Rewrite the database file, reclaiming free pages
+    Vacuum,
+    /// Cancel queued, running and paused tasks so they do not resume
+    CancelPendingTasks,
+    /// Drop cached LLM responses
+    ClearLlmCache,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    pub action: RepairAction,
+    pub rows_affected: usize,
+}
+
+const PENDING_TASK_STATUSES: &str = "('Queued', 'Running', 'Paused')";
+
+/// Report whether this session runs in safe mode and what it disabled.
+#[tauri::command]
+pub fn safe_mode_status() -> SafeModeStatus {
+    let mode = safe_mode::current();
+    SafeModeStatus {
+        enabled: mode.enabled,
+        source: mode.source,
+        disabled_subsystems: if mode.enabled {
+            DISABLED_SUBSYSTEMS.iter().map(|s| s.to_string()).collect()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Check the database for corruption, schema drift and leftover work.
+#[tauri::command]
+pub async fn safe_mode_diagnostics(
+    db: State<'_, AppDatabase>,
+) -> Result<SafeModeDiagnostics, String> {
+    let conn = db.conn.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = conn
+            .lock()
+            .map_err(|e| format!("Database lock poisoned: {}", e))?;
+        collect_diagnostics(&conn)
+    })
+    .await
+    .map_err(|e| format!("Diagnostics task failed: {}", e))?
+}
+
+/// Run one repair. Only available in safe mode, where nothing else is
+/// writing to the tables being repaired.
+#[tauri::command]
+pub async fn safe_mode_repair(
+    action: RepairAction,
+    db: State<'_, AppDatabase>,
+) -> Result<RepairReport, String> {
+    if !safe_mode::is_active() {
+        return Err("Repairs are only available in safe mode".to_string());
+    }
+
+    let conn = db.conn.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = conn
+            .lock()
+            .map_err(|e| format!("Database lock poisoned: {}", e))?;
+        let rows_affected = repair(&conn, action).map_err(|e| format!("Repair failed: {}", e))?;
+        tracing::info!(?action, rows_affected, "Safe mode repair completed");
+        Ok(RepairReport {
+            action,
+            rows_affected,
+        })
+    })
+    .await
+    .map_err(|e| format!("Repair task failed: {}", e))?
+}
+
+/// Restart the app, in safe mode when `safe` is true.
+#[tauri::command]
+pub fn safe_mode_restart(safe: bool, app: AppHandle) -> Result<(), String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    safe_mode::request_next_launch(&app_data_dir, safe)
+        .map_err(|e| format!("Failed to schedule safe mode: {}", e))?;
+    app.restart()
+}
+
+fn collect_diagnostics(conn: &Connection) -> Result<SafeModeDiagnostics, String> {
+    let integrity = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+
+    let schema_version = migrations::current_version(conn)
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+
+    let database_bytes = conn
+        .path()
+        .filter(|path| !path.is_empty())
+        .and_then(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len());
+
+    Ok(SafeModeDiagnostics {
+        integrity,
+        schema_version,
+        latest_schema_version: migrations::latest_version(),
+        database_bytes,
+        pending_tasks: count(
+            conn,
+            &format!(
+                "SELECT COUNT(*) FROM tasks WHERE status IN {}",
+                PENDING_TASK_STATUSES
+            ),
+        ),
+        llm_cache_entries: count(conn, "SELECT COUNT(*) FROM cache_entries"),
+    })
+}
+
+/// Row count, or zero when the table does not exist in a damaged database
+fn count(conn: &Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |row| row.get(0)).unwrap_or(0)
+}
+
+fn repair(conn: &Connection, action: RepairAction) -> rusqlite::Result<usize> {
+    match action {
+        RepairAction::Reindex => conn.execute_batch("REINDEX;").map(|_| 0),
+        RepairAction::Vacuum => conn.execute_batch("VACUUM;").map(|_| 0),
+        RepairAction::CancelPendingTasks => conn.execute(
+            &format!(
+                "UPDATE tasks SET status = 'Cancelled', completed_at = ?1 WHERE status IN {}",
+                PENDING_TASK_STATUSES
+            ),
+            [chrono::Utc::now().timestamp()],
+        ),
+        RepairAction::ClearLlmCache => conn.execute("DELETE FROM cache_entries", []),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_and_task_repair() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::run_migrations(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO tasks (id, name, status, created_at) VALUES ('a', 'A', 'Running', 0);
+             INSERT INTO tasks (id, name, status, created_at) VALUES ('b', 'B', 'Completed', 0);",
+        )
+        .unwrap();
+
+        let report = collect_diagnostics(&conn).unwrap();
+        assert_eq!(report.integrity, vec!["ok".to_string()]);
+        assert_eq!(report.schema_version, report.latest_schema_version);
+        assert_eq!(report.pending_tasks, 1);
+
+        assert_eq!(repair(&conn, RepairAction::CancelPendingTasks).unwrap(), 1);
+        assert_eq!(collect_diagnostics(&conn).unwrap().pending_tasks, 0);
+        repair(&conn, RepairAction::Reindex).unwrap();
+    }
+}
@@ -0,0 +1,25 @@
+use crate::commands::AppDatabase;
+use crate::search::global::{self, GlobalSearchQuery, GlobalSearchResult, SearchEntityType};
+use tauri::State;
+
+/// Search chat messages, read documents, emails and workflows in one query
+#[tauri::command]
+pub async fn search_global(
+    query: String,
+    types: Option<Vec<SearchEntityType>>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    db: State<'_, AppDatabase>,
+) -> Result<Vec<GlobalSearchResult>, String> {
+    let query = GlobalSearchQuery {
+        query,
+        types: types.unwrap_or_default(),
+        limit: limit.unwrap_or(50).min(200),
+        offset: offset.unwrap_or(0),
+    };
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    global::search_global(&conn, &query).map_err(|e| format!("Search failed: {}", e))
+}
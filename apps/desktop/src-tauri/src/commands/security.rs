@@ -1,5 +1,5 @@
 use crate::security::{
-    ApiSecurityManager, AuthManager, AuthToken, SecureStorage, UpdateMetadata,
+    AdminUser, ApiSecurityManager, AuthManager, AuthToken, SecureStorage, UpdateMetadata,
     UpdateSecurityManager, UserRole, VerificationResult,
 };
 use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
@@ -66,15 +66,21 @@ pub struct ChangePasswordRequest {
 // Authentication Commands
 // ============================================================================
 
+/// Once an account exists only an admin may add another. Before that the
+/// local user acts as the admin, so the first account is always an admin.
 #[tauri::command]
 pub async fn auth_register(
+    _admin: AdminUser,
     email: String,
     password: String,
     role: String,
     state: State<'_, AuthManagerState>,
 ) -> Result<String, String> {
     let manager = state.inner().read();
-    let user_role = UserRole::from_str(&role).ok_or("Invalid role")?;
+    let mut user_role = UserRole::from_str(&role).ok_or("Invalid role")?;
+    if !manager.has_users() {
+        user_role = UserRole::Admin;
+    }
     let user = manager.register(email, password.as_str(), user_role)?;
     Ok(user.id)
 }
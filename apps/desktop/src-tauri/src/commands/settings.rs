@@ -1,10 +1,10 @@
-use keyring::Entry;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 use tokio::sync::Mutex;
 
-const SERVICE_NAME: &str = "AGIWorkforce";
+use crate::security::vault::{SecretId, Vault};
+use crate::security::{AuthenticatedUser, ReauthManager, SensitiveAction};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -85,44 +85,71 @@ impl SettingsState {
 }
 
 #[tauri::command]
-pub async fn settings_save_api_key(provider: String, key: String) -> Result<(), String> {
-    let entry = Entry::new(SERVICE_NAME, &format!("api_key_{}", provider))
-        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-
+pub async fn settings_save_api_key(
+    _user: AuthenticatedUser,
+    vault: State<'_, Arc<Vault>>,
+    provider: String,
+    key: String,
+) -> Result<(), String> {
     // Trim the key to remove any whitespace before saving
     let trimmed_key = key.trim();
     if trimmed_key.is_empty() {
         return Err("API key cannot be empty".to_string());
     }
 
-    entry
-        .set_password(trimmed_key)
+    vault
+        .put(&SecretId::provider(&provider), trimmed_key, "settings")
         .map_err(|e| format!("Failed to save API key: {}", e))?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub async fn settings_get_api_key(provider: String) -> Result<String, String> {
-    let entry = Entry::new(SERVICE_NAME, &format!("api_key_{}", provider))
-        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-
-    let key = entry
-        .get_password()
-        .map_err(|e| format!("Failed to get API key: {}", e))?;
+pub async fn settings_get_api_key(
+    _user: AuthenticatedUser,
+    vault: State<'_, Arc<Vault>>,
+    provider: String,
+) -> Result<String, String> {
+    let key = vault
+        .get(&SecretId::provider(&provider), "settings")
+        .map_err(|e| format!("Failed to get API key: {}", e))?
+        .ok_or_else(|| format!("No API key saved for {}", provider))?;
 
     // Trim the key when retrieving to ensure no extra whitespace
-    Ok(key.trim().to_string())
+    Ok(key.expose_secret().trim().to_string())
 }
 
+/// Show a saved API key to the user, after Windows Hello or a password check
 #[tauri::command]
-pub async fn settings_load(state: State<'_, SettingsState>) -> Result<Settings, String> {
+pub async fn settings_reveal_api_key(
+    app: AppHandle,
+    user: AuthenticatedUser,
+    reauth: State<'_, ReauthManager>,
+    vault: State<'_, Arc<Vault>>,
+    provider: String,
+) -> Result<String, String> {
+    reauth
+        .require(&app, &user, SensitiveAction::RevealApiKey)
+        .await?;
+    let key = vault
+        .get(&SecretId::provider(&provider), "settings_reveal")
+        .map_err(|e| format!("Failed to get API key: {}", e))?
+        .ok_or_else(|| format!("No API key saved for {}", provider))?;
+    Ok(key.expose_secret().trim().to_string())
+}
+
+#[tauri::command]
+pub async fn settings_load(
+    _user: AuthenticatedUser,
+    state: State<'_, SettingsState>,
+) -> Result<Settings, String> {
     let settings = state.settings.lock().await;
     Ok(settings.clone())
 }
 
 #[tauri::command]
 pub async fn settings_save(
+    _user: AuthenticatedUser,
     settings: Settings,
     state: State<'_, SettingsState>,
 ) -> Result<(), String> {
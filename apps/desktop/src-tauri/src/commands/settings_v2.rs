@@ -1,3 +1,4 @@
+use crate::security::AuthenticatedUser;
 use crate::settings::{
     models::{AppSettings, SettingCategory, SettingValue},
     SettingsService,
@@ -54,6 +55,7 @@ pub struct GetSettingsResponse {
 /// Get a single setting value
 #[tauri::command]
 pub async fn settings_v2_get(
+    _user: AuthenticatedUser,
     key: String,
     state: State<'_, SettingsServiceState>,
 ) -> Result<serde_json::Value, String> {
@@ -78,6 +80,7 @@ pub async fn settings_v2_get(
 /// Set a single setting value
 #[tauri::command]
 pub async fn settings_v2_set(
+    _user: AuthenticatedUser,
     request: SetSettingRequest,
     state: State<'_, SettingsServiceState>,
 ) -> Result<SettingsResponse, String> {
@@ -104,6 +107,7 @@ pub async fn settings_v2_set(
 /// Get multiple settings at once
 #[tauri::command]
 pub async fn settings_v2_get_batch(
+    _user: AuthenticatedUser,
     request: GetSettingsRequest,
     state: State<'_, SettingsServiceState>,
 ) -> Result<GetSettingsResponse, String> {
@@ -127,6 +131,7 @@ pub async fn settings_v2_get_batch(
 /// Delete a setting
 #[tauri::command]
 pub async fn settings_v2_delete(
+    _user: AuthenticatedUser,
     key: String,
     state: State<'_, SettingsServiceState>,
 ) -> Result<SettingsResponse, String> {
@@ -148,6 +153,7 @@ pub async fn settings_v2_delete(
 /// Get all settings in a category
 #[tauri::command]
 pub async fn settings_v2_get_category(
+    _user: AuthenticatedUser,
     category: String,
     state: State<'_, SettingsServiceState>,
 ) -> Result<GetSettingsResponse, String> {
@@ -178,6 +184,7 @@ pub async fn settings_v2_get_category(
 /// Save API key to keyring
 #[tauri::command]
 pub async fn settings_v2_save_api_key(
+    _user: AuthenticatedUser,
     provider: String,
     key: String,
     state: State<'_, SettingsServiceState>,
@@ -200,6 +207,7 @@ pub async fn settings_v2_save_api_key(
 /// Get API key from keyring
 #[tauri::command]
 pub async fn settings_v2_get_api_key(
+    _user: AuthenticatedUser,
     provider: String,
     state: State<'_, SettingsServiceState>,
 ) -> Result<String, String> {
@@ -216,6 +224,7 @@ pub async fn settings_v2_get_api_key(
 /// Load complete application settings
 #[tauri::command]
 pub async fn settings_v2_load_app_settings(
+    _user: AuthenticatedUser,
     state: State<'_, SettingsServiceState>,
 ) -> Result<AppSettings, String> {
     let service = state
@@ -231,6 +240,7 @@ pub async fn settings_v2_load_app_settings(
 /// Save complete application settings
 #[tauri::command]
 pub async fn settings_v2_save_app_settings(
+    _user: AuthenticatedUser,
     settings: AppSettings,
     state: State<'_, SettingsServiceState>,
 ) -> Result<SettingsResponse, String> {
@@ -252,6 +262,7 @@ pub async fn settings_v2_save_app_settings(
 /// Clear settings cache
 #[tauri::command]
 pub async fn settings_v2_clear_cache(
+    _user: AuthenticatedUser,
     state: State<'_, SettingsServiceState>,
 ) -> Result<SettingsResponse, String> {
     let service = state
@@ -270,6 +281,7 @@ pub async fn settings_v2_clear_cache(
 /// List all settings (non-encrypted values only, for debugging)
 #[tauri::command]
 pub async fn settings_v2_list_all(
+    _user: AuthenticatedUser,
     state: State<'_, SettingsServiceState>,
 ) -> Result<GetSettingsResponse, String> {
     let service = state
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use tauri::State;
+
+use crate::db::Pool;
+use crate::security::shell_guard::{self, ShellGuard, ShellGuardState, ShellPlan, ShellPolicy};
+use crate::security::AdminUser;
+
+#[tauri::command]
+pub async fn shell_guard_get_policy(
+    state: State<'_, ShellGuardState>,
+) -> Result<ShellPolicy, String> {
+    Ok(state.guard().policy().clone())
+}
+
+/// Save the shell policy; agent commands use it from their next call
+#[tauri::command]
+pub async fn shell_guard_set_policy(
+    _admin: AdminUser,
+    pool: State<'_, Pool>,
+    state: State<'_, ShellGuardState>,
+    policy: ShellPolicy,
+) -> Result<(), String> {
+    let guard = ShellGuard::new(policy.clone()).map_err(|e| e.to_string())?;
+    pool.run(move |conn| -> Result<_, String> {
+        shell_guard::save_policy(conn, &policy).map_err(|e| e.to_string())
+    })
+    .await?;
+    state.replace(guard);
+    Ok(())
+}
+
+/// Dry run: what `command` would execute as and whether it needs approval
+#[tauri::command]
+pub async fn shell_guard_check(
+    state: State<'_, ShellGuardState>,
+    command: String,
+    shell: Option<String>,
+    cwd: Option<PathBuf>,
+) -> Result<ShellPlan, String> {
+    state
+        .guard()
+        .plan(
+            &command,
+            shell.as_deref().unwrap_or("powershell"),
+            cwd.as_deref(),
+        )
+        .map_err(|e| e.to_string())
+}
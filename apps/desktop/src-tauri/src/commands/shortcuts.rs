@@ -5,7 +5,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +75,20 @@ impl ShortcutsState {
                 action: "quick_capture".to_string(),
                 enabled: true,
             },
+            Shortcut {
+                id: "toggle_launcher".to_string(),
+                key: "Alt+Space".to_string(),
+                description: "Open quick launcher".to_string(),
+                action: "toggle_launcher".to_string(),
+                enabled: true,
+            },
+            Shortcut {
+                id: "kill_switch".to_string(),
+                key: "CommandOrControl+Alt+Shift+X".to_string(),
+                description: "Stop all automation (invokes kill_switch_engage)".to_string(),
+                action: "kill_switch".to_string(),
+                enabled: true,
+            },
         ];
 
         for shortcut in defaults {
@@ -113,27 +127,13 @@ pub async fn shortcuts_register(
     let mut shortcuts = shortcuts_state.shortcuts.lock().await;
     shortcuts.insert(shortcut.id.clone(), shortcut.clone());
 
-    // Register global hotkey (platform-specific)
-    #[cfg(target_os = "windows")]
-    {
-        // On Windows, we can use tauri-plugin-global-shortcut or native Windows APIs
-        // For now, emit an event when shortcut is triggered
-        let app_clone = app.clone();
-
-        // Store the key for cleanup
-        let mut registered = shortcuts_state.registered_keys.lock().await;
-        registered.push(shortcut.key.clone());
-
-        // Emit event (actual hotkey registration would happen via plugin)
-        app_clone
-            .emit("shortcut_registered", shortcut)
-            .map_err(|e| format!("Failed to emit event: {}", e))?;
-    }
+    // Store the key for cleanup
+    let mut registered = shortcuts_state.registered_keys.lock().await;
+    registered.push(shortcut.key.clone());
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        tracing::warn!("Global shortcuts not yet implemented for this platform");
-    }
+    // Emit event (actual hotkey registration happens via the global-shortcut plugin)
+    app.emit("shortcut_registered", shortcut)
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
 
     Ok(())
 }
@@ -218,6 +218,13 @@ pub async fn shortcuts_update(
 pub async fn shortcuts_trigger(action: String, app: AppHandle) -> Result<(), String> {
     tracing::info!("Triggering shortcut action: {}", action);
 
+    // Hotkeys of workflow triggers start their workflow
+    if let Some(workflows) = app.try_state::<crate::commands::WorkflowEngineState>() {
+        if workflows.triggers.on_hotkey(&action) {
+            return Ok(());
+        }
+    }
+
     // Emit event for the action
     app.emit("shortcut_action", action)
         .map_err(|e| format!("Failed to emit event: {}", e))?;
@@ -0,0 +1,111 @@
+use crate::agent::runtime::RunRecorder;
+use crate::agi::skills::{global_skill_library, Skill, SkillMatch, SkillParameter};
+use crate::audit::{self, Actor, AuditCategory, AuditEntry};
+use crate::security::AuthenticatedUser;
+use serde::Deserialize;
+use tauri::State;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSkillFromRunRequest {
+    pub run_id: String,
+    pub name: String,
+    pub description: String,
+    /// Values the run used that become parameters, given as their defaults
+    #[serde(default)]
+    pub parameters: Vec<SkillParameter>,
+    #[serde(default)]
+    pub preconditions: Vec<String>,
+    #[serde(default)]
+    pub integrations: Vec<String>,
+}
+
+/// Saved skills, most used first
+#[tauri::command]
+pub async fn skill_list() -> Result<Vec<Skill>, String> {
+    global_skill_library()
+        .list()
+        .await
+        .map_err(|e| format!("Failed to list skills: {}", e))
+}
+
+/// Skills closest in meaning to `query`
+#[tauri::command]
+pub async fn skill_search(query: String, limit: Option<usize>) -> Result<Vec<SkillMatch>, String> {
+    global_skill_library()
+        .search(&query, limit.unwrap_or(10))
+        .await
+        .map_err(|e| format!("Failed to search skills: {}", e))
+}
+
+/// Save the successful steps of a recorded run as a reusable skill
+#[tauri::command]
+pub async fn skill_save_from_run(
+    app: tauri::AppHandle,
+    user: AuthenticatedUser,
+    recorder: State<'_, RunRecorder>,
+    request: SaveSkillFromRunRequest,
+) -> Result<Skill, String> {
+    let trace = recorder
+        .trace(&request.run_id)
+        .await
+        .map_err(|e| format!("Failed to load run trace: {}", e))?
+        .ok_or_else(|| format!("Run {} not found", request.run_id))?;
+    let mut skill = Skill::from_run(
+        &trace,
+        request.name,
+        request.description,
+        request.parameters,
+    )
+    .map_err(|e| e.to_string())?;
+    skill.preconditions = request.preconditions;
+    skill.integrations = request.integrations;
+
+    save(&app, &user, skill).await
+}
+
+/// Create a skill, or update one with the same id
+#[tauri::command]
+pub async fn skill_save(
+    app: tauri::AppHandle,
+    user: AuthenticatedUser,
+    skill: Skill,
+) -> Result<Skill, String> {
+    save(&app, &user, skill).await
+}
+
+#[tauri::command]
+pub async fn skill_delete(
+    app: tauri::AppHandle,
+    user: AuthenticatedUser,
+    id: String,
+) -> Result<bool, String> {
+    let result = global_skill_library().delete(&id).await;
+    audit::record(
+        &app,
+        AuditEntry::new(
+            Actor::user(&user),
+            AuditCategory::Automation,
+            "skill_delete",
+        )
+        .target(id)
+        .result(&result),
+    );
+    result.map_err(|e| format!("Failed to delete skill: {}", e))
+}
+
+async fn save(
+    app: &tauri::AppHandle,
+    user: &AuthenticatedUser,
+    skill: Skill,
+) -> Result<Skill, String> {
+    let name = skill.name.clone();
+    let result = global_skill_library().save(skill).await;
+    audit::record(
+        app,
+        AuditEntry::new(Actor::user(user), AuditCategory::Automation, "skill_save")
+            .target(name)
+            .result(&result),
+    );
+    result.map_err(|e| format!("Failed to save skill: {}", e))
+}
@@ -5,6 +5,9 @@
 
 #[cfg(feature = "billing")]
 use crate::billing::{BillingStateWrapper, SubscriptionInfo};
+use crate::security::AdminUser;
+#[cfg(feature = "billing")]
+use crate::security::{ReauthManager, SensitiveAction};
 
 #[cfg(feature = "billing")]
 /// Subscribe to a plan
@@ -20,11 +23,18 @@ use crate::billing::{BillingStateWrapper, SubscriptionInfo};
 /// The created subscription information
 #[tauri::command]
 pub async fn subscribe_to_plan(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     user_id: String,
     plan_id: String,
     state: State<'_, BillingStateWrapper>,
     db_state: State<'_, crate::commands::AppDatabase>,
 ) -> Result<SubscriptionInfo, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -103,10 +113,17 @@ pub async fn subscribe_to_plan(
 /// The updated subscription information
 #[tauri::command]
 pub async fn upgrade_plan(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     user_id: String,
     new_plan_id: String,
     state: State<'_, BillingStateWrapper>,
 ) -> Result<SubscriptionInfo, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -150,11 +167,18 @@ pub async fn upgrade_plan(
 /// Success or error
 #[tauri::command]
 pub async fn cancel_subscription(
+    app: tauri::AppHandle,
+    admin: AdminUser,
+    reauth: tauri::State<'_, ReauthManager>,
     user_id: String,
     subscription_id: String,
     state: State<'_, BillingStateWrapper>,
     db_state: State<'_, crate::commands::AppDatabase>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let billing = state
         .0
         .lock()
@@ -204,21 +228,33 @@ pub async fn cancel_subscription(
 #[cfg(not(feature = "billing"))]
 /// Stub for subscribe_to_plan when billing feature is disabled
 #[tauri::command]
-pub async fn subscribe_to_plan(_user_id: String, _plan_id: String) -> Result<String, String> {
+pub async fn subscribe_to_plan(
+    _admin: AdminUser,
+    _user_id: String,
+    _plan_id: String,
+) -> Result<String, String> {
     Err("Billing feature is not enabled".to_string())
 }
 
 #[cfg(not(feature = "billing"))]
 /// Stub for upgrade_plan when billing feature is disabled
 #[tauri::command]
-pub async fn upgrade_plan(_user_id: String, _new_plan_id: String) -> Result<String, String> {
+pub async fn upgrade_plan(
+    _admin: AdminUser,
+    _user_id: String,
+    _new_plan_id: String,
+) -> Result<String, String> {
     Err("Billing feature is not enabled".to_string())
 }
 
 #[cfg(not(feature = "billing"))]
 /// Stub for cancel_subscription when billing feature is disabled
 #[tauri::command]
-pub async fn cancel_subscription(_user_id: String, _subscription_id: String) -> Result<(), String> {
+pub async fn cancel_subscription(
+    _admin: AdminUser,
+    _user_id: String,
+    _subscription_id: String,
+) -> Result<(), String> {
     Err("Billing feature is not enabled".to_string())
 }
 
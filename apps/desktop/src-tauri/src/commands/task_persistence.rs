@@ -7,6 +7,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Mutex, MutexGuard};
 
+use crate::db::pagination::{paginate_vec, Page, PageRequest, SortDirection};
+
 /// Task status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TaskStatus {
@@ -106,6 +108,60 @@ impl TaskManager {
         Ok(tasks.values().cloned().collect())
     }
 
+    /// One page of tasks, filterable by `status` and `priority` and searchable
+    /// by name and description
+    pub async fn list_tasks_page(
+        &self,
+        request: &PageRequest,
+    ) -> Result<Page<PersistedTask>, String> {
+        let sort = request.sort_column(&[
+            ("createdAt", "created_at"),
+            ("updatedAt", "updated_at"),
+            ("name", "name"),
+            ("priority", "priority"),
+            ("progress", "progress"),
+        ])?;
+        request.check_filters(&["status", "priority"])?;
+        let search = request.search_term();
+        let matches = |value: &dyn std::fmt::Debug, filter: &str| {
+            request
+                .filters
+                .get(filter)
+                .is_none_or(|wanted| format!("{:?}", value).eq_ignore_ascii_case(wanted))
+        };
+
+        let mut tasks: Vec<PersistedTask> = self
+            .tasks
+            .lock()
+            .await
+            .values()
+            .filter(|task| matches(&task.status, "status") && matches(&task.priority, "priority"))
+            .filter(|task| {
+                search.as_ref().is_none_or(|term| {
+                    task.name.to_lowercase().contains(term)
+                        || task.description.to_lowercase().contains(term)
+                })
+            })
+            .cloned()
+            .collect();
+
+        tasks.sort_by(|a, b| {
+            let order = match sort {
+                "updated_at" => a.updated_at.cmp(&b.updated_at),
+                "name" => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                "priority" => priority_rank(&a.priority).cmp(&priority_rank(&b.priority)),
+                "progress" => a.progress.total_cmp(&b.progress),
+                _ => a.created_at.cmp(&b.created_at),
+            };
+            order.then_with(|| a.id.cmp(&b.id))
+        });
+        if request.direction() == SortDirection::Desc {
+            tasks.reverse();
+        }
+
+        paginate_vec(tasks, request)
+    }
+
     pub async fn pause_task(&self, task_id: &str) -> Result<(), String> {
         let mut tasks = self.tasks.lock().await;
         if let Some(task) = tasks.get_mut(task_id) {
@@ -266,6 +322,16 @@ pub async fn task_list(
     manager.list_tasks().await
 }
 
+/// List tasks a page at a time with sorting, search and filters
+#[tauri::command]
+pub async fn task_list_page(
+    page: Option<PageRequest>,
+    state: tauri::State<'_, TaskManagerWrapper>,
+) -> Result<Page<PersistedTask>, String> {
+    let manager = state.inner().lock().await;
+    manager.list_tasks_page(&page.unwrap_or_default()).await
+}
+
 /// List tasks by status
 #[tauri::command]
 pub async fn task_list_by_status(
@@ -464,6 +530,15 @@ pub async fn coord_get_pending_approvals(
 
 // Helper functions
 
+fn priority_rank(priority: &TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::Low => 0,
+        TaskPriority::Normal => 1,
+        TaskPriority::High => 2,
+        TaskPriority::Critical => 3,
+    }
+}
+
 fn current_timestamp() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
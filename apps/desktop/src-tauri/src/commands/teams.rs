@@ -1,15 +1,18 @@
+use crate::audit::{self, Actor, AuditCategory, AuditEntry};
 use crate::commands::AppDatabase;
+use crate::security::{AdminUser, AuthenticatedUser, ReauthManager, SensitiveAction};
 use crate::teams::{
     ActivityType, BillingCycle, BillingPlan, ResourceType, Team, TeamActivity, TeamActivityManager,
     TeamBilling, TeamBillingManager, TeamInvitation, TeamManager, TeamMember, TeamResource,
     TeamResourceManager, TeamRole, TeamUpdates, UsageMetrics,
 };
 use serde_json::json;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 /// Create a new team
 #[tauri::command]
 pub async fn create_team(
+    _user: AuthenticatedUser,
     name: String,
     description: Option<String>,
     owner_id: String,
@@ -21,7 +24,11 @@ pub async fn create_team(
 
 /// Get a team by ID
 #[tauri::command]
-pub async fn get_team(team_id: String, db: State<'_, AppDatabase>) -> Result<Option<Team>, String> {
+pub async fn get_team(
+    _user: AuthenticatedUser,
+    team_id: String,
+    db: State<'_, AppDatabase>,
+) -> Result<Option<Team>, String> {
     let manager = TeamManager::new(db.conn.clone());
     manager.get_team(&team_id)
 }
@@ -29,6 +36,7 @@ pub async fn get_team(team_id: String, db: State<'_, AppDatabase>) -> Result<Opt
 /// Update a team
 #[tauri::command]
 pub async fn update_team(
+    _user: AuthenticatedUser,
     team_id: String,
     name: Option<String>,
     description: Option<String>,
@@ -45,7 +53,11 @@ pub async fn update_team(
 
 /// Delete a team
 #[tauri::command]
-pub async fn delete_team(team_id: String, db: State<'_, AppDatabase>) -> Result<(), String> {
+pub async fn delete_team(
+    _admin: AdminUser,
+    team_id: String,
+    db: State<'_, AppDatabase>,
+) -> Result<(), String> {
     let manager = TeamManager::new(db.conn.clone());
     manager.delete_team(&team_id)
 }
@@ -53,6 +65,7 @@ pub async fn delete_team(team_id: String, db: State<'_, AppDatabase>) -> Result<
 /// Get all teams for a user
 #[tauri::command]
 pub async fn get_user_teams(
+    _user: AuthenticatedUser,
     user_id: String,
     db: State<'_, AppDatabase>,
 ) -> Result<Vec<Team>, String> {
@@ -63,6 +76,7 @@ pub async fn get_user_teams(
 /// Invite a member to a team
 #[tauri::command]
 pub async fn invite_member(
+    _user: AuthenticatedUser,
     team_id: String,
     email: String,
     role: String,
@@ -92,6 +106,7 @@ pub async fn invite_member(
 /// Accept an invitation
 #[tauri::command]
 pub async fn accept_invitation(
+    _user: AuthenticatedUser,
     token: String,
     user_id: String,
     db: State<'_, AppDatabase>,
@@ -116,6 +131,7 @@ pub async fn accept_invitation(
 /// Remove a member from a team
 #[tauri::command]
 pub async fn remove_member(
+    _user: AuthenticatedUser,
     team_id: String,
     user_id: String,
     removed_by: String,
@@ -141,6 +157,7 @@ pub async fn remove_member(
 /// Update a member's role
 #[tauri::command]
 pub async fn update_member_role(
+    _user: AuthenticatedUser,
     team_id: String,
     user_id: String,
     role: String,
@@ -170,6 +187,7 @@ pub async fn update_member_role(
 /// Get all members of a team
 #[tauri::command]
 pub async fn get_team_members(
+    _user: AuthenticatedUser,
     team_id: String,
     db: State<'_, AppDatabase>,
 ) -> Result<Vec<TeamMember>, String> {
@@ -180,6 +198,7 @@ pub async fn get_team_members(
 /// Get pending invitations for a team
 #[tauri::command]
 pub async fn get_team_invitations(
+    _user: AuthenticatedUser,
     team_id: String,
     db: State<'_, AppDatabase>,
 ) -> Result<Vec<TeamInvitation>, String> {
@@ -190,6 +209,7 @@ pub async fn get_team_invitations(
 /// Share a resource with a team
 #[tauri::command]
 pub async fn share_resource(
+    _user: AuthenticatedUser,
     team_id: String,
     resource_type: String,
     resource_id: String,
@@ -228,6 +248,7 @@ pub async fn share_resource(
 /// Unshare a resource from a team
 #[tauri::command]
 pub async fn unshare_resource(
+    _user: AuthenticatedUser,
     team_id: String,
     resource_type: String,
     resource_id: String,
@@ -257,6 +278,7 @@ pub async fn unshare_resource(
 /// Get all resources shared with a team
 #[tauri::command]
 pub async fn get_team_resources(
+    _user: AuthenticatedUser,
     team_id: String,
     db: State<'_, AppDatabase>,
 ) -> Result<Vec<TeamResource>, String> {
@@ -267,6 +289,7 @@ pub async fn get_team_resources(
 /// Get resources by type
 #[tauri::command]
 pub async fn get_team_resources_by_type(
+    _user: AuthenticatedUser,
     team_id: String,
     resource_type: String,
     db: State<'_, AppDatabase>,
@@ -281,6 +304,7 @@ pub async fn get_team_resources_by_type(
 /// Get team activity
 #[tauri::command]
 pub async fn get_team_activity(
+    _user: AuthenticatedUser,
     team_id: String,
     limit: usize,
     offset: usize,
@@ -293,6 +317,7 @@ pub async fn get_team_activity(
 /// Get user activity in a team
 #[tauri::command]
 pub async fn get_user_team_activity(
+    _user: AuthenticatedUser,
     team_id: String,
     user_id: String,
     limit: usize,
@@ -305,6 +330,7 @@ pub async fn get_user_team_activity(
 /// Get team billing information
 #[tauri::command]
 pub async fn get_team_billing(
+    _user: AuthenticatedUser,
     team_id: String,
     db: State<'_, AppDatabase>,
 ) -> Result<Option<TeamBilling>, String> {
@@ -315,12 +341,19 @@ pub async fn get_team_billing(
 /// Initialize billing for a team
 #[tauri::command]
 pub async fn initialize_team_billing(
+    app: AppHandle,
+    admin: AdminUser,
+    reauth: State<'_, ReauthManager>,
     team_id: String,
     plan: String,
     cycle: String,
     seat_count: usize,
     db: State<'_, AppDatabase>,
 ) -> Result<TeamBilling, String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let plan_tier =
         BillingPlan::from_str(&plan).ok_or_else(|| format!("Invalid plan: {}", plan))?;
 
@@ -328,8 +361,16 @@ pub async fn initialize_team_billing(
         .ok_or_else(|| format!("Invalid billing cycle: {}", cycle))?;
 
     let manager = TeamBillingManager::new(db.conn.clone());
-    let billing =
-        manager.initialize_team_billing(&team_id, plan_tier, billing_cycle, seat_count)?;
+    let result = manager.initialize_team_billing(&team_id, plan_tier, billing_cycle, seat_count);
+    audit_billing(
+        &app,
+        &admin,
+        "initialize_team_billing",
+        &team_id,
+        &json!({ "plan": plan, "cycle": cycle, "seat_count": seat_count }),
+        &result,
+    );
+    let billing = result?;
 
     // Log activity
     let activity_manager = TeamActivityManager::new(db.conn.clone());
@@ -348,16 +389,32 @@ pub async fn initialize_team_billing(
 /// Update team plan
 #[tauri::command]
 pub async fn update_team_plan(
+    app: AppHandle,
+    admin: AdminUser,
+    reauth: State<'_, ReauthManager>,
     team_id: String,
     plan: String,
     updated_by: String,
     db: State<'_, AppDatabase>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let plan_tier =
         BillingPlan::from_str(&plan).ok_or_else(|| format!("Invalid plan: {}", plan))?;
 
     let manager = TeamBillingManager::new(db.conn.clone());
-    manager.update_team_plan(&team_id, plan_tier)?;
+    let result = manager.update_team_plan(&team_id, plan_tier);
+    audit_billing(
+        &app,
+        &admin,
+        "update_team_plan",
+        &team_id,
+        &json!({ "plan": plan }),
+        &result,
+    );
+    result?;
 
     // Log activity
     let activity_manager = TeamActivityManager::new(db.conn.clone());
@@ -376,13 +433,29 @@ pub async fn update_team_plan(
 /// Add seats to team billing
 #[tauri::command]
 pub async fn add_team_seats(
+    app: AppHandle,
+    admin: AdminUser,
+    reauth: State<'_, ReauthManager>,
     team_id: String,
     count: usize,
     updated_by: String,
     db: State<'_, AppDatabase>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let manager = TeamBillingManager::new(db.conn.clone());
-    manager.add_seats(&team_id, count)?;
+    let result = manager.add_seats(&team_id, count);
+    audit_billing(
+        &app,
+        &admin,
+        "add_team_seats",
+        &team_id,
+        &json!({ "count": count }),
+        &result,
+    );
+    result?;
 
     // Log activity
     let activity_manager = TeamActivityManager::new(db.conn.clone());
@@ -401,13 +474,29 @@ pub async fn add_team_seats(
 /// Remove seats from team billing
 #[tauri::command]
 pub async fn remove_team_seats(
+    app: AppHandle,
+    admin: AdminUser,
+    reauth: State<'_, ReauthManager>,
     team_id: String,
     count: usize,
     updated_by: String,
     db: State<'_, AppDatabase>,
 ) -> Result<(), String> {
+    reauth
+        .require(&app, &admin.0, SensitiveAction::ChangeBilling)
+        .await?;
+
     let manager = TeamBillingManager::new(db.conn.clone());
-    manager.remove_seats(&team_id, count)?;
+    let result = manager.remove_seats(&team_id, count);
+    audit_billing(
+        &app,
+        &admin,
+        "remove_team_seats",
+        &team_id,
+        &json!({ "count": count }),
+        &result,
+    );
+    result?;
 
     // Log activity
     let activity_manager = TeamActivityManager::new(db.conn.clone());
@@ -423,9 +512,28 @@ pub async fn remove_team_seats(
     Ok(())
 }
 
+/// Record a team billing change in the audit trail
+fn audit_billing<T>(
+    app: &AppHandle,
+    admin: &AdminUser,
+    action: &str,
+    team_id: &str,
+    params: &serde_json::Value,
+    result: &Result<T, String>,
+) {
+    audit::record(
+        app,
+        AuditEntry::new(Actor::user(&admin.0), AuditCategory::Payment, action)
+            .target(team_id)
+            .params(params)
+            .result(result),
+    );
+}
+
 /// Calculate team cost
 #[tauri::command]
 pub async fn calculate_team_cost(
+    _user: AuthenticatedUser,
     team_id: String,
     db: State<'_, AppDatabase>,
 ) -> Result<f64, String> {
@@ -436,6 +544,7 @@ pub async fn calculate_team_cost(
 /// Update team usage metrics
 #[tauri::command]
 pub async fn update_team_usage(
+    _user: AuthenticatedUser,
     team_id: String,
     metrics: UsageMetrics,
     db: State<'_, AppDatabase>,
@@ -447,6 +556,7 @@ pub async fn update_team_usage(
 /// Transfer team ownership
 #[tauri::command]
 pub async fn transfer_team_ownership(
+    _admin: AdminUser,
     team_id: String,
     new_owner_id: String,
     transferred_by: String,
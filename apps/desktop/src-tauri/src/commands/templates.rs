@@ -1,7 +1,7 @@
 use crate::agi::templates::{
-    get_builtin_templates, AgentTemplate, TemplateCategory, TemplateManager,
+    get_builtin_templates, AgentTemplate, RequirementsReport, TemplateCategory, TemplateManager,
 };
-use rusqlite::Connection;
+use crate::db::Pool;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tauri::State;
@@ -12,7 +12,7 @@ pub struct TemplateManagerState {
 }
 
 /// Initialize template manager and load built-in templates
-pub fn initialize_template_manager(db: Arc<Mutex<Connection>>) -> TemplateManager {
+pub fn initialize_template_manager(db: Pool) -> TemplateManager {
     let manager = TemplateManager::new(db).expect("Failed to create TemplateManager");
 
     // Load built-in templates
@@ -56,6 +56,26 @@ pub async fn get_templates_by_category(
         .map_err(|e| e.to_string())
 }
 
+/// Load a template and fail with setup hints if its requirements are unmet
+fn require_ready_template(
+    mgr: &TemplateManager,
+    user_id: &str,
+    template_id: &str,
+) -> Result<AgentTemplate, String> {
+    let template = mgr
+        .get_template_by_id(template_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Template not found: {}", template_id))?;
+
+    let report = mgr
+        .check_requirements(user_id, &template)
+        .map_err(|e| format!("Failed to check template requirements: {}", e))?;
+    if !report.satisfied {
+        return Err(report.error_message(&template.name));
+    }
+    Ok(template)
+}
+
 /// Install a template for the current user
 #[tauri::command]
 pub async fn install_template(
@@ -65,10 +85,27 @@ pub async fn install_template(
     let mgr = manager.manager.lock().map_err(|e| e.to_string())?;
     // For now, we use a default user_id. In production, this would come from auth
     let user_id = "default_user";
+    require_ready_template(&mgr, user_id, &template_id)?;
     mgr.install_template(user_id, &template_id)
         .map_err(|e| e.to_string())
 }
 
+/// Report which declared integrations, tools and permissions are missing
+#[tauri::command]
+pub async fn template_check_requirements(
+    template_id: String,
+    manager: State<'_, TemplateManagerState>,
+) -> Result<RequirementsReport, String> {
+    let mgr = manager.manager.lock().map_err(|e| e.to_string())?;
+    let user_id = "default_user";
+    let template = mgr
+        .get_template_by_id(&template_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Template not found: {}", template_id))?;
+    mgr.check_requirements(user_id, &template)
+        .map_err(|e| format!("Failed to check template requirements: {}", e))
+}
+
 /// Get installed templates for the current user
 #[tauri::command]
 pub async fn get_installed_templates(
@@ -98,12 +135,9 @@ pub async fn execute_template(
     _params: HashMap<String, String>,
     manager: State<'_, TemplateManagerState>,
 ) -> Result<String, String> {
-    // Get template
+    // Get template, refusing to start if an integration is not connected
     let mgr = manager.manager.lock().map_err(|e| e.to_string())?;
-    let template = mgr
-        .get_template_by_id(&template_id)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Template not found: {}", template_id))?;
+    let template = require_ready_template(&mgr, "default_user", &template_id)?;
 
     // In a real implementation, this would:
     // 1. Create an AGI goal from the template
@@ -154,18 +188,40 @@ pub async fn get_template_categories() -> Result<Vec<String>, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rusqlite::Connection;
+
+    fn migrated_pool() -> Pool {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        pool
+    }
 
     #[test]
     fn test_initialize_template_manager() {
-        let conn = Connection::open_in_memory().unwrap();
-        crate::db::migrations::run_migrations(&conn).unwrap();
-
-        let db = Arc::new(Mutex::new(conn));
-        let manager = initialize_template_manager(db);
+        let manager = initialize_template_manager(migrated_pool());
 
         // Verify templates were loaded
         let templates = manager.get_all_templates().unwrap();
         assert!(templates.len() > 0, "Should have loaded builtin templates");
     }
+
+    #[test]
+    fn test_install_requires_connected_integration() {
+        let db = migrated_pool();
+        let manager = initialize_template_manager(db.clone());
+
+        let err =
+            require_ready_template(&manager, "default_user", "email-management-agent").unwrap_err();
+        assert!(err.contains("missing integration 'email'"), "{}", err);
+
+        db.get()
+            .unwrap()
+            .execute(
+                "INSERT INTO email_accounts
+                 (provider, email, imap_host, imap_port, smtp_host, smtp_port, password_encrypted, created_at)
+                 VALUES ('gmail', 'me@example.com', 'imap', 993, 'smtp', 465, 'x', 0)",
+                [],
+            )
+            .unwrap();
+        assert!(require_ready_template(&manager, "default_user", "email-management-agent").is_ok());
+    }
 }
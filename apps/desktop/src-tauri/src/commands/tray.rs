@@ -1,7 +1,20 @@
+use crate::tray::{self, TrayStatus};
 use tauri::AppHandle;
 
 #[tauri::command]
-pub fn tray_set_unread_badge(_app: AppHandle, count: u32) -> Result<(), String> {
-    ::tracing::debug!(count, "[tray] placeholder unread badge update request");
+pub fn tray_set_unread_badge(app: AppHandle, count: u32) -> Result<(), String> {
+    tray::update_tray(&app, |status| status.unread = count);
     Ok(())
 }
+
+#[tauri::command]
+pub fn tray_get_status(app: AppHandle) -> Result<TrayStatus, String> {
+    Ok(tray::tray_status(&app))
+}
+
+/// Pause or resume every agent and background task, as the tray menu does
+#[tauri::command]
+pub async fn tray_set_paused(app: AppHandle, paused: bool) -> Result<TrayStatus, String> {
+    tray::set_automation_paused(&app, paused).await;
+    Ok(tray::tray_status(&app))
+}
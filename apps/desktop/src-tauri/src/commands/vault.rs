@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use tauri::State;
+
+use crate::security::vault::{SecretId, SecretInfo, SecretNamespace, Vault, VaultAccess};
+
+const DEFAULT_ACCESS_LOG_LIMIT: usize = 200;
+
+/// Stored secrets without their values, optionally for one namespace
+#[tauri::command]
+pub async fn vault_list_secrets(
+    vault: State<'_, Arc<Vault>>,
+    namespace: Option<SecretNamespace>,
+) -> Result<Vec<SecretInfo>, String> {
+    let vault = vault.inner().clone();
+    tokio::task::spawn_blocking(move || vault.list(namespace))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to list secrets: {}", e))
+}
+
+/// Replace a secret with a new value, e.g. after regenerating an API key.
+/// Returns the new version.
+#[tauri::command]
+pub async fn vault_rotate_secret(
+    vault: State<'_, Arc<Vault>>,
+    id: SecretId,
+    value: String,
+) -> Result<u32, String> {
+    if value.trim().is_empty() {
+        return Err("Secret value cannot be empty".to_string());
+    }
+    let vault = vault.inner().clone();
+    tokio::task::spawn_blocking(move || vault.rotate(&id, value.trim(), "user"))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to rotate secret: {}", e))
+}
+
+#[tauri::command]
+pub async fn vault_delete_secret(
+    vault: State<'_, Arc<Vault>>,
+    id: SecretId,
+) -> Result<bool, String> {
+    let vault = vault.inner().clone();
+    tokio::task::spawn_blocking(move || vault.delete(&id, "user"))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to delete secret: {}", e))
+}
+
+/// Recent reads and changes, newest first, optionally for one secret
+#[tauri::command]
+pub async fn vault_access_log(
+    vault: State<'_, Arc<Vault>>,
+    id: Option<SecretId>,
+    limit: Option<usize>,
+) -> Result<Vec<VaultAccess>, String> {
+    let vault = vault.inner().clone();
+    let limit = limit.unwrap_or(DEFAULT_ACCESS_LOG_LIMIT);
+    tokio::task::spawn_blocking(move || vault.access_log(id.as_ref(), limit))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to read vault access log: {}", e))
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle};
+
+use crate::commands::project_collections::add_web_page;
+use crate::error::{Error, Result};
+use crate::projects::CollectionSource;
+use crate::web::{fetch_page, FetchOptions, WebPage};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebFetchResult {
+    pub page: WebPage,
+    /// The collection source the page was indexed as, when one was asked for
+    pub source: Option<CollectionSource>,
+}
+
+/// Fetch a web page as Markdown, optionally indexing it into a project
+/// collection so later answers can cite it
+#[command]
+pub async fn web_fetch(
+    app: AppHandle,
+    url: String,
+    options: Option<FetchOptions>,
+    collection_id: Option<String>,
+) -> Result<WebFetchResult> {
+    fetch_and_index(&app, &url, &options.unwrap_or_default(), collection_id).await
+}
+
+/// Shared by the command and the `web_fetch` agent tool
+pub async fn fetch_and_index(
+    app: &AppHandle,
+    url: &str,
+    options: &FetchOptions,
+    collection_id: Option<String>,
+) -> Result<WebFetchResult> {
+    let page = fetch_page(url, options)
+        .await
+        .map_err(|e| Error::Generic(format!("Failed to fetch {}: {:#}", url, e)))?;
+    let source = match collection_id.filter(|id| !id.trim().is_empty()) {
+        Some(collection_id) => Some(add_web_page(app, collection_id, &page).await?),
+        None => None,
+    };
+    Ok(WebFetchResult { page, source })
+}
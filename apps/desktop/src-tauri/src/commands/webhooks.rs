@@ -0,0 +1,73 @@
+use super::WorkflowEngineState;
+use crate::orchestration::{
+    WebhookConfig, WebhookDelivery, WebhookEndpoint, WebhookEndpointCreated, WebhookEndpointInput,
+    WebhookStatus,
+};
+use tauri::State;
+
+const DEFAULT_DELIVERY_LIMIT: usize = 50;
+
+/// Listener settings, where it is listening, and the webhook endpoints
+#[tauri::command]
+pub async fn webhook_status(
+    state: State<'_, WorkflowEngineState>,
+) -> Result<WebhookStatus, String> {
+    state.triggers.webhook_status().await
+}
+
+/// Move the webhook listener to another port or interface
+#[tauri::command]
+pub async fn webhook_configure(
+    state: State<'_, WorkflowEngineState>,
+    config: WebhookConfig,
+) -> Result<WebhookStatus, String> {
+    state.triggers.configure_webhooks(config).await
+}
+
+/// Add a webhook trigger. Its signing secret is only returned here
+#[tauri::command]
+pub fn webhook_create_endpoint(
+    state: State<WorkflowEngineState>,
+    input: WebhookEndpointInput,
+) -> Result<WebhookEndpointCreated, String> {
+    state.triggers.create_webhook_endpoint(input)
+}
+
+#[tauri::command]
+pub fn webhook_update_endpoint(
+    state: State<WorkflowEngineState>,
+    id: String,
+    input: WebhookEndpointInput,
+) -> Result<WebhookEndpoint, String> {
+    state.triggers.update_webhook_endpoint(&id, input)
+}
+
+#[tauri::command]
+pub fn webhook_delete_endpoint(
+    state: State<WorkflowEngineState>,
+    id: String,
+) -> Result<bool, String> {
+    state.triggers.delete_webhook_endpoint(&id)
+}
+
+/// Recent deliveries, newest first, of one endpoint or all of them
+#[tauri::command]
+pub fn webhook_list_deliveries(
+    state: State<WorkflowEngineState>,
+    endpoint_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    state.triggers.deliveries(
+        endpoint_id.as_deref(),
+        limit.unwrap_or(DEFAULT_DELIVERY_LIMIT),
+    )
+}
+
+/// Start a verified delivery's workflow or task again
+#[tauri::command]
+pub fn webhook_replay_delivery(
+    state: State<WorkflowEngineState>,
+    delivery_id: String,
+) -> Result<WebhookDelivery, String> {
+    state.triggers.replay(&delivery_id)
+}
@@ -63,21 +63,33 @@ pub fn window_set_visibility(app: AppHandle, visible: bool) -> Result<(), String
     .map_err(|err| err.to_string())
 }
 
+/// Dock to `position`, optionally with a width (left/right) or height
+/// (top/bottom) and a target monitor from `window_list_monitors`. Omitted
+/// values reuse the last ones chosen. `None` undocks.
 #[tauri::command]
 pub fn window_dock(
     app: AppHandle,
     state: State<AppState>,
     position: Option<DockPosition>,
+    size: Option<f64>,
+    monitor: Option<String>,
 ) -> Result<(), String> {
     let window = main_window(&app)?;
     match position {
         Some(position) => {
-            window::apply_dock(&window, &state, position).map_err(|err| err.to_string())
+            let options = window::DockOptions { size, monitor };
+            window::apply_dock(&window, &state, position, options).map_err(|err| err.to_string())
         }
         None => window::undock(&window, &state).map_err(|err| err.to_string()),
     }
 }
 
+#[tauri::command]
+pub fn window_list_monitors(app: AppHandle) -> Result<Vec<window::MonitorInfo>, String> {
+    let window = main_window(&app)?;
+    window::list_monitors(&window).map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub fn window_is_maximized(app: AppHandle) -> Result<bool, String> {
     let window = main_window(&app)?;
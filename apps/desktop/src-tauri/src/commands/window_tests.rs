@@ -17,6 +17,8 @@ mod tests {
             previous_geometry: None,
             maximized: false,
             fullscreen: false,
+            dock_size: None,
+            dock_monitor: None,
         };
 
         let temp_dir = std::env::temp_dir();
@@ -6,9 +6,20 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 use tokio::sync::Mutex;
 
+use crate::codebase::dependency_graph::{ImpactAnalysis, ImpactedFile, ImportGraph};
+use crate::codebase::dependency_scan::{
+    self, AdvisoryDatabase, AdvisoryDatabaseStatus, DependencyScanReport, LicensePolicy,
+};
+use crate::codebase::project_tasks::{self, ProjectTask, TaskRun};
+use crate::codebase::{DependencyGraphState, TaskRunner};
+use crate::commands::FileWatcherState;
+use crate::filesystem::FileWatcher;
+use crate::terminal::env::{EnvSetOptions, EnvVarInfo};
+use crate::terminal::WorkspaceEnvManager;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceIndex {
     pub root_path: PathBuf,
@@ -127,6 +138,9 @@ impl Default for WorkspaceIndexState {
 pub async fn workspace_index(
     workspace_path: PathBuf,
     state: State<'_, Arc<Mutex<WorkspaceIndexState>>>,
+    graph_state: State<'_, DependencyGraphState>,
+    watcher_state: State<'_, FileWatcherState>,
+    app_handle: AppHandle,
 ) -> Result<WorkspaceIndex, String> {
     tracing::info!("Indexing workspace: {:?}", workspace_path);
 
@@ -147,6 +161,16 @@ pub async fn workspace_index(
     let mut current_index = workspace_state.index.lock().await;
     *current_index = Some(index.clone());
 
+    // Build the live dependency graph and keep it current from file events
+    let root = workspace_path.clone();
+    let graph = tokio::task::spawn_blocking(move || ImportGraph::build(&root))
+        .await
+        .map_err(|e| format!("Dependency graph task failed: {}", e))?;
+    *graph_state.0.write() = Some(graph);
+    if let Err(e) = watch_workspace(&workspace_path, &watcher_state, app_handle) {
+        tracing::warn!("Dependency graph will not update automatically: {}", e);
+    }
+
     // Mark indexing complete
     let mut indexing = workspace_state.indexing.lock().await;
     *indexing = false;
@@ -270,7 +294,13 @@ pub async fn workspace_find_references(
 #[tauri::command]
 pub async fn workspace_get_dependencies(
     state: State<'_, Arc<Mutex<WorkspaceIndexState>>>,
+    graph_state: State<'_, DependencyGraphState>,
 ) -> Result<DependencyGraph, String> {
+    // Prefer the live graph, whose edges point at resolved workspace files
+    if let Some(graph) = graph_state.0.read().as_ref() {
+        return Ok(live_dependency_graph(graph));
+    }
+
     let workspace_state = state.lock().await;
     let index = workspace_state.index.lock().await;
 
@@ -331,6 +361,211 @@ pub async fn workspace_get_stats(
     })
 }
 
+/// Report what a change to `path` can break, for agents planning a refactor
+#[tauri::command]
+pub async fn workspace_impact_analysis(
+    path: PathBuf,
+    graph_state: State<'_, DependencyGraphState>,
+) -> Result<ImpactAnalysis, String> {
+    let graph = graph_state.0.read();
+    let graph = graph.as_ref().ok_or("Workspace not indexed")?;
+
+    Ok(graph.impact(&path))
+}
+
+/// Get files importing `path`, optionally through other files
+#[tauri::command]
+pub async fn workspace_get_dependents(
+    path: PathBuf,
+    transitive: Option<bool>,
+    graph_state: State<'_, DependencyGraphState>,
+) -> Result<Vec<ImpactedFile>, String> {
+    let graph = graph_state.0.read();
+    let graph = graph.as_ref().ok_or("Workspace not indexed")?;
+
+    Ok(graph.dependents(&path, transitive.unwrap_or(false)))
+}
+
+/// Get import cycles in the workspace
+#[tauri::command]
+pub async fn workspace_find_cycles(
+    graph_state: State<'_, DependencyGraphState>,
+) -> Result<Vec<Vec<PathBuf>>, String> {
+    let graph = graph_state.0.read();
+    let graph = graph.as_ref().ok_or("Workspace not indexed")?;
+
+    Ok(graph.cycles())
+}
+
+/// Scan the workspace's dependencies for known vulnerabilities and license
+/// policy violations. Works offline against the local advisory database
+#[tauri::command]
+pub async fn workspace_scan_dependencies(
+    workspace_path: PathBuf,
+    policy: Option<LicensePolicy>,
+) -> Result<DependencyScanReport, String> {
+    tracing::info!("Scanning dependencies of {:?}", workspace_path);
+    let policy = policy.unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || dependency_scan::scan_workspace(&workspace_path, &policy))
+        .await
+        .map_err(|e| format!("Dependency scan task failed: {}", e))?
+        .map_err(|e| format!("Dependency scan failed: {}", e))
+}
+
+/// Refresh the local advisory database. Imports `source` (an OSV JSON file or
+/// zip export) when given, otherwise fetches advisories for the workspace's
+/// packages from osv.dev
+#[tauri::command]
+pub async fn workspace_refresh_advisories(
+    workspace_path: Option<PathBuf>,
+    source: Option<PathBuf>,
+) -> Result<AdvisoryDatabaseStatus, String> {
+    let db_path = AdvisoryDatabase::default_path().map_err(|e| e.to_string())?;
+
+    let advisories = match (source, workspace_path) {
+        (Some(source), _) => {
+            tokio::task::spawn_blocking(move || dependency_scan::import_advisories(&source))
+                .await
+                .map_err(|e| format!("Advisory import task failed: {}", e))?
+                .map_err(|e| format!("Failed to import advisories: {}", e))?
+        }
+        (None, Some(workspace_path)) => {
+            let dependencies = tokio::task::spawn_blocking(move || {
+                dependency_scan::collect_dependencies(&workspace_path, &mut Vec::new())
+            })
+            .await
+            .map_err(|e| format!("Dependency scan task failed: {}", e))?
+            .map_err(|e| e.to_string())?
+            .0;
+            dependency_scan::fetch_osv_advisories(&reqwest::Client::new(), &dependencies)
+                .await
+                .map_err(|e| format!("Failed to fetch advisories from OSV: {}", e))?
+        }
+        (None, None) => return Err("Provide a workspace path or an advisory file".to_string()),
+    };
+
+    let mut database = AdvisoryDatabase::load(&db_path).map_err(|e| e.to_string())?;
+    let refreshed = database.merge(advisories);
+    database.save(&db_path).map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Advisory database refreshed with {} advisories ({} total)",
+        refreshed,
+        database.advisories.len()
+    );
+
+    Ok(database.status(&db_path, refreshed))
+}
+
+/// Build, test and run tasks the workspace defines (Cargo, npm scripts,
+/// Makefile targets, Gradle)
+#[tauri::command]
+pub async fn project_detect_tasks(workspace_path: PathBuf) -> Result<Vec<ProjectTask>, String> {
+    tokio::task::spawn_blocking(move || project_tasks::detect_tasks(&workspace_path))
+        .await
+        .map_err(|e| format!("Task detection failed: {}", e))?
+        .map_err(|e| format!("Failed to detect tasks: {}", e))
+}
+
+/// Run a detected task by name, or the first task of a kind (`build`, `test`,
+/// `run`, `lint`). Output streams as `project://task-output` events and the
+/// call resolves when the task exits
+#[tauri::command]
+pub async fn project_run_task(
+    app: AppHandle,
+    workspace_path: PathBuf,
+    name: String,
+    timeout_secs: Option<u64>,
+    runner: State<'_, TaskRunner>,
+) -> Result<TaskRun, String> {
+    let root = workspace_path.clone();
+    let tasks = tokio::task::spawn_blocking(move || project_tasks::detect_tasks(&root))
+        .await
+        .map_err(|e| format!("Task detection failed: {}", e))?
+        .map_err(|e| format!("Failed to detect tasks: {}", e))?;
+    let task = project_tasks::resolve_task(&tasks, &name)
+        .cloned()
+        .ok_or_else(|| format!("No task named {} in {:?}", name, workspace_path))?;
+
+    runner
+        .run(
+            &app,
+            &workspace_path,
+            task,
+            timeout_secs.map(std::time::Duration::from_secs),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Stop a running task; returns whether it was running
+#[tauri::command]
+pub async fn project_cancel_task(
+    run_id: String,
+    runner: State<'_, TaskRunner>,
+) -> Result<bool, String> {
+    Ok(runner.cancel(&run_id))
+}
+
+/// List the variables defined by the workspace's `.env` files. Secret values
+/// are always masked
+#[tauri::command]
+pub async fn workspace_env_list(
+    workspace_path: PathBuf,
+    manager: State<'_, WorkspaceEnvManager>,
+) -> Result<Vec<EnvVarInfo>, String> {
+    manager
+        .list(&workspace_path)
+        .map_err(|e| format!("Failed to read workspace env: {}", e))
+}
+
+/// Look up one variable; secret values are only returned when `reveal` is set
+#[tauri::command]
+pub async fn workspace_env_get(
+    workspace_path: PathBuf,
+    key: String,
+    reveal: Option<bool>,
+    manager: State<'_, WorkspaceEnvManager>,
+) -> Result<Option<EnvVarInfo>, String> {
+    let reveal = reveal.unwrap_or(false);
+    if reveal {
+        tracing::info!("Revealing env var {} for {:?}", key, workspace_path);
+    }
+    manager
+        .get(&workspace_path, &key, reveal)
+        .map_err(|e| format!("Failed to read workspace env: {}", e))
+}
+
+/// Set a variable, or remove it when `value` is null. Secret values are kept
+/// in the secret store rather than written to the env file
+#[tauri::command]
+pub async fn workspace_env_set(
+    workspace_path: PathBuf,
+    key: String,
+    value: Option<String>,
+    options: Option<EnvSetOptions>,
+    manager: State<'_, WorkspaceEnvManager>,
+) -> Result<Option<EnvVarInfo>, String> {
+    tracing::info!(
+        "{} env var {} for {:?}",
+        if value.is_some() {
+            "Setting"
+        } else {
+            "Removing"
+        },
+        key,
+        workspace_path
+    );
+    manager
+        .set(
+            &workspace_path,
+            &key,
+            value.as_deref(),
+            options.unwrap_or_default(),
+        )
+        .map_err(|e| format!("Failed to update workspace env: {}", e))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceStats {
     pub total_files: usize,
@@ -342,6 +577,50 @@ pub struct WorkspaceStats {
 
 // Helper functions
 
+fn watch_workspace(
+    workspace_path: &std::path::Path,
+    watcher_state: &FileWatcherState,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut watcher = watcher_state
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to lock watcher: {}", e))?;
+    if watcher.is_none() {
+        *watcher = Some(FileWatcher::new(app_handle)?);
+    }
+    match watcher.as_mut() {
+        Some(watcher) => watcher.watch(&workspace_path.to_string_lossy(), true),
+        None => Err("File watcher not initialized".to_string()),
+    }
+}
+
+fn live_dependency_graph(graph: &ImportGraph) -> DependencyGraph {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+
+    for file in graph.files() {
+        let node_id = file.path.to_string_lossy().to_string();
+        nodes.push(DependencyNode {
+            id: node_id.clone(),
+            file_path: file.path.clone(),
+            module_name: file
+                .path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+        });
+        edges.extend(file.dependencies.iter().map(|dependency| DependencyEdge {
+            from: node_id.clone(),
+            to: dependency.to_string_lossy().to_string(),
+            edge_type: DependencyType::Import,
+        }));
+    }
+
+    DependencyGraph { nodes, edges }
+}
+
 async fn build_workspace_index(workspace_path: &PathBuf) -> Result<WorkspaceIndex, String> {
     let mut files = Vec::new();
     let mut all_symbols = Vec::new();
@@ -0,0 +1,505 @@
+//! Persistent queue for outgoing mail.
+//!
+//! `email_send` records each message here and a background worker delivers
+//! it once its `send_at` time has passed, so scheduled mail and retries
+//! survive restarts. Transient SMTP failures are retried with exponential
+//! backoff up to [`MAX_ATTEMPTS`] times; permanent ones fail the entry
+//! straight away. A queued entry can be cancelled until the worker claims it.
+//! Every status change is emitted as [`OUTBOX_EVENT`].
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::commands::email::SendEmailRequest;
+use crate::error::retry::BackoffStrategy;
+use crate::error::{Categorizable, Error, Result};
+
+/// Emitted with an [`OutboxEvent`] whenever an entry changes status
+pub const OUTBOX_EVENT: &str = "email://outbox";
+/// Delivery attempts before an entry is marked failed
+pub const MAX_ATTEMPTS: i64 = 5;
+
+const RETRY_BACKOFF: BackoffStrategy = BackoffStrategy::ExponentialWithJitter {
+    base: Duration::from_secs(30),
+    max: Duration::from_secs(60 * 60),
+};
+/// Longest the worker sleeps before checking the queue again
+const MAX_IDLE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    /// Waiting for `next_attempt_at`
+    Queued,
+    Sending,
+    Sent,
+    /// Out of attempts, or rejected by the server
+    Failed,
+    Cancelled,
+}
+
+impl OutboxStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            OutboxStatus::Queued => "queued",
+            OutboxStatus::Sending => "sending",
+            OutboxStatus::Sent => "sent",
+            OutboxStatus::Failed => "failed",
+            OutboxStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "sending" => OutboxStatus::Sending,
+            "sent" => OutboxStatus::Sent,
+            "failed" => OutboxStatus::Failed,
+            "cancelled" => OutboxStatus::Cancelled,
+            _ => OutboxStatus::Queued,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub account_id: i64,
+    pub request: SendEmailRequest,
+    pub status: OutboxStatus,
+    pub send_at: i64,
+    pub next_attempt_at: i64,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    /// Set once the server accepted the message
+    pub message_id: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub sent_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboxEvent {
+    pub id: String,
+    pub account_id: i64,
+    pub subject: String,
+    pub status: OutboxStatus,
+    pub attempts: i64,
+    /// When the next attempt is due, for queued entries
+    pub next_attempt_at: Option<i64>,
+    pub error: Option<String>,
+    pub message_id: Option<String>,
+}
+
+impl From<&OutboxEntry> for OutboxEvent {
+    fn from(entry: &OutboxEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            account_id: entry.account_id,
+            subject: entry.request.subject.clone(),
+            status: entry.status,
+            attempts: entry.attempts,
+            next_attempt_at: (entry.status == OutboxStatus::Queued)
+                .then_some(entry.next_attempt_at),
+            error: entry.last_error.clone(),
+            message_id: entry.message_id.clone(),
+        }
+    }
+}
+
+/// Owns the delivery worker
+#[derive(Default)]
+pub struct EmailOutbox {
+    wake: Arc<Notify>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl EmailOutbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the delivery worker. Entries a previous run left mid-send are
+    /// queued again, since it is unknown whether the server accepted them
+    pub fn start(&self, app: &AppHandle) -> Result<()> {
+        let conn = crate::commands::email::open_connection(app)?;
+        let requeued = requeue_interrupted(&conn)?;
+        if requeued > 0 {
+            warn!("Re-queued {} emails interrupted while sending", requeued);
+        }
+
+        let handle = tauri::async_runtime::spawn(run_worker(app.clone(), Arc::clone(&self.wake)));
+        if let Some(previous) = self.worker.lock().replace(handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Check the queue now instead of at the next scheduled time
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+}
+
+async fn run_worker(app: AppHandle, wake: Arc<Notify>) {
+    loop {
+        if let Err(e) = deliver_due(&app).await {
+            warn!("Email outbox worker failed: {}", e);
+        }
+
+        let delay =
+            match crate::commands::email::open_connection(&app).and_then(|conn| next_due(&conn)) {
+                Ok(Some(due)) => {
+                    let seconds = (due - Utc::now().timestamp()).max(0) as u64;
+                    Duration::from_secs(seconds).min(MAX_IDLE)
+                }
+                Ok(None) | Err(_) => MAX_IDLE,
+            };
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = wake.notified() => {}
+        }
+    }
+}
+
+/// Send every entry that is due, one at a time
+async fn deliver_due(app: &AppHandle) -> Result<()> {
+    let due = {
+        let conn = crate::commands::email::open_connection(app)?;
+        due_ids(&conn, Utc::now().timestamp())?
+    };
+
+    for id in due {
+        let entry = {
+            let conn = crate::commands::email::open_connection(app)?;
+            // Skip entries cancelled since the query
+            if !claim(&conn, &id)? {
+                continue;
+            }
+            get_entry(&conn, &id)?
+        };
+        let Some(entry) = entry else {
+            continue;
+        };
+        emit(app, &entry);
+
+        let result = crate::commands::email::deliver(app, &entry.request).await;
+        let conn = crate::commands::email::open_connection(app)?;
+        match result {
+            Ok(message_id) => {
+                info!("Delivered queued email {}", id);
+                mark_sent(&conn, &id, &message_id)?;
+            }
+            Err(e) => {
+                let status = mark_attempt_failed(&conn, &entry, &e)?;
+                warn!(
+                    "Delivery of queued email {} failed (attempt {}): {}; now {:?}",
+                    id, entry.attempts, e, status
+                );
+            }
+        }
+        if let Some(entry) = get_entry(&conn, &id)? {
+            emit(app, &entry);
+        }
+    }
+    Ok(())
+}
+
+pub fn emit(app: &AppHandle, entry: &OutboxEntry) {
+    if let Err(e) = app.emit(OUTBOX_EVENT, OutboxEvent::from(entry)) {
+        warn!("Failed to emit outbox event: {}", e);
+    }
+}
+
+/// Queue a message for `request.send_at`, or right away
+pub fn enqueue(conn: &Connection, request: &SendEmailRequest) -> Result<OutboxEntry> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+    let send_at = request.send_at.unwrap_or(now);
+
+    conn.execute(
+        "INSERT INTO email_outbox (id, account_id, request, status, send_at, next_attempt_at,
+                                   created_at, updated_at)
+         VALUES (?1, ?2, ?3, 'queued', ?4, ?4, ?5, ?5)",
+        params![
+            id,
+            request.account_id,
+            serde_json::to_string(request)?,
+            send_at,
+            now
+        ],
+    )?;
+
+    get_entry(conn, &id)?.ok_or_else(|| Error::Generic("Email was not queued".to_string()))
+}
+
+pub fn get_entry(conn: &Connection, id: &str) -> Result<Option<OutboxEntry>> {
+    let entry = conn
+        .query_row(
+            "SELECT id, account_id, request, status, send_at, next_attempt_at, attempts,
+                    last_error, message_id, created_at, updated_at, sent_at
+             FROM email_outbox WHERE id = ?1",
+            [id],
+            map_entry_row,
+        )
+        .optional()?;
+    Ok(entry)
+}
+
+/// Newest entries first, optionally for one account or in one status
+pub fn list_entries(
+    conn: &Connection,
+    account_id: Option<i64>,
+    status: Option<OutboxStatus>,
+    limit: usize,
+) -> Result<Vec<OutboxEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account_id, request, status, send_at, next_attempt_at, attempts,
+                last_error, message_id, created_at, updated_at, sent_at
+         FROM email_outbox
+         WHERE (?1 IS NULL OR account_id = ?1) AND (?2 IS NULL OR status = ?2)
+         ORDER BY created_at DESC
+         LIMIT ?3",
+    )?;
+    let entries = stmt
+        .query_map(
+            params![account_id, status.map(OutboxStatus::as_str), limit as i64],
+            map_entry_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+/// Cancel a queued entry; returns false once it is being sent or finished
+pub fn cancel(conn: &Connection, id: &str) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE email_outbox SET status = 'cancelled', updated_at = ?2
+         WHERE id = ?1 AND status = 'queued'",
+        params![id, Utc::now().timestamp()],
+    )?;
+    Ok(changed > 0)
+}
+
+fn due_ids(conn: &Connection, now: i64) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM email_outbox
+         WHERE status = 'queued' AND next_attempt_at <= ?1
+         ORDER BY next_attempt_at, created_at",
+    )?;
+    let ids = stmt
+        .query_map([now], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(ids)
+}
+
+fn next_due(conn: &Connection) -> Result<Option<i64>> {
+    Ok(conn.query_row(
+        "SELECT MIN(next_attempt_at) FROM email_outbox WHERE status = 'queued'",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Move a queued entry to sending and count the attempt. Returns false if
+/// it is no longer queued
+fn claim(conn: &Connection, id: &str) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE email_outbox SET status = 'sending', attempts = attempts + 1, updated_at = ?2
+         WHERE id = ?1 AND status = 'queued'",
+        params![id, Utc::now().timestamp()],
+    )?;
+    Ok(changed > 0)
+}
+
+fn mark_sent(conn: &Connection, id: &str, message_id: &str) -> Result<()> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "UPDATE email_outbox
+         SET status = 'sent', message_id = ?2, last_error = NULL, sent_at = ?3, updated_at = ?3
+         WHERE id = ?1",
+        params![id, message_id, now],
+    )?;
+    Ok(())
+}
+
+/// Schedule a retry for transient errors with attempts left, otherwise fail
+/// the entry. `entry` is the claimed entry, with this attempt counted
+fn mark_attempt_failed(
+    conn: &Connection,
+    entry: &OutboxEntry,
+    error: &Error,
+) -> Result<OutboxStatus> {
+    let now = Utc::now().timestamp();
+    let status = if error.is_retryable() && entry.attempts < MAX_ATTEMPTS {
+        OutboxStatus::Queued
+    } else {
+        OutboxStatus::Failed
+    };
+    let retry_delay = RETRY_BACKOFF.calculate((entry.attempts - 1).max(0) as u32);
+
+    conn.execute(
+        "UPDATE email_outbox
+         SET status = ?2, last_error = ?3, next_attempt_at = ?4, updated_at = ?5
+         WHERE id = ?1",
+        params![
+            entry.id,
+            status.as_str(),
+            error.to_string(),
+            now + retry_delay.as_secs() as i64,
+            now
+        ],
+    )?;
+    Ok(status)
+}
+
+fn requeue_interrupted(conn: &Connection) -> Result<usize> {
+    Ok(conn.execute(
+        "UPDATE email_outbox SET status = 'queued', updated_at = ?1 WHERE status = 'sending'",
+        [Utc::now().timestamp()],
+    )?)
+}
+
+fn map_entry_row(row: &Row<'_>) -> rusqlite::Result<OutboxEntry> {
+    let request: String = row.get(2)?;
+    let request = serde_json::from_str(&request).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    let status: String = row.get(3)?;
+
+    Ok(OutboxEntry {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        request,
+        status: OutboxStatus::parse(&status),
+        send_at: row.get(4)?,
+        next_attempt_at: row.get(5)?,
+        attempts: row.get(6)?,
+        last_error: row.get(7)?,
+        message_id: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        sent_at: row.get(11)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE email_outbox (
+                id TEXT PRIMARY KEY,
+                account_id INTEGER NOT NULL,
+                request TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'queued',
+                send_at INTEGER NOT NULL,
+                next_attempt_at INTEGER NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                message_id TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                sent_at INTEGER
+            );",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn request(send_at: Option<i64>) -> SendEmailRequest {
+        SendEmailRequest {
+            account_id: 1,
+            to: vec![crate::communications::EmailAddress::new(
+                "to@example.com".to_string(),
+                None,
+            )],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: None,
+            subject: "Hello".to_string(),
+            body_text: Some("Hi".to_string()),
+            body_html: None,
+            attachments: Vec::new(),
+            send_at,
+        }
+    }
+
+    #[test]
+    fn scheduled_entries_wait_and_can_be_cancelled() {
+        let conn = setup();
+        let now = Utc::now().timestamp();
+        let later = enqueue(&conn, &request(Some(now + 3600))).unwrap();
+        let soon = enqueue(&conn, &request(None)).unwrap();
+
+        assert_eq!(due_ids(&conn, now + 1).unwrap(), vec![soon.id.clone()]);
+        assert_eq!(next_due(&conn).unwrap(), Some(soon.next_attempt_at));
+
+        assert!(cancel(&conn, &later.id).unwrap());
+        assert!(claim(&conn, &soon.id).unwrap());
+        // Claimed entries are past the point of cancelling
+        assert!(!cancel(&conn, &soon.id).unwrap());
+        assert!(!claim(&conn, &later.id).unwrap());
+
+        mark_sent(&conn, &soon.id, "<id@example.com>").unwrap();
+        let sent = get_entry(&conn, &soon.id).unwrap().unwrap();
+        assert_eq!(sent.status, OutboxStatus::Sent);
+        assert_eq!(sent.attempts, 1);
+        assert_eq!(sent.request.subject, "Hello");
+        assert_eq!(next_due(&conn).unwrap(), None);
+    }
+
+    #[test]
+    fn transient_failures_retry_until_attempts_run_out() {
+        let conn = setup();
+        let entry = enqueue(&conn, &request(None)).unwrap();
+        let transient = Error::EmailSend("SMTP connection failed".to_string());
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let due = get_entry(&conn, &entry.id)
+                .unwrap()
+                .unwrap()
+                .next_attempt_at;
+            assert_eq!(due_ids(&conn, due).unwrap(), vec![entry.id.clone()]);
+            assert!(claim(&conn, &entry.id).unwrap());
+            let claimed = get_entry(&conn, &entry.id).unwrap().unwrap();
+            assert_eq!(claimed.attempts, attempt);
+
+            let status = mark_attempt_failed(&conn, &claimed, &transient).unwrap();
+            let expected = if attempt < MAX_ATTEMPTS {
+                OutboxStatus::Queued
+            } else {
+                OutboxStatus::Failed
+            };
+            assert_eq!(status, expected);
+        }
+
+        let retried = enqueue(&conn, &request(None)).unwrap();
+        assert!(claim(&conn, &retried.id).unwrap());
+        let claimed = get_entry(&conn, &retried.id).unwrap().unwrap();
+        let rejected = Error::Generic("SMTP server rejected the message".to_string());
+        assert_eq!(
+            mark_attempt_failed(&conn, &claimed, &rejected).unwrap(),
+            OutboxStatus::Failed
+        );
+
+        let interrupted = enqueue(&conn, &request(None)).unwrap();
+        assert!(claim(&conn, &interrupted.id).unwrap());
+        assert_eq!(requeue_interrupted(&conn).unwrap(), 1);
+        assert_eq!(
+            list_entries(&conn, Some(1), Some(OutboxStatus::Queued), 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
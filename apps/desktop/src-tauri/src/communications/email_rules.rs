@@ -0,0 +1,829 @@
+//! User-defined rules applied to incoming mail.
+//!
+//! A rule matches on the sender, a subject regex, attachments and optionally
+//! a category the LLM assigns to the message. The actions of every matching
+//! rule run in order: move, label, mark read, a templated auto-reply, a task
+//! in a productivity provider, or a notification. Background sync evaluates
+//! rules for each newly arrived message in `position` order; a rule with
+//! `stop_processing` set ends evaluation for that message.
+
+use chrono::Utc;
+use regex::RegexBuilder;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::imap_client::ImapClient;
+use super::{Email, EmailAddress};
+use crate::audit::Actor;
+use crate::error::{Error, Result};
+use crate::productivity::Provider;
+
+/// Emitted with a [`RuleMatchedEvent`] whenever a rule fires
+pub const RULE_MATCHED_EVENT: &str = "email://rule-matched";
+
+/// Body text sent to the classifier
+const CLASSIFY_BODY_CHARS: usize = 1500;
+/// Senders that must never get an auto-reply
+const NO_REPLY_MARKERS: &[&str] = &["noreply", "no-reply", "donotreply", "mailer-daemon"];
+
+/// Every set condition has to hold
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RuleConditions {
+    /// Case-insensitive substring of the sender's address or name
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Case-insensitive regex over the subject
+    #[serde(default)]
+    pub subject_regex: Option<String>,
+    #[serde(default)]
+    pub has_attachment: Option<bool>,
+    /// Category the LLM has to assign, e.g. "invoice" or "support request"
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+impl RuleConditions {
+    fn is_empty(&self) -> bool {
+        self.from.is_none()
+            && self.subject_regex.is_none()
+            && self.has_attachment.is_none()
+            && self.category.is_none()
+    }
+
+    /// Conditions that need no LLM call
+    pub fn matches_static(&self, email: &Email) -> bool {
+        if let Some(from) = &self.from {
+            let from = from.to_lowercase();
+            let name_matches = email
+                .from
+                .name
+                .as_ref()
+                .is_some_and(|name| name.to_lowercase().contains(&from));
+            if !name_matches && !email.from.email.to_lowercase().contains(&from) {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.subject_regex {
+            let matched = RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(|regex| regex.is_match(&email.subject))
+                .unwrap_or(false);
+            if !matched {
+                return false;
+            }
+        }
+
+        if let Some(has_attachment) = self.has_attachment {
+            if email.attachments.is_empty() == has_attachment {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    Move {
+        folder: String,
+    },
+    /// Adds an IMAP keyword
+    Label {
+        label: String,
+    },
+    MarkRead,
+    /// `subject` defaults to "Re: <subject>". Both accept `{{sender_name}}`,
+    /// `{{sender_email}}`, `{{subject}}` and `{{date}}`
+    AutoReply {
+        #[serde(default)]
+        subject: Option<String>,
+        body: String,
+    },
+    /// `account_id` may be omitted when only one account of `provider` is connected
+    CreateTask {
+        provider: Provider,
+        #[serde(default)]
+        account_id: Option<String>,
+        #[serde(default)]
+        project_id: Option<String>,
+    },
+    Notify {
+        #[serde(default)]
+        title: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailRule {
+    pub id: String,
+    /// `None` applies the rule to every account
+    pub account_id: Option<i64>,
+    pub name: String,
+    pub enabled: bool,
+    pub position: i64,
+    pub conditions: RuleConditions,
+    pub actions: Vec<RuleAction>,
+    pub stop_processing: bool,
+    pub match_count: i64,
+    pub last_matched_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Fields a user sets when creating or editing a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailRuleInput {
+    #[serde(default)]
+    pub account_id: Option<i64>,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Defaults to after the existing rules
+    #[serde(default)]
+    pub position: Option<i64>,
+    pub conditions: RuleConditions,
+    pub actions: Vec<RuleAction>,
+    #[serde(default)]
+    pub stop_processing: bool,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleMatchedEvent {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub account_id: i64,
+    pub email_id: String,
+    pub category: Option<String>,
+    /// Actions that failed, with the reason
+    pub errors: Vec<String>,
+}
+
+impl EmailRuleInput {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| Err(Error::Generic(message.to_string()));
+
+        if self.name.trim().is_empty() {
+            return invalid("Rule name is required");
+        }
+        if self.conditions.is_empty() {
+            return invalid("A rule needs at least one condition");
+        }
+        if self.actions.is_empty() {
+            return invalid("A rule needs at least one action");
+        }
+        if let Some(pattern) = &self.conditions.subject_regex {
+            if let Err(e) = RegexBuilder::new(pattern).build() {
+                return Err(Error::Generic(format!("Invalid subject regex: {}", e)));
+            }
+        }
+        if matches!(&self.conditions.category, Some(category) if category.trim().is_empty()) {
+            return invalid("Category is empty");
+        }
+
+        for action in &self.actions {
+            match action {
+                RuleAction::Move { folder } if folder.trim().is_empty() => {
+                    return invalid("Move needs a folder")
+                }
+                RuleAction::Label { label } if label.trim().is_empty() => {
+                    return invalid("Label is empty")
+                }
+                RuleAction::AutoReply { body, .. } if body.trim().is_empty() => {
+                    return invalid("Auto-reply needs a body")
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rules for `account_id` (including the ones for every account) in
+/// evaluation order, or all rules when `account_id` is `None`
+pub fn list_rules(conn: &Connection, account_id: Option<i64>) -> Result<Vec<EmailRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account_id, name, enabled, position, conditions, actions, stop_processing,
+                match_count, last_matched_at, created_at, updated_at
+         FROM email_rules
+         WHERE ?1 IS NULL OR account_id IS NULL OR account_id = ?1
+         ORDER BY position, created_at",
+    )?;
+    let rules = stmt
+        .query_map([account_id], map_rule_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rules)
+}
+
+pub fn get_rule(conn: &Connection, id: &str) -> Result<Option<EmailRule>> {
+    let rule = conn
+        .query_row(
+            "SELECT id, account_id, name, enabled, position, conditions, actions, stop_processing,
+                    match_count, last_matched_at, created_at, updated_at
+             FROM email_rules WHERE id = ?1",
+            [id],
+            map_rule_row,
+        )
+        .optional()?;
+    Ok(rule)
+}
+
+pub fn create_rule(conn: &Connection, input: EmailRuleInput) -> Result<EmailRule> {
+    input.validate()?;
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+    let position = match input.position {
+        Some(position) => position,
+        None => conn.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM email_rules",
+            [],
+            |row| row.get(0),
+        )?,
+    };
+
+    conn.execute(
+        "INSERT INTO email_rules (id, account_id, name, enabled, position, conditions, actions,
+                                  stop_processing, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
+        params![
+            id,
+            input.account_id,
+            input.name.trim(),
+            input.enabled,
+            position,
+            serde_json::to_string(&input.conditions)?,
+            serde_json::to_string(&input.actions)?,
+            input.stop_processing,
+            now,
+        ],
+    )?;
+
+    get_rule(conn, &id)?.ok_or_else(|| Error::Generic("Rule was not saved".to_string()))
+}
+
+pub fn update_rule(conn: &Connection, id: &str, input: EmailRuleInput) -> Result<EmailRule> {
+    input.validate()?;
+    let existing = get_rule(conn, id)?
+        .ok_or_else(|| Error::Generic(format!("Email rule {} not found", id)))?;
+
+    conn.execute(
+        "UPDATE email_rules
+         SET account_id = ?2, name = ?3, enabled = ?4, position = ?5, conditions = ?6,
+             actions = ?7, stop_processing = ?8, updated_at = ?9
+         WHERE id = ?1",
+        params![
+            id,
+            input.account_id,
+            input.name.trim(),
+            input.enabled,
+            input.position.unwrap_or(existing.position),
+            serde_json::to_string(&input.conditions)?,
+            serde_json::to_string(&input.actions)?,
+            input.stop_processing,
+            Utc::now().timestamp(),
+        ],
+    )?;
+
+    get_rule(conn, id)?.ok_or_else(|| Error::Generic("Rule was not saved".to_string()))
+}
+
+pub fn delete_rule(conn: &Connection, id: &str) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM email_rules WHERE id = ?1", [id])? > 0)
+}
+
+fn record_match(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE email_rules SET match_count = match_count + 1, last_matched_at = ?2 WHERE id = ?1",
+        params![id, Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+fn map_rule_row(row: &Row<'_>) -> rusqlite::Result<EmailRule> {
+    let json_column = |index: usize| -> rusqlite::Result<String> { row.get(index) };
+    let conditions = serde_json::from_str(&json_column(5)?).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    let actions = serde_json::from_str(&json_column(6)?).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok(EmailRule {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        name: row.get(2)?,
+        enabled: row.get(3)?,
+        position: row.get(4)?,
+        conditions,
+        actions,
+        stop_processing: row.get(7)?,
+        match_count: row.get(8)?,
+        last_matched_at: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+/// Cached messages that `input` would match, ignoring any category condition
+pub fn preview_matches(input: &EmailRuleInput, emails: Vec<Email>) -> Vec<Email> {
+    emails
+        .into_iter()
+        .filter(|email| input.conditions.matches_static(email))
+        .collect()
+}
+
+/// Run the account's enabled rules over newly arrived messages in the
+/// selected folder of `imap`. Action failures are logged and reported in the
+/// [`RULE_MATCHED_EVENT`]; only loading the rules can fail the call.
+/// Returns how many rules fired.
+pub async fn apply_rules(
+    app: &AppHandle,
+    imap: &mut ImapClient,
+    account_id: i64,
+    emails: &[Email],
+) -> Result<usize> {
+    if emails.is_empty() {
+        return Ok(0);
+    }
+    let rules: Vec<EmailRule> = {
+        let conn = crate::commands::email::open_connection(app)?;
+        list_rules(&conn, Some(account_id))?
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .collect()
+    };
+    if rules.is_empty() {
+        return Ok(0);
+    }
+
+    let mut categories: Vec<String> = rules
+        .iter()
+        .filter_map(|rule| rule.conditions.category.clone())
+        .collect();
+    categories.sort_by_key(|category| category.to_lowercase());
+    categories.dedup_by_key(|category| category.to_lowercase());
+
+    let mut fired = 0;
+    for email in emails {
+        // Classified at most once, and only when a rule needs it
+        let mut category: Option<Option<String>> = None;
+
+        for rule in &rules {
+            if !rule.conditions.matches_static(email) {
+                continue;
+            }
+            if let Some(wanted) = &rule.conditions.category {
+                if category.is_none() {
+                    category = Some(classify(app, email, &categories).await);
+                }
+                let assigned = category.as_ref().and_then(|c| c.as_deref());
+                if !assigned.is_some_and(|assigned| assigned.eq_ignore_ascii_case(wanted)) {
+                    continue;
+                }
+            }
+
+            info!("Email rule '{}' matched {}", rule.name, email.id);
+            let (errors, moved) = run_actions(app, imap, account_id, rule, email).await;
+            fired += 1;
+
+            if let Ok(conn) = crate::commands::email::open_connection(app) {
+                if let Err(e) = record_match(&conn, &rule.id) {
+                    warn!("Failed to record match for email rule {}: {}", rule.id, e);
+                }
+            }
+            let _ = app.emit(
+                RULE_MATCHED_EVENT,
+                RuleMatchedEvent {
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    account_id,
+                    email_id: email.id.clone(),
+                    category: category.clone().flatten(),
+                    errors,
+                },
+            );
+
+            // A moved message is no longer in this folder for later rules
+            if moved || rule.stop_processing {
+                break;
+            }
+        }
+    }
+    Ok(fired)
+}
+
+/// Run a rule's actions, moving last so the UID stays valid for the others.
+/// Returns the failures and whether the message was moved
+async fn run_actions(
+    app: &AppHandle,
+    imap: &mut ImapClient,
+    account_id: i64,
+    rule: &EmailRule,
+    email: &Email,
+) -> (Vec<String>, bool) {
+    let mut errors = Vec::new();
+    let mut moved = false;
+    let (moves, others): (Vec<&RuleAction>, Vec<&RuleAction>) = rule
+        .actions
+        .iter()
+        .partition(|action| matches!(action, RuleAction::Move { .. }));
+
+    for action in others.into_iter().chain(moves.into_iter().take(1)) {
+        let result = match action {
+            RuleAction::Move { folder } => match imap.move_email(email.uid, folder).await {
+                Ok(()) => {
+                    moved = true;
+                    // The message now has a new UID in the other folder
+                    crate::commands::email::open_connection(app).and_then(|conn| {
+                        conn.execute("DELETE FROM emails WHERE id = ?1", [&email.id])?;
+                        Ok(())
+                    })
+                }
+                Err(e) => Err(e),
+            },
+            RuleAction::Label { label } => imap.add_keyword(email.uid, label).await,
+            RuleAction::MarkRead => match imap.mark_as_read(email.uid, true).await {
+                Ok(()) => crate::commands::email::open_connection(app).and_then(|conn| {
+                    conn.execute("UPDATE emails SET is_read = 1 WHERE id = ?1", [&email.id])?;
+                    Ok(())
+                }),
+                Err(e) => Err(e),
+            },
+            RuleAction::AutoReply { subject, body } => {
+                auto_reply(app, account_id, email, subject.as_deref(), body).await
+            }
+            RuleAction::CreateTask {
+                provider,
+                account_id: task_account_id,
+                project_id,
+            } => {
+                create_task(
+                    app,
+                    email,
+                    provider,
+                    task_account_id.as_deref(),
+                    project_id.as_deref(),
+                )
+                .await
+            }
+            RuleAction::Notify { title } => {
+                use crate::notifications::{notify, Notification, NotificationCategory};
+
+                notify(
+                    app,
+                    Notification::new(
+                        NotificationCategory::EmailRule,
+                        title.clone().unwrap_or_else(|| rule.name.clone()),
+                        format!("{}: {}", email.from.format(), email.subject),
+                    )
+                    .with_target(email.id.clone()),
+                );
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            warn!(
+                "Email rule '{}' action {:?} failed for {}: {}",
+                rule.name, action, email.id, e
+            );
+            errors.push(e.to_string());
+        }
+    }
+    (errors, moved)
+}
+
+async fn auto_reply(
+    app: &AppHandle,
+    account_id: i64,
+    email: &Email,
+    subject: Option<&str>,
+    body: &str,
+) -> Result<()> {
+    let recipient = email.reply_to.clone().unwrap_or_else(|| email.from.clone());
+    if !should_auto_reply(&recipient, email) {
+        info!("Skipping auto-reply to {}", recipient.email);
+        return Ok(());
+    }
+
+    let subject = match subject {
+        Some(subject) => render_template(subject, email),
+        None if email.subject.to_lowercase().starts_with("re:") => email.subject.clone(),
+        None => format!("Re: {}", email.subject),
+    };
+    let request = crate::commands::email::SendEmailRequest {
+        account_id,
+        to: vec![recipient],
+        cc: Vec::new(),
+        bcc: Vec::new(),
+        reply_to: None,
+        subject,
+        body_text: Some(render_template(body, email)),
+        body_html: None,
+        attachments: Vec::new(),
+        send_at: None,
+    };
+    let queued = crate::commands::email::queue_email(app, &request);
+    crate::commands::email::audit_send(app, Actor::system(), &request, &queued);
+    queued.map(|_| ())
+}
+
+/// Never answer automated senders or our own messages, which would loop
+fn should_auto_reply(recipient: &EmailAddress, email: &Email) -> bool {
+    let address = recipient.email.to_lowercase();
+    let own = email
+        .to
+        .iter()
+        .any(|to| to.email.eq_ignore_ascii_case(&address));
+    !address.is_empty()
+        && !own
+        && !NO_REPLY_MARKERS
+            .iter()
+            .any(|marker| address.contains(marker))
+}
+
+async fn create_task(
+    app: &AppHandle,
+    email: &Email,
+    provider: &Provider,
+    account_id: Option<&str>,
+    project_id: Option<&str>,
+) -> Result<()> {
+    use crate::productivity::Task;
+
+    let state = app
+        .try_state::<crate::commands::ProductivityState>()
+        .ok_or_else(|| Error::Generic("Productivity integrations are not available".to_string()))?;
+
+    let body: String = email
+        .body_text
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .take(2000)
+        .collect();
+    let mut task = Task::new(String::new(), email.subject.clone()).with_description(format!(
+        "From: {}\n\n{}",
+        email.from.format(),
+        body
+    ));
+    task.project_id = project_id.map(str::to_string);
+
+    let manager = state.manager();
+    let account = manager.resolve_account(provider, account_id)?;
+    manager
+        .create_task(&account.account_id, task)
+        .await
+        .map(|_| ())
+        .map_err(|e| Error::Generic(format!("Failed to create task: {}", e)))
+}
+
+/// Ask the LLM which of `categories` the message belongs to
+async fn classify(app: &AppHandle, email: &Email, categories: &[String]) -> Option<String> {
+    let llm_state = app.try_state::<crate::commands::llm::LLMState>()?;
+    let body: String = email
+        .body_text
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .take(CLASSIFY_BODY_CHARS)
+        .collect();
+    let prompt = format!(
+        "Classify this email into exactly one of these categories: {}. \
+         Answer with the category name only, or \"none\" if none fits.\n\n\
+         From: {}\nSubject: {}\n\n{}",
+        categories.join(", "),
+        email.from.format(),
+        email.subject,
+        body
+    );
+
+    let response = {
+        let router = llm_state.router.lock().await;
+        router.send_message(&prompt, None).await
+    };
+    match response {
+        Ok(answer) => match_category(&answer, categories),
+        Err(e) => {
+            warn!("Failed to classify email {}: {}", email.id, e);
+            None
+        }
+    }
+}
+
+fn match_category(answer: &str, categories: &[String]) -> Option<String> {
+    let answer = answer
+        .trim()
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '.')
+        .to_lowercase();
+    categories
+        .iter()
+        .find(|category| category.to_lowercase() == answer)
+        .or_else(|| {
+            // Longest first so "urgent support" wins over "support"
+            let mut candidates: Vec<&String> = categories
+                .iter()
+                .filter(|category| answer.contains(&category.to_lowercase()))
+                .collect();
+            candidates.sort_by_key(|category| std::cmp::Reverse(category.len()));
+            candidates.into_iter().next()
+        })
+        .cloned()
+}
+
+pub fn render_template(template: &str, email: &Email) -> String {
+    let sender_name = email
+        .from
+        .name
+        .clone()
+        .unwrap_or_else(|| email.from.email.clone());
+    let date = chrono::DateTime::from_timestamp(email.date, 0)
+        .map(|date| date.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+
+    template
+        .replace("{{sender_name}}", &sender_name)
+        .replace("{{sender_email}}", &email.from.email)
+        .replace("{{subject}}", &email.subject)
+        .replace("{{date}}", &date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communications::EmailAttachment;
+
+    fn email(from: &str, subject: &str, attachments: usize) -> Email {
+        Email {
+            id: "1:INBOX:7".to_string(),
+            uid: 7,
+            account_id: 1,
+            message_id: "msg-7".to_string(),
+            subject: subject.to_string(),
+            from: EmailAddress::new(from.to_string(), Some("Ada Lovelace".to_string())),
+            to: vec![EmailAddress::new("me@example.com".to_string(), None)],
+            cc: vec![],
+            bcc: vec![],
+            reply_to: None,
+            date: 0,
+            body_text: Some("Please see attached".to_string()),
+            body_html: None,
+            attachments: (0..attachments)
+                .map(|i| EmailAttachment {
+                    filename: format!("file-{}.pdf", i),
+                    content_type: "application/pdf".to_string(),
+                    size: 1,
+                    content_id: None,
+                    file_path: None,
+                })
+                .collect(),
+            is_read: false,
+            is_flagged: false,
+            folder: "INBOX".to_string(),
+            size: 10,
+        }
+    }
+
+    fn input(conditions: RuleConditions) -> EmailRuleInput {
+        EmailRuleInput {
+            account_id: None,
+            name: "Invoices".to_string(),
+            enabled: true,
+            position: None,
+            conditions,
+            actions: vec![RuleAction::Move {
+                folder: "Invoices".to_string(),
+            }],
+            stop_processing: false,
+        }
+    }
+
+    #[test]
+    fn test_static_conditions() {
+        let conditions = RuleConditions {
+            from: Some("ADA".to_string()),
+            subject_regex: Some(r"invoice #\d+".to_string()),
+            has_attachment: Some(true),
+            category: None,
+        };
+        assert!(conditions.matches_static(&email("billing@corp.com", "Invoice #42", 1)));
+        assert!(!conditions.matches_static(&email("billing@corp.com", "Invoice #42", 0)));
+        assert!(!conditions.matches_static(&email("billing@corp.com", "Hello", 1)));
+
+        let by_address = RuleConditions {
+            from: Some("@corp.com".to_string()),
+            ..Default::default()
+        };
+        assert!(by_address.matches_static(&email("billing@corp.com", "x", 0)));
+        assert!(!by_address.matches_static(&email("billing@other.com", "x", 0)));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(input(RuleConditions::default()).validate().is_err());
+        let bad_regex = RuleConditions {
+            subject_regex: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(input(bad_regex).validate().is_err());
+
+        let mut no_body = input(RuleConditions {
+            has_attachment: Some(true),
+            ..Default::default()
+        });
+        no_body.actions = vec![RuleAction::AutoReply {
+            subject: None,
+            body: " ".to_string(),
+        }];
+        assert!(no_body.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_storage_and_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO email_accounts (id, provider, email, imap_host, imap_port, smtp_host, smtp_port, password_encrypted, created_at)
+             VALUES (1, 'gmail', 'me@example.com', 'imap', 993, 'smtp', 587, '', 0)",
+            [],
+        )
+        .unwrap();
+
+        let first = create_rule(
+            &conn,
+            input(RuleConditions {
+                from: Some("ada".to_string()),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let mut second_input = input(RuleConditions {
+            category: Some("support".to_string()),
+            ..Default::default()
+        });
+        second_input.account_id = Some(1);
+        second_input.actions = vec![
+            RuleAction::Label {
+                label: "Support".to_string(),
+            },
+            RuleAction::CreateTask {
+                provider: Provider::Asana,
+                account_id: None,
+                project_id: Some("p1".to_string()),
+            },
+        ];
+        let second = create_rule(&conn, second_input.clone()).unwrap();
+        assert_eq!((first.position, second.position), (0, 1));
+        assert_eq!(second.actions, second_input.actions);
+
+        // Moving the second rule first
+        second_input.position = Some(-1);
+        update_rule(&conn, &second.id, second_input).unwrap();
+        let rules = list_rules(&conn, Some(1)).unwrap();
+        assert_eq!(rules[0].id, second.id);
+        assert_eq!(list_rules(&conn, Some(2)).unwrap().len(), 1);
+
+        record_match(&conn, &first.id).unwrap();
+        assert_eq!(get_rule(&conn, &first.id).unwrap().unwrap().match_count, 1);
+        assert!(delete_rule(&conn, &first.id).unwrap());
+        assert!(get_rule(&conn, &first.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_templates_and_reply_guard() {
+        let message = email("ada@corp.com", "Quarterly numbers", 0);
+        assert_eq!(
+            render_template("Hi {{sender_name}}, re: {{subject}}", &message),
+            "Hi Ada Lovelace, re: Quarterly numbers"
+        );
+
+        assert!(should_auto_reply(&message.from, &message));
+        let robot = EmailAddress::new("no-reply@corp.com".to_string(), None);
+        assert!(!should_auto_reply(&robot, &message));
+        let own = EmailAddress::new("ME@example.com".to_string(), None);
+        assert!(!should_auto_reply(&own, &message));
+    }
+
+    #[test]
+    fn test_match_category() {
+        let categories = vec!["Support".to_string(), "Urgent support".to_string()];
+        assert_eq!(
+            match_category(" support.\n", &categories).as_deref(),
+            Some("Support")
+        );
+        assert_eq!(
+            match_category("This is an urgent support request", &categories).as_deref(),
+            Some("Urgent support")
+        );
+        assert_eq!(match_category("none", &categories), None);
+    }
+}
@@ -0,0 +1,528 @@
+//! Background email sync over IMAP IDLE.
+//!
+//! Every connected account gets a listener task that keeps an IMAP session
+//! idling on the inbox. When the server reports a change the listener pulls
+//! the messages above the last synced UID into the local `emails` table, so
+//! they stay readable offline, emits [`NEW_MESSAGE_EVENT`] for each one and
+//! runs the account's email rules over them.
+//! IDLE is re-issued every [`IDLE_TIMEOUT`] as RFC 2177 asks, and dropped
+//! connections are retried with backoff.
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use super::email_rules;
+use super::imap_client::ImapClient;
+use super::{Email, EmailAddress, EmailAttachment};
+use crate::error::Result;
+
+/// Emitted with a [`NewMessageEvent`] for every message that arrives
+pub const NEW_MESSAGE_EVENT: &str = "email://new-message";
+pub const SYNC_FOLDER: &str = "INBOX";
+
+/// Servers may drop idle sessions after 30 minutes
+const IDLE_TIMEOUT: Duration = Duration::from_secs(29 * 60);
+/// Messages pulled on an account's first sync; older mail stays on the server
+const INITIAL_SYNC_LIMIT: usize = 200;
+/// Messages pulled per round after that
+const SYNC_BATCH_LIMIT: usize = 500;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Connecting,
+    Syncing,
+    Idle,
+    /// Disconnected and waiting to retry
+    Offline,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailSyncStatus {
+    pub account_id: i64,
+    pub state: SyncState,
+    pub last_sync: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewMessageEvent {
+    pub account_id: i64,
+    pub email: Email,
+}
+
+struct Listener {
+    handle: JoinHandle<()>,
+    status: Arc<Mutex<EmailSyncStatus>>,
+}
+
+/// Owns the per-account listener tasks
+#[derive(Default)]
+pub struct EmailSyncManager {
+    listeners: Mutex<HashMap<i64, Listener>>,
+}
+
+impl EmailSyncManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start (or restart) the listener for `account_id`
+    pub fn start(&self, app: &AppHandle, account_id: i64) {
+        let status = Arc::new(Mutex::new(EmailSyncStatus {
+            account_id,
+            state: SyncState::Connecting,
+            last_sync: None,
+            last_error: None,
+        }));
+        let handle =
+            tauri::async_runtime::spawn(run_listener(app.clone(), account_id, Arc::clone(&status)));
+
+        if let Some(previous) = self
+            .listeners
+            .lock()
+            .insert(account_id, Listener { handle, status })
+        {
+            previous.handle.abort();
+        }
+    }
+
+    /// Stop the listener for `account_id`; returns whether one was running
+    pub fn stop(&self, account_id: i64) -> bool {
+        match self.listeners.lock().remove(&account_id) {
+            Some(listener) => {
+                listener.handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start listeners for every stored account; returns how many
+    pub fn start_all(&self, app: &AppHandle) -> Result<usize> {
+        let conn = crate::commands::email::open_connection(app)?;
+        let mut stmt = conn.prepare("SELECT id FROM email_accounts")?;
+        let account_ids = stmt
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        for account_id in &account_ids {
+            self.start(app, *account_id);
+        }
+        Ok(account_ids.len())
+    }
+
+    pub fn statuses(&self) -> Vec<EmailSyncStatus> {
+        let mut statuses: Vec<EmailSyncStatus> = self
+            .listeners
+            .lock()
+            .values()
+            .map(|listener| listener.status.lock().clone())
+            .collect();
+        statuses.sort_by_key(|status| status.account_id);
+        statuses
+    }
+}
+
+async fn run_listener(app: AppHandle, account_id: i64, status: Arc<Mutex<EmailSyncStatus>>) {
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        status.lock().state = SyncState::Connecting;
+
+        if let Err(e) = listen(&app, account_id, &status, &mut backoff).await {
+            warn!(
+                "Email sync for account {} lost its connection: {}; retrying in {:?}",
+                account_id, e, backoff
+            );
+            {
+                let mut status = status.lock();
+                status.state = SyncState::Offline;
+                status.last_error = Some(e.to_string());
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+/// Sync, then idle and sync on every change until the connection fails
+async fn listen(
+    app: &AppHandle,
+    account_id: i64,
+    status: &Mutex<EmailSyncStatus>,
+    backoff: &mut Duration,
+) -> Result<()> {
+    let mut imap = crate::commands::email::connect_account_imap(app, account_id).await?;
+    *backoff = INITIAL_BACKOFF;
+    info!("Email sync connected for account {}", account_id);
+
+    let mut changed = true;
+    loop {
+        if changed {
+            status.lock().state = SyncState::Syncing;
+            sync_inbox(app, &mut imap, account_id).await?;
+
+            let mut status = status.lock();
+            status.last_sync = Some(Utc::now().timestamp());
+            status.last_error = None;
+        }
+
+        status.lock().state = SyncState::Idle;
+        let (session, has_changes) = imap.idle(IDLE_TIMEOUT).await?;
+        imap = session;
+        changed = has_changes;
+    }
+}
+
+/// Pull new inbox messages into the cache and announce them
+async fn sync_inbox(app: &AppHandle, imap: &mut ImapClient, account_id: i64) -> Result<()> {
+    let uid_validity = imap.select_folder_uid_validity(SYNC_FOLDER).await?;
+    let last_uid = {
+        let conn = crate::commands::email::open_connection(app)?;
+        prepare_folder(&conn, account_id, SYNC_FOLDER, uid_validity)?
+    };
+
+    let limit = match last_uid {
+        Some(_) => SYNC_BATCH_LIMIT,
+        None => INITIAL_SYNC_LIMIT,
+    };
+    let emails = imap
+        .fetch_new_emails(account_id, SYNC_FOLDER, last_uid.unwrap_or(0), limit)
+        .await?;
+
+    let conn = crate::commands::email::open_connection(app)?;
+    let inserted = store_emails(&conn, &emails)?;
+    let newest_uid = emails
+        .iter()
+        .map(|email| email.uid)
+        .max()
+        .into_iter()
+        .chain(last_uid)
+        .max()
+        .unwrap_or(0);
+    record_sync(&conn, account_id, SYNC_FOLDER, uid_validity, newest_uid)?;
+
+    // The first sync backfills history; only later arrivals are news
+    if last_uid.is_some() {
+        let arrived: Vec<Email> = emails
+            .into_iter()
+            .filter(|email| inserted.contains(&email.id))
+            .collect();
+        for email in &arrived {
+            let _ = app.emit(
+                NEW_MESSAGE_EVENT,
+                NewMessageEvent {
+                    account_id,
+                    email: email.clone(),
+                },
+            );
+        }
+        if let Err(e) = email_rules::apply_rules(app, imap, account_id, &arrived).await {
+            warn!(
+                "Failed to apply email rules for account {}: {}",
+                account_id, e
+            );
+        }
+        if let Some(workflows) = app.try_state::<crate::commands::WorkflowEngineState>() {
+            workflows.triggers.on_emails(account_id, &arrived);
+        }
+    }
+
+    if !inserted.is_empty() {
+        info!(
+            "Synced {} new messages for account {}",
+            inserted.len(),
+            account_id
+        );
+    }
+    Ok(())
+}
+
+/// Last synced UID for the folder, or `None` when it has to be synced from
+/// scratch. A changed UIDVALIDITY invalidates every cached UID, so the
+/// folder's cache is dropped.
+fn prepare_folder(
+    conn: &Connection,
+    account_id: i64,
+    folder: &str,
+    uid_validity: u32,
+) -> Result<Option<u32>> {
+    let state: Option<(u32, u32)> = conn
+        .query_row(
+            "SELECT uid_validity, last_uid FROM email_sync_state
+             WHERE account_id = ?1 AND folder = ?2",
+            params![account_id, folder],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match state {
+        Some((stored, last_uid)) if stored == uid_validity => Ok(Some(last_uid)),
+        Some(_) => {
+            conn.execute(
+                "DELETE FROM email_attachments WHERE email_id IN
+                    (SELECT id FROM emails WHERE account_id = ?1 AND folder = ?2)",
+                params![account_id, folder],
+            )?;
+            conn.execute(
+                "DELETE FROM emails WHERE account_id = ?1 AND folder = ?2",
+                params![account_id, folder],
+            )?;
+            conn.execute(
+                "DELETE FROM email_sync_state WHERE account_id = ?1 AND folder = ?2",
+                params![account_id, folder],
+            )?;
+            Ok(None)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Cache messages with their bodies; returns the ids that were not cached yet
+fn store_emails(conn: &Connection, emails: &[Email]) -> Result<Vec<String>> {
+    let now = Utc::now().timestamp();
+    let mut inserted = Vec::new();
+
+    for email in emails {
+        let changes = conn.execute(
+            "INSERT INTO emails (id, account_id, uid, message_id, subject, from_email, from_name,
+                                 to_emails, cc_emails, bcc_emails, reply_to_email, reply_to_name,
+                                 date, body_text, body_html, is_read, is_flagged, folder, size,
+                                 has_attachments, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                     ?18, ?19, ?20, ?21)
+             ON CONFLICT(id) DO NOTHING",
+            params![
+                email.id,
+                email.account_id,
+                email.uid,
+                email.message_id,
+                email.subject,
+                email.from.email,
+                email.from.name,
+                to_json(&email.to),
+                to_json(&email.cc),
+                to_json(&email.bcc),
+                email.reply_to.as_ref().map(|address| &address.email),
+                email
+                    .reply_to
+                    .as_ref()
+                    .and_then(|address| address.name.as_ref()),
+                email.date,
+                email.body_text,
+                email.body_html,
+                email.is_read,
+                email.is_flagged,
+                email.folder,
+                email.size as i64,
+                !email.attachments.is_empty(),
+                now,
+            ],
+        )?;
+        if changes == 0 {
+            continue;
+        }
+
+        for attachment in &email.attachments {
+            conn.execute(
+                "INSERT INTO email_attachments (email_id, filename, content_type, size, content_id, file_path, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    email.id,
+                    attachment.filename,
+                    attachment.content_type,
+                    attachment.size as i64,
+                    attachment.content_id,
+                    attachment.file_path,
+                    now,
+                ],
+            )?;
+        }
+        inserted.push(email.id.clone());
+    }
+
+    Ok(inserted)
+}
+
+fn record_sync(
+    conn: &Connection,
+    account_id: i64,
+    folder: &str,
+    uid_validity: u32,
+    last_uid: u32,
+) -> Result<()> {
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO email_sync_state (account_id, folder, uid_validity, last_uid, synced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(account_id, folder) DO UPDATE SET
+            uid_validity = excluded.uid_validity,
+            last_uid = excluded.last_uid,
+            synced_at = excluded.synced_at",
+        params![account_id, folder, uid_validity, last_uid, now],
+    )?;
+    conn.execute(
+        "UPDATE email_accounts SET last_sync = ?1 WHERE id = ?2",
+        params![now, account_id],
+    )?;
+    Ok(())
+}
+
+/// Cached messages for offline reading, newest first
+pub fn cached_emails(
+    conn: &Connection,
+    account_id: i64,
+    folder: &str,
+    limit: usize,
+) -> Result<Vec<Email>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, uid, account_id, message_id, subject, from_email, from_name, to_emails,
+                cc_emails, bcc_emails, reply_to_email, reply_to_name, date, body_text, body_html,
+                is_read, is_flagged, folder, size
+         FROM emails
+         WHERE account_id = ?1 AND folder = ?2
+         ORDER BY date DESC
+         LIMIT ?3",
+    )?;
+    let mut emails = stmt
+        .query_map(params![account_id, folder, limit as i64], |row| {
+            let reply_to_email: Option<String> = row.get(10)?;
+            Ok(Email {
+                id: row.get(0)?,
+                uid: row.get(1)?,
+                account_id: row.get(2)?,
+                message_id: row.get(3)?,
+                subject: row.get(4)?,
+                from: EmailAddress::new(row.get(5)?, row.get(6)?),
+                to: from_json(row.get(7)?),
+                cc: from_json(row.get(8)?),
+                bcc: from_json(row.get(9)?),
+                reply_to: match reply_to_email {
+                    Some(email) => Some(EmailAddress::new(email, row.get(11)?)),
+                    None => None,
+                },
+                date: row.get(12)?,
+                body_text: row.get(13)?,
+                body_html: row.get(14)?,
+                attachments: Vec::new(),
+                is_read: row.get(15)?,
+                is_flagged: row.get(16)?,
+                folder: row.get(17)?,
+                size: row.get::<_, i64>(18)? as usize,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut attachments = conn.prepare(
+        "SELECT filename, content_type, size, content_id, file_path
+         FROM email_attachments WHERE email_id = ?1 ORDER BY id",
+    )?;
+    for email in &mut emails {
+        email.attachments = attachments
+            .query_map(params![email.id], |row| {
+                Ok(EmailAttachment {
+                    filename: row.get(0)?,
+                    content_type: row.get(1)?,
+                    size: row.get::<_, i64>(2)? as usize,
+                    content_id: row.get(3)?,
+                    file_path: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+    }
+
+    Ok(emails)
+}
+
+fn to_json(addresses: &[EmailAddress]) -> String {
+    serde_json::to_string(addresses).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn from_json(value: Option<String>) -> Vec<EmailAddress> {
+    value
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::communications::imap_client::email_id;
+
+    fn email(account_id: i64, uid: u32, date: i64) -> Email {
+        Email {
+            id: email_id(account_id, SYNC_FOLDER, uid),
+            uid,
+            account_id,
+            message_id: format!("msg-{}", uid),
+            subject: format!("Message {}", uid),
+            from: EmailAddress::new("sender@example.com".to_string(), None),
+            to: vec![EmailAddress::new(
+                "me@example.com".to_string(),
+                Some("Me".to_string()),
+            )],
+            cc: vec![],
+            bcc: vec![],
+            reply_to: None,
+            date,
+            body_text: Some("Cached body".to_string()),
+            body_html: None,
+            attachments: vec![EmailAttachment {
+                filename: "report.pdf".to_string(),
+                content_type: "application/pdf".to_string(),
+                size: 42,
+                content_id: None,
+                file_path: None,
+            }],
+            is_read: false,
+            is_flagged: false,
+            folder: SYNC_FOLDER.to_string(),
+            size: 100,
+        }
+    }
+
+    #[test]
+    fn test_incremental_sync_and_uid_validity_reset() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO email_accounts (id, provider, email, imap_host, imap_port, smtp_host, smtp_port, password_encrypted, created_at)
+             VALUES (1, 'gmail', 'me@example.com', 'imap', 993, 'smtp', 587, '', 0)",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(prepare_folder(&conn, 1, SYNC_FOLDER, 7).unwrap(), None);
+        let inserted = store_emails(&conn, &[email(1, 3, 10), email(1, 5, 20)]).unwrap();
+        assert_eq!(inserted.len(), 2);
+        record_sync(&conn, 1, SYNC_FOLDER, 7, 5).unwrap();
+
+        // Re-fetching a cached message does not announce it again
+        assert_eq!(prepare_folder(&conn, 1, SYNC_FOLDER, 7).unwrap(), Some(5));
+        let inserted = store_emails(&conn, &[email(1, 5, 20), email(1, 6, 30)]).unwrap();
+        assert_eq!(inserted, vec![email_id(1, SYNC_FOLDER, 6)]);
+
+        let cached = cached_emails(&conn, 1, SYNC_FOLDER, 10).unwrap();
+        assert_eq!(
+            cached.iter().map(|email| email.uid).collect::<Vec<_>>(),
+            vec![6, 5, 3]
+        );
+        assert_eq!(cached[0].body_text.as_deref(), Some("Cached body"));
+        assert_eq!(cached[0].to[0].name.as_deref(), Some("Me"));
+        assert_eq!(cached[0].attachments[0].filename, "report.pdf");
+
+        // A new UIDVALIDITY means the cached UIDs no longer identify anything
+        assert_eq!(prepare_folder(&conn, 1, SYNC_FOLDER, 8).unwrap(), None);
+        assert!(cached_emails(&conn, 1, SYNC_FOLDER, 10).unwrap().is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use async_imap::{types::Flag, Session};
+use async_imap::{extensions::idle::IdleResponse, types::Flag, Session};
 use futures::{pin_mut, StreamExt};
 use std::cmp::Reverse;
+use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio_native_tls::{native_tls, TlsConnector};
 use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
@@ -90,11 +91,18 @@ impl ImapClient {
 
     /// Select a particular folder/mailbox.
     pub async fn select_folder(&mut self, folder: &str) -> Result<()> {
-        debug!("Selecting IMAP folder {}", folder);
-        self.session.select(folder).await.map_err(map_imap_error)?;
+        self.select_folder_uid_validity(folder).await?;
         Ok(())
     }
 
+    /// Select a folder and return its UIDVALIDITY; cached UIDs are only
+    /// meaningful while it stays the same.
+    pub async fn select_folder_uid_validity(&mut self, folder: &str) -> Result<u32> {
+        debug!("Selecting IMAP folder {}", folder);
+        let mailbox = self.session.select(folder).await.map_err(map_imap_error)?;
+        Ok(mailbox.uid_validity.unwrap_or(0))
+    }
+
     /// Fetch emails from a folder applying optional filters.
     pub async fn fetch_emails(
         &mut self,
@@ -134,7 +142,44 @@ impl ImapClient {
             return Ok(Vec::new());
         }
 
-        let sequence = join_uids(&target);
+        self.fetch_uids(account_id, folder, &target, filter.as_ref())
+            .await
+    }
+
+    /// Fetch up to `limit` of the newest messages with a UID above
+    /// `after_uid` from the currently selected folder, oldest first.
+    pub async fn fetch_new_emails(
+        &mut self,
+        account_id: i64,
+        folder: &str,
+        after_uid: u32,
+        limit: usize,
+    ) -> Result<Vec<Email>> {
+        let uid_set = self
+            .session
+            .uid_search(format!("UID {}:*", after_uid.saturating_add(1)))
+            .await
+            .map_err(map_imap_error)?;
+
+        // `n:*` always matches the last message, even when it is older than n
+        let target = newest_uids_after(uid_set, after_uid, limit);
+        if target.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut emails = self.fetch_uids(account_id, folder, &target, None).await?;
+        emails.sort_by_key(|email| email.uid);
+        Ok(emails)
+    }
+
+    async fn fetch_uids(
+        &mut self,
+        account_id: i64,
+        folder: &str,
+        target: &[u32],
+        filter: Option<&EmailFilter>,
+    ) -> Result<Vec<Email>> {
+        let sequence = join_uids(target);
         let mut fetches = self
             .session
             .uid_fetch(
@@ -173,7 +218,7 @@ impl ImapClient {
             );
 
             let email = Email {
-                id: format!("{}:{}", folder, uid),
+                id: email_id(account_id, folder, uid),
                 uid,
                 account_id,
                 message_id: parsed.message_id.clone(),
@@ -196,7 +241,7 @@ impl ImapClient {
                     .unwrap_or_else(|| parsed.body_text.as_ref().map(|s| s.len()).unwrap_or(0)),
             };
 
-            if let Some(filter) = filter.as_ref() {
+            if let Some(filter) = filter {
                 if !matches_filter(&email, filter) {
                     continue;
                 }
@@ -231,6 +276,42 @@ impl ImapClient {
         Ok(())
     }
 
+    /// Add an IMAP keyword (label) to a message in the selected folder.
+    pub async fn add_keyword(&mut self, uid: u32, keyword: &str) -> Result<()> {
+        let keyword: String = keyword
+            .chars()
+            .filter(|c| !c.is_whitespace() && !"(){%*\"\\]".contains(*c))
+            .collect();
+        if keyword.is_empty() {
+            return Err(Error::Generic("Label is empty".to_string()));
+        }
+
+        let responses = self
+            .session
+            .uid_store(uid.to_string(), format!("+FLAGS ({})", keyword))
+            .await
+            .map_err(map_imap_error)?;
+        pin_mut!(responses);
+        while let Some(response) = responses.next().await {
+            response.map_err(map_imap_error)?;
+        }
+        Ok(())
+    }
+
+    /// Move a message from the selected folder to `folder`, falling back to
+    /// copy and delete on servers without the MOVE extension.
+    pub async fn move_email(&mut self, uid: u32, folder: &str) -> Result<()> {
+        if let Err(e) = self.session.uid_mv(uid.to_string(), folder).await {
+            debug!("UID MOVE failed ({}), falling back to COPY", e);
+            self.session
+                .uid_copy(uid.to_string(), folder)
+                .await
+                .map_err(map_imap_error)?;
+            self.delete_email(uid).await?;
+        }
+        Ok(())
+    }
+
     /// Delete a message and expunge the folder.
     pub async fn delete_email(&mut self, uid: u32) -> Result<()> {
         let sequence = uid.to_string();
@@ -277,6 +358,38 @@ impl ImapClient {
         )))
     }
 
+    /// Wait in IDLE on the selected folder until the server reports a change
+    /// or `timeout` passes, returning whether anything changed. The session
+    /// is handed back ready for further commands.
+    pub async fn idle(self, timeout: Duration) -> Result<(Self, bool)> {
+        let Self {
+            session,
+            host,
+            email,
+        } = self;
+
+        let mut handle = session.idle();
+        handle.init().await.map_err(map_imap_error)?;
+        let changed = {
+            // Dropping the stop source would interrupt the wait right away
+            let (wait, _stop) = handle.wait_with_timeout(timeout);
+            matches!(
+                wait.await.map_err(map_imap_error)?,
+                IdleResponse::NewData(_)
+            )
+        };
+        let session = handle.done().await.map_err(map_imap_error)?;
+
+        Ok((
+            Self {
+                session,
+                host,
+                email,
+            },
+            changed,
+        ))
+    }
+
     /// Gracefully close the IMAP session.
     pub async fn logout(mut self) -> Result<()> {
         if let Err(err) = self.session.logout().await {
@@ -289,6 +402,22 @@ impl ImapClient {
     }
 }
 
+/// Stable id for a message; UIDs are only unique per account and folder
+pub fn email_id(account_id: i64, folder: &str, uid: u32) -> String {
+    format!("{}:{}:{}", account_id, folder, uid)
+}
+
+fn newest_uids_after(
+    uids: impl IntoIterator<Item = u32>,
+    after_uid: u32,
+    limit: usize,
+) -> Vec<u32> {
+    let mut uids: Vec<u32> = uids.into_iter().filter(|uid| *uid > after_uid).collect();
+    uids.sort_unstable();
+    let skip = uids.len().saturating_sub(limit.max(1));
+    uids.split_off(skip)
+}
+
 fn join_uids(uids: &[u32]) -> String {
     uids.iter()
         .map(|uid| uid.to_string())
@@ -430,6 +559,14 @@ mod tests {
         assert_eq!(join_uids(&[42]), "42");
     }
 
+    #[test]
+    fn test_newest_uids_after() {
+        // The server answers `UID 8:*` with the last message even if it is older
+        assert!(newest_uids_after([7], 7, 10).is_empty());
+        assert_eq!(newest_uids_after([12, 9, 8, 15], 7, 10), vec![8, 9, 12, 15]);
+        assert_eq!(newest_uids_after([12, 9, 8, 15], 7, 2), vec![12, 15]);
+    }
+
     #[test]
     fn test_matches_filter_subject() {
         let email = Email {
@@ -1,10 +1,16 @@
 pub mod contacts;
+pub mod email_outbox;
 pub mod email_parser;
+pub mod email_rules;
+pub mod email_sync;
 /// Communications MCP (Modular Control Primitive)
 ///
 /// Provides email and contact management capabilities including:
 /// - IMAP client for receiving emails
+/// - Background IMAP IDLE sync into the local email cache
+/// - Rules that filter and triage newly arrived mail
 /// - SMTP client for sending emails
+/// - Persistent outbox that schedules and retries outgoing mail
 /// - Email parsing (MIME multipart, attachments, HTML)
 /// - Contact management with vCard import/export
 pub mod imap_client;
@@ -116,11 +116,14 @@ impl SmtpClient {
             .multipart(body_part)
             .map_err(|err| Error::EmailSend(format!("Failed to build email message: {}", err)))?;
 
-        let response = self
-            .transport
-            .send(message)
-            .await
-            .map_err(|err| Error::EmailSend(format!("SMTP send failed: {}", err)))?;
+        let response = self.transport.send(message).await.map_err(|err| {
+            // 5xx replies will not change on retry
+            if err.is_permanent() {
+                Error::Generic(format!("SMTP server rejected the message: {}", err))
+            } else {
+                Error::EmailSend(format!("SMTP send failed: {}", err))
+            }
+        })?;
 
         debug!("SMTP response: {:?}", response);
         Ok(extract_message_id(response))
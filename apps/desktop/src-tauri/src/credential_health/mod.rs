@@ -0,0 +1,422 @@
+//! Credential health across integrations.
+//!
+//! Every connected calendar, cloud storage, productivity and messaging
+//! account, plus the GitHub token, is checked periodically by the
+//! [`monitor`]. Each check refreshes the account's token where the provider
+//! allows it and records the outcome here together with when the credentials
+//! stop working on their own. Accounts that are failing, expired or about to
+//! expire are surfaced in the overview and raise one notification per stage,
+//! so the user can re-authorize before an unattended workflow breaks.
+//! User-defined HTTP health checks from [`crate::api::health_check`] report
+//! into the same registry.
+
+pub mod monitor;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+pub use monitor::CredentialHealthMonitor;
+
+/// Accounts expiring within this window are reported as expiring soon
+pub const EXPIRY_WARNING_SECS: i64 = 7 * 24 * 60 * 60;
+/// A second, more urgent reminder is raised this close to expiry
+pub const EXPIRY_URGENT_SECS: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    Calendar,
+    Cloud,
+    Productivity,
+    Messaging,
+    Github,
+    /// A user-defined check of a custom HTTP integration, see [`crate::api::health_check`]
+    HealthCheck,
+}
+
+impl CredentialSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CredentialSource::Calendar => "calendar",
+            CredentialSource::Cloud => "cloud",
+            CredentialSource::Productivity => "productivity",
+            CredentialSource::Messaging => "messaging",
+            CredentialSource::Github => "github",
+            CredentialSource::HealthCheck => "health_check",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "calendar" => Some(CredentialSource::Calendar),
+            "cloud" => Some(CredentialSource::Cloud),
+            "productivity" => Some(CredentialSource::Productivity),
+            "messaging" => Some(CredentialSource::Messaging),
+            "github" => Some(CredentialSource::Github),
+            "health_check" => Some(CredentialSource::HealthCheck),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialStatus {
+    Healthy,
+    /// Expires within [`EXPIRY_WARNING_SECS`]
+    ExpiringSoon,
+    Expired,
+    /// The last check failed, e.g. a revoked token or refresh
+    Failing,
+    /// Not checked successfully yet
+    Unknown,
+}
+
+impl CredentialStatus {
+    /// Whether the user has to re-authorize the account
+    pub fn needs_attention(self) -> bool {
+        matches!(
+            self,
+            CredentialStatus::ExpiringSoon | CredentialStatus::Expired | CredentialStatus::Failing
+        )
+    }
+}
+
+/// Outcome of checking one account
+#[derive(Debug, Clone)]
+pub struct CredentialCheck {
+    pub source: CredentialSource,
+    pub account_id: String,
+    pub provider: String,
+    pub label: Option<String>,
+    /// When the credentials stop working without the user re-authorizing;
+    /// `None` when they do not expire or a refresh token renews them
+    pub expires_at: Option<i64>,
+    pub outcome: std::result::Result<(), String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialHealth {
+    pub source: CredentialSource,
+    pub account_id: String,
+    pub provider: String,
+    pub label: Option<String>,
+    pub status: CredentialStatus,
+    pub expires_at: Option<i64>,
+    /// Whole days until `expires_at`, negative once expired
+    pub days_remaining: Option<i64>,
+    pub last_success_at: Option<i64>,
+    pub last_failure_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_checked_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialHealthOverview {
+    /// Accounts needing attention first, then by source
+    pub accounts: Vec<CredentialHealth>,
+    pub healthy: usize,
+    pub expiring_soon: usize,
+    pub expired: usize,
+    pub failing: usize,
+    pub unknown: usize,
+    /// When the monitor last ran a full check
+    pub last_checked_at: Option<i64>,
+}
+
+/// Store the result of a check
+pub fn record(conn: &Connection, check: &CredentialCheck, now: i64) -> Result<()> {
+    let (success_at, failure_at, error) = match &check.outcome {
+        Ok(()) => (Some(now), None, None),
+        Err(e) => (None, Some(now), Some(e.as_str())),
+    };
+    conn.execute(
+        "INSERT INTO credential_health
+            (source, account_id, provider, label, expires_at, last_success_at,
+             last_failure_at, last_error, last_checked_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(source, account_id) DO UPDATE SET
+            provider = excluded.provider,
+            label = excluded.label,
+            expires_at = excluded.expires_at,
+            last_success_at = COALESCE(excluded.last_success_at, credential_health.last_success_at),
+            last_failure_at = COALESCE(excluded.last_failure_at, credential_health.last_failure_at),
+            last_error = CASE WHEN excluded.last_success_at IS NULL
+                              THEN excluded.last_error ELSE NULL END,
+            last_checked_at = excluded.last_checked_at",
+        params![
+            check.source.as_str(),
+            check.account_id,
+            check.provider,
+            check.label,
+            check.expires_at,
+            success_at,
+            failure_at,
+            error,
+            now
+        ],
+    )?;
+    Ok(())
+}
+
+/// Drop rows of `source` for accounts that are no longer connected
+pub fn retain(conn: &Connection, source: CredentialSource, account_ids: &[String]) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT account_id FROM credential_health WHERE source = ?1")?;
+    let stale = stmt
+        .query_map([source.as_str()], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|id| !account_ids.contains(id));
+    for account_id in stale {
+        conn.execute(
+            "DELETE FROM credential_health WHERE source = ?1 AND account_id = ?2",
+            params![source.as_str(), account_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Current health of every tracked account
+pub fn load(conn: &Connection, now: i64) -> Result<Vec<CredentialHealth>> {
+    let mut stmt = conn.prepare(
+        "SELECT source, account_id, provider, label, expires_at, last_success_at,
+                last_failure_at, last_error, last_checked_at
+         FROM credential_health",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            let Some(source) = CredentialSource::parse(&row.get::<_, String>(0)?) else {
+                return Ok(None);
+            };
+            let mut health = CredentialHealth {
+                source,
+                account_id: row.get(1)?,
+                provider: row.get(2)?,
+                label: row.get(3)?,
+                status: CredentialStatus::Unknown,
+                expires_at: row.get(4)?,
+                days_remaining: None,
+                last_success_at: row.get(5)?,
+                last_failure_at: row.get(6)?,
+                last_error: row.get(7)?,
+                last_checked_at: row.get(8)?,
+            };
+            health.status = classify(&health, now);
+            health.days_remaining = health
+                .expires_at
+                .map(|expires_at| (expires_at - now).div_euclid(24 * 60 * 60));
+            Ok(Some(health))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+/// Summarize tracked accounts, most urgent first
+pub fn overview(conn: &Connection, now: i64) -> Result<CredentialHealthOverview> {
+    let mut accounts = load(conn, now)?;
+    accounts.sort_by(|a, b| {
+        urgency(a.status)
+            .cmp(&urgency(b.status))
+            .then_with(|| {
+                a.expires_at
+                    .unwrap_or(i64::MAX)
+                    .cmp(&b.expires_at.unwrap_or(i64::MAX))
+            })
+            .then_with(|| a.source.as_str().cmp(b.source.as_str()))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+
+    let count = |status| accounts.iter().filter(|a| a.status == status).count();
+    Ok(CredentialHealthOverview {
+        healthy: count(CredentialStatus::Healthy),
+        expiring_soon: count(CredentialStatus::ExpiringSoon),
+        expired: count(CredentialStatus::Expired),
+        failing: count(CredentialStatus::Failing),
+        unknown: count(CredentialStatus::Unknown),
+        last_checked_at: accounts.iter().map(|a| a.last_checked_at).max(),
+        accounts,
+    })
+}
+
+/// Accounts of `sources` whose warning stage changed since the user was last
+/// told, with the stage now recorded as notified. Accounts that recovered are
+/// reset so a later problem is reported again.
+pub fn take_notifications(
+    conn: &Connection,
+    now: i64,
+    sources: &[CredentialSource],
+) -> Result<Vec<CredentialHealth>> {
+    let mut due = Vec::new();
+    for health in load(conn, now)? {
+        if !sources.contains(&health.source) {
+            continue;
+        }
+        let stage = notification_stage(&health, now);
+        let notified: Option<String> = conn
+            .query_row(
+                "SELECT notified_stage FROM credential_health
+                 WHERE source = ?1 AND account_id = ?2",
+                params![health.source.as_str(), health.account_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        if notified.as_deref() == stage {
+            continue;
+        }
+
+        conn.execute(
+            "UPDATE credential_health SET notified_stage = ?1
+             WHERE source = ?2 AND account_id = ?3",
+            params![stage, health.source.as_str(), health.account_id],
+        )?;
+        if stage.is_some() {
+            due.push(health);
+        }
+    }
+    Ok(due)
+}
+
+fn classify(health: &CredentialHealth, now: i64) -> CredentialStatus {
+    let failed_last = match (health.last_failure_at, health.last_success_at) {
+        (Some(failure), Some(success)) => failure > success,
+        (Some(_), None) => true,
+        _ => false,
+    };
+    if failed_last {
+        return CredentialStatus::Failing;
+    }
+    match health.expires_at {
+        Some(expires_at) if expires_at <= now => CredentialStatus::Expired,
+        Some(expires_at) if expires_at - now <= EXPIRY_WARNING_SECS => {
+            CredentialStatus::ExpiringSoon
+        }
+        _ if health.last_success_at.is_some() => CredentialStatus::Healthy,
+        _ => CredentialStatus::Unknown,
+    }
+}
+
+fn urgency(status: CredentialStatus) -> u8 {
+    match status {
+        CredentialStatus::Failing => 0,
+        CredentialStatus::Expired => 1,
+        CredentialStatus::ExpiringSoon => 2,
+        CredentialStatus::Unknown => 3,
+        CredentialStatus::Healthy => 4,
+    }
+}
+
+/// The warning the user should have seen for an account's current state
+fn notification_stage(health: &CredentialHealth, now: i64) -> Option<&'static str> {
+    match health.status {
+        CredentialStatus::Failing => Some("failing"),
+        CredentialStatus::Expired => Some("expired"),
+        CredentialStatus::ExpiringSoon => match health.expires_at {
+            Some(expires_at) if expires_at - now <= EXPIRY_URGENT_SECS => Some("expiring_urgent"),
+            _ => Some("expiring"),
+        },
+        CredentialStatus::Healthy | CredentialStatus::Unknown => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+    const PRODUCTIVITY: &[CredentialSource] = &[CredentialSource::Productivity];
+
+    fn check(
+        account_id: &str,
+        expires_at: Option<i64>,
+        outcome: std::result::Result<(), String>,
+    ) -> CredentialCheck {
+        CredentialCheck {
+            source: CredentialSource::Productivity,
+            account_id: account_id.to_string(),
+            provider: "notion".to_string(),
+            label: None,
+            expires_at,
+            outcome,
+        }
+    }
+
+    fn conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_overview_classifies_accounts() {
+        let conn = conn();
+        let now = 100 * DAY;
+        record(&conn, &check("ok", None, Ok(())), now).unwrap();
+        record(&conn, &check("soon", Some(now + 3 * DAY), Ok(())), now).unwrap();
+        record(&conn, &check("gone", Some(now - DAY), Ok(())), now).unwrap();
+        record(&conn, &check("bad", None, Err("401".to_string())), now).unwrap();
+
+        let summary = overview(&conn, now).unwrap();
+        let statuses: Vec<_> = summary
+            .accounts
+            .iter()
+            .map(|a| (a.account_id.as_str(), a.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("bad", CredentialStatus::Failing),
+                ("gone", CredentialStatus::Expired),
+                ("soon", CredentialStatus::ExpiringSoon),
+                ("ok", CredentialStatus::Healthy),
+            ]
+        );
+        assert_eq!(summary.accounts[2].days_remaining, Some(3));
+        assert_eq!(summary.accounts[0].last_error.as_deref(), Some("401"));
+
+        // A later success clears the failure
+        record(&conn, &check("bad", None, Ok(())), now + 1).unwrap();
+        let recovered = overview(&conn, now + 1).unwrap();
+        assert_eq!(recovered.failing, 0);
+        assert_eq!(recovered.healthy, 2);
+
+        retain(&conn, CredentialSource::Productivity, &["ok".to_string()]).unwrap();
+        assert_eq!(load(&conn, now).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_notifies_once_per_stage() {
+        let conn = conn();
+        let now = 100 * DAY;
+        record(&conn, &check("a", Some(now + 5 * DAY), Ok(())), now).unwrap();
+
+        assert_eq!(
+            take_notifications(&conn, now, PRODUCTIVITY).unwrap().len(),
+            1
+        );
+        assert!(take_notifications(&conn, now + DAY, PRODUCTIVITY)
+            .unwrap()
+            .is_empty());
+        // Under a day left escalates to a second reminder
+        assert_eq!(
+            take_notifications(&conn, now + 4 * DAY + 1, PRODUCTIVITY)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Re-authorizing resets the stage so the next expiry warns again
+        record(&conn, &check("a", Some(now + 90 * DAY), Ok(())), now).unwrap();
+        assert!(take_notifications(&conn, now, PRODUCTIVITY)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            take_notifications(&conn, now + 85 * DAY, PRODUCTIVITY)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Periodic credential checks.
+//!
+//! The monitor walks every connected account, refreshing tokens and making
+//! one cheap authenticated call each, then records the results and notifies
+//! the user about accounts that changed into a failing or expiring state.
+//! Every run emits the overview as [`HEALTH_EVENT`].
+
+use chrono::{NaiveDateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use super::{
+    CredentialCheck, CredentialHealth, CredentialHealthOverview, CredentialSource, CredentialStatus,
+};
+use crate::calendar::CalendarProvider;
+use crate::cloud::CloudProvider;
+use crate::commands::{
+    AppDatabase, CalendarState, CloudState, ProductivityState, SettingsServiceState,
+};
+use crate::error::{Error, Result};
+use crate::messaging::MessagingManager;
+use crate::notifications::{self, Notification, NotificationCategory};
+use crate::productivity::accounts::provider_key;
+
+/// Emitted with a [`CredentialHealthOverview`] after every check
+pub const HEALTH_EVENT: &str = "credentials://health";
+
+/// Time between full checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// Delay before the first check so startup restores finish first
+const INITIAL_DELAY: Duration = Duration::from_secs(2 * 60);
+/// Sources checked here; health checks are run by their own scheduler
+const SOURCES: [CredentialSource; 5] = [
+    CredentialSource::Calendar,
+    CredentialSource::Cloud,
+    CredentialSource::Productivity,
+    CredentialSource::Messaging,
+    CredentialSource::Github,
+];
+
+/// Owns the periodic check task
+#[derive(Default)]
+pub struct CredentialHealthMonitor {
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CredentialHealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, app: &AppHandle) {
+        let app = app.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(INITIAL_DELAY).await;
+            loop {
+                if let Err(e) = check_all(&app).await {
+                    warn!("Credential health check failed: {}", e);
+                }
+                tokio::time::sleep(CHECK_INTERVAL).await;
+            }
+        });
+        if let Some(previous) = self.worker.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
+/// Check every connected account now, record the results, notify about
+/// changes and return the updated overview
+pub async fn check_all(app: &AppHandle) -> Result<CredentialHealthOverview> {
+    let mut checks = Vec::new();
+    checks.extend(check_calendar(app).await);
+    checks.extend(check_cloud(app).await);
+    checks.extend(check_productivity(app).await);
+    checks.extend(check_messaging(app).await?);
+    checks.extend(check_github(app).await);
+
+    let now = Utc::now().timestamp();
+    let conn = open_connection(app)?;
+    for check in &checks {
+        super::record(&conn, check, now)?;
+    }
+    for source in SOURCES {
+        let account_ids: Vec<String> = checks
+            .iter()
+            .filter(|check| check.source == source)
+            .map(|check| check.account_id.clone())
+            .collect();
+        super::retain(&conn, source, &account_ids)?;
+    }
+
+    for health in super::take_notifications(&conn, now, &SOURCES)? {
+        notifications::notify(app, notification(&health));
+    }
+
+    let overview = super::overview(&conn, now)?;
+    info!(
+        "Checked {} credentials: {} need attention",
+        overview.accounts.len(),
+        overview.failing + overview.expired + overview.expiring_soon
+    );
+    if let Err(e) = app.emit(HEALTH_EVENT, &overview) {
+        warn!("Failed to emit credential health event: {}", e);
+    }
+    Ok(overview)
+}
+
+/// Stored results without running any checks
+pub fn load_overview(app: &AppHandle) -> Result<CredentialHealthOverview> {
+    super::overview(&open_connection(app)?, Utc::now().timestamp())
+}
+
+async fn check_calendar(app: &AppHandle) -> Vec<CredentialCheck> {
+    let Some(state) = app.try_state::<CalendarState>() else {
+        return Vec::new();
+    };
+    let manager = state.manager.clone();
+
+    let mut checks = Vec::new();
+    for account_id in manager.list_accounts() {
+        let outcome = manager
+            .check_account(&account_id)
+            .await
+            .map_err(|e| e.to_string());
+        if outcome.is_ok() {
+            if let Err(e) = crate::commands::calendar::persist_account(&manager, app, &account_id) {
+                warn!("Failed to save refreshed calendar token: {}", e);
+            }
+        }
+        let Some(info) = manager.account_info(&account_id) else {
+            continue;
+        };
+        checks.push(CredentialCheck {
+            source: CredentialSource::Calendar,
+            account_id,
+            provider: match info.provider {
+                CalendarProvider::Google => "google",
+                CalendarProvider::Outlook => "outlook",
+            }
+            .to_string(),
+            label: info.email.or(info.display_name),
+            expires_at: info
+                .token
+                .refresh_token
+                .is_none()
+                .then_some(info.token.expires_at)
+                .flatten()
+                .map(|expires_at| expires_at as i64),
+            outcome,
+        });
+    }
+    checks
+}
+
+async fn check_cloud(app: &AppHandle) -> Vec<CredentialCheck> {
+    let Some(state) = app.try_state::<CloudState>() else {
+        return Vec::new();
+    };
+    let manager = state.manager.clone();
+
+    let mut checks = Vec::new();
+    for account in manager.list_accounts() {
+        let outcome = manager
+            .check_account(&account.account_id)
+            .await
+            .map_err(|e| e.to_string());
+        checks.push(CredentialCheck {
+            source: CredentialSource::Cloud,
+            provider: match account.provider {
+                CloudProvider::GoogleDrive => "google_drive",
+                CloudProvider::Dropbox => "dropbox",
+                CloudProvider::OneDrive => "one_drive",
+                CloudProvider::WebDav => "webdav",
+            }
+            .to_string(),
+            account_id: account.account_id,
+            label: account.label,
+            // Refresh tokens and WebDAV passwords renew the session indefinitely
+            expires_at: None,
+            outcome,
+        });
+    }
+    checks
+}
+
+async fn check_productivity(app: &AppHandle) -> Vec<CredentialCheck> {
+    let Some(state) = app.try_state::<ProductivityState>() else {
+        return Vec::new();
+    };
+
+    let mut checks = Vec::new();
+    for account in state.manager().list_accounts() {
+        let (expires_at, outcome) = match state.check_account(&account.account_id).await {
+            Ok(expires_at) => (expires_at, Ok(())),
+            Err(e) => (None, Err(e.to_string())),
+        };
+        checks.push(CredentialCheck {
+            source: CredentialSource::Productivity,
+            provider: provider_key(&account.provider).to_string(),
+            account_id: account.account_id,
+            label: account.label,
+            expires_at,
+            outcome,
+        });
+    }
+    checks
+}
+
+async fn check_messaging(app: &AppHandle) -> Result<Vec<CredentialCheck>> {
+    let (Some(db), Some(messaging)) = (
+        app.try_state::<AppDatabase>(),
+        app.try_state::<MessagingManager>(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let connections = {
+        let conn = db
+            .conn
+            .lock()
+            .map_err(|e| Error::Generic(format!("Database lock error: {}", e)))?;
+        let mut stmt = conn.prepare(
+            "SELECT id, platform, workspace_name FROM messaging_connections
+             WHERE is_active = 1",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows
+    };
+
+    let mut checks = Vec::new();
+    for (account_id, platform, label) in connections {
+        let outcome = messaging
+            .check(&db, &account_id)
+            .await
+            .map_err(|e| e.to_string());
+        checks.push(CredentialCheck {
+            source: CredentialSource::Messaging,
+            account_id,
+            provider: platform,
+            label,
+            expires_at: None,
+            outcome,
+        });
+    }
+    Ok(checks)
+}
+
+/// Checks the GitHub token from settings. Fine-grained and expiring classic
+/// tokens report their expiry in a response header
+async fn check_github(app: &AppHandle) -> Option<CredentialCheck> {
+    let token = app
+        .try_state::<SettingsServiceState>()?
+        .service
+        .lock()
+        .ok()?
+        .get_api_key("github")
+        .ok()
+        .filter(|token| !token.trim().is_empty())?;
+
+    let mut check = CredentialCheck {
+        source: CredentialSource::Github,
+        account_id: "github".to_string(),
+        provider: "github".to_string(),
+        label: None,
+        expires_at: None,
+        outcome: Ok(()),
+    };
+
+    let response = reqwest::Client::new()
+        .get("https://api.github.com/user")
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "AGI-Workforce")
+        .send()
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => {
+            check.expires_at = response
+                .headers()
+                .get("github-authentication-token-expiration")
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_github_expiration);
+            check.label = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|user| user["login"].as_str().map(str::to_string));
+        }
+        Ok(response) => {
+            check.outcome = Err(format!("GitHub rejected the token ({})", response.status()));
+        }
+        Err(e) => check.outcome = Err(format!("GitHub request failed: {}", e)),
+    }
+    Some(check)
+}
+
+/// Parse GitHub's `2024-03-01 12:00:00 UTC` expiration format
+fn parse_github_expiration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    NaiveDateTime::parse_from_str(value.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S")
+        .map(|naive| naive.and_utc().timestamp())
+        .or_else(|_| {
+            chrono::DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z")
+                .map(|date| date.timestamp())
+        })
+        .ok()
+}
+
+fn notification(health: &CredentialHealth) -> Notification {
+    let name = match &health.label {
+        Some(label) => format!("{} ({})", health.provider, label),
+        None => health.provider.clone(),
+    };
+    let (title, body) = match health.status {
+        CredentialStatus::Failing => (
+            format!("{} needs to be reconnected", name),
+            format!(
+                "The last check failed: {}",
+                health.last_error.as_deref().unwrap_or("unknown error")
+            ),
+        ),
+        CredentialStatus::Expired => (
+            format!("{} credentials expired", name),
+            "Reconnect the account to keep workflows that use it running.".to_string(),
+        ),
+        _ => (
+            format!("{} credentials expire soon", name),
+            match health.days_remaining {
+                Some(0) => {
+                    "They expire within a day. Reconnect the account to avoid interruptions."
+                        .to_string()
+                }
+                Some(days) => format!(
+                    "They expire in {} day{}. Reconnect the account to avoid interruptions.",
+                    days,
+                    if days == 1 { "" } else { "s" }
+                ),
+                None => "Reconnect the account to avoid interruptions.".to_string(),
+            },
+        ),
+    };
+    Notification::new(NotificationCategory::CredentialExpiry, title, body).with_target(format!(
+        "{}:{}",
+        health.source.as_str(),
+        health.account_id
+    ))
+}
+
+fn open_connection(app: &AppHandle) -> Result<Connection> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::Generic(format!("Failed to get app data dir: {}", e)))?
+        .join("agiworkforce.db");
+
+    Connection::open(db_path).map_err(|e| Error::Generic(format!("Database error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_github_expiration() {
+        assert_eq!(
+            parse_github_expiration("2024-03-01 12:00:00 UTC"),
+            Some(1_709_294_400)
+        );
+        assert_eq!(
+            parse_github_expiration("2024-03-01 14:00:00 +0200"),
+            Some(1_709_294_400)
+        );
+        assert_eq!(parse_github_expiration("never"), None);
+    }
+}
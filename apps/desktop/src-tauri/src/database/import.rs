@@ -0,0 +1,236 @@
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::document::tabular::TabularData;
+use crate::error::{Error, Result};
+
+/// What to do when the target table already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Fail if the table exists
+    #[default]
+    Create,
+    /// Drop and recreate the table
+    Replace,
+    /// Insert into the existing table, matching columns by name
+    Append,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub database_path: String,
+    pub table: String,
+    pub columns: Vec<String>,
+    pub rows_imported: usize,
+}
+
+/// Load a table read from a CSV or Parquet file into SQLite in one transaction
+pub fn import_table(
+    conn: &mut Connection,
+    table: &str,
+    data: &TabularData,
+    mode: ImportMode,
+) -> Result<ImportSummary> {
+    let table = sanitize_identifier(table)?;
+    if data.columns.is_empty() {
+        return Err(Error::Generic("File contains no columns".to_string()));
+    }
+    let mut columns: Vec<String> = Vec::with_capacity(data.columns.len());
+    for column in &data.columns {
+        let base = sanitize_identifier(&column.name)?;
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while columns
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&name))
+        {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        columns.push(name);
+    }
+
+    let tx = conn.transaction()?;
+    let exists: bool = tx
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")?
+        .exists([&table])?;
+
+    match (exists, mode) {
+        (true, ImportMode::Create) => {
+            return Err(Error::Generic(format!(
+                "Table {} already exists; choose replace or append",
+                table
+            )))
+        }
+        (true, ImportMode::Replace) => {
+            tx.execute_batch(&format!("DROP TABLE {};", quote(&table)))?;
+            create_table(&tx, &table, &columns, data)?;
+        }
+        (true, ImportMode::Append) => {
+            let existing: Vec<String> = tx
+                .prepare("SELECT name FROM pragma_table_info(?1)")?
+                .query_map([&table], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            if let Some(missing) = columns.iter().find(|column| {
+                !existing
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(column))
+            }) {
+                return Err(Error::Generic(format!(
+                    "Table {} has no column {}",
+                    table, missing
+                )));
+            }
+        }
+        (false, _) => create_table(&tx, &table, &columns, data)?,
+    }
+
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote(&table),
+        columns
+            .iter()
+            .map(|c| quote(c))
+            .collect::<Vec<_>>()
+            .join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+    {
+        let mut stmt = tx.prepare(&insert)?;
+        for row in &data.rows {
+            stmt.execute(params_from_iter(
+                (0..columns.len()).map(|index| sql_value(row.get(index))),
+            ))?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(ImportSummary {
+        database_path: conn.path().unwrap_or_default().to_string(),
+        table,
+        columns,
+        rows_imported: data.rows.len(),
+    })
+}
+
+fn create_table(
+    conn: &Connection,
+    table: &str,
+    columns: &[String],
+    data: &TabularData,
+) -> Result<()> {
+    let definitions = columns
+        .iter()
+        .zip(&data.columns)
+        .map(|(name, column)| format!("{} {}", quote(name), column.column_type.sqlite_type()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    conn.execute_batch(&format!("CREATE TABLE {} ({});", quote(table), definitions))?;
+    Ok(())
+}
+
+/// Reduce a header or table name to letters, digits and underscores so it
+/// can be used unambiguously as a SQLite identifier
+fn sanitize_identifier(name: &str) -> Result<String> {
+    let mut identifier: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    identifier = identifier.trim_matches('_').to_string();
+    if identifier.is_empty() {
+        return Err(Error::Generic(format!("Invalid identifier: {:?}", name)));
+    }
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    if identifier.to_ascii_lowercase().starts_with("sqlite_") {
+        return Err(Error::Generic(format!(
+            "Identifiers starting with sqlite_ are reserved: {}",
+            identifier
+        )));
+    }
+    Ok(identifier)
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn sql_value(value: Option<&JsonValue>) -> SqlValue {
+    match value {
+        None | Some(JsonValue::Null) => SqlValue::Null,
+        Some(JsonValue::Bool(flag)) => SqlValue::Integer(i64::from(*flag)),
+        Some(JsonValue::Number(number)) => match number.as_i64() {
+            Some(integer) => SqlValue::Integer(integer),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        Some(JsonValue::String(text)) => SqlValue::Text(text.clone()),
+        Some(other) => SqlValue::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::tabular::{ColumnSchema, ColumnType, TabularFormat};
+
+    fn sample() -> TabularData {
+        TabularData {
+            format: TabularFormat::Csv,
+            columns: vec![
+                ColumnSchema {
+                    name: "Invoice #".to_string(),
+                    column_type: ColumnType::Integer,
+                    nullable: false,
+                },
+                ColumnSchema {
+                    name: "Paid?".to_string(),
+                    column_type: ColumnType::Boolean,
+                    nullable: true,
+                },
+            ],
+            rows: vec![
+                vec![JsonValue::from(7), JsonValue::Bool(true)],
+                vec![JsonValue::from(8), JsonValue::Null],
+            ],
+            dialect: None,
+        }
+    }
+
+    #[test]
+    fn imports_and_appends_rows() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let summary =
+            import_table(&mut conn, "2024 invoices", &sample(), ImportMode::Create).unwrap();
+        assert_eq!(summary.table, "_2024_invoices");
+        assert_eq!(summary.columns, vec!["Invoice", "Paid"]);
+        assert_eq!(summary.rows_imported, 2);
+
+        assert!(import_table(&mut conn, "2024 invoices", &sample(), ImportMode::Create).is_err());
+        import_table(&mut conn, "2024 invoices", &sample(), ImportMode::Append).unwrap();
+
+        let (count, paid): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(Paid) FROM _2024_invoices",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((count, paid), (4, 2));
+
+        import_table(&mut conn, "2024 invoices", &sample(), ImportMode::Replace).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM _2024_invoices", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn rejects_reserved_names() {
+        assert!(sanitize_identifier("sqlite_master").is_err());
+        assert!(sanitize_identifier("  --  ").is_err());
+    }
+}
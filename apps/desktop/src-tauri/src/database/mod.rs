@@ -1,4 +1,5 @@
 pub mod connection;
+pub mod import;
 pub mod mysql_client;
 pub mod nosql_client;
 pub mod pool;
@@ -9,6 +10,7 @@ pub mod security;
 pub mod sql_client;
 
 pub use connection::{ConnectionConfig, DatabaseType, SslConfig};
+pub use import::{ImportMode, ImportSummary};
 pub use mysql_client::MySqlClient;
 pub use nosql_client::MongoClient;
 pub use pool::{ConnectionPool, PoolConfig};
@@ -293,6 +293,21 @@ impl SqlClient {
         Ok(pool.get_stats().await)
     }
 
+    /// Database file behind a SQLite connection pool
+    pub async fn sqlite_path(&self, connection_id: &str) -> Result<String> {
+        let pool = self.get_pool(connection_id).await?;
+        let config = pool.get_config();
+        if config.db_type != DatabaseType::SQLite {
+            return Err(Error::Other(format!(
+                "Connection {} is {}, not SQLite",
+                connection_id, config.db_type
+            )));
+        }
+        config.database.clone().ok_or_else(|| {
+            Error::Other(format!("Connection {} has no database file", connection_id))
+        })
+    }
+
     // MySQL-specific operations
 
     /// Test MySQL connection health
@@ -0,0 +1,595 @@
+//! Point-in-time copies of the application database.
+//!
+//! Backups are taken with SQLite's online backup API, so the app keeps
+//! running while one is written, and can be zstd-compressed afterwards. At
+//! startup [`check_and_recover`] runs `PRAGMA integrity_check` and swaps a
+//! corrupt database for the newest backup that passes the same check.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDateTime, SubsecRound, TimeZone, Utc};
+use parking_lot::Mutex;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+
+use crate::db::repository::{get_setting, set_setting};
+use crate::db::Pool;
+
+/// Number of backups kept per label prefix
+const MAX_BACKUPS: usize = 5;
+
+/// zstd level for compressed backups; SQLite pages compress well even at
+/// the fast end
+const ZSTD_LEVEL: i32 = 3;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+const COMPRESSED_EXT: &str = ".db.zst";
+
+/// Label of backups taken by [`BackupScheduler`]
+pub const SCHEDULED_LABEL: &str = "scheduled";
+
+const INTERVAL_SETTING: &str = "backup.interval_hours";
+const COMPRESS_SETTING: &str = "backup.compress";
+const DEFAULT_INTERVAL_HOURS: u32 = 24;
+const STARTUP_DELAY: Duration = Duration::from_secs(5 * 60);
+const TICK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A backup file on disk
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+    pub compressed: bool,
+}
+
+/// When automatic backups run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    /// Hours between scheduled backups; 0 turns them off
+    pub interval_hours: u32,
+    pub compress: bool,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self {
+            interval_hours: DEFAULT_INTERVAL_HOURS,
+            compress: true,
+        }
+    }
+}
+
+/// What [`check_and_recover`] found at startup
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartupCheck {
+    /// The database is missing (first run) or passed the check
+    Healthy,
+    /// The database was corrupt and was replaced from `backup`; the damaged
+    /// file was kept at `quarantined`
+    Restored {
+        backup: PathBuf,
+        quarantined: PathBuf,
+        problems: Vec<String>,
+    },
+    /// The database was corrupt and no backup passed the check, so it was
+    /// moved to `quarantined` and the app starts with an empty database
+    Unrecoverable {
+        quarantined: PathBuf,
+        problems: Vec<String>,
+    },
+}
+
+/// Copy the main database to `dir` using SQLite's online backup API.
+///
+/// Files are named `agiworkforce-<label>-<timestamp>.db`; older backups with
+/// the same label beyond `MAX_BACKUPS` are removed.
+pub fn backup_database(conn: &Connection, dir: &Path, label: &str) -> Result<PathBuf> {
+    create_backup(conn, dir, label, false).map(|info| info.path)
+}
+
+/// Like [`backup_database`], optionally compressing the copy to
+/// `agiworkforce-<label>-<timestamp>.db.zst`
+pub fn create_backup(
+    conn: &Connection,
+    dir: &Path,
+    label: &str,
+    compress: bool,
+) -> Result<BackupInfo> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create backup directory {}", dir.display()))?;
+
+    let prefix = format!("agiworkforce-{}-", label);
+    // Names keep milliseconds; match them so listings compare equal
+    let now = Utc::now().trunc_subsecs(3);
+    let raw = dir.join(format!("{}{}.db", prefix, now.format(TIMESTAMP_FORMAT)));
+    conn.backup(DatabaseName::Main, &raw, None)
+        .with_context(|| format!("Failed to write backup {}", raw.display()))?;
+
+    let path = if compress {
+        let compressed = raw.with_extension("db.zst");
+        let result = compress_file(&raw, &compressed);
+        fs::remove_file(&raw).ok();
+        result?;
+        compressed
+    } else {
+        raw
+    };
+
+    tracing::info!("Database backed up to {}", path.display());
+    prune_backups(dir, &prefix)?;
+    let size_bytes = fs::metadata(&path)?.len();
+    Ok(BackupInfo {
+        path,
+        label: label.to_string(),
+        created_at: now,
+        size_bytes,
+        compressed: compress,
+    })
+}
+
+/// Backups in `dir`, newest first
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<BackupInfo> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (label, created_at, compressed) = parse_backup_name(path.file_name()?.to_str()?)?;
+            let size_bytes = entry.metadata().ok()?.len();
+            Some(BackupInfo {
+                path,
+                label,
+                created_at,
+                size_bytes,
+                compressed,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+/// Problems reported by `PRAGMA integrity_check`; empty when the database
+/// is sound
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows.into_iter().filter(|row| row != "ok").collect())
+}
+
+/// Replace the contents of the open database with `backup`, through the
+/// online backup API so every connection sees the restored data.
+///
+/// The backup is checked first; a damaged one leaves the database as it
+/// was. Migrations are applied afterwards in case the backup predates the
+/// current schema.
+pub fn restore_backup(conn: &mut Connection, backup: &Path) -> Result<()> {
+    let source = SourceFile::open(backup)?;
+    let problems = check_file(source.path())?;
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "Backup {} failed the integrity check: {}",
+            backup.display(),
+            problems.join("; ")
+        ));
+    }
+
+    conn.restore(
+        DatabaseName::Main,
+        source.path(),
+        None::<fn(rusqlite::backup::Progress)>,
+    )
+    .with_context(|| format!("Failed to restore from {}", backup.display()))?;
+    crate::db::migrations::run_migrations(conn)
+        .context("Failed to migrate the restored database")?;
+
+    tracing::info!("Database restored from {}", backup.display());
+    Ok(())
+}
+
+/// Check the database at `db_path` before the app opens it. A corrupt file
+/// is moved aside, along with its WAL, and the newest backup in
+/// `backup_dir` that passes the check is copied into its place.
+pub fn check_and_recover(db_path: &Path, backup_dir: &Path) -> Result<StartupCheck> {
+    if !db_path.exists() {
+        return Ok(StartupCheck::Healthy);
+    }
+    let problems = match check_file(db_path) {
+        Ok(problems) if problems.is_empty() => return Ok(StartupCheck::Healthy),
+        Ok(problems) => problems,
+        // Opening fails outright when the header itself is damaged
+        Err(e) => vec![format!("{:#}", e)],
+    };
+    tracing::error!(
+        "Database {} failed the integrity check: {}",
+        db_path.display(),
+        problems.join("; ")
+    );
+
+    let quarantined = quarantine(db_path)?;
+    for candidate in list_backups(backup_dir)? {
+        match restore_file(&candidate.path, db_path) {
+            Ok(()) => {
+                tracing::warn!(
+                    "Replaced corrupt database with backup {}",
+                    candidate.path.display()
+                );
+                return Ok(StartupCheck::Restored {
+                    backup: candidate.path,
+                    quarantined,
+                    problems,
+                });
+            }
+            Err(e) => {
+                tracing::warn!("Skipping backup {}: {:#}", candidate.path.display(), e);
+                fs::remove_file(db_path).ok();
+            }
+        }
+    }
+
+    Ok(StartupCheck::Unrecoverable {
+        quarantined,
+        problems,
+    })
+}
+
+/// Read the automatic backup settings
+pub fn schedule(conn: &Connection) -> BackupSchedule {
+    let defaults = BackupSchedule::default();
+    BackupSchedule {
+        interval_hours: get_setting(conn, INTERVAL_SETTING)
+            .ok()
+            .and_then(|setting| setting.value.parse().ok())
+            .unwrap_or(defaults.interval_hours),
+        compress: get_setting(conn, COMPRESS_SETTING)
+            .map(|setting| setting.value != "false")
+            .unwrap_or(defaults.compress),
+    }
+}
+
+pub fn set_schedule(conn: &Connection, schedule: BackupSchedule) -> Result<()> {
+    set_setting(
+        conn,
+        INTERVAL_SETTING.to_string(),
+        schedule.interval_hours.to_string(),
+        false,
+    )?;
+    set_setting(
+        conn,
+        COMPRESS_SETTING.to_string(),
+        schedule.compress.to_string(),
+        false,
+    )?;
+    Ok(())
+}
+
+/// Directory backups are written to
+pub fn backup_dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .context("Failed to get app data dir")?
+        .join("backups"))
+}
+
+/// Takes a [`SCHEDULED_LABEL`] backup whenever the newest one is older than
+/// the configured interval
+#[derive(Default)]
+pub struct BackupScheduler {
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BackupScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, app: &AppHandle) {
+        let app = app.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(STARTUP_DELAY).await;
+            loop {
+                match run_scheduled(&app).await {
+                    Ok(Some(info)) => {
+                        tracing::info!("Scheduled backup written to {}", info.path.display())
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Scheduled backup failed: {:#}", e),
+                }
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+        if let Some(previous) = self.worker.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
+/// Take a scheduled backup if one is due
+async fn run_scheduled(app: &AppHandle) -> Result<Option<BackupInfo>> {
+    let pool = app.state::<Pool>().inner().clone();
+    let dir = backup_dir(app)?;
+    pool.run(move |conn| -> Result<_> {
+        let schedule = schedule(conn);
+        if schedule.interval_hours == 0 {
+            return Ok(None);
+        }
+        let last = list_backups(&dir)?
+            .into_iter()
+            .find(|backup| backup.label == SCHEDULED_LABEL)
+            .map(|backup| backup.created_at);
+        if !is_due(last, schedule.interval_hours, Utc::now()) {
+            return Ok(None);
+        }
+        create_backup(conn, &dir, SCHEDULED_LABEL, schedule.compress).map(Some)
+    })
+    .await
+}
+
+fn is_due(last: Option<DateTime<Utc>>, interval_hours: u32, now: DateTime<Utc>) -> bool {
+    match last {
+        Some(last) => now - last >= chrono::Duration::hours(i64::from(interval_hours)),
+        None => true,
+    }
+}
+
+/// `(label, created_at, compressed)` from `agiworkforce-<label>-<timestamp>.db[.zst]`
+fn parse_backup_name(name: &str) -> Option<(String, DateTime<Utc>, bool)> {
+    let rest = name.strip_prefix("agiworkforce-")?;
+    let (stem, compressed) = match rest.strip_suffix(COMPRESSED_EXT) {
+        Some(stem) => (stem, true),
+        None => (rest.strip_suffix(".db")?, false),
+    };
+    let (label, timestamp) = stem.rsplit_once('-')?;
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    Some((
+        label.to_string(),
+        Utc.from_utc_datetime(&created_at),
+        compressed,
+    ))
+}
+
+fn prune_backups(dir: &Path, prefix: &str) -> Result<()> {
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(prefix)
+                        && (name.ends_with(".db") || name.ends_with(COMPRESSED_EXT))
+                })
+        })
+        .collect();
+
+    // Timestamps sort lexically, newest last
+    backups.sort_by_key(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.trim_end_matches(".zst").to_string())
+    });
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for old in backups.into_iter().take(excess) {
+        if let Err(e) = fs::remove_file(&old) {
+            tracing::warn!("Failed to remove old backup {}: {}", old.display(), e);
+        }
+    }
+    Ok(())
+}
+
+fn compress_file(src: &Path, dst: &Path) -> Result<()> {
+    let reader = BufReader::new(File::open(src)?);
+    let writer = BufWriter::new(
+        File::create(dst).with_context(|| format!("Failed to create {}", dst.display()))?,
+    );
+    zstd::stream::copy_encode(reader, writer, ZSTD_LEVEL)
+        .with_context(|| format!("Failed to compress {}", src.display()))
+}
+
+fn decompress_file(src: &Path, dst: &Path) -> Result<()> {
+    let reader = BufReader::new(File::open(src)?);
+    let writer = BufWriter::new(
+        File::create(dst).with_context(|| format!("Failed to create {}", dst.display()))?,
+    );
+    zstd::stream::copy_decode(reader, writer)
+        .with_context(|| format!("Failed to decompress {}", src.display()))
+}
+
+/// Run the integrity check on a file without modifying it
+fn check_file(path: &Path) -> Result<Vec<String>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    integrity_check(&conn)
+}
+
+/// Copy a verified backup to `db_path`, which must not be open
+fn restore_file(backup: &Path, db_path: &Path) -> Result<()> {
+    let source = SourceFile::open(backup)?;
+    let problems = check_file(source.path())?;
+    if !problems.is_empty() {
+        return Err(anyhow!("integrity check failed: {}", problems.join("; ")));
+    }
+    fs::copy(source.path(), db_path)
+        .with_context(|| format!("Failed to copy backup to {}", db_path.display()))?;
+    Ok(())
+}
+
+/// Move a damaged database and its WAL/SHM files aside
+fn quarantine(db_path: &Path) -> Result<PathBuf> {
+    let target = with_suffix(
+        db_path,
+        &format!(".corrupt-{}", Utc::now().format(TIMESTAMP_FORMAT)),
+    );
+    fs::rename(db_path, &target)
+        .with_context(|| format!("Failed to move {} aside", db_path.display()))?;
+    for ext in ["-wal", "-shm"] {
+        let file = with_suffix(db_path, ext);
+        if file.exists() {
+            fs::rename(&file, with_suffix(&target, ext)).ok();
+        }
+    }
+    tracing::warn!("Corrupt database kept at {}", target.display());
+    Ok(target)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// A backup ready to be read by SQLite: the file itself, or a temporary
+/// decompressed copy that is removed on drop
+struct SourceFile {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl SourceFile {
+    fn open(backup: &Path) -> Result<Self> {
+        if !backup.exists() {
+            return Err(anyhow!("Backup {} does not exist", backup.display()));
+        }
+        let compressed = backup
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".zst"));
+        if !compressed {
+            return Ok(Self {
+                path: backup.to_path_buf(),
+                temporary: false,
+            });
+        }
+        let path =
+            std::env::temp_dir().join(format!("agiworkforce-restore-{}.db", uuid::Uuid::new_v4()));
+        let source = Self {
+            path,
+            temporary: true,
+        };
+        decompress_file(backup, &source.path)?;
+        Ok(source)
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SourceFile {
+    fn drop(&mut self) {
+        if self.temporary {
+            fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded(path: &Path, value: i64) -> Connection {
+        let conn = crate::db::pool::open(path).unwrap();
+        conn.execute_batch("CREATE TABLE IF NOT EXISTS t (v INTEGER)")
+            .unwrap();
+        conn.execute("DELETE FROM t", []).unwrap();
+        conn.execute("INSERT INTO t VALUES (?1)", [value]).unwrap();
+        conn
+    }
+
+    fn value(conn: &Connection) -> i64 {
+        conn.query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_compressed_backup_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = dir.path().join("backups");
+        let mut conn = seeded(&dir.path().join("app.db"), 1);
+
+        let info = create_backup(&conn, &backups, "manual", true).unwrap();
+        assert!(info.compressed);
+        assert!(info.path.to_string_lossy().ends_with(".db.zst"));
+        assert_eq!(list_backups(&backups).unwrap(), vec![info.clone()]);
+
+        conn.execute("UPDATE t SET v = 2", []).unwrap();
+        restore_backup(&mut conn, &info.path).unwrap();
+        assert_eq!(value(&conn), 1);
+        assert!(integrity_check(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_damaged_backup_is_not_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = seeded(&dir.path().join("app.db"), 1);
+        let bogus = dir.path().join("agiworkforce-manual-20240101T000000000.db");
+        fs::write(&bogus, b"not a database").unwrap();
+
+        assert!(restore_backup(&mut conn, &bogus).is_err());
+        assert_eq!(value(&conn), 1);
+    }
+
+    #[test]
+    fn test_corrupt_database_falls_back_to_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        let backups = dir.path().join("backups");
+        {
+            let conn = seeded(&db_path, 7);
+            create_backup(&conn, &backups, SCHEDULED_LABEL, true).unwrap();
+        }
+        assert_eq!(
+            check_and_recover(&db_path, &backups).unwrap(),
+            StartupCheck::Healthy
+        );
+
+        fs::write(&db_path, vec![0xAB; 8192]).unwrap();
+        let StartupCheck::Restored { quarantined, .. } =
+            check_and_recover(&db_path, &backups).unwrap()
+        else {
+            panic!("expected the backup to be restored");
+        };
+        assert!(quarantined.exists());
+        assert_eq!(value(&Connection::open(&db_path).unwrap()), 7);
+    }
+
+    #[test]
+    fn test_corrupt_database_without_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        fs::write(&db_path, vec![0xAB; 8192]).unwrap();
+
+        let check = check_and_recover(&db_path, &dir.path().join("backups")).unwrap();
+        assert!(matches!(check, StartupCheck::Unrecoverable { .. }));
+        assert!(!db_path.exists());
+    }
+
+    #[test]
+    fn test_backup_names_and_schedule() {
+        let (label, created_at, compressed) =
+            parse_backup_name("agiworkforce-pre-migration-20240102T030405006.db.zst").unwrap();
+        assert_eq!(label, "pre-migration");
+        assert!(compressed);
+        assert_eq!(created_at.to_rfc3339(), "2024-01-02T03:04:05.006+00:00");
+        assert!(parse_backup_name("agi.db").is_none());
+
+        let now = Utc::now();
+        assert!(is_due(None, 24, now));
+        assert!(!is_due(Some(now - chrono::Duration::hours(23)), 24, now));
+        assert!(is_due(Some(now - chrono::Duration::hours(24)), 24, now));
+    }
+}
@@ -0,0 +1,110 @@
+//! Batched writes for high-volume tables.
+//!
+//! Autocommit mode pays for a journal sync on every INSERT. Writers that
+//! produce many rows at once (embedding indexing, telemetry flushes, metrics
+//! backfills) go through [`write_batched`] instead: one prepared statement is
+//! reused for every row and rows are committed in transactions of
+//! `batch_size`.
+
+use rusqlite::{Connection, Statement};
+
+/// Rows per transaction. Large enough to amortise the commit, small enough
+/// that a long write does not hold the write lock for seconds.
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Execute `sql` once per row, committing every `batch_size` rows.
+///
+/// `bind` executes the cached statement for one row and returns the number
+/// of rows it changed. A failure rolls back the current chunk only; chunks
+/// already committed stay written.
+pub fn write_batched<T>(
+    conn: &mut Connection,
+    sql: &str,
+    rows: &[T],
+    batch_size: usize,
+    mut bind: impl FnMut(&mut Statement<'_>, &T) -> rusqlite::Result<usize>,
+) -> rusqlite::Result<usize> {
+    let mut written = 0;
+    for chunk in rows.chunks(batch_size.max(1)) {
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(sql)?;
+            for row in chunk {
+                written += bind(&mut stmt, row)?;
+            }
+        }
+        tx.commit()?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::params;
+    use std::time::Instant;
+
+    const INSERT: &str = "INSERT INTO rows (id, payload) VALUES (?1, ?2)";
+
+    fn open(dir: &tempfile::TempDir) -> Connection {
+        let conn = Connection::open(dir.path().join("batch.db")).unwrap();
+        conn.execute_batch("CREATE TABLE rows (id INTEGER PRIMARY KEY, payload TEXT NOT NULL);")
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_failed_chunk_rolls_back_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = open(&dir);
+        // Row 7 collides with row 2, failing the second chunk of five
+        let ids: Vec<i64> = vec![0, 1, 2, 3, 4, 5, 6, 2, 8, 9];
+
+        let result = write_batched(&mut conn, INSERT, &ids, 5, |stmt, id| {
+            stmt.execute(params![id, "x"])
+        });
+        assert!(result.is_err());
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM rows", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 5);
+    }
+
+    /// Throughput target: 10k rows through an on-disk database in well under
+    /// a second, and at least 5x faster than autocommit row-by-row inserts.
+    #[test]
+    #[ignore] // Wall-clock benchmark; run with --ignored
+    fn bench_batched_insert_throughput() {
+        const ROWS: i64 = 10_000;
+        const SAMPLE: i64 = 200;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = open(&dir);
+        let ids: Vec<i64> = (0..ROWS).collect();
+
+        let started = Instant::now();
+        let written = write_batched(&mut conn, INSERT, &ids, DEFAULT_BATCH_SIZE, |stmt, id| {
+            stmt.execute(params![id, "payload"])
+        })
+        .unwrap();
+        let batched = started.elapsed();
+        assert_eq!(written, ROWS as usize);
+
+        let started = Instant::now();
+        for id in ROWS..ROWS + SAMPLE {
+            conn.execute(INSERT, params![id, "payload"]).unwrap();
+        }
+        let per_row = started.elapsed() / SAMPLE as u32;
+        let batched_per_row = batched / ROWS as u32;
+
+        let rows_per_sec = ROWS as f64 / batched.as_secs_f64();
+        assert!(rows_per_sec > 10_000.0, "only {:.0} rows/s", rows_per_sec);
+        assert!(
+            per_row > batched_per_row * 5,
+            "batching gave less than 5x ({:?} vs {:?})",
+            per_row,
+            batched_per_row
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Conversation branches.
+//!
+//! Messages form a tree through `parent_message_id`, and a conversation
+//! shows the path from its active leaf back to the root. Regenerating a
+//! reply adds a sibling of the original on a new `branch_id`; messages sent
+//! afterwards inherit the branch of the message they follow. Switching
+//! branches moves the active leaf to the latest message on that branch.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+use super::models::Message;
+use super::repository;
+
+/// Longest branch preview, in characters
+const PREVIEW_CHARS: usize = 200;
+
+/// Line-diff table cells to fill before giving up and reporting the whole
+/// message as replaced
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationBranch {
+    pub branch_id: i64,
+    /// Message the branch continues from; `None` when it starts the
+    /// conversation
+    pub fork_message_id: Option<i64>,
+    pub first_message_id: i64,
+    /// Latest message on the branch, which becomes the active leaf when the
+    /// branch is switched to
+    pub leaf_message_id: i64,
+    pub message_count: i64,
+    pub preview: String,
+    pub created_at: DateTime<Utc>,
+    /// Whether the conversation currently shows this branch
+    pub active: bool,
+    /// How the first message differs from the oldest sibling it was
+    /// regenerated alongside; `None` for the original branch
+    pub diff: Option<MessageDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageDiff {
+    pub base_message_id: i64,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// Every line of both messages, prefixed with `+ `, `- ` or two spaces
+    pub preview: String,
+}
+
+/// Insert `message` as a sibling of `original` on a new branch and make it
+/// the active leaf. Returns the new message's ID and branch.
+pub fn add_sibling(conn: &Connection, original: &Message, message: &Message) -> Result<(i64, i64)> {
+    let parent: Option<i64> = conn.query_row(
+        "SELECT parent_message_id FROM messages WHERE id = ?1",
+        params![original.id],
+        |row| row.get(0),
+    )?;
+    let branch_id: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(branch_id), -1) + 1 FROM messages WHERE conversation_id = ?1",
+        params![original.conversation_id],
+        |row| row.get(0),
+    )?;
+
+    // The insert trigger threads the message onto the active leaf; move it
+    // next to the original instead
+    let id = repository::create_message(conn, message)?;
+    conn.execute(
+        "UPDATE messages SET parent_message_id = ?1, branch_id = ?2 WHERE id = ?3",
+        params![parent, branch_id, id],
+    )?;
+
+    Ok((id, branch_id))
+}
+
+/// Show `branch_id` in the conversation, returning the new active leaf
+pub fn switch_branch(conn: &Connection, conversation_id: i64, branch_id: i64) -> Result<i64> {
+    let leaf: Option<i64> = conn.query_row(
+        "SELECT MAX(id) FROM messages WHERE conversation_id = ?1 AND branch_id = ?2",
+        params![conversation_id, branch_id],
+        |row| row.get(0),
+    )?;
+    let leaf = leaf.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+    conn.execute(
+        "UPDATE conversations SET active_leaf_id = ?1 WHERE id = ?2",
+        params![leaf, conversation_id],
+    )?;
+    Ok(leaf)
+}
+
+/// Every branch in the conversation, oldest first
+pub fn list_branches(conn: &Connection, conversation_id: i64) -> Result<Vec<ConversationBranch>> {
+    let active_branch: Option<i64> = conn
+        .query_row(
+            "SELECT m.branch_id FROM conversations c
+             JOIN messages m ON m.id = c.active_leaf_id
+             WHERE c.id = ?1",
+            params![conversation_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT branch_id, MIN(id), MAX(id), COUNT(*)
+         FROM messages
+         WHERE conversation_id = ?1 AND branch_id IS NOT NULL
+         GROUP BY branch_id
+         ORDER BY branch_id ASC",
+    )?;
+    let rows = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut branches = Vec::with_capacity(rows.len());
+    for (branch_id, first_id, leaf_id, message_count) in rows {
+        let first = repository::get_message(conn, first_id)?;
+        let fork_message_id: Option<i64> = conn.query_row(
+            "SELECT parent_message_id FROM messages WHERE id = ?1",
+            params![first_id],
+            |row| row.get(0),
+        )?;
+
+        // The oldest earlier reply to the same message
+        let base: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM messages
+                 WHERE conversation_id = ?1 AND parent_message_id IS ?2
+                   AND id < ?3 AND branch_id < ?4
+                 ORDER BY id ASC
+                 LIMIT 1",
+                params![conversation_id, fork_message_id, first_id, branch_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let diff = match base {
+            Some(base_id) => {
+                let base = repository::get_message(conn, base_id)?;
+                Some(diff_messages(&base, &first))
+            }
+            None => None,
+        };
+
+        branches.push(ConversationBranch {
+            branch_id,
+            fork_message_id,
+            first_message_id: first_id,
+            leaf_message_id: leaf_id,
+            message_count,
+            preview: first.content.chars().take(PREVIEW_CHARS).collect(),
+            created_at: first.created_at,
+            active: active_branch == Some(branch_id),
+            diff,
+        });
+    }
+
+    Ok(branches)
+}
+
+/// Line diff of `new` against `base`
+pub fn diff_messages(base: &Message, new: &Message) -> MessageDiff {
+    let old: Vec<&str> = base.content.lines().collect();
+    let new_lines: Vec<&str> = new.content.lines().collect();
+
+    let mut diff = MessageDiff {
+        base_message_id: base.id,
+        lines_added: 0,
+        lines_removed: 0,
+        preview: String::new(),
+    };
+    let push = |diff: &mut MessageDiff, prefix: &str, line: &str| {
+        diff.preview.push_str(prefix);
+        diff.preview.push_str(line);
+        diff.preview.push('\n');
+    };
+
+    if (old.len() + 1) * (new_lines.len() + 1) > MAX_DIFF_CELLS {
+        for line in &old {
+            push(&mut diff, "- ", line);
+        }
+        for line in &new_lines {
+            push(&mut diff, "+ ", line);
+        }
+        diff.lines_removed = old.len();
+        diff.lines_added = new_lines.len();
+        return diff;
+    }
+
+    // Longest common subsequence of lines, filled from the end so the walk
+    // below can go front to back
+    let width = new_lines.len() + 1;
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new_lines.len()).rev() {
+            lcs[i * width + j] = if old[i] == new_lines[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new_lines.len() {
+        if i < old.len() && j < new_lines.len() && old[i] == new_lines[j] {
+            push(&mut diff, "  ", old[i]);
+            i += 1;
+            j += 1;
+        } else if i < old.len()
+            && (j == new_lines.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            push(&mut diff, "- ", old[i]);
+            diff.lines_removed += 1;
+            i += 1;
+        } else {
+            push(&mut diff, "+ ", new_lines[j]);
+            diff.lines_added += 1;
+            j += 1;
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use crate::db::models::MessageRole;
+
+    fn setup() -> (Connection, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let conversation_id = repository::create_conversation(&conn, "Branches".into()).unwrap();
+        (conn, conversation_id)
+    }
+
+    fn send(conn: &Connection, conversation_id: i64, role: MessageRole, content: &str) -> Message {
+        let id =
+            repository::create_message(conn, &Message::new(conversation_id, role, content.into()))
+                .unwrap();
+        repository::get_message(conn, id).unwrap()
+    }
+
+    fn contents(conn: &Connection, conversation_id: i64) -> Vec<String> {
+        repository::list_messages(conn, conversation_id)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.content)
+            .collect()
+    }
+
+    #[test]
+    fn test_regenerate_and_switch_branches() {
+        let (conn, conv) = setup();
+        send(&conn, conv, MessageRole::User, "Name a color");
+        let original = send(&conn, conv, MessageRole::Assistant, "Red\nIt is warm");
+        send(&conn, conv, MessageRole::User, "Why?");
+
+        let retry = Message::new(conv, MessageRole::Assistant, "Blue\nIt is warm".into());
+        let (_, branch) = add_sibling(&conn, &original, &retry).unwrap();
+        assert_eq!(branch, 1);
+        assert_eq!(contents(&conn, conv), ["Name a color", "Blue\nIt is warm"]);
+
+        // New messages continue the regenerated branch
+        send(&conn, conv, MessageRole::User, "Thanks");
+        assert_eq!(
+            contents(&conn, conv),
+            ["Name a color", "Blue\nIt is warm", "Thanks"]
+        );
+
+        let branches = list_branches(&conn, conv).unwrap();
+        assert_eq!(branches.len(), 2);
+        assert!(!branches[0].active && branches[1].active);
+        assert_eq!(branches[1].message_count, 2);
+        let diff = branches[1].diff.as_ref().unwrap();
+        assert_eq!(diff.base_message_id, original.id);
+        assert_eq!((diff.lines_added, diff.lines_removed), (1, 1));
+        assert_eq!(diff.preview, "- Red\n+ Blue\n  It is warm\n");
+
+        switch_branch(&conn, conv, 0).unwrap();
+        assert_eq!(
+            contents(&conn, conv),
+            ["Name a color", "Red\nIt is warm", "Why?"]
+        );
+        assert!(switch_branch(&conn, conv, 7).is_err());
+    }
+
+    #[test]
+    fn test_deleting_messages_keeps_the_thread() {
+        let (conn, conv) = setup();
+        send(&conn, conv, MessageRole::User, "one");
+        let two = send(&conn, conv, MessageRole::Assistant, "two");
+        let three = send(&conn, conv, MessageRole::User, "three");
+
+        repository::delete_message(&conn, two.id).unwrap();
+        assert_eq!(contents(&conn, conv), ["one", "three"]);
+        repository::delete_message(&conn, three.id).unwrap();
+        assert_eq!(contents(&conn, conv), ["one"]);
+        send(&conn, conv, MessageRole::User, "four");
+        assert_eq!(contents(&conn, conv), ["one", "four"]);
+    }
+}
@@ -1,241 +1,796 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
 use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use super::backup;
 
 /// Current schema version
-const CURRENT_VERSION: i32 = 41;
+const CURRENT_VERSION: i32 = 80;
+
+/// Command-line flag that prints the pending migration SQL and exits
+pub const DRY_RUN_FLAG: &str = "--migrate-dry-run";
 
 /// Initialize database and run migrations
 pub fn run_migrations(conn: &Connection) -> Result<()> {
     // Enable foreign keys
     conn.execute("PRAGMA foreign_keys = ON", [])?;
 
-    // Create version table if not exists
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS schema_version (
-            version INTEGER PRIMARY KEY,
-            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
-
-    // Get current version
-    let current_version: i32 = conn
-        .query_row(
-            "SELECT COALESCE(MAX(version), 0) FROM schema_version",
-            [],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+    let current_version = current_version(conn)?;
 
-    // Apply migrations
+    // A newer build already migrated this database; leave it alone
     if current_version > CURRENT_VERSION {
         return Ok(());
     }
-    if current_version < 1 {
-        apply_migration_v1(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [1])?;
-    }
 
-    if current_version < 2 {
-        apply_migration_v2(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [2])?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current_version) {
+        apply_step(conn, migration, MigrationDirection::Up)?;
     }
 
-    if current_version < 3 {
-        apply_migration_v3(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [3])?;
-    }
-
-    if current_version < 4 {
-        apply_migration_v4(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [4])?;
-    }
-
-    if current_version < 5 {
-        apply_migration_v5(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [5])?;
-    }
-
-    if current_version < 6 {
-        apply_migration_v6(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [6])?;
-    }
-
-    if current_version < 7 {
-        apply_migration_v7(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [7])?;
-    }
-
-    if current_version < 8 {
-        apply_migration_v8(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [8])?;
-    }
-
-    if current_version < 9 {
-        apply_migration_v9(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [9])?;
-    }
-
-    if current_version < 10 {
-        apply_migration_v10(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [10])?;
-    }
+    Ok(())
+}
 
-    if current_version < 11 {
-        apply_migration_v11(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [11])?;
-    }
+type MigrationFn = fn(&Connection) -> Result<()>;
 
-    if current_version < 12 {
-        apply_migration_v12(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [12])?;
-    }
+/// A numbered schema migration. `down` is only provided for migrations that
+/// add self-contained tables, so reverting them cannot break older columns.
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    up: MigrationFn,
+    down: Option<MigrationFn>,
+}
 
-    if current_version < 13 {
-        apply_migration_v13(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [13])?;
+impl Migration {
+    const fn up(version: i32, name: &'static str, up: MigrationFn) -> Self {
+        Self {
+            version,
+            name,
+            up,
+            down: None,
+        }
     }
 
-    if current_version < 14 {
-        apply_migration_v14(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [14])?;
+    const fn reversible(
+        version: i32,
+        name: &'static str,
+        up: MigrationFn,
+        down: MigrationFn,
+    ) -> Self {
+        Self {
+            version,
+            name,
+            up,
+            down: Some(down),
+        }
     }
 
-    if current_version < 15 {
-        apply_migration_v15(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [15])?;
+    pub fn is_reversible(&self) -> bool {
+        self.down.is_some()
     }
+}
 
-    if current_version < 16 {
-        apply_migration_v16(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [16])?;
-    }
+/// Every migration in version order; the last entry must be `CURRENT_VERSION`
+const MIGRATIONS: &[Migration] = &[
+    Migration::up(1, "Initial schema", apply_migration_v1),
+    Migration::up(2, "Screen capture and OCR tables", apply_migration_v2),
+    Migration::up(
+        3,
+        "System automation permissions and audit logging",
+        apply_migration_v3,
+    ),
+    Migration::up(
+        4,
+        "Enhanced settings table with categories and timestamps",
+        apply_migration_v4,
+    ),
+    Migration::up(
+        5,
+        "add provider/model metadata and cache table",
+        apply_migration_v5,
+    ),
+    Migration::up(
+        6,
+        "Browser automation sessions and tabs",
+        apply_migration_v6,
+    ),
+    Migration::up(7, "Email accounts and contacts", apply_migration_v7),
+    Migration::up(8, "Calendar accounts storage", apply_migration_v8),
+    Migration::up(
+        9,
+        "Enhanced messages with context items, images, tool calls, artifacts",
+        apply_migration_v9,
+    ),
+    Migration::up(
+        10,
+        "MCP (Model Context Protocol) infrastructure",
+        apply_migration_v10,
+    ),
+    Migration::up(
+        11,
+        "Autonomous operations (AGI task logs and sessions)",
+        apply_migration_v11,
+    ),
+    Migration::up(
+        12,
+        "Performance indexes for common queries",
+        apply_migration_v12,
+    ),
+    Migration::up(
+        13,
+        "Conversation checkpoints for safe AI editing",
+        apply_migration_v13,
+    ),
+    Migration::up(
+        14,
+        "Performance indexes for common queries",
+        apply_migration_v14,
+    ),
+    Migration::up(15, "Onboarding progress tracking", apply_migration_v15),
+    Migration::up(
+        16,
+        "Enhanced LLM response cache with statistics tracking",
+        apply_migration_v16,
+    ),
+    Migration::up(
+        17,
+        "Codebase analysis cache for AGI system",
+        apply_migration_v17,
+    ),
+    Migration::up(
+        18,
+        "Billing and subscription management (Stripe integration)",
+        apply_migration_v18,
+    ),
+    Migration::up(19, "Workflow definitions table", apply_migration_v19),
+    Migration::up(20, "Workflow executions table", apply_migration_v20),
+    Migration::up(21, "Workflow execution logs table", apply_migration_v21),
+    Migration::up(
+        22,
+        "Process Reasoning (Process-Aware Planning Layer / Outcome Engine)",
+        apply_migration_v22,
+    ),
+    Migration::up(
+        23,
+        "Agent templates and template installs",
+        apply_migration_v23,
+    ),
+    Migration::up(24, "Team collaboration tables", apply_migration_v24),
+    Migration::up(
+        25,
+        "Governance and audit system for enterprise compliance",
+        apply_migration_v25,
+    ),
+    Migration::up(26, "ROI Analytics - Snapshots table", apply_migration_v26),
+    Migration::up(
+        27,
+        "ROI Analytics - Enhanced automation tracking",
+        apply_migration_v27,
+    ),
+    Migration::up(
+        28,
+        "ROI Analytics - Process benchmarks and best practices",
+        apply_migration_v28,
+    ),
+    Migration::up(
+        29,
+        "Enhanced tutorial and onboarding system",
+        apply_migration_v29,
+    ),
+    Migration::up(30, "Real-time collaboration tables", apply_migration_v30),
+    Migration::up(
+        31,
+        "Computer Use Agent sessions and actions",
+        apply_migration_v31,
+    ),
+    Migration::up(32, "Messaging platform integrations", apply_migration_v32),
+    Migration::up(
+        33,
+        "AI Employee Library and Real-time metrics tracking",
+        apply_migration_v33,
+    ),
+    Migration::up(34, "User milestones tracking", apply_migration_v34),
+    Migration::up(
+        35,
+        "Metrics aggregation cache for dashboard performance",
+        apply_migration_v35,
+    ),
+    Migration::up(36, "ROI comparison benchmarks", apply_migration_v36),
+    Migration::up(37, "First-run experience tracking", apply_migration_v37),
+    Migration::up(38, "Demo runs tracking", apply_migration_v38),
+    Migration::up(39, "Public workflow marketplace", apply_migration_v39),
+    Migration::up(
+        40,
+        "Authentication and Authorization system",
+        apply_migration_v40,
+    ),
+    Migration::reversible(
+        41,
+        "Background task management system",
+        apply_migration_v41,
+        revert_migration_v41,
+    ),
+    Migration::reversible(
+        42,
+        "Persisted cloud storage accounts",
+        apply_migration_v42,
+        revert_migration_v42,
+    ),
+    Migration::reversible(
+        43,
+        "Per-tool reliability telemetry",
+        apply_migration_v43,
+        revert_migration_v43,
+    ),
+    Migration::reversible(
+        44,
+        "Daily LLM spend ledger used by the router budget guard",
+        apply_migration_v44,
+        revert_migration_v44,
+    ),
+    Migration::reversible(
+        45,
+        "Prompt embeddings for semantic cache lookups and hit-rate counters",
+        apply_migration_v45,
+        revert_migration_v45,
+    ),
+    Migration::reversible(
+        46,
+        "Per-minute provider health aggregates for adaptive routing",
+        apply_migration_v46,
+        revert_migration_v46,
+    ),
+    Migration::reversible(
+        47,
+        "Indexes backing paginated list queries",
+        apply_migration_v47,
+        revert_migration_v47,
+    ),
+    Migration::reversible(
+        48,
+        "Telemetry events flushed from the in-memory collector",
+        apply_migration_v48,
+        revert_migration_v48,
+    ),
+    Migration::reversible(
+        49,
+        "Template dependency declarations",
+        apply_migration_v49,
+        revert_migration_v49,
+    ),
+    Migration::reversible(
+        50,
+        "Workflow execution artifacts",
+        apply_migration_v50,
+        revert_migration_v50,
+    ),
+    Migration::reversible(
+        51,
+        "Background IMAP sync state",
+        apply_migration_v51,
+        revert_migration_v51,
+    ),
+    Migration::reversible(52, "Email rules", apply_migration_v52, revert_migration_v52),
+    Migration::reversible(
+        53,
+        "Email outbox",
+        apply_migration_v53,
+        revert_migration_v53,
+    ),
+    Migration::reversible(
+        54,
+        "Employee pipelines",
+        apply_migration_v54,
+        revert_migration_v54,
+    ),
+    Migration::reversible(
+        55,
+        "Productivity task cache",
+        apply_migration_v55,
+        revert_migration_v55,
+    ),
+    Migration::reversible(
+        56,
+        "Productivity accounts",
+        apply_migration_v56,
+        revert_migration_v56,
+    ),
+    Migration::reversible(
+        57,
+        "Credential health",
+        apply_migration_v57,
+        revert_migration_v57,
+    ),
+    Migration::reversible(
+        58,
+        "WebDAV cloud accounts",
+        apply_migration_v58,
+        revert_migration_v58,
+    ),
+    Migration::reversible(
+        59,
+        "Global full-text search index",
+        apply_migration_v59,
+        revert_migration_v59,
+    ),
+    Migration::reversible(
+        60,
+        "Integration health checks",
+        apply_migration_v60,
+        revert_migration_v60,
+    ),
+    Migration::reversible(
+        61,
+        "Autocomplete usage",
+        apply_migration_v61,
+        revert_migration_v61,
+    ),
+    Migration::reversible(
+        62,
+        "Cache warmup patterns",
+        apply_migration_v62,
+        revert_migration_v62,
+    ),
+    Migration::reversible(
+        63,
+        "Presence visibility rules",
+        apply_migration_v63,
+        revert_migration_v63,
+    ),
+    Migration::reversible(
+        64,
+        "Conversation summaries",
+        apply_migration_v64,
+        revert_migration_v64,
+    ),
+    Migration::reversible(
+        65,
+        "Tool cost ledger",
+        apply_migration_v65,
+        revert_migration_v65,
+    ),
+    Migration::reversible(
+        66,
+        "Conversation branches",
+        apply_migration_v66,
+        revert_migration_v66,
+    ),
+    Migration::reversible(
+        67,
+        "Secrets vault",
+        apply_migration_v67,
+        revert_migration_v67,
+    ),
+    Migration::reversible(
+        68,
+        "Permission profiles",
+        apply_migration_v68,
+        revert_migration_v68,
+    ),
+    Migration::reversible(
+        69,
+        "Append-only audit trail",
+        apply_migration_v69,
+        revert_migration_v69,
+    ),
+    Migration::reversible(
+        70,
+        "Agent run traces",
+        apply_migration_v70,
+        revert_migration_v70,
+    ),
+    Migration::reversible(
+        71,
+        "Agent file snapshots",
+        apply_migration_v71,
+        revert_migration_v71,
+    ),
+    Migration::reversible(
+        72,
+        "Agent run budgets",
+        apply_migration_v72,
+        revert_migration_v72,
+    ),
+    Migration::reversible(
+        73,
+        "Agent skill library",
+        apply_migration_v73,
+        revert_migration_v73,
+    ),
+    Migration::reversible(
+        74,
+        "Workflow triggers",
+        apply_migration_v74,
+        revert_migration_v74,
+    ),
+    Migration::reversible(
+        75,
+        "MCP server clients",
+        apply_migration_v75,
+        revert_migration_v75,
+    ),
+    Migration::reversible(
+        76,
+        "Browser profiles",
+        apply_migration_v76,
+        revert_migration_v76,
+    ),
+    Migration::reversible(
+        77,
+        "Selector healing events",
+        apply_migration_v77,
+        revert_migration_v77,
+    ),
+    Migration::reversible(
+        78,
+        "Remote API clients",
+        apply_migration_v78,
+        revert_migration_v78,
+    ),
+    Migration::reversible(
+        79,
+        "Workflow webhook deliveries",
+        apply_migration_v79,
+        revert_migration_v79,
+    ),
+    Migration::reversible(
+        80,
+        "Link background tasks to conversations",
+        apply_migration_v80,
+        revert_migration_v80,
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationDirection {
+    Up,
+    Down,
+}
 
-    if current_version < 17 {
-        apply_migration_v17(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [17])?;
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStep {
+    pub version: i32,
+    pub name: String,
+    pub direction: MigrationDirection,
+    /// Statements the step runs; only filled in by [`preview_migrations`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sql: Vec<String>,
+}
 
-    if current_version < 18 {
-        apply_migration_v18(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [18])?;
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationPlan {
+    pub current_version: i32,
+    pub target_version: i32,
+    pub steps: Vec<MigrationStep>,
+    pub dry_run: bool,
+    pub backup_path: Option<String>,
+}
 
-    if current_version < 19 {
-        apply_migration_v19(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [19])?;
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub version: i32,
+    pub name: Option<String>,
+    pub applied_at: String,
+}
 
-    if current_version < 20 {
-        apply_migration_v20(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [20])?;
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersionInfo {
+    pub current_version: i32,
+    pub latest_version: i32,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<MigrationStep>,
+}
 
-    if current_version < 21 {
-        apply_migration_v21(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [21])?;
-    }
+/// Latest schema version this build knows about
+pub fn latest_version() -> i32 {
+    CURRENT_VERSION
+}
 
-    if current_version < 22 {
-        apply_migration_v22(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [22])?;
-    }
+/// Every migration this build knows about, in version order
+pub fn registry() -> &'static [Migration] {
+    MIGRATIONS
+}
 
-    if current_version < 23 {
-        apply_migration_v23(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [23])?;
-    }
+/// Highest applied version, or 0 for a fresh database
+pub fn current_version(conn: &Connection) -> Result<i32> {
+    ensure_version_table(conn)?;
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+}
 
-    if current_version < 24 {
-        apply_migration_v24(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [24])?;
-    }
+pub fn schema_version_info(conn: &Connection) -> Result<SchemaVersionInfo> {
+    let current_version = current_version(conn)?;
 
-    if current_version < 25 {
-        apply_migration_v25(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [25])?;
-    }
+    let mut stmt =
+        conn.prepare("SELECT version, applied_at FROM schema_version ORDER BY version ASC")?;
+    let applied = stmt
+        .query_map([], |row| {
+            let version: i32 = row.get(0)?;
+            Ok(AppliedMigration {
+                version,
+                name: find_migration(version).map(|m| m.name.to_string()),
+                applied_at: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current_version)
+        .map(|m| step(m, MigrationDirection::Up))
+        .collect();
+
+    Ok(SchemaVersionInfo {
+        current_version,
+        latest_version: CURRENT_VERSION,
+        applied,
+        pending,
+    })
+}
 
-    if current_version < 26 {
-        apply_migration_v26(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [26])?;
+/// Work out which migrations would move the schema to `target` (defaults to latest)
+pub fn plan_migrations(conn: &Connection, target: Option<i32>) -> anyhow::Result<MigrationPlan> {
+    let current_version = current_version(conn)?;
+    let target_version = target.unwrap_or(CURRENT_VERSION);
+
+    if !(0..=CURRENT_VERSION).contains(&target_version) {
+        bail!(
+            "Target schema version {} is outside 0..={}",
+            target_version,
+            CURRENT_VERSION
+        );
     }
-
-    if current_version < 27 {
-        apply_migration_v27(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [27])?;
+    if current_version > CURRENT_VERSION {
+        bail!(
+            "Database schema version {} is newer than this build ({})",
+            current_version,
+            CURRENT_VERSION
+        );
     }
 
-    if current_version < 28 {
-        apply_migration_v28(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [28])?;
-    }
+    let steps = if target_version >= current_version {
+        MIGRATIONS
+            .iter()
+            .filter(|m| m.version > current_version && m.version <= target_version)
+            .map(|m| step(m, MigrationDirection::Up))
+            .collect()
+    } else {
+        let mut steps = Vec::new();
+        for migration in MIGRATIONS
+            .iter()
+            .rev()
+            .filter(|m| m.version > target_version && m.version <= current_version)
+        {
+            if !migration.is_reversible() {
+                bail!(
+                    "Migration v{} ({}) cannot be reverted",
+                    migration.version,
+                    migration.name
+                );
+            }
+            steps.push(step(migration, MigrationDirection::Down));
+        }
+        steps
+    };
+
+    Ok(MigrationPlan {
+        current_version,
+        target_version,
+        steps,
+        dry_run: false,
+        backup_path: None,
+    })
+}
 
-    if current_version < 29 {
-        apply_migration_v29(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [29])?;
+/// Move the schema to `target`, applying up or down migrations as needed.
+///
+/// With `dry_run` every step runs inside a savepoint that is rolled back at
+/// the end, so the plan is validated against the real data without changing
+/// it. Downgrading below `CURRENT_VERSION` only lasts until the next startup,
+/// which re-applies the pending migrations.
+pub fn migrate_to(
+    conn: &Connection,
+    target: Option<i32>,
+    dry_run: bool,
+) -> anyhow::Result<MigrationPlan> {
+    let mut plan = plan_migrations(conn, target)?;
+    plan.dry_run = dry_run;
+
+    if dry_run {
+        conn.execute_batch("SAVEPOINT schema_dry_run")?;
     }
-
-    if current_version < 30 {
-        apply_migration_v30(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [30])?;
+    let result = plan.steps.iter().try_for_each(|step| {
+        let migration = find_migration(step.version)
+            .ok_or_else(|| anyhow!("Unknown migration v{}", step.version))?;
+        apply_step(conn, migration, step.direction)
+            .with_context(|| format!("Migration v{} ({}) failed", step.version, step.name))
+    });
+    if dry_run {
+        conn.execute_batch("ROLLBACK TO schema_dry_run; RELEASE schema_dry_run")?;
     }
 
-    if current_version < 31 {
-        apply_migration_v31(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [31])?;
-    }
+    result.map(|_| plan)
+}
 
-    if current_version < 32 {
-        apply_migration_v32(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [32])?;
-    }
+thread_local! {
+    /// Statements seen by [`record_sql`] while a preview is running
+    static TRACED_SQL: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
 
-    if current_version < 33 {
-        apply_migration_v33(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [33])?;
-    }
+fn record_sql(sql: &str) {
+    TRACED_SQL.with(|traced| {
+        if let Some(statements) = traced.borrow_mut().as_mut() {
+            statements.push(sql.trim().to_string());
+        }
+    });
+}
 
-    if current_version < 34 {
-        apply_migration_v34(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [34])?;
+/// Dry-run the move to `target` and fill in the SQL each step would run.
+///
+/// Statements are traced while [`migrate_to`] runs inside its rolled-back
+/// savepoint, so what is listed is what the real run would execute against
+/// this database, including statements that depend on the existing data.
+/// Read-only queries are left out.
+pub fn preview_migrations(
+    conn: &mut Connection,
+    target: Option<i32>,
+) -> anyhow::Result<MigrationPlan> {
+    TRACED_SQL.with(|traced| *traced.borrow_mut() = Some(Vec::new()));
+    conn.trace(Some(record_sql));
+    let result = migrate_to(conn, target, true);
+    conn.trace(None);
+    let statements = TRACED_SQL
+        .with(|traced| traced.borrow_mut().take())
+        .unwrap_or_default();
+    let mut plan = result?;
+
+    // `apply_step` opens one savepoint per step, in plan order
+    let mut steps = plan.steps.iter_mut();
+    let mut current = None;
+    for sql in statements {
+        if sql == "SAVEPOINT schema_migration" {
+            current = steps.next();
+        } else if let Some(step) = current.as_mut() {
+            if !is_bookkeeping(&sql) {
+                step.sql.push(sql);
+            }
+        }
     }
+    Ok(plan)
+}
 
-    if current_version < 35 {
-        apply_migration_v35(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [35])?;
-    }
+/// Savepoint handling and read-only queries that a preview leaves out
+fn is_bookkeeping(sql: &str) -> bool {
+    let upper = sql.to_ascii_uppercase();
+    [
+        "RELEASE ",
+        "ROLLBACK ",
+        "SAVEPOINT ",
+        "SELECT ",
+        "PRAGMA TABLE_INFO",
+    ]
+    .iter()
+    .any(|prefix| upper.starts_with(prefix))
+}
 
-    if current_version < 36 {
-        apply_migration_v36(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [36])?;
+/// Plain-text plan, as printed for [`DRY_RUN_FLAG`]
+impl fmt::Display for MigrationPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "-- Schema v{} -> v{} ({} steps)",
+            self.current_version,
+            self.target_version,
+            self.steps.len()
+        )?;
+        for step in &self.steps {
+            let direction = match step.direction {
+                MigrationDirection::Up => "up",
+                MigrationDirection::Down => "down",
+            };
+            writeln!(f, "\n-- v{} {}: {}", step.version, direction, step.name)?;
+            for sql in &step.sql {
+                writeln!(f, "{};", sql.trim_end_matches(';'))?;
+            }
+        }
+        Ok(())
     }
+}
 
-    if current_version < 37 {
-        apply_migration_v37(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [37])?;
-    }
+/// Copy the database aside before applying pending migrations, then migrate.
+///
+/// Fresh databases and ones that are already current are not backed up.
+/// Returns the path of the backup when one was taken.
+pub fn run_migrations_with_backup(
+    conn: &Connection,
+    backup_dir: &Path,
+) -> anyhow::Result<Option<PathBuf>> {
+    let current_version = current_version(conn)?;
+    let backup_path = if current_version > 0 && current_version < CURRENT_VERSION {
+        let path = backup::backup_database(conn, backup_dir, "pre-migration")
+            .context("Failed to back up database before migrating")?;
+        tracing::info!(
+            "Backed up schema v{} before migrating to v{}",
+            current_version,
+            CURRENT_VERSION
+        );
+        Some(path)
+    } else {
+        None
+    };
+
+    run_migrations(conn)?;
+    Ok(backup_path)
+}
 
-    if current_version < 38 {
-        apply_migration_v38(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [38])?;
-    }
+fn ensure_version_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    Ok(())
+}
 
-    if current_version < 39 {
-        apply_migration_v39(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [39])?;
-    }
+fn find_migration(version: i32) -> Option<&'static Migration> {
+    MIGRATIONS.iter().find(|m| m.version == version)
+}
 
-    if current_version < 40 {
-        apply_migration_v40(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [40])?;
+fn step(migration: &Migration, direction: MigrationDirection) -> MigrationStep {
+    MigrationStep {
+        version: migration.version,
+        name: migration.name.to_string(),
+        direction,
+        sql: Vec::new(),
     }
+}
 
-    if current_version < 41 {
-        apply_migration_v41(conn)?;
-        conn.execute("INSERT INTO schema_version (version) VALUES (?1)", [41])?;
+/// Apply one migration atomically and record it in `schema_version`
+fn apply_step(
+    conn: &Connection,
+    migration: &Migration,
+    direction: MigrationDirection,
+) -> Result<()> {
+    conn.execute_batch("SAVEPOINT schema_migration")?;
+
+    let result = match direction {
+        MigrationDirection::Up => (migration.up)(conn).and_then(|_| {
+            conn.execute(
+                "INSERT INTO schema_version (version) VALUES (?1)",
+                [migration.version],
+            )
+        }),
+        MigrationDirection::Down => match migration.down {
+            Some(down) => down(conn).and_then(|_| {
+                conn.execute(
+                    "DELETE FROM schema_version WHERE version = ?1",
+                    [migration.version],
+                )
+            }),
+            None => Err(rusqlite::Error::InvalidQuery),
+        },
+    };
+
+    match result {
+        Ok(_) => conn.execute_batch("RELEASE schema_migration"),
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO schema_migration; RELEASE schema_migration")?;
+            Err(e)
+        }
     }
-
-    Ok(())
 }
 
 /// Migration v1: Initial schema
@@ -3551,6 +4106,38 @@ mod tests {
         assert!(tables.contains(&"schema_version".to_string()));
         assert!(tables.contains(&"cache_entries".to_string()));
         assert!(tables.contains(&"calendar_accounts".to_string()));
+        assert!(tables.contains(&"cloud_accounts".to_string()));
+        assert!(tables.contains(&"tool_reliability_events".to_string()));
+        assert!(tables.contains(&"llm_spend_ledger".to_string()));
+        assert!(tables.contains(&"cache_prompt_embeddings".to_string()));
+        assert!(tables.contains(&"llm_provider_health".to_string()));
+        assert!(tables.contains(&"telemetry_events".to_string()));
+        assert!(tables.contains(&"workflow_artifacts".to_string()));
+        assert!(tables.contains(&"email_sync_state".to_string()));
+        assert!(tables.contains(&"email_rules".to_string()));
+        assert!(tables.contains(&"email_outbox".to_string()));
+        assert!(tables.contains(&"employee_pipeline_runs".to_string()));
+        assert!(tables.contains(&"productivity_task_cache".to_string()));
+        assert!(tables.contains(&"productivity_accounts".to_string()));
+        assert!(tables.contains(&"credential_health".to_string()));
+        assert!(tables.contains(&"health_checks".to_string()));
+        assert!(tables.contains(&"autocomplete_usage".to_string()));
+        assert!(tables.contains(&"cache_warmup_patterns".to_string()));
+        assert!(tables.contains(&"presence_rules".to_string()));
+        assert!(tables.contains(&"conversation_summaries".to_string()));
+        assert!(tables.contains(&"tool_cost_events".to_string()));
+        assert!(tables.contains(&"tool_pricing".to_string()));
+        assert!(tables.contains(&"audit".to_string()));
+        assert!(tables.contains(&"agent_runs".to_string()));
+        assert!(tables.contains(&"agent_run_events".to_string()));
+        assert!(tables.contains(&"agent_file_snapshots".to_string()));
+        assert!(tables.contains(&"agent_skills".to_string()));
+        assert!(tables.contains(&"workflow_triggers".to_string()));
+        assert!(tables.contains(&"mcp_server_clients".to_string()));
+        assert!(tables.contains(&"browser_profiles".to_string()));
+        assert!(tables.contains(&"selector_healing_events".to_string()));
+        assert!(tables.contains(&"api_clients".to_string()));
+        assert!(tables.contains(&"workflow_trigger_deliveries".to_string()));
     }
 
     #[test]
@@ -3564,6 +4151,155 @@ mod tests {
 
         assert_eq!(fk_enabled, 1);
     }
+
+    #[test]
+    fn test_registry_is_contiguous() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i32 + 1);
+        }
+        assert_eq!(MIGRATIONS.last().unwrap().version, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_dry_run_and_down_migrations() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let table_exists = |name: &str| {
+            conn.prepare("SELECT 1 FROM sqlite_master WHERE type='table' AND name = ?1")
+                .unwrap()
+                .exists([name])
+                .unwrap()
+        };
+
+        // v44 created the spend ledger; reverting to v43 must drop it
+        let plan = migrate_to(&conn, Some(43), true).unwrap();
+        assert_eq!(plan.steps.len() as i32, CURRENT_VERSION - 43);
+        assert!(plan
+            .steps
+            .iter()
+            .all(|step| step.direction == MigrationDirection::Down));
+        assert_eq!(current_version(&conn).unwrap(), CURRENT_VERSION);
+        assert!(table_exists("llm_spend_ledger"));
+
+        migrate_to(&conn, Some(43), false).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 43);
+        assert!(!table_exists("llm_spend_ledger"));
+
+        let info = schema_version_info(&conn).unwrap();
+        assert_eq!(info.pending.len() as i32, CURRENT_VERSION - 43);
+
+        migrate_to(&conn, None, false).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), CURRENT_VERSION);
+        assert!(table_exists("llm_spend_ledger"));
+
+        // v40 has no down migration, so the whole plan is refused up front
+        assert!(plan_migrations(&conn, Some(39)).is_err());
+        assert_eq!(current_version(&conn).unwrap(), CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_preview_lists_pending_sql() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate_to(&conn, Some(43), false).unwrap();
+
+        let plan = preview_migrations(&mut conn, None).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 43);
+        assert_eq!(plan.steps.len() as i32, CURRENT_VERSION - 43);
+        let ledger = &plan.steps[0];
+        assert_eq!(ledger.version, 44);
+        assert!(ledger
+            .sql
+            .iter()
+            .any(|sql| sql.contains("CREATE TABLE IF NOT EXISTS llm_spend_ledger")));
+        assert!(ledger
+            .sql
+            .iter()
+            .any(|sql| sql.starts_with("INSERT INTO schema_version")));
+        assert!(!ledger.sql.iter().any(|sql| is_bookkeeping(sql)));
+        assert!(plan.to_string().contains("-- v44 up: "));
+
+        // Tracing is off again once the preview is done
+        migrate_to(&conn, None, false).unwrap();
+        assert!(TRACED_SQL.with(|traced| traced.borrow().is_none()));
+    }
+
+    #[test]
+    fn test_webdav_accounts_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_to(&conn, Some(57), false).unwrap();
+        conn.execute(
+            "INSERT INTO cloud_accounts (id, provider, label, client_id, redirect_uri, created_at, updated_at)
+             VALUES ('oauth', 'dropbox', NULL, 'client', 'http://localhost', 1, 1)",
+            [],
+        )
+        .unwrap();
+
+        migrate_to(&conn, Some(58), false).unwrap();
+        conn.execute(
+            "INSERT INTO cloud_accounts (id, provider, settings, created_at, updated_at)
+             VALUES ('dav', 'webdav', '{}', 2, 2)",
+            [],
+        )
+        .unwrap();
+        let count = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM cloud_accounts", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(count(&conn), 2);
+
+        // Reverting drops WebDAV rows but keeps OAuth accounts
+        migrate_to(&conn, Some(57), false).unwrap();
+        assert_eq!(count(&conn), 1);
+        assert!(!table_has_column(&conn, "cloud_accounts", "settings").unwrap());
+    }
+
+    #[test]
+    fn test_search_index_follows_source_tables() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_to(&conn, Some(58), false).unwrap();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES (1, 'Quarterly planning');
+             INSERT INTO messages (conversation_id, role, content)
+                 VALUES (1, 'user', 'Draft the roadmap for the mobile launch');",
+        )
+        .unwrap();
+
+        migrate_to(&conn, Some(59), false).unwrap();
+        conn.execute(
+            "INSERT INTO workflow_definitions (id, user_id, name, description, nodes, edges)
+             VALUES ('wf1', 'u1', 'Invoice approval', 'Route invoices to finance', '[]', '[]')",
+            [],
+        )
+        .unwrap();
+        let hits = |conn: &Connection, query: &str| -> Vec<String> {
+            conn.prepare("SELECT entity_type || ':' || entity_id FROM search_index WHERE search_index MATCH ?1")
+                .unwrap()
+                .query_map([query], |row| row.get(0))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap()
+        };
+        assert_eq!(hits(&conn, "roadmap"), vec!["message:1"]);
+        assert_eq!(hits(&conn, "invoices"), vec!["workflow:wf1"]);
+
+        conn.execute(
+            "UPDATE conversations SET title = 'Launch sync' WHERE id = 1",
+            [],
+        )
+        .unwrap();
+        assert_eq!(hits(&conn, "title:launch"), vec!["message:1"]);
+        conn.execute("DELETE FROM messages WHERE id = 1", [])
+            .unwrap();
+        assert!(hits(&conn, "roadmap").is_empty());
+
+        migrate_to(&conn, Some(58), false).unwrap();
+        let exists: bool = conn
+            .prepare("SELECT 1 FROM sqlite_master WHERE name = 'search_index'")
+            .unwrap()
+            .exists([])
+            .unwrap();
+        assert!(!exists);
+    }
 }
 
 /// Migration v40: Authentication and Authorization system
@@ -3896,6 +4632,1422 @@ fn apply_migration_v41(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Migration v42: Persisted cloud storage accounts
+fn apply_migration_v42(conn: &Connection) -> Result<()> {
+    // Account metadata only; client secrets and refresh tokens live in the secret vault
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cloud_accounts (
+            id TEXT PRIMARY KEY,
+            provider TEXT NOT NULL CHECK(provider IN ('google_drive', 'dropbox', 'one_drive')),
+            label TEXT,
+            client_id TEXT NOT NULL,
+            redirect_uri TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_cloud_accounts_provider
+         ON cloud_accounts(provider)",
+        [],
+    )?;
+
+    tracing::info!("Applied migration v42: Persisted cloud storage accounts");
+
+    Ok(())
+}
+
+/// Migration v43: Per-tool reliability telemetry
+fn apply_migration_v43(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tool_reliability_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tool_id TEXT NOT NULL,
+            method TEXT,
+            success INTEGER NOT NULL,
+            latency_ms INTEGER NOT NULL,
+            error TEXT,
+            recorded_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tool_reliability_tool
+         ON tool_reliability_events(tool_id, recorded_at DESC)",
+        [],
+    )?;
+
+    tracing::info!("Applied migration v43: Tool reliability telemetry");
+
+    Ok(())
+}
+
+/// Migration v44: Daily LLM spend ledger used by the router budget guard
+fn apply_migration_v44(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_spend_ledger (
+            day TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            cost REAL NOT NULL DEFAULT 0,
+            requests INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (day, provider, model)
+        )",
+        [],
+    )?;
+
+    tracing::info!("Applied migration v44: LLM spend ledger");
+
+    Ok(())
+}
+
+/// Revert v41: drop the background task table
+fn revert_migration_v41(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS tasks;")
+}
+
+/// Revert v42: drop persisted cloud accounts
+fn revert_migration_v42(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS cloud_accounts;")
+}
+
+/// Revert v43: drop tool reliability telemetry
+fn revert_migration_v43(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS tool_reliability_events;")
+}
+
+/// Revert v44: drop the LLM spend ledger
+fn revert_migration_v44(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS llm_spend_ledger;")
+}
+
+/// Migration v45: Prompt embeddings for semantic cache lookups and hit-rate counters
+fn apply_migration_v45(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cache_prompt_embeddings (
+            cache_key TEXT PRIMARY KEY
+                REFERENCES cache_entries(cache_key) ON DELETE CASCADE,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            embedding BLOB NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_cache_prompt_embeddings_model
+         ON cache_prompt_embeddings(provider, model)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cache_lookup_stats (
+            outcome TEXT PRIMARY KEY,
+            count INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Revert v45: drop semantic cache tables
+fn revert_migration_v45(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS cache_prompt_embeddings;
+         DROP TABLE IF EXISTS cache_lookup_stats;",
+    )
+}
+
+/// Migration v46: Per-minute provider health aggregates for adaptive routing
+fn apply_migration_v46(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS llm_provider_health (
+            provider TEXT NOT NULL,
+            minute INTEGER NOT NULL,
+            requests INTEGER NOT NULL DEFAULT 0,
+            errors INTEGER NOT NULL DEFAULT 0,
+            rate_limited INTEGER NOT NULL DEFAULT 0,
+            total_latency_ms INTEGER NOT NULL DEFAULT 0,
+            max_latency_ms INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            PRIMARY KEY (provider, minute)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_llm_provider_health_minute
+         ON llm_provider_health(minute)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Revert v46: drop provider health aggregates
+fn revert_migration_v46(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS llm_provider_health;")
+}
+
+/// Migration v47: Indexes backing paginated list queries
+fn apply_migration_v47(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_conversations_created
+         ON conversations(created_at DESC)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workflows_user_updated
+         ON workflow_definitions(user_id, updated_at DESC)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workflows_user_created
+         ON workflow_definitions(user_id, created_at DESC)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workflows_user_name
+         ON workflow_definitions(user_id, name)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v47(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_workflows_user_name;
+         DROP INDEX IF EXISTS idx_workflows_user_created;
+         DROP INDEX IF EXISTS idx_workflows_user_updated;
+         DROP INDEX IF EXISTS idx_conversations_created;",
+    )
+}
+
+/// Migration v48: Telemetry events flushed from the in-memory collector
+fn apply_migration_v48(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS telemetry_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            batch_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            properties TEXT NOT NULL DEFAULT '{}',
+            timestamp INTEGER NOT NULL,
+            session_id TEXT NOT NULL,
+            user_id TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_telemetry_events_name_time
+         ON telemetry_events(name, timestamp DESC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Revert v48: drop persisted telemetry events
+fn revert_migration_v48(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS telemetry_events;")
+}
+
+/// Migration v49: Template dependency declarations
+fn apply_migration_v49(conn: &Connection) -> Result<()> {
+    ensure_column(
+        conn,
+        "agent_templates",
+        "requirements",
+        "requirements TEXT NOT NULL DEFAULT '{}'",
+    )
+}
+
+/// Revert v49: drop template requirements
+fn revert_migration_v49(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE agent_templates DROP COLUMN requirements;")
+}
+
+/// Migration v50: Workflow execution artifacts
+fn apply_migration_v50(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS workflow_artifacts (
+            id TEXT PRIMARY KEY,
+            execution_id TEXT NOT NULL,
+            workflow_id TEXT NOT NULL,
+            node_id TEXT NOT NULL,
+            log_id TEXT,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            mime_type TEXT,
+            size_bytes INTEGER NOT NULL,
+            content_hash TEXT NOT NULL,
+            metadata TEXT NOT NULL DEFAULT '{}',
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER,
+            FOREIGN KEY (execution_id) REFERENCES workflow_executions(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workflow_artifacts_execution
+         ON workflow_artifacts(execution_id, created_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workflow_artifacts_expires
+         ON workflow_artifacts(expires_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_workflow_artifacts_hash
+         ON workflow_artifacts(content_hash)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v50(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_workflow_artifacts_hash;
+         DROP INDEX IF EXISTS idx_workflow_artifacts_expires;
+         DROP INDEX IF EXISTS idx_workflow_artifacts_execution;
+         DROP TABLE IF EXISTS workflow_artifacts;",
+    )
+}
+
+/// Migration v51: Background IMAP sync state
+fn apply_migration_v51(conn: &Connection) -> Result<()> {
+    ensure_column(conn, "emails", "uid", "uid INTEGER NOT NULL DEFAULT 0")?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_emails_uid
+         ON emails(account_id, folder, uid)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS email_sync_state (
+            account_id INTEGER NOT NULL,
+            folder TEXT NOT NULL,
+            uid_validity INTEGER NOT NULL,
+            last_uid INTEGER NOT NULL DEFAULT 0,
+            synced_at INTEGER NOT NULL,
+            PRIMARY KEY (account_id, folder),
+            FOREIGN KEY (account_id) REFERENCES email_accounts(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v51(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS email_sync_state;
+         DROP INDEX IF EXISTS idx_emails_uid;
+         ALTER TABLE emails DROP COLUMN uid;",
+    )
+}
+
+/// Migration v52: Email rules
+fn apply_migration_v52(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS email_rules (
+            id TEXT PRIMARY KEY,
+            account_id INTEGER,
+            name TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            position INTEGER NOT NULL DEFAULT 0,
+            conditions TEXT NOT NULL,
+            actions TEXT NOT NULL,
+            stop_processing INTEGER NOT NULL DEFAULT 0,
+            match_count INTEGER NOT NULL DEFAULT 0,
+            last_matched_at INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (account_id) REFERENCES email_accounts(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_email_rules_position
+         ON email_rules(enabled, position)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v52(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_email_rules_position;
+         DROP TABLE IF EXISTS email_rules;",
+    )
+}
+
+/// Migration v53: Email outbox
+fn apply_migration_v53(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS email_outbox (
+            id TEXT PRIMARY KEY,
+            account_id INTEGER NOT NULL,
+            request TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'queued',
+            send_at INTEGER NOT NULL,
+            next_attempt_at INTEGER NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            message_id TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            sent_at INTEGER,
+            FOREIGN KEY (account_id) REFERENCES email_accounts(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_email_outbox_due
+         ON email_outbox(status, next_attempt_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_email_outbox_account
+         ON email_outbox(account_id, created_at DESC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v53(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_email_outbox_account;
+         DROP INDEX IF EXISTS idx_email_outbox_due;
+         DROP TABLE IF EXISTS email_outbox;",
+    )
+}
+
+/// Migration v54: Employee pipelines
+fn apply_migration_v54(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS employee_pipelines (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            stages TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_employee_pipelines_user
+         ON employee_pipelines(user_id, created_at DESC)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS employee_pipeline_runs (
+            id TEXT PRIMARY KEY,
+            pipeline_id TEXT NOT NULL,
+            status TEXT NOT NULL,
+            input TEXT NOT NULL,
+            current_stage INTEGER NOT NULL DEFAULT 0,
+            stages TEXT NOT NULL,
+            error TEXT,
+            started_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            completed_at INTEGER,
+            FOREIGN KEY (pipeline_id) REFERENCES employee_pipelines(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_employee_pipeline_runs_pipeline
+         ON employee_pipeline_runs(pipeline_id, started_at DESC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v54(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_employee_pipeline_runs_pipeline;
+         DROP TABLE IF EXISTS employee_pipeline_runs;
+         DROP INDEX IF EXISTS idx_employee_pipelines_user;
+         DROP TABLE IF EXISTS employee_pipelines;",
+    )
+}
+
+/// Migration v55: Productivity task cache
+fn apply_migration_v55(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS productivity_task_cache (
+            provider TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            status TEXT NOT NULL,
+            assignee TEXT,
+            due_date INTEGER,
+            project_name TEXT,
+            task TEXT NOT NULL,
+            etag TEXT NOT NULL,
+            updated_at INTEGER,
+            synced_at INTEGER NOT NULL,
+            PRIMARY KEY (provider, task_id)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_productivity_task_cache_status
+         ON productivity_task_cache(status, due_date)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v55(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_productivity_task_cache_status;
+         DROP TABLE IF EXISTS productivity_task_cache;",
+    )
+}
+
+/// Migration v56: Productivity accounts
+///
+/// Several accounts may now be connected per provider, so cached tasks are
+/// keyed by account. The cache is rebuilt on the next sync.
+fn apply_migration_v56(conn: &Connection) -> Result<()> {
+    // Account metadata only; tokens live in the secret vault
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS productivity_accounts (
+            id TEXT PRIMARY KEY,
+            provider TEXT NOT NULL CHECK(provider IN ('notion', 'trello', 'asana')),
+            label TEXT,
+            remote_id TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_productivity_accounts_provider
+         ON productivity_accounts(provider)",
+        [],
+    )?;
+
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_productivity_task_cache_status;
+         DROP TABLE IF EXISTS productivity_task_cache;",
+    )?;
+
+    conn.execute(
+        "CREATE TABLE productivity_task_cache (
+            account_id TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            description TEXT,
+            status TEXT NOT NULL,
+            assignee TEXT,
+            due_date INTEGER,
+            project_name TEXT,
+            task TEXT NOT NULL,
+            etag TEXT NOT NULL,
+            updated_at INTEGER,
+            synced_at INTEGER NOT NULL,
+            PRIMARY KEY (account_id, task_id)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_productivity_task_cache_status
+         ON productivity_task_cache(status, due_date)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v56(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_productivity_task_cache_status;
+         DROP TABLE IF EXISTS productivity_task_cache;
+         DROP INDEX IF EXISTS idx_productivity_accounts_provider;
+         DROP TABLE IF EXISTS productivity_accounts;",
+    )?;
+    apply_migration_v55(conn)
+}
+
+/// Migration v57: Credential health
+///
+/// One row per connected account across integrations, tracking when its
+/// credentials expire, the last check outcome, and which warning the user was
+/// last shown.
+fn apply_migration_v57(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS credential_health (
+            source TEXT NOT NULL,
+            account_id TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            label TEXT,
+            expires_at INTEGER,
+            last_success_at INTEGER,
+            last_failure_at INTEGER,
+            last_error TEXT,
+            last_checked_at INTEGER NOT NULL,
+            notified_stage TEXT,
+            PRIMARY KEY (source, account_id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v57(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS credential_health", [])?;
+    Ok(())
+}
+
+/// Migration v58: WebDAV cloud accounts
+///
+/// SQLite cannot alter a CHECK constraint, so `cloud_accounts` is rebuilt to
+/// accept the `webdav` provider. WebDAV accounts have no OAuth client, so
+/// `client_id`/`redirect_uri` become nullable and the server settings are kept
+/// as JSON in `settings`.
+fn apply_migration_v58(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE cloud_accounts_v58 (
+            id TEXT PRIMARY KEY,
+            provider TEXT NOT NULL
+                CHECK(provider IN ('google_drive', 'dropbox', 'one_drive', 'webdav')),
+            label TEXT,
+            client_id TEXT,
+            redirect_uri TEXT,
+            settings TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        INSERT INTO cloud_accounts_v58
+            (id, provider, label, client_id, redirect_uri, created_at, updated_at)
+            SELECT id, provider, label, client_id, redirect_uri, created_at, updated_at
+            FROM cloud_accounts;
+        DROP TABLE cloud_accounts;
+        ALTER TABLE cloud_accounts_v58 RENAME TO cloud_accounts;
+        CREATE INDEX IF NOT EXISTS idx_cloud_accounts_provider
+            ON cloud_accounts(provider);",
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v58(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE cloud_accounts_v42 (
+            id TEXT PRIMARY KEY,
+            provider TEXT NOT NULL CHECK(provider IN ('google_drive', 'dropbox', 'one_drive')),
+            label TEXT,
+            client_id TEXT NOT NULL,
+            redirect_uri TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        INSERT INTO cloud_accounts_v42
+            SELECT id, provider, label, client_id, redirect_uri, created_at, updated_at
+            FROM cloud_accounts
+            WHERE provider != 'webdav';
+        DROP TABLE cloud_accounts;
+        ALTER TABLE cloud_accounts_v42 RENAME TO cloud_accounts;
+        CREATE INDEX IF NOT EXISTS idx_cloud_accounts_provider
+            ON cloud_accounts(provider);",
+    )?;
+
+    Ok(())
+}
+
+/// Migration v59: Global full-text search index
+///
+/// One FTS5 table covers chat messages, emails, workflows and read documents.
+/// Triggers keep the first three in step with their source tables; documents
+/// are added by the document commands. `updated_at` is Unix seconds for all types.
+fn apply_migration_v59(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            entity_type UNINDEXED,
+            entity_id UNINDEXED,
+            title,
+            body,
+            parent_id UNINDEXED,
+            updated_at UNINDEXED,
+            tokenize = 'porter unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS search_index_messages_ai AFTER INSERT ON messages BEGIN
+            INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+            VALUES ('message', CAST(new.id AS TEXT),
+                    (SELECT title FROM conversations WHERE id = new.conversation_id),
+                    new.content, CAST(new.conversation_id AS TEXT),
+                    CAST(strftime('%s', new.created_at) AS INTEGER));
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_messages_au AFTER UPDATE OF content ON messages BEGIN
+            UPDATE search_index SET body = new.content
+            WHERE entity_type = 'message' AND entity_id = CAST(new.id AS TEXT);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_messages_ad AFTER DELETE ON messages BEGIN
+            DELETE FROM search_index
+            WHERE entity_type = 'message' AND entity_id = CAST(old.id AS TEXT);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_conversations_au
+        AFTER UPDATE OF title ON conversations BEGIN
+            UPDATE search_index SET title = new.title
+            WHERE entity_type = 'message' AND parent_id = CAST(new.id AS TEXT);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_conversations_ad AFTER DELETE ON conversations BEGIN
+            DELETE FROM search_index
+            WHERE entity_type = 'message' AND parent_id = CAST(old.id AS TEXT);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_emails_ai AFTER INSERT ON emails BEGIN
+            INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+            VALUES ('email', new.id, new.subject, new.body_text, new.folder, new.date);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_emails_au
+        AFTER UPDATE OF subject, body_text, folder ON emails BEGIN
+            UPDATE search_index
+            SET title = new.subject, body = new.body_text, parent_id = new.folder
+            WHERE entity_type = 'email' AND entity_id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_emails_ad AFTER DELETE ON emails BEGIN
+            DELETE FROM search_index WHERE entity_type = 'email' AND entity_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_index_workflows_ai
+        AFTER INSERT ON workflow_definitions BEGIN
+            INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+            VALUES ('workflow', new.id, new.name, new.description, NULL, new.updated_at);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_workflows_au
+        AFTER UPDATE OF name, description ON workflow_definitions BEGIN
+            UPDATE search_index
+            SET title = new.name, body = new.description, updated_at = new.updated_at
+            WHERE entity_type = 'workflow' AND entity_id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_index_workflows_ad
+        AFTER DELETE ON workflow_definitions BEGIN
+            DELETE FROM search_index WHERE entity_type = 'workflow' AND entity_id = old.id;
+        END;",
+    )?;
+
+    // Index what already exists
+    conn.execute_batch(
+        "DELETE FROM search_index WHERE entity_type IN ('message', 'email', 'workflow');
+        INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+            SELECT 'message', CAST(m.id AS TEXT), c.title, m.content,
+                   CAST(m.conversation_id AS TEXT), CAST(strftime('%s', m.created_at) AS INTEGER)
+            FROM messages m LEFT JOIN conversations c ON c.id = m.conversation_id;
+        INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+            SELECT 'email', id, subject, body_text, folder, date FROM emails;
+        INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+            SELECT 'workflow', id, name, description, NULL, updated_at FROM workflow_definitions;",
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v59(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS search_index_messages_ai;
+        DROP TRIGGER IF EXISTS search_index_messages_au;
+        DROP TRIGGER IF EXISTS search_index_messages_ad;
+        DROP TRIGGER IF EXISTS search_index_conversations_au;
+        DROP TRIGGER IF EXISTS search_index_conversations_ad;
+        DROP TRIGGER IF EXISTS search_index_emails_ai;
+        DROP TRIGGER IF EXISTS search_index_emails_au;
+        DROP TRIGGER IF EXISTS search_index_emails_ad;
+        DROP TRIGGER IF EXISTS search_index_workflows_ai;
+        DROP TRIGGER IF EXISTS search_index_workflows_au;
+        DROP TRIGGER IF EXISTS search_index_workflows_ad;
+        DROP TABLE IF EXISTS search_index;",
+    )?;
+    Ok(())
+}
+
+/// Migration v60: Integration health checks
+///
+/// User-defined HTTP checks of custom integrations. Headers, accepted status
+/// codes and assertions are JSON; results also go to `credential_health`.
+fn apply_migration_v60(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS health_checks (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            method TEXT NOT NULL,
+            url TEXT NOT NULL,
+            headers TEXT NOT NULL DEFAULT '{}',
+            body TEXT,
+            expected_status TEXT NOT NULL DEFAULT '[]',
+            assertions TEXT NOT NULL DEFAULT '[]',
+            interval_secs INTEGER NOT NULL,
+            timeout_ms INTEGER NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            last_run_at INTEGER,
+            last_status INTEGER,
+            last_latency_ms INTEGER,
+            last_error TEXT
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v60(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DELETE FROM credential_health WHERE source = 'health_check';
+        DROP TABLE IF EXISTS health_checks;",
+    )?;
+    Ok(())
+}
+
+/// Migration v61: Autocomplete usage
+///
+/// How often each chat autocomplete suggestion was accepted, keyed by kind
+/// and command name, row id, path or email.
+fn apply_migration_v61(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS autocomplete_usage (
+            kind TEXT NOT NULL,
+            value TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            last_used_at INTEGER NOT NULL,
+            PRIMARY KEY (kind, value)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v61(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS autocomplete_usage", [])?;
+    Ok(())
+}
+
+/// Migration v62: Cache warmup patterns
+///
+/// Repeated LLM requests and read-only tool calls with an hour-of-day
+/// histogram, the payload needed to replay them, and how often warmup helped.
+fn apply_migration_v62(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cache_warmup_patterns (
+            kind TEXT NOT NULL,
+            pattern_key TEXT NOT NULL,
+            label TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            uses INTEGER NOT NULL DEFAULT 0,
+            active_days INTEGER NOT NULL DEFAULT 0,
+            last_day TEXT,
+            hour_counts TEXT NOT NULL DEFAULT '[]',
+            first_used_at INTEGER NOT NULL,
+            last_used_at INTEGER NOT NULL,
+            last_warmed_at INTEGER,
+            warm_pending INTEGER NOT NULL DEFAULT 0,
+            warm_runs INTEGER NOT NULL DEFAULT 0,
+            warm_hits INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (kind, pattern_key)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v62(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS cache_warmup_patterns", [])?;
+    Ok(())
+}
+
+/// Migration v63: Presence visibility rules
+///
+/// A member's own choice of who in a team may see their presence and how
+/// much of their activity is shared, overriding the team's defaults.
+fn apply_migration_v63(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS presence_rules (
+            team_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            visibility TEXT NOT NULL,
+            detail TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (team_id, user_id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v63(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS presence_rules", [])?;
+    Ok(())
+}
+
+/// Migration v64: Conversation summaries
+///
+/// Rolling summary of the older turns of a conversation that no longer fit
+/// in the model's context window, covering every message up to and
+/// including `covered_through`.
+fn apply_migration_v64(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS conversation_summaries (
+            conversation_id INTEGER PRIMARY KEY,
+            summary TEXT NOT NULL,
+            covered_through INTEGER NOT NULL,
+            covered_messages INTEGER NOT NULL,
+            tokens INTEGER NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v64(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS conversation_summaries", [])?;
+    Ok(())
+}
+
+/// Migration v65: Tool cost ledger
+///
+/// One row per tool call with its duration, metered resources and cost in
+/// USD, plus the flat prices configured per tool.
+fn apply_migration_v65(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tool_cost_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tool_id TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            cost REAL NOT NULL DEFAULT 0.0,
+            units TEXT,
+            source TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tool_cost_events_created
+         ON tool_cost_events(created_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tool_cost_events_tool
+         ON tool_cost_events(tool_id, created_at)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tool_pricing (
+            tool_id TEXT PRIMARY KEY,
+            pricing TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v65(conn: &Connection) -> Result<()> {
+    conn.execute("DROP TABLE IF EXISTS tool_pricing", [])?;
+    conn.execute("DROP TABLE IF EXISTS tool_cost_events", [])?;
+    Ok(())
+}
+
+/// Migration v66: Conversation branches
+///
+/// Messages form a tree: each one points at the message it follows, and a
+/// conversation shows the path from its active leaf back to the root.
+/// Regenerating a reply adds a sibling on a new `branch_id`. Triggers thread
+/// every insert onto the active leaf and splice deleted messages out, so code
+/// writing `messages` directly keeps the tree intact.
+fn apply_migration_v66(conn: &Connection) -> Result<()> {
+    ensure_column(
+        conn,
+        "messages",
+        "parent_message_id",
+        "parent_message_id INTEGER",
+    )?;
+    ensure_column(conn, "messages", "branch_id", "branch_id INTEGER")?;
+    ensure_column(
+        conn,
+        "conversations",
+        "active_leaf_id",
+        "active_leaf_id INTEGER",
+    )?;
+
+    // Existing conversations become a single branch in message order
+    conn.execute_batch(
+        "UPDATE messages SET
+            branch_id = 0,
+            parent_message_id = (
+                SELECT prev.id FROM messages prev
+                WHERE prev.conversation_id = messages.conversation_id
+                  AND (prev.created_at < messages.created_at
+                       OR (prev.created_at = messages.created_at AND prev.id < messages.id))
+                ORDER BY prev.created_at DESC, prev.id DESC
+                LIMIT 1
+            )
+         WHERE branch_id IS NULL;
+
+         UPDATE conversations SET active_leaf_id = (
+            SELECT id FROM messages
+            WHERE conversation_id = conversations.id
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+         );
+
+         CREATE INDEX IF NOT EXISTS idx_messages_parent ON messages(parent_message_id);
+         CREATE INDEX IF NOT EXISTS idx_messages_branch ON messages(conversation_id, branch_id);
+
+         CREATE TRIGGER IF NOT EXISTS messages_thread_ai AFTER INSERT ON messages BEGIN
+            UPDATE messages SET
+                parent_message_id = COALESCE(
+                    NEW.parent_message_id,
+                    (SELECT active_leaf_id FROM conversations
+                     WHERE id = NEW.conversation_id AND active_leaf_id < NEW.id)
+                ),
+                branch_id = COALESCE(
+                    NEW.branch_id,
+                    (SELECT parent.branch_id FROM messages parent
+                     WHERE parent.id = COALESCE(
+                        NEW.parent_message_id,
+                        (SELECT active_leaf_id FROM conversations
+                         WHERE id = NEW.conversation_id AND active_leaf_id < NEW.id)
+                     )),
+                    0
+                )
+            WHERE id = NEW.id;
+            UPDATE conversations SET active_leaf_id = NEW.id WHERE id = NEW.conversation_id;
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS messages_thread_bd BEFORE DELETE ON messages BEGIN
+            UPDATE messages SET parent_message_id = OLD.parent_message_id
+            WHERE parent_message_id = OLD.id;
+            UPDATE conversations SET active_leaf_id = OLD.parent_message_id
+            WHERE active_leaf_id = OLD.id;
+         END;",
+    )?;
+
+    Ok(())
+}
+
+fn revert_migration_v66(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS messages_thread_ai;
+         DROP TRIGGER IF EXISTS messages_thread_bd;
+         DROP INDEX IF EXISTS idx_messages_parent;
+         DROP INDEX IF EXISTS idx_messages_branch;
+         ALTER TABLE messages DROP COLUMN parent_message_id;
+         ALTER TABLE messages DROP COLUMN branch_id;
+         ALTER TABLE conversations DROP COLUMN active_leaf_id;",
+    )
+}
+
+/// Migration v67: Secrets vault
+///
+/// One row per secret whatever its storage; `ciphertext` is only set for
+/// secrets the OS keyring would not take. Every access is logged.
+fn apply_migration_v67(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS vault_secrets (
+            namespace TEXT NOT NULL,
+            name TEXT NOT NULL,
+            storage TEXT NOT NULL CHECK(storage IN ('keyring', 'database')),
+            ciphertext BLOB,
+            version INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            rotated_at INTEGER,
+            last_accessed_at INTEGER,
+            PRIMARY KEY (namespace, name)
+        );
+
+        CREATE TABLE IF NOT EXISTS vault_access_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            namespace TEXT NOT NULL,
+            name TEXT NOT NULL,
+            action TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            error TEXT,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_vault_access_log_secret
+            ON vault_access_log(namespace, name, created_at);",
+    )
+}
+
+fn revert_migration_v67(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS vault_access_log;
+         DROP TABLE IF EXISTS vault_secrets;",
+    )
+}
+
+fn apply_migration_v68(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS permission_profiles (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            rules TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS tool_permission_decisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id TEXT NOT NULL,
+            user_employee_id TEXT,
+            profile_id TEXT NOT NULL,
+            tool_name TEXT NOT NULL,
+            category TEXT NOT NULL,
+            outcome TEXT NOT NULL CHECK(outcome IN ('allowed', 'denied', 'approved', 'rejected')),
+            reason TEXT,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_tool_permission_decisions_run
+            ON tool_permission_decisions(run_id, created_at);",
+    )?;
+    if !table_has_column(conn, "user_employees", "permission_profile_id")? {
+        conn.execute_batch("ALTER TABLE user_employees ADD COLUMN permission_profile_id TEXT;")?;
+    }
+    Ok(())
+}
+
+fn revert_migration_v68(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE user_employees DROP COLUMN permission_profile_id;
+         DROP TABLE IF EXISTS tool_permission_decisions;
+         DROP TABLE IF EXISTS permission_profiles;",
+    )
+}
+
+fn apply_migration_v69(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at INTEGER NOT NULL,
+            actor_kind TEXT NOT NULL CHECK(actor_kind IN ('user', 'agent', 'employee', 'system')),
+            actor_id TEXT,
+            category TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target TEXT,
+            params_hash TEXT,
+            outcome TEXT NOT NULL CHECK(outcome IN ('success', 'failure', 'denied')),
+            detail TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_audit_created ON audit(created_at);
+        CREATE INDEX IF NOT EXISTS idx_audit_actor ON audit(actor_kind, actor_id, created_at);
+        CREATE INDEX IF NOT EXISTS idx_audit_category ON audit(category, created_at);
+
+        CREATE TRIGGER IF NOT EXISTS audit_no_update BEFORE UPDATE ON audit
+        BEGIN
+            SELECT RAISE(ABORT, 'audit is append-only');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS audit_no_delete BEFORE DELETE ON audit
+        BEGIN
+            SELECT RAISE(ABORT, 'audit is append-only');
+        END;",
+    )
+}
+
+fn revert_migration_v69(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS audit;")
+}
+
+fn apply_migration_v70(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_runs (
+            run_id TEXT PRIMARY KEY,
+            goal TEXT NOT NULL,
+            replay_of TEXT,
+            status TEXT NOT NULL CHECK(status IN ('running', 'completed', 'failed')),
+            error TEXT,
+            started_at INTEGER NOT NULL,
+            finished_at INTEGER
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_agent_runs_started ON agent_runs(started_at);
+
+        CREATE TABLE IF NOT EXISTS agent_run_events (
+            run_id TEXT NOT NULL REFERENCES agent_runs(run_id) ON DELETE CASCADE,
+            seq INTEGER NOT NULL,
+            at INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            PRIMARY KEY (run_id, seq)
+        );",
+    )
+}
+
+fn revert_migration_v70(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS agent_run_events;
+         DROP TABLE IF EXISTS agent_runs;",
+    )
+}
+
+fn apply_migration_v71(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_file_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            tool_name TEXT NOT NULL,
+            blob_hash TEXT,
+            created_at INTEGER NOT NULL,
+            rolled_back_at INTEGER
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_agent_file_snapshots_run
+            ON agent_file_snapshots(run_id, path);",
+    )
+}
+
+fn revert_migration_v71(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS agent_file_snapshots;")
+}
+
+fn apply_migration_v72(conn: &Connection) -> Result<()> {
+    for (column, definition) in [
+        ("budget", "TEXT"),
+        ("cost", "REAL NOT NULL DEFAULT 0"),
+        ("tool_calls", "INTEGER NOT NULL DEFAULT 0"),
+        ("budget_overruns", "INTEGER NOT NULL DEFAULT 0"),
+    ] {
+        if !table_has_column(conn, "agent_runs", column)? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE agent_runs ADD COLUMN {} {};",
+                column, definition
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+fn revert_migration_v72(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE agent_runs DROP COLUMN budget_overruns;
+         ALTER TABLE agent_runs DROP COLUMN tool_calls;
+         ALTER TABLE agent_runs DROP COLUMN cost;
+         ALTER TABLE agent_runs DROP COLUMN budget;",
+    )
+}
+
+fn apply_migration_v73(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_skills (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT NOT NULL,
+            definition TEXT NOT NULL,
+            embedding BLOB,
+            embedding_model TEXT,
+            use_count INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );",
+    )
+}
+
+fn revert_migration_v73(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS agent_skills;")
+}
+
+fn apply_migration_v74(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS workflow_triggers (
+            id TEXT PRIMARY KEY,
+            workflow_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            source TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            debounce_ms INTEGER NOT NULL DEFAULT 0,
+            secret TEXT,
+            fire_count INTEGER NOT NULL DEFAULT 0,
+            last_fired_at INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (workflow_id) REFERENCES workflow_definitions(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_workflow_triggers_workflow
+            ON workflow_triggers(workflow_id);",
+    )
+}
+
+fn revert_migration_v74(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS workflow_triggers;")
+}
+
+fn apply_migration_v75(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mcp_server_clients (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            profile_id TEXT NOT NULL,
+            tools TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            last_used_at INTEGER
+        );",
+    )
+}
+
+fn revert_migration_v75(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS mcp_server_clients;")
+}
+
+fn apply_migration_v76(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS browser_profiles (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            user_data_dir TEXT NOT NULL,
+            proxy TEXT,
+            extensions TEXT NOT NULL DEFAULT '[]',
+            created_at INTEGER NOT NULL,
+            last_used_at INTEGER,
+            pid INTEGER,
+            debug_port INTEGER,
+            started_at INTEGER
+        );",
+    )
+}
+
+fn revert_migration_v76(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS browser_profiles;")
+}
+
+fn apply_migration_v77(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS selector_healing_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workflow_id TEXT,
+            tool_id TEXT NOT NULL,
+            selector TEXT NOT NULL,
+            outcome TEXT NOT NULL,
+            strategy TEXT,
+            healed_selector TEXT,
+            recorded_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_selector_healing_workflow
+            ON selector_healing_events(workflow_id, recorded_at DESC);",
+    )
+}
+
+fn revert_migration_v77(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS selector_healing_events;")
+}
+
+fn apply_migration_v78(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS api_clients (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            profile_id TEXT NOT NULL,
+            user_id TEXT,
+            scopes TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            last_used_at INTEGER
+        );",
+    )
+}
+
+fn revert_migration_v78(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS api_clients;")
+}
+
+fn apply_migration_v79(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS workflow_trigger_deliveries (
+            id TEXT PRIMARY KEY,
+            trigger_id TEXT NOT NULL REFERENCES workflow_triggers(id) ON DELETE CASCADE,
+            received_at INTEGER NOT NULL,
+            headers TEXT NOT NULL,
+            body TEXT NOT NULL,
+            verified INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            execution_id TEXT,
+            replay_of TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_workflow_trigger_deliveries_trigger
+            ON workflow_trigger_deliveries(trigger_id, received_at DESC);",
+    )
+}
+
+fn revert_migration_v79(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS workflow_trigger_deliveries;")
+}
+
+fn apply_migration_v80(conn: &Connection) -> Result<()> {
+    if !table_has_column(conn, "tasks", "conversation_id")? {
+        conn.execute_batch("ALTER TABLE tasks ADD COLUMN conversation_id INTEGER;")?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_tasks_conversation ON tasks(conversation_id);",
+    )
+}
+
+fn revert_migration_v80(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_tasks_conversation;
+         ALTER TABLE tasks DROP COLUMN conversation_id;",
+    )
+}
+
 fn table_has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
     let mut stmt =
         conn.prepare("SELECT 1 FROM pragma_table_info(?1) WHERE lower(name) = lower(?2)")?;
@@ -1,9 +1,15 @@
 use rusqlite::{Connection, Result};
 use std::sync::{Arc, Mutex};
 
+pub mod backup;
+pub mod batch;
+pub mod branches;
 pub mod migrations;
 pub mod models;
+pub mod pagination;
+pub mod pool;
 pub mod repository;
+pub mod retention;
 
 // Re-export commonly used types
 pub use models::{
@@ -11,6 +17,8 @@ pub use models::{
     TaskType,
 };
 
+pub use pool::{DbError, Pool};
+
 pub use repository::{
     create_automation_history, create_conversation, create_message, create_overlay_event,
     delete_conversation, delete_message, delete_overlay_events_before, delete_setting,
@@ -115,6 +115,31 @@ impl Message {
     }
 }
 
+/// A tool call an assistant message made, stored in `messages.tool_calls`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ToolCallRecord {
+    /// Record of a router tool call; unparseable arguments are kept as a string
+    pub fn new(id: &str, name: &str, arguments: &str, error: Option<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments: serde_json::from_str(arguments)
+                .unwrap_or_else(|_| serde_json::Value::String(arguments.to_string())),
+            success: error.is_none(),
+            error,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CostTimeseriesPoint {
     pub date: String,
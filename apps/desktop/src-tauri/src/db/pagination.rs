@@ -0,0 +1,386 @@
+//! Shared query envelope for list commands.
+//!
+//! The frontend sends a [`PageRequest`] (limit/offset or an opaque cursor,
+//! sort key, free-text search and exact-match filters) and gets back a
+//! [`Page`] carrying the total count and the cursor for the next page. Sort
+//! keys and filter names are whitelisted per list so that nothing from the
+//! request is ever spliced into SQL.
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    fn sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRequest {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// `nextCursor` from the previous page; takes precedence over `offset`
+    pub cursor: Option<String>,
+    pub sort_by: Option<String>,
+    pub sort_dir: Option<SortDirection>,
+    /// Case-insensitive substring match on the list's text columns
+    pub search: Option<String>,
+    /// Exact matches on whitelisted fields
+    #[serde(default)]
+    pub filters: HashMap<String, String>,
+}
+
+impl PageRequest {
+    pub fn limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn offset(&self) -> Result<u32, String> {
+        match &self.cursor {
+            Some(cursor) => decode_cursor(cursor),
+            None => Ok(self.offset.unwrap_or(0)),
+        }
+    }
+
+    pub fn search_term(&self) -> Option<String> {
+        self.search
+            .as_deref()
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .map(str::to_lowercase)
+    }
+
+    /// Resolve `sort_by` against `(key, column)` pairs; the first pair is the default
+    pub fn sort_column<'a>(&self, allowed: &[(&str, &'a str)]) -> Result<&'a str, String> {
+        match &self.sort_by {
+            None => Ok(allowed[0].1),
+            Some(key) => allowed
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, column)| *column)
+                .ok_or_else(|| {
+                    format!(
+                        "Cannot sort by '{}'. Allowed: {}",
+                        key,
+                        allowed
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }),
+        }
+    }
+
+    pub fn direction(&self) -> SortDirection {
+        self.sort_dir.unwrap_or(SortDirection::Desc)
+    }
+
+    /// Reject filters the list does not support instead of silently ignoring them
+    pub fn check_filters(&self, allowed: &[&str]) -> Result<(), String> {
+        match self
+            .filters
+            .keys()
+            .find(|name| !allowed.contains(&name.as_str()))
+        {
+            Some(name) => Err(format!(
+                "Cannot filter by '{}'. Allowed: {}",
+                name,
+                allowed.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub limit: u32,
+    pub offset: u32,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: u64, limit: u32, offset: u32) -> Self {
+        let next_offset = offset as u64 + items.len() as u64;
+        let has_more = next_offset < total;
+        Self {
+            items,
+            total,
+            limit,
+            offset,
+            has_more,
+            next_cursor: has_more.then(|| encode_cursor(next_offset as u32)),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            offset: self.offset,
+            has_more: self.has_more,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+fn encode_cursor(offset: u32) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("o:{}", offset))
+}
+
+fn decode_cursor(cursor: &str) -> Result<u32, String> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|text| text.strip_prefix("o:").and_then(|n| n.parse().ok()))
+        .ok_or_else(|| "Invalid pagination cursor".to_string())
+}
+
+/// Page an in-memory list; for stores that are not backed by SQLite
+pub fn paginate_vec<T>(items: Vec<T>, request: &PageRequest) -> Result<Page<T>, String> {
+    let limit = request.limit();
+    let offset = request.offset()?;
+    let total = items.len() as u64;
+    let items = items
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    Ok(Page::new(items, total, limit, offset))
+}
+
+/// WHERE clause accumulated from fixed conditions, search and filters
+#[derive(Default)]
+pub struct Conditions {
+    clauses: Vec<String>,
+    params: Vec<SqlValue>,
+}
+
+impl Conditions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `clause` uses `?` placeholders, one per value
+    pub fn push(&mut self, clause: &str, values: impl IntoIterator<Item = SqlValue>) {
+        self.clauses.push(clause.to_string());
+        self.params.extend(values);
+    }
+
+    /// Match the request's search term against any of `columns`
+    pub fn search(&mut self, request: &PageRequest, columns: &[&str]) {
+        if let Some(term) = request.search_term() {
+            let pattern = format!("%{}%", term.replace('%', "\\%").replace('_', "\\_"));
+            let clause = columns
+                .iter()
+                .map(|column| format!("LOWER({}) LIKE ? ESCAPE '\\'", column))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            self.push(
+                &format!("({})", clause),
+                columns.iter().map(|_| SqlValue::Text(pattern.clone())),
+            );
+        }
+    }
+
+    /// Add `column = ?` for each requested filter, resolved through `(name, column)`
+    pub fn filters(
+        &mut self,
+        request: &PageRequest,
+        allowed: &[(&str, &str)],
+    ) -> Result<(), String> {
+        request.check_filters(&allowed.iter().map(|(name, _)| *name).collect::<Vec<_>>())?;
+        for (name, column) in allowed {
+            if let Some(value) = request.filters.get(*name) {
+                self.push(&format!("{} = ?", column), [SqlValue::Text(value.clone())]);
+            }
+        }
+        Ok(())
+    }
+
+    fn sql(&self) -> String {
+        if self.clauses.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.clauses.join(" AND "))
+        }
+    }
+}
+
+/// Shape of one paginated list
+pub struct PageQuery<'a> {
+    pub select: &'a str,
+    pub from: &'a str,
+    /// Breaks ties so pages stay stable when sort values repeat
+    pub id_column: &'a str,
+    /// `(key, column)` pairs accepted as `sortBy`; the first is the default
+    pub sorts: &'a [(&'a str, &'a str)],
+}
+
+/// Run the count and page queries for `query` under `conditions`
+pub fn query_page<T>(
+    conn: &Connection,
+    query: &PageQuery,
+    conditions: &Conditions,
+    request: &PageRequest,
+    map: impl FnMut(&Row) -> rusqlite::Result<T>,
+) -> Result<Page<T>, String> {
+    let limit = request.limit();
+    let offset = request.offset()?;
+    let order_column = request.sort_column(query.sorts)?;
+    let direction = request.direction();
+    let where_sql = conditions.sql();
+
+    let total: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM {}{}", query.from, where_sql),
+            params_from_iter(conditions.params.iter()),
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count rows: {}", e))?;
+
+    let sql = format!(
+        "SELECT {} FROM {}{} ORDER BY {} {dir}, {} {dir} LIMIT ? OFFSET ?",
+        query.select,
+        query.from,
+        where_sql,
+        order_column,
+        query.id_column,
+        dir = direction.sql()
+    );
+    let params = conditions.params.iter().cloned().chain([
+        SqlValue::Integer(limit as i64),
+        SqlValue::Integer(offset as i64),
+    ]);
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare page query: {}", e))?;
+    let items = stmt
+        .query_map(params_from_iter(params), map)
+        .map_err(|e| format!("Failed to run page query: {}", e))?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| format!("Failed to read page: {}", e))?;
+
+    Ok(Page::new(items, total.max(0) as u64, limit, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, kind TEXT, rank INTEGER);",
+        )
+        .unwrap();
+        for i in 0..7 {
+            conn.execute(
+                "INSERT INTO items (name, kind, rank) VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    format!("Item {}", i),
+                    if i % 2 == 0 { "even" } else { "odd" },
+                    i % 3
+                ],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    const QUERY: PageQuery = PageQuery {
+        select: "id",
+        from: "items",
+        id_column: "id",
+        sorts: &[("rank", "rank"), ("name", "name")],
+    };
+
+    fn page(conn: &Connection, request: &PageRequest) -> Page<i64> {
+        let mut conditions = Conditions::new();
+        conditions.search(request, &["name"]);
+        conditions.filters(request, &[("kind", "kind")]).unwrap();
+        query_page(conn, &QUERY, &conditions, request, |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_walks_every_row_once() {
+        let conn = seeded();
+        let mut request = PageRequest {
+            limit: Some(3),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = page(&conn, &request);
+            assert_eq!(page.total, 7);
+            seen.extend(page.items);
+            match page.next_cursor {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, (1..=7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_filters_search_and_whitelists() {
+        let conn = seeded();
+        let mut request = PageRequest {
+            search: Some("item 1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(page(&conn, &request).items, vec![2]);
+
+        request.search = None;
+        request
+            .filters
+            .insert("kind".to_string(), "odd".to_string());
+        let odd = page(&conn, &request);
+        assert_eq!(odd.total, 3);
+        assert!(!odd.has_more);
+
+        request.sort_by = Some("id; DROP TABLE items".to_string());
+        assert!(
+            query_page(&conn, &QUERY, &Conditions::new(), &request, |row| row
+                .get::<_, i64>(0))
+            .is_err()
+        );
+        assert!(Conditions::new()
+            .filters(&request, &[("status", "status")])
+            .is_err());
+        assert!(PageRequest {
+            cursor: Some("bogus".to_string()),
+            ..Default::default()
+        }
+        .offset()
+        .is_err());
+    }
+}
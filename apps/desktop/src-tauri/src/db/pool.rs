@@ -0,0 +1,213 @@
+//! Pooled SQLite connections.
+//!
+//! Every connection the pool hands out runs in WAL mode with a busy timeout
+//! and foreign keys on, so readers never wait on a writer and concurrent
+//! writers queue instead of failing with `database is locked`. States that
+//! used to open their own `Connection` borrow one per call instead.
+//!
+//! Async code should go through [`Pool::run`], which does the SQLite work on
+//! the blocking thread pool. Holding a [`PooledConnection`] across an
+//! `.await` is rejected by clippy (see `clippy.toml`).
+
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub type PooledConnection = r2d2::PooledConnection<SqliteConnectionManager>;
+pub type PoolError = r2d2::Error;
+
+/// Why [`Pool::run`] could not finish
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("Database connection unavailable: {0}")]
+    Pool(#[from] PoolError),
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Database task failed: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+/// Commands report errors as strings
+impl From<DbError> for String {
+    fn from(err: DbError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Connections kept open for the app database
+pub const DEFAULT_POOL_SIZE: u32 = 8;
+
+/// How long a connection waits for another writer before giving up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long `get` waits for a free connection
+const CHECKOUT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Apply the settings every app connection uses. Also called for the few
+/// connections opened outside the pool.
+pub fn configure(conn: &Connection) -> rusqlite::Result<()> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // In-memory databases answer "memory"; they have no journal to switch
+    let _mode: String =
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    Ok(())
+}
+
+/// Open a standalone connection with the pool's settings
+pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    configure(&conn)?;
+    Ok(conn)
+}
+
+/// Shared pool of connections to one database file. Cloning is cheap and
+/// shares the pool.
+#[derive(Clone)]
+pub struct Pool {
+    inner: r2d2::Pool<SqliteConnectionManager>,
+    path: PathBuf,
+}
+
+impl std::fmt::Debug for Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pool")
+            .field("path", &self.path)
+            .field("state", &self.inner.state())
+            .finish()
+    }
+}
+
+impl Pool {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PoolError> {
+        Self::with_size(path, DEFAULT_POOL_SIZE)
+    }
+
+    pub fn with_size(path: impl AsRef<Path>, size: u32) -> Result<Self, PoolError> {
+        let path = path.as_ref().to_path_buf();
+        let manager = SqliteConnectionManager::file(&path).with_init(|conn| configure(conn));
+        Self::build(manager, path, size)
+    }
+
+    /// Pool over a private in-memory database, for tests. Connections share
+    /// the database for as long as the pool is alive.
+    pub fn in_memory() -> Result<Self, PoolError> {
+        let name = format!(
+            "file:pool-{}?mode=memory&cache=shared",
+            uuid::Uuid::new_v4()
+        );
+        let manager = SqliteConnectionManager::file(&name).with_init(|conn| configure(conn));
+        Self::build(manager, PathBuf::from(name), 4)
+    }
+
+    fn build(
+        manager: SqliteConnectionManager,
+        path: PathBuf,
+        size: u32,
+    ) -> Result<Self, PoolError> {
+        let inner = r2d2::Pool::builder()
+            .max_size(size.max(1))
+            .connection_timeout(CHECKOUT_TIMEOUT)
+            .build(manager)?;
+        Ok(Self { inner, path })
+    }
+
+    /// Borrow a connection; it goes back to the pool when dropped
+    pub fn get(&self) -> Result<PooledConnection, PoolError> {
+        self.inner.get()
+    }
+
+    /// Run `f` on a pooled connection on the blocking thread pool, so the
+    /// async runtime keeps polling other tasks while SQLite works. The
+    /// connection goes back to the pool when `f` returns.
+    pub async fn run<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<DbError> + Send + 'static,
+    {
+        let pool = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get().map_err(DbError::from)?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(DbError::from)?
+    }
+
+    /// Database file the pool connects to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connections_share_the_database() {
+        let pool = Pool::in_memory().unwrap();
+        let first = pool.get().unwrap();
+        first
+            .execute_batch("CREATE TABLE t (v INTEGER); INSERT INTO t VALUES (7);")
+            .unwrap();
+
+        let second = pool.get().unwrap();
+        let value: i64 = second
+            .query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, 7);
+
+        let foreign_keys: bool = second
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert!(foreign_keys);
+    }
+
+    #[tokio::test]
+    async fn test_run_off_the_runtime() {
+        let pool = Pool::in_memory().unwrap();
+        pool.run(|conn| -> Result<_, DbError> {
+            conn.execute_batch("CREATE TABLE t (v INTEGER)")?;
+            let tx = conn.transaction()?;
+            tx.execute("INSERT INTO t VALUES (1), (2)", [])?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let total: i64 = pool
+            .run(|conn| -> Result<_, DbError> {
+                Ok(conn.query_row("SELECT SUM(v) FROM t", [], |row| row.get(0))?)
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 3);
+
+        let err: String = pool
+            .run(|conn| -> Result<(), String> {
+                conn.execute("INSERT INTO missing VALUES (1)", [])
+                    .map_err(|e| format!("Failed to insert: {}", e))?;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("Failed to insert"));
+    }
+
+    #[test]
+    fn test_file_pool_uses_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = Pool::open(dir.path().join("app.db")).unwrap();
+        let mode: String = pool
+            .get()
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+    }
+}
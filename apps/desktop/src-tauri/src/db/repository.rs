@@ -1,9 +1,12 @@
 use chrono::{DateTime, Duration, TimeZone, Utc};
-use rusqlite::{params, Connection, Result, Row};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+
+use super::pagination::{query_page, Conditions, Page, PageQuery, PageRequest};
 
 use super::models::{
     AutomationHistory, Conversation, ConversationCostBreakdown, CostTimeseriesPoint, Message,
     MessageRole, OverlayEvent, OverlayEventType, ProviderCostBreakdown, Setting, TaskType,
+    ToolCallRecord,
 };
 
 // ============================================================================
@@ -41,6 +44,33 @@ pub fn list_conversations(conn: &Connection, limit: i64, offset: i64) -> Result<
     Ok(conversations)
 }
 
+/// One page of conversations, searchable by title
+pub fn page_conversations(
+    conn: &Connection,
+    request: &PageRequest,
+) -> std::result::Result<Page<Conversation>, String> {
+    let mut conditions = Conditions::new();
+    conditions.search(request, &["title"]);
+    conditions.filters(request, &[])?;
+
+    query_page(
+        conn,
+        &PageQuery {
+            select: "id, title, created_at, updated_at",
+            from: "conversations",
+            id_column: "id",
+            sorts: &[
+                ("updatedAt", "updated_at"),
+                ("createdAt", "created_at"),
+                ("title", "title"),
+            ],
+        },
+        &conditions,
+        request,
+        map_conversation,
+    )
+}
+
 pub fn update_conversation_title(conn: &Connection, id: i64, title: String) -> Result<()> {
     conn.execute(
         "UPDATE conversations SET title = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
@@ -100,16 +130,42 @@ pub fn get_message(conn: &Connection, id: i64) -> Result<Message> {
     )
 }
 
+/// Messages on the conversation's active branch, oldest first
 pub fn list_messages(conn: &Connection, conversation_id: i64) -> Result<Vec<Message>> {
+    let leaf: Option<i64> = conn
+        .query_row(
+            "SELECT active_leaf_id FROM conversations WHERE id = ?1",
+            params![conversation_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+
+    match leaf {
+        Some(leaf) => list_message_path(conn, leaf),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The thread leading to `message_id`: its ancestors, root first, then the
+/// message itself
+pub fn list_message_path(conn: &Connection, message_id: i64) -> Result<Vec<Message>> {
     let mut stmt = conn.prepare(
-        "SELECT id, conversation_id, role, content, tokens, cost, provider, model, created_at
-         FROM messages
-         WHERE conversation_id = ?1
-         ORDER BY created_at ASC",
+        "WITH RECURSIVE path(id, depth) AS (
+            SELECT id, 0 FROM messages WHERE id = ?1
+            UNION ALL
+            SELECT m.parent_message_id, path.depth + 1
+            FROM messages m JOIN path ON m.id = path.id
+            WHERE m.parent_message_id IS NOT NULL AND m.parent_message_id < m.id
+         )
+         SELECT m.id, m.conversation_id, m.role, m.content, m.tokens, m.cost, m.provider,
+                m.model, m.created_at
+         FROM path JOIN messages m ON m.id = path.id
+         ORDER BY path.depth DESC",
     )?;
 
     let messages = stmt
-        .query_map(params![conversation_id], map_message)?
+        .query_map(params![message_id], map_message)?
         .collect::<Result<Vec<_>>>()?;
 
     Ok(messages)
@@ -129,6 +185,51 @@ pub fn update_message_content(conn: &Connection, id: i64, content: String) -> Re
     get_message(conn, id)
 }
 
+/// Record the tool calls an assistant message made
+pub fn set_message_tool_calls(
+    conn: &Connection,
+    id: i64,
+    tool_calls: &[ToolCallRecord],
+) -> Result<()> {
+    let json = serde_json::to_string(tool_calls)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "UPDATE messages SET tool_calls = ?1 WHERE id = ?2",
+        params![json, id],
+    )?;
+    Ok(())
+}
+
+/// Tool calls made in a conversation, oldest first, with the ID of the
+/// message that made them
+pub fn list_tool_calls(
+    conn: &Connection,
+    conversation_id: i64,
+) -> Result<Vec<(i64, ToolCallRecord)>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, tool_calls
+         FROM messages
+         WHERE conversation_id = ?1 AND tool_calls IS NOT NULL
+         ORDER BY created_at ASC, id ASC",
+    )?;
+
+    let rows = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(rows
+        .into_iter()
+        .flat_map(|(message_id, json)| {
+            serde_json::from_str::<Vec<ToolCallRecord>>(&json)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |call| (message_id, call))
+        })
+        .collect())
+}
+
 fn map_message(row: &Row) -> Result<Message> {
     let role_str: String = row.get(2)?;
     let role = MessageRole::from_str(&role_str).ok_or_else(|| rusqlite::Error::InvalidQuery)?;
@@ -618,6 +719,33 @@ mod tests {
         assert_eq!(messages.len(), 1);
     }
 
+    #[test]
+    fn test_message_tool_calls() {
+        let conn = setup_test_db();
+
+        let conv_id = create_conversation(&conn, "Test".to_string()).unwrap();
+        let msg = Message::new(conv_id, MessageRole::Assistant, String::new());
+        let id = create_message(&conn, &msg).unwrap();
+        create_message(
+            &conn,
+            &Message::new(conv_id, MessageRole::User, "Thanks".to_string()),
+        )
+        .unwrap();
+
+        let calls = vec![
+            ToolCallRecord::new("call_1", "file_read", r#"{"path":"notes.md"}"#, None),
+            ToolCallRecord::new("call_2", "file_write", "not json", Some("denied".into())),
+        ];
+        set_message_tool_calls(&conn, id, &calls).unwrap();
+
+        let listed = list_tool_calls(&conn, conv_id).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].0, id);
+        assert_eq!(listed[0].1.arguments["path"], "notes.md");
+        assert!(!listed[1].1.success);
+        assert_eq!(listed[1].1.arguments, "not json");
+    }
+
     #[test]
     fn test_settings_crud() {
         let conn = setup_test_db();
@@ -0,0 +1,356 @@
+//! Data retention and privacy purges.
+//!
+//! A [`RetentionPolicy`] gives each kind of accumulating data a time to live;
+//! [`RetentionScheduler`] deletes whatever has outlived it once a day.
+//! [`wipe_database`] empties every table for a "delete my data" request.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+
+use crate::db::repository::{get_setting, set_setting};
+use crate::db::Pool;
+
+const POLICY_SETTING: &str = "retention.policy";
+const STARTUP_DELAY: Duration = Duration::from_secs(10 * 60);
+const TICK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Days each kind of data is kept; `None` keeps it forever
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub messages_days: Option<u32>,
+    pub telemetry_days: Option<u32>,
+    pub automation_history_days: Option<u32>,
+    pub overlay_events_days: Option<u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            messages_days: None,
+            telemetry_days: Some(90),
+            automation_history_days: Some(180),
+            overlay_events_days: Some(30),
+        }
+    }
+}
+
+/// Rows removed by [`purge_expired`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeReport {
+    pub messages: usize,
+    /// Conversations left empty by the message purge
+    pub conversations: usize,
+    pub telemetry_events: usize,
+    pub automation_history: usize,
+    pub overlay_events: usize,
+}
+
+impl PurgeReport {
+    pub fn total(&self) -> usize {
+        self.messages
+            + self.conversations
+            + self.telemetry_events
+            + self.automation_history
+            + self.overlay_events
+    }
+}
+
+/// Rows removed by [`wipe_database`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeReport {
+    pub tables: usize,
+    pub rows: usize,
+}
+
+pub fn policy(conn: &Connection) -> RetentionPolicy {
+    get_setting(conn, POLICY_SETTING)
+        .ok()
+        .and_then(|setting| serde_json::from_str(&setting.value).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_policy(conn: &Connection, policy: &RetentionPolicy) -> Result<()> {
+    set_setting(
+        conn,
+        POLICY_SETTING.to_string(),
+        serde_json::to_string(policy)?,
+        false,
+    )?;
+    Ok(())
+}
+
+/// Delete everything older than the policy allows, as of `now`
+pub fn purge_expired(
+    conn: &mut Connection,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Result<PurgeReport> {
+    let cutoff = |days: u32| now - chrono::Duration::days(i64::from(days));
+    // Timestamps are stored both as `CURRENT_TIMESTAMP` and RFC 3339, so
+    // compare through julianday() rather than as text
+    let sql_cutoff = |days: u32| cutoff(days).format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let tx = conn.transaction()?;
+    let mut report = PurgeReport::default();
+
+    if let Some(days) = policy.messages_days {
+        let before = sql_cutoff(days);
+        report.messages = tx.execute(
+            "DELETE FROM messages WHERE julianday(created_at) < julianday(?1)",
+            params![before],
+        )?;
+        report.conversations = tx.execute(
+            "DELETE FROM conversations
+             WHERE julianday(updated_at) < julianday(?1)
+               AND NOT EXISTS (SELECT 1 FROM messages m WHERE m.conversation_id = conversations.id)",
+            params![before],
+        )?;
+    }
+    if let Some(days) = policy.telemetry_days {
+        report.telemetry_events = tx.execute(
+            "DELETE FROM telemetry_events WHERE timestamp < ?1",
+            params![cutoff(days).timestamp_millis()],
+        )?;
+    }
+    if let Some(days) = policy.automation_history_days {
+        report.automation_history = tx.execute(
+            "DELETE FROM automation_history WHERE julianday(created_at) < julianday(?1)",
+            params![sql_cutoff(days)],
+        )?;
+    }
+    if let Some(days) = policy.overlay_events_days {
+        report.overlay_events = tx.execute(
+            "DELETE FROM overlay_events WHERE julianday(timestamp) < julianday(?1)",
+            params![sql_cutoff(days)],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Delete every row of every table except the schema bookkeeping and the
+/// append-only audit trail, then rebuild the file so the deleted data does
+/// not linger in free pages or the WAL
+pub fn wipe_database(conn: &mut Connection) -> Result<WipeReport> {
+    // Virtual tables sort last so their source tables' triggers run first
+    let tables: Vec<(String, Option<String>)> = conn
+        .prepare(
+            "SELECT name, CASE WHEN sql LIKE 'CREATE VIRTUAL TABLE%' THEN sql END
+             FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+               AND name NOT IN ('schema_version', 'audit')
+             ORDER BY sql LIKE 'CREATE VIRTUAL TABLE%', name",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    // Full-text shadow tables are emptied through their virtual table
+    let virtual_tables: Vec<&str> = tables
+        .iter()
+        .filter(|(_, virtual_sql)| virtual_sql.is_some())
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let is_shadow = |name: &str| {
+        virtual_tables.iter().any(|vt| {
+            name.strip_prefix(vt)
+                .is_some_and(|rest| rest.starts_with('_'))
+        })
+    };
+
+    let tx = conn.transaction()?;
+    // Every parent and child goes, so only the end state has to hold
+    tx.execute_batch("PRAGMA defer_foreign_keys = ON")?;
+    let mut report = WipeReport::default();
+    for (name, virtual_sql) in tables.iter().filter(|(name, _)| !is_shadow(name)) {
+        let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+        let external_content = virtual_sql
+            .as_deref()
+            .is_some_and(|sql| sql.to_ascii_lowercase().contains("content="));
+        if external_content {
+            // Reads through to the source table, so DELETE would not work
+            tx.execute(
+                &format!("INSERT INTO {0}({0}) VALUES ('delete-all')", quoted),
+                [],
+            )?;
+        } else {
+            report.rows += tx.execute(&format!("DELETE FROM {}", quoted), [])?;
+        }
+        report.tables += 1;
+    }
+    tx.commit()?;
+
+    conn.execute_batch("VACUUM")?;
+    // Not every database is in WAL mode; the result row is not needed
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(report)
+}
+
+/// Applies the retention policy at startup and then daily
+#[derive(Default)]
+pub struct RetentionScheduler {
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl RetentionScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&self, app: &AppHandle) {
+        let pool = app.state::<Pool>().inner().clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(STARTUP_DELAY).await;
+            loop {
+                let purged = pool
+                    .run(|conn| -> Result<_> {
+                        let policy = policy(conn);
+                        purge_expired(conn, &policy, Utc::now())
+                    })
+                    .await;
+                match purged {
+                    Ok(report) if report.total() > 0 => {
+                        tracing::info!(
+                            "Retention purge removed {} rows: {:?}",
+                            report.total(),
+                            report
+                        )
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Retention purge failed: {:#}", e),
+                }
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+        if let Some(previous) = self.worker.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_purge_expired_respects_each_ttl() {
+        let mut conn = setup();
+        let now = Utc::now();
+        let old = (now - chrono::Duration::days(40))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        conn.execute_batch(&format!(
+            "INSERT INTO conversations (id, title, created_at, updated_at) VALUES
+                 (1, 'stale', '{old}', '{old}'), (2, 'active', '{old}', CURRENT_TIMESTAMP);
+             INSERT INTO messages (conversation_id, role, content, created_at) VALUES
+                 (1, 'user', 'old', '{old}'),
+                 (2, 'user', 'old', '{old}'),
+                 (2, 'assistant', 'new', CURRENT_TIMESTAMP);
+             INSERT INTO automation_history (task_type, success, duration_ms, created_at) VALUES
+                 ('other', 1, 5, '{old}'), ('other', 1, 5, CURRENT_TIMESTAMP);"
+        ))
+        .unwrap();
+        conn.execute(
+            "INSERT INTO overlay_events (event_type, x, y, timestamp) VALUES ('click', 0, 0, ?1)",
+            params![(now - chrono::Duration::days(40)).to_rfc3339()],
+        )
+        .unwrap();
+        for ts in [now - chrono::Duration::days(100), now] {
+            conn.execute(
+                "INSERT INTO telemetry_events (batch_id, name, timestamp, session_id)
+                 VALUES ('b', 'e', ?1, 's')",
+                params![ts.timestamp_millis()],
+            )
+            .unwrap();
+        }
+
+        let policy = RetentionPolicy {
+            messages_days: Some(30),
+            ..RetentionPolicy::default()
+        };
+        let report = purge_expired(&mut conn, &policy, now).unwrap();
+        assert_eq!(
+            report,
+            PurgeReport {
+                messages: 2,
+                conversations: 1,
+                telemetry_events: 1,
+                automation_history: 0,
+                overlay_events: 1,
+            }
+        );
+        assert_eq!(count(&conn, "messages"), 1);
+        assert_eq!(count(&conn, "conversations"), 1);
+        assert_eq!(count(&conn, "automation_history"), 2);
+
+        // Keeping messages forever leaves them alone
+        let report = purge_expired(&mut conn, &RetentionPolicy::default(), now).unwrap();
+        assert_eq!(report.total(), 0);
+    }
+
+    #[test]
+    fn test_policy_round_trip() {
+        let conn = setup();
+        assert_eq!(policy(&conn), RetentionPolicy::default());
+        let custom = RetentionPolicy {
+            messages_days: Some(365),
+            telemetry_days: None,
+            automation_history_days: Some(7),
+            overlay_events_days: Some(1),
+        };
+        set_policy(&conn, &custom).unwrap();
+        assert_eq!(policy(&conn), custom);
+    }
+
+    #[test]
+    fn test_wipe_database_keeps_schema() {
+        let mut conn = setup();
+        conn.execute_batch(
+            "INSERT INTO conversations (id, title) VALUES (1, 'private');
+             INSERT INTO messages (conversation_id, role, content) VALUES (1, 'user', 'secret');
+             INSERT INTO settings (key, value) VALUES ('theme', 'dark');
+             INSERT INTO audit (created_at, actor_kind, category, action, outcome)
+             VALUES (0, 'user', 'filesystem', 'file_write', 'success');",
+        )
+        .unwrap();
+
+        let report = wipe_database(&mut conn).unwrap();
+        assert!(report.rows >= 3);
+        assert_eq!(count(&conn, "conversations"), 0);
+        assert_eq!(count(&conn, "messages"), 0);
+        assert_eq!(count(&conn, "settings"), 0);
+        assert_eq!(count(&conn, "audit"), 1);
+        assert_eq!(
+            migrations::current_version(&conn).unwrap(),
+            migrations::latest_version()
+        );
+        let integrity: String = conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(integrity, "ok");
+    }
+}
@@ -5,6 +5,13 @@ use std::fs::File;
 use std::io::BufWriter;
 use std::path::Path;
 
+/// Page margin on every side
+const MARGIN: Mm = Mm(20.0);
+/// Average Helvetica glyph width as a fraction of the font size
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+/// Millimetres per typographic point
+const MM_PER_PT: f32 = 0.3528;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfDocumentConfig {
     pub title: Option<String>,
@@ -77,9 +84,6 @@ impl PdfDocumentCreator {
         let title = config.title.as_deref().unwrap_or("Document");
         let (doc, page1, layer1) = PdfDocument::new(title, page_width, page_height, "Layer 1");
 
-        // Get the current layer and page
-        let current_layer = doc.get_page(page1).get_layer(layer1);
-
         // Add built-in font
         let font = doc
             .add_builtin_font(BuiltinFont::Helvetica)
@@ -88,10 +92,13 @@ impl PdfDocumentCreator {
             .add_builtin_font(BuiltinFont::HelveticaBold)
             .map_err(|e| Error::Generic(format!("Failed to add bold font: {}", e)))?;
 
-        // Start position
-        let mut current_y = page_height - Mm(20.0); // Start 20mm from top
-        let margin_left = Mm(20.0);
-        let _margin_right = Mm(20.0);
+        let mut page = PageCursor {
+            doc: &doc,
+            layer: doc.get_page(page1).get_layer(layer1),
+            page_width,
+            page_height,
+            y: page_height - MARGIN,
+        };
 
         // Add content
         for content in contents {
@@ -103,15 +110,7 @@ impl PdfDocumentCreator {
                         3 => 14,
                         _ => 12,
                     };
-
-                    current_layer.use_text(
-                        &text,
-                        font_size as f32,
-                        margin_left,
-                        current_y,
-                        &font_bold,
-                    );
-                    current_y -= Mm(font_size as f32 * 0.5); // Add spacing
+                    page.text(&text, font_size as f32, Mm(0.0), &font_bold);
                 }
 
                 PdfContent::Paragraph {
@@ -127,55 +126,35 @@ impl PdfDocumentCreator {
                     } else {
                         &font
                     };
-
-                    current_layer.use_text(
-                        &text,
-                        size as f32,
-                        margin_left,
-                        current_y,
-                        selected_font,
-                    );
-                    current_y -= Mm(size as f32 * 0.5);
+                    page.text(&text, size as f32, Mm(0.0), selected_font);
                 }
 
                 PdfContent::BulletList { items } => {
                     for item in items {
-                        let bullet_text = format!("• {}", item);
-                        current_layer.use_text(&bullet_text, 12.0, margin_left, current_y, &font);
-                        current_y -= Mm(6.0);
+                        page.list_item("•", &item, &font);
                     }
                 }
 
                 PdfContent::NumberedList { items } => {
                     for (idx, item) in items.iter().enumerate() {
-                        let numbered_text = format!("{}. {}", idx + 1, item);
-                        current_layer.use_text(&numbered_text, 12.0, margin_left, current_y, &font);
-                        current_y -= Mm(6.0);
+                        page.list_item(&format!("{}.", idx + 1), item, &font);
                     }
                 }
 
                 PdfContent::Table { headers, rows } => {
                     // Simple table rendering
                     let header_text = headers.join(" | ");
-                    current_layer.use_text(&header_text, 12.0, margin_left, current_y, &font_bold);
-                    current_y -= Mm(6.0);
+                    page.text(&header_text, 12.0, Mm(0.0), &font_bold);
 
                     let separator = "-".repeat(header_text.len().min(80));
-                    current_layer.use_text(&separator, 12.0, margin_left, current_y, &font);
-                    current_y -= Mm(6.0);
+                    page.text(&separator, 12.0, Mm(0.0), &font);
 
                     for row in rows {
-                        let row_text = row.join(" | ");
-                        current_layer.use_text(&row_text, 12.0, margin_left, current_y, &font);
-                        current_y -= Mm(6.0);
+                        page.text(&row.join(" | "), 12.0, Mm(0.0), &font);
                     }
                 }
 
-                PdfContent::PageBreak => {
-                    // For simplicity, we'll skip actual page break implementation
-                    // In a full implementation, you would create a new page here
-                    current_y -= Mm(20.0);
-                }
+                PdfContent::PageBreak => page.new_page(),
 
                 PdfContent::Image {
                     path,
@@ -184,16 +163,9 @@ impl PdfDocumentCreator {
                 } => {
                     // Image placeholder
                     let placeholder = format!("[Image: {}]", path);
-                    current_layer.use_text(&placeholder, 12.0, margin_left, current_y, &font);
-                    current_y -= Mm(6.0);
+                    page.text(&placeholder, 12.0, Mm(0.0), &font);
                 }
             }
-
-            // Check if we need a new page (simplified)
-            if current_y < Mm(20.0) {
-                current_y = page_height - Mm(20.0);
-                // In a full implementation, create a new page here
-            }
         }
 
         // Save to file
@@ -243,6 +215,93 @@ impl Default for PdfDocumentCreator {
     }
 }
 
+/// Writes lines top to bottom, wrapping long text and starting a new page
+/// when the bottom margin is reached
+struct PageCursor<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    page_width: Mm,
+    page_height: Mm,
+    y: Mm,
+}
+
+impl PageCursor<'_> {
+    fn new_page(&mut self) {
+        let (page, layer) = self
+            .doc
+            .add_page(self.page_width, self.page_height, "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = self.page_height - MARGIN;
+    }
+
+    /// Write `text` at `indent` from the left margin, wrapped to the page width
+    fn text(&mut self, text: &str, size: f32, indent: Mm, font: &IndirectFontRef) {
+        let line_height = Mm(size * 0.5);
+        let width = self.page_width - MARGIN - MARGIN - indent;
+        for line in wrap_text(text, max_chars(width, size)) {
+            if self.y - line_height < MARGIN {
+                self.new_page();
+            }
+            self.layer
+                .use_text(line, size, MARGIN + indent, self.y, font);
+            self.y -= line_height;
+        }
+    }
+
+    /// Write a list item with its wrapped lines aligned after the marker.
+    /// Leading spaces in `text` indent nested items.
+    fn list_item(&mut self, marker: &str, text: &str, font: &IndirectFontRef) {
+        let glyph = 12.0 * AVERAGE_GLYPH_WIDTH * MM_PER_PT;
+        let nesting = text.len() - text.trim_start_matches(' ').len();
+        let indent = Mm(nesting as f32 * glyph);
+        let hanging = Mm((marker.chars().count() + 1) as f32 * glyph);
+        if self.y - Mm(6.0) < MARGIN {
+            self.new_page();
+        }
+        self.layer
+            .use_text(marker, 12.0, MARGIN + indent, self.y, font);
+        self.text(text.trim_start(), 12.0, indent + hanging, font);
+    }
+}
+
+/// Characters of average width that fit in `width` at `size` points
+fn max_chars(width: Mm, size: f32) -> usize {
+    ((width.0 / (size * AVERAGE_GLYPH_WIDTH * MM_PER_PT)) as usize).max(10)
+}
+
+/// Break text into lines of at most `max_chars`, splitting words only when a
+/// single word is longer than a line
+fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        while word.chars().count() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let split = word
+                .char_indices()
+                .nth(max_chars)
+                .map_or(word.len(), |(i, _)| i);
+            lines.push(word[..split].to_string());
+            word = word[split..].to_string();
+        }
+        if line.is_empty() {
+            line = word;
+        } else if line.chars().count() + 1 + word.chars().count() <= max_chars {
+            line.push(' ');
+            line.push_str(&word);
+        } else {
+            lines.push(std::mem::replace(&mut line, word));
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,4 +363,36 @@ mod tests {
         assert!(result.is_ok());
         assert!(output_path.exists());
     }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(
+            wrap_text("the quick brown fox jumps", 10),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(
+            wrap_text("abcdefghijklmnop", 10),
+            vec!["abcdefghij", "klmnop"]
+        );
+        assert_eq!(wrap_text("", 10), vec![""]);
+    }
+
+    #[test]
+    fn test_long_content_adds_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("long.pdf");
+
+        let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(20);
+        PdfDocumentCreator::new()
+            .create_simple(
+                output_path.to_str().unwrap(),
+                None,
+                None,
+                vec![paragraph; 10],
+            )
+            .unwrap();
+
+        let pdf = lopdf::Document::load(&output_path).unwrap();
+        assert!(pdf.get_pages().len() > 1);
+    }
 }
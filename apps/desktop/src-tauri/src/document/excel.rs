@@ -34,6 +34,7 @@ impl ExcelHandler {
         Ok(DocumentContent {
             text: extraction.text,
             metadata,
+            pages: Vec::new(),
         })
     }
 
@@ -176,7 +177,7 @@ fn timestamp_to_string(time: std::time::SystemTime) -> Option<String> {
         .map(|d| d.as_secs().to_string())
 }
 
-fn data_type_to_string(cell: &DataType) -> String {
+pub(super) fn data_type_to_string(cell: &DataType) -> String {
     match cell {
         DataType::Empty => String::new(),
         DataType::String(s) => s.trim().to_string(),
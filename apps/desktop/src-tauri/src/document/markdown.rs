@@ -0,0 +1,1020 @@
+//! Conversion between documents and Markdown.
+//!
+//! Word and PDF files are read into structured Markdown, which keeps headings,
+//! lists and tables intact when documents are fed into LLM context or RAG.
+//! Markdown is written back out as Word or PDF through the document creators,
+//! so agent-generated reports can be published in either format.
+//!
+//! Word documents carry their structure in paragraph styles and numbering, so
+//! the conversion is faithful. PDFs only carry positioned text; headings, lists
+//! and tables are inferred from the extracted lines.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use roxmltree::{Document as XmlDocument, Node};
+use serde::{Deserialize, Serialize};
+use zip::read::ZipArchive;
+
+use super::{
+    PdfContent, PdfDocumentConfig, PdfDocumentCreator, WordContent, WordDocumentConfig,
+    WordDocumentCreator,
+};
+use crate::error::{Error, Result};
+
+const WORD_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+/// Longest single line in a PDF still considered a heading
+const MAX_PDF_HEADING_CHARS: usize = 80;
+
+static NUMBERED_ITEM_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d{1,3})[.)]\s+(.*)$").unwrap());
+static BULLET_ITEM_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[•●▪◦‣\-–*]\s+(.*)$").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionFormat {
+    Markdown,
+    Word,
+    Pdf,
+}
+
+impl ConversionFormat {
+    /// Detect the format from the extension, falling back to the file's
+    /// leading bytes for paths without a recognised extension
+    pub fn detect(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("md" | "markdown" | "txt") => return Ok(Self::Markdown),
+            Some("docx") => return Ok(Self::Word),
+            Some("pdf") => return Ok(Self::Pdf),
+            Some("doc") => {
+                return Err(Error::Generic(
+                    "Legacy .doc files are not supported. Please convert the document to .docx and try again."
+                        .to_string(),
+                ))
+            }
+            _ => {}
+        }
+
+        let mut header = [0u8; 5];
+        let read = File::open(path)
+            .and_then(|mut file| file.read(&mut header))
+            .map_err(|e| {
+                Error::Generic(format!(
+                    "Cannot detect the format of {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        match &header[..read] {
+            [b'%', b'P', b'D', b'F', ..] => Ok(Self::Pdf),
+            [b'P', b'K', 3, 4, ..] => Ok(Self::Word),
+            _ => match fs::read(path) {
+                Ok(bytes) if std::str::from_utf8(&bytes).is_ok() => Ok(Self::Markdown),
+                _ => Err(Error::Generic(format!(
+                    "Unsupported file type: {}",
+                    path.display()
+                ))),
+            },
+        }
+    }
+
+    /// Format of a file that does not exist yet, from its extension only
+    fn for_output(path: &Path) -> Result<Self> {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("md" | "markdown" | "txt") => Ok(Self::Markdown),
+            Some("docx") => Ok(Self::Word),
+            Some("pdf") => Ok(Self::Pdf),
+            other => Err(Error::Generic(format!(
+                "Cannot convert to '{}': use a .md, .docx or .pdf output path",
+                other.unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentConversion {
+    pub input_format: ConversionFormat,
+    pub output_format: ConversionFormat,
+    pub output_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ListItem {
+    depth: usize,
+    ordered: bool,
+    text: String,
+}
+
+/// Document structure shared by every conversion.
+///
+/// Text read from documents holds Markdown inline markup (`**bold**`); text
+/// parsed from Markdown is plain, since the document creators format whole
+/// paragraphs only.
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Paragraph {
+        text: String,
+        bold: bool,
+    },
+    List(Vec<ListItem>),
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Code(String),
+}
+
+/// Read a Word, PDF or Markdown file as Markdown
+pub fn to_markdown(input_path: &str) -> Result<String> {
+    let path = Path::new(input_path);
+    if !path.exists() {
+        return Err(Error::Generic(format!("File not found: {}", input_path)));
+    }
+    match ConversionFormat::detect(path)? {
+        ConversionFormat::Markdown => fs::read_to_string(path)
+            .map_err(|e| Error::Generic(format!("Failed to read Markdown: {}", e))),
+        ConversionFormat::Word => Ok(render_markdown(&docx_blocks(path)?)),
+        ConversionFormat::Pdf => {
+            let (text, _) = super::pdf::extract_text_with_pages(path)?;
+            Ok(render_markdown(&pdf_blocks(&text)))
+        }
+    }
+}
+
+/// Convert between Word, PDF and Markdown; formats follow the file extensions.
+///
+/// Word and PDF inputs go through Markdown, so converting between them keeps
+/// the same headings, lists and tables.
+pub fn convert(input_path: &str, output_path: &str) -> Result<DocumentConversion> {
+    let input_format = ConversionFormat::detect(Path::new(input_path))?;
+    let output_format = ConversionFormat::for_output(Path::new(output_path))?;
+    let markdown = to_markdown(input_path)?;
+
+    if let Some(parent) = Path::new(output_path).parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| Error::Generic(format!("Failed to create directory: {}", e)))?;
+    }
+    match output_format {
+        ConversionFormat::Markdown => fs::write(output_path, markdown)
+            .map_err(|e| Error::Generic(format!("Failed to write Markdown: {}", e)))?,
+        ConversionFormat::Word => write_word(parse_markdown(&markdown), output_path)?,
+        ConversionFormat::Pdf => write_pdf(parse_markdown(&markdown), output_path)?,
+    }
+
+    Ok(DocumentConversion {
+        input_format,
+        output_format,
+        output_path: output_path.to_string(),
+    })
+}
+
+// ====================
+// Markdown output
+// ====================
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut sections = Vec::new();
+    for block in blocks {
+        sections.push(match block {
+            Block::Heading { level, text } => {
+                format!("{} {}", "#".repeat(usize::from(*level).clamp(1, 6)), text)
+            }
+            Block::Paragraph { text, bold: true } => format!("**{}**", text),
+            Block::Paragraph { text, bold: false } => text.clone(),
+            Block::List(items) => render_list(items),
+            Block::Table { headers, rows } => render_table(headers, rows),
+            Block::Code(text) => format!("```\n{}\n```", text),
+        });
+    }
+    let mut markdown = sections.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
+
+fn render_list(items: &[ListItem]) -> String {
+    // Numbering restarts whenever a deeper list ends and a new one begins
+    let mut counters: Vec<usize> = Vec::new();
+    let mut lines = Vec::new();
+    for item in items {
+        counters.truncate(item.depth + 1);
+        counters.resize(item.depth + 1, 0);
+        counters[item.depth] += 1;
+        let marker = if item.ordered {
+            format!("{}.", counters[item.depth])
+        } else {
+            "-".to_string()
+        };
+        lines.push(format!(
+            "{}{} {}",
+            "   ".repeat(item.depth),
+            marker,
+            item.text
+        ));
+    }
+    lines.join("\n")
+}
+
+fn render_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .chain([headers.len()])
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let row_line = |cells: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|i| {
+                cells
+                    .get(i)
+                    .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let mut lines = vec![row_line(headers), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows.iter().map(|row| row_line(row)));
+    lines.join("\n")
+}
+
+// ====================
+// Markdown input
+// ====================
+
+#[derive(Default)]
+struct MarkdownParser {
+    blocks: Vec<Block>,
+    text: String,
+    /// Text appeared outside `**strong**` in the current paragraph
+    plain_text: bool,
+    strong_depth: usize,
+    heading: Option<u8>,
+    lists: Vec<bool>,
+    items: Vec<ListItem>,
+    table: Option<(Vec<String>, Vec<Vec<String>>)>,
+    row: Vec<String>,
+    code: bool,
+}
+
+impl MarkdownParser {
+    fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.text).trim().to_string()
+    }
+
+    /// Close the list item whose text has been collected so far
+    fn flush_item(&mut self) {
+        let text = self.take_text();
+        if let Some(&ordered) = self.lists.last() {
+            if !text.is_empty() {
+                self.items.push(ListItem {
+                    depth: self.lists.len() - 1,
+                    ordered,
+                    text,
+                });
+            }
+        }
+    }
+
+    fn handle(&mut self, event: Event<'_>) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.text.clear();
+                self.heading = Some(heading_level(level));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let text = self.take_text();
+                if let Some(level) = self.heading.take() {
+                    if !text.is_empty() {
+                        self.blocks.push(Block::Heading { level, text });
+                    }
+                }
+            }
+            Event::Start(Tag::Paragraph) if self.lists.is_empty() && self.table.is_none() => {
+                self.text.clear();
+                self.plain_text = false;
+            }
+            Event::End(TagEnd::Paragraph) if self.lists.is_empty() && self.table.is_none() => {
+                let text = self.take_text();
+                if !text.is_empty() {
+                    self.blocks.push(Block::Paragraph {
+                        text,
+                        bold: !self.plain_text,
+                    });
+                }
+            }
+            Event::End(TagEnd::Paragraph) => self.text.push(' '),
+            Event::Start(Tag::List(start)) => {
+                self.flush_item();
+                self.lists.push(start.is_some());
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.flush_item();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blocks
+                        .push(Block::List(std::mem::take(&mut self.items)));
+                }
+            }
+            Event::Start(Tag::Item) | Event::End(TagEnd::Item) => self.flush_item(),
+            Event::Start(Tag::Table(_)) => self.table = Some((Vec::new(), Vec::new())),
+            Event::End(TagEnd::Table) => {
+                if let Some((headers, rows)) = self.table.take() {
+                    self.blocks.push(Block::Table { headers, rows });
+                }
+            }
+            Event::Start(Tag::TableHead | Tag::TableRow) => self.row.clear(),
+            Event::End(TagEnd::TableHead) => {
+                if let Some((headers, _)) = self.table.as_mut() {
+                    *headers = std::mem::take(&mut self.row);
+                }
+            }
+            Event::End(TagEnd::TableRow) => {
+                if let Some((_, rows)) = self.table.as_mut() {
+                    rows.push(std::mem::take(&mut self.row));
+                }
+            }
+            Event::Start(Tag::TableCell) => self.text.clear(),
+            Event::End(TagEnd::TableCell) => {
+                let cell = self.take_text();
+                self.row.push(cell);
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                self.text.clear();
+                self.code = true;
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                self.code = false;
+                let text = std::mem::take(&mut self.text);
+                let text = text.trim_end_matches('\n');
+                if !text.is_empty() {
+                    self.blocks.push(Block::Code(text.to_string()));
+                }
+            }
+            Event::Start(Tag::Strong) => self.strong_depth += 1,
+            Event::End(TagEnd::Strong) => self.strong_depth = self.strong_depth.saturating_sub(1),
+            Event::Text(text) | Event::Code(text) => {
+                if self.strong_depth == 0 && !text.trim().is_empty() {
+                    self.plain_text = true;
+                }
+                self.text.push_str(&text);
+            }
+            Event::SoftBreak => self.text.push(' '),
+            Event::HardBreak => self.text.push(if self.code { '\n' } else { ' ' }),
+            _ => {}
+        }
+    }
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+fn parse_markdown(markdown: &str) -> Vec<Block> {
+    let mut parser = MarkdownParser::default();
+    for event in Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+    ) {
+        parser.handle(event);
+    }
+    parser.blocks
+}
+
+// ====================
+// Word input
+// ====================
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+fn docx_blocks(path: &Path) -> Result<Vec<Block>> {
+    let file =
+        File::open(path).map_err(|e| Error::Generic(format!("Failed to open DOCX: {}", e)))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| Error::Generic(format!("Invalid DOCX archive: {}", e)))?;
+    let document_xml = read_entry(&mut archive, "word/document.xml")
+        .ok_or_else(|| Error::Generic("Failed to read document.xml".to_string()))?;
+    let numbering_xml = read_entry(&mut archive, "word/numbering.xml");
+
+    word_blocks(&document_xml, numbering_xml.as_deref())
+}
+
+/// Whether list `num_id` at `level` is numbered rather than bulleted
+struct Numbering<'a> {
+    xml: Option<XmlDocument<'a>>,
+}
+
+impl Numbering<'_> {
+    fn is_ordered(&self, num_id: &str, level: &str) -> bool {
+        let Some(xml) = &self.xml else {
+            return false;
+        };
+        let abstract_id = xml
+            .descendants()
+            .find(|n| n.has_tag_name((WORD_NS, "num")) && word_attr(*n, "numId") == Some(num_id))
+            .and_then(|n| child(n, "abstractNumId"))
+            .and_then(|n| word_attr(n, "val"));
+        let Some(abstract_id) = abstract_id else {
+            return false;
+        };
+        xml.descendants()
+            .find(|n| {
+                n.has_tag_name((WORD_NS, "abstractNum"))
+                    && word_attr(*n, "abstractNumId") == Some(abstract_id)
+            })
+            .and_then(|n| {
+                n.children().find(|l| {
+                    l.has_tag_name((WORD_NS, "lvl")) && word_attr(*l, "ilvl") == Some(level)
+                })
+            })
+            .and_then(|n| child(n, "numFmt"))
+            .and_then(|n| word_attr(n, "val"))
+            .is_some_and(|format| format != "bullet" && format != "none")
+    }
+}
+
+fn word_attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((WORD_NS, name))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name((WORD_NS, name)))
+}
+
+/// `w:b`, `w:i` and similar toggles are on unless their value says otherwise
+fn toggle_on(properties: Option<Node<'_, '_>>, name: &str) -> bool {
+    properties
+        .and_then(|p| child(p, name))
+        .is_some_and(|n| !matches!(word_attr(n, "val"), Some("0" | "false" | "none")))
+}
+
+fn heading_style(style: &str) -> Option<u8> {
+    let normalized = style.to_lowercase().replace(' ', "");
+    if normalized == "title" {
+        return Some(1);
+    }
+    normalized
+        .strip_prefix("heading")
+        .and_then(|level| level.parse::<u8>().ok())
+        .filter(|level| (1..=6).contains(level))
+}
+
+/// Paragraph text with bold and italic runs marked up
+fn paragraph_markdown(paragraph: Node<'_, '_>) -> String {
+    let mut segments: Vec<(String, bool, bool)> = Vec::new();
+    for run in paragraph
+        .descendants()
+        .filter(|n| n.has_tag_name((WORD_NS, "r")))
+    {
+        let properties = child(run, "rPr");
+        let bold = toggle_on(properties, "b");
+        let italic = toggle_on(properties, "i");
+        let mut text = String::new();
+        for node in run.children() {
+            if node.has_tag_name((WORD_NS, "t")) {
+                text.push_str(node.text().unwrap_or_default());
+            } else if node.has_tag_name((WORD_NS, "tab")) || node.has_tag_name((WORD_NS, "br")) {
+                text.push(' ');
+            }
+        }
+        match segments.last_mut() {
+            Some(last) if last.1 == bold && last.2 == italic => last.0.push_str(&text),
+            _ => segments.push((text, bold, italic)),
+        }
+    }
+
+    let mut output = String::new();
+    for (text, bold, italic) in segments {
+        let trimmed = text.trim();
+        if trimmed.is_empty() || (!bold && !italic) {
+            output.push_str(&text);
+            continue;
+        }
+        let marker = match (bold, italic) {
+            (true, true) => "***",
+            (true, false) => "**",
+            _ => "*",
+        };
+        // Markers must hug the text for Markdown to recognise them
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let trailing = &text[text.trim_end().len()..];
+        output.push_str(&format!("{leading}{marker}{trimmed}{marker}{trailing}"));
+    }
+    output.trim().replace('\u{a0}', " ")
+}
+
+fn word_blocks(document_xml: &str, numbering_xml: Option<&str>) -> Result<Vec<Block>> {
+    let xml = XmlDocument::parse(document_xml)
+        .map_err(|e| Error::Generic(format!("Invalid DOCX XML: {}", e)))?;
+    let numbering = Numbering {
+        xml: numbering_xml.and_then(|xml| XmlDocument::parse(xml).ok()),
+    };
+    let Some(body) = xml
+        .descendants()
+        .find(|n| n.has_tag_name((WORD_NS, "body")))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut blocks = Vec::new();
+    for node in body.children().filter(|n| n.is_element()) {
+        if node.has_tag_name((WORD_NS, "tbl")) {
+            let mut rows: Vec<Vec<String>> = node
+                .children()
+                .filter(|n| n.has_tag_name((WORD_NS, "tr")))
+                .map(|row| {
+                    row.children()
+                        .filter(|n| n.has_tag_name((WORD_NS, "tc")))
+                        .map(|cell| {
+                            cell.children()
+                                .filter(|n| n.has_tag_name((WORD_NS, "p")))
+                                .map(paragraph_markdown)
+                                .filter(|text| !text.is_empty())
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        })
+                        .collect()
+                })
+                .collect();
+            if !rows.is_empty() {
+                let headers = rows.remove(0);
+                blocks.push(Block::Table { headers, rows });
+            }
+            continue;
+        }
+        if !node.has_tag_name((WORD_NS, "p")) {
+            continue;
+        }
+
+        let text = paragraph_markdown(node);
+        if text.is_empty() {
+            continue;
+        }
+        let properties = child(node, "pPr");
+        let style = properties
+            .and_then(|p| child(p, "pStyle"))
+            .and_then(|n| word_attr(n, "val"))
+            .unwrap_or_default();
+
+        if let Some(level) = heading_style(style) {
+            // Headings are bold by style; run-level markup would only add noise
+            blocks.push(Block::Heading {
+                level,
+                text: text.replace("**", ""),
+            });
+            continue;
+        }
+
+        let list = properties.and_then(|p| child(p, "numPr")).map(|num| {
+            let level = child(num, "ilvl")
+                .and_then(|n| word_attr(n, "val"))
+                .unwrap_or("0");
+            let num_id = child(num, "numId")
+                .and_then(|n| word_attr(n, "val"))
+                .unwrap_or("0");
+            (
+                level.parse::<usize>().unwrap_or(0),
+                numbering.is_ordered(num_id, level),
+            )
+        });
+        let list = list.or_else(|| {
+            let style = style.to_lowercase();
+            (style.starts_with("listbullet") || style.starts_with("listnumber"))
+                .then(|| (0, style.starts_with("listnumber")))
+        });
+
+        match list {
+            Some((depth, ordered)) => {
+                let item = ListItem {
+                    depth,
+                    ordered,
+                    text,
+                };
+                match blocks.last_mut() {
+                    Some(Block::List(items)) => items.push(item),
+                    _ => blocks.push(Block::List(vec![item])),
+                }
+            }
+            None => blocks.push(Block::Paragraph { text, bold: false }),
+        }
+    }
+    Ok(blocks)
+}
+
+// ====================
+// PDF input
+// ====================
+
+/// Rebuild structure from extracted PDF text.
+///
+/// Blank lines separate blocks. Bullet and number prefixes start list items,
+/// runs of `a | b` lines form tables, a short single-line block without closing
+/// punctuation is taken as a heading, and wrapped lines are joined back into
+/// paragraphs.
+fn pdf_blocks(text: &str) -> Vec<Block> {
+    let text = text.replace('\u{c}', "\n\n");
+    let mut groups: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if groups.last().is_some_and(|group| !group.is_empty()) {
+                groups.push(Vec::new());
+            }
+        } else if let Some(group) = groups.last_mut() {
+            group.push(line);
+        }
+    }
+
+    let mut blocks = Vec::new();
+    for group in groups.into_iter().filter(|group| !group.is_empty()) {
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut in_list = false;
+        let mut index = 0;
+        while index < group.len() {
+            let line = group[index];
+
+            let table_lines = &group[index
+                ..group[index..]
+                    .iter()
+                    .position(|line| !line.contains(" | ") && !is_rule(line))
+                    .map_or(group.len(), |end| index + end)];
+            let mut rows: Vec<Vec<String>> = table_lines
+                .iter()
+                .filter(|line| !is_rule(line))
+                .map(|line| {
+                    line.split(" | ")
+                        .map(|cell| cell.trim().to_string())
+                        .collect()
+                })
+                .collect();
+            if rows.len() >= 2 {
+                flush_paragraph(&mut blocks, &mut paragraph);
+                in_list = false;
+                let headers = rows.remove(0);
+                blocks.push(Block::Table { headers, rows });
+                index += table_lines.len();
+                continue;
+            }
+
+            let item = if let Some(captures) = BULLET_ITEM_RE.captures(line) {
+                Some((false, captures[1].to_string()))
+            } else {
+                NUMBERED_ITEM_RE
+                    .captures(line)
+                    .map(|captures| (true, captures[2].to_string()))
+            };
+            match item {
+                Some((ordered, text)) => {
+                    flush_paragraph(&mut blocks, &mut paragraph);
+                    in_list = true;
+                    let item = ListItem {
+                        depth: 0,
+                        ordered,
+                        text,
+                    };
+                    match blocks.last_mut() {
+                        Some(Block::List(items)) => items.push(item),
+                        _ => blocks.push(Block::List(vec![item])),
+                    }
+                }
+                // Wrapped continuation of the previous list item
+                None if in_list => {
+                    if let Some(Block::List(items)) = blocks.last_mut() {
+                        if let Some(last) = items.last_mut() {
+                            last.text = join_wrapped(&last.text, line);
+                        }
+                    }
+                }
+                None => paragraph.push(line),
+            }
+            index += 1;
+        }
+
+        if paragraph.len() == 1 && is_heading_line(paragraph[0]) {
+            blocks.push(Block::Heading {
+                level: 2,
+                text: paragraph[0].to_string(),
+            });
+            paragraph.clear();
+        }
+        flush_paragraph(&mut blocks, &mut paragraph);
+    }
+    blocks
+}
+
+fn is_rule(line: &str) -> bool {
+    line.len() >= 3
+        && line
+            .chars()
+            .all(|c| matches!(c, '-' | '|' | '+' | ' ' | ':'))
+}
+
+fn is_heading_line(line: &str) -> bool {
+    line.chars().count() <= MAX_PDF_HEADING_CHARS
+        && line.chars().next().is_some_and(char::is_alphanumeric)
+        && !line.ends_with(['.', ',', ';', ':', '!', '?'])
+        && line.split_whitespace().count() <= 12
+}
+
+/// Join a wrapped line onto the text before it, undoing end-of-line hyphenation
+fn join_wrapped(text: &str, line: &str) -> String {
+    match text.strip_suffix('-') {
+        Some(stem) if line.starts_with(char::is_lowercase) => format!("{}{}", stem, line),
+        _ => format!("{} {}", text, line),
+    }
+}
+
+fn flush_paragraph(blocks: &mut Vec<Block>, lines: &mut Vec<&str>) {
+    let Some((first, rest)) = lines.split_first() else {
+        return;
+    };
+    let text = rest
+        .iter()
+        .fold(first.to_string(), |text, line| join_wrapped(&text, line));
+    blocks.push(Block::Paragraph { text, bold: false });
+    lines.clear();
+}
+
+// ====================
+// Document output
+// ====================
+
+/// Consecutive list items with the same kind, at any depth
+fn list_runs(items: Vec<ListItem>) -> Vec<(bool, Vec<ListItem>)> {
+    let mut runs: Vec<(bool, Vec<ListItem>)> = Vec::new();
+    for item in items {
+        match runs.last_mut() {
+            Some((ordered, run)) if *ordered == item.ordered => run.push(item),
+            _ => runs.push((item.ordered, vec![item])),
+        }
+    }
+    runs
+}
+
+fn first_heading(blocks: &[Block]) -> Option<String> {
+    blocks.iter().find_map(|block| match block {
+        Block::Heading { text, .. } => Some(text.clone()),
+        _ => None,
+    })
+}
+
+fn write_word(blocks: Vec<Block>, output: &str) -> Result<()> {
+    let config = WordDocumentConfig {
+        title: first_heading(&blocks),
+        author: None,
+        subject: None,
+        keywords: None,
+    };
+    let mut contents = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Heading { level, text } => contents.push(WordContent::Heading { level, text }),
+            Block::Paragraph { text, bold } => contents.push(WordContent::Paragraph {
+                text,
+                bold: Some(bold),
+                italic: None,
+                underline: None,
+                font_size: None,
+                alignment: None,
+            }),
+            Block::List(items) => {
+                for (ordered, run) in list_runs(items) {
+                    let items = run.into_iter().map(|item| item.text).collect();
+                    contents.push(if ordered {
+                        WordContent::NumberedList { items }
+                    } else {
+                        WordContent::BulletList { items }
+                    });
+                }
+            }
+            Block::Table { headers, rows } => contents.push(WordContent::Table { headers, rows }),
+            Block::Code(text) => contents.extend(text.lines().map(|line| WordContent::Paragraph {
+                text: line.to_string(),
+                bold: None,
+                italic: None,
+                underline: None,
+                font_size: Some(18),
+                alignment: None,
+            })),
+        }
+    }
+    WordDocumentCreator::new().create(output, config, contents)
+}
+
+fn write_pdf(blocks: Vec<Block>, output: &str) -> Result<()> {
+    let config = PdfDocumentConfig {
+        title: first_heading(&blocks),
+        author: None,
+        subject: None,
+        page_size: None,
+    };
+    let mut contents = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Heading { level, text } => contents.push(PdfContent::Heading {
+                level: level.min(3),
+                text,
+            }),
+            Block::Paragraph { text, bold } => contents.push(PdfContent::Paragraph {
+                text,
+                bold: Some(bold),
+                italic: None,
+                font_size: None,
+                alignment: None,
+            }),
+            Block::List(items) => {
+                for (ordered, run) in list_runs(items) {
+                    let items = run
+                        .into_iter()
+                        .map(|item| format!("{}{}", "    ".repeat(item.depth), item.text))
+                        .collect();
+                    contents.push(if ordered {
+                        PdfContent::NumberedList { items }
+                    } else {
+                        PdfContent::BulletList { items }
+                    });
+                }
+            }
+            Block::Table { headers, rows } => contents.push(PdfContent::Table { headers, rows }),
+            Block::Code(text) => contents.extend(text.lines().map(|line| PdfContent::Paragraph {
+                text: line.to_string(),
+                bold: None,
+                italic: None,
+                font_size: Some(10),
+                alignment: None,
+            })),
+        }
+    }
+    PdfDocumentCreator::new().create(output, config, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn converts_word_structure_to_markdown() {
+        let document = concat!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>"#,
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Quarterly report</w:t></w:r></w:p>"#,
+            r#"<w:p><w:r><w:t xml:space="preserve">Revenue was </w:t></w:r>"#,
+            r#"<w:r><w:rPr><w:b/></w:rPr><w:t>up 12%</w:t></w:r><w:r><w:t>.</w:t></w:r></w:p>"#,
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="2"/></w:numPr></w:pPr><w:r><w:t>Hire</w:t></w:r></w:p>"#,
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="2"/></w:numPr></w:pPr><w:r><w:t>Sales</w:t></w:r></w:p>"#,
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="2"/></w:numPr></w:pPr><w:r><w:t>Expand</w:t></w:r></w:p>"#,
+            r#"<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Region</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Total</w:t></w:r></w:p></w:tc></w:tr>"#,
+            r#"<w:tr><w:tc><w:p><w:r><w:t>EU|UK</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>40</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+            r#"</w:body></w:document>"#
+        );
+        let numbering = concat!(
+            r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+            r#"<w:abstractNum w:abstractNumId="7"><w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl>"#,
+            r#"<w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/></w:lvl></w:abstractNum>"#,
+            r#"<w:num w:numId="2"><w:abstractNumId w:val="7"/></w:num></w:numbering>"#
+        );
+
+        let blocks = word_blocks(document, Some(numbering)).unwrap();
+        assert_eq!(
+            render_markdown(&blocks),
+            "# Quarterly report\n\n\
+             Revenue was **up 12%**.\n\n\
+             1. Hire\n   - Sales\n2. Expand\n\n\
+             | Region | Total |\n| --- | --- |\n| EU\\|UK | 40 |\n"
+        );
+    }
+
+    #[test]
+    fn parses_markdown_blocks() {
+        let markdown = "# Title\n\n**Summary line**\n\nSome *mixed* text\nwrapped.\n\n\
+                        1. One\n   - Nested\n2. Two\n\n\
+                        | A | B |\n|---|---|\n| 1 | 2 |\n\n```\nlet x = 1;\n```\n";
+
+        assert_eq!(
+            parse_markdown(markdown),
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Title".to_string()
+                },
+                Block::Paragraph {
+                    text: "Summary line".to_string(),
+                    bold: true
+                },
+                Block::Paragraph {
+                    text: "Some mixed text wrapped.".to_string(),
+                    bold: false
+                },
+                Block::List(vec![
+                    ListItem {
+                        depth: 0,
+                        ordered: true,
+                        text: "One".to_string()
+                    },
+                    ListItem {
+                        depth: 1,
+                        ordered: false,
+                        text: "Nested".to_string()
+                    },
+                    ListItem {
+                        depth: 0,
+                        ordered: true,
+                        text: "Two".to_string()
+                    },
+                ]),
+                Block::Table {
+                    headers: vec!["A".to_string(), "B".to_string()],
+                    rows: vec![vec!["1".to_string(), "2".to_string()]],
+                },
+                Block::Code("let x = 1;".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn infers_pdf_structure() {
+        let text = "Project Status\n\nThe rollout finished on time and the team is now\n\
+                    focusing on follow-up work.\n\n• First item\ncontinues here\n• Second item\n\n\
+                    Name | Owner\n-----------\nAPI | Sam\n\u{c}Next steps";
+
+        let markdown = render_markdown(&pdf_blocks(text));
+        assert_eq!(
+            markdown,
+            "## Project Status\n\n\
+             The rollout finished on time and the team is now focusing on follow-up work.\n\n\
+             - First item continues here\n- Second item\n\n\
+             | Name | Owner |\n| --- | --- |\n| API | Sam |\n\n\
+             ## Next steps\n"
+        );
+    }
+
+    #[test]
+    fn detects_formats_from_contents() {
+        let dir = TempDir::new().unwrap();
+        let pdf = dir.path().join("download");
+        fs::write(&pdf, b"%PDF-1.7\n").unwrap();
+        let notes = dir.path().join("notes");
+        fs::write(&notes, "# Notes\n").unwrap();
+
+        assert_eq!(
+            ConversionFormat::detect(&pdf).unwrap(),
+            ConversionFormat::Pdf
+        );
+        assert_eq!(
+            ConversionFormat::detect(&notes).unwrap(),
+            ConversionFormat::Markdown
+        );
+        assert!(ConversionFormat::detect(Path::new("legacy.doc")).is_err());
+        assert!(ConversionFormat::for_output(Path::new("out.xlsx")).is_err());
+    }
+
+    #[test]
+    fn converts_markdown_to_pdf_and_back() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("report.md");
+        fs::write(
+            &source,
+            "# Report\n\nAll systems nominal.\n\n- Uptime\n- Latency\n",
+        )
+        .unwrap();
+        let output = dir.path().join("out/report.pdf");
+
+        let conversion = convert(source.to_str().unwrap(), output.to_str().unwrap()).unwrap();
+        assert_eq!(conversion.input_format, ConversionFormat::Markdown);
+        assert_eq!(conversion.output_format, ConversionFormat::Pdf);
+
+        let markdown = to_markdown(output.to_str().unwrap()).unwrap();
+        assert!(markdown.contains("All systems nominal."));
+        assert!(markdown.contains("Uptime"));
+    }
+}
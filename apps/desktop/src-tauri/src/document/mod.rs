@@ -17,6 +17,15 @@ pub mod edit_excel;
 pub mod edit_pdf;
 pub mod edit_word;
 
+// Template rendering
+pub mod template;
+
+// Markdown conversion
+pub mod markdown;
+
+// CSV and Parquet tables
+pub mod tabular;
+
 // Re-exports (reading)
 pub use excel::ExcelHandler;
 pub use pdf::PdfHandler;
@@ -32,6 +41,15 @@ pub use edit_excel::{ExcelEdit, ExcelEditor};
 pub use edit_pdf::{PdfEdit, PdfEditor};
 pub use edit_word::{WordEdit, WordEditor};
 
+// Re-exports (templates)
+pub use template::{DocumentTemplate, TemplateStore};
+
+// Re-exports (conversion)
+pub use markdown::{ConversionFormat, DocumentConversion};
+
+// Re-exports (tabular data)
+pub use tabular::{ColumnSchema, ColumnType, TabularPreview, TabularReadOptions};
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +77,30 @@ pub struct DocumentMetadata {
 pub struct DocumentContent {
     pub text: String,
     pub metadata: DocumentMetadata,
+    /// Per-page text for PDFs, showing which pages were recovered by OCR
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<PageContent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageTextSource {
+    /// Text embedded in the document
+    Embedded,
+    /// Text recognized from the page's scanned image
+    Ocr,
+    /// A scanned page whose image could not be recognized
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageContent {
+    /// 1-based page number
+    pub page: usize,
+    pub text: String,
+    pub source: PageTextSource,
+    /// Mean OCR confidence from 0 to 100, for OCR pages
+    pub confidence: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,12 +1,20 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use lopdf::{Dictionary, Document as LopdfDocument, Object};
+use image::{DynamicImage, GrayImage, RgbImage};
+use lopdf::{Dictionary, Document as LopdfDocument, Object, ObjectId};
 use pdf_extract;
 
-use super::{DocumentContent, DocumentMetadata, DocumentType, SearchResult};
+use super::{
+    DocumentContent, DocumentMetadata, DocumentType, PageContent, PageTextSource, SearchResult,
+};
 use crate::error::{Error, Result};
 
+/// Pages with fewer embedded characters than this are treated as scanned
+const MIN_EMBEDDED_CHARS: usize = 16;
+/// Smaller images are logos or decorations rather than page scans
+const MIN_SCAN_PIXELS: u32 = 250_000;
+
 pub struct PdfHandler;
 
 impl PdfHandler {
@@ -15,22 +23,19 @@ impl PdfHandler {
     }
 
     pub async fn read(&self, file_path: &str) -> Result<DocumentContent> {
-        let text = self.extract_text(file_path).await?;
+        let (text, pages) = read_text(file_path).await?;
         let mut metadata = self.get_metadata(file_path).await?;
         metadata.word_count = Some(text.split_whitespace().count());
 
-        Ok(DocumentContent { text, metadata })
+        Ok(DocumentContent {
+            text,
+            metadata,
+            pages,
+        })
     }
 
     pub async fn extract_text(&self, file_path: &str) -> Result<String> {
-        let path = Path::new(file_path);
-
-        if !path.exists() {
-            return Err(Error::Generic(format!("File not found: {}", file_path)));
-        }
-
-        pdf_extract::extract_text(path)
-            .map_err(|e| Error::Generic(format!("Failed to extract PDF text: {}", e)))
+        read_text(file_path).await.map(|(text, _)| text)
     }
 
     pub async fn get_metadata(&self, file_path: &str) -> Result<DocumentMetadata> {
@@ -146,3 +151,422 @@ fn resolve_info_dict<'a>(
         _ => None,
     }
 }
+
+async fn read_text(file_path: &str) -> Result<(String, Vec<PageContent>)> {
+    let path = PathBuf::from(file_path);
+    tokio::task::spawn_blocking(move || extract_text_with_pages(&path))
+        .await
+        .map_err(|e| Error::Generic(format!("PDF text extraction task failed: {}", e)))?
+}
+
+/// Document text plus per-page text, with scanned pages run through OCR.
+///
+/// Documents without scanned pages keep the full-document extraction, which
+/// handles text flow across pages better than per-page extraction.
+pub(crate) fn extract_text_with_pages(path: &Path) -> Result<(String, Vec<PageContent>)> {
+    if !path.exists() {
+        return Err(Error::Generic(format!(
+            "File not found: {}",
+            path.display()
+        )));
+    }
+
+    let pages = match LopdfDocument::load(path) {
+        Ok(pdf) => read_pages(&pdf),
+        Err(e) => {
+            tracing::debug!("Skipping per-page extraction for {}: {}", path.display(), e);
+            Vec::new()
+        }
+    };
+
+    if pages
+        .iter()
+        .all(|page| page.source == PageTextSource::Embedded)
+    {
+        let text = pdf_extract::extract_text(path)
+            .map_err(|e| Error::Generic(format!("Failed to extract PDF text: {}", e)))?;
+        return Ok((text, pages));
+    }
+
+    let text = pages
+        .iter()
+        .map(|page| page.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok((text, pages))
+}
+
+fn read_pages(pdf: &LopdfDocument) -> Vec<PageContent> {
+    let mut pages = Vec::new();
+    for (number, page_id) in pdf.get_pages() {
+        let embedded = pdf.extract_text(&[number]).unwrap_or_default();
+        let embedded_chars = embedded.chars().filter(|c| !c.is_whitespace()).count();
+        let scans = if embedded_chars < MIN_EMBEDDED_CHARS {
+            page_images(pdf, page_id)
+        } else {
+            Vec::new()
+        };
+
+        if scans.is_empty() {
+            pages.push(PageContent {
+                page: number as usize,
+                text: embedded,
+                source: PageTextSource::Embedded,
+                confidence: None,
+            });
+            continue;
+        }
+
+        let mut texts = Vec::new();
+        let mut confidences = Vec::new();
+        let mut failure = None;
+        for scan in scans {
+            match scan.and_then(|image| recognize(&image)) {
+                Ok((text, confidence)) => {
+                    texts.push(text);
+                    confidences.push(confidence);
+                }
+                Err(e) => failure = Some(e),
+            }
+        }
+
+        if texts.is_empty() {
+            tracing::warn!(
+                "Could not read scanned PDF page {}: {}",
+                number,
+                failure.unwrap_or_default()
+            );
+            pages.push(PageContent {
+                page: number as usize,
+                text: embedded,
+                source: PageTextSource::Unreadable,
+                confidence: None,
+            });
+        } else {
+            pages.push(PageContent {
+                page: number as usize,
+                text: texts.join("\n"),
+                source: PageTextSource::Ocr,
+                confidence: Some(confidences.iter().sum::<f32>() / confidences.len() as f32),
+            });
+        }
+    }
+    pages
+}
+
+/// Images on a page large enough to be a scan, decoded where the encoding is
+/// supported
+fn page_images(
+    pdf: &LopdfDocument,
+    page_id: ObjectId,
+) -> Vec<std::result::Result<DynamicImage, String>> {
+    let (resources, resource_ids) = pdf.get_page_resources(page_id);
+    let dictionaries = resources.into_iter().chain(
+        resource_ids
+            .into_iter()
+            .filter_map(|id| pdf.get_object(id).ok().and_then(|o| o.as_dict().ok())),
+    );
+
+    let mut images = Vec::new();
+    for resources in dictionaries {
+        let Some(xobjects) = resources
+            .get(b"XObject")
+            .ok()
+            .and_then(|o| resolve_dict(pdf, o))
+        else {
+            continue;
+        };
+        for (_, object) in xobjects.iter() {
+            let Some(stream) = pdf
+                .dereference(object)
+                .ok()
+                .and_then(|(_, o)| o.as_stream().ok())
+            else {
+                continue;
+            };
+            if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Image") {
+                continue;
+            }
+            let width = dict_u32(&stream.dict, b"Width");
+            let height = dict_u32(&stream.dict, b"Height");
+            if width.saturating_mul(height) < MIN_SCAN_PIXELS {
+                continue;
+            }
+            images.push(decode_image(pdf, stream, width, height));
+        }
+    }
+    images
+}
+
+fn resolve_dict<'a>(pdf: &'a LopdfDocument, object: &'a Object) -> Option<&'a Dictionary> {
+    pdf.dereference(object)
+        .ok()
+        .and_then(|(_, o)| o.as_dict().ok())
+}
+
+fn dict_u32(dict: &Dictionary, key: &[u8]) -> u32 {
+    dict.get(key)
+        .and_then(Object::as_i64)
+        .map_or(0, |value| value.max(0) as u32)
+}
+
+fn decode_image(
+    pdf: &LopdfDocument,
+    stream: &lopdf::Stream,
+    width: u32,
+    height: u32,
+) -> std::result::Result<DynamicImage, String> {
+    let filters: Vec<Vec<u8>> = match stream.dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.clone()],
+        Ok(Object::Array(names)) => names
+            .iter()
+            .filter_map(|n| n.as_name().ok().map(<[u8]>::to_vec))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    // JPEG data is a complete image file
+    if filters.last().map(Vec::as_slice) == Some(b"DCTDecode") {
+        return image::load_from_memory(&stream.content)
+            .map_err(|e| format!("Invalid JPEG image: {}", e));
+    }
+    if let Some(filter) = filters
+        .iter()
+        .find(|f| !matches!(f.as_slice(), b"FlateDecode" | b"LZWDecode"))
+    {
+        return Err(format!(
+            "{} images are not supported",
+            String::from_utf8_lossy(filter)
+        ));
+    }
+
+    let data = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        stream
+            .decompressed_content()
+            .map_err(|e| format!("Failed to decompress image: {}", e))?
+    };
+    let color_space = stream
+        .dict
+        .get(b"ColorSpace")
+        .ok()
+        .and_then(|o| pdf.dereference(o).ok())
+        .map(|(_, o)| o);
+    let channels = match color_space {
+        Some(Object::Name(name)) if name == b"DeviceRGB" => 3,
+        Some(Object::Name(name)) if name == b"DeviceGray" => 1,
+        // Scanners commonly tag images with an ICC profile naming the channel count
+        Some(Object::Array(items))
+            if items.first().and_then(|o| o.as_name().ok()) == Some(b"ICCBased") =>
+        {
+            items
+                .get(1)
+                .and_then(|o| pdf.dereference(o).ok())
+                .and_then(|(_, o)| o.as_stream().ok())
+                .map_or(0, |profile| dict_u32(&profile.dict, b"N"))
+        }
+        None if stream
+            .dict
+            .get(b"ImageMask")
+            .and_then(Object::as_bool)
+            .unwrap_or(false) =>
+        {
+            1
+        }
+        _ => 0,
+    };
+    if !matches!(channels, 1 | 3) {
+        return Err("Unsupported image color space".to_string());
+    }
+    // Image masks omit the depth, which is always one bit
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(Object::as_i64)
+        .unwrap_or(1);
+    raw_image(&data, width, height, channels, bits)
+        .ok_or_else(|| "Image data does not match its dimensions".to_string())
+}
+
+/// Build an image from uncompressed 8-bit gray/RGB or 1-bit gray samples
+fn raw_image(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    channels: u32,
+    bits: i64,
+) -> Option<DynamicImage> {
+    match (channels, bits) {
+        (3, 8) => {
+            let len = (width * height * 3) as usize;
+            RgbImage::from_raw(width, height, data.get(..len)?.to_vec())
+                .map(DynamicImage::ImageRgb8)
+        }
+        (1, 8) => {
+            let len = (width * height) as usize;
+            GrayImage::from_raw(width, height, data.get(..len)?.to_vec())
+                .map(DynamicImage::ImageLuma8)
+        }
+        (1, 1) => {
+            // Rows are padded to whole bytes; a set bit is white
+            let row_bytes = width.div_ceil(8) as usize;
+            if data.len() < row_bytes * height as usize {
+                return None;
+            }
+            let image = GrayImage::from_fn(width, height, |x, y| {
+                let byte = data[y as usize * row_bytes + x as usize / 8];
+                let bit = (byte >> (7 - x % 8)) & 1;
+                image::Luma([if bit == 1 { 255 } else { 0 }])
+            });
+            Some(DynamicImage::ImageLuma8(image))
+        }
+        _ => None,
+    }
+}
+
+/// Recognize a page image through the OCR pipeline, returning its text and
+/// mean confidence
+#[cfg(feature = "ocr")]
+fn recognize(image: &DynamicImage) -> std::result::Result<(String, f32), String> {
+    let path = std::env::temp_dir().join(format!("pdf_page_{}.png", uuid::Uuid::new_v4()));
+    image
+        .save(&path)
+        .map_err(|e| format!("Failed to save page image: {}", e))?;
+    let result = crate::commands::ocr::recognize_image(&path.to_string_lossy(), None, true);
+    let _ = fs::remove_file(&path);
+    result.map(|result| (result.text, result.confidence))
+}
+
+#[cfg(not(feature = "ocr"))]
+fn recognize(_image: &DynamicImage) -> std::result::Result<(String, f32), String> {
+    Err("OCR feature not enabled. Please rebuild with --features ocr".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Stream};
+    use tempfile::TempDir;
+
+    /// A PDF whose first page is a bare 8-bit gray scan and second page holds text
+    fn scanned_pdf(path: &Path) {
+        let mut doc = LopdfDocument::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let scan_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 600,
+                "Height" => 500,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![200; 600 * 500],
+        ));
+
+        let scan_content = Content {
+            operations: vec![
+                Operation::new("q", vec![]),
+                Operation::new(
+                    "cm",
+                    vec![
+                        600.into(),
+                        0.into(),
+                        0.into(),
+                        500.into(),
+                        0.into(),
+                        0.into(),
+                    ],
+                ),
+                Operation::new("Do", vec!["Im0".into()]),
+                Operation::new("Q", vec![]),
+            ],
+        };
+        let text_content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new("Td", vec![72.into(), 700.into()]),
+                Operation::new(
+                    "Tj",
+                    vec![Object::string_literal("Embedded invoice total 42")],
+                ),
+                Operation::new("ET", vec![]),
+            ],
+        };
+
+        let mut kids = Vec::new();
+        for content in [scan_content, text_content] {
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            kids.push(
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "Resources" => dictionary! {
+                        "Font" => dictionary! { "F1" => font_id },
+                        "XObject" => dictionary! { "Im0" => scan_id },
+                    },
+                    "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                })
+                .into(),
+            );
+        }
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => 2,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn detects_scanned_pages() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scan.pdf");
+        scanned_pdf(&path);
+
+        let pdf = LopdfDocument::load(&path).unwrap();
+        let pages = pdf.get_pages();
+        let scans = page_images(&pdf, pages[&1]);
+        assert_eq!(scans.len(), 1);
+        let scan = scans.into_iter().next().unwrap().unwrap();
+        assert_eq!((scan.width(), scan.height()), (600, 500));
+        // The text page shares the scan's resources but draws text only
+        assert_eq!(read_pages(&pdf)[1].source, PageTextSource::Embedded);
+
+        let (text, pages) = extract_text_with_pages(&path).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert!(text.contains("Embedded invoice total 42"));
+        if cfg!(not(feature = "ocr")) {
+            assert_eq!(pages[0].source, PageTextSource::Unreadable);
+            assert_eq!(pages[0].confidence, None);
+        }
+    }
+
+    #[test]
+    fn decodes_one_bit_images() {
+        // 10 pixels wide: rows padded to two bytes, first pixel black then white
+        let data = [0b0111_1111, 0b1100_0000, 0b0111_1111, 0b1100_0000];
+        let image = raw_image(&data, 10, 2, 1, 1).unwrap().to_luma8();
+        assert_eq!(image.get_pixel(0, 0).0, [0]);
+        assert_eq!(image.get_pixel(9, 1).0, [255]);
+        assert!(raw_image(&data[..3], 10, 2, 1, 1).is_none());
+    }
+}
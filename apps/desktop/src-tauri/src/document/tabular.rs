@@ -0,0 +1,585 @@
+//! CSV and Parquet readers producing a typed table
+//!
+//! CSV exports from other systems vary wildly, so the delimiter, text encoding
+//! and header row are sniffed unless the caller pins them down. Column types
+//! are inferred from the values (CSV) or taken from the file schema (Parquet).
+
+use std::fs::File;
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use parquet::basic::{ConvertedType, LogicalType, Repetition, Type as PhysicalType};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use parquet::schema::types::Type as SchemaType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::error::{Error, Result};
+
+/// Delimiters tried, in order of preference, when sniffing a CSV file
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+/// Lines inspected when sniffing the delimiter
+const SNIFF_LINES: usize = 20;
+/// Rows returned in a preview unless the caller asks for more
+const DEFAULT_PREVIEW_ROWS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TabularFormat {
+    Csv,
+    Parquet,
+}
+
+impl TabularFormat {
+    pub fn detect(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "csv" | "tsv" | "txt" => Ok(TabularFormat::Csv),
+            "parquet" | "pq" => Ok(TabularFormat::Parquet),
+            other => Err(Error::Generic(format!(
+                "Unsupported tabular file type: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Boolean,
+    /// ISO `YYYY-MM-DD`
+    Date,
+    Timestamp,
+    Text,
+}
+
+impl ColumnType {
+    /// SQLite column affinity used when importing
+    pub fn sqlite_type(self) -> &'static str {
+        match self {
+            ColumnType::Integer | ColumnType::Boolean => "INTEGER",
+            ColumnType::Real => "REAL",
+            ColumnType::Date | ColumnType::Timestamp | ColumnType::Text => "TEXT",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+/// How a CSV file was parsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvDialect {
+    pub delimiter: char,
+    /// WHATWG encoding label, e.g. `UTF-8` or `windows-1252`
+    pub encoding: String,
+    pub has_header: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TabularReadOptions {
+    /// Override the sniffed CSV delimiter
+    pub delimiter: Option<char>,
+    /// Override the sniffed CSV encoding (any WHATWG label)
+    pub encoding: Option<String>,
+    /// Override header detection for CSV files
+    pub has_header: Option<bool>,
+    /// Number of rows to include in a preview
+    pub preview_rows: Option<usize>,
+}
+
+/// A fully read table with typed values
+#[derive(Debug, Clone)]
+pub struct TabularData {
+    pub format: TabularFormat,
+    pub columns: Vec<ColumnSchema>,
+    pub rows: Vec<Vec<JsonValue>>,
+    pub dialect: Option<CsvDialect>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabularPreview {
+    pub format: TabularFormat,
+    pub columns: Vec<ColumnSchema>,
+    pub rows: Vec<Vec<JsonValue>>,
+    pub total_rows: usize,
+    pub dialect: Option<CsvDialect>,
+}
+
+/// Read a CSV or Parquet file, returning the first rows and the inferred schema
+pub fn preview(path: &Path, options: &TabularReadOptions) -> Result<TabularPreview> {
+    let data = read_table(path, options)?;
+    let total_rows = data.rows.len();
+    let mut rows = data.rows;
+    rows.truncate(options.preview_rows.unwrap_or(DEFAULT_PREVIEW_ROWS));
+
+    Ok(TabularPreview {
+        format: data.format,
+        columns: data.columns,
+        rows,
+        total_rows,
+        dialect: data.dialect,
+    })
+}
+
+/// Read every row of a CSV or Parquet file
+pub fn read_table(path: &Path, options: &TabularReadOptions) -> Result<TabularData> {
+    match TabularFormat::detect(path)? {
+        TabularFormat::Csv => {
+            let bytes = std::fs::read(path)
+                .map_err(|e| Error::Generic(format!("Failed to read CSV file: {}", e)))?;
+            read_csv(&bytes, options)
+        }
+        TabularFormat::Parquet => read_parquet(path),
+    }
+}
+
+fn read_csv(bytes: &[u8], options: &TabularReadOptions) -> Result<TabularData> {
+    let (text, encoding) = decode(bytes, options.encoding.as_deref())?;
+
+    let delimiter = match options.delimiter {
+        Some(delimiter) if delimiter.is_ascii() => delimiter as u8,
+        Some(delimiter) => {
+            return Err(Error::Generic(format!(
+                "CSV delimiter must be a single ASCII character, got {:?}",
+                delimiter
+            )))
+        }
+        None => sniff_delimiter(&text),
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut records: Vec<Vec<String>> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| Error::Generic(format!("Invalid CSV: {}", e)))?;
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        records.push(
+            record
+                .iter()
+                .map(|field| field.trim().to_string())
+                .collect(),
+        );
+    }
+
+    let has_header = options.has_header.unwrap_or_else(|| sniff_header(&records));
+    let width = records.iter().map(Vec::len).max().unwrap_or(0);
+    let header = if has_header && !records.is_empty() {
+        records.remove(0)
+    } else {
+        Vec::new()
+    };
+    let names = column_names(&header, width);
+
+    let columns: Vec<ColumnSchema> = (0..width)
+        .map(|index| ColumnSchema {
+            name: names[index].clone(),
+            column_type: infer_type(column_values(&records, index)),
+            nullable: column_values(&records, index).any(str::is_empty),
+        })
+        .collect();
+
+    let rows = records
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| {
+                    typed_value(
+                        row.get(index).map(String::as_str).unwrap_or(""),
+                        column.column_type,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(TabularData {
+        format: TabularFormat::Csv,
+        columns,
+        rows,
+        dialect: Some(CsvDialect {
+            delimiter: delimiter as char,
+            encoding: encoding.name().to_string(),
+            has_header,
+        }),
+    })
+}
+
+/// Decode CSV bytes: an explicit label wins, then a BOM, then UTF-8 if the
+/// bytes are valid, otherwise Windows-1252 (the usual legacy spreadsheet export)
+fn decode(bytes: &[u8], label: Option<&str>) -> Result<(String, &'static Encoding)> {
+    let encoding = match label {
+        Some(label) => Encoding::for_label(label.trim().as_bytes())
+            .ok_or_else(|| Error::Generic(format!("Unknown text encoding: {}", label)))?,
+        None => match Encoding::for_bom(bytes) {
+            Some((encoding, _)) => encoding,
+            None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+            None => WINDOWS_1252,
+        },
+    };
+
+    let (text, encoding, _) = encoding.decode(bytes);
+    Ok((text.into_owned(), encoding))
+}
+
+/// Pick the candidate that splits the first lines into the same, largest
+/// number of fields
+fn sniff_delimiter(text: &str) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+
+    let mut best = (b',', 0usize, 0usize);
+    for candidate in DELIMITER_CANDIDATES {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_unquoted(line, candidate))
+            .collect();
+        let Some(&first) = counts.first() else {
+            continue;
+        };
+        if first == 0 {
+            continue;
+        }
+        let consistent = counts.iter().filter(|&&count| count == first).count();
+        if (consistent, first) > (best.1, best.2) {
+            best = (candidate, consistent, first);
+        }
+    }
+    best.0
+}
+
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for byte in line.bytes() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// The first row is a header when it turns a typed column into text; it is
+/// data when some non-text column still fits once the first row is included
+fn sniff_header(records: &[Vec<String>]) -> bool {
+    let Some((first, rest)) = records.split_first() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return true;
+    }
+
+    let mut looks_like_data = false;
+    for (index, value) in first.iter().enumerate() {
+        let body = infer_type(column_values(rest, index));
+        if value.is_empty() || body == ColumnType::Text {
+            continue;
+        }
+        if infer_type(column_values(records, index)) == ColumnType::Text {
+            return true;
+        }
+        looks_like_data = true;
+    }
+    !looks_like_data
+}
+
+/// Values of one column, with missing trailing fields read as empty
+fn column_values(rows: &[Vec<String>], index: usize) -> impl Iterator<Item = &str> {
+    rows.iter()
+        .map(move |row| row.get(index).map(String::as_str).unwrap_or(""))
+}
+
+fn column_names(header: &[String], width: usize) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(width);
+    for index in 0..width {
+        let base = header
+            .get(index)
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("column_{}", index + 1));
+
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(&name))
+        {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Narrowest type that fits every non-empty value
+fn infer_type<'a>(values: impl Iterator<Item = &'a str>) -> ColumnType {
+    let mut candidates = [
+        ColumnType::Integer,
+        ColumnType::Real,
+        ColumnType::Boolean,
+        ColumnType::Date,
+        ColumnType::Timestamp,
+    ]
+    .to_vec();
+    let mut seen = false;
+
+    for value in values.filter(|value| !value.is_empty()) {
+        seen = true;
+        candidates.retain(|candidate| typed_value(value, *candidate) != JsonValue::Null);
+        if candidates.is_empty() {
+            return ColumnType::Text;
+        }
+    }
+
+    if seen {
+        candidates[0]
+    } else {
+        ColumnType::Text
+    }
+}
+
+/// Convert a raw CSV field; `Null` when the field is empty or does not fit
+fn typed_value(value: &str, column_type: ColumnType) -> JsonValue {
+    if value.is_empty() {
+        return JsonValue::Null;
+    }
+
+    match column_type {
+        ColumnType::Integer => value
+            .parse::<i64>()
+            .map(JsonValue::from)
+            .unwrap_or(JsonValue::Null),
+        ColumnType::Real => value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .and_then(serde_json::Number::from_f64)
+            .map(JsonValue::Number)
+            .unwrap_or(JsonValue::Null),
+        ColumnType::Boolean => match value.to_ascii_lowercase().as_str() {
+            "true" => JsonValue::Bool(true),
+            "false" => JsonValue::Bool(false),
+            _ => JsonValue::Null,
+        },
+        ColumnType::Date => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(|date| JsonValue::String(date.format("%Y-%m-%d").to_string()))
+            .unwrap_or(JsonValue::Null),
+        ColumnType::Timestamp => chrono::DateTime::parse_from_rfc3339(value)
+            .map(|timestamp| timestamp.naive_utc())
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+            .map(|timestamp| JsonValue::String(timestamp.format("%Y-%m-%d %H:%M:%S").to_string()))
+            .unwrap_or(JsonValue::Null),
+        ColumnType::Text => JsonValue::String(value.to_string()),
+    }
+}
+
+fn read_parquet(path: &Path) -> Result<TabularData> {
+    let file = File::open(path)
+        .map_err(|e| Error::Generic(format!("Failed to open Parquet file: {}", e)))?;
+    let reader = SerializedFileReader::new(file)
+        .map_err(|e| Error::Generic(format!("Invalid Parquet file: {}", e)))?;
+
+    let columns: Vec<ColumnSchema> = reader
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .root_schema()
+        .get_fields()
+        .iter()
+        .map(|field| parquet_column(field))
+        .collect();
+
+    let mut rows = Vec::new();
+    for row in reader
+        .get_row_iter(None)
+        .map_err(|e| Error::Generic(format!("Failed to read Parquet rows: {}", e)))?
+    {
+        let row = row.map_err(|e| Error::Generic(format!("Failed to read Parquet row: {}", e)))?;
+        rows.push(
+            row.get_column_iter()
+                .zip(&columns)
+                .map(|((_, field), column)| parquet_value(field, column.column_type))
+                .collect(),
+        );
+    }
+
+    Ok(TabularData {
+        format: TabularFormat::Parquet,
+        columns,
+        rows,
+        dialect: None,
+    })
+}
+
+fn parquet_column(field: &SchemaType) -> ColumnSchema {
+    let info = field.get_basic_info();
+    let column_type = if field.is_primitive() {
+        match (
+            field.get_physical_type(),
+            info.logical_type(),
+            info.converted_type(),
+        ) {
+            (_, Some(LogicalType::Date), _) | (_, _, ConvertedType::DATE) => ColumnType::Date,
+            (_, Some(LogicalType::Timestamp { .. }), _)
+            | (_, _, ConvertedType::TIMESTAMP_MILLIS | ConvertedType::TIMESTAMP_MICROS)
+            | (PhysicalType::INT96, _, _) => ColumnType::Timestamp,
+            (_, Some(LogicalType::Decimal { .. }), _) | (_, _, ConvertedType::DECIMAL) => {
+                ColumnType::Real
+            }
+            (PhysicalType::BOOLEAN, _, _) => ColumnType::Boolean,
+            (PhysicalType::INT32 | PhysicalType::INT64, _, _) => ColumnType::Integer,
+            (PhysicalType::FLOAT | PhysicalType::DOUBLE, _, _) => ColumnType::Real,
+            _ => ColumnType::Text,
+        }
+    } else {
+        // Nested groups, lists and maps are kept as JSON text
+        ColumnType::Text
+    };
+
+    ColumnSchema {
+        name: info.name().to_string(),
+        column_type,
+        nullable: !info.has_repetition() || info.repetition() != Repetition::REQUIRED,
+    }
+}
+
+fn parquet_value(field: &Field, column_type: ColumnType) -> JsonValue {
+    let value = field.to_json_value();
+    match (field, column_type) {
+        (Field::Null, _) => JsonValue::Null,
+        (Field::Decimal(_), ColumnType::Real) => value
+            .as_str()
+            .map(|decimal| typed_value(decimal, ColumnType::Real))
+            .unwrap_or(JsonValue::Null),
+        (Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_), _) => {
+            JsonValue::String(value.to_string())
+        }
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn sniffs_semicolon_latin1_exports() {
+        // "Müller" in Windows-1252, with a decimal comma column quoted
+        let mut bytes = b"id;name;amount;active;joined\n".to_vec();
+        bytes.extend_from_slice(b"1;M\xfcller;\"12,5\";true;2024-01-31\n");
+        bytes.extend_from_slice(b"2;Smith;;false;2024-02-01\n");
+
+        let data = read_csv(&bytes, &TabularReadOptions::default()).unwrap();
+        let dialect = data.dialect.unwrap();
+        assert_eq!(dialect.delimiter, ';');
+        assert_eq!(dialect.encoding, "windows-1252");
+        assert!(dialect.has_header);
+
+        let types: Vec<ColumnType> = data.columns.iter().map(|c| c.column_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                ColumnType::Integer,
+                ColumnType::Text,
+                ColumnType::Text,
+                ColumnType::Boolean,
+                ColumnType::Date
+            ]
+        );
+        assert!(data.columns[2].nullable);
+        assert!(!data.columns[0].nullable);
+        assert_eq!(data.rows[0][1], JsonValue::from("Müller"));
+        assert_eq!(data.rows[1][2], JsonValue::Null);
+    }
+
+    #[test]
+    fn detects_headerless_files() {
+        let bytes = "1\t2.5\tx\n2\t3\ty\n".as_bytes();
+        let data = read_csv(bytes, &TabularReadOptions::default()).unwrap();
+        let dialect = data.dialect.unwrap();
+        assert_eq!(dialect.delimiter, '\t');
+        assert!(!dialect.has_header);
+        assert_eq!(data.columns[0].name, "column_1");
+        assert_eq!(data.columns[1].column_type, ColumnType::Real);
+        assert_eq!(data.rows.len(), 2);
+    }
+
+    #[test]
+    fn reads_parquet_schema_and_rows() {
+        use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let schema = Arc::new(
+            parse_message_type(
+                "message export { REQUIRED INT64 id; OPTIONAL BYTE_ARRAY name (UTF8); }",
+            )
+            .unwrap(),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.parquet");
+
+        let file = File::create(&path).unwrap();
+        let mut writer =
+            SerializedFileWriter::new(file, schema, Arc::new(WriterProperties::builder().build()))
+                .unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[1, 2], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("Ada")], Some(&[1, 0]), None)
+            .unwrap();
+        column.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let preview = preview(&path, &TabularReadOptions::default()).unwrap();
+        assert_eq!(preview.format, TabularFormat::Parquet);
+        assert_eq!(preview.total_rows, 2);
+        assert_eq!(preview.columns[0].column_type, ColumnType::Integer);
+        assert!(!preview.columns[0].nullable);
+        assert_eq!(preview.columns[1].column_type, ColumnType::Text);
+        assert!(preview.columns[1].nullable);
+        assert_eq!(
+            preview.rows[0],
+            vec![JsonValue::from(1), JsonValue::from("Ada")]
+        );
+        assert_eq!(preview.rows[1], vec![JsonValue::from(2), JsonValue::Null]);
+    }
+}
@@ -0,0 +1,1096 @@
+//! Document templates with `{{placeholder}}` substitution.
+//!
+//! Templates are ordinary .docx or .xlsx files containing Handlebars-style tags:
+//!
+//! - `{{customer.name}}` inserts a value looked up in the JSON data
+//! - `{{#each items}} ... {{/each}}` repeats a section per array element, with
+//!   `{{this}}`, `{{@index}}` and the element's fields in scope
+//! - `{{#if paid}} ... {{else}} ... {{/if}}` and `{{#unless paid}} ... {{/unless}}`
+//!   keep a section based on the value's truthiness
+//!
+//! In Word templates a loop whose open and close tags sit in the same table row
+//! repeats the whole row, and a paragraph holding nothing but a block tag is
+//! dropped from the output. In Excel templates a row containing `{{#each}}` and
+//! `{{/each}}` is repeated once per element.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use roxmltree::Document as XmlDocument;
+use rust_xlsxwriter::Workbook;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::read::ZipArchive;
+use zip::write::{FileOptions, ZipWriter};
+
+use super::{
+    DocumentManager, DocumentType, ExcelCell, ExcelDocumentConfig, ExcelDocumentCreator,
+    ExcelSheet, PdfContent, PdfDocumentConfig, PdfDocumentCreator, WordContent, WordDocumentConfig,
+    WordDocumentCreator,
+};
+use crate::error::{Error, Result};
+
+const WORD_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([^{}]*?)\s*\}\}").unwrap());
+static PARAGRAPH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<w:p[ >].*?</w:p>").unwrap());
+static TABLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<w:tbl>.*?</w:tbl>").unwrap());
+static ROW_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<w:tr[ >].*?</w:tr>").unwrap());
+static TEXT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<w:t(?:\s[^>]*[^>/])?>(.*?)</w:t>|<w:t(?:\s[^>]*)?/>").unwrap());
+static EACH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*#each\s+([^{}]*?)\s*\}\}").unwrap());
+static END_EACH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*/each\s*\}\}").unwrap());
+
+// ====================
+// Template language
+// ====================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var(String),
+    Each(String, Vec<Node>),
+    If {
+        path: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+enum Block {
+    Each(String),
+    If(String, bool),
+}
+
+struct Frame<'a> {
+    value: &'a Value,
+    index: Option<usize>,
+}
+
+fn parse(source: &str) -> Result<Vec<Node>> {
+    // Each open block keeps its kind, the nodes collected so far and, for
+    // conditionals that reached `{{else}}`, the nodes of the `then` branch.
+    let mut stack: Vec<(Block, Vec<Node>, Option<Vec<Node>>)> = Vec::new();
+    let mut current: Vec<Node> = Vec::new();
+    let mut last = 0;
+
+    for caps in TAG_RE.captures_iter(source) {
+        let whole = caps.get(0).unwrap();
+        if whole.start() > last {
+            current.push(Node::Text(source[last..whole.start()].to_string()));
+        }
+        last = whole.end();
+
+        let tag = caps[1].trim();
+        if let Some(path) = tag.strip_prefix("#each ") {
+            stack.push((Block::Each(path.trim().to_string()), current, None));
+            current = Vec::new();
+        } else if let Some(path) = tag.strip_prefix("#if ") {
+            stack.push((Block::If(path.trim().to_string(), false), current, None));
+            current = Vec::new();
+        } else if let Some(path) = tag.strip_prefix("#unless ") {
+            stack.push((Block::If(path.trim().to_string(), true), current, None));
+            current = Vec::new();
+        } else if tag == "else" {
+            match stack.last_mut() {
+                Some((Block::If(..), _, then @ None)) => {
+                    *then = Some(std::mem::take(&mut current));
+                }
+                _ => return Err(template_error("{{else}} outside of {{#if}}")),
+            }
+        } else if let Some(name) = tag.strip_prefix('/') {
+            let (block, parent, then) = stack
+                .pop()
+                .ok_or_else(|| template_error(&format!("Unexpected {{{{/{}}}}}", name)))?;
+            let body = std::mem::replace(&mut current, parent);
+            let node = match (block, name.trim()) {
+                (Block::Each(path), "each") => Node::Each(path, body),
+                (Block::If(path, negate), "if" | "unless") => {
+                    let (then, otherwise) = match then {
+                        Some(then) => (then, body),
+                        None => (body, Vec::new()),
+                    };
+                    Node::If {
+                        path,
+                        negate,
+                        then,
+                        otherwise,
+                    }
+                }
+                (_, name) => {
+                    return Err(template_error(&format!("Mismatched {{{{/{}}}}}", name)));
+                }
+            };
+            current.push(node);
+        } else if tag.starts_with('#') {
+            return Err(template_error(&format!(
+                "Unknown block tag {{{{{}}}}}",
+                tag
+            )));
+        } else {
+            current.push(Node::Var(tag.to_string()));
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(template_error("Unclosed block tag"));
+    }
+    if last < source.len() {
+        current.push(Node::Text(source[last..].to_string()));
+    }
+    Ok(current)
+}
+
+fn render_nodes<'a>(
+    nodes: &[Node],
+    frames: &mut Vec<Frame<'a>>,
+    escape: fn(&str) -> String,
+) -> String {
+    let mut output = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Var(path) => {
+                if let Some(value) = lookup(frames, path) {
+                    output.push_str(&escape(&value_to_string(&value)));
+                }
+            }
+            Node::Each(path, body) => {
+                let Some(Cow::Borrowed(Value::Array(items))) = lookup(frames, path) else {
+                    continue;
+                };
+                for (index, item) in items.iter().enumerate() {
+                    frames.push(Frame {
+                        value: item,
+                        index: Some(index),
+                    });
+                    output.push_str(&render_nodes(body, frames, escape));
+                    frames.pop();
+                }
+            }
+            Node::If {
+                path,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let truthy = lookup(frames, path).is_some_and(|value| is_truthy(&value));
+                let branch = if truthy != *negate { then } else { otherwise };
+                output.push_str(&render_nodes(branch, frames, escape));
+            }
+        }
+    }
+    output
+}
+
+/// Resolve a dotted path against the innermost frame that has its first segment
+fn lookup<'a>(frames: &[Frame<'a>], path: &str) -> Option<Cow<'a, Value>> {
+    let frame = frames.last()?;
+    if path == "@index" {
+        return frame.index.map(|index| Cow::Owned(Value::from(index)));
+    }
+
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = if first == "this" {
+        frame.value
+    } else {
+        frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.value.get(first))?
+    };
+    for segment in segments {
+        value = match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => value.get(segment)?,
+        };
+    }
+    Some(Cow::Borrowed(value))
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn template_error(message: &str) -> Error {
+    Error::Generic(format!("Invalid template: {}", message))
+}
+
+fn no_escape(text: &str) -> String {
+    text.to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render a plain-text template against JSON data
+pub fn render_text(template: &str, data: &Value) -> Result<String> {
+    let nodes = parse(template)?;
+    Ok(render_nodes(
+        &nodes,
+        &mut vec![Frame {
+            value: data,
+            index: None,
+        }],
+        no_escape,
+    ))
+}
+
+/// Paths referenced by a template's tags, excluding `this` and `@index`
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    for caps in TAG_RE.captures_iter(template) {
+        let tag = caps[1].trim();
+        let path = ["#each ", "#if ", "#unless "]
+            .iter()
+            .find_map(|prefix| tag.strip_prefix(prefix))
+            .unwrap_or(tag)
+            .trim();
+        if path.is_empty()
+            || path == "else"
+            || path.starts_with(['/', '#', '@'])
+            || path.starts_with("this")
+        {
+            continue;
+        }
+        names.insert(path.to_string());
+    }
+    names.into_iter().collect()
+}
+
+// ====================
+// Word templates
+// ====================
+
+/// Merge the text of runs in paragraphs that contain tags.
+///
+/// Word splits text into runs at arbitrary points (spell checking, edits), so
+/// a `{{placeholder}}` typed by the user can span several `<w:t>` elements.
+/// The merged text takes the formatting of the paragraph's first run.
+fn merge_tag_runs(xml: &str) -> String {
+    PARAGRAPH_RE
+        .replace_all(xml, |caps: &Captures| {
+            let paragraph = &caps[0];
+            let text: String = TEXT_RE
+                .captures_iter(paragraph)
+                .filter_map(|t| t.get(1).map(|m| m.as_str().to_string()))
+                .collect();
+            if !text.contains("{{") {
+                return paragraph.to_string();
+            }
+
+            let mut first = true;
+            TEXT_RE
+                .replace_all(paragraph, |_: &Captures| {
+                    if std::mem::take(&mut first) {
+                        format!("<w:t xml:space=\"preserve\">{}</w:t>", text)
+                    } else {
+                        "<w:t></w:t>".to_string()
+                    }
+                })
+                .into_owned()
+        })
+        .into_owned()
+}
+
+/// Move loop tags out of table rows and block-only paragraphs so that
+/// sections repeat or disappear as whole XML elements
+fn hoist_block_tags(xml: &str) -> String {
+    let rows = ROW_RE.replace_all(xml, |caps: &Captures| {
+        let row = &caps[0];
+        match (EACH_RE.find(row), END_EACH_RE.find(row)) {
+            (Some(open), Some(close)) if open.start() < close.start() => {
+                let tag = open.as_str().to_string();
+                let row = END_EACH_RE
+                    .replace(&EACH_RE.replace(row, ""), "")
+                    .into_owned();
+                format!("{}{}{{{{/each}}}}", tag, row)
+            }
+            _ => row.to_string(),
+        }
+    });
+
+    // Table cells must keep at least one paragraph, so only body paragraphs are dropped
+    let tables: Vec<(usize, usize)> = TABLE_RE
+        .find_iter(&rows)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+
+    PARAGRAPH_RE
+        .replace_all(&rows, |caps: &Captures| {
+            let paragraph = &caps[0];
+            let start = caps.get(0).unwrap().start();
+            if tables
+                .iter()
+                .any(|(from, to)| (*from..*to).contains(&start))
+            {
+                return paragraph.to_string();
+            }
+            let text: String = TEXT_RE
+                .captures_iter(paragraph)
+                .filter_map(|t| t.get(1).map(|m| m.as_str().to_string()))
+                .collect();
+            let text = text.trim();
+            let block_only = TAG_RE.find(text).is_some_and(|tag| {
+                tag.start() == 0
+                    && tag.end() == text.len()
+                    && TAG_RE.captures(text).is_some_and(|c| {
+                        let inner = c[1].trim();
+                        inner.starts_with(['#', '/']) || inner == "else"
+                    })
+            });
+            if block_only {
+                text.to_string()
+            } else {
+                paragraph.to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Render one WordprocessingML part (document body, header or footer)
+fn render_word_xml(xml: &str, data: &Value) -> Result<String> {
+    let prepared = hoist_block_tags(&merge_tag_runs(xml));
+    let nodes = parse(&prepared)?;
+    let rendered = render_nodes(
+        &nodes,
+        &mut vec![Frame {
+            value: data,
+            index: None,
+        }],
+        xml_escape,
+    );
+
+    XmlDocument::parse(&rendered).map_err(|e| {
+        Error::Generic(format!(
+            "Template produced invalid XML ({}); block tags must open and close in the same paragraph, table row or body level",
+            e
+        ))
+    })?;
+    Ok(rendered)
+}
+
+fn is_word_content_part(name: &str) -> bool {
+    name == "word/document.xml"
+        || ((name.starts_with("word/header") || name.starts_with("word/footer"))
+            && name.ends_with(".xml"))
+}
+
+fn read_docx_parts(template_path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let file = File::open(template_path)
+        .map_err(|e| Error::Generic(format!("Failed to open template: {}", e)))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| Error::Generic(format!("Invalid DOCX archive: {}", e)))?;
+
+    let mut parts = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| Error::Generic(format!("Failed to read DOCX entry: {}", e)))?;
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::Generic(format!("Failed to read DOCX entry: {}", e)))?;
+        parts.push((entry.name().to_string(), bytes));
+    }
+    Ok(parts)
+}
+
+fn render_docx_parts(
+    parts: Vec<(String, Vec<u8>)>,
+    data: &Value,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    parts
+        .into_iter()
+        .map(|(name, bytes)| {
+            if !is_word_content_part(&name) {
+                return Ok((name, bytes));
+            }
+            let xml = String::from_utf8(bytes)
+                .map_err(|e| Error::Generic(format!("Invalid UTF-8 in {}: {}", name, e)))?;
+            Ok((name, render_word_xml(&xml, data)?.into_bytes()))
+        })
+        .collect()
+}
+
+fn write_docx_parts(parts: &[(String, Vec<u8>)], output_path: &Path) -> Result<()> {
+    let file = File::create(output_path)
+        .map_err(|e| Error::Generic(format!("Failed to create file: {}", e)))?;
+    let mut writer = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, bytes) in parts {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| Error::Generic(format!("Failed to write DOCX entry: {}", e)))?;
+        writer
+            .write_all(bytes)
+            .map_err(|e| Error::Generic(format!("Failed to write DOCX entry: {}", e)))?;
+    }
+    writer
+        .finish()
+        .map_err(|e| Error::Generic(format!("Failed to finish DOCX: {}", e)))?;
+    Ok(())
+}
+
+/// Content of a rendered document used when converting between formats
+#[derive(Debug, Clone, PartialEq)]
+enum RenderedBlock {
+    Heading(u8, String),
+    Paragraph(String),
+    Table(Vec<Vec<String>>),
+}
+
+fn word_text(node: roxmltree::Node<'_, '_>) -> String {
+    node.descendants()
+        .filter(|n| n.has_tag_name((WORD_NS, "t")))
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+fn word_blocks(document_xml: &str) -> Result<Vec<RenderedBlock>> {
+    let xml = XmlDocument::parse(document_xml)
+        .map_err(|e| Error::Generic(format!("Invalid DOCX XML: {}", e)))?;
+    let Some(body) = xml
+        .descendants()
+        .find(|n| n.has_tag_name((WORD_NS, "body")))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut blocks = Vec::new();
+    for child in body.children().filter(|n| n.is_element()) {
+        if child.has_tag_name((WORD_NS, "p")) {
+            let text = word_text(child);
+            if text.trim().is_empty() {
+                continue;
+            }
+            let heading_level = child
+                .descendants()
+                .find(|n| n.has_tag_name((WORD_NS, "pStyle")))
+                .and_then(|n| n.attribute((WORD_NS, "val")))
+                .and_then(|style| style.strip_prefix("Heading"))
+                .and_then(|level| level.parse::<u8>().ok());
+            blocks.push(match heading_level {
+                Some(level) => RenderedBlock::Heading(level, text),
+                None => RenderedBlock::Paragraph(text),
+            });
+        } else if child.has_tag_name((WORD_NS, "tbl")) {
+            let rows = child
+                .children()
+                .filter(|n| n.has_tag_name((WORD_NS, "tr")))
+                .map(|row| {
+                    row.children()
+                        .filter(|n| n.has_tag_name((WORD_NS, "tc")))
+                        .map(word_text)
+                        .collect()
+                })
+                .collect();
+            blocks.push(RenderedBlock::Table(rows));
+        }
+    }
+    Ok(blocks)
+}
+
+// ====================
+// Excel templates
+// ====================
+
+/// A rendered worksheet; a cell holds a number when the template produced one
+struct RenderedSheet {
+    name: String,
+    rows: Vec<Vec<ExcelCell>>,
+}
+
+fn render_cell(template: &str, frames: &mut Vec<Frame<'_>>) -> Result<ExcelCell> {
+    if !template.contains("{{") {
+        return Ok(match template.parse::<f64>() {
+            Ok(value) if value.is_finite() => ExcelCell::Number { value },
+            _ => text_cell(template.to_string()),
+        });
+    }
+
+    let nodes = parse(template)?;
+    // A cell holding a single placeholder keeps numeric values as numbers
+    if let [Node::Var(path)] = nodes.as_slice() {
+        if let Some(value) = lookup(frames, path).and_then(|value| value.as_f64()) {
+            return Ok(ExcelCell::Number { value });
+        }
+    }
+    Ok(text_cell(render_nodes(&nodes, frames, no_escape)))
+}
+
+fn text_cell(value: String) -> ExcelCell {
+    if value.is_empty() {
+        ExcelCell::Empty
+    } else {
+        ExcelCell::Text { value }
+    }
+}
+
+fn render_sheet_rows(rows: &[Vec<String>], data: &Value) -> Result<Vec<Vec<ExcelCell>>> {
+    let mut output = Vec::new();
+    let mut frames = vec![Frame {
+        value: data,
+        index: None,
+    }];
+
+    for row in rows {
+        let line = row.join("");
+        let each = EACH_RE.captures(&line);
+        if each.is_none() || !END_EACH_RE.is_match(&line) {
+            output.push(
+                row.iter()
+                    .map(|cell| render_cell(cell, &mut frames))
+                    .collect::<Result<Vec<_>>>()?,
+            );
+            continue;
+        }
+
+        let path = each.unwrap()[1].to_string();
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| {
+                END_EACH_RE
+                    .replace(&EACH_RE.replace(cell, ""), "")
+                    .into_owned()
+            })
+            .collect();
+        let Some(Cow::Borrowed(Value::Array(items))) = lookup(&frames, &path) else {
+            continue;
+        };
+        for (index, item) in items.iter().enumerate() {
+            frames.push(Frame {
+                value: item,
+                index: Some(index),
+            });
+            let rendered = cells
+                .iter()
+                .map(|cell| render_cell(cell, &mut frames))
+                .collect::<Result<Vec<_>>>();
+            frames.pop();
+            output.push(rendered?);
+        }
+    }
+    Ok(output)
+}
+
+fn read_xlsx_template(template_path: &Path) -> Result<Vec<(String, Vec<Vec<String>>)>> {
+    use calamine::{open_workbook_auto, Reader};
+
+    let mut workbook = open_workbook_auto(template_path)
+        .map_err(|e| Error::Generic(format!("Failed to open spreadsheet: {}", e)))?;
+    let sheet_names: Vec<String> = workbook.sheet_names().to_owned();
+
+    let mut sheets = Vec::with_capacity(sheet_names.len());
+    for name in sheet_names {
+        let rows = match workbook.worksheet_range(&name) {
+            Some(Ok(range)) => range
+                .rows()
+                .map(|row| row.iter().map(super::excel::data_type_to_string).collect())
+                .collect(),
+            _ => Vec::new(),
+        };
+        sheets.push((name, rows));
+    }
+    Ok(sheets)
+}
+
+fn write_xlsx(sheets: Vec<RenderedSheet>, output_path: &Path) -> Result<()> {
+    let mut workbook = Workbook::new();
+    for sheet in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet
+            .set_name(&sheet.name)
+            .map_err(|e| Error::Generic(format!("Failed to set sheet name: {}", e)))?;
+        for (row_idx, row) in sheet.rows.iter().enumerate() {
+            for (col_idx, cell) in row.iter().enumerate() {
+                let (row_idx, col_idx) = (row_idx as u32, col_idx as u16);
+                let written = match cell {
+                    ExcelCell::Number { value } => {
+                        worksheet.write_number(row_idx, col_idx, *value).map(|_| ())
+                    }
+                    ExcelCell::Text { value } => {
+                        worksheet.write_string(row_idx, col_idx, value).map(|_| ())
+                    }
+                    _ => Ok(()),
+                };
+                written.map_err(|e| Error::Generic(format!("Failed to write cell: {}", e)))?;
+            }
+        }
+    }
+    workbook
+        .save(output_path)
+        .map_err(|e| Error::Generic(format!("Failed to save workbook: {}", e)))
+}
+
+fn cell_text(cell: &ExcelCell) -> String {
+    match cell {
+        ExcelCell::Text { value } | ExcelCell::Date { value } => value.clone(),
+        ExcelCell::Number { value } => {
+            super::excel::data_type_to_string(&calamine::DataType::Float(*value))
+        }
+        ExcelCell::Boolean { value } => value.to_string(),
+        ExcelCell::Formula { formula } => formula.clone(),
+        ExcelCell::Empty => String::new(),
+    }
+}
+
+fn sheet_blocks(sheets: &[RenderedSheet]) -> Vec<RenderedBlock> {
+    sheets
+        .iter()
+        .flat_map(|sheet| {
+            [
+                RenderedBlock::Heading(1, sheet.name.clone()),
+                RenderedBlock::Table(
+                    sheet
+                        .rows
+                        .iter()
+                        .map(|row| row.iter().map(cell_text).collect())
+                        .collect(),
+                ),
+            ]
+        })
+        .collect()
+}
+
+// ====================
+// Output
+// ====================
+
+fn split_table(mut rows: Vec<Vec<String>>) -> (Vec<String>, Vec<Vec<String>>) {
+    if rows.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let headers = rows.remove(0);
+    (headers, rows)
+}
+
+fn write_blocks(
+    blocks: Vec<RenderedBlock>,
+    output_type: &DocumentType,
+    output: &str,
+) -> Result<()> {
+    match output_type {
+        DocumentType::Pdf => {
+            let contents = blocks
+                .into_iter()
+                .map(|block| match block {
+                    RenderedBlock::Heading(level, text) => PdfContent::Heading {
+                        level: level.min(3),
+                        text,
+                    },
+                    RenderedBlock::Paragraph(text) => PdfContent::Paragraph {
+                        text,
+                        bold: None,
+                        italic: None,
+                        font_size: None,
+                        alignment: None,
+                    },
+                    RenderedBlock::Table(rows) => {
+                        let (headers, rows) = split_table(rows);
+                        PdfContent::Table { headers, rows }
+                    }
+                })
+                .collect();
+            let config = PdfDocumentConfig {
+                title: None,
+                author: None,
+                subject: None,
+                page_size: None,
+            };
+            PdfDocumentCreator::new().create(output, config, contents)
+        }
+        DocumentType::Word => {
+            let contents = blocks
+                .into_iter()
+                .map(|block| match block {
+                    RenderedBlock::Heading(level, text) => WordContent::Heading { level, text },
+                    RenderedBlock::Paragraph(text) => WordContent::Paragraph {
+                        text,
+                        bold: None,
+                        italic: None,
+                        underline: None,
+                        font_size: None,
+                        alignment: None,
+                    },
+                    RenderedBlock::Table(rows) => {
+                        let (headers, rows) = split_table(rows);
+                        WordContent::Table { headers, rows }
+                    }
+                })
+                .collect();
+            let config = WordDocumentConfig {
+                title: None,
+                author: None,
+                subject: None,
+                keywords: None,
+            };
+            WordDocumentCreator::new().create(output, config, contents)
+        }
+        DocumentType::Excel => {
+            let sheets: Vec<ExcelSheet> = blocks
+                .into_iter()
+                .filter_map(|block| match block {
+                    RenderedBlock::Table(rows) => Some(rows),
+                    _ => None,
+                })
+                .enumerate()
+                .map(|(index, rows)| {
+                    let (headers, rows) = split_table(rows);
+                    ExcelSheet {
+                        name: format!("Table {}", index + 1),
+                        headers,
+                        rows: rows
+                            .into_iter()
+                            .map(|row| row.into_iter().map(text_cell).collect())
+                            .collect(),
+                        freeze_panes: Some((1, 0)),
+                    }
+                })
+                .collect();
+            if sheets.is_empty() {
+                return Err(Error::Generic(
+                    "The rendered document has no tables to write to a spreadsheet".to_string(),
+                ));
+            }
+            let config = ExcelDocumentConfig {
+                title: None,
+                author: None,
+                subject: None,
+                company: None,
+            };
+            ExcelDocumentCreator::new().create(output, config, sheets)
+        }
+    }
+}
+
+/// Render a .docx or .xlsx template with JSON data.
+///
+/// The output format follows the extension of `output_path`. Rendering into the
+/// template's own format preserves its layout; other formats are rebuilt from
+/// the rendered headings, paragraphs and tables.
+pub fn render_template(template_path: &str, data: &Value, output_path: &str) -> Result<()> {
+    let template_type = DocumentManager::detect_type(template_path)?;
+    let output_type = DocumentManager::detect_type(output_path)?;
+    let output = Path::new(output_path);
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| Error::Generic(format!("Failed to create directory: {}", e)))?;
+    }
+
+    match template_type {
+        DocumentType::Word => {
+            let parts = render_docx_parts(read_docx_parts(Path::new(template_path))?, data)?;
+            if matches!(output_type, DocumentType::Word) {
+                return write_docx_parts(&parts, output);
+            }
+            let document_xml = parts
+                .iter()
+                .find(|(name, _)| name == "word/document.xml")
+                .map(|(_, bytes)| String::from_utf8_lossy(bytes).into_owned())
+                .ok_or_else(|| Error::Generic("Template has no word/document.xml".to_string()))?;
+            write_blocks(word_blocks(&document_xml)?, &output_type, output_path)
+        }
+        DocumentType::Excel => {
+            let sheets = read_xlsx_template(Path::new(template_path))?
+                .into_iter()
+                .map(|(name, rows)| {
+                    Ok(RenderedSheet {
+                        name,
+                        rows: render_sheet_rows(&rows, data)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if matches!(output_type, DocumentType::Excel) {
+                return write_xlsx(sheets, output);
+            }
+            write_blocks(sheet_blocks(&sheets), &output_type, output_path)
+        }
+        DocumentType::Pdf => Err(Error::Generic(
+            "PDF files cannot be used as templates; use a .docx or .xlsx template".to_string(),
+        )),
+    }
+}
+
+/// Text of a template with Word runs merged, used to discover its placeholders
+fn template_source(template_path: &Path, document_type: &DocumentType) -> Result<String> {
+    match document_type {
+        DocumentType::Word => Ok(read_docx_parts(template_path)?
+            .into_iter()
+            .filter(|(name, _)| is_word_content_part(name))
+            .map(|(_, bytes)| merge_tag_runs(&String::from_utf8_lossy(&bytes)))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        DocumentType::Excel => Ok(read_xlsx_template(template_path)?
+            .into_iter()
+            .flat_map(|(_, rows)| rows.into_iter().flatten())
+            .collect::<Vec<_>>()
+            .join("\n")),
+        DocumentType::Pdf => Err(Error::Generic(
+            "PDF files cannot be used as templates; use a .docx or .xlsx template".to_string(),
+        )),
+    }
+}
+
+// ====================
+// Template store
+// ====================
+
+/// A stored template and the values it expects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentTemplate {
+    pub name: String,
+    pub file_path: String,
+    pub document_type: DocumentType,
+    pub placeholders: Vec<String>,
+    pub file_size: u64,
+}
+
+/// Directory of reusable .docx and .xlsx templates
+pub struct TemplateStore {
+    dir: PathBuf,
+}
+
+impl TemplateStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Copy a template file into the store under `name`, replacing any existing one
+    pub fn save(&self, name: &str, source_path: &str) -> Result<DocumentTemplate> {
+        let document_type = DocumentManager::detect_type(source_path)?;
+        let extension = match document_type {
+            DocumentType::Word => "docx",
+            DocumentType::Excel => "xlsx",
+            DocumentType::Pdf => {
+                return Err(Error::Generic(
+                    "PDF files cannot be used as templates; use a .docx or .xlsx template"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let stem = sanitize_name(name)?;
+        // Validate before storing so broken templates are rejected up front
+        parse(&template_source(Path::new(source_path), &document_type)?)?;
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| Error::Generic(format!("Failed to create template directory: {}", e)))?;
+        self.remove_files(&stem)?;
+        let target = self.dir.join(format!("{}.{}", stem, extension));
+        fs::copy(source_path, &target)
+            .map_err(|e| Error::Generic(format!("Failed to store template: {}", e)))?;
+
+        self.describe(&target)
+    }
+
+    pub fn list(&self) -> Result<Vec<DocumentTemplate>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| Error::Generic(format!("Failed to read template directory: {}", e)))?;
+
+        let mut templates = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("docx" | "xlsx")
+            ) {
+                continue;
+            }
+            match self.describe(&path) {
+                Ok(template) => templates.push(template),
+                Err(e) => tracing::warn!("Skipping template {}: {}", path.display(), e),
+            }
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// Path of a stored template
+    pub fn resolve(&self, name: &str) -> Result<PathBuf> {
+        let stem = sanitize_name(name)?;
+        ["docx", "xlsx"]
+            .iter()
+            .map(|extension| self.dir.join(format!("{}.{}", stem, extension)))
+            .find(|path| path.exists())
+            .ok_or_else(|| Error::Generic(format!("Template not found: {}", name)))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let path = self.resolve(name)?;
+        fs::remove_file(path)
+            .map_err(|e| Error::Generic(format!("Failed to delete template: {}", e)))
+    }
+
+    fn remove_files(&self, stem: &str) -> Result<()> {
+        for extension in ["docx", "xlsx"] {
+            let path = self.dir.join(format!("{}.{}", stem, extension));
+            if path.exists() {
+                fs::remove_file(path)
+                    .map_err(|e| Error::Generic(format!("Failed to replace template: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self, path: &Path) -> Result<DocumentTemplate> {
+        let file_path = path.to_string_lossy().into_owned();
+        let document_type = DocumentManager::detect_type(&file_path)?;
+        let placeholders = placeholders(&template_source(path, &document_type)?);
+        let file_size = fs::metadata(path)
+            .map_err(|e| Error::Generic(format!("Failed to read template metadata: {}", e)))?
+            .len();
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(DocumentTemplate {
+            name,
+            file_path,
+            document_type,
+            placeholders,
+            file_size,
+        })
+    }
+}
+
+fn sanitize_name(name: &str) -> Result<String> {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.trim().is_empty() {
+        return Err(Error::Generic("Template name cannot be empty".to_string()));
+    }
+    Ok(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_variables_loops_and_conditionals() {
+        let data = json!({
+            "customer": { "name": "Acme" },
+            "paid": false,
+            "items": [{ "sku": "A1" }, { "sku": "B2" }],
+        });
+        let template =
+            "To {{customer.name}}:{{#each items}} {{@index}}={{sku}}/{{customer.name}}{{/each}}\
+                        {{#if paid}} PAID{{else}} DUE{{/if}}{{#unless items}} none{{/unless}}";
+
+        assert_eq!(
+            render_text(template, &data).unwrap(),
+            "To Acme: 0=A1/Acme 1=B2/Acme DUE"
+        );
+        assert!(render_text("{{#each items}}", &data).is_err());
+        assert!(render_text("{{#if a}}{{/each}}", &data).is_err());
+    }
+
+    #[test]
+    fn lists_placeholders() {
+        let names =
+            placeholders("{{a}} {{#each rows}}{{this.x}}{{@index}}{{/each}} {{#if b.c}}{{/if}}");
+        assert_eq!(names, vec!["a", "b.c", "rows"]);
+    }
+
+    #[test]
+    fn merges_split_runs_and_repeats_table_rows() {
+        let xml = concat!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>"#,
+            r#"<w:p><w:r><w:t>Dear {{cust</w:t></w:r><w:r><w:t>omer}}</w:t></w:r></w:p>"#,
+            r#"<w:tbl><w:tr><w:tc><w:p><w:r><w:t>{{#each rows}}{{name}}</w:t></w:r></w:p></w:tc>"#,
+            r#"<w:tc><w:p><w:r><w:t>{{qty}}{{/each}}</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+            r#"<w:p><w:r><w:t>{{#if note}}</w:t></w:r></w:p><w:p><w:r><w:t>{{note}}</w:t></w:r></w:p>"#,
+            r#"<w:p><w:r><w:t>{{/if}}</w:t></w:r></w:p>"#,
+            r#"</w:body></w:document>"#
+        );
+        let data = json!({
+            "customer": "R&D",
+            "rows": [{ "name": "Bolt", "qty": 2 }, { "name": "Nut", "qty": 5 }],
+            "note": "",
+        });
+
+        let rendered = render_word_xml(xml, &data).unwrap();
+        let blocks = word_blocks(&rendered).unwrap();
+        assert_eq!(
+            blocks,
+            vec![
+                RenderedBlock::Paragraph("Dear R&D".to_string()),
+                RenderedBlock::Table(vec![
+                    vec!["Bolt".to_string(), "2".to_string()],
+                    vec!["Nut".to_string(), "5".to_string()],
+                ]),
+            ]
+        );
+        assert_eq!(rendered.matches("<w:p>").count(), 5);
+    }
+
+    #[test]
+    fn repeats_spreadsheet_rows() {
+        let rows = vec![
+            vec!["Invoice".to_string(), "{{number}}".to_string()],
+            vec![
+                "{{#each lines}}{{item}}".to_string(),
+                "{{price}}{{/each}}".to_string(),
+            ],
+        ];
+        let data = json!({
+            "number": "INV-7",
+            "lines": [{ "item": "Widget", "price": 9.5 }],
+        });
+
+        let rendered = render_sheet_rows(&rows, &data).unwrap();
+        assert_eq!(rendered.len(), 2);
+        assert!(matches!(&rendered[0][1], ExcelCell::Text { value } if value == "INV-7"));
+        assert!(matches!(&rendered[1][0], ExcelCell::Text { value } if value == "Widget"));
+        assert!(matches!(rendered[1][1], ExcelCell::Number { value } if value == 9.5));
+    }
+
+    #[test]
+    fn sanitizes_template_names() {
+        assert_eq!(sanitize_name("../invoice").unwrap(), "___invoice");
+        assert!(sanitize_name("  ").is_err());
+    }
+}
@@ -29,7 +29,11 @@ impl WordHandler {
             metadata.word_count = Some(text.split_whitespace().count());
         }
 
-        Ok(DocumentContent { text, metadata })
+        Ok(DocumentContent {
+            text,
+            metadata,
+            pages: Vec::new(),
+        })
     }
 
     pub async fn extract_text(&self, file_path: &str) -> Result<String> {
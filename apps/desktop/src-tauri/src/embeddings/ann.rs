@@ -0,0 +1,501 @@
+/**
+ * Approximate Nearest Neighbor Index
+ * Hierarchical navigable small world graph over normalized embeddings
+ */
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// Bumped whenever the on-disk layout changes; older files are rebuilt
+const FORMAT_VERSION: u32 = 1;
+
+/// Graph construction and query parameters
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HnswParams {
+    /// Links per node on upper layers; layer 0 keeps twice as many
+    pub m: usize,
+    /// Candidate list size while inserting; higher builds a better graph
+    pub ef_construction: usize,
+    /// Candidate list size while searching; higher trades speed for recall
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    id: String,
+    vector: Vec<f32>,
+    /// Neighbor node indexes per layer, layer 0 first
+    links: Vec<Vec<u32>>,
+    /// Removed nodes stay in the graph as waypoints until the next compaction
+    deleted: bool,
+}
+
+/// Distance to a node, ordered so `BinaryHeap` pops the farthest first
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: u32,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+/// HNSW index keyed by embedding id, using cosine distance.
+///
+/// Inserting an existing id replaces its vector. Removal only marks the node,
+/// so the graph stays navigable; [`HnswIndex::compact`] drops marked nodes once
+/// [`HnswIndex::needs_compaction`] reports too many.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndex {
+    version: u32,
+    params: HnswParams,
+    dimensions: usize,
+    nodes: Vec<Node>,
+    ids: HashMap<String, u32>,
+    entry: Option<u32>,
+    max_level: usize,
+    deleted: usize,
+    rng_state: u64,
+    /// Last change-log sequence number reflected in the graph
+    pub applied_seq: i64,
+}
+
+impl HnswIndex {
+    pub fn new(dimensions: usize, params: HnswParams) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            params,
+            dimensions,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry: None,
+            max_level: 0,
+            deleted: 0,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+            applied_seq: 0,
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of live vectors
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains_key(id)
+    }
+
+    /// Whether removed nodes make up more than a quarter of the graph
+    pub fn needs_compaction(&self) -> bool {
+        self.deleted > 1_000 && self.deleted * 4 > self.nodes.len()
+    }
+
+    pub fn insert(&mut self, id: &str, vector: &[f32]) -> Result<()> {
+        if vector.len() != self.dimensions {
+            bail!(
+                "Embedding has {} dimensions, index expects {}",
+                vector.len(),
+                self.dimensions
+            );
+        }
+        self.remove(id);
+
+        let vector = normalize(vector);
+        let level = self.random_level();
+        let index = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            id: id.to_string(),
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.ids.insert(id.to_string(), index);
+
+        let Some(mut entry) = self.entry else {
+            self.entry = Some(index);
+            self.max_level = level;
+            return Ok(());
+        };
+
+        let query = self.nodes[index as usize].vector.clone();
+        for layer in (level + 1..=self.max_level).rev() {
+            entry = self.greedy_closest(&query, entry, layer);
+        }
+
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let candidates = self.search_layer(&query, entry, self.params.ef_construction, layer);
+            let neighbors = self.select_neighbors(&candidates, self.max_links(layer));
+            self.nodes[index as usize].links[layer] = neighbors.clone();
+
+            for neighbor in neighbors {
+                let links = &mut self.nodes[neighbor as usize].links[layer];
+                links.push(index);
+                // Pruning is the most expensive step of an insert, so let a
+                // list overflow by half before cutting it back
+                if links.len() > self.max_links(layer) * 3 / 2 {
+                    self.prune(neighbor, layer);
+                }
+            }
+            entry = candidates[0].node;
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry = Some(index);
+        }
+        Ok(())
+    }
+
+    /// Mark `id` as removed; returns whether it was present
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(index) = self.ids.remove(id) else {
+            return false;
+        };
+        self.nodes[index as usize].deleted = true;
+        self.deleted += 1;
+        true
+    }
+
+    /// Up to `limit` nearest ids with their cosine similarity, best first
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<(String, f32)> {
+        let Some(mut entry) = self.entry else {
+            return Vec::new();
+        };
+        if query.len() != self.dimensions || limit == 0 {
+            return Vec::new();
+        }
+
+        let query = normalize(query);
+        for layer in (1..=self.max_level).rev() {
+            entry = self.greedy_closest(&query, entry, layer);
+        }
+        let ef = self.params.ef_search.max(limit * 2);
+        self.search_layer(&query, entry, ef, 0)
+            .into_iter()
+            .filter(|candidate| !self.nodes[candidate.node as usize].deleted)
+            .take(limit)
+            .map(|candidate| {
+                (
+                    self.nodes[candidate.node as usize].id.clone(),
+                    1.0 - candidate.distance,
+                )
+            })
+            .collect()
+    }
+
+    /// Rebuild the graph from live nodes only
+    pub fn compact(&mut self) -> Result<()> {
+        let mut rebuilt = HnswIndex::new(self.dimensions, self.params);
+        rebuilt.rng_state = self.rng_state;
+        rebuilt.applied_seq = self.applied_seq;
+        for node in self.nodes.iter().filter(|node| !node.deleted) {
+            rebuilt.insert(&node.id, &node.vector)?;
+        }
+        *self = rebuilt;
+        Ok(())
+    }
+
+    /// Write the index atomically, replacing any previous file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let temp = path.with_extension("tmp");
+        {
+            let writer = BufWriter::new(File::create(&temp)?);
+            bincode::serialize_into(writer, self).context("Failed to write vector index")?;
+        }
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// Read a saved index; `None` if the file is missing or from another format version
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let reader = BufReader::new(File::open(path)?);
+        match bincode::deserialize_from::<_, Self>(reader) {
+            Ok(index) if index.version == FORMAT_VERSION => Ok(Some(index)),
+            Ok(_) => Ok(None),
+            Err(e) => {
+                tracing::warn!("Discarding unreadable vector index: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.params.m * 2
+        } else {
+            self.params.m
+        }
+    }
+
+    fn distance(&self, query: &[f32], node: u32) -> f32 {
+        1.0 - dot(query, &self.nodes[node as usize].vector)
+    }
+
+    fn greedy_closest(&self, query: &[f32], mut current: u32, layer: usize) -> u32 {
+        let mut best = self.distance(query, current);
+        loop {
+            let mut improved = false;
+            for &neighbor in self.links(current, layer) {
+                let distance = self.distance(query, neighbor);
+                if distance < best {
+                    best = distance;
+                    current = neighbor;
+                    improved = true;
+                }
+            }
+            if !improved {
+                return current;
+            }
+        }
+    }
+
+    /// Best-first search on one layer, closest first
+    fn search_layer(&self, query: &[f32], entry: u32, ef: usize, layer: usize) -> Vec<Candidate> {
+        let start = Candidate {
+            distance: self.distance(query, entry),
+            node: entry,
+        };
+        // One bit per node; cheaper than hashing at these sizes
+        let mut visited = vec![0u64; self.nodes.len().div_ceil(64)];
+        visited[entry as usize / 64] |= 1 << (entry % 64);
+        let mut frontier = BinaryHeap::from([Reverse(start)]);
+        let mut found = BinaryHeap::from([start]);
+
+        while let Some(Reverse(current)) = frontier.pop() {
+            let farthest = found.peek().map_or(f32::MAX, |c| c.distance);
+            if current.distance > farthest && found.len() >= ef {
+                break;
+            }
+            for &neighbor in self.links(current.node, layer) {
+                let (word, bit) = (neighbor as usize / 64, 1u64 << (neighbor % 64));
+                if visited[word] & bit != 0 {
+                    continue;
+                }
+                visited[word] |= bit;
+                let candidate = Candidate {
+                    distance: self.distance(query, neighbor),
+                    node: neighbor,
+                };
+                let farthest = found.peek().map_or(f32::MAX, |c| c.distance);
+                if found.len() < ef || candidate.distance < farthest {
+                    frontier.push(Reverse(candidate));
+                    found.push(candidate);
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+
+        found.into_sorted_vec()
+    }
+
+    /// Keep candidates that are closer to the query than to any already kept
+    /// neighbor, so links spread in different directions, then top up with
+    /// the closest of the rest
+    fn select_neighbors(&self, candidates: &[Candidate], count: usize) -> Vec<u32> {
+        let mut selected: Vec<u32> = Vec::with_capacity(count);
+        let mut skipped = Vec::new();
+        for candidate in candidates {
+            if selected.len() == count {
+                break;
+            }
+            let vector = &self.nodes[candidate.node as usize].vector;
+            let diverse = selected
+                .iter()
+                .all(|&kept| self.distance(vector, kept) > candidate.distance);
+            if diverse {
+                selected.push(candidate.node);
+            } else {
+                skipped.push(candidate.node);
+            }
+        }
+        for node in skipped {
+            if selected.len() == count {
+                break;
+            }
+            selected.push(node);
+        }
+        selected
+    }
+
+    fn prune(&mut self, node: u32, layer: usize) {
+        let vector = self.nodes[node as usize].vector.clone();
+        let mut candidates: Vec<Candidate> = self.nodes[node as usize].links[layer]
+            .iter()
+            .map(|&neighbor| Candidate {
+                distance: self.distance(&vector, neighbor),
+                node: neighbor,
+            })
+            .collect();
+        candidates.sort();
+        let kept = self.select_neighbors(&candidates, self.max_links(layer));
+        self.nodes[node as usize].links[layer] = kept;
+    }
+
+    fn links(&self, node: u32, layer: usize) -> &[u32] {
+        self.nodes[node as usize]
+            .links
+            .get(layer)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Exponentially distributed layer with normalization factor 1/ln(M)
+    fn random_level(&mut self) -> usize {
+        // SplitMix64, so rebuilds of the same data produce the same graph
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        let uniform = ((z >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let level = -uniform.ln() / (self.params.m.max(2) as f64).ln();
+        (level as usize).min(16)
+    }
+}
+
+/// Dot product in eight independent lanes, which the compiler can vectorize
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut lanes = [0.0f32; 8];
+    let (a_chunks, b_chunks) = (a.chunks_exact(8), b.chunks_exact(8));
+    let tail: f32 = a_chunks
+        .remainder()
+        .iter()
+        .zip(b_chunks.remainder())
+        .map(|(x, y)| x * y)
+        .sum();
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for lane in 0..8 {
+            lanes[lane] += x[lane] * y[lane];
+        }
+    }
+    lanes.iter().sum::<f32>() + tail
+}
+
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let magnitude = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if magnitude == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|x| x / magnitude).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::cosine_similarity;
+    use std::collections::HashSet;
+
+    fn vectors(count: usize, dimensions: usize) -> Vec<Vec<f32>> {
+        let mut state = 42u64;
+        (0..count)
+            .map(|_| {
+                (0..dimensions)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6_364_136_223_846_793_005)
+                            .wrapping_add(1_442_695_040_888_963_407);
+                        (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recall_against_linear_scan() {
+        let data = vectors(2_000, 32);
+        let mut index = HnswIndex::new(32, HnswParams::default());
+        for (i, vector) in data.iter().enumerate() {
+            index.insert(&i.to_string(), vector).unwrap();
+        }
+
+        let mut hits = 0;
+        for query in data.iter().take(50) {
+            let mut exact: Vec<(usize, f32)> = data
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i, cosine_similarity(query, v)))
+                .collect();
+            exact.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let expected: HashSet<String> =
+                exact.iter().take(10).map(|(i, _)| i.to_string()).collect();
+
+            let found = index.search(query, 10);
+            assert_eq!(found.len(), 10);
+            hits += found.iter().filter(|(id, _)| expected.contains(id)).count();
+        }
+        let recall = hits as f32 / 500.0;
+        assert!(recall > 0.9, "recall {}", recall);
+    }
+
+    #[test]
+    fn test_remove_replace_and_persist() {
+        let data = vectors(300, 8);
+        let mut index = HnswIndex::new(8, HnswParams::default());
+        for (i, vector) in data.iter().enumerate() {
+            index.insert(&i.to_string(), vector).unwrap();
+        }
+
+        assert!(index.remove("0"));
+        assert!(!index.remove("0"));
+        assert!(index.search(&data[0], 5).iter().all(|(id, _)| id != "0"));
+
+        index.insert("1", &data[2]).unwrap();
+        assert_eq!(index.len(), 299);
+        let best = index.search(&data[2], 2);
+        assert!(best.iter().any(|(id, _)| id == "1"));
+        assert!(index.insert("bad", &[1.0]).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("embeddings.hnsw");
+        index.applied_seq = 7;
+        index.save(&path).unwrap();
+        let mut loaded = HnswIndex::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.applied_seq, 7);
+        assert_eq!(loaded.search(&data[5], 3), index.search(&data[5], 3));
+
+        loaded.compact().unwrap();
+        assert_eq!(loaded.len(), 299);
+        assert_eq!(loaded.search(&data[5], 1)[0].0, "5");
+    }
+}
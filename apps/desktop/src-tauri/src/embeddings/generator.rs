@@ -1,6 +1,6 @@
 /**
  * Embedding Generator
- * Generates vector embeddings using Ollama (primary) or fastembed-rs (fallback)
+ * Generates vector embeddings using Ollama or the OpenAI API, with fastembed-rs as the local fallback
  */
 use anyhow::{anyhow, Context, Result};
 use reqwest::Client;
@@ -8,23 +8,67 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 use super::Vector;
+use crate::security::SecretString;
+
+/// Texts sent per OpenAI embeddings request
+const OPENAI_BATCH_SIZE: usize = 128;
 
 /// Embedding model selection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EmbeddingModel {
     /// Ollama nomic-embed-text (768 dimensions, surpasses OpenAI ada-002)
+    #[serde(rename = "nomic-embed-text")]
     OllamaNomicEmbedText,
     /// Ollama mxbai-embed-large (1024 dimensions, high quality)
+    #[serde(rename = "mxbai-embed-large")]
     OllamaMxbaiEmbedLarge,
+    /// Ollama bge-small-en-v1.5 (384 dimensions, small and fast)
+    #[serde(rename = "bge-small-en-v1.5")]
+    OllamaBgeSmall,
+    /// OpenAI text-embedding-3-small (1536 dimensions)
+    #[serde(rename = "text-embedding-3-small")]
+    OpenAiTextEmbedding3Small,
+    /// OpenAI text-embedding-3-large (3072 dimensions, highest quality)
+    #[serde(rename = "text-embedding-3-large")]
+    OpenAiTextEmbedding3Large,
     /// Local fastembed all-MiniLM-L6-v2 (384 dimensions, fast)
+    #[serde(rename = "all-minilm-l6-v2")]
     FastembedAllMiniLM,
 }
 
 impl EmbeddingModel {
+    pub const ALL: [EmbeddingModel; 6] = [
+        Self::OllamaNomicEmbedText,
+        Self::OllamaMxbaiEmbedLarge,
+        Self::OllamaBgeSmall,
+        Self::OpenAiTextEmbedding3Small,
+        Self::OpenAiTextEmbedding3Large,
+        Self::FastembedAllMiniLM,
+    ];
+
+    /// Stable name recorded with an index
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::OllamaNomicEmbedText => "nomic-embed-text",
+            Self::OllamaMxbaiEmbedLarge => "mxbai-embed-large",
+            Self::OllamaBgeSmall => "bge-small-en-v1.5",
+            Self::OpenAiTextEmbedding3Small => "text-embedding-3-small",
+            Self::OpenAiTextEmbedding3Large => "text-embedding-3-large",
+            Self::FastembedAllMiniLM => "all-minilm-l6-v2",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|model| model.id() == id)
+    }
+
     pub fn dimensions(&self) -> usize {
         match self {
             Self::OllamaNomicEmbedText => 768,
             Self::OllamaMxbaiEmbedLarge => 1024,
+            Self::OllamaBgeSmall => 384,
+            Self::OpenAiTextEmbedding3Small => 1536,
+            Self::OpenAiTextEmbedding3Large => 3072,
             Self::FastembedAllMiniLM => 384,
         }
     }
@@ -33,7 +77,16 @@ impl EmbeddingModel {
         match self {
             Self::OllamaNomicEmbedText => Some("nomic-embed-text"),
             Self::OllamaMxbaiEmbedLarge => Some("mxbai-embed-large"),
-            Self::FastembedAllMiniLM => None,
+            Self::OllamaBgeSmall => Some("qllama/bge-small-en-v1.5"),
+            _ => None,
+        }
+    }
+
+    pub fn openai_model_name(&self) -> Option<&str> {
+        match self {
+            Self::OpenAiTextEmbedding3Small => Some("text-embedding-3-small"),
+            Self::OpenAiTextEmbedding3Large => Some("text-embedding-3-large"),
+            _ => None,
         }
     }
 }
@@ -43,6 +96,11 @@ impl EmbeddingModel {
 pub struct EmbeddingConfig {
     pub model: EmbeddingModel,
     pub ollama_url: String,
+    pub openai_url: String,
+    /// Required for the OpenAI models
+    pub openai_api_key: Option<SecretString>,
+    /// Use the local model when the primary one fails. Only applies when
+    /// both produce vectors of the same size.
     pub enable_fallback: bool,
     pub timeout: Duration,
 }
@@ -52,6 +110,8 @@ impl Default for EmbeddingConfig {
         Self {
             model: EmbeddingModel::OllamaNomicEmbedText,
             ollama_url: "http://localhost:11434".to_string(),
+            openai_url: "https://api.openai.com/v1".to_string(),
+            openai_api_key: None,
             enable_fallback: true,
             timeout: Duration::from_secs(30),
         }
@@ -102,6 +162,20 @@ impl EmbeddingGenerator {
 
     /// Generate embedding for text
     pub async fn generate(&self, text: &str) -> Result<Vector> {
+        let embedding = if let Some(model_name) = self.config.model.openai_model_name() {
+            self.generate_openai(&[text], model_name)
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("No embeddings in OpenAI response"))?
+        } else {
+            self.generate_local(text).await?
+        };
+        self.check_dimensions(&embedding)?;
+        Ok(embedding)
+    }
+
+    /// Ollama with the optional local fallback
+    async fn generate_local(&self, text: &str) -> Result<Vector> {
         // Try Ollama first
         if let Some(model_name) = self.config.model.ollama_model_name() {
             match self.generate_ollama(text, model_name).await {
@@ -109,7 +183,7 @@ impl EmbeddingGenerator {
                 Err(e) => {
                     tracing::warn!("Ollama embedding generation failed: {}", e);
 
-                    if !self.config.enable_fallback {
+                    if !self.fallback_allowed() {
                         return Err(e);
                     }
 
@@ -119,13 +193,31 @@ impl EmbeddingGenerator {
         }
 
         // Fallback to fastembed (if enabled)
-        if self.config.enable_fallback {
+        if self.fallback_allowed() || self.config.model == EmbeddingModel::FastembedAllMiniLM {
             self.generate_fastembed(text).await
         } else {
             Err(anyhow!("Ollama unavailable and fallback disabled"))
         }
     }
 
+    /// Falling back to a model of another size would mix dimensions in the index
+    fn fallback_allowed(&self) -> bool {
+        self.config.enable_fallback
+            && self.config.model.dimensions() == EmbeddingModel::FastembedAllMiniLM.dimensions()
+    }
+
+    fn check_dimensions(&self, embedding: &[f32]) -> Result<()> {
+        if embedding.len() != self.dimensions() {
+            return Err(anyhow!(
+                "{} returned {} dimensions, expected {}",
+                self.config.model.id(),
+                embedding.len(),
+                self.dimensions()
+            ));
+        }
+        Ok(())
+    }
+
     /// Generate embedding using Ollama
     async fn generate_ollama(&self, text: &str, model: &str) -> Result<Vector> {
         let url = format!("{}/api/embed", self.config.ollama_url);
@@ -173,10 +265,61 @@ impl EmbeddingGenerator {
         ))
     }
 
+    /// Generate embeddings using the OpenAI embeddings API, in input order
+    async fn generate_openai(&self, texts: &[&str], model: &str) -> Result<Vec<Vector>> {
+        let api_key = self
+            .config
+            .openai_api_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("No OpenAI API key configured"))?;
+
+        let response = self
+            .client
+            .post(format!("{}/embeddings", self.config.openai_url))
+            .bearer_auth(api_key.expose_secret())
+            .json(&OpenAiEmbedRequest {
+                model: model.to_string(),
+                input: texts.iter().map(|text| text.to_string()).collect(),
+            })
+            .send()
+            .await
+            .context("Failed to send OpenAI request")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("OpenAI error {}: {}", status, body));
+        }
+
+        let mut result: OpenAiEmbedResponse = response
+            .json()
+            .await
+            .context("Failed to parse OpenAI response")?;
+        if result.data.len() != texts.len() {
+            return Err(anyhow!(
+                "OpenAI returned {} embeddings for {} inputs",
+                result.data.len(),
+                texts.len()
+            ));
+        }
+        result.data.sort_by_key(|item| item.index);
+        Ok(result.data.into_iter().map(|item| item.embedding).collect())
+    }
+
     /// Generate batch embeddings (more efficient for multiple texts)
     pub async fn generate_batch(&self, texts: &[&str]) -> Result<Vec<Vector>> {
         let mut embeddings = Vec::with_capacity(texts.len());
 
+        if let Some(model_name) = self.config.model.openai_model_name() {
+            for batch in texts.chunks(OPENAI_BATCH_SIZE) {
+                for embedding in self.generate_openai(batch, model_name).await? {
+                    self.check_dimensions(&embedding)?;
+                    embeddings.push(embedding);
+                }
+            }
+            return Ok(embeddings);
+        }
+
         for text in texts {
             let embedding = self.generate(text).await?;
             embeddings.push(embedding);
@@ -185,6 +328,10 @@ impl EmbeddingGenerator {
         Ok(embeddings)
     }
 
+    pub fn model(&self) -> EmbeddingModel {
+        self.config.model
+    }
+
     /// Get the dimensionality of embeddings
     pub fn dimensions(&self) -> usize {
         self.config.model.dimensions()
@@ -202,6 +349,23 @@ struct OllamaEmbedResponse {
     embeddings: Vec<Vector>,
 }
 
+#[derive(Debug, Serialize)]
+struct OpenAiEmbedRequest {
+    model: String,
+    input: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbedResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vector,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,317 @@
+/**
+ * Hybrid Search
+ * Reciprocal rank fusion of vector and BM25 results, with optional LLM reranking
+ */
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::similarity::{KeywordResult, SearchResult};
+use super::EmbeddingMetadata;
+use crate::router::LLMRouter;
+
+/// Characters of each candidate shown to the reranker
+const RERANK_SNIPPET_CHARS: usize = 600;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    Semantic,
+    Keyword,
+    #[default]
+    Hybrid,
+}
+
+/// Options for `hybrid_search_codebase`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub limit: usize,
+    /// Candidates fetched from each retriever before fusion
+    pub candidates: usize,
+    /// Reciprocal rank fusion constant; larger values flatten the rank curve
+    pub rrf_k: f32,
+    pub semantic_weight: f32,
+    pub keyword_weight: f32,
+    /// Have the LLM score query relevance of the top fused results
+    pub rerank: bool,
+    pub rerank_top_n: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            mode: SearchMode::Hybrid,
+            limit: 10,
+            candidates: 50,
+            rrf_k: 60.0,
+            semantic_weight: 1.0,
+            keyword_weight: 1.0,
+            rerank: false,
+            rerank_top_n: 20,
+        }
+    }
+}
+
+/// Why a result was returned
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatchExplanation {
+    /// 1-based rank among vector matches
+    pub semantic_rank: Option<usize>,
+    pub similarity: Option<f32>,
+    /// 1-based rank among keyword matches
+    pub keyword_rank: Option<usize>,
+    pub keyword_score: Option<f64>,
+    /// Query words found in the chunk
+    pub matched_terms: Vec<String>,
+    /// LLM relevance from 0 to 10
+    pub rerank_score: Option<f32>,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridSearchResult {
+    pub metadata: EmbeddingMetadata,
+    /// Fused reciprocal rank score
+    pub score: f32,
+    pub explanation: MatchExplanation,
+}
+
+/// Merge vector and keyword rankings with weighted reciprocal rank fusion
+pub fn fuse_results(
+    semantic: Vec<SearchResult>,
+    keyword: Vec<KeywordResult>,
+    terms: &[String],
+    options: &SearchOptions,
+) -> Vec<HybridSearchResult> {
+    let mut fused: Vec<HybridSearchResult> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    let mut entry = |metadata: EmbeddingMetadata| -> usize {
+        *positions.entry(metadata.id.clone()).or_insert_with(|| {
+            fused.push(HybridSearchResult {
+                metadata,
+                score: 0.0,
+                explanation: MatchExplanation::default(),
+            });
+            fused.len() - 1
+        })
+    };
+
+    let mut updates = Vec::new();
+    if options.mode != SearchMode::Keyword {
+        for (rank, result) in semantic.into_iter().enumerate() {
+            let similarity = result.similarity;
+            updates.push((entry(result.metadata), rank + 1, Some(similarity), None));
+        }
+    }
+    if options.mode != SearchMode::Semantic {
+        for (rank, result) in keyword.into_iter().enumerate() {
+            let score = result.score;
+            updates.push((entry(result.metadata), rank + 1, None, Some(score)));
+        }
+    }
+
+    for (index, rank, similarity, keyword_score) in updates {
+        let result = &mut fused[index];
+        if let Some(similarity) = similarity {
+            result.score += options.semantic_weight / (options.rrf_k + rank as f32);
+            result.explanation.semantic_rank = Some(rank);
+            result.explanation.similarity = Some(similarity);
+        }
+        if let Some(keyword_score) = keyword_score {
+            result.score += options.keyword_weight / (options.rrf_k + rank as f32);
+            result.explanation.keyword_rank = Some(rank);
+            result.explanation.keyword_score = Some(keyword_score);
+        }
+    }
+
+    for result in &mut fused {
+        let haystack = format!(
+            "{} {}",
+            result.metadata.content,
+            result.metadata.symbol_name.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+        result.explanation.matched_terms = terms
+            .iter()
+            .filter(|term| haystack.contains(&term.to_lowercase()))
+            .cloned()
+            .collect();
+        result.explanation.summary = summarize(&result.explanation);
+    }
+
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+    fused
+}
+
+/// Reorder the first `top_n` results by LLM-judged relevance to the query.
+///
+/// Results the model did not score keep their fused order after scored ones.
+pub async fn rerank_results(
+    router: &LLMRouter,
+    query: &str,
+    results: &mut [HybridSearchResult],
+    top_n: usize,
+) -> Result<()> {
+    let top_n = top_n.min(results.len());
+    if top_n < 2 {
+        return Ok(());
+    }
+
+    let mut prompt = format!(
+        "Rate how relevant each code snippet is to the search query on a scale from 0 to 10.\n\n\
+         Query: {}\n\n",
+        query
+    );
+    for (index, result) in results[..top_n].iter().enumerate() {
+        let snippet: String = result
+            .metadata
+            .content
+            .chars()
+            .take(RERANK_SNIPPET_CHARS)
+            .collect();
+        prompt.push_str(&format!(
+            "[{}] {}:{}\n```\n{}\n```\n\n",
+            index, result.metadata.file_path, result.metadata.start_line, snippet
+        ));
+    }
+    prompt.push_str(
+        "Respond with only a JSON array like [{\"index\": 0, \"score\": 7.5}], one entry per snippet.",
+    );
+
+    let response = router.send_message(&prompt, None).await?;
+    let scores = parse_rerank_scores(&response, top_n)
+        .ok_or_else(|| anyhow!("Reranker returned no usable scores"))?;
+
+    for (index, score) in scores {
+        let explanation = &mut results[index].explanation;
+        explanation.rerank_score = Some(score);
+        explanation.summary = summarize(explanation);
+    }
+    results[..top_n].sort_by(|a, b| {
+        let a = a.explanation.rerank_score.unwrap_or(f32::MIN);
+        let b = b.explanation.rerank_score.unwrap_or(f32::MIN);
+        b.total_cmp(&a)
+    });
+
+    Ok(())
+}
+
+/// Extract `(index, score)` pairs for indexes below `count` from a reranker reply
+pub fn parse_rerank_scores(response: &str, count: usize) -> Option<Vec<(usize, f32)>> {
+    let start = response.find('[')?;
+    let end = response.rfind(']')?;
+    if end <= start {
+        return None;
+    }
+
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&response[start..=end]).ok()?;
+    let scores: Vec<(usize, f32)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let index = entry.get("index")?.as_u64()? as usize;
+            let score = entry.get("score")?.as_f64()? as f32;
+            (index < count).then_some((index, score.clamp(0.0, 10.0)))
+        })
+        .collect();
+
+    (!scores.is_empty()).then_some(scores)
+}
+
+fn summarize(explanation: &MatchExplanation) -> String {
+    let mut parts = Vec::new();
+    if let (Some(rank), Some(similarity)) = (explanation.semantic_rank, explanation.similarity) {
+        parts.push(format!("semantic #{} (similarity {:.2})", rank, similarity));
+    }
+    if let Some(rank) = explanation.keyword_rank {
+        if explanation.matched_terms.is_empty() {
+            parts.push(format!("keyword #{}", rank));
+        } else {
+            parts.push(format!(
+                "keyword #{} matching {}",
+                rank,
+                explanation
+                    .matched_terms
+                    .iter()
+                    .map(|term| format!("`{}`", term))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+    if let Some(score) = explanation.rerank_score {
+        parts.push(format!("reranked {:.1}/10", score));
+    }
+    parts.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(id: &str, content: &str) -> EmbeddingMetadata {
+        let mut metadata =
+            EmbeddingMetadata::new("src/lib.rs".into(), 0, content.into(), "rust".into(), 1, 5);
+        metadata.id = id.to_string();
+        metadata
+    }
+
+    fn semantic(id: &str, similarity: f32) -> SearchResult {
+        SearchResult {
+            metadata: metadata(id, "fn parse_config() {}"),
+            similarity,
+        }
+    }
+
+    fn keyword(id: &str, score: f64) -> KeywordResult {
+        KeywordResult {
+            metadata: metadata(id, "fn parse_config() {}"),
+            score,
+        }
+    }
+
+    #[test]
+    fn test_fusion_rewards_agreement() {
+        let terms = vec!["parse_config".to_string()];
+        let results = fuse_results(
+            vec![semantic("a", 0.9), semantic("b", 0.8)],
+            vec![keyword("b", 4.0), keyword("c", 2.0)],
+            &terms,
+            &SearchOptions::default(),
+        );
+
+        let ids: Vec<&str> = results.iter().map(|r| r.metadata.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a", "c"]);
+        let top = &results[0].explanation;
+        assert_eq!((top.semantic_rank, top.keyword_rank), (Some(2), Some(1)));
+        assert_eq!(
+            top.summary,
+            "semantic #2 (similarity 0.80); keyword #1 matching `parse_config`"
+        );
+
+        let keyword_only = fuse_results(
+            vec![semantic("a", 0.9)],
+            vec![keyword("c", 2.0)],
+            &terms,
+            &SearchOptions {
+                mode: SearchMode::Keyword,
+                ..SearchOptions::default()
+            },
+        );
+        assert_eq!(keyword_only.len(), 1);
+        assert_eq!(keyword_only[0].metadata.id, "c");
+    }
+
+    #[test]
+    fn test_parse_rerank_scores() {
+        let response = "Sure:\n```json\n[{\"index\": 1, \"score\": 9}, {\"index\": 0, \"score\": 12.5}, {\"index\": 7, \"score\": 3}]\n```";
+        assert_eq!(
+            parse_rerank_scores(response, 2).unwrap(),
+            vec![(1, 9.0), (0, 10.0)]
+        );
+        assert!(parse_rerank_scores("no scores", 2).is_none());
+        assert!(parse_rerank_scores("[]", 2).is_none());
+    }
+}
@@ -11,6 +11,8 @@ use tokio::sync::Mutex;
 use walkdir::WalkDir;
 
 use super::{ChunkStrategy, CodeChunker, EmbeddingGenerator, EmbeddingMetadata, SimilaritySearch};
+use crate::cancellation::{self, Cancelled};
+use crate::filesystem::{portable_path, strip_extended, to_extended};
 
 /// Indexing progress
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,15 +67,29 @@ impl IncrementalIndexer {
         }
 
         for file_path in files {
-            self.index_file(&file_path).await?;
+            let indexed = match cancellation::check() {
+                Ok(()) => self.index_file(&file_path).await,
+                Err(cancelled) => Err(cancelled.into()),
+            };
+            if let Err(e) = indexed {
+                if e.is::<Cancelled>() {
+                    // Keep the files finished so far searchable
+                    self.similarity.lock().await.persist_ann_index()?;
+                    self.progress.lock().await.current_file = None;
+                }
+                return Err(e);
+            }
 
             {
                 let mut progress = self.progress.lock().await;
                 progress.indexed_files += 1;
-                progress.current_file = Some(file_path.to_string_lossy().to_string());
+                progress.current_file = Some(file_path.display().to_string());
             }
         }
 
+        // Save the vector index once rather than after every file
+        self.similarity.lock().await.persist_ann_index()?;
+
         {
             let mut progress = self.progress.lock().await;
             progress.is_complete = true;
@@ -85,40 +101,50 @@ impl IncrementalIndexer {
 
     /// Index a single file
     pub async fn index_file(&self, file_path: &Path) -> Result<()> {
-        let file_path_str = file_path.to_string_lossy().to_string();
+        // Stored losslessly so non-UTF8 names don't collide in the index
+        let file_path_str = portable_path(file_path);
 
-        tracing::info!("Indexing file: {}", file_path_str);
+        tracing::info!("Indexing file: {}", file_path.display());
 
         // Read file content
-        let content = tokio::fs::read_to_string(file_path)
+        let content = tokio::fs::read_to_string(to_extended(file_path))
             .await
             .context("Failed to read file")?;
 
         // Chunk the file
         let chunks = self.chunker.chunk_file(&file_path_str, &content)?;
 
+        // Generate everything before touching the store so a failed request
+        // leaves the previous index for this file intact. The generator stays
+        // locked until the write so a model switch cannot land in between.
         let generator = self.generator.lock().await;
-        let mut similarity = self.similarity.lock().await;
+        let items = {
+            let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+            let embeddings = cancellation::cancellable(generator.generate_batch(&texts)).await??;
+
+            embeddings
+                .into_iter()
+                .zip(chunks)
+                .map(|(embedding, chunk)| {
+                    let metadata = EmbeddingMetadata::new(
+                        chunk.file_path,
+                        chunk.index,
+                        chunk.content,
+                        chunk.language,
+                        chunk.start_line,
+                        chunk.end_line,
+                    );
+                    (embedding, metadata)
+                })
+                .collect::<Vec<_>>()
+        };
 
-        // Delete existing embeddings for this file
-        similarity.delete_file_embeddings(&file_path_str)?;
-
-        // Generate and store embeddings for each chunk
-        for chunk in chunks {
-            let embedding = generator.generate(&chunk.content).await?;
-
-            let metadata = EmbeddingMetadata::new(
-                chunk.file_path.clone(),
-                chunk.index,
-                chunk.content,
-                chunk.language,
-                chunk.start_line,
-                chunk.end_line,
-            );
-
-            let metadata_id = metadata.id.clone();
-            similarity.add_embedding(&metadata_id, embedding, metadata)?;
+        {
+            let mut similarity = self.similarity.lock().await;
+            similarity.delete_file_embeddings(&file_path_str)?;
+            similarity.add_embeddings_batch(&items)?;
         }
+        drop(generator);
 
         // Mark file as indexed
         {
@@ -141,9 +167,9 @@ impl IncrementalIndexer {
 
     /// Handle file deletion event
     pub async fn on_file_deleted(&self, file_path: &Path) -> Result<()> {
-        let file_path_str = file_path.to_string_lossy().to_string();
+        let file_path_str = portable_path(file_path);
 
-        tracing::info!("File deleted, removing embeddings: {}", file_path_str);
+        tracing::info!("File deleted, removing embeddings: {}", file_path.display());
 
         let mut similarity = self.similarity.lock().await;
         similarity.delete_file_embeddings(&file_path_str)?;
@@ -158,7 +184,7 @@ impl IncrementalIndexer {
     async fn discover_files(&self) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
 
-        for entry in WalkDir::new(&self.workspace_root)
+        for entry in WalkDir::new(to_extended(&self.workspace_root))
             .follow_links(false)
             .into_iter()
             .filter_entry(|e| !self.should_ignore(e.path()))
@@ -167,7 +193,7 @@ impl IncrementalIndexer {
             let path = entry.path();
 
             if path.is_file() && self.should_index_file(path) {
-                files.push(path.to_path_buf());
+                files.push(strip_extended(path));
             }
         }
 
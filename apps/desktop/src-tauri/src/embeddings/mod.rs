@@ -1,3 +1,4 @@
+pub mod ann;
 pub mod cache;
 pub mod chunker;
 /**
@@ -8,42 +9,83 @@ pub mod chunker;
  * - Primary: Ollama embeddings API (nomic-embed-text)
  * - Fallback: fastembed-rs (all-MiniLM-L6-v2) for offline support
  * - Storage: SQLite with custom vector similarity search
+ * - Large stores: HNSW approximate nearest neighbor index saved beside the database
+ * - Model switches: the store records its model and is re-embedded in the background
  */
 pub mod generator;
+pub mod hybrid;
 pub mod indexer;
+pub mod reembed;
 pub mod similarity;
 
+pub use ann::{HnswIndex, HnswParams};
 pub use cache::{CacheStats, EmbeddingCache};
 pub use chunker::{ChunkStrategy, CodeChunk, CodeChunker};
 pub use generator::{EmbeddingConfig, EmbeddingGenerator, EmbeddingModel};
+pub use hybrid::{HybridSearchResult, MatchExplanation, SearchMode, SearchOptions};
 pub use indexer::{IncrementalIndexer, IndexingProgress};
-pub use similarity::{cosine_similarity, SearchResult, SimilaritySearch};
+pub use reembed::ReembedStatus;
+pub use similarity::{
+    cosine_similarity, IndexModel, KeywordResult, SearchResult, SimilaritySearch,
+};
 
-use anyhow::Result;
+use crate::cancellation;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-/// Vector embedding (384 dimensions for all-MiniLM-L6-v2 / nomic-embed-text)
+/// Vector embedding; its size depends on the [`EmbeddingModel`]
 pub type Vector = Vec<f32>;
 
+/// Recorded for stores written before the model was tracked
+const UNKNOWN_MODEL: &str = "unknown";
+
 /// Embedding service state
 pub struct EmbeddingService {
     generator: Arc<Mutex<EmbeddingGenerator>>,
     similarity: Arc<Mutex<SimilaritySearch>>,
     cache: Arc<Mutex<EmbeddingCache>>,
     indexer: Arc<Mutex<IncrementalIndexer>>,
+    reembed: Arc<parking_lot::RwLock<Option<ReembedStatus>>>,
+    reembed_task: parking_lot::Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
 }
 
 impl EmbeddingService {
-    /// Create a new embedding service
-    pub async fn new(workspace_root: PathBuf, config: EmbeddingConfig) -> Result<Self> {
-        let generator = EmbeddingGenerator::new(config).await?;
+    /// Create a new embedding service.
+    ///
+    /// The model recorded with an existing store takes precedence over
+    /// `config.model`, so a switch survives restarts. An interrupted
+    /// re-embedding is resumed, and a store of unknown origin whose vectors
+    /// do not fit `config.model` is re-embedded with it.
+    pub async fn new(workspace_root: PathBuf, mut config: EmbeddingConfig) -> Result<Self> {
         let db_path = workspace_root.join(".agi").join("embeddings.db");
         std::fs::create_dir_all(db_path.parent().unwrap())?;
 
-        let similarity = SimilaritySearch::new(db_path.clone())?;
+        let mut similarity = SimilaritySearch::new(db_path.clone())?;
+        let mut migrate_to = None;
+        match similarity.index_model()? {
+            Some(index) => {
+                if let Some(model) = EmbeddingModel::from_id(&index.model) {
+                    config.model = model;
+                }
+                migrate_to = index
+                    .pending_model
+                    .as_deref()
+                    .and_then(EmbeddingModel::from_id);
+            }
+            None => match similarity.stored_dimensions()? {
+                Some(dimensions) if dimensions != config.model.dimensions() => {
+                    similarity.set_index_model(UNKNOWN_MODEL, dimensions)?;
+                    similarity.begin_reembed(config.model.id())?;
+                    migrate_to = Some(config.model);
+                }
+                _ => similarity.set_index_model(config.model.id(), config.model.dimensions())?,
+            },
+        }
+
+        let generator = EmbeddingGenerator::new(config.clone()).await?;
         let cache = EmbeddingCache::new(db_path)?;
 
         let generator_arc = Arc::new(Mutex::new(generator));
@@ -55,12 +97,98 @@ impl EmbeddingService {
             similarity_arc.clone(),
         );
 
-        Ok(Self {
+        let service = Self {
             generator: generator_arc,
             similarity: similarity_arc,
             cache: Arc::new(Mutex::new(cache)),
             indexer: Arc::new(Mutex::new(indexer)),
-        })
+            reembed: Arc::new(parking_lot::RwLock::new(None)),
+            reembed_task: parking_lot::Mutex::new(None),
+        };
+
+        if let Some(model) = migrate_to {
+            match EmbeddingGenerator::new(EmbeddingConfig { model, ..config }).await {
+                Ok(next) => service.start_reembed(next).await?,
+                Err(e) => tracing::warn!("Cannot resume re-embedding with {}: {}", model.id(), e),
+            }
+        }
+
+        Ok(service)
+    }
+
+    /// Switch to the model in `config`. Unless the store already holds that
+    /// model's vectors, every chunk is re-embedded in the background while
+    /// searches keep using the current model; the switch takes effect when
+    /// that completes.
+    pub async fn switch_model(&self, config: EmbeddingConfig) -> Result<()> {
+        let next = EmbeddingGenerator::new(config).await?;
+        let model = next.model();
+        // Fail now rather than halfway through the store
+        next.generate("embedding model check")
+            .await
+            .with_context(|| format!("{} is not usable", model.id()))?;
+
+        if let Some(task) = self.reembed_task.lock().take() {
+            task.abort();
+        }
+
+        let index = self.similarity.lock().await.index_model()?;
+        if index.as_ref().is_none_or(|index| index.model == model.id()) {
+            {
+                let mut similarity = self.similarity.lock().await;
+                match index {
+                    Some(_) => similarity.cancel_reembed()?,
+                    None => similarity.set_index_model(model.id(), model.dimensions())?,
+                }
+            }
+            *self.generator.lock().await = next;
+            *self.reembed.write() = None;
+            return Ok(());
+        }
+
+        self.similarity.lock().await.begin_reembed(model.id())?;
+        self.start_reembed(next).await
+    }
+
+    /// Progress of the current or last re-embedding
+    pub fn reembed_status(&self) -> Option<ReembedStatus> {
+        self.reembed.read().clone()
+    }
+
+    async fn start_reembed(&self, next: EmbeddingGenerator) -> Result<()> {
+        let (from_model, (done, total)) = {
+            let similarity = self.similarity.lock().await;
+            let from_model = similarity
+                .index_model()?
+                .map(|index| index.model)
+                .unwrap_or_else(|| UNKNOWN_MODEL.to_string());
+            (from_model, similarity.reembed_progress()?)
+        };
+        tracing::info!(
+            "Re-embedding {} chunks from {} with {}",
+            total,
+            from_model,
+            next.model().id()
+        );
+        *self.reembed.write() = Some(ReembedStatus {
+            from_model,
+            to_model: next.model().id().to_string(),
+            total,
+            done,
+            running: true,
+            error: None,
+        });
+
+        let task = tauri::async_runtime::spawn(reembed::run(
+            self.similarity.clone(),
+            self.generator.clone(),
+            next,
+            self.reembed.clone(),
+        ));
+        if let Some(previous) = self.reembed_task.lock().replace(task) {
+            previous.abort();
+        }
+        Ok(())
     }
 
     /// Get generator
@@ -124,10 +252,16 @@ impl EmbeddingMetadata {
 }
 
 /// Tauri commands for embeddings
+///
+/// Runs as a cancellable operation under `operation_id` when given. Every
+/// chunk is embedded before anything is stored, so a cancelled run leaves
+/// the index as it was.
 #[tauri::command]
 pub async fn generate_code_embeddings(
     file_path: String,
     content: String,
+    operation_id: Option<String>,
+    app: tauri::AppHandle,
     embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
 ) -> Result<usize, String> {
     let service = embedding_service.lock().await;
@@ -139,38 +273,48 @@ pub async fn generate_code_embeddings(
         .map_err(|e| format!("Failed to chunk file: {}", e))?;
 
     let generator = service.generator();
-    let generator_guard = generator.lock().await;
-
     let similarity = service.similarity();
-    let mut similarity_guard = similarity.lock().await;
-
-    // Generate embeddings for each chunk
-    let mut count = 0;
-    for chunk in chunks {
-        let embedding = generator_guard
-            .generate(&chunk.content)
-            .await
-            .map_err(|e| format!("Failed to generate embedding: {}", e))?;
-
-        let metadata = EmbeddingMetadata::new(
-            chunk.file_path,
-            chunk.index,
-            chunk.content,
-            chunk.language,
-            chunk.start_line,
-            chunk.end_line,
-        );
 
-        let metadata_id = metadata.id.clone();
+    let work = async {
+        let generator_guard = generator.lock().await;
+
+        // Generate embeddings for each chunk
+        let mut items = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            cancellation::check().map_err(|e| e.to_string())?;
+            let embedding = generator_guard
+                .generate(&chunk.content)
+                .await
+                .map_err(|e| format!("Failed to generate embedding: {}", e))?;
+
+            let metadata = EmbeddingMetadata::new(
+                chunk.file_path,
+                chunk.index,
+                chunk.content,
+                chunk.language,
+                chunk.start_line,
+                chunk.end_line,
+            );
+            items.push((embedding, metadata));
+        }
 
-        similarity_guard
-            .add_embedding(&metadata_id, embedding, metadata)
-            .map_err(|e| format!("Failed to store embedding: {}", e))?;
+        let mut similarity_guard = similarity.lock().await;
+        let count = items.len();
+        for (embedding, metadata) in items {
+            let metadata_id = metadata.id.clone();
+            similarity_guard
+                .add_embedding(&metadata_id, embedding, metadata)
+                .map_err(|e| format!("Failed to store embedding: {}", e))?;
+        }
 
-        count += 1;
-    }
+        Ok::<_, String>(count)
+    };
 
-    Ok(count)
+    cancellation::global_operations()
+        .begin("generate_code_embeddings", operation_id)
+        .run_with_events(&app, work)
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -203,6 +347,64 @@ pub async fn semantic_search_codebase(
     Ok(results)
 }
 
+/// Search combining vector similarity with BM25 keyword matches, optionally
+/// reranked by the LLM router
+#[tauri::command]
+pub async fn hybrid_search_codebase(
+    query: String,
+    options: Option<SearchOptions>,
+    embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
+    llm_state: tauri::State<'_, crate::commands::LLMState>,
+) -> Result<Vec<HybridSearchResult>, String> {
+    let options = options.unwrap_or_default();
+    let service = embedding_service.lock().await;
+
+    let semantic = if options.mode == SearchMode::Keyword {
+        Vec::new()
+    } else {
+        let generator = service.generator();
+        let generator_guard = generator.lock().await;
+        let query_embedding = generator_guard
+            .generate(&query)
+            .await
+            .map_err(|e| format!("Failed to generate query embedding: {}", e))?;
+        drop(generator_guard);
+
+        let similarity = service.similarity();
+        let similarity_guard = similarity.lock().await;
+        similarity_guard
+            .search(query_embedding, options.candidates)
+            .map_err(|e| format!("Failed to search: {}", e))?
+    };
+
+    let keyword = if options.mode == SearchMode::Semantic {
+        Vec::new()
+    } else {
+        let similarity = service.similarity();
+        let similarity_guard = similarity.lock().await;
+        similarity_guard
+            .keyword_search(&query, options.candidates)
+            .map_err(|e| format!("Failed to run keyword search: {}", e))?
+    };
+    drop(service);
+
+    let terms = similarity::keyword_terms(&query);
+    let mut results = hybrid::fuse_results(semantic, keyword, &terms, &options);
+
+    if options.rerank {
+        let router = llm_state.router.lock().await;
+        // Fused order is still useful if the model is unavailable
+        if let Err(e) =
+            hybrid::rerank_results(&router, &query, &mut results, options.rerank_top_n).await
+        {
+            tracing::warn!("Reranking failed, keeping fused order: {}", e);
+        }
+    }
+
+    results.truncate(options.limit);
+    Ok(results)
+}
+
 #[tauri::command]
 pub async fn get_embedding_stats(
     embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
@@ -215,6 +417,7 @@ pub async fn get_embedding_stats(
     let total_embeddings = similarity_guard
         .count_embeddings()
         .map_err(|e| format!("Failed to get embedding count: {}", e))?;
+    let ann_index_size = similarity_guard.ann_index_size();
 
     let cache = service.cache();
     let cache_guard = cache.lock().await;
@@ -227,6 +430,7 @@ pub async fn get_embedding_stats(
         cache_hits: cache_stats.hits,
         cache_misses: cache_stats.misses,
         cache_size: cache_stats.size,
+        ann_index_size,
     })
 }
 
@@ -236,10 +440,108 @@ pub struct EmbeddingStats {
     pub cache_hits: u64,
     pub cache_misses: u64,
     pub cache_size: usize,
+    /// Chunks in the approximate index; `None` while searches scan linearly
+    pub ann_index_size: Option<usize>,
 }
 
+/// Rebuild the approximate nearest neighbor index from every stored chunk,
+/// e.g. after switching embedding models. Returns the number of indexed chunks.
+#[tauri::command]
+pub async fn rebuild_embedding_index(
+    embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
+) -> Result<usize, String> {
+    let similarity = {
+        let service = embedding_service.lock().await;
+        service.similarity()
+    };
+    let mut similarity_guard = similarity.lock().await;
+
+    similarity_guard
+        .rebuild_ann_index()
+        .map_err(|e| format!("Failed to rebuild vector index: {}", e))
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingModelInfo {
+    pub model: EmbeddingModel,
+    pub dimensions: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingModelStatus {
+    /// Model used for queries and new chunks
+    pub active_model: EmbeddingModel,
+    /// Model the stored vectors came from
+    pub index: Option<IndexModel>,
+    pub reembed: Option<ReembedStatus>,
+    pub available: Vec<EmbeddingModelInfo>,
+}
+
+#[tauri::command]
+pub async fn get_embedding_model_status(
+    embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
+) -> Result<EmbeddingModelStatus, String> {
+    let service = embedding_service.lock().await;
+    let active_model = service.generator().lock().await.model();
+    let index = service
+        .similarity()
+        .lock()
+        .await
+        .index_model()
+        .map_err(|e| format!("Failed to read index model: {}", e))?;
+
+    Ok(EmbeddingModelStatus {
+        active_model,
+        index,
+        reembed: service.reembed_status(),
+        available: EmbeddingModel::ALL
+            .into_iter()
+            .map(|model| EmbeddingModelInfo {
+                model,
+                dimensions: model.dimensions(),
+            })
+            .collect(),
+    })
+}
+
+/// Switch embedding models, re-embedding the workspace index in the
+/// background when it was built with another model. OpenAI models use the
+/// OpenAI key stored in settings.
+#[tauri::command]
+pub async fn set_embedding_model(
+    model: EmbeddingModel,
+    embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
+    settings_state: tauri::State<'_, crate::commands::SettingsServiceState>,
+) -> Result<(), String> {
+    let openai_api_key = if model.openai_model_name().is_some() {
+        let key = settings_state
+            .service
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .get_api_key("openai")
+            .map_err(|_| "No OpenAI API key configured".to_string())?;
+        Some(crate::security::SecretString::new(key))
+    } else {
+        None
+    };
+
+    let service = embedding_service.lock().await;
+    service
+        .switch_model(EmbeddingConfig {
+            model,
+            openai_api_key,
+            ..EmbeddingConfig::default()
+        })
+        .await
+        .map_err(|e| format!("Failed to switch embedding model: {}", e))
+}
+
+/// Index the whole workspace. Runs as a cancellable operation under
+/// `operation_id` when given; files indexed before a cancel stay indexed.
 #[tauri::command]
 pub async fn index_workspace(
+    operation_id: Option<String>,
+    app: tauri::AppHandle,
     embedding_service: tauri::State<'_, Arc<Mutex<EmbeddingService>>>,
 ) -> Result<(), String> {
     let indexer = {
@@ -248,9 +550,11 @@ pub async fn index_workspace(
     };
     let indexer_guard = indexer.lock().await;
 
-    indexer_guard
-        .index_workspace()
+    cancellation::global_operations()
+        .begin("index_workspace", operation_id)
+        .run_with_events(&app, indexer_guard.index_workspace())
         .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| format!("Failed to index workspace: {}", e))
 }
 
@@ -0,0 +1,112 @@
+/**
+ * Model Migration
+ * Re-embeds the whole store in the background after the embedding model changes
+ */
+use anyhow::Result;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{EmbeddingGenerator, SimilaritySearch};
+
+/// Chunks embedded per step; the store is unlocked between steps so
+/// searches and indexing carry on with the old model
+const REEMBED_BATCH: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReembedStatus {
+    pub from_model: String,
+    pub to_model: String,
+    /// Chunks in the store
+    pub total: usize,
+    /// Chunks that already have a vector from the new model
+    pub done: usize,
+    pub running: bool,
+    pub error: Option<String>,
+}
+
+/// Re-embed every chunk with `next`, then switch the store and `current`
+/// over to it. Progress and failures are reported through `status`; a failed
+/// run keeps its staged vectors and picks up from there next time.
+pub async fn run(
+    similarity: Arc<Mutex<SimilaritySearch>>,
+    current: Arc<Mutex<EmbeddingGenerator>>,
+    next: EmbeddingGenerator,
+    status: Arc<RwLock<Option<ReembedStatus>>>,
+) {
+    let to_model = next.model().id();
+    let result = migrate(&similarity, &current, next, &status).await;
+
+    let mut status = status.write();
+    let Some(status) = status.as_mut() else {
+        return;
+    };
+    status.running = false;
+    match result {
+        Ok(()) => {
+            status.done = status.total;
+            tracing::info!("Re-embedded {} chunks with {}", status.total, to_model);
+        }
+        Err(e) => {
+            tracing::warn!("Re-embedding with {} failed: {}", to_model, e);
+            status.error = Some(e.to_string());
+        }
+    }
+}
+
+async fn migrate(
+    similarity: &Mutex<SimilaritySearch>,
+    current: &Mutex<EmbeddingGenerator>,
+    next: EmbeddingGenerator,
+    status: &RwLock<Option<ReembedStatus>>,
+) -> Result<()> {
+    loop {
+        let pending = similarity.lock().await.reembed_pending(REEMBED_BATCH)?;
+        if pending.is_empty() {
+            break;
+        }
+        let items = embed(&next, pending).await?;
+
+        let mut search = similarity.lock().await;
+        search.stage_reembedded(next.model().id(), &items, next.dimensions())?;
+        let (done, total) = search.reembed_progress()?;
+        if let Some(status) = status.write().as_mut() {
+            status.done = done;
+            status.total = total;
+        }
+    }
+
+    // Writers hold the generator while they embed and store, so with both
+    // locks taken nothing old-model can land between the last batch and the swap
+    let mut current = current.lock().await;
+    let mut search = similarity.lock().await;
+    loop {
+        let pending = search.reembed_pending(REEMBED_BATCH)?;
+        if pending.is_empty() {
+            break;
+        }
+        let items = embed(&next, pending).await?;
+        search.stage_reembedded(next.model().id(), &items, next.dimensions())?;
+    }
+
+    search.finish_reembed(next.model().id(), next.dimensions())?;
+    *current = next;
+    Ok(())
+}
+
+async fn embed(
+    generator: &EmbeddingGenerator,
+    pending: Vec<(String, String)>,
+) -> Result<Vec<(String, Vec<f32>)>> {
+    let texts: Vec<&str> = pending
+        .iter()
+        .map(|(_, content)| content.as_str())
+        .collect();
+    let embeddings = generator.generate_batch(&texts).await?;
+    Ok(pending
+        .into_iter()
+        .map(|(id, _)| id)
+        .zip(embeddings)
+        .collect())
+}
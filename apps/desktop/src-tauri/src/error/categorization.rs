@@ -1,4 +1,6 @@
 use super::{AGIError, LLMError, ResourceError, ToolError};
+use crate::router::ProviderHttpError;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
 /// Error category for determining retry and recovery strategies
@@ -53,6 +55,7 @@ impl Categorizable for AGIError {
             AGIError::EmailSend(_) => ErrorCategory::Transient,
             AGIError::EmailParse(_) => ErrorCategory::Permanent,
             AGIError::InvalidPath(_) => ErrorCategory::Permanent,
+            AGIError::Cancelled => ErrorCategory::Permanent,
         }
     }
 
@@ -210,6 +213,7 @@ impl Categorizable for LLMError {
             LLMError::ApiError(_) => ErrorCategory::Transient,
             LLMError::NetworkError(_) => ErrorCategory::Transient,
             LLMError::InvalidResponse(_) => ErrorCategory::Transient,
+            LLMError::InvalidRequest(_) => ErrorCategory::Permanent,
             LLMError::ModelNotAvailable(_) => ErrorCategory::Configuration,
             LLMError::AuthenticationError(_) => ErrorCategory::Configuration,
             LLMError::Timeout(_) => ErrorCategory::Transient,
@@ -234,6 +238,9 @@ impl Categorizable for LLMError {
             LLMError::ContentFilterError(_) => {
                 "Content filtered by LLM provider. Please rephrase your input.".to_string()
             }
+            LLMError::InvalidRequest(_) => {
+                "LLM provider rejected the request. Please check its parameters.".to_string()
+            }
             LLMError::NetworkError(_) => "Network error connecting to LLM. Retrying...".to_string(),
             LLMError::ModelNotAvailable(_) => {
                 "LLM model not available. Please check your configuration or choose a different model.".to_string()
@@ -297,6 +304,65 @@ impl Categorizable for ResourceError {
     }
 }
 
+/// Map a provider failure onto an LLMError from its type and HTTP status so
+/// callers can decide whether trying another provider is worthwhile
+pub fn classify_llm_error(error: &(dyn std::error::Error + 'static)) -> LLMError {
+    let message = error.to_string();
+    let mut current = Some(error);
+    while let Some(err) = current {
+        if let Some(llm_error) = err.downcast_ref::<LLMError>() {
+            return llm_error.clone();
+        }
+        if let Some(http) = err.downcast_ref::<ProviderHttpError>() {
+            return classify_status(http.status, &http.body, message);
+        }
+        if let Some(request) = err.downcast_ref::<reqwest::Error>() {
+            return if request.is_timeout() {
+                LLMError::Timeout(message)
+            } else if let Some(status) = request.status() {
+                classify_status(status, "", message)
+            } else if request.is_decode() {
+                LLMError::InvalidResponse(message)
+            } else {
+                LLMError::NetworkError(message)
+            };
+        }
+        if err.is::<serde_json::Error>() {
+            return LLMError::InvalidResponse(message);
+        }
+        current = err.source();
+    }
+    LLMError::ApiError(message)
+}
+
+fn classify_status(status: StatusCode, body: &str, message: String) -> LLMError {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => LLMError::AuthenticationError(message),
+        StatusCode::NOT_FOUND => LLMError::ModelNotAvailable(message),
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => LLMError::Timeout(message),
+        StatusCode::PAYLOAD_TOO_LARGE => LLMError::ContextLengthError(message),
+        StatusCode::TOO_MANY_REQUESTS => LLMError::RateLimitError(message),
+        StatusCode::BAD_REQUEST => match error_code(body).as_deref() {
+            Some("context_length_exceeded") => LLMError::ContextLengthError(message),
+            Some("content_filter") | Some("content_policy_violation") => {
+                LLMError::ContentFilterError(message)
+            }
+            _ => LLMError::InvalidRequest(message),
+        },
+        StatusCode::UNPROCESSABLE_ENTITY => LLMError::InvalidRequest(message),
+        _ => LLMError::ApiError(message),
+    }
+}
+
+/// The `error.code` field of an OpenAI-style error body
+fn error_code(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value
+        .pointer("/error/code")
+        .and_then(|code| code.as_str())
+        .map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -340,4 +406,44 @@ mod tests {
         let error = AGIError::PermissionError("access denied".to_string());
         assert!(error.suggested_action().contains("Permission"));
     }
+
+    fn http_error(status: StatusCode, body: &str) -> ProviderHttpError {
+        ProviderHttpError {
+            provider: "OpenAI",
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_classify_llm_error() {
+        let error = classify_llm_error(&http_error(StatusCode::TOO_MANY_REQUESTS, ""));
+        assert_eq!(error.category(), ErrorCategory::ResourceLimit);
+
+        let error = classify_llm_error(&http_error(StatusCode::UNAUTHORIZED, ""));
+        assert!(matches!(error, LLMError::AuthenticationError(_)));
+        assert!(!error.is_retryable());
+
+        let error = classify_llm_error(&http_error(StatusCode::NOT_FOUND, ""));
+        assert!(matches!(error, LLMError::ModelNotAvailable(_)));
+
+        let body = r#"{"error":{"code":"context_length_exceeded","message":"too long"}}"#;
+        let error = classify_llm_error(&http_error(StatusCode::BAD_REQUEST, body));
+        assert!(matches!(error, LLMError::ContextLengthError(_)));
+
+        let error = classify_llm_error(&http_error(StatusCode::BAD_REQUEST, "{}"));
+        assert!(matches!(error, LLMError::InvalidRequest(_)));
+        assert!(!error.is_retryable());
+
+        let error = classify_llm_error(&http_error(StatusCode::SERVICE_UNAVAILABLE, ""));
+        assert!(error.is_retryable());
+
+        // Message text alone no longer decides the category
+        let error = anyhow::anyhow!("401 Unauthorized");
+        assert!(matches!(classify_llm_error(&*error), LLMError::ApiError(_)));
+
+        let error = anyhow::Error::new(LLMError::Timeout("slow".to_string()))
+            .context("after failover from anthropic");
+        assert!(matches!(classify_llm_error(&*error), LLMError::Timeout(_)));
+    }
 }
@@ -19,8 +19,9 @@ where
 {
     // Select appropriate retry policy based on tool type
     let policy = match tool_name {
-        "browser_navigate" | "browser_click" | "browser_extract" => RetryPolicy::browser(),
-        "api_call" | "api_upload" | "api_download" => RetryPolicy::network(),
+        "browser_navigate" | "browser_click" | "browser_type" | "browser_select"
+        | "browser_check" | "browser_press_key" | "browser_extract" => RetryPolicy::browser(),
+        "api_call" | "api_upload" | "api_download" | "web_fetch" => RetryPolicy::network(),
         "db_query" | "db_execute" => RetryPolicy::database(),
         "file_read" | "file_write" => RetryPolicy::filesystem(),
         "llm_reason" => RetryPolicy::llm(),
@@ -63,10 +64,11 @@ pub fn convert_tool_error(tool_name: &str, error: impl std::error::Error) -> AGI
     let error_msg = error.to_string();
 
     match tool_name {
-        "browser_navigate" | "browser_click" | "browser_extract" => {
+        "browser_navigate" | "browser_click" | "browser_type" | "browser_select"
+        | "browser_check" | "browser_press_key" | "browser_extract" => {
             AGIError::ToolError(ToolError::BrowserError(error_msg))
         }
-        "api_call" | "api_upload" | "api_download" => {
+        "api_call" | "api_upload" | "api_download" | "web_fetch" => {
             AGIError::ToolError(ToolError::ApiError(error_msg))
         }
         "db_query" | "db_execute" => AGIError::ToolError(ToolError::DatabaseError(error_msg)),
@@ -8,7 +8,7 @@ pub mod integration;
 pub mod recovery;
 pub mod retry;
 
-pub use categorization::{Categorizable, ErrorCategory};
+pub use categorization::{classify_llm_error, Categorizable, ErrorCategory};
 pub use commands::{ErrorContextResponse, ErrorContextStore};
 pub use integration::{
     convert_tool_error, emit_error_event, execute_tool_with_recovery, EnhancedExecutionContext,
@@ -76,6 +76,9 @@ pub enum AGIError {
 
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 impl From<rusqlite::Error> for AGIError {
@@ -147,6 +150,9 @@ pub enum LLMError {
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
     #[error("Model not available: {0}")]
     ModelNotAvailable(String),
 
@@ -1,5 +1,9 @@
+pub mod paths;
 pub mod search;
 pub mod watcher;
 
+pub use paths::{
+    decode_path, encode_path, portable_path, resolve_path, strip_extended, to_extended,
+};
 pub use search::*;
 pub use watcher::{FileEvent, FileWatcher};
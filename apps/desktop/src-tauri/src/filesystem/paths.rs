@@ -0,0 +1,335 @@
+//! Path handling that survives long Windows paths and non-UTF8 file names.
+//!
+//! Filesystem calls go through [`to_extended`], which switches absolute
+//! Windows paths near `MAX_PATH` to the `\\?\` form. Paths that cross into the
+//! database or the frontend are stored with [`encode_path`], which keeps UTF-8
+//! paths readable and hex-encodes anything else so it can be decoded back to
+//! the exact same `OsString`.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+/// Win32 APIs reject paths at 260 characters; switch early so that joining a
+/// file name onto a long directory still works
+pub const LONG_PATH_THRESHOLD: usize = 240;
+
+/// Marks an encoded path. Control characters are invalid in Windows file
+/// names and effectively never used on Unix, and UTF-8 paths that do start
+/// with it are encoded as well, so decoding is always unambiguous.
+const ENCODED_MARKER: char = '\u{1}';
+
+/// Encode a path as a string without losing non-UTF8 bytes
+pub fn encode_path(path: &Path) -> String {
+    match path.to_str() {
+        Some(text) if !text.starts_with(ENCODED_MARKER) => text.to_string(),
+        _ => format!("{}{}", ENCODED_MARKER, hex::encode(os_bytes(path))),
+    }
+}
+
+/// Inverse of [`encode_path`]; plain strings are taken as-is
+pub fn decode_path(encoded: &str) -> PathBuf {
+    encoded
+        .strip_prefix(ENCODED_MARKER)
+        .and_then(|hex_bytes| hex::decode(hex_bytes).ok())
+        .and_then(|bytes| os_string_from_bytes(&bytes))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(encoded))
+}
+
+/// Decode a path received from the frontend and prepare it for filesystem calls
+pub fn resolve_path(path: &str) -> PathBuf {
+    to_extended(&decode_path(path))
+}
+
+/// Encoding used for paths returned to the frontend or written to the database
+pub fn portable_path(path: &Path) -> String {
+    encode_path(&strip_extended(path))
+}
+
+/// Serde adapter that writes paths with [`portable_path`] instead of failing
+/// on non-UTF8 names; use `portable_paths::single` for a lone `PathBuf`
+pub mod portable_paths {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::path::PathBuf;
+
+    pub fn serialize<S: Serializer>(paths: &[PathBuf], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(paths.iter().map(|path| super::portable_path(path)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<PathBuf>, D::Error> {
+        let encoded = Vec::<String>::deserialize(deserializer)?;
+        Ok(encoded
+            .iter()
+            .map(|path| super::decode_path(path))
+            .collect())
+    }
+
+    pub mod single {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use std::path::{Path, PathBuf};
+
+        pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&super::super::portable_path(path))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<PathBuf, D::Error> {
+            let encoded = String::deserialize(deserializer)?;
+            Ok(super::super::decode_path(&encoded))
+        }
+    }
+}
+
+/// Use the `\\?\` extended-length form for long absolute Windows paths.
+///
+/// Extended paths skip Win32 normalisation, so `.` and `..` are resolved
+/// lexically first. Short, relative-to-drive and device paths are unchanged.
+#[cfg(windows)]
+pub fn to_extended(path: &Path) -> PathBuf {
+    use std::path::Prefix;
+
+    if path.as_os_str().len() < LONG_PATH_THRESHOLD {
+        return path.to_path_buf();
+    }
+    if path.is_relative() {
+        return match std::path::absolute(path) {
+            Ok(absolute) if absolute.is_absolute() => to_extended(&absolute),
+            _ => path.to_path_buf(),
+        };
+    }
+
+    let mut components = path.components();
+    let prefix = match components.next() {
+        Some(Component::Prefix(prefix)) => prefix,
+        _ => return path.to_path_buf(),
+    };
+    if components.next() != Some(Component::RootDir) {
+        return path.to_path_buf();
+    }
+
+    let mut extended = match prefix.kind() {
+        Prefix::Disk(letter) => OsString::from(format!(r"\\?\{}:\", letter as char)),
+        Prefix::UNC(server, share) => {
+            let mut unc = OsString::from(r"\\?\UNC\");
+            unc.push(server);
+            unc.push(r"\");
+            unc.push(share);
+            unc.push(r"\");
+            unc
+        }
+        // Already verbatim or a device path
+        _ => return path.to_path_buf(),
+    };
+
+    let relative = normalize_lexically(components);
+    extended.push(relative.as_os_str());
+    PathBuf::from(extended)
+}
+
+#[cfg(not(windows))]
+pub fn to_extended(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Drop a `\\?\` prefix added by [`to_extended`] so paths display normally
+#[cfg(windows)]
+pub fn strip_extended(path: &Path) -> PathBuf {
+    use std::path::Prefix;
+
+    let mut components = path.components();
+    let Some(Component::Prefix(prefix)) = components.next() else {
+        return path.to_path_buf();
+    };
+
+    let mut plain = match prefix.kind() {
+        Prefix::VerbatimDisk(letter) => OsString::from(format!("{}:", letter as char)),
+        Prefix::VerbatimUNC(server, share) => {
+            let mut unc = OsString::from(r"\\");
+            unc.push(server);
+            unc.push(r"\");
+            unc.push(share);
+            unc
+        }
+        _ => return path.to_path_buf(),
+    };
+    plain.push(components.as_path().as_os_str());
+    PathBuf::from(plain)
+}
+
+#[cfg(not(windows))]
+pub fn strip_extended(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+#[cfg_attr(not(windows), allow(dead_code))]
+fn normalize_lexically<'a>(components: impl Iterator<Item = Component<'a>>) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in components {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(part) => normalized.push(part),
+            Component::Prefix(_) | Component::RootDir => {}
+        }
+    }
+    normalized
+}
+
+#[cfg(unix)]
+fn os_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: &[u8]) -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(OsString::from_vec(bytes.to_vec()))
+}
+
+/// UTF-16 code units, little endian, unpaired surrogates included
+#[cfg(windows)]
+fn os_bytes(path: &Path) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str()
+        .encode_wide()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect()
+}
+
+#[cfg(windows)]
+fn os_string_from_bytes(bytes: &[u8]) -> Option<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    Some(OsString::from_wide(&units))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_string_from_bytes(bytes: &[u8]) -> Option<OsString> {
+    String::from_utf8(bytes.to_vec()).ok().map(OsString::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::fs;
+
+    /// File name segments mixing ASCII, spaces and non-Latin scripts
+    fn segment() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9 _é漢字ß🙂-]{1,12}".prop_filter("no edge spaces or dots", |s| {
+            !s.starts_with(' ') && !s.ends_with(' ') && !s.ends_with('.')
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn unicode_paths_round_trip(text in "\\PC{0,200}") {
+            let path = PathBuf::from(&text);
+            prop_assert_eq!(decode_path(&encode_path(&path)), path);
+        }
+
+        #[test]
+        fn plain_utf8_paths_are_stored_verbatim(text in "[^\u{1}]\\PC{0,100}") {
+            prop_assert_eq!(encode_path(Path::new(&text)), text);
+        }
+    }
+
+    #[cfg(unix)]
+    proptest! {
+        #[test]
+        fn arbitrary_unix_bytes_round_trip(bytes in proptest::collection::vec(1u8..=255, 1..128)) {
+            use std::os::unix::ffi::OsStringExt;
+            let path = PathBuf::from(OsString::from_vec(bytes));
+            prop_assert_eq!(decode_path(&encode_path(&path)), path);
+        }
+    }
+
+    #[cfg(windows)]
+    proptest! {
+        #[test]
+        fn arbitrary_wide_strings_round_trip(units in proptest::collection::vec(1u16..=u16::MAX, 1..128)) {
+            use std::os::windows::ffi::OsStringExt;
+            let path = PathBuf::from(OsString::from_wide(&units));
+            prop_assert_eq!(decode_path(&encode_path(&path)), path);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(12))]
+
+        #[test]
+        fn deep_trees_with_unicode_names(segments in proptest::collection::vec(segment(), 20..40)) {
+            let root = tempfile::tempdir().unwrap();
+            let mut dir = root.path().to_path_buf();
+            for segment in &segments {
+                dir.push(segment);
+            }
+            let file = dir.join("notes 📝.txt");
+
+            fs::create_dir_all(to_extended(&dir)).unwrap();
+            fs::write(to_extended(&file), "deep").unwrap();
+
+            let stored = portable_path(&file);
+            prop_assert_eq!(fs::read_to_string(resolve_path(&stored)).unwrap(), "deep");
+
+            let found = walkdir::WalkDir::new(to_extended(root.path()))
+                .into_iter()
+                .filter_map(Result::ok)
+                .find(|entry| entry.file_type().is_file())
+                .map(|entry| strip_extended(entry.path()));
+            prop_assert_eq!(found, Some(file));
+        }
+    }
+
+    #[test]
+    fn marker_prefixed_utf8_is_encoded() {
+        let path = PathBuf::from("\u{1}not-hex");
+        let encoded = encode_path(&path);
+        assert_ne!(encoded, "\u{1}not-hex");
+        assert_eq!(decode_path(&encoded), path);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_windows_paths_use_extended_form() {
+        let long = format!(r"C:\work\{}\.\sub\..\file.txt", "a".repeat(260));
+        let extended = to_extended(Path::new(&long));
+        assert_eq!(
+            extended,
+            PathBuf::from(format!(r"\\?\C:\work\{}\file.txt", "a".repeat(260)))
+        );
+        assert_eq!(
+            strip_extended(&extended),
+            PathBuf::from(format!(r"C:\work\{}\file.txt", "a".repeat(260)))
+        );
+
+        let unc = format!(r"\\server\share\{}", "b".repeat(260));
+        assert_eq!(
+            to_extended(Path::new(&unc)),
+            PathBuf::from(format!(r"\\?\UNC\server\share\{}", "b".repeat(260)))
+        );
+
+        assert_eq!(
+            to_extended(Path::new(r"C:\short")),
+            PathBuf::from(r"C:\short")
+        );
+    }
+}
@@ -7,6 +7,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use super::paths::{portable_path, strip_extended, to_extended};
+
 /// Search for files matching a query
 #[tauri::command]
 pub async fn fs_search_files(query: String, limit: usize) -> Result<Vec<String>, String> {
@@ -36,7 +38,7 @@ fn search_files_blocking(root: &Path, query: &str, limit: usize) -> Result<Vec<S
     let mut results = Vec::new();
     let query_lower = query.to_lowercase();
 
-    for entry in WalkDir::new(root)
+    for entry in WalkDir::new(to_extended(root))
         .max_depth(5) // Limit depth for performance
         .follow_links(false)
         .into_iter()
@@ -53,12 +55,12 @@ fn search_files_blocking(root: &Path, query: &str, limit: usize) -> Result<Vec<S
             continue;
         }
 
-        let path = entry.path();
+        let path = strip_extended(entry.path());
         let path_str = path.to_string_lossy();
 
         // Match query against filename or full path
         if query_lower.is_empty() {
-            results.push(path_str.to_string());
+            results.push(portable_path(&path));
         } else {
             let file_name = path
                 .file_name()
@@ -67,7 +69,7 @@ fn search_files_blocking(root: &Path, query: &str, limit: usize) -> Result<Vec<S
                 .to_lowercase();
 
             if file_name.contains(&query_lower) || path_str.to_lowercase().contains(&query_lower) {
-                results.push(path_str.to_string());
+                results.push(portable_path(&path));
             }
         }
     }
@@ -103,7 +105,7 @@ fn search_folders_blocking(root: &Path, query: &str, limit: usize) -> Result<Vec
     let mut results = Vec::new();
     let query_lower = query.to_lowercase();
 
-    for entry in WalkDir::new(root)
+    for entry in WalkDir::new(to_extended(root))
         .max_depth(5)
         .follow_links(false)
         .into_iter()
@@ -120,12 +122,12 @@ fn search_folders_blocking(root: &Path, query: &str, limit: usize) -> Result<Vec
             continue;
         }
 
-        let path = entry.path();
+        let path = strip_extended(entry.path());
         let path_str = path.to_string_lossy();
 
         // Match query against folder name or full path
         if query_lower.is_empty() {
-            results.push(path_str.to_string());
+            results.push(portable_path(&path));
         } else {
             let folder_name = path
                 .file_name()
@@ -135,7 +137,7 @@ fn search_folders_blocking(root: &Path, query: &str, limit: usize) -> Result<Vec
 
             if folder_name.contains(&query_lower) || path_str.to_lowercase().contains(&query_lower)
             {
-                results.push(path_str.to_string());
+                results.push(portable_path(&path));
             }
         }
     }
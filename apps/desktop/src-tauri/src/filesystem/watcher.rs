@@ -6,14 +6,21 @@ use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter};
 use tracing::{debug, error, info, warn};
 
+use super::paths::{decode_path, portable_path, portable_paths};
+
 /// File event type for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "paths")]
 pub enum FileEvent {
-    Created(Vec<PathBuf>),
-    Modified(Vec<PathBuf>),
-    Deleted(Vec<PathBuf>),
-    Renamed { from: PathBuf, to: PathBuf },
+    Created(#[serde(with = "portable_paths")] Vec<PathBuf>),
+    Modified(#[serde(with = "portable_paths")] Vec<PathBuf>),
+    Deleted(#[serde(with = "portable_paths")] Vec<PathBuf>),
+    Renamed {
+        #[serde(with = "portable_paths::single")]
+        from: PathBuf,
+        #[serde(with = "portable_paths::single")]
+        to: PathBuf,
+    },
 }
 
 /// File watcher state
@@ -57,6 +64,8 @@ impl FileWatcher {
                         }
                     };
 
+                    crate::codebase::dependency_graph::apply_file_event(&app_handle, &file_event);
+
                     // Emit event to frontend
                     if let Err(e) = app_handle.emit("file-event", &file_event) {
                         error!("Failed to emit file event: {}", e);
@@ -77,7 +86,7 @@ impl FileWatcher {
 
     /// Start watching a path
     pub fn watch(&mut self, path: &str, recursive: bool) -> Result<(), String> {
-        let path_buf = PathBuf::from(path);
+        let path_buf = decode_path(path);
 
         if !path_buf.exists() {
             return Err(format!("Path does not exist: {}", path));
@@ -106,7 +115,7 @@ impl FileWatcher {
 
     /// Stop watching a path
     pub fn unwatch(&mut self, path: &str) -> Result<(), String> {
-        let path_buf = PathBuf::from(path);
+        let path_buf = decode_path(path);
 
         self.watcher
             .unwatch(&path_buf)
@@ -130,10 +139,7 @@ impl FileWatcher {
             .lock()
             .map_err(|e| format!("Failed to lock watched paths: {}", e))?;
 
-        Ok(watched
-            .keys()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect())
+        Ok(watched.keys().map(|p| portable_path(p)).collect())
     }
 
     /// Stop watching all paths
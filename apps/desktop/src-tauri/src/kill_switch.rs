@@ -0,0 +1,201 @@
+//! Workspace-wide kill switch.
+//!
+//! Engaging it stops every kind of automation the app can be running:
+//! the AGI loop, orchestrator agents, workflow executions, background tasks,
+//! computer-use sessions and automation browsers. While engaged, automation
+//! entry points refuse to start anything until someone explicitly re-arms it.
+//! Both transitions are written to the signed audit log together with who or
+//! what triggered them (a user command, the tray menu, the global shortcut).
+
+use crate::commands::{
+    AppDatabase, BrowserStateWrapper, ComputerUseState, TaskManagerState, WorkflowEngineState,
+};
+use crate::security::{AuditEvent, AuditEventType, AuditStatus, EnhancedAuditLogger};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex as TokioMutex;
+
+pub const KILL_SWITCH_EVENT: &str = "automation://kill-switch";
+
+static ENGAGED: AtomicBool = AtomicBool::new(false);
+static LAST_CHANGE: Mutex<Option<KillSwitchChange>> = Mutex::new(None);
+
+/// Who flipped the switch and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchChange {
+    pub engaged: bool,
+    pub initiator: String,
+    pub reason: Option<String>,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchStatus {
+    pub engaged: bool,
+    pub last_change: Option<KillSwitchChange>,
+}
+
+/// What engaging the switch actually stopped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchReport {
+    pub agi_stopped: bool,
+    pub agents_cancelled: bool,
+    pub workflows_cancelled: usize,
+    pub tasks_cancelled: usize,
+    pub computer_use_sessions_ended: usize,
+    pub browsers_closed: usize,
+    /// Long-running commands (LLM calls, indexing, extraction) cancelled
+    pub operations_cancelled: usize,
+    /// Subsystems that could not be stopped cleanly
+    pub errors: Vec<String>,
+}
+
+pub fn is_engaged() -> bool {
+    ENGAGED.load(Ordering::SeqCst)
+}
+
+pub fn status() -> KillSwitchStatus {
+    KillSwitchStatus {
+        engaged: is_engaged(),
+        last_change: LAST_CHANGE.lock().clone(),
+    }
+}
+
+/// Gate for automation entry points: refuses while in safe mode or while the
+/// kill switch is engaged
+pub fn ensure_allowed(action: &str) -> Result<(), String> {
+    crate::safe_mode::ensure_allowed(action)?;
+    if is_engaged() {
+        Err(format!(
+            "{} is blocked: the automation kill switch is engaged. Re-arm it to continue.",
+            action
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn record_change(engaged: bool, initiator: &str, reason: Option<String>) -> KillSwitchChange {
+    let change = KillSwitchChange {
+        engaged,
+        initiator: initiator.to_string(),
+        reason,
+        at: chrono::Utc::now().timestamp(),
+    };
+    ENGAGED.store(engaged, Ordering::SeqCst);
+    *LAST_CHANGE.lock() = Some(change.clone());
+    change
+}
+
+/// Stop all automation. Engaging twice is harmless; the second call sweeps
+/// anything that started in between.
+pub async fn engage(app: &AppHandle, initiator: &str, reason: Option<String>) -> KillSwitchReport {
+    // Flip first so nothing new starts while we tear down
+    let change = record_change(true, initiator, reason);
+    tracing::warn!(initiator, "Automation kill switch engaged");
+
+    let mut report = KillSwitchReport::default();
+
+    match crate::commands::agi_stop().await {
+        Ok(()) => report.agi_stopped = true,
+        Err(e) => report.errors.push(format!("agi: {}", e)),
+    }
+
+    // Errors only when the orchestrator was never initialised, i.e. no agents
+    report.agents_cancelled = crate::commands::orchestrator_cancel_all().await.is_ok();
+
+    if let Some(state) = app.try_state::<WorkflowEngineState>() {
+        match state.engine.cancel_active_executions() {
+            Ok(count) => report.workflows_cancelled = count,
+            Err(e) => report.errors.push(format!("workflows: {}", e)),
+        }
+    }
+
+    report.operations_cancelled = crate::cancellation::global_operations().cancel_all();
+
+    if let Some(state) = app.try_state::<TaskManagerState>() {
+        report.tasks_cancelled = state.0.cancel_all().await;
+    }
+
+    if let Some(state) = app.try_state::<Arc<TokioMutex<ComputerUseState>>>() {
+        let computer_use = state.lock().await;
+        if computer_use.current_session.lock().await.take().is_some() {
+            report.computer_use_sessions_ended = 1;
+        }
+    }
+
+    if let Some(state) = app.try_state::<BrowserStateWrapper>() {
+        let browser = state.0.lock().await;
+        let playwright = browser.playwright.lock().await;
+        match playwright.list_browsers().await {
+            Ok(handles) => {
+                for handle in handles {
+                    match playwright.close_browser(handle).await {
+                        Ok(()) => report.browsers_closed += 1,
+                        Err(e) => report.errors.push(format!("browser: {}", e)),
+                    }
+                }
+            }
+            Err(e) => report.errors.push(format!("browser: {}", e)),
+        }
+        // Dropping the CDP clients aborts in-flight page commands
+        browser.cdp_clients.lock().await.clear();
+    }
+
+    crate::tray::update_tray(app, |status| status.halted = true);
+    audit(app, &change, serde_json::to_value(&report).ok());
+    if let Err(e) = app.emit(KILL_SWITCH_EVENT, status()) {
+        tracing::warn!("Failed to emit kill switch event: {}", e);
+    }
+
+    report
+}
+
+/// Allow automation again
+pub fn rearm(app: &AppHandle, initiator: &str) -> KillSwitchStatus {
+    let change = record_change(false, initiator, None);
+    tracing::info!(initiator, "Automation kill switch re-armed");
+
+    crate::tray::update_tray(app, |status| status.halted = false);
+    audit(app, &change, None);
+    let status = status();
+    if let Err(e) = app.emit(KILL_SWITCH_EVENT, &status) {
+        tracing::warn!("Failed to emit kill switch event: {}", e);
+    }
+    status
+}
+
+fn audit(app: &AppHandle, change: &KillSwitchChange, details: Option<serde_json::Value>) {
+    let Some(db) = app.try_state::<AppDatabase>() else {
+        tracing::warn!("Kill switch change not audited: database unavailable");
+        return;
+    };
+
+    let event = AuditEvent {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: change.at,
+        user_id: None,
+        team_id: None,
+        event_type: AuditEventType::KillSwitch,
+        resource_type: Some("automation".to_string()),
+        resource_id: None,
+        action: if change.engaged { "engage" } else { "rearm" }.to_string(),
+        status: AuditStatus::Success,
+        metadata: Some(serde_json::json!({
+            "initiator": change.initiator,
+            "reason": change.reason,
+            "report": details,
+        })),
+    };
+
+    let logged = EnhancedAuditLogger::new(db.conn.clone()).and_then(|logger| logger.log(event));
+    if let Err(e) = logged {
+        tracing::error!("Failed to audit kill switch change: {}", e);
+    }
+}
@@ -0,0 +1,165 @@
+//! Subsequence fuzzy matcher for the command palette.
+//!
+//! Every query character must appear in the candidate in order. Among all
+//! such alignments the best-scoring one is picked with a small dynamic
+//! program, rewarding matches at word starts and runs of consecutive
+//! characters and penalising gaps. Matching is case-insensitive.
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_FIRST_CHAR: i32 = 12;
+const BONUS_WORD_START: i32 = 10;
+const BONUS_CONSECUTIVE: i32 = 8;
+const PENALTY_GAP: i32 = 1;
+const PENALTY_LEADING_GAP: i32 = 1;
+const MAX_LEADING_PENALTY: i32 = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// Char indices of the matched characters in the candidate
+    pub positions: Vec<usize>,
+}
+
+/// Score `candidate` against `query`, or `None` if it does not contain every
+/// query character in order. An empty query matches everything with score 0.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let original: Vec<char> = candidate.chars().collect();
+    let lowered: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    if !is_subsequence(&query, &lowered) {
+        return None;
+    }
+
+    let (n, m) = (query.len(), lowered.len());
+    // best[i][j]: best score with query[i] matched at candidate[j]
+    let mut best = vec![vec![None::<i32>; m]; n];
+    let mut from = vec![vec![0usize; m]; n];
+
+    for j in 0..m {
+        if lowered[j] == query[0] {
+            let leading = (j as i32 * PENALTY_LEADING_GAP).min(MAX_LEADING_PENALTY);
+            best[0][j] = Some(SCORE_MATCH + bonus(&original, j) - leading);
+        }
+    }
+
+    for i in 1..n {
+        // Running best of best[i-1][k] + k * gap, so a gap of j-k-1 costs
+        // (j-k-1) * gap without rescanning every k
+        let mut running: Option<(i32, usize)> = None;
+        for j in i..m {
+            let k = j - 1;
+            if let Some(prev) = best[i - 1][k] {
+                let candidate = prev + k as i32 * PENALTY_GAP;
+                if running.is_none_or(|(value, _)| candidate > value) {
+                    running = Some((candidate, k));
+                }
+            }
+            if lowered[j] != query[i] {
+                continue;
+            }
+
+            let gapped = running.map(|(value, k)| {
+                let gap = (j - k - 1) as i32;
+                (value - k as i32 * PENALTY_GAP - gap * PENALTY_GAP, k)
+            });
+            let consecutive = best[i - 1][j - 1].map(|prev| (prev + BONUS_CONSECUTIVE, j - 1));
+            let chosen = match (gapped, consecutive) {
+                (Some(g), Some(c)) => Some(if c.0 >= g.0 { c } else { g }),
+                (g, c) => g.or(c),
+            };
+            if let Some((score, k)) = chosen {
+                best[i][j] = Some(score + SCORE_MATCH + bonus(&original, j));
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let (end, score) = best[n - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|s| (j, s)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![0; n];
+    let mut j = end;
+    for i in (0..n).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+fn is_subsequence(query: &[char], candidate: &[char]) -> bool {
+    let mut rest = candidate.iter();
+    query.iter().all(|q| rest.any(|c| c == q))
+}
+
+fn bonus(chars: &[char], j: usize) -> i32 {
+    if j == 0 {
+        return BONUS_FIRST_CHAR;
+    }
+    let (prev, current) = (chars[j - 1], chars[j]);
+    let separator = !prev.is_alphanumeric();
+    let camel = prev.is_lowercase() && current.is_uppercase();
+    let digit = !prev.is_ascii_digit() && current.is_ascii_digit();
+    if separator || camel || digit {
+        BONUS_WORD_START
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_ordered_subsequence() {
+        assert!(fuzzy_match("wfl", "Workflow").is_some());
+        assert!(fuzzy_match("lfw", "Workflow").is_none());
+        assert!(fuzzy_match("", "anything").is_some());
+
+        let found = fuzzy_match("nc", "New conversation").unwrap();
+        assert_eq!(found.positions, vec![0, 4]);
+    }
+
+    fn best(query: &str, candidates: &[&'static str]) -> &'static str {
+        candidates
+            .iter()
+            .filter_map(|c| fuzzy_match(query, c).map(|m| (m.score, *c)))
+            .max_by_key(|&(score, _)| score)
+            .map(|(_, c)| c)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_prefers_word_starts_and_runs() {
+        assert_eq!(
+            best("set", &["Reset shortcuts", "Open settings"]),
+            "Open settings"
+        );
+        assert_eq!(
+            best("sa", &["Toggle main window (sa)", "Stop all automation"]),
+            "Stop all automation"
+        );
+        assert_eq!(
+            best("inv", &["Sales invoice", "Invoice processor"]),
+            "Invoice processor"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Quick launcher: an always-on-top spotlight window with a fuzzy command
+//! palette over app commands, AI employees, workflows and recent
+//! conversations.
+//!
+//! The searchable items are loaded into a [`PaletteIndex`] once and reused
+//! for every keystroke, so incremental queries only pay for the match, not
+//! for the database. The index is rebuilt when it goes stale or when the
+//! launcher is reopened.
+
+mod fuzzy;
+mod window;
+
+pub use fuzzy::{fuzzy_match, FuzzyMatch};
+pub use window::{hide_launcher, toggle_launcher, LAUNCHER_LABEL};
+
+use crate::commands::Shortcut;
+use parking_lot::RwLock;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Rebuild the index at most this often while the launcher stays open
+const INDEX_TTL: Duration = Duration::from_secs(30);
+const RECENT_CONVERSATIONS: usize = 200;
+/// Matches on the subtitle (role, description) rank below title matches
+const SUBTITLE_PENALTY: i32 = 24;
+
+/// Commands that have no shortcut but belong in the palette
+const BUILTIN_COMMANDS: &[(&str, &str)] = &[
+    ("new_conversation", "New conversation"),
+    ("open_settings", "Open settings"),
+    ("open_workflows", "Open workflows"),
+    ("open_employees", "Open AI employees"),
+    ("kill_switch_rearm", "Re-arm automation"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteKind {
+    Command,
+    Employee,
+    Workflow,
+    Conversation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteItem {
+    pub kind: PaletteKind,
+    /// Action name for commands, row id otherwise
+    pub id: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteResult {
+    #[serde(flatten)]
+    pub item: PaletteItem,
+    pub score: i32,
+    /// Char indices in `title` to highlight
+    pub matches: Vec<usize>,
+}
+
+pub struct PaletteIndex {
+    items: Vec<PaletteItem>,
+    built_at: Instant,
+}
+
+impl PaletteIndex {
+    /// Collect every searchable item. `shortcuts` supplies the bindable
+    /// commands; the rest is read from the database.
+    pub fn build(conn: &Connection, shortcuts: &[Shortcut]) -> rusqlite::Result<Self> {
+        let mut items = command_items(shortcuts);
+
+        let mut stmt = conn.prepare("SELECT id, name, role FROM ai_employees ORDER BY name")?;
+        let employees = stmt.query_map([], |row| {
+            Ok(PaletteItem {
+                kind: PaletteKind::Employee,
+                id: row.get(0)?,
+                title: row.get(1)?,
+                subtitle: row.get(2)?,
+            })
+        })?;
+        items.extend(employees.collect::<rusqlite::Result<Vec<_>>>()?);
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, description FROM workflow_definitions ORDER BY updated_at DESC",
+        )?;
+        let workflows = stmt.query_map([], |row| {
+            Ok(PaletteItem {
+                kind: PaletteKind::Workflow,
+                id: row.get(0)?,
+                title: row.get(1)?,
+                subtitle: row.get(2)?,
+            })
+        })?;
+        items.extend(workflows.collect::<rusqlite::Result<Vec<_>>>()?);
+
+        let mut stmt = conn.prepare(
+            "SELECT id, title, updated_at FROM conversations ORDER BY updated_at DESC LIMIT ?1",
+        )?;
+        let conversations = stmt.query_map([RECENT_CONVERSATIONS as i64], |row| {
+            Ok(PaletteItem {
+                kind: PaletteKind::Conversation,
+                id: row.get::<_, i64>(0)?.to_string(),
+                title: row.get(1)?,
+                subtitle: row.get(2)?,
+            })
+        })?;
+        items.extend(conversations.collect::<rusqlite::Result<Vec<_>>>()?);
+
+        Ok(Self {
+            items,
+            built_at: Instant::now(),
+        })
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.built_at.elapsed() > INDEX_TTL
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Best `limit` matches for `query`. Ties keep index order: commands,
+    /// then employees, workflows and conversations (most recent first). An
+    /// empty query returns the first `limit` items.
+    pub fn query(&self, query: &str, limit: usize) -> Vec<PaletteResult> {
+        let mut results: Vec<PaletteResult> = self
+            .items
+            .iter()
+            .filter_map(|item| {
+                let (score, matches) = match fuzzy_match(query, &item.title) {
+                    Some(found) => (found.score, found.positions),
+                    None => {
+                        let subtitle = item.subtitle.as_deref()?;
+                        let found = fuzzy_match(query, subtitle)?;
+                        (found.score - SUBTITLE_PENALTY, Vec::new())
+                    }
+                };
+                Some(PaletteResult {
+                    item: item.clone(),
+                    score,
+                    matches,
+                })
+            })
+            .collect();
+
+        // Stable sort keeps the index order among equal scores
+        results.sort_by(|a, b| b.score.cmp(&a.score));
+        results.truncate(limit);
+        results
+    }
+}
+
+fn command_items(shortcuts: &[Shortcut]) -> Vec<PaletteItem> {
+    let mut items: Vec<PaletteItem> = shortcuts
+        .iter()
+        .filter(|shortcut| shortcut.enabled)
+        .map(|shortcut| PaletteItem {
+            kind: PaletteKind::Command,
+            id: shortcut.action.clone(),
+            title: shortcut.description.clone(),
+            subtitle: Some(shortcut.key.clone()),
+        })
+        .collect();
+    items.sort_by(|a, b| a.title.cmp(&b.title));
+
+    for (action, title) in BUILTIN_COMMANDS {
+        if !items.iter().any(|item| item.id == *action) {
+            items.push(PaletteItem {
+                kind: PaletteKind::Command,
+                id: action.to_string(),
+                title: title.to_string(),
+                subtitle: None,
+            });
+        }
+    }
+    items
+}
+
+/// Cached palette index, shared by the launcher commands
+#[derive(Default)]
+pub struct LauncherState {
+    index: RwLock<Option<Arc<PaletteIndex>>>,
+}
+
+impl LauncherState {
+    /// The cached index if it is still fresh
+    pub fn cached(&self) -> Option<Arc<PaletteIndex>> {
+        self.index
+            .read()
+            .as_ref()
+            .filter(|index| !index.is_stale())
+            .cloned()
+    }
+
+    pub fn store(&self, index: PaletteIndex) -> Arc<PaletteIndex> {
+        let index = Arc::new(index);
+        *self.index.write() = Some(index.clone());
+        index
+    }
+
+    pub fn invalidate(&self) {
+        self.index.write().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations;
+
+    #[test]
+    fn test_index_ranks_across_sources() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrations::run_migrations(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO conversations (title) VALUES ('Quarterly invoice review');
+             INSERT INTO workflow_definitions (id, user_id, name, description, nodes, edges)
+                 VALUES ('wf-1', 'u', 'Invoice approval', 'Route invoices to finance', '[]', '[]');",
+        )
+        .unwrap();
+        let shortcuts = vec![Shortcut {
+            id: "toggle_window".to_string(),
+            key: "CommandOrControl+Shift+Space".to_string(),
+            description: "Toggle main window".to_string(),
+            action: "toggle_window".to_string(),
+            enabled: true,
+        }];
+
+        let index = PaletteIndex::build(&conn, &shortcuts).unwrap();
+        assert!(!index.is_stale());
+
+        let results = index.query("invoice", 10);
+        assert!(results.len() >= 2);
+        assert_eq!(results[0].item.kind, PaletteKind::Workflow);
+        assert_eq!(results[0].matches, (0..7).collect::<Vec<_>>());
+        assert!(results
+            .iter()
+            .any(|r| r.item.kind == PaletteKind::Conversation));
+
+        let results = index.query("tmw", 10);
+        assert_eq!(results[0].item.id, "toggle_window");
+
+        assert_eq!(index.query("", 3).len(), 3.min(index.len()));
+    }
+}
@@ -0,0 +1,81 @@
+use tauri::{
+    AppHandle, LogicalPosition, Manager, Monitor, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+pub const LAUNCHER_LABEL: &str = "launcher";
+
+const LAUNCHER_WIDTH: f64 = 640.0;
+const LAUNCHER_HEIGHT: f64 = 420.0;
+
+/// Show the launcher on the monitor under the cursor, or hide it if it is
+/// already visible. Returns whether it is now visible.
+pub fn toggle_launcher(app: &AppHandle) -> tauri::Result<bool> {
+    let window = ensure_launcher(app)?;
+    if window.is_visible()? {
+        window.hide()?;
+        return Ok(false);
+    }
+
+    if let Some(monitor) = cursor_monitor(app) {
+        let area = crate::window::work_area(&monitor);
+        let x = area.x + (area.width - LAUNCHER_WIDTH).max(0.0) / 2.0;
+        // Upper third, like other spotlight-style launchers
+        let y = area.y + (area.height - LAUNCHER_HEIGHT).max(0.0) / 3.0;
+        window.set_position(LogicalPosition::new(x, y))?;
+    }
+    window.show()?;
+    window.set_focus()?;
+    Ok(true)
+}
+
+pub fn hide_launcher(app: &AppHandle) -> tauri::Result<()> {
+    match app.get_webview_window(LAUNCHER_LABEL) {
+        Some(window) => window.hide(),
+        None => Ok(()),
+    }
+}
+
+/// The launcher is created hidden on first use and kept around afterwards so
+/// reopening it is instant.
+fn ensure_launcher(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    if let Some(window) = app.get_webview_window(LAUNCHER_LABEL) {
+        return Ok(window);
+    }
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        LAUNCHER_LABEL,
+        WebviewUrl::App("index.html?mode=launcher".into()),
+    )
+    .title("AGI Workforce Launcher")
+    .decorations(false)
+    .transparent(true)
+    .resizable(false)
+    .shadow(true)
+    .skip_taskbar(true)
+    .always_on_top(true)
+    .visible(false)
+    .inner_size(LAUNCHER_WIDTH, LAUNCHER_HEIGHT)
+    .build()?;
+
+    // Dismiss like a popup when the user clicks elsewhere
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            if let Err(err) = handle.hide() {
+                tracing::warn!("Failed to hide launcher: {err:?}");
+            }
+        }
+    });
+
+    Ok(window)
+}
+
+fn cursor_monitor(app: &AppHandle) -> Option<Monitor> {
+    let cursor = app.cursor_position().ok()?;
+    app.monitor_from_point(cursor.x, cursor.y)
+        .ok()
+        .flatten()
+        .or_else(|| app.primary_monitor().ok().flatten())
+}
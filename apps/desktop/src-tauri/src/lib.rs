@@ -2,6 +2,7 @@
 #![allow(unsafe_code)] // Required for Windows API calls
 #![allow(unused_qualifications)] // Some qualifications improve code clarity
 #![allow(clippy::should_implement_trait)]
+#![deny(clippy::await_holding_invalid_type)] // See clippy.toml
 
 use tauri::Manager;
 
@@ -26,6 +27,30 @@ pub mod api_integrations;
 // Automation modules
 pub mod automation;
 
+// Per-platform capability report
+pub mod platform;
+
+// Safe-mode startup (UI, DB and settings only)
+pub mod safe_mode;
+
+// Global stop for all running automation
+pub mod kill_switch;
+
+// Cancellable long-running operations
+pub mod cancellation;
+
+// Startup readiness of backend subsystems
+pub mod readiness;
+
+// Quick launcher window and fuzzy command palette
+pub mod launcher;
+
+// Chat input autocomplete suggestions
+pub mod autocomplete;
+
+// Native OS notifications with action buttons
+pub mod notifications;
+
 // Browser integration
 pub mod browser;
 
@@ -52,6 +77,9 @@ pub mod overlay;
 // Security and guardrails
 pub mod security;
 
+// Append-only audit trail of agent and user actions
+pub mod audit;
+
 // Modular Control Primitives (MCPs)
 // pub mod mcps; // REMOVED duplicate
 
@@ -73,6 +101,9 @@ pub mod embeddings;
 // API client and OAuth
 pub mod api;
 
+// Web page fetching and article extraction
+pub mod web;
+
 // Database clients (SQL and NoSQL)
 pub mod database;
 
@@ -91,6 +122,9 @@ pub mod cloud;
 // Productivity tools (Notion, Trello, Asana)
 pub mod productivity;
 
+// Expiry and last-success tracking for integration credentials
+pub mod credential_health;
+
 // Document MCP (M16) - Word, Excel, PDF support
 pub mod document;
 
@@ -136,6 +170,9 @@ pub mod workflows;
 // Model Context Protocol (MCP) integration
 pub mod mcp;
 
+// HTTP API for headless and remote control
+pub mod remote_api;
+
 // Cache system for LLM responses and tool results
 pub mod cache;
 
@@ -169,6 +206,9 @@ pub use error::{Error, Result};
 // Utilities
 pub mod utils;
 
+// Headless core for the agiworkforce-cli binary
+pub mod cli;
+
 // Test utilities (only compiled in test builds)
 #[cfg(test)]
 pub mod test_utils;
@@ -199,6 +239,7 @@ pub fn run() {
             app.manage(commands::chat::AppDatabase {
                 conn: db.get_connection(),
             });
+            app.manage(crate::db::Pool::open(&db_path).expect("Failed to open DB pool"));
 
             app.manage(crate::billing::BillingStateWrapper::default());
             app.manage(commands::llm::LLMState::default());
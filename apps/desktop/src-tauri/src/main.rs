@@ -7,13 +7,21 @@
 // CodeGenerator, ContextManager, and AgentRuntime are now stubbed in commands/ai_native.rs
 use agiworkforce_desktop::agent::approval::ApprovalController;
 use agiworkforce_desktop::billing::BillingStateWrapper;
-use agiworkforce_desktop::security::{AuthManager, SecretManager};
+use agiworkforce_desktop::browser::{
+    BrowserPool, BrowserPoolConfig, BrowserProfileManager, BrowserRecorder, DownloadManager,
+    DOWNLOAD_PROGRESS_EVENT,
+};
+use agiworkforce_desktop::security::{
+    guardrails, AuthDatabaseManager, AuthManager, Guardrails, ReauthManager, SecretManager,
+    ShellGuardState, Vault,
+};
 use agiworkforce_desktop::{
     build_system_tray,
     commands::{
         // Note: CodeGeneratorState and ContextManagerState moved to ai_native module (stubbed)
         ai_native::{CodeGeneratorState, ContextManagerState},
         load_persisted_calendar_accounts,
+        load_persisted_cloud_accounts,
         security::AuthManagerState,
         AIEmployeeState,
         ApiState,
@@ -41,23 +49,29 @@ use agiworkforce_desktop::{
         WorkflowEngineState,
         WorkspaceIndexState,
     },
+    db::backup::{self, StartupCheck},
     db::migrations,
-    initialize_window,
+    initialize_window, readiness,
     settings::SettingsService,
     state::AppState,
     telemetry,
 };
 use anyhow::Context;
-use rusqlite::Connection;
 use std::sync::{Arc, Mutex};
-use tauri::{async_runtime, Manager};
+use tauri::{async_runtime, Emitter, Manager};
 use tokio::sync::Mutex as TokioMutex;
 
 fn main() {
+    // Relay an outside MCP client on stdin/stdout to the running app, then quit
+    if std::env::args().any(|arg| arg == agiworkforce_desktop::mcp::server::STDIO_FLAG) {
+        std::process::exit(agiworkforce_desktop::mcp::server::run_stdio_bridge());
+    }
+
     // Initialize telemetry (logging, tracing, metrics)
     let _telemetry_guard = telemetry::init().expect("Failed to initialize telemetry");
 
     tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             // Initialize database
             let app_data_dir = app
@@ -66,21 +80,73 @@ fn main() {
                 .context("Failed to get app data dir")?;
             let db_path = app_data_dir.join("agiworkforce.db");
 
+            // Safe mode starts only the UI, database and settings
+            let safe_mode = agiworkforce_desktop::safe_mode::init(
+                agiworkforce_desktop::safe_mode::detect(std::env::args(), &app_data_dir),
+            );
+            if safe_mode.enabled {
+                tracing::warn!(
+                    source = ?safe_mode.source,
+                    "Starting in safe mode: background loops and automations are disabled"
+                );
+            }
+            let safe_mode_reason = "Disabled in safe mode";
+
+            // Report subsystem readiness to the frontend as setup progresses
+            readiness::init(app.handle().clone());
+
             // Ensure parent directory exists
             if let Some(parent) = db_path.parent() {
                 std::fs::create_dir_all(parent).context("Failed to create data directory")?;
             }
 
+            // Swap a corrupt database for the newest good backup before opening it
+            let backup_dir = app_data_dir.join("backups");
+            let recovery_note = match backup::check_and_recover(&db_path, &backup_dir) {
+                Ok(StartupCheck::Healthy) => None,
+                Ok(StartupCheck::Restored { backup, .. }) => Some(format!(
+                    "Database was corrupt and was restored from {}",
+                    backup.display()
+                )),
+                Ok(StartupCheck::Unrecoverable { quarantined, .. }) => Some(format!(
+                    "Database was corrupt and no backup was usable; it was moved to {}",
+                    quarantined.display()
+                )),
+                Err(e) => {
+                    tracing::error!("Database integrity check failed: {:#}", e);
+                    None
+                }
+            };
+
             // Open database connection
-            let conn = Connection::open(&db_path).context("Failed to open database")?;
+            let mut conn =
+                agiworkforce_desktop::db::pool::open(&db_path).context("Failed to open database")?;
+
+            // Print the pending migration SQL without applying it, then quit
+            if std::env::args().any(|arg| arg == migrations::DRY_RUN_FLAG) {
+                let plan = migrations::preview_migrations(&mut conn, None)
+                    .context("Migration dry run failed")?;
+                print!("{}", plan);
+                std::process::exit(0);
+            }
 
-            // Run migrations
-            if let Err(e) = migrations::run_migrations(&conn) {
+            // Run migrations, backing up the database first if any are pending
+            if let Err(e) = migrations::run_migrations_with_backup(&conn, &backup_dir) {
                 tracing::error!("Failed to run migrations: {}", e);
+                readiness::failed("database", format!("Failed to run migrations: {}", e));
                 return Err(anyhow::anyhow!("Failed to run migrations: {}", e).into());
             }
 
+            // Pooled connections for states that query the database concurrently
+            let pool = agiworkforce_desktop::db::Pool::open(&db_path)
+                .context("Failed to open database connection pool")?;
+            app.manage(pool.clone());
+
             tracing::info!("Database initialized at {:?}", db_path);
+            match recovery_note {
+                Some(reason) => readiness::degraded("database", reason),
+                None => readiness::ready("database"),
+            }
 
             // Manage database state
             let db_conn_arc = Arc::new(Mutex::new(conn));
@@ -98,11 +164,61 @@ fn main() {
             let secret_manager = Arc::new(SecretManager::new(db_conn_arc.clone()));
             tracing::info!("SecretManager initialized");
 
+            // Vault for API keys and integration credentials (OS keyring + encrypted database fallback)
+            let vault = Arc::new(
+                Vault::open(pool.clone(), &app_data_dir.join("vault.key"))
+                    .context("Failed to open secrets vault")?,
+            );
+            if let Err(e) = vault.migrate_legacy_api_keys() {
+                tracing::warn!("Failed to move API keys into the vault: {}", e);
+            }
+            app.manage(vault.clone());
+            readiness::ready("secrets");
+
+            // Per-workspace .env management; secret values live in the SecretManager
+            app.manage(agiworkforce_desktop::terminal::WorkspaceEnvManager::new(
+                secret_manager.clone(),
+            ));
+
             // AuthManager handles user authentication, sessions, and token management
             // CRITICAL: This must be initialized to enforce authentication on protected commands
-            let auth_manager = Arc::new(parking_lot::RwLock::new(AuthManager::new(secret_manager.clone())));
+            let auth_manager = Arc::new(parking_lot::RwLock::new(
+                AuthManager::new(secret_manager.clone())
+                    .with_database(AuthDatabaseManager::new(pool.clone())),
+            ));
             app.manage(AuthManagerState(auth_manager));
+            // Windows Hello / password checks before sensitive actions
+            app.manage(ReauthManager::new(pool.clone()));
+            // Allow/deny rules for shell commands run by agents
+            let shell_guard = match pool.get() {
+                Ok(conn) => ShellGuardState::load(&conn),
+                Err(e) => {
+                    tracing::warn!("Using the default shell policy: {}", e);
+                    ShellGuardState::default()
+                }
+            };
+            app.manage(shell_guard);
+            // Prompt-injection and secret-egress checks on agent tool traffic
+            let guardrail_config = pool
+                .get()
+                .map(|conn| guardrails::load_config(&conn))
+                .unwrap_or_default();
+            app.manage(Arc::new(Guardrails::new(guardrail_config)));
+            // Append-only audit trail of agent and user actions
+            app.manage(agiworkforce_desktop::audit::AuditLogger::new(pool.clone()));
+            // Replayable traces of agent runs
+            app.manage(agiworkforce_desktop::agent::runtime::RunRecorder::new(
+                pool.clone(),
+            ));
+            // Pre-change snapshots of files agents write or delete
+            match agiworkforce_desktop::agent::file_checkpoints::CheckpointStore::open(pool.clone()) {
+                Ok(store) => {
+                    app.manage(store);
+                }
+                Err(e) => tracing::error!("File checkpoints unavailable: {}", e),
+            }
             tracing::info!("AuthManager initialized - authentication system ready");
+            readiness::ready("auth");
 
             // Initialize analytics telemetry state
             use agiworkforce_desktop::commands::analytics::TelemetryState;
@@ -113,34 +229,101 @@ fn main() {
                 batch_size: 50,
                 flush_interval_secs: 30,
             };
-            let telemetry_collector = TelemetryCollector::new(telemetry_config);
+            let telemetry_collector =
+                TelemetryCollector::new(telemetry_config).with_sink(db_conn_arc.clone());
             let analytics_metrics = AnalyticsMetricsCollector::new();
             app.manage(TelemetryState::new(telemetry_collector, analytics_metrics));
 
             tracing::info!("Analytics telemetry state initialized");
-
-            // Initialize LLM router state
-            app.manage(LLMState::new());
+            readiness::ready("telemetry");
+
+            // Initialize LLM router state with spend tracking and budget caps
+            let llm_state = LLMState::new();
+            let mut llm_issues = Vec::new();
+            if let Err(e) = llm_state
+                .budget_guard
+                .attach(db_conn_arc.clone(), Some(app.handle().clone()))
+            {
+                tracing::warn!("Failed to load LLM budget ledger: {}", e);
+                llm_issues.push(format!("Failed to load budget ledger: {}", e));
+            }
+            if let Err(e) = llm_state
+                .health_monitor
+                .attach(db_conn_arc.clone(), Some(app.handle().clone()))
+            {
+                tracing::warn!("Failed to load LLM provider health: {}", e);
+                llm_issues.push(format!("Failed to load provider health: {}", e));
+            }
+            llm_state.enable_cache(db_conn_arc.clone());
+            let budget_guard = llm_state.budget_guard.clone();
+            let health_monitor = llm_state.health_monitor.clone();
+            app.manage(llm_state);
+            if llm_issues.is_empty() {
+                readiness::ready("llm_router");
+            } else {
+                readiness::degraded("llm_router", llm_issues.join("; "));
+            }
 
             // Initialize browser automation state
-            app.manage(BrowserStateWrapper::new());
+            let browser_state = BrowserStateWrapper::new();
+            // Named profiles; browsers they left running are re-attached so
+            // logged-in sessions survive the restart
+            let browser_profiles = Arc::new(BrowserProfileManager::new(
+                pool.clone(),
+                app_data_dir.join("browser-profiles"),
+            ));
+            {
+                let browser = browser_state.0.clone();
+                let profiles = browser_profiles.clone();
+                tauri::async_runtime::spawn(async move {
+                    match profiles.reattach().await {
+                        Ok(handles) => {
+                            let browser = browser.lock().await;
+                            let playwright = browser.playwright.lock().await;
+                            for handle in handles {
+                                playwright.register_browser(handle).await;
+                            }
+                        }
+                        Err(e) => tracing::warn!("Failed to re-attach browser profiles: {}", e),
+                    }
+                });
+            }
+            app.manage(browser_state);
+            app.manage(browser_profiles);
+            app.manage(BrowserRecorder::new());
+            // Isolated headless contexts for parallel orchestrator agents
+            let browser_pool = Arc::new(BrowserPool::new(BrowserPoolConfig::default()));
+            browser_pool.spawn_reaper();
+            app.manage(browser_pool);
+            // Downloads of automated tabs, handed to a project's knowledge base on request
+            let download_events = app.handle().clone();
+            app.manage(Arc::new(DownloadManager::new(
+                app_data_dir.join("downloads"),
+                app_data_dir.join("projects.db"),
+                move |record| {
+                    let _ = download_events.emit(DOWNLOAD_PROGRESS_EVENT, record);
+                },
+            )));
+            readiness::ready("browser");
 
             // Initialize settings state (legacy)
             app.manage(SettingsState::new());
 
             // Initialize new settings service with database connection
-            let settings_conn =
-                Connection::open(&db_path).context("Failed to open settings database")?;
-            let settings_service = SettingsService::new(Arc::new(Mutex::new(settings_conn)))
+            let settings_service = SettingsService::new(pool.clone(), vault.clone())
                 .context("Failed to initialize settings service")?;
             app.manage(SettingsServiceState::new(settings_service));
 
             tracing::info!("Settings service initialized");
+            readiness::ready("settings");
 
             // Initialize file watcher state
             app.manage(FileWatcherState::new());
+            app.manage(agiworkforce_desktop::codebase::DependencyGraphState::new());
+            app.manage(agiworkforce_desktop::codebase::TaskRunner::new());
 
             tracing::info!("File watcher initialized");
+            readiness::ready("file_watcher");
 
             // Initialize API state
             app.manage(ApiState::new());
@@ -152,14 +335,46 @@ fn main() {
 
             tracing::info!("Database state initialized");
 
-            // Initialize cloud storage state
-            app.manage(CloudState::new());
+            // Initialize cloud storage state and restore persisted accounts
+            let cloud_state = CloudState::with_secrets(secret_manager.clone());
+            let mut cloud_issue = None;
+            match pool.get() {
+                Ok(cloud_conn) => match load_persisted_cloud_accounts(&cloud_conn) {
+                    Ok(accounts) => {
+                        let mut restored = 0usize;
+                        for (account_id, connection, label) in accounts {
+                            match cloud_state.manager.restore_account(account_id, connection, label) {
+                                Ok(()) => restored += 1,
+                                Err(err) => {
+                                    tracing::warn!("Failed to restore cloud account: {err}");
+                                    cloud_issue = Some(format!("Failed to restore cloud account: {err}"));
+                                }
+                            }
+                        }
+                        tracing::info!("Cloud storage manager restored {restored} account(s)");
+                    }
+                    Err(err) => {
+                        tracing::warn!("Failed to load cloud accounts: {err}");
+                        cloud_issue = Some(format!("Failed to load cloud accounts: {err}"));
+                    }
+                },
+                Err(err) => {
+                    tracing::warn!("Failed to open database for cloud restore: {err}");
+                    cloud_issue = Some(format!("Failed to open database for cloud restore: {err}"));
+                }
+            }
+            app.manage(cloud_state);
 
             tracing::info!("Cloud storage state initialized");
+            match cloud_issue {
+                Some(issue) => readiness::degraded("cloud_storage", issue),
+                None => readiness::ready("cloud_storage"),
+            }
 
             // Initialize calendar state and restore persisted accounts
             let calendar_state = CalendarState::new();
-            match Connection::open(&db_path) {
+            let mut calendar_issue = None;
+            match pool.get() {
                 Ok(calendar_conn) => match load_persisted_calendar_accounts(&calendar_conn) {
                     Ok(accounts) => {
                         let mut restored = 0usize;
@@ -173,13 +388,20 @@ fn main() {
                     }
                     Err(err) => {
                         tracing::warn!("Failed to load calendar accounts: {err}");
+                        calendar_issue = Some(format!("Failed to load calendar accounts: {err}"));
                     }
                 },
                 Err(err) => {
                     tracing::warn!("Failed to open database for calendar restore: {err}");
+                    calendar_issue =
+                        Some(format!("Failed to open database for calendar restore: {err}"));
                 }
             }
             app.manage(calendar_state);
+            match calendar_issue {
+                Some(issue) => readiness::degraded("calendar", issue),
+                None => readiness::ready("calendar"),
+            }
 
             // Initialize terminal session manager
             let session_manager =
@@ -189,7 +411,10 @@ fn main() {
             tracing::info!("Terminal session manager initialized");
 
             // Initialize LLM router for terminal AI
-            let terminal_llm_router = Arc::new(agiworkforce_desktop::router::LLMRouter::new());
+            let mut terminal_llm_router = agiworkforce_desktop::router::LLMRouter::new();
+            terminal_llm_router.set_budget_guard(budget_guard.clone());
+            terminal_llm_router.set_health_monitor(health_monitor.clone());
+            let terminal_llm_router = Arc::new(terminal_llm_router);
 
             // Initialize terminal AI assistant
             let terminal_ai = agiworkforce_desktop::terminal::TerminalAI::new(
@@ -199,16 +424,28 @@ fn main() {
             app.manage(terminal_ai);
 
             tracing::info!("Terminal AI assistant initialized");
+            readiness::ready("terminal");
 
-            // Initialize productivity state
-            app.manage(ProductivityState::new());
+            // Initialize productivity state and restore persisted accounts
+            let productivity_state = match pool.get() {
+                Ok(productivity_conn) => {
+                    ProductivityState::with_secrets(secret_manager.clone(), &productivity_conn)
+                }
+                Err(err) => {
+                    tracing::warn!("Failed to open database for productivity restore: {err}");
+                    ProductivityState::new()
+                }
+            };
+            app.manage(productivity_state);
 
             tracing::info!("Productivity state initialized");
+            readiness::ready("productivity");
 
             // Initialize document state
             app.manage(DocumentState::new());
 
             tracing::info!("Document state initialized");
+            readiness::ready("documents");
 
             // Initialize automation service
             let automation_service = agiworkforce_desktop::automation::AutomationService::new()
@@ -216,12 +453,59 @@ fn main() {
             app.manage(std::sync::Arc::new(automation_service));
 
             tracing::info!("Automation service initialized");
+            readiness::ready("automation");
 
             // Initialize MCP state
             let mcp_state = McpState::new();
+            mcp_state.enable_result_summaries(app.handle().clone());
+            mcp_state.forward_notifications(app.handle().clone());
+            mcp_state.enable_oauth(vault.clone());
             app.manage(mcp_state);
 
             tracing::info!("MCP state initialized");
+            readiness::ready("mcp");
+
+            // Serve the app's own tools to registered outside MCP clients
+            let mcp_tool_server = Arc::new(
+                agiworkforce_desktop::mcp::McpToolServer::new(pool.clone())
+                    .context("Failed to initialize MCP server")?,
+            );
+            if safe_mode.enabled {
+                readiness::disabled("mcp_server", safe_mode_reason);
+            } else {
+                let server = mcp_tool_server.clone();
+                async_runtime::spawn(async move {
+                    match server.sync_listener().await {
+                        Ok(()) => readiness::ready("mcp_server"),
+                        Err(e) => {
+                            tracing::warn!("Failed to start MCP server: {}", e);
+                            readiness::degraded("mcp_server", e);
+                        }
+                    }
+                });
+            }
+            app.manage(mcp_tool_server);
+
+            // Let scripts and other devices drive the app over HTTP
+            let remote_api = Arc::new(agiworkforce_desktop::remote_api::RemoteApiServer::new(
+                pool.clone(),
+                Some(app.handle().clone()),
+            ));
+            if safe_mode.enabled {
+                readiness::disabled("remote_api", safe_mode_reason);
+            } else {
+                let server = remote_api.clone();
+                async_runtime::spawn(async move {
+                    match server.sync_listener().await {
+                        Ok(()) => readiness::ready("remote_api"),
+                        Err(e) => {
+                            tracing::warn!("Failed to start remote API: {}", e);
+                            readiness::degraded("remote_api", e);
+                        }
+                    }
+                });
+            }
+            app.manage(remote_api);
 
             // TODO: AgentRuntime, ContextManager, and CodeGenerator are temporarily disabled
             // These were part of the deleted agent/ module and should be reimplemented using agi/ if needed
@@ -241,109 +525,285 @@ fn main() {
             app.manage(Arc::new(TokioMutex::new(GitHubState::new(workspace_dir))));
 
             tracing::info!("GitHub state initialized");
+            readiness::ready("github");
 
             // Initialize Computer Use state
             app.manage(Arc::new(TokioMutex::new(ComputerUseState::new())));
 
             tracing::info!("Computer use state initialized");
+            readiness::ready("computer_use");
 
             // Initialize Code Editing state
             app.manage(Arc::new(TokioMutex::new(CodeEditingState::new())));
 
             tracing::info!("Code Editing state initialized");
+            readiness::ready("code_editing");
 
             // Initialize Voice Input state
             app.manage(Arc::new(TokioMutex::new(VoiceState::new())));
 
             tracing::info!("Voice state initialized");
+            readiness::ready("voice");
 
             // Initialize Shortcuts state with defaults
             app.manage(Arc::new(TokioMutex::new(ShortcutsState::with_defaults())));
 
             tracing::info!("Shortcuts state initialized");
+            readiness::ready("shortcuts");
+
+            // Initialize quick launcher palette cache
+            app.manage(agiworkforce_desktop::launcher::LauncherState::default());
+
+            // Initialize chat autocomplete cache
+            app.manage(agiworkforce_desktop::autocomplete::AutocompleteState::default());
 
             // Initialize Workspace Indexing state
             app.manage(Arc::new(TokioMutex::new(WorkspaceIndexState::new())));
 
             tracing::info!("Workspace indexing state initialized");
+            readiness::ready("workspace_index");
 
             // Initialize LSP state
             app.manage(Arc::new(LSPState::new()));
 
             tracing::info!("LSP state initialized");
+            readiness::ready("lsp");
 
             // Initialize Codebase Cache
-            let cache_conn =
-                Connection::open(&db_path).context("Failed to open database for codebase cache")?;
-            let codebase_cache =
-                agiworkforce_desktop::cache::CodebaseCache::new(Arc::new(Mutex::new(cache_conn)))
-                    .context("Failed to initialize codebase cache")?;
+            let codebase_cache = agiworkforce_desktop::cache::CodebaseCache::new(pool.clone())
+                .context("Failed to initialize codebase cache")?;
             app.manage(agiworkforce_desktop::commands::cache::CodebaseCacheState(
                 Arc::new(codebase_cache),
             ));
 
             tracing::info!("Codebase cache initialized");
+            readiness::ready("codebase_cache");
 
             // Initialize Billing state (Stripe integration)
             app.manage(BillingStateWrapper::new());
 
             tracing::info!("Billing state initialized");
+            readiness::ready("billing");
+
+            // Keep connected mailboxes synced in the background over IMAP IDLE
+            let email_sync = agiworkforce_desktop::communications::email_sync::EmailSyncManager::new();
+            match email_sync.start_all(app.handle()) {
+                Ok(count) => {
+                    tracing::info!("Email sync started for {} accounts", count);
+                    readiness::ready("email_sync");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to start email sync: {}", e);
+                    readiness::degraded("email_sync", format!("Failed to start email sync: {}", e));
+                }
+            }
+            app.manage(email_sync);
+
+            // Deliver queued and scheduled outgoing mail
+            let email_outbox = agiworkforce_desktop::communications::email_outbox::EmailOutbox::new();
+            match email_outbox.start(app.handle()) {
+                Ok(()) => readiness::ready("email_outbox"),
+                Err(e) => {
+                    tracing::warn!("Failed to start email outbox: {}", e);
+                    readiness::degraded("email_outbox", format!("Failed to start email outbox: {}", e));
+                }
+            }
+            app.manage(email_outbox);
+
+            // Listen for Slack messages and mentions over Socket Mode
+            let slack_events =
+                agiworkforce_desktop::messaging::SlackEventManager::new(secret_manager.clone());
+            match slack_events.start_all(app.handle()) {
+                Ok(count) => {
+                    tracing::info!("Slack listeners started for {} connections", count);
+                    readiness::ready("slack_events");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to start Slack listeners: {}", e);
+                    readiness::degraded("slack_events", format!("Failed to start Slack listeners: {}", e));
+                }
+            }
+            app.manage(slack_events);
+
+            // Unified send-and-poll API over Slack, Teams and WhatsApp
+            let messaging_manager =
+                agiworkforce_desktop::messaging::MessagingManager::new(secret_manager.clone());
+            match pool
+                .get()
+                .map_err(anyhow::Error::from)
+                .and_then(|conn| messaging_manager.migrate_connections(&conn))
+            {
+                Ok(0) => {}
+                Ok(migrated) => tracing::info!("Migrated {migrated} messaging connection(s)"),
+                Err(e) => tracing::warn!("Failed to migrate messaging connections: {e}"),
+            }
+            app.manage(messaging_manager);
+            readiness::ready("messaging");
+
+            // Watch integration credentials and warn before they expire
+            let credential_health =
+                agiworkforce_desktop::credential_health::CredentialHealthMonitor::new();
+            credential_health.start(app.handle());
+            app.manage(credential_health);
+            readiness::ready("credential_health");
+
+            // Run user-defined integration health checks on their intervals
+            let health_checks = agiworkforce_desktop::api::HealthCheckScheduler::new();
+            health_checks.start(app.handle());
+            app.manage(health_checks);
+
+            // Pre-warm caches for recurring prompts and tool calls while idle
+            let cache_warmup = agiworkforce_desktop::cache::CacheWarmupScheduler::new();
+            if safe_mode.enabled {
+                readiness::disabled("cache_warmup", safe_mode_reason);
+            } else {
+                cache_warmup.start(app.handle());
+                readiness::ready("cache_warmup");
+            }
+            app.manage(cache_warmup);
+
+            // Take automatic database backups on the configured interval
+            let backup_scheduler = backup::BackupScheduler::new();
+            if safe_mode.enabled {
+                readiness::disabled("backups", safe_mode_reason);
+            } else {
+                backup_scheduler.start(app.handle());
+                readiness::ready("backups");
+            }
+            app.manage(backup_scheduler);
+
+            // Purge data older than the retention policy allows
+            let retention_scheduler = agiworkforce_desktop::db::retention::RetentionScheduler::new();
+            if safe_mode.enabled {
+                readiness::disabled("retention", safe_mode_reason);
+            } else {
+                retention_scheduler.start(app.handle());
+                readiness::ready("retention");
+            }
+            app.manage(retention_scheduler);
 
             // Initialize Workflow Orchestration state
             let workflow_engine_state =
-                WorkflowEngineState::new(db_path.to_string_lossy().to_string());
+                WorkflowEngineState::new(pool.clone()).with_secrets(secret_manager.clone());
+
+            // Drop artifacts past their workflow's retention in the background
+            let workflow_engine = Arc::clone(&workflow_engine_state.engine);
+            tauri::async_runtime::spawn_blocking(move || {
+                match workflow_engine.artifacts().prune_expired() {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Pruned {} expired workflow artifacts", count),
+                    Err(e) => tracing::warn!("Failed to prune workflow artifacts: {}", e),
+                }
+            });
+            // File, email, webhook, clipboard and hotkey sources start workflows
+            if safe_mode.enabled {
+                readiness::disabled("workflow_triggers", safe_mode_reason);
+            } else {
+                match workflow_engine_state.triggers.start(app.handle().clone()) {
+                    Ok(count) => {
+                        tracing::info!("{} workflow triggers enabled", count);
+                        readiness::ready("workflow_triggers");
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to start workflow triggers: {}", e);
+                        readiness::degraded(
+                            "workflow_triggers",
+                            format!("Failed to start workflow triggers: {}", e),
+                        );
+                    }
+                }
+            }
             app.manage(workflow_engine_state);
 
             tracing::info!("Workflow orchestration state initialized");
+            if safe_mode.enabled {
+                readiness::disabled("workflows", safe_mode_reason);
+            } else {
+                readiness::ready("workflows");
+            }
 
             // Initialize Marketplace state for public workflows
-            let marketplace_conn =
-                Connection::open(&db_path).context("Failed to open database for marketplace")?;
             app.manage(
-                agiworkforce_desktop::commands::marketplace::MarketplaceState {
-                    db: Arc::new(Mutex::new(marketplace_conn)),
-                },
+                agiworkforce_desktop::commands::marketplace::MarketplaceState { db: pool.clone() },
             );
 
             tracing::info!("Marketplace state initialized");
+            readiness::ready("marketplace");
 
             // Initialize Template Manager state
-            let template_conn =
-                Connection::open(&db_path).context("Failed to open database for template manager")?;
-            let template_db = Arc::new(Mutex::new(template_conn));
             let template_manager =
-                agiworkforce_desktop::commands::templates::initialize_template_manager(template_db);
+                agiworkforce_desktop::commands::templates::initialize_template_manager(pool.clone());
             app.manage(TemplateManagerState {
                 manager: Arc::new(Mutex::new(template_manager)),
             });
 
             tracing::info!("Template manager state initialized");
+            readiness::ready("templates");
 
             // Initialize Real-time Metrics and ROI Dashboard
-            let presence_db = Arc::new(Mutex::new(
-                Connection::open(&db_path).context("Failed to open database for presence")?,
-            ));
             let presence_manager =
-                Arc::new(agiworkforce_desktop::realtime::PresenceManager::new(presence_db));
+                Arc::new(agiworkforce_desktop::realtime::PresenceManager::new(pool.clone()));
             let websocket_port = 8787;
             let realtime_server = Arc::new(
                 agiworkforce_desktop::realtime::RealtimeServer::new(presence_manager.clone()),
             );
-            {
+            if !safe_mode.enabled {
                 let server = realtime_server.clone();
                 async_runtime::spawn(async move {
                     if let Err(e) = server.start(websocket_port).await {
                         tracing::error!("Realtime server failed: {}", e);
+                        readiness::failed("realtime_server", e.to_string());
                     }
                 });
+            } else {
+                readiness::disabled("realtime_server", safe_mode_reason);
             }
             app.manage(agiworkforce_desktop::commands::RealtimeState::new(
                 presence_manager.clone(),
                 websocket_port,
             ));
-            let metrics_db = Arc::new(Mutex::new(
-                Connection::open(&db_path).context("Failed to open database for metrics")?,
-            ));
+            // Tool reliability telemetry feeds planner tool choices
+            match agiworkforce_desktop::agi::global_tool_reliability().attach_database(pool.clone()) {
+                Ok(_) => readiness::ready("tool_reliability"),
+                Err(e) => {
+                    tracing::warn!("Failed to load tool reliability history: {}", e);
+                    readiness::degraded(
+                        "tool_reliability",
+                        format!("Failed to load tool reliability history: {}", e),
+                    );
+                }
+            }
+            // Tool spend is reported next to LLM spend in cost analytics
+            match agiworkforce_desktop::agi::global_tool_costs().attach_database(pool.clone()) {
+                Ok(_) => readiness::ready("tool_costs"),
+                Err(e) => {
+                    tracing::warn!("Failed to load tool pricing: {}", e);
+                    readiness::degraded("tool_costs", format!("Failed to load tool pricing: {}", e));
+                }
+            }
+            // Saved skills are offered to the planner and run as one step
+            match agiworkforce_desktop::agi::global_skill_library().attach_database(pool.clone()) {
+                Ok(_) => readiness::ready("skills"),
+                Err(e) => {
+                    tracing::warn!("Failed to open skill library: {}", e);
+                    readiness::degraded("skills", format!("Failed to open skill library: {}", e));
+                }
+            }
+            // Browser selector lookups and heals, reported per workflow
+            match agiworkforce_desktop::browser::healing::global_selector_healing()
+                .attach_database(pool.clone())
+            {
+                Ok(_) => readiness::ready("selector_healing"),
+                Err(e) => {
+                    tracing::warn!("Failed to load selector healing history: {}", e);
+                    readiness::degraded(
+                        "selector_healing",
+                        format!("Failed to load selector healing history: {}", e),
+                    );
+                }
+            }
+
+            let metrics_db = pool.clone();
             let metrics_collector = Arc::new(
                 agiworkforce_desktop::metrics::RealtimeMetricsCollector::new(
                     metrics_db.clone(),
@@ -363,38 +823,82 @@ fn main() {
             ));
 
             tracing::info!("Real-time metrics and ROI dashboard initialized");
+            readiness::ready("metrics");
+
+            // Initialize mobile companion sync (publishes only once paired)
+            let companion_sync = Arc::new(agiworkforce_desktop::sync::CompanionSync::new(
+                secret_manager.clone(),
+                app.state::<CloudState>().manager.clone(),
+                realtime_server.clone(),
+            ));
+            if !safe_mode.enabled {
+                companion_sync
+                    .clone()
+                    .start_auto_publish(app.handle().clone(), std::time::Duration::from_secs(15));
+                companion_sync.clone().start_inbound(app.handle().clone());
+                readiness::ready("companion_sync");
+            } else {
+                readiness::disabled("companion_sync", safe_mode_reason);
+            }
+            app.manage(agiworkforce_desktop::commands::CompanionSyncState(
+                companion_sync,
+            ));
+
+            tracing::info!("Companion sync initialized");
 
             // Initialize Embedding Service for semantic code search
             let workspace_root = app
                 .path()
                 .app_data_dir()
                 .context("Failed to get app data dir")?;
-            let embedding_config = agiworkforce_desktop::embeddings::EmbeddingConfig::default();
+            // A store built with an OpenAI model needs the key to keep searching
+            let embedding_config = agiworkforce_desktop::embeddings::EmbeddingConfig {
+                openai_api_key: app
+                    .state::<SettingsServiceState>()
+                    .service
+                    .lock()
+                    .ok()
+                    .and_then(|settings| settings.get_api_key("openai").ok())
+                    .map(agiworkforce_desktop::security::SecretString::new),
+                ..agiworkforce_desktop::embeddings::EmbeddingConfig::default()
+            };
 
-            match async_runtime::block_on(
-                agiworkforce_desktop::embeddings::EmbeddingService::new(
-                    workspace_root,
-                    embedding_config,
-                ),
-            ) {
-                Ok(embedding_service) => {
-                    app.manage(EmbeddingServiceState(Arc::new(TokioMutex::new(
-                        embedding_service,
-                    ))));
-                    tracing::info!("Embedding service initialized");
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to initialize embedding service: {}. Semantic search will be unavailable.", e);
+            if safe_mode.enabled {
+                tracing::info!("Embedding service skipped in safe mode");
+                readiness::disabled("embeddings", safe_mode_reason);
+            } else {
+                match async_runtime::block_on(
+                    agiworkforce_desktop::embeddings::EmbeddingService::new(
+                        workspace_root,
+                        embedding_config,
+                    ),
+                ) {
+                    Ok(embedding_service) => {
+                        app.manage(EmbeddingServiceState(Arc::new(TokioMutex::new(
+                            embedding_service,
+                        ))));
+                        tracing::info!("Embedding service initialized");
+                        readiness::ready("embeddings");
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to initialize embedding service: {}. \
+                             Semantic search will be unavailable.",
+                            e
+                        );
+                        readiness::failed("embeddings", e.to_string());
+                    }
                 }
             }
 
             // Initialize AI Employee system
-            let employee_db = Arc::new(Mutex::new(
-                Connection::open(&db_path).context("Failed to open database for AI employees")?,
-            ));
+            let employee_db = pool.clone();
 
             // Create LLM router for employee executor (reuse existing LLM state)
-            let llm_router = Arc::new(Mutex::new(agiworkforce_desktop::router::LLMRouter::new()));
+            let mut llm_router = agiworkforce_desktop::router::LLMRouter::new();
+            llm_router.set_budget_guard(budget_guard.clone());
+            llm_router.set_health_monitor(health_monitor.clone());
+            let llm_router = Arc::new(Mutex::new(llm_router));
 
             // Create tool registry for employee executor
             let tools = Arc::new(agiworkforce_desktop::agi::tools::ToolRegistry::new()
@@ -426,60 +930,88 @@ fn main() {
                 Ok(registry) => {
                     if let Err(e) = registry.initialize() {
                         tracing::warn!("Failed to initialize AI employee registry: {}", e);
+                        readiness::degraded(
+                            "ai_employees",
+                            format!("Failed to initialize AI employee registry: {}", e),
+                        );
                     }
                 }
                 Err(e) => {
                     tracing::error!("Failed to lock employee registry: {}", e);
+                    readiness::failed("ai_employees", format!("Employee registry lock poisoned: {}", e));
                     return Err(anyhow::anyhow!("Employee registry lock poisoned: {}", e).into());
                 }
             }
 
+            let employee_pipelines = Arc::new(
+                agiworkforce_desktop::ai_employees::pipelines::EmployeePipelineManager::new(
+                    employee_db.clone(),
+                    employee_executor.clone(),
+                ),
+            );
+
             // Manage AI employee state
             app.manage(AIEmployeeState {
                 executor: employee_executor,
                 marketplace: employee_marketplace,
                 registry: employee_registry,
+                pipelines: employee_pipelines,
             });
 
             tracing::info!("AI Employee system initialized");
+            if safe_mode.enabled {
+                readiness::disabled("ai_employees", safe_mode_reason);
+            } else if readiness::get("ai_employees")
+                .is_some_and(|s| s.state == readiness::ReadinessState::Initializing)
+            {
+                readiness::ready("ai_employees");
+            }
 
             // Initialize Hook Registry for event-driven automation
             app.manage(agiworkforce_desktop::commands::HookRegistryState::new());
 
             tracing::info!("Hook registry state initialized");
+            readiness::ready("hooks");
 
             // Initialize Prompt Enhancement state for AI routing
             app.manage(agiworkforce_desktop::commands::PromptEnhancementState::new());
 
             tracing::info!("Prompt enhancement state initialized");
+            readiness::ready("prompt_enhancement");
 
             // Initialize Background Task Manager
-            let task_db_conn = Arc::new(Mutex::new(
-                Connection::open(&db_path).context("Failed to open database for task manager")?,
-            ));
+            let task_db_conn = pool.clone();
             let task_manager = Arc::new(agiworkforce_desktop::tasks::TaskManager::new(
                 task_db_conn,
                 app.handle().clone(),
                 4, // Max concurrent tasks
             ));
 
-            // Restore queued tasks from database
-            let task_manager_clone = task_manager.clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = task_manager_clone.restore().await {
-                    tracing::error!("Failed to restore tasks: {}", e);
-                }
-            });
+            // Queued tasks stay in the database until the next normal start
+            if !safe_mode.enabled {
+                // Restore queued tasks from database
+                let task_manager_clone = task_manager.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = task_manager_clone.restore().await {
+                        tracing::error!("Failed to restore tasks: {}", e);
+                    }
+                });
 
-            // Start background task loop
-            let task_manager_loop = task_manager.clone();
-            tauri::async_runtime::spawn(async move {
-                agiworkforce_desktop::tasks::start_task_loop(task_manager_loop).await;
-            });
+                // Start background task loop
+                let task_manager_loop = task_manager.clone();
+                tauri::async_runtime::spawn(async move {
+                    agiworkforce_desktop::tasks::start_task_loop(task_manager_loop).await;
+                });
+            }
 
             app.manage(TaskManagerState(task_manager));
 
             tracing::info!("Background task manager initialized");
+            if safe_mode.enabled {
+                readiness::disabled("background_tasks", safe_mode_reason);
+            } else {
+                readiness::ready("background_tasks");
+            }
 
             // Initialize window state
             let state = AppState::load(app.handle())?;
@@ -506,6 +1038,14 @@ fn main() {
             agiworkforce_desktop::commands::agi_get_goal_status,
             agiworkforce_desktop::commands::agi_list_goals,
             agiworkforce_desktop::commands::agi_stop,
+            agiworkforce_desktop::commands::agent_list_runs,
+            agiworkforce_desktop::commands::agent_get_run_trace,
+            agiworkforce_desktop::commands::agent_replay_run,
+            agiworkforce_desktop::commands::agent_get_default_run_budget,
+            agiworkforce_desktop::commands::agent_set_default_run_budget,
+            agiworkforce_desktop::commands::agent_get_budget_metrics,
+            agiworkforce_desktop::commands::agent_get_run_file_changes,
+            agiworkforce_desktop::commands::agent_rollback_run,
             // Parallel Agent Orchestration commands
             agiworkforce_desktop::commands::orchestrator_init,
             agiworkforce_desktop::commands::orchestrator_init_default,
@@ -515,8 +1055,19 @@ fn main() {
             agiworkforce_desktop::commands::orchestrator_list_agents,
             agiworkforce_desktop::commands::orchestrator_cancel_agent,
             agiworkforce_desktop::commands::orchestrator_cancel_all,
+            agiworkforce_desktop::commands::orchestrator_pause_all,
+            agiworkforce_desktop::commands::orchestrator_resume_all,
             agiworkforce_desktop::commands::orchestrator_wait_all,
             agiworkforce_desktop::commands::orchestrator_cleanup,
+            agiworkforce_desktop::commands::blackboard_get,
+            agiworkforce_desktop::commands::blackboard_get_artifact,
+            agiworkforce_desktop::commands::blackboard_write,
+            agiworkforce_desktop::commands::blackboard_close,
+            agiworkforce_desktop::commands::skill_list,
+            agiworkforce_desktop::commands::skill_search,
+            agiworkforce_desktop::commands::skill_save_from_run,
+            agiworkforce_desktop::commands::skill_save,
+            agiworkforce_desktop::commands::skill_delete,
             // System monitoring and agent management commands
             agiworkforce_desktop::commands::get_system_resources,
             agiworkforce_desktop::commands::pause_agent,
@@ -529,15 +1080,24 @@ fn main() {
               agiworkforce_desktop::commands::agent_resolve_approval,
               agiworkforce_desktop::commands::agent_set_workflow_hash,
               agiworkforce_desktop::commands::agent_list_trusted_workflows,
+              agiworkforce_desktop::commands::approval_resolve_batch,
+              agiworkforce_desktop::commands::approval_list_policies,
+              agiworkforce_desktop::commands::approval_set_policy,
+              agiworkforce_desktop::commands::approval_delete_policy,
               agiworkforce_desktop::commands::cancel_background_task,
             agiworkforce_desktop::commands::pause_background_task,
             agiworkforce_desktop::commands::resume_background_task,
             agiworkforce_desktop::commands::list_background_tasks,
             agiworkforce_desktop::commands::list_active_agents,
+            agiworkforce_desktop::commands::operation_cancel,
+            agiworkforce_desktop::commands::operation_list,
             // Knowledge base commands
             agiworkforce_desktop::commands::query_knowledge,
             agiworkforce_desktop::commands::get_recent_knowledge,
             agiworkforce_desktop::commands::get_knowledge_by_category,
+            // Tool reliability telemetry
+            agiworkforce_desktop::commands::tools_get_reliability_stats,
+            agiworkforce_desktop::commands::tools_reset_reliability_stats,
             // TODO: Agent and Runtime commands disabled - were part of deleted agent/ module
             // agent_init, agent_submit_task, agent_get_task_status, agent_list_tasks, agent_stop
             // runtime_queue_task, runtime_get_next_task, runtime_execute_task, runtime_cancel_task,
@@ -559,6 +1119,7 @@ fn main() {
             agiworkforce_desktop::commands::window_set_always_on_top,
             agiworkforce_desktop::commands::window_set_visibility,
             agiworkforce_desktop::commands::window_dock,
+            agiworkforce_desktop::commands::window_list_monitors,
             agiworkforce_desktop::commands::window_is_maximized,
             agiworkforce_desktop::commands::window_maximize,
             agiworkforce_desktop::commands::window_unmaximize,
@@ -566,9 +1127,12 @@ fn main() {
             agiworkforce_desktop::commands::window_set_fullscreen,
             agiworkforce_desktop::commands::window_is_fullscreen,
             agiworkforce_desktop::commands::tray_set_unread_badge,
+            agiworkforce_desktop::commands::tray_get_status,
+            agiworkforce_desktop::commands::tray_set_paused,
             // Chat commands
             agiworkforce_desktop::commands::chat_create_conversation,
             agiworkforce_desktop::commands::chat_get_conversations,
+            agiworkforce_desktop::commands::chat_get_conversations_page,
             agiworkforce_desktop::commands::chat_get_conversation,
             agiworkforce_desktop::commands::chat_update_conversation,
             agiworkforce_desktop::commands::chat_delete_conversation,
@@ -576,11 +1140,22 @@ fn main() {
             agiworkforce_desktop::commands::chat_get_messages,
             agiworkforce_desktop::commands::chat_update_message,
             agiworkforce_desktop::commands::chat_delete_message,
+            agiworkforce_desktop::commands::chat_regenerate_message,
+            agiworkforce_desktop::commands::chat_switch_branch,
+            agiworkforce_desktop::commands::chat_list_branches,
             agiworkforce_desktop::commands::chat_send_message,
+            agiworkforce_desktop::commands::chat_convert_to_workflow,
             agiworkforce_desktop::commands::chat_get_conversation_stats,
+            agiworkforce_desktop::commands::chat_get_context_usage,
+            agiworkforce_desktop::commands::chat_estimate_cost,
             agiworkforce_desktop::commands::chat_get_cost_overview,
             agiworkforce_desktop::commands::chat_get_cost_analytics,
+            agiworkforce_desktop::commands::chat_get_tool_pricing,
+            agiworkforce_desktop::commands::chat_set_tool_pricing,
             agiworkforce_desktop::commands::chat_set_monthly_budget,
+            agiworkforce_desktop::commands::llm_get_budget_status,
+            agiworkforce_desktop::commands::llm_set_budget_limits,
+            agiworkforce_desktop::commands::llm_get_provider_health,
             // Checkpoint commands
             agiworkforce_desktop::commands::checkpoint_create,
             agiworkforce_desktop::commands::checkpoint_restore,
@@ -589,6 +1164,7 @@ fn main() {
             // Cloud storage commands
             agiworkforce_desktop::commands::cloud_connect,
             agiworkforce_desktop::commands::cloud_complete_oauth,
+            agiworkforce_desktop::commands::cloud_connect_webdav,
             agiworkforce_desktop::commands::cloud_disconnect,
             agiworkforce_desktop::commands::cloud_list_accounts,
             agiworkforce_desktop::commands::cloud_list,
@@ -597,6 +1173,15 @@ fn main() {
             agiworkforce_desktop::commands::cloud_delete,
             agiworkforce_desktop::commands::cloud_create_folder,
             agiworkforce_desktop::commands::cloud_share,
+            // Mobile companion commands
+            agiworkforce_desktop::commands::companion_pair,
+            agiworkforce_desktop::commands::companion_unpair,
+            agiworkforce_desktop::commands::companion_set_channel,
+            agiworkforce_desktop::commands::companion_publish,
+            agiworkforce_desktop::commands::companion_submit_approval,
+            // Session handoff commands
+            agiworkforce_desktop::commands::session_handoff_export,
+            agiworkforce_desktop::commands::session_handoff_import,
             // Email commands
             agiworkforce_desktop::commands::email_connect,
             agiworkforce_desktop::commands::email_list_accounts,
@@ -607,6 +1192,31 @@ fn main() {
             agiworkforce_desktop::commands::email_delete,
             agiworkforce_desktop::commands::email_download_attachment,
             agiworkforce_desktop::commands::email_send,
+            agiworkforce_desktop::commands::email_outbox_list,
+            agiworkforce_desktop::commands::email_outbox_cancel,
+            agiworkforce_desktop::commands::email_list_cached,
+            agiworkforce_desktop::commands::email_sync_start,
+            agiworkforce_desktop::commands::email_sync_stop,
+            agiworkforce_desktop::commands::email_sync_status,
+            agiworkforce_desktop::commands::email_rules_list,
+            agiworkforce_desktop::commands::email_rules_create,
+            agiworkforce_desktop::commands::email_rules_update,
+            agiworkforce_desktop::commands::email_rules_delete,
+            agiworkforce_desktop::commands::email_rules_test,
+            // Messaging
+            agiworkforce_desktop::commands::connect_slack,
+            agiworkforce_desktop::commands::get_messaging_history,
+            agiworkforce_desktop::commands::disconnect_platform,
+            agiworkforce_desktop::commands::list_messaging_connections,
+            agiworkforce_desktop::commands::slack_events_start,
+            agiworkforce_desktop::commands::slack_events_stop,
+            agiworkforce_desktop::commands::slack_events_status,
+            agiworkforce_desktop::commands::connect_whatsapp,
+            agiworkforce_desktop::commands::connect_teams,
+            agiworkforce_desktop::commands::messaging_send,
+            agiworkforce_desktop::commands::messaging_list_threads,
+            agiworkforce_desktop::commands::messaging_poll,
+            agiworkforce_desktop::commands::messaging_whatsapp_webhook,
             // Contact commands
             agiworkforce_desktop::commands::contact_create,
             agiworkforce_desktop::commands::contact_get,
@@ -629,8 +1239,14 @@ fn main() {
             agiworkforce_desktop::commands::calendar_get_system_timezone,
             // Productivity commands
             agiworkforce_desktop::commands::productivity_connect,
+            agiworkforce_desktop::commands::productivity_start_oauth,
+            agiworkforce_desktop::commands::productivity_complete_oauth,
+            agiworkforce_desktop::commands::productivity_disconnect,
+            agiworkforce_desktop::commands::productivity_list_accounts,
             agiworkforce_desktop::commands::productivity_list_tasks,
             agiworkforce_desktop::commands::productivity_create_task,
+            agiworkforce_desktop::commands::productivity_search_all,
+            agiworkforce_desktop::commands::productivity_get_unified_board,
             agiworkforce_desktop::commands::productivity_notion_list_pages,
             agiworkforce_desktop::commands::productivity_notion_query_database,
             agiworkforce_desktop::commands::productivity_notion_create_database_row,
@@ -732,6 +1348,23 @@ fn main() {
             agiworkforce_desktop::commands::get_dom_semantic_graph,
             agiworkforce_desktop::commands::get_interactive_elements,
             agiworkforce_desktop::commands::find_by_role,
+            agiworkforce_desktop::commands::browser_create_profile,
+            agiworkforce_desktop::commands::browser_list_profiles,
+            agiworkforce_desktop::commands::browser_set_profile_proxy,
+            agiworkforce_desktop::commands::browser_delete_profile,
+            agiworkforce_desktop::commands::browser_launch_profile,
+            agiworkforce_desktop::commands::browser_stop_profile,
+            agiworkforce_desktop::commands::browser_start_recording,
+            agiworkforce_desktop::commands::browser_recording_snapshot,
+            agiworkforce_desktop::commands::browser_stop_recording,
+            agiworkforce_desktop::commands::browser_recording_to_skill,
+            agiworkforce_desktop::commands::browser_recording_to_workflow,
+            agiworkforce_desktop::commands::browser_healing_stats,
+            agiworkforce_desktop::commands::browser_pool_stats,
+            agiworkforce_desktop::commands::browser_enable_downloads,
+            agiworkforce_desktop::commands::browser_list_downloads,
+            agiworkforce_desktop::commands::browser_wait_for_download,
+            agiworkforce_desktop::commands::browser_disable_downloads,
             // Git commands
             agiworkforce_desktop::commands::git_init,
             agiworkforce_desktop::commands::git_status,
@@ -754,6 +1387,10 @@ fn main() {
             agiworkforce_desktop::commands::git_reset,
             agiworkforce_desktop::commands::git_list_remotes,
             agiworkforce_desktop::commands::git_add_remote,
+            // Code review commands
+            agiworkforce_desktop::commands::code_review_run,
+            agiworkforce_desktop::commands::code_review_to_markdown,
+            agiworkforce_desktop::commands::code_review_post_github,
             // Design/CSS generation commands
             agiworkforce_desktop::commands::design_generate_css,
             agiworkforce_desktop::commands::design_apply_css,
@@ -777,6 +1414,7 @@ fn main() {
             agiworkforce_desktop::commands::task_resume,
             agiworkforce_desktop::commands::task_cancel,
             agiworkforce_desktop::commands::task_list,
+            agiworkforce_desktop::commands::task_list_page,
             agiworkforce_desktop::commands::task_list_by_status,
             agiworkforce_desktop::commands::task_complete,
             agiworkforce_desktop::commands::task_save_context,
@@ -823,7 +1461,11 @@ fn main() {
             // Embedding and semantic search commands
             agiworkforce_desktop::commands::generate_code_embeddings,
             agiworkforce_desktop::commands::semantic_search_codebase,
+            agiworkforce_desktop::commands::hybrid_search_codebase,
             agiworkforce_desktop::commands::get_embedding_stats,
+            agiworkforce_desktop::commands::rebuild_embedding_index,
+            agiworkforce_desktop::commands::get_embedding_model_status,
+            agiworkforce_desktop::commands::set_embedding_model,
             agiworkforce_desktop::commands::index_workspace,
             agiworkforce_desktop::commands::index_file,
             agiworkforce_desktop::commands::get_indexing_progress,
@@ -832,6 +1474,33 @@ fn main() {
             // Settings commands (legacy)
             agiworkforce_desktop::commands::settings_save_api_key,
             agiworkforce_desktop::commands::settings_get_api_key,
+            agiworkforce_desktop::commands::settings_reveal_api_key,
+            agiworkforce_desktop::commands::vault_list_secrets,
+            agiworkforce_desktop::commands::vault_rotate_secret,
+            agiworkforce_desktop::commands::vault_delete_secret,
+            agiworkforce_desktop::commands::vault_access_log,
+            agiworkforce_desktop::commands::permission_profiles_list,
+            agiworkforce_desktop::commands::permission_profiles_save,
+            agiworkforce_desktop::commands::permission_profiles_delete,
+            agiworkforce_desktop::commands::permission_profiles_get_default,
+            agiworkforce_desktop::commands::permission_profiles_set_default,
+            agiworkforce_desktop::commands::ai_employees_get_permission_profile,
+            agiworkforce_desktop::commands::ai_employees_set_permission_profile,
+            agiworkforce_desktop::commands::permission_decisions_log,
+            agiworkforce_desktop::commands::reauth_confirm,
+            agiworkforce_desktop::commands::reauth_submit_password,
+            agiworkforce_desktop::commands::reauth_status,
+            agiworkforce_desktop::commands::reauth_set_grace_period,
+            agiworkforce_desktop::commands::reauth_set_password,
+            agiworkforce_desktop::commands::shell_guard_get_policy,
+            agiworkforce_desktop::commands::shell_guard_set_policy,
+            agiworkforce_desktop::commands::shell_guard_check,
+            agiworkforce_desktop::commands::guardrails_get_config,
+            agiworkforce_desktop::commands::guardrails_set_config,
+            agiworkforce_desktop::commands::guardrails_list_reports,
+            agiworkforce_desktop::commands::guardrails_get_report,
+            agiworkforce_desktop::commands::audit_query,
+            agiworkforce_desktop::commands::audit_export,
             agiworkforce_desktop::commands::settings_load,
             agiworkforce_desktop::commands::settings_save,
             // Settings v2 commands
@@ -953,6 +1622,19 @@ fn main() {
             agiworkforce_desktop::commands::db_redis_hset,
             agiworkforce_desktop::commands::db_redis_hgetall,
             agiworkforce_desktop::commands::db_redis_disconnect,
+            agiworkforce_desktop::commands::db_get_schema_version,
+            agiworkforce_desktop::commands::db_migrate_schema,
+            agiworkforce_desktop::commands::db_backup_create,
+            agiworkforce_desktop::commands::db_backup_list,
+            agiworkforce_desktop::commands::db_backup_restore,
+            agiworkforce_desktop::commands::db_integrity_check,
+            agiworkforce_desktop::commands::db_backup_get_schedule,
+            agiworkforce_desktop::commands::db_backup_set_schedule,
+            agiworkforce_desktop::commands::retention_get_policy,
+            agiworkforce_desktop::commands::retention_set_policy,
+            agiworkforce_desktop::commands::retention_purge_now,
+            agiworkforce_desktop::commands::privacy_purge_all,
+            agiworkforce_desktop::commands::db_import_file,
             // Document reading commands
             agiworkforce_desktop::commands::document_read,
             agiworkforce_desktop::commands::document_extract_text,
@@ -967,6 +1649,25 @@ fn main() {
             agiworkforce_desktop::commands::document_create_excel_numbers,
             agiworkforce_desktop::commands::document_create_pdf,
             agiworkforce_desktop::commands::document_create_pdf_simple,
+            agiworkforce_desktop::commands::document_template_save,
+            agiworkforce_desktop::commands::document_template_list,
+            agiworkforce_desktop::commands::document_template_delete,
+            agiworkforce_desktop::commands::document_render_template,
+            agiworkforce_desktop::commands::document_convert,
+            agiworkforce_desktop::commands::document_to_markdown,
+            agiworkforce_desktop::commands::document_read_tabular,
+            agiworkforce_desktop::commands::document_ingest_archive,
+            // Project knowledge collections
+            agiworkforce_desktop::commands::project_collection_create,
+            agiworkforce_desktop::commands::project_collection_list,
+            agiworkforce_desktop::commands::project_collection_list_sources,
+            agiworkforce_desktop::commands::project_collection_delete,
+            agiworkforce_desktop::commands::project_collection_add_source,
+            agiworkforce_desktop::commands::project_collection_query,
+            // Web page fetching
+            agiworkforce_desktop::commands::web_fetch,
+            // Global search
+            agiworkforce_desktop::commands::search_global,
             // File operations for document processing
             agiworkforce_desktop::commands::file_read_text,
             agiworkforce_desktop::commands::file_write_text,
@@ -983,6 +1684,8 @@ fn main() {
             agiworkforce_desktop::commands::mcp_call_tool,
             agiworkforce_desktop::commands::mcp_get_config,
             agiworkforce_desktop::commands::mcp_update_config,
+            agiworkforce_desktop::commands::mcp_get_result_processing,
+            agiworkforce_desktop::commands::mcp_set_result_processing,
             agiworkforce_desktop::commands::mcp_enable_server,
             agiworkforce_desktop::commands::mcp_disable_server,
             agiworkforce_desktop::commands::mcp_get_stats,
@@ -991,6 +1694,37 @@ fn main() {
             agiworkforce_desktop::commands::mcp_get_tool_schemas,
             agiworkforce_desktop::commands::mcp_get_health,
             agiworkforce_desktop::commands::mcp_check_server_health,
+            agiworkforce_desktop::commands::mcp_get_server_statuses,
+            agiworkforce_desktop::commands::mcp_list_resources,
+            agiworkforce_desktop::commands::mcp_read_resource,
+            agiworkforce_desktop::commands::mcp_subscribe_resource,
+            agiworkforce_desktop::commands::mcp_unsubscribe_resource,
+            agiworkforce_desktop::commands::mcp_list_prompts,
+            agiworkforce_desktop::commands::mcp_get_prompt,
+            agiworkforce_desktop::commands::mcp_add_remote_server,
+            agiworkforce_desktop::commands::mcp_start_oauth,
+            agiworkforce_desktop::commands::mcp_complete_oauth,
+            agiworkforce_desktop::commands::mcp_sign_out,
+            agiworkforce_desktop::commands::mcp_server_status,
+            agiworkforce_desktop::commands::mcp_server_list_tools,
+            agiworkforce_desktop::commands::mcp_server_create_client,
+            agiworkforce_desktop::commands::mcp_server_update_client,
+            agiworkforce_desktop::commands::mcp_server_delete_client,
+            agiworkforce_desktop::commands::mcp_catalog_list,
+            agiworkforce_desktop::commands::mcp_catalog_install,
+            // Remote API commands
+            agiworkforce_desktop::commands::remote_api_status,
+            agiworkforce_desktop::commands::remote_api_configure,
+            agiworkforce_desktop::commands::remote_api_create_client,
+            agiworkforce_desktop::commands::remote_api_update_client,
+            agiworkforce_desktop::commands::remote_api_delete_client,
+            agiworkforce_desktop::commands::webhook_status,
+            agiworkforce_desktop::commands::webhook_configure,
+            agiworkforce_desktop::commands::webhook_create_endpoint,
+            agiworkforce_desktop::commands::webhook_update_endpoint,
+            agiworkforce_desktop::commands::webhook_delete_endpoint,
+            agiworkforce_desktop::commands::webhook_list_deliveries,
+            agiworkforce_desktop::commands::webhook_replay_delivery,
             // GitHub integration commands
             agiworkforce_desktop::commands::github_clone_repo,
             agiworkforce_desktop::commands::github_get_repo_context,
@@ -1035,6 +1769,36 @@ fn main() {
             agiworkforce_desktop::commands::shortcuts_reset,
             agiworkforce_desktop::commands::shortcuts_check_key,
             agiworkforce_desktop::commands::shortcuts_get_defaults,
+            // Platform capability report
+            agiworkforce_desktop::commands::platform_capabilities,
+            // Automation kill switch
+            agiworkforce_desktop::commands::kill_switch_engage,
+            agiworkforce_desktop::commands::kill_switch_rearm,
+            agiworkforce_desktop::commands::kill_switch_status,
+            // Quick launcher and command palette
+            agiworkforce_desktop::commands::launcher_toggle,
+            agiworkforce_desktop::commands::launcher_hide,
+            agiworkforce_desktop::commands::palette_query,
+            agiworkforce_desktop::commands::palette_open,
+            // Chat input autocomplete
+            agiworkforce_desktop::commands::autocomplete_query,
+            agiworkforce_desktop::commands::autocomplete_record_usage,
+            // Native notifications
+            agiworkforce_desktop::commands::notifications_get_preferences,
+            agiworkforce_desktop::commands::notifications_set_preferences,
+            agiworkforce_desktop::commands::notifications_dispatch_action,
+            // Credential expiry dashboard
+            agiworkforce_desktop::commands::credentials_health_overview,
+            agiworkforce_desktop::commands::credentials_health_check,
+            agiworkforce_desktop::commands::healthchecks_create,
+            agiworkforce_desktop::commands::healthchecks_list,
+            agiworkforce_desktop::commands::healthchecks_delete,
+            // Safe mode diagnostics and repair
+            agiworkforce_desktop::commands::safe_mode_status,
+            agiworkforce_desktop::commands::app_get_readiness,
+            agiworkforce_desktop::commands::safe_mode_diagnostics,
+            agiworkforce_desktop::commands::safe_mode_repair,
+            agiworkforce_desktop::commands::safe_mode_restart,
             // Workspace indexing commands
             agiworkforce_desktop::commands::workspace_index,
             agiworkforce_desktop::commands::workspace_search_symbols,
@@ -1043,6 +1807,17 @@ fn main() {
             agiworkforce_desktop::commands::workspace_get_dependencies,
             agiworkforce_desktop::commands::workspace_get_file_symbols,
             agiworkforce_desktop::commands::workspace_get_stats,
+            agiworkforce_desktop::commands::workspace_impact_analysis,
+            agiworkforce_desktop::commands::workspace_get_dependents,
+            agiworkforce_desktop::commands::workspace_find_cycles,
+            agiworkforce_desktop::commands::workspace_scan_dependencies,
+            agiworkforce_desktop::commands::workspace_refresh_advisories,
+            agiworkforce_desktop::commands::project_detect_tasks,
+            agiworkforce_desktop::commands::project_run_task,
+            agiworkforce_desktop::commands::project_cancel_task,
+            agiworkforce_desktop::commands::workspace_env_list,
+            agiworkforce_desktop::commands::workspace_env_get,
+            agiworkforce_desktop::commands::workspace_env_set,
             // LSP integration commands
             agiworkforce_desktop::commands::lsp_start_server,
             agiworkforce_desktop::commands::lsp_stop_server,
@@ -1104,14 +1879,23 @@ fn main() {
             agiworkforce_desktop::commands::delete_workflow,
             agiworkforce_desktop::commands::get_workflow,
             agiworkforce_desktop::commands::get_user_workflows,
+            agiworkforce_desktop::commands::get_user_workflows_page,
             agiworkforce_desktop::commands::execute_workflow,
             agiworkforce_desktop::commands::pause_workflow,
             agiworkforce_desktop::commands::resume_workflow,
             agiworkforce_desktop::commands::cancel_workflow,
             agiworkforce_desktop::commands::get_workflow_status,
             agiworkforce_desktop::commands::get_execution_logs,
+            agiworkforce_desktop::commands::workflow_get_artifacts,
             agiworkforce_desktop::commands::schedule_workflow,
             agiworkforce_desktop::commands::trigger_workflow_on_event,
+            agiworkforce_desktop::commands::workflow_trigger_list,
+            agiworkforce_desktop::commands::workflow_trigger_create,
+            agiworkforce_desktop::commands::workflow_trigger_update,
+            agiworkforce_desktop::commands::workflow_trigger_set_enabled,
+            agiworkforce_desktop::commands::workflow_trigger_delete,
+            agiworkforce_desktop::commands::workflow_export_bundle,
+            agiworkforce_desktop::commands::workflow_import_bundle,
             agiworkforce_desktop::commands::get_next_execution_time,
             // Marketplace commands - Public workflow sharing
             agiworkforce_desktop::commands::publish_workflow_to_marketplace,
@@ -1168,6 +1952,15 @@ fn main() {
             agiworkforce_desktop::commands::calculate_team_cost,
             agiworkforce_desktop::commands::update_team_usage,
             agiworkforce_desktop::commands::transfer_team_ownership,
+            // Team presence commands
+            agiworkforce_desktop::commands::connect_websocket,
+            agiworkforce_desktop::commands::get_team_presence,
+            agiworkforce_desktop::commands::get_user_presence,
+            agiworkforce_desktop::commands::update_user_activity,
+            agiworkforce_desktop::commands::set_user_online,
+            agiworkforce_desktop::commands::set_user_offline,
+            agiworkforce_desktop::commands::get_presence_policy,
+            agiworkforce_desktop::commands::set_presence_policy,
             // Process reasoning commands
             agiworkforce_desktop::commands::get_process_templates,
             agiworkforce_desktop::commands::get_outcome_tracking,
@@ -1179,6 +1972,7 @@ fn main() {
             agiworkforce_desktop::commands::get_template_by_id,
             agiworkforce_desktop::commands::get_templates_by_category,
             agiworkforce_desktop::commands::install_template,
+            agiworkforce_desktop::commands::template_check_requirements,
             agiworkforce_desktop::commands::get_installed_templates,
             agiworkforce_desktop::commands::search_templates,
             agiworkforce_desktop::commands::execute_template,
@@ -1214,6 +2008,10 @@ fn main() {
             agiworkforce_desktop::commands::ai_employees_run_demo,
             agiworkforce_desktop::commands::ai_employees_get_stats,
             agiworkforce_desktop::commands::ai_employees_publish,
+            agiworkforce_desktop::commands::employee_pipelines_create,
+            agiworkforce_desktop::commands::employee_pipelines_execute,
+            agiworkforce_desktop::commands::employee_pipelines_status,
+            agiworkforce_desktop::commands::employee_pipelines_approve,
             agiworkforce_desktop::commands::update_custom_employee,
             agiworkforce_desktop::commands::delete_custom_employee,
             agiworkforce_desktop::commands::publish_employee_to_marketplace,
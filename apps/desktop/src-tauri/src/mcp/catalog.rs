@@ -0,0 +1,301 @@
+use crate::mcp::config::CREDENTIAL_PLACEHOLDER;
+use crate::mcp::{McpError, McpResult, McpServerConfig};
+use crate::security::vault::{mcp_secret_id, Vault};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const CATALOG_JSON: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/mcp/catalog.json"));
+
+static CATALOG: Lazy<Vec<CatalogEntry>> = Lazy::new(|| {
+    serde_json::from_str(CATALOG_JSON).unwrap_or_else(|error| {
+        tracing::error!("Failed to parse packaged MCP catalog: {}", error);
+        Vec::new()
+    })
+});
+
+/// How a catalog server is installed and started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CatalogPackage {
+    /// Installed into the app's data folder with npm and run with node
+    Npm { package: String },
+    /// Installed with `uv tool install` and run with uvx
+    Uvx { package: String },
+    /// Prebuilt executable per `<os>-<arch>` target, e.g. `linux-x86_64`
+    Binary {
+        downloads: HashMap<String, BinaryDownload>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryDownload {
+    pub url: String,
+    /// Hex SHA-256 the download must match
+    pub sha256: String,
+}
+
+/// Environment variable a server needs from the user, kept in the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogCredential {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_true")]
+    pub required: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A known MCP server that can be installed in one step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// Also the server's name in the MCP config
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    pub package: CatalogPackage,
+    /// Arguments after the server's command; `{home}` is the user's home folder
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Fixed, non-secret environment variables
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub credentials: Vec<CatalogCredential>,
+}
+
+/// Servers in the packaged catalog
+pub fn catalog() -> &'static [CatalogEntry] {
+    &CATALOG
+}
+
+pub fn find_catalog_entry(id: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|entry| entry.id == id)
+}
+
+impl CatalogEntry {
+    /// Required credentials that are neither in `given` nor in the vault
+    pub fn missing_credentials(
+        &self,
+        given: &HashMap<String, String>,
+        vault: &Vault,
+    ) -> Vec<CatalogCredential> {
+        self.credentials
+            .iter()
+            .filter(|credential| credential.required)
+            .filter(|credential| {
+                given
+                    .get(&credential.key)
+                    .is_none_or(|value| value.trim().is_empty())
+            })
+            .filter(|credential| {
+                !matches!(
+                    vault.get(&mcp_secret_id(&self.id, &credential.key), "mcp"),
+                    Ok(Some(_))
+                )
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Config that runs `command` with the entry's arguments; credentials are
+    /// placeholders filled from the vault on connect
+    fn server_config(&self, command: String, mut args: Vec<String>) -> McpServerConfig {
+        let home = dirs::home_dir()
+            .map(|home| home.to_string_lossy().to_string())
+            .unwrap_or_else(|| ".".to_string());
+        args.extend(self.args.iter().map(|arg| arg.replace("{home}", &home)));
+
+        let mut env = self.env.clone();
+        for credential in &self.credentials {
+            env.insert(credential.key.clone(), CREDENTIAL_PLACEHOLDER.to_string());
+        }
+
+        McpServerConfig {
+            command,
+            args,
+            url: None,
+            env,
+            enabled: true,
+        }
+    }
+}
+
+/// Folder catalog servers are installed into
+fn install_dir(id: &str) -> McpResult<PathBuf> {
+    let app_data = dirs::data_dir().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Failed to get app data directory",
+        )
+    })?;
+    Ok(app_data.join("agiworkforce").join("mcp-servers").join(id))
+}
+
+/// Install a catalog server and return the config that starts it
+pub async fn install_catalog_server(entry: &CatalogEntry) -> McpResult<McpServerConfig> {
+    let dir = install_dir(&entry.id)?;
+    tokio::fs::create_dir_all(&dir).await?;
+    tracing::info!(
+        "Installing MCP server '{}' into {}",
+        entry.id,
+        dir.display()
+    );
+
+    let config = match &entry.package {
+        CatalogPackage::Npm { package } => {
+            let prefix = dir.to_string_lossy().to_string();
+            run(
+                "npm",
+                &[
+                    "install",
+                    "--prefix",
+                    &prefix,
+                    "--no-audit",
+                    "--no-fund",
+                    package,
+                ],
+            )
+            .await?;
+            let script = npm_entry_point(&dir, package)?;
+            entry.server_config("node".to_string(), vec![script])
+        }
+        CatalogPackage::Uvx { package } => {
+            run("uv", &["tool", "install", package]).await?;
+            entry.server_config("uvx".to_string(), vec![package.clone()])
+        }
+        CatalogPackage::Binary { downloads } => {
+            let target = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
+            let download = downloads.get(&target).ok_or_else(|| {
+                McpError::InstallError(format!("{} has no build for {}", entry.name, target))
+            })?;
+            let path = dir.join(format!("{}{}", entry.id, std::env::consts::EXE_SUFFIX));
+            download_binary(download, &path).await?;
+            entry.server_config(path.to_string_lossy().to_string(), Vec::new())
+        }
+    };
+    Ok(config)
+}
+
+async fn run(program: &str, args: &[&str]) -> McpResult<()> {
+    let path = which::which(program).map_err(|_| {
+        McpError::InstallError(format!(
+            "{} is required to install this server but was not found on PATH",
+            program
+        ))
+    })?;
+    let output = tokio::process::Command::new(path)
+        .args(args)
+        .output()
+        .await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(McpError::InstallError(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Script an npm package installed under `dir` declares as its executable
+fn npm_entry_point(dir: &Path, package: &str) -> McpResult<String> {
+    let package_dir = dir.join("node_modules").join(package);
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(package_dir.join("package.json"))?)?;
+    let bin = match manifest.get("bin") {
+        Some(serde_json::Value::String(path)) => Some(path.as_str()),
+        Some(serde_json::Value::Object(bins)) => bins.values().find_map(|path| path.as_str()),
+        _ => None,
+    }
+    .ok_or_else(|| McpError::InstallError(format!("{} does not declare an executable", package)))?;
+    Ok(package_dir.join(bin).to_string_lossy().to_string())
+}
+
+async fn download_binary(download: &BinaryDownload, path: &Path) -> McpResult<()> {
+    let bytes = reqwest::get(&download.url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| McpError::InstallError(format!("Download failed: {}", e)))?
+        .bytes()
+        .await
+        .map_err(|e| McpError::InstallError(format!("Download failed: {}", e)))?;
+    let digest = hex::encode(Sha256::digest(&bytes));
+    if !digest.eq_ignore_ascii_case(&download.sha256) {
+        return Err(McpError::InstallError(format!(
+            "Checksum mismatch for {}",
+            download.url
+        )));
+    }
+
+    tokio::fs::write(path, &bytes).await?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_parses_with_unique_ids() {
+        let entries = catalog();
+        assert!(entries.len() >= 4);
+        let mut ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), entries.len());
+        assert!(find_catalog_entry("github").is_some());
+        assert!(find_catalog_entry("postgres").is_some());
+    }
+
+    #[test]
+    fn test_server_config_uses_credential_placeholders() {
+        let entry = find_catalog_entry("github").unwrap();
+        let config = entry.server_config("node".to_string(), vec!["server.js".to_string()]);
+        assert_eq!(config.args, ["server.js"]);
+        assert_eq!(
+            config.env["GITHUB_PERSONAL_ACCESS_TOKEN"],
+            CREDENTIAL_PLACEHOLDER
+        );
+
+        let filesystem = find_catalog_entry("filesystem").unwrap();
+        let config = filesystem.server_config("node".to_string(), Vec::new());
+        assert!(!config.args[0].contains("{home}"));
+    }
+
+    #[test]
+    fn test_npm_entry_point_reads_bin() {
+        let dir = tempfile::tempdir().unwrap();
+        let package_dir = dir
+            .path()
+            .join("node_modules")
+            .join("@scope")
+            .join("server");
+        std::fs::create_dir_all(&package_dir).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{ "name": "@scope/server", "bin": { "mcp-server": "dist/index.js" } }"#,
+        )
+        .unwrap();
+
+        let script = npm_entry_point(dir.path(), "@scope/server").unwrap();
+        assert!(script.ends_with("index.js"));
+        assert!(npm_entry_point(dir.path(), "@scope/missing").is_err());
+    }
+}
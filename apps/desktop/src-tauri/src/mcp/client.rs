@@ -2,13 +2,19 @@
 //
 // This replaces the stub client with a real implementation using the MCP protocol
 
-use super::protocol::McpToolDefinition;
+use super::oauth::McpOAuth;
+use super::protocol::{
+    McpToolDefinition, PromptDefinition, PromptGetResult, ResourceContent, ResourceDefinition,
+    ResourceReadResult,
+};
 use super::session::McpSession;
 use crate::mcp::{McpError, McpResult, McpServerConfig};
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 /// MCP Tool (simplified view for AGI integration)
 #[derive(Debug, Clone)]
@@ -28,9 +34,36 @@ impl From<McpToolDefinition> for McpTool {
     }
 }
 
+/// A resource and the server that provides it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResource {
+    pub server_name: String,
+    #[serde(flatten)]
+    pub resource: ResourceDefinition,
+}
+
+/// A prompt template and the server that provides it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPrompt {
+    pub server_name: String,
+    #[serde(flatten)]
+    pub prompt: PromptDefinition,
+}
+
+/// A notification a connected server sent, e.g. `notifications/resources/updated`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerNotification {
+    pub server_name: String,
+    pub method: String,
+    pub params: Option<Value>,
+}
+
 /// MCP Client manager that handles multiple MCP servers
 pub struct McpClient {
     sessions: Arc<RwLock<HashMap<String, Arc<McpSession>>>>,
+    notifications: broadcast::Sender<ServerNotification>,
+    /// Authorizes requests to remote servers
+    oauth: RwLock<Option<Arc<McpOAuth>>>,
 }
 
 impl McpClient {
@@ -38,15 +71,31 @@ impl McpClient {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            notifications: broadcast::channel(64).0,
+            oauth: RwLock::new(None),
         }
     }
 
+    /// Authorize remote servers with tokens from `oauth`
+    pub fn set_oauth(&self, oauth: Arc<McpOAuth>) {
+        *self.oauth.write() = Some(oauth);
+    }
+
+    pub fn oauth(&self) -> Option<Arc<McpOAuth>> {
+        self.oauth.read().clone()
+    }
+
+    /// Notifications from every connected server
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notifications.subscribe()
+    }
+
     /// Connect to an MCP server
     pub async fn connect_server(&self, name: String, config: McpServerConfig) -> McpResult<()> {
         tracing::info!("[MCP Client] Connecting to server '{}'", name);
 
         // Create session
-        let mut session = McpSession::connect(name.clone(), config).await?;
+        let mut session = McpSession::connect_with_auth(name.clone(), config, self.oauth()).await?;
 
         // Initialize
         let init_result = session.initialize().await?;
@@ -65,6 +114,19 @@ impl McpClient {
             tools.len()
         );
 
+        let mut notifications = session.notifications();
+        let forward = self.notifications.clone();
+        let server_name = name.clone();
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                let _ = forward.send(ServerNotification {
+                    server_name: server_name.clone(),
+                    method: notification.method,
+                    params: notification.params,
+                });
+            }
+        });
+
         // Store session
         self.sessions
             .write()
@@ -177,6 +239,99 @@ impl McpClient {
         Ok(serde_json::to_value(result)?)
     }
 
+    fn session(&self, server_name: &str) -> McpResult<Arc<McpSession>> {
+        self.sessions
+            .read()
+            .get(server_name)
+            .cloned()
+            .ok_or_else(|| McpError::ServerNotFound(format!("Server '{}' not found", server_name)))
+    }
+
+    /// Resources of one server, or of every connected server that has any
+    pub async fn list_resources(&self, server_name: Option<&str>) -> McpResult<Vec<McpResource>> {
+        let sessions: Vec<(String, Arc<McpSession>)> = match server_name {
+            Some(name) => vec![(name.to_string(), self.session(name)?)],
+            None => self
+                .sessions
+                .read()
+                .iter()
+                .filter(|(_, session)| session.supports_resources())
+                .map(|(name, session)| (name.clone(), session.clone()))
+                .collect(),
+        };
+
+        let mut resources = Vec::new();
+        for (name, session) in sessions {
+            match session.list_resources().await {
+                Ok(listed) => resources.extend(listed.into_iter().map(|resource| McpResource {
+                    server_name: name.clone(),
+                    resource,
+                })),
+                Err(e) if server_name.is_some() => return Err(e),
+                Err(e) => {
+                    tracing::warn!("[MCP Client] Failed to list resources of '{}': {}", name, e)
+                }
+            }
+        }
+        Ok(resources)
+    }
+
+    pub async fn read_resource(
+        &self,
+        server_name: &str,
+        uri: &str,
+    ) -> McpResult<ResourceReadResult> {
+        self.session(server_name)?.read_resource(uri).await
+    }
+
+    /// Updates arrive as `notifications/resources/updated` through
+    /// [`Self::subscribe_notifications`]
+    pub async fn subscribe_resource(&self, server_name: &str, uri: &str) -> McpResult<()> {
+        self.session(server_name)?.subscribe_resource(uri).await
+    }
+
+    pub async fn unsubscribe_resource(&self, server_name: &str, uri: &str) -> McpResult<()> {
+        self.session(server_name)?.unsubscribe_resource(uri).await
+    }
+
+    /// Prompt templates of one server, or of every connected server that has any
+    pub async fn list_prompts(&self, server_name: Option<&str>) -> McpResult<Vec<McpPrompt>> {
+        let sessions: Vec<(String, Arc<McpSession>)> = match server_name {
+            Some(name) => vec![(name.to_string(), self.session(name)?)],
+            None => self
+                .sessions
+                .read()
+                .iter()
+                .filter(|(_, session)| session.supports_prompts())
+                .map(|(name, session)| (name.clone(), session.clone()))
+                .collect(),
+        };
+
+        let mut prompts = Vec::new();
+        for (name, session) in sessions {
+            match session.list_prompts().await {
+                Ok(listed) => prompts.extend(listed.into_iter().map(|prompt| McpPrompt {
+                    server_name: name.clone(),
+                    prompt,
+                })),
+                Err(e) if server_name.is_some() => return Err(e),
+                Err(e) => {
+                    tracing::warn!("[MCP Client] Failed to list prompts of '{}': {}", name, e)
+                }
+            }
+        }
+        Ok(prompts)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        server_name: &str,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> McpResult<PromptGetResult> {
+        self.session(server_name)?.get_prompt(name, arguments).await
+    }
+
     /// Search for tools across all servers
     pub fn search_tools(&self, query: &str) -> Vec<(String, McpTool)> {
         let sessions = self.sessions.read();
@@ -228,6 +383,40 @@ impl McpClient {
     }
 }
 
+/// Chat context from resources read for a message. Text is cut after
+/// `max_chars` per resource; binary contents are only named
+pub fn format_resource_context(
+    resources: &[(String, ResourceReadResult)],
+    max_chars: usize,
+) -> String {
+    let mut context = String::from(
+        "The user attached the following resources from MCP servers. Use them when they are \
+         relevant.\n",
+    );
+    for (server_name, result) in resources {
+        for content in &result.contents {
+            match content {
+                ResourceContent::Text { uri, text, .. } => {
+                    let mut excerpt: String = text.trim().chars().take(max_chars).collect();
+                    if excerpt.len() < text.trim().len() {
+                        excerpt.push_str("\n[truncated]");
+                    }
+                    context.push_str(&format!("\n[{}] {}\n{}\n", server_name, uri, excerpt));
+                }
+                ResourceContent::Blob { uri, mime_type, .. } => {
+                    context.push_str(&format!(
+                        "\n[{}] {}\n(binary content, {})\n",
+                        server_name,
+                        uri,
+                        mime_type.as_deref().unwrap_or("unknown type")
+                    ));
+                }
+            }
+        }
+    }
+    context
+}
+
 impl Default for McpClient {
     fn default() -> Self {
         Self::new()
@@ -251,6 +440,29 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn test_resource_context_truncates_text() {
+        let read = ResourceReadResult {
+            contents: vec![
+                ResourceContent::Text {
+                    uri: "file:///notes.md".to_string(),
+                    mime_type: Some("text/markdown".to_string()),
+                    text: "abcdefghij".to_string(),
+                },
+                ResourceContent::Blob {
+                    uri: "file:///logo.png".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    blob: "iVBORw0KGgo=".to_string(),
+                },
+            ],
+        };
+
+        let context = format_resource_context(&[("files".to_string(), read)], 4);
+        assert!(context.contains("[files] file:///notes.md\nabcd\n[truncated]"));
+        assert!(!context.contains("efgh"));
+        assert!(context.contains("(binary content, image/png)"));
+    }
+
     #[test]
     fn test_mcp_tool_conversion() {
         let def = McpToolDefinition {
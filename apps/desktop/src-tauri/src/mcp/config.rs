@@ -1,7 +1,12 @@
+use crate::mcp::result_processor::ResultProcessingSettings;
+use crate::security::vault::{mcp_secret_id, Vault};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Env value standing in for a credential kept in the vault
+pub(crate) const CREDENTIAL_PLACEHOLDER: &str = "<from_credential_manager>";
+
 const DEFAULT_CONFIG_JSON: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/mcp/default_servers.json"
@@ -11,11 +16,18 @@ const DEFAULT_CONFIG_JSON: &str = include_str!(concat!(
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerConfig {
     /// Command to run the server (e.g., "npx", "python", "node")
+    #[serde(default)]
     pub command: String,
 
     /// Arguments to pass to the command
+    #[serde(default)]
     pub args: Vec<String>,
 
+    /// Endpoint of a remote server reached over Streamable HTTP instead of
+    /// a local process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
     /// Environment variables for the server
     #[serde(default)]
     pub env: HashMap<String, String>,
@@ -34,6 +46,10 @@ fn default_true() -> bool {
 pub struct McpServersConfig {
     #[serde(rename = "mcpServers")]
     pub mcp_servers: HashMap<String, McpServerConfig>,
+
+    /// How tool results are trimmed before they re-enter the model
+    #[serde(default, rename = "resultProcessing")]
+    pub result_processing: ResultProcessingSettings,
 }
 
 impl McpServersConfig {
@@ -94,6 +110,7 @@ impl McpServersConfig {
                     "@modelcontextprotocol/server-filesystem".to_string(),
                     ".".to_string(), // Current directory
                 ],
+                url: None,
                 env: HashMap::new(),
                 enabled: true,
             },
@@ -108,11 +125,12 @@ impl McpServersConfig {
                     "-y".to_string(),
                     "@modelcontextprotocol/server-github".to_string(),
                 ],
+                url: None,
                 env: {
                     let mut env = HashMap::new();
                     env.insert(
                         "GITHUB_PERSONAL_ACCESS_TOKEN".to_string(),
-                        "<from_credential_manager>".to_string(),
+                        CREDENTIAL_PLACEHOLDER.to_string(),
                     );
                     env
                 },
@@ -129,6 +147,7 @@ impl McpServersConfig {
                     "-y".to_string(),
                     "@modelcontextprotocol/server-gdrive".to_string(),
                 ],
+                url: None,
                 env: HashMap::new(),
                 enabled: false,
             },
@@ -143,11 +162,12 @@ impl McpServersConfig {
                     "-y".to_string(),
                     "@modelcontextprotocol/server-slack".to_string(),
                 ],
+                url: None,
                 env: {
                     let mut env = HashMap::new();
                     env.insert(
                         "SLACK_BOT_TOKEN".to_string(),
-                        "<from_credential_manager>".to_string(),
+                        CREDENTIAL_PLACEHOLDER.to_string(),
                     );
                     env
                 },
@@ -164,11 +184,12 @@ impl McpServersConfig {
                     "-y".to_string(),
                     "@modelcontextprotocol/server-brave-search".to_string(),
                 ],
+                url: None,
                 env: {
                     let mut env = HashMap::new();
                     env.insert(
                         "BRAVE_API_KEY".to_string(),
-                        "<from_credential_manager>".to_string(),
+                        CREDENTIAL_PLACEHOLDER.to_string(),
                     );
                     env
                 },
@@ -185,11 +206,12 @@ impl McpServersConfig {
                     "-y".to_string(),
                     "@modelcontextprotocol/server-stripe".to_string(),
                 ],
+                url: None,
                 env: {
                     let mut env = HashMap::new();
                     env.insert(
                         "STRIPE_SECRET_KEY".to_string(),
-                        "<from_credential_manager>".to_string(),
+                        CREDENTIAL_PLACEHOLDER.to_string(),
                     );
                     env
                 },
@@ -197,24 +219,48 @@ impl McpServersConfig {
             },
         );
 
-        McpServersConfig { mcp_servers }
+        McpServersConfig {
+            mcp_servers,
+            result_processing: ResultProcessingSettings::default(),
+        }
     }
 
-    /// Inject credentials from Windows Credential Manager
-    pub fn inject_credentials(&mut self) -> crate::mcp::McpResult<()> {
-        for (server_name, config) in &mut self.mcp_servers {
-            for (key, value) in &mut config.env {
-                if value == "<from_credential_manager>" {
-                    // Try to get credential from keyring
-                    let service = format!("agiworkforce-mcp-{}", server_name);
-                    if let Ok(entry) = keyring::Entry::new(&service, key) {
-                        if let Ok(password) = entry.get_password() {
-                            *value = password;
-                        } else {
-                            tracing::warn!("Credential not found for {} / {}", server_name, key);
-                        }
+    /// Replace `<from_credential_manager>` placeholders with credentials
+    /// from the vault
+    pub fn inject_credentials(&mut self, vault: &Vault) -> crate::mcp::McpResult<()> {
+        let wanted: Vec<(String, String)> = self
+            .mcp_servers
+            .iter()
+            .flat_map(|(server_name, config)| {
+                config
+                    .env
+                    .iter()
+                    .filter(|(_, value)| *value == CREDENTIAL_PLACEHOLDER)
+                    .map(move |(key, _)| (server_name.clone(), key.clone()))
+            })
+            .collect();
+        if let Err(e) = vault.migrate_legacy_mcp_credentials(&wanted) {
+            tracing::warn!("Failed to move MCP credentials into the vault: {}", e);
+        }
+
+        for (server_name, key) in wanted {
+            match vault.get(&mcp_secret_id(&server_name, &key), "mcp") {
+                Ok(Some(secret)) => {
+                    if let Some(value) = self
+                        .mcp_servers
+                        .get_mut(&server_name)
+                        .and_then(|config| config.env.get_mut(&key))
+                    {
+                        *value = secret.expose_secret().to_string();
                     }
                 }
+                Ok(None) => tracing::warn!("Credential not found for {} / {}", server_name, key),
+                Err(e) => tracing::warn!(
+                    "Failed to read credential for {} / {}: {}",
+                    server_name,
+                    key,
+                    e
+                ),
             }
         }
         Ok(())
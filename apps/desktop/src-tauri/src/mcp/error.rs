@@ -23,6 +23,15 @@ pub enum McpError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("Failed to install MCP server: {0}")]
+    InstallError(String),
+
+    #[error("Authorization required for MCP server: {0}")]
+    AuthorizationRequired(String),
+
+    #[error("MCP authorization failed: {0}")]
+    AuthError(String),
+
     #[error("RMCP error: {0}")]
     RmcpError(String),
 }
@@ -35,6 +35,14 @@ pub enum McpEvent {
     },
     /// Configuration updated
     ConfigurationUpdated { servers_enabled: Vec<String> },
+    /// A subscribed resource changed on the server
+    ResourceUpdated { server_name: String, uri: String },
+    /// Server resource list changed
+    ResourcesChanged { server_name: String },
+    /// Server prompt list changed
+    PromptsChanged { server_name: String },
+    /// A remote server needs the user to sign in
+    AuthorizationRequired { server_name: String },
 }
 
 impl McpEvent {
@@ -47,6 +55,10 @@ impl McpEvent {
             Self::ToolExecutionCompleted { .. } => "mcp://tool-execution-completed",
             Self::SystemInitialized { .. } => "mcp://system-initialized",
             Self::ConfigurationUpdated { .. } => "mcp://configuration-updated",
+            Self::ResourceUpdated { .. } => "mcp://resource-updated",
+            Self::ResourcesChanged { .. } => "mcp://resources-changed",
+            Self::PromptsChanged { .. } => "mcp://prompts-changed",
+            Self::AuthorizationRequired { .. } => "mcp://authorization-required",
         }
     }
 }
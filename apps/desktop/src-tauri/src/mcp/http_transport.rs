@@ -0,0 +1,662 @@
+// MCP Streamable HTTP Transport
+//
+// Talks to remote MCP servers over a single endpoint: messages are POSTed and
+// replies come back as JSON or as an SSE stream, while server-initiated
+// notifications arrive on a standing GET stream. The session id the server
+// hands out goes with every request; when the server forgets the session, a
+// new one is initialized and the request retried. Streams that drop before
+// the reply are resumed with `Last-Event-ID`.
+
+use super::oauth::McpOAuth;
+use super::protocol::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, McpMessage, RequestId,
+};
+use crate::mcp::{McpError, McpResult};
+use crate::security::SecretString;
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, WWW_AUTHENTICATE};
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use url::Url;
+
+/// Protocol revision sent when initializing over HTTP
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+const PROTOCOL_HEADER: &str = "MCP-Protocol-Version";
+const LAST_EVENT_HEADER: &str = "Last-Event-ID";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Reconnects to a reply stream that dropped before the reply
+const RESUME_ATTEMPTS: u32 = 3;
+const MAX_LISTEN_BACKOFF: Duration = Duration::from_secs(30);
+/// Largest SSE event accepted
+const MAX_EVENT_BYTES: usize = 8 * 1024 * 1024;
+
+type PendingRequests = HashMap<RequestId, oneshot::Sender<McpResult<JsonRpcResponse>>>;
+
+/// Remote servers need https; plain http is only allowed on this machine
+pub fn validate_url(url: &str) -> McpResult<String> {
+    let parsed = Url::parse(url)
+        .map_err(|e| McpError::InvalidConfig(format!("Invalid server URL '{}': {}", url, e)))?;
+    let loopback = matches!(parsed.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    match parsed.scheme() {
+        "https" => Ok(parsed.to_string()),
+        "http" if loopback => Ok(parsed.to_string()),
+        _ => Err(McpError::InvalidConfig(format!(
+            "Remote MCP servers must use https: {}",
+            url
+        ))),
+    }
+}
+
+/// One SSE event; only the fields MCP uses
+#[derive(Debug, Default, PartialEq)]
+struct SseEvent {
+    id: Option<String>,
+    data: String,
+}
+
+/// Splits received bytes into SSE events
+#[derive(Default)]
+struct SseBuffer {
+    buffer: Vec<u8>,
+}
+
+impl SseBuffer {
+    /// Add received bytes, returning the events they complete
+    fn push(&mut self, bytes: &[u8]) -> McpResult<Vec<SseEvent>> {
+        self.buffer
+            .extend(bytes.iter().filter(|byte| **byte != b'\r'));
+        if self.buffer.len() > MAX_EVENT_BYTES {
+            return Err(McpError::ConnectionError(
+                "SSE event exceeds the size limit".to_string(),
+            ));
+        }
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|pair| pair == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let text = String::from_utf8_lossy(&block[..end]);
+            let mut event = SseEvent::default();
+            let mut data = Vec::new();
+            for line in text.lines().filter(|line| !line.starts_with(':')) {
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match field {
+                    "id" => event.id = Some(value.to_string()),
+                    "data" => data.push(value),
+                    _ => {}
+                }
+            }
+            if event.id.is_some() || !data.is_empty() {
+                event.data = data.join("\n");
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// State shared with the standing GET stream
+struct Shared {
+    server_name: String,
+    url: String,
+    http: reqwest::Client,
+    auth: Option<Arc<McpOAuth>>,
+    session_id: Mutex<Option<String>>,
+    protocol_version: Mutex<Option<String>>,
+    pending: Mutex<PendingRequests>,
+    notifications: Mutex<Option<mpsc::UnboundedSender<JsonRpcNotification>>>,
+    /// Cleared while the server is unreachable
+    healthy: AtomicBool,
+    closed: AtomicBool,
+}
+
+impl Shared {
+    async fn token(&self) -> McpResult<Option<SecretString>> {
+        match &self.auth {
+            Some(auth) => auth.access_token(&self.server_name).await,
+            None => Ok(None),
+        }
+    }
+
+    fn request(&self, method: Method, token: Option<&SecretString>) -> reqwest::RequestBuilder {
+        let mut request = self.http.request(method, &self.url);
+        let session_id = self.session_id.lock().clone();
+        if let Some(session_id) = session_id {
+            request = request.header(SESSION_HEADER, session_id);
+        }
+        let protocol_version = self.protocol_version.lock().clone();
+        if let Some(protocol_version) = protocol_version {
+            request = request.header(PROTOCOL_HEADER, protocol_version);
+        }
+        if let Some(token) = token {
+            request = request.bearer_auth(token.expose_secret());
+        }
+        request
+    }
+
+    fn is_waiting(&self, id: &RequestId) -> bool {
+        self.pending.lock().contains_key(id)
+    }
+
+    fn dispatch(&self, message: McpMessage) {
+        match message {
+            McpMessage::Response(response) => match self.pending.lock().remove(&response.id) {
+                Some(sender) => {
+                    let _ = sender.send(Ok(response));
+                }
+                None => tracing::warn!(
+                    "[MCP HTTP] Received response for unknown request: {:?}",
+                    response.id
+                ),
+            },
+            McpMessage::Error(error) => match self.pending.lock().remove(&error.id) {
+                Some(sender) => {
+                    let _ = sender.send(Err(McpError::RmcpError(error.error.message)));
+                }
+                None => tracing::warn!(
+                    "[MCP HTTP] Received error for unknown request: {:?}",
+                    error.id
+                ),
+            },
+            McpMessage::Notification(notification) => {
+                tracing::info!("[MCP HTTP] Received notification: {}", notification.method);
+                if let Some(sender) = self.notifications.lock().as_ref() {
+                    let _ = sender.send(notification);
+                }
+            }
+            McpMessage::Request(request) => {
+                tracing::warn!(
+                    "[MCP HTTP] Received request '{}' from server (not supported in client mode)",
+                    request.method
+                );
+            }
+        }
+    }
+
+    /// Dispatch the messages of an SSE stream until it ends or `waiting` is
+    /// answered
+    async fn read_events(
+        &self,
+        response: reqwest::Response,
+        waiting: Option<&RequestId>,
+        last_event_id: &mut Option<String>,
+    ) -> McpResult<()> {
+        let mut stream = response.bytes_stream();
+        let mut buffer = SseBuffer::default();
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::debug!(
+                        "[MCP HTTP] Stream from '{}' dropped: {}",
+                        self.server_name,
+                        e
+                    );
+                    return Ok(());
+                }
+            };
+            for event in buffer.push(&chunk)? {
+                if event.id.is_some() {
+                    *last_event_id = event.id;
+                }
+                // Priming events only carry an id to resume from
+                if event.data.is_empty() {
+                    continue;
+                }
+                match McpMessage::from_str(&event.data) {
+                    Ok(message) => self.dispatch(message),
+                    Err(e) => tracing::error!("[MCP HTTP] Failed to parse message: {}", e),
+                }
+            }
+            if waiting.is_some_and(|id| !self.is_waiting(id)) {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a GET stream, resuming after `last_event_id`. `None` when the
+    /// server offers none
+    async fn open_stream(
+        &self,
+        last_event_id: Option<&str>,
+    ) -> McpResult<Option<reqwest::Response>> {
+        let token = self.token().await?;
+        let mut request = self
+            .request(Method::GET, token.as_ref())
+            .header(ACCEPT, "text/event-stream");
+        if let Some(last_event_id) = last_event_id {
+            request = request.header(LAST_EVENT_HEADER, last_event_id);
+        }
+        let response = request.send().await.map_err(|e| {
+            McpError::ConnectionError(format!("Stream from '{}' failed: {}", self.server_name, e))
+        })?;
+        let is_stream = content_type(&response).starts_with("text/event-stream");
+        Ok((response.status().is_success() && is_stream).then_some(response))
+    }
+
+    /// Keep the standing GET stream open for server-initiated messages
+    async fn listen(self: Arc<Self>) {
+        let mut last_event_id = None;
+        let mut backoff = Duration::from_secs(1);
+        while !self.closed.load(Ordering::SeqCst) {
+            match self.open_stream(last_event_id.as_deref()).await {
+                Ok(Some(response)) => {
+                    backoff = Duration::from_secs(1);
+                    if let Err(e) = self.read_events(response, None, &mut last_event_id).await {
+                        tracing::warn!(
+                            "[MCP HTTP] Stream from '{}' failed: {}",
+                            self.server_name,
+                            e
+                        );
+                    }
+                }
+                Ok(None) => return,
+                Err(e) => tracing::debug!("{}", e),
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_LISTEN_BACKOFF);
+        }
+    }
+}
+
+fn content_type(response: &reqwest::Response) -> &str {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+}
+
+/// Streamable HTTP transport for remote MCP servers
+pub struct StreamableHttpTransport {
+    shared: Arc<Shared>,
+    /// Request ID counter
+    request_id: AtomicU64,
+    /// Replayed when the server drops the session
+    initialize_params: Mutex<Option<Value>>,
+    /// Standing GET stream
+    listener: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl StreamableHttpTransport {
+    /// Create a transport for the server at `url`; nothing is sent until the
+    /// first request
+    pub fn new(server_name: String, url: &str, auth: Option<Arc<McpOAuth>>) -> McpResult<Self> {
+        let url = validate_url(url)?;
+        tracing::info!(
+            "[MCP HTTP] Using remote server '{}' at {}",
+            server_name,
+            url
+        );
+
+        let http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| {
+                McpError::ConnectionError(format!("Failed to create HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            shared: Arc::new(Shared {
+                server_name,
+                url,
+                http,
+                auth,
+                session_id: Mutex::new(None),
+                protocol_version: Mutex::new(None),
+                pending: Mutex::new(HashMap::new()),
+                notifications: Mutex::new(None),
+                healthy: AtomicBool::new(true),
+                closed: AtomicBool::new(false),
+            }),
+            request_id: AtomicU64::new(1),
+            initialize_params: Mutex::new(None),
+            listener: Mutex::new(None),
+        })
+    }
+
+    /// Receive the server's notifications; replaces an earlier receiver
+    pub fn notifications(&self) -> mpsc::UnboundedReceiver<JsonRpcNotification> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.shared.notifications.lock() = Some(sender);
+        receiver
+    }
+
+    /// Send a request and wait for response
+    pub async fn send_request(
+        &self,
+        method: String,
+        params: Option<Value>,
+    ) -> McpResult<JsonRpcResponse> {
+        if method == "initialize" {
+            *self.initialize_params.lock() = params.clone();
+            *self.shared.session_id.lock() = None;
+            return self.initialize(params).await;
+        }
+
+        match self.exchange(&method, params.clone()).await? {
+            Some(response) => Ok(response),
+            None => {
+                tracing::info!(
+                    "[MCP HTTP] Server '{}' ended the session; starting a new one",
+                    self.shared.server_name
+                );
+                self.reinitialize().await?;
+                self.exchange(&method, params).await?.ok_or_else(|| {
+                    McpError::ConnectionError(format!(
+                        "Server '{}' rejected the new session",
+                        self.shared.server_name
+                    ))
+                })
+            }
+        }
+    }
+
+    /// Send a notification (no response expected)
+    pub async fn send_notification(&self, method: String, params: Option<Value>) {
+        let initialized = method == "notifications/initialized";
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method,
+            params,
+        };
+        match serde_json::to_value(&notification) {
+            Ok(body) => {
+                if let Err(e) = self.post(&body, None).await {
+                    tracing::warn!("[MCP HTTP] Failed to send notification: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("[MCP HTTP] Failed to serialize notification: {}", e),
+        }
+
+        if initialized {
+            let task = tokio::spawn(self.shared.clone().listen());
+            if let Some(previous) = self.listener.lock().replace(task) {
+                previous.abort();
+            }
+        }
+    }
+
+    async fn initialize(&self, params: Option<Value>) -> McpResult<JsonRpcResponse> {
+        let response = self.exchange("initialize", params).await?.ok_or_else(|| {
+            McpError::ConnectionError(format!(
+                "Server '{}' rejected initialization",
+                self.shared.server_name
+            ))
+        })?;
+        let protocol_version = response
+            .result
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or(PROTOCOL_VERSION);
+        *self.shared.protocol_version.lock() = Some(protocol_version.to_string());
+        Ok(response)
+    }
+
+    /// Start a new session after the server dropped the old one
+    async fn reinitialize(&self) -> McpResult<()> {
+        *self.shared.session_id.lock() = None;
+        let params = self.initialize_params.lock().clone();
+        self.initialize(params).await?;
+        self.send_notification("notifications/initialized".to_string(), None)
+            .await;
+        Ok(())
+    }
+
+    /// Send one request; `None` when the server no longer knows the session
+    async fn exchange(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> McpResult<Option<JsonRpcResponse>> {
+        let id = RequestId::Number(self.request_id.fetch_add(1, Ordering::SeqCst) as i64);
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: id.clone(),
+        };
+        let body = serde_json::to_value(&request)?;
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.shared.pending.lock().insert(id.clone(), response_tx);
+
+        let exchange = async {
+            if !self.post(&body, Some(&id)).await? {
+                return Ok(None);
+            }
+            match response_rx.await {
+                Ok(result) => result.map(Some),
+                Err(_) => Err(McpError::ConnectionError(
+                    "Response channel closed".to_string(),
+                )),
+            }
+        };
+        let result = match tokio::time::timeout(REQUEST_TIMEOUT, exchange).await {
+            Ok(result) => result,
+            Err(_) => Err(McpError::ConnectionError("Request timeout".to_string())),
+        };
+        self.shared.pending.lock().remove(&id);
+        result
+    }
+
+    /// POST a message and dispatch what comes back. False when the server
+    /// no longer knows the session
+    async fn post(&self, body: &Value, id: Option<&RequestId>) -> McpResult<bool> {
+        let shared = &self.shared;
+        let mut refreshed = false;
+        let response = loop {
+            let token = shared.token().await?;
+            let response = shared
+                .request(Method::POST, token.as_ref())
+                .header(ACCEPT, "application/json, text/event-stream")
+                .json(body)
+                .send()
+                .await
+                .map_err(|e| {
+                    shared.healthy.store(false, Ordering::SeqCst);
+                    McpError::ConnectionError(format!(
+                        "Request to '{}' failed: {}",
+                        shared.server_name, e
+                    ))
+                })?;
+            shared.healthy.store(true, Ordering::SeqCst);
+
+            let has_session = shared.session_id.lock().is_some();
+            match response.status() {
+                StatusCode::UNAUTHORIZED => {
+                    let challenge = response
+                        .headers()
+                        .get(WWW_AUTHENTICATE)
+                        .and_then(|value| value.to_str().ok());
+                    if let (Some(auth), Some(challenge)) = (&shared.auth, challenge) {
+                        auth.note_challenge(&shared.server_name, challenge);
+                    }
+                    if let (Some(auth), Some(token), false) = (&shared.auth, &token, refreshed) {
+                        match auth
+                            .refresh(&shared.server_name, token.expose_secret())
+                            .await
+                        {
+                            Ok(true) => {
+                                refreshed = true;
+                                continue;
+                            }
+                            Ok(false) => {}
+                            Err(e) => tracing::warn!(
+                                "[MCP HTTP] Token refresh for '{}' failed: {}",
+                                shared.server_name,
+                                e
+                            ),
+                        }
+                    }
+                    return Err(McpError::AuthorizationRequired(shared.server_name.clone()));
+                }
+                StatusCode::NOT_FOUND if has_session => return Ok(false),
+                status if !status.is_success() => {
+                    let text = response.text().await.unwrap_or_default();
+                    return Err(McpError::ConnectionError(format!(
+                        "Server '{}' answered {}: {}",
+                        shared.server_name, status, text
+                    )));
+                }
+                _ => break response,
+            }
+        };
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *shared.session_id.lock() = Some(session_id.to_string());
+        }
+        let Some(id) = id else {
+            return Ok(true);
+        };
+
+        if content_type(&response).starts_with("text/event-stream") {
+            let mut last_event_id = None;
+            shared
+                .read_events(response, Some(id), &mut last_event_id)
+                .await?;
+            self.resume(id, last_event_id).await?;
+        } else {
+            let text = response.text().await.map_err(|e| {
+                McpError::ConnectionError(format!("Failed to read response: {}", e))
+            })?;
+            shared.dispatch(McpMessage::from_str(&text)?);
+        }
+
+        if shared.is_waiting(id) {
+            return Err(McpError::ConnectionError(format!(
+                "Server '{}' sent no reply",
+                shared.server_name
+            )));
+        }
+        Ok(true)
+    }
+
+    /// Pick a dropped reply stream up where it left off
+    async fn resume(&self, id: &RequestId, mut last_event_id: Option<String>) -> McpResult<()> {
+        for _ in 0..RESUME_ATTEMPTS {
+            if !self.shared.is_waiting(id) {
+                break;
+            }
+            // Streams without event ids cannot be resumed
+            let Some(event_id) = last_event_id.clone() else {
+                break;
+            };
+            tracing::debug!(
+                "[MCP HTTP] Resuming stream from '{}' after event {}",
+                self.shared.server_name,
+                event_id
+            );
+            match self.shared.open_stream(Some(&event_id)).await? {
+                Some(response) => {
+                    self.shared
+                        .read_events(response, Some(id), &mut last_event_id)
+                        .await?
+                }
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the server answered the last request
+    pub fn is_alive(&self) -> bool {
+        !self.shared.closed.load(Ordering::SeqCst) && self.shared.healthy.load(Ordering::SeqCst)
+    }
+
+    /// End the session on the server and stop listening
+    pub async fn shutdown(&self) -> McpResult<()> {
+        tracing::info!(
+            "[MCP HTTP] Closing session with '{}'",
+            self.shared.server_name
+        );
+        self.shared.closed.store(true, Ordering::SeqCst);
+        if let Some(listener) = self.listener.lock().take() {
+            listener.abort();
+        }
+
+        let has_session = self.shared.session_id.lock().is_some();
+        if has_session {
+            // Servers that keep sessions until they expire answer 405
+            let token = self.shared.token().await.ok().flatten();
+            if let Err(e) = self
+                .shared
+                .request(Method::DELETE, token.as_ref())
+                .timeout(Duration::from_secs(5))
+                .send()
+                .await
+            {
+                tracing::debug!("[MCP HTTP] Failed to end session: {}", e);
+            }
+        }
+
+        for (_, sender) in self.shared.pending.lock().drain() {
+            let _ = sender.send(Err(McpError::ConnectionError("Session closed".to_string())));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StreamableHttpTransport {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        if let Some(listener) = self.listener.lock().take() {
+            listener.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://mcp.example.com/mcp").is_ok());
+        assert!(validate_url("http://localhost:3000/mcp").is_ok());
+        assert!(validate_url("http://127.0.0.1:3000/mcp").is_ok());
+        assert!(validate_url("http://mcp.example.com/mcp").is_err());
+        assert!(validate_url("file:///tmp/mcp").is_err());
+    }
+
+    #[test]
+    fn test_sse_buffer_splits_events() {
+        let mut buffer = SseBuffer::default();
+        assert!(buffer.push(b"id: 1\r\ndata: {\"a\":").unwrap().is_empty());
+
+        let events = buffer
+            .push(b"1}\r\n\r\n: keep-alive\n\nid: 2\ndata:\n\nevent: message\ndata: x\ndata: y\n\n")
+            .unwrap();
+        assert_eq!(
+            events,
+            [
+                SseEvent {
+                    id: Some("1".to_string()),
+                    data: "{\"a\":1}".to_string(),
+                },
+                SseEvent {
+                    id: Some("2".to_string()),
+                    data: String::new(),
+                },
+                SseEvent {
+                    id: None,
+                    data: "x\ny".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -263,6 +263,7 @@ mod tests {
                 "-y".to_string(),
                 "@modelcontextprotocol/server-filesystem".to_string(),
             ],
+            url: None,
             env: HashMap::new(),
             enabled: true,
         };
@@ -284,6 +285,7 @@ mod tests {
                 "-y".to_string(),
                 "@modelcontextprotocol/server-filesystem".to_string(),
             ],
+            url: None,
             env: HashMap::new(),
             enabled: true,
         };
@@ -6,34 +6,65 @@
 // Architecture:
 // - protocol: JSON-RPC 2.0 message definitions
 // - transport: STDIO transport for process communication
+// - http_transport: Streamable HTTP transport for remote servers
+// - oauth: OAuth 2.1 authorization for remote servers
 // - session: Session management with initialization and capabilities
 // - client: High-level client API for multiple servers
+// - catalog: Curated servers that install in one step
 // - manager: Server lifecycle management
 // - registry: AGI tool integration
+// - server: Serves the app's own tools to outside MCP clients
+// - supervisor: Restarts crashed servers and quarantines flaky ones
 // - tool_executor: Execution tracking and statistics
+// - result_processor: Size limits, projection and summarization of tool results
 
+pub mod catalog;
 pub mod client;
 pub mod config;
 pub mod error;
 pub mod events;
 pub mod health;
+pub mod http_transport;
 pub mod manager;
+pub mod oauth;
 pub mod protocol;
 pub mod registry;
+pub mod result_processor;
+pub mod server;
 pub mod session;
+pub mod supervisor;
 pub mod tool_executor;
 pub mod transport;
 
 #[cfg(test)]
 mod tests;
 
-pub use client::{McpClient, McpTool};
+pub use catalog::{
+    catalog, find_catalog_entry, install_catalog_server, CatalogCredential, CatalogEntry,
+    CatalogPackage,
+};
+pub use client::{
+    format_resource_context, McpClient, McpPrompt, McpResource, McpTool, ServerNotification,
+};
 pub use config::{McpServerConfig, McpServersConfig};
 pub use error::{McpError, McpResult};
 pub use events::{emit_mcp_event, McpEvent};
 pub use health::{HealthStatus, McpHealthMonitor, ServerHealth};
 pub use manager::{ManagedServer, McpServerManager, ServerStatus};
-pub use protocol::{McpToolDefinition, ToolCallResult, ToolContent};
+pub use oauth::McpOAuth;
+pub use protocol::{
+    McpToolDefinition, PromptGetResult, ResourceContent, ResourceReadResult, ToolCallResult,
+    ToolContent,
+};
 pub use registry::McpToolRegistry;
+pub use result_processor::{
+    McpResultProcessor, ResultProcessingRule, ResultProcessingSettings, ResultSummarizer,
+};
+pub use server::{
+    McpServerClient, McpServerClientCreated, McpServerClientInput, McpServerStatus, McpToolServer,
+};
 pub use session::McpSession;
+pub use supervisor::{
+    McpSupervisor, ServerStatusEvent, SupervisedState, SupervisorPolicy, SERVER_STATUS_EVENT,
+};
 pub use tool_executor::{McpToolExecutor, ToolExecutionResult, ToolStats};
@@ -0,0 +1,674 @@
+//! OAuth 2.1 for remote MCP servers
+//!
+//! Follows the MCP authorization spec: the server's protected resource
+//! metadata names its authorization server, whose metadata lists the
+//! endpoints. The app registers itself as a client dynamically, then runs the
+//! authorization-code flow with PKCE, binding tokens to the server with the
+//! `resource` parameter. Registrations and tokens are kept in the vault per
+//! server and refreshed shortly before they expire or when the server
+//! rejects them.
+
+use crate::api::oauth::{PkceChallenge, TokenResponse};
+use crate::mcp::{McpError, McpResult};
+use crate::security::vault::{mcp_secret_id, Vault};
+use crate::security::SecretString;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+use uuid::Uuid;
+
+/// Refresh tokens this many seconds before they expire
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// Name the app registers under
+const CLIENT_NAME: &str = "AGI Workforce";
+
+/// Vault key of a server's registration and tokens
+const VAULT_KEY: &str = "oauth";
+const VAULT_ACTOR: &str = "mcp";
+
+/// RFC 9728 metadata of the MCP server itself
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+/// RFC 8414 metadata of the server's authorization server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationServerMetadata {
+    #[serde(default)]
+    pub issuer: Option<String>,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub registration_endpoint: Option<String>,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+/// RFC 7591 registration response
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+/// What a server asked for when it last answered 401
+#[derive(Debug, Clone, Default)]
+struct Challenge {
+    resource_metadata: Option<String>,
+    scope: Option<String>,
+}
+
+/// A server's client registration and tokens, kept in the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredAuth {
+    pub client_id: String,
+    pub client_secret: Option<SecretString>,
+    pub redirect_uri: String,
+    pub token_endpoint: String,
+    /// Canonical server URL the tokens are bound to
+    pub resource: String,
+    pub access_token: SecretString,
+    pub refresh_token: Option<SecretString>,
+    pub expires_at: Option<i64>,
+}
+
+impl StoredAuth {
+    /// The access token is about to expire and can be refreshed
+    pub fn needs_refresh(&self, now: i64) -> bool {
+        self.refresh_token.is_some()
+            && self
+                .expires_at
+                .is_some_and(|expires_at| expires_at - REFRESH_MARGIN_SECS <= now)
+    }
+
+    fn apply_token(&mut self, token: TokenResponse) {
+        self.access_token = token.access_token;
+        // Servers that do not rotate refresh tokens omit them on refresh
+        if token.refresh_token.is_some() {
+            self.refresh_token = token.refresh_token;
+        }
+        self.expires_at = token.expires_at.map(|at| at as i64);
+    }
+}
+
+struct PendingAuth {
+    server_name: String,
+    resource: String,
+    redirect_uri: String,
+    client_id: String,
+    client_secret: Option<SecretString>,
+    token_endpoint: String,
+    pkce: PkceChallenge,
+}
+
+/// Consent flows and tokens for remote MCP servers
+pub struct McpOAuth {
+    vault: Arc<Vault>,
+    http: reqwest::Client,
+    pending: DashMap<String, PendingAuth>,
+    /// Tokens read from the vault; `None` for servers without any
+    tokens: DashMap<String, Option<StoredAuth>>,
+    challenges: DashMap<String, Challenge>,
+    /// One refresh at a time, so rotated refresh tokens are only used once
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl McpOAuth {
+    pub fn new(vault: Arc<Vault>) -> Self {
+        Self {
+            vault,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            pending: DashMap::new(),
+            tokens: DashMap::new(),
+            challenges: DashMap::new(),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Remember a server's `WWW-Authenticate` challenge for the next consent flow
+    pub fn note_challenge(&self, server_name: &str, header: &str) {
+        self.challenges.insert(
+            server_name.to_string(),
+            Challenge {
+                resource_metadata: challenge_param(header, "resource_metadata"),
+                scope: challenge_param(header, "scope"),
+            },
+        );
+    }
+
+    /// Begin a consent flow for a server, returning the URL to open and its state
+    ///
+    /// Registers the app with the server's authorization server unless a
+    /// registration for `redirect_uri` is already stored.
+    pub async fn start(
+        &self,
+        server_name: &str,
+        server_url: &str,
+        redirect_uri: &str,
+    ) -> McpResult<(String, String)> {
+        let resource = canonical_resource(server_url)?;
+        let challenge = self
+            .challenges
+            .get(server_name)
+            .map(|entry| entry.clone())
+            .unwrap_or_default();
+        let (metadata, scopes) = self
+            .discover(&resource, challenge.resource_metadata)
+            .await?;
+        if !metadata.code_challenge_methods_supported.is_empty()
+            && !metadata
+                .code_challenge_methods_supported
+                .iter()
+                .any(|method| method == "S256")
+        {
+            return Err(McpError::AuthError(format!(
+                "The authorization server of '{}' does not support PKCE with S256",
+                server_name
+            )));
+        }
+
+        let (client_id, client_secret) = match self.stored(server_name).filter(|stored| {
+            stored.redirect_uri == redirect_uri && stored.token_endpoint == metadata.token_endpoint
+        }) {
+            Some(stored) => (stored.client_id, stored.client_secret),
+            None => self.register(&metadata, redirect_uri).await?,
+        };
+
+        let state = Uuid::new_v4().to_string();
+        let pkce = PkceChallenge::generate();
+        let mut auth_url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| McpError::AuthError(format!("Invalid authorization endpoint: {}", e)))?;
+        {
+            let mut query = auth_url.query_pairs_mut();
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &client_id)
+                .append_pair("redirect_uri", redirect_uri)
+                .append_pair("state", &state)
+                .append_pair("code_challenge", &pkce.code_challenge)
+                .append_pair("code_challenge_method", "S256")
+                .append_pair("resource", &resource);
+            if let Some(scope) = challenge
+                .scope
+                .or_else(|| (!scopes.is_empty()).then(|| scopes.join(" ")))
+            {
+                query.append_pair("scope", &scope);
+            }
+        }
+
+        self.pending.insert(
+            state.clone(),
+            PendingAuth {
+                server_name: server_name.to_string(),
+                resource,
+                redirect_uri: redirect_uri.to_string(),
+                client_id,
+                client_secret,
+                token_endpoint: metadata.token_endpoint,
+                pkce,
+            },
+        );
+        Ok((auth_url.to_string(), state))
+    }
+
+    /// Finish a consent flow and store the server's tokens, returning the
+    /// server's name
+    pub async fn complete(&self, state: &str, code: &str) -> McpResult<String> {
+        let (_, pending) = self
+            .pending
+            .remove(state)
+            .ok_or_else(|| McpError::AuthError("Invalid or expired OAuth state".to_string()))?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", pending.redirect_uri.as_str()),
+            ("client_id", pending.client_id.as_str()),
+            ("code_verifier", pending.pkce.code_verifier.as_str()),
+            ("resource", pending.resource.as_str()),
+        ];
+        if let Some(secret) = &pending.client_secret {
+            form.push(("client_secret", secret.expose_secret()));
+        }
+        let token = self.token_request(&pending.token_endpoint, &form).await?;
+
+        let mut stored = StoredAuth {
+            client_id: pending.client_id,
+            client_secret: pending.client_secret,
+            redirect_uri: pending.redirect_uri,
+            token_endpoint: pending.token_endpoint,
+            resource: pending.resource,
+            access_token: SecretString::default(),
+            refresh_token: None,
+            expires_at: None,
+        };
+        stored.apply_token(token);
+        self.save(&pending.server_name, &stored)?;
+        self.challenges.remove(&pending.server_name);
+
+        tracing::info!("[MCP OAuth] Authorized server '{}'", pending.server_name);
+        Ok(pending.server_name)
+    }
+
+    /// Token to send to a server, refreshed first when it is about to expire
+    pub async fn access_token(&self, server_name: &str) -> McpResult<Option<SecretString>> {
+        let now = chrono::Utc::now().timestamp();
+        match self.stored(server_name) {
+            Some(stored) if stored.needs_refresh(now) => {}
+            stored => return Ok(stored.map(|stored| stored.access_token)),
+        }
+
+        let _guard = self.refreshing.lock().await;
+        let Some(stored) = self.stored(server_name) else {
+            return Ok(None);
+        };
+        if !stored.needs_refresh(now) {
+            return Ok(Some(stored.access_token));
+        }
+        match self.refresh_stored(server_name, stored.clone()).await {
+            Ok(refreshed) => Ok(Some(refreshed.access_token)),
+            Err(e) => {
+                // The server decides whether the old token still works
+                tracing::warn!("[MCP OAuth] Failed to refresh '{}': {}", server_name, e);
+                Ok(Some(stored.access_token))
+            }
+        }
+    }
+
+    /// Refresh after the server rejected `rejected`. False when the user has
+    /// to authorize again
+    pub async fn refresh(&self, server_name: &str, rejected: &str) -> McpResult<bool> {
+        let _guard = self.refreshing.lock().await;
+        let Some(stored) = self.stored(server_name) else {
+            return Ok(false);
+        };
+        if stored.access_token.expose_secret() != rejected {
+            // Refreshed while the request was in flight
+            return Ok(true);
+        }
+        if stored.refresh_token.is_none() {
+            return Ok(false);
+        }
+        self.refresh_stored(server_name, stored).await?;
+        Ok(true)
+    }
+
+    /// Whether tokens are stored for a server
+    pub fn is_authorized(&self, server_name: &str) -> bool {
+        self.stored(server_name).is_some()
+    }
+
+    /// Drop a server's registration and tokens
+    pub fn forget(&self, server_name: &str) -> McpResult<()> {
+        self.vault
+            .delete(&mcp_secret_id(server_name, VAULT_KEY), VAULT_ACTOR)
+            .map_err(|e| McpError::AuthError(format!("Failed to remove tokens: {}", e)))?;
+        self.tokens.insert(server_name.to_string(), None);
+        Ok(())
+    }
+
+    async fn refresh_stored(
+        &self,
+        server_name: &str,
+        mut stored: StoredAuth,
+    ) -> McpResult<StoredAuth> {
+        let refresh_token = stored.refresh_token.clone().unwrap_or_default();
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.expose_secret()),
+            ("client_id", stored.client_id.as_str()),
+            ("resource", stored.resource.as_str()),
+        ];
+        if let Some(secret) = &stored.client_secret {
+            form.push(("client_secret", secret.expose_secret()));
+        }
+        let token = self.token_request(&stored.token_endpoint, &form).await?;
+
+        stored.apply_token(token);
+        self.save(server_name, &stored)?;
+        tracing::info!("[MCP OAuth] Refreshed access token for '{}'", server_name);
+        Ok(stored)
+    }
+
+    fn stored(&self, server_name: &str) -> Option<StoredAuth> {
+        if let Some(entry) = self.tokens.get(server_name) {
+            return entry.clone();
+        }
+
+        let stored = match self
+            .vault
+            .get(&mcp_secret_id(server_name, VAULT_KEY), VAULT_ACTOR)
+        {
+            Ok(Some(raw)) => serde_json::from_str::<StoredAuth>(raw.expose_secret())
+                .map_err(|e| {
+                    tracing::warn!("[MCP OAuth] Corrupt tokens for '{}': {}", server_name, e)
+                })
+                .ok(),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(
+                    "[MCP OAuth] Tokens for '{}' unavailable: {}",
+                    server_name,
+                    e
+                );
+                return None;
+            }
+        };
+        self.tokens.insert(server_name.to_string(), stored.clone());
+        stored
+    }
+
+    fn save(&self, server_name: &str, stored: &StoredAuth) -> McpResult<()> {
+        let raw = serde_json::to_string(stored)?;
+        self.vault
+            .put(&mcp_secret_id(server_name, VAULT_KEY), &raw, VAULT_ACTOR)
+            .map_err(|e| McpError::AuthError(format!("Failed to store tokens: {}", e)))?;
+        self.tokens
+            .insert(server_name.to_string(), Some(stored.clone()));
+        Ok(())
+    }
+
+    /// Find the authorization server of `resource` and the scopes the server
+    /// supports
+    async fn discover(
+        &self,
+        resource: &str,
+        resource_metadata: Option<String>,
+    ) -> McpResult<(AuthorizationServerMetadata, Vec<String>)> {
+        let server_url = Url::parse(resource)
+            .map_err(|e| McpError::InvalidConfig(format!("Invalid server URL: {}", e)))?;
+
+        let mut protected_resource = None;
+        for url in resource_metadata
+            .into_iter()
+            .chain(well_known_urls(&server_url, "oauth-protected-resource"))
+        {
+            if let Some(found) = self.fetch_json::<ProtectedResourceMetadata>(&url).await {
+                protected_resource = Some(found);
+                break;
+            }
+        }
+
+        let issuer = match protected_resource
+            .as_ref()
+            .and_then(|metadata| metadata.authorization_servers.first())
+        {
+            Some(issuer) => Url::parse(issuer).map_err(|e| {
+                McpError::AuthError(format!("Invalid authorization server '{}': {}", issuer, e))
+            })?,
+            // Servers without resource metadata host their authorization
+            // server at their origin
+            None => Url::parse(&server_url.origin().ascii_serialization())
+                .map_err(|e| McpError::InvalidConfig(format!("Invalid server URL: {}", e)))?,
+        };
+        let scopes = protected_resource
+            .map(|metadata| metadata.scopes_supported)
+            .unwrap_or_default();
+
+        for url in authorization_server_metadata_urls(&issuer) {
+            if let Some(metadata) = self.fetch_json::<AuthorizationServerMetadata>(&url).await {
+                return Ok((metadata, scopes));
+            }
+        }
+
+        // Authorization servers without metadata serve the default endpoints
+        let origin = issuer.origin().ascii_serialization();
+        Ok((
+            AuthorizationServerMetadata {
+                issuer: Some(origin.clone()),
+                authorization_endpoint: format!("{}/authorize", origin),
+                token_endpoint: format!("{}/token", origin),
+                registration_endpoint: Some(format!("{}/register", origin)),
+                code_challenge_methods_supported: Vec::new(),
+            },
+            scopes,
+        ))
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Option<T> {
+        let response = self
+            .http
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|e| tracing::debug!("[MCP OAuth] {} unreachable: {}", url, e))
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| tracing::debug!("[MCP OAuth] Unexpected metadata at {}: {}", url, e))
+            .ok()
+    }
+
+    /// Register the app with an authorization server, returning its client
+    /// id and secret
+    async fn register(
+        &self,
+        metadata: &AuthorizationServerMetadata,
+        redirect_uri: &str,
+    ) -> McpResult<(String, Option<SecretString>)> {
+        let endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+            McpError::AuthError(
+                "The authorization server does not support dynamic client registration".to_string(),
+            )
+        })?;
+
+        let response = self
+            .http
+            .post(endpoint)
+            .json(&serde_json::json!({
+                "client_name": CLIENT_NAME,
+                "redirect_uris": [redirect_uri],
+                "grant_types": ["authorization_code", "refresh_token"],
+                "response_types": ["code"],
+                "token_endpoint_auth_method": "none",
+            }))
+            .send()
+            .await
+            .map_err(|e| McpError::AuthError(format!("Client registration failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(McpError::AuthError(format!(
+                "Client registration failed: {} - {}",
+                status, text
+            )));
+        }
+
+        let registration: ClientRegistration = response.json().await.map_err(|e| {
+            McpError::AuthError(format!("Failed to parse client registration: {}", e))
+        })?;
+        Ok((
+            registration.client_id,
+            registration.client_secret.map(SecretString::from),
+        ))
+    }
+
+    async fn token_request(
+        &self,
+        token_endpoint: &str,
+        form: &[(&str, &str)],
+    ) -> McpResult<TokenResponse> {
+        let response = self
+            .http
+            .post(token_endpoint)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| McpError::AuthError(format!("Token request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(McpError::AuthError(format!(
+                "Token request failed: {} - {}",
+                status, text
+            )));
+        }
+
+        let token: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| McpError::AuthError(format!("Failed to parse token response: {}", e)))?;
+        Ok(token.with_expiration())
+    }
+}
+
+/// Server URL in the form tokens are bound to: no fragment, and no trailing
+/// slash on a bare host
+pub fn canonical_resource(server_url: &str) -> McpResult<String> {
+    let mut url = Url::parse(server_url)
+        .map_err(|e| McpError::InvalidConfig(format!("Invalid server URL: {}", e)))?;
+    url.set_fragment(None);
+    let canonical = url.to_string();
+    Ok(match canonical.strip_suffix('/') {
+        Some(bare) if url.path() == "/" && url.query().is_none() => bare.to_string(),
+        _ => canonical,
+    })
+}
+
+/// RFC 9728 locations of a server's metadata: under its path, then at the root
+fn well_known_urls(server_url: &Url, suffix: &str) -> Vec<String> {
+    let origin = server_url.origin().ascii_serialization();
+    let path = server_url.path().trim_end_matches('/');
+    let mut urls = Vec::new();
+    if !path.is_empty() {
+        urls.push(format!("{}/.well-known/{}{}", origin, suffix, path));
+    }
+    urls.push(format!("{}/.well-known/{}", origin, suffix));
+    urls
+}
+
+/// RFC 8414 and OpenID locations of an authorization server's metadata
+fn authorization_server_metadata_urls(issuer: &Url) -> Vec<String> {
+    let origin = issuer.origin().ascii_serialization();
+    let path = issuer.path().trim_end_matches('/');
+    if path.is_empty() {
+        vec![
+            format!("{}/.well-known/oauth-authorization-server", origin),
+            format!("{}/.well-known/openid-configuration", origin),
+        ]
+    } else {
+        vec![
+            format!("{}/.well-known/oauth-authorization-server{}", origin, path),
+            format!("{}/.well-known/openid-configuration{}", origin, path),
+            format!("{}{}/.well-known/openid-configuration", origin, path),
+        ]
+    }
+}
+
+/// A parameter of a `WWW-Authenticate: Bearer` challenge
+fn challenge_param(header: &str, name: &str) -> Option<String> {
+    let params = header.trim().strip_prefix("Bearer").unwrap_or(header);
+    params.split(',').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_resource() {
+        assert_eq!(
+            canonical_resource("https://MCP.Example.com/").unwrap(),
+            "https://mcp.example.com"
+        );
+        assert_eq!(
+            canonical_resource("https://mcp.example.com/mcp#frag").unwrap(),
+            "https://mcp.example.com/mcp"
+        );
+        assert!(canonical_resource("not a url").is_err());
+    }
+
+    #[test]
+    fn test_metadata_urls() {
+        let server = Url::parse("https://mcp.example.com/v1/mcp").unwrap();
+        assert_eq!(
+            well_known_urls(&server, "oauth-protected-resource"),
+            [
+                "https://mcp.example.com/.well-known/oauth-protected-resource/v1/mcp",
+                "https://mcp.example.com/.well-known/oauth-protected-resource",
+            ]
+        );
+
+        let issuer = Url::parse("https://auth.example.com/tenant1").unwrap();
+        assert_eq!(
+            authorization_server_metadata_urls(&issuer),
+            [
+                "https://auth.example.com/.well-known/oauth-authorization-server/tenant1",
+                "https://auth.example.com/.well-known/openid-configuration/tenant1",
+                "https://auth.example.com/tenant1/.well-known/openid-configuration",
+            ]
+        );
+        let root = Url::parse("https://auth.example.com").unwrap();
+        assert_eq!(authorization_server_metadata_urls(&root).len(), 2);
+    }
+
+    #[test]
+    fn test_challenge_params() {
+        let header = r#"Bearer error="invalid_token", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource", scope="files:read files:write""#;
+        assert_eq!(
+            challenge_param(header, "resource_metadata").as_deref(),
+            Some("https://mcp.example.com/.well-known/oauth-protected-resource")
+        );
+        assert_eq!(
+            challenge_param(header, "scope").as_deref(),
+            Some("files:read files:write")
+        );
+        assert_eq!(challenge_param("Bearer", "scope"), None);
+    }
+
+    #[test]
+    fn test_refresh_keeps_unrotated_refresh_token() {
+        let mut stored = StoredAuth {
+            client_id: "client".to_string(),
+            client_secret: None,
+            redirect_uri: "http://localhost/callback".to_string(),
+            token_endpoint: "https://auth.example.com/token".to_string(),
+            resource: "https://mcp.example.com".to_string(),
+            access_token: "old".into(),
+            refresh_token: Some("refresh-1".into()),
+            expires_at: Some(1_000),
+        };
+        assert!(!stored.needs_refresh(900));
+        assert!(stored.needs_refresh(950));
+
+        stored.apply_token(TokenResponse {
+            access_token: "new".into(),
+            token_type: "Bearer".to_string(),
+            expires_in: Some(3600),
+            refresh_token: None,
+            scope: None,
+            expires_at: Some(5_000),
+        });
+        assert_eq!(stored.access_token.expose_secret(), "new");
+        assert_eq!(
+            stored
+                .refresh_token
+                .as_ref()
+                .map(SecretString::expose_secret),
+            Some("refresh-1")
+        );
+        assert!(!stored.needs_refresh(950));
+    }
+}
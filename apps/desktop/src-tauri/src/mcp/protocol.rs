@@ -230,6 +230,9 @@ pub struct ResourceReference {
     pub uri: String,
     #[serde(skip_serializing_if = "Option::is_none", rename = "mimeType")]
     pub mime_type: Option<String>,
+    /// Contents of an embedded text resource
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
 }
 
 /// resources/list params
@@ -272,10 +275,33 @@ pub struct ResourceReadResult {
 
 /// Resource content
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "lowercase")]
+#[serde(untagged)]
 pub enum ResourceContent {
-    Text { uri: String, text: String },
-    Blob { uri: String, blob: String }, // Base64 encoded
+    Text {
+        uri: String,
+        #[serde(default, skip_serializing_if = "Option::is_none", rename = "mimeType")]
+        mime_type: Option<String>,
+        text: String,
+    },
+    Blob {
+        uri: String,
+        #[serde(default, skip_serializing_if = "Option::is_none", rename = "mimeType")]
+        mime_type: Option<String>,
+        blob: String, // Base64 encoded
+    },
+}
+
+/// resources/subscribe and resources/unsubscribe params
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceSubscribeParams {
+    pub uri: String,
+}
+
+/// prompts/list params
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PromptsListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
 /// prompts/list result
@@ -306,6 +332,29 @@ pub struct PromptArgument {
     pub required: Option<bool>,
 }
 
+/// prompts/get params
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptGetParams {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub arguments: HashMap<String, String>,
+}
+
+/// prompts/get result: the prompt rendered with its arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptGetResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
+/// Prompt message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String, // "user" or "assistant"
+    pub content: ToolContent,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
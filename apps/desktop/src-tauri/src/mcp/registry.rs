@@ -1,6 +1,6 @@
 use crate::agi::tools::{ParameterType, Tool, ToolCapability, ToolParameter};
 use crate::mcp::client::McpTool;
-use crate::mcp::{McpClient, McpResult};
+use crate::mcp::{McpClient, McpResult, McpResultProcessor};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -8,12 +8,21 @@ use std::sync::Arc;
 /// Registry that bridges MCP tools with the AGI tool system
 pub struct McpToolRegistry {
     mcp_client: Arc<McpClient>,
+    result_processor: Arc<McpResultProcessor>,
 }
 
 impl McpToolRegistry {
     /// Create a new MCP tool registry
     pub fn new(mcp_client: Arc<McpClient>) -> Self {
-        Self { mcp_client }
+        Self {
+            mcp_client,
+            result_processor: Arc::new(McpResultProcessor::new()),
+        }
+    }
+
+    /// Processor applied to every tool result returned by `execute_tool`
+    pub fn result_processor(&self) -> Arc<McpResultProcessor> {
+        Arc::clone(&self.result_processor)
     }
 
     /// Convert MCP tools to AGI tool schemas
@@ -129,10 +138,15 @@ impl McpToolRegistry {
         // Convert arguments to JSON Value
         let args_value = serde_json::to_value(arguments)?;
 
-        // Call the tool
-        self.mcp_client
+        // Call the tool and trim the result before it reaches the model
+        let result = self
+            .mcp_client
             .call_tool(server_name, &tool_name, args_value)
-            .await
+            .await?;
+        Ok(self
+            .result_processor
+            .process(server_name, &tool_name, result)
+            .await)
     }
 
     /// Search for tools
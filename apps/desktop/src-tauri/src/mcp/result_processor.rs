@@ -0,0 +1,573 @@
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Results larger than this are cut down unless a rule says otherwise
+const DEFAULT_MAX_CHARS: usize = 20_000;
+/// Share of the kept characters taken from the start of an oversized result
+const DEFAULT_HEAD_RATIO: f32 = 0.7;
+/// Upper bound on the text handed to the summarizer
+const SUMMARY_INPUT_CHARS: usize = 200_000;
+
+/// Post-processing applied to a tool result before it re-enters the model.
+///
+/// Every field is optional so a server or tool rule only overrides what it sets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultProcessingRule {
+    /// Maximum characters per text result; 0 disables the limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+
+    /// Fraction of `max_chars` kept from the start when truncating (rest from the end)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_ratio: Option<f32>,
+
+    /// JSONPath expressions selecting the fields to keep from JSON results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<Vec<String>>,
+
+    /// Summarize oversized results with the LLM instead of truncating them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarize: Option<bool>,
+}
+
+impl ResultProcessingRule {
+    fn overlay(&mut self, other: &ResultProcessingRule) {
+        if other.max_chars.is_some() {
+            self.max_chars = other.max_chars;
+        }
+        if other.head_ratio.is_some() {
+            self.head_ratio = other.head_ratio;
+        }
+        if other.projection.is_some() {
+            self.projection = other.projection.clone();
+        }
+        if other.summarize.is_some() {
+            self.summarize = other.summarize;
+        }
+    }
+}
+
+/// Result processing rules for all servers, stored alongside the server config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultProcessingSettings {
+    /// Applied to every tool
+    #[serde(default)]
+    pub defaults: ResultProcessingRule,
+
+    /// Keyed by server name
+    #[serde(default)]
+    pub servers: HashMap<String, ResultProcessingRule>,
+
+    /// Keyed by `<server>/<tool>`
+    #[serde(default)]
+    pub tools: HashMap<String, ResultProcessingRule>,
+}
+
+impl ResultProcessingSettings {
+    /// Effective rule for a tool: defaults, then server, then tool overrides
+    pub fn rule_for(&self, server_name: &str, tool_name: &str) -> ResultProcessingRule {
+        let mut rule = ResultProcessingRule {
+            max_chars: Some(DEFAULT_MAX_CHARS),
+            head_ratio: Some(DEFAULT_HEAD_RATIO),
+            projection: None,
+            summarize: Some(false),
+        };
+        rule.overlay(&self.defaults);
+        if let Some(server_rule) = self.servers.get(server_name) {
+            rule.overlay(server_rule);
+        }
+        if let Some(tool_rule) = self.tools.get(&format!("{}/{}", server_name, tool_name)) {
+            rule.overlay(tool_rule);
+        }
+        rule
+    }
+}
+
+/// Condenses oversized tool results, typically by asking an LLM
+#[async_trait]
+pub trait ResultSummarizer: Send + Sync {
+    async fn summarize(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        text: &str,
+        max_chars: usize,
+    ) -> anyhow::Result<String>;
+}
+
+/// Applies result processing rules to MCP tool results
+pub struct McpResultProcessor {
+    settings: RwLock<ResultProcessingSettings>,
+    summarizer: RwLock<Option<Arc<dyn ResultSummarizer>>>,
+}
+
+impl Default for McpResultProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpResultProcessor {
+    pub fn new() -> Self {
+        Self {
+            settings: RwLock::new(ResultProcessingSettings::default()),
+            summarizer: RwLock::new(None),
+        }
+    }
+
+    pub fn settings(&self) -> ResultProcessingSettings {
+        self.settings.read().clone()
+    }
+
+    pub fn set_settings(&self, settings: ResultProcessingSettings) {
+        *self.settings.write() = settings;
+    }
+
+    pub fn set_summarizer(&self, summarizer: Arc<dyn ResultSummarizer>) {
+        *self.summarizer.write() = Some(summarizer);
+    }
+
+    /// Project, summarize or truncate a raw tool result according to its rule
+    pub async fn process(&self, server_name: &str, tool_name: &str, result: Value) -> Value {
+        let rule = self.settings.read().rule_for(server_name, tool_name);
+        let summarizer = if rule.summarize.unwrap_or(false) {
+            self.summarizer.read().clone()
+        } else {
+            None
+        };
+
+        let mut result = result;
+        let Some(content) = result.get_mut("content").and_then(Value::as_array_mut) else {
+            // Not a standard tool result: process the whole value as one JSON document
+            let text = project_text(&result.to_string(), &rule);
+            return match self
+                .condense(server_name, tool_name, text, &rule, summarizer)
+                .await
+            {
+                Condensed::Unchanged(_) if rule.projection.is_none() => result,
+                Condensed::Unchanged(text) => {
+                    serde_json::from_str(&text).unwrap_or(Value::String(text))
+                }
+                Condensed::Reduced(text) => Value::String(text),
+            };
+        };
+
+        for item in content.iter_mut() {
+            if item.get("type").and_then(Value::as_str) != Some("text") {
+                continue;
+            }
+            let Some(text) = item.get("text").and_then(Value::as_str) else {
+                continue;
+            };
+            let text = project_text(text, &rule);
+            let text = match self
+                .condense(server_name, tool_name, text, &rule, summarizer.clone())
+                .await
+            {
+                Condensed::Unchanged(text) | Condensed::Reduced(text) => text,
+            };
+            item["text"] = Value::String(text);
+        }
+        result
+    }
+
+    async fn condense(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        text: String,
+        rule: &ResultProcessingRule,
+        summarizer: Option<Arc<dyn ResultSummarizer>>,
+    ) -> Condensed {
+        let max_chars = rule.max_chars.unwrap_or(DEFAULT_MAX_CHARS);
+        let char_count = text.chars().count();
+        if max_chars == 0 || char_count <= max_chars {
+            return Condensed::Unchanged(text);
+        }
+
+        if let Some(summarizer) = summarizer {
+            let input = truncate_middle(&text, SUMMARY_INPUT_CHARS, DEFAULT_HEAD_RATIO);
+            match summarizer
+                .summarize(server_name, tool_name, &input, max_chars)
+                .await
+            {
+                Ok(summary) => {
+                    tracing::debug!(
+                        "[MCP] Summarized {} characters from {}/{}",
+                        char_count,
+                        server_name,
+                        tool_name
+                    );
+                    return Condensed::Reduced(format!(
+                        "[Summary of a {}-character result]\n{}",
+                        char_count,
+                        truncate_middle(&summary, max_chars, DEFAULT_HEAD_RATIO)
+                    ));
+                }
+                Err(e) => tracing::warn!(
+                    "[MCP] Failed to summarize result from {}/{}, truncating instead: {}",
+                    server_name,
+                    tool_name,
+                    e
+                ),
+            }
+        }
+
+        Condensed::Reduced(truncate_middle(
+            &text,
+            max_chars,
+            rule.head_ratio.unwrap_or(DEFAULT_HEAD_RATIO),
+        ))
+    }
+}
+
+enum Condensed {
+    Unchanged(String),
+    Reduced(String),
+}
+
+/// Apply the rule's projection when the text is JSON
+fn project_text(text: &str, rule: &ResultProcessingRule) -> String {
+    let Some(paths) = rule.projection.as_ref().filter(|paths| !paths.is_empty()) else {
+        return text.to_string();
+    };
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        return text.to_string();
+    };
+
+    match project(&value, paths) {
+        Some(projected) => projected.to_string(),
+        None => {
+            tracing::debug!("[MCP] Projection matched nothing, keeping full result");
+            text.to_string()
+        }
+    }
+}
+
+/// Keep `max_chars` characters, taken from the start and end of the text
+pub fn truncate_middle(text: &str, max_chars: usize, head_ratio: f32) -> String {
+    let char_count = text.chars().count();
+    if char_count <= max_chars {
+        return text.to_string();
+    }
+
+    let head = ((max_chars as f32) * head_ratio.clamp(0.0, 1.0)) as usize;
+    let tail = max_chars - head;
+    let head_end = text
+        .char_indices()
+        .nth(head)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+    let tail_start = text
+        .char_indices()
+        .nth(char_count - tail)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+
+    format!(
+        "{}\n\n[... {} characters omitted ...]\n\n{}",
+        &text[..head_end],
+        char_count - max_chars,
+        &text[tail_start..]
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parse the JSONPath subset used for projections: `$`, `.key`, `['key']`, `[0]`, `[*]`, `.*`
+fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let inner = after[..end].trim();
+            segments.push(if inner == "*" {
+                PathSegment::Wildcard
+            } else if let Some(key) = inner
+                .strip_prefix('\'')
+                .and_then(|k| k.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')))
+            {
+                PathSegment::Key(key.to_string())
+            } else {
+                PathSegment::Index(inner.parse().ok()?)
+            });
+            rest = &after[end + 1..];
+        } else {
+            let after = rest.strip_prefix('.').unwrap_or(rest);
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            if key.is_empty() {
+                return None;
+            }
+            segments.push(if key == "*" {
+                PathSegment::Wildcard
+            } else {
+                PathSegment::Key(key.to_string())
+            });
+            rest = &after[end..];
+        }
+    }
+    Some(segments)
+}
+
+/// Copy of `value` keeping only what the paths select, in its original shape
+fn project(value: &Value, paths: &[String]) -> Option<Value> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let segments = parse_path(path);
+            if segments.is_none() {
+                tracing::warn!("[MCP] Ignoring invalid projection path: {}", path);
+            }
+            project_segments(value, &segments?)
+        })
+        .reduce(merge)
+}
+
+fn project_segments(value: &Value, segments: &[PathSegment]) -> Option<Value> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return Some(value.clone());
+    };
+
+    match (segment, value) {
+        (PathSegment::Key(key), Value::Object(map)) => {
+            let projected = project_segments(map.get(key)?, rest)?;
+            let mut output = Map::new();
+            output.insert(key.clone(), projected);
+            Some(Value::Object(output))
+        }
+        (PathSegment::Index(index), Value::Array(items)) => {
+            Some(Value::Array(vec![project_segments(
+                items.get(*index)?,
+                rest,
+            )?]))
+        }
+        (PathSegment::Wildcard, Value::Array(items)) => {
+            // Keep positions with nulls so projections of the same array line up when merged
+            let projected: Vec<Option<Value>> = items
+                .iter()
+                .map(|item| project_segments(item, rest))
+                .collect();
+            if projected.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Value::Array(
+                projected
+                    .into_iter()
+                    .map(|item| item.unwrap_or(Value::Null))
+                    .collect(),
+            ))
+        }
+        (PathSegment::Wildcard, Value::Object(map)) => {
+            let output: Map<String, Value> = map
+                .iter()
+                .filter_map(|(key, item)| Some((key.clone(), project_segments(item, rest)?)))
+                .collect();
+            (!output.is_empty()).then_some(Value::Object(output))
+        }
+        _ => None,
+    }
+}
+
+fn merge(left: Value, right: Value) -> Value {
+    match (left, right) {
+        (Value::Object(mut left), Value::Object(right)) => {
+            for (key, value) in right {
+                let merged = match left.remove(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => value,
+                };
+                left.insert(key, merged);
+            }
+            Value::Object(left)
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            let len = left.len().max(right.len());
+            let mut left = left.into_iter();
+            let mut right = right.into_iter();
+            Value::Array(
+                (0..len)
+                    .map(|_| match (left.next(), right.next()) {
+                        (Some(Value::Null) | None, Some(item)) => item,
+                        (Some(item), Some(Value::Null) | None) => item,
+                        (Some(l), Some(r)) => merge(l, r),
+                        (None, None) => Value::Null,
+                    })
+                    .collect(),
+            )
+        }
+        (Value::Null, right) => right,
+        (left, _) => left,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct FixedSummarizer;
+
+    #[async_trait]
+    impl ResultSummarizer for FixedSummarizer {
+        async fn summarize(
+            &self,
+            _server_name: &str,
+            tool_name: &str,
+            _text: &str,
+            _max_chars: usize,
+        ) -> anyhow::Result<String> {
+            Ok(format!("{} ok", tool_name))
+        }
+    }
+
+    fn text_result(text: &str) -> Value {
+        json!({ "content": [{ "type": "text", "text": text }] })
+    }
+
+    #[test]
+    fn test_rule_resolution() {
+        let mut settings = ResultProcessingSettings::default();
+        settings.servers.insert(
+            "github".to_string(),
+            ResultProcessingRule {
+                max_chars: Some(500),
+                summarize: Some(true),
+                ..Default::default()
+            },
+        );
+        settings.tools.insert(
+            "github/list_issues".to_string(),
+            ResultProcessingRule {
+                max_chars: Some(0),
+                ..Default::default()
+            },
+        );
+
+        let rule = settings.rule_for("github", "search");
+        assert_eq!(rule.max_chars, Some(500));
+        assert_eq!(rule.summarize, Some(true));
+        assert_eq!(
+            settings.rule_for("github", "list_issues").max_chars,
+            Some(0)
+        );
+        assert_eq!(
+            settings.rule_for("slack", "post").max_chars,
+            Some(DEFAULT_MAX_CHARS)
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_head_and_tail() {
+        let text = "ab".repeat(50);
+        let truncated = truncate_middle(&text, 10, 0.7);
+        assert!(truncated.starts_with("abababa\n"));
+        assert!(truncated.ends_with("\nbab"));
+        assert!(truncated.contains("90 characters omitted"));
+        assert_eq!(truncate_middle("héllo", 10, 0.7), "héllo");
+    }
+
+    #[test]
+    fn test_projection() {
+        let value = json!({
+            "total": 2,
+            "items": [
+                { "id": 1, "title": "a", "body": "long" },
+                { "id": 2, "title": "b", "body": "long" }
+            ]
+        });
+        let projected = project(
+            &value,
+            &[
+                "$.items[*].id".to_string(),
+                "$['items'][*].title".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            projected,
+            json!({ "items": [{ "id": 1, "title": "a" }, { "id": 2, "title": "b" }] })
+        );
+        assert_eq!(
+            project(&value, &["$.items[1].id".to_string()]).unwrap(),
+            json!({ "items": [{ "id": 2 }] })
+        );
+        assert!(project(&value, &["$.missing".to_string()]).is_none());
+        assert!(parse_path("$.items[").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_process_text_content() {
+        let processor = McpResultProcessor::new();
+        let mut settings = ResultProcessingSettings::default();
+        settings.tools.insert(
+            "github/list_issues".to_string(),
+            ResultProcessingRule {
+                max_chars: Some(40),
+                projection: Some(vec!["$[*].number".to_string()]),
+                ..Default::default()
+            },
+        );
+        processor.set_settings(settings);
+
+        let issues = json!([{ "number": 7, "body": "x".repeat(100) }]).to_string();
+        let result = processor
+            .process("github", "list_issues", text_result(&issues))
+            .await;
+        assert_eq!(result["content"][0]["text"], r#"[{"number":7}]"#);
+
+        let long = "y".repeat(100);
+        let result = processor
+            .process("github", "list_issues", text_result(&long))
+            .await;
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("60 characters omitted"));
+    }
+
+    #[tokio::test]
+    async fn test_process_summarizes_when_enabled() {
+        let processor = McpResultProcessor::new();
+        processor.set_settings(ResultProcessingSettings {
+            defaults: ResultProcessingRule {
+                max_chars: Some(10),
+                summarize: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        // Without a summarizer the result is truncated
+        let result = processor
+            .process("s", "scan", text_result(&"z".repeat(50)))
+            .await;
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("characters omitted"));
+
+        processor.set_summarizer(Arc::new(FixedSummarizer));
+        let result = processor
+            .process("s", "scan", text_result(&"z".repeat(50)))
+            .await;
+        let text = result["content"][0]["text"].as_str().unwrap();
+        assert!(text.starts_with("[Summary of a 50-character result]"));
+        assert!(text.ends_with("\nscan ok"));
+    }
+}
@@ -0,0 +1,1077 @@
+//! Serves the app's own tools to outside MCP clients.
+//!
+//! Clients such as Claude Desktop or an IDE reach a listener on 127.0.0.1
+//! either over SSE (`GET /sse`, then `POST /messages`) or by launching the
+//! app binary with [`STDIO_FLAG`], which relays stdin and stdout to
+//! `POST /rpc` of the running app. Every client is registered with its own
+//! bearer token and permission profile, and may be limited to some of the
+//! [`EXPOSED_TOOLS`]. Calls run through the agent's executor, so the
+//! client's profile, approval prompts, guardrails and the audit log apply as
+//! for the agent's own tool calls. Clients are stored in `mcp_server_clients`;
+//! the listener starts once one is enabled.
+
+use crate::agi::tools::{ParameterType, Tool, ToolRegistry};
+use crate::db::Pool;
+use crate::mcp::protocol::{
+    ErrorObject, Implementation, InitializeResult, JsonRpcError, JsonRpcResponse, RequestId,
+    ServerCapabilities, ToolCallParams, ToolsListResult, INVALID_PARAMS, INVALID_REQUEST,
+    METHOD_NOT_FOUND, PARSE_ERROR,
+};
+use crate::mcp::{McpToolDefinition, ToolCallResult, ToolContent};
+use crate::permissions::profiles::{self, RunPermissions};
+use chrono::Utc;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+pub const SERVER_PORT: u16 = 8791;
+/// Command-line flag that relays an MCP client on stdin/stdout to the running app
+pub const STDIO_FLAG: &str = "--mcp-stdio";
+/// Environment variable the stdio relay reads its client token from
+pub const TOKEN_ENV: &str = "AGIWORKFORCE_MCP_TOKEN";
+
+/// Filesystem, browser automation, document and calendar tools
+pub const EXPOSED_TOOLS: &[&str] = &[
+    "file_read",
+    "file_write",
+    "file_delete",
+    "browser_navigate",
+    "browser_click",
+    "browser_extract",
+    "document_read",
+    "document_search",
+    "document_create_word",
+    "document_create_excel",
+    "document_create_pdf",
+    "calendar_list_events",
+    "calendar_create_event",
+];
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// An outside MCP client allowed to call tools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerClient {
+    pub id: String,
+    pub name: String,
+    /// Permission profile every call of the client is checked against
+    pub profile_id: String,
+    /// Exposed tools the client may call; `None` for all of them
+    pub tools: Option<Vec<String>>,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+impl McpServerClient {
+    pub fn allows(&self, tool: &str) -> bool {
+        EXPOSED_TOOLS.contains(&tool)
+            && self
+                .tools
+                .as_ref()
+                .is_none_or(|tools| tools.iter().any(|allowed| allowed == tool))
+    }
+}
+
+/// Fields of a client the user sets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerClientInput {
+    pub name: String,
+    pub profile_id: String,
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+impl McpServerClientInput {
+    fn validate(&self, conn: &Connection) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Client name is required".to_string());
+        }
+        if let Some(unknown) = self
+            .tools
+            .iter()
+            .flatten()
+            .find(|tool| !EXPOSED_TOOLS.contains(&tool.as_str()))
+        {
+            return Err(format!("Tool '{}' is not exposed to MCP clients", unknown));
+        }
+        profiles::get_profile(conn, &self.profile_id)
+            .map_err(|e| format!("Failed to load permission profile: {}", e))?
+            .ok_or_else(|| format!("Unknown permission profile '{}'", self.profile_id))?;
+        Ok(())
+    }
+}
+
+/// A new client and the token it authenticates with, shown once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerClientCreated {
+    pub client: McpServerClient,
+    pub token: String,
+    /// Entry for a client's `mcpServers` config that starts the stdio relay
+    pub stdio_config: Value,
+    pub sse_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    pub listening: bool,
+    pub port: u16,
+    pub sse_url: String,
+    pub clients: Vec<McpServerClient>,
+}
+
+fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn sse_url() -> String {
+    format!("http://127.0.0.1:{}/sse", SERVER_PORT)
+}
+
+const CLIENT_COLUMNS: &str =
+    "id, name, profile_id, tools, enabled, created_at, updated_at, last_used_at";
+
+fn map_client(row: &Row) -> rusqlite::Result<McpServerClient> {
+    let tools: Option<String> = row.get(3)?;
+    Ok(McpServerClient {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        profile_id: row.get(2)?,
+        tools: tools
+            .map(|tools| serde_json::from_str(&tools))
+            .transpose()
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+        enabled: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        last_used_at: row.get(7)?,
+    })
+}
+
+pub fn list_clients(conn: &Connection) -> rusqlite::Result<Vec<McpServerClient>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM mcp_server_clients ORDER BY created_at ASC, rowid ASC",
+        CLIENT_COLUMNS
+    ))?;
+    let clients = stmt
+        .query_map([], map_client)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(clients)
+}
+
+pub fn get_client(conn: &Connection, id: &str) -> rusqlite::Result<Option<McpServerClient>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM mcp_server_clients WHERE id = ?1",
+            CLIENT_COLUMNS
+        ),
+        [id],
+        map_client,
+    )
+    .optional()
+}
+
+/// Register a client; returns it with its token, which is only stored hashed
+pub fn create_client(
+    conn: &Connection,
+    input: &McpServerClientInput,
+) -> Result<(McpServerClient, String), String> {
+    input.validate(conn)?;
+    let id = Uuid::new_v4().to_string();
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let tools = input
+        .tools
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize client tools: {}", e))?;
+    conn.execute(
+        "INSERT INTO mcp_server_clients
+             (id, name, token_hash, profile_id, tools, enabled, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+        params![
+            id,
+            input.name.trim(),
+            token_hash(&token),
+            input.profile_id,
+            tools,
+            input.enabled,
+            Utc::now().timestamp(),
+        ],
+    )
+    .map_err(|e| format!("Failed to save MCP client: {}", e))?;
+
+    let client = get_client(conn, &id)
+        .map_err(|e| format!("Failed to load MCP client: {}", e))?
+        .ok_or_else(|| format!("MCP client {} not found", id))?;
+    Ok((client, token))
+}
+
+/// Change a client's name, profile, tools or enabled state; its token stays
+pub fn update_client(
+    conn: &Connection,
+    id: &str,
+    input: &McpServerClientInput,
+) -> Result<McpServerClient, String> {
+    input.validate(conn)?;
+    let tools = input
+        .tools
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize client tools: {}", e))?;
+    let updated = conn
+        .execute(
+            "UPDATE mcp_server_clients
+             SET name = ?2, profile_id = ?3, tools = ?4, enabled = ?5, updated_at = ?6
+             WHERE id = ?1",
+            params![
+                id,
+                input.name.trim(),
+                input.profile_id,
+                tools,
+                input.enabled,
+                Utc::now().timestamp(),
+            ],
+        )
+        .map_err(|e| format!("Failed to save MCP client: {}", e))?;
+    if updated == 0 {
+        return Err(format!("MCP client {} not found", id));
+    }
+    get_client(conn, id)
+        .map_err(|e| format!("Failed to load MCP client: {}", e))?
+        .ok_or_else(|| format!("MCP client {} not found", id))
+}
+
+pub fn delete_client(conn: &Connection, id: &str) -> rusqlite::Result<bool> {
+    Ok(conn.execute("DELETE FROM mcp_server_clients WHERE id = ?1", [id])? > 0)
+}
+
+/// The enabled client `token` belongs to; marks it as used
+pub fn authenticate(conn: &Connection, token: &str) -> rusqlite::Result<Option<McpServerClient>> {
+    let client = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM mcp_server_clients WHERE token_hash = ?1 AND enabled = 1",
+                CLIENT_COLUMNS
+            ),
+            [token_hash(token)],
+            map_client,
+        )
+        .optional()?;
+    if let Some(client) = &client {
+        conn.execute(
+            "UPDATE mcp_server_clients SET last_used_at = ?2 WHERE id = ?1",
+            params![client.id, Utc::now().timestamp()],
+        )?;
+    }
+    Ok(client)
+}
+
+/// JSON Schema of a tool's parameters, as MCP's `inputSchema`
+fn input_schema(tool: &Tool) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for parameter in &tool.parameters {
+        let mut schema = match parameter.parameter_type {
+            ParameterType::String | ParameterType::FilePath => json!({ "type": "string" }),
+            ParameterType::URL => json!({ "type": "string", "format": "uri" }),
+            ParameterType::Integer => json!({ "type": "integer" }),
+            ParameterType::Float => json!({ "type": "number" }),
+            ParameterType::Boolean => json!({ "type": "boolean" }),
+            ParameterType::Object => json!({ "type": "object" }),
+            ParameterType::Array => json!({ "type": "array" }),
+        };
+        schema["description"] = Value::from(parameter.description.clone());
+        if let Some(default) = &parameter.default {
+            schema["default"] = default.clone();
+        }
+        properties.insert(parameter.name.clone(), schema);
+        if parameter.required {
+            required.push(parameter.name.clone());
+        }
+    }
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn tool_definition(tool: &Tool) -> McpToolDefinition {
+    McpToolDefinition {
+        name: tool.id.clone(),
+        description: Some(tool.description.clone()),
+        input_schema: input_schema(tool),
+    }
+}
+
+fn error_result(message: impl Into<String>) -> ToolCallResult {
+    ToolCallResult {
+        content: vec![ToolContent::Text {
+            text: message.into(),
+        }],
+        is_error: Some(true),
+    }
+}
+
+/// A parsed request to the server's listener
+#[derive(Debug, Clone, PartialEq)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// From `Authorization: Bearer`, or `?token=` for clients that cannot set headers
+    token: Option<String>,
+    session_id: Option<String>,
+    content_length: usize,
+}
+
+impl HttpRequest {
+    /// Request line and headers, up to the blank line
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.lines();
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query_value = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+
+        let mut token = query_value("token");
+        let mut content_length = 0;
+        for (name, value) in lines.filter_map(|line| line.split_once(':')) {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                if let Some(bearer) = value.trim().strip_prefix("Bearer ") {
+                    token = Some(bearer.trim().to_string());
+                }
+            }
+        }
+        Some(Self {
+            method,
+            path: path.to_string(),
+            token,
+            session_id: query_value("session_id"),
+            content_length,
+        })
+    }
+}
+
+/// An open SSE stream; replies to its `POST /messages` go out on it
+struct SseSession {
+    client_id: String,
+    sender: mpsc::UnboundedSender<String>,
+}
+
+/// Answers outside MCP clients with the app's exposed tools
+pub struct McpToolServer {
+    pool: Pool,
+    /// Tool definitions, so tools can be listed before the agent starts
+    registry: ToolRegistry,
+    sessions: Mutex<HashMap<String, SseSession>>,
+    listening: AtomicBool,
+}
+
+impl McpToolServer {
+    pub fn new(pool: Pool) -> anyhow::Result<Self> {
+        let registry = ToolRegistry::new()?;
+        registry.register_builtin_tools()?;
+        Ok(Self {
+            pool,
+            registry,
+            sessions: Mutex::new(HashMap::new()),
+            listening: AtomicBool::new(false),
+        })
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::SeqCst)
+    }
+
+    pub async fn status(&self) -> Result<McpServerStatus, String> {
+        let clients = self
+            .pool
+            .run(|conn| list_clients(conn).map_err(|e| e.to_string()))
+            .await?;
+        Ok(McpServerStatus {
+            listening: self.is_listening(),
+            port: SERVER_PORT,
+            sse_url: sse_url(),
+            clients,
+        })
+    }
+
+    /// Every exposed tool, for picking what a client may call
+    pub fn exposed_tools(&self) -> Vec<McpToolDefinition> {
+        EXPOSED_TOOLS
+            .iter()
+            .filter_map(|id| self.registry.get_tool(id))
+            .map(|tool| tool_definition(&tool))
+            .collect()
+    }
+
+    pub async fn create_client(
+        self: &Arc<Self>,
+        input: McpServerClientInput,
+    ) -> Result<McpServerClientCreated, String> {
+        let (client, token) = self
+            .pool
+            .run(move |conn| create_client(conn, &input))
+            .await?;
+        self.sync_listener().await?;
+
+        let command = std::env::current_exe()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "agiworkforce".to_string());
+        let stdio_config = json!({
+            "command": command,
+            "args": [STDIO_FLAG],
+            "env": { TOKEN_ENV: token },
+        });
+        Ok(McpServerClientCreated {
+            client,
+            token,
+            stdio_config,
+            sse_url: sse_url(),
+        })
+    }
+
+    pub async fn update_client(
+        self: &Arc<Self>,
+        id: String,
+        input: McpServerClientInput,
+    ) -> Result<McpServerClient, String> {
+        let client = self
+            .pool
+            .run(move |conn| update_client(conn, &id, &input))
+            .await?;
+        if !client.enabled {
+            self.close_sessions(&client.id);
+        }
+        self.sync_listener().await?;
+        Ok(client)
+    }
+
+    pub async fn delete_client(&self, id: String) -> Result<bool, String> {
+        self.close_sessions(&id);
+        self.pool
+            .run(move |conn| delete_client(conn, &id).map_err(|e| e.to_string()))
+            .await
+    }
+
+    /// End a client's SSE streams
+    fn close_sessions(&self, client_id: &str) {
+        self.sessions
+            .lock()
+            .retain(|_, session| session.client_id != client_id);
+    }
+
+    /// Start listening once a client is enabled, unless in safe mode; later
+    /// calls do nothing
+    pub async fn sync_listener(self: &Arc<Self>) -> Result<(), String> {
+        if crate::safe_mode::is_active() {
+            return Ok(());
+        }
+        let any_enabled = self
+            .pool
+            .run(|conn| {
+                list_clients(conn)
+                    .map(|clients| clients.iter().any(|client| client.enabled))
+                    .map_err(|e| e.to_string())
+            })
+            .await?;
+        if !any_enabled || self.listening.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let listener = match TcpListener::bind(("127.0.0.1", SERVER_PORT)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                self.listening.store(false, Ordering::SeqCst);
+                return Err(format!(
+                    "Failed to bind MCP server on {}: {}",
+                    SERVER_PORT, e
+                ));
+            }
+        };
+        tracing::info!("MCP server listening on 127.0.0.1:{}", SERVER_PORT);
+
+        let server = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                let Some(server) = server.upgrade() else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    if let Err(e) = server.handle_connection(stream).await {
+                        tracing::debug!("MCP server connection failed: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(())
+    }
+
+    async fn authenticate(&self, token: Option<String>) -> Option<McpServerClient> {
+        let token = token?;
+        self.pool
+            .run(move |conn| authenticate(conn, &token).map_err(|e| e.to_string()))
+            .await
+            .unwrap_or_else(|e: String| {
+                tracing::warn!("Failed to authenticate MCP client: {}", e);
+                None
+            })
+    }
+
+    async fn handle_connection(self: Arc<Self>, mut stream: TcpStream) -> std::io::Result<()> {
+        let timed_out = || std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out");
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let header_end = loop {
+            let read = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut chunk))
+                .await
+                .map_err(|_| timed_out())??;
+            if read == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+            if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+            if buffer.len() > MAX_HEADER_BYTES {
+                return respond(
+                    &mut stream,
+                    431,
+                    Some(json!({ "error": "Headers too large" })),
+                )
+                .await;
+            }
+        };
+
+        let Some(request) = HttpRequest::parse(&String::from_utf8_lossy(&buffer[..header_end]))
+        else {
+            return respond(
+                &mut stream,
+                400,
+                Some(json!({ "error": "Malformed request" })),
+            )
+            .await;
+        };
+        if request.content_length > MAX_BODY_BYTES {
+            return respond(
+                &mut stream,
+                413,
+                Some(json!({ "error": "Payload too large" })),
+            )
+            .await;
+        }
+        let mut body = buffer.split_off(header_end);
+        while body.len() < request.content_length {
+            let read = tokio::time::timeout(READ_TIMEOUT, stream.read(&mut chunk))
+                .await
+                .map_err(|_| timed_out())??;
+            if read == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..read]);
+        }
+        body.truncate(request.content_length);
+
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/sse") => match self.authenticate(request.token).await {
+                Some(client) => self.serve_sse(stream, client).await,
+                None => unauthorized(&mut stream).await,
+            },
+            ("POST", "/messages") => {
+                let session = request.session_id.and_then(|id| {
+                    let sessions = self.sessions.lock();
+                    sessions
+                        .get(&id)
+                        .map(|session| (session.client_id.clone(), session.sender.clone()))
+                });
+                let Some((client_id, sender)) = session else {
+                    return respond(
+                        &mut stream,
+                        404,
+                        Some(json!({ "error": "Unknown session" })),
+                    )
+                    .await;
+                };
+                let client = self
+                    .pool
+                    .run(move |conn| get_client(conn, &client_id).map_err(|e| e.to_string()))
+                    .await
+                    .ok()
+                    .flatten()
+                    .filter(|client| client.enabled);
+                let Some(client) = client else {
+                    return unauthorized(&mut stream).await;
+                };
+                respond(&mut stream, 202, None).await?;
+                if let Some(reply) = self.handle_body(&client, &body).await {
+                    let _ = sender.send(reply.to_string());
+                }
+                Ok(())
+            }
+            ("POST", "/rpc") => {
+                let Some(client) = self.authenticate(request.token).await else {
+                    return unauthorized(&mut stream).await;
+                };
+                let reply = self.handle_body(&client, &body).await;
+                respond(&mut stream, if reply.is_some() { 200 } else { 202 }, reply).await
+            }
+            _ => respond(&mut stream, 404, Some(json!({ "error": "Not found" }))).await,
+        }
+    }
+
+    /// Stream replies to the client's `POST /messages` until it disconnects
+    /// or is disabled
+    async fn serve_sse(
+        &self,
+        mut stream: TcpStream,
+        client: McpServerClient,
+    ) -> std::io::Result<()> {
+        let session_id = Uuid::new_v4().to_string();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        self.sessions.lock().insert(
+            session_id.clone(),
+            SseSession {
+                client_id: client.id.clone(),
+                sender,
+            },
+        );
+        tracing::info!("MCP client '{}' connected over SSE", client.name);
+
+        let result = async {
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+                )
+                .await?;
+            stream
+                .write_all(
+                    format!(
+                        "event: endpoint\ndata: /messages?session_id={}\n\n",
+                        session_id
+                    )
+                    .as_bytes(),
+                )
+                .await?;
+            loop {
+                let event = match tokio::time::timeout(SSE_KEEPALIVE, receiver.recv()).await {
+                    Ok(Some(message)) => format!("event: message\ndata: {}\n\n", message),
+                    Ok(None) => return Ok(()),
+                    Err(_) => ": keepalive\n\n".to_string(),
+                };
+                stream.write_all(event.as_bytes()).await?;
+            }
+        }
+        .await;
+
+        self.sessions.lock().remove(&session_id);
+        result
+    }
+
+    async fn handle_body(&self, client: &McpServerClient, body: &[u8]) -> Option<Value> {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(message) => self.handle_message(client, message).await,
+            Err(e) => Some(rpc_error(
+                RequestId::Null,
+                PARSE_ERROR,
+                format!("Invalid JSON: {}", e),
+            )),
+        }
+    }
+
+    /// Reply to one JSON-RPC message; notifications get none
+    pub async fn handle_message(&self, client: &McpServerClient, message: Value) -> Option<Value> {
+        let id = match message.get("id") {
+            Some(id) => serde_json::from_value(id.clone()).unwrap_or(RequestId::Null),
+            None => return None,
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(rpc_error(id, INVALID_REQUEST, "Missing method"));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => serde_json::to_value(InitializeResult {
+                protocol_version: PROTOCOL_VERSION.to_string(),
+                capabilities: ServerCapabilities {
+                    tools: Some(HashMap::from([(
+                        "listChanged".to_string(),
+                        Value::Bool(false),
+                    )])),
+                    ..Default::default()
+                },
+                server_info: Implementation {
+                    name: "agiworkforce".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+            }),
+            "ping" => Ok(json!({})),
+            "tools/list" => serde_json::to_value(ToolsListResult {
+                tools: self
+                    .exposed_tools()
+                    .into_iter()
+                    .filter(|tool| client.allows(&tool.name))
+                    .collect(),
+                next_cursor: None,
+            }),
+            "tools/call" => match serde_json::from_value::<ToolCallParams>(params) {
+                Ok(params) => serde_json::to_value(self.call_tool(client, params).await),
+                Err(e) => return Some(rpc_error(id, INVALID_PARAMS, e.to_string())),
+            },
+            _ => {
+                return Some(rpc_error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    format!("Method '{}' not found", method),
+                ))
+            }
+        };
+
+        Some(match result {
+            Ok(result) => json!(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result,
+                id,
+            }),
+            Err(e) => rpc_error(id, INVALID_PARAMS, e.to_string()),
+        })
+    }
+
+    /// Run a tool for `client`. Failures, including permission denials, are
+    /// tool results with `isError` so the client's model sees them
+    async fn call_tool(&self, client: &McpServerClient, params: ToolCallParams) -> ToolCallResult {
+        if !client.allows(&params.name) {
+            return error_result(format!("Tool '{}' is not available", params.name));
+        }
+        let arguments = params.arguments.unwrap_or_default();
+        let permissions = RunPermissions {
+            profile_id: Some(client.profile_id.clone()),
+            user_employee_id: None,
+        };
+        let caller = format!("MCP client '{}'", client.name);
+        match crate::commands::agi::call_tool(&params.name, &arguments, &caller, permissions).await
+        {
+            Ok(Some(value)) => ToolCallResult {
+                content: vec![ToolContent::Text {
+                    text: match value {
+                        Value::String(text) => text,
+                        value => serde_json::to_string_pretty(&value).unwrap_or_default(),
+                    },
+                }],
+                is_error: None,
+            },
+            Ok(None) => error_result("The AGI Workforce agent is not running; start it in the app"),
+            Err(e) => error_result(e),
+        }
+    }
+}
+
+fn rpc_error(id: RequestId, code: i32, message: impl Into<String>) -> Value {
+    json!(JsonRpcError {
+        jsonrpc: "2.0".to_string(),
+        error: ErrorObject {
+            code,
+            message: message.into(),
+            data: None,
+        },
+        id,
+    })
+}
+
+async fn unauthorized(stream: &mut TcpStream) -> std::io::Result<()> {
+    respond(
+        stream,
+        401,
+        Some(json!({ "error": "Invalid or missing token" })),
+    )
+    .await
+}
+
+async fn respond(stream: &mut TcpStream, status: u16, body: Option<Value>) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        _ => "Request Header Fields Too Large",
+    };
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Relay JSON-RPC lines between stdin/stdout and the running app's
+/// `POST /rpc`, for clients that only launch stdio servers. Returns the
+/// process exit code
+pub fn run_stdio_bridge() -> i32 {
+    let Ok(token) = std::env::var(TOKEN_ENV) else {
+        eprintln!("{} must be set to an MCP client token", TOKEN_ENV);
+        return 2;
+    };
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start MCP stdio relay: {}", e);
+            return 1;
+        }
+    };
+
+    runtime.block_on(async move {
+        let http = reqwest::Client::new();
+        let url = format!("http://127.0.0.1:{}/rpc", SERVER_PORT);
+        let (replies, mut outgoing) = mpsc::unbounded_channel::<String>();
+
+        let writer = tokio::spawn(async move {
+            let mut stdout = tokio::io::stdout();
+            while let Some(reply) = outgoing.recv().await {
+                if stdout
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .await
+                    .is_err()
+                    || stdout.flush().await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (http, url, token, replies) =
+                (http.clone(), url.clone(), token.clone(), replies.clone());
+            // Calls may wait on an approval prompt, so later messages don't queue behind them
+            tokio::spawn(async move {
+                let id = serde_json::from_str::<Value>(&line)
+                    .ok()
+                    .and_then(|message| message.get("id").cloned());
+                let sent = http
+                    .post(&url)
+                    .bearer_auth(&token)
+                    .header("Content-Type", "application/json")
+                    .body(line)
+                    .send()
+                    .await;
+                let reply = match sent {
+                    Ok(response) if response.status() == reqwest::StatusCode::ACCEPTED => None,
+                    Ok(response) if response.status().is_success() => response.text().await.ok(),
+                    Ok(response) => {
+                        let status = response.status();
+                        eprintln!("AGI Workforce rejected the request: {}", status);
+                        id.map(|id| {
+                            rpc_error(
+                                serde_json::from_value(id).unwrap_or(RequestId::Null),
+                                INVALID_REQUEST,
+                                format!("AGI Workforce rejected the request: {}", status),
+                            )
+                            .to_string()
+                        })
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to reach AGI Workforce: {}", e);
+                        id.map(|id| {
+                            rpc_error(
+                                serde_json::from_value(id).unwrap_or(RequestId::Null),
+                                crate::mcp::protocol::INTERNAL_ERROR,
+                                "AGI Workforce is not running",
+                            )
+                            .to_string()
+                        })
+                    }
+                };
+                if let Some(reply) = reply {
+                    let _ = replies.send(reply);
+                }
+            });
+        }
+
+        drop(replies);
+        let _ = writer.await;
+    });
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> Arc<McpToolServer> {
+        let pool = Pool::in_memory().unwrap();
+        pool.get()
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE mcp_server_clients (
+                    id TEXT PRIMARY KEY, name TEXT NOT NULL, token_hash TEXT NOT NULL UNIQUE,
+                    profile_id TEXT NOT NULL, tools TEXT, enabled INTEGER NOT NULL DEFAULT 1,
+                    created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL, last_used_at INTEGER
+                );
+                CREATE TABLE permission_profiles (
+                    id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT NOT NULL,
+                    rules TEXT NOT NULL
+                );",
+            )
+            .unwrap();
+        Arc::new(McpToolServer::new(pool).unwrap())
+    }
+
+    fn input(tools: Option<&[&str]>) -> McpServerClientInput {
+        McpServerClientInput {
+            name: "Claude Desktop".to_string(),
+            profile_id: profiles::READ_ONLY_ANALYST.to_string(),
+            tools: tools.map(|tools| tools.iter().map(|tool| tool.to_string()).collect()),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_clients_authenticate_by_token() {
+        let server = server();
+        let conn = server.pool.get().unwrap();
+
+        let (client, token) = create_client(&conn, &input(Some(&["file_read"]))).unwrap();
+        assert!(client.allows("file_read"));
+        assert!(!client.allows("file_write"));
+        assert!(authenticate(&conn, "wrong").unwrap().is_none());
+        let authenticated = authenticate(&conn, &token).unwrap().unwrap();
+        assert_eq!(authenticated.id, client.id);
+        assert!(get_client(&conn, &client.id)
+            .unwrap()
+            .unwrap()
+            .last_used_at
+            .is_some());
+
+        let mut disabled = input(None);
+        disabled.enabled = false;
+        update_client(&conn, &client.id, &disabled).unwrap();
+        assert!(authenticate(&conn, &token).unwrap().is_none());
+
+        assert!(create_client(&conn, &input(Some(&["terminal_execute"]))).is_err());
+        let mut unknown_profile = input(None);
+        unknown_profile.profile_id = "missing".to_string();
+        assert!(create_client(&conn, &unknown_profile).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lists_only_the_clients_tools() {
+        let server = server();
+        let client = create_client(
+            &server.pool.get().unwrap(),
+            &input(Some(&["file_read", "document_search"])),
+        )
+        .unwrap()
+        .0;
+
+        let reply = server
+            .handle_message(
+                &client,
+                json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }),
+            )
+            .await
+            .unwrap();
+        let tools = reply["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 2);
+        let file_read = tools
+            .iter()
+            .find(|tool| tool["name"] == "file_read")
+            .unwrap();
+        assert_eq!(file_read["inputSchema"]["type"], "object");
+        assert!(file_read["inputSchema"]["required"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("path")));
+
+        let denied = server
+            .handle_message(
+                &client,
+                json!({
+                    "jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                    "params": { "name": "file_write", "arguments": {} }
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(denied["result"]["isError"], true);
+
+        assert!(server
+            .handle_message(
+                &client,
+                json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            )
+            .await
+            .is_none());
+        let unknown = server
+            .handle_message(
+                &client,
+                json!({ "jsonrpc": "2.0", "id": "x", "method": "resources/list" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_parse_request_reads_bearer_token_and_session() {
+        let request = HttpRequest::parse(
+            "POST /messages?session_id=abc HTTP/1.1\r\nAuthorization: Bearer t0k\r\nContent-Length: 12\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.path, "/messages");
+        assert_eq!(request.token.as_deref(), Some("t0k"));
+        assert_eq!(request.session_id.as_deref(), Some("abc"));
+        assert_eq!(request.content_length, 12);
+
+        let request = HttpRequest::parse("GET /sse?token=q HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(request.token.as_deref(), Some("q"));
+    }
+}
@@ -3,23 +3,28 @@
 // Manages the lifecycle of an MCP server connection, including initialization,
 // capabilities negotiation, and tool discovery.
 
+use super::http_transport::StreamableHttpTransport;
+use super::oauth::McpOAuth;
 use super::protocol::{
-    ClientCapabilities, Implementation, InitializeParams, InitializeResult, McpToolDefinition,
-    ResourceDefinition, ResourceReadParams, ResourceReadResult, ResourcesListParams,
-    ResourcesListResult, ToolCallParams, ToolCallResult, ToolsListResult,
+    ClientCapabilities, Implementation, InitializeParams, InitializeResult, JsonRpcNotification,
+    McpToolDefinition, PromptDefinition, PromptGetParams, PromptGetResult, PromptsListParams,
+    PromptsListResult, ResourceDefinition, ResourceReadParams, ResourceReadResult,
+    ResourceSubscribeParams, ResourcesListParams, ResourcesListResult, ToolCallParams,
+    ToolCallResult, ToolsListResult,
 };
-use super::transport::StdioTransport;
+use super::transport::{McpTransport, StdioTransport};
 use crate::mcp::{McpError, McpResult, McpServerConfig};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 
 /// MCP Session with a connected server
 pub struct McpSession {
     /// Server name
     name: String,
     /// Transport layer
-    transport: Arc<StdioTransport>,
+    transport: Arc<McpTransport>,
     /// Server info from initialization
     server_info: Option<Implementation>,
     /// Server capabilities
@@ -31,10 +36,24 @@ pub struct McpSession {
 impl McpSession {
     /// Create a new session and connect to the server
     pub async fn connect(name: String, config: McpServerConfig) -> McpResult<Self> {
+        Self::connect_with_auth(name, config, None).await
+    }
+
+    /// Connect, authorizing requests to a remote server with `auth`
+    pub async fn connect_with_auth(
+        name: String,
+        config: McpServerConfig,
+        auth: Option<Arc<McpOAuth>>,
+    ) -> McpResult<Self> {
         tracing::info!("[MCP Session] Connecting to server '{}'", name);
 
         // Create transport
-        let transport = StdioTransport::new(&config.command, &config.args, &config.env).await?;
+        let transport = match &config.url {
+            Some(url) => McpTransport::Http(StreamableHttpTransport::new(name.clone(), url, auth)?),
+            None => McpTransport::Stdio(
+                StdioTransport::new(&config.command, &config.args, &config.env).await?,
+            ),
+        };
 
         let session = Self {
             name,
@@ -52,7 +71,7 @@ impl McpSession {
         tracing::info!("[MCP Session] Initializing session for '{}'", self.name);
 
         let params = InitializeParams {
-            protocol_version: "2024-11-05".to_string(),
+            protocol_version: self.transport.protocol_version().to_string(),
             capabilities: ClientCapabilities::default(),
             client_info: Implementation {
                 name: "AGI Workforce".to_string(),
@@ -82,7 +101,8 @@ impl McpSession {
 
         // Send initialized notification
         self.transport
-            .send_notification("notifications/initialized".to_string(), None);
+            .send_notification("notifications/initialized".to_string(), None)
+            .await;
 
         Ok(result)
     }
@@ -150,50 +170,167 @@ impl McpSession {
         Ok(result)
     }
 
-    /// List available resources (if supported)
+    /// List available resources (if supported), following pagination
     pub async fn list_resources(&self) -> McpResult<Vec<ResourceDefinition>> {
         tracing::debug!("[MCP Session] Listing resources for '{}'", self.name);
 
-        let params = ResourcesListParams { cursor: None };
+        let mut resources = Vec::new();
+        let mut cursor = None;
+        loop {
+            let params = ResourcesListParams { cursor };
+
+            let response = self
+                .transport
+                .send_request(
+                    "resources/list".to_string(),
+                    Some(serde_json::to_value(params)?),
+                )
+                .await?;
+
+            let result: ResourcesListResult = serde_json::from_value(response.result)?;
+            resources.extend(result.resources);
+            match result.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(resources),
+            }
+        }
+    }
+
+    /// Read a resource by URI
+    pub async fn read_resource(&self, uri: &str) -> McpResult<ResourceReadResult> {
+        tracing::debug!(
+            "[MCP Session] Reading resource '{}' from server '{}'",
+            uri,
+            self.name
+        );
+
+        let params = ResourceReadParams {
+            uri: uri.to_string(),
+        };
 
         let response = self
             .transport
             .send_request(
-                "resources/list".to_string(),
+                "resources/read".to_string(),
                 Some(serde_json::to_value(params)?),
             )
             .await?;
 
-        let result: ResourcesListResult = serde_json::from_value(response.result)?;
+        let result: ResourceReadResult = serde_json::from_value(response.result)?;
 
-        Ok(result.resources)
+        Ok(result)
     }
 
-    /// Read a resource by URI
-    pub async fn read_resource(&self, uri: &str) -> McpResult<ResourceReadResult> {
+    /// Ask the server to send `notifications/resources/updated` when a
+    /// resource changes
+    pub async fn subscribe_resource(&self, uri: &str) -> McpResult<()> {
+        self.resource_subscription("resources/subscribe", uri).await
+    }
+
+    pub async fn unsubscribe_resource(&self, uri: &str) -> McpResult<()> {
+        self.resource_subscription("resources/unsubscribe", uri)
+            .await
+    }
+
+    async fn resource_subscription(&self, method: &str, uri: &str) -> McpResult<()> {
+        let subscribable = self
+            .capabilities
+            .as_ref()
+            .and_then(|caps| caps.resources.as_ref())
+            .and_then(|resources| resources.get("subscribe"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if !subscribable {
+            return Err(McpError::InvalidConfig(format!(
+                "Server '{}' does not support resource subscriptions",
+                self.name
+            )));
+        }
+
+        let params = ResourceSubscribeParams {
+            uri: uri.to_string(),
+        };
+        self.transport
+            .send_request(method.to_string(), Some(serde_json::to_value(params)?))
+            .await?;
+        Ok(())
+    }
+
+    /// List prompt templates (if supported), following pagination
+    pub async fn list_prompts(&self) -> McpResult<Vec<PromptDefinition>> {
+        tracing::debug!("[MCP Session] Listing prompts for '{}'", self.name);
+
+        let mut prompts = Vec::new();
+        let mut cursor = None;
+        loop {
+            let params = PromptsListParams { cursor };
+
+            let response = self
+                .transport
+                .send_request(
+                    "prompts/list".to_string(),
+                    Some(serde_json::to_value(params)?),
+                )
+                .await?;
+
+            let result: PromptsListResult = serde_json::from_value(response.result)?;
+            prompts.extend(result.prompts);
+            match result.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(prompts),
+            }
+        }
+    }
+
+    /// Render a prompt template with its arguments
+    pub async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> McpResult<PromptGetResult> {
         tracing::debug!(
-            "[MCP Session] Reading resource '{}' from server '{}'",
-            uri,
+            "[MCP Session] Getting prompt '{}' from server '{}'",
+            name,
             self.name
         );
 
-        let params = ResourceReadParams {
-            uri: uri.to_string(),
+        let params = PromptGetParams {
+            name: name.to_string(),
+            arguments,
         };
 
         let response = self
             .transport
             .send_request(
-                "resources/read".to_string(),
+                "prompts/get".to_string(),
                 Some(serde_json::to_value(params)?),
             )
             .await?;
 
-        let result: ResourceReadResult = serde_json::from_value(response.result)?;
+        let result: PromptGetResult = serde_json::from_value(response.result)?;
 
         Ok(result)
     }
 
+    /// Whether the server declared resource support when initializing
+    pub fn supports_resources(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_some_and(|caps| caps.resources.is_some())
+    }
+
+    /// Whether the server declared prompt support when initializing
+    pub fn supports_prompts(&self) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_some_and(|caps| caps.prompts.is_some())
+    }
+
+    /// Receive the server's notifications
+    pub fn notifications(&self) -> mpsc::UnboundedReceiver<JsonRpcNotification> {
+        self.transport.notifications()
+    }
+
     /// Get server information
     pub fn get_server_info(&self) -> Option<Implementation> {
         self.server_info.clone()
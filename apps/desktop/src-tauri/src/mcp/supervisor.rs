@@ -0,0 +1,362 @@
+use crate::mcp::{McpClient, McpServersConfig};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// Event emitted whenever a supervised server changes state
+pub const SERVER_STATUS_EVENT: &str = "mcp://server-status";
+
+/// When crashed servers are restarted and when they are given up on
+#[derive(Debug, Clone)]
+pub struct SupervisorPolicy {
+    /// How often server processes are checked
+    pub check_interval: Duration,
+    /// Wait before the first restart; doubled for each further crash
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Crashes in a row after which a server is quarantined
+    pub max_restarts: u32,
+    /// Uptime after which a server's crash count resets
+    pub stable_after: Duration,
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(2),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_restarts: 5,
+            stable_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl SupervisorPolicy {
+    fn backoff(&self, crashes: u32) -> Duration {
+        let factor = 2u32.saturating_pow(crashes.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupervisedState {
+    Running,
+    /// Crashed; a restart is scheduled
+    Restarting,
+    /// Crashed too often; stays down until reconnected by hand
+    Quarantined,
+}
+
+/// Payload of `mcp://server-status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatusEvent {
+    pub server_name: String,
+    pub state: SupervisedState,
+    /// Crashes since the server last ran stably
+    pub crashes: u32,
+    /// When the next restart is attempted
+    pub retry_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+struct Record {
+    state: SupervisedState,
+    crashes: u32,
+    retry_at: Option<(Instant, DateTime<Utc>)>,
+    started_at: Option<Instant>,
+    error: Option<String>,
+}
+
+/// Crash bookkeeping per server, kept apart from process handling
+#[derive(Default)]
+struct Supervision {
+    records: HashMap<String, Record>,
+}
+
+impl Supervision {
+    fn crashed(
+        &mut self,
+        name: &str,
+        error: String,
+        now: Instant,
+        policy: &SupervisorPolicy,
+    ) -> ServerStatusEvent {
+        let record = self.records.entry(name.to_string()).or_insert(Record {
+            state: SupervisedState::Running,
+            crashes: 0,
+            retry_at: None,
+            started_at: None,
+            error: None,
+        });
+        if record
+            .started_at
+            .is_some_and(|started| now.duration_since(started) >= policy.stable_after)
+        {
+            record.crashes = 0;
+        }
+        record.crashes += 1;
+        record.started_at = None;
+        record.error = Some(error);
+
+        if record.crashes > policy.max_restarts {
+            record.state = SupervisedState::Quarantined;
+            record.retry_at = None;
+        } else {
+            let backoff = policy.backoff(record.crashes);
+            record.state = SupervisedState::Restarting;
+            record.retry_at = Some((
+                now + backoff,
+                Utc::now() + chrono::Duration::from_std(backoff).unwrap_or_default(),
+            ));
+        }
+        Self::event(name, record)
+    }
+
+    fn started(&mut self, name: &str, now: Instant) -> Option<ServerStatusEvent> {
+        let record = self.records.get_mut(name)?;
+        record.state = SupervisedState::Running;
+        record.retry_at = None;
+        record.started_at = Some(now);
+        record.error = None;
+        Some(Self::event(name, record))
+    }
+
+    /// Forget servers that have run long enough since their last restart
+    fn settle(&mut self, now: Instant, policy: &SupervisorPolicy) {
+        self.records.retain(|_, record| {
+            record
+                .started_at
+                .is_none_or(|started| now.duration_since(started) < policy.stable_after)
+        });
+    }
+
+    /// Servers whose restart is due
+    fn due(&self, now: Instant) -> Vec<String> {
+        self.records
+            .iter()
+            .filter(|(_, record)| {
+                record.state == SupervisedState::Restarting
+                    && record.retry_at.is_some_and(|(at, _)| at <= now)
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    fn event(name: &str, record: &Record) -> ServerStatusEvent {
+        ServerStatusEvent {
+            server_name: name.to_string(),
+            state: record.state,
+            crashes: record.crashes,
+            retry_at: record.retry_at.map(|(_, at)| at),
+            error: record.error.clone(),
+        }
+    }
+}
+
+/// Watches connected stdio servers, restarts crashed ones with exponential
+/// backoff and quarantines those that keep crashing. A crashed server's
+/// session is dropped right away, so its tools leave the tool listings
+pub struct McpSupervisor {
+    client: Arc<McpClient>,
+    config: Arc<Mutex<McpServersConfig>>,
+    policy: SupervisorPolicy,
+    supervision: Mutex<Supervision>,
+    running: AtomicBool,
+}
+
+impl McpSupervisor {
+    pub fn new(client: Arc<McpClient>, config: Arc<Mutex<McpServersConfig>>) -> Self {
+        Self::with_policy(client, config, SupervisorPolicy::default())
+    }
+
+    pub fn with_policy(
+        client: Arc<McpClient>,
+        config: Arc<Mutex<McpServersConfig>>,
+        policy: SupervisorPolicy,
+    ) -> Self {
+        Self {
+            client,
+            config,
+            policy,
+            supervision: Mutex::new(Supervision::default()),
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Start supervising; later calls do nothing
+    pub fn start(self: Arc<Self>, app_handle: tauri::AppHandle) {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(self.policy.check_interval);
+            loop {
+                interval_timer.tick().await;
+                for event in self.check().await {
+                    if let Err(e) = app_handle.emit(SERVER_STATUS_EVENT, &event) {
+                        tracing::error!("[MCP Supervisor] Failed to emit status event: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Drop sessions whose process exited and restart servers that are due
+    async fn check(&self) -> Vec<ServerStatusEvent> {
+        let mut events = Vec::new();
+
+        for (name, alive) in self.client.health_check() {
+            if alive {
+                continue;
+            }
+            tracing::warn!("[MCP Supervisor] Server '{}' exited", name);
+            let _ = self.client.disconnect_server(&name).await;
+            let event = self.supervision.lock().crashed(
+                &name,
+                "Server process exited".to_string(),
+                Instant::now(),
+                &self.policy,
+            );
+            if event.state == SupervisedState::Quarantined {
+                tracing::error!(
+                    "[MCP Supervisor] Quarantined '{}' after {} crashes",
+                    name,
+                    event.crashes
+                );
+            }
+            events.push(event);
+        }
+
+        let due = {
+            let mut supervision = self.supervision.lock();
+            supervision.settle(Instant::now(), &self.policy);
+            supervision.due(Instant::now())
+        };
+        for name in due {
+            let config = self
+                .config
+                .lock()
+                .mcp_servers
+                .get(&name)
+                .filter(|config| config.enabled)
+                .cloned();
+            let Some(config) = config else {
+                // Removed or disabled since it crashed
+                self.reset(&name);
+                continue;
+            };
+
+            tracing::info!("[MCP Supervisor] Restarting server '{}'", name);
+            let result = self.client.connect_server(name.clone(), config).await;
+            let mut supervision = self.supervision.lock();
+            let event = match result {
+                Ok(()) => supervision.started(&name, Instant::now()),
+                Err(e) => {
+                    Some(supervision.crashed(&name, e.to_string(), Instant::now(), &self.policy))
+                }
+            };
+            events.extend(event);
+        }
+
+        events
+    }
+
+    /// Stop tracking a server, e.g. after it was connected or disconnected by
+    /// hand. Lifts a quarantine
+    pub fn reset(&self, name: &str) {
+        self.supervision.lock().records.remove(name);
+    }
+
+    /// Servers that crashed recently, are restarting or are quarantined
+    pub fn statuses(&self) -> Vec<ServerStatusEvent> {
+        let supervision = self.supervision.lock();
+        supervision
+            .records
+            .iter()
+            .map(|(name, record)| Supervision::event(name, record))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> SupervisorPolicy {
+        SupervisorPolicy {
+            max_restarts: 3,
+            ..SupervisorPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let policy = policy();
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(30), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_repeated_crashes_quarantine_server() {
+        let policy = policy();
+        let mut supervision = Supervision::default();
+        let now = Instant::now();
+
+        for attempt in 1..=3 {
+            let event = supervision.crashed("git", "exited".to_string(), now, &policy);
+            assert_eq!(event.state, SupervisedState::Restarting);
+            assert_eq!(event.crashes, attempt);
+            assert!(event.retry_at.is_some());
+        }
+        assert!(supervision.due(now).is_empty());
+        assert_eq!(supervision.due(now + Duration::from_secs(4)), ["git"]);
+
+        let event = supervision.crashed("git", "exited".to_string(), now, &policy);
+        assert_eq!(event.state, SupervisedState::Quarantined);
+        assert!(event.retry_at.is_none());
+        assert!(supervision.due(now + Duration::from_secs(3600)).is_empty());
+    }
+
+    #[test]
+    fn test_stable_uptime_resets_crash_count() {
+        let policy = policy();
+        let mut supervision = Supervision::default();
+        let now = Instant::now();
+
+        supervision.crashed("git", "exited".to_string(), now, &policy);
+        supervision.crashed("git", "exited".to_string(), now, &policy);
+        let event = supervision.started("git", now).unwrap();
+        assert_eq!(event.state, SupervisedState::Running);
+        assert_eq!(event.crashes, 2);
+
+        // A crash soon after the restart keeps counting
+        let soon = now + Duration::from_secs(10);
+        assert_eq!(
+            supervision
+                .crashed("git", "exited".to_string(), soon, &policy)
+                .crashes,
+            3
+        );
+
+        supervision.started("git", soon);
+        let later = soon + policy.stable_after;
+        supervision.settle(later, &policy);
+        assert!(supervision.records.is_empty());
+        assert_eq!(
+            supervision
+                .crashed("git", "exited".to_string(), later, &policy)
+                .crashes,
+            1
+        );
+    }
+}
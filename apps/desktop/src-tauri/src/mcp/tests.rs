@@ -153,6 +153,7 @@ mod tests {
                 "-y".to_string(),
                 "@modelcontextprotocol/server-filesystem".to_string(),
             ],
+            url: None,
             env: HashMap::from([("KEY".to_string(), "value".to_string())]),
             enabled: true,
         };
@@ -226,6 +227,63 @@ mod tests {
         assert!(json.contains("Review code"));
     }
 
+    #[test]
+    fn test_resource_read_result_parsing() {
+        let json = r#"{"contents":[
+            {"uri":"file:///a.txt","mimeType":"text/plain","text":"hello"},
+            {"uri":"file:///b.png","mimeType":"image/png","blob":"iVBORw0KGgo="}
+        ]}"#;
+        let result: ResourceReadResult = serde_json::from_str(json).unwrap();
+
+        match &result.contents[0] {
+            ResourceContent::Text {
+                uri,
+                mime_type,
+                text,
+            } => {
+                assert_eq!(uri, "file:///a.txt");
+                assert_eq!(mime_type.as_deref(), Some("text/plain"));
+                assert_eq!(text, "hello");
+            }
+            other => panic!("Expected text content, got {:?}", other),
+        }
+        assert!(matches!(
+            &result.contents[1],
+            ResourceContent::Blob { blob, .. } if blob == "iVBORw0KGgo="
+        ));
+    }
+
+    #[test]
+    fn test_prompt_get_result_parsing() {
+        let json = r#"{"description":"Review a file","messages":[
+            {"role":"user","content":{"type":"text","text":"Review this code"}},
+            {"role":"user","content":{"type":"resource","resource":
+                {"uri":"file:///main.rs","mimeType":"text/x-rust","text":"fn main() {}"}}}
+        ]}"#;
+        let result: PromptGetResult = serde_json::from_str(json).unwrap();
+
+        assert_eq!(result.messages.len(), 2);
+        assert!(matches!(
+            &result.messages[0].content,
+            ToolContent::Text { text } if text == "Review this code"
+        ));
+        match &result.messages[1].content {
+            ToolContent::Resource { resource } => {
+                assert_eq!(resource.text.as_deref(), Some("fn main() {}"));
+            }
+            other => panic!("Expected resource content, got {:?}", other),
+        }
+
+        let params = PromptGetParams {
+            name: "review".to_string(),
+            arguments: HashMap::new(),
+        };
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            serde_json::json!({ "name": "review" })
+        );
+    }
+
     // Integration test (requires npx and @modelcontextprotocol/server-filesystem)
     // This test is ignored by default
     #[tokio::test]
@@ -238,6 +296,7 @@ mod tests {
                 "@modelcontextprotocol/server-filesystem".to_string(),
                 ".".to_string(),
             ],
+            url: None,
             env: HashMap::new(),
             enabled: true,
         };
@@ -277,6 +336,7 @@ mod tests {
                 "@modelcontextprotocol/server-filesystem".to_string(),
                 ".".to_string(),
             ],
+            url: None,
             env: HashMap::new(),
             enabled: true,
         };
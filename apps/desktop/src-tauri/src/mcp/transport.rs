@@ -1,9 +1,13 @@
 // MCP STDIO Transport Implementation
 //
 // Manages child processes for MCP servers and handles newline-delimited JSON-RPC messages
-// over stdin/stdout according to the MCP specification.
+// over stdin/stdout according to the MCP specification. Remote servers go through
+// `http_transport` behind the same `McpTransport` interface.
 
-use super::protocol::{JsonRpcRequest, JsonRpcResponse, McpMessage, RequestId};
+use super::http_transport::{self, StreamableHttpTransport};
+use super::protocol::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, McpMessage, RequestId,
+};
 use crate::mcp::{McpError, McpResult};
 use parking_lot::Mutex;
 use std::collections::HashMap;
@@ -14,6 +18,61 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::{mpsc, oneshot};
 
+/// How a session reaches its server: a local process or a remote endpoint
+pub enum McpTransport {
+    Stdio(StdioTransport),
+    Http(StreamableHttpTransport),
+}
+
+impl McpTransport {
+    /// Protocol revision to ask for when initializing
+    pub fn protocol_version(&self) -> &'static str {
+        match self {
+            Self::Stdio(_) => "2024-11-05",
+            Self::Http(_) => http_transport::PROTOCOL_VERSION,
+        }
+    }
+
+    pub async fn send_request(
+        &self,
+        method: String,
+        params: Option<serde_json::Value>,
+    ) -> McpResult<JsonRpcResponse> {
+        match self {
+            Self::Stdio(transport) => transport.send_request(method, params).await,
+            Self::Http(transport) => transport.send_request(method, params).await,
+        }
+    }
+
+    pub async fn send_notification(&self, method: String, params: Option<serde_json::Value>) {
+        match self {
+            Self::Stdio(transport) => transport.send_notification(method, params),
+            Self::Http(transport) => transport.send_notification(method, params).await,
+        }
+    }
+
+    pub fn notifications(&self) -> mpsc::UnboundedReceiver<JsonRpcNotification> {
+        match self {
+            Self::Stdio(transport) => transport.notifications(),
+            Self::Http(transport) => transport.notifications(),
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        match self {
+            Self::Stdio(transport) => transport.is_alive(),
+            Self::Http(transport) => transport.is_alive(),
+        }
+    }
+
+    pub async fn shutdown(&self) -> McpResult<()> {
+        match self {
+            Self::Stdio(transport) => transport.shutdown().await,
+            Self::Http(transport) => transport.shutdown().await,
+        }
+    }
+}
+
 /// STDIO transport for MCP servers
 pub struct StdioTransport {
     /// Child process
@@ -24,6 +83,8 @@ pub struct StdioTransport {
     pending: Arc<Mutex<HashMap<RequestId, oneshot::Sender<McpResult<JsonRpcResponse>>>>>,
     /// Channel for sending messages to the server
     tx: mpsc::UnboundedSender<JsonRpcRequest>,
+    /// Where notifications from the server go, once someone listens
+    notifications: Arc<Mutex<Option<mpsc::UnboundedSender<JsonRpcNotification>>>>,
     /// Shutdown signal
     shutdown_tx: Mutex<Option<oneshot::Sender<()>>>,
 }
@@ -112,6 +173,9 @@ impl StdioTransport {
 
         // Spawn task to read responses from stdout
         let pending_read = pending.clone();
+        let notifications: Arc<Mutex<Option<mpsc::UnboundedSender<JsonRpcNotification>>>> =
+            Arc::new(Mutex::new(None));
+        let notifications_read = notifications.clone();
         tokio::spawn(async move {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
@@ -148,6 +212,9 @@ impl StdioTransport {
                     }
                     Ok(McpMessage::Notification(notif)) => {
                         tracing::info!("[MCP Transport] Received notification: {}", notif.method);
+                        if let Some(sender) = notifications_read.lock().as_ref() {
+                            let _ = sender.send(notif);
+                        }
                     }
                     Ok(McpMessage::Request(_)) => {
                         tracing::warn!("[MCP Transport] Received request from server (not supported in client mode)");
@@ -159,6 +226,13 @@ impl StdioTransport {
             }
 
             tracing::info!("[MCP Transport] stdout reader finished");
+
+            // Fail requests still waiting instead of letting them time out
+            for (_, sender) in pending_read.lock().drain() {
+                let _ = sender.send(Err(McpError::ConnectionError(
+                    "Server process exited".to_string(),
+                )));
+            }
         });
 
         // Spawn task to log stderr
@@ -176,10 +250,18 @@ impl StdioTransport {
             request_id: Arc::new(AtomicU64::new(1)),
             pending,
             tx,
+            notifications,
             shutdown_tx: Mutex::new(Some(shutdown_tx)),
         })
     }
 
+    /// Receive the server's notifications; replaces an earlier receiver
+    pub fn notifications(&self) -> mpsc::UnboundedReceiver<JsonRpcNotification> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.notifications.lock() = Some(sender);
+        receiver
+    }
+
     /// Send a request and wait for response
     pub async fn send_request(
         &self,
@@ -241,8 +323,11 @@ impl StdioTransport {
 
     /// Check if the process is still alive
     pub fn is_alive(&self) -> bool {
-        let child = self.child.lock();
-        child.is_some()
+        let mut child = self.child.lock();
+        match child.as_mut() {
+            Some(c) => matches!(c.try_wait(), Ok(None)),
+            None => false,
+        }
     }
 
     /// Shutdown the transport
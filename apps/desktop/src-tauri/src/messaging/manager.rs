@@ -0,0 +1,1071 @@
+//! Provider-agnostic send-and-poll API over the connected messaging platforms.
+//!
+//! Every platform speaks the same three verbs: send a message (optionally in
+//! a thread), list recent conversations, and poll for new inbound messages.
+//! Inbound messages are recorded in `messaging_history`, which is also where
+//! the Slack listener and WhatsApp webhooks deliver theirs, so polling is a
+//! cursor over that table after an optional sync with the remote API. WhatsApp
+//! Business API has no read endpoint, so its messages only arrive through
+//! [`MessagingManager::record_whatsapp_webhook`].
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+use super::whatsapp::WhatsAppWebhook;
+use super::{
+    MessagingPlatform, SlackClient, SlackConfig, TeamsClient, TeamsConfig, UnifiedMessage,
+    WhatsAppClient,
+};
+use crate::commands::AppDatabase;
+use crate::security::SecretManager;
+
+/// Emitted with a [`UnifiedMessage`] for every newly recorded inbound message
+pub const MESSAGE_EVENT: &str = "messaging://message";
+
+pub const MESSAGING_SEND_TOOL: &str = "messaging_send";
+pub const MESSAGING_LIST_THREADS_TOOL: &str = "messaging_list_threads";
+pub const MESSAGING_POLL_TOOL: &str = "messaging_poll";
+
+const DEFAULT_POLL_LIMIT: usize = 50;
+const DEFAULT_THREAD_LIMIT: usize = 20;
+/// History rows scanned when grouping conversations into threads
+const THREAD_SCAN_ROWS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagingSendRequest {
+    /// Connection (account) to send from
+    pub connection_id: String,
+    /// Slack channel ID, Teams `team_id/channel_id`, or WhatsApp phone number
+    pub channel_id: String,
+    pub text: String,
+    /// Slack `thread_ts` or Teams root message ID; ignored by WhatsApp
+    #[serde(default)]
+    pub thread_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagingSendResult {
+    pub connection_id: String,
+    pub platform: MessagingPlatform,
+    pub channel_id: String,
+    pub message_id: String,
+    /// Pass back as `thread_id` to continue the conversation in a thread
+    pub thread_id: Option<String>,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessagingPollRequest {
+    pub connection_id: String,
+    /// Required to fetch new Slack or Teams messages from the remote API;
+    /// without it only already-recorded messages are returned
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    /// `cursor` from the previous poll; omit to get the latest messages
+    #[serde(default)]
+    pub cursor: Option<i64>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagingPollResult {
+    pub connection_id: String,
+    pub platform: MessagingPlatform,
+    /// Inbound messages after the cursor, oldest first
+    pub messages: Vec<UnifiedMessage>,
+    pub cursor: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagingThreadSummary {
+    pub connection_id: String,
+    pub platform: MessagingPlatform,
+    pub channel_id: String,
+    pub thread_id: Option<String>,
+    pub last_message: String,
+    pub last_sender: Option<String>,
+    pub last_direction: String,
+    pub last_message_at: i64,
+    pub message_count: usize,
+    /// The latest message came from someone else
+    pub awaiting_reply: bool,
+}
+
+/// An active `messaging_connections` row
+#[derive(Debug, Clone)]
+struct ConnectionRow {
+    id: String,
+    platform: MessagingPlatform,
+    credentials: String,
+}
+
+/// An inbound message about to be recorded in `messaging_history`
+#[derive(Debug, Clone, PartialEq)]
+struct InboundMessage {
+    message_id: String,
+    channel_id: String,
+    sender_id: Option<String>,
+    sender_name: Option<String>,
+    text: String,
+    timestamp: i64,
+    thread_id: Option<String>,
+}
+
+/// A `messaging_history` row as read for thread summaries, newest first
+#[derive(Debug, Clone)]
+struct HistoryRow {
+    connection_id: String,
+    platform: MessagingPlatform,
+    channel_id: String,
+    direction: String,
+    sender: Option<String>,
+    content: String,
+    timestamp: i64,
+    thread_id: Option<String>,
+}
+
+/// Sends and polls messages across Slack, Teams and WhatsApp connections
+pub struct MessagingManager {
+    secrets: Arc<SecretManager>,
+    /// Teams clients are kept per connection so their Graph token is reused
+    teams: Mutex<HashMap<String, Arc<tokio::sync::Mutex<TeamsClient>>>>,
+}
+
+impl MessagingManager {
+    pub fn new(secrets: Arc<SecretManager>) -> Self {
+        Self {
+            secrets,
+            teams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Store a connection's credentials in the secret store and return the
+    /// value to keep in its `credentials` column
+    pub fn store_credentials(
+        &self,
+        platform: &MessagingPlatform,
+        key: &str,
+        credentials: &Value,
+    ) -> Result<String> {
+        let name = format!("messaging.{}.{}", platform.as_str(), key);
+        self.secrets
+            .store_secret(&name, &credentials.to_string())
+            .map_err(|e| anyhow!("Failed to store {} credentials: {}", platform.as_str(), e))?;
+        Ok(json!({ "secret": name }).to_string())
+    }
+
+    /// Resolve a `credentials` column, which either references the secret
+    /// store or, for older connections, holds the credentials inline
+    fn resolve_credentials(&self, credentials: &str) -> Result<Value> {
+        let value: Value =
+            serde_json::from_str(credentials).context("Invalid messaging credentials")?;
+        match value.get("secret").and_then(Value::as_str) {
+            Some(name) => {
+                let raw = self.secrets.get_secret(name).map_err(|e| {
+                    anyhow!(
+                        "Messaging credentials are missing from the secret store: {}",
+                        e
+                    )
+                })?;
+                Ok(serde_json::from_str(&raw)?)
+            }
+            None => Ok(value),
+        }
+    }
+
+    /// Drop cached clients for a disconnected connection
+    pub fn forget(&self, connection_id: &str) {
+        self.teams.lock().remove(connection_id);
+    }
+
+    /// Bring connections saved by older builds in line with the per-account
+    /// layout: inline credentials move into the secret store, and WhatsApp
+    /// connections get their phone number ID as `workspace_id` so each number
+    /// is one account. Returns how many connections were updated
+    pub fn migrate_connections(&self, conn: &Connection) -> Result<usize> {
+        let mut stmt = conn
+            .prepare("SELECT id, platform, workspace_id, credentials FROM messaging_connections")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut migrated = 0;
+        for (id, platform, workspace_id, credentials) in rows {
+            let Some(platform) = MessagingPlatform::from_str(&platform) else {
+                continue;
+            };
+            let resolved = match self.resolve_credentials(&credentials) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    warn!("Skipping messaging connection {}: {}", id, e);
+                    continue;
+                }
+            };
+
+            let new_workspace_id = match (&platform, &workspace_id) {
+                (MessagingPlatform::WhatsApp, None) => resolved
+                    .get("phone_number_id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                _ => workspace_id.clone(),
+            };
+            let new_credentials = if is_inline(&credentials) {
+                let key = match &platform {
+                    MessagingPlatform::Slack => new_workspace_id.as_deref().unwrap_or(&id),
+                    _ => &id,
+                };
+                self.store_credentials(&platform, key, &resolved)?
+            } else {
+                credentials.clone()
+            };
+
+            if new_workspace_id != workspace_id || new_credentials != credentials {
+                conn.execute(
+                    "UPDATE messaging_connections SET workspace_id = ?1, credentials = ?2
+                     WHERE id = ?3",
+                    params![new_workspace_id, new_credentials, id],
+                )?;
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+
+    /// Confirm a connection's credentials still work: Slack and WhatsApp
+    /// tokens are used for a read-only call, Teams re-authenticates
+    pub async fn check(&self, db: &AppDatabase, connection_id: &str) -> Result<()> {
+        let connection = find_connection(&*lock(db)?, connection_id)?;
+        match connection.platform {
+            MessagingPlatform::Slack => {
+                self.slack_client(&connection)?
+                    .auth_test()
+                    .await
+                    .map_err(|e| anyhow!("Slack rejected the token: {}", e))?;
+            }
+            MessagingPlatform::Teams => {
+                let client = self.teams_client(&connection)?;
+                let mut client = client.lock().await;
+                client
+                    .authenticate()
+                    .await
+                    .map_err(|e| anyhow!("Teams authentication failed: {}", e))?;
+            }
+            MessagingPlatform::WhatsApp => {
+                self.whatsapp_client(&connection)?
+                    .verify()
+                    .await
+                    .map_err(|e| anyhow!("WhatsApp rejected the token: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn slack_client(&self, connection: &ConnectionRow) -> Result<SlackClient> {
+        let config: SlackConfig =
+            serde_json::from_value(self.resolve_credentials(&connection.credentials)?)?;
+        SlackClient::new(config).map_err(|e| anyhow!("Failed to create Slack client: {}", e))
+    }
+
+    fn whatsapp_client(&self, connection: &ConnectionRow) -> Result<WhatsAppClient> {
+        let credentials = self.resolve_credentials(&connection.credentials)?;
+        let field = |name: &str| {
+            credentials
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("WhatsApp credentials are missing {}", name))
+        };
+        WhatsAppClient::new(field("phone_number_id")?, field("access_token")?)
+            .map_err(|e| anyhow!("Failed to create WhatsApp client: {}", e))
+    }
+
+    fn teams_client(
+        &self,
+        connection: &ConnectionRow,
+    ) -> Result<Arc<tokio::sync::Mutex<TeamsClient>>> {
+        if let Some(client) = self.teams.lock().get(&connection.id) {
+            return Ok(Arc::clone(client));
+        }
+        let config: TeamsConfig =
+            serde_json::from_value(self.resolve_credentials(&connection.credentials)?)?;
+        let client = TeamsClient::new(config)
+            .map_err(|e| anyhow!("Failed to create Teams client: {}", e))?;
+        let client = Arc::new(tokio::sync::Mutex::new(client));
+        self.teams
+            .lock()
+            .insert(connection.id.clone(), Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Send a message, replying in a thread when `thread_id` is set
+    pub async fn send(
+        &self,
+        db: &AppDatabase,
+        request: MessagingSendRequest,
+    ) -> Result<MessagingSendResult> {
+        if request.text.trim().is_empty() {
+            bail!("Message text is empty");
+        }
+        let connection = find_connection(&*lock(db)?, &request.connection_id)?;
+        let channel_id = request.channel_id.as_str();
+        let thread_id = request.thread_id.filter(|id| !id.is_empty());
+
+        let (message_id, thread_id) = match connection.platform {
+            MessagingPlatform::Slack => {
+                let client = self.slack_client(&connection)?;
+                let message = match thread_id.as_deref() {
+                    Some(thread_ts) => {
+                        client
+                            .reply_in_thread(channel_id, thread_ts, &request.text)
+                            .await
+                    }
+                    None => client.send_message(channel_id, &request.text).await,
+                }
+                .map_err(|e| anyhow!("Failed to send Slack message: {}", e))?;
+                let thread_id = thread_id.or(Some(message.ts.clone()));
+                (message.ts, thread_id)
+            }
+            MessagingPlatform::Teams => {
+                let client = self.teams_client(&connection)?;
+                let mut client = client.lock().await;
+                let message = match thread_id.as_deref() {
+                    Some(root_id) => {
+                        client
+                            .reply_to_message(channel_id, root_id, &request.text)
+                            .await
+                    }
+                    None => client.send_message(channel_id, &request.text).await,
+                }
+                .map_err(|e| anyhow!("Failed to send Teams message: {}", e))?;
+                let thread_id = thread_id.or(Some(message.id.clone()));
+                (message.id, thread_id)
+            }
+            MessagingPlatform::WhatsApp => {
+                let client = self.whatsapp_client(&connection)?;
+                let message_id = client
+                    .send_text(channel_id, &request.text)
+                    .await
+                    .map_err(|e| anyhow!("Failed to send WhatsApp message: {}", e))?;
+                (message_id, None)
+            }
+        };
+
+        let timestamp = Utc::now().timestamp();
+        {
+            let conn = lock(db)?;
+            conn.execute(
+                "INSERT INTO messaging_history
+                (id, connection_id, channel_id, message_id, direction, content, timestamp, metadata)
+                VALUES (?1, ?2, ?3, ?4, 'outbound', ?5, ?6, ?7)",
+                params![
+                    uuid::Uuid::new_v4().to_string(),
+                    connection.id,
+                    channel_id,
+                    message_id,
+                    request.text,
+                    timestamp,
+                    json!({ "thread_id": thread_id }).to_string(),
+                ],
+            )?;
+            conn.execute(
+                "UPDATE messaging_connections SET last_used_at = ?1 WHERE id = ?2",
+                params![timestamp, connection.id],
+            )?;
+        }
+
+        Ok(MessagingSendResult {
+            connection_id: connection.id,
+            platform: connection.platform,
+            channel_id: request.channel_id,
+            message_id,
+            thread_id,
+            timestamp,
+        })
+    }
+
+    /// Recent conversations, newest first, grouped by channel and thread
+    pub fn list_threads(
+        &self,
+        db: &AppDatabase,
+        connection_id: Option<&str>,
+        platform: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<MessagingThreadSummary>> {
+        let platform = parse_platform(platform)?;
+        let conn = lock(db)?;
+        let mut stmt = conn.prepare(
+            "SELECT h.connection_id, c.platform, h.channel_id, h.direction,
+                    COALESCE(h.sender_name, h.sender_id), h.content, h.timestamp, h.metadata
+             FROM messaging_history h
+             JOIN messaging_connections c ON c.id = h.connection_id
+             WHERE (?1 IS NULL OR h.connection_id = ?1)
+               AND (?2 IS NULL OR c.platform = ?2)
+             ORDER BY h.timestamp DESC, h.rowid DESC
+             LIMIT ?3",
+        )?;
+        let rows = stmt
+            .query_map(
+                params![
+                    connection_id,
+                    platform.as_ref().map(MessagingPlatform::as_str),
+                    THREAD_SCAN_ROWS as i64
+                ],
+                |row| {
+                    let platform: String = row.get(1)?;
+                    let metadata: Option<String> = row.get(7)?;
+                    Ok(HistoryRow {
+                        connection_id: row.get(0)?,
+                        platform: MessagingPlatform::from_str(&platform)
+                            .unwrap_or(MessagingPlatform::Slack),
+                        channel_id: row.get(2)?,
+                        direction: row.get(3)?,
+                        sender: row.get(4)?,
+                        content: row.get(5)?,
+                        timestamp: row.get(6)?,
+                        thread_id: thread_id_from_metadata(metadata.as_deref()),
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(summarize_threads(
+            rows,
+            limit.unwrap_or(DEFAULT_THREAD_LIMIT),
+        ))
+    }
+
+    /// Inbound messages after `cursor`. When a Slack or Teams channel is
+    /// given, new messages are first fetched from the platform and recorded
+    pub async fn poll(
+        &self,
+        app: &AppHandle,
+        request: MessagingPollRequest,
+    ) -> Result<MessagingPollResult> {
+        let db = app.state::<AppDatabase>();
+        let connection = find_connection(&*lock(&db)?, &request.connection_id)?;
+        let limit = request.limit.unwrap_or(DEFAULT_POLL_LIMIT).max(1);
+        let channel_id = request.channel_id.as_deref().filter(|id| !id.is_empty());
+        let thread_id = request.thread_id.as_deref().filter(|id| !id.is_empty());
+
+        if let Some(channel_id) = channel_id {
+            let fetched = self
+                .fetch_remote(&connection, channel_id, thread_id, limit)
+                .await?;
+            record_inbound(app, &connection, fetched)?;
+        }
+
+        let conn = lock(&db)?;
+        let order = if request.cursor.is_some() {
+            "ASC"
+        } else {
+            "DESC"
+        };
+        let sql = format!(
+            "SELECT rowid, id, message_id, channel_id, sender_id, sender_name, content, timestamp, metadata
+             FROM messaging_history
+             WHERE connection_id = ?1 AND direction = 'inbound' AND rowid > ?2
+               AND (?3 IS NULL OR channel_id = ?3)
+               AND (?4 IS NULL OR message_id = ?4
+                    OR CASE WHEN json_valid(metadata) THEN
+                        COALESCE(json_extract(metadata, '$.thread_id'),
+                                 json_extract(metadata, '$.thread_ts')) END = ?4)
+             ORDER BY rowid {}
+             LIMIT ?5",
+            order
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt
+            .query_map(
+                params![
+                    connection.id,
+                    request.cursor.unwrap_or(0),
+                    channel_id,
+                    thread_id,
+                    limit as i64
+                ],
+                |row| {
+                    let history_id: String = row.get(1)?;
+                    let message_id: Option<String> = row.get(2)?;
+                    let metadata: Option<String> = row.get(8)?;
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        to_unified(
+                            &connection,
+                            message_id.unwrap_or_else(|| history_id.clone()),
+                            row.get(3)?,
+                            row.get(4)?,
+                            row.get(5)?,
+                            row.get(6)?,
+                            row.get(7)?,
+                            thread_id_from_metadata(metadata.as_deref()),
+                        ),
+                    ))
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if request.cursor.is_none() {
+            rows.reverse();
+        }
+
+        let cursor = match (rows.last(), request.cursor) {
+            (Some((rowid, _)), _) => *rowid,
+            (None, Some(cursor)) => cursor,
+            (None, None) => conn.query_row(
+                "SELECT COALESCE(MAX(rowid), 0) FROM messaging_history",
+                [],
+                |row| row.get(0),
+            )?,
+        };
+
+        Ok(MessagingPollResult {
+            connection_id: connection.id.clone(),
+            platform: connection.platform.clone(),
+            messages: rows.into_iter().map(|(_, message)| message).collect(),
+            cursor,
+        })
+    }
+
+    async fn fetch_remote(
+        &self,
+        connection: &ConnectionRow,
+        channel_id: &str,
+        thread_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<InboundMessage>> {
+        match connection.platform {
+            MessagingPlatform::Slack => {
+                let client = self.slack_client(connection)?;
+                let messages = match thread_id {
+                    Some(thread_ts) => {
+                        client
+                            .get_thread_replies(channel_id, thread_ts, limit)
+                            .await
+                    }
+                    None => client.get_conversation_history(channel_id, limit).await,
+                }
+                .map_err(|e| anyhow!("Failed to read Slack messages: {}", e))?;
+
+                // Messages without a user are bot or system messages
+                Ok(messages
+                    .into_iter()
+                    .filter(|message| message.user.is_some())
+                    .map(|message| InboundMessage {
+                        timestamp: message.ts.parse::<f64>().unwrap_or(0.0) as i64,
+                        thread_id: message
+                            .thread_ts
+                            .filter(|thread_ts| *thread_ts != message.ts),
+                        message_id: message.ts,
+                        channel_id: channel_id.to_string(),
+                        sender_id: message.user,
+                        sender_name: None,
+                        text: message.text,
+                    })
+                    .collect())
+            }
+            MessagingPlatform::Teams => {
+                let client = self.teams_client(connection)?;
+                let mut client = client.lock().await;
+                let messages = match thread_id {
+                    Some(root_id) => client.get_message_replies(channel_id, root_id, limit).await,
+                    None => client.get_channel_messages(channel_id, limit).await,
+                }
+                .map_err(|e| anyhow!("Failed to read Teams messages: {}", e))?;
+
+                Ok(messages
+                    .into_iter()
+                    .map(|message| InboundMessage {
+                        thread_id: message
+                            .reply_to_id
+                            .or_else(|| thread_id.map(str::to_string)),
+                        message_id: message.id,
+                        channel_id: channel_id.to_string(),
+                        sender_id: Some(message.from_user_id).filter(|id| !id.is_empty()),
+                        sender_name: message.from_user_name,
+                        text: message.body,
+                        timestamp: message.created_at,
+                    })
+                    .collect())
+            }
+            // Business API has no read endpoint; messages arrive by webhook
+            MessagingPlatform::WhatsApp => Ok(Vec::new()),
+        }
+    }
+
+    /// Record the messages in a WhatsApp Business webhook payload against
+    /// the connection for their phone number; returns how many were new
+    pub fn record_whatsapp_webhook(
+        &self,
+        app: &AppHandle,
+        payload: &WhatsAppWebhook,
+    ) -> Result<usize> {
+        let connections = {
+            let db = app.state::<AppDatabase>();
+            let conn = lock(&db)?;
+            active_connections(&conn, &MessagingPlatform::WhatsApp)?
+        };
+        let mut by_phone_number = HashMap::new();
+        for connection in connections {
+            match self.resolve_credentials(&connection.credentials) {
+                Ok(credentials) => {
+                    if let Some(phone_number_id) =
+                        credentials.get("phone_number_id").and_then(Value::as_str)
+                    {
+                        by_phone_number.insert(phone_number_id.to_string(), connection);
+                    }
+                }
+                Err(e) => warn!("Skipping WhatsApp connection {}: {}", connection.id, e),
+            }
+        }
+
+        let mut grouped: HashMap<String, Vec<InboundMessage>> = HashMap::new();
+        for (phone_number_id, message) in whatsapp_inbound(payload) {
+            grouped.entry(phone_number_id).or_default().push(message);
+        }
+
+        let mut recorded = 0;
+        for (phone_number_id, messages) in grouped {
+            match by_phone_number.get(&phone_number_id) {
+                Some(connection) => recorded += record_inbound(app, connection, messages)?,
+                None => warn!(
+                    "Ignoring WhatsApp webhook for unknown phone number {}",
+                    phone_number_id
+                ),
+            }
+        }
+        Ok(recorded)
+    }
+}
+
+/// Whether a `credentials` column holds the credentials rather than a
+/// secret store reference
+fn is_inline(credentials: &str) -> bool {
+    serde_json::from_str::<Value>(credentials)
+        .map(|value| value.get("secret").is_none())
+        .unwrap_or(false)
+}
+
+fn lock(db: &AppDatabase) -> Result<std::sync::MutexGuard<'_, Connection>> {
+    db.conn
+        .lock()
+        .map_err(|e| anyhow!("Database lock error: {}", e))
+}
+
+fn parse_platform(platform: Option<&str>) -> Result<Option<MessagingPlatform>> {
+    platform
+        .filter(|platform| !platform.is_empty())
+        .map(|platform| {
+            MessagingPlatform::from_str(platform)
+                .ok_or_else(|| anyhow!("Unknown messaging platform: {}", platform))
+        })
+        .transpose()
+}
+
+fn active_connections(
+    conn: &Connection,
+    platform: &MessagingPlatform,
+) -> Result<Vec<ConnectionRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, credentials FROM messaging_connections
+         WHERE platform = ?1 AND is_active = 1
+         ORDER BY created_at DESC",
+    )?;
+    let rows = stmt
+        .query_map(params![platform.as_str()], |row| {
+            Ok(ConnectionRow {
+                id: row.get(0)?,
+                platform: platform.clone(),
+                credentials: row.get(1)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+/// The active connection `connection_id`
+fn find_connection(conn: &Connection, connection_id: &str) -> Result<ConnectionRow> {
+    if connection_id.is_empty() {
+        bail!("connection_id is required");
+    }
+    let (platform_name, credentials): (String, String) = conn
+        .query_row(
+            "SELECT platform, credentials FROM messaging_connections
+             WHERE id = ?1 AND is_active = 1",
+            params![connection_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| anyhow!("No active messaging connection {}", connection_id))?;
+    let platform = MessagingPlatform::from_str(&platform_name)
+        .ok_or_else(|| anyhow!("Unknown messaging platform: {}", platform_name))?;
+    Ok(ConnectionRow {
+        id: connection_id.to_string(),
+        platform,
+        credentials,
+    })
+}
+
+/// Thread key written by this module (`thread_id`) or the Slack listener
+/// (`thread_ts`)
+fn thread_id_from_metadata(metadata: Option<&str>) -> Option<String> {
+    let metadata: Value = serde_json::from_str(metadata?).ok()?;
+    ["thread_id", "thread_ts"].iter().find_map(|key| {
+        metadata
+            .get(*key)
+            .and_then(Value::as_str)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn to_unified(
+    connection: &ConnectionRow,
+    id: String,
+    channel_id: String,
+    sender_id: Option<String>,
+    sender_name: Option<String>,
+    text: String,
+    timestamp: i64,
+    thread_id: Option<String>,
+) -> UnifiedMessage {
+    let mut metadata = HashMap::from([("connection_id".to_string(), connection.id.clone())]);
+    if let Some(thread_id) = thread_id {
+        metadata.insert("thread_id".to_string(), thread_id);
+    }
+    UnifiedMessage {
+        id,
+        platform: connection.platform.clone(),
+        channel_id,
+        sender_id: sender_id.unwrap_or_default(),
+        sender_name,
+        text,
+        timestamp,
+        attachments: vec![],
+        metadata,
+    }
+}
+
+/// Insert messages not yet in `messaging_history` and announce them;
+/// returns how many were new
+fn record_inbound(
+    app: &AppHandle,
+    connection: &ConnectionRow,
+    messages: Vec<InboundMessage>,
+) -> Result<usize> {
+    if messages.is_empty() {
+        return Ok(0);
+    }
+    let db = app.state::<AppDatabase>();
+    let mut recorded = Vec::new();
+    {
+        let conn = lock(&db)?;
+        let mut seen = HashSet::new();
+        for message in messages {
+            if !seen.insert(message.message_id.clone()) {
+                continue;
+            }
+            let inserted = conn.execute(
+                "INSERT INTO messaging_history
+                (id, connection_id, channel_id, message_id, direction, sender_id, sender_name,
+                 content, timestamp, metadata)
+                SELECT ?1, ?2, ?3, ?4, 'inbound', ?5, ?6, ?7, ?8, ?9
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM messaging_history WHERE connection_id = ?2 AND message_id = ?4
+                )",
+                params![
+                    uuid::Uuid::new_v4().to_string(),
+                    connection.id,
+                    message.channel_id,
+                    message.message_id,
+                    message.sender_id,
+                    message.sender_name,
+                    message.text,
+                    message.timestamp,
+                    json!({ "thread_id": message.thread_id }).to_string(),
+                ],
+            )?;
+            if inserted > 0 {
+                recorded.push(message);
+            }
+        }
+    }
+
+    let count = recorded.len();
+    for message in recorded {
+        let unified = to_unified(
+            connection,
+            message.message_id,
+            message.channel_id,
+            message.sender_id,
+            message.sender_name,
+            message.text,
+            message.timestamp,
+            message.thread_id,
+        );
+        if let Err(e) = app.emit(MESSAGE_EVENT, &unified) {
+            warn!("Failed to emit messaging event: {}", e);
+        }
+    }
+    Ok(count)
+}
+
+/// `(phone_number_id, message)` for each message in a webhook payload
+fn whatsapp_inbound(payload: &WhatsAppWebhook) -> Vec<(String, InboundMessage)> {
+    let mut inbound = Vec::new();
+    for change in payload.entry.iter().flat_map(|entry| &entry.changes) {
+        let Some(value) = &change.value else {
+            continue;
+        };
+        let Some(phone_number_id) = value
+            .metadata
+            .as_ref()
+            .map(|metadata| metadata.phone_number_id.clone())
+        else {
+            continue;
+        };
+        let names: HashMap<&str, &str> = value
+            .contacts
+            .iter()
+            .flatten()
+            .map(|contact| (contact.wa_id.as_str(), contact.profile.name.as_str()))
+            .collect();
+
+        for message in value.messages.iter().flatten() {
+            let text = message
+                .text
+                .as_ref()
+                .map(|text| text.body.clone())
+                .or_else(|| {
+                    [&message.image, &message.video, &message.audio]
+                        .into_iter()
+                        .flatten()
+                        .find_map(|media| media.caption.clone())
+                })
+                .or_else(|| {
+                    message.document.as_ref().map(|document| {
+                        document
+                            .caption
+                            .clone()
+                            .unwrap_or(document.filename.clone())
+                    })
+                })
+                .unwrap_or_else(|| format!("[{}]", message.message_type));
+            inbound.push((
+                phone_number_id.clone(),
+                InboundMessage {
+                    message_id: message.id.clone(),
+                    channel_id: message.from.clone(),
+                    sender_id: Some(message.from.clone()),
+                    sender_name: names
+                        .get(message.from.as_str())
+                        .map(|name| name.to_string()),
+                    text,
+                    timestamp: message
+                        .timestamp
+                        .parse()
+                        .unwrap_or_else(|_| Utc::now().timestamp()),
+                    thread_id: None,
+                },
+            ));
+        }
+    }
+    inbound
+}
+
+/// Group newest-first history rows into conversations
+fn summarize_threads(rows: Vec<HistoryRow>, limit: usize) -> Vec<MessagingThreadSummary> {
+    let mut index: HashMap<(String, String, Option<String>), usize> = HashMap::new();
+    let mut threads: Vec<MessagingThreadSummary> = Vec::new();
+    for row in rows {
+        let key = (
+            row.connection_id.clone(),
+            row.channel_id.clone(),
+            row.thread_id.clone(),
+        );
+        if let Some(&position) = index.get(&key) {
+            threads[position].message_count += 1;
+            continue;
+        }
+        index.insert(key, threads.len());
+        threads.push(MessagingThreadSummary {
+            awaiting_reply: row.direction == "inbound",
+            connection_id: row.connection_id,
+            platform: row.platform,
+            channel_id: row.channel_id,
+            thread_id: row.thread_id,
+            last_message: row.content,
+            last_sender: row.sender,
+            last_direction: row.direction,
+            last_message_at: row.timestamp,
+            message_count: 1,
+        });
+    }
+    threads.truncate(limit);
+    threads
+}
+
+/// Run one of the cross-platform messaging agent tools
+pub async fn execute_tool(
+    app: &AppHandle,
+    tool_id: &str,
+    args: &HashMap<String, Value>,
+) -> Result<Value> {
+    let manager = app
+        .try_state::<MessagingManager>()
+        .ok_or_else(|| anyhow!("Messaging is not initialized"))?;
+    let args = Value::Object(args.clone().into_iter().collect());
+
+    match tool_id {
+        MESSAGING_SEND_TOOL => {
+            let request: MessagingSendRequest =
+                serde_json::from_value(args).context("Invalid messaging_send parameters")?;
+            let db = app.state::<AppDatabase>();
+            Ok(serde_json::to_value(manager.send(&db, request).await?)?)
+        }
+        MESSAGING_LIST_THREADS_TOOL => {
+            let text = |name: &str| args.get(name).and_then(Value::as_str);
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map(|limit| limit as usize);
+            let db = app.state::<AppDatabase>();
+            let threads =
+                manager.list_threads(&db, text("connection_id"), text("platform"), limit)?;
+            Ok(json!({ "threads": threads }))
+        }
+        MESSAGING_POLL_TOOL => {
+            let request: MessagingPollRequest =
+                serde_json::from_value(args).context("Invalid messaging_poll parameters")?;
+            Ok(serde_json::to_value(manager.poll(app, request).await?)?)
+        }
+        other => bail!("Unknown messaging tool: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(channel: &str, thread: Option<&str>, direction: &str, at: i64) -> HistoryRow {
+        HistoryRow {
+            connection_id: "c1".to_string(),
+            platform: MessagingPlatform::Teams,
+            channel_id: channel.to_string(),
+            direction: direction.to_string(),
+            sender: None,
+            content: format!("message {}", at),
+            timestamp: at,
+            thread_id: thread.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn groups_history_into_threads() {
+        let rows = vec![
+            row("team/general", Some("root"), "inbound", 40),
+            row("team/general", None, "outbound", 30),
+            row("team/general", Some("root"), "outbound", 20),
+            row("team/random", None, "inbound", 10),
+        ];
+
+        let threads = summarize_threads(rows.clone(), 10);
+        assert_eq!(threads.len(), 3);
+        assert_eq!(threads[0].thread_id.as_deref(), Some("root"));
+        assert_eq!(threads[0].message_count, 2);
+        assert_eq!(threads[0].last_message, "message 40");
+        assert!(threads[0].awaiting_reply);
+        assert!(!threads[1].awaiting_reply);
+        assert_eq!(threads[2].channel_id, "team/random");
+
+        assert_eq!(summarize_threads(rows, 1).len(), 1);
+    }
+
+    #[test]
+    fn reads_thread_ids_from_either_metadata_key() {
+        assert_eq!(
+            thread_id_from_metadata(Some(r#"{"thread_id":"root"}"#)).as_deref(),
+            Some("root")
+        );
+        assert_eq!(
+            thread_id_from_metadata(Some(r#"{"kind":"message","thread_ts":"171.5"}"#)).as_deref(),
+            Some("171.5")
+        );
+        assert_eq!(thread_id_from_metadata(Some(r#"{"thread_id":null}"#)), None);
+        assert_eq!(thread_id_from_metadata(Some("not json")), None);
+        assert_eq!(thread_id_from_metadata(None), None);
+    }
+
+    #[test]
+    fn extracts_whatsapp_webhook_messages() {
+        let payload: WhatsAppWebhook = serde_json::from_value(json!({
+            "object": "whatsapp_business_account",
+            "entry": [{
+                "id": "waba",
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "messaging_product": "whatsapp",
+                        "metadata": { "display_phone_number": "15550000000", "phone_number_id": "pn1" },
+                        "contacts": [{ "profile": { "name": "Grace" }, "wa_id": "15551234567" }],
+                        "messages": [
+                            { "from": "15551234567", "id": "wamid.1", "timestamp": "1700000000",
+                              "type": "text", "text": { "body": "Is my order shipped?" } },
+                            { "from": "15551234567", "id": "wamid.2", "timestamp": "1700000005",
+                              "type": "sticker" }
+                        ]
+                    }
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let inbound = whatsapp_inbound(&payload);
+        assert_eq!(inbound.len(), 2);
+        let (phone_number_id, first) = &inbound[0];
+        assert_eq!(phone_number_id, "pn1");
+        assert_eq!(first.channel_id, "15551234567");
+        assert_eq!(first.sender_name.as_deref(), Some("Grace"));
+        assert_eq!(first.text, "Is my order shipped?");
+        assert_eq!(first.timestamp, 1_700_000_000);
+        assert_eq!(inbound[1].1.text, "[sticker]");
+    }
+
+    #[test]
+    fn finds_connections_by_id_only() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO messaging_connections
+             (id, user_id, platform, workspace_id, credentials, is_active, created_at)
+             VALUES ('c1', 'u1', 'teams', 't1', '{\"secret\":\"messaging.teams.c1\"}', 1, 0),
+                    ('c2', 'u1', 'teams', 't2', '{}', 0, 0)",
+            [],
+        )
+        .unwrap();
+
+        let found = find_connection(&conn, "c1").unwrap();
+        assert_eq!(found.platform, MessagingPlatform::Teams);
+        assert!(!is_inline(&found.credentials));
+        assert!(is_inline(r#"{"access_token":"xoxb"}"#));
+        assert!(find_connection(&conn, "c2").is_err());
+        assert!(find_connection(&conn, "").is_err());
+    }
+}
@@ -1,4 +1,6 @@
+pub mod manager;
 pub mod slack;
+pub mod slack_events;
 pub mod teams;
 pub mod types;
 pub mod whatsapp;
@@ -6,6 +8,8 @@ pub mod whatsapp;
 pub use types::*;
 
 // Re-export main clients and configs
+pub use manager::{MessagingManager, MESSAGE_EVENT};
 pub use slack::{SlackClient, SlackConfig};
+pub use slack_events::{SlackEventManager, SlackInboundEvent, SLACK_EVENT};
 pub use teams::{TeamsClient, TeamsConfig};
 pub use whatsapp::WhatsAppClient;
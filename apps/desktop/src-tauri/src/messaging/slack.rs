@@ -64,6 +64,49 @@ impl SlackClient {
             channel: result.channel.unwrap_or_default(),
             text: text.to_string(),
             user: None,
+            thread_ts: None,
+        })
+    }
+
+    /// Reply in the thread started by the message at `thread_ts`
+    pub async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        text: &str,
+    ) -> Result<SlackMessage, Box<dyn std::error::Error>> {
+        let url = "https://slack.com/api/chat.postMessage";
+
+        let payload = json!({
+            "channel": channel,
+            "thread_ts": thread_ts,
+            "text": text,
+        });
+
+        let response = self
+            .client
+            .post(url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.config.bot_token),
+            )
+            .header(header::CONTENT_TYPE, "application/json")
+            .json(&payload)
+            .send()
+            .await?;
+
+        let result: SlackMessageResponse = response.json().await?;
+
+        if !result.ok {
+            return Err(format!("Slack API error: {}", result.error.unwrap_or_default()).into());
+        }
+
+        Ok(SlackMessage {
+            ts: result.ts.unwrap_or_default(),
+            channel: result.channel.unwrap_or_default(),
+            text: text.to_string(),
+            user: None,
+            thread_ts: Some(thread_ts.to_string()),
         })
     }
 
@@ -103,6 +146,7 @@ impl SlackClient {
             channel: result.channel.unwrap_or_default(),
             text: String::new(),
             user: None,
+            thread_ts: None,
         })
     }
 
@@ -133,7 +177,97 @@ impl SlackClient {
             return Err(format!("Slack API error: {}", result.error.unwrap_or_default()).into());
         }
 
-        Ok(result.messages.unwrap_or_default())
+        let mut messages = result.messages.unwrap_or_default();
+        for message in &mut messages {
+            message.channel = channel.to_string();
+        }
+        Ok(messages)
+    }
+
+    /// Get a thread's parent message and its replies, oldest first
+    pub async fn get_thread_replies(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        limit: usize,
+    ) -> Result<Vec<SlackMessage>, Box<dyn std::error::Error>> {
+        let url = format!(
+            "https://slack.com/api/conversations.replies?channel={}&ts={}&limit={}",
+            channel, thread_ts, limit
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.config.bot_token),
+            )
+            .send()
+            .await?;
+
+        let result: SlackHistoryResponse = response.json().await?;
+
+        if !result.ok {
+            return Err(format!("Slack API error: {}", result.error.unwrap_or_default()).into());
+        }
+
+        let mut messages = result.messages.unwrap_or_default();
+        for message in &mut messages {
+            message.channel = channel.to_string();
+        }
+        Ok(messages)
+    }
+
+    /// Identify the workspace and bot user the bot token belongs to
+    pub async fn auth_test(&self) -> Result<SlackAuthInfo, Box<dyn std::error::Error>> {
+        let url = "https://slack.com/api/auth.test";
+
+        let response = self
+            .client
+            .post(url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.config.bot_token),
+            )
+            .send()
+            .await?;
+
+        let result: SlackAuthResponse = response.json().await?;
+
+        if !result.ok {
+            return Err(format!("Slack API error: {}", result.error.unwrap_or_default()).into());
+        }
+
+        Ok(SlackAuthInfo {
+            team_id: result.team_id.unwrap_or_default(),
+            team: result.team,
+            user_id: result.user_id.unwrap_or_default(),
+            bot_id: result.bot_id,
+        })
+    }
+
+    /// Open a Socket Mode connection URL using the app-level token
+    pub async fn open_socket_url(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let url = "https://slack.com/api/apps.connections.open";
+
+        let response = self
+            .client
+            .post(url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.config.app_token),
+            )
+            .send()
+            .await?;
+
+        let result: SlackSocketResponse = response.json().await?;
+
+        if !result.ok {
+            return Err(format!("Slack API error: {}", result.error.unwrap_or_default()).into());
+        }
+
+        Ok(result.url.ok_or("No WebSocket URL returned")?)
     }
 
     /// Handle slash commands (e.g., /agi do something)
@@ -204,26 +338,7 @@ impl SlackClient {
 
     /// Listen to Slack events via WebSocket (Socket Mode)
     pub async fn listen_events(&self) -> Result<SlackEventStream, Box<dyn std::error::Error>> {
-        // Get WebSocket URL
-        let url = "https://slack.com/api/apps.connections.open";
-
-        let response = self
-            .client
-            .post(url)
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", self.config.app_token),
-            )
-            .send()
-            .await?;
-
-        let result: SlackSocketResponse = response.json().await?;
-
-        if !result.ok {
-            return Err(format!("Slack API error: {}", result.error.unwrap_or_default()).into());
-        }
-
-        let ws_url = result.url.ok_or("No WebSocket URL returned")?;
+        let ws_url = self.open_socket_url().await?;
 
         // Connect to WebSocket
         let (ws_stream, _) = connect_async(&ws_url).await?;
@@ -460,6 +575,16 @@ struct SlackSocketResponse {
     error: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct SlackAuthResponse {
+    ok: bool,
+    team_id: Option<String>,
+    team: Option<String>,
+    user_id: Option<String>,
+    bot_id: Option<String>,
+    error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct SlackUserResponse {
     ok: bool,
@@ -478,9 +603,23 @@ struct SlackChannelsResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackMessage {
     pub ts: String,
+    /// Not included by history endpoints; filled in from the request
+    #[serde(default)]
     pub channel: String,
+    #[serde(default)]
     pub text: String,
     pub user: Option<String>,
+    #[serde(default)]
+    pub thread_ts: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackAuthInfo {
+    pub team_id: String,
+    pub team: Option<String>,
+    /// The bot's own user ID, used to skip its messages and spot mentions
+    pub user_id: String,
+    pub bot_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
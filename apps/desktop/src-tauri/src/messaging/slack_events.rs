@@ -0,0 +1,657 @@
+//! Inbound Slack events over Socket Mode.
+//!
+//! Every active Slack connection with an app-level token gets a listener task
+//! holding a Socket Mode WebSocket open. Channel messages, direct messages and
+//! mentions of the bot are acknowledged, stored in `messaging_history` and
+//! emitted to the frontend as [`SLACK_EVENT`]. Agents answer through the
+//! `slack_reply`, `slack_react` and `slack_open_thread` tools.
+//!
+//! Tokens are kept per Slack workspace in the [`SecretManager`]; the
+//! connection row only stores a reference to them.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{info, warn};
+
+use super::slack::{SlackAuthInfo, SlackClient, SlackConfig};
+use crate::commands::AppDatabase;
+use crate::security::SecretManager;
+
+/// Emitted with a [`SlackInboundEvent`] for every message the bot receives
+pub const SLACK_EVENT: &str = "messaging://slack-event";
+
+pub const SLACK_REPLY_TOOL: &str = "slack_reply";
+pub const SLACK_REACT_TOOL: &str = "slack_react";
+pub const SLACK_OPEN_THREAD_TOOL: &str = "slack_open_thread";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// Pause before reconnecting after Slack closes the socket on purpose
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Recent event IDs remembered to drop Slack's redeliveries
+const SEEN_EVENTS: usize = 128;
+const DEFAULT_THREAD_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlackEventKind {
+    /// A message in a channel the bot is a member of
+    Message,
+    /// A message that @-mentions the bot
+    Mention,
+    DirectMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackInboundEvent {
+    pub connection_id: String,
+    pub team_id: Option<String>,
+    pub kind: SlackEventKind,
+    pub channel: String,
+    pub user: Option<String>,
+    pub text: String,
+    pub ts: String,
+    /// Set when the message is a reply in a thread
+    pub thread_ts: Option<String>,
+    pub event_id: Option<String>,
+    pub received_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListenerState {
+    Connecting,
+    Connected,
+    /// Disconnected and waiting to retry
+    Offline,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlackListenerStatus {
+    pub connection_id: String,
+    pub team_id: Option<String>,
+    pub state: ListenerState,
+    pub connected_at: Option<i64>,
+    pub last_event_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SocketEnvelope {
+    #[serde(rename = "type")]
+    kind: String,
+    envelope_id: Option<String>,
+    #[serde(default)]
+    payload: Value,
+    reason: Option<String>,
+}
+
+struct Listener {
+    handle: JoinHandle<()>,
+    status: Arc<Mutex<SlackListenerStatus>>,
+}
+
+/// Owns Slack credentials and the per-connection Socket Mode listeners
+pub struct SlackEventManager {
+    secrets: Arc<SecretManager>,
+    listeners: Mutex<HashMap<String, Listener>>,
+}
+
+impl SlackEventManager {
+    pub fn new(secrets: Arc<SecretManager>) -> Self {
+        Self {
+            secrets,
+            listeners: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Store a workspace's tokens in the secret store and return the value to
+    /// keep in the connection's `credentials` column
+    pub fn store_credentials(&self, team_id: &str, config: &SlackConfig) -> Result<String> {
+        let name = secret_name(team_id);
+        self.secrets
+            .store_secret(&name, &serde_json::to_string(config)?)
+            .map_err(|e| anyhow!("Failed to store Slack tokens: {}", e))?;
+        Ok(json!({ "secret": name }).to_string())
+    }
+
+    /// Resolve a connection's `credentials` column. Connections created
+    /// before tokens moved to the secret store hold them inline
+    pub fn config_from_credentials(&self, credentials: &str) -> Result<SlackConfig> {
+        let value: Value =
+            serde_json::from_str(credentials).context("Invalid Slack credentials")?;
+        match value.get("secret").and_then(Value::as_str) {
+            Some(name) => {
+                let raw = self.secrets.get_secret(name).map_err(|e| {
+                    anyhow!("Slack tokens are missing from the secret store: {}", e)
+                })?;
+                Ok(serde_json::from_str(&raw)?)
+            }
+            None => Ok(serde_json::from_value(value)?),
+        }
+    }
+
+    /// Client for `connection_id`, or for the only active Slack connection
+    pub fn client(
+        &self,
+        app: &AppHandle,
+        connection_id: Option<&str>,
+    ) -> Result<(String, SlackClient)> {
+        let (connection_id, credentials) = {
+            let db = app.state::<AppDatabase>();
+            let conn = db
+                .conn
+                .lock()
+                .map_err(|e| anyhow!("Database lock error: {}", e))?;
+            find_connection(&conn, connection_id)?
+        };
+        let config = self.config_from_credentials(&credentials)?;
+        let client = SlackClient::new(config)
+            .map_err(|e| anyhow!("Failed to create Slack client: {}", e))?;
+        Ok((connection_id, client))
+    }
+
+    /// Start (or restart) the listener for `connection_id`
+    pub fn start(&self, app: &AppHandle, connection_id: &str) -> Result<()> {
+        let (connection_id, client) = self.client(app, Some(connection_id))?;
+        let status = Arc::new(Mutex::new(SlackListenerStatus {
+            connection_id: connection_id.clone(),
+            team_id: None,
+            state: ListenerState::Connecting,
+            connected_at: None,
+            last_event_at: None,
+            last_error: None,
+        }));
+        let handle = tauri::async_runtime::spawn(run_listener(
+            app.clone(),
+            connection_id.clone(),
+            client,
+            Arc::clone(&status),
+        ));
+
+        if let Some(previous) = self
+            .listeners
+            .lock()
+            .insert(connection_id, Listener { handle, status })
+        {
+            previous.handle.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the listener for `connection_id`; returns whether one was running
+    pub fn stop(&self, connection_id: &str) -> bool {
+        match self.listeners.lock().remove(connection_id) {
+            Some(listener) => {
+                listener.handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start listeners for every active Slack connection that has an
+    /// app-level token; returns how many
+    pub fn start_all(&self, app: &AppHandle) -> Result<usize> {
+        let connections = {
+            let db = app.state::<AppDatabase>();
+            let conn = db
+                .conn
+                .lock()
+                .map_err(|e| anyhow!("Database lock error: {}", e))?;
+            let mut stmt = conn.prepare(
+                "SELECT id, credentials FROM messaging_connections
+                 WHERE platform = 'slack' AND is_active = 1",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows
+        };
+
+        let mut started = 0;
+        for (connection_id, credentials) in connections {
+            match self.config_from_credentials(&credentials) {
+                Ok(config) if config.app_token.is_empty() => {}
+                Ok(_) => match self.start(app, &connection_id) {
+                    Ok(()) => started += 1,
+                    Err(e) => warn!("Failed to start Slack listener {}: {}", connection_id, e),
+                },
+                Err(e) => warn!("Skipping Slack connection {}: {}", connection_id, e),
+            }
+        }
+        Ok(started)
+    }
+
+    pub fn statuses(&self) -> Vec<SlackListenerStatus> {
+        let mut statuses: Vec<SlackListenerStatus> = self
+            .listeners
+            .lock()
+            .values()
+            .map(|listener| listener.status.lock().clone())
+            .collect();
+        statuses.sort_by(|a, b| a.connection_id.cmp(&b.connection_id));
+        statuses
+    }
+}
+
+fn secret_name(team_id: &str) -> String {
+    format!("messaging.slack.{}", team_id)
+}
+
+/// `(id, credentials)` of an active Slack connection
+fn find_connection(conn: &Connection, connection_id: Option<&str>) -> Result<(String, String)> {
+    if let Some(connection_id) = connection_id {
+        return conn
+            .query_row(
+                "SELECT id, credentials FROM messaging_connections
+                 WHERE id = ?1 AND platform = 'slack' AND is_active = 1",
+                params![connection_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("No active Slack connection {}", connection_id));
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, credentials FROM messaging_connections
+         WHERE platform = 'slack' AND is_active = 1
+         LIMIT 2",
+    )?;
+    let mut rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<(String, String)>>>()?;
+    match rows.len() {
+        0 => bail!("No Slack workspace is connected"),
+        1 => Ok(rows.remove(0)),
+        _ => bail!("Several Slack workspaces are connected; pass connection_id"),
+    }
+}
+
+async fn run_listener(
+    app: AppHandle,
+    connection_id: String,
+    client: SlackClient,
+    status: Arc<Mutex<SlackListenerStatus>>,
+) {
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        status.lock().state = ListenerState::Connecting;
+
+        match listen(&app, &connection_id, &client, &status, &mut backoff).await {
+            Ok(()) => tokio::time::sleep(RECONNECT_DELAY).await,
+            Err(e) => {
+                warn!(
+                    "Slack listener {} lost its connection: {}; retrying in {:?}",
+                    connection_id, e, backoff
+                );
+                {
+                    let mut status = status.lock();
+                    status.state = ListenerState::Offline;
+                    status.last_error = Some(e.to_string());
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+/// Receive events until Slack closes the socket (`Ok`) or it fails (`Err`)
+async fn listen(
+    app: &AppHandle,
+    connection_id: &str,
+    client: &SlackClient,
+    status: &Mutex<SlackListenerStatus>,
+    backoff: &mut Duration,
+) -> Result<()> {
+    let auth = client
+        .auth_test()
+        .await
+        .map_err(|e| anyhow!("Slack auth failed: {}", e))?;
+    let url = client
+        .open_socket_url()
+        .await
+        .map_err(|e| anyhow!("Failed to open Socket Mode connection: {}", e))?;
+    let (socket, _) = connect_async(url.as_str()).await?;
+    let (mut write, mut read) = socket.split();
+
+    *backoff = INITIAL_BACKOFF;
+    {
+        let mut status = status.lock();
+        status.team_id = Some(auth.team_id.clone());
+        status.state = ListenerState::Connected;
+        status.connected_at = Some(Utc::now().timestamp());
+        status.last_error = None;
+    }
+    info!(
+        "Slack listener {} connected to {}",
+        connection_id, auth.team_id
+    );
+
+    let mut seen: VecDeque<String> = VecDeque::with_capacity(SEEN_EVENTS);
+    while let Some(message) = read.next().await {
+        let text = match message? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Ok(()),
+            _ => continue,
+        };
+        let Ok(envelope) = serde_json::from_str::<SocketEnvelope>(&text) else {
+            continue;
+        };
+
+        // Slack redelivers anything not acknowledged within three seconds
+        if let Some(envelope_id) = &envelope.envelope_id {
+            write
+                .send(Message::Text(
+                    json!({ "envelope_id": envelope_id }).to_string(),
+                ))
+                .await?;
+        }
+
+        match envelope.kind.as_str() {
+            "disconnect" => {
+                info!(
+                    "Slack asked listener {} to reconnect ({})",
+                    connection_id,
+                    envelope.reason.as_deref().unwrap_or("no reason")
+                );
+                return Ok(());
+            }
+            "events_api" => {
+                let Some(event) = inbound_event(connection_id, &auth, &envelope.payload) else {
+                    continue;
+                };
+                if let Some(event_id) = &event.event_id {
+                    if seen.contains(event_id) {
+                        continue;
+                    }
+                    if seen.len() == SEEN_EVENTS {
+                        seen.pop_front();
+                    }
+                    seen.push_back(event_id.clone());
+                }
+
+                status.lock().last_event_at = Some(event.received_at);
+                if let Err(e) = record_inbound(app, &event) {
+                    warn!("Failed to store Slack message: {}", e);
+                }
+                if let Err(e) = app.emit(SLACK_EVENT, &event) {
+                    warn!("Failed to emit Slack event: {}", e);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The message an `events_api` payload carries, if it is one we surface
+fn inbound_event(
+    connection_id: &str,
+    auth: &SlackAuthInfo,
+    payload: &Value,
+) -> Option<SlackInboundEvent> {
+    let event = payload.get("event")?;
+    let text = event
+        .get("text")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let user = event.get("user").and_then(Value::as_str);
+    if user == Some(auth.user_id.as_str()) || event.get("bot_id").is_some() {
+        return None;
+    }
+
+    let kind = match event.get("type").and_then(Value::as_str)? {
+        "app_mention" => SlackEventKind::Mention,
+        "message" => {
+            // Edits, deletions, joins and the like arrive as subtypes
+            if event.get("subtype").is_some() {
+                return None;
+            }
+            // Mentions are delivered again as `app_mention`
+            if !auth.user_id.is_empty() && text.contains(&format!("<@{}>", auth.user_id)) {
+                return None;
+            }
+            match event.get("channel_type").and_then(Value::as_str) {
+                Some("im") => SlackEventKind::DirectMessage,
+                _ => SlackEventKind::Message,
+            }
+        }
+        _ => return None,
+    };
+
+    let ts = event.get("ts").and_then(Value::as_str)?.to_string();
+    Some(SlackInboundEvent {
+        connection_id: connection_id.to_string(),
+        team_id: payload
+            .get("team_id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| Some(auth.team_id.clone()).filter(|t| !t.is_empty())),
+        kind,
+        channel: event.get("channel").and_then(Value::as_str)?.to_string(),
+        user: user.map(str::to_string),
+        text: text.to_string(),
+        thread_ts: event
+            .get("thread_ts")
+            .and_then(Value::as_str)
+            .filter(|thread_ts| *thread_ts != ts)
+            .map(str::to_string),
+        ts,
+        event_id: payload
+            .get("event_id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        received_at: Utc::now().timestamp(),
+    })
+}
+
+fn record_inbound(app: &AppHandle, event: &SlackInboundEvent) -> Result<()> {
+    let db = app.state::<AppDatabase>();
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow!("Database lock error: {}", e))?;
+    conn.execute(
+        "INSERT INTO messaging_history
+        (id, connection_id, channel_id, message_id, direction, sender_id, content, timestamp, metadata)
+        VALUES (?1, ?2, ?3, ?4, 'inbound', ?5, ?6, ?7, ?8)",
+        params![
+            uuid::Uuid::new_v4().to_string(),
+            event.connection_id,
+            event.channel,
+            event.ts,
+            event.user,
+            event.text,
+            event.received_at,
+            json!({ "kind": event.kind, "thread_ts": event.thread_ts }).to_string(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn record_outbound(
+    app: &AppHandle,
+    connection_id: &str,
+    channel: &str,
+    ts: &str,
+    text: &str,
+) -> Result<()> {
+    let db = app.state::<AppDatabase>();
+    let conn = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow!("Database lock error: {}", e))?;
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO messaging_history
+        (id, connection_id, channel_id, message_id, direction, content, timestamp)
+        VALUES (?1, ?2, ?3, ?4, 'outbound', ?5, ?6)",
+        params![
+            uuid::Uuid::new_v4().to_string(),
+            connection_id,
+            channel,
+            ts,
+            text,
+            now
+        ],
+    )?;
+    conn.execute(
+        "UPDATE messaging_connections SET last_used_at = ?1 WHERE id = ?2",
+        params![now, connection_id],
+    )?;
+    Ok(())
+}
+
+fn str_arg<'a>(args: &'a HashMap<String, Value>, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("Missing {} parameter", name))
+}
+
+/// Run one of the Slack agent tools
+pub async fn execute_tool(
+    app: &AppHandle,
+    tool_id: &str,
+    args: &HashMap<String, Value>,
+) -> Result<Value> {
+    let manager = app
+        .try_state::<SlackEventManager>()
+        .ok_or_else(|| anyhow!("Slack integration is not initialized"))?;
+    let (connection_id, client) =
+        manager.client(app, args.get("connection_id").and_then(Value::as_str))?;
+    let channel = str_arg(args, "channel")?;
+
+    match tool_id {
+        SLACK_REPLY_TOOL => {
+            let text = str_arg(args, "text")?;
+            let thread_ts = args
+                .get("thread_ts")
+                .and_then(Value::as_str)
+                .filter(|ts| !ts.is_empty());
+            let message = match thread_ts {
+                Some(thread_ts) => client.reply_in_thread(channel, thread_ts, text).await,
+                None => client.send_message(channel, text).await,
+            }
+            .map_err(|e| anyhow!("Failed to send Slack message: {}", e))?;
+
+            if let Err(e) = record_outbound(app, &connection_id, channel, &message.ts, text) {
+                warn!("Failed to store Slack message: {}", e);
+            }
+            Ok(json!({
+                "connection_id": connection_id,
+                "channel": channel,
+                "ts": message.ts,
+                "thread_ts": message.thread_ts,
+            }))
+        }
+        SLACK_REACT_TOOL => {
+            let ts = str_arg(args, "ts")?;
+            let emoji = str_arg(args, "emoji")?.trim_matches(':');
+            client
+                .add_reaction(channel, ts, emoji)
+                .await
+                .map_err(|e| anyhow!("Failed to add Slack reaction: {}", e))?;
+            Ok(
+                json!({ "connection_id": connection_id, "channel": channel, "ts": ts, "emoji": emoji }),
+            )
+        }
+        SLACK_OPEN_THREAD_TOOL => {
+            let thread_ts = str_arg(args, "thread_ts")?;
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_THREAD_LIMIT, |limit| limit as usize);
+            let messages = client
+                .get_thread_replies(channel, thread_ts, limit)
+                .await
+                .map_err(|e| anyhow!("Failed to read Slack thread: {}", e))?;
+            Ok(json!({
+                "connection_id": connection_id,
+                "channel": channel,
+                "thread_ts": thread_ts,
+                "messages": messages,
+            }))
+        }
+        other => bail!("Unknown Slack tool: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> SlackAuthInfo {
+        SlackAuthInfo {
+            team_id: "T1".to_string(),
+            team: Some("Acme".to_string()),
+            user_id: "UBOT".to_string(),
+            bot_id: Some("B1".to_string()),
+        }
+    }
+
+    fn payload(event: Value) -> Value {
+        json!({ "team_id": "T1", "event_id": "Ev1", "event": event })
+    }
+
+    #[test]
+    fn classifies_inbound_messages() {
+        let mention = inbound_event(
+            "c1",
+            &auth(),
+            &payload(json!({
+                "type": "app_mention", "user": "U1", "text": "<@UBOT> deploy?",
+                "channel": "C1", "ts": "2.0", "thread_ts": "1.0"
+            })),
+        )
+        .unwrap();
+        assert_eq!(mention.kind, SlackEventKind::Mention);
+        assert_eq!(mention.thread_ts.as_deref(), Some("1.0"));
+        assert_eq!(mention.team_id.as_deref(), Some("T1"));
+
+        let dm = inbound_event(
+            "c1",
+            &auth(),
+            &payload(json!({
+                "type": "message", "channel_type": "im", "user": "U1", "text": "hi",
+                "channel": "D1", "ts": "3.0", "thread_ts": "3.0"
+            })),
+        )
+        .unwrap();
+        assert_eq!(dm.kind, SlackEventKind::DirectMessage);
+        assert_eq!(dm.thread_ts, None);
+    }
+
+    #[test]
+    fn skips_own_edited_and_duplicate_mention_messages() {
+        let skipped = [
+            json!({ "type": "message", "user": "UBOT", "text": "mine", "channel": "C1", "ts": "1.0" }),
+            json!({ "type": "message", "bot_id": "B2", "text": "bot", "channel": "C1", "ts": "1.0" }),
+            json!({ "type": "message", "subtype": "message_changed", "channel": "C1", "ts": "1.0" }),
+            json!({ "type": "message", "user": "U1", "text": "<@UBOT> hi", "channel": "C1", "ts": "1.0" }),
+            json!({ "type": "reaction_added", "user": "U1", "channel": "C1", "ts": "1.0" }),
+        ];
+        for event in skipped {
+            assert!(
+                inbound_event("c1", &auth(), &payload(event.clone())).is_none(),
+                "{}",
+                event
+            );
+        }
+    }
+}
@@ -113,10 +113,11 @@ impl TeamsClient {
             }
         });
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .post(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&payload)
             .send()
@@ -159,10 +160,11 @@ impl TeamsClient {
             }
         });
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .post(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&payload)
             .send()
@@ -211,10 +213,11 @@ impl TeamsClient {
             ]
         });
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .post(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&payload)
             .send()
@@ -256,10 +259,11 @@ impl TeamsClient {
             }
         });
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .post(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&payload)
             .send()
@@ -294,10 +298,49 @@ impl TeamsClient {
             team_id, chan_id, limit
         );
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .get(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("Teams API error: {}", error_text).into());
+        }
+
+        let result: TeamsMessagesResponse = response.json().await?;
+        Ok(result.value)
+    }
+
+    /// Get the replies to a channel message
+    pub async fn get_message_replies(
+        &mut self,
+        channel_id: &str,
+        message_id: &str,
+        limit: usize,
+    ) -> Result<Vec<TeamsMessage>, Box<dyn std::error::Error>> {
+        self.ensure_authenticated().await?;
+
+        let parts: Vec<&str> = channel_id.split('/').collect();
+        if parts.len() < 2 {
+            return Err("Invalid channel_id format. Expected: team_id/channel_id".into());
+        }
+        let team_id = parts[0];
+        let chan_id = parts[1];
+
+        let url = format!(
+            "https://graph.microsoft.com/v1.0/teams/{}/channels/{}/messages/{}/replies?$top={}",
+            team_id, chan_id, message_id, limit
+        );
+
+        let auth = self.auth_header()?;
+        let response = self
+            .client
+            .get(&url)
+            .header(header::AUTHORIZATION, auth)
             .send()
             .await?;
 
@@ -344,10 +387,11 @@ impl TeamsClient {
             }
         });
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .post(url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&payload)
             .send()
@@ -388,10 +432,11 @@ impl TeamsClient {
 
         let url = "https://graph.microsoft.com/v1.0/me/joinedTeams";
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .get(url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .send()
             .await?;
 
@@ -416,10 +461,11 @@ impl TeamsClient {
             team_id
         );
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .get(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .send()
             .await?;
 
@@ -444,10 +490,11 @@ impl TeamsClient {
             user_id
         );
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .get(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .send()
             .await?;
 
@@ -473,10 +520,11 @@ impl TeamsClient {
             user_id
         );
 
+        let auth = self.auth_header()?;
         let response = self
             .client
             .post(&url)
-            .header(header::AUTHORIZATION, self.auth_header()?)
+            .header(header::AUTHORIZATION, auth)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&notification)
             .send()
@@ -516,6 +564,7 @@ struct ChannelsListResponse {
 
 // Data types
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "GraphChatMessage")]
 pub struct TeamsMessage {
     pub id: String,
     #[serde(rename = "createdDateTime")]
@@ -523,10 +572,109 @@ pub struct TeamsMessage {
     pub from_user_id: String,
     pub from_user_name: Option<String>,
     pub body: String,
-    #[serde(default)]
+    /// Set on replies; the ID of the thread's root message
+    pub reply_to_id: Option<String>,
     pub attachments: Vec<TeamsAttachment>,
 }
 
+/// `chatMessage` as returned by Microsoft Graph
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphChatMessage {
+    id: String,
+    #[serde(default)]
+    created_date_time: Option<String>,
+    #[serde(default)]
+    reply_to_id: Option<String>,
+    #[serde(default)]
+    from: Option<GraphFrom>,
+    #[serde(default)]
+    body: Option<GraphItemBody>,
+    #[serde(default)]
+    attachments: Vec<TeamsAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphFrom {
+    #[serde(default)]
+    user: Option<GraphIdentity>,
+    #[serde(default)]
+    application: Option<GraphIdentity>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphIdentity {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphItemBody {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+impl From<GraphChatMessage> for TeamsMessage {
+    fn from(message: GraphChatMessage) -> Self {
+        let created_at = message
+            .created_date_time
+            .and_then(|date| chrono::DateTime::parse_from_rfc3339(&date).ok())
+            .map_or(0, |date| date.timestamp());
+        let sender = message.from.and_then(|from| from.user.or(from.application));
+        let body = message.body.map_or_else(String::new, |body| {
+            if body
+                .content_type
+                .is_some_and(|kind| kind.eq_ignore_ascii_case("html"))
+            {
+                html_to_text(&body.content)
+            } else {
+                body.content
+            }
+        });
+
+        Self {
+            id: message.id,
+            created_at,
+            from_user_id: sender
+                .as_ref()
+                .and_then(|sender| sender.id.clone())
+                .unwrap_or_default(),
+            from_user_name: sender.and_then(|sender| sender.display_name),
+            body,
+            reply_to_id: message.reply_to_id,
+            attachments: message.attachments,
+        }
+    }
+}
+
+/// Strip tags and decode the few entities Teams puts in message bodies
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamsAttachment {
     pub id: String,
@@ -729,4 +877,23 @@ mod tests {
         assert_eq!(card.card_type, "AdaptiveCard");
         assert_eq!(card.version, "1.4");
     }
+
+    #[test]
+    fn test_parse_graph_chat_message() {
+        let message: TeamsMessage = serde_json::from_value(json!({
+            "id": "1700000000001",
+            "replyToId": "1700000000000",
+            "createdDateTime": "2023-11-14T22:13:20.001Z",
+            "from": { "user": { "id": "u1", "displayName": "Ada" } },
+            "body": { "contentType": "html", "content": "<p>Ship it &amp; tell &quot;ops&quot;</p>" },
+            "attachments": []
+        }))
+        .unwrap();
+
+        assert_eq!(message.created_at, 1_700_000_000);
+        assert_eq!(message.from_user_id, "u1");
+        assert_eq!(message.from_user_name.as_deref(), Some("Ada"));
+        assert_eq!(message.body, "Ship it & tell \"ops\"");
+        assert_eq!(message.reply_to_id.as_deref(), Some("1700000000000"));
+    }
 }
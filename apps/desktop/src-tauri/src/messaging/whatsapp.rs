@@ -353,6 +353,34 @@ impl WhatsAppClient {
         Ok(())
     }
 
+    /// Check that the access token can still read the phone number
+    pub async fn verify(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}?fields=display_phone_number",
+            self.phone_number_id
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", self.access_token),
+            )
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            let message = body["error"]["message"]
+                .as_str()
+                .unwrap_or("request failed");
+            return Err(format!("WhatsApp API error ({}): {}", status, message).into());
+        }
+        Ok(())
+    }
+
     /// Get media URL from media ID
     pub async fn get_media_url(
         &self,
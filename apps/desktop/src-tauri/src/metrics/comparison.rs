@@ -1,7 +1,6 @@
+use crate::db::Pool;
 use chrono::Utc;
-use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
 
 use super::PeriodStats;
 
@@ -42,11 +41,11 @@ pub struct BenchmarkComparison {
 
 /// Metrics comparison engine
 pub struct MetricsComparison {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
 }
 
 impl MetricsComparison {
-    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self { db }
     }
 
@@ -160,7 +159,10 @@ impl MetricsComparison {
         offset_days: i64,
         period_days: i64,
     ) -> Result<PeriodStats, String> {
-        let conn = self.db.lock().unwrap();
+        let conn = self
+            .db
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
         let now = Utc::now().timestamp();
         let end = now - (offset_days * 24 * 60 * 60);
         let start = end - (period_days * 24 * 60 * 60);
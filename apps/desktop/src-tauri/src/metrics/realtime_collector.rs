@@ -1,9 +1,11 @@
 use chrono::Utc;
 use rusqlite::{Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::db::batch::{write_batched, DEFAULT_BATCH_SIZE};
+use crate::db::Pool;
 use crate::realtime::RealtimeServer;
 
 /// Configuration for hourly rate (defaults to $50/hr)
@@ -112,13 +114,13 @@ pub struct EmployeePerformance {
 
 /// Real-time metrics collector
 pub struct RealtimeMetricsCollector {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
     realtime_server: Arc<RealtimeServer>,
     hourly_rate: f64,
 }
 
 impl RealtimeMetricsCollector {
-    pub fn new(db: Arc<Mutex<Connection>>, realtime_server: Arc<RealtimeServer>) -> Self {
+    pub fn new(db: Pool, realtime_server: Arc<RealtimeServer>) -> Self {
         Self {
             db,
             realtime_server,
@@ -127,8 +129,8 @@ impl RealtimeMetricsCollector {
     }
 
     /// Get database connection
-    pub fn db_conn(&self) -> Arc<Mutex<Connection>> {
-        Arc::clone(&self.db)
+    pub fn db_conn(&self) -> Pool {
+        self.db.clone()
     }
 
     /// Record automation run and broadcast metrics update
@@ -153,6 +155,33 @@ impl RealtimeMetricsCollector {
     }
 
     /// Calculate metrics from automation run
+    /// Record many runs at once (imports, offline queue replay). Rows are
+    /// written in batched transactions; milestones are checked once per user.
+    pub async fn record_automation_runs(
+        &self,
+        runs: Vec<AutomationRun>,
+    ) -> Result<Vec<MetricsSnapshot>, String> {
+        let snapshots: Vec<MetricsSnapshot> =
+            runs.iter().map(|run| self.calculate_metrics(run)).collect();
+
+        self.store_metrics_batch(&snapshots)
+            .map_err(|e| format!("Failed to store metrics: {}", e))?;
+
+        let mut users: Vec<&str> = Vec::new();
+        for metrics in &snapshots {
+            self.broadcast_update(&metrics.user_id, metrics.clone())
+                .await;
+            if !users.contains(&metrics.user_id.as_str()) {
+                users.push(&metrics.user_id);
+            }
+        }
+        for user_id in users {
+            self.check_milestones(user_id).await;
+        }
+
+        Ok(snapshots)
+    }
+
     fn calculate_metrics(&self, run: &AutomationRun) -> MetricsSnapshot {
         let time_saved_minutes = if run.estimated_manual_time_ms > run.actual_execution_time_ms {
             (run.estimated_manual_time_ms - run.actual_execution_time_ms) / 60_000
@@ -178,32 +207,42 @@ impl RealtimeMetricsCollector {
 
     /// Store metrics in database
     fn store_metrics(&self, metrics: &MetricsSnapshot) -> SqliteResult<()> {
-        let conn = self.db.lock().map_err(|e| {
+        self.store_metrics_batch(std::slice::from_ref(metrics))
+            .map(|_| ())
+    }
+
+    /// Insert snapshots through one cached statement in batched transactions
+    fn store_metrics_batch(&self, snapshots: &[MetricsSnapshot]) -> SqliteResult<usize> {
+        let mut conn = self.db.get().map_err(|e| {
             rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Database lock poisoned: {}",
+                "Failed to get database connection: {}",
                 e
             ))))
         })?;
-        conn.execute(
+        write_batched(
+            &mut conn,
             "INSERT INTO realtime_metrics (
                 id, user_id, automation_id, employee_id,
                 time_saved_minutes, cost_saved_usd, tasks_completed,
                 errors_prevented, quality_score, timestamp
             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            rusqlite::params![
-                &metrics.id,
-                &metrics.user_id,
-                &metrics.automation_id,
-                &metrics.employee_id,
-                metrics.time_saved_minutes as i64,
-                metrics.cost_saved_usd,
-                metrics.tasks_completed as i64,
-                metrics.errors_prevented as i64,
-                metrics.quality_score,
-                metrics.timestamp,
-            ],
-        )?;
-        Ok(())
+            snapshots,
+            DEFAULT_BATCH_SIZE,
+            |stmt, metrics| {
+                stmt.execute(rusqlite::params![
+                    &metrics.id,
+                    &metrics.user_id,
+                    &metrics.automation_id,
+                    &metrics.employee_id,
+                    metrics.time_saved_minutes as i64,
+                    metrics.cost_saved_usd,
+                    metrics.tasks_completed as i64,
+                    metrics.errors_prevented as i64,
+                    metrics.quality_score,
+                    metrics.timestamp,
+                ])
+            },
+        )
     }
 
     /// Broadcast metrics update via WebSocket
@@ -268,9 +307,9 @@ impl RealtimeMetricsCollector {
 
     /// Check if milestone has been recorded
     fn is_milestone_recorded(&self, user_id: &str, milestone_type: &str) -> SqliteResult<bool> {
-        let conn = self.db.lock().map_err(|e| {
+        let conn = self.db.get().map_err(|e| {
             rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Database lock poisoned: {}",
+                "Failed to get database connection: {}",
                 e
             ))))
         })?;
@@ -290,9 +329,9 @@ impl RealtimeMetricsCollector {
         threshold_value: f64,
         _cost_saved: f64,
     ) -> SqliteResult<()> {
-        let conn = self.db.lock().map_err(|e| {
+        let conn = self.db.get().map_err(|e| {
             rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Database lock poisoned: {}",
+                "Failed to get database connection: {}",
                 e
             ))))
         })?;
@@ -339,9 +378,9 @@ impl RealtimeMetricsCollector {
 
     /// Aggregate metrics for a specific time period (in days)
     async fn aggregate_period(&self, user_id: &str, days: i64) -> SqliteResult<PeriodStats> {
-        let conn = self.db.lock().map_err(|e| {
+        let conn = self.db.get().map_err(|e| {
             rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Database lock poisoned: {}",
+                "Failed to get database connection: {}",
                 e
             ))))
         })?;
@@ -383,9 +422,9 @@ impl RealtimeMetricsCollector {
 
     /// Aggregate all-time metrics
     async fn aggregate_all_time(&self, user_id: &str) -> SqliteResult<PeriodStats> {
-        let conn = self.db.lock().map_err(|e| {
+        let conn = self.db.get().map_err(|e| {
             rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Database lock poisoned: {}",
+                "Failed to get database connection: {}",
                 e
             ))))
         })?;
@@ -430,9 +469,9 @@ impl RealtimeMetricsCollector {
         user_id: &str,
         cutoff_timestamp: Option<i64>,
     ) -> SqliteResult<Vec<EmployeePerformance>> {
-        let conn = self.db.lock().map_err(|e| {
+        let conn = self.db.get().map_err(|e| {
             rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Database lock poisoned: {}",
+                "Failed to get database connection: {}",
                 e
             ))))
         })?;
@@ -510,8 +549,8 @@ impl RealtimeMetricsCollector {
     ) -> Result<Vec<MetricsSnapshot>, String> {
         let conn = self
             .db
-            .lock()
-            .map_err(|e| format!("Database lock poisoned: {}", e))?;
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
         let cutoff = Utc::now().timestamp() - (days * 24 * 60 * 60);
 
         let mut stmt = conn
@@ -0,0 +1,307 @@
+//! Native OS notifications for task completion, approval requests, budget
+//! warnings, expiring credentials and failing integration health checks.
+//!
+//! On Windows notifications are toasts with action buttons (Approve/Reject,
+//! Open); activating one dispatches back into the matching command. Other
+//! platforms go through the Tauri notification plugin, which has no desktop
+//! action support, so their actions stay available in the app instead. Every
+//! notification is also emitted as `notification://shown` for the in-app
+//! notification list.
+
+#[cfg(windows)]
+mod toast;
+
+use crate::agent::approval::ApprovalController;
+use crate::commands::{AppDatabase, SettingsServiceState};
+use crate::settings::NotificationPreferences;
+use crate::settings::{SettingCategory, SettingValue};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+const PREFERENCES_KEY: &str = "notification_preferences";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    TaskCompletion,
+    ApprovalRequest,
+    BudgetWarning,
+    /// Raised by an email rule's notify action
+    EmailRule,
+    /// Integration credentials failing or about to expire
+    CredentialExpiry,
+    /// A custom integration failing its health check
+    IntegrationHealth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationAction {
+    Approve,
+    Reject,
+    Open,
+}
+
+impl NotificationAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationAction::Approve => "Approve",
+            NotificationAction::Reject => "Reject",
+            NotificationAction::Open => "Open",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: String,
+    /// Approval or task id the actions apply to
+    pub target: Option<String>,
+    pub actions: Vec<NotificationAction>,
+}
+
+impl Notification {
+    pub fn new(
+        category: NotificationCategory,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            category,
+            title: title.into(),
+            body: body.into(),
+            target: None,
+            actions: vec![NotificationAction::Open],
+        }
+    }
+
+    /// An approval request with Approve/Reject buttons
+    pub fn approval(approval_id: &str, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            target: Some(approval_id.to_string()),
+            actions: vec![NotificationAction::Approve, NotificationAction::Reject],
+            ..Self::new(NotificationCategory::ApprovalRequest, title, body)
+        }
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+}
+
+/// What a clicked notification or button asks for, round-tripped through the
+/// OS as the toast's activation arguments
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationActivation {
+    pub category: NotificationCategory,
+    pub action: NotificationAction,
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationOpenEvent {
+    pub category: NotificationCategory,
+    pub target: Option<String>,
+}
+
+/// Current preferences from settings v2, or the defaults
+pub fn load_preferences(app: &AppHandle) -> NotificationPreferences {
+    let Some(state) = app.try_state::<SettingsServiceState>() else {
+        return NotificationPreferences::default();
+    };
+    let Ok(service) = state.service.lock() else {
+        return NotificationPreferences::default();
+    };
+    service
+        .get(PREFERENCES_KEY)
+        .ok()
+        .and_then(|value| value.as_json().cloned())
+        .and_then(|json| serde_json::from_value(json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_preferences(
+    service: &crate::settings::SettingsService,
+    preferences: &NotificationPreferences,
+) -> anyhow::Result<()> {
+    service.set(
+        PREFERENCES_KEY.to_string(),
+        SettingValue::Json(serde_json::to_value(preferences)?),
+        SettingCategory::Ui,
+        false,
+    )?;
+    Ok(())
+}
+
+pub fn is_enabled(preferences: &NotificationPreferences, category: NotificationCategory) -> bool {
+    match category {
+        NotificationCategory::TaskCompletion => preferences.task_completion,
+        NotificationCategory::ApprovalRequest => preferences.approval_requests,
+        NotificationCategory::BudgetWarning => preferences.budget_warnings,
+        NotificationCategory::EmailRule => preferences.email_rules,
+        NotificationCategory::CredentialExpiry => preferences.credential_expiry,
+        NotificationCategory::IntegrationHealth => preferences.integration_health,
+    }
+}
+
+/// Show `notification` if the user has its category enabled. Failures are
+/// logged; a missing notification never fails the caller.
+pub fn notify(app: &AppHandle, notification: Notification) {
+    let preferences = load_preferences(app);
+    if !is_enabled(&preferences, notification.category) {
+        return;
+    }
+
+    let _ = app.emit("notification://shown", &notification);
+
+    let focused = app
+        .get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false);
+    if focused && !preferences.show_when_focused {
+        return;
+    }
+
+    if let Err(e) = show_native(app, &notification) {
+        tracing::warn!("Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(windows)]
+fn show_native(app: &AppHandle, notification: &Notification) -> anyhow::Result<()> {
+    toast::show(app, notification)
+}
+
+#[cfg(not(windows))]
+fn show_native(app: &AppHandle, notification: &Notification) -> anyhow::Result<()> {
+    use tauri_plugin_notification::NotificationExt;
+
+    app.notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show()?;
+    Ok(())
+}
+
+/// Carry out a notification action. Approvals resolve through the agent
+/// approval controller when it is waiting on them and through the approval
+/// workflow otherwise; Open brings the main window forward.
+pub async fn dispatch_action(
+    app: &AppHandle,
+    activation: NotificationActivation,
+) -> Result<(), String> {
+    use crate::commands::{agent_resolve_approval, approve_operation, reject_operation};
+    use crate::security::{AuthenticatedUser, ReauthManager};
+
+    let approval_id = || {
+        activation
+            .target
+            .clone()
+            .ok_or_else(|| "Notification action has no target".to_string())
+    };
+
+    match activation.action {
+        NotificationAction::Approve | NotificationAction::Reject => {
+            let approval_id = approval_id()?;
+            let approve = activation.action == NotificationAction::Approve;
+
+            let agent_pending = match app.try_state::<ApprovalController>() {
+                Some(controller) => controller
+                    .pending_requests()
+                    .await
+                    .iter()
+                    .any(|request| request.action_id == approval_id),
+                None => false,
+            };
+
+            if agent_pending {
+                let decision = if approve { "approve" } else { "reject" };
+                // Toast actions are clicked on this machine, outside any session
+                agent_resolve_approval(
+                    app.clone(),
+                    AuthenticatedUser::local_owner(),
+                    app.state::<ReauthManager>(),
+                    app.state::<ApprovalController>(),
+                    approval_id,
+                    decision.to_string(),
+                    None,
+                    None,
+                )
+                .await
+            } else if approve {
+                approve_operation(app.clone(), approval_id, app.state::<AppDatabase>()).await
+            } else {
+                reject_operation(app.clone(), approval_id, None, app.state::<AppDatabase>()).await
+            }
+        }
+        NotificationAction::Open => {
+            let main = app
+                .get_webview_window("main")
+                .ok_or_else(|| "Main window not found".to_string())?;
+            crate::window::show_window(&main).map_err(|e| e.to_string())?;
+            main.emit(
+                "notification://open",
+                NotificationOpenEvent {
+                    category: activation.category,
+                    target: activation.target,
+                },
+            )
+            .map_err(|e| format!("Failed to emit event: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_gate_categories() {
+        let mut preferences = NotificationPreferences::default();
+        assert!(is_enabled(
+            &preferences,
+            NotificationCategory::ApprovalRequest
+        ));
+
+        preferences.budget_warnings = false;
+        assert!(!is_enabled(
+            &preferences,
+            NotificationCategory::BudgetWarning
+        ));
+        assert!(is_enabled(
+            &preferences,
+            NotificationCategory::TaskCompletion
+        ));
+
+        // Stored preferences from an older build fill in new fields
+        let stored: NotificationPreferences =
+            serde_json::from_value(serde_json::json!({ "taskCompletion": false })).unwrap();
+        assert!(!stored.task_completion);
+        assert!(stored.approval_requests);
+    }
+
+    #[test]
+    fn test_activation_round_trip() {
+        let notification = Notification::approval("ap-1", "Run command?", "rm -rf build");
+        assert_eq!(
+            notification.actions,
+            vec![NotificationAction::Approve, NotificationAction::Reject]
+        );
+
+        let activation = NotificationActivation {
+            category: notification.category,
+            action: NotificationAction::Reject,
+            target: notification.target.clone(),
+        };
+        let encoded = serde_json::to_string(&activation).unwrap();
+        let decoded: NotificationActivation = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, activation);
+    }
+}
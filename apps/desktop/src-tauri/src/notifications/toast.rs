@@ -0,0 +1,100 @@
+use super::{Notification, NotificationAction, NotificationActivation};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use windows::core::{IInspectable, Interface, HSTRING};
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::Foundation::TypedEventHandler;
+use windows::UI::Notifications::{
+    ToastActivatedEventArgs, ToastNotification, ToastNotificationManager,
+};
+
+/// Unpackaged dev builds have no registered AppUserModelID; Windows only
+/// shows toasts for known ids, so borrow PowerShell's like other Rust toast
+/// crates do.
+const DEV_APP_ID: &str =
+    "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+
+/// Activation handlers live on the toast object, so recent toasts are kept
+/// alive until they have surely left the action center.
+const LIVE_TOASTS: usize = 32;
+
+static TOASTS: Mutex<VecDeque<ToastNotification>> = Mutex::new(VecDeque::new());
+
+pub(super) fn show(app: &AppHandle, notification: &Notification) -> anyhow::Result<()> {
+    let document = XmlDocument::new()?;
+    document.LoadXml(&HSTRING::from(toast_xml(notification)?))?;
+    let toast = ToastNotification::CreateToastNotification(&document)?;
+
+    let handle = app.clone();
+    toast.Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(
+        move |_, args| {
+            let Some(args) = args.as_ref() else {
+                return Ok(());
+            };
+            let arguments = args.cast::<ToastActivatedEventArgs>()?.Arguments()?;
+            match serde_json::from_str::<NotificationActivation>(&arguments.to_string()) {
+                Ok(activation) => {
+                    let handle = handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = super::dispatch_action(&handle, activation).await {
+                            tracing::warn!("Notification action failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!("Unreadable toast activation: {}", e),
+            }
+            Ok(())
+        },
+    ))?;
+
+    let app_id = if tauri::is_dev() {
+        DEV_APP_ID.to_string()
+    } else {
+        app.config().identifier.clone()
+    };
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))?.Show(&toast)?;
+
+    let mut live = TOASTS.lock().unwrap_or_else(|e| e.into_inner());
+    live.push_back(toast);
+    while live.len() > LIVE_TOASTS {
+        live.pop_front();
+    }
+    Ok(())
+}
+
+fn toast_xml(notification: &Notification) -> anyhow::Result<String> {
+    let arguments = |action: NotificationAction| -> anyhow::Result<String> {
+        let activation = NotificationActivation {
+            category: notification.category,
+            action,
+            target: notification.target.clone(),
+        };
+        Ok(escape(&serde_json::to_string(&activation)?))
+    };
+
+    let mut actions = String::new();
+    for action in &notification.actions {
+        actions.push_str(&format!(
+            r#"<action content="{}" arguments="{}" activationType="foreground"/>"#,
+            action.label(),
+            arguments(*action)?
+        ));
+    }
+
+    Ok(format!(
+        r#"<toast launch="{}"><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual><actions>{}</actions></toast>"#,
+        arguments(NotificationAction::Open)?,
+        escape(&notification.title),
+        escape(&notification.body),
+        actions
+    ))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,578 @@
+//! Shared memory for agents working on the same orchestrated run.
+//!
+//! Agents the orchestrator spawns together share a run-scoped blackboard: JSON
+//! entries under string keys plus named text artifacts (notes, drafts,
+//! reports), so a researcher agent can leave findings for a writer agent.
+//! Writes either replace the current value (last write wins) or append to it,
+//! and can be made conditional on the version the writer last read. Every
+//! change is broadcast to waiting agents and emitted as `blackboard:changed`.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+
+pub const CHANGED_EVENT: &str = "blackboard:changed";
+
+/// Agent tools that read and write the blackboard of the agent's run
+pub const BLACKBOARD_TOOLS: &[&str] = &[
+    "blackboard_write",
+    "blackboard_read",
+    "blackboard_put_artifact",
+    "blackboard_get_artifact",
+];
+
+const MAX_ARTIFACT_BYTES: usize = 5 * 1024 * 1024;
+/// Longest an agent may block waiting for another agent's write
+const MAX_WAIT: Duration = Duration::from_secs(300);
+/// Oldest runs' blackboards are dropped beyond this many
+const MAX_OPEN_RUNS: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteMode {
+    /// Replace the current value; the last write wins
+    #[default]
+    Replace,
+    /// Keep the entry as a list and add the value to it
+    Append,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackboardEntry {
+    pub key: String,
+    pub value: Value,
+    /// Starts at 1 and increases with every write
+    pub version: u64,
+    /// Goal id of the agent, or `user`
+    pub written_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    pub name: String,
+    pub content_type: String,
+    pub content: String,
+    pub version: u64,
+    pub written_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An artifact without its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactInfo {
+    pub name: String,
+    pub content_type: String,
+    pub size: usize,
+    pub version: u64,
+    pub written_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Artifact> for ArtifactInfo {
+    fn from(artifact: &Artifact) -> Self {
+        Self {
+            name: artifact.name.clone(),
+            content_type: artifact.content_type.clone(),
+            size: artifact.content.len(),
+            version: artifact.version,
+            written_by: artifact.written_by.clone(),
+            updated_at: artifact.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Entry,
+    Artifact,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackboardChange {
+    pub run_id: String,
+    pub kind: ChangeKind,
+    /// Entry key or artifact name
+    pub key: String,
+    pub version: u64,
+    pub written_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackboardSnapshot {
+    pub run_id: String,
+    pub entries: Vec<BlackboardEntry>,
+    pub artifacts: Vec<ArtifactInfo>,
+}
+
+#[derive(Default)]
+struct BoardState {
+    entries: HashMap<String, BlackboardEntry>,
+    artifacts: HashMap<String, Artifact>,
+}
+
+/// Entries and artifacts shared by the agents of one run
+pub struct Blackboard {
+    run_id: String,
+    state: RwLock<BoardState>,
+    changes: broadcast::Sender<BlackboardChange>,
+    app_handle: Option<AppHandle>,
+}
+
+impl Blackboard {
+    pub fn new(run_id: impl Into<String>, app_handle: Option<AppHandle>) -> Self {
+        let (changes, _) = broadcast::channel(256);
+        Self {
+            run_id: run_id.into(),
+            state: RwLock::new(BoardState::default()),
+            changes,
+            app_handle,
+        }
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Write `value` under `key`. With `expected_version` the write only goes
+    /// ahead if the entry is still at that version (`0` for a new entry).
+    pub fn write(
+        &self,
+        key: &str,
+        value: Value,
+        mode: WriteMode,
+        expected_version: Option<u64>,
+        writer: &str,
+    ) -> Result<BlackboardEntry, String> {
+        if key.trim().is_empty() {
+            return Err("Blackboard key cannot be empty".to_string());
+        }
+
+        let entry = {
+            let mut state = self.state.write();
+            let current = state.entries.get(key);
+            let current_version = current.map(|entry| entry.version).unwrap_or(0);
+            if let Some(expected) = expected_version {
+                if expected != current_version {
+                    return Err(format!(
+                        "Conflict on '{}': expected version {}, but it is at version {}",
+                        key, expected, current_version
+                    ));
+                }
+            }
+
+            let value = match (mode, current.map(|entry| &entry.value)) {
+                (WriteMode::Replace, _) => value,
+                (WriteMode::Append, None) => json!([value]),
+                (WriteMode::Append, Some(Value::Array(items))) => {
+                    let mut items = items.clone();
+                    items.push(value);
+                    Value::Array(items)
+                }
+                (WriteMode::Append, Some(existing)) => json!([existing.clone(), value]),
+            };
+            let entry = BlackboardEntry {
+                key: key.to_string(),
+                value,
+                version: current_version + 1,
+                written_by: writer.to_string(),
+                updated_at: Utc::now(),
+            };
+            state.entries.insert(key.to_string(), entry.clone());
+            entry
+        };
+
+        self.publish(ChangeKind::Entry, key, entry.version, writer);
+        Ok(entry)
+    }
+
+    pub fn read(&self, key: &str) -> Option<BlackboardEntry> {
+        self.state.read().entries.get(key).cloned()
+    }
+
+    /// The entry once it is past `after_version`, waiting up to `timeout` for
+    /// another agent to write it
+    pub async fn wait_for(
+        &self,
+        key: &str,
+        after_version: u64,
+        timeout: Duration,
+    ) -> Option<BlackboardEntry> {
+        let current = || self.read(key).filter(|entry| entry.version > after_version);
+        self.wait_until(ChangeKind::Entry, key, timeout, current)
+            .await
+    }
+
+    pub fn entries(&self) -> Vec<BlackboardEntry> {
+        let mut entries: Vec<_> = self.state.read().entries.values().cloned().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+    }
+
+    /// Store an artifact, replacing any earlier one with the same name
+    pub fn put_artifact(
+        &self,
+        name: &str,
+        content_type: &str,
+        content: String,
+        writer: &str,
+    ) -> Result<ArtifactInfo, String> {
+        if name.trim().is_empty() {
+            return Err("Artifact name cannot be empty".to_string());
+        }
+        if content.len() > MAX_ARTIFACT_BYTES {
+            return Err(format!(
+                "Artifact '{}' is {} bytes; the limit is {}",
+                name,
+                content.len(),
+                MAX_ARTIFACT_BYTES
+            ));
+        }
+
+        let info = {
+            let mut state = self.state.write();
+            let version = state
+                .artifacts
+                .get(name)
+                .map(|artifact| artifact.version)
+                .unwrap_or(0)
+                + 1;
+            let artifact = Artifact {
+                name: name.to_string(),
+                content_type: content_type.to_string(),
+                content,
+                version,
+                written_by: writer.to_string(),
+                updated_at: Utc::now(),
+            };
+            let info = ArtifactInfo::from(&artifact);
+            state.artifacts.insert(name.to_string(), artifact);
+            info
+        };
+
+        self.publish(ChangeKind::Artifact, name, info.version, writer);
+        Ok(info)
+    }
+
+    pub fn artifact(&self, name: &str) -> Option<Artifact> {
+        self.state.read().artifacts.get(name).cloned()
+    }
+
+    /// The artifact, waiting up to `timeout` for another agent to store it
+    pub async fn wait_for_artifact(&self, name: &str, timeout: Duration) -> Option<Artifact> {
+        self.wait_until(ChangeKind::Artifact, name, timeout, || self.artifact(name))
+            .await
+    }
+
+    pub fn artifacts(&self) -> Vec<ArtifactInfo> {
+        let mut artifacts: Vec<_> = self
+            .state
+            .read()
+            .artifacts
+            .values()
+            .map(ArtifactInfo::from)
+            .collect();
+        artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+        artifacts
+    }
+
+    pub fn snapshot(&self) -> BlackboardSnapshot {
+        BlackboardSnapshot {
+            run_id: self.run_id.clone(),
+            entries: self.entries(),
+            artifacts: self.artifacts(),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BlackboardChange> {
+        self.changes.subscribe()
+    }
+
+    async fn wait_until<T>(
+        &self,
+        kind: ChangeKind,
+        key: &str,
+        timeout: Duration,
+        current: impl Fn() -> Option<T>,
+    ) -> Option<T> {
+        // Subscribe before checking so a write in between is not missed
+        let mut changes = self.subscribe();
+        if let Some(found) = current() {
+            return Some(found);
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout.min(MAX_WAIT);
+        loop {
+            match tokio::time::timeout_at(deadline, changes.recv()).await {
+                Err(_) | Ok(Err(broadcast::error::RecvError::Closed)) => return None,
+                Ok(Ok(change)) if change.kind != kind || change.key != key => continue,
+                // A change to the key, or missed changes that may include one
+                Ok(_) => {
+                    if let Some(found) = current() {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+    }
+
+    fn publish(&self, kind: ChangeKind, key: &str, version: u64, writer: &str) {
+        let change = BlackboardChange {
+            run_id: self.run_id.clone(),
+            kind,
+            key: key.to_string(),
+            version,
+            written_by: writer.to_string(),
+        };
+        if let Some(app) = &self.app_handle {
+            let _ = app.emit(CHANGED_EVENT, &change);
+        }
+        // Nobody may be waiting; that is fine
+        let _ = self.changes.send(change);
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    boards: HashMap<String, Arc<Blackboard>>,
+    /// Run ids, oldest first
+    opened: VecDeque<String>,
+    /// Agent goal id to run id
+    goals: HashMap<String, String>,
+}
+
+/// Blackboards of open runs and the goals taking part in each
+#[derive(Default)]
+pub struct Blackboards {
+    registry: Mutex<Registry>,
+}
+
+impl Blackboards {
+    /// The run's blackboard, created if needed
+    pub fn open(&self, run_id: &str, app_handle: Option<AppHandle>) -> Arc<Blackboard> {
+        let mut registry = self.registry.lock();
+        if let Some(board) = registry.boards.get(run_id) {
+            return board.clone();
+        }
+
+        while registry.opened.len() >= MAX_OPEN_RUNS {
+            if let Some(oldest) = registry.opened.pop_front() {
+                registry.boards.remove(&oldest);
+                registry.goals.retain(|_, run| *run != oldest);
+            }
+        }
+        let board = Arc::new(Blackboard::new(run_id, app_handle));
+        registry.boards.insert(run_id.to_string(), board.clone());
+        registry.opened.push_back(run_id.to_string());
+        board
+    }
+
+    /// Give the agent working on `goal_id` access to the run's blackboard
+    pub fn join(&self, run_id: &str, goal_id: &str) {
+        self.registry
+            .lock()
+            .goals
+            .insert(goal_id.to_string(), run_id.to_string());
+    }
+
+    pub fn get(&self, run_id: &str) -> Option<Arc<Blackboard>> {
+        self.registry.lock().boards.get(run_id).cloned()
+    }
+
+    pub fn for_goal(&self, goal_id: &str) -> Option<Arc<Blackboard>> {
+        let registry = self.registry.lock();
+        let run_id = registry.goals.get(goal_id)?;
+        registry.boards.get(run_id).cloned()
+    }
+
+    /// Drop the run's blackboard; whether it was open
+    pub fn close(&self, run_id: &str) -> bool {
+        let mut registry = self.registry.lock();
+        registry.opened.retain(|id| id != run_id);
+        registry.goals.retain(|_, run| run != run_id);
+        registry.boards.remove(run_id).is_some()
+    }
+}
+
+static BLACKBOARDS: Lazy<Blackboards> = Lazy::new(Blackboards::default);
+
+pub fn blackboards() -> &'static Blackboards {
+    &BLACKBOARDS
+}
+
+/// Run a blackboard tool for the agent working on `goal_id`
+pub async fn run_tool(
+    tool_name: &str,
+    parameters: &HashMap<String, Value>,
+    goal_id: &str,
+) -> Result<Value, String> {
+    let board = blackboards().for_goal(goal_id).ok_or_else(|| {
+        "This agent is not part of an orchestrated run, so it has no blackboard".to_string()
+    })?;
+    let text = |name: &str| parameters.get(name).and_then(Value::as_str);
+    let required = |name: &str| text(name).ok_or_else(|| format!("Missing {} parameter", name));
+    let wait = parameters
+        .get("wait_secs")
+        .and_then(Value::as_u64)
+        .map(Duration::from_secs);
+
+    match tool_name {
+        "blackboard_write" => {
+            let key = required("key")?;
+            let value = parameters
+                .get("value")
+                .cloned()
+                .ok_or_else(|| "Missing value parameter".to_string())?;
+            let mode = match parameters.get("mode") {
+                Some(mode) => serde_json::from_value(mode.clone())
+                    .map_err(|_| "mode must be \"replace\" or \"append\"".to_string())?,
+                None => WriteMode::default(),
+            };
+            let expected_version = parameters.get("expected_version").and_then(Value::as_u64);
+            let entry = board.write(key, value, mode, expected_version, goal_id)?;
+            Ok(json!({ "key": entry.key, "version": entry.version }))
+        }
+        "blackboard_read" => {
+            let Some(key) = text("key") else {
+                return serde_json::to_value(board.snapshot()).map_err(|e| e.to_string());
+            };
+            let after_version = parameters
+                .get("after_version")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let entry = match wait {
+                Some(timeout) => board.wait_for(key, after_version, timeout).await,
+                None => board.read(key),
+            };
+            Ok(match entry {
+                Some(entry) => json!({ "found": true, "entry": entry }),
+                None => json!({ "found": false, "key": key }),
+            })
+        }
+        "blackboard_put_artifact" => {
+            let name = required("name")?;
+            let content = required("content")?.to_string();
+            let content_type = text("content_type").unwrap_or("text/markdown");
+            let info = board.put_artifact(name, content_type, content, goal_id)?;
+            serde_json::to_value(info).map_err(|e| e.to_string())
+        }
+        "blackboard_get_artifact" => {
+            let name = required("name")?;
+            let artifact = match wait {
+                Some(timeout) => board.wait_for_artifact(name, timeout).await,
+                None => board.artifact(name),
+            };
+            Ok(match artifact {
+                Some(artifact) => json!({ "found": true, "artifact": artifact }),
+                None => json!({ "found": false, "name": name }),
+            })
+        }
+        other => Err(format!("Unknown blackboard tool: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_modes_and_conflicts() {
+        let board = Blackboard::new("run_1", None);
+        board
+            .write("findings", json!("a"), WriteMode::Append, None, "goal_a")
+            .unwrap();
+        let entry = board
+            .write("findings", json!("b"), WriteMode::Append, None, "goal_b")
+            .unwrap();
+        assert_eq!(entry.value, json!(["a", "b"]));
+        assert_eq!(entry.version, 2);
+
+        let entry = board
+            .write(
+                "findings",
+                json!("c"),
+                WriteMode::Replace,
+                Some(2),
+                "goal_a",
+            )
+            .unwrap();
+        assert_eq!(entry.value, json!("c"));
+        let stale = board.write(
+            "findings",
+            json!("d"),
+            WriteMode::Replace,
+            Some(2),
+            "goal_b",
+        );
+        assert!(stale.unwrap_err().contains("Conflict"));
+        assert_eq!(board.read("findings").unwrap().written_by, "goal_a");
+    }
+
+    #[tokio::test]
+    async fn test_agents_share_a_run_blackboard() {
+        let registry = Blackboards::default();
+        let board = registry.open("run_2", None);
+        registry.join("run_2", "researcher");
+        registry.join("run_2", "writer");
+        assert!(registry.for_goal("outsider").is_none());
+
+        let writer_board = registry.for_goal("writer").unwrap();
+        let waiting = tokio::spawn(async move {
+            writer_board
+                .wait_for("summary", 0, Duration::from_secs(5))
+                .await
+        });
+        let mut changes = board.subscribe();
+        registry
+            .for_goal("researcher")
+            .unwrap()
+            .write(
+                "summary",
+                json!("done"),
+                WriteMode::Replace,
+                None,
+                "researcher",
+            )
+            .unwrap();
+
+        let entry = waiting.await.unwrap().unwrap();
+        assert_eq!(entry.value, json!("done"));
+        let change = changes.recv().await.unwrap();
+        assert_eq!(change.key, "summary");
+        assert_eq!(change.written_by, "researcher");
+
+        assert!(registry.close("run_2"));
+        assert!(registry.for_goal("writer").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_artifacts() {
+        let board = Blackboard::new("run_3", None);
+        assert!(board
+            .wait_for_artifact("draft", Duration::from_millis(10))
+            .await
+            .is_none());
+        board
+            .put_artifact("draft", "text/markdown", "# Draft".to_string(), "writer")
+            .unwrap();
+        let info = board
+            .put_artifact("draft", "text/markdown", "# Draft 2".to_string(), "writer")
+            .unwrap();
+        assert_eq!(info.version, 2);
+        assert_eq!(board.artifacts()[0].size, 9);
+        assert_eq!(board.artifact("draft").unwrap().content, "# Draft 2");
+    }
+}
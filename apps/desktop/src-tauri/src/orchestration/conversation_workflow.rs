@@ -0,0 +1,509 @@
+//! Turn a chat conversation's tool calls into a draft workflow.
+//!
+//! Each successful tool call becomes a tool node, run in the order the agent
+//! made them. Literals worth changing between runs are lifted into workflow
+//! parameters and referenced from node inputs as `{{name}}`: values the user
+//! typed in the chat, values several calls share, and values of keys that
+//! name a target (paths, URLs, recipients, ...). The draft is not saved; the
+//! caller edits it and stores it with `create_workflow`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::workflow_engine::{
+    NodePosition, ToolNodeData, WorkflowDefinition, WorkflowEdge, WorkflowNode, WorkflowTrigger,
+};
+use crate::db::models::ToolCallRecord;
+
+/// Argument keys whose values are the target of a call rather than settings
+const TARGET_KEYS: &[&str] = &[
+    "path",
+    "file",
+    "file_path",
+    "dir",
+    "directory",
+    "cwd",
+    "workspace_path",
+    "url",
+    "to",
+    "recipient",
+    "email",
+    "channel",
+    "channel_id",
+    "query",
+    "repo",
+    "branch",
+];
+/// Shorter literals are too ambiguous to generalize
+const MIN_PARAM_LEN: usize = 3;
+/// Longer literals are content (file bodies, prompts), not parameters
+const MAX_PARAM_LEN: usize = 200;
+/// Shorter parameter values are only substituted when they are a whole value
+const MIN_EMBEDDED_LEN: usize = 6;
+
+const NODE_X: f64 = 250.0;
+const NODE_Y_START: f64 = 100.0;
+const NODE_Y_STEP: f64 = 150.0;
+
+/// A workflow input lifted out of the recorded tool calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowParameter {
+    pub name: String,
+    /// The literal the conversation used
+    pub default: Value,
+    pub description: String,
+    /// IDs of the nodes that reference it
+    pub used_by: Vec<String>,
+}
+
+/// A recorded tool call left out of the draft
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedToolCall {
+    pub tool_call_id: String,
+    pub tool_name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationWorkflowDraft {
+    pub workflow: WorkflowDefinition,
+    pub parameters: Vec<WorkflowParameter>,
+    pub skipped: Vec<SkippedToolCall>,
+}
+
+/// Build a draft workflow from a conversation's tool calls, oldest first.
+/// `user_messages` are the user's chat messages, used to tell which
+/// literals came from the user
+pub fn draft_from_tool_calls(
+    conversation_id: i64,
+    title: &str,
+    user_messages: &[String],
+    calls: &[ToolCallRecord],
+) -> Result<ConversationWorkflowDraft, String> {
+    let mut steps: Vec<(&ToolCallRecord, &Map<String, Value>)> = Vec::new();
+    let mut skipped = Vec::new();
+    for call in calls {
+        let skip = |reason: String| SkippedToolCall {
+            tool_call_id: call.id.clone(),
+            tool_name: call.name.clone(),
+            reason,
+        };
+        if !call.success {
+            let error = call.error.as_deref().unwrap_or("unknown error");
+            skipped.push(skip(format!("Call failed: {}", error)));
+            continue;
+        }
+        let Some(arguments) = call.arguments.as_object() else {
+            skipped.push(skip("Arguments are not a JSON object".to_string()));
+            continue;
+        };
+        if steps.last().is_some_and(|(previous, _)| {
+            previous.name == call.name && previous.arguments == call.arguments
+        }) {
+            skipped.push(skip("Repeats the previous call".to_string()));
+            continue;
+        }
+        steps.push((call, arguments));
+    }
+
+    if steps.is_empty() {
+        return Err("The conversation has no successful tool calls to convert".to_string());
+    }
+
+    let node_ids: Vec<String> = (1..=steps.len()).map(|n| format!("step_{}", n)).collect();
+    let mut parameters = choose_parameters(&steps, user_messages);
+    // Substitute the longest values first so a path is not split by a
+    // parameter that matches part of it
+    let mut by_length: Vec<usize> = (0..parameters.len()).collect();
+    by_length.sort_by_key(|&i| std::cmp::Reverse(parameter_value(&parameters[i]).len()));
+
+    let mut nodes = Vec::with_capacity(steps.len());
+    for (index, (call, arguments)) in steps.iter().enumerate() {
+        let node_id = &node_ids[index];
+        let mut used = Vec::new();
+        let tool_input: HashMap<String, Value> = arguments
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.clone(),
+                    generalize(value, &parameters, &by_length, &mut used),
+                )
+            })
+            .collect();
+        for param in used {
+            if !parameters[param].used_by.contains(node_id) {
+                parameters[param].used_by.push(node_id.clone());
+            }
+        }
+
+        nodes.push(WorkflowNode::ToolNode {
+            id: node_id.clone(),
+            position: NodePosition {
+                x: NODE_X,
+                y: NODE_Y_START + NODE_Y_STEP * index as f64,
+            },
+            data: ToolNodeData {
+                label: humanize(&call.name),
+                tool_name: call.name.clone(),
+                tool_input,
+                timeout_seconds: None,
+            },
+        });
+    }
+    parameters.retain(|param| !param.used_by.is_empty());
+
+    let edges = node_ids
+        .windows(2)
+        .enumerate()
+        .map(|(index, pair)| WorkflowEdge {
+            id: format!("edge_{}", index + 1),
+            source: pair[0].clone(),
+            target: pair[1].clone(),
+            source_handle: None,
+            target_handle: None,
+            condition: None,
+            label: None,
+        })
+        .collect();
+
+    let mut metadata = HashMap::new();
+    metadata.insert("source".to_string(), Value::from("conversation"));
+    metadata.insert("conversation_id".to_string(), Value::from(conversation_id));
+    metadata.insert(
+        "parameters".to_string(),
+        serde_json::to_value(&parameters).map_err(|e| e.to_string())?,
+    );
+
+    let now = chrono::Utc::now().timestamp();
+    let workflow = WorkflowDefinition {
+        id: String::new(),
+        user_id: "default_user".to_string(),
+        name: title.trim().to_string(),
+        description: user_messages
+            .first()
+            .map(|message| truncate(message.trim(), 280)),
+        nodes,
+        edges,
+        triggers: vec![WorkflowTrigger::Manual],
+        metadata,
+        created_at: now,
+        updated_at: now,
+    };
+
+    Ok(ConversationWorkflowDraft {
+        workflow,
+        parameters,
+        skipped,
+    })
+}
+
+fn parameter_value(param: &WorkflowParameter) -> &str {
+    param.default.as_str().unwrap_or_default()
+}
+
+/// Pick the string literals to lift into parameters, in first-use order
+fn choose_parameters(
+    steps: &[(&ToolCallRecord, &Map<String, Value>)],
+    user_messages: &[String],
+) -> Vec<WorkflowParameter> {
+    struct Literal {
+        key: String,
+        tools: Vec<String>,
+        nodes: Vec<usize>,
+    }
+
+    let mut order: Vec<String> = Vec::new();
+    let mut literals: HashMap<String, Literal> = HashMap::new();
+    for (index, (call, arguments)) in steps.iter().enumerate() {
+        let mut leaves = Vec::new();
+        for (key, value) in arguments.iter() {
+            string_leaves(key, value, &mut leaves);
+        }
+        for (key, value) in leaves {
+            let len = value.chars().count();
+            if !(MIN_PARAM_LEN..=MAX_PARAM_LEN).contains(&len) || value.contains("{{") {
+                continue;
+            }
+            let literal = literals.entry(value.to_string()).or_insert_with(|| {
+                order.push(value.to_string());
+                Literal {
+                    key: key.to_string(),
+                    tools: Vec::new(),
+                    nodes: Vec::new(),
+                }
+            });
+            if !literal.nodes.contains(&index) {
+                literal.nodes.push(index);
+            }
+            if !literal.tools.contains(&call.name) {
+                literal.tools.push(call.name.clone());
+            }
+        }
+    }
+
+    let mut names: HashMap<String, usize> = HashMap::new();
+    let mut parameters = Vec::new();
+    for value in order {
+        let literal = &literals[&value];
+        let from_user = user_messages.iter().any(|message| message.contains(&value));
+        let shared = literal.nodes.len() > 1;
+        let target = TARGET_KEYS.contains(&literal.key.to_ascii_lowercase().as_str());
+        if !(from_user || shared || target) {
+            continue;
+        }
+
+        let base = parameter_name(&literal.key);
+        let count = names.entry(base.clone()).or_insert(0);
+        *count += 1;
+        let name = match *count {
+            1 => base,
+            n => format!("{}_{}", base, n),
+        };
+        let description = format!(
+            "{} for {}{}",
+            literal.key,
+            literal.tools.join(", "),
+            if from_user {
+                " (given in the conversation)"
+            } else {
+                ""
+            }
+        );
+        parameters.push(WorkflowParameter {
+            name,
+            default: Value::String(value),
+            description,
+            used_by: Vec::new(),
+        });
+    }
+    parameters
+}
+
+/// `(key, value)` for every string in an argument, keyed by the nearest
+/// object key
+fn string_leaves<'a>(key: &'a str, value: &'a Value, out: &mut Vec<(&'a str, &'a str)>) {
+    match value {
+        Value::String(text) => out.push((key, text)),
+        Value::Array(items) => {
+            for item in items {
+                string_leaves(key, item, out);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                string_leaves(key, value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace parameter values in an argument with `{{name}}` placeholders,
+/// recording which parameters were used
+fn generalize(
+    value: &Value,
+    parameters: &[WorkflowParameter],
+    by_length: &[usize],
+    used: &mut Vec<usize>,
+) -> Value {
+    match value {
+        Value::String(text) => {
+            if let Some(index) = parameters
+                .iter()
+                .position(|param| parameter_value(param) == text)
+            {
+                used.push(index);
+                return Value::String(format!("{{{{{}}}}}", parameters[index].name));
+            }
+            let mut text = text.clone();
+            for &index in by_length {
+                let literal = parameter_value(&parameters[index]);
+                if literal.chars().count() >= MIN_EMBEDDED_LEN && text.contains(literal) {
+                    text = text.replace(literal, &format!("{{{{{}}}}}", parameters[index].name));
+                    used.push(index);
+                }
+            }
+            Value::String(text)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| generalize(item, parameters, by_length, used))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), generalize(value, parameters, by_length, used)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn parameter_name(key: &str) -> String {
+    let mut name = String::with_capacity(key.len());
+    for (i, c) in key.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !name.ends_with('_') {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_matches('_').to_string();
+    if name.is_empty() {
+        "value".to_string()
+    } else {
+        name
+    }
+}
+
+/// `file_read` -> `File Read`
+fn humanize(tool_name: &str) -> String {
+    tool_name
+        .split(['_', '-', '.'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(id: &str, name: &str, arguments: Value) -> ToolCallRecord {
+        ToolCallRecord {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments,
+            success: true,
+            error: None,
+        }
+    }
+
+    fn input(node: &WorkflowNode) -> &HashMap<String, Value> {
+        match node {
+            WorkflowNode::ToolNode { data, .. } => &data.tool_input,
+            _ => panic!("expected a tool node"),
+        }
+    }
+
+    #[test]
+    fn generalizes_user_and_shared_literals() {
+        let calls = vec![
+            call(
+                "1",
+                "file_read",
+                json!({ "path": "/home/ada/reports/q3.csv" }),
+            ),
+            call(
+                "1b",
+                "file_read",
+                json!({ "path": "/home/ada/reports/q3.csv" }),
+            ),
+            call(
+                "2",
+                "llm_reason",
+                json!({ "prompt": "Summarize the numbers", "model": "gpt-4o" }),
+            ),
+            call(
+                "3",
+                "email_send",
+                json!({
+                    "to": ["cfo@example.com"],
+                    "subject": "Q3 summary",
+                    "attachment": "/home/ada/reports/q3.csv"
+                }),
+            ),
+            ToolCallRecord {
+                success: false,
+                error: Some("timeout".to_string()),
+                ..call(
+                    "4",
+                    "slack_reply",
+                    json!({ "channel": "C1", "text": "done" }),
+                )
+            },
+        ];
+        let user = vec!["Summarize /home/ada/reports/q3.csv and mail it to the CFO".to_string()];
+
+        let draft = draft_from_tool_calls(7, "Q3 report", &user, &calls).unwrap();
+        let workflow = &draft.workflow;
+
+        assert_eq!(workflow.nodes.len(), 3);
+        assert_eq!(workflow.edges.len(), 2);
+        assert_eq!(workflow.edges[1].source, "step_2");
+        assert_eq!(draft.skipped.len(), 2);
+        assert_eq!(draft.skipped[0].tool_call_id, "1b");
+
+        let names: Vec<&str> = draft.parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["path", "to"]);
+        assert_eq!(draft.parameters[0].used_by, ["step_1", "step_3"]);
+
+        assert_eq!(input(&workflow.nodes[0])["path"], "{{path}}");
+        // Settings stay literal
+        assert_eq!(input(&workflow.nodes[1])["model"], "gpt-4o");
+        assert_eq!(input(&workflow.nodes[2])["to"], json!(["{{to}}"]));
+        assert_eq!(input(&workflow.nodes[2])["attachment"], "{{path}}");
+        assert_eq!(input(&workflow.nodes[2])["subject"], "Q3 summary");
+    }
+
+    #[test]
+    fn substitutes_parameters_inside_longer_values() {
+        let calls = vec![
+            call(
+                "1",
+                "terminal_execute",
+                json!({ "cwd": "/srv/app", "command": "make" }),
+            ),
+            call(
+                "2",
+                "llm_reason",
+                json!({ "prompt": "Explain the build log in /srv/app/build.log" }),
+            ),
+        ];
+
+        let draft = draft_from_tool_calls(1, "Release", &[], &calls).unwrap();
+        let names: Vec<&str> = draft.parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["cwd"]);
+        assert_eq!(draft.parameters[0].used_by, ["step_1", "step_2"]);
+        assert_eq!(input(&draft.workflow.nodes[0])["command"], "make");
+        assert_eq!(
+            input(&draft.workflow.nodes[1])["prompt"],
+            "Explain the build log in {{cwd}}/build.log"
+        );
+    }
+
+    #[test]
+    fn rejects_conversations_without_tool_calls() {
+        assert!(draft_from_tool_calls(1, "Chat", &[], &[]).is_err());
+    }
+
+    #[test]
+    fn names_and_labels() {
+        assert_eq!(parameter_name("filePath"), "file_path");
+        assert_eq!(parameter_name("--"), "value");
+        assert_eq!(humanize("browser.navigate_to"), "Browser Navigate To");
+    }
+}
@@ -1,7 +1,19 @@
+pub mod blackboard;
+pub mod conversation_workflow;
+pub mod workflow_artifacts;
+pub mod workflow_bundle;
 pub mod workflow_engine;
 pub mod workflow_executor;
 pub mod workflow_scheduler;
+pub mod workflow_simulator;
+pub mod workflow_triggers;
+pub mod workflow_webhooks;
 
+pub use workflow_artifacts::*;
+pub use workflow_bundle::*;
 pub use workflow_engine::*;
 pub use workflow_executor::*;
 pub use workflow_scheduler::*;
+pub use workflow_simulator::*;
+pub use workflow_triggers::*;
+pub use workflow_webhooks::*;
@@ -0,0 +1,490 @@
+//! Files produced by workflow steps (reports, exports, screenshots).
+//!
+//! A step hands its outputs to the [`ExecutionContext`](super::ExecutionContext);
+//! when the step finishes the executor stores them here, linked to the
+//! execution and to the step's log entry. Contents live in a content-addressed
+//! [`BlobStore`] so identical outputs across runs are kept once, and every
+//! artifact expires after its workflow's retention period.
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::workflow_engine::WorkflowDefinition;
+
+/// Workflow metadata key holding the artifact retention in days; `0` keeps
+/// artifacts forever
+pub const RETENTION_METADATA_KEY: &str = "artifact_retention_days";
+/// Retention for workflows that do not set one
+pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+/// Content-addressed file store: each blob is saved once under its SHA-256
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Store `bytes` and return their hash
+    pub fn put(&self, bytes: &[u8]) -> Result<String, String> {
+        let hash = format!("{:x}", Sha256::digest(bytes));
+        let path = self.path(&hash);
+        if path.exists() {
+            return Ok(hash);
+        }
+
+        let dir = path
+            .parent()
+            .ok_or_else(|| "Invalid blob path".to_string())?;
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create blob dir: {}", e))?;
+
+        // Write then rename so a crash never leaves a truncated blob behind
+        let partial = dir.join(format!("{}.{}.tmp", hash, Uuid::new_v4()));
+        std::fs::write(&partial, bytes).map_err(|e| format!("Failed to write blob: {}", e))?;
+        std::fs::rename(&partial, &path).map_err(|e| {
+            let _ = std::fs::remove_file(&partial);
+            format!("Failed to store blob: {}", e)
+        })?;
+
+        Ok(hash)
+    }
+
+    pub fn path(&self, hash: &str) -> PathBuf {
+        let shard = hash.get(..2).unwrap_or("00");
+        self.root.join(shard).join(hash)
+    }
+
+    pub fn read(&self, hash: &str) -> Result<Vec<u8>, String> {
+        std::fs::read(self.path(hash)).map_err(|e| format!("Failed to read blob: {}", e))
+    }
+
+    pub fn remove(&self, hash: &str) -> Result<(), String> {
+        match std::fs::remove_file(self.path(hash)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove blob: {}", e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Report,
+    Export,
+    Screenshot,
+    File,
+}
+
+impl std::fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactKind::Report => write!(f, "report"),
+            ArtifactKind::Export => write!(f, "export"),
+            ArtifactKind::Screenshot => write!(f, "screenshot"),
+            ArtifactKind::File => write!(f, "file"),
+        }
+    }
+}
+
+impl ArtifactKind {
+    fn parse(value: &str) -> Self {
+        match value {
+            "report" => ArtifactKind::Report,
+            "export" => ArtifactKind::Export,
+            "screenshot" => ArtifactKind::Screenshot,
+            _ => ArtifactKind::File,
+        }
+    }
+}
+
+/// Where a new artifact's contents come from
+#[derive(Debug, Clone)]
+pub enum ArtifactContent {
+    Bytes(Vec<u8>),
+    /// A file the step wrote to disk; it is copied into the store
+    File(PathBuf),
+}
+
+/// An output a step registers with the execution context
+#[derive(Debug, Clone)]
+pub struct NewArtifact {
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub mime_type: Option<String>,
+    pub content: ArtifactContent,
+    pub metadata: HashMap<String, Value>,
+}
+
+impl NewArtifact {
+    pub fn new(name: impl Into<String>, kind: ArtifactKind, content: ArtifactContent) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            mime_type: None,
+            content,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+}
+
+/// A stored artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowArtifact {
+    pub id: String,
+    pub execution_id: String,
+    pub workflow_id: String,
+    pub node_id: String,
+    /// Log entry of the step run that produced it
+    pub log_id: Option<String>,
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub mime_type: Option<String>,
+    pub size_bytes: i64,
+    pub content_hash: String,
+    pub metadata: HashMap<String, Value>,
+    /// Location of the contents in the blob store
+    pub path: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+/// The step run an artifact belongs to
+pub struct ArtifactOrigin<'a> {
+    pub execution_id: &'a str,
+    pub workflow_id: &'a str,
+    pub node_id: &'a str,
+    pub log_id: Option<&'a str>,
+}
+
+/// Retention for `workflow` in days, `None` to keep its artifacts forever
+pub fn retention_days(workflow: &WorkflowDefinition) -> Option<i64> {
+    match workflow
+        .metadata
+        .get(RETENTION_METADATA_KEY)
+        .and_then(Value::as_i64)
+    {
+        Some(days) if days <= 0 => None,
+        Some(days) => Some(days),
+        None => Some(DEFAULT_RETENTION_DAYS),
+    }
+}
+
+pub struct ArtifactStore {
+    db_path: String,
+    blobs: BlobStore,
+}
+
+impl ArtifactStore {
+    pub fn new(db_path: String, blob_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            db_path,
+            blobs: BlobStore::new(blob_dir),
+        }
+    }
+
+    fn get_connection(&self) -> Result<rusqlite::Connection, String> {
+        rusqlite::Connection::open(&self.db_path)
+            .map_err(|e| format!("Failed to open database: {}", e))
+    }
+
+    pub fn blobs(&self) -> &BlobStore {
+        &self.blobs
+    }
+
+    /// Store an artifact for the step run `origin`
+    pub fn register(
+        &self,
+        origin: &ArtifactOrigin<'_>,
+        retention_days: Option<i64>,
+        artifact: NewArtifact,
+    ) -> Result<WorkflowArtifact, String> {
+        let bytes = match &artifact.content {
+            ArtifactContent::Bytes(bytes) => bytes.clone(),
+            ArtifactContent::File(path) => std::fs::read(path)
+                .map_err(|e| format!("Failed to read artifact {}: {}", path.display(), e))?,
+        };
+        let content_hash = self.blobs.put(&bytes)?;
+
+        let now = Utc::now();
+        let stored = WorkflowArtifact {
+            id: Uuid::new_v4().to_string(),
+            execution_id: origin.execution_id.to_string(),
+            workflow_id: origin.workflow_id.to_string(),
+            node_id: origin.node_id.to_string(),
+            log_id: origin.log_id.map(str::to_string),
+            name: artifact.name,
+            kind: artifact.kind,
+            mime_type: artifact
+                .mime_type
+                .or_else(|| guess_mime_type(&artifact.content)),
+            size_bytes: bytes.len() as i64,
+            path: self.blobs.path(&content_hash).to_string_lossy().to_string(),
+            content_hash,
+            metadata: artifact.metadata,
+            created_at: now.timestamp(),
+            expires_at: retention_days.map(|days| (now + Duration::days(days)).timestamp()),
+        };
+
+        let metadata_json = serde_json::to_string(&stored.metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT INTO workflow_artifacts (id, execution_id, workflow_id, node_id, log_id, name, kind, mime_type, size_bytes, content_hash, metadata, created_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            rusqlite::params![
+                &stored.id,
+                &stored.execution_id,
+                &stored.workflow_id,
+                &stored.node_id,
+                &stored.log_id,
+                &stored.name,
+                stored.kind.to_string(),
+                &stored.mime_type,
+                stored.size_bytes,
+                &stored.content_hash,
+                &metadata_json,
+                stored.created_at,
+                stored.expires_at,
+            ],
+        )
+        .map_err(|e| format!("Failed to register artifact: {}", e))?;
+
+        Ok(stored)
+    }
+
+    /// Artifacts of an execution in the order they were produced
+    pub fn list(&self, execution_id: &str) -> Result<Vec<WorkflowArtifact>, String> {
+        let conn = self.get_connection()?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, execution_id, workflow_id, node_id, log_id, name, kind, mime_type, size_bytes, content_hash, metadata, created_at, expires_at
+                 FROM workflow_artifacts WHERE execution_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+        let artifacts = stmt
+            .query_map(rusqlite::params![execution_id], |row| {
+                let kind: String = row.get(6)?;
+                let content_hash: String = row.get(9)?;
+                let metadata_json: String = row.get(10)?;
+
+                Ok(WorkflowArtifact {
+                    id: row.get(0)?,
+                    execution_id: row.get(1)?,
+                    workflow_id: row.get(2)?,
+                    node_id: row.get(3)?,
+                    log_id: row.get(4)?,
+                    name: row.get(5)?,
+                    kind: ArtifactKind::parse(&kind),
+                    mime_type: row.get(7)?,
+                    size_bytes: row.get(8)?,
+                    path: self.blobs.path(&content_hash).to_string_lossy().to_string(),
+                    content_hash,
+                    metadata: serde_json::from_str(&metadata_json).unwrap_or_default(),
+                    created_at: row.get(11)?,
+                    expires_at: row.get(12)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query artifacts: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect artifacts: {}", e))?;
+
+        Ok(artifacts)
+    }
+
+    /// Drop artifacts past their retention and any blobs no longer referenced;
+    /// returns how many artifacts were removed
+    pub fn prune_expired(&self) -> Result<usize, String> {
+        let conn = self.get_connection()?;
+        let now = Utc::now().timestamp();
+
+        let hashes = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT DISTINCT content_hash FROM workflow_artifacts
+                     WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                )
+                .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            let hashes = stmt
+                .query_map(rusqlite::params![now], |row| row.get::<_, String>(0))
+                .map_err(|e| format!("Failed to query expired artifacts: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to collect expired artifacts: {}", e))?;
+            hashes
+        };
+
+        let removed = conn
+            .execute(
+                "DELETE FROM workflow_artifacts WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                rusqlite::params![now],
+            )
+            .map_err(|e| format!("Failed to prune artifacts: {}", e))?;
+
+        for hash in hashes {
+            let referenced = conn
+                .prepare("SELECT 1 FROM workflow_artifacts WHERE content_hash = ?1")
+                .and_then(|mut stmt| stmt.exists(rusqlite::params![&hash]))
+                .map_err(|e| format!("Failed to check blob references: {}", e))?;
+            if !referenced {
+                self.blobs.remove(&hash)?;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+fn guess_mime_type(content: &ArtifactContent) -> Option<String> {
+    let ArtifactContent::File(path) = content else {
+        return None;
+    };
+    let mime = match extension(path)?.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "pdf" => "application/pdf",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "html" => "text/html",
+        "md" => "text/markdown",
+        "txt" | "log" => "text/plain",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => return None,
+    };
+    Some(mime.to_string())
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path) -> ArtifactStore {
+        let db_path = dir.join("test.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE workflow_artifacts (
+                id TEXT PRIMARY KEY,
+                execution_id TEXT NOT NULL,
+                workflow_id TEXT NOT NULL,
+                node_id TEXT NOT NULL,
+                log_id TEXT,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                mime_type TEXT,
+                size_bytes INTEGER NOT NULL,
+                content_hash TEXT NOT NULL,
+                metadata TEXT NOT NULL DEFAULT '{}',
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER
+            );",
+        )
+        .unwrap();
+        ArtifactStore::new(db_path.to_string_lossy().to_string(), dir.join("blobs"))
+    }
+
+    #[test]
+    fn test_artifacts_share_blobs_and_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+
+        let screenshot = dir.path().join("step.png");
+        std::fs::write(&screenshot, b"png-bytes").unwrap();
+
+        let origin = ArtifactOrigin {
+            execution_id: "exec-1",
+            workflow_id: "wf-1",
+            node_id: "node-1",
+            log_id: Some("log-1"),
+        };
+        let kept = store
+            .register(
+                &origin,
+                None,
+                NewArtifact::new(
+                    "step.png",
+                    ArtifactKind::Screenshot,
+                    ArtifactContent::File(screenshot),
+                ),
+            )
+            .unwrap();
+        let expired = store
+            .register(
+                &origin,
+                Some(-1),
+                NewArtifact::new(
+                    "copy.png",
+                    ArtifactKind::Screenshot,
+                    ArtifactContent::Bytes(b"png-bytes".to_vec()),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(kept.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(kept.content_hash, expired.content_hash);
+        assert_eq!(store.list("exec-1").unwrap().len(), 2);
+
+        // The expired copy goes, but its blob stays for the artifact still using it
+        assert_eq!(store.prune_expired().unwrap(), 1);
+        let remaining = store.list("exec-1").unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].log_id.as_deref(), Some("log-1"));
+        assert_eq!(
+            store.blobs().read(&kept.content_hash).unwrap(),
+            b"png-bytes"
+        );
+    }
+
+    #[test]
+    fn test_retention_from_workflow_metadata() {
+        let mut workflow: WorkflowDefinition = serde_json::from_value(serde_json::json!({
+            "id": "wf-1",
+            "user_id": "user",
+            "name": "Report",
+            "description": null,
+            "nodes": [],
+            "edges": [],
+            "triggers": [],
+            "metadata": {},
+            "created_at": 0,
+            "updated_at": 0
+        }))
+        .unwrap();
+        assert_eq!(retention_days(&workflow), Some(DEFAULT_RETENTION_DAYS));
+
+        workflow
+            .metadata
+            .insert(RETENTION_METADATA_KEY.to_string(), Value::from(7));
+        assert_eq!(retention_days(&workflow), Some(7));
+
+        workflow
+            .metadata
+            .insert(RETENTION_METADATA_KEY.to_string(), Value::from(0));
+        assert_eq!(retention_days(&workflow), None);
+    }
+}
@@ -0,0 +1,782 @@
+//! Portable workflow bundles.
+//!
+//! A bundle is a single JSON or YAML file holding a workflow, the
+//! sub-workflows it runs, the agent templates its agent nodes use and the
+//! integrations, tools and permissions they need. Secrets in node settings
+//! are replaced by `{{credential:<name>}}` placeholders that are filled in on
+//! import. Bundles are signed with an HMAC under a key kept by this install:
+//! a bundle it exported is checked for changes on import, while bundles from
+//! other installs import as unverified.
+
+use super::workflow_engine::*;
+use super::workflow_executor::DEPENDENCY_SCAN_TOOL;
+use crate::agi::templates::{
+    AgentTemplate, IntegrationAccount, MissingRequirement, TemplateManager, TemplateRequirements,
+};
+use crate::security::SecretManager;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const BUNDLE_FORMAT: &str = "agiworkforce.workflow-bundle";
+/// Newest bundle version this build reads
+pub const BUNDLE_VERSION: u32 = 1;
+const SIGNING_KEY_SECRET: &str = "workflow_bundles.signing_key";
+
+/// Settings whose values are treated as secrets
+static SENSITIVE_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)(api[_-]?key|token|secret|password|passwd|authorization|credential|private[_-]?key)",
+    )
+    .unwrap()
+});
+static CREDENTIAL_PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{credential:([^}]+)\}\}").unwrap());
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleEncoding {
+    #[default]
+    Json,
+    Yaml,
+}
+
+/// A secret removed from a node's settings on export
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialPlaceholder {
+    /// Appears in the setting as `{{credential:<name>}}`
+    pub name: String,
+    pub workflow_id: String,
+    pub node_id: String,
+    /// Path of the setting, e.g. `tool_input.headers.authorization`
+    pub field: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSignature {
+    /// Start of the signing key's SHA-256, identifying the install
+    pub key_id: String,
+    /// Hex HMAC-SHA256 of the bundle's canonical JSON without its signature
+    pub hmac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    /// The exported workflow first, then the sub-workflows it runs
+    pub workflows: Vec<WorkflowDefinition>,
+    /// Templates used by agent nodes
+    #[serde(default)]
+    pub templates: Vec<AgentTemplate>,
+    #[serde(default)]
+    pub requirements: TemplateRequirements,
+    #[serde(default)]
+    pub credentials: Vec<CredentialPlaceholder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<BundleSignature>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Signed by this install and unchanged since
+    Verified,
+    Unsigned,
+    /// Signed by another install, whose key is not known here
+    UnknownSigner,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BundleImportOptions {
+    /// Local account id per integration. An integration with exactly one
+    /// connected account uses it unless another is chosen
+    pub accounts: HashMap<String, String>,
+    /// Secret per credential placeholder name
+    pub credentials: HashMap<String, String>,
+    /// Only validate the bundle and report what is missing
+    pub check_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrationMapping {
+    pub integration: String,
+    /// Account the imported workflow uses
+    pub account_id: Option<String>,
+    /// Connected accounts to choose from
+    pub accounts: Vec<IntegrationAccount>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleImportReport {
+    /// Id of the imported workflow; unset when only checking
+    pub workflow_id: Option<String>,
+    pub name: String,
+    pub signature: SignatureStatus,
+    pub integrations: Vec<IntegrationMapping>,
+    /// Integrations, tools and permissions this install lacks
+    pub missing: Vec<MissingRequirement>,
+    /// Placeholders no secret was given for; their settings keep the placeholder
+    pub missing_credentials: Vec<CredentialPlaceholder>,
+    /// Templates added to the library; ones already present are kept
+    pub templates_added: Vec<String>,
+}
+
+impl WorkflowBundle {
+    /// Package a saved workflow with its sub-workflows and the agent
+    /// templates `template` loads by id
+    pub fn export(
+        engine: &WorkflowEngine,
+        workflow_id: &str,
+        template: impl Fn(&str) -> Result<Option<AgentTemplate>, String>,
+    ) -> Result<Self, String> {
+        let mut workflows = vec![engine.get_workflow(workflow_id)?];
+        let mut seen = HashSet::from([workflow_id.to_string()]);
+        let mut index = 0;
+        while index < workflows.len() {
+            let ids: Vec<String> = workflows[index]
+                .sub_workflow_ids()
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            for id in ids {
+                if seen.insert(id.clone()) {
+                    workflows.push(engine.get_workflow(&id)?);
+                }
+            }
+            index += 1;
+        }
+
+        let template_ids: BTreeSet<&str> = workflows
+            .iter()
+            .flat_map(|workflow| &workflow.nodes)
+            .filter_map(|node| match node {
+                WorkflowNode::AgentNode { data, .. } => data.agent_template_id.as_deref(),
+                _ => None,
+            })
+            .collect();
+        let templates = template_ids
+            .into_iter()
+            .map(|id| template(id)?.ok_or_else(|| format!("Agent template {} not found", id)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self::new(workflows, templates))
+    }
+
+    /// Bundle workflows, the exported one first, replacing secrets with
+    /// placeholders
+    pub fn new(mut workflows: Vec<WorkflowDefinition>, templates: Vec<AgentTemplate>) -> Self {
+        let mut credentials = Vec::new();
+        for workflow in &mut workflows {
+            scrub_credentials(workflow, &mut credentials);
+        }
+        let requirements = collect_requirements(&workflows, &templates);
+
+        Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: Utc::now().timestamp(),
+            workflows,
+            templates,
+            requirements,
+            credentials,
+            signature: None,
+        }
+    }
+
+    /// Read a JSON or YAML bundle, checking its format, version and graphs
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let value: Value = if contents.trim_start().starts_with('{') {
+            serde_json::from_str(contents).map_err(|e| format!("Invalid workflow bundle: {}", e))?
+        } else {
+            serde_yaml::from_str(contents).map_err(|e| format!("Invalid workflow bundle: {}", e))?
+        };
+        if value.get("format").and_then(Value::as_str) != Some(BUNDLE_FORMAT) {
+            return Err("Not a workflow bundle".to_string());
+        }
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or("Workflow bundle has no version")?;
+        if version > BUNDLE_VERSION as u64 {
+            return Err(format!(
+                "Workflow bundle version {} is newer than this app supports ({}); update AGI Workforce to import it",
+                version, BUNDLE_VERSION
+            ));
+        }
+
+        let bundle: Self =
+            serde_json::from_value(value).map_err(|e| format!("Invalid workflow bundle: {}", e))?;
+        let root = bundle
+            .workflows
+            .first()
+            .ok_or("Workflow bundle contains no workflow")?;
+        root.validate(|id| {
+            bundle
+                .workflow(id)
+                .cloned()
+                .ok_or_else(|| format!("Sub-workflow {} not found", id))
+        })?;
+        Ok(bundle)
+    }
+
+    pub fn encode(&self, encoding: BundleEncoding) -> Result<String, String> {
+        match encoding {
+            BundleEncoding::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+            BundleEncoding::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("Failed to encode workflow bundle: {}", e))
+    }
+
+    fn workflow(&self, id: &str) -> Option<&WorkflowDefinition> {
+        self.workflows.iter().find(|workflow| workflow.id == id)
+    }
+
+    fn signing_payload(&self) -> Result<String, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+        Ok(canonical_json(&value))
+    }
+
+    fn mac(&self, key: &[u8]) -> Result<HmacSha256, String> {
+        let mut mac = HmacSha256::new_from_slice(key).map_err(|e| e.to_string())?;
+        mac.update(self.signing_payload()?.as_bytes());
+        Ok(mac)
+    }
+
+    pub fn sign(&mut self, key: &[u8]) -> Result<(), String> {
+        self.signature = Some(BundleSignature {
+            key_id: key_id(key),
+            hmac: hex::encode(self.mac(key)?.finalize().into_bytes()),
+        });
+        Ok(())
+    }
+
+    /// Check the signature against this install's key. A bundle this install
+    /// signed that was changed afterwards is rejected
+    pub fn verify(&self, key: &[u8]) -> Result<SignatureStatus, String> {
+        let Some(signature) = &self.signature else {
+            return Ok(SignatureStatus::Unsigned);
+        };
+        if signature.key_id != key_id(key) {
+            return Ok(SignatureStatus::UnknownSigner);
+        }
+        let expected = hex::decode(&signature.hmac).map_err(|_| "Malformed bundle signature")?;
+        self.mac(key)?
+            .verify_slice(&expected)
+            .map_err(|_| "Workflow bundle was modified after it was signed".to_string())?;
+        Ok(SignatureStatus::Verified)
+    }
+
+    /// Save the workflows under new ids for `user_id`, filling in credential
+    /// placeholders and recording the account chosen per integration on the
+    /// exported workflow. Returns its new id
+    pub fn install(
+        &self,
+        engine: &WorkflowEngine,
+        user_id: &str,
+        accounts: &HashMap<String, String>,
+        credentials: &HashMap<String, String>,
+    ) -> Result<String, String> {
+        let ids: HashMap<&str, String> = self
+            .workflows
+            .iter()
+            .map(|workflow| (workflow.id.as_str(), Uuid::new_v4().to_string()))
+            .collect();
+        let root_id = self.workflows[0].id.as_str();
+
+        let mut created = Vec::new();
+        for original in self.install_order() {
+            let mut workflow = original.clone();
+            workflow.id = ids[original.id.as_str()].clone();
+            workflow.user_id = user_id.to_string();
+            for node in &mut workflow.nodes {
+                if let WorkflowNode::SubWorkflowNode { data, .. } = node {
+                    if let Some(id) = ids.get(data.workflow_id.as_str()) {
+                        data.workflow_id = id.clone();
+                    }
+                }
+            }
+            fill_credentials(&mut workflow, credentials)?;
+            if original.id == root_id && !accounts.is_empty() {
+                workflow
+                    .metadata
+                    .insert("accounts".to_string(), serde_json::json!(accounts));
+            }
+
+            match engine.create_workflow(workflow) {
+                Ok(id) => created.push(id),
+                Err(e) => {
+                    for id in &created {
+                        let _ = engine.delete_workflow(id);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(ids[root_id].clone())
+    }
+
+    /// Workflows ordered so each one's sub-workflows come before it
+    fn install_order(&self) -> Vec<&WorkflowDefinition> {
+        fn visit<'a>(
+            bundle: &'a WorkflowBundle,
+            workflow: &'a WorkflowDefinition,
+            done: &mut HashSet<&'a str>,
+            order: &mut Vec<&'a WorkflowDefinition>,
+        ) {
+            if !done.insert(workflow.id.as_str()) {
+                return;
+            }
+            for id in workflow.sub_workflow_ids() {
+                if let Some(sub_workflow) = bundle.workflow(id) {
+                    visit(bundle, sub_workflow, done, order);
+                }
+            }
+            order.push(workflow);
+        }
+
+        let mut order = Vec::new();
+        visit(self, &self.workflows[0], &mut HashSet::new(), &mut order);
+        order
+    }
+}
+
+/// Package a workflow into a signed bundle file
+pub fn export_bundle(
+    engine: &WorkflowEngine,
+    templates: &TemplateManager,
+    key: &[u8],
+    workflow_id: &str,
+    encoding: BundleEncoding,
+) -> Result<String, String> {
+    let mut bundle = WorkflowBundle::export(engine, workflow_id, |id| {
+        templates.get_template_by_id(id).map_err(|e| e.to_string())
+    })?;
+    bundle.sign(key)?;
+    bundle.encode(encoding)
+}
+
+/// Check a bundle file against this install and, unless `check_only`,
+/// import it for `user_id`. Missing prerequisites are reported, not refused
+pub fn import_bundle(
+    engine: &WorkflowEngine,
+    templates: &TemplateManager,
+    key: &[u8],
+    user_id: &str,
+    contents: &str,
+    options: &BundleImportOptions,
+) -> Result<BundleImportReport, String> {
+    let bundle = WorkflowBundle::parse(contents)?;
+    let signature = bundle.verify(key)?;
+    let missing = templates
+        .missing_requirements(user_id, &bundle.requirements)
+        .map_err(|e| format!("Failed to check requirements: {}", e))?;
+
+    let mut integrations = Vec::new();
+    for integration in &bundle.requirements.integrations {
+        let accounts = templates
+            .integration_accounts(integration)
+            .map_err(|e| format!("Failed to list {} accounts: {}", integration, e))?
+            .unwrap_or_default();
+        let account_id = match options.accounts.get(integration) {
+            Some(id) if accounts.iter().any(|account| &account.id == id) => Some(id.clone()),
+            Some(id) => return Err(format!("No connected {} account {}", integration, id)),
+            None if accounts.len() == 1 => Some(accounts[0].id.clone()),
+            None => None,
+        };
+        integrations.push(IntegrationMapping {
+            integration: integration.clone(),
+            account_id,
+            accounts,
+        });
+    }
+
+    let mut report = BundleImportReport {
+        workflow_id: None,
+        name: bundle.workflows[0].name.clone(),
+        signature,
+        integrations,
+        missing,
+        missing_credentials: bundle
+            .credentials
+            .iter()
+            .filter(|credential| !options.credentials.contains_key(&credential.name))
+            .cloned()
+            .collect(),
+        templates_added: Vec::new(),
+    };
+    if options.check_only {
+        return Ok(report);
+    }
+
+    for template in &bundle.templates {
+        let exists = templates
+            .get_template_by_id(&template.id)
+            .map_err(|e| e.to_string())?
+            .is_some();
+        if !exists {
+            templates
+                .save_template(template)
+                .map_err(|e| format!("Failed to save template {}: {}", template.name, e))?;
+            report.templates_added.push(template.id.clone());
+        }
+    }
+    let accounts = report
+        .integrations
+        .iter()
+        .filter_map(|mapping| {
+            mapping
+                .account_id
+                .clone()
+                .map(|id| (mapping.integration.clone(), id))
+        })
+        .collect();
+    report.workflow_id = Some(bundle.install(engine, user_id, &accounts, &options.credentials)?);
+    Ok(report)
+}
+
+/// This install's bundle signing key, created on first use
+pub fn signing_key(secrets: &SecretManager) -> Result<Vec<u8>, String> {
+    if let Some(key) = secrets
+        .get_secret(SIGNING_KEY_SECRET)
+        .ok()
+        .and_then(|key| hex::decode(key).ok())
+    {
+        return Ok(key);
+    }
+    let key: [u8; 32] = rand::random();
+    secrets
+        .store_secret(SIGNING_KEY_SECRET, &hex::encode(key))
+        .map_err(|e| format!("Failed to store bundle signing key: {}", e))?;
+    Ok(key.to_vec())
+}
+
+fn key_id(key: &[u8]) -> String {
+    hex::encode(&Sha256::digest(key)[..8])
+}
+
+/// JSON with object keys sorted, so equal bundles sign identically
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            let entries: Vec<String> = keys
+                .into_iter()
+                .map(|key| {
+                    format!(
+                        "{}:{}",
+                        Value::from(key.as_str()),
+                        canonical_json(&fields[key])
+                    )
+                })
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Integrations, tools and permissions declared by the templates, the
+/// workflows' `integrations` metadata and their tool nodes
+fn collect_requirements(
+    workflows: &[WorkflowDefinition],
+    templates: &[AgentTemplate],
+) -> TemplateRequirements {
+    let mut integrations = BTreeSet::new();
+    let mut tools = BTreeSet::new();
+    let mut permissions = BTreeSet::new();
+    for template in templates {
+        integrations.extend(template.requirements.integrations.iter().cloned());
+        tools.extend(template.requirements.tools.iter().cloned());
+        permissions.extend(template.requirements.permissions.iter().cloned());
+    }
+    for workflow in workflows {
+        if let Some(Value::Array(names)) = workflow.metadata.get("integrations") {
+            integrations.extend(names.iter().filter_map(Value::as_str).map(str::to_string));
+        }
+        for node in &workflow.nodes {
+            if let WorkflowNode::ToolNode { data, .. } = node {
+                // The executor runs these itself
+                if data.tool_name != DEPENDENCY_SCAN_TOOL {
+                    tools.insert(data.tool_name.clone());
+                }
+            }
+        }
+    }
+
+    TemplateRequirements {
+        integrations: integrations.into_iter().collect(),
+        tools: tools.into_iter().collect(),
+        permissions: permissions.into_iter().collect(),
+    }
+}
+
+/// Replace secret values in the workflow's node settings with placeholders
+/// named after the node and setting
+fn scrub_credentials(workflow: &mut WorkflowDefinition, found: &mut Vec<CredentialPlaceholder>) {
+    fn scrub(
+        value: &mut Value,
+        prefix: &str,
+        field: &str,
+        sensitive: bool,
+        fields: &mut Vec<String>,
+    ) {
+        let join = |key: &str| {
+            if field.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", field, key)
+            }
+        };
+        match value {
+            Value::Object(entries) => {
+                for (key, entry) in entries.iter_mut() {
+                    let sensitive = sensitive || SENSITIVE_KEY.is_match(key);
+                    scrub(entry, prefix, &join(key), sensitive, fields);
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    scrub(item, prefix, &join(&index.to_string()), sensitive, fields);
+                }
+            }
+            // Variables and placeholders are not secrets
+            Value::String(text) if sensitive && !text.is_empty() && !text.contains("{{") => {
+                *text = format!("{{{{credential:{}.{}}}}}", prefix, field);
+                fields.push(field.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    for node in &mut workflow.nodes {
+        let Ok(mut value) = serde_json::to_value(&*node) else {
+            continue;
+        };
+        let node_id = node.id().to_string();
+        // Sub-workflows may reuse node ids
+        let prefix = if found.iter().any(|credential| credential.node_id == node_id) {
+            format!("{}.{}", workflow.id, node_id)
+        } else {
+            node_id.clone()
+        };
+        let mut fields = Vec::new();
+        if let Some(data) = value.get_mut("data") {
+            scrub(data, &prefix, "", false, &mut fields);
+        }
+        if fields.is_empty() {
+            continue;
+        }
+        let Ok(scrubbed) = serde_json::from_value(value) else {
+            continue;
+        };
+        *node = scrubbed;
+        found.extend(fields.into_iter().map(|field| CredentialPlaceholder {
+            name: format!("{}.{}", prefix, field),
+            workflow_id: workflow.id.clone(),
+            node_id: node_id.clone(),
+            field,
+        }));
+    }
+}
+
+/// Substitute the given secrets for their placeholders
+fn fill_credentials(
+    workflow: &mut WorkflowDefinition,
+    credentials: &HashMap<String, String>,
+) -> Result<(), String> {
+    fn fill(value: &mut Value, credentials: &HashMap<String, String>) {
+        match value {
+            Value::Object(fields) => fields
+                .values_mut()
+                .for_each(|field| fill(field, credentials)),
+            Value::Array(items) => items.iter_mut().for_each(|item| fill(item, credentials)),
+            Value::String(text) => {
+                let filled = CREDENTIAL_PLACEHOLDER.replace_all(text, |caps: &regex::Captures| {
+                    credentials
+                        .get(&caps[1])
+                        .cloned()
+                        .unwrap_or_else(|| caps[0].to_string())
+                });
+                *text = filled.into_owned();
+            }
+            _ => {}
+        }
+    }
+
+    if credentials.is_empty() {
+        return Ok(());
+    }
+    for node in &mut workflow.nodes {
+        let mut value = serde_json::to_value(&*node).map_err(|e| e.to_string())?;
+        fill(&mut value, credentials);
+        *node = serde_json::from_value(value)
+            .map_err(|e| format!("Failed to fill credentials in {}: {}", node.id(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn engine(dir: &std::path::Path) -> WorkflowEngine {
+        let db_path = dir.join("test.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE workflow_definitions (
+                id TEXT PRIMARY KEY, user_id TEXT NOT NULL, name TEXT NOT NULL,
+                description TEXT, nodes TEXT NOT NULL, edges TEXT NOT NULL,
+                triggers TEXT, metadata TEXT, created_at INTEGER, updated_at INTEGER
+            );",
+        )
+        .unwrap();
+        WorkflowEngine::new(db_path.to_string_lossy().to_string())
+    }
+
+    fn workflow(id: &str, nodes: Vec<Value>) -> WorkflowDefinition {
+        serde_json::from_value(json!({
+            "id": id,
+            "user_id": "exporter",
+            "name": id,
+            "description": null,
+            "nodes": nodes,
+            "edges": [],
+            "triggers": [],
+            "metadata": { "integrations": ["slack"] },
+            "created_at": 0,
+            "updated_at": 0,
+        }))
+        .unwrap()
+    }
+
+    fn bundle() -> WorkflowBundle {
+        let root = workflow(
+            "root",
+            vec![
+                json!({
+                    "id": "call", "type": "tool", "position": { "x": 0, "y": 0 },
+                    "data": {
+                        "label": "Call", "tool_name": "http_request",
+                        "tool_input": {
+                            "url": "https://example.com",
+                            "headers": { "Authorization": "Bearer abc" },
+                            "api_key": "{{api_key}}",
+                        },
+                    },
+                }),
+                json!({
+                    "id": "child", "type": "sub_workflow", "position": { "x": 0, "y": 100 },
+                    "data": { "label": "Child", "workflow_id": "child" },
+                }),
+            ],
+        );
+        let child = workflow(
+            "child",
+            vec![json!({
+                "id": "call", "type": "tool", "position": { "x": 0, "y": 0 },
+                "data": {
+                    "label": "Call", "tool_name": "dependency_scan",
+                    "tool_input": { "token": "secret-2" },
+                },
+            })],
+        );
+        WorkflowBundle::new(vec![root, child], Vec::new())
+    }
+
+    #[test]
+    fn test_bundle_scrubs_credentials_and_signs() {
+        let mut bundle = bundle();
+        let names: Vec<&str> = bundle.credentials.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "call.tool_input.headers.Authorization",
+                "child.call.tool_input.token"
+            ]
+        );
+        let json = serde_json::to_string(&bundle.workflows).unwrap();
+        assert!(!json.contains("Bearer abc") && !json.contains("secret-2"));
+        // Variables are left in place
+        assert!(json.contains("{{api_key}}"));
+        assert_eq!(bundle.requirements.integrations, ["slack"]);
+        assert_eq!(bundle.requirements.tools, ["http_request"]);
+
+        let key = [7u8; 32];
+        bundle.sign(&key).unwrap();
+        for encoding in [BundleEncoding::Json, BundleEncoding::Yaml] {
+            let text = bundle.encode(encoding).unwrap();
+            let parsed = WorkflowBundle::parse(&text).unwrap();
+            assert_eq!(parsed.verify(&key).unwrap(), SignatureStatus::Verified);
+            assert_eq!(
+                parsed.verify(&[8u8; 32]).unwrap(),
+                SignatureStatus::UnknownSigner
+            );
+        }
+
+        let tampered = bundle
+            .encode(BundleEncoding::Json)
+            .unwrap()
+            .replace("https://example.com", "https://attacker.example");
+        let parsed = WorkflowBundle::parse(&tampered).unwrap();
+        assert!(parsed.verify(&key).is_err());
+
+        bundle.version = BUNDLE_VERSION + 1;
+        let newer = bundle.encode(BundleEncoding::Json).unwrap();
+        assert!(WorkflowBundle::parse(&newer).unwrap_err().contains("newer"));
+        assert!(WorkflowBundle::parse("format: other\nversion: 1").is_err());
+    }
+
+    #[test]
+    fn test_install_remaps_ids_and_fills_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine(dir.path());
+        let bundle = bundle();
+        let accounts = HashMap::from([("slack".to_string(), "T01".to_string())]);
+        let credentials = HashMap::from([(
+            "call.tool_input.headers.Authorization".to_string(),
+            "Bearer local".to_string(),
+        )]);
+
+        let root_id = bundle
+            .install(&engine, "importer", &accounts, &credentials)
+            .unwrap();
+        assert_ne!(root_id, "root");
+        let root = engine.get_workflow(&root_id).unwrap();
+        assert_eq!(root.user_id, "importer");
+        assert_eq!(root.metadata["accounts"], json!({ "slack": "T01" }));
+        let child_id = root.sub_workflow_ids()[0].to_string();
+        assert_ne!(child_id, "child");
+
+        let root_json = serde_json::to_string(&root.nodes).unwrap();
+        assert!(root_json.contains("Bearer local"));
+        let child = engine.get_workflow(&child_id).unwrap();
+        let child_json = serde_json::to_string(&child.nodes).unwrap();
+        assert!(child_json.contains("{{credential:child.call.tool_input.token}}"));
+
+        // A second import gets its own copies
+        let again = bundle
+            .install(&engine, "importer", &HashMap::new(), &HashMap::new())
+            .unwrap();
+        assert_ne!(again, root_id);
+    }
+}
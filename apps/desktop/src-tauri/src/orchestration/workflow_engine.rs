@@ -1,9 +1,13 @@
 use chrono::Utc;
+use rusqlite::types::Value as SqlValue;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use super::workflow_artifacts::ArtifactStore;
+use crate::db::pagination::{query_page, Conditions, Page, PageQuery, PageRequest};
+
 /// Workflow definition containing all workflow metadata and structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowDefinition {
@@ -19,6 +23,117 @@ pub struct WorkflowDefinition {
     pub updated_at: i64,
 }
 
+impl WorkflowDefinition {
+    /// Nodes each node leads to, through edges and decision paths
+    fn successors(&self) -> HashMap<&str, Vec<&str>> {
+        let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            successors
+                .entry(edge.source.as_str())
+                .or_default()
+                .push(edge.target.as_str());
+        }
+        for node in &self.nodes {
+            if let WorkflowNode::DecisionNode { id, data, .. } = node {
+                for path in [&data.true_path, &data.false_path].into_iter().flatten() {
+                    successors
+                        .entry(id.as_str())
+                        .or_default()
+                        .push(path.as_str());
+                }
+            }
+        }
+        successors
+    }
+
+    /// A path of node ids that starts and ends at the same node, if any.
+    /// Repetition belongs in loop nodes, so a saved graph must be acyclic
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        fn visit<'a>(
+            node: &'a str,
+            successors: &HashMap<&'a str, Vec<&'a str>>,
+            path: &mut Vec<&'a str>,
+            done: &mut HashSet<&'a str>,
+        ) -> Option<Vec<String>> {
+            if let Some(start) = path.iter().position(|n| *n == node) {
+                let mut cycle: Vec<String> = path[start..].iter().map(|n| n.to_string()).collect();
+                cycle.push(node.to_string());
+                return Some(cycle);
+            }
+            if !done.insert(node) {
+                return None;
+            }
+            path.push(node);
+            for next in successors.get(node).into_iter().flatten() {
+                if let Some(cycle) = visit(next, successors, path, done) {
+                    return Some(cycle);
+                }
+            }
+            path.pop();
+            None
+        }
+
+        let successors = self.successors();
+        let mut roots: Vec<&str> = successors.keys().copied().collect();
+        roots.sort_unstable();
+        let mut done = HashSet::new();
+        roots
+            .into_iter()
+            .find_map(|root| visit(root, &successors, &mut Vec::new(), &mut done))
+    }
+
+    /// Ids of the workflows this one invokes
+    pub fn sub_workflow_ids(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter_map(|node| match node {
+                WorkflowNode::SubWorkflowNode { data, .. } => Some(data.workflow_id.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The first node without incoming edges, where a run starts
+    pub fn start_node(&self) -> Option<&WorkflowNode> {
+        let targets: HashSet<&str> = self.edges.iter().map(|e| e.target.as_str()).collect();
+        self.nodes.iter().find(|node| !targets.contains(node.id()))
+    }
+
+    /// Reject graph cycles, and sub-workflows that are missing or lead back to
+    /// a workflow already being run. `load` fetches saved workflows by id
+    pub fn validate(
+        &self,
+        load: impl Fn(&str) -> Result<WorkflowDefinition, String>,
+    ) -> Result<(), String> {
+        fn visit(
+            workflow: &WorkflowDefinition,
+            chain: &mut Vec<String>,
+            load: &dyn Fn(&str) -> Result<WorkflowDefinition, String>,
+        ) -> Result<(), String> {
+            for id in workflow.sub_workflow_ids() {
+                if chain.iter().any(|seen| seen == id) {
+                    chain.push(id.to_string());
+                    return Err(format!("Sub-workflow cycle: {}", chain.join(" -> ")));
+                }
+                let sub_workflow =
+                    load(id).map_err(|_| format!("Sub-workflow {} not found", id))?;
+                chain.push(id.to_string());
+                visit(&sub_workflow, chain, load)?;
+                chain.pop();
+            }
+            Ok(())
+        }
+
+        if let Some(cycle) = self.find_cycle() {
+            return Err(format!(
+                "Workflow contains a cycle: {}. Use a loop node to repeat steps",
+                cycle.join(" -> ")
+            ));
+        }
+        visit(self, &mut vec![self.id.clone()], &load)
+    }
+}
+
 /// Types of nodes in a workflow
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -65,6 +180,18 @@ pub enum WorkflowNode {
         position: NodePosition,
         data: ToolNodeData,
     },
+    #[serde(rename = "try")]
+    TryNode {
+        id: String,
+        position: NodePosition,
+        data: TryNodeData,
+    },
+    #[serde(rename = "sub_workflow")]
+    SubWorkflowNode {
+        id: String,
+        position: NodePosition,
+        data: SubWorkflowNodeData,
+    },
 }
 
 impl WorkflowNode {
@@ -77,6 +204,37 @@ impl WorkflowNode {
             WorkflowNode::WaitNode { id, .. } => id,
             WorkflowNode::ScriptNode { id, .. } => id,
             WorkflowNode::ToolNode { id, .. } => id,
+            WorkflowNode::TryNode { id, .. } => id,
+            WorkflowNode::SubWorkflowNode { id, .. } => id,
+        }
+    }
+
+    /// The node's `type` tag
+    pub fn node_type(&self) -> &'static str {
+        match self {
+            WorkflowNode::AgentNode { .. } => "agent",
+            WorkflowNode::DecisionNode { .. } => "decision",
+            WorkflowNode::LoopNode { .. } => "loop",
+            WorkflowNode::ParallelNode { .. } => "parallel",
+            WorkflowNode::WaitNode { .. } => "wait",
+            WorkflowNode::ScriptNode { .. } => "script",
+            WorkflowNode::ToolNode { .. } => "tool",
+            WorkflowNode::TryNode { .. } => "try",
+            WorkflowNode::SubWorkflowNode { .. } => "sub_workflow",
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            WorkflowNode::AgentNode { data, .. } => &data.label,
+            WorkflowNode::DecisionNode { data, .. } => &data.label,
+            WorkflowNode::LoopNode { data, .. } => &data.label,
+            WorkflowNode::ParallelNode { data, .. } => &data.label,
+            WorkflowNode::WaitNode { data, .. } => &data.label,
+            WorkflowNode::ScriptNode { data, .. } => &data.label,
+            WorkflowNode::ToolNode { data, .. } => &data.label,
+            WorkflowNode::TryNode { data, .. } => &data.label,
+            WorkflowNode::SubWorkflowNode { data, .. } => &data.label,
         }
     }
 
@@ -89,6 +247,8 @@ impl WorkflowNode {
             WorkflowNode::WaitNode { position, .. } => position,
             WorkflowNode::ScriptNode { position, .. } => position,
             WorkflowNode::ToolNode { position, .. } => position,
+            WorkflowNode::TryNode { position, .. } => position,
+            WorkflowNode::SubWorkflowNode { position, .. } => position,
         }
     }
 }
@@ -194,6 +354,36 @@ pub struct ToolNodeData {
     pub timeout_seconds: Option<i32>,
 }
 
+/// Runs the branch on its `try` edges; if that fails, stores the error in
+/// `error_variable` (default `error`) and runs the branch on its `catch` edges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TryNodeData {
+    pub label: String,
+    pub error_variable: Option<String>,
+}
+
+/// Runs another saved workflow to completion as a single step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubWorkflowNodeData {
+    pub label: String,
+    pub workflow_id: String,
+    /// Sub-workflow input name -> variable of this workflow
+    #[serde(default)]
+    pub input_mapping: HashMap<String, String>,
+    /// Sub-workflow variable -> variable of this workflow
+    #[serde(default)]
+    pub output_mapping: HashMap<String, String>,
+}
+
+/// Edge `source_handle`s of a decision node's branches
+pub const HANDLE_TRUE: &str = "true";
+pub const HANDLE_FALSE: &str = "false";
+/// Edge `source_handle` of a loop node's body, run once per iteration
+pub const HANDLE_BODY: &str = "body";
+/// Edge `source_handle`s of a try node's protected and error handler branches
+pub const HANDLE_TRY: &str = "try";
+pub const HANDLE_CATCH: &str = "catch";
+
 /// Edge connecting two nodes in a workflow
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowEdge {
@@ -287,6 +477,8 @@ pub enum LogEventType {
     Completed,
     Failed,
     Skipped,
+    /// A decision, loop iteration, error handler or sub-workflow was taken
+    Branch,
 }
 
 impl std::fmt::Display for LogEventType {
@@ -296,6 +488,7 @@ impl std::fmt::Display for LogEventType {
             LogEventType::Completed => write!(f, "completed"),
             LogEventType::Failed => write!(f, "failed"),
             LogEventType::Skipped => write!(f, "skipped"),
+            LogEventType::Branch => write!(f, "branch"),
         }
     }
 }
@@ -303,14 +496,28 @@ impl std::fmt::Display for LogEventType {
 /// Workflow engine for managing workflow operations
 pub struct WorkflowEngine {
     db_path: String,
+    artifacts: ArtifactStore,
 }
 
 impl WorkflowEngine {
+    /// Artifact blobs are kept in `workflow_artifacts/` next to the database
     pub fn new(db_path: String) -> Self {
-        Self { db_path }
+        let blob_dir = std::path::Path::new(&db_path)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join("workflow_artifacts");
+        Self {
+            artifacts: ArtifactStore::new(db_path.clone(), blob_dir),
+            db_path,
+        }
     }
 
-    fn get_connection(&self) -> Result<rusqlite::Connection, String> {
+    /// Store for files produced by workflow steps
+    pub fn artifacts(&self) -> &ArtifactStore {
+        &self.artifacts
+    }
+
+    pub(super) fn get_connection(&self) -> Result<rusqlite::Connection, String> {
         rusqlite::Connection::open(&self.db_path)
             .map_err(|e| format!("Failed to open database: {}", e))
     }
@@ -324,6 +531,8 @@ impl WorkflowEngine {
             definition.id = Uuid::new_v4().to_string();
         }
 
+        definition.validate(|id| self.get_workflow(id))?;
+
         let now = Utc::now().timestamp();
         definition.created_at = now;
         definition.updated_at = now;
@@ -363,6 +572,9 @@ impl WorkflowEngine {
         id: &str,
         mut definition: WorkflowDefinition,
     ) -> Result<(), String> {
+        definition.id = id.to_string();
+        definition.validate(|id| self.get_workflow(id))?;
+
         let conn = self.get_connection()?;
 
         definition.updated_at = Utc::now().timestamp();
@@ -418,34 +630,7 @@ impl WorkflowEngine {
         ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
         let workflow = stmt
-            .query_row(rusqlite::params![id], |row| {
-                let nodes_json: String = row.get(4)?;
-                let edges_json: String = row.get(5)?;
-                let triggers_json: String = row.get(6)?;
-                let metadata_json: String = row.get(7)?;
-
-                let nodes: Vec<WorkflowNode> = serde_json::from_str(&nodes_json)
-                    .map_err(|_e| rusqlite::Error::InvalidQuery)?;
-                let edges: Vec<WorkflowEdge> = serde_json::from_str(&edges_json)
-                    .map_err(|_e| rusqlite::Error::InvalidQuery)?;
-                let triggers: Vec<WorkflowTrigger> = serde_json::from_str(&triggers_json)
-                    .map_err(|_e| rusqlite::Error::InvalidQuery)?;
-                let metadata: HashMap<String, Value> = serde_json::from_str(&metadata_json)
-                    .map_err(|_e| rusqlite::Error::InvalidQuery)?;
-
-                Ok(WorkflowDefinition {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    name: row.get(2)?,
-                    description: row.get(3)?,
-                    nodes,
-                    edges,
-                    triggers,
-                    metadata,
-                    created_at: row.get(8)?,
-                    updated_at: row.get(9)?,
-                })
-            })
+            .query_row(rusqlite::params![id], map_workflow_definition)
             .map_err(|e| format!("Failed to query workflow: {}", e))?;
 
         Ok(workflow)
@@ -461,34 +646,7 @@ impl WorkflowEngine {
         ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
         let workflows = stmt
-            .query_map(rusqlite::params![user_id], |row| {
-                let nodes_json: String = row.get(4)?;
-                let edges_json: String = row.get(5)?;
-                let triggers_json: String = row.get(6)?;
-                let metadata_json: String = row.get(7)?;
-
-                let nodes: Vec<WorkflowNode> =
-                    serde_json::from_str(&nodes_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let edges: Vec<WorkflowEdge> =
-                    serde_json::from_str(&edges_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let triggers: Vec<WorkflowTrigger> = serde_json::from_str(&triggers_json)
-                    .map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let metadata: HashMap<String, Value> = serde_json::from_str(&metadata_json)
-                    .map_err(|_| rusqlite::Error::InvalidQuery)?;
-
-                Ok(WorkflowDefinition {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    name: row.get(2)?,
-                    description: row.get(3)?,
-                    nodes,
-                    edges,
-                    triggers,
-                    metadata,
-                    created_at: row.get(8)?,
-                    updated_at: row.get(9)?,
-                })
-            })
+            .query_map(rusqlite::params![user_id], map_workflow_definition)
             .map_err(|e| format!("Failed to query workflows: {}", e))?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| format!("Failed to collect workflows: {}", e))?;
@@ -496,6 +654,37 @@ impl WorkflowEngine {
         Ok(workflows)
     }
 
+    /// One page of a user's workflows, searchable by name and description
+    pub fn get_user_workflows_page(
+        &self,
+        user_id: &str,
+        request: &PageRequest,
+    ) -> Result<Page<WorkflowDefinition>, String> {
+        let conn = self.get_connection()?;
+
+        let mut conditions = Conditions::new();
+        conditions.push("user_id = ?", [SqlValue::Text(user_id.to_string())]);
+        conditions.search(request, &["name", "description"]);
+        conditions.filters(request, &[])?;
+
+        query_page(
+            &conn,
+            &PageQuery {
+                select: "id, user_id, name, description, nodes, edges, triggers, metadata, created_at, updated_at",
+                from: "workflow_definitions",
+                id_column: "id",
+                sorts: &[
+                    ("updatedAt", "updated_at"),
+                    ("createdAt", "created_at"),
+                    ("name", "name"),
+                ],
+            },
+            &conditions,
+            request,
+            map_workflow_definition,
+        )
+    }
+
     /// Create a workflow execution
     pub fn create_execution(
         &self,
@@ -567,6 +756,25 @@ impl WorkflowEngine {
         Ok(())
     }
 
+    /// Cancel every pending, running or paused execution; returns how many
+    pub fn cancel_active_executions(&self) -> Result<usize, String> {
+        let conn = self.get_connection()?;
+
+        conn.execute(
+            "UPDATE workflow_executions
+             SET status = ?1, completed_at = ?2
+             WHERE status IN (?3, ?4, ?5)",
+            rusqlite::params![
+                WorkflowStatus::Cancelled.to_string(),
+                Utc::now().timestamp(),
+                WorkflowStatus::Pending.to_string(),
+                WorkflowStatus::Running.to_string(),
+                WorkflowStatus::Paused.to_string(),
+            ],
+        )
+        .map_err(|e| format!("Failed to cancel executions: {}", e))
+    }
+
     /// Get execution status
     pub fn get_execution_status(&self, execution_id: &str) -> Result<WorkflowExecution, String> {
         let conn = self.get_connection()?;
@@ -659,7 +867,7 @@ impl WorkflowEngine {
         let mut stmt = conn
             .prepare(
                 "SELECT id, execution_id, node_id, event_type, data, timestamp
-             FROM workflow_execution_logs WHERE execution_id = ?1 ORDER BY timestamp ASC",
+             FROM workflow_execution_logs WHERE execution_id = ?1 ORDER BY timestamp ASC, rowid ASC",
             )
             .map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
@@ -671,6 +879,7 @@ impl WorkflowEngine {
                     "completed" => LogEventType::Completed,
                     "failed" => LogEventType::Failed,
                     "skipped" => LogEventType::Skipped,
+                    "branch" => LogEventType::Branch,
                     _ => LogEventType::Started,
                 };
 
@@ -694,6 +903,36 @@ impl WorkflowEngine {
     }
 }
 
+/// Map a `workflow_definitions` row selected in table column order
+fn map_workflow_definition(row: &rusqlite::Row) -> rusqlite::Result<WorkflowDefinition> {
+    let nodes_json: String = row.get(4)?;
+    let edges_json: String = row.get(5)?;
+    let triggers_json: String = row.get(6)?;
+    let metadata_json: String = row.get(7)?;
+
+    let nodes: Vec<WorkflowNode> =
+        serde_json::from_str(&nodes_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+    let edges: Vec<WorkflowEdge> =
+        serde_json::from_str(&edges_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+    let triggers: Vec<WorkflowTrigger> =
+        serde_json::from_str(&triggers_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+    let metadata: HashMap<String, Value> =
+        serde_json::from_str(&metadata_json).map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+    Ok(WorkflowDefinition {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        name: row.get(2)?,
+        description: row.get(3)?,
+        nodes,
+        edges,
+        triggers,
+        metadata,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -715,6 +954,91 @@ mod tests {
         assert_eq!(node.id(), "test-id");
     }
 
+    fn workflow(id: &str, nodes: Vec<WorkflowNode>, edges: &[(&str, &str)]) -> WorkflowDefinition {
+        WorkflowDefinition {
+            id: id.to_string(),
+            user_id: "user-1".to_string(),
+            name: id.to_string(),
+            description: None,
+            nodes,
+            edges: edges
+                .iter()
+                .enumerate()
+                .map(|(i, (source, target))| WorkflowEdge {
+                    id: format!("e{}", i),
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    source_handle: None,
+                    target_handle: None,
+                    condition: None,
+                    label: None,
+                })
+                .collect(),
+            triggers: Vec::new(),
+            metadata: HashMap::new(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn sub_workflow(id: &str, workflow_id: &str) -> WorkflowNode {
+        WorkflowNode::SubWorkflowNode {
+            id: id.to_string(),
+            position: NodePosition { x: 0.0, y: 0.0 },
+            data: SubWorkflowNodeData {
+                label: id.to_string(),
+                workflow_id: workflow_id.to_string(),
+                input_mapping: HashMap::new(),
+                output_mapping: HashMap::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let acyclic = workflow(
+            "wf",
+            Vec::new(),
+            &[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")],
+        );
+        assert_eq!(acyclic.find_cycle(), None);
+
+        let cyclic = workflow("wf", Vec::new(), &[("a", "b"), ("b", "c"), ("c", "b")]);
+        assert_eq!(cyclic.find_cycle().unwrap(), vec!["b", "c", "b"]);
+        assert!(cyclic
+            .validate(|id| Err(id.to_string()))
+            .unwrap_err()
+            .contains("b -> c -> b"));
+    }
+
+    #[test]
+    fn test_validate_sub_workflow_cycles() {
+        let saved: HashMap<String, WorkflowDefinition> = [
+            workflow("b", vec![sub_workflow("call-c", "c")], &[]),
+            workflow("c", vec![sub_workflow("call-a", "a")], &[]),
+            workflow("d", Vec::new(), &[]),
+        ]
+        .into_iter()
+        .map(|wf| (wf.id.clone(), wf))
+        .collect();
+        let load = |id: &str| saved.get(id).cloned().ok_or_else(|| "missing".to_string());
+
+        let calls_d = workflow("a", vec![sub_workflow("call-d", "d")], &[]);
+        assert!(calls_d.validate(load).is_ok());
+
+        let calls_b = workflow("a", vec![sub_workflow("call-b", "b")], &[]);
+        assert_eq!(
+            calls_b.validate(load).unwrap_err(),
+            "Sub-workflow cycle: a -> b -> c -> a"
+        );
+
+        let calls_missing = workflow("a", vec![sub_workflow("call-x", "x")], &[]);
+        assert_eq!(
+            calls_missing.validate(load).unwrap_err(),
+            "Sub-workflow x not found"
+        );
+    }
+
     #[test]
     fn test_workflow_status_display() {
         assert_eq!(WorkflowStatus::Running.to_string(), "running");
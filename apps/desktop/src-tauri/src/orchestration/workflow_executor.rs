@@ -1,9 +1,26 @@
+use super::workflow_artifacts::{
+    retention_days, ArtifactContent, ArtifactKind, ArtifactOrigin, NewArtifact,
+};
 use super::workflow_engine::*;
+use super::workflow_simulator::{simulate, WorkflowSimulation};
+use crate::codebase::dependency_scan::{self, LicensePolicy};
+use crate::codebase::review::Severity;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
 
+const CANCELLED: &str = "Workflow execution cancelled";
+/// How deeply sub-workflows may nest
+pub(super) const MAX_SUB_WORKFLOW_DEPTH: u32 = 8;
+/// Iterations after which a condition loop is stopped
+pub(super) const MAX_LOOP_ITERATIONS: i32 = 1000;
+/// Tool node that runs [`dependency_scan::scan_workspace`]
+pub const DEPENDENCY_SCAN_TOOL: &str = "dependency_scan";
+
 /// Context for workflow execution
 #[derive(Debug, Clone)]
 pub struct ExecutionContext {
@@ -13,6 +30,13 @@ pub struct ExecutionContext {
     pub current_node_id: Option<String>,
     pub execution_path: Vec<String>,
     pub loop_counters: HashMap<String, i32>,
+    /// Outputs registered by the running step, stored when it finishes
+    pub pending_artifacts: Vec<NewArtifact>,
+    /// Sub-workflows between this execution and the one the user started
+    pub depth: u32,
+    /// Executions that started this one through sub-workflow nodes,
+    /// outermost first; cancelling any of them cancels this one
+    pub parent_execution_ids: Vec<String>,
 }
 
 impl ExecutionContext {
@@ -24,6 +48,9 @@ impl ExecutionContext {
             current_node_id: None,
             execution_path: Vec::new(),
             loop_counters: HashMap::new(),
+            pending_artifacts: Vec::new(),
+            depth: 0,
+            parent_execution_ids: Vec::new(),
         }
     }
 
@@ -35,6 +62,142 @@ impl ExecutionContext {
         self.variables.get(key)
     }
 
+    /// Replace `{{name}}` placeholders in strings with workflow variables. A
+    /// string that is only a placeholder takes the variable's value as is;
+    /// unknown names are left in place
+    pub fn render(&self, value: &Value) -> Value {
+        static PLACEHOLDER: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap());
+
+        match value {
+            Value::String(text) => {
+                if let Some(caps) = PLACEHOLDER.captures(text) {
+                    if caps[0].len() == text.len() {
+                        if let Some(variable) = self.get_variable(&caps[1]) {
+                            return variable.clone();
+                        }
+                    }
+                }
+                let rendered = PLACEHOLDER.replace_all(text, |caps: &regex::Captures| {
+                    match self.get_variable(&caps[1]) {
+                        Some(Value::String(variable)) => variable.clone(),
+                        Some(variable) => variable.to_string(),
+                        None => caps[0].to_string(),
+                    }
+                });
+                Value::String(rendered.into_owned())
+            }
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|item| self.render(item)).collect())
+            }
+            Value::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), self.render(value)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Value of a condition operand: a variable as `$name`, `$name.field.0` or
+    /// `{{name}}`, a quoted string, a JSON literal, or else the text itself.
+    /// Unknown variables are null
+    pub fn resolve(&self, operand: &str) -> Value {
+        let operand = operand.trim();
+        let path = operand.strip_prefix('$').or_else(|| {
+            operand
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .map(str::trim)
+        });
+        if let Some(path) = path {
+            let mut segments = path.split('.');
+            let mut value = segments
+                .next()
+                .and_then(|name| self.get_variable(name))
+                .unwrap_or(&Value::Null);
+            for segment in segments {
+                value = match value {
+                    Value::Array(items) => segment
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| items.get(index)),
+                    other => other.get(segment),
+                }
+                .unwrap_or(&Value::Null);
+            }
+            return value.clone();
+        }
+
+        for quote in ['\'', '"'] {
+            if let Some(text) = operand
+                .strip_prefix(quote)
+                .and_then(|rest| rest.strip_suffix(quote))
+            {
+                return Value::String(text.to_string());
+            }
+        }
+        serde_json::from_str(operand).unwrap_or_else(|_| Value::String(operand.to_string()))
+    }
+
+    /// Evaluate a condition on workflow variables: an operand alone is tested
+    /// for truthiness, `a <op> b` compares two operands with `==`, `!=`, `>`,
+    /// `>=`, `<`, `<=` or `contains`. An empty condition holds
+    pub fn evaluate(&self, condition: &str) -> bool {
+        const OPERATORS: [&str; 7] = [" contains ", "==", "!=", ">=", "<=", ">", "<"];
+
+        let condition = condition.trim();
+        if condition.is_empty() {
+            return true;
+        }
+        let Some((lhs, operator, rhs)) = OPERATORS.iter().find_map(|operator| {
+            condition
+                .split_once(operator)
+                .map(|(lhs, rhs)| (self.resolve(lhs), operator.trim(), self.resolve(rhs)))
+        }) else {
+            return truthy(&self.resolve(condition));
+        };
+
+        match operator {
+            "contains" => match &lhs {
+                Value::String(text) => text.contains(&as_text(&rhs)),
+                Value::Array(items) => items.iter().any(|item| loosely_equal(item, &rhs)),
+                Value::Object(fields) => fields.contains_key(&as_text(&rhs)),
+                _ => false,
+            },
+            "==" => loosely_equal(&lhs, &rhs),
+            "!=" => !loosely_equal(&lhs, &rhs),
+            _ => {
+                let ordering = match (as_number(&lhs), as_number(&rhs)) {
+                    (Some(lhs), Some(rhs)) => lhs.partial_cmp(&rhs),
+                    _ => Some(as_text(&lhs).cmp(&as_text(&rhs))),
+                };
+                ordering.is_some_and(|ordering| match operator {
+                    ">" => ordering.is_gt(),
+                    ">=" => ordering.is_ge(),
+                    "<" => ordering.is_lt(),
+                    _ => ordering.is_le(),
+                })
+            }
+        }
+    }
+
+    /// Items of a for-each loop's collection, where a bare name refers to a
+    /// variable. An unset collection is empty
+    pub fn resolve_collection(&self, collection: &str) -> Result<Vec<Value>, String> {
+        let collection = collection.trim();
+        match self.resolve(&if collection.starts_with(['$', '{']) {
+            collection.to_string()
+        } else {
+            format!("${}", collection)
+        }) {
+            Value::Array(items) => Ok(items),
+            Value::Null => Ok(Vec::new()),
+            _ => Err(format!("Loop collection `{}` is not a list", collection)),
+        }
+    }
+
     pub fn increment_loop_counter(&mut self, loop_id: &str) -> i32 {
         let counter = self.loop_counters.entry(loop_id.to_string()).or_insert(0);
         *counter += 1;
@@ -44,6 +207,136 @@ impl ExecutionContext {
     pub fn reset_loop_counter(&mut self, loop_id: &str) {
         self.loop_counters.remove(loop_id);
     }
+
+    /// Register a file the current step produced
+    pub fn add_artifact(&mut self, artifact: NewArtifact) {
+        self.pending_artifacts.push(artifact);
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(text) => !text.is_empty() && text != "false",
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Numbers compare by value and everything else by its text, so `"3" == 3`
+fn loosely_equal(lhs: &Value, rhs: &Value) -> bool {
+    match (as_number(lhs), as_number(rhs)) {
+        (Some(lhs), Some(rhs)) => lhs == rhs,
+        _ => lhs == rhs || as_text(lhs) == as_text(rhs),
+    }
+}
+
+/// Nodes to run after `node`. Loop bodies and try/catch branches are run by
+/// their node; after a decision only the branch it `taken` and unlabeled
+/// edges are followed
+pub(super) fn next_nodes<'w>(
+    workflow: &'w WorkflowDefinition,
+    node: &WorkflowNode,
+    taken: Option<bool>,
+    context: &ExecutionContext,
+) -> Vec<&'w WorkflowNode> {
+    let mut targets: Vec<&str> = Vec::new();
+    for edge in workflow.edges.iter().filter(|e| e.source == node.id()) {
+        let handle = edge.source_handle.as_deref();
+        let follow = match node {
+            WorkflowNode::LoopNode { .. } => handle != Some(HANDLE_BODY),
+            WorkflowNode::TryNode { .. } => {
+                handle != Some(HANDLE_TRY) && handle != Some(HANDLE_CATCH)
+            }
+            WorkflowNode::DecisionNode { data, .. } => {
+                let branch = match handle {
+                    Some(HANDLE_TRUE) => Some(true),
+                    Some(HANDLE_FALSE) => Some(false),
+                    _ if data.true_path.as_deref() == Some(edge.target.as_str()) => Some(true),
+                    _ if data.false_path.as_deref() == Some(edge.target.as_str()) => Some(false),
+                    _ => None,
+                };
+                branch.is_none() || branch == taken
+            }
+            _ => true,
+        };
+
+        // Check edge condition if present
+        if follow
+            && edge
+                .condition
+                .as_deref()
+                .is_none_or(|condition| context.evaluate(condition))
+        {
+            targets.push(&edge.target);
+        }
+    }
+
+    // A decision path may name its target without an edge
+    if let WorkflowNode::DecisionNode { data, .. } = node {
+        let path = match taken {
+            Some(true) => data.true_path.as_deref(),
+            Some(false) => data.false_path.as_deref(),
+            None => None,
+        };
+        if let Some(path) = path.filter(|path| !targets.contains(path)) {
+            targets.push(path);
+        }
+    }
+
+    targets
+        .into_iter()
+        .filter_map(|target| workflow.nodes.iter().find(|n| n.id() == target))
+        .collect()
+}
+
+/// Nodes on `node`'s edges with the given `source_handle` whose condition holds
+pub(super) fn branch_nodes<'w>(
+    workflow: &'w WorkflowDefinition,
+    node: &WorkflowNode,
+    handle: &str,
+    context: &ExecutionContext,
+) -> Vec<&'w WorkflowNode> {
+    workflow
+        .edges
+        .iter()
+        .filter(|e| e.source == node.id() && e.source_handle.as_deref() == Some(handle))
+        .filter(|e| {
+            e.condition
+                .as_deref()
+                .is_none_or(|condition| context.evaluate(condition))
+        })
+        .filter_map(|e| workflow.nodes.iter().find(|n| n.id() == e.target))
+        .collect()
+}
+
+/// Variables that `after` adds or changes relative to `before`
+fn changed_variables(
+    before: &HashMap<String, Value>,
+    after: &HashMap<String, Value>,
+) -> serde_json::Map<String, Value> {
+    after
+        .iter()
+        .filter(|(name, value)| before.get(*name) != Some(*value))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
 }
 
 /// Workflow executor for running workflow definitions
@@ -62,6 +355,18 @@ impl WorkflowExecutor {
         workflow_id: String,
         inputs: HashMap<String, Value>,
     ) -> Result<String, String> {
+        self.start_workflow(workflow_id, inputs)
+            .map(|(execution_id, _)| execution_id)
+    }
+
+    /// Start a workflow in the background. The handle finishes with the run,
+    /// so a caller waiting on it can tell a live run from one whose task
+    /// died before recording an outcome
+    pub fn start_workflow(
+        &self,
+        workflow_id: String,
+        inputs: HashMap<String, Value>,
+    ) -> Result<(String, JoinHandle<()>), String> {
         // Create execution record
         let execution_id = self.engine.create_execution(&workflow_id, inputs.clone())?;
 
@@ -69,25 +374,34 @@ impl WorkflowExecutor {
         let workflow = self.engine.get_workflow(&workflow_id)?;
 
         // Create execution context
-        let context = ExecutionContext::new(execution_id.clone(), workflow_id.clone(), inputs);
+        let mut context = ExecutionContext::new(execution_id.clone(), workflow_id.clone(), inputs);
 
         // Start execution in background
         let engine = Arc::clone(&self.engine);
-        tokio::spawn(async move {
+        let run = tokio::spawn(async move {
             let executor = WorkflowExecutor::new(engine);
-            if let Err(e) = executor.run_workflow(workflow, context).await {
+            if let Err(e) = executor.run_workflow(&workflow, &mut context).await {
                 eprintln!("Workflow execution failed: {}", e);
             }
         });
 
-        Ok(execution_id)
+        Ok((execution_id, run))
+    }
+
+    /// Predict a run without side effects: nothing is executed or recorded
+    pub fn simulate_workflow(
+        &self,
+        workflow_id: &str,
+        inputs: HashMap<String, Value>,
+    ) -> Result<WorkflowSimulation, String> {
+        simulate(&self.engine, workflow_id, inputs)
     }
 
     /// Run the workflow execution
     async fn run_workflow(
         &self,
-        workflow: WorkflowDefinition,
-        mut context: ExecutionContext,
+        workflow: &WorkflowDefinition,
+        context: &mut ExecutionContext,
     ) -> Result<(), String> {
         // Update status to running
         self.engine.update_execution_status(
@@ -98,12 +412,10 @@ impl WorkflowExecutor {
         )?;
 
         // Find start node (node with no incoming edges)
-        let start_node = self.find_start_node(&workflow)?;
+        let start_node = self.find_start_node(workflow)?;
 
         // Execute from start node
-        let result = self
-            .execute_node(&workflow, &start_node, &mut context)
-            .await;
+        let result = self.execute_node(workflow, &start_node, context).await;
 
         // Update final status
         match &result {
@@ -115,6 +427,14 @@ impl WorkflowExecutor {
                     None,
                 )?;
             }
+            Err(e) if e == CANCELLED => {
+                self.engine.update_execution_status(
+                    &context.execution_id,
+                    WorkflowStatus::Cancelled,
+                    context.current_node_id.clone(),
+                    None,
+                )?;
+            }
             Err(e) => {
                 self.engine.update_execution_status(
                     &context.execution_id,
@@ -130,20 +450,10 @@ impl WorkflowExecutor {
 
     /// Find the start node of a workflow
     fn find_start_node(&self, workflow: &WorkflowDefinition) -> Result<WorkflowNode, String> {
-        // Find nodes with no incoming edges
-        let mut incoming_counts: HashMap<String, usize> = HashMap::new();
-
-        for edge in &workflow.edges {
-            *incoming_counts.entry(edge.target.clone()).or_insert(0) += 1;
-        }
-
-        for node in &workflow.nodes {
-            if !incoming_counts.contains_key(node.id()) {
-                return Ok(node.clone());
-            }
-        }
-
-        Err("No start node found".to_string())
+        workflow
+            .start_node()
+            .cloned()
+            .ok_or_else(|| "No start node found".to_string())
     }
 
     /// Execute a single node
@@ -154,6 +464,11 @@ impl WorkflowExecutor {
         context: &'a mut ExecutionContext,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>> {
         Box::pin(async move {
+            // Cancellation takes effect at the next node boundary
+            if self.is_cancelled(context) {
+                return Err(CANCELLED.to_string());
+            }
+
             context.current_node_id = Some(node.id().to_string());
             context.execution_path.push(node.id().to_string());
 
@@ -173,14 +488,24 @@ impl WorkflowExecutor {
                 None,
             )?;
 
-            // Execute node based on type
+            // Outputs of a step are logged so dry runs can reuse them; loop
+            // and try nodes only run other steps
+            let before = (!matches!(
+                node,
+                WorkflowNode::LoopNode { .. } | WorkflowNode::TryNode { .. }
+            ))
+            .then(|| context.variables.clone());
+
+            // Execute node based on type; a decision reports the branch it took
+            let mut taken = None;
             let result = match node {
                 WorkflowNode::AgentNode { data, .. } => {
                     self.execute_agent_node(data, context).await
                 }
-                WorkflowNode::DecisionNode { data, .. } => {
-                    self.execute_decision_node(workflow, data, context).await
-                }
+                WorkflowNode::DecisionNode { data, .. } => self
+                    .execute_decision_node(node, data, context)
+                    .await
+                    .map(|branch| taken = Some(branch)),
                 WorkflowNode::LoopNode { data, .. } => {
                     self.execute_loop_node(workflow, node, data, context).await
                 }
@@ -192,29 +517,41 @@ impl WorkflowExecutor {
                     self.execute_script_node(data, context).await
                 }
                 WorkflowNode::ToolNode { data, .. } => self.execute_tool_node(data, context).await,
+                WorkflowNode::TryNode { data, .. } => {
+                    self.execute_try_node(workflow, node, data, context).await
+                }
+                WorkflowNode::SubWorkflowNode { data, .. } => {
+                    self.execute_sub_workflow_node(node, data, context).await
+                }
             };
 
             match result {
                 Ok(_) => {
                     // Log node completed
-                    self.engine.add_execution_log(
+                    let outputs = before
+                        .map(|before| changed_variables(&before, &context.variables))
+                        .filter(|outputs| !outputs.is_empty());
+                    let log_id = self.engine.add_execution_log(
                         &context.execution_id,
                         node.id(),
                         LogEventType::Completed,
-                        None,
+                        outputs.map(|outputs| serde_json::json!({ "outputs": outputs })),
                     )?;
+                    self.store_artifacts(workflow, node, &log_id, context);
 
                     // Execute next nodes
-                    self.execute_next_nodes(workflow, node, context).await
+                    self.execute_next_nodes(workflow, node, taken, context)
+                        .await
                 }
                 Err(e) => {
                     // Log node failed
-                    self.engine.add_execution_log(
+                    let log_id = self.engine.add_execution_log(
                         &context.execution_id,
                         node.id(),
                         LogEventType::Failed,
                         Some(Value::String(e.clone())),
                     )?;
+                    self.store_artifacts(workflow, node, &log_id, context);
 
                     Err(e)
                 }
@@ -222,43 +559,97 @@ impl WorkflowExecutor {
         })
     }
 
+    /// Whether the kill switch is engaged or the execution, or one that
+    /// started it as a sub-workflow, was cancelled
+    fn is_cancelled(&self, context: &ExecutionContext) -> bool {
+        crate::kill_switch::is_engaged()
+            || context
+                .parent_execution_ids
+                .iter()
+                .chain(std::iter::once(&context.execution_id))
+                .any(|execution_id| {
+                    self.engine
+                        .get_execution_status(execution_id)
+                        .is_ok_and(|execution| execution.status == WorkflowStatus::Cancelled)
+                })
+    }
+
+    /// Move the step's registered outputs into the artifact store, linked to
+    /// its log entry. A failed upload never fails the workflow.
+    fn store_artifacts(
+        &self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        log_id: &str,
+        context: &mut ExecutionContext,
+    ) {
+        if context.pending_artifacts.is_empty() {
+            return;
+        }
+
+        let origin = ArtifactOrigin {
+            execution_id: &context.execution_id,
+            workflow_id: &context.workflow_id,
+            node_id: node.id(),
+            log_id: Some(log_id),
+        };
+        let retention = retention_days(workflow);
+        for artifact in std::mem::take(&mut context.pending_artifacts) {
+            let name = artifact.name.clone();
+            if let Err(e) = self
+                .engine
+                .artifacts()
+                .register(&origin, retention, artifact)
+            {
+                tracing::warn!("Failed to store workflow artifact {}: {}", name, e);
+            }
+        }
+    }
+
     /// Execute next nodes based on edges
     async fn execute_next_nodes(
         &self,
         workflow: &WorkflowDefinition,
         current_node: &WorkflowNode,
+        taken: Option<bool>,
         context: &mut ExecutionContext,
     ) -> Result<(), String> {
-        // Find outgoing edges from current node
-        let outgoing_edges: Vec<&WorkflowEdge> = workflow
-            .edges
-            .iter()
-            .filter(|e| e.source == current_node.id())
-            .collect();
-
-        if outgoing_edges.is_empty() {
-            // No more nodes, workflow complete
-            return Ok(());
+        for next_node in next_nodes(workflow, current_node, taken, context) {
+            self.execute_node(workflow, next_node, context).await?;
         }
+        Ok(())
+    }
 
-        // Execute next nodes
-        for edge in outgoing_edges {
-            // Check edge condition if present
-            if let Some(condition) = &edge.condition {
-                if !self.evaluate_condition(condition, context)? {
-                    continue;
-                }
-            }
-
-            // Find target node
-            if let Some(next_node) = workflow.nodes.iter().find(|n| n.id() == edge.target) {
-                self.execute_node(workflow, next_node, context).await?;
-            }
+    /// Run the nodes on `node`'s edges with the given `source_handle`
+    async fn execute_branch(
+        &self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        handle: &str,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        for next_node in branch_nodes(workflow, node, handle, context) {
+            self.execute_node(workflow, next_node, context).await?;
         }
-
         Ok(())
     }
 
+    fn log_branch(
+        &self,
+        node: &WorkflowNode,
+        context: &ExecutionContext,
+        data: Value,
+    ) -> Result<(), String> {
+        self.engine
+            .add_execution_log(
+                &context.execution_id,
+                node.id(),
+                LogEventType::Branch,
+                Some(data),
+            )
+            .map(|_| ())
+    }
+
     /// Execute agent node
     async fn execute_agent_node(
         &self,
@@ -290,16 +681,24 @@ impl WorkflowExecutor {
         Ok(())
     }
 
-    /// Execute decision node
+    /// Execute decision node; returns whether the condition held
     async fn execute_decision_node(
         &self,
-        _workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
         data: &DecisionNodeData,
         context: &mut ExecutionContext,
-    ) -> Result<(), String> {
+    ) -> Result<bool, String> {
         println!("Executing decision node: {}", data.label);
 
-        let condition_result = self.evaluate_condition(&data.condition, context)?;
+        let condition_result = context.evaluate(&data.condition);
+        self.log_branch(
+            node,
+            context,
+            serde_json::json!({
+                "branch": if condition_result { HANDLE_TRUE } else { HANDLE_FALSE },
+                "condition": data.condition,
+            }),
+        )?;
 
         // Store decision result in context
         context.set_variable(
@@ -307,56 +706,176 @@ impl WorkflowExecutor {
             Value::Bool(condition_result),
         );
 
-        Ok(())
+        Ok(condition_result)
     }
 
-    /// Execute loop node
+    /// Execute loop node, running the nodes on its `body` edges once per
+    /// iteration. For-each loops set `item_variable` to each element of
+    /// `collection` and `<item_variable>_index` to its position
     async fn execute_loop_node(
         &self,
-        _workflow: &WorkflowDefinition,
+        workflow: &WorkflowDefinition,
         node: &WorkflowNode,
         data: &LoopNodeData,
         context: &mut ExecutionContext,
     ) -> Result<(), String> {
         println!("Executing loop node: {}", data.label);
 
+        context.reset_loop_counter(node.id());
+        let mut iterations = 0;
         match data.loop_type {
             LoopType::Count => {
-                let iterations = data.iterations.unwrap_or(1);
-                for i in 0..iterations {
+                for i in 0..data.iterations.unwrap_or(1) {
                     context.set_variable(data.item_variable.clone(), Value::Number(i.into()));
-
-                    // Execute loop body (nodes connected to this loop node)
-                    // This is simplified - in reality would need to handle loop body separately
-                    sleep(Duration::from_millis(50)).await;
+                    self.run_iteration(workflow, node, i, None, context).await?;
+                    iterations += 1;
                 }
             }
             LoopType::Condition => {
                 if let Some(condition) = &data.condition {
-                    while self.evaluate_condition(condition, context)? {
-                        sleep(Duration::from_millis(50)).await;
-
+                    while context.evaluate(condition) {
                         // Prevent infinite loops
                         let counter = context.increment_loop_counter(node.id());
-                        if counter > 1000 {
+                        if counter > MAX_LOOP_ITERATIONS {
                             return Err("Loop iteration limit exceeded".to_string());
                         }
+                        self.run_iteration(workflow, node, counter - 1, None, context)
+                            .await?;
+                        iterations += 1;
                     }
                 }
             }
             LoopType::ForEach => {
-                if let Some(collection_name) = &data.collection {
-                    if let Some(Value::Array(items)) = context.get_variable(collection_name) {
-                        for item in items.clone() {
-                            context.set_variable(data.item_variable.clone(), item);
-                            sleep(Duration::from_millis(50)).await;
-                        }
-                    }
+                let items =
+                    context.resolve_collection(data.collection.as_deref().unwrap_or_default())?;
+                for (i, item) in items.into_iter().enumerate() {
+                    context.set_variable(data.item_variable.clone(), item.clone());
+                    context.set_variable(format!("{}_index", data.item_variable), Value::from(i));
+                    self.run_iteration(workflow, node, i as i32, Some(item), context)
+                        .await?;
+                    iterations += 1;
                 }
             }
         }
 
         context.reset_loop_counter(node.id());
+        self.log_branch(
+            node,
+            context,
+            serde_json::json!({ "branch": "done", "iterations": iterations }),
+        )
+    }
+
+    async fn run_iteration(
+        &self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        iteration: i32,
+        item: Option<Value>,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        let mut entry = serde_json::json!({ "branch": HANDLE_BODY, "iteration": iteration });
+        if let Some(item) = item {
+            entry["item"] = item;
+        }
+        self.log_branch(node, context, entry)?;
+        self.execute_branch(workflow, node, HANDLE_BODY, context)
+            .await
+    }
+
+    /// Execute try node: an error in the `try` branch runs the `catch` branch
+    /// instead of failing the workflow. Cancellation is never caught
+    async fn execute_try_node(
+        &self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        data: &TryNodeData,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        println!("Executing try node: {}", data.label);
+
+        match self
+            .execute_branch(workflow, node, HANDLE_TRY, context)
+            .await
+        {
+            Err(e) if e != CANCELLED => {
+                self.log_branch(
+                    node,
+                    context,
+                    serde_json::json!({ "branch": HANDLE_CATCH, "error": e }),
+                )?;
+                context.set_variable(
+                    data.error_variable
+                        .clone()
+                        .unwrap_or_else(|| "error".to_string()),
+                    Value::String(e),
+                );
+                self.execute_branch(workflow, node, HANDLE_CATCH, context)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// Execute sub-workflow node as its own execution, passing mapped
+    /// variables in and copying mapped variables back out. Cancelling either
+    /// execution cancels both
+    async fn execute_sub_workflow_node(
+        &self,
+        node: &WorkflowNode,
+        data: &SubWorkflowNodeData,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        println!("Executing sub-workflow node: {}", data.label);
+
+        if context.depth >= MAX_SUB_WORKFLOW_DEPTH {
+            return Err(format!(
+                "Sub-workflows nested more than {} deep",
+                MAX_SUB_WORKFLOW_DEPTH
+            ));
+        }
+        let workflow = self.engine.get_workflow(&data.workflow_id)?;
+        let inputs: HashMap<String, Value> = data
+            .input_mapping
+            .iter()
+            .filter_map(|(input, variable)| {
+                context
+                    .get_variable(variable)
+                    .map(|value| (input.clone(), value.clone()))
+            })
+            .collect();
+        let execution_id = self.engine.create_execution(&workflow.id, inputs.clone())?;
+        self.log_branch(
+            node,
+            context,
+            serde_json::json!({
+                "branch": "sub_workflow",
+                "workflow_id": workflow.id,
+                "execution_id": execution_id,
+            }),
+        )?;
+
+        let mut sub_context = ExecutionContext::new(execution_id, workflow.id.clone(), inputs);
+        sub_context.depth = context.depth + 1;
+        sub_context.parent_execution_ids = context.parent_execution_ids.clone();
+        sub_context
+            .parent_execution_ids
+            .push(context.execution_id.clone());
+        self.run_workflow(&workflow, &mut sub_context)
+            .await
+            .map_err(|e| {
+                if e == CANCELLED {
+                    e
+                } else {
+                    format!("Sub-workflow {} failed: {}", workflow.name, e)
+                }
+            })?;
+
+        for (sub_variable, variable) in &data.output_mapping {
+            if let Some(value) = sub_context.get_variable(sub_variable) {
+                context.set_variable(variable.clone(), value.clone());
+            }
+        }
         Ok(())
     }
 
@@ -446,6 +965,19 @@ impl WorkflowExecutor {
     ) -> Result<(), String> {
         println!("Executing tool node: {}", data.label);
 
+        let data = &ToolNodeData {
+            tool_input: data
+                .tool_input
+                .iter()
+                .map(|(key, value)| (key.clone(), context.render(value)))
+                .collect(),
+            ..data.clone()
+        };
+
+        if data.tool_name == DEPENDENCY_SCAN_TOOL {
+            return self.execute_dependency_scan(data, context).await;
+        }
+
         // Placeholder: In real implementation, would call the tool from AGI system
         sleep(Duration::from_millis(100)).await;
 
@@ -458,25 +990,70 @@ impl WorkflowExecutor {
         Ok(())
     }
 
-    /// Evaluate a condition
-    fn evaluate_condition(
+    /// Scan a workspace's dependencies. Reads `path` (default: the
+    /// `workspace_path` variable), an optional license `policy`, and an optional
+    /// `fail_on` severity that fails the step when a vulnerability reaches it or
+    /// a license is denied. The report goes to `dependency_scan_output` and is
+    /// stored as a Markdown artifact
+    async fn execute_dependency_scan(
         &self,
-        condition: &str,
-        context: &ExecutionContext,
-    ) -> Result<bool, String> {
-        // Placeholder: Simple condition evaluation
-        // In real implementation, would use a proper expression evaluator
-
-        // Check for simple variable checks
-        if condition.starts_with("$") {
-            let var_name = condition.trim_start_matches('$');
-            if let Some(value) = context.get_variable(var_name) {
-                return Ok(value.as_bool().unwrap_or(false));
-            }
+        data: &ToolNodeData,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        let root = data
+            .tool_input
+            .get("path")
+            .or_else(|| context.get_variable("workspace_path"))
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from)
+            .ok_or("Dependency scan needs a `path` input")?;
+        let policy: LicensePolicy = match data.tool_input.get("policy") {
+            Some(policy) => serde_json::from_value(policy.clone())
+                .map_err(|e| format!("Invalid license policy: {}", e))?,
+            None => LicensePolicy::default(),
+        };
+        let fail_on = data
+            .tool_input
+            .get("fail_on")
+            .and_then(|v| v.as_str())
+            .map(Severity::parse);
+
+        let scan =
+            tokio::task::spawn_blocking(move || dependency_scan::scan_workspace(&root, &policy));
+        let report = match data.timeout_seconds.filter(|secs| *secs > 0) {
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs as u64), scan)
+                .await
+                .map_err(|_| format!("Dependency scan timed out after {}s", secs))?,
+            None => scan.await,
         }
+        .map_err(|e| format!("Dependency scan task failed: {}", e))?
+        .map_err(|e| format!("Dependency scan failed: {}", e))?;
+
+        context.add_artifact(
+            NewArtifact::new(
+                "dependency-scan.md",
+                ArtifactKind::Report,
+                ArtifactContent::Bytes(report.to_markdown().into_bytes()),
+            )
+            .with_mime_type("text/markdown")
+            .with_metadata("vulnerabilities", Value::from(report.vulnerabilities.len()))
+            .with_metadata("license_findings", Value::from(report.licenses.len())),
+        );
+        let passed = fail_on.is_none_or(|threshold| report.passes(threshold));
+        context.set_variable(
+            format!("{}_output", DEPENDENCY_SCAN_TOOL),
+            serde_json::to_value(&report).map_err(|e| e.to_string())?,
+        );
 
-        // Default to true for now
-        Ok(true)
+        if passed {
+            Ok(())
+        } else {
+            Err(format!(
+                "Dependency scan found {} vulnerabilities and {} license issues",
+                report.vulnerabilities.len(),
+                report.licenses.len()
+            ))
+        }
     }
 
     /// Pause a workflow execution
@@ -553,6 +1130,336 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_render_placeholders() {
+        let mut context = ExecutionContext::new(
+            "exec-1".to_string(),
+            "workflow-1".to_string(),
+            HashMap::new(),
+        );
+        context.set_variable("path".to_string(), Value::from("/srv/app"));
+        context.set_variable("retries".to_string(), Value::from(3));
+
+        let input = serde_json::json!({
+            "path": "{{path}}",
+            "log": "{{ path }}/build.log",
+            "retries": "{{retries}}",
+            "targets": ["{{path}}", "{{missing}}"]
+        });
+        assert_eq!(
+            context.render(&input),
+            serde_json::json!({
+                "path": "/srv/app",
+                "log": "/srv/app/build.log",
+                "retries": 3,
+                "targets": ["/srv/app", "{{missing}}"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_conditions() {
+        let mut context = ExecutionContext::new(
+            "exec-1".to_string(),
+            "workflow-1".to_string(),
+            HashMap::new(),
+        );
+        context.set_variable(
+            "result".to_string(),
+            serde_json::json!({ "status": "ok", "count": 3, "tags": ["urgent"] }),
+        );
+        context.set_variable("approved".to_string(), Value::Bool(false));
+
+        assert!(context.evaluate(""));
+        assert!(context.evaluate("$result.status == 'ok'"));
+        assert!(context.evaluate("{{ result }} contains status"));
+        assert!(context.evaluate("$result.count >= 3"));
+        assert!(context.evaluate("$result.count == \"3\""));
+        assert!(context.evaluate("$result.tags contains urgent"));
+        assert!(context.evaluate("$result.tags.0 != normal"));
+        assert!(!context.evaluate("$result.count < 2"));
+        assert!(!context.evaluate("$approved"));
+        assert!(!context.evaluate("$missing"));
+    }
+
+    fn executor(dir: &std::path::Path) -> WorkflowExecutor {
+        let db_path = dir.join("test.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE workflow_definitions (
+                id TEXT PRIMARY KEY, user_id TEXT NOT NULL, name TEXT NOT NULL,
+                description TEXT, nodes TEXT NOT NULL, edges TEXT NOT NULL,
+                triggers TEXT, metadata TEXT, created_at INTEGER, updated_at INTEGER
+            );
+            CREATE TABLE workflow_executions (
+                id TEXT PRIMARY KEY, workflow_id TEXT NOT NULL, status TEXT NOT NULL,
+                current_node_id TEXT, inputs TEXT, outputs TEXT, error TEXT,
+                started_at INTEGER, completed_at INTEGER
+            );
+            CREATE TABLE workflow_execution_logs (
+                id TEXT PRIMARY KEY, execution_id TEXT NOT NULL, node_id TEXT NOT NULL,
+                event_type TEXT NOT NULL, data TEXT, timestamp INTEGER
+            );",
+        )
+        .unwrap();
+        WorkflowExecutor::new(Arc::new(WorkflowEngine::new(
+            db_path.to_string_lossy().to_string(),
+        )))
+    }
+
+    fn node(id: &str, node: Value) -> WorkflowNode {
+        let mut node = node;
+        node["id"] = Value::from(id);
+        node["position"] = serde_json::json!({ "x": 0, "y": 0 });
+        serde_json::from_value(node).unwrap()
+    }
+
+    fn tool(id: &str, tool_name: &str) -> WorkflowNode {
+        node(
+            id,
+            serde_json::json!({
+                "type": "tool",
+                "data": { "label": id, "tool_name": tool_name, "tool_input": {} }
+            }),
+        )
+    }
+
+    fn edge(source: &str, target: &str, handle: Option<&str>) -> WorkflowEdge {
+        WorkflowEdge {
+            id: format!("{}-{}", source, target),
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: handle.map(str::to_string),
+            target_handle: None,
+            condition: None,
+            label: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_branches_loops_and_error_handlers() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor = executor(dir.path());
+        let workflow = WorkflowDefinition {
+            id: "wf-1".to_string(),
+            user_id: "user-1".to_string(),
+            name: "Branches".to_string(),
+            description: None,
+            nodes: vec![
+                node(
+                    "each",
+                    serde_json::json!({
+                        "type": "loop",
+                        "data": {
+                            "label": "each",
+                            "loop_type": "for_each",
+                            "collection": "orders",
+                            "item_variable": "order"
+                        }
+                    }),
+                ),
+                node(
+                    "check",
+                    serde_json::json!({
+                        "type": "decision",
+                        "data": {
+                            "label": "large",
+                            "condition": "$order.total > 100",
+                            "condition_type": "expression"
+                        }
+                    }),
+                ),
+                tool("review", "review"),
+                tool("ship", "ship"),
+                node(
+                    "guard",
+                    serde_json::json!({
+                        "type": "try",
+                        "data": { "label": "guard", "error_variable": "scan_error" }
+                    }),
+                ),
+                tool("scan", DEPENDENCY_SCAN_TOOL),
+                tool("report", "report"),
+                tool("finish", "finish"),
+            ],
+            edges: vec![
+                edge("each", "check", Some(HANDLE_BODY)),
+                edge("check", "review", Some(HANDLE_TRUE)),
+                edge("check", "ship", Some(HANDLE_FALSE)),
+                edge("each", "guard", None),
+                edge("guard", "scan", Some(HANDLE_TRY)),
+                edge("guard", "report", Some(HANDLE_CATCH)),
+                edge("guard", "finish", None),
+            ],
+            triggers: Vec::new(),
+            metadata: HashMap::new(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        assert!(workflow.validate(|_| Err(String::new())).is_ok());
+
+        let inputs = HashMap::from([(
+            "orders".to_string(),
+            serde_json::json!([{ "total": 250 }, { "total": 20 }, { "total": 40 }]),
+        )]);
+        let execution_id = executor
+            .engine
+            .create_execution(&workflow.id, inputs.clone())
+            .unwrap();
+        let mut context = ExecutionContext::new(execution_id.clone(), workflow.id.clone(), inputs);
+        executor
+            .run_workflow(&workflow, &mut context)
+            .await
+            .unwrap();
+
+        let logs = executor.engine.get_execution_logs(&execution_id).unwrap();
+        let started = |node_id: &str| {
+            logs.iter()
+                .filter(|log| {
+                    log.node_id == node_id && matches!(log.event_type, LogEventType::Started)
+                })
+                .count()
+        };
+        assert_eq!(started("check"), 3);
+        assert_eq!(started("review"), 1);
+        assert_eq!(started("ship"), 2);
+        assert_eq!(started("report"), 1);
+        assert_eq!(started("finish"), 1);
+
+        // Completed steps log the variables they set, for dry runs to reuse
+        assert!(logs.iter().any(|log| {
+            log.node_id == "review"
+                && matches!(log.event_type, LogEventType::Completed)
+                && log
+                    .data
+                    .as_ref()
+                    .is_some_and(|data| data["outputs"]["review_output"] == "Tool review executed")
+        }));
+
+        let branches: Vec<&Value> = logs
+            .iter()
+            .filter(|log| matches!(log.event_type, LogEventType::Branch))
+            .filter_map(|log| log.data.as_ref())
+            .collect();
+        assert_eq!(
+            branches
+                .iter()
+                .filter(|b| b["branch"] == HANDLE_BODY)
+                .count(),
+            3
+        );
+        assert!(branches
+            .iter()
+            .any(|b| b["branch"] == "done" && b["iterations"] == 3));
+        assert!(branches.iter().any(|b| b["branch"] == HANDLE_CATCH));
+        assert!(context
+            .get_variable("scan_error")
+            .and_then(Value::as_str)
+            .is_some_and(|error| error.contains("path")));
+        assert_eq!(
+            executor
+                .engine
+                .get_execution_status(&execution_id)
+                .unwrap()
+                .status,
+            WorkflowStatus::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_the_parent_stops_a_sub_workflow_inside_a_try_node() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor = executor(dir.path());
+        let definition = |id: &str, nodes, edges| WorkflowDefinition {
+            id: id.to_string(),
+            user_id: "user-1".to_string(),
+            name: id.to_string(),
+            description: None,
+            nodes,
+            edges,
+            triggers: Vec::new(),
+            metadata: HashMap::new(),
+            created_at: 0,
+            updated_at: 0,
+        };
+        let child = definition(
+            "child",
+            vec![
+                node(
+                    "pause",
+                    serde_json::json!({
+                        "type": "wait",
+                        "data": { "label": "pause", "wait_type": "duration", "duration_seconds": 1 }
+                    }),
+                ),
+                tool("after", "after"),
+            ],
+            vec![edge("pause", "after", None)],
+        );
+        executor.engine.create_workflow(child).unwrap();
+        let parent = definition(
+            "parent",
+            vec![
+                node(
+                    "guard",
+                    serde_json::json!({
+                        "type": "try",
+                        "data": { "label": "guard" }
+                    }),
+                ),
+                node(
+                    "nested",
+                    serde_json::json!({
+                        "type": "sub_workflow",
+                        "data": { "label": "nested", "workflow_id": "child" }
+                    }),
+                ),
+                tool("report", "report"),
+                tool("finish", "finish"),
+            ],
+            vec![
+                edge("guard", "nested", Some(HANDLE_TRY)),
+                edge("guard", "report", Some(HANDLE_CATCH)),
+                edge("guard", "finish", None),
+            ],
+        );
+
+        let execution_id = executor
+            .engine
+            .create_execution(&parent.id, HashMap::new())
+            .unwrap();
+        let mut context =
+            ExecutionContext::new(execution_id.clone(), parent.id.clone(), HashMap::new());
+        let engine = Arc::clone(&executor.engine);
+        let run = tokio::spawn(async move {
+            WorkflowExecutor::new(engine)
+                .run_workflow(&parent, &mut context)
+                .await
+        });
+
+        // Cancel while the child waits
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        executor.cancel_execution(&execution_id).unwrap();
+        assert_eq!(run.await.unwrap().unwrap_err(), CANCELLED);
+
+        let status = |id: &str| executor.engine.get_execution_status(id).unwrap().status;
+        assert_eq!(status(&execution_id), WorkflowStatus::Cancelled);
+        let logs = executor.engine.get_execution_logs(&execution_id).unwrap();
+        let child_id = logs
+            .iter()
+            .filter_map(|log| log.data.as_ref())
+            .find(|data| data["branch"] == "sub_workflow")
+            .and_then(|data| data["execution_id"].as_str())
+            .unwrap()
+            .to_string();
+        assert_eq!(status(&child_id), WorkflowStatus::Cancelled);
+        let child_logs = executor.engine.get_execution_logs(&child_id).unwrap();
+        assert!(!child_logs.iter().any(|log| log.node_id == "after"));
+        assert!(!logs
+            .iter()
+            .any(|log| log.node_id == "report" || log.node_id == "finish"));
+    }
+
     #[test]
     fn test_loop_counter() {
         let mut context = ExecutionContext::new(
@@ -0,0 +1,858 @@
+//! Workflow dry runs.
+//!
+//! A simulation walks a workflow the way the executor would, without side
+//! effects: no execution is recorded, no agent, script or tool runs, and
+//! waits do not wait. A step's outputs are the ones its node produced the
+//! last time the workflow ran it, or else sample values, so the decisions and
+//! loops after it are still evaluated. Durations are estimated from recent
+//! runs and the tool cost ledger, and costs from the ledger.
+
+use super::workflow_engine::*;
+use super::workflow_executor::{
+    branch_nodes, next_nodes, ExecutionContext, MAX_LOOP_ITERATIONS, MAX_SUB_WORKFLOW_DEPTH,
+};
+use crate::agi::tool_costs::{tool_cost_breakdown, ToolCostBreakdown};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Loop iterations walked step by step; the rest are extrapolated
+const MAX_SIMULATED_ITERATIONS: usize = 25;
+/// Recent executions that cached outputs and durations are taken from
+const HISTORY_RUNS: i64 = 20;
+/// Days of the tool cost ledger that tool estimates are averaged over
+const TOOL_COST_DAYS: i64 = 30;
+/// Duration assumed for a step that has never run
+const DEFAULT_STEP_MS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSource {
+    /// What the node produced in a previous run
+    Cached,
+    /// Placeholders shaped like the node's outputs
+    Sample,
+}
+
+/// A node the run is predicted to reach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedStep {
+    pub node_id: String,
+    pub label: String,
+    pub node_type: String,
+    /// Differs from the simulated workflow for steps of a sub-workflow
+    pub workflow_id: String,
+    /// Iteration of the innermost loop the step runs in
+    pub iteration: Option<usize>,
+    /// Branch a decision or try node takes
+    pub branch: Option<String>,
+    /// Iterations a loop node is predicted to run
+    pub iterations: Option<usize>,
+    pub outputs: HashMap<String, Value>,
+    /// Set for agent, script and tool steps
+    pub output_source: Option<OutputSource>,
+    pub estimated_duration_ms: u64,
+    pub estimated_cost: f64,
+    /// Reasons the prediction may not hold
+    pub notes: Vec<String>,
+}
+
+/// Predicted path of a workflow run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSimulation {
+    pub workflow_id: String,
+    /// In the order they would run
+    pub steps: Vec<SimulatedStep>,
+    /// Includes loop iterations that were not walked
+    pub estimated_duration_ms: u64,
+    pub estimated_cost: f64,
+    /// Nodes of the workflow the predicted path does not reach
+    pub unreached_nodes: Vec<String>,
+    /// Error the run is predicted to stop with, and the node raising it
+    pub error: Option<String>,
+    pub failed_node_id: Option<String>,
+    /// Variables at the end of the run
+    pub variables: HashMap<String, Value>,
+}
+
+/// Predict a run of a saved workflow with the given inputs
+pub(super) fn simulate(
+    engine: &WorkflowEngine,
+    workflow_id: &str,
+    inputs: HashMap<String, Value>,
+) -> Result<WorkflowSimulation, String> {
+    let workflow = engine.get_workflow(workflow_id)?;
+    let start = workflow.start_node().ok_or("No start node found")?;
+
+    let mut simulator = Simulator::new(engine);
+    let mut context = ExecutionContext::new(String::new(), workflow.id.clone(), inputs);
+    let error = simulator.node(&workflow, start, &mut context).err();
+
+    let reached: HashSet<&str> = simulator
+        .steps
+        .iter()
+        .filter(|step| step.workflow_id == workflow.id)
+        .map(|step| step.node_id.as_str())
+        .collect();
+    let unreached_nodes = workflow
+        .nodes
+        .iter()
+        .map(WorkflowNode::id)
+        .filter(|id| !reached.contains(id))
+        .map(str::to_string)
+        .collect();
+
+    Ok(WorkflowSimulation {
+        workflow_id: workflow.id.clone(),
+        estimated_duration_ms: simulator
+            .steps
+            .iter()
+            .map(|step| step.estimated_duration_ms)
+            .sum::<u64>()
+            + simulator.extra_duration_ms,
+        estimated_cost: simulator
+            .steps
+            .iter()
+            .map(|step| step.estimated_cost)
+            .sum::<f64>()
+            + simulator.extra_cost,
+        steps: simulator.steps,
+        unreached_nodes,
+        failed_node_id: error.as_ref().and(simulator.failed_node_id),
+        error,
+        variables: context.variables,
+    })
+}
+
+#[derive(Debug, Default)]
+struct NodeHistory {
+    durations_ms: Vec<u64>,
+    /// Outputs of the most recent completed run
+    outputs: Option<HashMap<String, Value>>,
+}
+
+impl NodeHistory {
+    fn average_duration_ms(&self) -> Option<u64> {
+        (!self.durations_ms.is_empty())
+            .then(|| self.durations_ms.iter().sum::<u64>() / self.durations_ms.len() as u64)
+    }
+}
+
+struct Simulator<'a> {
+    engine: &'a WorkflowEngine,
+    /// Per workflow, per node
+    histories: HashMap<String, HashMap<String, NodeHistory>>,
+    tool_costs: Option<HashMap<String, ToolCostBreakdown>>,
+    /// Variables currently holding sample values
+    sampled: HashSet<String>,
+    steps: Vec<SimulatedStep>,
+    iteration: Option<usize>,
+    failed_node_id: Option<String>,
+    /// Estimates for loop iterations that were not walked
+    extra_duration_ms: u64,
+    extra_cost: f64,
+}
+
+impl<'a> Simulator<'a> {
+    fn new(engine: &'a WorkflowEngine) -> Self {
+        Self {
+            engine,
+            histories: HashMap::new(),
+            tool_costs: None,
+            sampled: HashSet::new(),
+            steps: Vec::new(),
+            iteration: None,
+            failed_node_id: None,
+            extra_duration_ms: 0,
+            extra_cost: 0.0,
+        }
+    }
+
+    /// Walk a node and the nodes after it, mirroring the executor
+    fn node(
+        &mut self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        let index = self.steps.len();
+        self.steps.push(SimulatedStep {
+            node_id: node.id().to_string(),
+            label: node.label().to_string(),
+            node_type: node.node_type().to_string(),
+            workflow_id: workflow.id.clone(),
+            iteration: self.iteration,
+            branch: None,
+            iterations: None,
+            outputs: HashMap::new(),
+            output_source: None,
+            estimated_duration_ms: 0,
+            estimated_cost: 0.0,
+            notes: Vec::new(),
+        });
+
+        let mut taken = None;
+        let result = match node {
+            WorkflowNode::AgentNode { data, .. } => {
+                let sample = data
+                    .output_mapping
+                    .values()
+                    .map(|variable| {
+                        (
+                            variable.clone(),
+                            Value::String(format!("Output from {}", data.label)),
+                        )
+                    })
+                    .collect();
+                self.produce(workflow, node, index, sample, context);
+                Ok(())
+            }
+            WorkflowNode::ScriptNode { .. } => {
+                let sample = HashMap::from([(
+                    "script_output".to_string(),
+                    Value::String("Script executed successfully".to_string()),
+                )]);
+                self.produce(workflow, node, index, sample, context);
+                Ok(())
+            }
+            WorkflowNode::ToolNode { data, .. } => {
+                let sample = HashMap::from([(
+                    format!("{}_output", data.tool_name),
+                    Value::String(format!("Tool {} executed", data.tool_name)),
+                )]);
+                self.produce(workflow, node, index, sample, context);
+                Ok(())
+            }
+            WorkflowNode::DecisionNode { data, .. } => {
+                let holds = context.evaluate(&data.condition);
+                let variable = format!("decision_{}", data.label);
+                context.set_variable(variable.clone(), Value::Bool(holds));
+                self.sampled.remove(&variable);
+
+                let notes = self.condition_notes(&data.condition, context);
+                let step = &mut self.steps[index];
+                step.branch = Some(if holds { HANDLE_TRUE } else { HANDLE_FALSE }.to_string());
+                step.outputs.insert(variable, Value::Bool(holds));
+                step.notes = notes;
+                taken = Some(holds);
+                Ok(())
+            }
+            WorkflowNode::LoopNode { data, .. } => {
+                self.simulate_loop(workflow, node, index, data, context)
+            }
+            WorkflowNode::TryNode { data, .. } => {
+                self.simulate_try(workflow, node, index, data, context)
+            }
+            WorkflowNode::SubWorkflowNode { data, .. } => {
+                self.simulate_sub_workflow(index, data, context)
+            }
+            WorkflowNode::WaitNode { data, .. } => {
+                if matches!(data.wait_type, WaitType::Condition) {
+                    self.steps[index]
+                        .notes
+                        .push("Waits for a condition; the duration is a guess".to_string());
+                }
+                Ok(())
+            }
+            WorkflowNode::ParallelNode { .. } => Ok(()),
+        };
+
+        let (duration_ms, cost) = self.estimate(workflow, node);
+        self.steps[index].estimated_duration_ms = duration_ms;
+        self.steps[index].estimated_cost = cost;
+
+        if let Err(e) = result {
+            if self.failed_node_id.is_none() {
+                self.failed_node_id = Some(node.id().to_string());
+            }
+            return Err(e);
+        }
+        for next_node in next_nodes(workflow, node, taken, context) {
+            self.node(workflow, next_node, context)?;
+        }
+        Ok(())
+    }
+
+    /// Set a step's outputs from its last run, or else from `sample`
+    fn produce(
+        &mut self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        index: usize,
+        sample: HashMap<String, Value>,
+        context: &mut ExecutionContext,
+    ) {
+        let cached = self
+            .history(&workflow.id)
+            .get(node.id())
+            .and_then(|history| history.outputs.clone());
+        let (outputs, source) = match cached {
+            Some(outputs) => (outputs, OutputSource::Cached),
+            None => (sample, OutputSource::Sample),
+        };
+
+        for (variable, value) in &outputs {
+            context.set_variable(variable.clone(), value.clone());
+            if source == OutputSource::Sample {
+                self.sampled.insert(variable.clone());
+            } else {
+                self.sampled.remove(variable);
+            }
+        }
+        let step = &mut self.steps[index];
+        step.outputs = outputs;
+        step.output_source = Some(source);
+    }
+
+    fn simulate_loop(
+        &mut self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        index: usize,
+        data: &LoopNodeData,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        let first_step = self.steps.len();
+        let extra_before = (self.extra_duration_ms, self.extra_cost);
+        let mut walked = 0;
+        let total = match data.loop_type {
+            LoopType::Count => {
+                let total = data.iterations.unwrap_or(1).max(0) as usize;
+                while walked < total.min(MAX_SIMULATED_ITERATIONS) {
+                    context.set_variable(data.item_variable.clone(), Value::from(walked));
+                    self.iterate(workflow, node, walked, context)?;
+                    walked += 1;
+                }
+                total
+            }
+            LoopType::Condition => {
+                if let Some(condition) = &data.condition {
+                    self.steps[index].notes = self.condition_notes(condition, context);
+                    while context.evaluate(condition) {
+                        if walked == MAX_SIMULATED_ITERATIONS {
+                            self.steps[index].notes.push(format!(
+                                "The condition still held after {} simulated iterations; a run stops after {}",
+                                walked, MAX_LOOP_ITERATIONS
+                            ));
+                            break;
+                        }
+                        self.iterate(workflow, node, walked, context)?;
+                        walked += 1;
+                    }
+                }
+                walked
+            }
+            LoopType::ForEach => {
+                let collection = data.collection.as_deref().unwrap_or_default();
+                let variable = collection_variable(collection);
+                let items = match context.resolve_collection(collection) {
+                    Err(_) if self.sampled.contains(variable) => {
+                        self.steps[index].notes.push(format!(
+                            "`{}` is a sample value; assumed one item",
+                            variable
+                        ));
+                        vec![Value::String(format!("Sample item of {}", variable))]
+                    }
+                    items => items?,
+                };
+                if self.sampled.contains(variable) {
+                    self.sampled.insert(data.item_variable.clone());
+                } else {
+                    self.sampled.remove(&data.item_variable);
+                }
+                let total = items.len();
+                for (i, item) in items.into_iter().take(MAX_SIMULATED_ITERATIONS).enumerate() {
+                    context.set_variable(data.item_variable.clone(), item);
+                    context.set_variable(format!("{}_index", data.item_variable), Value::from(i));
+                    self.iterate(workflow, node, i, context)?;
+                    walked += 1;
+                }
+                total
+            }
+        };
+
+        if walked < total {
+            let (duration_ms, cost) =
+                self.steps[first_step..]
+                    .iter()
+                    .fold((0, 0.0), |(duration_ms, cost), step| {
+                        (
+                            duration_ms + step.estimated_duration_ms,
+                            cost + step.estimated_cost,
+                        )
+                    });
+            let duration_ms = duration_ms + self.extra_duration_ms - extra_before.0;
+            let cost = cost + self.extra_cost - extra_before.1;
+            let remaining = (total - walked) as f64 / walked as f64;
+            self.extra_duration_ms += (duration_ms as f64 * remaining).round() as u64;
+            self.extra_cost += cost * remaining;
+            self.steps[index].notes.push(format!(
+                "Walked {} of {} iterations; the rest are extrapolated",
+                walked, total
+            ));
+        }
+        self.steps[index].iterations = Some(total);
+        Ok(())
+    }
+
+    /// Walk one iteration of a loop body
+    fn iterate(
+        &mut self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        iteration: usize,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        let outer = self.iteration.replace(iteration);
+        let result = self.branch(workflow, node, HANDLE_BODY, context);
+        self.iteration = outer;
+        result
+    }
+
+    fn branch(
+        &mut self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        handle: &str,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        for next_node in branch_nodes(workflow, node, handle, context) {
+            self.node(workflow, next_node, context)?;
+        }
+        Ok(())
+    }
+
+    fn simulate_try(
+        &mut self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        index: usize,
+        data: &TryNodeData,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        match self.branch(workflow, node, HANDLE_TRY, context) {
+            Ok(()) => {
+                self.steps[index].branch = Some(HANDLE_TRY.to_string());
+                Ok(())
+            }
+            Err(e) => {
+                self.failed_node_id = None;
+                let step = &mut self.steps[index];
+                step.branch = Some(HANDLE_CATCH.to_string());
+                step.notes
+                    .push(format!("The try branch is predicted to fail: {}", e));
+                let variable = data
+                    .error_variable
+                    .clone()
+                    .unwrap_or_else(|| "error".to_string());
+                self.sampled.remove(&variable);
+                context.set_variable(variable, Value::String(e));
+                self.branch(workflow, node, HANDLE_CATCH, context)
+            }
+        }
+    }
+
+    fn simulate_sub_workflow(
+        &mut self,
+        index: usize,
+        data: &SubWorkflowNodeData,
+        context: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        if context.depth >= MAX_SUB_WORKFLOW_DEPTH {
+            return Err(format!(
+                "Sub-workflows nested more than {} deep",
+                MAX_SUB_WORKFLOW_DEPTH
+            ));
+        }
+        let workflow = self.engine.get_workflow(&data.workflow_id)?;
+        let start = workflow
+            .start_node()
+            .ok_or_else(|| format!("Sub-workflow {} failed: No start node found", workflow.name))?;
+        let inputs: HashMap<String, Value> = data
+            .input_mapping
+            .iter()
+            .filter_map(|(input, variable)| {
+                context
+                    .get_variable(variable)
+                    .map(|value| (input.clone(), value.clone()))
+            })
+            .collect();
+
+        let mut sub_context = ExecutionContext::new(String::new(), workflow.id.clone(), inputs);
+        sub_context.depth = context.depth + 1;
+        let outer = self.iteration.take();
+        let result = self.node(&workflow, start, &mut sub_context);
+        self.iteration = outer;
+        result.map_err(|e| format!("Sub-workflow {} failed: {}", workflow.name, e))?;
+
+        for (sub_variable, variable) in &data.output_mapping {
+            if let Some(value) = sub_context.get_variable(sub_variable) {
+                context.set_variable(variable.clone(), value.clone());
+                if self.sampled.contains(sub_variable) {
+                    self.sampled.insert(variable.clone());
+                } else {
+                    self.sampled.remove(variable);
+                }
+                self.steps[index]
+                    .outputs
+                    .insert(variable.clone(), value.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Duration and cost of the node itself. Loops, try and sub-workflow
+    /// nodes cost nothing beyond the steps they run
+    fn estimate(&mut self, workflow: &WorkflowDefinition, node: &WorkflowNode) -> (u64, f64) {
+        let history = self
+            .history(&workflow.id)
+            .get(node.id())
+            .and_then(NodeHistory::average_duration_ms);
+        match node {
+            WorkflowNode::DecisionNode { .. }
+            | WorkflowNode::LoopNode { .. }
+            | WorkflowNode::TryNode { .. }
+            | WorkflowNode::SubWorkflowNode { .. } => (0, 0.0),
+            WorkflowNode::WaitNode { data, .. } => {
+                let duration_ms = match data.wait_type {
+                    WaitType::Duration => data.duration_seconds.unwrap_or(0).max(0) as u64 * 1000,
+                    WaitType::UntilTime => data.until_time.map_or(0, |until| {
+                        (until - Utc::now().timestamp()).max(0) as u64 * 1000
+                    }),
+                    WaitType::Condition => history.unwrap_or(DEFAULT_STEP_MS),
+                };
+                (duration_ms, 0.0)
+            }
+            WorkflowNode::ToolNode { data, .. } => {
+                let ledger = self.tool_costs().get(&data.tool_name);
+                let duration_ms = history
+                    .or_else(|| ledger.map(|entry| entry.avg_duration_ms.round() as u64))
+                    .unwrap_or(DEFAULT_STEP_MS);
+                let cost = ledger
+                    .filter(|entry| entry.calls > 0)
+                    .map_or(0.0, |entry| entry.total_cost / entry.calls as f64);
+                (duration_ms, cost)
+            }
+            _ => (history.unwrap_or(DEFAULT_STEP_MS), 0.0),
+        }
+    }
+
+    /// Notes on variables a condition reads that are unset or sample values
+    fn condition_notes(&self, condition: &str, context: &ExecutionContext) -> Vec<String> {
+        static VARIABLE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"\$([A-Za-z0-9_]+)|\{\{\s*([A-Za-z0-9_]+)").unwrap());
+
+        let mut notes = Vec::new();
+        for caps in VARIABLE.captures_iter(condition) {
+            let Some(name) = caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str()) else {
+                continue;
+            };
+            let note = if context.get_variable(name).is_none() {
+                format!("`{}` is not set", name)
+            } else if self.sampled.contains(name) {
+                format!("`{}` is a sample value", name)
+            } else {
+                continue;
+            };
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+        notes
+    }
+
+    fn history(&mut self, workflow_id: &str) -> &HashMap<String, NodeHistory> {
+        if !self.histories.contains_key(workflow_id) {
+            let history = load_history(self.engine, workflow_id).unwrap_or_else(|e| {
+                tracing::debug!("No run history for workflow {}: {}", workflow_id, e);
+                HashMap::new()
+            });
+            self.histories.insert(workflow_id.to_string(), history);
+        }
+        &self.histories[workflow_id]
+    }
+
+    fn tool_costs(&mut self) -> &HashMap<String, ToolCostBreakdown> {
+        let engine = self.engine;
+        self.tool_costs.get_or_insert_with(|| {
+            let now = Utc::now();
+            engine
+                .get_connection()
+                .and_then(|conn| {
+                    tool_cost_breakdown(&conn, now - chrono::Duration::days(TOOL_COST_DAYS), now)
+                        .map_err(|e| e.to_string())
+                })
+                .map(|breakdown| {
+                    breakdown
+                        .into_iter()
+                        .map(|entry| (entry.tool_id.clone(), entry))
+                        .collect()
+                })
+                .unwrap_or_else(|e| {
+                    tracing::debug!("No tool cost history: {}", e);
+                    HashMap::new()
+                })
+        })
+    }
+}
+
+/// Durations and latest outputs of each node over the workflow's recent runs
+fn load_history(
+    engine: &WorkflowEngine,
+    workflow_id: &str,
+) -> Result<HashMap<String, NodeHistory>, String> {
+    let conn = engine.get_connection()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT execution_id, node_id, event_type, data, timestamp
+             FROM workflow_execution_logs
+             WHERE event_type IN ('started', 'completed') AND execution_id IN (
+                 SELECT id FROM workflow_executions WHERE workflow_id = ?1
+                 ORDER BY started_at DESC LIMIT ?2
+             )
+             ORDER BY timestamp ASC, rowid ASC",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let rows = stmt
+        .query_map(rusqlite::params![workflow_id, HISTORY_RUNS], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to query logs: {}", e))?;
+
+    let mut started: HashMap<(String, String), i64> = HashMap::new();
+    let mut history: HashMap<String, NodeHistory> = HashMap::new();
+    for row in rows {
+        let (execution_id, node_id, event_type, data, timestamp) =
+            row.map_err(|e| format!("Failed to read log: {}", e))?;
+        if event_type == "started" {
+            started.insert((execution_id, node_id), timestamp);
+            continue;
+        }
+
+        let entry = history.entry(node_id.clone()).or_default();
+        if let Some(start) = started.remove(&(execution_id, node_id)) {
+            entry
+                .durations_ms
+                .push((timestamp - start).max(0) as u64 * 1000);
+        }
+        let outputs = data
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+            .and_then(|data| serde_json::from_value(data.get("outputs")?.clone()).ok());
+        if outputs.is_some() {
+            entry.outputs = outputs;
+        }
+    }
+    Ok(history)
+}
+
+/// Variable a for-each collection refers to
+fn collection_variable(collection: &str) -> &str {
+    collection
+        .trim()
+        .trim_start_matches(['$', '{', ' '])
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(dir: &std::path::Path) -> WorkflowEngine {
+        let db_path = dir.join("test.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE workflow_definitions (
+                id TEXT PRIMARY KEY, user_id TEXT NOT NULL, name TEXT NOT NULL,
+                description TEXT, nodes TEXT NOT NULL, edges TEXT NOT NULL,
+                triggers TEXT, metadata TEXT, created_at INTEGER, updated_at INTEGER
+            );
+            CREATE TABLE workflow_executions (
+                id TEXT PRIMARY KEY, workflow_id TEXT NOT NULL, status TEXT NOT NULL,
+                current_node_id TEXT, inputs TEXT, outputs TEXT, error TEXT,
+                started_at INTEGER, completed_at INTEGER
+            );
+            CREATE TABLE workflow_execution_logs (
+                id TEXT PRIMARY KEY, execution_id TEXT NOT NULL, node_id TEXT NOT NULL,
+                event_type TEXT NOT NULL, data TEXT, timestamp INTEGER
+            );
+            CREATE TABLE tool_cost_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, tool_id TEXT NOT NULL,
+                success INTEGER NOT NULL, duration_ms INTEGER NOT NULL,
+                cost REAL NOT NULL DEFAULT 0.0, units TEXT, source TEXT,
+                created_at TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        WorkflowEngine::new(db_path.to_string_lossy().to_string())
+    }
+
+    fn node(id: &str, node_type: &str, data: Value) -> WorkflowNode {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": node_type,
+            "position": { "x": 0, "y": 0 },
+            "data": data,
+        }))
+        .unwrap()
+    }
+
+    fn tool(id: &str) -> WorkflowNode {
+        node(
+            id,
+            "tool",
+            serde_json::json!({ "label": id, "tool_name": id, "tool_input": {} }),
+        )
+    }
+
+    fn edge(source: &str, target: &str, handle: Option<&str>) -> WorkflowEdge {
+        WorkflowEdge {
+            id: format!("{}-{}", source, target),
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: handle.map(str::to_string),
+            target_handle: None,
+            condition: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_simulation_uses_history_and_extrapolates_loops() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = engine(dir.path());
+        let workflow_id = engine
+            .create_workflow(WorkflowDefinition {
+                id: "orders".to_string(),
+                user_id: "user-1".to_string(),
+                name: "Orders".to_string(),
+                description: None,
+                nodes: vec![
+                    tool("fetch"),
+                    node(
+                        "each",
+                        "loop",
+                        serde_json::json!({
+                            "label": "each",
+                            "loop_type": "for_each",
+                            "collection": "fetch_output",
+                            "item_variable": "order"
+                        }),
+                    ),
+                    node(
+                        "check",
+                        "decision",
+                        serde_json::json!({
+                            "label": "large",
+                            "condition": "$order.priority == 'high'",
+                            "condition_type": "expression"
+                        }),
+                    ),
+                    tool("review"),
+                    node(
+                        "pause",
+                        "wait",
+                        serde_json::json!({
+                            "label": "pause",
+                            "wait_type": "duration",
+                            "duration_seconds": 2
+                        }),
+                    ),
+                ],
+                edges: vec![
+                    edge("fetch", "each", None),
+                    edge("each", "check", Some(HANDLE_BODY)),
+                    edge("check", "review", Some(HANDLE_TRUE)),
+                    edge("each", "pause", None),
+                ],
+                triggers: Vec::new(),
+                metadata: HashMap::new(),
+                created_at: 0,
+                updated_at: 0,
+            })
+            .unwrap();
+
+        // Without history, tool outputs are samples
+        let simulation = simulate(&engine, &workflow_id, HashMap::new()).unwrap();
+        assert!(simulation.error.is_none());
+        let path: Vec<&str> = simulation
+            .steps
+            .iter()
+            .map(|step| step.node_id.as_str())
+            .collect();
+        assert_eq!(path, ["fetch", "each", "check", "pause"]);
+        assert_eq!(
+            simulation.steps[0].output_source,
+            Some(OutputSource::Sample)
+        );
+        assert!(simulation.steps[2].notes[0].contains("sample"));
+        assert_eq!(simulation.unreached_nodes, ["review"]);
+        assert_eq!(simulation.estimated_duration_ms, DEFAULT_STEP_MS + 2000);
+
+        // A past run supplies the orders and the fetch duration; the ledger
+        // prices reviews
+        let mut orders = vec![serde_json::json!({ "priority": "high" })];
+        orders.extend((0..29).map(|_| serde_json::json!({ "priority": "low" })));
+        let conn = engine.get_connection().unwrap();
+        conn.execute(
+            "INSERT INTO workflow_executions (id, workflow_id, status, started_at)
+             VALUES ('run-1', ?1, 'completed', 100)",
+            [&workflow_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO workflow_execution_logs (id, execution_id, node_id, event_type, data, timestamp)
+             VALUES ('log-1', 'run-1', 'fetch', 'started', 'null', 100),
+                    ('log-2', 'run-1', 'fetch', 'completed', ?1, 103)",
+            [serde_json::json!({ "outputs": { "fetch_output": orders } }).to_string()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO tool_cost_events (tool_id, success, duration_ms, cost, created_at)
+             VALUES ('review', 1, 200, 0.25, strftime('%Y-%m-%d %H:%M:%S', 'now')),
+                    ('review', 1, 400, 0.75, strftime('%Y-%m-%d %H:%M:%S', 'now'))",
+            [],
+        )
+        .unwrap();
+
+        let simulation = simulate(&engine, &workflow_id, HashMap::new()).unwrap();
+        assert_eq!(
+            simulation.steps[0].output_source,
+            Some(OutputSource::Cached)
+        );
+        assert_eq!(simulation.steps[0].estimated_duration_ms, 3000);
+        let each = &simulation.steps[1];
+        assert_eq!(each.iterations, Some(30));
+        assert!(each.notes[0].contains("25 of 30"));
+        let reviews: Vec<&SimulatedStep> = simulation
+            .steps
+            .iter()
+            .filter(|step| step.node_id == "review")
+            .collect();
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0].iteration, Some(0));
+        assert_eq!(reviews[0].output_source, Some(OutputSource::Sample));
+        assert!(simulation.unreached_nodes.is_empty());
+        // fetch 3s, one review 300ms over 25 iterations extrapolated to 30, wait 2s
+        assert_eq!(simulation.estimated_duration_ms, 3000 + 360 + 2000);
+        assert!((simulation.estimated_cost - 0.6).abs() < 1e-9);
+
+        // Nothing was executed
+        let executions: i64 = conn
+            .query_row("SELECT COUNT(*) FROM workflow_executions", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(executions, 1);
+    }
+}
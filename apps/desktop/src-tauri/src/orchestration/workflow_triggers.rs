@@ -0,0 +1,1649 @@
+//! Sources that start workflows on their own.
+//!
+//! A trigger binds one workflow to a source: files created or changed in a
+//! watched folder, newly synced mail matching rule conditions, a signed POST
+//! to the webhook listener, text copied to the clipboard that matches a
+//! pattern, or a global hotkey. Every trigger has its own debounce: events that
+//! arrive within `debounce_ms` of each other start the workflow once, with the
+//! last event's inputs. Triggers are stored in `workflow_triggers` and only
+//! listen while enabled.
+//!
+//! Webhook triggers are called at `/hooks/{path_token}` on the port and
+//! interface in [`WebhookConfig`], and may queue a background task instead of
+//! running a workflow. Their recent requests, rejected ones included, are
+//! kept in `workflow_trigger_deliveries` and can be replayed.
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path as UrlPath, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::Utc;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::OnceCell;
+use parking_lot::{Mutex, RwLock};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::workflow_executor::WorkflowExecutor;
+use super::workflow_webhooks::{
+    self, DeliveryStatus, SignatureScheme, WebhookConfig, WebhookDelivery, WebhookEndpoint,
+    WebhookEndpointCreated, WebhookEndpointInput, WebhookStatus, WebhookTarget, WebhookTask,
+};
+use crate::commands::shortcuts::{Shortcut, ShortcutsState};
+use crate::commands::TaskManagerState;
+use crate::communications::email_rules::RuleConditions;
+use crate::communications::Email;
+use crate::db::pool::{Pool, PooledConnection};
+
+/// Emitted with a [`TriggerFiredEvent`] when a trigger starts its workflow
+pub const TRIGGER_FIRED_EVENT: &str = "workflow://trigger-fired";
+/// Shortcut actions of hotkey triggers are this prefix and the trigger id
+pub const HOTKEY_ACTION_PREFIX: &str = "workflow_trigger:";
+
+const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_WEBHOOK_BODY_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+fn default_file_events() -> Vec<FileChangeKind> {
+    vec![FileChangeKind::Created, FileChangeKind::Modified]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerSource {
+    /// Files in `path` (and below it when `recursive`) whose name matches the
+    /// optional glob `pattern`
+    FileChange {
+        path: String,
+        #[serde(default)]
+        recursive: bool,
+        #[serde(default)]
+        pattern: Option<String>,
+        #[serde(default = "default_file_events")]
+        events: Vec<FileChangeKind>,
+    },
+    /// Newly synced mail; `account_id` of `None` watches every account.
+    /// Conditions are those of email rules, except the LLM category
+    Email {
+        #[serde(default)]
+        account_id: Option<i64>,
+        conditions: RuleConditions,
+    },
+    /// A POST to `/hooks/{path_token}` on the webhook listener, its body
+    /// signed with the trigger's secret the way `scheme` says. With a `task`
+    /// the delivery queues that background task instead of running the
+    /// workflow
+    Webhook {
+        #[serde(default)]
+        scheme: SignatureScheme,
+        /// Kept across edits, or a random one made, when unset
+        #[serde(default)]
+        path_token: Option<String>,
+        #[serde(default)]
+        task: Option<WebhookTask>,
+    },
+    /// Copied text matching the regex `pattern`
+    Clipboard { pattern: String },
+    /// A global shortcut such as "CommandOrControl+Shift+R"
+    Hotkey { key: String },
+}
+
+impl TriggerSource {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TriggerSource::FileChange { .. } => "file_change",
+            TriggerSource::Email { .. } => "email",
+            TriggerSource::Webhook { .. } => "webhook",
+            TriggerSource::Clipboard { .. } => "clipboard",
+            TriggerSource::Hotkey { .. } => "hotkey",
+        }
+    }
+
+    /// Debounce used when none is given: editors save in bursts and
+    /// clipboard managers copy twice
+    pub fn default_debounce_ms(&self) -> u64 {
+        match self {
+            TriggerSource::FileChange { .. } => 1000,
+            TriggerSource::Clipboard { .. } => 500,
+            _ => 0,
+        }
+    }
+
+    pub fn matches_file(&self, kind: FileChangeKind, file: &Path) -> bool {
+        let TriggerSource::FileChange {
+            path,
+            recursive,
+            pattern,
+            events,
+        } = self
+        else {
+            return false;
+        };
+        let Ok(relative) = file.strip_prefix(path) else {
+            return false;
+        };
+        if relative.as_os_str().is_empty()
+            || (!recursive && relative.components().count() != 1)
+            || !events.contains(&kind)
+        {
+            return false;
+        }
+        pattern.as_deref().is_none_or(|pattern| {
+            glob::Pattern::new(pattern).is_ok_and(|pattern| {
+                file.file_name()
+                    .is_some_and(|name| pattern.matches(&name.to_string_lossy()))
+            })
+        })
+    }
+
+    pub fn matches_email(&self, account: i64, email: &Email) -> bool {
+        match self {
+            TriggerSource::Email {
+                account_id,
+                conditions,
+            } => account_id.is_none_or(|id| id == account) && conditions.matches_static(email),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredTrigger {
+    pub id: String,
+    pub workflow_id: String,
+    pub name: String,
+    pub source: TriggerSource,
+    pub enabled: bool,
+    pub debounce_ms: u64,
+    pub fire_count: i64,
+    pub last_fired_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// URL a webhook trigger is called at from this machine
+    #[serde(default, skip_deserializing)]
+    pub webhook_url: Option<String>,
+    /// Key the sender of a webhook trigger signs requests with
+    #[serde(default, skip_deserializing)]
+    pub webhook_secret: Option<String>,
+}
+
+/// Fields a user sets when creating or editing a trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerInput {
+    pub workflow_id: String,
+    pub name: String,
+    pub source: TriggerSource,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Defaults to [`TriggerSource::default_debounce_ms`]
+    #[serde(default)]
+    pub debounce_ms: Option<u64>,
+    /// Signing secret of a webhook trigger, such as Stripe's `whsec_...`;
+    /// the current one is kept, or one generated, when unset
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+impl From<WebhookEndpointInput> for TriggerInput {
+    fn from(input: WebhookEndpointInput) -> Self {
+        let (workflow_id, task) = match input.target {
+            WebhookTarget::Workflow { workflow_id } => (workflow_id, None),
+            WebhookTarget::Task(task) => (String::new(), Some(task)),
+        };
+        Self {
+            workflow_id,
+            name: input.name,
+            source: TriggerSource::Webhook {
+                scheme: input.scheme,
+                path_token: input.path_token,
+                task,
+            },
+            enabled: input.enabled,
+            debounce_ms: None,
+            secret: input.secret,
+        }
+    }
+}
+
+impl TriggerInput {
+    /// Check the source and resolve a watched folder to its canonical path,
+    /// which is how file events report it
+    pub fn validate(&mut self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Trigger name is required".to_string());
+        }
+        let queues_task = matches!(&self.source, TriggerSource::Webhook { task: Some(_), .. });
+        if self.workflow_id.trim().is_empty() && !queues_task {
+            return Err("Trigger needs a workflow".to_string());
+        }
+
+        match &mut self.source {
+            TriggerSource::FileChange {
+                path,
+                pattern,
+                events,
+                ..
+            } => {
+                let folder = crate::filesystem::decode_path(path);
+                if !folder.is_dir() {
+                    return Err(format!("Folder does not exist: {}", path));
+                }
+                *path = folder
+                    .canonicalize()
+                    .map_err(|e| format!("Failed to resolve {}: {}", path, e))?
+                    .to_string_lossy()
+                    .to_string();
+                if let Some(pattern) = pattern {
+                    glob::Pattern::new(pattern)
+                        .map_err(|e| format!("Invalid file pattern: {}", e))?;
+                }
+                if events.is_empty() {
+                    return Err("Pick at least one file event".to_string());
+                }
+            }
+            TriggerSource::Email { conditions, .. } => {
+                if conditions.category.is_some() {
+                    return Err("Email triggers can't match on a category".to_string());
+                }
+                if *conditions == RuleConditions::default() {
+                    return Err("An email trigger needs at least one condition".to_string());
+                }
+                if let Some(pattern) = &conditions.subject_regex {
+                    Regex::new(pattern).map_err(|e| format!("Invalid subject regex: {}", e))?;
+                }
+            }
+            TriggerSource::Webhook {
+                path_token, task, ..
+            } => {
+                if self
+                    .secret
+                    .as_ref()
+                    .is_some_and(|secret| secret.trim().len() < 8)
+                {
+                    return Err("Signing secrets need at least 8 characters".to_string());
+                }
+                if let Some(token) = path_token {
+                    workflow_webhooks::validate_path_token(token)?;
+                }
+                if task
+                    .as_ref()
+                    .is_some_and(|task| task.name.trim().is_empty())
+                {
+                    return Err("Name the task the webhook queues".to_string());
+                }
+            }
+            TriggerSource::Clipboard { pattern } => {
+                if pattern.is_empty() {
+                    return Err("Clipboard pattern is empty".to_string());
+                }
+                Regex::new(pattern).map_err(|e| format!("Invalid clipboard pattern: {}", e))?;
+            }
+            TriggerSource::Hotkey { key } => {
+                if key.trim().is_empty() {
+                    return Err("Hotkey is empty".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerFiredEvent {
+    pub trigger_id: String,
+    pub workflow_id: String,
+    pub execution_id: String,
+}
+
+const TRIGGER_COLUMNS: &str = "id, workflow_id, name, source, enabled, debounce_ms, secret, \
+     fire_count, last_fired_at, created_at, updated_at";
+
+fn map_trigger(row: &Row) -> rusqlite::Result<RegisteredTrigger> {
+    let source: String = row.get(3)?;
+    Ok(RegisteredTrigger {
+        id: row.get(0)?,
+        workflow_id: row.get(1)?,
+        name: row.get(2)?,
+        source: serde_json::from_str(&source).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        enabled: row.get(4)?,
+        debounce_ms: row.get::<_, i64>(5)?.max(0) as u64,
+        webhook_secret: row.get(6)?,
+        fire_count: row.get(7)?,
+        last_fired_at: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        webhook_url: None,
+    })
+}
+
+impl RegisteredTrigger {
+    fn path_token(&self) -> Option<&str> {
+        match &self.source {
+            TriggerSource::Webhook { path_token, .. } => path_token.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn with_webhook_url(mut self, config: &WebhookConfig) -> Self {
+        self.webhook_url = self.path_token().map(|token| config.url(token));
+        self
+    }
+
+    /// The trigger as the webhook endpoint its sender calls
+    pub fn webhook_endpoint(&self) -> Option<WebhookEndpoint> {
+        let TriggerSource::Webhook {
+            scheme,
+            path_token,
+            task,
+        } = &self.source
+        else {
+            return None;
+        };
+        Some(WebhookEndpoint {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            url: self.webhook_url.clone().unwrap_or_default(),
+            path_token: path_token.clone().unwrap_or_default(),
+            scheme: *scheme,
+            target: match task {
+                Some(task) => WebhookTarget::Task(task.clone()),
+                None => WebhookTarget::Workflow {
+                    workflow_id: self.workflow_id.clone(),
+                },
+            },
+            enabled: self.enabled,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            last_delivery_at: self.last_fired_at,
+        })
+    }
+}
+
+/// Triggers of `workflow_id`, or all of them, oldest first
+pub fn list_triggers(
+    conn: &Connection,
+    workflow_id: Option<&str>,
+) -> rusqlite::Result<Vec<RegisteredTrigger>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM workflow_triggers
+         WHERE ?1 IS NULL OR workflow_id = ?1
+         ORDER BY created_at ASC, rowid ASC",
+        TRIGGER_COLUMNS
+    ))?;
+    let config = workflow_webhooks::load_webhook_config(conn);
+    let triggers = stmt
+        .query_map(params![workflow_id], map_trigger)?
+        .map(|trigger| trigger.map(|trigger| trigger.with_webhook_url(&config)))
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(triggers)
+}
+
+pub fn get_trigger(conn: &Connection, id: &str) -> rusqlite::Result<Option<RegisteredTrigger>> {
+    let trigger = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM workflow_triggers WHERE id = ?1",
+                TRIGGER_COLUMNS
+            ),
+            [id],
+            map_trigger,
+        )
+        .optional()?;
+    Ok(trigger
+        .map(|trigger| trigger.with_webhook_url(&workflow_webhooks::load_webhook_config(conn))))
+}
+
+/// Insert a trigger, or replace the one with `id`. A webhook trigger keeps
+/// its secret and path token across edits unless given new ones
+pub fn save_trigger(
+    conn: &Connection,
+    id: Option<&str>,
+    input: &TriggerInput,
+) -> Result<RegisteredTrigger, String> {
+    let existing = match id {
+        Some(id) => Some(
+            get_trigger(conn, id)
+                .map_err(|e| format!("Failed to load trigger: {}", e))?
+                .ok_or_else(|| format!("Trigger {} not found", id))?,
+        ),
+        None => None,
+    };
+    let now = Utc::now().timestamp();
+    let id = id
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut source = input.source.clone();
+    if let TriggerSource::Webhook { path_token, .. } = &mut source {
+        let token = path_token
+            .take()
+            .or_else(|| {
+                existing
+                    .as_ref()
+                    .and_then(|trigger| trigger.path_token().map(str::to_string))
+            })
+            .unwrap_or_else(workflow_webhooks::random_path_token);
+        let taken = list_triggers(conn, None)
+            .map_err(|e| format!("Failed to load triggers: {}", e))?
+            .iter()
+            .any(|trigger| trigger.id != id && trigger.path_token() == Some(token.as_str()));
+        if taken {
+            return Err("Another webhook already uses that path".to_string());
+        }
+        *path_token = Some(token);
+    }
+    let secret = match source {
+        TriggerSource::Webhook { .. } => input
+            .secret
+            .as_ref()
+            .map(|secret| secret.trim().to_string())
+            .or_else(|| {
+                existing
+                    .as_ref()
+                    .and_then(|trigger| trigger.webhook_secret.clone())
+            })
+            .or_else(|| Some(hex::encode(rand::random::<[u8; 32]>()))),
+        _ => None,
+    };
+    let source = serde_json::to_string(&source)
+        .map_err(|e| format!("Failed to serialize trigger source: {}", e))?;
+
+    conn.execute(
+        "INSERT INTO workflow_triggers
+             (id, workflow_id, name, source, enabled, debounce_ms, secret, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)
+         ON CONFLICT(id) DO UPDATE SET
+             workflow_id = excluded.workflow_id, name = excluded.name,
+             source = excluded.source, enabled = excluded.enabled,
+             debounce_ms = excluded.debounce_ms, secret = excluded.secret,
+             updated_at = excluded.updated_at",
+        params![
+            id,
+            input.workflow_id,
+            input.name.trim(),
+            source,
+            input.enabled,
+            input
+                .debounce_ms
+                .unwrap_or_else(|| input.source.default_debounce_ms()) as i64,
+            secret,
+            now,
+        ],
+    )
+    .map_err(|e| format!("Failed to save trigger: {}", e))?;
+
+    get_trigger(conn, &id)
+        .map_err(|e| format!("Failed to load trigger: {}", e))?
+        .ok_or_else(|| format!("Trigger {} not found", id))
+}
+
+/// A delivery's body as workflow inputs: a JSON object's fields, plus the
+/// whole body as `payload` and the `delivery_id`
+fn webhook_inputs(delivery: &WebhookDelivery) -> HashMap<String, Value> {
+    let payload = if delivery.body.trim().is_empty() {
+        Value::Object(Default::default())
+    } else {
+        serde_json::from_str(&delivery.body).unwrap_or_else(|_| Value::from(delivery.body.clone()))
+    };
+    let mut inputs: HashMap<String, Value> = match &payload {
+        Value::Object(fields) => fields.clone().into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    inputs.insert("payload".to_string(), payload);
+    inputs.insert("delivery_id".to_string(), Value::from(delivery.id.clone()));
+    inputs
+}
+
+/// A running webhook listener
+struct WebhookListener {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+/// Connects trigger sources to workflow executions
+pub struct TriggerRegistry {
+    pool: Pool,
+    executor: Arc<WorkflowExecutor>,
+    /// Enabled triggers, loaded when the registry starts
+    triggers: RwLock<HashMap<String, RegisteredTrigger>>,
+    /// Latest event number per trigger; a debounced run only starts if no
+    /// event arrived after it
+    pending: Mutex<HashMap<String, u64>>,
+    app: OnceCell<AppHandle>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    clipboard_polling: AtomicBool,
+    /// Serves the webhook listener while a webhook trigger is enabled
+    webhook_listener: tokio::sync::Mutex<Option<WebhookListener>>,
+}
+
+impl TriggerRegistry {
+    pub fn new(pool: Pool, executor: Arc<WorkflowExecutor>) -> Self {
+        Self {
+            pool,
+            executor,
+            triggers: RwLock::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            app: OnceCell::new(),
+            watcher: Mutex::new(None),
+            clipboard_polling: AtomicBool::new(false),
+            webhook_listener: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    fn get_connection(&self) -> Result<PooledConnection, String> {
+        self.pool
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))
+    }
+
+    /// Load the enabled triggers and start listening to their sources.
+    /// Returns how many are enabled
+    pub fn start(self: &Arc<Self>, app: AppHandle) -> Result<usize, String> {
+        let _ = self.app.set(app);
+        self.reload()?;
+        Ok(self.triggers.read().len())
+    }
+
+    /// Re-read triggers after a change and update what is listened to
+    fn reload(self: &Arc<Self>) -> Result<(), String> {
+        if self.app.get().is_none() {
+            return Ok(());
+        }
+        let conn = self.get_connection()?;
+        let enabled: HashMap<String, RegisteredTrigger> = list_triggers(&conn, None)
+            .map_err(|e| format!("Failed to load triggers: {}", e))?
+            .into_iter()
+            .filter(|trigger| trigger.enabled)
+            .map(|trigger| (trigger.id.clone(), trigger))
+            .collect();
+        *self.triggers.write() = enabled;
+
+        self.sync_file_watcher();
+        self.sync_clipboard_poller();
+        let registry = Arc::clone(self);
+        tauri::async_runtime::spawn(async move {
+            registry.sync_hotkeys().await;
+            if let Err(e) = registry.sync_webhook_listener().await {
+                tracing::warn!("{}", e);
+            }
+        });
+        Ok(())
+    }
+
+    pub fn list(&self, workflow_id: Option<&str>) -> Result<Vec<RegisteredTrigger>, String> {
+        let conn = self.get_connection()?;
+        list_triggers(&conn, workflow_id).map_err(|e| format!("Failed to list triggers: {}", e))
+    }
+
+    pub fn create(self: &Arc<Self>, mut input: TriggerInput) -> Result<RegisteredTrigger, String> {
+        input.validate()?;
+        let conn = self.get_connection()?;
+        let trigger = save_trigger(&conn, None, &input)?;
+        self.reload()?;
+        Ok(trigger)
+    }
+
+    pub fn update(
+        self: &Arc<Self>,
+        id: &str,
+        mut input: TriggerInput,
+    ) -> Result<RegisteredTrigger, String> {
+        input.validate()?;
+        let conn = self.get_connection()?;
+        let trigger = save_trigger(&conn, Some(id), &input)?;
+        self.reload()?;
+        Ok(trigger)
+    }
+
+    pub fn set_enabled(
+        self: &Arc<Self>,
+        id: &str,
+        enabled: bool,
+    ) -> Result<RegisteredTrigger, String> {
+        let conn = self.get_connection()?;
+        let changed = conn
+            .execute(
+                "UPDATE workflow_triggers SET enabled = ?1, updated_at = ?2 WHERE id = ?3",
+                params![enabled, Utc::now().timestamp(), id],
+            )
+            .map_err(|e| format!("Failed to update trigger: {}", e))?;
+        if changed == 0 {
+            return Err(format!("Trigger {} not found", id));
+        }
+        self.reload()?;
+        get_trigger(&conn, id)
+            .map_err(|e| format!("Failed to load trigger: {}", e))?
+            .ok_or_else(|| format!("Trigger {} not found", id))
+    }
+
+    pub fn delete(self: &Arc<Self>, id: &str) -> Result<bool, String> {
+        let deleted = self
+            .get_connection()?
+            .execute("DELETE FROM workflow_triggers WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to delete trigger: {}", e))?;
+        self.pending.lock().remove(id);
+        self.reload()?;
+        Ok(deleted > 0)
+    }
+
+    /// Drop the triggers of a deleted workflow
+    pub fn delete_for_workflow(self: &Arc<Self>, workflow_id: &str) -> Result<usize, String> {
+        let deleted = self
+            .get_connection()?
+            .execute(
+                "DELETE FROM workflow_triggers WHERE workflow_id = ?1",
+                [workflow_id],
+            )
+            .map_err(|e| format!("Failed to delete triggers: {}", e))?;
+        self.reload()?;
+        Ok(deleted)
+    }
+
+    fn enabled_where(&self, filter: impl Fn(&TriggerSource) -> bool) -> Vec<RegisteredTrigger> {
+        self.triggers
+            .read()
+            .values()
+            .filter(|trigger| filter(&trigger.source))
+            .cloned()
+            .collect()
+    }
+
+    /// Start the trigger's workflow once its debounce has passed without a
+    /// newer event
+    pub fn fire(self: &Arc<Self>, trigger_id: &str, inputs: HashMap<String, Value>) {
+        let Some(trigger) = self.triggers.read().get(trigger_id).cloned() else {
+            return;
+        };
+        let event = {
+            let mut pending = self.pending.lock();
+            let event = pending.entry(trigger.id.clone()).or_insert(0);
+            *event += 1;
+            *event
+        };
+
+        let registry = Arc::clone(self);
+        tauri::async_runtime::spawn(async move {
+            if trigger.debounce_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(trigger.debounce_ms)).await;
+                if registry.pending.lock().get(&trigger.id) != Some(&event) {
+                    return;
+                }
+            }
+            registry.run(&trigger, inputs).await;
+        });
+    }
+
+    async fn run(&self, trigger: &RegisteredTrigger, mut inputs: HashMap<String, Value>) {
+        let delivery_id = match trigger.source {
+            TriggerSource::Webhook { .. } => inputs
+                .get("delivery_id")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+        if let Err(e) = crate::kill_switch::ensure_allowed("Running workflow triggers") {
+            tracing::warn!("Workflow trigger {} not run: {}", trigger.name, e);
+            self.finish_delivery(delivery_id.as_deref(), Err(&e));
+            return;
+        }
+        inputs.insert(
+            "trigger".to_string(),
+            json!({
+                "id": trigger.id,
+                "name": trigger.name,
+                "type": trigger.source.kind(),
+            }),
+        );
+
+        let task = match &trigger.source {
+            TriggerSource::Webhook { task, .. } => task.as_ref(),
+            _ => None,
+        };
+        let started = match task {
+            Some(task) => self.queue_task(trigger, task, &inputs).await,
+            None => {
+                self.executor
+                    .execute_workflow(trigger.workflow_id.clone(), inputs)
+                    .await
+            }
+        };
+        let execution_id = match started {
+            Ok(execution_id) => execution_id,
+            Err(e) => {
+                tracing::warn!("Workflow trigger {} failed to start: {}", trigger.name, e);
+                self.finish_delivery(delivery_id.as_deref(), Err(&e));
+                return;
+            }
+        };
+        self.finish_delivery(delivery_id.as_deref(), Ok(&execution_id));
+        tracing::info!(
+            "Workflow trigger {} started execution {}",
+            trigger.name,
+            execution_id
+        );
+
+        if let Err(e) = self.get_connection().and_then(|conn| {
+            conn.execute(
+                "UPDATE workflow_triggers
+                 SET fire_count = fire_count + 1, last_fired_at = ?1 WHERE id = ?2",
+                params![Utc::now().timestamp(), trigger.id],
+            )
+            .map_err(|e| e.to_string())
+        }) {
+            tracing::warn!("Failed to record trigger {}: {}", trigger.id, e);
+        }
+        if let (Some(app), None) = (self.app.get(), task) {
+            let _ = app.emit(
+                TRIGGER_FIRED_EVENT,
+                TriggerFiredEvent {
+                    trigger_id: trigger.id.clone(),
+                    workflow_id: trigger.workflow_id.clone(),
+                    execution_id,
+                },
+            );
+        }
+    }
+
+    /// Queue a webhook's background task with the delivery as its payload.
+    /// Returns the task's id
+    async fn queue_task(
+        &self,
+        trigger: &RegisteredTrigger,
+        task: &WebhookTask,
+        inputs: &HashMap<String, Value>,
+    ) -> Result<String, String> {
+        let tasks = self
+            .app
+            .get()
+            .and_then(|app| app.try_state::<TaskManagerState>())
+            .ok_or("The task manager is not available yet")?;
+        let payload = serde_json::to_string(inputs)
+            .map_err(|e| format!("Failed to serialize task payload: {}", e))?;
+        tasks
+            .0
+            .submit(
+                task.name.clone(),
+                Some(format!("Webhook delivery to '{}'", trigger.name)),
+                task.priority(),
+                Some(payload),
+            )
+            .await
+            .map_err(|e| format!("Failed to submit task: {}", e))
+    }
+
+    /// Note on the webhook delivery that fired a run how the run went
+    fn finish_delivery(&self, delivery_id: Option<&str>, outcome: Result<&str, &str>) {
+        let Some(delivery_id) = delivery_id else {
+            return;
+        };
+        if let Err(e) = self.get_connection().and_then(|conn| {
+            workflow_webhooks::finish_delivery(&conn, delivery_id, outcome)
+                .map_err(|e| e.to_string())
+        }) {
+            tracing::warn!("Failed to record webhook delivery {}: {}", delivery_id, e);
+        }
+    }
+
+    fn sync_file_watcher(self: &Arc<Self>) {
+        let folders: Vec<(PathBuf, RecursiveMode)> = self
+            .enabled_where(|source| matches!(source, TriggerSource::FileChange { .. }))
+            .into_iter()
+            .filter_map(|trigger| match trigger.source {
+                TriggerSource::FileChange {
+                    path, recursive, ..
+                } => Some((
+                    PathBuf::from(path),
+                    if recursive {
+                        RecursiveMode::Recursive
+                    } else {
+                        RecursiveMode::NonRecursive
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let mut slot = self.watcher.lock();
+        *slot = None;
+        if folders.is_empty() {
+            return;
+        }
+
+        let registry: Weak<Self> = Arc::downgrade(self);
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let (Ok(event), Some(registry)) = (res, registry.upgrade()) {
+                registry.on_file_event(&event);
+            }
+        });
+        match watcher {
+            Ok(mut watcher) => {
+                for (folder, mode) in folders {
+                    if let Err(e) = watcher.watch(&folder, mode) {
+                        tracing::warn!("Failed to watch {} for triggers: {}", folder.display(), e);
+                    }
+                }
+                *slot = Some(watcher);
+            }
+            Err(e) => tracing::warn!("Failed to create trigger file watcher: {}", e),
+        }
+    }
+
+    fn on_file_event(self: &Arc<Self>, event: &Event) {
+        let kind = match event.kind {
+            EventKind::Create(_) => FileChangeKind::Created,
+            EventKind::Modify(_) => FileChangeKind::Modified,
+            EventKind::Remove(_) => FileChangeKind::Deleted,
+            _ => return,
+        };
+        for trigger in
+            self.enabled_where(|source| matches!(source, TriggerSource::FileChange { .. }))
+        {
+            if let Some(file) = event
+                .paths
+                .iter()
+                .find(|file| trigger.source.matches_file(kind, file))
+            {
+                self.fire(
+                    &trigger.id,
+                    HashMap::from([
+                        (
+                            "path".to_string(),
+                            Value::from(crate::filesystem::portable_path(file)),
+                        ),
+                        ("event".to_string(), json!(kind)),
+                    ]),
+                );
+            }
+        }
+    }
+
+    /// Start the workflows of email triggers matching newly synced messages
+    pub fn on_emails(self: &Arc<Self>, account_id: i64, emails: &[Email]) {
+        let triggers = self.enabled_where(|source| matches!(source, TriggerSource::Email { .. }));
+        for email in emails {
+            for trigger in &triggers {
+                if trigger.source.matches_email(account_id, email) {
+                    self.fire(
+                        &trigger.id,
+                        HashMap::from([
+                            ("email".to_string(), json!(email)),
+                            ("subject".to_string(), Value::from(email.subject.clone())),
+                            ("from".to_string(), Value::from(email.from.email.clone())),
+                        ]),
+                    );
+                }
+            }
+        }
+    }
+
+    fn sync_clipboard_poller(self: &Arc<Self>) {
+        let watching = |registry: &Self| {
+            !registry
+                .enabled_where(|source| matches!(source, TriggerSource::Clipboard { .. }))
+                .is_empty()
+        };
+        if !watching(self) || self.clipboard_polling.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let registry = Arc::downgrade(self);
+        tauri::async_runtime::spawn(async move {
+            let read = || async {
+                tokio::task::spawn_blocking(crate::clipboard::read_clipboard_text)
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+            };
+            // Only text copied from now on counts
+            let mut last = read().await;
+            let mut ticker = tokio::time::interval(CLIPBOARD_POLL_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let Some(registry) = registry.upgrade() else {
+                    break;
+                };
+                if !watching(&registry) {
+                    registry.clipboard_polling.store(false, Ordering::SeqCst);
+                    // A trigger enabled meanwhile saw this poller still running
+                    if !watching(&registry)
+                        || registry.clipboard_polling.swap(true, Ordering::SeqCst)
+                    {
+                        break;
+                    }
+                }
+
+                let text = read().await;
+                if text.is_some() && text != last {
+                    if let Some(text) = &text {
+                        registry.on_clipboard(text);
+                    }
+                    last = text;
+                }
+            }
+        });
+    }
+
+    fn on_clipboard(self: &Arc<Self>, text: &str) {
+        for trigger in
+            self.enabled_where(|source| matches!(source, TriggerSource::Clipboard { .. }))
+        {
+            let TriggerSource::Clipboard { pattern } = &trigger.source else {
+                continue;
+            };
+            let Some(captures) = Regex::new(pattern).ok().and_then(|re| re.captures(text)) else {
+                continue;
+            };
+            let groups: Vec<Value> = captures
+                .iter()
+                .skip(1)
+                .map(|group| group.map_or(Value::Null, |group| Value::from(group.as_str())))
+                .collect();
+            self.fire(
+                &trigger.id,
+                HashMap::from([
+                    ("text".to_string(), Value::from(text)),
+                    ("match".to_string(), Value::from(&captures[0])),
+                    ("groups".to_string(), Value::Array(groups)),
+                ]),
+            );
+        }
+    }
+
+    /// Register hotkey triggers as global shortcuts whose action routes back
+    /// here through `shortcuts_trigger`
+    async fn sync_hotkeys(&self) {
+        let Some(app) = self.app.get() else {
+            return;
+        };
+        let Some(state) = app.try_state::<Arc<tokio::sync::Mutex<ShortcutsState>>>() else {
+            return;
+        };
+        let wanted: Vec<Shortcut> = self
+            .enabled_where(|source| matches!(source, TriggerSource::Hotkey { .. }))
+            .into_iter()
+            .filter_map(|trigger| match &trigger.source {
+                TriggerSource::Hotkey { key } => Some(Shortcut {
+                    id: format!("{}{}", HOTKEY_ACTION_PREFIX, trigger.id),
+                    key: key.clone(),
+                    description: format!("Run workflow trigger: {}", trigger.name),
+                    action: format!("{}{}", HOTKEY_ACTION_PREFIX, trigger.id),
+                    enabled: true,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let shortcuts_state = state.lock().await;
+        let mut shortcuts = shortcuts_state.shortcuts.lock().await;
+        let mut registered = shortcuts_state.registered_keys.lock().await;
+        let stale: Vec<Shortcut> = shortcuts
+            .values()
+            .filter(|shortcut| {
+                shortcut.id.starts_with(HOTKEY_ACTION_PREFIX)
+                    && !wanted
+                        .iter()
+                        .any(|want| want.id == shortcut.id && want.key == shortcut.key)
+            })
+            .cloned()
+            .collect();
+        for shortcut in stale {
+            shortcuts.remove(&shortcut.id);
+            registered.retain(|key| key != &shortcut.key);
+            let _ = app.emit("shortcut_unregistered", &shortcut.id);
+        }
+        for shortcut in wanted {
+            if !shortcuts.contains_key(&shortcut.id) {
+                registered.push(shortcut.key.clone());
+                let _ = app.emit("shortcut_registered", &shortcut);
+                shortcuts.insert(shortcut.id.clone(), shortcut);
+            }
+        }
+    }
+
+    /// Start a hotkey trigger; `action` is its shortcut action
+    pub fn on_hotkey(self: &Arc<Self>, action: &str) -> bool {
+        let Some(trigger_id) = action.strip_prefix(HOTKEY_ACTION_PREFIX) else {
+            return false;
+        };
+        self.fire(trigger_id, HashMap::new());
+        true
+    }
+
+    /// Listen where [`WebhookConfig`] says while a webhook trigger is
+    /// enabled, moving when the configuration changes
+    async fn sync_webhook_listener(self: &Arc<Self>) -> Result<(), String> {
+        let config = self
+            .pool
+            .run(|conn| Ok::<_, String>(workflow_webhooks::load_webhook_config(conn)))
+            .await?;
+        let wanted = (!self
+            .enabled_where(|source| matches!(source, TriggerSource::Webhook { .. }))
+            .is_empty())
+        .then(|| config.address());
+
+        let mut listener = self.webhook_listener.lock().await;
+        let running = listener
+            .as_ref()
+            .filter(|listener| !listener.task.is_finished())
+            .map(|listener| listener.address);
+        if running == wanted {
+            return Ok(());
+        }
+        if let Some(previous) = listener.take() {
+            previous.task.abort();
+            let _ = previous.task.await;
+            tracing::info!("Workflow webhook listener on {} stopped", previous.address);
+        }
+        let Some(address) = wanted else {
+            return Ok(());
+        };
+
+        let socket = TcpListener::bind(address)
+            .await
+            .map_err(|e| format!("Failed to bind webhook listener on {}: {}", address, e))?;
+        let router = self.webhook_router();
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(socket, router).await {
+                tracing::warn!("Workflow webhook listener failed: {}", e);
+            }
+        });
+        tracing::info!("Workflow webhook listener on {}", address);
+        *listener = Some(WebhookListener { address, task });
+        Ok(())
+    }
+
+    fn webhook_router(self: &Arc<Self>) -> Router {
+        Router::new()
+            .route("/hooks/{path_token}", post(receive_webhook))
+            .layer(DefaultBodyLimit::max(MAX_WEBHOOK_BODY_BYTES))
+            .with_state(Arc::downgrade(self))
+    }
+
+    /// Check a webhook call's signature and record it as a delivery; a
+    /// verified one fires the trigger. Calls to unknown path tokens are not
+    /// recorded
+    fn receive_webhook_call(
+        self: &Arc<Self>,
+        path_token: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+        now: i64,
+    ) -> Result<WebhookDelivery, StatusCode> {
+        let trigger = self
+            .triggers
+            .read()
+            .values()
+            .find(|trigger| trigger.path_token() == Some(path_token))
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let TriggerSource::Webhook { scheme, .. } = trigger.source else {
+            return Err(StatusCode::NOT_FOUND);
+        };
+
+        let mut delivery = WebhookDelivery::new(
+            &trigger.id,
+            headers.iter().map(|(name, value)| {
+                (
+                    name.as_str().to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            }),
+            String::from_utf8_lossy(body).into_owned(),
+        );
+        let verified = match &trigger.webhook_secret {
+            Some(secret) => workflow_webhooks::verify_signature(scheme, secret, headers, body, now),
+            None => Err("Webhook has no secret".to_string()),
+        };
+        match verified {
+            Ok(()) => {
+                delivery.verified = true;
+                delivery.status = DeliveryStatus::Accepted;
+            }
+            Err(e) => {
+                tracing::warn!("Rejected webhook call to '{}': {}", trigger.name, e);
+                delivery.error = Some(e);
+            }
+        }
+        if let Err(e) = self.get_connection().and_then(|conn| {
+            workflow_webhooks::record_delivery(&conn, &delivery).map_err(|e| e.to_string())
+        }) {
+            tracing::warn!("Failed to record webhook delivery: {}", e);
+        }
+        if delivery.verified {
+            self.fire(&trigger.id, webhook_inputs(&delivery));
+        }
+        Ok(delivery)
+    }
+
+    /// Recent webhook deliveries, newest first, of one trigger or all of them
+    pub fn deliveries(
+        &self,
+        trigger_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, String> {
+        let conn = self.get_connection()?;
+        workflow_webhooks::list_deliveries(&conn, trigger_id, limit)
+            .map_err(|e| format!("Failed to list webhook deliveries: {}", e))
+    }
+
+    /// The webhook listener's configuration and address, and every webhook
+    /// endpoint
+    pub async fn webhook_status(&self) -> Result<WebhookStatus, String> {
+        let (config, triggers) = self
+            .pool
+            .run(|conn| -> Result<_, String> {
+                let config = workflow_webhooks::load_webhook_config(conn);
+                let triggers = list_triggers(conn, None)
+                    .map_err(|e| format!("Failed to list triggers: {}", e))?;
+                Ok((config, triggers))
+            })
+            .await?;
+        let address = self
+            .webhook_listener
+            .lock()
+            .await
+            .as_ref()
+            .filter(|listener| !listener.task.is_finished())
+            .map(|listener| listener.address.to_string());
+        Ok(WebhookStatus {
+            config,
+            address,
+            endpoints: triggers
+                .iter()
+                .filter_map(RegisteredTrigger::webhook_endpoint)
+                .collect(),
+        })
+    }
+
+    /// Save where the webhook listener binds and move it there
+    pub async fn configure_webhooks(
+        self: &Arc<Self>,
+        config: WebhookConfig,
+    ) -> Result<WebhookStatus, String> {
+        self.pool
+            .run(move |conn| workflow_webhooks::save_webhook_config(conn, &config))
+            .await?;
+        self.sync_webhook_listener().await?;
+        self.webhook_status().await
+    }
+
+    /// Add a webhook trigger. Its signing secret is only returned here
+    pub fn create_webhook_endpoint(
+        self: &Arc<Self>,
+        input: WebhookEndpointInput,
+    ) -> Result<WebhookEndpointCreated, String> {
+        let trigger = self.create(input.into())?;
+        Ok(WebhookEndpointCreated {
+            secret: trigger.webhook_secret.clone().unwrap_or_default(),
+            endpoint: trigger
+                .webhook_endpoint()
+                .ok_or("Trigger is not a webhook")?,
+        })
+    }
+
+    pub fn update_webhook_endpoint(
+        self: &Arc<Self>,
+        id: &str,
+        input: WebhookEndpointInput,
+    ) -> Result<WebhookEndpoint, String> {
+        self.webhook_trigger(id)?;
+        self.update(id, input.into())?
+            .webhook_endpoint()
+            .ok_or_else(|| "Trigger is not a webhook".to_string())
+    }
+
+    pub fn delete_webhook_endpoint(self: &Arc<Self>, id: &str) -> Result<bool, String> {
+        self.webhook_trigger(id)?;
+        self.delete(id)
+    }
+
+    fn webhook_trigger(&self, id: &str) -> Result<RegisteredTrigger, String> {
+        let conn = self.get_connection()?;
+        get_trigger(&conn, id)
+            .map_err(|e| format!("Failed to load trigger: {}", e))?
+            .filter(|trigger| matches!(trigger.source, TriggerSource::Webhook { .. }))
+            .ok_or_else(|| format!("Webhook endpoint {} not found", id))
+    }
+
+    /// Fire a verified delivery's trigger again with its body, recorded as a
+    /// new delivery
+    pub fn replay(self: &Arc<Self>, delivery_id: &str) -> Result<WebhookDelivery, String> {
+        let conn = self.get_connection()?;
+        let original = workflow_webhooks::get_delivery(&conn, delivery_id)
+            .map_err(|e| format!("Failed to load webhook delivery: {}", e))?
+            .ok_or_else(|| format!("Webhook delivery {} not found", delivery_id))?;
+        if !original.verified {
+            return Err("Only deliveries with a valid signature can be replayed".to_string());
+        }
+        if !self.triggers.read().contains_key(&original.trigger_id) {
+            return Err("The delivery's trigger is disabled or was deleted".to_string());
+        }
+
+        let mut delivery =
+            WebhookDelivery::new(&original.trigger_id, original.headers, original.body);
+        delivery.verified = true;
+        delivery.status = DeliveryStatus::Accepted;
+        delivery.replay_of = Some(original.id);
+        workflow_webhooks::record_delivery(&conn, &delivery)
+            .map_err(|e| format!("Failed to record webhook delivery: {}", e))?;
+        self.fire(&delivery.trigger_id, webhook_inputs(&delivery));
+        Ok(delivery)
+    }
+}
+
+async fn receive_webhook(
+    State(registry): State<Weak<TriggerRegistry>>,
+    UrlPath(path_token): UrlPath<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(registry) = registry.upgrade() else {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    };
+    match registry.receive_webhook_call(&path_token, &headers, &body, Utc::now().timestamp()) {
+        Ok(delivery) if delivery.verified => (
+            StatusCode::ACCEPTED,
+            Json(json!({ "accepted": true, "delivery_id": delivery.id })),
+        )
+            .into_response(),
+        Ok(delivery) => (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": delivery.error, "delivery_id": delivery.id })),
+        )
+            .into_response(),
+        Err(status) => (status, Json(json!({ "error": "Unknown webhook" }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::WorkflowEngine;
+    use std::net::Ipv4Addr;
+
+    fn registry() -> Arc<TriggerRegistry> {
+        let pool = Pool::in_memory().unwrap();
+        pool.get()
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE workflow_triggers (
+                    id TEXT PRIMARY KEY, workflow_id TEXT NOT NULL, name TEXT NOT NULL,
+                    source TEXT NOT NULL, enabled INTEGER NOT NULL DEFAULT 1,
+                    debounce_ms INTEGER NOT NULL DEFAULT 0, secret TEXT,
+                    fire_count INTEGER NOT NULL DEFAULT 0, last_fired_at INTEGER,
+                    created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL
+                );
+                CREATE TABLE workflow_trigger_deliveries (
+                    id TEXT PRIMARY KEY, trigger_id TEXT NOT NULL, received_at INTEGER NOT NULL,
+                    headers TEXT NOT NULL, body TEXT NOT NULL, verified INTEGER NOT NULL,
+                    status TEXT NOT NULL, error TEXT, execution_id TEXT, replay_of TEXT
+                );
+                CREATE TABLE settings (
+                    key TEXT PRIMARY KEY, value TEXT NOT NULL, encrypted INTEGER NOT NULL
+                );",
+            )
+            .unwrap();
+        let engine = Arc::new(WorkflowEngine::new(
+            pool.path().to_string_lossy().to_string(),
+        ));
+        Arc::new(TriggerRegistry::new(
+            pool,
+            Arc::new(WorkflowExecutor::new(engine)),
+        ))
+    }
+
+    #[test]
+    fn test_file_trigger_matching() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut input = TriggerInput {
+            workflow_id: "wf-1".to_string(),
+            name: "Invoices".to_string(),
+            source: TriggerSource::FileChange {
+                path: dir.path().to_string_lossy().to_string(),
+                recursive: false,
+                pattern: Some("*.pdf".to_string()),
+                events: vec![FileChangeKind::Created],
+            },
+            enabled: true,
+            debounce_ms: None,
+            secret: None,
+        };
+        input.validate().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let source = &input.source;
+
+        assert!(source.matches_file(FileChangeKind::Created, &root.join("march.pdf")));
+        assert!(!source.matches_file(FileChangeKind::Modified, &root.join("march.pdf")));
+        assert!(!source.matches_file(FileChangeKind::Created, &root.join("notes.txt")));
+        assert!(!source.matches_file(FileChangeKind::Created, &root.join("old/march.pdf")));
+        assert!(!source.matches_file(FileChangeKind::Created, &root));
+        assert_eq!(source.default_debounce_ms(), 1000);
+
+        input.source = TriggerSource::FileChange {
+            path: dir.path().join("missing").to_string_lossy().to_string(),
+            recursive: false,
+            pattern: None,
+            events: default_file_events(),
+        };
+        assert!(input.validate().is_err());
+    }
+
+    fn webhook_input(name: &str, scheme: SignatureScheme) -> TriggerInput {
+        TriggerInput {
+            workflow_id: "wf-1".to_string(),
+            name: name.to_string(),
+            source: TriggerSource::Webhook {
+                scheme,
+                path_token: None,
+                task: None,
+            },
+            enabled: true,
+            // Keeps the workflow from starting while the test runs
+            debounce_ms: Some(60_000),
+            secret: None,
+        }
+    }
+
+    fn signed(secret: &str, timestamp: i64, body: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            workflow_webhooks::WEBHOOK_TIMESTAMP_HEADER,
+            timestamp.into(),
+        );
+        headers.insert(
+            workflow_webhooks::WEBHOOK_SIGNATURE_HEADER,
+            format!(
+                "sha256={}",
+                workflow_webhooks::webhook_signature(secret, timestamp, body)
+            )
+            .parse()
+            .unwrap(),
+        );
+        headers.insert("authorization", "Bearer hidden".parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_webhook_calls_are_verified_recorded_and_replayable() {
+        let registry = registry();
+        let conn = registry.get_connection().unwrap();
+        let trigger = save_trigger(
+            &conn,
+            None,
+            &webhook_input("Deploy", SignatureScheme::Generic),
+        )
+        .unwrap();
+        let secret = trigger.webhook_secret.clone().unwrap();
+        let token = trigger.path_token().unwrap().to_string();
+        assert_eq!(
+            trigger.webhook_url,
+            Some(WebhookConfig::default().url(&token))
+        );
+        registry
+            .triggers
+            .write()
+            .insert(trigger.id.clone(), trigger.clone());
+
+        let now = 1_700_000_000;
+        let body = br#"{"branch":"main"}"#;
+        let accepted = registry
+            .receive_webhook_call(&token, &signed(&secret, now, body), body, now + 10)
+            .unwrap();
+        assert!(accepted.verified);
+        assert_eq!(accepted.status, DeliveryStatus::Accepted);
+        assert!(!accepted.headers.contains_key("authorization"));
+        let inputs = webhook_inputs(&accepted);
+        assert_eq!(inputs["branch"], "main");
+        assert_eq!(inputs["payload"], json!({ "branch": "main" }));
+        assert_eq!(inputs["delivery_id"], json!(accepted.id));
+
+        // The signature covers the body and goes stale
+        let forged = registry
+            .receive_webhook_call(
+                &token,
+                &signed(&secret, now, body),
+                br#"{"branch":"prod"}"#,
+                now,
+            )
+            .unwrap();
+        assert_eq!(forged.status, DeliveryStatus::Rejected);
+        let stale = registry
+            .receive_webhook_call(&token, &signed(&secret, now, body), body, now + 3600)
+            .unwrap();
+        assert!(!stale.verified);
+        assert_eq!(
+            registry
+                .receive_webhook_call(&trigger.id, &signed(&secret, now, body), body, now)
+                .unwrap_err(),
+            StatusCode::NOT_FOUND
+        );
+
+        let deliveries = registry.deliveries(Some(&trigger.id), 10).unwrap();
+        assert_eq!(deliveries.len(), 3);
+        assert!(registry.replay(&forged.id).is_err());
+        let replayed = registry.replay(&accepted.id).unwrap();
+        assert_eq!(replayed.replay_of.as_deref(), Some(accepted.id.as_str()));
+        assert_eq!(replayed.body, accepted.body);
+
+        // Editing the trigger keeps its secret and path unless given new ones
+        let mut input = webhook_input("Deploy main", SignatureScheme::Stripe);
+        input.enabled = false;
+        let renamed = save_trigger(&conn, Some(&trigger.id), &input).unwrap();
+        assert_eq!(renamed.webhook_secret, Some(secret));
+        assert_eq!(renamed.path_token(), Some(token.as_str()));
+        assert!(!renamed.enabled);
+        input.secret = Some("whsec_test".to_string());
+        let rekeyed = save_trigger(&conn, Some(&trigger.id), &input).unwrap();
+        assert_eq!(rekeyed.webhook_secret.as_deref(), Some("whsec_test"));
+        assert_eq!(list_triggers(&conn, Some("wf-1")).unwrap().len(), 1);
+
+        input.secret = Some("short".to_string());
+        assert!(input.validate().is_err());
+    }
+
+    fn endpoint_input(target: WebhookTarget, path_token: Option<&str>) -> WebhookEndpointInput {
+        WebhookEndpointInput {
+            name: "Zapier".to_string(),
+            scheme: SignatureScheme::Generic,
+            target,
+            secret: Some("zapier-secret".to_string()),
+            path_token: path_token.map(str::to_string),
+            enabled: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_endpoints_take_signed_deliveries_over_http() {
+        let registry = registry();
+        let created = registry
+            .create_webhook_endpoint(endpoint_input(
+                WebhookTarget::Task(WebhookTask {
+                    name: "sync-crm".to_string(),
+                    priority: Some("High".to_string()),
+                }),
+                None,
+            ))
+            .unwrap();
+        let endpoint = created.endpoint;
+        assert_eq!(created.secret, "zapier-secret");
+        assert_eq!(endpoint.path_token.len(), 32);
+        assert_eq!(
+            endpoint.url,
+            WebhookConfig::default().url(&endpoint.path_token)
+        );
+        let trigger = registry.webhook_trigger(&endpoint.id).unwrap();
+        assert!(trigger.workflow_id.is_empty());
+        registry
+            .triggers
+            .write()
+            .insert(trigger.id.clone(), trigger);
+
+        let socket = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let base = format!("http://{}", socket.local_addr().unwrap());
+        let router = registry.webhook_router();
+        tokio::spawn(async move { axum::serve(socket, router).await });
+        let http = reqwest::Client::new();
+        let body = r#"{"contact":"ada@example.com"}"#;
+        let now = Utc::now().timestamp();
+        let post = |path: &str, secret: &str| {
+            http.post(format!("{}/hooks/{}", base, path))
+                .headers(signed(secret, now, body.as_bytes()))
+                .body(body)
+                .send()
+        };
+
+        // The trigger id is not a path to it
+        let by_id = post(&endpoint.id, "zapier-secret").await.unwrap();
+        assert_eq!(by_id.status(), StatusCode::NOT_FOUND);
+        let forged = post(&endpoint.path_token, "wrong-secret").await.unwrap();
+        assert_eq!(forged.status(), StatusCode::UNAUTHORIZED);
+        let accepted = post(&endpoint.path_token, "zapier-secret").await.unwrap();
+        assert_eq!(accepted.status(), StatusCode::ACCEPTED);
+
+        // Without the app the task can't be queued, which the delivery notes
+        let mut deliveries = Vec::new();
+        for _ in 0..100 {
+            deliveries = registry.deliveries(Some(&endpoint.id), 10).unwrap();
+            if deliveries[0].status == DeliveryStatus::Failed {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(deliveries.len(), 2);
+        assert!(deliveries[0].verified);
+        assert_eq!(deliveries[0].body, body);
+        assert_eq!(
+            deliveries[0].error.as_deref(),
+            Some("The task manager is not available yet")
+        );
+        assert!(!deliveries[0].headers.contains_key("authorization"));
+        assert_eq!(deliveries[1].status, DeliveryStatus::Rejected);
+        assert_eq!(registry.deliveries(None, 1).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_endpoints_keep_their_path_and_secret() {
+        let registry = registry();
+        let workflow = WebhookTarget::Workflow {
+            workflow_id: "wf-1".to_string(),
+        };
+        let first = registry
+            .create_webhook_endpoint(endpoint_input(workflow.clone(), None))
+            .unwrap()
+            .endpoint;
+
+        let mut input = endpoint_input(workflow.clone(), None);
+        input.name = "Zapier CRM".to_string();
+        input.secret = None;
+        let renamed = registry.update_webhook_endpoint(&first.id, input).unwrap();
+        assert_eq!(renamed.name, "Zapier CRM");
+        assert_eq!(renamed.path_token, first.path_token);
+        assert_eq!(renamed.target, workflow);
+        assert_eq!(
+            registry.webhook_trigger(&first.id).unwrap().webhook_secret,
+            Some("zapier-secret".to_string())
+        );
+
+        let custom = registry
+            .create_webhook_endpoint(endpoint_input(workflow.clone(), Some("crm-hook")))
+            .unwrap()
+            .endpoint;
+        assert_eq!(custom.path_token, "crm-hook");
+        assert!(registry
+            .update_webhook_endpoint(
+                &first.id,
+                endpoint_input(workflow.clone(), Some("crm-hook"))
+            )
+            .is_err());
+        assert!(registry
+            .create_webhook_endpoint(endpoint_input(workflow.clone(), Some("not a path")))
+            .is_err());
+        assert!(registry
+            .create_webhook_endpoint(endpoint_input(
+                WebhookTarget::Task(WebhookTask {
+                    name: " ".to_string(),
+                    priority: None,
+                }),
+                None,
+            ))
+            .is_err());
+
+        assert_eq!(registry.webhook_status().await.unwrap().endpoints.len(), 2);
+        assert!(registry.delete_webhook_endpoint(&custom.id).unwrap());
+        assert!(registry.delete_webhook_endpoint(&custom.id).is_err());
+        let status = registry.webhook_status().await.unwrap();
+        assert_eq!(status.endpoints.len(), 1);
+        assert_eq!(status.address, None);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_listener_follows_triggers_and_config() {
+        let registry = registry();
+        let trigger = save_trigger(
+            &registry.get_connection().unwrap(),
+            None,
+            &webhook_input("Deploy", SignatureScheme::Github),
+        )
+        .unwrap();
+        let token = trigger.path_token().unwrap().to_string();
+        let port = {
+            let probe = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+            probe.local_addr().unwrap().port()
+        };
+
+        registry
+            .triggers
+            .write()
+            .insert(trigger.id.clone(), trigger.clone());
+        let status = registry
+            .configure_webhooks(WebhookConfig {
+                port,
+                allow_remote: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(status.address, Some(format!("127.0.0.1:{}", port)));
+        assert_eq!(
+            status.endpoints[0].url,
+            format!("http://127.0.0.1:{}/hooks/{}", port, token)
+        );
+
+        registry.triggers.write().clear();
+        registry.sync_webhook_listener().await.unwrap();
+        assert_eq!(registry.webhook_status().await.unwrap().address, None);
+        assert!(TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await.is_ok());
+        assert!(registry
+            .configure_webhooks(WebhookConfig {
+                port: 0,
+                allow_remote: false,
+            })
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,622 @@
+//! Webhook endpoints: signature checks, where the listener binds, and the
+//! deliveries webhook triggers received.
+//!
+//! An endpoint is a webhook trigger seen from the sender's side: a random
+//! path token in its URL, a signing scheme and secret, and what a verified
+//! delivery starts, the trigger's workflow or a background task.
+
+use axum::http::HeaderMap;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use crate::db::repository::{get_setting, set_setting};
+use crate::tasks::types::Priority;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// `sha256=<hex>`, the HMAC-SHA256 of `<timestamp>.<body>` under the
+/// trigger's secret
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-signature";
+/// Unix time the sender signed the request at
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+/// Deliveries kept per trigger; older ones are dropped as new ones arrive
+pub const MAX_DELIVERIES_PER_TRIGGER: usize = 100;
+/// Port of the webhook listener until one is configured
+pub const DEFAULT_WEBHOOK_PORT: u16 = 8790;
+
+const CONFIG_SETTING: &str = "webhooks.config";
+
+/// How far a signature's timestamp may be from now, so a captured request
+/// can't be replayed later
+const WEBHOOK_TOLERANCE_SECS: i64 = 300;
+/// Headers never written to the delivery log
+const REDACTED_HEADERS: &[&str] = &["authorization", "cookie", "proxy-authorization"];
+
+/// How a sender signs its requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    /// [`WEBHOOK_SIGNATURE_HEADER`] and [`WEBHOOK_TIMESTAMP_HEADER`]; scripts,
+    /// Zapier and most other senders
+    #[default]
+    Generic,
+    /// `X-Hub-Signature-256: sha256=<hex>` over the body
+    Github,
+    /// `Stripe-Signature: t=<unix>,v1=<hex>` over `<t>.<body>`
+    Stripe,
+}
+
+fn hmac(secret: &str) -> HmacSha256 {
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size")
+}
+
+fn timestamped_mac(secret: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    let mut mac = hmac(secret);
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// Hex HMAC-SHA256 of `<timestamp>.<body>`, the [`WEBHOOK_SIGNATURE_HEADER`]
+/// of a generic request signed at `timestamp`
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    hex::encode(
+        timestamped_mac(secret, timestamp, body)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+/// Whether `signature` (hex, optionally `sha256=`-prefixed) is what `mac`
+/// computed; compared in constant time
+fn verify_hex(mac: HmacSha256, signature: &str) -> bool {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    hex::decode(signature).is_ok_and(|signature| mac.verify_slice(&signature).is_ok())
+}
+
+fn check_timestamp(timestamp: &str, now: i64) -> Result<i64, String> {
+    let timestamp: i64 = timestamp
+        .trim()
+        .parse()
+        .map_err(|_| "Invalid signature timestamp".to_string())?;
+    if (now - timestamp).abs() > WEBHOOK_TOLERANCE_SECS {
+        return Err("Signature timestamp is outside the tolerance".to_string());
+    }
+    Ok(timestamp)
+}
+
+/// Check a request's signature headers under `scheme` against its body.
+/// `now` is a Unix timestamp
+pub fn verify_signature(
+    scheme: SignatureScheme,
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> Result<(), String> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| format!("Missing {} header", name))
+    };
+    let valid = match scheme {
+        SignatureScheme::Generic => {
+            let signature = header(WEBHOOK_SIGNATURE_HEADER)?;
+            let timestamp = check_timestamp(header(WEBHOOK_TIMESTAMP_HEADER)?, now)?;
+            verify_hex(timestamped_mac(secret, timestamp, body), signature)
+        }
+        SignatureScheme::Github => {
+            let mut mac = hmac(secret);
+            mac.update(body);
+            verify_hex(mac, header("x-hub-signature-256")?)
+        }
+        SignatureScheme::Stripe => {
+            let mut timestamp = None;
+            let mut signatures = Vec::new();
+            for part in header("stripe-signature")?.split(',') {
+                match part.trim().split_once('=') {
+                    Some(("t", t)) => timestamp = Some(t),
+                    Some(("v1", signature)) => signatures.push(signature),
+                    _ => {}
+                }
+            }
+            let timestamp =
+                check_timestamp(timestamp.ok_or("Stripe-Signature has no timestamp")?, now)?;
+            signatures
+                .iter()
+                .any(|signature| verify_hex(timestamped_mac(secret, timestamp, body), signature))
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err("Signature does not match".to_string())
+    }
+}
+
+/// Where the webhook listener binds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub port: u16,
+    /// Listen on every network interface instead of only 127.0.0.1, for
+    /// senders reaching the machine directly rather than through a tunnel
+    #[serde(default)]
+    pub allow_remote: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_WEBHOOK_PORT,
+            allow_remote: false,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn address(&self) -> SocketAddr {
+        let host = if self.allow_remote {
+            Ipv4Addr::UNSPECIFIED
+        } else {
+            Ipv4Addr::LOCALHOST
+        };
+        SocketAddr::from((host, self.port))
+    }
+
+    /// URL an endpoint with `path_token` is called at from this machine
+    pub fn url(&self, path_token: &str) -> String {
+        format!("http://127.0.0.1:{}/hooks/{}", self.port, path_token)
+    }
+}
+
+pub fn load_webhook_config(conn: &Connection) -> WebhookConfig {
+    get_setting(conn, CONFIG_SETTING)
+        .ok()
+        .and_then(|setting| serde_json::from_str(&setting.value).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_webhook_config(conn: &Connection, config: &WebhookConfig) -> Result<(), String> {
+    if config.port == 0 {
+        return Err("Pick a port for the webhook listener".to_string());
+    }
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize webhook settings: {}", e))?;
+    set_setting(conn, CONFIG_SETTING.to_string(), value, false)
+        .map_err(|e| format!("Failed to save webhook settings: {}", e))
+}
+
+/// A random last URL segment for a new endpoint
+pub fn random_path_token() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
+
+pub fn validate_path_token(token: &str) -> Result<(), String> {
+    let valid = (8..=128).contains(&token.len())
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err("Path tokens are 8 to 128 letters, digits, dashes or underscores".to_string())
+    }
+}
+
+/// Background task a webhook queues, carrying the delivery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookTask {
+    pub name: String,
+    /// "Low", "Normal" or "High"; defaults to "Normal"
+    #[serde(default)]
+    pub priority: Option<String>,
+}
+
+impl WebhookTask {
+    pub fn priority(&self) -> Priority {
+        match self.priority.as_deref() {
+            Some("Low") => Priority::Low,
+            Some("High") => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
+}
+
+/// What a verified delivery starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WebhookTarget {
+    /// Run a saved workflow with the payload's fields as inputs
+    Workflow { workflow_id: String },
+    /// Queue a background task carrying the payload
+    Task(WebhookTask),
+}
+
+/// A webhook trigger as its sender sees it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// Last segment of the endpoint's URL
+    pub path_token: String,
+    pub scheme: SignatureScheme,
+    pub target: WebhookTarget,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_delivery_at: Option<i64>,
+}
+
+/// Fields of an endpoint the user sets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpointInput {
+    pub name: String,
+    #[serde(default)]
+    pub scheme: SignatureScheme,
+    pub target: WebhookTarget,
+    /// Signing secret the sender uses, such as Stripe's `whsec_...`; the
+    /// current one is kept, or one generated, when unset
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Custom last URL segment; the current one is kept, or a random one
+    /// made, when unset
+    #[serde(default)]
+    pub path_token: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+/// A new endpoint and its signing secret, shown once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpointCreated {
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookStatus {
+    pub config: WebhookConfig,
+    /// `host:port` the listener is on; it only listens while an endpoint
+    /// is enabled
+    pub address: Option<String>,
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Verified and handed to its trigger
+    Accepted,
+    /// The signature did not check out
+    Rejected,
+    /// Verified, but the workflow or task could not be started
+    Failed,
+}
+
+impl DeliveryStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            DeliveryStatus::Accepted => "accepted",
+            DeliveryStatus::Rejected => "rejected",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "accepted" => DeliveryStatus::Accepted,
+            "rejected" => DeliveryStatus::Rejected,
+            _ => DeliveryStatus::Failed,
+        }
+    }
+}
+
+/// One request a webhook trigger received, kept for replay and debugging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub trigger_id: String,
+    pub received_at: i64,
+    /// Lower-cased request headers, without credentials
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+    pub verified: bool,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    /// Workflow execution or background task the delivery started
+    pub execution_id: Option<String>,
+    /// Delivery this one replayed
+    pub replay_of: Option<String>,
+}
+
+impl WebhookDelivery {
+    /// A delivery to record; `headers` are filtered of credentials
+    pub fn new(
+        trigger_id: &str,
+        headers: impl IntoIterator<Item = (String, String)>,
+        body: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            trigger_id: trigger_id.to_string(),
+            received_at: Utc::now().timestamp(),
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (name.to_ascii_lowercase(), value))
+                .filter(|(name, _)| !REDACTED_HEADERS.contains(&name.as_str()))
+                .collect(),
+            body,
+            verified: false,
+            status: DeliveryStatus::Rejected,
+            error: None,
+            execution_id: None,
+            replay_of: None,
+        }
+    }
+}
+
+const DELIVERY_COLUMNS: &str =
+    "id, trigger_id, received_at, headers, body, verified, status, error, execution_id, replay_of";
+
+fn map_delivery(row: &Row) -> rusqlite::Result<WebhookDelivery> {
+    let headers: String = row.get(3)?;
+    let status: String = row.get(6)?;
+    Ok(WebhookDelivery {
+        id: row.get(0)?,
+        trigger_id: row.get(1)?,
+        received_at: row.get(2)?,
+        headers: serde_json::from_str(&headers).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        body: row.get(4)?,
+        verified: row.get(5)?,
+        status: DeliveryStatus::parse(&status),
+        error: row.get(7)?,
+        execution_id: row.get(8)?,
+        replay_of: row.get(9)?,
+    })
+}
+
+/// Store a delivery, dropping the trigger's oldest beyond
+/// [`MAX_DELIVERIES_PER_TRIGGER`]
+pub fn record_delivery(conn: &Connection, delivery: &WebhookDelivery) -> rusqlite::Result<()> {
+    let headers = serde_json::to_string(&delivery.headers)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO workflow_trigger_deliveries
+             (id, trigger_id, received_at, headers, body, verified, status, error,
+              execution_id, replay_of)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            delivery.id,
+            delivery.trigger_id,
+            delivery.received_at,
+            headers,
+            delivery.body,
+            delivery.verified,
+            delivery.status.as_str(),
+            delivery.error,
+            delivery.execution_id,
+            delivery.replay_of,
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM workflow_trigger_deliveries
+         WHERE trigger_id = ?1 AND id NOT IN (
+             SELECT id FROM workflow_trigger_deliveries WHERE trigger_id = ?1
+             ORDER BY received_at DESC, rowid DESC LIMIT ?2
+         )",
+        params![delivery.trigger_id, MAX_DELIVERIES_PER_TRIGGER as i64],
+    )?;
+    Ok(())
+}
+
+/// Note the execution or task a delivery started, or why it could not
+/// start one
+pub fn finish_delivery(
+    conn: &Connection,
+    id: &str,
+    outcome: Result<&str, &str>,
+) -> rusqlite::Result<()> {
+    let (status, execution_id, error) = match outcome {
+        Ok(execution_id) => (DeliveryStatus::Accepted, Some(execution_id), None),
+        Err(error) => (DeliveryStatus::Failed, None, Some(error)),
+    };
+    conn.execute(
+        "UPDATE workflow_trigger_deliveries
+         SET status = ?2, execution_id = ?3, error = ?4 WHERE id = ?1",
+        params![id, status.as_str(), execution_id, error],
+    )?;
+    Ok(())
+}
+
+pub fn get_delivery(conn: &Connection, id: &str) -> rusqlite::Result<Option<WebhookDelivery>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM workflow_trigger_deliveries WHERE id = ?1",
+            DELIVERY_COLUMNS
+        ),
+        [id],
+        map_delivery,
+    )
+    .optional()
+}
+
+/// Newest deliveries first, of one trigger or all of them
+pub fn list_deliveries(
+    conn: &Connection,
+    trigger_id: Option<&str>,
+    limit: usize,
+) -> rusqlite::Result<Vec<WebhookDelivery>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM workflow_trigger_deliveries
+         WHERE ?1 IS NULL OR trigger_id = ?1
+         ORDER BY received_at DESC, rowid DESC
+         LIMIT ?2",
+        DELIVERY_COLUMNS
+    ))?;
+    let deliveries = stmt
+        .query_map(params![trigger_id, limit as i64], map_delivery)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(deliveries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, String)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_signatures_are_checked_per_scheme() {
+        let body = br#"{"type":"invoice.paid"}"#;
+        let now = 1_700_000_000;
+        let check = |scheme, secret, headers: &HeaderMap, body: &[u8], at| {
+            verify_signature(scheme, secret, headers, body, at).is_ok()
+        };
+
+        let generic = headers(&[
+            (WEBHOOK_TIMESTAMP_HEADER, now.to_string()),
+            (
+                WEBHOOK_SIGNATURE_HEADER,
+                format!("sha256={}", webhook_signature("topsecret", now, body)),
+            ),
+        ]);
+        assert!(check(
+            SignatureScheme::Generic,
+            "topsecret",
+            &generic,
+            body,
+            now + 10
+        ));
+        assert!(!check(
+            SignatureScheme::Generic,
+            "other",
+            &generic,
+            body,
+            now
+        ));
+        assert!(!check(
+            SignatureScheme::Generic,
+            "topsecret",
+            &generic,
+            b"{}",
+            now
+        ));
+        assert!(!check(
+            SignatureScheme::Generic,
+            "topsecret",
+            &generic,
+            body,
+            now + 3600
+        ));
+        assert!(!check(
+            SignatureScheme::Github,
+            "topsecret",
+            &generic,
+            body,
+            now
+        ));
+
+        let mut mac = hmac("topsecret");
+        mac.update(body);
+        let github = headers(&[(
+            "x-hub-signature-256",
+            format!("sha256={}", hex::encode(mac.finalize().into_bytes())),
+        )]);
+        assert!(check(
+            SignatureScheme::Github,
+            "topsecret",
+            &github,
+            body,
+            now
+        ));
+        assert!(!check(
+            SignatureScheme::Github,
+            "topsecret",
+            &github,
+            b"{}",
+            now
+        ));
+
+        let stripe = headers(&[(
+            "stripe-signature",
+            format!(
+                "t={},v1=deadbeef,v1={}",
+                now,
+                webhook_signature("whsec_test", now, body)
+            ),
+        )]);
+        assert!(check(
+            SignatureScheme::Stripe,
+            "whsec_test",
+            &stripe,
+            body,
+            now + 10
+        ));
+        assert!(!check(
+            SignatureScheme::Stripe,
+            "whsec_test",
+            &stripe,
+            body,
+            now + 3600
+        ));
+    }
+
+    #[test]
+    fn test_deliveries_are_kept_per_trigger_up_to_the_limit() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE workflow_trigger_deliveries (
+                id TEXT PRIMARY KEY, trigger_id TEXT NOT NULL, received_at INTEGER NOT NULL,
+                headers TEXT NOT NULL, body TEXT NOT NULL, verified INTEGER NOT NULL,
+                status TEXT NOT NULL, error TEXT, execution_id TEXT, replay_of TEXT
+            );",
+        )
+        .unwrap();
+
+        for _ in 0..MAX_DELIVERIES_PER_TRIGGER + 5 {
+            let delivery = WebhookDelivery::new(
+                "trigger-1",
+                vec![
+                    ("Content-Type".to_string(), "application/json".to_string()),
+                    ("Authorization".to_string(), "Bearer x".to_string()),
+                ],
+                "{}".to_string(),
+            );
+            record_delivery(&conn, &delivery).unwrap();
+        }
+        let deliveries = list_deliveries(&conn, Some("trigger-1"), 500).unwrap();
+        assert_eq!(deliveries.len(), MAX_DELIVERIES_PER_TRIGGER);
+        assert_eq!(
+            deliveries[0].headers,
+            BTreeMap::from([("content-type".to_string(), "application/json".to_string())])
+        );
+
+        finish_delivery(&conn, &deliveries[0].id, Err("Workflow not found")).unwrap();
+        let failed = get_delivery(&conn, &deliveries[0].id).unwrap().unwrap();
+        assert_eq!(failed.status, DeliveryStatus::Failed);
+        assert_eq!(failed.error.as_deref(), Some("Workflow not found"));
+        assert!(list_deliveries(&conn, Some("trigger-2"), 500)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 pub mod audit;
 pub mod manager;
 pub mod policy;
+pub mod profiles;
 
 pub use audit::*;
 pub use manager::*;
 pub use policy::*;
+pub use profiles::*;
@@ -0,0 +1,673 @@
+//! Role-based permission profiles for agent tool calls.
+//!
+//! A [`PermissionProfile`] maps each [`ToolCategory`] to allow, deny or ask.
+//! Profiles are assigned per hired AI employee and per agent run; the AGI
+//! executor resolves one with [`resolve_profile`] before every tool call that
+//! changes something and audits the outcome with [`record_decision`].
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::db::repository::{get_setting, set_setting};
+
+const DEFAULT_PROFILE_SETTING: &str = "permissions.default_profile";
+
+pub const READ_ONLY_ANALYST: &str = "read_only_analyst";
+pub const DEVELOPER: &str = "developer";
+pub const FULL_AUTOMATION: &str = "full_automation";
+/// Used until the user picks another default
+pub const DEFAULT_PROFILE: &str = DEVELOPER;
+
+/// Kinds of tool calls a profile decides on. Tools outside every category
+/// only read and are always allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCategory {
+    FilesystemWrite,
+    Shell,
+    Browser,
+    UiAutomation,
+    EmailSend,
+    Messaging,
+    Integrations,
+    DatabaseWrite,
+    Payments,
+}
+
+impl ToolCategory {
+    pub const ALL: [ToolCategory; 9] = [
+        ToolCategory::FilesystemWrite,
+        ToolCategory::Shell,
+        ToolCategory::Browser,
+        ToolCategory::UiAutomation,
+        ToolCategory::EmailSend,
+        ToolCategory::Messaging,
+        ToolCategory::Integrations,
+        ToolCategory::DatabaseWrite,
+        ToolCategory::Payments,
+    ];
+
+    /// The category of an AGI tool id, or `None` for read-only tools
+    pub fn of(tool_name: &str) -> Option<Self> {
+        let category = match tool_name {
+            "file_write"
+            | "file_delete"
+            | "document_create_word"
+            | "document_create_excel"
+            | "document_create_pdf"
+            | "git_init"
+            | "git_add"
+            | "git_commit" => Self::FilesystemWrite,
+            "terminal_execute" | "code_execute" | "project_run_task" => Self::Shell,
+            "email_send" => Self::EmailSend,
+            "messaging_send" | "slack_reply" | "slack_react" => Self::Messaging,
+            "api_call"
+            | "api_upload"
+            | "cloud_upload"
+            | "git_push"
+            | "github_create_repo"
+            | "calendar_create_event"
+            | "productivity_create_task" => Self::Integrations,
+            "db_execute"
+            | "db_transaction_begin"
+            | "db_transaction_commit"
+            | "db_transaction_rollback" => Self::DatabaseWrite,
+            "ui_screenshot" => return None,
+            name if name.starts_with("browser_") => Self::Browser,
+            name if name.starts_with("ui_") => Self::UiAutomation,
+            name if name.starts_with("payment_") || name.starts_with("stripe_") => Self::Payments,
+            _ => return None,
+        };
+        Some(category)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FilesystemWrite => "filesystem_write",
+            Self::Shell => "shell",
+            Self::Browser => "browser",
+            Self::UiAutomation => "ui_automation",
+            Self::EmailSend => "email_send",
+            Self::Messaging => "messaging",
+            Self::Integrations => "integrations",
+            Self::DatabaseWrite => "database_write",
+            Self::Payments => "payments",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == value)
+    }
+
+    /// For approval prompts and errors, e.g. "does not allow payments"
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::FilesystemWrite => "file changes",
+            Self::Shell => "shell commands",
+            Self::Browser => "browser automation",
+            Self::UiAutomation => "desktop UI automation",
+            Self::EmailSend => "sending email",
+            Self::Messaging => "sending messages",
+            Self::Integrations => "changes in connected services",
+            Self::DatabaseWrite => "database writes",
+            Self::Payments => "payments",
+        }
+    }
+
+    /// Risk level shown with approval requests
+    pub fn risk(&self) -> &'static str {
+        match self {
+            Self::Shell | Self::EmailSend | Self::Payments => "high",
+            _ => "medium",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolDecision {
+    Allow,
+    Deny,
+    /// Pause the run until the user approves the call
+    Ask,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Categories left out are asked about
+    pub rules: BTreeMap<ToolCategory, ToolDecision>,
+    /// Shipped with the app; cannot be changed or deleted
+    #[serde(default)]
+    pub built_in: bool,
+}
+
+impl PermissionProfile {
+    pub fn decision(&self, category: ToolCategory) -> ToolDecision {
+        self.rules
+            .get(&category)
+            .copied()
+            .unwrap_or(ToolDecision::Ask)
+    }
+}
+
+/// Which profile an agent run uses
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunPermissions {
+    /// Overrides the employee's profile and the default
+    pub profile_id: Option<String>,
+    /// The hired employee (`user_employees.id`) the run works for
+    pub user_employee_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionOutcome {
+    /// Allowed by the profile
+    Allowed,
+    /// Denied by the profile
+    Denied,
+    /// Asked about and approved by the user
+    Approved,
+    /// Asked about and rejected, or nobody could be asked
+    Rejected,
+}
+
+impl DecisionOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allowed => "allowed",
+            Self::Denied => "denied",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    fn from_str(value: &str) -> Self {
+        match value {
+            "allowed" => Self::Allowed,
+            "approved" => Self::Approved,
+            "rejected" => Self::Rejected,
+            _ => Self::Denied,
+        }
+    }
+
+    /// Whether the tool call may go ahead
+    pub fn permits(&self) -> bool {
+        matches!(self, Self::Allowed | Self::Approved)
+    }
+}
+
+/// Audit record of one profile decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDecisionRecord {
+    /// Assigned on insert
+    #[serde(default)]
+    pub id: i64,
+    /// Goal id of the agent run
+    pub run_id: String,
+    pub user_employee_id: Option<String>,
+    pub profile_id: String,
+    pub tool_name: String,
+    pub category: ToolCategory,
+    pub outcome: DecisionOutcome,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn builtin_profiles() -> Vec<PermissionProfile> {
+    use ToolCategory::*;
+    use ToolDecision::*;
+
+    let profile =
+        |id: &str, name: &str, description: &str, rules: &[(ToolCategory, ToolDecision)]| {
+            PermissionProfile {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                rules: rules.iter().copied().collect(),
+                built_in: true,
+            }
+        };
+    vec![
+        profile(
+            READ_ONLY_ANALYST,
+            "Read-only analyst",
+            "Reads files, documents and the web; asks before browsing and never changes anything",
+            &[
+                (FilesystemWrite, Deny),
+                (Shell, Deny),
+                (Browser, Ask),
+                (UiAutomation, Deny),
+                (EmailSend, Deny),
+                (Messaging, Deny),
+                (Integrations, Deny),
+                (DatabaseWrite, Deny),
+                (Payments, Deny),
+            ],
+        ),
+        profile(
+            DEVELOPER,
+            "Developer",
+            "Edits files, runs commands and browses; asks before contacting people or changing data elsewhere",
+            &[
+                (FilesystemWrite, Allow),
+                (Shell, Allow),
+                (Browser, Allow),
+                (UiAutomation, Ask),
+                (EmailSend, Ask),
+                (Messaging, Ask),
+                (Integrations, Ask),
+                (DatabaseWrite, Ask),
+                (Payments, Deny),
+            ],
+        ),
+        profile(
+            FULL_AUTOMATION,
+            "Full automation",
+            "Runs unattended; only payments need approval",
+            &[
+                (FilesystemWrite, Allow),
+                (Shell, Allow),
+                (Browser, Allow),
+                (UiAutomation, Allow),
+                (EmailSend, Allow),
+                (Messaging, Allow),
+                (Integrations, Allow),
+                (DatabaseWrite, Allow),
+                (Payments, Ask),
+            ],
+        ),
+    ]
+}
+
+pub fn builtin_profile(id: &str) -> Option<PermissionProfile> {
+    builtin_profiles()
+        .into_iter()
+        .find(|profile| profile.id == id)
+}
+
+/// Built-in profiles first, then the user's by name
+pub fn list_profiles(conn: &Connection) -> Result<Vec<PermissionProfile>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, description, rules FROM permission_profiles ORDER BY name COLLATE NOCASE",
+    )?;
+    let custom = stmt
+        .query_map([], custom_profile_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut profiles = builtin_profiles();
+    profiles.extend(
+        custom
+            .into_iter()
+            .map(parse_custom_profile)
+            .collect::<Result<Vec<_>>>()?,
+    );
+    Ok(profiles)
+}
+
+pub fn get_profile(conn: &Connection, id: &str) -> Result<Option<PermissionProfile>> {
+    if let Some(profile) = builtin_profile(id) {
+        return Ok(Some(profile));
+    }
+    conn.query_row(
+        "SELECT id, name, description, rules FROM permission_profiles WHERE id = ?1",
+        params![id],
+        custom_profile_from_row,
+    )
+    .optional()?
+    .map(parse_custom_profile)
+    .transpose()
+}
+
+/// Create or replace a custom profile
+pub fn save_profile(conn: &Connection, profile: &PermissionProfile) -> Result<()> {
+    let id = profile.id.trim();
+    if id.is_empty() || profile.name.trim().is_empty() {
+        bail!("Permission profiles need an id and a name");
+    }
+    if builtin_profile(id).is_some() {
+        bail!("Built-in profile '{}' cannot be changed", id);
+    }
+    let now = Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO permission_profiles (id, name, description, rules, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            description = excluded.description,
+            rules = excluded.rules,
+            updated_at = excluded.updated_at",
+        params![
+            id,
+            profile.name.trim(),
+            profile.description,
+            serde_json::to_string(&profile.rules)?,
+            now
+        ],
+    )?;
+    Ok(())
+}
+
+/// Delete a custom profile. Employees using it and the default fall back to
+/// [`DEFAULT_PROFILE`].
+pub fn delete_profile(conn: &mut Connection, id: &str) -> Result<bool> {
+    if builtin_profile(id).is_some() {
+        bail!("Built-in profile '{}' cannot be deleted", id);
+    }
+    let tx = conn.transaction()?;
+    let deleted = tx.execute("DELETE FROM permission_profiles WHERE id = ?1", params![id])? > 0;
+    tx.execute(
+        "UPDATE user_employees SET permission_profile_id = NULL WHERE permission_profile_id = ?1",
+        params![id],
+    )?;
+    tx.execute(
+        "DELETE FROM settings WHERE key = ?1 AND value = ?2",
+        params![DEFAULT_PROFILE_SETTING, id],
+    )?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Profile for runs that name neither a profile nor an employee with one
+pub fn default_profile_id(conn: &Connection) -> String {
+    get_setting(conn, DEFAULT_PROFILE_SETTING)
+        .map(|setting| setting.value)
+        .unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+}
+
+pub fn set_default_profile(conn: &Connection, id: &str) -> Result<()> {
+    if get_profile(conn, id)?.is_none() {
+        bail!("Unknown permission profile '{}'", id);
+    }
+    set_setting(
+        conn,
+        DEFAULT_PROFILE_SETTING.to_string(),
+        id.to_string(),
+        false,
+    )?;
+    Ok(())
+}
+
+/// Profile assigned to a hired employee, if any
+pub fn employee_profile_id(conn: &Connection, user_employee_id: &str) -> Result<Option<String>> {
+    let assigned: Option<Option<String>> = conn
+        .query_row(
+            "SELECT permission_profile_id FROM user_employees WHERE id = ?1",
+            params![user_employee_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match assigned {
+        Some(profile_id) => Ok(profile_id),
+        None => Err(anyhow!("Unknown employee '{}'", user_employee_id)),
+    }
+}
+
+/// Assign a profile to a hired employee, or clear it with `None`
+pub fn assign_employee_profile(
+    conn: &Connection,
+    user_employee_id: &str,
+    profile_id: Option<&str>,
+) -> Result<()> {
+    if let Some(profile_id) = profile_id {
+        if get_profile(conn, profile_id)?.is_none() {
+            bail!("Unknown permission profile '{}'", profile_id);
+        }
+    }
+    let updated = conn.execute(
+        "UPDATE user_employees SET permission_profile_id = ?1 WHERE id = ?2",
+        params![profile_id, user_employee_id],
+    )?;
+    if updated == 0 {
+        bail!("Unknown employee '{}'", user_employee_id);
+    }
+    Ok(())
+}
+
+/// The run's own profile, else its employee's, else the default
+pub fn resolve_profile(conn: &Connection, run: &RunPermissions) -> Result<PermissionProfile> {
+    let id = match (&run.profile_id, &run.user_employee_id) {
+        (Some(profile_id), _) => profile_id.clone(),
+        (None, Some(employee)) => match employee_profile_id(conn, employee)? {
+            Some(profile_id) => profile_id,
+            None => default_profile_id(conn),
+        },
+        (None, None) => default_profile_id(conn),
+    };
+    get_profile(conn, &id)?.ok_or_else(|| anyhow!("Unknown permission profile '{}'", id))
+}
+
+pub fn record_decision(conn: &Connection, record: &ToolDecisionRecord) -> Result<()> {
+    conn.execute(
+        "INSERT INTO tool_permission_decisions
+            (run_id, user_employee_id, profile_id, tool_name, category, outcome, reason, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            record.run_id,
+            record.user_employee_id,
+            record.profile_id,
+            record.tool_name,
+            record.category.as_str(),
+            record.outcome.as_str(),
+            record.reason,
+            record.created_at.timestamp()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Recent decisions, newest first, optionally for one run
+pub fn decision_log(
+    conn: &Connection,
+    run_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ToolDecisionRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, run_id, user_employee_id, profile_id, tool_name, category, outcome, reason, created_at
+         FROM tool_permission_decisions
+         WHERE ?1 IS NULL OR run_id = ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+    let records = stmt
+        .query_map(params![run_id, limit as i64], |row| {
+            let category: String = row.get(5)?;
+            let Some(category) = ToolCategory::from_str(&category) else {
+                return Ok(None);
+            };
+            let outcome: String = row.get(6)?;
+            Ok(Some(ToolDecisionRecord {
+                id: row.get(0)?,
+                run_id: row.get(1)?,
+                user_employee_id: row.get(2)?,
+                profile_id: row.get(3)?,
+                tool_name: row.get(4)?,
+                category,
+                outcome: DecisionOutcome::from_str(&outcome),
+                reason: row.get(7)?,
+                created_at: Utc
+                    .timestamp_opt(row.get(8)?, 0)
+                    .single()
+                    .unwrap_or_default(),
+            }))
+        })?
+        .filter_map(|row| row.transpose())
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(records)
+}
+
+type CustomProfileRow = (String, String, Option<String>, String);
+
+fn custom_profile_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CustomProfileRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+}
+
+fn parse_custom_profile(
+    (id, name, description, rules): CustomProfileRow,
+) -> Result<PermissionProfile> {
+    Ok(PermissionProfile {
+        rules: serde_json::from_str(&rules)
+            .map_err(|e| anyhow!("Invalid rules in permission profile '{}': {}", id, e))?,
+        id,
+        name,
+        description: description.unwrap_or_default(),
+        built_in: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO ai_employees (id, name, role, description, capabilities, estimated_time_saved, estimated_cost_saved, created_at)
+             VALUES ('support', 'Support', 'SupportAgent', '', '[]', 10, 5.0, 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO user_employees (id, user_id, employee_id, hired_at) VALUES ('hired-1', 'user', 'support', 0)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn custom(id: &str, shell: ToolDecision) -> PermissionProfile {
+        PermissionProfile {
+            id: id.to_string(),
+            name: "Ops".to_string(),
+            description: String::new(),
+            rules: [(ToolCategory::Shell, shell)].into_iter().collect(),
+            built_in: false,
+        }
+    }
+
+    #[test]
+    fn categorises_tools() {
+        assert_eq!(
+            ToolCategory::of("file_write"),
+            Some(ToolCategory::FilesystemWrite)
+        );
+        assert_eq!(
+            ToolCategory::of("terminal_execute"),
+            Some(ToolCategory::Shell)
+        );
+        assert_eq!(
+            ToolCategory::of("browser_navigate"),
+            Some(ToolCategory::Browser)
+        );
+        assert_eq!(
+            ToolCategory::of("email_send"),
+            Some(ToolCategory::EmailSend)
+        );
+        assert_eq!(
+            ToolCategory::of("stripe_charge"),
+            Some(ToolCategory::Payments)
+        );
+        assert_eq!(ToolCategory::of("file_read"), None);
+        assert_eq!(ToolCategory::of("ui_screenshot"), None);
+    }
+
+    #[test]
+    fn unlisted_categories_are_asked_about() {
+        let profile = custom("ops", ToolDecision::Allow);
+        assert_eq!(profile.decision(ToolCategory::Shell), ToolDecision::Allow);
+        assert_eq!(profile.decision(ToolCategory::Payments), ToolDecision::Ask);
+    }
+
+    #[test]
+    fn resolves_run_then_employee_then_default() {
+        let conn = setup();
+        save_profile(&conn, &custom("ops", ToolDecision::Deny)).unwrap();
+        let employee_run = RunPermissions {
+            profile_id: None,
+            user_employee_id: Some("hired-1".to_string()),
+        };
+
+        assert_eq!(
+            resolve_profile(&conn, &employee_run).unwrap().id,
+            DEFAULT_PROFILE
+        );
+
+        assign_employee_profile(&conn, "hired-1", Some("ops")).unwrap();
+        assert_eq!(resolve_profile(&conn, &employee_run).unwrap().id, "ops");
+
+        let overridden = RunPermissions {
+            profile_id: Some(READ_ONLY_ANALYST.to_string()),
+            ..employee_run
+        };
+        assert_eq!(
+            resolve_profile(&conn, &overridden).unwrap().id,
+            READ_ONLY_ANALYST
+        );
+
+        set_default_profile(&conn, FULL_AUTOMATION).unwrap();
+        assert_eq!(
+            resolve_profile(&conn, &RunPermissions::default())
+                .unwrap()
+                .id,
+            FULL_AUTOMATION
+        );
+    }
+
+    #[test]
+    fn deleting_a_profile_unassigns_it() {
+        let mut conn = setup();
+        save_profile(&conn, &custom("ops", ToolDecision::Ask)).unwrap();
+        assign_employee_profile(&conn, "hired-1", Some("ops")).unwrap();
+        set_default_profile(&conn, "ops").unwrap();
+
+        assert!(delete_profile(&mut conn, "ops").unwrap());
+        assert_eq!(employee_profile_id(&conn, "hired-1").unwrap(), None);
+        assert_eq!(default_profile_id(&conn), DEFAULT_PROFILE);
+        assert!(delete_profile(&mut conn, DEVELOPER).is_err());
+        assert!(save_profile(&conn, &custom(DEVELOPER, ToolDecision::Allow)).is_err());
+    }
+
+    #[test]
+    fn records_decisions() {
+        let conn = setup();
+        for (run_id, outcome) in [
+            ("goal_1", DecisionOutcome::Allowed),
+            ("goal_2", DecisionOutcome::Rejected),
+        ] {
+            record_decision(
+                &conn,
+                &ToolDecisionRecord {
+                    id: 0,
+                    run_id: run_id.to_string(),
+                    user_employee_id: None,
+                    profile_id: DEVELOPER.to_string(),
+                    tool_name: "email_send".to_string(),
+                    category: ToolCategory::EmailSend,
+                    outcome,
+                    reason: None,
+                    created_at: Utc::now(),
+                },
+            )
+            .unwrap();
+        }
+
+        let all = decision_log(&conn, None, 10).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].run_id, "goal_2");
+        let run = decision_log(&conn, Some("goal_1"), 10).unwrap();
+        assert_eq!(run.len(), 1);
+        assert!(run[0].outcome.permits());
+    }
+}
@@ -0,0 +1,185 @@
+//! Per-platform capability report.
+//!
+//! Windows gets the native UIA/SendInput/GDI stack; Linux and macOS build
+//! the same core with portable backends (AT-SPI, enigo, arboard, Whisper).
+//! This module describes which of those backends are usable on the running
+//! machine so the UI can hide or explain features that are not.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capability {
+    pub name: String,
+    pub available: bool,
+    pub backend: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformCapabilities {
+    pub os: String,
+    pub arch: String,
+    pub capabilities: Vec<Capability>,
+}
+
+impl PlatformCapabilities {
+    pub fn is_available(&self, name: &str) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability.name == name && capability.available)
+    }
+}
+
+/// Subsystems with no platform-specific code paths.
+const PORTABLE_SUBSYSTEMS: &[&str] = &[
+    "chat",
+    "workflows",
+    "cloud_storage",
+    "calendar",
+    "documents",
+    "terminal",
+    "browser_automation",
+];
+
+fn capability(name: &str, available: bool, backend: &str, note: Option<&str>) -> Capability {
+    Capability {
+        name: name.to_string(),
+        available,
+        backend: backend.to_string(),
+        note: note.map(str::to_string),
+    }
+}
+
+/// Probe the running machine. Performs blocking D-Bus and display queries,
+/// so call it off the async runtime.
+pub fn detect() -> PlatformCapabilities {
+    let mut capabilities: Vec<Capability> = PORTABLE_SUBSYSTEMS
+        .iter()
+        .map(|name| capability(name, true, "portable", None))
+        .collect();
+
+    capabilities.push(ui_automation());
+    capabilities.push(input_simulation());
+    capabilities.push(clipboard());
+    capabilities.push(screen_capture());
+    capabilities.push(window_capture());
+    capabilities.push(capability(
+        "voice_transcription",
+        true,
+        "whisper_api",
+        Some("Requires an OpenAI API key"),
+    ));
+    capabilities.push(capability(
+        "native_speech_recognition",
+        false,
+        "none",
+        Some("Use voice transcription instead"),
+    ));
+
+    PlatformCapabilities {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        capabilities,
+    }
+}
+
+fn ui_automation() -> Capability {
+    let probe = crate::automation::uia::UIAutomationService::new();
+    let backend = if cfg!(windows) {
+        "uia"
+    } else if cfg!(target_os = "macos") {
+        "none"
+    } else {
+        "at_spi"
+    };
+    let note = match &probe {
+        Ok(_) => None,
+        Err(_) if cfg!(target_os = "macos") => {
+            Some("macOS Accessibility backend is not implemented")
+        }
+        Err(_) if cfg!(windows) => Some("UI Automation could not be initialised"),
+        Err(_) => Some("AT-SPI accessibility bus is not running"),
+    };
+    capability("ui_automation", probe.is_ok(), backend, note)
+}
+
+fn input_simulation() -> Capability {
+    if cfg!(windows) {
+        capability("input_simulation", true, "send_input", None)
+    } else {
+        let wayland =
+            std::env::var_os("WAYLAND_DISPLAY").is_some() && std::env::var_os("DISPLAY").is_none();
+        capability(
+            "input_simulation",
+            !wayland,
+            "enigo",
+            wayland.then_some("Requires an X11 or XWayland session"),
+        )
+    }
+}
+
+fn clipboard() -> Capability {
+    #[cfg(windows)]
+    {
+        capability("clipboard", true, "win32", None)
+    }
+    #[cfg(not(windows))]
+    {
+        let available = arboard::Clipboard::new().is_ok();
+        capability(
+            "clipboard",
+            available,
+            "arboard",
+            (!available).then_some("No clipboard provider is reachable"),
+        )
+    }
+}
+
+fn screen_capture() -> Capability {
+    let available = crate::automation::screen::list_displays()
+        .map(|displays| !displays.is_empty())
+        .unwrap_or(false);
+    capability(
+        "screen_capture",
+        available,
+        "screenshots",
+        (!available).then_some("No capturable display detected"),
+    )
+}
+
+fn window_capture() -> Capability {
+    if cfg!(windows) {
+        capability("window_capture", true, "gdi", None)
+    } else {
+        capability(
+            "window_capture",
+            false,
+            "none",
+            Some("Per-window capture and image paste are Windows-only"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_portable_subsystems_everywhere() {
+        let report = PlatformCapabilities {
+            os: "linux".into(),
+            arch: "x86_64".into(),
+            capabilities: PORTABLE_SUBSYSTEMS
+                .iter()
+                .map(|name| capability(name, true, "portable", None))
+                .chain(std::iter::once(window_capture()))
+                .collect(),
+        };
+
+        assert!(report.is_available("chat"));
+        assert!(report.is_available("calendar"));
+        assert_eq!(report.is_available("window_capture"), cfg!(windows));
+        assert!(!report.is_available("unknown"));
+    }
+}
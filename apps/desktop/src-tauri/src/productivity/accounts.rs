@@ -0,0 +1,138 @@
+//! Persisted metadata for connected productivity accounts.
+//!
+//! Rows in `productivity_accounts` describe each account; its tokens live in
+//! the secret vault under the same account ID.
+
+use super::Provider;
+use crate::error::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Summary information for a connected productivity account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductivityAccount {
+    pub account_id: String,
+    pub provider: Provider,
+    /// User-facing name, e.g. the workspace it belongs to
+    pub label: Option<String>,
+    /// User or bot ID reported by the provider
+    pub remote_id: Option<String>,
+}
+
+pub(crate) fn provider_key(provider: &Provider) -> &'static str {
+    match provider {
+        Provider::Notion => "notion",
+        Provider::Trello => "trello",
+        Provider::Asana => "asana",
+    }
+}
+
+fn provider_from_key(key: &str) -> Option<Provider> {
+    match key {
+        "notion" => Some(Provider::Notion),
+        "trello" => Some(Provider::Trello),
+        "asana" => Some(Provider::Asana),
+        _ => None,
+    }
+}
+
+/// Insert or update an account row
+pub fn save_account(conn: &Connection, account: &ProductivityAccount) -> Result<()> {
+    conn.execute(
+        "INSERT INTO productivity_accounts (id, provider, label, remote_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+         ON CONFLICT(id) DO UPDATE SET
+            label = excluded.label,
+            remote_id = excluded.remote_id,
+            updated_at = excluded.updated_at",
+        params![
+            account.account_id,
+            provider_key(&account.provider),
+            account.label,
+            account.remote_id,
+            Utc::now().timestamp()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Remove an account row together with its cached tasks
+pub fn delete_account(conn: &Connection, account_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM productivity_task_cache WHERE account_id = ?1",
+        params![account_id],
+    )?;
+    conn.execute(
+        "DELETE FROM productivity_accounts WHERE id = ?1",
+        params![account_id],
+    )?;
+    Ok(())
+}
+
+/// All persisted accounts, oldest first
+pub fn load_accounts(conn: &Connection) -> Result<Vec<ProductivityAccount>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, provider, label, remote_id FROM productivity_accounts
+         ORDER BY created_at ASC, rowid ASC",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(account_id, provider, label, remote_id)| {
+            let Some(provider) = provider_from_key(&provider) else {
+                tracing::warn!("Skipping productivity account with provider {}", provider);
+                return None;
+            };
+            Some(ProductivityAccount {
+                account_id,
+                provider,
+                label,
+                remote_id,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+
+        let mut work = ProductivityAccount {
+            account_id: "a1".to_string(),
+            provider: Provider::Notion,
+            label: Some("Work".to_string()),
+            remote_id: Some("bot-1".to_string()),
+        };
+        let personal = ProductivityAccount {
+            account_id: "a2".to_string(),
+            provider: Provider::Notion,
+            label: None,
+            remote_id: None,
+        };
+        save_account(&conn, &work).unwrap();
+        save_account(&conn, &personal).unwrap();
+
+        work.label = Some("Acme".to_string());
+        save_account(&conn, &work).unwrap();
+        assert_eq!(load_accounts(&conn).unwrap(), vec![work, personal.clone()]);
+
+        delete_account(&conn, "a1").unwrap();
+        assert_eq!(load_accounts(&conn).unwrap(), vec![personal]);
+    }
+}
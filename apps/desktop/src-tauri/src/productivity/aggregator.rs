@@ -0,0 +1,514 @@
+use super::accounts::provider_key;
+use super::{ProductivityAccount, ProductivityManager, Provider, Task, TaskStatus};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// Cached tasks older than this are refreshed before a search
+pub const CACHE_TTL_SECS: i64 = 300;
+
+/// A task tagged with the account it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnifiedTask {
+    pub account_id: String,
+    pub provider: Provider,
+    #[serde(flatten)]
+    pub task: Task,
+    /// Content hash used to detect changes between syncs
+    pub etag: String,
+    pub synced_at: i64,
+}
+
+/// Filters for searching the unified task cache
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskFilter {
+    /// Case-insensitive match against title and description
+    pub query: Option<String>,
+    #[serde(default)]
+    pub providers: Vec<Provider>,
+    #[serde(default)]
+    pub account_ids: Vec<String>,
+    #[serde(default)]
+    pub statuses: Vec<TaskStatus>,
+    /// Case-insensitive substring of the assignee name or email
+    pub assignee: Option<String>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub due_after: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+/// Outcome of syncing one account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderSyncReport {
+    pub account_id: String,
+    pub provider: Provider,
+    pub fetched: usize,
+    pub changed: usize,
+    pub removed: usize,
+    /// Set when the fetch failed; cached tasks from the last sync are still served
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnifiedTaskSearch {
+    pub tasks: Vec<UnifiedTask>,
+    pub last_synced_at: Option<i64>,
+    /// Empty when the results came straight from the cache
+    pub sync: Vec<ProviderSyncReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub status: TaskStatus,
+    pub tasks: Vec<UnifiedTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnifiedBoard {
+    pub columns: Vec<BoardColumn>,
+    pub total: usize,
+    pub last_synced_at: Option<i64>,
+    pub sync: Vec<ProviderSyncReport>,
+}
+
+const BOARD_COLUMNS: [TaskStatus; 5] = [
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::Blocked,
+    TaskStatus::Completed,
+    TaskStatus::Cancelled,
+];
+
+fn status_key(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "todo",
+        TaskStatus::InProgress => "in_progress",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Blocked => "blocked",
+        TaskStatus::Cancelled => "cancelled",
+    }
+}
+
+/// Hash of the normalized task, stable across syncs while the task is unchanged
+pub fn task_etag(task: &Task) -> String {
+    let json = serde_json::to_vec(task).unwrap_or_default();
+    hex::encode(&Sha256::digest(&json)[..16])
+}
+
+/// Fetch tasks from every connected account concurrently
+pub async fn fetch_all(
+    manager: &ProductivityManager,
+) -> Vec<(ProductivityAccount, Result<Vec<Task>>)> {
+    let fetches = manager
+        .list_accounts()
+        .into_iter()
+        .map(|account| async move {
+            let result = match manager.client(&account.account_id) {
+                Ok(client) => client.list_tasks().await,
+                Err(e) => Err(e),
+            };
+            (account, result)
+        });
+
+    futures::future::join_all(fetches).await
+}
+
+/// Replace an account's cached tasks, rewriting only rows whose etag changed
+pub fn store_tasks(
+    conn: &Connection,
+    account: &ProductivityAccount,
+    tasks: &[Task],
+    now: i64,
+) -> Result<ProviderSyncReport> {
+    let account_id = account.account_id.as_str();
+    let tx = conn.unchecked_transaction()?;
+
+    let existing: HashMap<String, String> = {
+        let mut stmt =
+            tx.prepare("SELECT task_id, etag FROM productivity_task_cache WHERE account_id = ?1")?;
+        let rows = stmt.query_map([account_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let mut changed = 0;
+    for task in tasks {
+        let etag = task_etag(task);
+        if existing.get(&task.id) == Some(&etag) {
+            tx.execute(
+                "UPDATE productivity_task_cache SET synced_at = ?1 WHERE account_id = ?2 AND task_id = ?3",
+                params![now, account_id, task.id],
+            )?;
+            continue;
+        }
+
+        tx.execute(
+            "INSERT INTO productivity_task_cache
+                (account_id, provider, task_id, title, description, status, assignee, due_date, project_name, task, etag, updated_at, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT(account_id, task_id) DO UPDATE SET
+                title = excluded.title,
+                description = excluded.description,
+                status = excluded.status,
+                assignee = excluded.assignee,
+                due_date = excluded.due_date,
+                project_name = excluded.project_name,
+                task = excluded.task,
+                etag = excluded.etag,
+                updated_at = excluded.updated_at,
+                synced_at = excluded.synced_at",
+            params![
+                account_id,
+                provider_key(&account.provider),
+                task.id,
+                task.title,
+                task.description,
+                status_key(&task.status),
+                task.assignee,
+                task.due_date.map(|d| d.timestamp()),
+                task.project_name,
+                serde_json::to_string(task)?,
+                etag,
+                task.updated_at.map(|d| d.timestamp()),
+                now
+            ],
+        )?;
+        changed += 1;
+    }
+
+    let fetched: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+    let mut removed = 0;
+    for task_id in existing.keys().filter(|id| !fetched.contains(id.as_str())) {
+        removed += tx.execute(
+            "DELETE FROM productivity_task_cache WHERE account_id = ?1 AND task_id = ?2",
+            params![account_id, task_id],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(ProviderSyncReport {
+        account_id: account_id.to_string(),
+        provider: account.provider.clone(),
+        fetched: tasks.len(),
+        changed,
+        removed,
+        error: None,
+    })
+}
+
+/// Fetch every connected account and update the cache
+pub async fn sync_all(
+    manager: &ProductivityManager,
+    db: &std::sync::Mutex<Connection>,
+) -> Result<Vec<ProviderSyncReport>> {
+    let results = fetch_all(manager).await;
+    let now = Utc::now().timestamp();
+    let conn = db
+        .lock()
+        .map_err(|e| Error::Database(format!("Failed to lock database: {}", e)))?;
+
+    let mut reports = Vec::with_capacity(results.len());
+    for (account, result) in results {
+        match result {
+            Ok(tasks) => reports.push(store_tasks(&conn, &account, &tasks, now)?),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to sync {:?} tasks for {}: {}",
+                    account.provider,
+                    account.account_id,
+                    e
+                );
+                reports.push(ProviderSyncReport {
+                    account_id: account.account_id,
+                    provider: account.provider,
+                    fetched: 0,
+                    changed: 0,
+                    removed: 0,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    Ok(reports)
+}
+
+pub fn last_synced_at(conn: &Connection) -> Result<Option<i64>> {
+    Ok(conn
+        .query_row(
+            "SELECT MAX(synced_at) FROM productivity_task_cache",
+            [],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .optional()?
+        .flatten())
+}
+
+/// Whether the cache should be refreshed before answering
+pub fn needs_refresh(refresh: Option<bool>, last_synced_at: Option<i64>, now: i64) -> bool {
+    match refresh {
+        Some(refresh) => refresh,
+        None => last_synced_at.map_or(true, |synced| now - synced >= CACHE_TTL_SECS),
+    }
+}
+
+/// Query the cache, soonest due first
+pub fn search_cached(conn: &Connection, filter: &TaskFilter) -> Result<Vec<UnifiedTask>> {
+    let mut sql = String::from(
+        "SELECT account_id, provider, task, etag, synced_at FROM productivity_task_cache WHERE 1 = 1",
+    );
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+    if let Some(query) = filter
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+    {
+        sql.push_str(
+            " AND (instr(lower(title), ?) > 0 OR instr(lower(coalesce(description, '')), ?) > 0)",
+        );
+        values.push(query.to_lowercase().into());
+        values.push(query.to_lowercase().into());
+    }
+    if !filter.providers.is_empty() {
+        sql.push_str(&format!(
+            " AND provider IN ({})",
+            vec!["?"; filter.providers.len()].join(", ")
+        ));
+        values.extend(
+            filter
+                .providers
+                .iter()
+                .map(|p| provider_key(p).to_string().into()),
+        );
+    }
+    if !filter.account_ids.is_empty() {
+        sql.push_str(&format!(
+            " AND account_id IN ({})",
+            vec!["?"; filter.account_ids.len()].join(", ")
+        ));
+        values.extend(filter.account_ids.iter().map(|id| id.clone().into()));
+    }
+    if !filter.statuses.is_empty() {
+        sql.push_str(&format!(
+            " AND status IN ({})",
+            vec!["?"; filter.statuses.len()].join(", ")
+        ));
+        values.extend(
+            filter
+                .statuses
+                .iter()
+                .map(|s| status_key(s).to_string().into()),
+        );
+    }
+    if let Some(assignee) = filter
+        .assignee
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+    {
+        sql.push_str(" AND instr(lower(coalesce(assignee, '')), ?) > 0");
+        values.push(assignee.to_lowercase().into());
+    }
+    if let Some(before) = filter.due_before {
+        sql.push_str(" AND due_date IS NOT NULL AND due_date <= ?");
+        values.push(before.timestamp().into());
+    }
+    if let Some(after) = filter.due_after {
+        sql.push_str(" AND due_date IS NOT NULL AND due_date >= ?");
+        values.push(after.timestamp().into());
+    }
+
+    sql.push_str(" ORDER BY due_date IS NULL, due_date ASC, updated_at DESC, title ASC");
+    if let Some(limit) = filter.limit {
+        sql.push_str(" LIMIT ?");
+        values.push((limit as i64).into());
+    }
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(values), |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, i64>(4)?,
+        ))
+    })?;
+
+    let mut tasks = Vec::new();
+    for row in rows {
+        let (account_id, provider, task, etag, synced_at) = row?;
+        let provider: Provider = serde_json::from_value(serde_json::Value::String(provider))?;
+        tasks.push(UnifiedTask {
+            account_id,
+            provider,
+            task: serde_json::from_str(&task)?,
+            etag,
+            synced_at,
+        });
+    }
+
+    Ok(tasks)
+}
+
+/// Group tasks into one column per status, keeping the search order within each
+pub fn build_board(tasks: Vec<UnifiedTask>) -> Vec<BoardColumn> {
+    let mut columns: Vec<BoardColumn> = BOARD_COLUMNS
+        .iter()
+        .map(|status| BoardColumn {
+            status: status.clone(),
+            tasks: Vec::new(),
+        })
+        .collect();
+
+    for task in tasks {
+        if let Some(column) = columns.iter_mut().find(|c| c.status == task.task.status) {
+            column.tasks.push(task);
+        }
+    }
+
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn timestamp_to_datetime(timestamp: i64) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(timestamp, 0).single()
+    }
+
+    fn task(id: &str, title: &str, status: TaskStatus, due: Option<i64>) -> Task {
+        let mut task = Task::new(id.to_string(), title.to_string()).with_status(status);
+        task.due_date = due.and_then(timestamp_to_datetime);
+        task
+    }
+
+    fn account(account_id: &str, provider: Provider) -> ProductivityAccount {
+        ProductivityAccount {
+            account_id: account_id.to_string(),
+            provider,
+            label: None,
+            remote_id: None,
+        }
+    }
+
+    fn cache() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_store_tasks_tracks_changes() {
+        let conn = cache();
+        let tasks = vec![
+            task("1", "Write launch post", TaskStatus::Todo, None),
+            task("2", "Fix login bug", TaskStatus::InProgress, None),
+        ];
+
+        let work = account("work", Provider::Asana);
+        let personal = account("personal", Provider::Asana);
+
+        let report = store_tasks(&conn, &work, &tasks, 100).unwrap();
+        assert_eq!((report.fetched, report.changed, report.removed), (2, 2, 0));
+        store_tasks(&conn, &personal, &tasks[..1], 100).unwrap();
+
+        let report = store_tasks(&conn, &work, &tasks, 200).unwrap();
+        assert_eq!((report.changed, report.removed), (0, 0));
+
+        // Syncing one account leaves the other account's tasks alone
+        let updated = vec![task("1", "Write launch post", TaskStatus::Completed, None)];
+        let report = store_tasks(&conn, &work, &updated, 300).unwrap();
+        assert_eq!((report.changed, report.removed), (1, 1));
+        assert_eq!(last_synced_at(&conn).unwrap(), Some(300));
+
+        let filter = TaskFilter {
+            account_ids: vec!["personal".to_string()],
+            ..Default::default()
+        };
+        let found = search_cached(&conn, &filter).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].task.status, TaskStatus::Todo);
+    }
+
+    #[test]
+    fn test_search_cached_filters() {
+        let conn = cache();
+        let mut review = task("a", "Review Q3 roadmap", TaskStatus::Todo, Some(2_000));
+        review.assignee = Some("Dana@example.com".to_string());
+        store_tasks(&conn, &account("n1", Provider::Notion), &[review], 10).unwrap();
+        store_tasks(
+            &conn,
+            &account("t1", Provider::Trello),
+            &[
+                task("b", "Roadmap retro", TaskStatus::Completed, Some(1_000)),
+                task("c", "Order snacks", TaskStatus::Todo, None),
+            ],
+            10,
+        )
+        .unwrap();
+
+        let all = search_cached(&conn, &TaskFilter::default()).unwrap();
+        let ids: Vec<_> = all.iter().map(|t| t.task.id.as_str()).collect();
+        assert_eq!(ids, ["b", "a", "c"]);
+
+        let filter = TaskFilter {
+            query: Some("ROADMAP".to_string()),
+            statuses: vec![TaskStatus::Todo],
+            ..Default::default()
+        };
+        let found = search_cached(&conn, &filter).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].provider, Provider::Notion);
+
+        let filter = TaskFilter {
+            assignee: Some("dana".to_string()),
+            due_before: timestamp_to_datetime(2_500),
+            ..Default::default()
+        };
+        assert_eq!(search_cached(&conn, &filter).unwrap().len(), 1);
+
+        let filter = TaskFilter {
+            providers: vec![Provider::Trello],
+            due_after: timestamp_to_datetime(500),
+            ..Default::default()
+        };
+        assert_eq!(search_cached(&conn, &filter).unwrap()[0].task.id, "b");
+    }
+
+    #[test]
+    fn test_build_board_and_refresh() {
+        let tasks = vec![
+            UnifiedTask {
+                account_id: "a1".to_string(),
+                provider: Provider::Asana,
+                task: task("1", "Ship", TaskStatus::Blocked, None),
+                etag: String::new(),
+                synced_at: 0,
+            },
+            UnifiedTask {
+                account_id: "t1".to_string(),
+                provider: Provider::Trello,
+                task: task("2", "Plan", TaskStatus::Todo, None),
+                etag: String::new(),
+                synced_at: 0,
+            },
+        ];
+        let board = build_board(tasks);
+        assert_eq!(board.len(), 5);
+        assert_eq!(board[0].tasks[0].task.id, "2");
+        assert_eq!(board[2].tasks[0].task.id, "1");
+
+        assert!(needs_refresh(None, None, 1_000));
+        assert!(!needs_refresh(None, Some(900), 1_000));
+        assert!(needs_refresh(None, Some(600), 1_000));
+        assert!(needs_refresh(Some(true), Some(999), 1_000));
+        assert!(!needs_refresh(Some(false), None, 1_000));
+    }
+}
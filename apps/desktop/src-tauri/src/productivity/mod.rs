@@ -1,19 +1,26 @@
+pub mod accounts;
+pub mod aggregator;
 pub mod asana_client;
 pub mod notion_client;
+pub mod oauth;
 pub mod trello_client;
 pub mod unified_task;
 
+pub use accounts::ProductivityAccount;
 pub use asana_client::AsanaClient;
 pub use notion_client::NotionClient;
+pub use oauth::{ProductivityOAuth, ProductivityOAuthConfig};
 pub use trello_client::TrelloClient;
 pub use unified_task::{Task, TaskStatus, UnifiedTaskProvider};
 
 use crate::error::{Error, Result};
+use dashmap::DashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use uuid::Uuid;
 
 /// Provider type for productivity tools
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Provider {
     Notion,
@@ -21,145 +28,240 @@ pub enum Provider {
     Asana,
 }
 
-/// Unified productivity manager that handles all providers
+/// Client for one connected account
+#[derive(Clone)]
+pub enum ProviderClient {
+    Notion(Arc<Mutex<NotionClient>>),
+    Trello(Arc<Mutex<TrelloClient>>),
+    Asana(Arc<Mutex<AsanaClient>>),
+}
+
+impl ProviderClient {
+    /// Build a client from credentials without contacting the provider
+    fn from_credentials(provider: &Provider, credentials: &serde_json::Value) -> Result<Self> {
+        let field = |name: &str| {
+            credentials
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| Error::Config(format!("Missing {:?} {}", provider, name)))
+        };
+
+        Ok(match provider {
+            Provider::Notion => {
+                Self::Notion(Arc::new(Mutex::new(NotionClient::new(field("token")?))))
+            }
+            Provider::Trello => Self::Trello(Arc::new(Mutex::new(TrelloClient::new(
+                field("api_key")?,
+                field("token")?,
+            )))),
+            Provider::Asana => Self::Asana(Arc::new(Mutex::new(AsanaClient::new(field("token")?)))),
+        })
+    }
+
+    async fn verify_connection(&self) -> Result<String> {
+        match self {
+            Self::Notion(client) => client.lock().await.verify_connection().await,
+            Self::Trello(client) => client.lock().await.verify_connection().await,
+            Self::Asana(client) => client.lock().await.verify_connection().await,
+        }
+    }
+
+    pub async fn list_tasks(&self) -> Result<Vec<Task>> {
+        match self {
+            Self::Notion(client) => client.lock().await.list_tasks().await,
+            Self::Trello(client) => client.lock().await.list_tasks().await,
+            Self::Asana(client) => client.lock().await.list_tasks().await,
+        }
+    }
+
+    pub async fn create_task(&self, task: Task) -> Result<String> {
+        match self {
+            Self::Notion(client) => client.lock().await.create_task(task).await,
+            Self::Trello(client) => client.lock().await.create_task(task).await,
+            Self::Asana(client) => client.lock().await.create_task(task).await,
+        }
+    }
+}
+
+struct AccountEntry {
+    account: ProductivityAccount,
+    client: ProviderClient,
+}
+
+/// Unified productivity manager holding any number of accounts per provider
 pub struct ProductivityManager {
-    notion_client: Option<Arc<Mutex<NotionClient>>>,
-    trello_client: Option<Arc<Mutex<TrelloClient>>>,
-    asana_client: Option<Arc<Mutex<AsanaClient>>>,
+    accounts: DashMap<String, AccountEntry>,
 }
 
 impl ProductivityManager {
     pub fn new() -> Self {
         Self {
-            notion_client: None,
-            trello_client: None,
-            asana_client: None,
+            accounts: DashMap::new(),
         }
     }
 
-    /// Connect to a productivity provider
+    /// Verify credentials and register them as a new account
     pub async fn connect(
-        &mut self,
+        &self,
         provider: Provider,
         credentials: serde_json::Value,
-    ) -> Result<String> {
-        match provider {
-            Provider::Notion => {
-                let token = credentials
-                    .get("token")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| Error::Config("Missing Notion token".to_string()))?;
-
-                let mut client = NotionClient::new(token.to_string());
-                let account_id = client.verify_connection().await?;
-                self.notion_client = Some(Arc::new(Mutex::new(client)));
-                Ok(account_id)
-            }
-            Provider::Trello => {
-                let api_key = credentials
-                    .get("api_key")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| Error::Config("Missing Trello API key".to_string()))?;
-                let token = credentials
-                    .get("token")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| Error::Config("Missing Trello token".to_string()))?;
-
-                let mut client = TrelloClient::new(api_key.to_string(), token.to_string());
-                let account_id = client.verify_connection().await?;
-                self.trello_client = Some(Arc::new(Mutex::new(client)));
-                Ok(account_id)
-            }
-            Provider::Asana => {
-                let token = credentials
-                    .get("token")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| Error::Config("Missing Asana token".to_string()))?;
-
-                let mut client = AsanaClient::new(token.to_string());
-                let account_id = client.verify_connection().await?;
-                self.asana_client = Some(Arc::new(Mutex::new(client)));
-                Ok(account_id)
-            }
-        }
+        label: Option<String>,
+    ) -> Result<ProductivityAccount> {
+        let client = ProviderClient::from_credentials(&provider, &credentials)?;
+        let remote_id = client.verify_connection().await?;
+
+        let account = ProductivityAccount {
+            account_id: Uuid::new_v4().to_string(),
+            provider,
+            label,
+            remote_id: Some(remote_id),
+        };
+        self.accounts.insert(
+            account.account_id.clone(),
+            AccountEntry {
+                account: account.clone(),
+                client,
+            },
+        );
+        Ok(account)
     }
 
-    /// List tasks from a provider
-    pub async fn list_tasks(&self, provider: Provider) -> Result<Vec<Task>> {
-        match provider {
-            Provider::Notion => {
-                let client = self
-                    .notion_client
-                    .as_ref()
-                    .ok_or_else(|| Error::Config("Notion client not connected".to_string()))?;
-                let client = client.lock().await;
-                client.list_tasks().await
-            }
-            Provider::Trello => {
-                let client = self
-                    .trello_client
-                    .as_ref()
-                    .ok_or_else(|| Error::Config("Trello client not connected".to_string()))?;
-                let client = client.lock().await;
-                client.list_tasks().await
-            }
-            Provider::Asana => {
-                let client = self
-                    .asana_client
-                    .as_ref()
-                    .ok_or_else(|| Error::Config("Asana client not connected".to_string()))?;
-                let client = client.lock().await;
-                client.list_tasks().await
-            }
-        }
+    /// Register an account from stored credentials without verifying them,
+    /// replacing its client if already registered
+    pub fn restore(
+        &self,
+        account: ProductivityAccount,
+        credentials: &serde_json::Value,
+    ) -> Result<()> {
+        let client = ProviderClient::from_credentials(&account.provider, credentials)?;
+        self.accounts
+            .insert(account.account_id.clone(), AccountEntry { account, client });
+        Ok(())
     }
 
-    /// Create a task in a provider
-    pub async fn create_task(&self, provider: Provider, task: Task) -> Result<String> {
-        match provider {
-            Provider::Notion => {
-                let client = self
-                    .notion_client
-                    .as_ref()
-                    .ok_or_else(|| Error::Config("Notion client not connected".to_string()))?;
-                let client = client.lock().await;
-                client.create_task(task).await
-            }
-            Provider::Trello => {
-                let client = self
-                    .trello_client
-                    .as_ref()
-                    .ok_or_else(|| Error::Config("Trello client not connected".to_string()))?;
-                let client = client.lock().await;
-                client.create_task(task).await
-            }
-            Provider::Asana => {
-                let client = self
-                    .asana_client
-                    .as_ref()
-                    .ok_or_else(|| Error::Config("Asana client not connected".to_string()))?;
-                let client = client.lock().await;
-                client.create_task(task).await
+    /// Drop an account's client
+    pub fn disconnect(&self, account_id: &str) -> Result<ProductivityAccount> {
+        self.accounts
+            .remove(account_id)
+            .map(|(_, entry)| entry.account)
+            .ok_or_else(|| account_not_found(account_id))
+    }
+
+    /// List all connected accounts
+    pub fn list_accounts(&self) -> Vec<ProductivityAccount> {
+        self.accounts
+            .iter()
+            .map(|entry| entry.value().account.clone())
+            .collect()
+    }
+
+    pub fn account(&self, account_id: &str) -> Option<ProductivityAccount> {
+        self.accounts
+            .get(account_id)
+            .map(|entry| entry.account.clone())
+    }
+
+    /// The account `account_id`, or the only account connected for `provider`
+    pub fn resolve_account(
+        &self,
+        provider: &Provider,
+        account_id: Option<&str>,
+    ) -> Result<ProductivityAccount> {
+        if let Some(account_id) = account_id.filter(|id| !id.is_empty()) {
+            let account = self
+                .account(account_id)
+                .ok_or_else(|| account_not_found(account_id))?;
+            if account.provider != *provider {
+                return Err(Error::Config(format!(
+                    "Account {} is a {:?} account, not {:?}",
+                    account_id, account.provider, provider
+                )));
             }
+            return Ok(account);
         }
+
+        let mut matching: Vec<_> = self
+            .list_accounts()
+            .into_iter()
+            .filter(|account| account.provider == *provider)
+            .collect();
+        match matching.len() {
+            0 => Err(Error::Config(format!(
+                "No {:?} account is connected",
+                provider
+            ))),
+            1 => Ok(matching.remove(0)),
+            _ => Err(Error::Config(format!(
+                "Several {:?} accounts are connected; pass account_id",
+                provider
+            ))),
+        }
+    }
+
+    /// Client for an account
+    pub fn client(&self, account_id: &str) -> Result<ProviderClient> {
+        self.accounts
+            .get(account_id)
+            .map(|entry| entry.client.clone())
+            .ok_or_else(|| account_not_found(account_id))
+    }
+
+    /// Confirm an account's credentials are accepted, returning the
+    /// provider-side user ID
+    pub async fn verify_account(&self, account_id: &str) -> Result<String> {
+        self.client(account_id)?.verify_connection().await
+    }
+
+    /// List tasks from an account
+    pub async fn list_tasks(&self, account_id: &str) -> Result<Vec<Task>> {
+        self.client(account_id)?.list_tasks().await
+    }
+
+    /// Create a task in an account
+    pub async fn create_task(&self, account_id: &str, task: Task) -> Result<String> {
+        self.client(account_id)?.create_task(task).await
     }
 
-    /// Get a reference to the Notion client
-    pub fn notion_client(&self) -> Option<&Arc<Mutex<NotionClient>>> {
-        self.notion_client.as_ref()
+    /// Notion client for an account
+    pub fn notion_client(&self, account_id: &str) -> Result<Arc<Mutex<NotionClient>>> {
+        match self.client(account_id)? {
+            ProviderClient::Notion(client) => Ok(client),
+            _ => Err(wrong_provider(account_id, Provider::Notion)),
+        }
     }
 
-    /// Get a reference to the Trello client
-    pub fn trello_client(&self) -> Option<&Arc<Mutex<TrelloClient>>> {
-        self.trello_client.as_ref()
+    /// Trello client for an account
+    pub fn trello_client(&self, account_id: &str) -> Result<Arc<Mutex<TrelloClient>>> {
+        match self.client(account_id)? {
+            ProviderClient::Trello(client) => Ok(client),
+            _ => Err(wrong_provider(account_id, Provider::Trello)),
+        }
     }
 
-    /// Get a reference to the Asana client
-    pub fn asana_client(&self) -> Option<&Arc<Mutex<AsanaClient>>> {
-        self.asana_client.as_ref()
+    /// Asana client for an account
+    pub fn asana_client(&self, account_id: &str) -> Result<Arc<Mutex<AsanaClient>>> {
+        match self.client(account_id)? {
+            ProviderClient::Asana(client) => Ok(client),
+            _ => Err(wrong_provider(account_id, Provider::Asana)),
+        }
     }
 }
 
+fn account_not_found(account_id: &str) -> Error {
+    Error::Config(format!(
+        "Productivity account {} is not connected",
+        account_id
+    ))
+}
+
+fn wrong_provider(account_id: &str, provider: Provider) -> Error {
+    Error::Config(format!(
+        "Account {} is not a {:?} account",
+        account_id, provider
+    ))
+}
+
 impl Default for ProductivityManager {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,638 @@
+//! Browser consent flows for productivity providers.
+//!
+//! Asana uses the standard authorization-code flow with PKCE. Notion uses the
+//! authorization-code flow but authenticates the token request with HTTP Basic
+//! and a JSON body. Trello has no OAuth 2.0; its token flow returns the token
+//! in the URL fragment of the return URL, which the frontend hands back as the
+//! `code`. Tokens are kept in the secret vault per account and refreshed
+//! shortly before they expire.
+
+use super::accounts::provider_key;
+use super::{ProductivityAccount, ProductivityManager, Provider};
+use crate::api::oauth::{OAuth2Client, OAuth2Config, PkceChallenge, TokenResponse};
+use crate::error::{Error, Result};
+use crate::security::{SecretError, SecretManager, SecretString};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const NOTION_AUTH_URL: &str = "https://api.notion.com/v1/oauth/authorize";
+const NOTION_TOKEN_URL: &str = "https://api.notion.com/v1/oauth/token";
+const ASANA_AUTH_URL: &str = "https://app.asana.com/-/oauth_authorize";
+const ASANA_TOKEN_URL: &str = "https://app.asana.com/-/oauth_token";
+const TRELLO_AUTH_URL: &str = "https://trello.com/1/authorize";
+
+/// Refresh tokens this many seconds before they expire
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+const PROVIDERS: [Provider; 3] = [Provider::Notion, Provider::Trello, Provider::Asana];
+
+/// App registration used to start a consent flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductivityOAuthConfig {
+    pub provider: Provider,
+    /// OAuth client ID, or the API key for Trello
+    pub client_id: String,
+    /// Required for Notion and Asana
+    pub client_secret: Option<SecretString>,
+    pub redirect_uri: String,
+    /// Application name shown on Trello's consent screen
+    pub app_name: Option<String>,
+    /// Name for the account, e.g. the workspace it belongs to
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Credentials kept in the secret vault so a connection survives restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCredentials {
+    pub client_id: String,
+    pub client_secret: Option<SecretString>,
+    pub redirect_uri: String,
+    pub access_token: SecretString,
+    pub refresh_token: Option<SecretString>,
+    pub expires_at: Option<i64>,
+    /// Provider-side user ID; stored as `account_id` before accounts had their own IDs
+    #[serde(default, alias = "account_id")]
+    pub remote_id: Option<String>,
+}
+
+impl StoredCredentials {
+    /// Credentials for an account connected with a personal token
+    pub fn from_token(provider: &Provider, credentials: &serde_json::Value) -> Result<Self> {
+        let field = |name: &str| {
+            credentials
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| Error::Config(format!("Missing {:?} {}", provider, name)))
+        };
+
+        Ok(Self {
+            client_id: match provider {
+                Provider::Trello => field("api_key")?,
+                Provider::Notion | Provider::Asana => String::new(),
+            },
+            client_secret: None,
+            redirect_uri: String::new(),
+            access_token: field("token")?.into(),
+            refresh_token: None,
+            expires_at: None,
+            remote_id: None,
+        })
+    }
+
+    /// The access token is missing or about to expire and can be refreshed
+    pub fn needs_refresh(&self, now: i64) -> bool {
+        self.refresh_token.is_some()
+            && self
+                .expires_at
+                .is_some_and(|expires_at| expires_at - REFRESH_MARGIN_SECS <= now)
+    }
+
+    /// Credentials in the shape `ProductivityManager::connect` expects
+    pub fn connect_credentials(&self, provider: &Provider) -> serde_json::Value {
+        match provider {
+            Provider::Trello => serde_json::json!({
+                "api_key": self.client_id,
+                "token": self.access_token.expose_secret(),
+            }),
+            Provider::Notion | Provider::Asana => serde_json::json!({
+                "token": self.access_token.expose_secret(),
+            }),
+        }
+    }
+
+    fn apply_token(&mut self, token: TokenResponse) {
+        self.access_token = token.access_token;
+        // Providers that do not rotate refresh tokens omit them on refresh
+        if token.refresh_token.is_some() {
+            self.refresh_token = token.refresh_token;
+        }
+        self.expires_at = token.expires_at.map(|at| at as i64);
+    }
+}
+
+struct PendingAuth {
+    config: ProductivityOAuthConfig,
+    pkce: Option<PkceChallenge>,
+}
+
+/// Token response from Notion, which may omit the standard fields
+#[derive(Debug, Deserialize)]
+struct NotionTokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+/// Pending consent flows and persisted credentials for productivity accounts
+pub struct ProductivityOAuth {
+    pending: DashMap<String, PendingAuth>,
+    credentials: DashMap<String, StoredCredentials>,
+    secrets: Option<Arc<SecretManager>>,
+}
+
+impl Default for ProductivityOAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProductivityOAuth {
+    pub fn new() -> Self {
+        Self {
+            pending: DashMap::new(),
+            credentials: DashMap::new(),
+            secrets: None,
+        }
+    }
+
+    /// Persist credentials through the secret vault
+    pub fn with_secrets(secrets: Arc<SecretManager>) -> Self {
+        Self {
+            secrets: Some(secrets),
+            ..Self::new()
+        }
+    }
+
+    /// Begin a consent flow, returning the URL to open and its state
+    pub fn start(&self, config: ProductivityOAuthConfig) -> Result<(String, String)> {
+        if matches!(config.provider, Provider::Notion | Provider::Asana)
+            && config.client_secret.is_none()
+        {
+            return Err(Error::Config(format!(
+                "A client secret is required for {:?}",
+                config.provider
+            )));
+        }
+
+        let state = Uuid::new_v4().to_string();
+        let pkce = matches!(config.provider, Provider::Asana).then(PkceChallenge::generate);
+        let auth_url = authorization_url(&config, &state, pkce.as_ref())?;
+
+        self.pending
+            .insert(state.clone(), PendingAuth { config, pkce });
+        Ok((auth_url, state))
+    }
+
+    /// Finish a consent flow, connect a new account and persist its credentials
+    pub async fn complete(
+        &self,
+        manager: &ProductivityManager,
+        state: &str,
+        code: &str,
+    ) -> Result<ProductivityAccount> {
+        let (_, pending) = self
+            .pending
+            .remove(state)
+            .ok_or_else(|| Error::Other("Invalid or expired OAuth state".to_string()))?;
+        let config = pending.config;
+
+        let token = match config.provider {
+            Provider::Trello => TokenResponse {
+                access_token: code.into(),
+                token_type: "token".to_string(),
+                expires_in: None,
+                refresh_token: None,
+                scope: None,
+                expires_at: None,
+            },
+            Provider::Notion => {
+                notion_token_request(
+                    &config.client_id,
+                    config
+                        .client_secret
+                        .as_ref()
+                        .map(SecretString::expose_secret)
+                        .unwrap_or_default(),
+                    serde_json::json!({
+                        "grant_type": "authorization_code",
+                        "code": code,
+                        "redirect_uri": config.redirect_uri,
+                    }),
+                )
+                .await?
+            }
+            Provider::Asana => {
+                let verifier = pending.pkce.as_ref().map(|p| p.code_verifier.as_str());
+                oauth_client(&config.provider, &config)?
+                    .exchange_code(code, verifier)
+                    .await?
+            }
+        };
+
+        let mut credentials = StoredCredentials {
+            client_id: config.client_id,
+            client_secret: config.client_secret,
+            redirect_uri: config.redirect_uri,
+            access_token: SecretString::default(),
+            refresh_token: None,
+            expires_at: None,
+            remote_id: None,
+        };
+        credentials.apply_token(token);
+
+        let account = manager
+            .connect(
+                config.provider.clone(),
+                credentials.connect_credentials(&config.provider),
+                config.label,
+            )
+            .await?;
+        credentials.remote_id = account.remote_id.clone();
+        self.save(&account.account_id, credentials);
+
+        Ok(account)
+    }
+
+    /// Reconnect accounts from the secret vault without contacting them
+    ///
+    /// Returns the number of accounts restored and a message per failure.
+    pub fn restore(
+        &self,
+        manager: &ProductivityManager,
+        accounts: Vec<ProductivityAccount>,
+    ) -> (usize, Vec<String>) {
+        let Some(secrets) = &self.secrets else {
+            return (0, Vec::new());
+        };
+
+        let mut restored = 0;
+        let mut failures = Vec::new();
+        for account in accounts {
+            let result = secrets
+                .get_secret(&credentials_key(&account.account_id))
+                .map_err(|e| Error::Other(format!("Credentials unavailable: {}", e)))
+                .and_then(|raw| {
+                    serde_json::from_str::<StoredCredentials>(&raw)
+                        .map_err(|e| Error::Other(format!("Corrupt credentials: {}", e)))
+                })
+                .and_then(|credentials| {
+                    let connect = credentials.connect_credentials(&account.provider);
+                    let account_id = account.account_id.clone();
+                    manager.restore(account, &connect)?;
+                    self.credentials.insert(account_id, credentials);
+                    Ok(())
+                });
+            match result {
+                Ok(()) => restored += 1,
+                Err(e) => failures.push(format!("Failed to restore productivity account: {}", e)),
+            }
+        }
+
+        (restored, failures)
+    }
+
+    /// Move credentials saved when each provider held a single connection
+    /// into accounts of their own
+    ///
+    /// `persist` records the new account's metadata; the old entry is only
+    /// removed once it succeeds. Returns the migrated accounts and a message
+    /// per failure.
+    pub fn migrate_legacy<F>(&self, mut persist: F) -> (Vec<ProductivityAccount>, Vec<String>)
+    where
+        F: FnMut(&ProductivityAccount) -> Result<()>,
+    {
+        let Some(secrets) = &self.secrets else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let mut migrated = Vec::new();
+        let mut failures = Vec::new();
+        for provider in PROVIDERS {
+            let legacy_key = legacy_credentials_key(&provider);
+            let raw = match secrets.get_secret(&legacy_key) {
+                Ok(raw) => raw,
+                Err(SecretError::SecretNotFound) => continue,
+                Err(e) => {
+                    failures.push(format!("{:?} credentials unavailable: {}", provider, e));
+                    continue;
+                }
+            };
+
+            let result = serde_json::from_str::<StoredCredentials>(&raw)
+                .map_err(|e| Error::Other(format!("Corrupt credentials: {}", e)))
+                .and_then(|credentials| {
+                    let account = ProductivityAccount {
+                        account_id: Uuid::new_v4().to_string(),
+                        provider: provider.clone(),
+                        label: None,
+                        remote_id: credentials.remote_id.clone(),
+                    };
+                    secrets
+                        .store_secret(&credentials_key(&account.account_id), &raw)
+                        .map_err(|e| Error::Other(format!("Failed to store credentials: {}", e)))?;
+                    if let Err(e) = persist(&account) {
+                        let _ = secrets.delete_secret(&credentials_key(&account.account_id));
+                        return Err(e);
+                    }
+                    if let Err(e) = secrets.delete_secret(&legacy_key) {
+                        tracing::warn!("Failed to remove legacy {:?} credentials: {}", provider, e);
+                    }
+                    Ok(account)
+                });
+            match result {
+                Ok(account) => migrated.push(account),
+                Err(e) => failures.push(format!("Failed to migrate {:?}: {}", provider, e)),
+            }
+        }
+
+        (migrated, failures)
+    }
+
+    /// Refresh an account's access token if it is about to expire
+    pub async fn refresh_if_needed(
+        &self,
+        manager: &ProductivityManager,
+        account_id: &str,
+    ) -> Result<bool> {
+        let Some(account) = manager.account(account_id) else {
+            return Ok(false);
+        };
+        let Some(mut credentials) = self.credentials.get(account_id).map(|entry| entry.clone())
+        else {
+            return Ok(false);
+        };
+        let provider = &account.provider;
+        if !credentials.needs_refresh(chrono::Utc::now().timestamp()) {
+            return Ok(false);
+        }
+        let refresh_token = credentials.refresh_token.clone().unwrap_or_default();
+
+        let token = match provider {
+            Provider::Trello => return Ok(false),
+            Provider::Notion => {
+                notion_token_request(
+                    &credentials.client_id,
+                    credentials
+                        .client_secret
+                        .as_ref()
+                        .map(SecretString::expose_secret)
+                        .unwrap_or_default(),
+                    serde_json::json!({
+                        "grant_type": "refresh_token",
+                        "refresh_token": refresh_token.expose_secret(),
+                    }),
+                )
+                .await?
+            }
+            Provider::Asana => {
+                let config = ProductivityOAuthConfig {
+                    provider: provider.clone(),
+                    client_id: credentials.client_id.clone(),
+                    client_secret: credentials.client_secret.clone(),
+                    redirect_uri: credentials.redirect_uri.clone(),
+                    app_name: None,
+                    label: None,
+                };
+                oauth_client(provider, &config)?
+                    .refresh_token(refresh_token.expose_secret())
+                    .await?
+            }
+        };
+
+        credentials.apply_token(token);
+        let connect = credentials.connect_credentials(provider);
+        manager.restore(account.clone(), &connect)?;
+        self.save(account_id, credentials);
+        tracing::info!("Refreshed {:?} access token for {}", provider, account_id);
+        Ok(true)
+    }
+
+    /// When an account stops working unless the user re-authorizes it.
+    /// `None` when its token does not expire or can be refreshed
+    pub fn expires_at(&self, account_id: &str) -> Option<i64> {
+        self.credentials
+            .get(account_id)
+            .filter(|credentials| credentials.refresh_token.is_none())
+            .and_then(|credentials| credentials.expires_at)
+    }
+
+    /// Drop an account's stored credentials
+    pub fn forget(&self, account_id: &str) {
+        self.credentials.remove(account_id);
+        if let Some(secrets) = &self.secrets {
+            if let Err(e) = secrets.delete_secret(&credentials_key(account_id)) {
+                tracing::warn!("Failed to remove productivity credentials: {}", e);
+            }
+        }
+    }
+
+    /// Keep an account's credentials, persisting them when a vault is configured
+    pub fn save(&self, account_id: &str, credentials: StoredCredentials) {
+        if let Some(secrets) = &self.secrets {
+            match serde_json::to_string(&credentials) {
+                Ok(raw) => {
+                    if let Err(e) = secrets.store_secret(&credentials_key(account_id), &raw) {
+                        tracing::warn!("Failed to persist productivity credentials: {}", e);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to serialize productivity credentials: {}", e),
+            }
+        }
+        self.credentials.insert(account_id.to_string(), credentials);
+    }
+}
+
+fn credentials_key(account_id: &str) -> String {
+    format!("productivity_account.{}", account_id)
+}
+
+/// Where credentials lived when each provider held a single connection
+fn legacy_credentials_key(provider: &Provider) -> String {
+    format!("productivity.{}", provider_key(provider))
+}
+
+fn oauth_client(provider: &Provider, config: &ProductivityOAuthConfig) -> Result<OAuth2Client> {
+    let (auth_url, token_url, use_pkce) = match provider {
+        Provider::Notion => (NOTION_AUTH_URL, NOTION_TOKEN_URL, false),
+        Provider::Asana => (ASANA_AUTH_URL, ASANA_TOKEN_URL, true),
+        Provider::Trello => {
+            return Err(Error::Config(
+                "Trello uses its token flow, not OAuth 2.0".to_string(),
+            ))
+        }
+    };
+
+    OAuth2Client::new(OAuth2Config {
+        client_id: config.client_id.clone(),
+        client_secret: config.client_secret.clone(),
+        auth_url: auth_url.to_string(),
+        token_url: token_url.to_string(),
+        redirect_uri: config.redirect_uri.clone(),
+        scopes: match provider {
+            Provider::Asana => vec!["default".to_string()],
+            _ => Vec::new(),
+        },
+        use_pkce,
+    })
+}
+
+/// Build the consent URL for a provider
+pub fn authorization_url(
+    config: &ProductivityOAuthConfig,
+    state: &str,
+    pkce: Option<&PkceChallenge>,
+) -> Result<String> {
+    match config.provider {
+        Provider::Trello => {
+            let separator = if config.redirect_uri.contains('?') {
+                '&'
+            } else {
+                '?'
+            };
+            let return_url = format!("{}{}state={}", config.redirect_uri, separator, state);
+            let name = config.app_name.as_deref().unwrap_or("AGI Workforce");
+            Ok(format!(
+                "{}?expiration=never&scope=read,write&response_type=token&callback_method=fragment&key={}&name={}&return_url={}",
+                TRELLO_AUTH_URL,
+                urlencoding::encode(&config.client_id),
+                urlencoding::encode(name),
+                urlencoding::encode(&return_url),
+            ))
+        }
+        Provider::Notion => Ok(format!(
+            "{}&owner=user",
+            oauth_client(&config.provider, config)?.get_authorization_url(state, None)
+        )),
+        Provider::Asana => {
+            Ok(oauth_client(&config.provider, config)?.get_authorization_url(state, pkce))
+        }
+    }
+}
+
+/// Notion's token endpoint takes HTTP Basic client authentication and a JSON body
+async fn notion_token_request(
+    client_id: &str,
+    client_secret: &str,
+    body: serde_json::Value,
+) -> Result<TokenResponse> {
+    let basic = STANDARD.encode(format!("{}:{}", client_id, client_secret));
+    let response = reqwest::Client::new()
+        .post(NOTION_TOKEN_URL)
+        .header("Authorization", format!("Basic {}", basic))
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| Error::Http(format!("Notion token request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(Error::Provider(format!(
+            "Notion token request failed: {} - {}",
+            status, text
+        )));
+    }
+
+    let token: NotionTokenResponse = response
+        .json()
+        .await
+        .map_err(|e| Error::Provider(format!("Failed to parse Notion token response: {}", e)))?;
+
+    Ok(TokenResponse {
+        access_token: token.access_token.into(),
+        token_type: token.token_type.unwrap_or_else(|| "bearer".to_string()),
+        expires_in: token.expires_in,
+        refresh_token: token.refresh_token.map(SecretString::from),
+        scope: None,
+        expires_at: None,
+    }
+    .with_expiration())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: Provider) -> ProductivityOAuthConfig {
+        ProductivityOAuthConfig {
+            provider,
+            client_id: "client-123".to_string(),
+            client_secret: Some("shh".into()),
+            redirect_uri: "http://localhost:5173/oauth/callback".to_string(),
+            app_name: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_authorization_urls() {
+        let pkce = PkceChallenge::generate();
+        let asana = authorization_url(&config(Provider::Asana), "s1", Some(&pkce)).unwrap();
+        assert!(asana.starts_with(ASANA_AUTH_URL));
+        assert!(asana.contains("code_challenge_method=S256"));
+        assert!(asana.contains("state=s1"));
+
+        let notion = authorization_url(&config(Provider::Notion), "s2", None).unwrap();
+        assert!(notion.starts_with(NOTION_AUTH_URL));
+        assert!(notion.ends_with("&owner=user"));
+
+        let trello = authorization_url(&config(Provider::Trello), "s3", None).unwrap();
+        assert!(trello.starts_with(TRELLO_AUTH_URL));
+        assert!(trello.contains("key=client-123"));
+        assert!(trello.contains(&*urlencoding::encode("callback?state=s3")));
+    }
+
+    #[test]
+    fn test_start_requires_client_secret() {
+        let oauth = ProductivityOAuth::new();
+        let mut notion = config(Provider::Notion);
+        notion.client_secret = None;
+        assert!(oauth.start(notion).is_err());
+
+        let mut trello = config(Provider::Trello);
+        trello.client_secret = None;
+        let (_, state) = oauth.start(trello).unwrap();
+        assert!(oauth.pending.contains_key(&state));
+    }
+
+    #[test]
+    fn test_needs_refresh_and_rotation() {
+        let mut credentials = StoredCredentials {
+            client_id: "key".to_string(),
+            client_secret: None,
+            redirect_uri: String::new(),
+            access_token: "old".into(),
+            refresh_token: Some("refresh-1".into()),
+            expires_at: Some(1_000),
+            remote_id: Some("me".to_string()),
+        };
+        assert!(!credentials.needs_refresh(900));
+        assert!(credentials.needs_refresh(950));
+
+        credentials.apply_token(TokenResponse {
+            access_token: "new".into(),
+            token_type: "bearer".to_string(),
+            expires_in: Some(3600),
+            refresh_token: None,
+            scope: None,
+            expires_at: Some(5_000),
+        });
+        assert_eq!(credentials.access_token.expose_secret(), "new");
+        assert_eq!(
+            credentials
+                .refresh_token
+                .as_ref()
+                .map(SecretString::expose_secret),
+            Some("refresh-1")
+        );
+        assert!(!credentials.needs_refresh(950));
+
+        credentials.refresh_token = None;
+        assert!(!credentials.needs_refresh(10_000));
+
+        assert_eq!(
+            credentials.connect_credentials(&Provider::Trello)["api_key"],
+            "key"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// File types the knowledge base can extract text from
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["pdf", "docx", "txt", "md", "markdown", "html", "htm"];
+
+/// Guards against zip bombs when unpacking an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveLimits {
+    pub max_entries: usize,
+    pub max_file_bytes: u64,
+    pub max_total_bytes: u64,
+    /// Largest allowed ratio of uncompressed to compressed size for one entry
+    pub max_compression_ratio: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 2_000,
+            max_file_bytes: 100 * 1024 * 1024,
+            max_total_bytes: 1024 * 1024 * 1024,
+            max_compression_ratio: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFileStatus {
+    Ingested,
+    Skipped,
+    Failed,
+}
+
+/// Outcome for one entry of an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveFileResult {
+    pub entry: String,
+    pub status: ArchiveFileStatus,
+    pub document_id: Option<String>,
+    pub chunks: usize,
+    pub error: Option<String>,
+}
+
+impl ArchiveFileResult {
+    pub fn ingested(entry: &str, document_id: String, chunks: usize) -> Self {
+        Self {
+            entry: entry.to_string(),
+            status: ArchiveFileStatus::Ingested,
+            document_id: Some(document_id),
+            chunks,
+            error: None,
+        }
+    }
+
+    pub fn skipped(entry: &str, reason: impl Into<String>) -> Self {
+        Self {
+            entry: entry.to_string(),
+            status: ArchiveFileStatus::Skipped,
+            document_id: None,
+            chunks: 0,
+            error: Some(reason.into()),
+        }
+    }
+
+    pub fn failed(entry: &str, error: impl Into<String>) -> Self {
+        Self {
+            entry: entry.to_string(),
+            status: ArchiveFileStatus::Failed,
+            document_id: None,
+            chunks: 0,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Emitted after each archive entry has been handled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveIngestProgress {
+    pub source_group: String,
+    /// 1-based position of `file` among all entries
+    pub index: usize,
+    pub total: usize,
+    pub file: ArchiveFileResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveIngestReport {
+    /// Shared by every document and chunk that came from the archive
+    pub source_group: String,
+    pub archive: String,
+    pub files: Vec<ArchiveFileResult>,
+    pub ingested: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl ArchiveIngestReport {
+    pub fn new(source_group: String, archive: String, files: Vec<ArchiveFileResult>) -> Self {
+        let count = |status| files.iter().filter(|file| file.status == status).count();
+        Self {
+            ingested: count(ArchiveFileStatus::Ingested),
+            skipped: count(ArchiveFileStatus::Skipped),
+            failed: count(ArchiveFileStatus::Failed),
+            source_group,
+            archive,
+            files,
+        }
+    }
+}
+
+/// A supported file unpacked into the sandbox
+#[derive(Debug, Clone)]
+pub struct ExtractedFile {
+    /// Path inside the archive, always relative and `/`-separated
+    pub entry: String,
+    pub path: PathBuf,
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| SUPPORTED_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Unpack the supported documents of a zip archive into `dest`.
+///
+/// Entries that escape `dest`, symlinks and unsupported files are reported as
+/// skipped instead of being written. Exceeding the entry count or total size
+/// limit aborts the whole extraction.
+pub fn extract_archive(
+    archive_path: &Path,
+    dest: &Path,
+    limits: &ArchiveLimits,
+) -> Result<(Vec<ExtractedFile>, Vec<ArchiveFileResult>)> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open archive {}", archive_path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).context("Not a valid zip archive")?;
+    if archive.len() > limits.max_entries {
+        bail!(
+            "Archive has {} entries; at most {} are allowed",
+            archive.len(),
+            limits.max_entries
+        );
+    }
+
+    let mut extracted = Vec::new();
+    let mut skipped = Vec::new();
+    let mut total_bytes = 0u64;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+
+        let Some(relative) = entry.enclosed_name().map(Path::to_path_buf) else {
+            skipped.push(ArchiveFileResult::skipped(
+                &name,
+                "Path escapes the archive",
+            ));
+            continue;
+        };
+        if entry
+            .unix_mode()
+            .is_some_and(|mode| mode & 0o170000 == 0o120000)
+        {
+            skipped.push(ArchiveFileResult::skipped(
+                &name,
+                "Symbolic links are not extracted",
+            ));
+            continue;
+        }
+        if relative.components().any(|c| {
+            let part = c.as_os_str().to_string_lossy();
+            part == "__MACOSX" || part.starts_with("._")
+        }) {
+            skipped.push(ArchiveFileResult::skipped(&name, "Archive metadata"));
+            continue;
+        }
+        if !is_supported(&relative) {
+            skipped.push(ArchiveFileResult::skipped(&name, "Unsupported file type"));
+            continue;
+        }
+        if entry.size() > limits.max_file_bytes {
+            skipped.push(ArchiveFileResult::skipped(
+                &name,
+                format!("File exceeds {} bytes", limits.max_file_bytes),
+            ));
+            continue;
+        }
+        if entry.size()
+            > entry
+                .compressed_size()
+                .max(1)
+                .saturating_mul(limits.max_compression_ratio)
+        {
+            skipped.push(ArchiveFileResult::skipped(
+                &name,
+                "Compression ratio is suspiciously high",
+            ));
+            continue;
+        }
+
+        let target = dest.join(&relative);
+        if target.exists() {
+            skipped.push(ArchiveFileResult::skipped(&name, "Duplicate entry"));
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Header sizes can lie, so cap what is actually written
+        let mut out = File::create(&target)?;
+        let written = io::copy(&mut (&mut entry).take(limits.max_file_bytes + 1), &mut out)
+            .with_context(|| format!("Failed to extract {}", name))?;
+        if written > limits.max_file_bytes {
+            drop(out);
+            fs::remove_file(&target)?;
+            skipped.push(ArchiveFileResult::skipped(
+                &name,
+                format!("File exceeds {} bytes", limits.max_file_bytes),
+            ));
+            continue;
+        }
+        total_bytes += written;
+        if total_bytes > limits.max_total_bytes {
+            bail!(
+                "Archive expands to more than {} bytes",
+                limits.max_total_bytes
+            );
+        }
+
+        extracted.push(ExtractedFile {
+            entry: relative.to_string_lossy().replace('\\', "/"),
+            path: target,
+        });
+    }
+
+    Ok((extracted, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::FileOptions;
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in entries {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn extracts_only_safe_supported_entries() {
+        let dir = tempdir().unwrap();
+        let archive = dir.path().join("docs.zip");
+        write_zip(
+            &archive,
+            &[
+                ("notes/readme.md", b"# Notes"),
+                ("../escape.txt", b"outside"),
+                ("image.png", b"png"),
+                ("__MACOSX/notes/._readme.md", b"fork"),
+            ],
+        );
+
+        let sandbox = dir.path().join("sandbox");
+        let (files, skipped) =
+            extract_archive(&archive, &sandbox, &ArchiveLimits::default()).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].entry, "notes/readme.md");
+        assert_eq!(fs::read_to_string(&files[0].path).unwrap(), "# Notes");
+        assert_eq!(skipped.len(), 3);
+        assert!(!dir.path().join("escape.txt").exists());
+    }
+
+    #[test]
+    fn enforces_size_limits() {
+        let dir = tempdir().unwrap();
+        let archive = dir.path().join("bomb.zip");
+        let zeros = vec![0u8; 64 * 1024];
+        write_zip(&archive, &[("a.txt", &zeros), ("b.txt", &zeros)]);
+
+        let limits = ArchiveLimits {
+            max_compression_ratio: 10,
+            ..ArchiveLimits::default()
+        };
+        let (files, skipped) =
+            extract_archive(&archive, &dir.path().join("ratio"), &limits).unwrap();
+        assert!(files.is_empty());
+        assert_eq!(skipped.len(), 2);
+
+        let limits = ArchiveLimits {
+            max_total_bytes: 100 * 1024,
+            max_compression_ratio: u64::MAX,
+            ..ArchiveLimits::default()
+        };
+        assert!(extract_archive(&archive, &dir.path().join("total"), &limits).is_err());
+    }
+}
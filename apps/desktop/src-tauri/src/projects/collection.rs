@@ -0,0 +1,686 @@
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::archive::is_supported;
+use super::knowledge::KnowledgeDocument;
+use super::rag::{ChunkingConfig, RAGEngine};
+
+/// Files read from one folder source; deeper trees should be split up
+pub const MAX_FOLDER_FILES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionSourceKind {
+    File,
+    Folder,
+    Url,
+}
+
+impl CollectionSourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Folder => "folder",
+            Self::Url => "url",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "file" => Some(Self::File),
+            "folder" => Some(Self::Folder),
+            "url" => Some(Self::Url),
+            _ => None,
+        }
+    }
+}
+
+/// A named knowledge base inside a project. Every chunk in it is embedded
+/// with the same model, recorded on the first write.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectCollection {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub embedding_model: Option<String>,
+    pub dimensions: Option<usize>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionSource {
+    pub id: String,
+    pub collection_id: String,
+    pub kind: CollectionSourceKind,
+    /// File or folder path, or URL
+    pub location: String,
+    pub status: String, // "pending", "ready", "failed"
+    pub chunk_count: usize,
+    pub error: Option<String>,
+    pub added_at: String,
+}
+
+/// Text read from a source before chunking. A folder yields one per file.
+#[derive(Debug, Clone)]
+pub struct SourceDocument {
+    /// Path or URL the text came from
+    pub document: String,
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreparedChunk {
+    pub document: String,
+    pub title: String,
+    pub chunk_index: u32,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    pub source_id: String,
+    pub document: String,
+    pub title: String,
+    pub chunk_index: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionMatch {
+    pub chunk_id: String,
+    pub content: String,
+    pub similarity: f32,
+    pub citation: Citation,
+}
+
+pub struct ProjectCollections {
+    db_path: PathBuf,
+    rag_engine: RAGEngine,
+}
+
+impl ProjectCollections {
+    pub fn new(db_path: PathBuf) -> Result<Self> {
+        let collections = Self {
+            db_path,
+            rag_engine: RAGEngine::new(ChunkingConfig::default()),
+        };
+
+        collections.init_database()?;
+        Ok(collections)
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        Ok(conn)
+    }
+
+    fn init_database(&self) -> Result<()> {
+        let conn = self.open()?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS project_collections (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                embedding_model TEXT,
+                dimensions INTEGER,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS collection_sources (
+                id TEXT PRIMARY KEY,
+                collection_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                location TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                chunk_count INTEGER NOT NULL DEFAULT 0,
+                error TEXT,
+                added_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (collection_id) REFERENCES project_collections(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS collection_chunks (
+                id TEXT PRIMARY KEY,
+                collection_id TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                document TEXT NOT NULL,
+                title TEXT NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                FOREIGN KEY (collection_id) REFERENCES project_collections(id) ON DELETE CASCADE,
+                FOREIGN KEY (source_id) REFERENCES collection_sources(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_project_collections_project
+             ON project_collections(project_id, created_at DESC)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_collection_chunks_collection
+             ON collection_chunks(collection_id)",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn create_collection(
+        &self,
+        project_id: &str,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<ProjectCollection> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("Collection name cannot be empty");
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        self.open()?.execute(
+            "INSERT INTO project_collections (id, project_id, name, description)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![&id, project_id, name, &description],
+        )?;
+
+        self.get_collection(&id)?
+            .ok_or_else(|| anyhow!("Collection {} vanished after insert", id))
+    }
+
+    pub fn get_collection(&self, collection_id: &str) -> Result<Option<ProjectCollection>> {
+        let conn = self.open()?;
+        let collection = conn
+            .query_row(
+                "SELECT id, project_id, name, description, embedding_model, dimensions, created_at
+                 FROM project_collections WHERE id = ?1",
+                [collection_id],
+                collection_from_row,
+            )
+            .optional()?;
+        Ok(collection)
+    }
+
+    pub fn list_collections(&self, project_id: &str) -> Result<Vec<ProjectCollection>> {
+        let conn = self.open()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, name, description, embedding_model, dimensions, created_at
+             FROM project_collections WHERE project_id = ?1 ORDER BY created_at DESC",
+        )?;
+        let collections = stmt
+            .query_map([project_id], collection_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(collections)
+    }
+
+    /// Delete a collection along with its sources and chunks
+    pub fn delete_collection(&self, collection_id: &str) -> Result<()> {
+        self.open()?.execute(
+            "DELETE FROM project_collections WHERE id = ?1",
+            [collection_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_sources(&self, collection_id: &str) -> Result<Vec<CollectionSource>> {
+        let conn = self.open()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, collection_id, kind, location, status, chunk_count, error, added_at
+             FROM collection_sources WHERE collection_id = ?1 ORDER BY added_at",
+        )?;
+        let sources = stmt
+            .query_map([collection_id], source_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sources)
+    }
+
+    /// Register a source as pending; its chunks arrive via [`Self::store_chunks`]
+    pub fn add_source(
+        &self,
+        collection_id: &str,
+        kind: CollectionSourceKind,
+        location: &str,
+    ) -> Result<CollectionSource> {
+        if self.get_collection(collection_id)?.is_none() {
+            bail!("Collection not found: {}", collection_id);
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let conn = self.open()?;
+        conn.execute(
+            "INSERT INTO collection_sources (id, collection_id, kind, location)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![&id, collection_id, kind.as_str(), location],
+        )?;
+        let source = conn.query_row(
+            "SELECT id, collection_id, kind, location, status, chunk_count, error, added_at
+             FROM collection_sources WHERE id = ?1",
+            [&id],
+            source_from_row,
+        )?;
+        Ok(source)
+    }
+
+    pub fn fail_source(&self, source_id: &str, error: &str) -> Result<()> {
+        self.open()?.execute(
+            "UPDATE collection_sources SET status = 'failed', error = ?1 WHERE id = ?2",
+            params![error, source_id],
+        )?;
+        Ok(())
+    }
+
+    /// Read a file, or every supported file under a folder. URL sources are
+    /// fetched with [`crate::web::fetch_page`] instead.
+    pub fn read_source(
+        &self,
+        kind: CollectionSourceKind,
+        location: &str,
+    ) -> Result<Vec<SourceDocument>> {
+        let path = Path::new(location);
+        match kind {
+            CollectionSourceKind::File => {
+                if !path.is_file() {
+                    bail!("File not found: {}", location);
+                }
+                Ok(vec![self.read_file(path)?])
+            }
+            CollectionSourceKind::Folder => {
+                if !path.is_dir() {
+                    bail!("Folder not found: {}", location);
+                }
+                let files: Vec<PathBuf> = walkdir::WalkDir::new(path)
+                    .into_iter()
+                    .filter_entry(|entry| {
+                        entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+                    })
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_type().is_file() && is_supported(entry.path()))
+                    .map(|entry| entry.into_path())
+                    .collect();
+                if files.len() > MAX_FOLDER_FILES {
+                    bail!(
+                        "Folder has {} supported files; at most {} are allowed per source",
+                        files.len(),
+                        MAX_FOLDER_FILES
+                    );
+                }
+
+                let mut documents = Vec::with_capacity(files.len());
+                for file in files {
+                    match self.read_file(&file) {
+                        Ok(document) => documents.push(document),
+                        Err(e) => tracing::warn!("Skipping {}: {:#}", file.display(), e),
+                    }
+                }
+                Ok(documents)
+            }
+            CollectionSourceKind::Url => bail!("URL sources must be fetched, not read"),
+        }
+    }
+
+    fn read_file(&self, path: &Path) -> Result<SourceDocument> {
+        let file_type = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("unknown");
+        let content = self
+            .rag_engine
+            .extract_text_from_file(&path.to_string_lossy(), file_type)?;
+        Ok(SourceDocument {
+            document: path.to_string_lossy().to_string(),
+            title: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+            content,
+        })
+    }
+
+    /// Split documents into chunks ready for embedding
+    pub fn chunk(&self, documents: &[SourceDocument]) -> Result<Vec<PreparedChunk>> {
+        let mut prepared = Vec::new();
+        for source in documents {
+            if source.content.trim().is_empty() {
+                continue;
+            }
+            let document = KnowledgeDocument {
+                id: source.document.clone(),
+                project_id: String::new(),
+                file_path: source.document.clone(),
+                file_name: source.title.clone(),
+                file_type: String::new(),
+                size: source.content.len(),
+                content: source.content.clone(),
+                metadata: None,
+                indexed_at: String::new(),
+                created_at: String::new(),
+            };
+            prepared.extend(
+                self.rag_engine
+                    .chunk_document(&document)?
+                    .into_iter()
+                    .map(|chunk| PreparedChunk {
+                        document: source.document.clone(),
+                        title: source.title.clone(),
+                        chunk_index: chunk.chunk_index,
+                        content: chunk.content,
+                    }),
+            );
+        }
+        Ok(prepared)
+    }
+
+    /// Store a source's embedded chunks and mark it ready. The first write
+    /// pins the collection to `model`; later writes from another model are
+    /// refused so one collection never mixes vector spaces.
+    pub fn store_chunks(
+        &self,
+        source_id: &str,
+        model: &str,
+        chunks: &[PreparedChunk],
+        embeddings: &[Vec<f32>],
+    ) -> Result<usize> {
+        if chunks.len() != embeddings.len() {
+            bail!(
+                "Got {} embeddings for {} chunks",
+                embeddings.len(),
+                chunks.len()
+            );
+        }
+        let dimensions = embeddings.first().map(Vec::len);
+        if embeddings.iter().any(|e| Some(e.len()) != dimensions) {
+            bail!("Embeddings in one batch have different sizes");
+        }
+
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        let collection_id: String = tx
+            .query_row(
+                "SELECT collection_id FROM collection_sources WHERE id = ?1",
+                [source_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Source not found: {}", source_id))?;
+
+        if let Some(dimensions) = dimensions {
+            let (stored_model, stored_dims): (Option<String>, Option<usize>) = tx.query_row(
+                "SELECT embedding_model, dimensions FROM project_collections WHERE id = ?1",
+                [&collection_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            match stored_model {
+                Some(stored) if stored != model || stored_dims != Some(dimensions) => bail!(
+                    "Collection is embedded with {}; switch back to it or rebuild the collection",
+                    stored
+                ),
+                Some(_) => {}
+                None => {
+                    tx.execute(
+                        "UPDATE project_collections SET embedding_model = ?1, dimensions = ?2
+                         WHERE id = ?3",
+                        params![model, dimensions, &collection_id],
+                    )?;
+                }
+            }
+        }
+
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO collection_chunks
+                 (id, collection_id, source_id, document, title, chunk_index, content, embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for (chunk, embedding) in chunks.iter().zip(embeddings) {
+                insert.execute(params![
+                    uuid::Uuid::new_v4().to_string(),
+                    &collection_id,
+                    source_id,
+                    &chunk.document,
+                    &chunk.title,
+                    chunk.chunk_index,
+                    &chunk.content,
+                    bincode::serialize(embedding)?,
+                ])?;
+            }
+        }
+
+        tx.execute(
+            "UPDATE collection_sources SET status = 'ready', chunk_count = ?1, error = NULL
+             WHERE id = ?2",
+            params![chunks.len(), source_id],
+        )?;
+        tx.commit()?;
+
+        Ok(chunks.len())
+    }
+
+    /// The `top_k` chunks closest to `query_embedding`, each with the source
+    /// it came from. `model` must be the one the collection was built with.
+    pub fn query(
+        &self,
+        collection_id: &str,
+        model: &str,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<CollectionMatch>> {
+        let collection = self
+            .get_collection(collection_id)?
+            .ok_or_else(|| anyhow!("Collection not found: {}", collection_id))?;
+        let Some(stored_model) = collection.embedding_model else {
+            return Ok(Vec::new());
+        };
+        if stored_model != model || collection.dimensions != Some(query_embedding.len()) {
+            bail!(
+                "Collection is embedded with {} but the active model is {}",
+                stored_model,
+                model
+            );
+        }
+
+        let conn = self.open()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, source_id, document, title, chunk_index, content, embedding
+             FROM collection_chunks WHERE collection_id = ?1",
+        )?;
+        let rows = stmt.query_map([collection_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                Citation {
+                    source_id: row.get(1)?,
+                    document: row.get(2)?,
+                    title: row.get(3)?,
+                    chunk_index: row.get(4)?,
+                },
+                row.get::<_, String>(5)?,
+                row.get::<_, Vec<u8>>(6)?,
+            ))
+        })?;
+
+        let mut matches = Vec::new();
+        for row in rows {
+            let (chunk_id, citation, content, embedding) = row?;
+            let embedding: Vec<f32> = bincode::deserialize(&embedding)
+                .with_context(|| format!("Corrupt embedding for chunk {}", chunk_id))?;
+            matches.push(CollectionMatch {
+                chunk_id,
+                content,
+                similarity: self
+                    .rag_engine
+                    .cosine_similarity(query_embedding, &embedding),
+                citation,
+            });
+        }
+
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        matches.truncate(top_k);
+        Ok(matches)
+    }
+}
+
+fn collection_from_row(row: &rusqlite::Row) -> rusqlite::Result<ProjectCollection> {
+    Ok(ProjectCollection {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        description: row.get(3)?,
+        embedding_model: row.get(4)?,
+        dimensions: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn source_from_row(row: &rusqlite::Row) -> rusqlite::Result<CollectionSource> {
+    let kind: String = row.get(2)?;
+    Ok(CollectionSource {
+        id: row.get(0)?,
+        collection_id: row.get(1)?,
+        kind: CollectionSourceKind::parse(&kind).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                2,
+                rusqlite::types::Type::Text,
+                format!("unknown source kind {}", kind).into(),
+            )
+        })?,
+        location: row.get(3)?,
+        status: row.get(4)?,
+        chunk_count: row.get(5)?,
+        error: row.get(6)?,
+        added_at: row.get(7)?,
+    })
+}
+
+/// Retrieved chunks as a system-prompt block. Each chunk is numbered so the
+/// model can cite it as `[n]`; the sources are listed at the end.
+pub fn format_context(matches: &[CollectionMatch]) -> String {
+    if matches.is_empty() {
+        return String::new();
+    }
+
+    let mut context = String::from(
+        "Use the following excerpts from the project's knowledge base when they are relevant. \
+         Cite them as [n].\n",
+    );
+    for (i, m) in matches.iter().enumerate() {
+        context.push_str(&format!(
+            "\n[{}] {}\n{}\n",
+            i + 1,
+            m.citation.title,
+            m.content.trim()
+        ));
+    }
+    context.push_str("\nSources:\n");
+    for (i, m) in matches.iter().enumerate() {
+        context.push_str(&format!(
+            "[{}] {} (chunk {})\n",
+            i + 1,
+            m.citation.document,
+            m.citation.chunk_index
+        ));
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn chunk(document: &str, index: u32, content: &str) -> PreparedChunk {
+        PreparedChunk {
+            document: document.to_string(),
+            title: document.to_string(),
+            chunk_index: index,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_query_ranks_chunks_with_citations() {
+        let dir = tempdir().unwrap();
+        let collections = ProjectCollections::new(dir.path().join("projects.db")).unwrap();
+        let collection = collections
+            .create_collection("project-1", "Docs", None)
+            .unwrap();
+        let source = collections
+            .add_source(&collection.id, CollectionSourceKind::File, "/docs/a.md")
+            .unwrap();
+
+        let stored = collections
+            .store_chunks(
+                &source.id,
+                "nomic-embed-text",
+                &[
+                    chunk("/docs/a.md", 0, "about cats"),
+                    chunk("/docs/a.md", 1, "about dogs"),
+                ],
+                &[vec![1.0, 0.0], vec![0.0, 1.0]],
+            )
+            .unwrap();
+        assert_eq!(stored, 2);
+
+        let matches = collections
+            .query(&collection.id, "nomic-embed-text", &[0.1, 0.9], 1)
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].content, "about dogs");
+        assert_eq!(matches[0].citation.source_id, source.id);
+        assert_eq!(matches[0].citation.chunk_index, 1);
+
+        let sources = collections.list_sources(&collection.id).unwrap();
+        assert_eq!(sources[0].status, "ready");
+        assert_eq!(sources[0].chunk_count, 2);
+
+        let context = format_context(&matches);
+        assert!(context.contains("[1] /docs/a.md"));
+        assert!(context.contains("about dogs"));
+    }
+
+    #[test]
+    fn test_collection_refuses_other_model() {
+        let dir = tempdir().unwrap();
+        let collections = ProjectCollections::new(dir.path().join("projects.db")).unwrap();
+        let collection = collections
+            .create_collection("project-1", "Docs", None)
+            .unwrap();
+        let first = collections
+            .add_source(&collection.id, CollectionSourceKind::Url, "https://a.test")
+            .unwrap();
+        collections
+            .store_chunks(
+                &first.id,
+                "nomic-embed-text",
+                &[chunk("https://a.test", 0, "text")],
+                &[vec![1.0, 0.0]],
+            )
+            .unwrap();
+
+        let second = collections
+            .add_source(&collection.id, CollectionSourceKind::Url, "https://b.test")
+            .unwrap();
+        assert!(collections
+            .store_chunks(
+                &second.id,
+                "bge-small-en-v1.5",
+                &[chunk("https://b.test", 0, "text")],
+                &[vec![1.0, 0.0, 0.0]],
+            )
+            .is_err());
+        assert!(collections
+            .query(&collection.id, "bge-small-en-v1.5", &[1.0, 0.0, 0.0], 5)
+            .is_err());
+    }
+}
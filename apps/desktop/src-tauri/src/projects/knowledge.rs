@@ -249,6 +249,24 @@ impl KnowledgeBase {
         Ok(result)
     }
 
+    /// Documents ingested together from one archive
+    pub fn get_source_group_documents(
+        &self,
+        project_id: &str,
+        source_group: &str,
+    ) -> Result<Vec<KnowledgeDocument>> {
+        Ok(self
+            .get_project_documents(project_id)?
+            .into_iter()
+            .filter(|doc| {
+                doc.metadata
+                    .as_deref()
+                    .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+                    .is_some_and(|m| m["source_group"] == source_group)
+            })
+            .collect())
+    }
+
     pub fn get_document_chunks(&self, document_id: &str) -> Result<Vec<KnowledgeChunk>> {
         let conn = Connection::open(&self.db_path)?;
 
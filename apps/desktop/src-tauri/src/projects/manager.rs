@@ -1,8 +1,11 @@
 use anyhow::Result;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use super::archive::{
+    extract_archive, ArchiveFileResult, ArchiveIngestProgress, ArchiveIngestReport, ArchiveLimits,
+};
 use super::knowledge::{KnowledgeBase, KnowledgeDocument};
 use super::rag::{ChunkingConfig, RAGEngine};
 
@@ -275,13 +278,100 @@ impl ProjectManager {
     }
 
     pub fn add_document(&self, project_id: &str, file_path: &str) -> Result<KnowledgeDocument> {
-        // Extract file info
         let path = PathBuf::from(file_path);
         let file_name = path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown")
             .to_string();
+
+        let (document, _) = self.ingest_file(project_id, &path, file_path, file_name, None)?;
+        Ok(document)
+    }
+
+    /// Ingest every supported document inside a zip archive.
+    ///
+    /// The archive is unpacked into a temporary sandbox that is removed
+    /// afterwards. A failing entry is recorded in the report without stopping
+    /// the rest, and `on_progress` is called once per entry. All documents and
+    /// chunks carry the report's `source_group` in their metadata.
+    pub fn add_archive<F>(
+        &self,
+        project_id: &str,
+        archive_path: &str,
+        mut on_progress: F,
+    ) -> Result<ArchiveIngestReport>
+    where
+        F: FnMut(&ArchiveIngestProgress),
+    {
+        let archive = Path::new(archive_path);
+        let archive_name = archive
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("archive.zip")
+            .to_string();
+        let source_group = uuid::Uuid::new_v4().to_string();
+
+        let sandbox = tempfile::Builder::new()
+            .prefix("knowledge-archive-")
+            .tempdir()?;
+        let (files, mut results) =
+            extract_archive(archive, sandbox.path(), &ArchiveLimits::default())?;
+        let total = files.len() + results.len();
+
+        for (index, file) in results.iter().enumerate() {
+            on_progress(&ArchiveIngestProgress {
+                source_group: source_group.clone(),
+                index: index + 1,
+                total,
+                file: file.clone(),
+            });
+        }
+
+        for file in files {
+            let metadata = serde_json::json!({
+                "source_group": source_group,
+                "archive": archive_name,
+                "archive_path": archive_path,
+                "entry": file.entry,
+            });
+            let result = match self.ingest_file(
+                project_id,
+                &file.path,
+                &format!("{}!/{}", archive_path, file.entry),
+                format!("{}/{}", archive_name, file.entry),
+                Some(metadata),
+            ) {
+                Ok((document, chunks)) => {
+                    ArchiveFileResult::ingested(&file.entry, document.id, chunks)
+                }
+                Err(e) => ArchiveFileResult::failed(&file.entry, format!("{:#}", e)),
+            };
+            results.push(result.clone());
+            on_progress(&ArchiveIngestProgress {
+                source_group: source_group.clone(),
+                index: results.len(),
+                total,
+                file: result,
+            });
+        }
+
+        Ok(ArchiveIngestReport::new(
+            source_group,
+            archive_path.to_string(),
+            results,
+        ))
+    }
+
+    /// Extract, chunk and embed one file; `metadata` is copied onto every chunk
+    fn ingest_file(
+        &self,
+        project_id: &str,
+        path: &Path,
+        file_path: &str,
+        file_name: String,
+        metadata: Option<serde_json::Value>,
+    ) -> Result<(KnowledgeDocument, usize)> {
         let file_type = path
             .extension()
             .and_then(|e| e.to_str())
@@ -291,7 +381,7 @@ impl ProjectManager {
         // Extract text from file
         let content = self
             .rag_engine
-            .extract_text_from_file(file_path, &file_type)?;
+            .extract_text_from_file(&path.to_string_lossy(), &file_type)?;
         let size = content.len();
 
         // Create document
@@ -303,7 +393,7 @@ impl ProjectManager {
             file_type,
             size,
             content,
-            metadata: None,
+            metadata: metadata.as_ref().map(|m| m.to_string()),
             indexed_at: chrono::Utc::now().to_rfc3339(),
             created_at: chrono::Utc::now().to_rfc3339(),
         };
@@ -313,10 +403,20 @@ impl ProjectManager {
 
         // Chunk the document
         let chunks = self.rag_engine.chunk_document(&document)?;
+        let chunk_count = chunks.len();
 
         // Generate embeddings and add chunks
         for chunk in chunks {
             let mut chunk_with_embedding = chunk;
+            if let Some(serde_json::Value::Object(extra)) = &metadata {
+                let mut merged: serde_json::Map<String, serde_json::Value> = chunk_with_embedding
+                    .metadata
+                    .as_deref()
+                    .and_then(|m| serde_json::from_str(m).ok())
+                    .unwrap_or_default();
+                merged.extend(extra.clone());
+                chunk_with_embedding.metadata = Some(serde_json::Value::Object(merged).to_string());
+            }
             let embedding = self
                 .rag_engine
                 .generate_embedding(&chunk_with_embedding.content)?;
@@ -324,7 +424,7 @@ impl ProjectManager {
             self.knowledge_base.add_chunk(chunk_with_embedding)?;
         }
 
-        Ok(document)
+        Ok((document, chunk_count))
     }
 
     pub fn search_knowledge(
@@ -368,4 +468,64 @@ mod tests {
         let manager = ProjectManager::new(db_path, kb_path);
         assert!(manager.is_ok());
     }
+
+    #[test]
+    fn test_add_archive_groups_documents() {
+        use std::io::Write;
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("projects.db");
+        let manager = ProjectManager::new(db_path.clone(), db_path).unwrap();
+        manager
+            .create_project(Project {
+                id: "proj1".to_string(),
+                name: "Handbook".to_string(),
+                description: None,
+                custom_instructions: None,
+                visibility: "private".to_string(),
+                created_by: "user1".to_string(),
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .unwrap();
+
+        let archive_path = dir.path().join("handbook.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+        let body = "Expense reports are due on the fifth of every month. ".repeat(20);
+        for name in ["policies/expenses.md", "notes.txt", "logo.png"] {
+            zip.start_file(name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let mut progress = Vec::new();
+        let report = manager
+            .add_archive("proj1", archive_path.to_str().unwrap(), |p| {
+                progress.push((p.index, p.total))
+            })
+            .unwrap();
+
+        assert_eq!((report.ingested, report.skipped, report.failed), (2, 1, 0));
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+
+        let documents = manager
+            .knowledge_base
+            .get_source_group_documents("proj1", &report.source_group)
+            .unwrap();
+        assert_eq!(documents.len(), 2);
+        for document in documents {
+            assert!(document.file_name.starts_with("handbook.zip/"));
+            for chunk in manager
+                .knowledge_base
+                .get_document_chunks(&document.id)
+                .unwrap()
+            {
+                let metadata: serde_json::Value =
+                    serde_json::from_str(chunk.metadata.as_deref().unwrap()).unwrap();
+                assert_eq!(metadata["source_group"], report.source_group.as_str());
+                assert_eq!(metadata["source_file"], document.file_name.as_str());
+            }
+        }
+    }
 }
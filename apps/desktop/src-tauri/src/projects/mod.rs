@@ -1,7 +1,11 @@
+pub mod archive;
+pub mod collection;
 pub mod knowledge;
 pub mod manager;
 pub mod rag;
 
+pub use archive::*;
+pub use collection::*;
 pub use knowledge::*;
 pub use manager::*;
 pub use rag::*;
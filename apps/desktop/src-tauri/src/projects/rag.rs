@@ -95,8 +95,10 @@ impl RAGEngine {
             current_chunk.push_str(". ");
         }
 
-        // Add remaining chunk
-        if current_chunk.len() >= self.chunking_config.min_chunk_size {
+        // Add remaining chunk; a document shorter than the minimum still gets one
+        if current_chunk.len() >= self.chunking_config.min_chunk_size
+            || (chunks.is_empty() && !current_chunk.trim().is_empty())
+        {
             chunks.push(self.create_chunk(&current_chunk, document, chunk_index)?);
         }
 
@@ -270,20 +272,13 @@ impl RAGEngine {
     }
 
     pub fn extract_text_from_file(&self, file_path: &str, file_type: &str) -> Result<String> {
-        // TODO: Implement text extraction based on file type
         match file_type.to_lowercase().as_str() {
             "txt" | "md" | "markdown" => {
                 let content = std::fs::read_to_string(file_path)?;
                 Ok(content)
             }
-            "pdf" => {
-                // TODO: Use pdf-extract or similar library
-                Ok(String::from("PDF extraction not yet implemented"))
-            }
-            "docx" => {
-                // TODO: Use docx parsing library
-                Ok(String::from("DOCX extraction not yet implemented"))
-            }
+            "pdf" | "docx" => crate::document::markdown::to_markdown(file_path)
+                .map_err(|e| anyhow::anyhow!("Failed to extract text from {}: {}", file_path, e)),
             "html" | "htm" => {
                 // TODO: Use HTML parser to extract text
                 let content = std::fs::read_to_string(file_path)?;
@@ -0,0 +1,260 @@
+//! Startup readiness registry.
+//!
+//! Setup registers every managed subsystem as `initializing` and then reports
+//! how each one came up. A subsystem that started without part of its state
+//! (a cache that failed to load, accounts that could not be restored) is
+//! `degraded`; one that is unusable is `failed`. Both carry a reason. Every
+//! transition is emitted as [`READINESS_EVENT`] so the UI can gray out
+//! features whose backend is not available.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+
+pub const READINESS_EVENT: &str = "app://readiness";
+
+/// Subsystems registered at startup, in setup order
+pub const SUBSYSTEMS: &[&str] = &[
+    "database",
+    "secrets",
+    "auth",
+    "telemetry",
+    "llm_router",
+    "browser",
+    "settings",
+    "file_watcher",
+    "cloud_storage",
+    "calendar",
+    "terminal",
+    "productivity",
+    "documents",
+    "automation",
+    "mcp",
+    "mcp_server",
+    "remote_api",
+    "github",
+    "computer_use",
+    "code_editing",
+    "voice",
+    "shortcuts",
+    "workspace_index",
+    "lsp",
+    "codebase_cache",
+    "billing",
+    "email_sync",
+    "email_outbox",
+    "slack_events",
+    "messaging",
+    "credential_health",
+    "cache_warmup",
+    "workflows",
+    "workflow_triggers",
+    "marketplace",
+    "templates",
+    "realtime_server",
+    "tool_reliability",
+    "tool_costs",
+    "skills",
+    "selector_healing",
+    "metrics",
+    "companion_sync",
+    "embeddings",
+    "ai_employees",
+    "hooks",
+    "prompt_enhancement",
+    "background_tasks",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessState {
+    Initializing,
+    Ready,
+    Degraded,
+    Failed,
+    /// Deliberately not started, e.g. in safe mode
+    Disabled,
+}
+
+impl ReadinessState {
+    /// Whether features backed by the subsystem can be used
+    pub fn is_usable(self) -> bool {
+        matches!(self, Self::Ready | Self::Degraded)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemReadiness {
+    pub name: String,
+    pub state: ReadinessState,
+    pub reason: Option<String>,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessReport {
+    /// No subsystem is still initializing
+    pub settled: bool,
+    pub subsystems: BTreeMap<String, SubsystemReadiness>,
+}
+
+static REGISTRY: Mutex<BTreeMap<String, SubsystemReadiness>> = Mutex::new(BTreeMap::new());
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Register [`SUBSYSTEMS`] as initializing and emit later transitions through `app`
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+    for name in SUBSYSTEMS {
+        set(name, ReadinessState::Initializing, None);
+    }
+}
+
+pub fn ready(name: &str) {
+    set(name, ReadinessState::Ready, None);
+}
+
+pub fn degraded(name: &str, reason: impl Into<String>) {
+    set(name, ReadinessState::Degraded, Some(reason.into()));
+}
+
+pub fn failed(name: &str, reason: impl Into<String>) {
+    set(name, ReadinessState::Failed, Some(reason.into()));
+}
+
+pub fn disabled(name: &str, reason: impl Into<String>) {
+    set(name, ReadinessState::Disabled, Some(reason.into()));
+}
+
+pub fn get(name: &str) -> Option<SubsystemReadiness> {
+    REGISTRY.lock().get(name).cloned()
+}
+
+pub fn snapshot() -> ReadinessReport {
+    let subsystems = REGISTRY.lock().clone();
+    ReadinessReport {
+        settled: subsystems
+            .values()
+            .all(|s| s.state != ReadinessState::Initializing),
+        subsystems,
+    }
+}
+
+fn set(name: &str, state: ReadinessState, reason: Option<String>) {
+    let changed = apply(
+        &mut REGISTRY.lock(),
+        name,
+        state,
+        reason,
+        chrono::Utc::now().timestamp(),
+    );
+
+    let Some(entry) = changed else {
+        return;
+    };
+    match entry.state {
+        ReadinessState::Degraded | ReadinessState::Failed => tracing::warn!(
+            subsystem = name,
+            state = ?entry.state,
+            reason = entry.reason.as_deref().unwrap_or_default(),
+            "Subsystem readiness changed"
+        ),
+        _ => tracing::debug!(subsystem = name, state = ?entry.state, "Subsystem readiness changed"),
+    }
+    if let Some(app) = APP.get() {
+        if let Err(e) = app.emit(READINESS_EVENT, &entry) {
+            tracing::debug!("Failed to emit readiness event: {}", e);
+        }
+    }
+}
+
+/// Record a state, returning the entry only when it changed
+fn apply(
+    registry: &mut BTreeMap<String, SubsystemReadiness>,
+    name: &str,
+    state: ReadinessState,
+    reason: Option<String>,
+    now: i64,
+) -> Option<SubsystemReadiness> {
+    if let Some(current) = registry.get(name) {
+        if current.state == state && current.reason == reason {
+            return None;
+        }
+    }
+
+    let entry = SubsystemReadiness {
+        name: name.to_string(),
+        state,
+        reason,
+        updated_at: now,
+    };
+    registry.insert(name.to_string(), entry.clone());
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_reports_only_transitions() {
+        let mut registry = BTreeMap::new();
+
+        let entry = apply(
+            &mut registry,
+            "embeddings",
+            ReadinessState::Initializing,
+            None,
+            1,
+        );
+        assert_eq!(entry.unwrap().state, ReadinessState::Initializing);
+        assert!(apply(
+            &mut registry,
+            "embeddings",
+            ReadinessState::Initializing,
+            None,
+            2
+        )
+        .is_none());
+
+        let reason = Some("model download failed".to_string());
+        let entry = apply(
+            &mut registry,
+            "embeddings",
+            ReadinessState::Failed,
+            reason.clone(),
+            3,
+        );
+        assert_eq!(entry.unwrap().updated_at, 3);
+        assert!(apply(
+            &mut registry,
+            "embeddings",
+            ReadinessState::Failed,
+            reason,
+            4
+        )
+        .is_none());
+
+        let entry = apply(
+            &mut registry,
+            "embeddings",
+            ReadinessState::Failed,
+            Some("other".to_string()),
+            5,
+        );
+        assert!(entry.is_some());
+        assert_eq!(registry["embeddings"].reason.as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn test_usable_states() {
+        assert!(ReadinessState::Ready.is_usable());
+        assert!(ReadinessState::Degraded.is_usable());
+        assert!(!ReadinessState::Initializing.is_usable());
+        assert!(!ReadinessState::Failed.is_usable());
+        assert!(!ReadinessState::Disabled.is_usable());
+    }
+}
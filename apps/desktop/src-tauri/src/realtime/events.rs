@@ -1,4 +1,4 @@
-use super::{CursorPosition, PresenceStatus};
+use super::{CursorPosition, PresenceStatus, UserActivity};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +14,11 @@ pub enum RealtimeEvent {
         status: PresenceStatus,
     },
 
+    UserActivityChanged {
+        user_id: String,
+        activity: Option<UserActivity>,
+    },
+
     UserTyping {
         user_id: String,
         resource_id: String,
@@ -67,4 +72,13 @@ pub enum RealtimeEvent {
     MilestoneReached {
         milestone: serde_json::Value,
     },
+
+    CompanionStateUpdated {
+        blob: serde_json::Value,
+    },
+
+    /// Approval decision from the mobile companion, sealed under the pairing key
+    CompanionApprovalSubmitted {
+        blob: serde_json::Value,
+    },
 }
@@ -1,9 +1,11 @@
 pub mod collaboration;
 pub mod events;
+pub mod permissions;
 pub mod presence;
 pub mod websocket_server;
 
 pub use collaboration::{CollaborationSession, CursorPosition, Participant};
 pub use events::RealtimeEvent;
+pub use permissions::{ActivityDetail, PresencePolicy, PresenceVisibility};
 pub use presence::{ActivityType, PresenceManager, PresenceStatus, UserActivity, UserPresence};
-pub use websocket_server::RealtimeServer;
+pub use websocket_server::{CompanionInbound, RealtimeServer};
@@ -0,0 +1,329 @@
+use super::{PresenceStatus, RealtimeEvent, UserPresence};
+use crate::teams::TeamRole;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+/// Who in a team may see a member's presence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceVisibility {
+    /// Every member of the team
+    #[default]
+    Team,
+    /// Only the team's admins and owner
+    Admins,
+    /// Nobody; the member always appears offline
+    Nobody,
+}
+
+/// How much of a member's presence is shared with those who may see it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityDetail {
+    /// Online or offline, nothing more
+    OnlineOnly,
+    /// Online, away, busy or offline
+    #[default]
+    Status,
+    /// Status plus the goal or workflow being worked on, typing and cursors
+    CurrentTask,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresencePolicy {
+    pub visibility: PresenceVisibility,
+    pub detail: ActivityDetail,
+}
+
+impl PresenceVisibility {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PresenceVisibility::Team => "team",
+            PresenceVisibility::Admins => "admins",
+            PresenceVisibility::Nobody => "nobody",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "admins" => PresenceVisibility::Admins,
+            "nobody" => PresenceVisibility::Nobody,
+            _ => PresenceVisibility::Team,
+        }
+    }
+}
+
+impl ActivityDetail {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ActivityDetail::OnlineOnly => "online_only",
+            ActivityDetail::Status => "status",
+            ActivityDetail::CurrentTask => "current_task",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "online_only" => ActivityDetail::OnlineOnly,
+            "current_task" => ActivityDetail::CurrentTask,
+            _ => ActivityDetail::Status,
+        }
+    }
+}
+
+/// The member's own rule for `team_id`, falling back to the team's
+/// presence settings and then to [`PresencePolicy::default`]
+pub fn policy(conn: &Connection, team_id: &str, user_id: &str) -> Result<PresencePolicy> {
+    let rule = conn
+        .query_row(
+            "SELECT visibility, detail FROM presence_rules WHERE team_id = ?1 AND user_id = ?2",
+            params![team_id, user_id],
+            |row| {
+                Ok(PresencePolicy {
+                    visibility: PresenceVisibility::parse(&row.get::<_, String>(0)?),
+                    detail: ActivityDetail::parse(&row.get::<_, String>(1)?),
+                })
+            },
+        )
+        .optional()?;
+    if let Some(rule) = rule {
+        return Ok(rule);
+    }
+    team_default(conn, team_id)
+}
+
+/// The team's default from its settings
+pub fn team_default(conn: &Connection, team_id: &str) -> Result<PresencePolicy> {
+    let settings: Option<Option<String>> = conn
+        .query_row(
+            "SELECT settings FROM teams WHERE id = ?1",
+            params![team_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(settings
+        .flatten()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .and_then(|settings| serde_json::from_value(settings.get("presence")?.clone()).ok())
+        .unwrap_or_default())
+}
+
+/// Set a member's rule, or clear it with `None` to follow the team default
+pub fn set_rule(
+    conn: &Connection,
+    team_id: &str,
+    user_id: &str,
+    rule: Option<PresencePolicy>,
+) -> Result<()> {
+    match rule {
+        Some(rule) => conn.execute(
+            "INSERT INTO presence_rules (team_id, user_id, visibility, detail, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(team_id, user_id) DO UPDATE SET
+                visibility = excluded.visibility,
+                detail = excluded.detail,
+                updated_at = excluded.updated_at",
+            params![
+                team_id,
+                user_id,
+                rule.visibility.as_str(),
+                rule.detail.as_str(),
+                Utc::now().timestamp(),
+            ],
+        )?,
+        None => conn.execute(
+            "DELETE FROM presence_rules WHERE team_id = ?1 AND user_id = ?2",
+            params![team_id, user_id],
+        )?,
+    };
+    Ok(())
+}
+
+pub fn member_role(conn: &Connection, team_id: &str, user_id: &str) -> Result<Option<TeamRole>> {
+    let role: Option<String> = conn
+        .query_row(
+            "SELECT role FROM team_members WHERE team_id = ?1 AND user_id = ?2",
+            params![team_id, user_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(role.map(|role| TeamRole::from_str(&role).unwrap_or(TeamRole::Viewer)))
+}
+
+/// How much of `subject`'s presence in `team_id` the `viewer` may see, or
+/// `None` if none of it. Both have to be members of the team.
+pub fn visible_detail(
+    conn: &Connection,
+    team_id: &str,
+    viewer_id: &str,
+    subject_id: &str,
+) -> Result<Option<ActivityDetail>> {
+    if viewer_id == subject_id {
+        return Ok(Some(ActivityDetail::CurrentTask));
+    }
+    if member_role(conn, team_id, subject_id)?.is_none() {
+        return Ok(None);
+    }
+    let Some(viewer_role) = member_role(conn, team_id, viewer_id)? else {
+        return Ok(None);
+    };
+
+    let policy = policy(conn, team_id, subject_id)?;
+    let allowed = match policy.visibility {
+        PresenceVisibility::Team => true,
+        PresenceVisibility::Admins => matches!(viewer_role, TeamRole::Admin | TeamRole::Owner),
+        PresenceVisibility::Nobody => false,
+    };
+    Ok(allowed.then_some(policy.detail))
+}
+
+/// `presence` reduced to what `detail` allows
+pub fn redact(presence: &UserPresence, detail: ActivityDetail) -> UserPresence {
+    let mut presence = presence.clone();
+    if detail < ActivityDetail::CurrentTask {
+        presence.current_activity = None;
+    }
+    if detail == ActivityDetail::OnlineOnly {
+        presence.status = online_only(&presence.status);
+    }
+    presence
+}
+
+/// The member an event reveals the presence or activity of, for events
+/// that are subject to presence rules
+pub fn event_subject(event: &RealtimeEvent) -> Option<&str> {
+    match event {
+        RealtimeEvent::UserPresenceChanged { user_id, .. }
+        | RealtimeEvent::UserActivityChanged { user_id, .. }
+        | RealtimeEvent::UserTyping { user_id, .. }
+        | RealtimeEvent::CursorMoved { user_id, .. } => Some(user_id),
+        _ => None,
+    }
+}
+
+/// `event` as it may be shown at `detail`, or `None` if it reveals more
+pub fn redact_event(event: &RealtimeEvent, detail: ActivityDetail) -> Option<RealtimeEvent> {
+    match event {
+        RealtimeEvent::UserPresenceChanged { user_id, status } => {
+            let status = if detail == ActivityDetail::OnlineOnly {
+                online_only(status)
+            } else {
+                status.clone()
+            };
+            Some(RealtimeEvent::UserPresenceChanged {
+                user_id: user_id.clone(),
+                status,
+            })
+        }
+        RealtimeEvent::UserActivityChanged { .. }
+        | RealtimeEvent::UserTyping { .. }
+        | RealtimeEvent::CursorMoved { .. } => {
+            (detail == ActivityDetail::CurrentTask).then(|| event.clone())
+        }
+        _ => Some(event.clone()),
+    }
+}
+
+fn online_only(status: &PresenceStatus) -> PresenceStatus {
+    match status {
+        PresenceStatus::Offline => PresenceStatus::Offline,
+        _ => PresenceStatus::Online,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use crate::realtime::{ActivityType, UserActivity};
+
+    fn team_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO teams (id, name, owner_id, settings) VALUES ('t1', 'Team', 'owner', ?1)",
+            params![r#"{"presence":{"visibility":"team","detail":"online_only"}}"#],
+        )
+        .unwrap();
+        for (user, role) in [("owner", "owner"), ("alice", "editor"), ("bob", "viewer")] {
+            conn.execute(
+                "INSERT INTO team_members (team_id, user_id, role) VALUES ('t1', ?1, ?2)",
+                params![user, role],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn team_settings_provide_the_default() {
+        let conn = team_db();
+        assert_eq!(
+            visible_detail(&conn, "t1", "bob", "alice").unwrap(),
+            Some(ActivityDetail::OnlineOnly)
+        );
+        assert_eq!(
+            visible_detail(&conn, "t1", "alice", "alice").unwrap(),
+            Some(ActivityDetail::CurrentTask)
+        );
+        // Outsiders see nothing, and nobody sees outsiders
+        assert_eq!(
+            visible_detail(&conn, "t1", "mallory", "alice").unwrap(),
+            None
+        );
+        assert_eq!(
+            visible_detail(&conn, "t1", "alice", "mallory").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn member_rules_override_the_team_default() {
+        let conn = team_db();
+        let admins_only = PresencePolicy {
+            visibility: PresenceVisibility::Admins,
+            detail: ActivityDetail::CurrentTask,
+        };
+        set_rule(&conn, "t1", "alice", Some(admins_only)).unwrap();
+        assert_eq!(visible_detail(&conn, "t1", "bob", "alice").unwrap(), None);
+        assert_eq!(
+            visible_detail(&conn, "t1", "owner", "alice").unwrap(),
+            Some(ActivityDetail::CurrentTask)
+        );
+
+        set_rule(&conn, "t1", "alice", None).unwrap();
+        assert_eq!(
+            visible_detail(&conn, "t1", "bob", "alice").unwrap(),
+            Some(ActivityDetail::OnlineOnly)
+        );
+    }
+
+    #[test]
+    fn redaction_drops_what_the_detail_level_hides() {
+        let presence = UserPresence {
+            user_id: "alice".to_string(),
+            status: PresenceStatus::Busy,
+            last_seen: 0,
+            current_activity: Some(UserActivity {
+                activity_type: ActivityType::EditingWorkflow,
+                resource_id: "wf-1".to_string(),
+                started_at: 0,
+            }),
+        };
+        let online = redact(&presence, ActivityDetail::OnlineOnly);
+        assert_eq!(online.status, PresenceStatus::Online);
+        assert!(online.current_activity.is_none());
+        let status = redact(&presence, ActivityDetail::Status);
+        assert_eq!(status.status, PresenceStatus::Busy);
+        assert!(status.current_activity.is_none());
+
+        let typing = RealtimeEvent::UserTyping {
+            user_id: "alice".to_string(),
+            resource_id: "wf-1".to_string(),
+        };
+        assert!(redact_event(&typing, ActivityDetail::Status).is_none());
+        assert!(redact_event(&typing, ActivityDetail::CurrentTask).is_some());
+    }
+}
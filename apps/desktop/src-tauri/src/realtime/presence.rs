@@ -1,6 +1,7 @@
 // Updated Nov 16, 2025: Replaced .unwrap() with proper error handling
+use super::permissions::{self, ActivityDetail, PresencePolicy};
+use crate::db::Pool;
 use chrono::Utc;
-use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -37,12 +38,12 @@ pub enum ActivityType {
 }
 
 pub struct PresenceManager {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
     online_users: Arc<Mutex<HashMap<String, UserPresence>>>,
 }
 
 impl PresenceManager {
-    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self {
             db,
             online_users: Arc::new(Mutex::new(HashMap::new())),
@@ -83,13 +84,94 @@ impl PresenceManager {
         }
     }
 
-    pub fn get_team_presence(&self, _team_id: &str) -> Vec<UserPresence> {
-        // For now, return all online users
-        // In a real implementation, filter by team_id from database
-        self.online_users
+    pub fn set_status(&self, user_id: &str, status: PresenceStatus) {
+        if let Ok(mut users) = self.online_users.lock() {
+            if let Some(presence) = users.get_mut(user_id) {
+                presence.status = status;
+                presence.last_seen = Utc::now().timestamp();
+                let _ = self.persist_presence(presence);
+            }
+        }
+    }
+
+    pub fn clear_activity(&self, user_id: &str) {
+        if let Ok(mut users) = self.online_users.lock() {
+            if let Some(presence) = users.get_mut(user_id) {
+                presence.current_activity = None;
+                let _ = self.persist_presence(presence);
+            }
+        }
+    }
+
+    /// Online members of `team_id`, reduced to what `viewer_id` may see
+    pub fn get_team_presence(&self, team_id: &str, viewer_id: &str) -> Vec<UserPresence> {
+        let online: Vec<UserPresence> = self
+            .online_users
             .lock()
             .map(|guard| guard.values().cloned().collect())
-            .unwrap_or_default()
+            .unwrap_or_default();
+
+        online
+            .into_iter()
+            .filter_map(|presence| {
+                let detail = self.visible_detail(team_id, viewer_id, &presence.user_id)?;
+                Some(permissions::redact(&presence, detail))
+            })
+            .collect()
+    }
+
+    /// How much of `subject_id`'s presence `viewer_id` may see in `team_id`.
+    /// Rules that cannot be read hide the presence.
+    pub fn visible_detail(
+        &self,
+        team_id: &str,
+        viewer_id: &str,
+        subject_id: &str,
+    ) -> Option<ActivityDetail> {
+        let db = self.db.get().ok()?;
+        permissions::visible_detail(&db, team_id, viewer_id, subject_id).unwrap_or_else(|e| {
+            tracing::warn!("Failed to check presence rules: {}", e);
+            None
+        })
+    }
+
+    pub fn presence_policy(&self, team_id: &str, user_id: &str) -> Result<PresencePolicy, String> {
+        let db = self
+            .db
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        permissions::policy(&db, team_id, user_id)
+            .map_err(|e| format!("Failed to load presence rule: {}", e))
+    }
+
+    /// Set `user_id`'s rule in `team_id`. Members set their own; admins and
+    /// the owner may set anyone's.
+    pub fn set_presence_policy(
+        &self,
+        actor_id: &str,
+        team_id: &str,
+        user_id: &str,
+        policy: Option<PresencePolicy>,
+    ) -> Result<(), String> {
+        let db = self
+            .db
+            .get()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        let actor_role = permissions::member_role(&db, team_id, actor_id)
+            .map_err(|e| format!("Failed to load team member: {}", e))?;
+        let allowed = match actor_role {
+            Some(crate::teams::TeamRole::Admin | crate::teams::TeamRole::Owner) => true,
+            Some(_) => actor_id == user_id,
+            None => false,
+        };
+        if !allowed {
+            return Err(format!(
+                "{} may not change presence rules for {} in team {}",
+                actor_id, user_id, team_id
+            ));
+        }
+        permissions::set_rule(&db, team_id, user_id, policy)
+            .map_err(|e| format!("Failed to save presence rule: {}", e))
     }
 
     pub fn get_user_presence(&self, user_id: &str) -> Option<UserPresence> {
@@ -100,12 +182,10 @@ impl PresenceManager {
     }
 
     fn persist_presence(&self, presence: &UserPresence) -> Result<(), rusqlite::Error> {
-        let db = self.db.lock().map_err(|e| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(format!(
-                "Failed to acquire database lock: {}",
-                e
-            ))))
-        })?;
+        let db = self
+            .db
+            .get()
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
         let activity_json = presence
             .current_activity
             .as_ref()
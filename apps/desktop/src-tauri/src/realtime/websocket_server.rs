@@ -1,4 +1,5 @@
-use super::{PresenceManager, RealtimeEvent};
+use super::permissions;
+use super::{PresenceManager, PresenceStatus, RealtimeEvent};
 use futures::{
     stream::{SplitSink, SplitStream},
     SinkExt, StreamExt,
@@ -7,7 +8,7 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::Mutex as TokioMutex;
+use tokio::sync::{broadcast, Mutex as TokioMutex};
 use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
 
 pub struct WebSocketClient {
@@ -16,10 +17,18 @@ pub struct WebSocketClient {
     pub team_id: Option<String>,
 }
 
+/// Sealed companion message and the signed-in user whose session relayed it
+#[derive(Debug, Clone)]
+pub struct CompanionInbound {
+    pub user_id: String,
+    pub blob: serde_json::Value,
+}
+
 pub struct RealtimeServer {
     clients: Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
     senders: Arc<TokioMutex<HashMap<String, SplitSink<WebSocketStream<TcpStream>, Message>>>>,
     presence: Arc<PresenceManager>,
+    companion_inbox: broadcast::Sender<CompanionInbound>,
 }
 
 impl RealtimeServer {
@@ -28,9 +37,15 @@ impl RealtimeServer {
             clients: Arc::new(TokioMutex::new(HashMap::new())),
             senders: Arc::new(TokioMutex::new(HashMap::new())),
             presence,
+            companion_inbox: broadcast::channel(32).0,
         }
     }
 
+    /// Companion messages received from connected sessions
+    pub fn subscribe_companion(&self) -> broadcast::Receiver<CompanionInbound> {
+        self.companion_inbox.subscribe()
+    }
+
     pub async fn broadcast_to_user(
         &self,
         user_id: &str,
@@ -44,6 +59,7 @@ impl RealtimeServer {
         let listener = TcpListener::bind(&addr).await?;
 
         tracing::info!("WebSocket server listening on {}", addr);
+        crate::readiness::ready("realtime_server");
 
         loop {
             match listener.accept().await {
@@ -51,10 +67,16 @@ impl RealtimeServer {
                     let clients = self.clients.clone();
                     let senders = self.senders.clone();
                     let presence = self.presence.clone();
+                    let companion_inbox = self.companion_inbox.clone();
 
                     tokio::spawn(async move {
                         if let Err(e) = Self::handle_connection_wrapper(
-                            stream, peer, clients, senders, presence,
+                            stream,
+                            peer,
+                            clients,
+                            senders,
+                            presence,
+                            companion_inbox,
                         )
                         .await
                         {
@@ -75,9 +97,10 @@ impl RealtimeServer {
         clients: Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
         senders: Arc<TokioMutex<HashMap<String, SplitSink<WebSocketStream<TcpStream>, Message>>>>,
         presence: Arc<PresenceManager>,
+        companion_inbox: broadcast::Sender<CompanionInbound>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let ws_stream = accept_async(stream).await?;
-        Self::handle_connection(ws_stream, peer, clients, senders, presence).await;
+        Self::handle_connection(ws_stream, peer, clients, senders, presence, companion_inbox).await;
         Ok(())
     }
 
@@ -87,6 +110,7 @@ impl RealtimeServer {
         clients: Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
         senders: Arc<TokioMutex<HashMap<String, SplitSink<WebSocketStream<TcpStream>, Message>>>>,
         presence: Arc<PresenceManager>,
+        companion_inbox: broadcast::Sender<CompanionInbound>,
     ) {
         let (sender, receiver) = ws_stream.split();
         let client_id = uuid::Uuid::new_v4().to_string();
@@ -110,24 +134,44 @@ impl RealtimeServer {
         }
 
         // Handle messages
-        Self::handle_messages(receiver, &client_id, &clients, &senders, &presence).await;
+        Self::handle_messages(
+            receiver,
+            &client_id,
+            &clients,
+            &senders,
+            &presence,
+            &companion_inbox,
+        )
+        .await;
 
         // Remove client on disconnect
-        {
+        let departed = {
             let mut clients_lock = clients.lock().await;
-            if let Some(client) = clients_lock.get(&client_id) {
-                if let Some(user_id) = &client.user_id {
-                    presence.set_offline(user_id);
-                }
+            let departed = clients_lock.remove(&client_id);
+            if let Some(user_id) = departed.as_ref().and_then(|c| c.user_id.as_ref()) {
+                presence.set_offline(user_id);
             }
-            clients_lock.remove(&client_id);
-        }
+            departed
+        };
 
         {
             let mut senders_lock = senders.lock().await;
             senders_lock.remove(&client_id);
         }
 
+        if let Some(WebSocketClient {
+            user_id: Some(user_id),
+            team_id: Some(team_id),
+            ..
+        }) = departed
+        {
+            let event = RealtimeEvent::UserPresenceChanged {
+                user_id,
+                status: PresenceStatus::Offline,
+            };
+            Self::broadcast_presence(&team_id, event, &clients, &senders, &presence).await;
+        }
+
         tracing::info!("Client disconnected: {}", client_id);
     }
 
@@ -137,11 +181,20 @@ impl RealtimeServer {
         clients: &Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
         senders: &Arc<TokioMutex<HashMap<String, SplitSink<WebSocketStream<TcpStream>, Message>>>>,
         presence: &Arc<PresenceManager>,
+        companion_inbox: &broadcast::Sender<CompanionInbound>,
     ) {
         while let Some(Ok(msg)) = receiver.next().await {
             if let Message::Text(text) = msg {
                 if let Ok(event) = serde_json::from_str::<RealtimeEvent>(&text) {
-                    Self::handle_event(event, client_id, clients, senders, presence).await;
+                    Self::handle_event(
+                        event,
+                        client_id,
+                        clients,
+                        senders,
+                        presence,
+                        companion_inbox,
+                    )
+                    .await;
                 }
             }
         }
@@ -153,6 +206,7 @@ impl RealtimeServer {
         clients: &Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
         senders: &Arc<TokioMutex<HashMap<String, SplitSink<WebSocketStream<TcpStream>, Message>>>>,
         presence: &Arc<PresenceManager>,
+        companion_inbox: &broadcast::Sender<CompanionInbound>,
     ) {
         match &event {
             RealtimeEvent::Authenticate { user_id, team_id } => {
@@ -166,6 +220,37 @@ impl RealtimeServer {
                 }
                 presence.set_online(user_id);
                 tracing::info!("Client authenticated: {} as user {}", client_id, user_id);
+
+                if let Some(team_id) = team_id {
+                    let event = RealtimeEvent::UserPresenceChanged {
+                        user_id: user_id.clone(),
+                        status: PresenceStatus::Online,
+                    };
+                    Self::broadcast_presence(team_id, event, clients, senders, presence).await;
+                }
+            }
+
+            RealtimeEvent::UserPresenceChanged { status, .. } => {
+                let Some((user_id, team_id)) =
+                    Self::authenticated_subject(&event, client_id, clients).await
+                else {
+                    return;
+                };
+                presence.set_status(&user_id, status.clone());
+                Self::broadcast_presence(&team_id, event.clone(), clients, senders, presence).await;
+            }
+
+            RealtimeEvent::UserActivityChanged { activity, .. } => {
+                let Some((user_id, team_id)) =
+                    Self::authenticated_subject(&event, client_id, clients).await
+                else {
+                    return;
+                };
+                match activity {
+                    Some(activity) => presence.set_activity(&user_id, activity.clone()),
+                    None => presence.clear_activity(&user_id),
+                }
+                Self::broadcast_presence(&team_id, event.clone(), clients, senders, presence).await;
             }
 
             RealtimeEvent::GoalCreated { .. } => {
@@ -186,14 +271,33 @@ impl RealtimeServer {
                 }
             }
 
-            RealtimeEvent::UserTyping { resource_id, .. } => {
-                Self::broadcast_to_resource(resource_id, event.clone(), clients, senders).await;
+            RealtimeEvent::UserTyping { .. } | RealtimeEvent::CursorMoved { .. } => {
+                // Typing and cursors reveal what a member is working on
+                if let Some((_, team_id)) =
+                    Self::authenticated_subject(&event, client_id, clients).await
+                {
+                    Self::broadcast_presence(&team_id, event.clone(), clients, senders, presence)
+                        .await;
+                }
             }
 
-            RealtimeEvent::CursorMoved { .. } => {
-                // Broadcast to all clients in the same team
-                if let Some(team_id) = Self::get_client_team(client_id, clients).await {
-                    Self::broadcast_to_team(&team_id, event.clone(), clients, senders).await;
+            RealtimeEvent::CompanionApprovalSubmitted { blob } => {
+                // Only signed-in sessions may relay; companion sync checks the seal
+                let user_id = {
+                    let clients_lock = clients.lock().await;
+                    clients_lock.get(client_id).and_then(|c| c.user_id.clone())
+                };
+                match user_id {
+                    Some(user_id) => {
+                        let _ = companion_inbox.send(CompanionInbound {
+                            user_id,
+                            blob: blob.clone(),
+                        });
+                    }
+                    None => tracing::debug!(
+                        "Dropping companion approval from unauthenticated client {}",
+                        client_id
+                    ),
                 }
             }
 
@@ -230,23 +334,58 @@ impl RealtimeServer {
         }
     }
 
-    async fn broadcast_to_resource(
-        _resource_id: &str,
+    /// The sender's user and team, if the event is about the sender. Clients
+    /// may only announce their own presence.
+    async fn authenticated_subject(
+        event: &RealtimeEvent,
+        client_id: &str,
+        clients: &Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
+    ) -> Option<(String, String)> {
+        let clients_lock = clients.lock().await;
+        let client = clients_lock.get(client_id)?;
+        let user_id = client.user_id.clone()?;
+        let team_id = client.team_id.clone()?;
+        if permissions::event_subject(event) != Some(user_id.as_str()) {
+            tracing::debug!(
+                "Dropping presence event from {} about another user",
+                client_id
+            );
+            return None;
+        }
+        Some((user_id, team_id))
+    }
+
+    /// Send a presence event to the members of `team_id`, each seeing only
+    /// what the subject's presence rules allow
+    async fn broadcast_presence(
+        team_id: &str,
         event: RealtimeEvent,
         clients: &Arc<TokioMutex<HashMap<String, WebSocketClient>>>,
         senders: &Arc<TokioMutex<HashMap<String, SplitSink<WebSocketStream<TcpStream>, Message>>>>,
+        presence: &Arc<PresenceManager>,
     ) {
-        // For now, broadcast to all authenticated clients
-        // In a real implementation, track which clients are viewing/editing the resource
-        let message = Message::Text(serde_json::to_string(&event).unwrap_or_default());
+        let Some(subject_id) = permissions::event_subject(&event) else {
+            return;
+        };
         let clients_lock = clients.lock().await;
         let mut senders_lock = senders.lock().await;
 
         for (client_id, client) in clients_lock.iter() {
-            if client.user_id.is_some() {
-                if let Some(sender) = senders_lock.get_mut(client_id) {
-                    let _ = sender.send(message.clone()).await;
-                }
+            if client.team_id.as_deref() != Some(team_id) {
+                continue;
+            }
+            let Some(viewer_id) = client.user_id.as_deref() else {
+                continue;
+            };
+            let Some(visible) = presence
+                .visible_detail(team_id, viewer_id, subject_id)
+                .and_then(|detail| permissions::redact_event(&event, detail))
+            else {
+                continue;
+            };
+            if let Some(sender) = senders_lock.get_mut(client_id) {
+                let message = Message::Text(serde_json::to_string(&visible).unwrap_or_default());
+                let _ = sender.send(message).await;
             }
         }
     }
@@ -0,0 +1,291 @@
+//! Scripts, CI jobs and devices allowed to call the remote API
+
+use crate::permissions::profiles;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Groups of endpoints a client may call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Submit goals to the agent
+    Goals,
+    /// Run saved workflows
+    Workflows,
+    /// Goal and workflow status, readiness and live goal events
+    Status,
+    /// ROI metrics
+    Metrics,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 4] = [
+        ApiScope::Goals,
+        ApiScope::Workflows,
+        ApiScope::Status,
+        ApiScope::Metrics,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiScope::Goals => "goals",
+            ApiScope::Workflows => "workflows",
+            ApiScope::Status => "status",
+            ApiScope::Metrics => "metrics",
+        }
+    }
+}
+
+/// A caller of the remote API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiClient {
+    pub id: String,
+    pub name: String,
+    /// Permission profile the tool calls of the client's goals are checked against
+    pub profile_id: String,
+    /// Account whose metrics the client may read
+    pub user_id: Option<String>,
+    pub scopes: Vec<ApiScope>,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+impl ApiClient {
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// Fields of a client the user sets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiClientInput {
+    pub name: String,
+    pub profile_id: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub scopes: Vec<ApiScope>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+impl ApiClientInput {
+    fn validate(&self, conn: &Connection) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Client name is required".to_string());
+        }
+        if self.scopes.is_empty() {
+            return Err("Pick at least one scope".to_string());
+        }
+        profiles::get_profile(conn, &self.profile_id)
+            .map_err(|e| format!("Failed to load permission profile: {}", e))?
+            .ok_or_else(|| format!("Unknown permission profile '{}'", self.profile_id))?;
+        Ok(())
+    }
+
+    /// Scopes in a stable order without repeats
+    fn scopes_json(&self) -> Result<String, String> {
+        let scopes: Vec<ApiScope> = ApiScope::ALL
+            .into_iter()
+            .filter(|scope| self.scopes.contains(scope))
+            .collect();
+        serde_json::to_string(&scopes).map_err(|e| format!("Failed to serialize scopes: {}", e))
+    }
+}
+
+/// A new client and the token it authenticates with, shown once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiClientCreated {
+    pub client: ApiClient,
+    pub token: String,
+}
+
+fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+const CLIENT_COLUMNS: &str =
+    "id, name, profile_id, user_id, scopes, enabled, created_at, updated_at, last_used_at";
+
+fn map_client(row: &Row) -> rusqlite::Result<ApiClient> {
+    let scopes: String = row.get(4)?;
+    Ok(ApiClient {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        profile_id: row.get(2)?,
+        user_id: row.get(3)?,
+        scopes: serde_json::from_str(&scopes).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        enabled: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        last_used_at: row.get(8)?,
+    })
+}
+
+pub fn list_clients(conn: &Connection) -> rusqlite::Result<Vec<ApiClient>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM api_clients ORDER BY created_at ASC, rowid ASC",
+        CLIENT_COLUMNS
+    ))?;
+    let clients = stmt
+        .query_map([], map_client)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(clients)
+}
+
+pub fn get_client(conn: &Connection, id: &str) -> rusqlite::Result<Option<ApiClient>> {
+    conn.query_row(
+        &format!("SELECT {} FROM api_clients WHERE id = ?1", CLIENT_COLUMNS),
+        [id],
+        map_client,
+    )
+    .optional()
+}
+
+/// Register a client; returns it with its token, which is only stored hashed
+pub fn create_client(
+    conn: &Connection,
+    input: &ApiClientInput,
+) -> Result<(ApiClient, String), String> {
+    input.validate(conn)?;
+    let id = Uuid::new_v4().to_string();
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    conn.execute(
+        "INSERT INTO api_clients
+             (id, name, token_hash, profile_id, user_id, scopes, enabled, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+        params![
+            id,
+            input.name.trim(),
+            token_hash(&token),
+            input.profile_id,
+            input.user_id,
+            input.scopes_json()?,
+            input.enabled,
+            Utc::now().timestamp(),
+        ],
+    )
+    .map_err(|e| format!("Failed to save API client: {}", e))?;
+
+    let client = get_client(conn, &id)
+        .map_err(|e| format!("Failed to load API client: {}", e))?
+        .ok_or_else(|| format!("API client {} not found", id))?;
+    Ok((client, token))
+}
+
+/// Change a client's name, profile, scopes or enabled state; its token stays
+pub fn update_client(
+    conn: &Connection,
+    id: &str,
+    input: &ApiClientInput,
+) -> Result<ApiClient, String> {
+    input.validate(conn)?;
+    let updated = conn
+        .execute(
+            "UPDATE api_clients
+             SET name = ?2, profile_id = ?3, user_id = ?4, scopes = ?5, enabled = ?6,
+                 updated_at = ?7
+             WHERE id = ?1",
+            params![
+                id,
+                input.name.trim(),
+                input.profile_id,
+                input.user_id,
+                input.scopes_json()?,
+                input.enabled,
+                Utc::now().timestamp(),
+            ],
+        )
+        .map_err(|e| format!("Failed to save API client: {}", e))?;
+    if updated == 0 {
+        return Err(format!("API client {} not found", id));
+    }
+    get_client(conn, id)
+        .map_err(|e| format!("Failed to load API client: {}", e))?
+        .ok_or_else(|| format!("API client {} not found", id))
+}
+
+pub fn delete_client(conn: &Connection, id: &str) -> rusqlite::Result<bool> {
+    Ok(conn.execute("DELETE FROM api_clients WHERE id = ?1", [id])? > 0)
+}
+
+/// The enabled client `token` belongs to; marks it as used
+pub fn authenticate(conn: &Connection, token: &str) -> rusqlite::Result<Option<ApiClient>> {
+    let client = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM api_clients WHERE token_hash = ?1 AND enabled = 1",
+                CLIENT_COLUMNS
+            ),
+            [token_hash(token)],
+            map_client,
+        )
+        .optional()?;
+    if let Some(client) = &client {
+        conn.execute(
+            "UPDATE api_clients SET last_used_at = ?2 WHERE id = ?1",
+            params![client.id, Utc::now().timestamp()],
+        )?;
+    }
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+
+    fn input(scopes: &[ApiScope]) -> ApiClientInput {
+        ApiClientInput {
+            name: "CI".to_string(),
+            profile_id: profiles::READ_ONLY_ANALYST.to_string(),
+            user_id: None,
+            scopes: scopes.to_vec(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_clients_authenticate_by_token_with_their_scopes() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let (client, token) = create_client(
+            &conn,
+            &input(&[ApiScope::Status, ApiScope::Goals, ApiScope::Status]),
+        )
+        .unwrap();
+        assert_eq!(client.scopes, vec![ApiScope::Goals, ApiScope::Status]);
+        assert!(!client.allows(ApiScope::Workflows));
+        assert!(authenticate(&conn, "wrong").unwrap().is_none());
+        let authenticated = authenticate(&conn, &token).unwrap().unwrap();
+        assert_eq!(authenticated.id, client.id);
+        assert!(get_client(&conn, &client.id)
+            .unwrap()
+            .unwrap()
+            .last_used_at
+            .is_some());
+
+        let mut disabled = input(&[ApiScope::Metrics]);
+        disabled.enabled = false;
+        update_client(&conn, &client.id, &disabled).unwrap();
+        assert!(authenticate(&conn, &token).unwrap().is_none());
+
+        assert!(create_client(&conn, &input(&[])).is_err());
+        let mut unknown_profile = input(&[ApiScope::Goals]);
+        unknown_profile.profile_id = "missing".to_string();
+        assert!(create_client(&conn, &unknown_profile).is_err());
+        assert!(delete_client(&conn, &client.id).unwrap());
+        assert!(list_clients(&conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//! HTTP API for driving the app headlessly or from another device.
+//!
+//! When enabled, a listener on 127.0.0.1 (or every interface with
+//! `allow_remote`) serves a small set of commands under `/v1`: submit goals,
+//! run workflows, read goal and workflow status, readiness and ROI metrics,
+//! and follow goal events over a WebSocket at `/v1/events`. Callers are
+//! registered in `api_clients` with a bearer token, the scopes they may use,
+//! and a permission profile their goals run under; only the WebSocket also
+//! takes the token as `?token=`. Every call, including refused ones, is
+//! written to the audit log. The listener speaks plain HTTP, so a status
+//! with `allow_remote` carries a warning for the settings page.
+
+pub mod clients;
+pub mod server;
+
+pub use clients::{ApiClient, ApiClientCreated, ApiClientInput, ApiScope};
+pub use server::{RemoteApiConfig, RemoteApiServer, RemoteApiStatus, DEFAULT_PORT};
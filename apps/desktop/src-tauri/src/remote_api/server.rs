@@ -0,0 +1,1023 @@
+//! HTTP and WebSocket listener of the remote API
+
+use super::clients::{self, ApiClient, ApiClientCreated, ApiClientInput, ApiScope};
+use crate::audit::{self, Actor, AuditCategory, AuditEntry, AuditOutcome};
+use crate::db::Pool;
+use crate::orchestration::{WorkflowDefinition, WorkflowNode};
+use crate::permissions::profiles::{self, PermissionProfile, ToolCategory, ToolDecision};
+use axum::body::Bytes;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tauri::{AppHandle, Listener, Manager};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub const DEFAULT_PORT: u16 = 8792;
+const CONFIG_SETTING: &str = "remote_api.config";
+
+/// App events forwarded to `GET /v1/events` subscribers
+pub const STREAMED_EVENTS: &[&str] = &[
+    "agi:goal:submitted",
+    "agi:goal:plan_created",
+    "agi:goal:step_started",
+    "agi:goal:step_completed",
+    "agi:goal:progress",
+    "agi:goal:achieved",
+    "agi:goal:budget_stopped",
+    "agi:goal:budget_exceeded",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on every network interface instead of only 127.0.0.1, so
+    /// phones and other machines can connect
+    #[serde(default)]
+    pub allow_remote: bool,
+}
+
+impl RemoteApiConfig {
+    /// Why this configuration is risky: the listener speaks plain HTTP, so
+    /// beyond 127.0.0.1 tokens and goals cross the network unencrypted
+    pub fn warning(&self) -> Option<String> {
+        (self.enabled && self.allow_remote).then(|| {
+            "The remote API listens on every network interface without TLS. Tokens and \
+             requests can be read by anyone on the network; only allow remote access on \
+             a trusted network or behind an HTTPS proxy."
+                .to_string()
+        })
+    }
+}
+
+impl Default for RemoteApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            allow_remote: false,
+        }
+    }
+}
+
+pub fn load_config(conn: &rusqlite::Connection) -> anyhow::Result<RemoteApiConfig> {
+    match crate::db::repository::get_setting(conn, CONFIG_SETTING) {
+        Ok(setting) => Ok(serde_json::from_str(&setting.value)?),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(RemoteApiConfig::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save_config(conn: &rusqlite::Connection, config: &RemoteApiConfig) -> anyhow::Result<()> {
+    if config.port == 0 {
+        anyhow::bail!("Pick a port for the remote API");
+    }
+    crate::db::repository::set_setting(
+        conn,
+        CONFIG_SETTING.to_string(),
+        serde_json::to_string(config)?,
+        false,
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteApiStatus {
+    pub config: RemoteApiConfig,
+    /// `host:port` the API is listening on
+    pub address: Option<String>,
+    pub clients: Vec<ApiClient>,
+    /// Set while the configuration exposes the API to the network, which
+    /// it serves without TLS
+    pub warning: Option<String>,
+}
+
+/// A running listener
+struct Listening {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+    /// Ends the listener's WebSocket streams
+    stopped: CancellationToken,
+}
+
+/// Lets registered clients drive the app over HTTP: submit goals, run
+/// workflows, read status and metrics, and follow goals over a WebSocket
+pub struct RemoteApiServer {
+    pool: Pool,
+    /// Managed state the endpoints call into; `None` in tests
+    app: Option<AppHandle>,
+    listening: tokio::sync::Mutex<Option<Listening>>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    server: Arc<RemoteApiServer>,
+    stopped: CancellationToken,
+}
+
+/// Query of `/v1/events`
+#[derive(Debug, Default, Deserialize)]
+struct AuthQuery {
+    /// For WebSocket clients that cannot set headers
+    token: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MetricsQuery {
+    days: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WorkflowRunRequest {
+    #[serde(default)]
+    inputs: HashMap<String, Value>,
+    /// Predict the run's path, duration and cost without running anything
+    #[serde(default)]
+    simulate: bool,
+}
+
+/// A failed call, answered as `{ "error": message }`
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message.to_string())
+    }
+
+    /// The app understood the call but could not do it
+    fn failed(message: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, message.to_string())
+    }
+
+    fn unavailable(what: &str) -> Self {
+        Self::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("{} is not available yet", what),
+        )
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// Token from `Authorization: Bearer`
+fn bearer(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+/// A JSON body, or `null` when there is none
+fn body_json(body: &Bytes) -> Result<Value, ApiError> {
+    if body.is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(body).map_err(|e| ApiError::bad_request(format!("Invalid JSON: {}", e)))
+}
+
+impl RemoteApiServer {
+    pub fn new(pool: Pool, app: Option<AppHandle>) -> Self {
+        Self {
+            pool,
+            app,
+            listening: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    pub async fn status(&self) -> Result<RemoteApiStatus, String> {
+        let (config, clients) = self
+            .pool
+            .run(|conn| -> Result<_, String> {
+                let config = load_config(conn).map_err(|e| e.to_string())?;
+                let clients = clients::list_clients(conn).map_err(|e| e.to_string())?;
+                Ok((config, clients))
+            })
+            .await?;
+        let address = self
+            .listening
+            .lock()
+            .await
+            .as_ref()
+            .map(|listening| listening.address.to_string());
+        Ok(RemoteApiStatus {
+            warning: config.warning(),
+            config,
+            address,
+            clients,
+        })
+    }
+
+    /// Save the configuration and restart the listener with it
+    pub async fn configure(
+        self: &Arc<Self>,
+        config: RemoteApiConfig,
+    ) -> Result<RemoteApiStatus, String> {
+        self.pool
+            .run(move |conn| save_config(conn, &config).map_err(|e| e.to_string()))
+            .await?;
+        self.sync_listener().await?;
+        self.status().await
+    }
+
+    /// Listen as configured, restarting on a changed configuration; stops
+    /// when disabled or in safe mode
+    pub async fn sync_listener(self: &Arc<Self>) -> Result<(), String> {
+        let config = self
+            .pool
+            .run(|conn| load_config(conn).map_err(|e| e.to_string()))
+            .await?;
+
+        let mut listening = self.listening.lock().await;
+        if let Some(previous) = listening.take() {
+            previous.stopped.cancel();
+            previous.task.abort();
+            let _ = previous.task.await;
+            tracing::info!("Remote API stopped listening on {}", previous.address);
+        }
+        if !config.enabled || crate::safe_mode::is_active() {
+            return Ok(());
+        }
+
+        let host = if config.allow_remote {
+            Ipv4Addr::UNSPECIFIED
+        } else {
+            Ipv4Addr::LOCALHOST
+        };
+        let listener = TcpListener::bind((host, config.port))
+            .await
+            .map_err(|e| format!("Failed to bind remote API on {}: {}", config.port, e))?;
+        let address = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read remote API address: {}", e))?;
+        let stopped = CancellationToken::new();
+        let router = self.router(stopped.clone());
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::warn!("Remote API listener failed: {}", e);
+            }
+        });
+        tracing::info!("Remote API listening on {}", address);
+        if let Some(warning) = config.warning() {
+            tracing::warn!("{}", warning);
+        }
+        *listening = Some(Listening {
+            address,
+            task,
+            stopped,
+        });
+        Ok(())
+    }
+
+    pub async fn create_client(&self, input: ApiClientInput) -> Result<ApiClientCreated, String> {
+        let (client, token) = self
+            .pool
+            .run(move |conn| clients::create_client(conn, &input))
+            .await?;
+        Ok(ApiClientCreated { client, token })
+    }
+
+    pub async fn update_client(
+        &self,
+        id: String,
+        input: ApiClientInput,
+    ) -> Result<ApiClient, String> {
+        self.pool
+            .run(move |conn| clients::update_client(conn, &id, &input))
+            .await
+    }
+
+    pub async fn delete_client(&self, id: String) -> Result<bool, String> {
+        self.pool
+            .run(move |conn| clients::delete_client(conn, &id).map_err(|e| e.to_string()))
+            .await
+    }
+
+    fn router(self: &Arc<Self>, stopped: CancellationToken) -> Router {
+        Router::new()
+            .route("/v1/health", get(health))
+            .route("/v1/status", get(app_status))
+            .route("/v1/goals", get(list_goals).post(submit_goal))
+            .route("/v1/goals/{goal_id}", get(goal_status))
+            .route("/v1/workflows/{workflow_id}/run", post(run_workflow))
+            .route(
+                "/v1/workflows/executions/{execution_id}",
+                get(workflow_status),
+            )
+            .route("/v1/metrics", get(metrics))
+            .route("/v1/events", get(events))
+            .with_state(ApiState {
+                server: Arc::clone(self),
+                stopped,
+            })
+    }
+
+    fn app(&self) -> Result<&AppHandle, ApiError> {
+        self.app
+            .as_ref()
+            .ok_or_else(|| ApiError::unavailable("The app"))
+    }
+
+    /// The enabled client holding `token`, if it has `scope`; refusals are audited
+    async fn authorize(
+        &self,
+        token: Option<String>,
+        scope: ApiScope,
+        action: &str,
+        params: &Value,
+    ) -> Result<ApiClient, ApiError> {
+        let client = match token {
+            Some(token) => self
+                .pool
+                .run(move |conn| clients::authenticate(conn, &token).map_err(|e| e.to_string()))
+                .await
+                .unwrap_or_else(|e: String| {
+                    tracing::warn!("Failed to authenticate remote API client: {}", e);
+                    None
+                }),
+            None => None,
+        };
+        let Some(client) = client else {
+            self.audit(
+                AuditEntry::new(
+                    Actor::api_client("unknown"),
+                    AuditCategory::RemoteApi,
+                    action,
+                )
+                .params(params)
+                .outcome(AuditOutcome::Denied)
+                .detail("Invalid or missing token"),
+            )
+            .await;
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "Invalid or missing token",
+            ));
+        };
+        if !client.allows(scope) {
+            let message = format!("Client lacks the '{}' scope", scope.as_str());
+            self.audit(
+                AuditEntry::new(
+                    Actor::api_client(client.id.clone()),
+                    AuditCategory::RemoteApi,
+                    action,
+                )
+                .params(params)
+                .outcome(AuditOutcome::Denied)
+                .detail(message.clone()),
+            )
+            .await;
+            return Err(ApiError::new(StatusCode::FORBIDDEN, message));
+        }
+        Ok(client)
+    }
+
+    /// Authorize, run and audit one call answered with JSON
+    async fn call<T, F, Fut>(
+        &self,
+        token: Option<String>,
+        scope: ApiScope,
+        action: &str,
+        target: Option<String>,
+        params: Value,
+        run: F,
+    ) -> Response
+    where
+        T: Serialize,
+        F: FnOnce(ApiClient, Value) -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        self.respond(
+            token,
+            scope,
+            action,
+            target,
+            params,
+            |client, params| async move { run(client, params).await.map(Json) },
+        )
+        .await
+    }
+
+    /// Authorize, run and audit one call
+    async fn respond<T, F, Fut>(
+        &self,
+        token: Option<String>,
+        scope: ApiScope,
+        action: &str,
+        target: Option<String>,
+        params: Value,
+        run: F,
+    ) -> Response
+    where
+        T: IntoResponse,
+        F: FnOnce(ApiClient, Value) -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let client = match self.authorize(token, scope, action, &params).await {
+            Ok(client) => client,
+            Err(e) => return e.into_response(),
+        };
+        let mut entry = AuditEntry::new(
+            Actor::api_client(client.id.clone()),
+            AuditCategory::RemoteApi,
+            action,
+        )
+        .params(&params);
+        entry.target = target;
+
+        let result = run(client, params).await;
+        // Responses are not `Sync`, so settle the entry before awaiting
+        let entry = entry.result(&result.as_ref().map_err(|e| &e.message));
+        self.audit(entry).await;
+        match result {
+            Ok(response) => response.into_response(),
+            Err(e) => e.into_response(),
+        }
+    }
+
+    /// The permission profile a client acts under
+    async fn profile(&self, profile_id: String) -> Result<PermissionProfile, ApiError> {
+        let lookup_id = profile_id.clone();
+        self.pool
+            .run(move |conn| profiles::get_profile(conn, &lookup_id).map_err(|e| e.to_string()))
+            .await
+            .map_err(ApiError::failed)?
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    format!("Unknown permission profile '{}'", profile_id),
+                )
+            })
+    }
+
+    async fn audit(&self, entry: AuditEntry) {
+        let appended = self
+            .pool
+            .run(move |conn| audit::append(conn, &entry).map_err(|e| e.to_string()))
+            .await;
+        if let Err(e) = appended {
+            tracing::warn!("Failed to audit remote API call: {}", e);
+        }
+    }
+}
+
+/// Liveness probe; needs no token and is not audited
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))
+}
+
+async fn app_status(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    let token = bearer(&headers);
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Status,
+            "api_status",
+            None,
+            Value::Null,
+            |_, _| async {
+                Ok(json!({
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "agent_initialized": crate::commands::agi::agi_initialized(),
+                    "readiness": crate::readiness::snapshot(),
+                }))
+            },
+        )
+        .await
+}
+
+async fn list_goals(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    let token = bearer(&headers);
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Status,
+            "api_list_goals",
+            None,
+            Value::Null,
+            |_, _| async {
+                crate::commands::agi::agi_list_goals()
+                    .await
+                    .map_err(ApiError::failed)
+            },
+        )
+        .await
+}
+
+/// Body as for `agi_submit_goal`; the goal runs under the client's permission profile
+async fn submit_goal(State(state): State<ApiState>, headers: HeaderMap, body: Bytes) -> Response {
+    let token = bearer(&headers);
+    let params = match body_json(&body) {
+        Ok(params) => params,
+        Err(e) => return e.into_response(),
+    };
+    let server = Arc::clone(&state.server);
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Goals,
+            "api_submit_goal",
+            None,
+            params,
+            |client, params| async move {
+                let mut request: crate::commands::agi::SubmitGoalRequest =
+                    serde_json::from_value(params).map_err(ApiError::bad_request)?;
+                request.permission_profile = Some(client.profile_id);
+                let recorder = server
+                    .app()?
+                    .try_state::<crate::agent::runtime::RunRecorder>()
+                    .ok_or_else(|| ApiError::unavailable("The run recorder"))?;
+                crate::commands::agi::submit_goal(&server.pool, &recorder, request)
+                    .await
+                    .map_err(ApiError::failed)
+            },
+        )
+        .await
+}
+
+async fn goal_status(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(goal_id): Path<String>,
+) -> Response {
+    let token = bearer(&headers);
+    let target = Some(goal_id.clone());
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Status,
+            "api_goal_status",
+            target,
+            Value::Null,
+            |_, _| async {
+                crate::commands::agi::agi_get_goal_status(goal_id)
+                    .await
+                    .map_err(ApiError::failed)
+            },
+        )
+        .await
+}
+
+/// Why `profile` may not run `workflow_id`. Tool and script steps, including
+/// those of the workflows it calls, run without the per-call checks goals
+/// get, so the profile has to allow each of them outright
+fn workflow_refusal(
+    profile: &PermissionProfile,
+    workflow_id: &str,
+    load: impl Fn(&str) -> Result<WorkflowDefinition, String>,
+) -> Result<Option<String>, String> {
+    let mut pending = vec![workflow_id.to_string()];
+    let mut seen = HashSet::new();
+    while let Some(id) = pending.pop() {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        for node in load(&id)?.nodes {
+            let (label, category) = match node {
+                WorkflowNode::ToolNode { data, .. } => {
+                    let category = ToolCategory::of(&data.tool_name);
+                    (data.label, category)
+                }
+                WorkflowNode::ScriptNode { data, .. } => (data.label, Some(ToolCategory::Shell)),
+                WorkflowNode::SubWorkflowNode { data, .. } => {
+                    pending.push(data.workflow_id);
+                    continue;
+                }
+                _ => continue,
+            };
+            if let Some(category) =
+                category.filter(|category| profile.decision(*category) != ToolDecision::Allow)
+            {
+                return Ok(Some(format!(
+                    "Permission profile '{}' does not allow {} without asking, which step '{}' needs",
+                    profile.name,
+                    category.label(),
+                    label
+                )));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Body: `{ "inputs": {...}, "simulate": false }`, both optional. Only
+/// clients whose profile allows every step may run the workflow; any may
+/// simulate it
+async fn run_workflow(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(workflow_id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let token = bearer(&headers);
+    let params = match body_json(&body) {
+        Ok(params) => params,
+        Err(e) => return e.into_response(),
+    };
+    let server = Arc::clone(&state.server);
+    let target = Some(workflow_id.clone());
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Workflows,
+            "api_run_workflow",
+            target,
+            params,
+            |client, params| async move {
+                let request: WorkflowRunRequest = if params.is_null() {
+                    WorkflowRunRequest::default()
+                } else {
+                    serde_json::from_value(params).map_err(ApiError::bad_request)?
+                };
+                let workflows = server
+                    .app()?
+                    .try_state::<crate::commands::WorkflowEngineState>()
+                    .ok_or_else(|| ApiError::unavailable("The workflow engine"))?;
+                if !request.simulate {
+                    let profile = server.profile(client.profile_id).await?;
+                    let refusal = workflow_refusal(&profile, &workflow_id, |id| {
+                        workflows.engine.get_workflow(id)
+                    })
+                    .map_err(ApiError::failed)?;
+                    if let Some(reason) = refusal {
+                        return Err(ApiError::new(StatusCode::FORBIDDEN, reason));
+                    }
+                }
+                let run = crate::commands::orchestration::run_workflow(
+                    &workflows,
+                    workflow_id,
+                    request.inputs,
+                    request.simulate,
+                )
+                .await
+                .map_err(ApiError::failed)?;
+                Ok(match run {
+                    crate::commands::WorkflowRun::Started(execution_id) => {
+                        json!({ "execution_id": execution_id })
+                    }
+                    crate::commands::WorkflowRun::Simulated(simulation) => {
+                        json!({ "simulation": simulation })
+                    }
+                })
+            },
+        )
+        .await
+}
+
+async fn workflow_status(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(execution_id): Path<String>,
+) -> Response {
+    let token = bearer(&headers);
+    let server = Arc::clone(&state.server);
+    let target = Some(execution_id.clone());
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Status,
+            "api_workflow_status",
+            target,
+            Value::Null,
+            |_, _| async move {
+                let workflows = server
+                    .app()?
+                    .try_state::<crate::commands::WorkflowEngineState>()
+                    .ok_or_else(|| ApiError::unavailable("The workflow engine"))?;
+                workflows
+                    .engine
+                    .get_execution_status(&execution_id)
+                    .map_err(ApiError::failed)
+            },
+        )
+        .await
+}
+
+/// `?days=7`: ROI totals of the client's account and the daily history
+/// behind them
+async fn metrics(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(metrics_query): Query<MetricsQuery>,
+) -> Response {
+    let token = bearer(&headers);
+    let server = Arc::clone(&state.server);
+    let params = json!({ "days": metrics_query.days });
+    state
+        .server
+        .call(
+            token,
+            ApiScope::Metrics,
+            "api_metrics",
+            None,
+            params,
+            |client, _| async move {
+                let user_id = client.user_id.ok_or_else(|| {
+                    ApiError::new(StatusCode::FORBIDDEN, "Client is not linked to an account")
+                })?;
+                let collector = server
+                    .app()?
+                    .try_state::<crate::commands::MetricsCollectorState>()
+                    .ok_or_else(|| ApiError::unavailable("Metrics"))?;
+                let stats = collector
+                    .0
+                    .get_realtime_stats(&user_id)
+                    .await
+                    .map_err(ApiError::failed)?;
+                let history = collector
+                    .0
+                    .get_metrics_history(&user_id, metrics_query.days.unwrap_or(7).clamp(1, 365))
+                    .await
+                    .map_err(ApiError::failed)?;
+                Ok(json!({ "stats": stats, "history": history }))
+            },
+        )
+        .await
+}
+
+/// WebSocket of goal events, each `{ "event": name, "payload": ... }`
+async fn events(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(query): Query<AuthQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    // Browsers cannot set headers on a WebSocket handshake, so this is the
+    // one route that also takes the token from the query string
+    let token = bearer(&headers).or(query.token);
+    let server = Arc::clone(&state.server);
+    let stopped = state.stopped.clone();
+    state
+        .server
+        .respond(
+            token,
+            ApiScope::Status,
+            "api_events",
+            None,
+            Value::Null,
+            |_, _| async move {
+                let app = server.app()?.clone();
+                Ok(upgrade.on_upgrade(move |socket| stream_events(socket, app, stopped)))
+            },
+        )
+        .await
+}
+
+async fn stream_events(mut socket: WebSocket, app: AppHandle, stopped: CancellationToken) {
+    let (sender, mut events) = mpsc::unbounded_channel::<Value>();
+    let listeners: Vec<_> = STREAMED_EVENTS
+        .iter()
+        .map(|name| {
+            let sender = sender.clone();
+            app.listen_any(*name, move |event| {
+                let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+                let _ = sender.send(json!({ "event": name, "payload": payload }));
+            })
+        })
+        .collect();
+    drop(sender);
+
+    loop {
+        tokio::select! {
+            _ = stopped.cancelled() => break,
+            Some(event) = events.recv() => {
+                if socket.send(Message::Text(event.to_string().into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    for id in listeners {
+        app.unlisten(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn serve(server: &Arc<RemoteApiServer>) -> String {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let router = server.router(CancellationToken::new());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        format!("http://{}", address)
+    }
+
+    fn audit_outcomes(pool: &Pool) -> Vec<(String, String, Option<String>)> {
+        let conn = pool.get().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT action, outcome, actor_id FROM audit ORDER BY id")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calls_need_a_token_with_the_scope_and_are_audited() {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let server = Arc::new(RemoteApiServer::new(pool.clone(), None));
+        let created = server
+            .create_client(ApiClientInput {
+                name: "CI".to_string(),
+                profile_id: crate::permissions::profiles::READ_ONLY_ANALYST.to_string(),
+                user_id: None,
+                scopes: vec![ApiScope::Workflows],
+                enabled: true,
+            })
+            .await
+            .unwrap();
+        let base = serve(&server).await;
+        let http = reqwest::Client::new();
+
+        let health = http
+            .get(format!("{}/v1/health", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+
+        let anonymous = http.get(format!("{}/v1/goals", base)).send().await.unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+        let out_of_scope = http
+            .post(format!("{}/v1/goals", base))
+            .bearer_auth(&created.token)
+            .json(&json!({ "description": "Pull the weekly report" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(out_of_scope.status(), StatusCode::FORBIDDEN);
+
+        let run = http
+            .post(format!("{}/v1/workflows/wf-1/run", base))
+            .bearer_auth(&created.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(run.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Only the WebSocket takes the token from the query string
+        let query_token = http
+            .post(format!(
+                "{}/v1/workflows/wf-1/run?token={}",
+                base, created.token
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(query_token.status(), StatusCode::UNAUTHORIZED);
+        let events = http
+            .get(format!("{}/v1/events?token={}", base, created.token))
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(events.status(), StatusCode::FORBIDDEN);
+
+        let client_actor = Some(format!("api_client:{}", created.client.id));
+        assert_eq!(
+            audit_outcomes(&pool),
+            vec![
+                (
+                    "api_list_goals".to_string(),
+                    "denied".to_string(),
+                    Some("api_client:unknown".to_string())
+                ),
+                (
+                    "api_submit_goal".to_string(),
+                    "denied".to_string(),
+                    client_actor.clone()
+                ),
+                (
+                    "api_run_workflow".to_string(),
+                    "failure".to_string(),
+                    client_actor.clone()
+                ),
+                (
+                    "api_run_workflow".to_string(),
+                    "denied".to_string(),
+                    Some("api_client:unknown".to_string())
+                ),
+                ("api_events".to_string(), "denied".to_string(), client_actor),
+            ]
+        );
+    }
+
+    fn workflow(id: &str, nodes: Value) -> WorkflowDefinition {
+        serde_json::from_value(json!({
+            "id": id,
+            "user_id": "user",
+            "name": id,
+            "description": null,
+            "nodes": nodes,
+            "edges": [],
+            "triggers": [],
+            "metadata": {},
+            "created_at": 0,
+            "updated_at": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_workflows_need_a_profile_allowing_every_step() {
+        let position = json!({ "x": 0.0, "y": 0.0 });
+        let report = workflow(
+            "report",
+            json!([
+                { "type": "tool", "id": "n1", "position": position, "data": {
+                    "label": "Read figures", "tool_name": "file_read",
+                    "tool_input": {}, "timeout_seconds": null } },
+                { "type": "sub_workflow", "id": "n2", "position": position, "data": {
+                    "label": "Notify", "workflow_id": "notify" } }
+            ]),
+        );
+        let notify = workflow(
+            "notify",
+            json!([
+                { "type": "tool", "id": "n1", "position": position, "data": {
+                    "label": "Email team", "tool_name": "email_send",
+                    "tool_input": {}, "timeout_seconds": null } },
+                { "type": "sub_workflow", "id": "n2", "position": position, "data": {
+                    "label": "Again", "workflow_id": "report" } }
+            ]),
+        );
+        let load = |id: &str| match id {
+            "report" => Ok(report.clone()),
+            "notify" => Ok(notify.clone()),
+            _ => Err(format!("Workflow {} not found", id)),
+        };
+
+        let developer = profiles::builtin_profile(profiles::DEVELOPER).unwrap();
+        let refusal = workflow_refusal(&developer, "report", load)
+            .unwrap()
+            .unwrap();
+        assert!(refusal.contains("'Email team'"), "{}", refusal);
+
+        let automation = profiles::builtin_profile(profiles::FULL_AUTOMATION).unwrap();
+        assert_eq!(workflow_refusal(&automation, "report", load).unwrap(), None);
+        assert!(workflow_refusal(&automation, "missing", load).is_err());
+    }
+
+    #[test]
+    fn test_config_defaults_to_disabled_on_localhost() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        assert_eq!(load_config(&conn).unwrap(), RemoteApiConfig::default());
+        assert_eq!(RemoteApiConfig::default().warning(), None);
+
+        let config = RemoteApiConfig {
+            enabled: true,
+            port: 9911,
+            allow_remote: true,
+        };
+        save_config(&conn, &config).unwrap();
+        assert_eq!(load_config(&conn).unwrap(), config);
+        assert!(config.warning().is_some());
+        assert!(save_config(&conn, &RemoteApiConfig { port: 0, ..config }).is_err());
+    }
+}
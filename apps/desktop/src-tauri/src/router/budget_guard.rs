@@ -0,0 +1,568 @@
+//! Spend tracking and enforcement for routed LLM calls.
+//!
+//! The guard keeps a per-day ledger of cost by provider and model. Before a
+//! request is sent it checks the daily and monthly caps: past a soft cap the
+//! request is downgraded to the provider's cheapest model, past a hard cap it
+//! is rejected. Crossing 80%, 95% and 100% of a cap emits `budget://warning`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::notifications::{self, Notification, NotificationCategory};
+use crate::router::cost_calculator::CostCalculator;
+use crate::router::Provider;
+
+const LIMITS_SETTING: &str = "billing.budget_limits";
+/// Written by `chat_set_monthly_budget`; enforced as the monthly hard cap
+const MONTHLY_BUDGET_SETTING: &str = "billing.monthly_budget";
+const WARNING_THRESHOLDS: [u8; 3] = [80, 95, 100];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetLimits {
+    pub daily_soft_cap: Option<f64>,
+    pub daily_hard_cap: Option<f64>,
+    pub monthly_soft_cap: Option<f64>,
+    pub monthly_hard_cap: Option<f64>,
+    /// Past a soft cap, switch to the provider's cheapest model
+    #[serde(default = "default_true")]
+    pub downgrade_on_soft_cap: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for BudgetLimits {
+    fn default() -> Self {
+        Self {
+            daily_soft_cap: None,
+            daily_hard_cap: None,
+            monthly_soft_cap: None,
+            monthly_hard_cap: None,
+            downgrade_on_soft_cap: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Monthly => "monthly",
+        }
+    }
+}
+
+/// What the router should do with a request
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetDecision {
+    Allow,
+    Downgrade { model: String, period: BudgetPeriod },
+    Reject(BudgetExceeded),
+}
+
+/// Returned when a hard cap blocks a request; never worth failing over
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    pub period: BudgetPeriod,
+    pub spent: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} LLM budget of ${:.2} reached (${:.2} spent)",
+            if self.period == BudgetPeriod::Daily {
+                "Daily"
+            } else {
+                "Monthly"
+            },
+            self.limit,
+            self.spent
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendEntry {
+    pub day: String,
+    pub provider: String,
+    pub model: String,
+    pub cost: f64,
+    pub requests: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub limits: BudgetLimits,
+    pub today_spent: f64,
+    pub month_spent: f64,
+    pub today_by_model: Vec<SpendEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BudgetWarning {
+    period: BudgetPeriod,
+    threshold: u8,
+    spent: f64,
+    limit: f64,
+    percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SpendKey {
+    day: NaiveDate,
+    provider: Provider,
+    model: String,
+}
+
+#[derive(Default)]
+struct LedgerState {
+    limits: BudgetLimits,
+    spend: HashMap<SpendKey, (f64, u32)>,
+    /// Highest threshold already announced, keyed by period and period start
+    warned: HashMap<(BudgetPeriod, NaiveDate), u8>,
+}
+
+/// Shared budget tracker consulted by every `LLMRouter`
+#[derive(Default)]
+pub struct BudgetGuard {
+    state: Mutex<LedgerState>,
+    db: Mutex<Option<Arc<Mutex<Connection>>>>,
+    app_handle: Mutex<Option<AppHandle>>,
+    cost_calculator: CostCalculator,
+}
+
+impl BudgetGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load limits and this month's ledger, and start persisting spend
+    pub fn attach(&self, db: Arc<Mutex<Connection>>, app_handle: Option<AppHandle>) -> Result<()> {
+        {
+            let conn = db
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+            let limits = load_limits(&conn)?;
+            let month_start = month_start(today());
+
+            let mut stmt = conn.prepare(
+                "SELECT day, provider, model, cost, requests
+                 FROM llm_spend_ledger
+                 WHERE day >= ?1",
+            )?;
+            let rows = stmt
+                .query_map(params![month_start.to_string()], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, f64>(3)?,
+                        row.get::<_, u32>(4)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let mut state = self.lock_state();
+            state.limits = limits;
+            state.spend.clear();
+            for (day, provider, model, cost, requests) in rows {
+                let (Ok(day), Some(provider)) = (day.parse(), Provider::from_string(&provider))
+                else {
+                    continue;
+                };
+                state.spend.insert(
+                    SpendKey {
+                        day,
+                        provider,
+                        model,
+                    },
+                    (cost, requests),
+                );
+            }
+        }
+
+        *self.db.lock().unwrap_or_else(|e| e.into_inner()) = Some(db);
+        *self.app_handle.lock().unwrap_or_else(|e| e.into_inner()) = app_handle;
+        Ok(())
+    }
+
+    /// Re-read limits after the settings changed
+    pub fn reload_limits(&self) -> Result<()> {
+        let Some(db) = self.db() else {
+            return Ok(());
+        };
+        let conn = db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+        let limits = load_limits(&conn)?;
+        self.lock_state().limits = limits;
+        Ok(())
+    }
+
+    /// Persist new limits; the monthly hard cap shares the monthly budget setting
+    pub fn set_limits(&self, limits: BudgetLimits) -> Result<()> {
+        if let Some(db) = self.db() {
+            let conn = db
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+            crate::db::repository::set_setting(
+                &conn,
+                LIMITS_SETTING.to_string(),
+                serde_json::to_string(&limits)?,
+                false,
+            )?;
+            match limits.monthly_hard_cap {
+                Some(value) => crate::db::repository::set_setting(
+                    &conn,
+                    MONTHLY_BUDGET_SETTING.to_string(),
+                    format!("{:.2}", value),
+                    false,
+                )?,
+                None => crate::db::repository::delete_setting(&conn, MONTHLY_BUDGET_SETTING)?,
+            }
+        }
+        self.lock_state().limits = limits;
+        Ok(())
+    }
+
+    /// Decide whether a request to `provider`/`model` may go ahead
+    pub fn check(&self, provider: Provider, model: &str) -> BudgetDecision {
+        let state = self.lock_state();
+        let today = today();
+        let periods = [
+            (
+                BudgetPeriod::Daily,
+                state.limits.daily_soft_cap,
+                state.limits.daily_hard_cap,
+                spent_since(&state, today),
+            ),
+            (
+                BudgetPeriod::Monthly,
+                state.limits.monthly_soft_cap,
+                state.limits.monthly_hard_cap,
+                spent_since(&state, month_start(today)),
+            ),
+        ];
+
+        for (period, _, hard, spent) in periods {
+            if let Some(limit) = hard {
+                if spent >= limit {
+                    return BudgetDecision::Reject(BudgetExceeded {
+                        period,
+                        spent,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        if state.limits.downgrade_on_soft_cap {
+            for (period, soft, _, spent) in periods {
+                if !soft.is_some_and(|limit| spent >= limit) {
+                    continue;
+                }
+                if let Some(cheaper) = self.cheaper_model(provider, model) {
+                    return BudgetDecision::Downgrade {
+                        model: cheaper.to_string(),
+                        period,
+                    };
+                }
+            }
+        }
+
+        BudgetDecision::Allow
+    }
+
+    /// Add the cost of a completed request and announce newly crossed thresholds
+    pub fn record(&self, provider: Provider, model: &str, cost: f64) {
+        if cost <= 0.0 {
+            return;
+        }
+
+        let day = today();
+        let warnings = {
+            let mut state = self.lock_state();
+            let entry = state
+                .spend
+                .entry(SpendKey {
+                    day,
+                    provider,
+                    model: model.to_string(),
+                })
+                .or_insert((0.0, 0));
+            entry.0 += cost;
+            entry.1 += 1;
+
+            // Keep only the current month in memory
+            let month = month_start(day);
+            state.spend.retain(|key, _| key.day >= month);
+            state.warned.retain(|(_, start), _| *start >= month);
+
+            collect_warnings(&mut state, day)
+        };
+
+        if let Some(db) = self.db() {
+            if let Ok(conn) = db.lock() {
+                if let Err(e) = conn.execute(
+                    "INSERT INTO llm_spend_ledger (day, provider, model, cost, requests)
+                     VALUES (?1, ?2, ?3, ?4, 1)
+                     ON CONFLICT(day, provider, model)
+                     DO UPDATE SET cost = cost + excluded.cost, requests = requests + 1",
+                    params![day.to_string(), provider.as_string(), model, cost],
+                ) {
+                    tracing::warn!("Failed to record LLM spend: {}", e);
+                }
+            }
+        }
+
+        if warnings.is_empty() {
+            return;
+        }
+        let app_handle = self
+            .app_handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        for warning in warnings {
+            tracing::warn!(
+                "LLM {} spend at {}% of ${:.2} cap",
+                warning.period.as_str(),
+                warning.threshold,
+                warning.limit
+            );
+            if let Some(app) = &app_handle {
+                let _ = app.emit("budget://warning", &warning);
+                notifications::notify(
+                    app,
+                    Notification::new(
+                        NotificationCategory::BudgetWarning,
+                        format!(
+                            "LLM {} budget at {}%",
+                            warning.period.as_str(),
+                            warning.threshold
+                        ),
+                        format!(
+                            "${:.2} of your ${:.2} {} cap is spent.",
+                            warning.spent,
+                            warning.limit,
+                            warning.period.as_str()
+                        ),
+                    ),
+                );
+            }
+        }
+    }
+
+    pub fn status(&self) -> BudgetStatus {
+        let state = self.lock_state();
+        let today = today();
+        let mut today_by_model: Vec<SpendEntry> = state
+            .spend
+            .iter()
+            .filter(|(key, _)| key.day == today)
+            .map(|(key, (cost, requests))| SpendEntry {
+                day: key.day.to_string(),
+                provider: key.provider.as_string().to_string(),
+                model: key.model.clone(),
+                cost: *cost,
+                requests: *requests,
+            })
+            .collect();
+        today_by_model.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+
+        BudgetStatus {
+            limits: state.limits.clone(),
+            today_spent: spent_since(&state, today),
+            month_spent: spent_since(&state, month_start(today)),
+            today_by_model,
+        }
+    }
+
+    fn cheaper_model(&self, provider: Provider, model: &str) -> Option<&'static str> {
+        let cheapest = self.cost_calculator.cheapest_model(provider)?;
+        let unit = |m: &str| {
+            self.cost_calculator
+                .calculate(provider, m, 1_000_000, 1_000_000)
+        };
+        (cheapest != model && unit(cheapest) < unit(model)).then_some(cheapest)
+    }
+
+    fn db(&self) -> Option<Arc<Mutex<Connection>>> {
+        self.db.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, LedgerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+fn month_start(day: NaiveDate) -> NaiveDate {
+    day.with_day(1).unwrap_or(day)
+}
+
+fn spent_since(state: &LedgerState, since: NaiveDate) -> f64 {
+    state
+        .spend
+        .iter()
+        .filter(|(key, _)| key.day >= since)
+        .map(|(_, (cost, _))| cost)
+        .sum()
+}
+
+fn collect_warnings(state: &mut LedgerState, today: NaiveDate) -> Vec<BudgetWarning> {
+    let periods = [
+        (
+            BudgetPeriod::Daily,
+            today,
+            state.limits.daily_hard_cap.or(state.limits.daily_soft_cap),
+        ),
+        (
+            BudgetPeriod::Monthly,
+            month_start(today),
+            state
+                .limits
+                .monthly_hard_cap
+                .or(state.limits.monthly_soft_cap),
+        ),
+    ];
+
+    let mut warnings = Vec::new();
+    for (period, start, limit) in periods {
+        let Some(limit) = limit.filter(|l| *l > 0.0) else {
+            continue;
+        };
+        let spent = spent_since(state, start);
+        let percent = spent / limit * 100.0;
+        let already = state.warned.get(&(period, start)).copied().unwrap_or(0);
+        let Some(threshold) = WARNING_THRESHOLDS
+            .iter()
+            .rev()
+            .copied()
+            .find(|t| percent >= *t as f64 && *t > already)
+        else {
+            continue;
+        };
+        state.warned.insert((period, start), threshold);
+        warnings.push(BudgetWarning {
+            period,
+            threshold,
+            spent,
+            limit,
+            percent,
+        });
+    }
+    warnings
+}
+
+fn load_limits(conn: &Connection) -> Result<BudgetLimits> {
+    let mut limits = crate::db::repository::get_setting(conn, LIMITS_SETTING)
+        .ok()
+        .and_then(|setting| serde_json::from_str::<BudgetLimits>(&setting.value).ok())
+        .unwrap_or_default();
+    limits.monthly_hard_cap = crate::db::repository::get_setting(conn, MONTHLY_BUDGET_SETTING)
+        .ok()
+        .and_then(|setting| setting.value.parse::<f64>().ok());
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard_with(limits: BudgetLimits) -> BudgetGuard {
+        let guard = BudgetGuard::new();
+        guard.lock_state().limits = limits;
+        guard
+    }
+
+    #[test]
+    fn test_hard_cap_rejects() {
+        let guard = guard_with(BudgetLimits {
+            daily_hard_cap: Some(1.0),
+            ..Default::default()
+        });
+        assert_eq!(
+            guard.check(Provider::OpenAI, "gpt-4o"),
+            BudgetDecision::Allow
+        );
+
+        guard.record(Provider::OpenAI, "gpt-4o", 1.25);
+        match guard.check(Provider::OpenAI, "gpt-4o") {
+            BudgetDecision::Reject(exceeded) => {
+                assert_eq!(exceeded.period, BudgetPeriod::Daily);
+                assert!(exceeded.spent >= 1.0);
+            }
+            other => panic!("expected rejection, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_soft_cap_downgrades_to_cheaper_model() {
+        let guard = guard_with(BudgetLimits {
+            monthly_soft_cap: Some(0.5),
+            ..Default::default()
+        });
+        guard.record(Provider::OpenAI, "gpt-4o", 0.75);
+
+        match guard.check(Provider::OpenAI, "gpt-4o") {
+            BudgetDecision::Downgrade { model, period } => {
+                assert_ne!(model, "gpt-4o");
+                assert_eq!(period, BudgetPeriod::Monthly);
+            }
+            other => panic!("expected downgrade, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_warning_thresholds_fire_once() {
+        let mut state = LedgerState {
+            limits: BudgetLimits {
+                daily_hard_cap: Some(10.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let day = today();
+        state.spend.insert(
+            SpendKey {
+                day,
+                provider: Provider::Anthropic,
+                model: "claude".into(),
+            },
+            (9.6, 3),
+        );
+
+        let warnings = collect_warnings(&mut state, day);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].threshold, 95);
+        assert!(collect_warnings(&mut state, day).is_empty());
+    }
+}
@@ -8,6 +8,7 @@ use crate::db::models::CacheEntry;
 use crate::router::{ChatMessage, Provider};
 
 /// Manages caching of LLM responses in SQLite.
+#[derive(Clone)]
 pub struct CacheManager {
     ttl: Duration,
     max_entries: usize,
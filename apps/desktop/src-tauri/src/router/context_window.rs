@@ -0,0 +1,496 @@
+//! Keeps a conversation inside the model's context window. Older turns that
+//! no longer fit are folded into a rolling summary stored per conversation;
+//! requests carry the summary followed by the turns after it.
+
+use crate::db::models::{Message, MessageRole};
+use crate::db::{DbError, Pool};
+use crate::router::llm_router::LLMRouter;
+use crate::router::token_counter::TokenCounter;
+use crate::router::ChatMessage;
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Characters of a single message included in a summarization prompt
+const MAX_SUMMARIZED_CHARS: usize = 2_000;
+/// Characters of the summary kept when the model is unavailable
+const MAX_FALLBACK_CHARS: usize = 6_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextWindowConfig {
+    /// Share of the available window at which older turns are summarized
+    pub trigger_ratio: f32,
+    /// Tokens left free for the reply
+    pub reserved_output_tokens: u32,
+    /// Most recent messages that are never summarized
+    pub keep_recent_messages: usize,
+}
+
+impl Default for ContextWindowConfig {
+    fn default() -> Self {
+        Self {
+            trigger_ratio: 0.75,
+            reserved_output_tokens: 4_096,
+            keep_recent_messages: 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub conversation_id: i64,
+    pub summary: String,
+    /// Id of the last message the summary covers
+    pub covered_through: i64,
+    pub covered_messages: usize,
+    pub tokens: u32,
+    pub updated_at: String,
+}
+
+/// Token usage of a conversation against its model's window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextUsage {
+    pub conversation_id: i64,
+    pub model: String,
+    pub context_limit: u32,
+    /// Tokens of the summary plus the messages after it
+    pub used_tokens: u32,
+    pub summary_tokens: u32,
+    pub summarized_messages: usize,
+    pub recent_messages: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContextWindowManager {
+    pub config: ContextWindowConfig,
+}
+
+impl ContextWindowManager {
+    pub fn new(config: ContextWindowConfig) -> Self {
+        Self { config }
+    }
+
+    /// Context window of `model` in tokens, by model family. Unknown models
+    /// get a conservative default.
+    pub fn context_limit(model: &str) -> u32 {
+        let model = model.to_ascii_lowercase();
+        if model.contains("gemini") {
+            1_000_000
+        } else if model.contains("claude") {
+            200_000
+        } else if model.contains("gpt-4.1") {
+            1_000_000
+        } else if model.contains("gpt-3.5") {
+            16_000
+        } else if model.contains("gpt-4-") && !model.contains("turbo") {
+            8_000
+        } else if model.starts_with("gpt")
+            || model.starts_with("o1")
+            || model.starts_with("o3")
+            || model.starts_with("o4")
+            || model.contains("grok")
+            || model.contains("deepseek")
+            || model.contains("mistral-large")
+            || model.contains("moonshot")
+            || model.contains("kimi")
+            || model.contains("qwen")
+        {
+            128_000
+        } else {
+            32_000
+        }
+    }
+
+    pub fn load_summary(
+        conn: &Connection,
+        conversation_id: i64,
+    ) -> rusqlite::Result<Option<ConversationSummary>> {
+        conn.query_row(
+            "SELECT conversation_id, summary, covered_through, covered_messages, tokens, updated_at
+             FROM conversation_summaries WHERE conversation_id = ?1",
+            params![conversation_id],
+            |row| {
+                Ok(ConversationSummary {
+                    conversation_id: row.get(0)?,
+                    summary: row.get(1)?,
+                    covered_through: row.get(2)?,
+                    covered_messages: row.get::<_, i64>(3)? as usize,
+                    tokens: row.get::<_, i64>(4)? as u32,
+                    updated_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    pub fn save_summary(conn: &Connection, summary: &ConversationSummary) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO conversation_summaries
+                (conversation_id, summary, covered_through, covered_messages, tokens, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(conversation_id) DO UPDATE SET
+                summary = excluded.summary,
+                covered_through = excluded.covered_through,
+                covered_messages = excluded.covered_messages,
+                tokens = excluded.tokens,
+                updated_at = excluded.updated_at",
+            params![
+                summary.conversation_id,
+                summary.summary,
+                summary.covered_through,
+                summary.covered_messages as i64,
+                summary.tokens as i64,
+                summary.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Drop a conversation's summary, e.g. after its history was rewritten
+    pub fn clear_summary(conn: &Connection, conversation_id: i64) -> rusqlite::Result<()> {
+        conn.execute(
+            "DELETE FROM conversation_summaries WHERE conversation_id = ?1",
+            params![conversation_id],
+        )?;
+        Ok(())
+    }
+
+    /// Index of the first message in `history` the summary does not cover.
+    /// A summary whose last message is gone no longer matches the history
+    /// and covers nothing.
+    fn uncovered_from(history: &[Message], summary: Option<&ConversationSummary>) -> Option<usize> {
+        match summary {
+            None => Some(0),
+            Some(summary) => history
+                .iter()
+                .position(|m| m.id == summary.covered_through)
+                .map(|i| i + 1),
+        }
+    }
+
+    /// Tokens left for history once the reply and `fixed_tokens` (system
+    /// prompt, retrieved context, tool definitions) are accounted for
+    fn history_budget(&self, model: &str, fixed_tokens: u32) -> u32 {
+        Self::context_limit(model)
+            .saturating_sub(self.config.reserved_output_tokens)
+            .saturating_sub(fixed_tokens)
+            .max(1_000)
+    }
+
+    pub fn usage(
+        conn: &Connection,
+        conversation_id: i64,
+        history: &[Message],
+        model: &str,
+    ) -> rusqlite::Result<ContextUsage> {
+        let summary = Self::load_summary(conn, conversation_id)?;
+        let start = Self::uncovered_from(history, summary.as_ref());
+        let summary = summary.filter(|_| start.is_some());
+        let recent = &history[start.unwrap_or(0)..];
+        let summary_tokens = summary.as_ref().map(|s| s.tokens).unwrap_or(0);
+        Ok(ContextUsage {
+            conversation_id,
+            model: model.to_string(),
+            context_limit: Self::context_limit(model),
+            used_tokens: summary_tokens + message_tokens(recent),
+            summary_tokens,
+            summarized_messages: summary.map(|s| s.covered_messages).unwrap_or(0),
+            recent_messages: recent.len(),
+        })
+    }
+
+    /// Messages to send for `history`: the rolling summary, if any, and the
+    /// turns after it. When they would pass the trigger point the older
+    /// turns are summarized first (with `router`, falling back to an
+    /// extract when the model is unavailable) and the summary is stored.
+    pub async fn window(
+        &self,
+        pool: &Pool,
+        router: &tokio::sync::Mutex<LLMRouter>,
+        conversation_id: i64,
+        history: &[Message],
+        model: &str,
+        fixed_tokens: u32,
+    ) -> Result<Vec<ChatMessage>> {
+        let summary = pool
+            .run(move |conn| Ok::<_, DbError>(Self::load_summary(conn, conversation_id)?))
+            .await?;
+        let (mut summary, start) = match Self::uncovered_from(history, summary.as_ref()) {
+            Some(start) => (summary, start),
+            None => (None, 0),
+        };
+        let uncovered = &history[start..];
+
+        let budget = self.history_budget(model, fixed_tokens);
+        let summary_tokens = summary.as_ref().map(|s| s.tokens).unwrap_or(0);
+        let trigger = (budget as f32 * self.config.trigger_ratio) as u32;
+        if summary_tokens + message_tokens(uncovered) <= trigger {
+            return Ok(assemble(summary.as_ref(), uncovered));
+        }
+
+        let cut = self.split_point(uncovered, budget / 2);
+        if cut > 0 {
+            let older = &uncovered[..cut];
+            let previous = summary.as_ref().map(|s| s.summary.as_str());
+            let text = summarize(router, previous, older).await;
+            let next = ConversationSummary {
+                conversation_id,
+                tokens: TokenCounter::estimate_completion_tokens(&text),
+                summary: text,
+                covered_through: older[older.len() - 1].id,
+                covered_messages: summary.as_ref().map(|s| s.covered_messages).unwrap_or(0)
+                    + older.len(),
+                updated_at: Utc::now().to_rfc3339(),
+            };
+            let stored = next.clone();
+            pool.run(move |conn| Ok::<_, DbError>(Self::save_summary(conn, &stored)?))
+                .await?;
+            tracing::info!(
+                "Summarized {} messages of conversation {} into {} tokens",
+                older.len(),
+                conversation_id,
+                next.tokens
+            );
+            summary = Some(next);
+        }
+
+        // Whatever is still too large loses its oldest turns; the latest
+        // message always goes out
+        let mut recent = &uncovered[cut..];
+        let summary_tokens = summary.as_ref().map(|s| s.tokens).unwrap_or(0);
+        while recent.len() > 1 && summary_tokens + message_tokens(recent) > budget {
+            recent = &recent[1..];
+        }
+        Ok(assemble(summary.as_ref(), recent))
+    }
+
+    /// How many of the oldest `messages` to summarize so the rest fit in
+    /// `keep_tokens`, keeping at least the configured recent messages and
+    /// always the latest one
+    fn split_point(&self, messages: &[Message], keep_tokens: u32) -> usize {
+        let keep_recent = self.config.keep_recent_messages.max(1);
+        let mut kept_tokens = 0;
+        let mut cut = messages.len();
+        for (i, message) in messages.iter().enumerate().rev() {
+            let tokens = message_tokens(std::slice::from_ref(message));
+            let kept = messages.len() - i - 1;
+            if kept >= keep_recent && kept_tokens + tokens > keep_tokens {
+                break;
+            }
+            kept_tokens += tokens;
+            cut = i;
+        }
+        cut
+    }
+}
+
+fn message_tokens(messages: &[Message]) -> u32 {
+    messages
+        .iter()
+        .map(|m| {
+            m.tokens
+                .filter(|&tokens| tokens > 0)
+                .map(|tokens| tokens as u32)
+                .unwrap_or_else(|| TokenCounter::estimate_completion_tokens(&m.content))
+        })
+        .sum()
+}
+
+/// The summary as a system message, followed by the recent turns
+fn assemble(summary: Option<&ConversationSummary>, recent: &[Message]) -> Vec<ChatMessage> {
+    let summary = summary.map(|s| ChatMessage {
+        role: "system".to_string(),
+        content: format!(
+            "Summary of the earlier part of this conversation ({} messages):\n\n{}",
+            s.covered_messages, s.summary
+        ),
+        tool_calls: None,
+        tool_call_id: None,
+        multimodal_content: None,
+    });
+    summary
+        .into_iter()
+        .chain(recent.iter().map(|m| ChatMessage {
+            role: m.role.as_str().to_string(),
+            content: m.content.clone(),
+            tool_calls: None,
+            tool_call_id: None,
+            multimodal_content: None,
+        }))
+        .collect()
+}
+
+async fn summarize(
+    router: &tokio::sync::Mutex<LLMRouter>,
+    previous: Option<&str>,
+    messages: &[Message],
+) -> String {
+    let prompt = summary_prompt(previous, messages);
+    let reply = {
+        let router = router.lock().await;
+        router.send_message(&prompt, None).await
+    };
+    match reply {
+        Ok(summary) if !summary.trim().is_empty() => summary.trim().to_string(),
+        Ok(_) => fallback_summary(previous, messages),
+        Err(e) => {
+            tracing::warn!("Summarization failed, keeping an extract instead: {}", e);
+            fallback_summary(previous, messages)
+        }
+    }
+}
+
+fn summary_prompt(previous: Option<&str>, messages: &[Message]) -> String {
+    let mut prompt = String::from(
+        "Update the running summary of a conversation between a user and an assistant. \
+         Keep decisions, facts, constraints, user preferences, open tasks, file names, \
+         code changes and errors with their resolutions. Be concise but specific, and \
+         write only the summary.\n\n",
+    );
+    if let Some(previous) = previous {
+        prompt.push_str("Current summary:\n");
+        prompt.push_str(previous);
+        prompt.push_str("\n\n");
+    }
+    prompt.push_str("New messages:\n\n");
+    for message in messages {
+        prompt.push_str(&format!(
+            "[{}]: {}\n\n",
+            message.role.as_str(),
+            truncate(&message.content, MAX_SUMMARIZED_CHARS)
+        ));
+    }
+    prompt
+}
+
+/// An extract of the user's requests and the assistant's replies, used when
+/// no model is available to summarize
+fn fallback_summary(previous: Option<&str>, messages: &[Message]) -> String {
+    let mut summary = previous.map(|p| format!("{}\n", p)).unwrap_or_default();
+    for message in messages {
+        let label = match message.role {
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
+            MessageRole::System => continue,
+        };
+        let line = truncate(message.content.lines().next().unwrap_or_default(), 200);
+        if !line.trim().is_empty() {
+            summary.push_str(&format!("- {}: {}\n", label, line.trim()));
+        }
+    }
+    // Keep the most recent part when the extract grows too long
+    let chars = summary.chars().count();
+    if chars > MAX_FALLBACK_CHARS {
+        summary = summary.chars().skip(chars - MAX_FALLBACK_CHARS).collect();
+    }
+    summary.trim().to_string()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!(
+            "{}... [truncated]",
+            text.chars().take(max_chars).collect::<String>()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: i64, role: MessageRole, tokens: i32) -> Message {
+        Message {
+            id,
+            conversation_id: 1,
+            role,
+            content: format!("message {}", id),
+            tokens: Some(tokens),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_keeps_recent_messages_within_budget() {
+        let manager = ContextWindowManager::new(ContextWindowConfig {
+            keep_recent_messages: 2,
+            ..Default::default()
+        });
+        let history: Vec<Message> = (1..=10)
+            .map(|id| message(id, MessageRole::User, 100))
+            .collect();
+        // Room for four messages
+        assert_eq!(manager.split_point(&history, 400), 6);
+        // The recent messages are kept even when they do not fit
+        assert_eq!(manager.split_point(&history, 50), 8);
+        assert_eq!(manager.split_point(&history, 5_000), 0);
+    }
+
+    #[test]
+    fn test_summary_covers_messages_through_its_last_id() {
+        let history: Vec<Message> = (1..=4)
+            .map(|id| message(id, MessageRole::Assistant, 10))
+            .collect();
+        let summary = ConversationSummary {
+            conversation_id: 1,
+            summary: "Earlier".to_string(),
+            covered_through: 2,
+            covered_messages: 2,
+            tokens: 5,
+            updated_at: String::new(),
+        };
+        assert_eq!(
+            ContextWindowManager::uncovered_from(&history, Some(&summary)),
+            Some(2)
+        );
+
+        let messages = assemble(Some(&summary), &history[2..]);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].role, "system");
+        assert!(messages[0].content.ends_with("Earlier"));
+        assert_eq!(messages[1].content, "message 3");
+
+        let stale = ConversationSummary {
+            covered_through: 99,
+            ..summary
+        };
+        assert_eq!(
+            ContextWindowManager::uncovered_from(&history, Some(&stale)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_summary_round_trips_through_the_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO conversations (id, title) VALUES (1, 'Long chat')",
+            [],
+        )
+        .unwrap();
+
+        let summary = ConversationSummary {
+            conversation_id: 1,
+            summary: "The user wants a CLI".to_string(),
+            covered_through: 40,
+            covered_messages: 40,
+            tokens: 6,
+            updated_at: Utc::now().to_rfc3339(),
+        };
+        ContextWindowManager::save_summary(&conn, &summary).unwrap();
+        let loaded = ContextWindowManager::load_summary(&conn, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.covered_through, 40);
+        assert_eq!(loaded.summary, "The user wants a CLI");
+
+        ContextWindowManager::clear_summary(&conn, 1).unwrap();
+        assert!(ContextWindowManager::load_summary(&conn, 1)
+            .unwrap()
+            .is_none());
+    }
+}
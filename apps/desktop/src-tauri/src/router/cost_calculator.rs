@@ -100,6 +100,101 @@ impl CostCalculator {
             },
         );
 
+        // Azure OpenAI pricing (global deployments, keyed by the model the
+        // deployment reports)
+        pricing.insert(
+            (Provider::AzureOpenAI, "gpt-4o"),
+            Pricing {
+                input_per_million: 2.5,
+                output_per_million: 10.0,
+            },
+        );
+        pricing.insert(
+            (Provider::AzureOpenAI, "gpt-4o-mini"),
+            Pricing {
+                input_per_million: 0.15,
+                output_per_million: 0.60,
+            },
+        );
+        pricing.insert(
+            (Provider::AzureOpenAI, "gpt-4.1"),
+            Pricing {
+                input_per_million: 2.0,
+                output_per_million: 8.0,
+            },
+        );
+        pricing.insert(
+            (Provider::AzureOpenAI, "gpt-4.1-mini"),
+            Pricing {
+                input_per_million: 0.40,
+                output_per_million: 1.60,
+            },
+        );
+        pricing.insert(
+            (Provider::AzureOpenAI, "o3-mini"),
+            Pricing {
+                input_per_million: 1.10,
+                output_per_million: 4.40,
+            },
+        );
+
+        // AWS Bedrock on-demand pricing (us-east-1)
+        pricing.insert(
+            (
+                Provider::Bedrock,
+                "anthropic.claude-3-5-sonnet-20241022-v2:0",
+            ),
+            Pricing {
+                input_per_million: 3.0,
+                output_per_million: 15.0,
+            },
+        );
+        pricing.insert(
+            (
+                Provider::Bedrock,
+                "anthropic.claude-3-5-haiku-20241022-v1:0",
+            ),
+            Pricing {
+                input_per_million: 0.80,
+                output_per_million: 4.0,
+            },
+        );
+        pricing.insert(
+            (Provider::Bedrock, "anthropic.claude-3-opus-20240229-v1:0"),
+            Pricing {
+                input_per_million: 15.0,
+                output_per_million: 75.0,
+            },
+        );
+        pricing.insert(
+            (Provider::Bedrock, "meta.llama3-1-70b-instruct-v1:0"),
+            Pricing {
+                input_per_million: 0.72,
+                output_per_million: 0.72,
+            },
+        );
+        pricing.insert(
+            (Provider::Bedrock, "amazon.nova-pro-v1:0"),
+            Pricing {
+                input_per_million: 0.80,
+                output_per_million: 3.20,
+            },
+        );
+        pricing.insert(
+            (Provider::Bedrock, "amazon.nova-lite-v1:0"),
+            Pricing {
+                input_per_million: 0.06,
+                output_per_million: 0.24,
+            },
+        );
+        pricing.insert(
+            (Provider::Bedrock, "mistral.mistral-large-2407-v1:0"),
+            Pricing {
+                input_per_million: 2.0,
+                output_per_million: 6.0,
+            },
+        );
+
         // Ollama - local (no cost)
         pricing.insert(
             (Provider::Ollama, "llama3"),
@@ -139,6 +234,21 @@ impl CostCalculator {
             },
         );
 
+        provider_defaults.insert(
+            Provider::AzureOpenAI,
+            Pricing {
+                input_per_million: 2.5,
+                output_per_million: 10.0,
+            },
+        );
+        provider_defaults.insert(
+            Provider::Bedrock,
+            Pricing {
+                input_per_million: 3.0,
+                output_per_million: 15.0,
+            },
+        );
+
         Self {
             pricing,
             provider_defaults,
@@ -169,4 +279,28 @@ impl CostCalculator {
 
         pricing.cost(input_tokens, output_tokens)
     }
+
+    /// Share of the input rate billed for prompt tokens the provider serves
+    /// from its automatic prompt cache. `None` for providers that only cache
+    /// on explicit request, which the app does not make.
+    pub fn cached_input_rate(&self, provider: Provider) -> Option<f64> {
+        match provider {
+            Provider::OpenAI | Provider::AzureOpenAI => Some(0.5),
+            Provider::Google => Some(0.25),
+            Provider::DeepSeek => Some(0.1),
+            _ => None,
+        }
+    }
+
+    /// Cheapest priced model for a provider, by combined input and output rate
+    pub fn cheapest_model(&self, provider: Provider) -> Option<&'static str> {
+        self.pricing
+            .iter()
+            .filter(|((p, _), _)| *p == provider)
+            .min_by(|(_, a), (_, b)| {
+                (a.input_per_million + a.output_per_million)
+                    .total_cmp(&(b.input_per_million + b.output_per_million))
+            })
+            .map(|((_, model), _)| *model)
+    }
 }
@@ -0,0 +1,153 @@
+//! Cost forecast for a chat turn before it is sent.
+//!
+//! The caller assembles the prompt the send path would build and reports its
+//! size per section; the forecast prices it with the [`CostCalculator`],
+//! brackets the reply length by the conversation's recent replies and
+//! credits the prompt prefix the provider is expected to serve from its
+//! automatic prompt cache.
+
+use crate::router::cost_calculator::CostCalculator;
+use crate::router::Provider;
+use serde::Serialize;
+
+/// Providers only cache prompt prefixes of at least this many tokens
+pub const PROMPT_CACHE_MIN_TOKENS: u32 = 1024;
+/// Reply length bracket for a conversation without earlier replies
+const DEFAULT_OUTPUT_RANGE: (u32, u32) = (150, 1_000);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionTokens {
+    /// `system`, `tools`, `history` or `draft`
+    pub section: String,
+    pub tokens: u32,
+}
+
+impl SectionTokens {
+    pub fn new(section: &str, tokens: u32) -> Self {
+        Self {
+            section: section.to_string(),
+            tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostForecast {
+    pub provider: String,
+    pub model: String,
+    pub sections: Vec<SectionTokens>,
+    pub input_tokens: u32,
+    pub output_tokens_min: u32,
+    pub output_tokens_max: u32,
+    /// Input tokens expected to be billed at the provider's cached rate
+    pub cached_tokens: u32,
+    /// Input cost after the cache discount
+    pub input_cost: f64,
+    pub cost_min: f64,
+    pub cost_max: f64,
+    /// What the cached tokens save compared to the full input rate
+    pub cached_savings: f64,
+}
+
+/// Price a prompt made of `sections`. `cacheable_tokens` is the prefix that
+/// was already sent in the previous turn; `recent_replies` are the token
+/// counts of the conversation's latest assistant replies.
+pub fn forecast(
+    calculator: &CostCalculator,
+    provider: Provider,
+    model: &str,
+    sections: Vec<SectionTokens>,
+    cacheable_tokens: u32,
+    recent_replies: &[u32],
+) -> CostForecast {
+    let input_tokens: u32 = sections.iter().map(|s| s.tokens).sum();
+
+    let cached_rate = calculator.cached_input_rate(provider);
+    let cached_tokens = match cached_rate {
+        Some(_) if cacheable_tokens >= PROMPT_CACHE_MIN_TOKENS => {
+            cacheable_tokens.min(input_tokens)
+        }
+        _ => 0,
+    };
+    let cached_savings = calculator.calculate(provider, model, cached_tokens, 0)
+        * (1.0 - cached_rate.unwrap_or(1.0));
+    let input_cost = calculator.calculate(provider, model, input_tokens, 0) - cached_savings;
+
+    let (output_tokens_min, output_tokens_max) = output_range(recent_replies);
+    let output_cost = |tokens| calculator.calculate(provider, model, 0, tokens);
+
+    CostForecast {
+        provider: provider.as_string().to_string(),
+        model: model.to_string(),
+        sections,
+        input_tokens,
+        output_tokens_min,
+        output_tokens_max,
+        cached_tokens,
+        input_cost,
+        cost_min: input_cost + output_cost(output_tokens_min),
+        cost_max: input_cost + output_cost(output_tokens_max),
+        cached_savings,
+    }
+}
+
+fn output_range(recent_replies: &[u32]) -> (u32, u32) {
+    let replies = recent_replies.iter().copied().filter(|&tokens| tokens > 0);
+    match (replies.clone().min(), replies.max()) {
+        (Some(min), Some(max)) => (min, max),
+        _ => DEFAULT_OUTPUT_RANGE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forecast_credits_cached_prefix() {
+        let calculator = CostCalculator::new();
+        let sections = vec![
+            SectionTokens::new("system", 400),
+            SectionTokens::new("tools", 3_000),
+            SectionTokens::new("history", 600),
+            SectionTokens::new("draft", 1_000),
+        ];
+
+        let result = forecast(
+            &calculator,
+            Provider::OpenAI,
+            "gpt-4o",
+            sections.clone(),
+            4_000,
+            &[200, 0, 800],
+        );
+        assert_eq!(result.input_tokens, 5_000);
+        assert_eq!(result.cached_tokens, 4_000);
+        assert_eq!(
+            (result.output_tokens_min, result.output_tokens_max),
+            (200, 800)
+        );
+        // gpt-4o: $5 in, $15 out per million; cached input at half price
+        assert!((result.cached_savings - 0.01).abs() < 1e-9);
+        assert!((result.input_cost - 0.015).abs() < 1e-9);
+        assert!((result.cost_max - 0.027).abs() < 1e-9);
+
+        // Providers without automatic caching pay the full input rate
+        let result = forecast(
+            &calculator,
+            Provider::Anthropic,
+            "claude-sonnet-4-5",
+            sections,
+            4_000,
+            &[],
+        );
+        assert_eq!(result.cached_tokens, 0);
+        assert_eq!(result.cached_savings, 0.0);
+        assert_eq!(
+            (result.output_tokens_min, result.output_tokens_max),
+            DEFAULT_OUTPUT_RANGE
+        );
+    }
+}
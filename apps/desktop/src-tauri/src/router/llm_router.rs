@@ -1,17 +1,28 @@
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
 use futures_util::Stream;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 
+use crate::cache::warmup::{self, PatternKind};
+use crate::cancellation::{cancellable, Cancelled};
+use crate::error::{classify_llm_error, Categorizable, LLMError};
+use crate::router::budget_guard::{BudgetDecision, BudgetExceeded, BudgetGuard};
 use crate::router::cache_manager::CacheManager;
 use crate::router::cost_calculator::CostCalculator;
+use crate::router::provider_health::ProviderHealthMonitor;
+use crate::router::semantic_cache::{record_lookup, CacheLookup, SemanticCache};
 use crate::router::sse_parser::StreamChunk;
+use crate::router::structured_output::{self, StructuredOutputError};
 use crate::router::token_counter::TokenCounter;
-use crate::router::{ChatMessage, LLMProvider, LLMRequest, LLMResponse, Provider};
+use crate::router::{
+    ChatMessage, FailoverAttempt, LLMProvider, LLMRequest, LLMResponse, Provider, ResponseFormat,
+    RoutingTrace,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum RoutingStrategy {
@@ -93,6 +104,9 @@ pub struct LLMRouter {
     cost_calculator: CostCalculator,
     cache_manager: Option<CacheManager>,
     db_connection: Option<Arc<Mutex<Connection>>>,
+    budget_guard: Option<Arc<BudgetGuard>>,
+    health_monitor: Option<Arc<ProviderHealthMonitor>>,
+    semantic_cache: Option<Arc<SemanticCache>>,
 }
 
 impl Default for LLMRouter {
@@ -173,6 +187,8 @@ impl LLMRouter {
             Provider::Qwen,
             Provider::Mistral,
             Provider::Moonshot,
+            Provider::AzureOpenAI,
+            Provider::Bedrock,
             Provider::Ollama,
             Provider::XAI,
         ];
@@ -210,9 +226,22 @@ impl LLMRouter {
             cost_calculator: CostCalculator::new(),
             cache_manager: None,
             db_connection: None,
+            budget_guard: None,
+            health_monitor: None,
+            semantic_cache: None,
         }
     }
 
+    /// Enforce spend caps and record cost for every request routed through this router
+    pub fn set_budget_guard(&mut self, guard: Arc<BudgetGuard>) {
+        self.budget_guard = Some(guard);
+    }
+
+    /// Record latency and failures per provider and try degraded providers last
+    pub fn set_health_monitor(&mut self, monitor: Arc<ProviderHealthMonitor>) {
+        self.health_monitor = Some(monitor);
+    }
+
     /// Set cache manager and database connection for LLM response caching
     pub fn set_cache(
         &mut self,
@@ -223,6 +252,11 @@ impl LLMRouter {
         self.db_connection = Some(db_connection);
     }
 
+    /// Serve deterministic requests from similar cached prompts; needs `set_cache`
+    pub fn set_semantic_cache(&mut self, semantic_cache: Arc<SemanticCache>) {
+        self.semantic_cache = Some(semantic_cache);
+    }
+
     pub fn set_default_provider(&mut self, provider: Provider) {
         self.default_provider = provider;
     }
@@ -267,6 +301,14 @@ impl LLMRouter {
         self.set_provider(Provider::Moonshot, provider);
     }
 
+    pub fn set_azure_openai(&mut self, provider: Box<dyn LLMProvider>) {
+        self.set_provider(Provider::AzureOpenAI, provider);
+    }
+
+    pub fn set_bedrock(&mut self, provider: Box<dyn LLMProvider>) {
+        self.set_provider(Provider::Bedrock, provider);
+    }
+
     pub fn has_provider(&self, provider: Provider) -> bool {
         self.providers
             .get(&provider)
@@ -280,8 +322,9 @@ impl LLMRouter {
         preferences: &RouterPreferences,
     ) -> Vec<RouteCandidate> {
         let mut order = Vec::new();
-        let _user_specified_provider = preferences.provider.is_some();
 
+        // A preferred provider is always tried first; the rest stay behind it
+        // as failover candidates
         if let Some(preferred) = preferences.provider {
             if self.has_provider(preferred) {
                 order.push(RouteCandidate {
@@ -293,10 +336,8 @@ impl LLMRouter {
                     reason: "user-preference",
                 });
             }
-            // If user specified a provider, we strictly respect it and return only that candidate.
-            // We do not want to fallback to other providers or strategies.
-            return order;
         }
+        let pinned = order.len();
 
         if let Some(context) = &preferences.context {
             let suggestion = self.suggest_for_context(context);
@@ -349,6 +390,8 @@ impl LLMRouter {
             Provider::Qwen,
             Provider::Mistral,
             Provider::Moonshot,
+            Provider::AzureOpenAI,
+            Provider::Bedrock,
         ] {
             if order.iter().any(|c| c.provider == provider) {
                 continue;
@@ -362,15 +405,79 @@ impl LLMRouter {
             }
         }
 
+        // Stable sort keeps the strategy order among equally healthy providers
+        if let Some(monitor) = &self.health_monitor {
+            order[pinned..].sort_by_key(|candidate| monitor.status(candidate.provider).rank());
+        }
+
         order
     }
 
+    /// Invoke a single candidate. With `response_format` set the reply is
+    /// validated locally, and a reply that is not valid JSON for the schema
+    /// gets one corrective retry before the request fails.
     pub async fn invoke_candidate(
         &self,
         candidate: &RouteCandidate,
         request: &LLMRequest,
     ) -> Result<RouteOutcome> {
-        // Check cache if available
+        let Some(format) = &request.response_format else {
+            return self.invoke_once(candidate, request).await;
+        };
+
+        let mut first = self.invoke_once(candidate, request).await?;
+        let errors = match checked_structured_reply(format, &first.response) {
+            Ok(content) => {
+                first.response.content = content;
+                return Ok(first);
+            }
+            Err(errors) => errors,
+        };
+
+        tracing::warn!(
+            "{}/{} returned invalid structured output, retrying: {}",
+            candidate.provider.as_string(),
+            first.model,
+            errors.join("; ")
+        );
+
+        let mut retry_request = request.clone();
+        retry_request.messages.push(ChatMessage {
+            role: "assistant".to_string(),
+            content: first.response.content.clone(),
+            tool_calls: None,
+            tool_call_id: None,
+            multimodal_content: None,
+        });
+        retry_request
+            .messages
+            .push(structured_output::correction_message(&errors));
+
+        let mut retry = self.invoke_once(candidate, &retry_request).await?;
+        retry.prompt_tokens += first.prompt_tokens;
+        retry.completion_tokens += first.completion_tokens;
+        retry.cost += first.cost;
+        retry.response.prompt_tokens = Some(retry.prompt_tokens);
+        retry.response.completion_tokens = Some(retry.completion_tokens);
+        retry.response.tokens = Some(retry.prompt_tokens + retry.completion_tokens);
+        retry.response.cost = Some(retry.cost);
+
+        match checked_structured_reply(format, &retry.response) {
+            Ok(content) => {
+                retry.response.content = content;
+                Ok(retry)
+            }
+            Err(errors) => Err(StructuredOutputError { errors }.into()),
+        }
+    }
+
+    async fn invoke_once(
+        &self,
+        candidate: &RouteCandidate,
+        request: &LLMRequest,
+    ) -> Result<RouteOutcome> {
+        // Exact cache first, then similar prompts for deterministic requests
+        let mut prompt_embedding = None;
         if let (Some(cache_manager), Some(db_conn)) = (&self.cache_manager, &self.db_connection) {
             let cache_key = CacheManager::compute_cache_key(
                 candidate.provider,
@@ -380,46 +487,66 @@ impl LLMRouter {
                 request.max_tokens,
             );
 
-            // Try to fetch from cache
             if let Ok(conn) = db_conn.lock() {
-                if let Ok(Some(cached_entry)) = cache_manager.fetch(&conn, &cache_key) {
-                    // Parse cached response
-                    if let Ok(cached_response) =
-                        serde_json::from_str::<LLMResponse>(&cached_entry.response)
-                    {
-                        let prompt_tokens = cached_response.prompt_tokens.unwrap_or(0);
-                        let completion_tokens = cached_response.completion_tokens.unwrap_or(0);
-                        let cost = cached_response.cost.unwrap_or(0.0);
-
-                        // Update cache hit statistics
-                        let _ = cache_manager.update_cache_hit(
-                            &conn,
-                            &cache_key,
-                            prompt_tokens + completion_tokens,
-                            cost,
-                        );
+                warmup::record_llm_request(
+                    &conn,
+                    &cache_key,
+                    candidate.provider,
+                    &candidate.model,
+                    request,
+                    chrono::Local::now(),
+                );
+                if let Some(outcome) = serve_cached(cache_manager, &conn, &cache_key, candidate)
+                    .filter(|outcome| satisfies_format(request, &outcome.response))
+                {
+                    record_lookup(&conn, CacheLookup::ExactHit);
+                    warmup::record_warm_hit(&conn, PatternKind::Llm, &cache_key);
+                    return Ok(outcome);
+                }
+            }
 
-                        tracing::info!(
-                            "Cache hit for {}/{} - saved {} tokens, ${:.4}",
-                            candidate.provider.as_string(),
-                            candidate.model,
-                            prompt_tokens + completion_tokens,
-                            cost
-                        );
+            if let Some(semantic_cache) = self
+                .semantic_cache
+                .as_ref()
+                .filter(|_| SemanticCache::is_eligible(request))
+            {
+                prompt_embedding = semantic_cache.embed(&request.messages).await;
+            }
 
-                        let mut response = cached_response;
-                        response.cached = true;
-
-                        return Ok(RouteOutcome {
-                            provider: candidate.provider,
-                            model: response.model.clone(),
-                            response,
-                            prompt_tokens,
-                            completion_tokens,
-                            cost,
-                        });
+            if let Ok(conn) = db_conn.lock() {
+                if let (Some(semantic_cache), Some(embedding)) =
+                    (&self.semantic_cache, &prompt_embedding)
+                {
+                    match semantic_cache.find(
+                        &conn,
+                        candidate.provider,
+                        &candidate.model,
+                        embedding,
+                    ) {
+                        Ok(Some((similar_key, similarity))) => {
+                            if let Some(outcome) =
+                                serve_cached(cache_manager, &conn, &similar_key, candidate)
+                                    .filter(|outcome| satisfies_format(request, &outcome.response))
+                            {
+                                tracing::info!(
+                                    "Semantic cache hit for {}/{} (similarity {:.3})",
+                                    candidate.provider.as_string(),
+                                    candidate.model,
+                                    similarity
+                                );
+                                if !warmup::is_warming() {
+                                    record_lookup(&conn, CacheLookup::SemanticHit);
+                                }
+                                return Ok(outcome);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("Semantic cache lookup failed: {}", e),
                     }
                 }
+                if !warmup::is_warming() {
+                    record_lookup(&conn, CacheLookup::Miss);
+                }
             }
         }
 
@@ -432,12 +559,59 @@ impl LLMRouter {
         let mut routed_request = request.clone();
         routed_request.model = candidate.model.clone();
 
-        let mut response = provider
-            .send_message(&routed_request)
-            .await
-            .map_err(|e| anyhow!(e.to_string()))?;
+        // Send an object-rooted schema; providers limited to generic JSON
+        // mode also get it in the prompt
+        if let Some(format) = &request.response_format {
+            let format = structured_output::provider_format(format);
+            if !structured_output::supports_native_schema(candidate.provider) {
+                routed_request.messages.insert(
+                    0,
+                    ChatMessage {
+                        role: "system".to_string(),
+                        content: structured_output::instruction(&format),
+                        tool_calls: None,
+                        tool_call_id: None,
+                        multimodal_content: None,
+                    },
+                );
+            }
+            routed_request.response_format = Some(format);
+        }
+
+        if let Some(guard) = &self.budget_guard {
+            match guard.check(candidate.provider, &candidate.model) {
+                BudgetDecision::Allow => {}
+                BudgetDecision::Downgrade { model, period } => {
+                    tracing::info!(
+                        "{:?} soft budget cap reached, downgrading {}/{} to {}",
+                        period,
+                        candidate.provider.as_string(),
+                        candidate.model,
+                        model
+                    );
+                    routed_request.model = model;
+                }
+                BudgetDecision::Reject(exceeded) => return Err(exceeded.into()),
+            }
+        }
+
+        let started = Instant::now();
+        // A cancelled request says nothing about the provider's health
+        let result = cancellable(provider.send_message(&routed_request))
+            .await?
+            .map_err(|e| classify_llm_error(&*e));
+        if let Some(monitor) = &self.health_monitor {
+            match &result {
+                Ok(_) => monitor.record_success(candidate.provider, started.elapsed()),
+                Err(e) => monitor.record_error(candidate.provider, started.elapsed(), e),
+            }
+        }
+        let mut response = result?;
         if response.model.is_empty() {
-            response.model = candidate.model.clone();
+            response.model = routed_request.model.clone();
+        }
+        if let Some(format) = &request.response_format {
+            response.content = structured_output::unwrap_reply(format, &response.content);
         }
 
         // Compute token estimates if missing
@@ -468,12 +642,19 @@ impl LLMRouter {
 
         let total_cost = response.cost.unwrap_or(0.0);
 
-        // Store in cache if available
-        if let (Some(cache_manager), Some(db_conn)) = (&self.cache_manager, &self.db_connection) {
+        if let Some(guard) = &self.budget_guard {
+            guard.record(candidate.provider, &response.model, total_cost);
+        }
+
+        // Store in cache if available; replies that fail the schema are not kept
+        let cacheable = satisfies_format(request, &response);
+        if let (Some(cache_manager), Some(db_conn), true) =
+            (&self.cache_manager, &self.db_connection, cacheable)
+        {
             if let Ok(conn) = db_conn.lock() {
                 let cache_key = CacheManager::compute_cache_key(
                     candidate.provider,
-                    &candidate.model,
+                    &routed_request.model,
                     &request.messages,
                     request.temperature,
                     request.max_tokens,
@@ -486,7 +667,7 @@ impl LLMRouter {
                     let cache_record = crate::router::cache_manager::CacheRecord {
                         cache_key: &cache_key,
                         provider: candidate.provider,
-                        model: &candidate.model,
+                        model: &routed_request.model,
                         prompt_hash: &prompt_hash,
                         response: &response_json,
                         tokens: Some(total_tokens),
@@ -499,10 +680,24 @@ impl LLMRouter {
                     if let Err(e) = cache_manager.upsert(&conn, cache_record) {
                         tracing::warn!("Failed to cache LLM response: {}", e);
                     } else {
+                        if let (Some(semantic_cache), Some(embedding)) =
+                            (&self.semantic_cache, &prompt_embedding)
+                        {
+                            if let Err(e) = semantic_cache.store(
+                                &conn,
+                                &cache_key,
+                                candidate.provider,
+                                &routed_request.model,
+                                embedding,
+                            ) {
+                                tracing::warn!("Failed to store prompt embedding: {}", e);
+                            }
+                        }
+
                         tracing::debug!(
                             "Cached response for {}/{} (expires: {})",
                             candidate.provider.as_string(),
-                            candidate.model,
+                            routed_request.model,
                             expires_at
                         );
                     }
@@ -520,6 +715,91 @@ impl LLMRouter {
         })
     }
 
+    /// Invoke candidates in order, moving to the next one when a provider fails
+    /// with a retryable error (rate limit, timeout, network). Non-retryable
+    /// errors such as bad credentials or content filtering stop the chain.
+    /// The call is added to the trace of the agent run being recorded, if any.
+    pub async fn invoke_with_failover(
+        &self,
+        candidates: &[RouteCandidate],
+        request: &LLMRequest,
+    ) -> Result<RouteOutcome> {
+        let outcome = self.failover(candidates, request).await;
+        crate::agent::runtime::record_llm_call(request, &outcome);
+        outcome
+    }
+
+    async fn failover(
+        &self,
+        candidates: &[RouteCandidate],
+        request: &LLMRequest,
+    ) -> Result<RouteOutcome> {
+        if candidates.is_empty() {
+            return Err(anyhow!("No LLM providers configured"));
+        }
+
+        let mut chain: Vec<FailoverAttempt> = Vec::new();
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            match self.invoke_candidate(candidate, request).await {
+                Ok(mut outcome) => {
+                    if !chain.is_empty() {
+                        tracing::info!(
+                            "LLM request served by {}/{} after {} failover(s)",
+                            candidate.provider.as_string(),
+                            outcome.model,
+                            chain.len()
+                        );
+                    }
+                    outcome.response.routing = Some(RoutingTrace {
+                        provider: candidate.provider.as_string().to_string(),
+                        model: outcome.model.clone(),
+                        failover_chain: chain,
+                    });
+                    return Ok(outcome);
+                }
+                Err(err) => {
+                    let message = err.to_string();
+                    let has_next = index + 1 < candidates.len();
+
+                    if !should_fail_over(&err) || !has_next {
+                        if chain.is_empty() {
+                            return Err(err);
+                        }
+                        let attempts: Vec<String> = chain
+                            .iter()
+                            .map(|a| format!("{}/{}: {}", a.provider, a.model, a.error))
+                            .collect();
+                        return Err(err.context(format!(
+                            "{}/{} failed: {} (after failover from {})",
+                            candidate.provider.as_string(),
+                            candidate.model,
+                            message,
+                            attempts.join("; ")
+                        )));
+                    }
+
+                    let category = classify_llm_error(&*err).category();
+                    tracing::warn!(
+                        "{}/{} failed with {:?} error, failing over: {}",
+                        candidate.provider.as_string(),
+                        candidate.model,
+                        category,
+                        message
+                    );
+                    chain.push(FailoverAttempt {
+                        provider: candidate.provider.as_string().to_string(),
+                        model: candidate.model.clone(),
+                        error: message,
+                        category,
+                    });
+                }
+            }
+        }
+
+        Err(anyhow!("No LLM providers configured"))
+    }
+
     fn strategy_order(&self, task: TaskCategory, strategy: RoutingStrategy) -> Vec<RouteCandidate> {
         match strategy {
             RoutingStrategy::LocalFirst => {
@@ -673,6 +953,12 @@ impl LLMRouter {
                 TaskCategory::Complex => "kimi-k2-thinking".to_string(),
                 TaskCategory::Creative => "kimi-k2-thinking".to_string(),
             },
+            Provider::AzureOpenAI => "gpt-4o".to_string(),
+            Provider::Bedrock => match task {
+                TaskCategory::Simple => "anthropic.claude-3-5-haiku-20241022-v1:0".to_string(),
+                TaskCategory::Complex => "anthropic.claude-3-5-sonnet-20241022-v2:0".to_string(),
+                TaskCategory::Creative => "anthropic.claude-3-5-sonnet-20241022-v2:0".to_string(),
+            },
         }
     }
 }
@@ -684,6 +970,78 @@ enum TaskCategory {
     Creative,
 }
 
+/// Whether the next candidate should get a request that failed with this
+/// error: only rate limits and transient failures (timeouts, network and
+/// server errors) as classified by [`classify_llm_error`]. Bad credentials,
+/// rejected requests, unknown models and content filter refusals stop the
+/// chain, as do spend caps, which are global, and cancellation.
+fn should_fail_over(error: &anyhow::Error) -> bool {
+    if error.is::<BudgetExceeded>() || error.is::<Cancelled>() {
+        return false;
+    }
+    classify_llm_error(&**error).is_retryable()
+}
+
+/// Replies that called a tool are left for the caller; anything else must be
+/// JSON matching the requested format
+fn checked_structured_reply(
+    format: &ResponseFormat,
+    response: &LLMResponse,
+) -> std::result::Result<String, Vec<String>> {
+    let called_tool = response
+        .tool_calls
+        .as_ref()
+        .is_some_and(|calls| !calls.is_empty());
+    if called_tool {
+        return Ok(response.content.clone());
+    }
+    structured_output::validate(format, &response.content)
+}
+
+fn satisfies_format(request: &LLMRequest, response: &LLMResponse) -> bool {
+    match &request.response_format {
+        Some(format) => checked_structured_reply(format, response).is_ok(),
+        None => true,
+    }
+}
+
+/// Turn a live cache entry into a routed outcome and count the hit
+fn serve_cached(
+    cache_manager: &CacheManager,
+    conn: &Connection,
+    cache_key: &str,
+    candidate: &RouteCandidate,
+) -> Option<RouteOutcome> {
+    let cached_entry = cache_manager.fetch(conn, cache_key).ok()??;
+    let mut response = serde_json::from_str::<LLMResponse>(&cached_entry.response).ok()?;
+
+    let prompt_tokens = response.prompt_tokens.unwrap_or(0);
+    let completion_tokens = response.completion_tokens.unwrap_or(0);
+    let cost = response.cost.unwrap_or(0.0);
+
+    // Update cache hit statistics
+    let _ =
+        cache_manager.update_cache_hit(conn, cache_key, prompt_tokens + completion_tokens, cost);
+
+    tracing::info!(
+        "Cache hit for {}/{} - saved {} tokens, ${:.4}",
+        candidate.provider.as_string(),
+        candidate.model,
+        prompt_tokens + completion_tokens,
+        cost
+    );
+
+    response.cached = true;
+    Some(RouteOutcome {
+        provider: candidate.provider,
+        model: response.model.clone(),
+        response,
+        prompt_tokens,
+        completion_tokens,
+        cost,
+    })
+}
+
 fn classify_request(request: &LLMRequest) -> TaskCategory {
     let last_user_message = request
         .messages
@@ -735,6 +1093,7 @@ impl LLMRouter {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let candidates = self.candidates(&request, &prefs);
@@ -742,8 +1101,7 @@ impl LLMRouter {
             return Err(anyhow!("No LLM providers configured"));
         }
 
-        // Try first candidate
-        let outcome = self.invoke_candidate(&candidates[0], &request).await?;
+        let outcome = self.invoke_with_failover(&candidates, &request).await?;
         Ok(outcome.response.content)
     }
 
@@ -783,9 +1141,44 @@ impl LLMRouter {
             candidate.model
         );
 
-        provider
-            .send_message_streaming(&routed_request)
-            .await
-            .map_err(|e| anyhow!(e.to_string()))
+        let started = Instant::now();
+        let result = cancellable(provider.send_message_streaming(&routed_request))
+            .await?
+            .map_err(|e| classify_llm_error(&*e));
+        // Only failures to open the stream are attributed to the provider
+        if let (Some(monitor), Err(e)) = (&self.health_monitor, &result) {
+            monitor.record_error(candidate.provider, started.elapsed(), e);
+        }
+        result.map_err(anyhow::Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::ProviderHttpError;
+    use reqwest::StatusCode;
+
+    fn http_error(status: StatusCode) -> anyhow::Error {
+        anyhow::Error::new(ProviderHttpError {
+            provider: "OpenAI",
+            status,
+            body: String::new(),
+        })
+    }
+
+    #[test]
+    fn test_only_retryable_errors_fail_over() {
+        assert!(!should_fail_over(&http_error(StatusCode::UNAUTHORIZED)));
+        assert!(!should_fail_over(&http_error(StatusCode::BAD_REQUEST)));
+        assert!(!should_fail_over(&anyhow::Error::new(
+            LLMError::ContentFilterError("refused".to_string())
+        )));
+
+        assert!(should_fail_over(&http_error(StatusCode::TOO_MANY_REQUESTS)));
+        assert!(should_fail_over(&http_error(StatusCode::BAD_GATEWAY)));
+        assert!(should_fail_over(
+            &anyhow::Error::new(LLMError::Timeout("slow".to_string())).context("openai/gpt-4o")
+        ));
     }
 }
@@ -1,9 +1,15 @@
+pub mod budget_guard;
 pub mod cache_manager;
+pub mod context_window;
 pub mod cost_calculator;
+pub mod cost_forecast;
 pub mod function_executor;
 pub mod llm_router;
+pub mod provider_health;
 pub mod providers;
+pub mod semantic_cache;
 pub mod sse_parser;
+pub mod structured_output;
 pub mod token_counter;
 pub mod tool_executor;
 
@@ -12,6 +18,9 @@ mod tests;
 
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::fmt;
+
+use crate::error::ErrorCategory;
 use std::pin::Pin;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +34,9 @@ pub struct LLMRequest {
     pub tools: Option<Vec<ToolDefinition>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<ToolChoice>,
+    /// Constrain the reply to JSON, optionally matching a schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +108,27 @@ pub struct LLMResponse {
     pub tool_calls: Option<Vec<ToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<String>,
+    /// Which provider served the response and any providers skipped on the way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<RoutingTrace>,
+}
+
+/// Provider that actually answered a request after failover
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RoutingTrace {
+    pub provider: String,
+    pub model: String,
+    /// Earlier candidates that failed with a retryable error, in order
+    #[serde(default)]
+    pub failover_chain: Vec<FailoverAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverAttempt {
+    pub provider: String,
+    pub model: String,
+    pub error: String,
+    pub category: ErrorCategory,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +150,42 @@ pub enum ToolChoice {
     Specific(String),
 }
 
+/// Structured output mode, mapped onto each provider's native JSON support
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Any syntactically valid JSON object
+    JsonObject,
+    /// JSON validated against a schema
+    JsonSchema {
+        name: String,
+        schema: serde_json::Value,
+        #[serde(default = "default_strict")]
+        strict: bool,
+    },
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+impl ResponseFormat {
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        ResponseFormat::JsonSchema {
+            name: name.into(),
+            schema,
+            strict: true,
+        }
+    }
+
+    pub fn schema(&self) -> Option<&serde_json::Value> {
+        match self {
+            ResponseFormat::JsonObject => None,
+            ResponseFormat::JsonSchema { schema, .. } => Some(schema),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
@@ -148,9 +217,26 @@ pub enum Provider {
     Qwen,     // Qwen2.5-Max, Qwen3-Coder (Alibaba)
     Mistral,  // Mistral Large 2, Codestral
     Moonshot, // Kimi K2 Thinking (November 2025)
+    // Enterprise clouds
+    AzureOpenAI, // OpenAI models behind an Azure deployment
+    Bedrock,     // AWS Bedrock Converse API
 }
 
 impl Provider {
+    pub const ALL: [Provider; 11] = [
+        Provider::OpenAI,
+        Provider::Anthropic,
+        Provider::Google,
+        Provider::Ollama,
+        Provider::XAI,
+        Provider::DeepSeek,
+        Provider::Qwen,
+        Provider::Mistral,
+        Provider::Moonshot,
+        Provider::AzureOpenAI,
+        Provider::Bedrock,
+    ];
+
     #[allow(clippy::should_implement_trait)]
     pub fn as_string(&self) -> &'static str {
         match self {
@@ -163,6 +249,8 @@ impl Provider {
             Provider::Qwen => "qwen",
             Provider::Mistral => "mistral",
             Provider::Moonshot => "moonshot",
+            Provider::AzureOpenAI => "azure_openai",
+            Provider::Bedrock => "bedrock",
         }
     }
 
@@ -178,6 +266,8 @@ impl Provider {
             "qwen" | "alibaba" => Some(Provider::Qwen),
             "mistral" | "mistralai" => Some(Provider::Mistral),
             "moonshot" | "kimi" => Some(Provider::Moonshot),
+            "azure_openai" | "azure" => Some(Provider::AzureOpenAI),
+            "bedrock" | "aws_bedrock" => Some(Provider::Bedrock),
             _ => None,
         }
     }
@@ -194,6 +284,8 @@ impl Provider {
             Provider::Qwen => "qwen-max-2025-01-25",
             Provider::Mistral => "mistral-large-2",
             Provider::Moonshot => "kimi-k2-thinking",
+            Provider::AzureOpenAI => "gpt-4o",
+            Provider::Bedrock => "anthropic.claude-3-5-sonnet-20241022-v2:0",
         }
     }
 
@@ -245,6 +337,15 @@ impl Provider {
             // Moonshot routing
             (Provider::Moonshot, TaskType::ComplexReasoning) => "kimi-k2-thinking",
             (Provider::Moonshot, _) => "kimi-k2-thinking",
+
+            // Azure serves whatever model the deployment pins
+            (Provider::AzureOpenAI, _) => "gpt-4o",
+
+            // Bedrock routing
+            (Provider::Bedrock, TaskType::FastCompletion) => {
+                "anthropic.claude-3-5-haiku-20241022-v1:0"
+            }
+            (Provider::Bedrock, _) => "anthropic.claude-3-5-sonnet-20241022-v2:0",
         }
     }
 }
@@ -307,7 +408,46 @@ pub trait LLMProvider: Send + Sync {
     }
 }
 
+/// Non-success HTTP reply from a provider API. Kept typed so routing can
+/// act on the status code instead of the message text.
+#[derive(Debug, Clone)]
+pub struct ProviderHttpError {
+    pub provider: &'static str,
+    pub status: reqwest::StatusCode,
+    pub body: String,
+}
+
+impl ProviderHttpError {
+    pub async fn from_response(provider: &'static str, response: reqwest::Response) -> Self {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        Self {
+            provider,
+            status,
+            body,
+        }
+    }
+}
+
+impl fmt::Display for ProviderHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} API error {}: {}",
+            self.provider, self.status, self.body
+        )
+    }
+}
+
+impl Error for ProviderHttpError {}
+
+pub use budget_guard::{BudgetGuard, BudgetLimits, BudgetStatus};
 pub use llm_router::{
     CostPriority, LLMRouter, RouteCandidate, RouteOutcome, RouterContext, RouterPreferences,
     RouterSuggestion, RoutingStrategy,
 };
+pub use provider_health::{HealthStatus, ProviderHealth, ProviderHealthMonitor};
+pub use semantic_cache::SemanticCache;
@@ -0,0 +1,549 @@
+//! Sliding-window health tracking for LLM providers.
+//!
+//! Every provider call reports its latency and outcome. Calls are folded into
+//! one-minute buckets and the last `WINDOW_MINUTES` of them decide whether a
+//! provider is healthy, degraded or unhealthy; the router then moves degraded
+//! providers behind healthy ones. Buckets are mirrored to
+//! `llm_provider_health` so health survives restarts, and status changes are
+//! announced on `llm://provider-health`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::LLMError;
+use crate::router::Provider;
+
+const WINDOW_MINUTES: i64 = 15;
+/// Fewer calls than this are not enough to judge an error rate
+const MIN_SAMPLES: u32 = 3;
+const DEGRADED_ERROR_RATE: f64 = 0.2;
+const UNHEALTHY_ERROR_RATE: f64 = 0.5;
+const SLOW_AVERAGE_LATENCY_MS: u64 = 20_000;
+/// A rate limit this recent keeps the provider unhealthy
+const RATE_LIMIT_COOLDOWN_MINUTES: i64 = 2;
+const RETENTION_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    /// No calls in the window
+    Unknown,
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthStatus {
+    /// Routing priority; lower is tried first
+    pub fn rank(&self) -> u8 {
+        match self {
+            HealthStatus::Healthy | HealthStatus::Unknown => 0,
+            HealthStatus::Degraded => 1,
+            HealthStatus::Unhealthy => 2,
+        }
+    }
+}
+
+/// How a provider call ended, as far as provider health is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    Success,
+    Error,
+    RateLimited,
+}
+
+impl CallOutcome {
+    /// Classify a provider error. Errors caused by the request itself
+    /// (context length, content filters, unknown models, rejected
+    /// parameters) say nothing about
+    /// the provider and return `None`.
+    pub fn from_error(error: &LLMError) -> Option<Self> {
+        match error {
+            LLMError::RateLimitError(_) => Some(CallOutcome::RateLimited),
+            LLMError::ContextLengthError(_)
+            | LLMError::ContentFilterError(_)
+            | LLMError::ModelNotAvailable(_)
+            | LLMError::InvalidRequest(_) => None,
+            _ => Some(CallOutcome::Error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthTotals {
+    pub requests: u32,
+    pub errors: u32,
+    pub rate_limited: u32,
+    pub avg_latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub status: HealthStatus,
+    pub window_minutes: i64,
+    pub requests: u32,
+    pub errors: u32,
+    pub rate_limited: u32,
+    /// Errors and rate limits as a share of requests in the window
+    pub error_rate: f64,
+    pub avg_latency_ms: Option<u64>,
+    pub max_latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+    /// Persisted totals for the last 24 hours, when a database is attached
+    pub last_24h: Option<HealthTotals>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Bucket {
+    requests: u32,
+    errors: u32,
+    rate_limited: u32,
+    total_latency_ms: u64,
+    max_latency_ms: u64,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    buckets: HashMap<Provider, BTreeMap<i64, Bucket>>,
+    last_status: HashMap<Provider, HealthStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthChange {
+    provider: String,
+    previous: HealthStatus,
+    status: HealthStatus,
+}
+
+/// Shared provider health tracker consulted by every `LLMRouter`
+#[derive(Default)]
+pub struct ProviderHealthMonitor {
+    state: Mutex<MonitorState>,
+    db: Mutex<Option<Arc<Mutex<Connection>>>>,
+    app_handle: Mutex<Option<AppHandle>>,
+}
+
+impl ProviderHealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the current window from the database and start persisting buckets
+    pub fn attach(&self, db: Arc<Mutex<Connection>>, app_handle: Option<AppHandle>) -> Result<()> {
+        {
+            let conn = db
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Failed to lock database: {}", e))?;
+            let now = current_minute();
+            conn.execute(
+                "DELETE FROM llm_provider_health WHERE minute < ?1",
+                params![now - RETENTION_DAYS * 24 * 60],
+            )?;
+
+            let mut stmt = conn.prepare(
+                "SELECT provider, minute, requests, errors, rate_limited,
+                        total_latency_ms, max_latency_ms, last_error
+                 FROM llm_provider_health
+                 WHERE minute > ?1",
+            )?;
+            let rows = stmt
+                .query_map(params![now - WINDOW_MINUTES], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?,
+                        Bucket {
+                            requests: row.get(2)?,
+                            errors: row.get(3)?,
+                            rate_limited: row.get(4)?,
+                            total_latency_ms: row.get(5)?,
+                            max_latency_ms: row.get(6)?,
+                            last_error: row.get(7)?,
+                        },
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let mut state = self.lock_state();
+            state.buckets.clear();
+            for (provider, minute, bucket) in rows {
+                if let Some(provider) = Provider::from_string(&provider) {
+                    state
+                        .buckets
+                        .entry(provider)
+                        .or_default()
+                        .insert(minute, bucket);
+                }
+            }
+            let providers: Vec<Provider> = state.buckets.keys().copied().collect();
+            for provider in providers {
+                let status = health_at(&state, provider, now).status;
+                state.last_status.insert(provider, status);
+            }
+        }
+
+        *self.db.lock().unwrap_or_else(|e| e.into_inner()) = Some(db);
+        *self.app_handle.lock().unwrap_or_else(|e| e.into_inner()) = app_handle;
+        Ok(())
+    }
+
+    pub fn record_success(&self, provider: Provider, latency: Duration) {
+        self.record(provider, latency, CallOutcome::Success, None);
+    }
+
+    /// Record a failed call; errors that are the request's fault are ignored
+    pub fn record_error(&self, provider: Provider, latency: Duration, error: &LLMError) {
+        if let Some(outcome) = CallOutcome::from_error(error) {
+            self.record(provider, latency, outcome, Some(&error.to_string()));
+        }
+    }
+
+    pub fn record(
+        &self,
+        provider: Provider,
+        latency: Duration,
+        outcome: CallOutcome,
+        error: Option<&str>,
+    ) {
+        let minute = current_minute();
+        let latency_ms = latency.as_millis() as u64;
+        let error = error.map(|message| truncate(message, 500));
+
+        let change = {
+            let mut state = self.lock_state();
+            record_into(
+                &mut state,
+                provider,
+                minute,
+                latency_ms,
+                outcome,
+                error.clone(),
+            );
+            let status = health_at(&state, provider, minute).status;
+            let previous = state
+                .last_status
+                .insert(provider, status)
+                .unwrap_or(HealthStatus::Unknown);
+            (previous != status).then_some(HealthChange {
+                provider: provider.as_string().to_string(),
+                previous,
+                status,
+            })
+        };
+
+        if let Some(db) = self.db() {
+            if let Ok(conn) = db.lock() {
+                if let Err(e) = conn.execute(
+                    "INSERT INTO llm_provider_health
+                        (provider, minute, requests, errors, rate_limited,
+                         total_latency_ms, max_latency_ms, last_error)
+                     VALUES (?1, ?2, 1, ?3, ?4, ?5, ?5, ?6)
+                     ON CONFLICT(provider, minute) DO UPDATE SET
+                        requests = requests + 1,
+                        errors = errors + excluded.errors,
+                        rate_limited = rate_limited + excluded.rate_limited,
+                        total_latency_ms = total_latency_ms + excluded.total_latency_ms,
+                        max_latency_ms = MAX(max_latency_ms, excluded.max_latency_ms),
+                        last_error = COALESCE(excluded.last_error, last_error)",
+                    params![
+                        provider.as_string(),
+                        minute,
+                        (outcome == CallOutcome::Error) as u32,
+                        (outcome == CallOutcome::RateLimited) as u32,
+                        latency_ms as i64,
+                        error,
+                    ],
+                ) {
+                    tracing::warn!("Failed to record provider health: {}", e);
+                }
+            }
+        }
+
+        let Some(change) = change else {
+            return;
+        };
+        tracing::info!(
+            "LLM provider {} health changed from {:?} to {:?}",
+            change.provider,
+            change.previous,
+            change.status
+        );
+        let app_handle = self
+            .app_handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(app) = app_handle {
+            let _ = app.emit("llm://provider-health", &change);
+        }
+    }
+
+    pub fn status(&self, provider: Provider) -> HealthStatus {
+        health_at(&self.lock_state(), provider, current_minute()).status
+    }
+
+    /// Current window health for every provider seen recently, with
+    /// persisted 24-hour totals when a database is attached
+    pub fn report(&self) -> Vec<ProviderHealth> {
+        let now = current_minute();
+        let mut report: Vec<ProviderHealth> = {
+            let state = self.lock_state();
+            state
+                .buckets
+                .keys()
+                .map(|provider| health_at(&state, *provider, now))
+                .collect()
+        };
+
+        if let Some(db) = self.db() {
+            if let Ok(conn) = db.lock() {
+                match daily_totals(&conn, now) {
+                    Ok(totals) => {
+                        for (provider, totals) in totals {
+                            match report.iter_mut().find(|h| h.provider == provider) {
+                                Some(health) => health.last_24h = Some(totals),
+                                None => {
+                                    let mut health = empty_health(&provider);
+                                    health.last_24h = Some(totals);
+                                    report.push(health);
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to load provider health history: {}", e),
+                }
+            }
+        }
+
+        report.sort_by(|a, b| a.provider.cmp(&b.provider));
+        report
+    }
+
+    fn db(&self) -> Option<Arc<Mutex<Connection>>> {
+        self.db.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, MonitorState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn current_minute() -> i64 {
+    Utc::now().timestamp().div_euclid(60)
+}
+
+fn truncate(message: &str, max_chars: usize) -> String {
+    message.chars().take(max_chars).collect()
+}
+
+fn record_into(
+    state: &mut MonitorState,
+    provider: Provider,
+    minute: i64,
+    latency_ms: u64,
+    outcome: CallOutcome,
+    error: Option<String>,
+) {
+    let buckets = state.buckets.entry(provider).or_default();
+    let bucket = buckets.entry(minute).or_default();
+    bucket.requests += 1;
+    bucket.total_latency_ms += latency_ms;
+    bucket.max_latency_ms = bucket.max_latency_ms.max(latency_ms);
+    match outcome {
+        CallOutcome::Success => {}
+        CallOutcome::Error => bucket.errors += 1,
+        CallOutcome::RateLimited => bucket.rate_limited += 1,
+    }
+    if error.is_some() {
+        bucket.last_error = error;
+    }
+
+    // Only the window is kept in memory
+    buckets.retain(|bucket_minute, _| *bucket_minute > minute - WINDOW_MINUTES);
+}
+
+fn empty_health(provider: &str) -> ProviderHealth {
+    ProviderHealth {
+        provider: provider.to_string(),
+        status: HealthStatus::Unknown,
+        window_minutes: WINDOW_MINUTES,
+        requests: 0,
+        errors: 0,
+        rate_limited: 0,
+        error_rate: 0.0,
+        avg_latency_ms: None,
+        max_latency_ms: None,
+        last_error: None,
+        last_24h: None,
+    }
+}
+
+fn health_at(state: &MonitorState, provider: Provider, now: i64) -> ProviderHealth {
+    let mut health = empty_health(provider.as_string());
+    let Some(buckets) = state.buckets.get(&provider) else {
+        return health;
+    };
+
+    let mut total_latency_ms = 0u64;
+    let mut max_latency_ms = 0u64;
+    let mut rate_limited_recently = false;
+    for (minute, bucket) in buckets.range(now - WINDOW_MINUTES + 1..=now) {
+        health.requests += bucket.requests;
+        health.errors += bucket.errors;
+        health.rate_limited += bucket.rate_limited;
+        total_latency_ms += bucket.total_latency_ms;
+        max_latency_ms = max_latency_ms.max(bucket.max_latency_ms);
+        if bucket.last_error.is_some() {
+            health.last_error = bucket.last_error.clone();
+        }
+        if bucket.rate_limited > 0 && *minute > now - RATE_LIMIT_COOLDOWN_MINUTES {
+            rate_limited_recently = true;
+        }
+    }
+
+    if health.requests == 0 {
+        return health;
+    }
+
+    let failures = health.errors + health.rate_limited;
+    let avg_latency_ms = total_latency_ms / health.requests as u64;
+    health.error_rate = failures as f64 / health.requests as f64;
+    health.avg_latency_ms = Some(avg_latency_ms);
+    health.max_latency_ms = Some(max_latency_ms);
+
+    let judged = health.requests >= MIN_SAMPLES;
+    health.status =
+        if rate_limited_recently || (judged && health.error_rate >= UNHEALTHY_ERROR_RATE) {
+            HealthStatus::Unhealthy
+        } else if health.rate_limited > 0
+            || (judged && health.error_rate >= DEGRADED_ERROR_RATE)
+            || (!judged && failures > 0)
+            || avg_latency_ms >= SLOW_AVERAGE_LATENCY_MS
+        {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+    health
+}
+
+fn daily_totals(conn: &Connection, now: i64) -> Result<Vec<(String, HealthTotals)>> {
+    let mut stmt = conn.prepare(
+        "SELECT provider, SUM(requests), SUM(errors), SUM(rate_limited), SUM(total_latency_ms)
+         FROM llm_provider_health
+         WHERE minute > ?1
+         GROUP BY provider",
+    )?;
+    let rows = stmt
+        .query_map(params![now - 24 * 60], |row| {
+            let requests: u32 = row.get(1)?;
+            let total_latency_ms: i64 = row.get(4)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                HealthTotals {
+                    requests,
+                    errors: row.get(2)?,
+                    rate_limited: row.get(3)?,
+                    avg_latency_ms: (requests > 0)
+                        .then(|| total_latency_ms.max(0) as u64 / requests as u64),
+                },
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(state: &mut MonitorState, minute: i64, outcome: CallOutcome) {
+        record_into(state, Provider::OpenAI, minute, 800, outcome, None);
+    }
+
+    #[test]
+    fn test_error_rate_drives_status() {
+        let mut state = MonitorState::default();
+        let now = 1_000;
+        assert_eq!(
+            health_at(&state, Provider::OpenAI, now).status,
+            HealthStatus::Unknown
+        );
+
+        for _ in 0..4 {
+            record(&mut state, now, CallOutcome::Success);
+        }
+        assert_eq!(
+            health_at(&state, Provider::OpenAI, now).status,
+            HealthStatus::Healthy
+        );
+
+        record(&mut state, now, CallOutcome::Error);
+        let health = health_at(&state, Provider::OpenAI, now);
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert!((health.error_rate - 0.2).abs() < f64::EPSILON);
+
+        for _ in 0..5 {
+            record(&mut state, now, CallOutcome::Error);
+        }
+        assert_eq!(
+            health_at(&state, Provider::OpenAI, now).status,
+            HealthStatus::Unhealthy
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_cooldown_and_window_expiry() {
+        let mut state = MonitorState::default();
+        let start = 5_000;
+        for _ in 0..5 {
+            record(&mut state, start, CallOutcome::Success);
+        }
+        record(&mut state, start, CallOutcome::RateLimited);
+        assert_eq!(
+            health_at(&state, Provider::OpenAI, start).status,
+            HealthStatus::Unhealthy
+        );
+
+        // Past the cooldown the rate limit still counts against the provider
+        let later = start + RATE_LIMIT_COOLDOWN_MINUTES;
+        assert_eq!(
+            health_at(&state, Provider::OpenAI, later).status,
+            HealthStatus::Degraded
+        );
+
+        // Once the window has slid past, the provider starts fresh
+        let expired = start + WINDOW_MINUTES;
+        assert_eq!(
+            health_at(&state, Provider::OpenAI, expired).status,
+            HealthStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_request_errors_do_not_count_against_provider() {
+        assert_eq!(
+            CallOutcome::from_error(&LLMError::RateLimitError("429".to_string())),
+            Some(CallOutcome::RateLimited)
+        );
+        assert_eq!(
+            CallOutcome::from_error(&LLMError::NetworkError("connection reset".to_string())),
+            Some(CallOutcome::Error)
+        );
+        assert_eq!(
+            CallOutcome::from_error(&LLMError::ContextLengthError("128000 tokens".to_string())),
+            None
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use crate::router::sse_parser::{parse_sse_stream, StreamChunk};
-use crate::router::{ContentPart, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ToolCall};
+use crate::router::structured_output::{self, ANTHROPIC_TOOL_NAME};
+use crate::router::{
+    ContentPart, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ProviderHttpError, ToolCall,
+};
+use crate::security::SecretString;
 use futures_util::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -56,6 +60,8 @@ struct AnthropicRequest {
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<AnthropicTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -88,7 +94,7 @@ struct AnthropicUsage {
 }
 
 pub struct AnthropicProvider {
-    api_key: String,
+    api_key: SecretString,
     client: Client,
     base_url: String,
 }
@@ -96,7 +102,7 @@ pub struct AnthropicProvider {
 impl AnthropicProvider {
     pub fn new(api_key: String) -> Self {
         Self {
-            api_key,
+            api_key: api_key.into(),
             client: Client::new(),
             base_url: "https://api.anthropic.com/v1".to_string(),
         }
@@ -185,7 +191,7 @@ impl LLMProvider for AnthropicProvider {
         request: &LLMRequest,
     ) -> Result<LLMResponse, Box<dyn Error + Send + Sync>> {
         // ✅ Convert ToolDefinition to Anthropic format
-        let anthropic_tools = request.tools.as_ref().map(|tools| {
+        let mut anthropic_tools: Option<Vec<AnthropicTool>> = request.tools.as_ref().map(|tools| {
             tools
                 .iter()
                 .map(|tool| AnthropicTool {
@@ -196,6 +202,18 @@ impl LLMProvider for AnthropicProvider {
                 .collect()
         });
 
+        // Structured output is a single forced tool whose input is the reply
+        let tool_choice = request.response_format.as_ref().map(|format| {
+            anthropic_tools
+                .get_or_insert_with(Vec::new)
+                .push(AnthropicTool {
+                    name: ANTHROPIC_TOOL_NAME.to_string(),
+                    description: "Return the final response in the required format".to_string(),
+                    input_schema: structured_output::anthropic_tool_schema(format),
+                });
+            serde_json::json!({ "type": "tool", "name": ANTHROPIC_TOOL_NAME })
+        });
+
         // ✅ Extract system messages for top-level system parameter
         let system_message = request
             .messages
@@ -230,12 +248,13 @@ impl LLMProvider for AnthropicProvider {
             temperature: request.temperature,
             stream: if request.stream { Some(false) } else { None },
             tools: anthropic_tools,
+            tool_choice,
         };
 
         let response = self
             .client
             .post(format!("{}/messages", self.base_url))
-            .header("x-api-key", &self.api_key)
+            .header("x-api-key", self.api_key.expose_secret())
             .header("anthropic-version", "2023-06-01")
             .header("Content-Type", "application/json")
             .json(&anthropic_request)
@@ -243,12 +262,9 @@ impl LLMProvider for AnthropicProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("Anthropic", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Anthropic API error {}: {}", status, error_text).into());
+                .into());
         }
 
         let response_text = response.text().await?;
@@ -271,6 +287,11 @@ impl LLMProvider for AnthropicProvider {
                 AnthropicContent::Text { text } => {
                     text_content.push_str(text);
                 }
+                AnthropicContent::ToolUse { name, input, .. }
+                    if name == ANTHROPIC_TOOL_NAME && request.response_format.is_some() =>
+                {
+                    text_content = input.to_string();
+                }
                 AnthropicContent::ToolUse { id, name, input } => {
                     tool_calls.push(ToolCall {
                         id: id.clone(),
@@ -296,6 +317,7 @@ impl LLMProvider for AnthropicProvider {
                 .stop_reason
                 .as_ref()
                 .map(|reason| match reason.as_str() {
+                    "tool_use" if tool_calls.is_empty() => "stop".to_string(),
                     "tool_use" => "tool_calls".to_string(),
                     "end_turn" => "stop".to_string(),
                     "max_tokens" => "length".to_string(),
@@ -315,12 +337,13 @@ impl LLMProvider for AnthropicProvider {
                 Some(tool_calls)
             },
             finish_reason,
+            routing: None,
             ..LLMResponse::default()
         })
     }
 
     fn is_configured(&self) -> bool {
-        !self.api_key.is_empty() && self.api_key != "your-api-key-here"
+        !self.api_key.is_empty() && self.api_key.expose_secret() != "your-api-key-here"
     }
 
     fn name(&self) -> &str {
@@ -360,6 +383,13 @@ impl LLMProvider for AnthropicProvider {
             .iter()
             .filter(|m| m.role == "system")
             .map(|m| m.content.clone())
+            .chain(
+                // Forced tools do not stream as text, so ask for JSON instead
+                request
+                    .response_format
+                    .as_ref()
+                    .map(structured_output::instruction),
+            )
             .collect::<Vec<String>>()
             .join("\n\n");
 
@@ -388,6 +418,7 @@ impl LLMProvider for AnthropicProvider {
             temperature: request.temperature,
             stream: Some(true), // Enable streaming
             tools: anthropic_tools,
+            tool_choice: None,
         };
 
         tracing::debug!(
@@ -398,7 +429,7 @@ impl LLMProvider for AnthropicProvider {
         let response = self
             .client
             .post(format!("{}/messages", self.base_url))
-            .header("x-api-key", &self.api_key)
+            .header("x-api-key", self.api_key.expose_secret())
             .header("anthropic-version", "2023-06-01")
             .header("Content-Type", "application/json")
             .json(&anthropic_request)
@@ -406,12 +437,9 @@ impl LLMProvider for AnthropicProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("Anthropic", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Anthropic API error {}: {}", status, error_text).into());
+                .into());
         }
 
         tracing::debug!("Anthropic streaming response received, starting SSE parsing");
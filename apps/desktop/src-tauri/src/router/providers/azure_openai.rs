@@ -0,0 +1,129 @@
+use crate::router::providers::openai::OpenAIProvider;
+use crate::router::sse_parser::{parse_sse_stream, StreamChunk};
+use crate::router::{LLMProvider, LLMRequest, LLMResponse, Provider, ProviderHttpError};
+use crate::security::SecretString;
+use futures_util::Stream;
+use reqwest::Client;
+use std::error::Error;
+use std::pin::Pin;
+
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// Azure OpenAI speaks the OpenAI chat completions format, but the model is
+/// pinned by the deployment in the URL and the key goes in an `api-key`
+/// header. One provider instance targets one deployment.
+pub struct AzureOpenAIProvider {
+    api_key: SecretString,
+    client: Client,
+    endpoint: String,
+    deployment: String,
+    api_version: String,
+}
+
+impl AzureOpenAIProvider {
+    /// `endpoint` is the resource URL, e.g. `https://my-resource.openai.azure.com`
+    pub fn new(
+        api_key: String,
+        endpoint: String,
+        deployment: String,
+        api_version: Option<String>,
+    ) -> Self {
+        Self {
+            api_key: api_key.into(),
+            client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            deployment,
+            api_version: api_version.unwrap_or_else(|| DEFAULT_API_VERSION.to_string()),
+        }
+    }
+
+    pub fn chat_url(&self) -> String {
+        format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.endpoint,
+            urlencoding::encode(&self.deployment),
+            self.api_version
+        )
+    }
+
+    /// The deployment decides the model; its name stands in for the model so
+    /// that OpenAI's per-model parameter rules still apply to e.g. `o3-mini`
+    fn deployment_request(&self, request: &LLMRequest) -> LLMRequest {
+        let mut request = request.clone();
+        request.model = self.deployment.clone();
+        request
+    }
+
+    async fn post(
+        &self,
+        request: &LLMRequest,
+        stream: bool,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        let body = OpenAIProvider::request_body(&self.deployment_request(request), stream)?;
+
+        let response = self
+            .client
+            .post(self.chat_url())
+            .header("api-key", self.api_key.expose_secret())
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ProviderHttpError::from_response("Azure OpenAI", response)
+                .await
+                .into());
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMProvider for AzureOpenAIProvider {
+    async fn send_message(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<LLMResponse, Box<dyn Error + Send + Sync>> {
+        let response = self.post(request, false).await?;
+        let response_text = response.text().await?;
+        tracing::debug!("Azure OpenAI response body: {}", response_text);
+
+        let mut response = OpenAIProvider::parse_response(&response_text, "Azure OpenAI")?;
+        // Azure bills differently from api.openai.com; leave cost to the router's table
+        response.cost = None;
+        Ok(response)
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.api_key.is_empty() && !self.endpoint.is_empty() && !self.deployment.is_empty()
+    }
+
+    fn name(&self) -> &str {
+        "Azure OpenAI"
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_function_calling(&self) -> bool {
+        true
+    }
+
+    async fn send_message_streaming(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<StreamChunk, Box<dyn Error + Send + Sync>>> + Send>>,
+        Box<dyn Error + Send + Sync>,
+    > {
+        tracing::debug!(
+            "Starting Azure OpenAI streaming request for deployment: {}",
+            self.deployment
+        );
+        let response = self.post(request, true).await?;
+        Ok(Box::pin(parse_sse_stream(response, Provider::AzureOpenAI)))
+    }
+}
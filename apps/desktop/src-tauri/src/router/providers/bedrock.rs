@@ -0,0 +1,643 @@
+use crate::router::sse_parser::{StreamChunk, TokenUsage};
+use crate::router::{
+    ContentPart, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ProviderHttpError, ToolCall,
+    ToolChoice,
+};
+use crate::security::SecretString;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::pin::Pin;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SERVICE: &str = "bedrock";
+
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: SecretString,
+    pub session_token: Option<SecretString>,
+}
+
+impl AwsCredentials {
+    /// Standard `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`
+    pub fn from_env() -> Option<Self> {
+        let access_key_id = std::env::var("AWS_ACCESS_KEY_ID").ok()?;
+        let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY").ok()?;
+        Some(Self {
+            access_key_id,
+            secret_access_key: secret_access_key.into(),
+            session_token: std::env::var("AWS_SESSION_TOKEN")
+                .ok()
+                .map(SecretString::from),
+        })
+    }
+}
+
+/// `AWS_REGION`, then `AWS_DEFAULT_REGION`
+pub fn region_from_env() -> Option<String> {
+    std::env::var("AWS_REGION")
+        .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+        .ok()
+}
+
+/// AWS Bedrock through the model-agnostic Converse API, signed with SigV4
+pub struct BedrockProvider {
+    credentials: AwsCredentials,
+    region: String,
+    client: Client,
+    base_url: String,
+}
+
+impl BedrockProvider {
+    pub fn new(credentials: AwsCredentials, region: String) -> Self {
+        let base_url = format!("https://bedrock-runtime.{}.amazonaws.com", region);
+        Self {
+            credentials,
+            region,
+            client: Client::new(),
+            base_url,
+        }
+    }
+
+    /// Route through a VPC or FIPS endpoint instead of the public one
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn image_format(format: ImageFormat) -> &'static str {
+        match format {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpeg",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    /// Converse request body. System messages move to `system`, tool results
+    /// become user `toolResult` blocks, and consecutive turns from the same
+    /// role are merged because Bedrock requires alternating roles.
+    pub fn converse_body(request: &LLMRequest) -> Value {
+        let mut system = Vec::new();
+        let mut messages: Vec<(String, Vec<Value>)> = Vec::new();
+
+        for message in &request.messages {
+            let mut blocks = Vec::new();
+            let role = match message.role.as_str() {
+                "system" => {
+                    if !message.content.is_empty() {
+                        system.push(json!({ "text": message.content }));
+                    }
+                    continue;
+                }
+                "tool" => {
+                    blocks.push(json!({
+                        "toolResult": {
+                            "toolUseId": message.tool_call_id.clone().unwrap_or_default(),
+                            "content": [{ "text": message.content }],
+                        }
+                    }));
+                    "user"
+                }
+                "assistant" => {
+                    if !message.content.is_empty() {
+                        blocks.push(json!({ "text": message.content }));
+                    }
+                    for call in message.tool_calls.iter().flatten() {
+                        let input = serde_json::from_str::<Value>(&call.arguments)
+                            .unwrap_or_else(|_| json!({}));
+                        blocks.push(json!({
+                            "toolUse": {
+                                "toolUseId": call.id,
+                                "name": call.name,
+                                "input": input,
+                            }
+                        }));
+                    }
+                    "assistant"
+                }
+                _ => {
+                    if !message.content.is_empty() {
+                        blocks.push(json!({ "text": message.content }));
+                    }
+                    for part in message.multimodal_content.iter().flatten() {
+                        match part {
+                            ContentPart::Text { text } if !text.is_empty() => {
+                                blocks.push(json!({ "text": text }));
+                            }
+                            ContentPart::Text { .. } => {}
+                            ContentPart::Image { image } => {
+                                let bytes = base64::Engine::encode(
+                                    &base64::engine::general_purpose::STANDARD,
+                                    &image.data,
+                                );
+                                blocks.push(json!({
+                                    "image": {
+                                        "format": Self::image_format(image.format),
+                                        "source": { "bytes": bytes },
+                                    }
+                                }));
+                            }
+                        }
+                    }
+                    "user"
+                }
+            };
+
+            if blocks.is_empty() {
+                continue;
+            }
+            match messages.last_mut() {
+                Some((last_role, content)) if last_role == role => content.extend(blocks),
+                _ => messages.push((role.to_string(), blocks)),
+            }
+        }
+
+        let mut body = json!({
+            "messages": messages
+                .into_iter()
+                .map(|(role, content)| json!({ "role": role, "content": content }))
+                .collect::<Vec<_>>(),
+        });
+
+        if !system.is_empty() {
+            body["system"] = Value::Array(system);
+        }
+
+        let mut inference = serde_json::Map::new();
+        if let Some(max_tokens) = request.max_tokens {
+            inference.insert("maxTokens".to_string(), json!(max_tokens));
+        }
+        if let Some(temperature) = request.temperature {
+            inference.insert("temperature".to_string(), json!(temperature));
+        }
+        if !inference.is_empty() {
+            body["inferenceConfig"] = Value::Object(inference);
+        }
+
+        if let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) {
+            let mut tool_config = json!({
+                "tools": tools
+                    .iter()
+                    .map(|tool| json!({
+                        "toolSpec": {
+                            "name": tool.name,
+                            "description": tool.description,
+                            "inputSchema": { "json": tool.parameters },
+                        }
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            // Converse has no "none"; leaving the choice out lets the model decide
+            let choice = match &request.tool_choice {
+                Some(ToolChoice::Auto) => Some(json!({ "auto": {} })),
+                Some(ToolChoice::Required) => Some(json!({ "any": {} })),
+                Some(ToolChoice::Specific(name)) => Some(json!({ "tool": { "name": name } })),
+                Some(ToolChoice::None) | None => None,
+            };
+            if let Some(choice) = choice {
+                tool_config["toolChoice"] = choice;
+            }
+            body["toolConfig"] = tool_config;
+        }
+
+        body
+    }
+
+    async fn post(
+        &self,
+        model: &str,
+        action: &str,
+        body: &Value,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        let payload = serde_json::to_vec(body)?;
+        let path = format!("/model/{}/{}", urlencoding::encode(model), action);
+        let url = url::Url::parse(&format!("{}{}", self.base_url, path))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("Invalid Bedrock endpoint: {}", self.base_url).into()),
+        };
+
+        let mut headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("host".to_string(), host),
+        ];
+        let authorization = sign_v4(
+            "POST",
+            &path,
+            &mut headers,
+            &payload,
+            &self.credentials,
+            &self.region,
+            SERVICE,
+            Utc::now(),
+        );
+
+        let mut builder = self.client.post(url.as_str());
+        for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let response = builder
+            .header("authorization", authorization)
+            .body(payload)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ProviderHttpError::from_response("Bedrock", response)
+                .await
+                .into());
+        }
+
+        Ok(response)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseResponse {
+    output: ConverseOutput,
+    stop_reason: Option<String>,
+    usage: Option<ConverseUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseOutput {
+    message: Option<ConverseMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseMessage {
+    #[serde(default)]
+    content: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+    total_tokens: Option<u32>,
+}
+
+#[async_trait::async_trait]
+impl LLMProvider for BedrockProvider {
+    async fn send_message(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<LLMResponse, Box<dyn Error + Send + Sync>> {
+        let body = Self::converse_body(request);
+        let response = self.post(&request.model, "converse", &body).await?;
+        let response_text = response.text().await?;
+        tracing::debug!("Bedrock response body: {}", response_text);
+
+        let converse: ConverseResponse = serde_json::from_str(&response_text).map_err(|e| {
+            format!(
+                "Failed to parse Bedrock response: {}. Body: {}",
+                e, response_text
+            )
+        })?;
+
+        let mut content = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in converse
+            .output
+            .message
+            .map(|message| message.content)
+            .unwrap_or_default()
+        {
+            if let Some(text) = block.get("text").and_then(Value::as_str) {
+                content.push(text.to_string());
+            } else if let Some(tool_use) = block.get("toolUse") {
+                tool_calls.push(ToolCall {
+                    id: tool_use["toolUseId"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    name: tool_use["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: tool_use
+                        .get("input")
+                        .cloned()
+                        .unwrap_or_else(|| json!({}))
+                        .to_string(),
+                });
+            }
+        }
+
+        let (prompt_tokens, completion_tokens, total_tokens) = match converse.usage {
+            Some(usage) => (
+                Some(usage.input_tokens),
+                Some(usage.output_tokens),
+                Some(
+                    usage
+                        .total_tokens
+                        .unwrap_or(usage.input_tokens + usage.output_tokens),
+                ),
+            ),
+            None => (None, None, None),
+        };
+
+        Ok(LLMResponse {
+            content: content.join("\n"),
+            tokens: total_tokens,
+            prompt_tokens,
+            completion_tokens,
+            // Priced per model and region by the router's cost table
+            cost: None,
+            model: request.model.clone(),
+            tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            finish_reason: converse.stop_reason,
+            routing: None,
+            ..LLMResponse::default()
+        })
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.credentials.access_key_id.is_empty()
+            && !self.credentials.secret_access_key.is_empty()
+            && !self.region.is_empty()
+    }
+
+    fn name(&self) -> &str {
+        "AWS Bedrock"
+    }
+
+    fn supports_vision(&self) -> bool {
+        true // Claude, Nova and Llama 3.2 vision models accept images through Converse
+    }
+
+    fn supports_function_calling(&self) -> bool {
+        true
+    }
+
+    async fn send_message_streaming(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<StreamChunk, Box<dyn Error + Send + Sync>>> + Send>>,
+        Box<dyn Error + Send + Sync>,
+    > {
+        tracing::debug!(
+            "Starting Bedrock streaming request for model: {}",
+            request.model
+        );
+        let body = Self::converse_body(request);
+        let response = self.post(&request.model, "converse-stream", &body).await?;
+        Ok(Box::pin(event_stream(response, request.model.clone())))
+    }
+}
+
+/// Sign a request with AWS Signature Version 4. `path` must already be
+/// URI-encoded as sent; adds `x-amz-date` (and the session token) to
+/// `headers` and returns the `Authorization` header value.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_v4(
+    method: &str,
+    path: &str,
+    headers: &mut Vec<(String, String)>,
+    payload: &[u8],
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+
+    headers.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        headers.push((
+            "x-amz-security-token".to_string(),
+            token.expose_secret().to_string(),
+        ));
+    }
+
+    let mut canonical: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+        .collect();
+    canonical.sort();
+    let canonical_headers: String = canonical
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = canonical
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    // Every service but S3 encodes each path segment a second time
+    let canonical_uri = path
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method,
+        canonical_uri,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(payload))
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date_stamp, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [date_stamp.as_str(), region, service, "aws4_request"]
+        .iter()
+        .fold(
+            format!("AWS4{}", credentials.secret_access_key.expose_secret()).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// One frame of the `application/vnd.amazon.eventstream` encoding
+#[derive(Debug)]
+pub struct EventMessage {
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+}
+
+/// Decode the first complete frame in `buffer`, returning it with the number
+/// of bytes consumed, or `None` until more bytes arrive. Only string headers
+/// are kept; CRCs are not checked since TLS already guards the transport.
+pub fn decode_event(buffer: &[u8]) -> Result<Option<(EventMessage, usize)>, String> {
+    const PRELUDE: usize = 12;
+    const TRAILER: usize = 4;
+
+    if buffer.len() < PRELUDE {
+        return Ok(None);
+    }
+    let read_u32 = |at: usize| {
+        u32::from_be_bytes([buffer[at], buffer[at + 1], buffer[at + 2], buffer[at + 3]]) as usize
+    };
+    let total_len = read_u32(0);
+    let headers_len = read_u32(4);
+    if total_len < PRELUDE + TRAILER + headers_len {
+        return Err(format!(
+            "Malformed event-stream frame of {} bytes",
+            total_len
+        ));
+    }
+    if buffer.len() < total_len {
+        return Ok(None);
+    }
+
+    let mut headers = HashMap::new();
+    let header_bytes = &buffer[PRELUDE..PRELUDE + headers_len];
+    let mut at = 0;
+    while at < header_bytes.len() {
+        let truncated = || "Truncated event-stream header".to_string();
+        let name_len = *header_bytes.get(at).ok_or_else(truncated)? as usize;
+        let name = header_bytes
+            .get(at + 1..at + 1 + name_len)
+            .ok_or_else(truncated)?;
+        at += 1 + name_len;
+        let value_type = *header_bytes.get(at).ok_or_else(truncated)?;
+        at += 1;
+        let value_len = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len = header_bytes.get(at..at + 2).ok_or_else(truncated)?;
+                at += 2;
+                u16::from_be_bytes([len[0], len[1]]) as usize
+            }
+            other => return Err(format!("Unknown event-stream header type {}", other)),
+        };
+        let value = header_bytes.get(at..at + value_len).ok_or_else(truncated)?;
+        if value_type == 7 {
+            headers.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                String::from_utf8_lossy(value).into_owned(),
+            );
+        }
+        at += value_len;
+    }
+
+    let payload = buffer[PRELUDE + headers_len..total_len - TRAILER].to_vec();
+    Ok(Some((EventMessage { headers, payload }, total_len)))
+}
+
+/// Map a ConverseStream event onto a chunk; `None` for events with nothing to report
+fn chunk_from_event(
+    message: &EventMessage,
+    model: &str,
+) -> Result<Option<StreamChunk>, Box<dyn Error + Send + Sync>> {
+    let message_type = message.headers.get(":message-type").map(String::as_str);
+    if message_type != Some("event") {
+        let kind = message
+            .headers
+            .get(":exception-type")
+            .or_else(|| message.headers.get(":error-code"))
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string());
+        return Err(format!(
+            "Bedrock stream error ({}): {}",
+            kind,
+            String::from_utf8_lossy(&message.payload)
+        )
+        .into());
+    }
+
+    let payload: Value = serde_json::from_slice(&message.payload)?;
+    let chunk = |content: String, done: bool| StreamChunk {
+        content,
+        done,
+        finish_reason: None,
+        model: Some(model.to_string()),
+        usage: None,
+    };
+
+    let event_type = message.headers.get(":event-type").map(String::as_str);
+    Ok(match event_type {
+        Some("contentBlockDelta") => payload["delta"]["text"]
+            .as_str()
+            .map(|text| chunk(text.to_string(), false)),
+        Some("messageStop") => Some(StreamChunk {
+            finish_reason: payload["stopReason"].as_str().map(str::to_string),
+            ..chunk(String::new(), false)
+        }),
+        // Metadata always comes last, after messageStop
+        Some("metadata") => {
+            let usage = &payload["usage"];
+            let count = |key: &str| usage[key].as_u64().map(|n| n as u32);
+            Some(StreamChunk {
+                usage: Some(TokenUsage {
+                    prompt_tokens: count("inputTokens"),
+                    completion_tokens: count("outputTokens"),
+                    total_tokens: count("totalTokens"),
+                }),
+                ..chunk(String::new(), true)
+            })
+        }
+        _ => None,
+    })
+}
+
+fn event_stream(
+    response: reqwest::Response,
+    model: String,
+) -> impl Stream<Item = Result<StreamChunk, Box<dyn Error + Send + Sync>>> + Send {
+    let state = (response.bytes_stream().boxed(), Vec::new(), false);
+    futures_util::stream::unfold(state, move |(mut body, mut buffer, finished)| {
+        let model = model.clone();
+        async move {
+            if finished {
+                return None;
+            }
+            loop {
+                match decode_event(&buffer) {
+                    Ok(Some((message, used))) => {
+                        buffer.drain(..used);
+                        match chunk_from_event(&message, &model) {
+                            Ok(Some(chunk)) => return Some((Ok(chunk), (body, buffer, false))),
+                            Ok(None) => continue,
+                            Err(e) => return Some((Err(e), (body, buffer, true))),
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => return Some((Err(e.into()), (body, buffer, true))),
+                }
+
+                match body.next().await {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(e)) => return Some((Err(e.into()), (body, buffer, true))),
+                    None => return None,
+                }
+            }
+        }
+    })
+}
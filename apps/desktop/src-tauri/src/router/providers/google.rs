@@ -1,5 +1,9 @@
 use crate::router::sse_parser::{parse_sse_stream, StreamChunk};
-use crate::router::{ContentPart, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ToolCall};
+use crate::router::structured_output;
+use crate::router::{
+    ContentPart, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ProviderHttpError, ToolCall,
+};
+use crate::security::SecretString;
 use futures_util::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -79,6 +83,10 @@ struct GoogleGenerationConfig {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_schema: Option<Value>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -104,7 +112,7 @@ struct GoogleUsageMetadata {
 }
 
 pub struct GoogleProvider {
-    api_key: String,
+    api_key: SecretString,
     client: Client,
     base_url: String,
 }
@@ -112,7 +120,7 @@ pub struct GoogleProvider {
 impl GoogleProvider {
     pub fn new(api_key: String) -> Self {
         Self {
-            api_key,
+            api_key: api_key.into(),
             client: Client::new(),
             base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
         }
@@ -229,13 +237,24 @@ impl LLMProvider for GoogleProvider {
             generation_config: Some(GoogleGenerationConfig {
                 temperature: request.temperature,
                 max_output_tokens: request.max_tokens,
+                response_mime_type: request
+                    .response_format
+                    .as_ref()
+                    .map(|_| "application/json".to_string()),
+                response_schema: request
+                    .response_format
+                    .as_ref()
+                    .and_then(|format| format.schema())
+                    .map(structured_output::gemini_schema),
             }),
             tools: google_tools,
         };
 
         let url = format!(
             "{}/models/{}:generateContent?key={}",
-            self.base_url, request.model, self.api_key
+            self.base_url,
+            request.model,
+            self.api_key.expose_secret()
         );
 
         let response = self
@@ -247,12 +266,9 @@ impl LLMProvider for GoogleProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("Google", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Google API error {}: {}", status, error_text).into());
+                .into());
         }
 
         let google_response: GoogleResponse = response.json().await?;
@@ -325,12 +341,13 @@ impl LLMProvider for GoogleProvider {
                 Some(tool_calls)
             },
             finish_reason,
+            routing: None,
             ..LLMResponse::default()
         })
     }
 
     fn is_configured(&self) -> bool {
-        !self.api_key.is_empty() && self.api_key != "your-api-key-here"
+        !self.api_key.is_empty() && self.api_key.expose_secret() != "your-api-key-here"
     }
 
     fn name(&self) -> &str {
@@ -378,6 +395,15 @@ impl LLMProvider for GoogleProvider {
             generation_config: Some(GoogleGenerationConfig {
                 temperature: request.temperature,
                 max_output_tokens: request.max_tokens,
+                response_mime_type: request
+                    .response_format
+                    .as_ref()
+                    .map(|_| "application/json".to_string()),
+                response_schema: request
+                    .response_format
+                    .as_ref()
+                    .and_then(|format| format.schema())
+                    .map(structured_output::gemini_schema),
             }),
             tools: google_tools,
         };
@@ -390,7 +416,9 @@ impl LLMProvider for GoogleProvider {
         // Google uses streamGenerateContent endpoint for streaming
         let url = format!(
             "{}/models/{}:streamGenerateContent?key={}&alt=sse",
-            self.base_url, request.model, self.api_key
+            self.base_url,
+            request.model,
+            self.api_key.expose_secret()
         );
 
         let response = self
@@ -402,12 +430,9 @@ impl LLMProvider for GoogleProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("Google", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Google API error {}: {}", status, error_text).into());
+                .into());
         }
 
         tracing::debug!("Google streaming response received, starting SSE parsing");
@@ -2,7 +2,11 @@
  * Mistral AI Provider (Mistral Large 2, Codestral)
  * OpenAI-compatible API at https://api.mistral.ai/v1
  */
-use crate::router::{LLMProvider, LLMRequest, LLMResponse, ToolCall, ToolChoice, ToolDefinition};
+use crate::router::structured_output;
+use crate::router::{
+    LLMProvider, LLMRequest, LLMResponse, ProviderHttpError, ToolCall, ToolChoice, ToolDefinition,
+};
+use crate::security::SecretString;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -11,21 +15,22 @@ use std::error::Error;
 const MISTRAL_API_BASE: &str = "https://api.mistral.ai/v1";
 
 pub struct MistralProvider {
-    api_key: Option<String>,
+    api_key: Option<SecretString>,
     client: Client,
 }
 
 impl MistralProvider {
     pub fn new(api_key: Option<String>) -> Self {
         Self {
-            api_key,
+            api_key: api_key.map(SecretString::from),
             client: Client::new(),
         }
     }
 
     fn get_api_key(&self) -> Result<&str, Box<dyn Error + Send + Sync>> {
         self.api_key
-            .as_deref()
+            .as_ref()
+            .map(SecretString::expose_secret)
             .ok_or_else(|| "Mistral API key not configured".into())
     }
 
@@ -126,6 +131,8 @@ struct MistralRequest {
     tools: Option<Vec<MistralTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<MistralToolChoiceValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
     stream: bool,
 }
 
@@ -228,6 +235,10 @@ impl LLMProvider for MistralProvider {
                 .tool_choice
                 .as_ref()
                 .and_then(Self::convert_tool_choice),
+            response_format: request
+                .response_format
+                .as_ref()
+                .map(|format| structured_output::openai_response_format(format, true)),
             stream: false,
         };
 
@@ -241,8 +252,9 @@ impl LLMProvider for MistralProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Mistral API error: {}", error_text).into());
+            return Err(ProviderHttpError::from_response("Mistral", response)
+                .await
+                .into());
         }
 
         let mistral_response: MistralResponse = response.json().await?;
@@ -274,6 +286,7 @@ impl LLMProvider for MistralProvider {
             cached: false,
             tool_calls,
             finish_reason: choice.finish_reason.clone(),
+            routing: None,
         })
     }
 
@@ -14,7 +14,7 @@
  * Note: Larger models (405B) require significant VRAM/RAM
  */
 use crate::router::sse_parser::{parse_sse_stream, StreamChunk};
-use crate::router::{ContentPart, LLMProvider, LLMRequest, LLMResponse};
+use crate::router::{ContentPart, LLMProvider, LLMRequest, LLMResponse, ProviderHttpError};
 use futures_util::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -37,6 +37,9 @@ struct OllamaRequest {
     options: Option<OllamaOptions>,
     #[serde(skip_serializing_if = "Option::is_none")]
     images: Option<Vec<String>>, // base64 encoded images
+    /// `"json"` or a JSON Schema the reply must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -160,6 +163,12 @@ impl LLMProvider for OllamaProvider {
                 num_predict: request.max_tokens,
             }),
             images,
+            format: request.response_format.as_ref().map(|format| {
+                format
+                    .schema()
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::Value::from("json"))
+            }),
         };
 
         let response = self
@@ -171,12 +180,9 @@ impl LLMProvider for OllamaProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("Ollama", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Ollama API error {}: {}", status, error_text).into());
+                .into());
         }
 
         let ollama_response: OllamaResponse = response.json().await?;
@@ -262,6 +268,12 @@ impl LLMProvider for OllamaProvider {
                 num_predict: request.max_tokens,
             }),
             images,
+            format: request.response_format.as_ref().map(|format| {
+                format
+                    .schema()
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::Value::from("json"))
+            }),
         };
 
         tracing::debug!(
@@ -278,12 +290,9 @@ impl LLMProvider for OllamaProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("Ollama", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Ollama API error {}: {}", status, error_text).into());
+                .into());
         }
 
         tracing::debug!("Ollama streaming response received, starting JSON line parsing");
@@ -1,8 +1,10 @@
 use crate::router::sse_parser::{parse_sse_stream, StreamChunk};
+use crate::router::structured_output;
 use crate::router::{
-    ContentPart, ImageDetail, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ToolCall,
-    ToolChoice, ToolDefinition,
+    ContentPart, ImageDetail, ImageFormat, LLMProvider, LLMRequest, LLMResponse, ProviderHttpError,
+    ToolCall, ToolChoice, ToolDefinition,
 };
+use crate::security::SecretString;
 use futures_util::Stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -103,6 +105,8 @@ struct OpenAIRequest {
     tools: Option<Vec<OpenAITool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<OpenAIToolChoiceValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -127,7 +131,7 @@ struct OpenAIUsage {
 }
 
 pub struct OpenAIProvider {
-    api_key: String,
+    api_key: SecretString,
     client: Client,
     base_url: String,
 }
@@ -135,7 +139,7 @@ pub struct OpenAIProvider {
 impl OpenAIProvider {
     pub fn new(api_key: String) -> Self {
         Self {
-            api_key,
+            api_key: api_key.into(),
             client: Client::new(),
             base_url: "https://api.openai.com/v1".to_string(),
         }
@@ -276,19 +280,26 @@ impl OpenAIProvider {
             })
             .collect()
     }
-}
 
-#[async_trait::async_trait]
-impl LLMProvider for OpenAIProvider {
-    async fn send_message(
-        &self,
-        request: &LLMRequest,
-    ) -> Result<LLMResponse, Box<dyn Error + Send + Sync>> {
+    /// Chat completions body. Streaming requests send plain message history
+    /// without tool call bookkeeping.
+    fn build_request(request: &LLMRequest, stream: bool) -> OpenAIRequest {
         let uses_new_param = Self::uses_max_completion_tokens(&request.model);
 
-        let openai_request = OpenAIRequest {
-            model: request.model.clone(),
-            messages: request
+        let messages = if stream {
+            request
+                .messages
+                .iter()
+                .map(|m| OpenAIMessage {
+                    role: m.role.clone(),
+                    content: Self::convert_content(&m.content, m.multimodal_content.as_ref()),
+                    tool_calls: None,
+                    tool_call_id: None,
+                    name: None,
+                })
+                .collect()
+        } else {
+            request
                 .messages
                 .iter()
                 .map(|m| {
@@ -330,7 +341,12 @@ impl LLMProvider for OpenAIProvider {
 
                     msg
                 })
-                .collect(),
+                .collect()
+        };
+
+        OpenAIRequest {
+            model: request.model.clone(),
+            messages,
             temperature: request.temperature,
             max_tokens: if uses_new_param {
                 None
@@ -342,42 +358,40 @@ impl LLMProvider for OpenAIProvider {
             } else {
                 None
             },
-            stream: if request.stream { Some(false) } else { None },
+            stream: if stream {
+                Some(true)
+            } else if request.stream {
+                Some(false)
+            } else {
+                None
+            },
             tools: request.tools.as_ref().map(|t| Self::convert_tools(t)),
             tool_choice: request
                 .tool_choice
                 .as_ref()
                 .and_then(Self::convert_tool_choice),
-        };
-
-        let response = self
-            .client
-            .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&openai_request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("OpenAI API error {}: {}", status, error_text).into());
+            response_format: request
+                .response_format
+                .as_ref()
+                .map(|format| structured_output::openai_response_format(format, true)),
         }
+    }
 
-        let response_text = response.text().await?;
-        tracing::debug!("OpenAI response body: {}", response_text);
+    /// Chat completions request body, shared with Azure OpenAI deployments
+    pub(crate) fn request_body(
+        request: &LLMRequest,
+        stream: bool,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(Self::build_request(request, stream))
+    }
 
-        let openai_response: OpenAIResponse =
-            serde_json::from_str(&response_text).map_err(|e| {
-                format!(
-                    "Failed to parse OpenAI response: {}. Body: {}",
-                    e, response_text
-                )
-            })?;
+    /// Parse a chat completions reply; `vendor` names the API in errors
+    pub(crate) fn parse_response(
+        body: &str,
+        vendor: &str,
+    ) -> Result<LLMResponse, Box<dyn Error + Send + Sync>> {
+        let openai_response: OpenAIResponse = serde_json::from_str(body)
+            .map_err(|e| format!("Failed to parse {} response: {}. Body: {}", vendor, e, body))?;
 
         let choice = openai_response
             .choices
@@ -421,12 +435,46 @@ impl LLMProvider for OpenAIProvider {
             model: openai_response.model,
             tool_calls,
             finish_reason: choice.finish_reason.clone(),
+            routing: None,
             ..LLMResponse::default()
         })
     }
+}
+
+#[async_trait::async_trait]
+impl LLMProvider for OpenAIProvider {
+    async fn send_message(
+        &self,
+        request: &LLMRequest,
+    ) -> Result<LLMResponse, Box<dyn Error + Send + Sync>> {
+        let openai_request = Self::build_request(request, false);
+
+        let response = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.api_key.expose_secret()),
+            )
+            .header("Content-Type", "application/json")
+            .json(&openai_request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ProviderHttpError::from_response("OpenAI", response)
+                .await
+                .into());
+        }
+
+        let response_text = response.text().await?;
+        tracing::debug!("OpenAI response body: {}", response_text);
+
+        Self::parse_response(&response_text, "OpenAI")
+    }
 
     fn is_configured(&self) -> bool {
-        !self.api_key.is_empty() && self.api_key != "your-api-key-here"
+        !self.api_key.is_empty() && self.api_key.expose_secret() != "your-api-key-here"
     }
 
     fn name(&self) -> &str {
@@ -448,39 +496,7 @@ impl LLMProvider for OpenAIProvider {
         Pin<Box<dyn Stream<Item = Result<StreamChunk, Box<dyn Error + Send + Sync>>> + Send>>,
         Box<dyn Error + Send + Sync>,
     > {
-        let uses_new_param = Self::uses_max_completion_tokens(&request.model);
-
-        let openai_request = OpenAIRequest {
-            model: request.model.clone(),
-            messages: request
-                .messages
-                .iter()
-                .map(|m| OpenAIMessage {
-                    role: m.role.clone(),
-                    content: Self::convert_content(&m.content, m.multimodal_content.as_ref()),
-                    tool_calls: None,
-                    tool_call_id: None,
-                    name: None,
-                })
-                .collect(),
-            temperature: request.temperature,
-            max_tokens: if uses_new_param {
-                None
-            } else {
-                request.max_tokens
-            },
-            max_completion_tokens: if uses_new_param {
-                request.max_tokens
-            } else {
-                None
-            },
-            stream: Some(true),
-            tools: request.tools.as_ref().map(|t| Self::convert_tools(t)),
-            tool_choice: request
-                .tool_choice
-                .as_ref()
-                .and_then(Self::convert_tool_choice),
-        };
+        let openai_request = Self::build_request(request, true);
 
         tracing::debug!(
             "Starting OpenAI streaming request for model: {}",
@@ -490,19 +506,19 @@ impl LLMProvider for OpenAIProvider {
         let response = self
             .client
             .post(format!("{}/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.api_key.expose_secret()),
+            )
             .header("Content-Type", "application/json")
             .json(&openai_request)
             .send()
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("OpenAI", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("OpenAI API error {}: {}", status, error_text).into());
+                .into());
         }
 
         tracing::debug!("OpenAI streaming response received, starting SSE parsing");
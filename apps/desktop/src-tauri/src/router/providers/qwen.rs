@@ -2,7 +2,11 @@
  * Qwen Provider (Alibaba Cloud - Qwen2.5-Max, Qwen3-Coder)
  * OpenAI-compatible API at https://dashscope-intl.aliyuncs.com/compatible-mode/v1
  */
-use crate::router::{LLMProvider, LLMRequest, LLMResponse, ToolCall, ToolChoice, ToolDefinition};
+use crate::router::structured_output;
+use crate::router::{
+    LLMProvider, LLMRequest, LLMResponse, ProviderHttpError, ToolCall, ToolChoice, ToolDefinition,
+};
+use crate::security::SecretString;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -11,21 +15,22 @@ use std::error::Error;
 const QWEN_API_BASE: &str = "https://dashscope-intl.aliyuncs.com/compatible-mode/v1";
 
 pub struct QwenProvider {
-    api_key: Option<String>,
+    api_key: Option<SecretString>,
     client: Client,
 }
 
 impl QwenProvider {
     pub fn new(api_key: Option<String>) -> Self {
         Self {
-            api_key,
+            api_key: api_key.map(SecretString::from),
             client: Client::new(),
         }
     }
 
     fn get_api_key(&self) -> Result<&str, Box<dyn Error + Send + Sync>> {
         self.api_key
-            .as_deref()
+            .as_ref()
+            .map(SecretString::expose_secret)
             .ok_or_else(|| "Qwen API key not configured".into())
     }
 
@@ -126,6 +131,8 @@ struct QwenRequest {
     tools: Option<Vec<QwenTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<QwenToolChoiceValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
     stream: bool,
 }
 
@@ -228,6 +235,10 @@ impl LLMProvider for QwenProvider {
                 .tool_choice
                 .as_ref()
                 .and_then(Self::convert_tool_choice),
+            response_format: request
+                .response_format
+                .as_ref()
+                .map(|format| structured_output::openai_response_format(format, false)),
             stream: false,
         };
 
@@ -241,8 +252,9 @@ impl LLMProvider for QwenProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("Qwen API error: {}", error_text).into());
+            return Err(ProviderHttpError::from_response("Qwen", response)
+                .await
+                .into());
         }
 
         let qwen_response: QwenResponse = response.json().await?;
@@ -274,6 +286,7 @@ impl LLMProvider for QwenProvider {
             cached: false,
             tool_calls,
             finish_reason: choice.finish_reason.clone(),
+            routing: None,
         })
     }
 
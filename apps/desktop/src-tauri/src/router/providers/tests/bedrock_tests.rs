@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use crate::router::providers::azure_openai::AzureOpenAIProvider;
+    use crate::router::providers::bedrock::{
+        decode_event, sign_v4, AwsCredentials, BedrockProvider,
+    };
+    use crate::router::{ChatMessage, LLMProvider, LLMRequest, ToolCall};
+    use chrono::{TimeZone, Utc};
+
+    fn message(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            multimodal_content: None,
+        }
+    }
+
+    fn request(messages: Vec<ChatMessage>) -> LLMRequest {
+        LLMRequest {
+            messages,
+            model: "anthropic.claude-3-5-sonnet-20241022-v2:0".to_string(),
+            temperature: Some(0.2),
+            max_tokens: Some(512),
+            stream: false,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+        }
+    }
+
+    #[test]
+    fn test_sigv4_matches_aws_test_suite() {
+        // "get-vanilla" from the AWS Signature Version 4 test suite
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: None,
+        };
+        let mut headers = vec![("Host".to_string(), "example.amazonaws.com".to_string())];
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+
+        let authorization = sign_v4(
+            "GET",
+            "/",
+            &mut headers,
+            b"",
+            &credentials,
+            "us-east-1",
+            "service",
+            now,
+        );
+
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert!(headers
+            .iter()
+            .any(|(name, value)| name == "x-amz-date" && value == "20150830T123600Z"));
+    }
+
+    #[test]
+    fn test_converse_body_merges_roles_and_tool_results() {
+        let mut assistant = message("assistant", "");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call-1".to_string(),
+            name: "lookup".to_string(),
+            arguments: r#"{"q":"rust"}"#.to_string(),
+        }]);
+        let mut tool = message("tool", "found it");
+        tool.tool_call_id = Some("call-1".to_string());
+
+        let body = BedrockProvider::converse_body(&request(vec![
+            message("system", "Be brief"),
+            message("user", "Search"),
+            assistant,
+            tool,
+            message("user", "Thanks"),
+        ]));
+
+        assert_eq!(body["system"][0]["text"], "Be brief");
+        assert_eq!(body["inferenceConfig"]["maxTokens"], 512);
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["content"][0]["toolUse"]["input"]["q"], "rust");
+        // The tool result and the follow-up share one user turn
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(
+            messages[2]["content"][0]["toolResult"]["toolUseId"],
+            "call-1"
+        );
+        assert_eq!(messages[2]["content"][1]["text"], "Thanks");
+    }
+
+    #[test]
+    fn test_decode_event_stream_frame() {
+        let name = b":event-type";
+        let value = b"contentBlockDelta";
+        let payload = br#"{"delta":{"text":"hi"}}"#;
+
+        let mut headers = vec![name.len() as u8];
+        headers.extend_from_slice(name);
+        headers.push(7);
+        headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        headers.extend_from_slice(value);
+
+        let total = 12 + headers.len() + payload.len() + 4;
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&(total as u32).to_be_bytes());
+        frame.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0; 4]);
+        frame.extend_from_slice(&headers);
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(&[0; 4]);
+
+        assert!(decode_event(&frame[..total - 1]).unwrap().is_none());
+        let (event, used) = decode_event(&frame).unwrap().unwrap();
+        assert_eq!(used, total);
+        assert_eq!(event.headers[":event-type"], "contentBlockDelta");
+        assert_eq!(event.payload, payload);
+    }
+
+    #[test]
+    fn test_azure_deployment_url() {
+        let provider = AzureOpenAIProvider::new(
+            "azure-key-123".to_string(),
+            "https://contoso.openai.azure.com/".to_string(),
+            "gpt-4o-prod".to_string(),
+            None,
+        );
+        assert!(provider.is_configured());
+        assert_eq!(
+            provider.chat_url(),
+            "https://contoso.openai.azure.com/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-10-21"
+        );
+    }
+}
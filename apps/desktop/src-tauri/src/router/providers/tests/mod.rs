@@ -4,3 +4,6 @@ mod openai_tests;
 
 #[cfg(test)]
 mod anthropic_tests;
+
+#[cfg(test)]
+mod bedrock_tests;
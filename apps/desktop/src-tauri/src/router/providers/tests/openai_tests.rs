@@ -50,6 +50,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert_eq!(request.messages.len(), 1);
@@ -86,6 +87,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert_eq!(request.messages.len(), 1);
@@ -125,6 +127,7 @@ mod tests {
             stream: false,
             tools: Some(tools.clone()),
             tool_choice: None,
+            response_format: None,
         };
 
         assert!(request.tools.is_some());
@@ -143,6 +146,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: Some(ToolChoice::Auto),
+            response_format: None,
         };
 
         assert!(request.tool_choice.is_some());
@@ -159,6 +163,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: Some(ToolChoice::Required),
+            response_format: None,
         };
 
         assert!(matches!(request.tool_choice, Some(ToolChoice::Required)));
@@ -174,6 +179,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: Some(ToolChoice::Specific("get_weather".to_string())),
+            response_format: None,
         };
 
         if let Some(ToolChoice::Specific(name)) = &request.tool_choice {
@@ -199,6 +205,7 @@ mod tests {
             stream: true,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert!(request.stream);
@@ -268,6 +275,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert_eq!(request.messages.len(), 3);
@@ -286,6 +294,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let high_temp = LLMRequest {
@@ -296,6 +305,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert_eq!(low_temp.temperature, Some(0.0));
@@ -312,6 +322,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert_eq!(request.max_tokens, Some(4096));
@@ -333,6 +344,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         assert_eq!(request.messages[0].content, "");
@@ -351,6 +363,7 @@ mod tests {
                 stream: false,
                 tools: None,
                 tool_choice: None,
+                response_format: None,
             };
 
             assert_eq!(request.model, model);
@@ -373,6 +386,7 @@ mod tests {
             stream: false,
             tools: None,
             tool_choice: None,
+            response_format: None,
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
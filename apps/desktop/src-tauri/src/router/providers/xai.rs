@@ -4,7 +4,11 @@
  * With full function calling support
  */
 use crate::router::sse_parser::{parse_sse_stream, StreamChunk};
-use crate::router::{LLMProvider, LLMRequest, LLMResponse, ToolCall, ToolChoice, ToolDefinition};
+use crate::router::structured_output;
+use crate::router::{
+    LLMProvider, LLMRequest, LLMResponse, ProviderHttpError, ToolCall, ToolChoice, ToolDefinition,
+};
+use crate::security::SecretString;
 use async_trait::async_trait;
 use futures_util::Stream;
 use reqwest::Client;
@@ -15,21 +19,22 @@ use std::pin::Pin;
 const XAI_API_BASE: &str = "https://api.x.ai/v1";
 
 pub struct XAIProvider {
-    api_key: Option<String>,
+    api_key: Option<SecretString>,
     client: Client,
 }
 
 impl XAIProvider {
     pub fn new(api_key: Option<String>) -> Self {
         Self {
-            api_key,
+            api_key: api_key.map(SecretString::from),
             client: Client::new(),
         }
     }
 
     fn get_api_key(&self) -> Result<&str, Box<dyn Error + Send + Sync>> {
         self.api_key
-            .as_deref()
+            .as_ref()
+            .map(SecretString::expose_secret)
             .ok_or_else(|| "XAI API key not configured".into())
     }
 
@@ -116,6 +121,8 @@ struct XAIRequest {
     tools: Option<Vec<XAITool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<XAIToolChoiceValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
     stream: bool,
 }
 
@@ -232,6 +239,10 @@ impl LLMProvider for XAIProvider {
                 .tool_choice
                 .as_ref()
                 .and_then(Self::convert_tool_choice),
+            response_format: request
+                .response_format
+                .as_ref()
+                .map(|format| structured_output::openai_response_format(format, true)),
             stream: false,
         };
 
@@ -245,8 +256,9 @@ impl LLMProvider for XAIProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(format!("XAI API error: {}", error_text).into());
+            return Err(ProviderHttpError::from_response("XAI", response)
+                .await
+                .into());
         }
 
         let xai_response: XAIResponse = response.json().await?;
@@ -278,6 +290,7 @@ impl LLMProvider for XAIProvider {
             cached: false,
             tool_calls,
             finish_reason: choice.finish_reason.clone(),
+            routing: None,
         })
     }
 
@@ -364,6 +377,10 @@ impl LLMProvider for XAIProvider {
                 .tool_choice
                 .as_ref()
                 .and_then(Self::convert_tool_choice),
+            response_format: request
+                .response_format
+                .as_ref()
+                .map(|format| structured_output::openai_response_format(format, true)),
             stream: true,
         };
 
@@ -382,12 +399,9 @@ impl LLMProvider for XAIProvider {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
+            return Err(ProviderHttpError::from_response("XAI", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("XAI API error {}: {}", status, error_text).into());
+                .into());
         }
 
         tracing::debug!("XAI streaming response received, starting SSE parsing");
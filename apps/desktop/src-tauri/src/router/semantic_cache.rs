@@ -0,0 +1,369 @@
+//! Semantic lookups over the LLM response cache.
+//!
+//! Deterministic requests (temperature 0) are embedded with the shared
+//! `EmbeddingGenerator`. When the exact cache key misses, the closest stored
+//! prompt for the same provider and model is served instead, provided its
+//! cosine similarity clears the configured threshold.
+
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::embeddings::{cosine_similarity, EmbeddingConfig, EmbeddingGenerator, Vector};
+use crate::router::{ChatMessage, LLMRequest, Provider};
+
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.95;
+const THRESHOLD_SETTING: &str = "cache.semantic_threshold";
+/// How long to stop asking for embeddings after the generator fails
+const GENERATOR_RETRY_AFTER: Duration = Duration::from_secs(300);
+/// Long prompts are truncated before embedding; the tail carries the question
+const MAX_PROMPT_CHARS: usize = 8_000;
+
+/// How a cache lookup was resolved, persisted for hit-rate analytics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheLookup {
+    ExactHit,
+    SemanticHit,
+    Miss,
+}
+
+impl CacheLookup {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CacheLookup::ExactHit => "exact_hit",
+            CacheLookup::SemanticHit => "semantic_hit",
+            CacheLookup::Miss => "miss",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheHitRate {
+    pub lookups: u64,
+    pub exact_hits: u64,
+    pub semantic_hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub semantic_hit_rate: f64,
+    pub semantic_threshold: f32,
+}
+
+pub struct SemanticCache {
+    generator: tokio::sync::Mutex<Option<Arc<EmbeddingGenerator>>>,
+    unavailable_until: Mutex<Option<Instant>>,
+    threshold: RwLock<f32>,
+}
+
+impl Default for SemanticCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemanticCache {
+    pub fn new() -> Self {
+        Self {
+            generator: tokio::sync::Mutex::new(None),
+            unavailable_until: Mutex::new(None),
+            threshold: RwLock::new(DEFAULT_SIMILARITY_THRESHOLD),
+        }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        *self.threshold.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_threshold(&self, threshold: f32) {
+        *self.threshold.write().unwrap_or_else(|e| e.into_inner()) = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Pick up the threshold saved by `cache_configure`
+    pub fn load_threshold(&self, conn: &Connection) {
+        self.set_threshold(configured_threshold(conn));
+    }
+
+    /// Only deterministic requests may be answered from a similar prompt
+    pub fn is_eligible(request: &LLMRequest) -> bool {
+        request.temperature == Some(0.0) && !request.messages.is_empty()
+    }
+
+    /// Embed the prompt, or `None` while the embedding backend is unavailable
+    pub async fn embed(&self, messages: &[ChatMessage]) -> Option<Vector> {
+        if let Some(until) = *self
+            .unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+        {
+            if Instant::now() < until {
+                return None;
+            }
+        }
+
+        let generator = {
+            let mut slot = self.generator.lock().await;
+            if slot.is_none() {
+                let config = EmbeddingConfig {
+                    enable_fallback: false,
+                    timeout: Duration::from_secs(5),
+                    ..EmbeddingConfig::default()
+                };
+                match EmbeddingGenerator::new(config).await {
+                    Ok(generator) => *slot = Some(Arc::new(generator)),
+                    Err(e) => {
+                        self.mark_unavailable(&e);
+                        return None;
+                    }
+                }
+            }
+            slot.clone()?
+        };
+
+        match generator.generate(&prompt_text(messages)).await {
+            Ok(vector) => Some(vector),
+            Err(e) => {
+                self.mark_unavailable(&e);
+                None
+            }
+        }
+    }
+
+    /// Closest cached prompt for this provider and model above the threshold
+    pub fn find(
+        &self,
+        conn: &Connection,
+        provider: Provider,
+        model: &str,
+        embedding: &[f32],
+    ) -> Result<Option<(String, f32)>> {
+        let threshold = self.threshold();
+        let mut stmt = conn.prepare(
+            "SELECT e.cache_key, e.embedding
+             FROM cache_prompt_embeddings e
+             JOIN cache_entries c ON c.cache_key = e.cache_key
+             WHERE e.provider = ?1 AND e.model = ?2
+               AND c.expires_at > CURRENT_TIMESTAMP",
+        )?;
+        let rows = stmt.query_map(params![provider.as_string(), model], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })?;
+
+        let mut best: Option<(String, f32)> = None;
+        for row in rows {
+            let (cache_key, bytes) = row?;
+            let similarity = cosine_similarity(embedding, &decode_vector(&bytes));
+            let closer = match &best {
+                Some((_, best_similarity)) => similarity > *best_similarity,
+                None => true,
+            };
+            if similarity >= threshold && closer {
+                best = Some((cache_key, similarity));
+            }
+        }
+        Ok(best)
+    }
+
+    /// Remember the prompt embedding for a freshly cached response
+    pub fn store(
+        &self,
+        conn: &Connection,
+        cache_key: &str,
+        provider: Provider,
+        model: &str,
+        embedding: &[f32],
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO cache_prompt_embeddings (cache_key, provider, model, embedding)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(cache_key) DO UPDATE SET
+                provider = excluded.provider,
+                model = excluded.model,
+                embedding = excluded.embedding,
+                created_at = CURRENT_TIMESTAMP",
+            params![
+                cache_key,
+                provider.as_string(),
+                model,
+                encode_vector(embedding)
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn mark_unavailable(&self, error: &anyhow::Error) {
+        tracing::debug!(
+            "Semantic cache disabled for {}s: {}",
+            GENERATOR_RETRY_AFTER.as_secs(),
+            error
+        );
+        *self
+            .unavailable_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + GENERATOR_RETRY_AFTER);
+    }
+}
+
+pub fn configured_threshold(conn: &Connection) -> f32 {
+    crate::db::repository::get_setting(conn, THRESHOLD_SETTING)
+        .ok()
+        .and_then(|setting| setting.value.parse::<f32>().ok())
+        .filter(|value| (0.0..=1.0).contains(value))
+        .unwrap_or(DEFAULT_SIMILARITY_THRESHOLD)
+}
+
+pub fn save_threshold(conn: &Connection, threshold: f32) -> Result<()> {
+    crate::db::repository::set_setting(
+        conn,
+        THRESHOLD_SETTING.to_string(),
+        threshold.to_string(),
+        false,
+    )?;
+    Ok(())
+}
+
+pub fn record_lookup(conn: &Connection, lookup: CacheLookup) {
+    if let Err(e) = conn.execute(
+        "INSERT INTO cache_lookup_stats (outcome, count) VALUES (?1, 1)
+         ON CONFLICT(outcome) DO UPDATE SET count = count + 1",
+        params![lookup.as_str()],
+    ) {
+        tracing::debug!("Failed to record cache lookup: {}", e);
+    }
+}
+
+pub fn hit_rate(conn: &Connection) -> Result<CacheHitRate> {
+    let count = |lookup: CacheLookup| -> Result<u64> {
+        Ok(conn
+            .query_row(
+                "SELECT count FROM cache_lookup_stats WHERE outcome = ?1",
+                params![lookup.as_str()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .unwrap_or(0) as u64)
+    };
+
+    let exact_hits = count(CacheLookup::ExactHit)?;
+    let semantic_hits = count(CacheLookup::SemanticHit)?;
+    let misses = count(CacheLookup::Miss)?;
+    let lookups = exact_hits + semantic_hits + misses;
+    let ratio = |hits: u64| {
+        if lookups == 0 {
+            0.0
+        } else {
+            hits as f64 / lookups as f64
+        }
+    };
+
+    Ok(CacheHitRate {
+        lookups,
+        exact_hits,
+        semantic_hits,
+        misses,
+        hit_rate: ratio(exact_hits + semantic_hits),
+        semantic_hit_rate: ratio(semantic_hits),
+        semantic_threshold: configured_threshold(conn),
+    })
+}
+
+fn prompt_text(messages: &[ChatMessage]) -> String {
+    let text = messages
+        .iter()
+        .map(|m| format!("{}: {}", m.role, m.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match text.char_indices().rev().nth(MAX_PROMPT_CHARS - 1) {
+        Some((start, _)) => text[start..].to_string(),
+        None => text,
+    }
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+
+    fn insert_entry(conn: &Connection, key: &str) {
+        conn.execute(
+            "INSERT INTO cache_entries (cache_key, provider, model, prompt_hash, response, expires_at)
+             VALUES (?1, 'openai', 'gpt-4o', 'hash', '{}', datetime('now', '+1 day'))",
+            params![key],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn finds_closest_prompt_above_threshold() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let cache = SemanticCache::new();
+
+        insert_entry(&conn, "near");
+        insert_entry(&conn, "far");
+        cache
+            .store(&conn, "near", Provider::OpenAI, "gpt-4o", &[1.0, 0.1, 0.0])
+            .unwrap();
+        cache
+            .store(&conn, "far", Provider::OpenAI, "gpt-4o", &[0.0, 1.0, 0.0])
+            .unwrap();
+
+        let hit = cache
+            .find(&conn, Provider::OpenAI, "gpt-4o", &[1.0, 0.0, 0.0])
+            .unwrap();
+        assert_eq!(hit.map(|(key, _)| key), Some("near".to_string()));
+
+        // Other models never share entries
+        assert!(cache
+            .find(&conn, Provider::OpenAI, "gpt-4o-mini", &[1.0, 0.0, 0.0])
+            .unwrap()
+            .is_none());
+
+        cache.set_threshold(0.999);
+        assert!(cache
+            .find(&conn, Provider::OpenAI, "gpt-4o", &[1.0, 0.0, 0.0])
+            .unwrap()
+            .is_none());
+
+        // Embeddings go away with their cache entry
+        conn.execute("DELETE FROM cache_entries WHERE cache_key = 'near'", [])
+            .unwrap();
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM cache_prompt_embeddings", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+
+    #[test]
+    fn hit_rate_counts_lookups() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        record_lookup(&conn, CacheLookup::ExactHit);
+        record_lookup(&conn, CacheLookup::SemanticHit);
+        record_lookup(&conn, CacheLookup::Miss);
+        record_lookup(&conn, CacheLookup::Miss);
+
+        let stats = hit_rate(&conn).unwrap();
+        assert_eq!(stats.lookups, 4);
+        assert!((stats.hit_rate - 0.5).abs() < f64::EPSILON);
+        assert!((stats.semantic_hit_rate - 0.25).abs() < f64::EPSILON);
+        assert_eq!(stats.semantic_threshold, DEFAULT_SIMILARITY_THRESHOLD);
+    }
+}
@@ -139,6 +139,10 @@ fn parse_sse_event(
         crate::router::Provider::Qwen => parse_openai_sse(event), // Qwen uses OpenAI-compatible format
         crate::router::Provider::Mistral => parse_openai_sse(event), // Mistral uses OpenAI-compatible format
         crate::router::Provider::Moonshot => parse_openai_sse(event), // Moonshot uses OpenAI-compatible format
+        crate::router::Provider::AzureOpenAI => parse_openai_sse(event),
+        crate::router::Provider::Bedrock => {
+            Err("Bedrock streams use AWS event-stream framing, not SSE".into())
+        }
     }
 }
 
@@ -0,0 +1,520 @@
+//! JSON-constrained responses for `LLMRequest::response_format`.
+//!
+//! Providers are asked for JSON through their native mechanism (OpenAI
+//! `response_format`, an Anthropic forced tool, Gemini `responseSchema`,
+//! Ollama `format`). Their output is still checked here before it reaches the
+//! caller: the router extracts the JSON, validates it against the schema and
+//! retries once with the validation errors when it does not match.
+
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use crate::router::{ChatMessage, Provider, ResponseFormat};
+
+/// Tool name used to force Anthropic models into structured output
+pub const ANTHROPIC_TOOL_NAME: &str = "structured_response";
+
+/// Reply still failed validation after the corrective retry
+#[derive(Debug, Clone)]
+pub struct StructuredOutputError {
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for StructuredOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Structured output did not match the requested format: {}",
+            self.errors.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for StructuredOutputError {}
+
+/// Validate a reply and return the JSON text it contains
+pub fn validate(format: &ResponseFormat, content: &str) -> Result<String, Vec<String>> {
+    let json = extract_json(content);
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| vec![format!("response is not valid JSON: {}", e)])?;
+
+    let errors = match format {
+        ResponseFormat::JsonObject if !value.is_object() => {
+            vec!["$: expected a JSON object".to_string()]
+        }
+        ResponseFormat::JsonObject => Vec::new(),
+        ResponseFormat::JsonSchema { schema, .. } => validate_schema(schema, &value),
+    };
+
+    if errors.is_empty() {
+        Ok(json.to_string())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Strip Markdown fences and surrounding prose from a JSON reply
+pub fn extract_json(content: &str) -> &str {
+    let trimmed = content.trim();
+    if let Some(fenced) = trimmed.strip_prefix("```") {
+        let body = fenced
+            .split_once('\n')
+            .map(|(_, rest)| rest)
+            .unwrap_or(fenced);
+        if let Some(end) = body.rfind("```") {
+            return body[..end].trim();
+        }
+    }
+
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return trimmed;
+    }
+
+    match (trimmed.find(['{', '[']), trimmed.rfind(['}', ']'])) {
+        (Some(start), Some(end)) if start < end => &trimmed[start..=end],
+        _ => trimmed,
+    }
+}
+
+/// Check a value against the subset of JSON Schema that providers accept:
+/// `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`,
+/// `items`, `minItems`/`maxItems`, `minLength`/`maxLength`,
+/// `minimum`/`maximum` and `anyOf`
+pub fn validate_schema(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "$", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|name| matches_type(name, value)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{}: must be one of {}",
+                path,
+                Value::from(options.clone())
+            ));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            errors.push(format!("{}: must equal {}", path, constant));
+        }
+    }
+
+    if let Some(variants) = schema.get("anyOf").and_then(Value::as_array) {
+        let matched = variants
+            .iter()
+            .any(|variant| validate_schema(variant, value).is_empty());
+        if !matched {
+            errors.push(format!("{}: does not match any allowed schema", path));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => check_object(schema, fields, path, errors),
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if count < min {
+                    errors.push(format!("{}: expected at least {} items", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if count > max {
+                    errors.push(format!("{}: expected at most {} items", path, max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    errors.push(format!("{}: shorter than {} characters", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    errors.push(format!("{}: longer than {} characters", path, max));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    errors.push(format!("{}: must be at least {}", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    errors.push(format!("{}: must be at most {}", path, max));
+                }
+            }
+        }
+        Value::Bool(_) | Value::Null => {}
+    }
+}
+
+fn check_object(
+    schema: &Map<String, Value>,
+    fields: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !fields.contains_key(name) {
+                errors.push(format!("{}: missing required property '{}'", path, name));
+            }
+        }
+    }
+
+    for (name, field) in fields {
+        let field_path = format!("{}.{}", path, name);
+        match (
+            properties.and_then(|p| p.get(name)),
+            schema.get("additionalProperties"),
+        ) {
+            (Some(field_schema), _) => check(field_schema, field, &field_path, errors),
+            (None, Some(Value::Bool(false))) => {
+                errors.push(format!("{}: unexpected property", field_path));
+            }
+            (None, Some(extra @ Value::Object(_))) => check(extra, field, &field_path, errors),
+            (None, _) => {}
+        }
+    }
+}
+
+fn matches_type(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+        Value::Number(_) => "number",
+    }
+}
+
+/// Follow-up turn asking the model to fix its previous reply
+pub fn correction_message(errors: &[String]) -> ChatMessage {
+    ChatMessage {
+        role: "user".to_string(),
+        content: format!(
+            "Your previous reply did not match the required JSON format:\n- {}\n\
+             Reply again with only the corrected JSON and no other text.",
+            errors.join("\n- ")
+        ),
+        tool_calls: None,
+        tool_call_id: None,
+        multimodal_content: None,
+    }
+}
+
+/// Providers whose APIs enforce a JSON Schema; the rest only offer a generic
+/// JSON mode and are given the schema through [`instruction`]
+pub fn supports_native_schema(provider: Provider) -> bool {
+    matches!(
+        provider,
+        Provider::OpenAI
+            | Provider::Anthropic
+            | Provider::Google
+            | Provider::Ollama
+            | Provider::XAI
+            | Provider::Mistral
+            | Provider::AzureOpenAI
+    )
+}
+
+/// System prompt text for providers that can only be asked for generic JSON
+pub fn instruction(format: &ResponseFormat) -> String {
+    match format.schema() {
+        Some(schema) => format!(
+            "Respond only with a JSON value that matches this JSON Schema, with no other text:\n{}",
+            schema
+        ),
+        None => "Respond only with a single JSON object and no other text.".to_string(),
+    }
+}
+
+/// `response_format` body for OpenAI-compatible chat completion APIs.
+/// Providers without schema support get `json_object` plus [`instruction`].
+pub fn openai_response_format(format: &ResponseFormat, supports_schema: bool) -> Value {
+    match format {
+        ResponseFormat::JsonSchema {
+            name,
+            schema,
+            strict,
+        } if supports_schema => json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": sanitize_name(name),
+                "schema": schema,
+                "strict": strict,
+            }
+        }),
+        _ => json!({ "type": "json_object" }),
+    }
+}
+
+/// Providers only accept object schemas at the root (Anthropic tool input,
+/// OpenAI strict mode), so other schemas are sent wrapped in `{"result": ...}`
+pub fn provider_format(format: &ResponseFormat) -> ResponseFormat {
+    match format {
+        ResponseFormat::JsonSchema {
+            name,
+            schema,
+            strict,
+        } if needs_wrapping(schema) => ResponseFormat::JsonSchema {
+            name: name.clone(),
+            schema: json!({
+                "type": "object",
+                "properties": { WRAPPED_KEY: schema },
+                "required": [WRAPPED_KEY],
+                "additionalProperties": false,
+            }),
+            strict: *strict,
+        },
+        _ => format.clone(),
+    }
+}
+
+/// Undo [`provider_format`] on the reply; anything unexpected is returned
+/// unchanged for validation to report
+pub fn unwrap_reply(format: &ResponseFormat, content: &str) -> String {
+    if !format.schema().is_some_and(needs_wrapping) {
+        return content.to_string();
+    }
+    serde_json::from_str::<Value>(extract_json(content))
+        .ok()
+        .and_then(|mut value| value.get_mut(WRAPPED_KEY).map(Value::take))
+        .map(|result| result.to_string())
+        .unwrap_or_else(|| content.to_string())
+}
+
+const WRAPPED_KEY: &str = "result";
+
+fn needs_wrapping(schema: &Value) -> bool {
+    schema.get("type") != Some(&Value::from("object"))
+}
+
+/// Input schema for the forced Anthropic tool
+pub fn anthropic_tool_schema(format: &ResponseFormat) -> Value {
+    format
+        .schema()
+        .cloned()
+        .unwrap_or_else(|| json!({ "type": "object" }))
+}
+
+/// Gemini `responseSchema` accepts an OpenAPI subset; drop the keywords it
+/// rejects so that strict JSON Schemas can be reused as-is
+pub fn gemini_schema(schema: &Value) -> Value {
+    const SUPPORTED: &[&str] = &[
+        "type",
+        "format",
+        "description",
+        "nullable",
+        "enum",
+        "properties",
+        "required",
+        "items",
+        "minItems",
+        "maxItems",
+        "anyOf",
+        "propertyOrdering",
+    ];
+
+    let Some(fields) = schema.as_object() else {
+        return schema.clone();
+    };
+
+    let mut cleaned = Map::new();
+    for (key, value) in fields {
+        if !SUPPORTED.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match key.as_str() {
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .map(|props| {
+                        props
+                            .iter()
+                            .map(|(name, prop)| (name.clone(), gemini_schema(prop)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+            "items" => gemini_schema(value),
+            "anyOf" => Value::Array(
+                value
+                    .as_array()
+                    .map(|variants| variants.iter().map(gemini_schema).collect())
+                    .unwrap_or_default(),
+            ),
+            _ => value.clone(),
+        };
+        cleaned.insert(key.clone(), value);
+    }
+    Value::Object(cleaned)
+}
+
+/// OpenAI limits schema names to `[a-zA-Z0-9_-]{1,64}`
+fn sanitize_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    if cleaned.is_empty() {
+        "response".to_string()
+    } else {
+        cleaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_format() -> ResponseFormat {
+        ResponseFormat::json_schema(
+            "plan",
+            json!({
+                "type": "object",
+                "properties": {
+                    "steps": {
+                        "type": "array",
+                        "minItems": 1,
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "tool": { "type": "string", "enum": ["browser", "shell"] },
+                                "timeout": { "type": "integer", "minimum": 1 }
+                            },
+                            "required": ["tool"],
+                            "additionalProperties": false
+                        }
+                    }
+                },
+                "required": ["steps"]
+            }),
+        )
+    }
+
+    #[test]
+    fn accepts_fenced_json_matching_schema() {
+        let reply = "Here is the plan:\n```json\n{\"steps\": [{\"tool\": \"shell\", \"timeout\": 30}]}\n```";
+        assert_eq!(
+            validate(&plan_format(), reply).unwrap(),
+            r#"{"steps": [{"tool": "shell", "timeout": 30}]}"#
+        );
+        assert_eq!(
+            extract_json("Sure! {\"a\": 1} Hope that helps."),
+            "{\"a\": 1}"
+        );
+    }
+
+    #[test]
+    fn reports_schema_violations_with_paths() {
+        let mut errors = validate(
+            &plan_format(),
+            r#"{"steps": [{"tool": "email", "timeout": 0.5, "extra": true}]}"#,
+        )
+        .unwrap_err();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$.steps[0].extra: unexpected property",
+                "$.steps[0].timeout: expected integer, got number",
+                "$.steps[0].tool: must be one of [\"browser\",\"shell\"]",
+            ]
+        );
+
+        assert!(validate(&plan_format(), r#"{"steps": []}"#).is_err());
+        assert!(validate(&plan_format(), "not json at all").is_err());
+        assert!(validate(&ResponseFormat::JsonObject, "[1, 2]").is_err());
+        assert!(validate(&ResponseFormat::JsonObject, r#"{"ok": true}"#).is_ok());
+    }
+
+    #[test]
+    fn provider_mappings() {
+        let format = plan_format();
+        assert_eq!(
+            openai_response_format(&format, true)["json_schema"]["name"],
+            "plan"
+        );
+        assert_eq!(
+            openai_response_format(&format, false),
+            json!({ "type": "json_object" })
+        );
+
+        let gemini = gemini_schema(format.schema().unwrap());
+        assert!(gemini["properties"]["steps"]["items"]
+            .get("additionalProperties")
+            .is_none());
+        assert_eq!(gemini["required"], json!(["steps"]));
+
+        let list = ResponseFormat::json_schema("tags", json!({ "type": "array" }));
+        let wrapped = provider_format(&list);
+        assert_eq!(wrapped.schema().unwrap()["required"], json!(["result"]));
+        assert_eq!(
+            unwrap_reply(&list, r#"{"result": ["a", "b"]}"#),
+            r#"["a","b"]"#
+        );
+        assert_eq!(provider_format(&format), format);
+        assert_eq!(unwrap_reply(&format, "{}"), "{}");
+    }
+}
@@ -13,6 +13,7 @@ mod tests {
             model: "gpt-4".to_string(),
             cached: false,
             finish_reason: None,
+            routing: None,
             tool_calls: None,
         };
 
@@ -33,6 +34,7 @@ mod tests {
             model: "gpt-3.5-turbo".to_string(),
             cached: true,
             finish_reason: None,
+            routing: None,
             tool_calls: None,
         };
 
@@ -59,6 +61,7 @@ mod tests {
             model: "test-model".to_string(),
             cached: false,
             finish_reason: None,
+            routing: None,
             tool_calls: None,
         };
 
@@ -110,6 +113,7 @@ mod tests {
             model: "unknown".to_string(),
             cached: false,
             finish_reason: None,
+            routing: None,
             tool_calls: None,
         };
 
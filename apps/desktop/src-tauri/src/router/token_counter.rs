@@ -26,8 +26,30 @@ impl TokenCounter {
         messages: &[ChatMessage],
         completion: &str,
     ) -> (u32, u32) {
+        let (prompt_multiplier, completion_multiplier) = Self::provider_multipliers(provider);
+
+        let prompt =
+            (Self::estimate_prompt_tokens(messages) as f32 * prompt_multiplier).ceil() as u32;
+        let completion = (Self::estimate_completion_tokens(completion) as f32
+            * completion_multiplier)
+            .ceil() as u32;
+
+        (prompt.max(1), completion.max(1))
+    }
+
+    /// Estimate prompt tokens for one part of a request, such as the system
+    /// prompt or the serialized tool definitions.
+    pub fn estimate_section_tokens(provider: Provider, text: &str) -> u32 {
+        if text.is_empty() {
+            return 0;
+        }
+        let (prompt_multiplier, _) = Self::provider_multipliers(provider);
+        (Self::estimate_text_tokens(text) as f32 * prompt_multiplier).ceil() as u32
+    }
+
+    fn provider_multipliers(provider: Provider) -> (f32, f32) {
         // Adjust heuristic slightly per provider to reflect average tokenization differences.
-        let (prompt_multiplier, completion_multiplier) = match provider {
+        match provider {
             Provider::OpenAI => (1.0, 1.0),
             Provider::Anthropic => (1.05, 1.05),
             Provider::Google => (0.95, 0.95),
@@ -37,15 +59,9 @@ impl TokenCounter {
             Provider::Qwen => (1.0, 1.0),
             Provider::Mistral => (1.0, 1.0),
             Provider::Moonshot => (1.0, 1.0), // Moonshot uses similar tokenization to OpenAI
-        };
-
-        let prompt =
-            (Self::estimate_prompt_tokens(messages) as f32 * prompt_multiplier).ceil() as u32;
-        let completion = (Self::estimate_completion_tokens(completion) as f32
-            * completion_multiplier)
-            .ceil() as u32;
-
-        (prompt.max(1), completion.max(1))
+            Provider::AzureOpenAI => (1.0, 1.0),
+            Provider::Bedrock => (1.05, 1.05), // Mostly Anthropic models
+        }
     }
 
     fn estimate_text_tokens(text: &str) -> u32 {
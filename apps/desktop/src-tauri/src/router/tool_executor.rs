@@ -1,9 +1,14 @@
+use crate::agent::file_checkpoints::CheckpointStore;
+use crate::agi::tool_costs::{global_tool_costs, ToolUsage, USAGE_KEY};
 use crate::agi::tools::{Tool, ToolRegistry, ToolResult};
+use crate::audit::{self, Actor, AuditEntry, AuditOutcome};
 use crate::events::{
     create_file_delete_event, create_file_read_event, create_file_write_event, emit_file_operation,
     emit_terminal_command, TerminalCommand,
 };
 use crate::router::{ToolCall, ToolDefinition};
+use crate::security::{guardrails, shell_guard};
+use crate::terminal::{EnvMasker, WorkspaceEnvManager};
 use anyhow::{anyhow, Result};
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -46,6 +51,8 @@ pub struct ToolExecutor {
     registry: Arc<ToolRegistry>,
     app_handle: Option<tauri::AppHandle>,
     conversation_mode: Option<String>, // "safe" or "full_control"
+    /// Run that guardrail findings are reported under
+    run_id: String,
 }
 
 impl ToolExecutor {
@@ -54,6 +61,7 @@ impl ToolExecutor {
             registry,
             app_handle: None,
             conversation_mode: None,
+            run_id: Uuid::new_v4().to_string(),
         }
     }
 
@@ -62,6 +70,7 @@ impl ToolExecutor {
             registry,
             app_handle: Some(app_handle),
             conversation_mode: None,
+            run_id: Uuid::new_v4().to_string(),
         }
     }
 
@@ -70,6 +79,10 @@ impl ToolExecutor {
         self.conversation_mode = mode;
     }
 
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
     /// Convert AGI tools to LLM tool definitions
     pub fn get_tool_definitions(&self, tool_ids: Option<Vec<String>>) -> Vec<ToolDefinition> {
         let tools = if let Some(ids) = tool_ids {
@@ -147,6 +160,33 @@ impl ToolExecutor {
             None,
         );
 
+        if let Err(message) = guardrails::shared(self.app_handle.as_ref()).check_egress(
+            self.app_handle.as_ref(),
+            &self.run_id,
+            &tool_call.name,
+            &metadata_snapshot,
+        ) {
+            self.emit_tool_action(
+                &action_id,
+                &tool_call.name,
+                "blocked",
+                &metadata_snapshot,
+                Some(message.clone()),
+            );
+            self.emit_tool_metrics(
+                &action_id,
+                &tool_call.name,
+                start_time.elapsed().as_millis() as u64,
+                false,
+            );
+            return Ok(ToolResult {
+                success: false,
+                data: json!({ "guardrail_blocked": true }),
+                error: Some(message),
+                metadata: HashMap::new(),
+            });
+        }
+
         if tool_call.name.starts_with("mcp_") {
             let result = self.execute_mcp_tool(tool_call, args).await;
             return self.finalize_tool_result(
@@ -265,7 +305,19 @@ impl ToolExecutor {
             );
         }
 
-        let result = self.execute_tool_impl(&tool, args).await;
+        // Keep the prior content of any file the tool changes so the run can be rolled back
+        let checkpoint = match self.app_handle.as_ref().and_then(CheckpointStore::from_app) {
+            Some(store) => {
+                store
+                    .before_tool(&self.run_id, &tool_call.name, &metadata_snapshot)
+                    .await
+            }
+            None => Ok(()),
+        };
+        let result = match checkpoint {
+            Ok(()) => self.execute_tool_impl(&tool, args).await,
+            Err(e) => Err(e.context("Failed to snapshot file before changing it")),
+        };
         self.finalize_tool_result(
             &action_id,
             &tool_call.name,
@@ -292,12 +344,22 @@ impl ToolExecutor {
 
         // Execute the MCP tool
         match mcp_state.registry.execute_tool(&tool_call.name, args).await {
-            Ok(result_value) => Ok(ToolResult {
-                success: true,
-                data: result_value,
-                error: None,
-                metadata: HashMap::new(),
-            }),
+            Ok(result_value) => {
+                // Servers fronting paid APIs may report what the call cost
+                let mut metadata = HashMap::new();
+                if let Some(mut usage) = ToolUsage::from_result(&result_value) {
+                    usage.source.get_or_insert_with(|| "mcp".to_string());
+                    if let Ok(usage) = serde_json::to_value(usage) {
+                        metadata.insert(USAGE_KEY.to_string(), usage);
+                    }
+                }
+                Ok(ToolResult {
+                    success: true,
+                    data: result_value,
+                    error: None,
+                    metadata,
+                })
+            }
             Err(e) => Ok(ToolResult {
                 success: false,
                 data: json!(null),
@@ -818,6 +880,24 @@ impl ToolExecutor {
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| anyhow!("Missing code parameter"))?;
 
+                let verdict = shell_guard::guard_for(self.app_handle.as_ref())?.check(code);
+                if let Err(e) = shell_guard::enforce(
+                    self.app_handle.as_ref(),
+                    "code_execute",
+                    code,
+                    None,
+                    &verdict,
+                )
+                .await
+                {
+                    return Ok(ToolResult {
+                        success: false,
+                        data: json!({ "verdict": verdict }),
+                        error: Some(e),
+                        metadata: HashMap::new(),
+                    });
+                }
+
                 if let Some(ref app) = self.app_handle {
                     use crate::terminal::{SessionManager, ShellType};
                     use tauri::Manager;
@@ -899,34 +979,68 @@ impl ToolExecutor {
                     .and_then(|v| v.as_u64())
                     .unwrap_or(60_000);
 
-                let (program, mut shell_args): (String, Vec<String>) = match shell.as_str() {
-                    "cmd" => (
-                        "cmd.exe".to_string(),
-                        vec!["/C".to_string(), command.clone()],
-                    ),
-                    "bash" => ("bash".to_string(), vec!["-lc".to_string(), command.clone()]),
-                    "wsl" => (
-                        "wsl.exe".to_string(),
-                        vec!["bash".to_string(), "-lc".to_string(), command.clone()],
-                    ),
-                    _ => (
-                        "powershell.exe".to_string(),
-                        vec![
-                            "-NoLogo".to_string(),
-                            "-NoProfile".to_string(),
-                            "-Command".to_string(),
-                            command.clone(),
-                        ],
-                    ),
-                };
+                let dry_run = args
+                    .get("dry_run")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
 
-                let mut cmd = Command::new(&program);
-                for arg in shell_args.drain(..) {
-                    cmd.arg(arg);
+                let guard = shell_guard::guard_for(self.app_handle.as_ref())?;
+                let plan = match guard.plan(&command, &shell, cwd.as_deref().map(Path::new)) {
+                    Ok(plan) => plan,
+                    Err(e) => {
+                        return Ok(ToolResult {
+                            success: false,
+                            data: json!(null),
+                            error: Some(e.to_string()),
+                            metadata: HashMap::new(),
+                        })
+                    }
+                };
+                if dry_run {
+                    return Ok(ToolResult {
+                        success: true,
+                        data: serde_json::to_value(&plan)?,
+                        error: None,
+                        metadata: HashMap::from([("dry_run".to_string(), json!(true))]),
+                    });
                 }
-                if let Some(dir) = &cwd {
-                    cmd.current_dir(dir);
+                if let Err(e) = shell_guard::enforce(
+                    self.app_handle.as_ref(),
+                    "terminal_execute",
+                    &command,
+                    Some(&plan.cwd),
+                    &plan.verdict,
+                )
+                .await
+                {
+                    return Ok(ToolResult {
+                        success: false,
+                        data: json!({ "verdict": plan.verdict }),
+                        error: Some(e),
+                        metadata: HashMap::new(),
+                    });
                 }
+                let cwd = plan.cwd.display().to_string();
+
+                let mut cmd = Command::new(&plan.program);
+                cmd.args(&plan.args)
+                    .current_dir(&plan.cwd)
+                    .env_clear()
+                    .envs(plan.env().iter().cloned());
+                // Inject the workspace's env file variables, and keep their
+                // secret values out of the output we log and return
+                let workspace_env = self.app_handle.as_ref().and_then(|app| {
+                    app.try_state::<WorkspaceEnvManager>()
+                        .and_then(|manager| manager.resolve_for(&plan.cwd))
+                });
+                let masker = match workspace_env {
+                    Some(env) => {
+                        cmd.envs(env.vars);
+                        env.masker
+                    }
+                    None => EnvMasker::default(),
+                };
+                let logged_command = masker.mask(&command);
                 cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
                 cmd.kill_on_drop(true);
 
@@ -948,8 +1062,8 @@ impl ToolExecutor {
                         if let Some(app_handle) = &self.app_handle {
                             let terminal_event = TerminalCommand {
                                 id: Uuid::new_v4().to_string(),
-                                command: command.clone(),
-                                cwd: cwd.clone().unwrap_or_else(|| ".".to_string()),
+                                command: logged_command.clone(),
+                                cwd: cwd.clone(),
                                 exit_code: None,
                                 stdout: None,
                                 stderr: Some(timeout_error.clone()),
@@ -969,16 +1083,16 @@ impl ToolExecutor {
                 };
 
                 let duration_ms = start.elapsed().as_millis() as u64;
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                let stdout = masker.mask(&String::from_utf8_lossy(&output.stdout));
+                let stderr = masker.mask(&String::from_utf8_lossy(&output.stderr));
                 let exit_code = output.status.code();
                 let success = output.status.success();
 
                 if let Some(app_handle) = &self.app_handle {
                     let terminal_event = TerminalCommand {
                         id: Uuid::new_v4().to_string(),
-                        command: command.clone(),
-                        cwd: cwd.clone().unwrap_or_else(|| ".".to_string()),
+                        command: logged_command,
+                        cwd: cwd.clone(),
                         exit_code,
                         stdout: if stdout.is_empty() {
                             None
@@ -999,10 +1113,8 @@ impl ToolExecutor {
 
                 let mut metadata = HashMap::new();
                 metadata.insert("shell".to_string(), json!(shell));
-                metadata.insert("program".to_string(), json!(program));
-                if let Some(dir) = &cwd {
-                    metadata.insert("cwd".to_string(), json!(dir));
-                }
+                metadata.insert("program".to_string(), json!(plan.program));
+                metadata.insert("cwd".to_string(), json!(cwd));
 
                 let error_message = if success {
                     None
@@ -1150,6 +1262,37 @@ impl ToolExecutor {
                     })
                 }
             }
+            "web_fetch" => {
+                let url = args
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing url parameter"))?
+                    .to_string();
+
+                if let Some(ref app) = self.app_handle {
+                    match crate::agi::api_tools_impl::execute_web_fetch(app, &args).await {
+                        Ok(data) => Ok(ToolResult {
+                            success: true,
+                            data,
+                            error: None,
+                            metadata: HashMap::from([("url".to_string(), json!(url))]),
+                        }),
+                        Err(e) => Ok(ToolResult {
+                            success: false,
+                            data: json!(null),
+                            error: Some(format!("Web fetch failed: {}", e)),
+                            metadata: HashMap::from([("url".to_string(), json!(url))]),
+                        }),
+                    }
+                } else {
+                    Ok(ToolResult {
+                        success: false,
+                        data: json!(null),
+                        error: Some("App handle not available for web fetch".to_string()),
+                        metadata: HashMap::new(),
+                    })
+                }
+            }
             "image_ocr" => {
                 // ✅ Actual OCR implementation
                 let image_path = args
@@ -1220,6 +1363,119 @@ impl ToolExecutor {
                     metadata: HashMap::from([("language".to_string(), json!(language))]),
                 })
             }
+            "code_impact_analysis" => {
+                let path = args
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing path parameter"))?;
+                let graph_state = self
+                    .app_handle
+                    .as_ref()
+                    .and_then(|h| h.try_state::<crate::codebase::DependencyGraphState>())
+                    .ok_or_else(|| anyhow!("Dependency graph not available"))?;
+                let graph = graph_state.0.read();
+                let graph = graph.as_ref().ok_or_else(|| {
+                    anyhow!("Workspace not indexed; run workspace indexing first")
+                })?;
+                let impact = graph.impact(Path::new(path));
+
+                Ok(ToolResult {
+                    success: true,
+                    metadata: HashMap::from([("risk".to_string(), json!(impact.risk))]),
+                    data: serde_json::to_value(&impact)?,
+                    error: None,
+                })
+            }
+            "dependency_scan" => {
+                let path = args
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing path parameter"))?
+                    .to_string();
+                let report = tokio::task::spawn_blocking(move || {
+                    crate::codebase::dependency_scan::scan_workspace(
+                        Path::new(&path),
+                        &Default::default(),
+                    )
+                })
+                .await??;
+
+                Ok(ToolResult {
+                    success: true,
+                    metadata: HashMap::from([
+                        (
+                            "vulnerabilities".to_string(),
+                            json!(report.vulnerabilities.len()),
+                        ),
+                        ("license_findings".to_string(), json!(report.licenses.len())),
+                    ]),
+                    data: serde_json::to_value(&report)?,
+                    error: None,
+                })
+            }
+            "project_detect_tasks" | "project_run_task" => {
+                if let Some(ref app) = self.app_handle {
+                    let data =
+                        crate::codebase::project_tasks::execute_tool(app, tool.id.as_str(), &args)
+                            .await?;
+                    let success = data
+                        .get("status")
+                        .is_none_or(|status| status.as_str() == Some("succeeded"));
+                    Ok(ToolResult {
+                        success,
+                        data,
+                        error: None,
+                        metadata: HashMap::new(),
+                    })
+                } else {
+                    Ok(ToolResult {
+                        success: false,
+                        data: json!(null),
+                        error: Some("App handle not available for project tasks".to_string()),
+                        metadata: HashMap::new(),
+                    })
+                }
+            }
+            "slack_reply" | "slack_react" | "slack_open_thread" => {
+                if let Some(ref app) = self.app_handle {
+                    let data =
+                        crate::messaging::slack_events::execute_tool(app, tool.id.as_str(), &args)
+                            .await?;
+                    Ok(ToolResult {
+                        success: true,
+                        data,
+                        error: None,
+                        metadata: HashMap::new(),
+                    })
+                } else {
+                    Ok(ToolResult {
+                        success: false,
+                        data: json!(null),
+                        error: Some("App handle not available for Slack".to_string()),
+                        metadata: HashMap::new(),
+                    })
+                }
+            }
+            "messaging_send" | "messaging_list_threads" | "messaging_poll" => {
+                if let Some(ref app) = self.app_handle {
+                    let data =
+                        crate::messaging::manager::execute_tool(app, tool.id.as_str(), &args)
+                            .await?;
+                    Ok(ToolResult {
+                        success: true,
+                        data,
+                        error: None,
+                        metadata: HashMap::new(),
+                    })
+                } else {
+                    Ok(ToolResult {
+                        success: false,
+                        data: json!(null),
+                        error: Some("App handle not available for messaging".to_string()),
+                        metadata: HashMap::new(),
+                    })
+                }
+            }
             "llm_reason" => {
                 // ✅ LLM sub-reasoning implementation
                 let prompt = args
@@ -1449,8 +1705,29 @@ impl ToolExecutor {
         start_time: Instant,
         result: Result<ToolResult>,
     ) -> Result<ToolResult> {
+        let duration_ms = start_time.elapsed().as_millis() as u64;
+        if let Some(app) = &self.app_handle {
+            if let Some(entry) =
+                AuditEntry::for_tool(Actor::agent(self.run_id.clone()), tool_name, &metadata)
+            {
+                let entry = match &result {
+                    Ok(tool_result) if tool_result.success => entry,
+                    Ok(tool_result) => entry
+                        .outcome(AuditOutcome::Failure)
+                        .detail(tool_result.error.clone().unwrap_or_default()),
+                    Err(err) => entry.outcome(AuditOutcome::Failure).detail(err.to_string()),
+                };
+                audit::record(app, entry);
+            }
+        }
         match result {
             Ok(tool_result) => {
+                let usage = tool_result
+                    .metadata
+                    .get(USAGE_KEY)
+                    .and_then(|usage| serde_json::from_value(usage.clone()).ok())
+                    .or_else(|| ToolUsage::from_result(&tool_result.data));
+                global_tool_costs().record(tool_name, tool_result.success, duration_ms, usage);
                 let status = if tool_result.success {
                     "success"
                 } else {
@@ -1463,15 +1740,11 @@ impl ToolExecutor {
                     &metadata,
                     tool_result.error.clone(),
                 );
-                self.emit_tool_metrics(
-                    action_id,
-                    tool_name,
-                    start_time.elapsed().as_millis() as u64,
-                    tool_result.success,
-                );
+                self.emit_tool_metrics(action_id, tool_name, duration_ms, tool_result.success);
                 Ok(tool_result)
             }
             Err(err) => {
+                global_tool_costs().record(tool_name, false, duration_ms, None);
                 let message = err.to_string();
                 self.emit_tool_action(
                     action_id,
@@ -1480,20 +1753,15 @@ impl ToolExecutor {
                     &metadata,
                     Some(message.clone()),
                 );
-                self.emit_tool_metrics(
-                    action_id,
-                    tool_name,
-                    start_time.elapsed().as_millis() as u64,
-                    false,
-                );
+                self.emit_tool_metrics(action_id, tool_name, duration_ms, false);
                 Err(err)
             }
         }
     }
 
-    /// Format tool result for LLM consumption
-    pub fn format_tool_result(&self, _tool_call: &ToolCall, result: &ToolResult) -> String {
-        if result.success {
+    /// Format tool result for LLM consumption, screened by the guardrails
+    pub fn format_tool_result(&self, tool_call: &ToolCall, result: &ToolResult) -> String {
+        let text = if result.success {
             serde_json::to_string_pretty(&result.data).unwrap_or_else(|_| "{}".to_string())
         } else {
             format!(
@@ -1503,7 +1771,13 @@ impl ToolExecutor {
                     .as_ref()
                     .unwrap_or(&"Unknown error".to_string())
             )
-        }
+        };
+        guardrails::shared(self.app_handle.as_ref()).screen_text(
+            self.app_handle.as_ref(),
+            &self.run_id,
+            &tool_call.name,
+            &text,
+        )
     }
 }
 
@@ -0,0 +1,187 @@
+//! Safe-mode startup.
+//!
+//! After a crash or a corrupted state the app can be started with only the
+//! UI, database and settings. Background loops (task runner, realtime server,
+//! companion sync, embedding indexing) are not started and automation entry
+//! points refuse to run, so nothing fires while the user inspects and repairs
+//! the installation.
+//!
+//! Safe mode is requested by any of, in order:
+//! - the `--safe-mode` command-line flag
+//! - `AGIWORKFORCE_SAFE_MODE=1` in the environment
+//! - a non-zero `SafeMode` DWORD under `HKCU\Software\AGI Workforce` (Windows)
+//! - a one-shot marker left by [`request_next_launch`]
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const SAFE_MODE_FLAG: &str = "--safe-mode";
+pub const SAFE_MODE_ENV: &str = "AGIWORKFORCE_SAFE_MODE";
+const NEXT_LAUNCH_MARKER: &str = "safe_mode.next";
+
+/// Subsystems that are not started in safe mode
+pub const DISABLED_SUBSYSTEMS: &[&str] = &[
+    "background_tasks",
+    "agi",
+    "workflows",
+    "ai_employees",
+    "realtime_server",
+    "companion_sync",
+    "embedding_indexing",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeModeSource {
+    CommandLine,
+    Environment,
+    Registry,
+    NextLaunch,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SafeMode {
+    pub enabled: bool,
+    pub source: Option<SafeModeSource>,
+}
+
+impl SafeMode {
+    fn from(source: SafeModeSource) -> Self {
+        Self {
+            enabled: true,
+            source: Some(source),
+        }
+    }
+}
+
+static SAFE_MODE: OnceLock<SafeMode> = OnceLock::new();
+
+/// Work out whether this launch runs in safe mode. Consumes the next-launch
+/// marker, so a restart requested from the UI applies exactly once.
+pub fn detect(args: impl IntoIterator<Item = String>, app_data_dir: &Path) -> SafeMode {
+    let marker = app_data_dir.join(NEXT_LAUNCH_MARKER);
+    let marked = marker.exists();
+    if marked {
+        if let Err(e) = std::fs::remove_file(&marker) {
+            tracing::warn!("Failed to clear safe mode marker: {}", e);
+        }
+    }
+
+    if args.into_iter().any(|arg| arg == SAFE_MODE_FLAG) {
+        SafeMode::from(SafeModeSource::CommandLine)
+    } else if std::env::var(SAFE_MODE_ENV).is_ok_and(|value| is_truthy(&value)) {
+        SafeMode::from(SafeModeSource::Environment)
+    } else if registry_flag() {
+        SafeMode::from(SafeModeSource::Registry)
+    } else if marked {
+        SafeMode::from(SafeModeSource::NextLaunch)
+    } else {
+        SafeMode::default()
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+#[cfg(windows)]
+fn registry_flag() -> bool {
+    use windows::core::w;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    // SAFETY: `value` and `size` outlive the call and `size` matches the buffer
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\AGI Workforce"),
+            w!("SafeMode"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut value as *mut u32 as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    status.is_ok() && value != 0
+}
+
+#[cfg(not(windows))]
+fn registry_flag() -> bool {
+    false
+}
+
+/// Record the mode for this process. Later calls keep the first value.
+pub fn init(mode: SafeMode) -> SafeMode {
+    *SAFE_MODE.get_or_init(|| mode)
+}
+
+pub fn current() -> SafeMode {
+    SAFE_MODE.get().copied().unwrap_or_default()
+}
+
+pub fn is_active() -> bool {
+    current().enabled
+}
+
+/// Refuse `action` while in safe mode
+pub fn ensure_allowed(action: &str) -> Result<(), String> {
+    if is_active() {
+        Err(format!(
+            "{} is disabled in safe mode. Restart normally to re-enable automations.",
+            action
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Start the next launch in safe mode (or cancel a pending request)
+pub fn request_next_launch(app_data_dir: &Path, enabled: bool) -> std::io::Result<()> {
+    let marker = app_data_dir.join(NEXT_LAUNCH_MARKER);
+    if enabled {
+        std::fs::create_dir_all(app_data_dir)?;
+        std::fs::write(marker, b"1")
+    } else if marker.exists() {
+        std::fs::remove_file(marker)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_and_one_shot_marker() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mode = detect(
+            vec!["app".to_string(), SAFE_MODE_FLAG.to_string()],
+            dir.path(),
+        );
+        assert!(mode.enabled);
+        assert_eq!(mode.source, Some(SafeModeSource::CommandLine));
+
+        request_next_launch(dir.path(), true).unwrap();
+        let mode = detect(Vec::new(), dir.path());
+        if std::env::var(SAFE_MODE_ENV).is_err() && !registry_flag() {
+            assert_eq!(mode.source, Some(SafeModeSource::NextLaunch));
+            assert!(!detect(Vec::new(), dir.path()).enabled);
+        }
+        assert!(!dir.path().join(NEXT_LAUNCH_MARKER).exists());
+    }
+
+    #[test]
+    fn test_truthy_values() {
+        assert!(is_truthy("1"));
+        assert!(is_truthy(" TRUE "));
+        assert!(!is_truthy("0"));
+        assert!(!is_truthy(""));
+    }
+}
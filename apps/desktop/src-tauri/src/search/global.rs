@@ -0,0 +1,249 @@
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Kinds of records in the global `search_index` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchEntityType {
+    Message,
+    Document,
+    Email,
+    Workflow,
+}
+
+impl SearchEntityType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchEntityType::Message => "message",
+            SearchEntityType::Document => "document",
+            SearchEntityType::Email => "email",
+            SearchEntityType::Workflow => "workflow",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "message" => Some(SearchEntityType::Message),
+            "document" => Some(SearchEntityType::Document),
+            "email" => Some(SearchEntityType::Email),
+            "workflow" => Some(SearchEntityType::Workflow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalSearchQuery {
+    pub query: String,
+    /// Restrict results to these types; empty searches everything
+    #[serde(default)]
+    pub types: Vec<SearchEntityType>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+fn default_limit() -> usize {
+    50
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalSearchResult {
+    pub entity_type: SearchEntityType,
+    /// Message id, document path, email id or workflow id
+    pub entity_id: String,
+    pub title: Option<String>,
+    /// Matching text with terms wrapped in `<mark>`
+    pub snippet: String,
+    /// Conversation id for messages, folder for emails
+    pub parent_id: Option<String>,
+    pub updated_at: Option<i64>,
+    /// Higher is more relevant
+    pub score: f64,
+}
+
+/// Search messages, documents, emails and workflows at once, best matches first.
+///
+/// Free text is matched word by word, with the last word treated as a prefix
+/// so results update while typing.
+pub fn search_global(
+    conn: &Connection,
+    query: &GlobalSearchQuery,
+) -> Result<Vec<GlobalSearchResult>> {
+    let Some(match_expr) = fts_query(&query.query) else {
+        return Ok(Vec::new());
+    };
+
+    // Title matches weigh twice as much as body matches
+    let mut sql = String::from(
+        "SELECT entity_type, entity_id, title, parent_id, updated_at,
+                snippet(search_index, -1, '<mark>', '</mark>', '…', 16),
+                bm25(search_index, 0.0, 0.0, 2.0, 1.0, 0.0, 0.0) AS score
+         FROM search_index
+         WHERE search_index MATCH ?",
+    );
+    let mut values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(match_expr)];
+
+    if !query.types.is_empty() {
+        sql.push_str(" AND entity_type IN (");
+        sql.push_str(&vec!["?"; query.types.len()].join(", "));
+        sql.push(')');
+        for entity_type in &query.types {
+            values.push(Box::new(entity_type.as_str()));
+        }
+    }
+
+    sql.push_str(" ORDER BY score LIMIT ? OFFSET ?");
+    values.push(Box::new(query.limit as i64));
+    values.push(Box::new(query.offset as i64));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        rusqlite::params_from_iter(values.iter().map(|v| v.as_ref())),
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                GlobalSearchResult {
+                    entity_type: SearchEntityType::Message,
+                    entity_id: row.get(1)?,
+                    title: row.get(2)?,
+                    parent_id: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    snippet: row.get(5)?,
+                    // bm25 is lower for better matches
+                    score: -row.get::<_, f64>(6)?,
+                },
+            ))
+        },
+    )?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        let (entity_type, mut result) = row?;
+        let Some(entity_type) = SearchEntityType::parse(&entity_type) else {
+            continue;
+        };
+        result.entity_type = entity_type;
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// Add or refresh a document's extracted text, keyed by its path
+pub fn index_document(conn: &Connection, path: &str, title: &str, text: &str) -> Result<()> {
+    remove_document(conn, path)?;
+    conn.execute(
+        "INSERT INTO search_index (entity_type, entity_id, title, body, parent_id, updated_at)
+         VALUES ('document', ?1, ?2, ?3, NULL, ?4)",
+        params![path, title, text, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+pub fn remove_document(conn: &Connection, path: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM search_index WHERE entity_type = 'document' AND entity_id = ?1",
+        [path],
+    )?;
+    Ok(())
+}
+
+/// Turn user input into an FTS5 expression that cannot be a syntax error.
+///
+/// Each word becomes a quoted phrase so operators and punctuation are taken
+/// literally; the last word also matches as a prefix.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"", term))
+        .collect();
+    let last = terms.last()?;
+    let mut expr = terms[..terms.len() - 1].join(" ");
+    if !expr.is_empty() {
+        expr.push(' ');
+    }
+    expr.push_str(last);
+    expr.push('*');
+    Some(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE search_index USING fts5(
+                entity_type UNINDEXED, entity_id UNINDEXED, title, body,
+                parent_id UNINDEXED, updated_at UNINDEXED,
+                tokenize = 'porter unicode61 remove_diacritics 2'
+            );
+            INSERT INTO search_index VALUES
+                ('message', '1', 'Budget review', 'Can you summarise the budget spreadsheet?', '7', 1),
+                ('email', 'e1', 'Budget approved', 'The finance team approved it.', 'INBOX', 2),
+                ('workflow', 'w1', 'Weekly report', 'Collect budget numbers every Friday', NULL, 3);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn query(text: &str, types: Vec<SearchEntityType>) -> GlobalSearchQuery {
+        GlobalSearchQuery {
+            query: text.to_string(),
+            types,
+            limit: 10,
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn ranks_title_matches_first_and_filters_types() {
+        let conn = index();
+        index_document(&conn, "/tmp/plan.docx", "plan.docx", "Budget plan for 2025").unwrap();
+
+        let results = search_global(&conn, &query("budget", vec![])).unwrap();
+        assert_eq!(results.len(), 4);
+        assert_ne!(results[0].entity_type, SearchEntityType::Workflow);
+        assert_eq!(results[3].entity_type, SearchEntityType::Workflow);
+        assert!(results[0].snippet.contains("<mark>"));
+
+        let results = search_global(
+            &conn,
+            &query(
+                "budg",
+                vec![SearchEntityType::Email, SearchEntityType::Document],
+            ),
+        )
+        .unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.entity_id.as_str()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(ids.contains(&"e1") && ids.contains(&"/tmp/plan.docx"));
+    }
+
+    #[test]
+    fn treats_operators_literally() {
+        let conn = index();
+        assert!(search_global(&conn, &query("budget AND (\"", vec![])).is_ok());
+        assert!(search_global(&conn, &query("   ", vec![]))
+            .unwrap()
+            .is_empty());
+
+        index_document(&conn, "/a.pdf", "a.pdf", "first").unwrap();
+        index_document(&conn, "/a.pdf", "a.pdf", "second").unwrap();
+        assert!(search_global(&conn, &query("first", vec![]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            search_global(&conn, &query("second", vec![]))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -1,3 +1,5 @@
 pub mod fts;
+pub mod global;
 
 pub use fts::*;
+pub use global::*;
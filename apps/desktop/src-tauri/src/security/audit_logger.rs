@@ -25,6 +25,7 @@ pub enum AuditEventType {
     AgentDeleted,
     PermissionGranted,
     PermissionRevoked,
+    KillSwitch,
     Other(String),
 }
 
@@ -43,6 +44,7 @@ impl AuditEventType {
             Self::AgentDeleted => "agent_deleted",
             Self::PermissionGranted => "permission_granted",
             Self::PermissionRevoked => "permission_revoked",
+            Self::KillSwitch => "kill_switch",
             Self::Other(s) => s,
         }
     }
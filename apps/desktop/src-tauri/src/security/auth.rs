@@ -8,6 +8,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use super::auth_db::AuthDatabaseManager;
 use super::secret_manager::SecretManager;
 
 const ACCESS_TOKEN_DURATION: i64 = 60; // 1 hour
@@ -165,6 +166,8 @@ pub struct AuthManager {
     users: Arc<parking_lot::RwLock<HashMap<String, User>>>,
     sessions: Arc<parking_lot::RwLock<HashMap<String, Session>>>,
     secret_manager: Arc<SecretManager>,
+    /// Accounts are written through to the `users` table when attached
+    store: Option<AuthDatabaseManager>,
 }
 
 impl AuthManager {
@@ -178,9 +181,24 @@ impl AuthManager {
             users: Arc::new(parking_lot::RwLock::new(HashMap::new())),
             sessions: Arc::new(parking_lot::RwLock::new(HashMap::new())),
             secret_manager,
+            store: None,
         }
     }
 
+    /// Persist accounts in the database and load the ones already registered
+    pub fn with_database(mut self, store: AuthDatabaseManager) -> Self {
+        match store.list_users() {
+            Ok(users) => {
+                self.users
+                    .write()
+                    .extend(users.into_iter().map(|user| (user.id.clone(), user)));
+            }
+            Err(e) => tracing::warn!("Failed to load registered accounts: {}", e),
+        }
+        self.store = Some(store);
+        self
+    }
+
     /// Get the JWT secret (used internally for token signing/verification)
     ///
     /// # Security Note
@@ -222,6 +240,12 @@ impl AuthManager {
         drop(users);
 
         let user = User::new(email, password, role)?;
+        let user = match &self.store {
+            Some(store) => store
+                .register(user.email, user.password_hash, role)
+                .map_err(|e| e.to_string())?,
+            None => user,
+        };
         let mut users = self.users.write();
         users.insert(user.id.clone(), user.clone());
 
@@ -245,12 +269,22 @@ impl AuthManager {
 
         if !user.verify_password(password)? {
             user.record_failed_login();
+            if let Some(store) = &self.store {
+                if let Err(e) = store.record_failed_login(&user.id, user.locked_until) {
+                    tracing::warn!("Failed to persist failed login: {}", e);
+                }
+            }
             return Err("Invalid email or password".to_string());
         }
 
         user.record_successful_login();
         let user_id = user.id.clone();
         drop(users);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.record_successful_login(&user_id) {
+                tracing::warn!("Failed to persist login: {}", e);
+            }
+        }
 
         let session = Session::new(user_id);
         let token = AuthToken::from_session(&session);
@@ -316,17 +350,42 @@ impl AuthManager {
         Ok(user)
     }
 
+    /// Whether anyone has registered; until then the app is single-user.
+    /// With a database attached this reads the persisted accounts, and an
+    /// unreadable table counts as having users so sessions stay required.
+    pub fn has_users(&self) -> bool {
+        match &self.store {
+            Some(store) => store.has_users().unwrap_or_else(|e| {
+                tracing::warn!("Failed to check registered accounts: {}", e);
+                true
+            }),
+            None => !self.users.read().is_empty(),
+        }
+    }
+
     /// Get user by ID
     pub fn get_user(&self, user_id: &str) -> Option<User> {
         let users = self.users.read();
         users.get(user_id).cloned()
     }
 
+    /// Check a user's password without starting a session
+    pub fn verify_user_password(&self, user_id: &str, password: &str) -> Result<bool, String> {
+        let users = self.users.read();
+        let user = users.get(user_id).ok_or("User not found")?;
+        user.verify_password(password)
+    }
+
     /// Update user role
     pub fn update_role(&self, user_id: &str, role: UserRole) -> Result<(), String> {
         let mut users = self.users.write();
         let user = users.get_mut(user_id).ok_or("User not found")?;
 
+        if let Some(store) = &self.store {
+            store
+                .update_user_role(user_id, role)
+                .map_err(|e| e.to_string())?;
+        }
         user.role = role;
         Ok(())
     }
@@ -345,7 +404,13 @@ impl AuthManager {
             return Err("Invalid current password".to_string());
         }
 
-        user.password_hash = hash_password(new_password)?;
+        let password_hash = hash_password(new_password)?;
+        if let Some(store) = &self.store {
+            store
+                .update_password(user_id, &password_hash)
+                .map_err(|e| e.to_string())?;
+        }
+        user.password_hash = password_hash;
         Ok(())
     }
 
@@ -360,7 +425,7 @@ impl AuthManager {
 // to be constructed securely. Use AuthManager::new(secret_manager) instead.
 
 /// Hash password using Argon2
-fn hash_password(password: &str) -> Result<String, String> {
+pub(crate) fn hash_password(password: &str) -> Result<String, String> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
 
@@ -371,7 +436,7 @@ fn hash_password(password: &str) -> Result<String, String> {
 }
 
 /// Verify password against hash
-fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
+pub(crate) fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
     let parsed_hash =
         PasswordHash::new(hash).map_err(|e| format!("Failed to parse password hash: {}", e))?;
 
@@ -2,24 +2,26 @@ use super::auth::{Session, User, UserRole};
 use super::oauth::OAuthProvider;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::db::pool::Pool;
+
 /// Database-backed authentication manager
+#[derive(Clone)]
 pub struct AuthDatabaseManager {
-    db: Arc<parking_lot::Mutex<Connection>>,
+    db: Pool,
 }
 
 impl AuthDatabaseManager {
-    pub fn new(db: Arc<parking_lot::Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self { db }
     }
 
     /// Register a new user
     pub fn register(&self, email: String, password_hash: String, role: UserRole) -> Result<User> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         // Check if email already exists
         let exists: bool = db
@@ -67,33 +69,14 @@ impl AuthDatabaseManager {
 
     /// Get user by ID
     pub fn get_user(&self, user_id: &str) -> Result<User> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let user = db.query_row(
             "SELECT id, email, password_hash, role, created_at, last_login_at,
              failed_login_attempts, locked_until
              FROM users WHERE id = ?1",
             [user_id],
-            |row| {
-                Ok(User {
-                    id: row.get(0)?,
-                    email: row.get(1)?,
-                    password_hash: row.get(2)?,
-                    role: UserRole::from_str(&row.get::<_, String>(3)?).unwrap_or(UserRole::Viewer),
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    last_login_at: row
-                        .get::<_, Option<String>>(5)?
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                    failed_login_attempts: row.get(6)?,
-                    locked_until: row
-                        .get::<_, Option<String>>(7)?
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                })
-            },
+            user_from_row,
         )?;
 
         Ok(user)
@@ -101,45 +84,51 @@ impl AuthDatabaseManager {
 
     /// Get user by email
     pub fn get_user_by_email(&self, email: &str) -> Result<User> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let user = db.query_row(
             "SELECT id, email, password_hash, role, created_at, last_login_at,
              failed_login_attempts, locked_until
              FROM users WHERE email = ?1",
             [email],
-            |row| {
-                Ok(User {
-                    id: row.get(0)?,
-                    email: row.get(1)?,
-                    password_hash: row.get(2)?,
-                    role: UserRole::from_str(&row.get::<_, String>(3)?).unwrap_or(UserRole::Viewer),
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    last_login_at: row
-                        .get::<_, Option<String>>(5)?
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                    failed_login_attempts: row.get(6)?,
-                    locked_until: row
-                        .get::<_, Option<String>>(7)?
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                })
-            },
+            user_from_row,
         )?;
 
         Ok(user)
     }
 
+    /// Every registered account
+    pub fn list_users(&self) -> Result<Vec<User>> {
+        let db = self.db.get()?;
+
+        let mut stmt = db.prepare(
+            "SELECT id, email, password_hash, role, created_at, last_login_at,
+             failed_login_attempts, locked_until
+             FROM users ORDER BY created_at",
+        )?;
+        let users = stmt
+            .query_map([], user_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(users)
+    }
+
+    /// Whether any account has been registered
+    pub fn has_users(&self) -> Result<bool> {
+        let db = self.db.get()?;
+
+        let exists = db.query_row("SELECT EXISTS(SELECT 1 FROM users)", [], |row| row.get(0))?;
+
+        Ok(exists)
+    }
+
     /// Update user's failed login attempts
     pub fn record_failed_login(
         &self,
         user_id: &str,
         locked_until: Option<DateTime<Utc>>,
     ) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "UPDATE users SET
@@ -154,7 +143,7 @@ impl AuthDatabaseManager {
 
     /// Update user's successful login
     pub fn record_successful_login(&self, user_id: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "UPDATE users SET
@@ -175,7 +164,7 @@ impl AuthDatabaseManager {
         ip_address: Option<String>,
         user_agent: Option<String>,
     ) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "INSERT INTO auth_sessions (session_id, user_id, access_token, refresh_token,
@@ -199,7 +188,7 @@ impl AuthDatabaseManager {
 
     /// Get session by access token
     pub fn get_session_by_access_token(&self, access_token: &str) -> Result<Session> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let session = db.query_row(
             "SELECT session_id, user_id, access_token, refresh_token, created_at,
@@ -230,7 +219,7 @@ impl AuthDatabaseManager {
 
     /// Get session by refresh token
     pub fn get_session_by_refresh_token(&self, refresh_token: &str) -> Result<Session> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let session = db.query_row(
             "SELECT session_id, user_id, access_token, refresh_token, created_at,
@@ -261,7 +250,7 @@ impl AuthDatabaseManager {
 
     /// Update session activity
     pub fn update_session_activity(&self, session_id: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "UPDATE auth_sessions SET last_activity_at = ?1 WHERE session_id = ?2",
@@ -278,7 +267,7 @@ impl AuthDatabaseManager {
         new_access_token: &str,
         new_expires_at: DateTime<Utc>,
     ) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "UPDATE auth_sessions SET
@@ -299,7 +288,7 @@ impl AuthDatabaseManager {
 
     /// Delete session (logout)
     pub fn delete_session(&self, access_token: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "DELETE FROM auth_sessions WHERE access_token = ?1",
@@ -311,7 +300,7 @@ impl AuthDatabaseManager {
 
     /// Clean up expired sessions
     pub fn cleanup_expired_sessions(&self) -> Result<usize> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let count = db.execute(
             "DELETE FROM auth_sessions WHERE expires_at < ?1",
@@ -332,7 +321,7 @@ impl AuthDatabaseManager {
         expires_at: Option<DateTime<Utc>>,
         scope: Option<&str>,
     ) -> Result<String> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let id = Uuid::new_v4().to_string();
         let now = Utc::now();
@@ -365,7 +354,7 @@ impl AuthDatabaseManager {
         provider: OAuthProvider,
         provider_user_id: &str,
     ) -> Result<Option<String>> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let user_id: Option<String> = db
             .query_row(
@@ -380,7 +369,7 @@ impl AuthDatabaseManager {
 
     /// Update password
     pub fn update_password(&self, user_id: &str, new_password_hash: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "UPDATE users SET password_hash = ?1 WHERE id = ?2",
@@ -392,7 +381,7 @@ impl AuthDatabaseManager {
 
     /// Update user role
     pub fn update_user_role(&self, user_id: &str, role: UserRole) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "UPDATE users SET role = ?1 WHERE id = ?2",
@@ -413,7 +402,7 @@ impl AuthDatabaseManager {
         success: bool,
         error_message: Option<&str>,
     ) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         db.execute(
             "INSERT INTO auth_audit_log
@@ -438,7 +427,7 @@ impl AuthDatabaseManager {
 
     /// Get audit logs for a user
     pub fn get_user_audit_logs(&self, user_id: &str, limit: usize) -> Result<Vec<AuthAuditLog>> {
-        let db = self.db.lock();
+        let db = self.db.get()?;
 
         let mut stmt = db.prepare(
             "SELECT id, user_id, event_type, event_data, ip_address, user_agent,
@@ -469,6 +458,27 @@ impl AuthDatabaseManager {
     }
 }
 
+fn user_from_row(row: &Row<'_>) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        email: row.get(1)?,
+        password_hash: row.get(2)?,
+        role: UserRole::from_str(&row.get::<_, String>(3)?).unwrap_or(UserRole::Viewer),
+        created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+            .unwrap()
+            .with_timezone(&Utc),
+        last_login_at: row
+            .get::<_, Option<String>>(5)?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        failed_login_attempts: row.get(6)?,
+        locked_until: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthAuditLog {
     pub id: String,
@@ -485,12 +495,11 @@ pub struct AuthAuditLog {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rusqlite::Connection;
 
-    fn setup_test_db() -> Arc<parking_lot::Mutex<Connection>> {
-        let conn = Connection::open_in_memory().unwrap();
-        crate::db::migrations::run_migrations(&conn).unwrap();
-        Arc::new(parking_lot::Mutex::new(conn))
+    fn setup_test_db() -> Pool {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        pool
     }
 
     #[test]
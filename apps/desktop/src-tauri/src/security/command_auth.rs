@@ -0,0 +1,240 @@
+//! Session checks for Tauri commands.
+//!
+//! A command declares what it needs through its arguments: nothing for
+//! public commands, [`AuthenticatedUser`] for any signed-in user and [`AdminUser`]
+//! for admins. The frontend sends its access token as an
+//! `Authorization: Bearer` invoke header, and a failed check reaches it as a
+//! typed [`AuthError`] (`{ code: "AUTH_REQUIRED" | "FORBIDDEN", message }`)
+//! before the command body runs.
+//!
+//! Until the first account is registered the app has a single local user,
+//! who acts as an admin.
+
+use serde::Serialize;
+use tauri::http::header::AUTHORIZATION;
+use tauri::http::HeaderMap;
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+use tauri::{Manager, Runtime};
+use tracing::warn;
+
+use super::auth::{AuthManager, UserRole};
+use crate::commands::AuthManagerState;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(tag = "code", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthError {
+    /// No session, or it expired or timed out
+    #[error("{message}")]
+    AuthRequired { message: String },
+    /// Signed in, but the role is not enough
+    #[error("{message}")]
+    Forbidden { message: String },
+}
+
+impl AuthError {
+    fn required(message: impl Into<String>) -> Self {
+        Self::AuthRequired {
+            message: message.into(),
+        }
+    }
+}
+
+/// The caller of a command that needs a signed-in user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedUser {
+    /// `None` for the local user of an app without accounts
+    pub user_id: Option<String>,
+    pub role: UserRole,
+}
+
+impl AuthenticatedUser {
+    /// The single user of an app where nobody has registered
+    pub fn local_owner() -> Self {
+        Self {
+            user_id: None,
+            role: UserRole::Admin,
+        }
+    }
+}
+
+/// The caller of a command only admins may run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminUser(pub AuthenticatedUser);
+
+/// Check an access token, as the [`AuthenticatedUser`] argument does
+pub fn authenticate(
+    manager: &AuthManager,
+    access_token: Option<&str>,
+) -> Result<AuthenticatedUser, AuthError> {
+    if !manager.has_users() {
+        return Ok(AuthenticatedUser::local_owner());
+    }
+    let token = access_token.ok_or_else(|| AuthError::required("Sign in to continue"))?;
+    let user = manager.validate_token(token).map_err(AuthError::required)?;
+    Ok(AuthenticatedUser {
+        user_id: Some(user.id),
+        role: user.role,
+    })
+}
+
+/// Check an access token, as the [`AdminUser`] argument does
+pub fn authenticate_admin(
+    manager: &AuthManager,
+    access_token: Option<&str>,
+) -> Result<AdminUser, AuthError> {
+    let session = authenticate(manager, access_token)?;
+    if session.role != UserRole::Admin {
+        return Err(AuthError::Forbidden {
+            message: "Only admins can do this".to_string(),
+        });
+    }
+    Ok(AdminUser(session))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+fn check_command<R: Runtime, T>(
+    command: &CommandItem<'_, R>,
+    check: impl FnOnce(&AuthManager, Option<&str>) -> Result<T, AuthError>,
+) -> Result<T, InvokeError> {
+    let result = match command
+        .message
+        .webview_ref()
+        .try_state::<AuthManagerState>()
+    {
+        Some(state) => check(&state.read(), bearer_token(command.message.headers())),
+        None => Err(AuthError::required("Authentication is unavailable")),
+    };
+    result.map_err(|e| {
+        warn!("Rejected command '{}': {}", command.name, e);
+        InvokeError::from(e)
+    })
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for AuthenticatedUser {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        check_command(&command, authenticate)
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for AdminUser {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        check_command(&command, authenticate_admin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{AuthDatabaseManager, SecretManager};
+    use rusqlite::Connection;
+    use std::sync::{Arc, Mutex};
+    use tauri::http::HeaderValue;
+
+    fn auth_manager() -> AuthManager {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, encrypted INTEGER NOT NULL DEFAULT 0)",
+            [],
+        )
+        .unwrap();
+        AuthManager::new(Arc::new(SecretManager::new(Arc::new(Mutex::new(conn)))))
+    }
+
+    #[test]
+    fn test_local_owner_until_first_account() {
+        let manager = auth_manager();
+        assert_eq!(
+            authenticate(&manager, None),
+            Ok(AuthenticatedUser::local_owner())
+        );
+        assert!(authenticate_admin(&manager, None).is_ok());
+
+        manager
+            .register("a@example.com".to_string(), "Password123!", UserRole::Admin)
+            .unwrap();
+        assert!(matches!(
+            authenticate(&manager, None),
+            Err(AuthError::AuthRequired { .. })
+        ));
+        assert!(matches!(
+            authenticate(&manager, Some("stale-token")),
+            Err(AuthError::AuthRequired { .. })
+        ));
+    }
+
+    #[test]
+    fn test_persisted_accounts_require_a_session() {
+        let pool = crate::db::pool::Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let manager = auth_manager().with_database(AuthDatabaseManager::new(pool.clone()));
+        manager
+            .register("a@example.com".to_string(), "Password123!", UserRole::Admin)
+            .unwrap();
+
+        // After a restart the account is still there, so no local owner
+        let restarted = auth_manager().with_database(AuthDatabaseManager::new(pool));
+        assert!(matches!(
+            authenticate(&restarted, None),
+            Err(AuthError::AuthRequired { .. })
+        ));
+        let token = restarted.login("a@example.com", "Password123!").unwrap();
+        assert!(authenticate_admin(&restarted, Some(&token.access_token)).is_ok());
+    }
+
+    #[test]
+    fn test_roles() {
+        let manager = auth_manager();
+        manager
+            .register(
+                "viewer@example.com".to_string(),
+                "Password123!",
+                UserRole::Viewer,
+            )
+            .unwrap();
+        manager
+            .register(
+                "admin@example.com".to_string(),
+                "Password123!",
+                UserRole::Admin,
+            )
+            .unwrap();
+        let viewer = manager.login("viewer@example.com", "Password123!").unwrap();
+        let admin = manager.login("admin@example.com", "Password123!").unwrap();
+
+        let session = authenticate(&manager, Some(&viewer.access_token)).unwrap();
+        assert_eq!(session.role, UserRole::Viewer);
+        assert!(matches!(
+            authenticate_admin(&manager, Some(&viewer.access_token)),
+            Err(AuthError::Forbidden { .. })
+        ));
+        assert!(authenticate_admin(&manager, Some(&admin.access_token)).is_ok());
+    }
+
+    #[test]
+    fn test_error_shape() {
+        let json = serde_json::to_value(AuthError::required("Sign in to continue")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "code": "AUTH_REQUIRED", "message": "Sign in to continue" })
+        );
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer abc"));
+        assert_eq!(bearer_token(&headers), Some("abc"));
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer_token(&headers), None);
+    }
+}
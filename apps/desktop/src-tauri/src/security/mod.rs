@@ -4,7 +4,9 @@ pub mod audit;
 pub mod audit_logger;
 pub mod auth;
 pub mod auth_db;
+pub mod command_auth;
 pub mod encryption;
+pub mod guardrails;
 pub mod injection_detector;
 pub mod oauth;
 pub mod permissions;
@@ -13,12 +15,16 @@ pub mod policy_integration;
 pub mod prompt_injection;
 pub mod rate_limit;
 pub mod rbac;
+pub mod reauth;
 pub mod sandbox;
 pub mod secret_manager;
+pub mod secret_string;
+pub mod shell_guard;
 pub mod storage;
 pub mod tool_guard;
 pub mod updater;
 pub mod validator;
+pub mod vault;
 
 pub use api::{ApiKey, ApiSecurityManager, CorsConfig, CspBuilder};
 pub use approval_workflow::{
@@ -32,7 +38,9 @@ pub use audit_logger::{
 };
 pub use auth::{AuthManager, AuthToken, Session, User, UserRole};
 pub use auth_db::{AuthAuditLog, AuthDatabaseManager};
+pub use command_auth::{AdminUser, AuthError, AuthenticatedUser};
 pub use encryption::{EncryptedSecret, SecretStore};
+pub use guardrails::{GuardrailConfig, GuardrailReport, Guardrails};
 pub use oauth::{
     OAuthAuthorizationUrl, OAuthManager, OAuthProvider, OAuthTokenResult, OAuthUserInfo,
 };
@@ -50,8 +58,18 @@ pub use policy_integration::{
 pub use prompt_injection::{PromptInjectionDetector, SecurityAnalysis, SecurityRecommendation};
 pub use rate_limit::{RateLimitConfig, RateLimiter};
 pub use rbac::{Permission, RBACManager};
+pub use reauth::{ReauthManager, SensitiveAction};
 pub use secret_manager::{SecretError, SecretManager};
-pub use storage::{decrypt_file, encrypt_file, EncryptedData, SecureStorage};
+pub use secret_string::SecretString;
+pub use shell_guard::{ShellGuard, ShellGuardState, ShellPlan, ShellPolicy, ShellVerdict};
+pub use storage::{
+    decrypt_file, decrypt_with_password, encrypt_file, encrypt_with_password, EncryptedData,
+    SecureStorage,
+};
 pub use tool_guard::{SecurityError, ToolExecutionGuard, ToolPolicy};
 pub use updater::{UpdateMetadata, UpdateSecurityManager, VerificationResult};
 pub use validator::{CommandValidator, SafetyLevel};
+pub use vault::{
+    SecretId, SecretInfo, SecretNamespace, SecretStorage, Vault, VaultAccess, VaultAction,
+    VaultError,
+};
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::SecretString;
+
 /// OAuth2 provider types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
 #[serde(rename_all = "lowercase")]
@@ -70,7 +72,7 @@ impl OAuthProvider {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthConfig {
     pub client_id: String,
-    pub client_secret: String,
+    pub client_secret: SecretString,
     pub redirect_uri: String,
 }
 
@@ -85,8 +87,8 @@ pub struct OAuthAuthorizationUrl {
 /// OAuth2 token result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthTokenResult {
-    pub access_token: String,
-    pub refresh_token: Option<String>,
+    pub access_token: SecretString,
+    pub refresh_token: Option<SecretString>,
     pub expires_in: Option<u64>,
     pub scope: Option<String>,
 }
@@ -115,7 +117,7 @@ impl OAuthManager {
     ) -> Result<()> {
         let config = OAuthConfig {
             client_id,
-            client_secret,
+            client_secret: client_secret.into(),
             redirect_uri,
         };
 
@@ -139,7 +141,9 @@ impl OAuthManager {
         // Create OAuth2 client
         let client = BasicClient::new(
             ClientId::new(config.client_id.clone()),
-            Some(ClientSecret::new(config.client_secret.clone())),
+            Some(ClientSecret::new(
+                config.client_secret.expose_secret().to_string(),
+            )),
             AuthUrl::new(provider.auth_url().to_string())?,
             Some(TokenUrl::new(provider.token_url().to_string())?),
         )
@@ -209,7 +213,9 @@ impl OAuthManager {
         // Create OAuth2 client
         let client = BasicClient::new(
             ClientId::new(config.client_id.clone()),
-            Some(ClientSecret::new(config.client_secret.clone())),
+            Some(ClientSecret::new(
+                config.client_secret.expose_secret().to_string(),
+            )),
             AuthUrl::new(provider.auth_url().to_string())?,
             Some(TokenUrl::new(provider.token_url().to_string())?),
         )
@@ -224,8 +230,10 @@ impl OAuthManager {
             .map_err(|e| anyhow!("Token exchange failed: {}", e))?;
 
         Ok(OAuthTokenResult {
-            access_token: token_result.access_token().secret().clone(),
-            refresh_token: token_result.refresh_token().map(|t| t.secret().clone()),
+            access_token: token_result.access_token().secret().as_str().into(),
+            refresh_token: token_result
+                .refresh_token()
+                .map(|t| t.secret().as_str().into()),
             expires_in: token_result.expires_in().map(|d| d.as_secs()),
             scope: token_result.scopes().map(|scopes| {
                 scopes
@@ -251,7 +259,9 @@ impl OAuthManager {
         // Create OAuth2 client
         let client = BasicClient::new(
             ClientId::new(config.client_id.clone()),
-            Some(ClientSecret::new(config.client_secret.clone())),
+            Some(ClientSecret::new(
+                config.client_secret.expose_secret().to_string(),
+            )),
             AuthUrl::new(provider.auth_url().to_string())?,
             Some(TokenUrl::new(provider.token_url().to_string())?),
         )
@@ -265,8 +275,10 @@ impl OAuthManager {
             .map_err(|e| anyhow!("Token refresh failed: {}", e))?;
 
         Ok(OAuthTokenResult {
-            access_token: token_result.access_token().secret().clone(),
-            refresh_token: token_result.refresh_token().map(|t| t.secret().clone()),
+            access_token: token_result.access_token().secret().as_str().into(),
+            refresh_token: token_result
+                .refresh_token()
+                .map(|t| t.secret().as_str().into()),
             expires_in: token_result.expires_in().map(|d| d.as_secs()),
             scope: token_result.scopes().map(|scopes| {
                 scopes
@@ -0,0 +1,524 @@
+//! Re-authentication before sensitive actions
+//!
+//! Revealing API keys, approving dangerous shell commands, changing billing
+//! and exporting user data ask the user to prove it is them again: Windows
+//! Hello where the device has it, otherwise a password entered in the
+//! `reauth:password_required` prompt. Signed-in users enter their account
+//! password; without accounts the app keeps a local re-authentication
+//! password, created the first time it is needed. A successful check covers
+//! that action for a configurable grace period.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{oneshot, Mutex as TokioMutex};
+use tracing::{info, warn};
+
+use super::auth::{hash_password, verify_password};
+use super::command_auth::AuthenticatedUser;
+use crate::commands::AuthManagerState;
+use crate::db::pool::{DbError, Pool};
+use crate::db::repository::{get_setting, set_setting};
+
+const GRACE_PERIOD_SETTING: &str = "security.reauth_grace_secs";
+const LOCAL_PASSWORD_SETTING: &str = "security.reauth_password_hash";
+
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5 * 60);
+/// Longest grace period that can be configured
+pub const MAX_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+/// With a zero grace period a check covers one call made within this window
+const SINGLE_USE_WINDOW: Duration = Duration::from_secs(60);
+/// How long the password prompt waits for an answer
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+const MIN_LOCAL_PASSWORD_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensitiveAction {
+    RevealApiKey,
+    ApproveDangerousCommand,
+    ChangeBilling,
+    ExportUserData,
+}
+
+impl SensitiveAction {
+    /// Shown in the Windows Hello dialog and the password prompt
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            Self::RevealApiKey => "Confirm it's you to reveal an API key",
+            Self::ApproveDangerousCommand => "Confirm it's you to approve a dangerous command",
+            Self::ChangeBilling => "Confirm it's you to change billing",
+            Self::ExportUserData => "Confirm it's you to export your data",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReauthMethod {
+    WindowsHello,
+    Password,
+}
+
+/// Which password the prompt asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordKind {
+    /// The signed-in user's account password
+    Account,
+    /// The local re-authentication password
+    Local,
+    /// No local password yet; the entered one becomes it
+    CreateLocal,
+}
+
+/// Payload of the `reauth:password_required` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordPrompt {
+    pub request_id: String,
+    pub action: SensitiveAction,
+    pub message: String,
+    pub kind: PasswordKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReauthStatus {
+    pub windows_hello_available: bool,
+    pub local_password_set: bool,
+    pub grace_period_secs: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReauthError {
+    #[error("Identity check was cancelled")]
+    Cancelled,
+
+    #[error("Identity check failed: {0}")]
+    Failed(String),
+
+    #[error("Invalid password: {0}")]
+    InvalidPassword(String),
+
+    #[error(transparent)]
+    Database(#[from] DbError),
+}
+
+impl From<rusqlite::Error> for ReauthError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Database(err.into())
+    }
+}
+
+/// Commands report errors as strings
+impl From<ReauthError> for String {
+    fn from(err: ReauthError) -> Self {
+        err.to_string()
+    }
+}
+
+pub fn grace_period(conn: &Connection) -> Duration {
+    get_setting(conn, GRACE_PERIOD_SETTING)
+        .ok()
+        .and_then(|setting| setting.value.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_GRACE_PERIOD)
+}
+
+/// Zero asks on every sensitive action
+pub fn set_grace_period(conn: &Connection, period: Duration) -> Result<(), ReauthError> {
+    if period > MAX_GRACE_PERIOD {
+        return Err(ReauthError::Failed(format!(
+            "Grace period can be at most {} seconds",
+            MAX_GRACE_PERIOD.as_secs()
+        )));
+    }
+    set_setting(
+        conn,
+        GRACE_PERIOD_SETTING.to_string(),
+        period.as_secs().to_string(),
+        false,
+    )?;
+    Ok(())
+}
+
+fn local_password_hash(conn: &Connection) -> Option<String> {
+    get_setting(conn, LOCAL_PASSWORD_SETTING)
+        .ok()
+        .map(|setting| setting.value)
+}
+
+pub fn local_password_set(conn: &Connection) -> bool {
+    local_password_hash(conn).is_some()
+}
+
+/// Set the local re-authentication password; changing it needs the current one
+pub fn set_local_password(
+    conn: &Connection,
+    current: Option<&str>,
+    new: &str,
+) -> Result<(), ReauthError> {
+    if let Some(hash) = local_password_hash(conn) {
+        let current = current.unwrap_or_default();
+        if !verify_password(current, &hash).map_err(ReauthError::Failed)? {
+            return Err(ReauthError::InvalidPassword(
+                "the current password is incorrect".to_string(),
+            ));
+        }
+    }
+    if new.chars().count() < MIN_LOCAL_PASSWORD_LEN {
+        return Err(ReauthError::InvalidPassword(format!(
+            "use at least {} characters",
+            MIN_LOCAL_PASSWORD_LEN
+        )));
+    }
+    let hash = hash_password(new).map_err(ReauthError::Failed)?;
+    set_setting(conn, LOCAL_PASSWORD_SETTING.to_string(), hash, false)?;
+    Ok(())
+}
+
+fn verify_local_password(conn: &Connection, password: &str) -> Result<bool, ReauthError> {
+    match local_password_hash(conn) {
+        Some(hash) => verify_password(password, &hash).map_err(ReauthError::Failed),
+        None => Ok(false),
+    }
+}
+
+/// Recent successful checks and open password prompts. Managed as app state.
+pub struct ReauthManager {
+    pool: Pool,
+    verified: Mutex<HashMap<SensitiveAction, Instant>>,
+    prompts: TokioMutex<HashMap<String, oneshot::Sender<Option<String>>>>,
+}
+
+impl ReauthManager {
+    pub fn new(pool: Pool) -> Self {
+        Self {
+            pool,
+            verified: Mutex::new(HashMap::new()),
+            prompts: TokioMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Make sure the user confirmed their identity for `action` recently,
+    /// asking them now if not
+    pub async fn require(
+        &self,
+        app: &AppHandle,
+        user: &AuthenticatedUser,
+        action: SensitiveAction,
+    ) -> Result<(), ReauthError> {
+        let grace = self
+            .pool
+            .run(|conn| -> Result<_, ReauthError> { Ok(grace_period(conn)) })
+            .await?;
+        if self.take_recent(action, grace) {
+            return Ok(());
+        }
+
+        let method = if os_verify(app, action).await? {
+            ReauthMethod::WindowsHello
+        } else {
+            self.ask_password(app, user, action).await?;
+            ReauthMethod::Password
+        };
+        info!("Re-authenticated for {:?} with {:?}", action, method);
+        self.verified.lock().insert(action, Instant::now());
+        Ok(())
+    }
+
+    /// Forget earlier checks, e.g. on sign-out or when the screen locks
+    pub fn clear(&self) {
+        self.verified.lock().clear();
+    }
+
+    /// Whether `action` was verified within the grace period; with no grace
+    /// period a check is used up by the call it was made for
+    fn take_recent(&self, action: SensitiveAction, grace: Duration) -> bool {
+        let mut verified = self.verified.lock();
+        let window = if grace.is_zero() {
+            SINGLE_USE_WINDOW
+        } else {
+            grace
+        };
+        let recent = verified
+            .get(&action)
+            .is_some_and(|at| at.elapsed() <= window);
+        if !recent || grace.is_zero() {
+            verified.remove(&action);
+        }
+        recent
+    }
+
+    async fn ask_password(
+        &self,
+        app: &AppHandle,
+        user: &AuthenticatedUser,
+        action: SensitiveAction,
+    ) -> Result<(), ReauthError> {
+        let kind = match &user.user_id {
+            Some(_) => PasswordKind::Account,
+            None => {
+                if self
+                    .pool
+                    .run(|conn| -> Result<_, ReauthError> { Ok(local_password_set(conn)) })
+                    .await?
+                {
+                    PasswordKind::Local
+                } else {
+                    PasswordKind::CreateLocal
+                }
+            }
+        };
+        let prompt = PasswordPrompt {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            action,
+            message: action.prompt().to_string(),
+            kind,
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.prompts
+            .lock()
+            .await
+            .insert(prompt.request_id.clone(), tx);
+        if let Err(e) = app.emit("reauth:password_required", &prompt) {
+            self.prompts.lock().await.remove(&prompt.request_id);
+            return Err(ReauthError::Failed(format!(
+                "Failed to show the password prompt: {}",
+                e
+            )));
+        }
+
+        let password = match tokio::time::timeout(PROMPT_TIMEOUT, rx).await {
+            Ok(Ok(Some(password))) => password,
+            Ok(Ok(None)) | Ok(Err(_)) => return Err(ReauthError::Cancelled),
+            Err(_) => {
+                self.prompts.lock().await.remove(&prompt.request_id);
+                return Err(ReauthError::Cancelled);
+            }
+        };
+
+        let verified = match (kind, &user.user_id) {
+            (PasswordKind::Account, Some(user_id)) => {
+                let state = app
+                    .try_state::<AuthManagerState>()
+                    .ok_or_else(|| ReauthError::Failed("Accounts are unavailable".to_string()))?;
+                let verified = state.read().verify_user_password(user_id, &password);
+                verified.map_err(ReauthError::Failed)?
+            }
+            (PasswordKind::CreateLocal, _) => {
+                self.pool
+                    .run(move |conn| set_local_password(conn, None, &password))
+                    .await?;
+                true
+            }
+            _ => {
+                self.pool
+                    .run(move |conn| verify_local_password(conn, &password))
+                    .await?
+            }
+        };
+        if !verified {
+            warn!("Wrong password while re-authenticating for {:?}", action);
+            return Err(ReauthError::InvalidPassword(
+                "the password is incorrect".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Answer a password prompt; `None` cancels it
+    pub async fn submit_password(
+        &self,
+        request_id: &str,
+        password: Option<String>,
+    ) -> Result<(), ReauthError> {
+        let sender = self
+            .prompts
+            .lock()
+            .await
+            .remove(request_id)
+            .ok_or_else(|| ReauthError::Failed(format!("No password prompt {}", request_id)))?;
+        sender
+            .send(password)
+            .map_err(|_| ReauthError::Failed("The password prompt has closed".to_string()))
+    }
+
+    pub async fn status(&self) -> Result<ReauthStatus, ReauthError> {
+        let (local_password_set, grace) = self
+            .pool
+            .run(|conn| -> Result<_, ReauthError> {
+                Ok((local_password_set(conn), grace_period(conn)))
+            })
+            .await?;
+        Ok(ReauthStatus {
+            windows_hello_available: windows_hello_available().await,
+            local_password_set,
+            grace_period_secs: grace.as_secs(),
+        })
+    }
+}
+
+/// `Ok(true)` when Windows Hello confirmed the user, `Ok(false)` when the
+/// device cannot do it and the password prompt should be used
+#[cfg(windows)]
+async fn os_verify(app: &AppHandle, action: SensitiveAction) -> Result<bool, ReauthError> {
+    let hwnd = app
+        .get_webview_window("main")
+        .and_then(|window| window.hwnd().ok())
+        .map(|hwnd| hwnd.0 as isize);
+    let Some(hwnd) = hwnd else {
+        return Ok(false);
+    };
+    let outcome = tokio::task::spawn_blocking(move || hello::verify(hwnd, action.prompt()))
+        .await
+        .map_err(|e| ReauthError::Failed(e.to_string()))?;
+    match outcome {
+        Ok(hello::Outcome::Verified) => Ok(true),
+        Ok(hello::Outcome::Unavailable) => Ok(false),
+        Ok(hello::Outcome::Rejected(reason)) => Err(ReauthError::Failed(reason)),
+        Err(e) => {
+            warn!("Windows Hello failed, falling back to a password: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(not(windows))]
+async fn os_verify(_app: &AppHandle, _action: SensitiveAction) -> Result<bool, ReauthError> {
+    Ok(false)
+}
+
+#[cfg(windows)]
+async fn windows_hello_available() -> bool {
+    tokio::task::spawn_blocking(hello::available)
+        .await
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+async fn windows_hello_available() -> bool {
+    false
+}
+
+#[cfg(windows)]
+mod hello {
+    use windows::core::{factory, HSTRING};
+    use windows::Foundation::IAsyncOperation;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::WinRT::IUserConsentVerifierInterop;
+
+    pub(super) enum Outcome {
+        Verified,
+        Unavailable,
+        Rejected(String),
+    }
+
+    pub(super) fn available() -> bool {
+        UserConsentVerifier::CheckAvailabilityAsync()
+            .and_then(|op| op.get())
+            .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+    }
+
+    /// Show the Windows Hello dialog over the app window
+    pub(super) fn verify(hwnd: isize, message: &str) -> windows::core::Result<Outcome> {
+        if !available() {
+            return Ok(Outcome::Unavailable);
+        }
+        let interop = factory::<UserConsentVerifier, IUserConsentVerifierInterop>()?;
+        let operation: IAsyncOperation<UserConsentVerificationResult> = unsafe {
+            interop.RequestVerificationForWindowAsync(HWND(hwnd), &HSTRING::from(message))?
+        };
+        Ok(match operation.get()? {
+            UserConsentVerificationResult::Verified => Outcome::Verified,
+            UserConsentVerificationResult::DeviceNotPresent
+            | UserConsentVerificationResult::NotConfiguredForUser
+            | UserConsentVerificationResult::DisabledByPolicy => Outcome::Unavailable,
+            UserConsentVerificationResult::Canceled => {
+                Outcome::Rejected("Windows Hello was cancelled".to_string())
+            }
+            UserConsentVerificationResult::RetriesExhausted => {
+                Outcome::Rejected("Too many failed Windows Hello attempts".to_string())
+            }
+            _ => Outcome::Rejected("Windows Hello could not confirm your identity".to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, encrypted INTEGER NOT NULL DEFAULT 0)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_grace_period_setting() {
+        let conn = conn();
+        assert_eq!(grace_period(&conn), DEFAULT_GRACE_PERIOD);
+        set_grace_period(&conn, Duration::from_secs(30)).unwrap();
+        assert_eq!(grace_period(&conn), Duration::from_secs(30));
+        assert!(set_grace_period(&conn, MAX_GRACE_PERIOD + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_local_password() {
+        let conn = conn();
+        assert!(!local_password_set(&conn));
+        assert!(set_local_password(&conn, None, "short").is_err());
+        set_local_password(&conn, None, "correct horse").unwrap();
+        assert!(verify_local_password(&conn, "correct horse").unwrap());
+        assert!(!verify_local_password(&conn, "wrong").unwrap());
+
+        // Changing it needs the current password
+        assert!(set_local_password(&conn, None, "battery staple").is_err());
+        assert!(set_local_password(&conn, Some("wrong"), "battery staple").is_err());
+        set_local_password(&conn, Some("correct horse"), "battery staple").unwrap();
+        assert!(verify_local_password(&conn, "battery staple").unwrap());
+    }
+
+    #[test]
+    fn test_grace_period_covers_action() {
+        let manager = ReauthManager::new(Pool::in_memory().unwrap());
+        let grace = Duration::from_secs(60);
+        assert!(!manager.take_recent(SensitiveAction::ChangeBilling, grace));
+
+        manager
+            .verified
+            .lock()
+            .insert(SensitiveAction::ChangeBilling, Instant::now());
+        assert!(manager.take_recent(SensitiveAction::ChangeBilling, grace));
+        assert!(manager.take_recent(SensitiveAction::ChangeBilling, grace));
+        assert!(!manager.take_recent(SensitiveAction::ExportUserData, grace));
+
+        manager.clear();
+        assert!(!manager.take_recent(SensitiveAction::ChangeBilling, grace));
+    }
+
+    #[test]
+    fn test_zero_grace_period_is_single_use() {
+        let manager = ReauthManager::new(Pool::in_memory().unwrap());
+        manager
+            .verified
+            .lock()
+            .insert(SensitiveAction::RevealApiKey, Instant::now());
+        assert!(manager.take_recent(SensitiveAction::RevealApiKey, Duration::ZERO));
+        assert!(!manager.take_recent(SensitiveAction::RevealApiKey, Duration::ZERO));
+    }
+}
@@ -177,6 +177,116 @@ impl SecretManager {
         }
     }
 
+    /// Store a named secret (e.g. an integration refresh token)
+    ///
+    /// Uses the OS keyring when available and always keeps a database copy
+    /// so the secret survives keyring resets.
+    pub fn store_secret(&self, name: &str, value: &str) -> Result<(), SecretError> {
+        let keyring_result = Entry::new(SERVICE_NAME, &keyring_key(name))
+            .and_then(|entry| entry.set_password(value))
+            .map_err(SecretError::KeyringStoreError);
+        if let Err(e) = &keyring_result {
+            warn!(
+                "Failed to store named secret in keyring: {}",
+                sanitize_error(e)
+            );
+        }
+
+        let conn = self.db_conn.lock().unwrap();
+        let db_result = conn
+            .execute(
+                "INSERT OR REPLACE INTO settings (key, value, encrypted) VALUES (?1, ?2, 1)",
+                rusqlite::params![database_key(name), value],
+            )
+            .map(|_| ())
+            .map_err(SecretError::DatabaseStoreError);
+
+        match (keyring_result, db_result) {
+            (Err(_), Err(e)) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Retrieve a named secret, preferring the OS keyring
+    pub fn get_secret(&self, name: &str) -> Result<String, SecretError> {
+        if let Ok(secret) =
+            Entry::new(SERVICE_NAME, &keyring_key(name)).and_then(|entry| entry.get_password())
+        {
+            return Ok(secret);
+        }
+
+        let conn = self.db_conn.lock().unwrap();
+        let secret: String = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1 AND encrypted = 1",
+                rusqlite::params![database_key(name)],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => SecretError::SecretNotFound,
+                other => SecretError::DatabaseRetrieveError(other),
+            })?;
+
+        if secret.is_empty() {
+            return Err(SecretError::SecretNotFound);
+        }
+
+        Ok(secret)
+    }
+
+    /// Remove a named secret from all storage locations
+    pub fn delete_secret(&self, name: &str) -> Result<(), SecretError> {
+        if let Ok(entry) = Entry::new(SERVICE_NAME, &keyring_key(name)) {
+            let _ = entry.delete_password(); // Missing keyring entries are fine
+        }
+
+        let conn = self.db_conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM settings WHERE key = ?1",
+            rusqlite::params![database_key(name)],
+        )
+        .map_err(SecretError::DatabaseStoreError)?;
+
+        Ok(())
+    }
+
+    /// Remove the JWT secret and every named secret from all storage
+    /// locations, returning how many were removed
+    ///
+    /// # Warning
+    /// Invalidates all existing JWT tokens and integration credentials.
+    pub fn delete_all_secrets(&self) -> Result<usize, SecretError> {
+        let conn = self.db_conn.lock().unwrap();
+        let names: Vec<String> = conn
+            .prepare("SELECT substr(key, 8) FROM settings WHERE key LIKE 'secret.%'")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()
+            })
+            .map_err(SecretError::DatabaseRetrieveError)?;
+
+        // The keyring may hold entries whose database copy is already gone,
+        // but it cannot be listed, so only the known names are removed there
+        let keyring_keys = names
+            .iter()
+            .map(|name| keyring_key(name))
+            .chain(std::iter::once(JWT_SECRET_KEY.to_string()));
+        for key in keyring_keys {
+            if let Ok(entry) = Entry::new(SERVICE_NAME, &key) {
+                let _ = entry.delete_password(); // Missing keyring entries are fine
+            }
+        }
+
+        let removed = conn
+            .execute(
+                "DELETE FROM settings WHERE key LIKE 'secret.%' OR key = ?1",
+                rusqlite::params![JWT_SECRET_DB_KEY],
+            )
+            .map_err(SecretError::DatabaseStoreError)?;
+
+        Ok(removed)
+    }
+
     /// Generate a cryptographically secure random secret
     fn generate_secret(&self) -> Result<String, SecretError> {
         let mut secret_bytes = vec![0u8; SECRET_LENGTH];
@@ -265,6 +375,16 @@ impl SecretManager {
     }
 }
 
+/// Keyring account name for a named secret
+fn keyring_key(name: &str) -> String {
+    format!("agiworkforce.secret.{}", name)
+}
+
+/// Settings table key for a named secret
+fn database_key(name: &str) -> String {
+    format!("secret.{}", name)
+}
+
 /// Sanitize error messages to prevent secret leakage
 fn sanitize_error(error: &SecretError) -> String {
     match error {
@@ -331,6 +451,34 @@ mod tests {
         assert_eq!(secret, retrieved);
     }
 
+    #[test]
+    fn test_named_secret_roundtrip() {
+        let manager = create_test_manager();
+
+        manager
+            .store_secret("cloud_account.test", "refresh-token-value")
+            .unwrap();
+        assert_eq!(
+            manager.get_secret("cloud_account.test").unwrap(),
+            "refresh-token-value"
+        );
+
+        manager.delete_secret("cloud_account.test").unwrap();
+        assert!(manager.get_secret("cloud_account.test").is_err());
+    }
+
+    #[test]
+    fn test_delete_all_secrets() {
+        let manager = create_test_manager();
+        manager.store_secret("cloud_account.a", "one").unwrap();
+        manager.store_secret("cloud_account.b", "two").unwrap();
+        manager.store_secret_in_database("jwt").unwrap();
+
+        assert_eq!(manager.delete_all_secrets().unwrap(), 3);
+        assert!(manager.get_secret("cloud_account.a").is_err());
+        assert!(manager.get_secret_from_database().is_err());
+    }
+
     #[test]
     fn test_get_or_create_jwt_secret() {
         let manager = create_test_manager();
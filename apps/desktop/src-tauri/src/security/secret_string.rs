@@ -0,0 +1,269 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use zeroize::Zeroize;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// An API key, token or client secret held in memory.
+///
+/// The bytes live in a fixed allocation that is pinned in RAM where the OS
+/// allows it (`mlock` on Unix, `VirtualLock` on Windows), so it is never
+/// written to swap or the pagefile, and it is overwritten with zeros on drop.
+/// `Debug` and `Display` print `[REDACTED]`; call [`SecretString::expose_secret`]
+/// at the point the plaintext is actually sent.
+///
+/// Serialization still writes the plaintext, since credentials are persisted
+/// through the encrypted vault and returned to the frontend after OAuth.
+#[derive(Default)]
+pub struct SecretString {
+    bytes: Box<[u8]>,
+    locked: bool,
+}
+
+impl SecretString {
+    /// Take ownership of `value`, wiping the original buffer
+    pub fn new(mut value: String) -> Self {
+        let bytes: Box<[u8]> = value.as_bytes().into();
+        value.zeroize();
+        let locked = memory::lock(&bytes);
+        Self { bytes, locked }
+    }
+
+    pub fn expose_secret(&self) -> &str {
+        // Only ever built from a `String`, so always valid UTF-8
+        std::str::from_utf8(&self.bytes).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Whether the OS agreed to keep the secret out of swap
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        if self.locked {
+            memory::unlock(&self.bytes);
+        }
+    }
+}
+
+impl Clone for SecretString {
+    fn clone(&self) -> Self {
+        Self::new(self.expose_secret().to_string())
+    }
+}
+
+impl PartialEq for SecretString {
+    /// Compares in time independent of where the first difference is
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes.len() == other.bytes.len()
+            && self
+                .bytes
+                .iter()
+                .zip(other.bytes.iter())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+impl Eq for SecretString {}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SecretString").field(&REDACTED).finish()
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.expose_secret())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// OS page locks do not nest: unlocking one secret would also unlock every
+/// other secret sharing its pages. Locks are counted per page and a page is
+/// only handed back to the OS when the last secret on it is dropped.
+mod memory {
+    use std::collections::BTreeMap;
+    use std::sync::{Mutex, PoisonError};
+
+    use super::os;
+
+    static LOCKED_PAGES: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+    fn pages(bytes: &[u8]) -> impl Iterator<Item = usize> {
+        let size = os::page_size();
+        let start = bytes.as_ptr() as usize / size * size;
+        let end = bytes.as_ptr() as usize + bytes.len();
+        (start..end).step_by(size)
+    }
+
+    pub fn lock(bytes: &[u8]) -> bool {
+        if bytes.is_empty() {
+            return false;
+        }
+        let mut locked = LOCKED_PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut newly_locked = Vec::new();
+        for page in pages(bytes).filter(|page| !locked.contains_key(page)) {
+            if !os::lock_page(page) {
+                newly_locked.into_iter().for_each(os::unlock_page);
+                return false;
+            }
+            newly_locked.push(page);
+        }
+        for page in pages(bytes) {
+            *locked.entry(page).or_insert(0) += 1;
+        }
+        true
+    }
+
+    pub fn unlock(bytes: &[u8]) {
+        let mut locked = LOCKED_PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+        for page in pages(bytes) {
+            let Some(count) = locked.get_mut(&page) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                locked.remove(&page);
+                os::unlock_page(page);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn lock_count(page: usize) -> usize {
+        let locked = LOCKED_PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+        locked.get(&page).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(unix)]
+mod os {
+    pub fn page_size() -> usize {
+        // SAFETY: sysconf has no preconditions
+        match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => 4096,
+        }
+    }
+
+    pub fn lock_page(page: usize) -> bool {
+        // SAFETY: the page holds a live allocation owned by a secret
+        unsafe { libc::mlock(page as *const libc::c_void, page_size()) == 0 }
+    }
+
+    pub fn unlock_page(page: usize) {
+        // SAFETY: same page that was passed to `lock_page`
+        unsafe {
+            libc::munlock(page as *const libc::c_void, page_size());
+        }
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use windows::Win32::System::Memory::{VirtualLock, VirtualUnlock};
+
+    /// Every architecture Windows runs on uses 4 KiB pages
+    pub fn page_size() -> usize {
+        4096
+    }
+
+    pub fn lock_page(page: usize) -> bool {
+        // SAFETY: the page holds a live allocation owned by a secret
+        unsafe { VirtualLock(page as *const std::ffi::c_void, page_size()) }.is_ok()
+    }
+
+    pub fn unlock_page(page: usize) {
+        // SAFETY: same page that was passed to `lock_page`
+        let _ = unsafe { VirtualUnlock(page as *const std::ffi::c_void, page_size()) };
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod os {
+    pub fn page_size() -> usize {
+        4096
+    }
+
+    pub fn lock_page(_page: usize) -> bool {
+        false
+    }
+
+    pub fn unlock_page(_page: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_formatting() {
+        let secret = SecretString::from("sk-live-123");
+        assert_eq!(format!("{}", secret), "[REDACTED]");
+        assert_eq!(format!("{:?}", secret), "SecretString(\"[REDACTED]\")");
+        assert_eq!(secret.expose_secret(), "sk-live-123");
+    }
+
+    #[test]
+    fn clones_compare_and_round_trip() {
+        let secret = SecretString::from("token".to_string());
+        let copy = secret.clone();
+        assert_eq!(secret, copy);
+        assert_ne!(secret, SecretString::from("tokem"));
+        assert!(SecretString::default().is_empty());
+
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(json, "\"token\"");
+        let parsed: SecretString = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.expose_secret(), "token");
+    }
+
+    #[test]
+    fn shared_pages_stay_locked_until_the_last_secret_drops() {
+        let buffer = [7u8; 64];
+        let (first, second) = buffer.split_at(32);
+        if !memory::lock(first) {
+            // mlock is not permitted here
+            return;
+        }
+        assert!(memory::lock(second));
+
+        let page = first.as_ptr() as usize / os::page_size() * os::page_size();
+        memory::unlock(first);
+        // Still held for the second secret on the same page
+        assert!(memory::lock_count(page) >= 1);
+        memory::unlock(second);
+    }
+}
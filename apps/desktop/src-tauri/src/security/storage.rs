@@ -212,13 +212,11 @@ fn generate_salt() -> Vec<u8> {
     salt
 }
 
-/// Encrypt file at rest with AES-256-GCM
+/// Encrypt a buffer with a password-derived AES-256-GCM key
+///
+/// Output format: [salt (32 bytes)][nonce (12 bytes)][ciphertext]
 #[allow(deprecated)]
-pub fn encrypt_file(input_path: &str, output_path: &str, password: &str) -> Result<(), String> {
-    use std::fs;
-
-    let plaintext = fs::read(input_path).map_err(|e| format!("Failed to read file: {}", e))?;
-
+pub fn encrypt_with_password(plaintext: &[u8], password: &str) -> Result<Vec<u8>, String> {
     let salt = generate_salt();
     let key = derive_key_from_password(password, &salt);
 
@@ -231,29 +229,22 @@ pub fn encrypt_file(input_path: &str, output_path: &str, password: &str) -> Resu
     let nonce = Nonce::from_slice(&nonce_bytes);
 
     let ciphertext = cipher
-        .encrypt(nonce, plaintext.as_ref())
+        .encrypt(nonce, plaintext)
         .map_err(|e| format!("Encryption failed: {}", e))?;
 
-    // Format: [salt (32 bytes)][nonce (12 bytes)][ciphertext]
-    let mut output = Vec::new();
+    let mut output = Vec::with_capacity(SALT_SIZE + NONCE_SIZE + ciphertext.len());
     output.extend_from_slice(&salt);
     output.extend_from_slice(&nonce_bytes);
     output.extend_from_slice(&ciphertext);
 
-    fs::write(output_path, output).map_err(|e| format!("Failed to write encrypted file: {}", e))?;
-
-    Ok(())
+    Ok(output)
 }
 
-/// Decrypt file encrypted with encrypt_file
+/// Decrypt a buffer produced by encrypt_with_password
 #[allow(deprecated)]
-pub fn decrypt_file(input_path: &str, output_path: &str, password: &str) -> Result<(), String> {
-    use std::fs;
-
-    let encrypted_data = fs::read(input_path).map_err(|e| format!("Failed to read file: {}", e))?;
-
+pub fn decrypt_with_password(encrypted_data: &[u8], password: &str) -> Result<Vec<u8>, String> {
     if encrypted_data.len() < SALT_SIZE + NONCE_SIZE {
-        return Err("Invalid encrypted file format".to_string());
+        return Err("Invalid encrypted data format".to_string());
     }
 
     // Parse: [salt (32 bytes)][nonce (12 bytes)][ciphertext]
@@ -268,9 +259,34 @@ pub fn decrypt_file(input_path: &str, output_path: &str, password: &str) -> Resu
 
     let nonce = Nonce::from_slice(nonce_bytes);
 
-    let plaintext = cipher
+    cipher
         .decrypt(nonce, ciphertext)
-        .map_err(|e| format!("Decryption failed (wrong password?): {}", e))?;
+        .map_err(|e| format!("Decryption failed (wrong password?): {}", e))
+}
+
+/// Encrypt file at rest with AES-256-GCM
+pub fn encrypt_file(input_path: &str, output_path: &str, password: &str) -> Result<(), String> {
+    use std::fs;
+
+    let plaintext = fs::read(input_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let output = encrypt_with_password(&plaintext, password)?;
+
+    fs::write(output_path, output).map_err(|e| format!("Failed to write encrypted file: {}", e))?;
+
+    Ok(())
+}
+
+/// Decrypt file encrypted with encrypt_file
+pub fn decrypt_file(input_path: &str, output_path: &str, password: &str) -> Result<(), String> {
+    use std::fs;
+
+    let encrypted_data = fs::read(input_path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    if encrypted_data.len() < SALT_SIZE + NONCE_SIZE {
+        return Err("Invalid encrypted file format".to_string());
+    }
+
+    let plaintext = decrypt_with_password(&encrypted_data, password)?;
 
     fs::write(output_path, plaintext)
         .map_err(|e| format!("Failed to write decrypted file: {}", e))?;
@@ -125,6 +125,45 @@ impl ToolExecutionGuard {
             },
         );
 
+        // Page interactions; recorded browser scripts replay through these
+        let target = ["selector", "fallbacks", "description", "tab_id"];
+        for (tool, extra) in [
+            ("browser_click", &[][..]),
+            ("browser_type", &["text", "clear_first"][..]),
+            ("browser_select", &["value"][..]),
+            ("browser_check", &["checked"][..]),
+            ("browser_press_key", &["key"][..]),
+        ] {
+            allowed_tools.insert(
+                tool.to_string(),
+                ToolPolicy {
+                    max_rate_per_minute: 60,
+                    requires_approval: false,
+                    allowed_parameters: target
+                        .iter()
+                        .chain(extra)
+                        .map(|name| name.to_string())
+                        .collect(),
+                    risk_level: RiskLevel::Medium,
+                },
+            );
+        }
+
+        allowed_tools.insert(
+            "browser_wait_for_download".to_string(),
+            ToolPolicy {
+                max_rate_per_minute: 30,
+                requires_approval: false,
+                allowed_parameters: vec![
+                    "timeout_ms".to_string(),
+                    "file_name".to_string(),
+                    "sha256".to_string(),
+                    "tab_id".to_string(),
+                ],
+                risk_level: RiskLevel::Low,
+            },
+        );
+
         allowed_tools.insert(
             "code_execute".to_string(),
             ToolPolicy {
@@ -565,6 +604,10 @@ mod tests {
             guard.get_risk_level("code_execute"),
             Some(RiskLevel::Critical)
         );
+        assert_eq!(
+            guard.get_risk_level("browser_type"),
+            Some(RiskLevel::Medium)
+        );
     }
 
     #[test]
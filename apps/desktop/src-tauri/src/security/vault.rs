@@ -0,0 +1,778 @@
+//! Namespaced secret storage
+//!
+//! Provider API keys, integration credentials and account tokens all go
+//! through [`Vault`]. Values live in the OS keyring when it accepts them and
+//! otherwise AES-256-GCM encrypted in the `vault_secrets` table; which one is
+//! recorded per secret. Every read, write, rotation and deletion lands in
+//! `vault_access_log`.
+
+use std::fmt;
+use std::path::Path;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, TimeZone, Utc};
+use keyring::Entry;
+use rand::RngCore;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::db::pool::{Pool, PoolError};
+use crate::router::Provider;
+use crate::security::SecretString;
+
+const KEYRING_SERVICE: &str = "AGIWorkforce Vault";
+const MASTER_KEY_NAME: &str = "vault_master_key";
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+
+/// Keyring service API keys were kept under before the vault
+const LEGACY_SERVICE: &str = "AGIWorkforce";
+/// Providers with legacy `api_key_<provider>` keyring entries besides the
+/// LLM router's
+const LEGACY_EXTRA_PROVIDERS: [&str; 3] = ["github", "stability", "midjourney"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum VaultError {
+    #[error("Secret not found: {0}")]
+    NotFound(SecretId),
+
+    #[error("Invalid secret name: {0}")]
+    InvalidName(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error("Database connection unavailable: {0}")]
+    Pool(#[from] PoolError),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Keyring error: {0}")]
+    Keyring(String),
+
+    #[error("Master key error: {0}")]
+    MasterKey(String),
+}
+
+pub type VaultResult<T> = Result<T, VaultError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretNamespace {
+    /// LLM and other API provider keys
+    Provider,
+    /// Credentials for a connected integration, such as an MCP server
+    Integration,
+    /// Tokens for a signed-in account
+    Account,
+}
+
+impl SecretNamespace {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecretNamespace::Provider => "provider",
+            SecretNamespace::Integration => "integration",
+            SecretNamespace::Account => "account",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "provider" => Some(SecretNamespace::Provider),
+            "integration" => Some(SecretNamespace::Integration),
+            "account" => Some(SecretNamespace::Account),
+            _ => None,
+        }
+    }
+}
+
+/// Where a secret is in its namespace, e.g. `provider/openai` or
+/// `integration/mcp-github/GITHUB_TOKEN`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecretId {
+    pub namespace: SecretNamespace,
+    pub name: String,
+}
+
+impl SecretId {
+    pub fn new(namespace: SecretNamespace, name: impl Into<String>) -> Self {
+        Self {
+            namespace,
+            name: name.into(),
+        }
+    }
+
+    pub fn provider(provider: &str) -> Self {
+        Self::new(SecretNamespace::Provider, provider.to_lowercase())
+    }
+
+    pub fn integration(integration: &str, key: &str) -> Self {
+        Self::new(
+            SecretNamespace::Integration,
+            format!("{}/{}", integration, key),
+        )
+    }
+
+    pub fn account(account: &str) -> Self {
+        Self::new(SecretNamespace::Account, account)
+    }
+
+    fn validate(&self) -> VaultResult<()> {
+        if self.name.trim().is_empty() || self.name.chars().any(char::is_control) {
+            return Err(VaultError::InvalidName(self.name.clone()));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SecretId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.namespace.as_str(), self.name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretStorage {
+    Keyring,
+    Database,
+}
+
+impl SecretStorage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecretStorage::Keyring => "keyring",
+            SecretStorage::Database => "database",
+        }
+    }
+}
+
+/// A stored secret, without its value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretInfo {
+    pub id: SecretId,
+    pub storage: SecretStorage,
+    /// Incremented on every write
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultAction {
+    Read,
+    Write,
+    Rotate,
+    Delete,
+}
+
+impl VaultAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            VaultAction::Read => "read",
+            VaultAction::Write => "write",
+            VaultAction::Rotate => "rotate",
+            VaultAction::Delete => "delete",
+        }
+    }
+
+    fn from_str(value: &str) -> Self {
+        match value {
+            "write" => VaultAction::Write,
+            "rotate" => VaultAction::Rotate,
+            "delete" => VaultAction::Delete,
+            _ => VaultAction::Read,
+        }
+    }
+}
+
+/// One entry of the access log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultAccess {
+    pub id: i64,
+    pub secret: SecretId,
+    pub action: VaultAction,
+    /// What asked for the secret, e.g. `settings` or `mcp`
+    pub actor: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Namespaced secrets in the OS keyring with an encrypted database fallback
+pub struct Vault {
+    pool: Pool,
+    cipher: Aes256Gcm,
+    use_keyring: bool,
+}
+
+impl Vault {
+    /// Open the vault over the app database. The fallback encryption key is
+    /// kept in the OS keyring, or in `key_file` when there is no keyring.
+    pub fn open(pool: Pool, key_file: &Path) -> VaultResult<Self> {
+        let key = load_or_create_master_key(key_file)?;
+        Ok(Self::with_key(pool, key, true))
+    }
+
+    /// Vault with an explicit fallback key; without the keyring every secret
+    /// is stored in the database
+    pub fn with_key(pool: Pool, key: [u8; KEY_SIZE], use_keyring: bool) -> Self {
+        Self {
+            pool,
+            cipher: Aes256Gcm::new(&Key::<Aes256Gcm>::from(key)),
+            use_keyring,
+        }
+    }
+
+    /// Database-only vault over a private in-memory database
+    #[cfg(test)]
+    pub(crate) fn in_memory() -> Self {
+        let pool = Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        Self::with_key(pool, [7u8; KEY_SIZE], false)
+    }
+
+    /// Store a secret, replacing any previous value
+    pub fn put(&self, id: &SecretId, value: &str, actor: &str) -> VaultResult<SecretStorage> {
+        let result = self.write(id, value, false);
+        self.record(id, VaultAction::Write, actor, &result);
+        result
+    }
+
+    /// Replace an existing secret and mark it rotated. Returns the new version.
+    pub fn rotate(&self, id: &SecretId, new_value: &str, actor: &str) -> VaultResult<u32> {
+        let result = self.info(id).and_then(|info| match info {
+            Some(_) => self.write(id, new_value, true),
+            None => Err(VaultError::NotFound(id.clone())),
+        });
+        self.record(id, VaultAction::Rotate, actor, &result);
+        result?;
+        let info = self
+            .info(id)?
+            .ok_or_else(|| VaultError::NotFound(id.clone()))?;
+        info!("Rotated secret {} to version {}", id, info.version);
+        Ok(info.version)
+    }
+
+    /// Read a secret; `None` when it was never stored
+    pub fn get(&self, id: &SecretId, actor: &str) -> VaultResult<Option<SecretString>> {
+        let result = self.read(id);
+        self.record(id, VaultAction::Read, actor, &result);
+        result
+    }
+
+    /// Remove a secret from both storages. Returns whether it existed.
+    pub fn delete(&self, id: &SecretId, actor: &str) -> VaultResult<bool> {
+        let result = self.remove(id);
+        self.record(id, VaultAction::Delete, actor, &result);
+        result
+    }
+
+    /// Remove every secret from both storages, returning how many there were
+    pub fn delete_all(&self, actor: &str) -> VaultResult<usize> {
+        let secrets = self.list(None)?;
+        for secret in &secrets {
+            self.delete(&secret.id, actor)?;
+        }
+        Ok(secrets.len())
+    }
+
+    /// Stored secrets, optionally limited to one namespace
+    pub fn list(&self, namespace: Option<SecretNamespace>) -> VaultResult<Vec<SecretInfo>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT namespace, name, storage, version, created_at, updated_at, rotated_at,
+                    last_accessed_at
+             FROM vault_secrets
+             WHERE ?1 IS NULL OR namespace = ?1
+             ORDER BY namespace, name",
+        )?;
+        let secrets = stmt
+            .query_map(
+                params![namespace.map(|ns| ns.as_str())],
+                secret_info_from_row,
+            )?
+            .filter_map(|row| row.transpose())
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(secrets)
+    }
+
+    /// Latest access log entries, newest first, optionally for one secret
+    pub fn access_log(&self, id: Option<&SecretId>, limit: usize) -> VaultResult<Vec<VaultAccess>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, namespace, name, action, actor, success, error, created_at
+             FROM vault_access_log
+             WHERE ?1 IS NULL OR (namespace = ?1 AND name = ?2)
+             ORDER BY id DESC
+             LIMIT ?3",
+        )?;
+        let entries = stmt
+            .query_map(
+                params![
+                    id.map(|id| id.namespace.as_str()),
+                    id.map(|id| id.name.as_str()),
+                    limit as i64
+                ],
+                |row| {
+                    let namespace: String = row.get(1)?;
+                    let Some(namespace) = SecretNamespace::from_str(&namespace) else {
+                        return Ok(None);
+                    };
+                    let action: String = row.get(3)?;
+                    Ok(Some(VaultAccess {
+                        id: row.get(0)?,
+                        secret: SecretId::new(namespace, row.get::<_, String>(2)?),
+                        action: VaultAction::from_str(&action),
+                        actor: row.get(4)?,
+                        success: row.get(5)?,
+                        error: row.get(6)?,
+                        created_at: timestamp(row.get(7)?),
+                    }))
+                },
+            )?
+            .filter_map(|row| row.transpose())
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(entries)
+    }
+
+    /// Metadata for one secret
+    pub fn info(&self, id: &SecretId) -> VaultResult<Option<SecretInfo>> {
+        let conn = self.pool.get()?;
+        let info = conn
+            .query_row(
+                "SELECT namespace, name, storage, version, created_at, updated_at, rotated_at,
+                        last_accessed_at
+                 FROM vault_secrets WHERE namespace = ?1 AND name = ?2",
+                params![id.namespace.as_str(), id.name],
+                secret_info_from_row,
+            )
+            .optional()?
+            .flatten();
+        Ok(info)
+    }
+
+    /// Move provider API keys from their pre-vault keyring entries into the
+    /// vault. Returns how many moved.
+    pub fn migrate_legacy_api_keys(&self) -> VaultResult<usize> {
+        let providers = Provider::ALL
+            .iter()
+            .map(|provider| provider.as_string())
+            .chain(LEGACY_EXTRA_PROVIDERS);
+        self.migrate_legacy(providers.map(|provider| {
+            (
+                LEGACY_SERVICE.to_string(),
+                format!("api_key_{}", provider),
+                SecretId::provider(provider),
+            )
+        }))
+    }
+
+    /// Move MCP server credentials, given as `(server, env key)` pairs, from
+    /// their pre-vault keyring entries into the vault. Returns how many moved.
+    pub fn migrate_legacy_mcp_credentials(
+        &self,
+        credentials: &[(String, String)],
+    ) -> VaultResult<usize> {
+        self.migrate_legacy(credentials.iter().map(|(server, key)| {
+            (
+                format!("agiworkforce-mcp-{}", server),
+                key.clone(),
+                mcp_secret_id(server, key),
+            )
+        }))
+    }
+
+    /// Copy each `(service, account)` keyring entry to its vault id and
+    /// remove the original
+    fn migrate_legacy(
+        &self,
+        entries: impl Iterator<Item = (String, String, SecretId)>,
+    ) -> VaultResult<usize> {
+        if !self.use_keyring {
+            return Ok(0);
+        }
+
+        let mut moved = 0;
+        for (service, account, id) in entries {
+            let Ok(entry) = Entry::new(&service, &account) else {
+                continue;
+            };
+            let Ok(value) = entry.get_password() else {
+                continue;
+            };
+            // A value already in the vault is newer than the legacy copy
+            if self.info(&id)?.is_none() {
+                self.put(&id, &value, "migration")?;
+                moved += 1;
+            }
+            if let Err(e) = entry.delete_password() {
+                warn!("Failed to remove legacy keyring entry for {}: {}", id, e);
+            }
+        }
+
+        if moved > 0 {
+            info!(
+                "Moved {} secrets from legacy keyring entries into the vault",
+                moved
+            );
+        }
+        Ok(moved)
+    }
+
+    fn write(&self, id: &SecretId, value: &str, rotated: bool) -> VaultResult<SecretStorage> {
+        id.validate()?;
+        let previous = self.info(id)?.map(|info| info.storage);
+
+        let storage = match self.keyring_entry(id) {
+            Some(entry) => match entry.set_password(value) {
+                Ok(()) => SecretStorage::Keyring,
+                Err(e) => {
+                    warn!("Keyring refused secret {}, storing it encrypted: {}", id, e);
+                    SecretStorage::Database
+                }
+            },
+            None => SecretStorage::Database,
+        };
+        let ciphertext = match storage {
+            SecretStorage::Keyring => None,
+            SecretStorage::Database => Some(self.encrypt(value)?),
+        };
+
+        let now = Utc::now().timestamp();
+        let conn = self.pool.get()?;
+        conn.execute(
+            "INSERT INTO vault_secrets (namespace, name, storage, ciphertext, version, created_at,
+                                        updated_at, rotated_at)
+             VALUES (?1, ?2, ?3, ?4, 1, ?5, ?5, NULL)
+             ON CONFLICT(namespace, name) DO UPDATE SET
+                storage = excluded.storage,
+                ciphertext = excluded.ciphertext,
+                version = version + 1,
+                updated_at = excluded.updated_at,
+                rotated_at = CASE WHEN ?6 THEN excluded.updated_at ELSE rotated_at END",
+            params![
+                id.namespace.as_str(),
+                id.name,
+                storage.as_str(),
+                ciphertext,
+                now,
+                rotated
+            ],
+        )?;
+
+        // Do not leave an older copy behind in the other storage
+        if previous == Some(SecretStorage::Keyring) && storage == SecretStorage::Database {
+            if let Some(entry) = self.keyring_entry(id) {
+                let _ = entry.delete_password();
+            }
+        }
+        Ok(storage)
+    }
+
+    fn read(&self, id: &SecretId) -> VaultResult<Option<SecretString>> {
+        let conn = self.pool.get()?;
+        let row: Option<(String, Option<Vec<u8>>)> = conn
+            .query_row(
+                "SELECT storage, ciphertext FROM vault_secrets WHERE namespace = ?1 AND name = ?2",
+                params![id.namespace.as_str(), id.name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((storage, ciphertext)) = row else {
+            return Ok(None);
+        };
+
+        let value = match (storage.as_str(), ciphertext) {
+            ("database", Some(ciphertext)) => self.decrypt(&ciphertext)?,
+            _ => self
+                .keyring_entry(id)
+                .ok_or_else(|| VaultError::Keyring("keyring is disabled".to_string()))?
+                .get_password()
+                .map_err(|e| VaultError::Keyring(e.to_string()))?,
+        };
+
+        conn.execute(
+            "UPDATE vault_secrets SET last_accessed_at = ?3 WHERE namespace = ?1 AND name = ?2",
+            params![id.namespace.as_str(), id.name, Utc::now().timestamp()],
+        )?;
+        Ok(Some(SecretString::new(value)))
+    }
+
+    fn remove(&self, id: &SecretId) -> VaultResult<bool> {
+        if let Some(entry) = self.keyring_entry(id) {
+            let _ = entry.delete_password(); // Missing keyring entries are fine
+        }
+        let conn = self.pool.get()?;
+        let removed = conn.execute(
+            "DELETE FROM vault_secrets WHERE namespace = ?1 AND name = ?2",
+            params![id.namespace.as_str(), id.name],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Append to the access log. A failure to log is reported but does not
+    /// fail the access itself.
+    fn record<T>(&self, id: &SecretId, action: VaultAction, actor: &str, result: &VaultResult<T>) {
+        let logged = self.pool.get().map_err(VaultError::from).and_then(|conn| {
+            log_access(&conn, id, action, actor, result.as_ref().err())?;
+            Ok(())
+        });
+        if let Err(e) = logged {
+            warn!("Failed to log vault {} of {}: {}", action.as_str(), id, e);
+        }
+    }
+
+    fn keyring_entry(&self, id: &SecretId) -> Option<Entry> {
+        if !self.use_keyring {
+            return None;
+        }
+        Entry::new(KEYRING_SERVICE, &id.to_string()).ok()
+    }
+
+    fn encrypt(&self, plaintext: &str) -> VaultResult<Vec<u8>> {
+        let mut nonce_bytes = [0u8; NONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let ciphertext = self
+            .cipher
+            .encrypt(&Nonce::from(nonce_bytes), plaintext.as_bytes())
+            .map_err(|e| VaultError::Encryption(format!("Encryption failed: {}", e)))?;
+
+        let mut combined = nonce_bytes.to_vec();
+        combined.extend_from_slice(&ciphertext);
+        Ok(combined)
+    }
+
+    fn decrypt(&self, combined: &[u8]) -> VaultResult<String> {
+        if combined.len() < NONCE_SIZE {
+            return Err(VaultError::Encryption("Encrypted data too short".into()));
+        }
+        let (nonce_bytes, ciphertext) = combined.split_at(NONCE_SIZE);
+        let nonce: [u8; NONCE_SIZE] = nonce_bytes
+            .try_into()
+            .map_err(|_| VaultError::Encryption("Invalid nonce length".into()))?;
+        let plaintext = self
+            .cipher
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|e| VaultError::Encryption(format!("Decryption failed: {}", e)))?;
+        String::from_utf8(plaintext)
+            .map_err(|e| VaultError::Encryption(format!("Invalid UTF-8: {}", e)))
+    }
+}
+
+/// Vault id for an MCP server credential
+pub fn mcp_secret_id(server: &str, key: &str) -> SecretId {
+    SecretId::integration(&format!("mcp-{}", server), key)
+}
+
+fn log_access(
+    conn: &Connection,
+    id: &SecretId,
+    action: VaultAction,
+    actor: &str,
+    error: Option<&VaultError>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO vault_access_log (namespace, name, action, actor, success, error, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            id.namespace.as_str(),
+            id.name,
+            action.as_str(),
+            actor,
+            error.is_none(),
+            error.map(|e| e.to_string()),
+            Utc::now().timestamp()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Rows in an unknown namespace, written by a newer build, are skipped
+fn secret_info_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Option<SecretInfo>> {
+    let namespace: String = row.get(0)?;
+    let Some(namespace) = SecretNamespace::from_str(&namespace) else {
+        return Ok(None);
+    };
+    let storage: String = row.get(2)?;
+    Ok(Some(SecretInfo {
+        id: SecretId::new(namespace, row.get::<_, String>(1)?),
+        storage: if storage == "database" {
+            SecretStorage::Database
+        } else {
+            SecretStorage::Keyring
+        },
+        version: row.get(3)?,
+        created_at: timestamp(row.get(4)?),
+        updated_at: timestamp(row.get(5)?),
+        rotated_at: row.get::<_, Option<i64>>(6)?.map(timestamp),
+        last_accessed_at: row.get::<_, Option<i64>>(7)?.map(timestamp),
+    }))
+}
+
+fn timestamp(secs: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(secs, 0).single().unwrap_or_default()
+}
+
+/// The fallback encryption key: from the OS keyring, else from `key_file`,
+/// else newly generated and saved to whichever of the two accepts it
+fn load_or_create_master_key(key_file: &Path) -> VaultResult<[u8; KEY_SIZE]> {
+    let entry = Entry::new(KEYRING_SERVICE, MASTER_KEY_NAME).ok();
+    if let Some(encoded) = entry.as_ref().and_then(|entry| entry.get_password().ok()) {
+        return decode_key(&encoded);
+    }
+    if key_file.exists() {
+        let encoded = std::fs::read_to_string(key_file)
+            .map_err(|e| VaultError::MasterKey(format!("Failed to read key file: {}", e)))?;
+        return decode_key(encoded.trim());
+    }
+
+    let mut key = [0u8; KEY_SIZE];
+    OsRng.fill_bytes(&mut key);
+    let encoded = general_purpose::STANDARD.encode(key);
+    let in_keyring = entry.is_some_and(|entry| entry.set_password(&encoded).is_ok());
+    if !in_keyring {
+        warn!(
+            "OS keyring unavailable; keeping the vault key in {}",
+            key_file.display()
+        );
+        write_key_file(key_file, &encoded)
+            .map_err(|e| VaultError::MasterKey(format!("Failed to write key file: {}", e)))?;
+    }
+    Ok(key)
+}
+
+fn decode_key(encoded: &str) -> VaultResult<[u8; KEY_SIZE]> {
+    general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| VaultError::MasterKey("Invalid master key".to_string()))
+}
+
+/// Write the key readable by the current user only
+fn write_key_file(path: &Path, encoded: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    std::io::Write::write_all(&mut options.open(path)?, encoded.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_round_trip_encrypted() {
+        let vault = Vault::in_memory();
+        let id = SecretId::provider("OpenAI");
+        assert_eq!(id.to_string(), "provider/openai");
+        assert!(vault.get(&id, "test").unwrap().is_none());
+
+        let storage = vault.put(&id, "sk-test-123", "test").unwrap();
+        assert_eq!(storage, SecretStorage::Database);
+        assert_eq!(
+            vault.get(&id, "test").unwrap().unwrap().expose_secret(),
+            "sk-test-123"
+        );
+
+        // Only ciphertext reaches the database
+        let stored: Vec<u8> = vault
+            .pool
+            .get()
+            .unwrap()
+            .query_row("SELECT ciphertext FROM vault_secrets", [], |row| row.get(0))
+            .unwrap();
+        assert!(!stored.windows(11).any(|window| window == b"sk-test-123"));
+    }
+
+    #[test]
+    fn test_rotate_bumps_version() {
+        let vault = Vault::in_memory();
+        let id = mcp_secret_id("github", "GITHUB_TOKEN");
+        assert!(matches!(
+            vault.rotate(&id, "new", "test"),
+            Err(VaultError::NotFound(_))
+        ));
+
+        vault.put(&id, "old", "test").unwrap();
+        assert_eq!(vault.rotate(&id, "new", "test").unwrap(), 2);
+        let info = vault.info(&id).unwrap().unwrap();
+        assert!(info.rotated_at.is_some());
+        assert_eq!(
+            vault.get(&id, "test").unwrap().unwrap().expose_secret(),
+            "new"
+        );
+    }
+
+    #[test]
+    fn test_list_delete_and_access_log() {
+        let vault = Vault::in_memory();
+        let key = SecretId::provider("anthropic");
+        let token = SecretId::account("cloud");
+        vault.put(&key, "a", "settings").unwrap();
+        vault.put(&token, "b", "auth").unwrap();
+
+        let providers = vault.list(Some(SecretNamespace::Provider)).unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id, key);
+        assert_eq!(vault.list(None).unwrap().len(), 2);
+
+        vault.get(&key, "router").unwrap();
+        assert!(vault.delete(&key, "settings").unwrap());
+        assert!(!vault.delete(&key, "settings").unwrap());
+        assert!(vault.put(&SecretId::provider(" "), "x", "test").is_err());
+
+        let log = vault.access_log(Some(&key), 10).unwrap();
+        let actions: Vec<_> = log
+            .iter()
+            .map(|entry| (entry.action, entry.actor.as_str()))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (VaultAction::Delete, "settings"),
+                (VaultAction::Delete, "settings"),
+                (VaultAction::Read, "router"),
+                (VaultAction::Write, "settings"),
+            ]
+        );
+        assert_eq!(vault.access_log(None, 100).unwrap().len(), 6);
+        assert!(!vault.access_log(None, 1).unwrap()[0].success);
+
+        assert_eq!(vault.delete_all("privacy").unwrap(), 1);
+        assert!(vault.list(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_master_key_file_fallback_is_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.key");
+        write_key_file(&path, &general_purpose::STANDARD.encode([3u8; KEY_SIZE])).unwrap();
+        assert_eq!(
+            decode_key(&std::fs::read_to_string(&path).unwrap()).unwrap(),
+            [3u8; KEY_SIZE]
+        );
+        assert!(write_key_file(&path, "other").is_err());
+        assert!(decode_key("not a key").is_err());
+    }
+}
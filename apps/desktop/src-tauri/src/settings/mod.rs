@@ -16,8 +16,8 @@ mod tests;
 
 // Re-export commonly used types
 pub use models::{
-    AppSettings, LLMProviderConfig, ModelConfig, SecuritySettings, Setting, SettingCategory,
-    SettingValue, UIPreferences, WindowStatePreferences,
+    AppSettings, LLMProviderConfig, ModelConfig, NotificationPreferences, SecuritySettings,
+    Setting, SettingCategory, SettingValue, UIPreferences, WindowStatePreferences,
 };
 
 pub use repository::{
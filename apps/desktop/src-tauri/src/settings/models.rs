@@ -289,6 +289,34 @@ impl Default for SecuritySettings {
     }
 }
 
+/// Which events raise a native OS notification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationPreferences {
+    pub task_completion: bool,
+    pub approval_requests: bool,
+    pub budget_warnings: bool,
+    pub email_rules: bool,
+    pub credential_expiry: bool,
+    pub integration_health: bool,
+    /// Also notify while the main window has focus
+    pub show_when_focused: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            task_completion: true,
+            approval_requests: true,
+            budget_warnings: true,
+            email_rules: true,
+            credential_expiry: true,
+            integration_health: true,
+            show_when_focused: false,
+        }
+    }
+}
+
 /// Complete application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -300,6 +328,8 @@ pub struct AppSettings {
     pub ui_preferences: UIPreferences,
     pub window_preferences: WindowStatePreferences,
     pub security_settings: SecuritySettings,
+    #[serde(default)]
+    pub notification_preferences: NotificationPreferences,
     pub schema_version: u32,
 }
 
@@ -345,6 +375,7 @@ impl Default for AppSettings {
             ui_preferences: UIPreferences::default(),
             window_preferences: WindowStatePreferences::default(),
             security_settings: SecuritySettings::default(),
+            notification_preferences: NotificationPreferences::default(),
             schema_version: 1,
         }
     }
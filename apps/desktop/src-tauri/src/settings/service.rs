@@ -1,3 +1,5 @@
+use crate::db::pool::{Pool, PoolError};
+use crate::security::vault::{SecretId, Vault, VaultError};
 use crate::settings::{
     models::{AppSettings, Setting, SettingCategory, SettingValue},
     repository,
@@ -9,7 +11,6 @@ use aes_gcm::{
 };
 use base64::{engine::general_purpose, Engine as _};
 use keyring::Entry;
-use rusqlite::Connection;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::{Arc, Mutex};
@@ -24,6 +25,9 @@ pub enum SettingsServiceError {
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[error("Database connection unavailable: {0}")]
+    Pool(#[from] PoolError),
+
     #[error("Validation error: {0}")]
     Validation(#[from] ValidationError),
 
@@ -36,6 +40,9 @@ pub enum SettingsServiceError {
     #[error("Keyring error: {0}")]
     Keyring(String),
 
+    #[error("Vault error: {0}")]
+    Vault(#[from] VaultError),
+
     #[error("Setting not found: {0}")]
     NotFound(String),
 
@@ -45,14 +52,15 @@ pub enum SettingsServiceError {
 
 /// Settings service with encryption and caching
 pub struct SettingsService {
-    conn: Arc<Mutex<Connection>>,
+    conn: Pool,
     cipher: Arc<Mutex<Aes256Gcm>>,
     cache: Arc<Mutex<HashMap<String, SettingValue>>>,
+    vault: Arc<Vault>,
 }
 
 impl SettingsService {
     /// Create a new settings service
-    pub fn new(conn: Arc<Mutex<Connection>>) -> Result<Self, SettingsServiceError> {
+    pub fn new(conn: Pool, vault: Arc<Vault>) -> Result<Self, SettingsServiceError> {
         // Get or create encryption key
         let master_key = Self::get_or_create_master_key()?;
         let key_bytes: [u8; 32] = master_key
@@ -66,6 +74,7 @@ impl SettingsService {
             conn,
             cipher: Arc::new(Mutex::new(cipher)),
             cache: Arc::new(Mutex::new(HashMap::new())),
+            vault,
         })
     }
 
@@ -166,7 +175,7 @@ impl SettingsService {
         // Validate based on key
         self.validate_setting(&key, &value)?;
 
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
 
         // Encrypt value if needed
         let value_to_store = if encrypted {
@@ -197,7 +206,7 @@ impl SettingsService {
         }
 
         // Load from database
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
         let setting = repository::get_setting(&conn, key)
             .map_err(|_| SettingsServiceError::NotFound(key.to_string()))?;
 
@@ -253,7 +262,7 @@ impl SettingsService {
             cache.insert(key, value);
         }
 
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
         repository::upsert_settings_batch(&conn, processed_settings)?;
 
         Ok(())
@@ -261,7 +270,7 @@ impl SettingsService {
 
     /// Delete a setting
     pub fn delete(&self, key: &str) -> Result<(), SettingsServiceError> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
         repository::delete_setting(&conn, key)?;
 
         // Remove from cache
@@ -276,13 +285,13 @@ impl SettingsService {
         &self,
         category: SettingCategory,
     ) -> Result<Vec<Setting>, SettingsServiceError> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
         Ok(repository::get_settings_by_category(&conn, category)?)
     }
 
     /// List all settings
     pub fn list_all(&self) -> Result<Vec<Setting>, SettingsServiceError> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
         Ok(repository::list_all_settings(&conn)?)
     }
 
@@ -336,35 +345,25 @@ impl SettingsService {
         Ok(())
     }
 
-    /// Save API key to keyring (legacy support)
+    /// Save a provider API key to the vault
     pub fn save_api_key(&self, provider: &str, key: &str) -> Result<(), SettingsServiceError> {
         validation::validate_api_key(provider, key)?;
-
-        let entry = Entry::new(SERVICE_NAME, &format!("api_key_{}", provider)).map_err(|e| {
-            SettingsServiceError::Keyring(format!("Failed to access keyring: {}", e))
-        })?;
-
-        entry
-            .set_password(key)
-            .map_err(|e| SettingsServiceError::Keyring(format!("Failed to save API key: {}", e)))?;
-
+        self.vault
+            .put(&SecretId::provider(provider), key, "settings")?;
         Ok(())
     }
 
-    /// Get API key from keyring (legacy support)
+    /// Get a provider API key from the vault
     pub fn get_api_key(&self, provider: &str) -> Result<String, SettingsServiceError> {
-        let entry = Entry::new(SERVICE_NAME, &format!("api_key_{}", provider)).map_err(|e| {
-            SettingsServiceError::Keyring(format!("Failed to access keyring: {}", e))
-        })?;
-
-        entry
-            .get_password()
-            .map_err(|e| SettingsServiceError::Keyring(format!("Failed to get API key: {}", e)))
+        self.vault
+            .get(&SecretId::provider(provider), "settings")?
+            .map(|key| key.expose_secret().to_string())
+            .ok_or_else(|| SettingsServiceError::NotFound(format!("API key for {}", provider)))
     }
 
     /// Load complete application settings
     pub fn load_app_settings(&self) -> Result<AppSettings, SettingsServiceError> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn.get()?;
         let all_settings = repository::list_all_settings(&conn)?;
 
         let mut app_settings = AppSettings::default();
@@ -409,6 +408,12 @@ impl SettingsService {
                         app_settings.security_settings = serde_json::from_value(json.clone())?;
                     }
                 }
+                "notification_preferences" => {
+                    if let Some(json) = value.as_json() {
+                        app_settings.notification_preferences =
+                            serde_json::from_value(json.clone())?;
+                    }
+                }
                 _ => {}
             }
         }
@@ -449,6 +454,12 @@ impl SettingsService {
                 SettingCategory::Security,
                 false,
             ),
+            (
+                "notification_preferences".to_string(),
+                SettingValue::Json(serde_json::to_value(&settings.notification_preferences)?),
+                SettingCategory::Ui,
+                false,
+            ),
         ];
 
         self.set_batch(batch)?;
@@ -459,10 +470,10 @@ impl SettingsService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rusqlite::Connection;
 
     fn setup_test_service() -> SettingsService {
-        let conn = Connection::open_in_memory().unwrap();
+        let pool = Pool::in_memory().unwrap();
+        let conn = pool.get().unwrap();
 
         conn.execute(
             "CREATE TABLE settings_v2 (
@@ -477,7 +488,7 @@ mod tests {
         )
         .unwrap();
 
-        SettingsService::new(Arc::new(Mutex::new(conn))).unwrap()
+        SettingsService::new(pool.clone(), Arc::new(Vault::in_memory())).unwrap()
     }
 
     #[test]
@@ -1,15 +1,17 @@
 /// Integration tests for settings storage system
 #[cfg(test)]
 mod integration_tests {
+    use crate::db::Pool;
+    use crate::security::vault::Vault;
     use crate::settings::{
         models::{AppSettings, SettingCategory, SettingValue},
         repository, SettingsService,
     };
-    use rusqlite::Connection;
     use std::sync::{Arc, Mutex};
 
-    fn setup_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
+    fn setup_test_db() -> Pool {
+        let pool = Pool::in_memory().unwrap();
+        let conn = pool.get().unwrap();
 
         conn.execute(
             "CREATE TABLE settings_v2 (
@@ -24,12 +26,12 @@ mod integration_tests {
         )
         .unwrap();
 
-        conn
+        pool
     }
 
-    fn setup_test_service_with_conn() -> (SettingsService, Arc<Mutex<Connection>>) {
-        let conn = Arc::new(Mutex::new(setup_test_db()));
-        let service = SettingsService::new(conn.clone()).unwrap();
+    fn setup_test_service_with_conn() -> (SettingsService, Pool) {
+        let conn = setup_test_db();
+        let service = SettingsService::new(conn.clone(), Arc::new(Vault::in_memory())).unwrap();
         (service, conn)
     }
 
@@ -96,7 +98,7 @@ mod integration_tests {
 
         // Verify it's actually encrypted in DB
         let raw_setting = {
-            let conn_guard = conn.lock().unwrap();
+            let conn_guard = conn.get().unwrap();
             repository::get_setting(&conn_guard, "api_key").unwrap()
         };
         assert!(raw_setting.encrypted);
@@ -246,6 +248,7 @@ mod integration_tests {
         settings.default_model = "claude-3-5-sonnet".to_string();
         settings.ui_preferences.theme = "dark".to_string();
         settings.ui_preferences.font_size = 16;
+        settings.notification_preferences.task_completion = false;
 
         // Save
         service.save_app_settings(&settings).unwrap();
@@ -257,6 +260,8 @@ mod integration_tests {
         assert_eq!(loaded.default_model, "claude-3-5-sonnet");
         assert_eq!(loaded.ui_preferences.theme, "dark");
         assert_eq!(loaded.ui_preferences.font_size, 16);
+        assert!(!loaded.notification_preferences.task_completion);
+        assert!(loaded.notification_preferences.approval_requests);
     }
 
     #[test]
@@ -14,6 +14,16 @@ use tauri::{AppHandle, Manager};
 pub enum DockPosition {
     Left,
     Right,
+    Top,
+    Bottom,
+}
+
+impl DockPosition {
+    /// Left and right docks span the work area's height and are sized by
+    /// width; top and bottom docks span its width and are sized by height.
+    pub fn is_side(&self) -> bool {
+        matches!(self, DockPosition::Left | DockPosition::Right)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +57,13 @@ pub struct PersistentWindowState {
     pub maximized: bool,
     #[serde(default)]
     pub fullscreen: bool,
+    /// Docked width (left/right) or height (top/bottom) chosen by the user
+    #[serde(default)]
+    pub dock_size: Option<f64>,
+    /// Name of the monitor to dock on; the current monitor when unset or
+    /// no longer connected
+    #[serde(default)]
+    pub dock_monitor: Option<String>,
 }
 
 impl Default for PersistentWindowState {
@@ -59,6 +76,8 @@ impl Default for PersistentWindowState {
             previous_geometry: None,
             maximized: false,
             fullscreen: false,
+            dock_size: None,
+            dock_monitor: None,
         }
     }
 }
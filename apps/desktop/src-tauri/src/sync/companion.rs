@@ -0,0 +1,661 @@
+//! Companion sync: pushes a small, encrypted view of desktop state to a
+//! user-owned channel so a mobile companion can follow along and approve
+//! pending operations remotely.
+//!
+//! The desktop and companion share a pairing key. Published blobs are sealed
+//! with AES-256-GCM under that key, and remote approval decisions must carry
+//! an HMAC token derived from the same key before the desktop will act on them.
+//! Over the realtime channel the companion sends decisions back sealed the
+//! same way, as `CompanionApprovalSubmitted` events.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::agent::approval::{ApprovalController, ApprovalRequestPayload, ApprovalResolution};
+use crate::cloud::CloudStorageManager;
+use crate::realtime::{CompanionInbound, RealtimeEvent, RealtimeServer};
+use crate::security::encryption::{decrypt_secret, encrypt_secret, EncryptedSecret};
+use crate::security::SecretManager;
+use crate::tasks::types::{TaskFilter, TaskStatus};
+use crate::tasks::TaskManager;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const PAIRING_KEY_SECRET: &str = "companion.pairing_key";
+const CHANNEL_SECRET: &str = "companion.channel";
+const DEVICE_ID_SECRET: &str = "companion.device_id";
+const BLOB_VERSION: u32 = 1;
+const MAX_NOTIFICATIONS: usize = 50;
+const MAX_TASKS: usize = 25;
+
+/// Where sealed companion state is published
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CompanionChannelConfig {
+    /// Upload the sealed blob to a file in one of the user's cloud accounts
+    CloudStorage {
+        account_id: String,
+        remote_path: String,
+    },
+    /// Push the sealed blob to the user's sessions on the realtime server
+    Realtime { user_id: String },
+}
+
+/// Pending approval as seen by the companion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionApproval {
+    pub action_id: String,
+    pub tool_name: String,
+    pub title: String,
+    pub description: String,
+    pub risk_level: String,
+}
+
+impl From<ApprovalRequestPayload> for CompanionApproval {
+    fn from(payload: ApprovalRequestPayload) -> Self {
+        Self {
+            action_id: payload.action_id,
+            tool_name: payload.tool_name,
+            title: payload.title,
+            description: payload.description,
+            risk_level: payload.risk_level,
+        }
+    }
+}
+
+/// Background task progress as seen by the companion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionTaskProgress {
+    pub task_id: String,
+    pub name: String,
+    pub status: TaskStatus,
+    pub progress: u8,
+}
+
+/// Notification mirrored to the companion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionNotification {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub created_at: i64,
+}
+
+/// Plaintext state published to the companion (before sealing)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionSnapshot {
+    pub device_id: String,
+    pub generated_at: i64,
+    pub pending_approvals: Vec<CompanionApproval>,
+    pub tasks: Vec<CompanionTaskProgress>,
+    pub notifications: Vec<CompanionNotification>,
+}
+
+/// Encrypted envelope that actually leaves the machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedCompanionBlob {
+    pub version: u32,
+    pub device_id: String,
+    pub generated_at: i64,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Approval decision sent back by the companion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionApprovalDecision {
+    pub action_id: String,
+    pub approve: bool,
+    /// Unix timestamp (seconds) after which the token is no longer accepted
+    pub expires_at: i64,
+    /// Hex HMAC-SHA256 of `action_id:decision:expires_at` under the pairing key
+    pub token: String,
+    pub reason: Option<String>,
+}
+
+/// Details handed to the user when pairing a companion device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanionPairing {
+    pub device_id: String,
+    /// Base64 pairing key; shown once so the companion can import it
+    pub pairing_key: String,
+}
+
+pub struct CompanionSync {
+    device_id: String,
+    secrets: Arc<SecretManager>,
+    cloud: Arc<CloudStorageManager>,
+    realtime: Arc<RealtimeServer>,
+    channel: RwLock<Option<CompanionChannelConfig>>,
+    notifications: Mutex<VecDeque<CompanionNotification>>,
+    last_published_digest: Mutex<Option<String>>,
+}
+
+impl CompanionSync {
+    pub fn new(
+        secrets: Arc<SecretManager>,
+        cloud: Arc<CloudStorageManager>,
+        realtime: Arc<RealtimeServer>,
+    ) -> Self {
+        // Stable identifier so the companion can tell desktops apart
+        let device_id = secrets.get_secret(DEVICE_ID_SECRET).unwrap_or_else(|_| {
+            let generated = uuid::Uuid::new_v4().to_string();
+            if let Err(e) = secrets.store_secret(DEVICE_ID_SECRET, &generated) {
+                tracing::warn!("Failed to persist companion device id: {}", e);
+            }
+            generated
+        });
+
+        let channel = secrets
+            .get_secret(CHANNEL_SECRET)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok());
+
+        Self {
+            device_id,
+            secrets,
+            cloud,
+            realtime,
+            channel: RwLock::new(channel),
+            notifications: Mutex::new(VecDeque::new()),
+            last_published_digest: Mutex::new(None),
+        }
+    }
+
+    /// Generate a fresh pairing key, replacing any previous pairing
+    pub fn pair(&self) -> Result<CompanionPairing> {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let encoded = general_purpose::STANDARD.encode(key);
+
+        self.secrets
+            .store_secret(PAIRING_KEY_SECRET, &encoded)
+            .map_err(|e| anyhow!("Failed to store pairing key: {}", e))?;
+
+        Ok(CompanionPairing {
+            device_id: self.device_id.clone(),
+            pairing_key: encoded,
+        })
+    }
+
+    /// Forget the pairing key and channel; nothing is published afterwards
+    pub async fn unpair(&self) -> Result<()> {
+        self.secrets
+            .delete_secret(PAIRING_KEY_SECRET)
+            .map_err(|e| anyhow!("Failed to remove pairing key: {}", e))?;
+        self.secrets
+            .delete_secret(CHANNEL_SECRET)
+            .map_err(|e| anyhow!("Failed to remove companion channel: {}", e))?;
+        *self.channel.write().await = None;
+        *self.last_published_digest.lock().await = None;
+        Ok(())
+    }
+
+    pub fn is_paired(&self) -> bool {
+        self.pairing_key().is_ok()
+    }
+
+    pub async fn set_channel(&self, channel: CompanionChannelConfig) -> Result<()> {
+        let raw = serde_json::to_string(&channel)?;
+        self.secrets
+            .store_secret(CHANNEL_SECRET, &raw)
+            .map_err(|e| anyhow!("Failed to store companion channel: {}", e))?;
+        *self.channel.write().await = Some(channel);
+        *self.last_published_digest.lock().await = None;
+        Ok(())
+    }
+
+    pub async fn channel(&self) -> Option<CompanionChannelConfig> {
+        self.channel.read().await.clone()
+    }
+
+    /// Queue a notification to be mirrored on the next publish
+    pub async fn record_notification(&self, title: impl Into<String>, body: impl Into<String>) {
+        let mut notifications = self.notifications.lock().await;
+        notifications.push_back(CompanionNotification {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: title.into(),
+            body: body.into(),
+            created_at: Utc::now().timestamp(),
+        });
+        while notifications.len() > MAX_NOTIFICATIONS {
+            notifications.pop_front();
+        }
+    }
+
+    /// Gather the companion-visible state from the running app
+    pub async fn collect_snapshot(&self, app: &AppHandle) -> CompanionSnapshot {
+        let pending_approvals = match app.try_state::<ApprovalController>() {
+            Some(controller) => controller
+                .pending_requests()
+                .await
+                .into_iter()
+                .map(CompanionApproval::from)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let tasks = match app.try_state::<crate::commands::TaskManagerState>() {
+            Some(state) => collect_tasks(&state.0).await,
+            None => Vec::new(),
+        };
+
+        let notifications = self.notifications.lock().await.iter().cloned().collect();
+
+        CompanionSnapshot {
+            device_id: self.device_id.clone(),
+            generated_at: Utc::now().timestamp(),
+            pending_approvals,
+            tasks,
+            notifications,
+        }
+    }
+
+    /// Seal the snapshot under the pairing key
+    pub fn seal(&self, snapshot: &CompanionSnapshot) -> Result<SealedCompanionBlob> {
+        self.seal_value(snapshot, snapshot.generated_at)
+    }
+
+    /// Open a sealed blob (used for verification and tests)
+    pub fn open(&self, blob: &SealedCompanionBlob) -> Result<CompanionSnapshot> {
+        self.open_value(blob)
+    }
+
+    /// Seal a decision the way the companion sends it back
+    pub fn seal_decision(
+        &self,
+        decision: &CompanionApprovalDecision,
+    ) -> Result<SealedCompanionBlob> {
+        self.seal_value(decision, Utc::now().timestamp())
+    }
+
+    /// Open a decision sealed for this desktop and validate its token
+    pub fn open_decision(&self, blob: &SealedCompanionBlob) -> Result<CompanionApprovalDecision> {
+        if blob.device_id != self.device_id {
+            return Err(anyhow!("Approval was sealed for another device"));
+        }
+        let decision = self.open_value(blob)?;
+        self.verify_decision(&decision)?;
+        Ok(decision)
+    }
+
+    fn seal_value(&self, value: &impl Serialize, generated_at: i64) -> Result<SealedCompanionBlob> {
+        let key = self.pairing_key()?;
+        let plaintext = serde_json::to_string(value)?;
+        let encrypted = encrypt_secret(&key, &plaintext).map_err(|e| anyhow!(e))?;
+
+        Ok(SealedCompanionBlob {
+            version: BLOB_VERSION,
+            device_id: self.device_id.clone(),
+            generated_at,
+            nonce: encrypted.nonce,
+            ciphertext: encrypted.ciphertext,
+        })
+    }
+
+    fn open_value<T: serde::de::DeserializeOwned>(&self, blob: &SealedCompanionBlob) -> Result<T> {
+        let key = self.pairing_key()?;
+        let plaintext = decrypt_secret(
+            &key,
+            &EncryptedSecret {
+                ciphertext: blob.ciphertext.clone(),
+                nonce: blob.nonce.clone(),
+            },
+        )
+        .map_err(|e| anyhow!(e))?;
+        Ok(serde_json::from_str(&plaintext)?)
+    }
+
+    /// Collect, seal and publish the current state.
+    ///
+    /// Returns `false` when nothing was published because the device is not
+    /// paired, no channel is configured, or the state has not changed.
+    pub async fn publish(&self, app: &AppHandle, force: bool) -> Result<bool> {
+        if !self.is_paired() {
+            return Ok(false);
+        }
+        let Some(channel) = self.channel().await else {
+            return Ok(false);
+        };
+
+        let snapshot = self.collect_snapshot(app).await;
+        let digest = snapshot_digest(&snapshot)?;
+        if !force && self.last_published_digest.lock().await.as_deref() == Some(digest.as_str()) {
+            return Ok(false);
+        }
+
+        let blob = self.seal(&snapshot)?;
+        match channel {
+            CompanionChannelConfig::CloudStorage {
+                account_id,
+                remote_path,
+            } => {
+                self.publish_to_cloud(&account_id, &remote_path, &blob)
+                    .await?
+            }
+            CompanionChannelConfig::Realtime { user_id } => {
+                self.realtime
+                    .broadcast_to_user(
+                        &user_id,
+                        RealtimeEvent::CompanionStateUpdated {
+                            blob: serde_json::to_value(&blob)?,
+                        },
+                    )
+                    .await
+                    .map_err(|e| anyhow!("Failed to push companion state: {}", e))?;
+            }
+        }
+
+        *self.last_published_digest.lock().await = Some(digest);
+        Ok(true)
+    }
+
+    async fn publish_to_cloud(
+        &self,
+        account_id: &str,
+        remote_path: &str,
+        blob: &SealedCompanionBlob,
+    ) -> Result<()> {
+        let local_path = std::env::temp_dir().join(format!(
+            "agiworkforce-companion-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        tokio::fs::write(&local_path, serde_json::to_vec(blob)?).await?;
+
+        let local = local_path.to_string_lossy().to_string();
+        let remote = remote_path.to_string();
+        let result = self
+            .cloud
+            .with_client(account_id, move |client| {
+                Box::pin(async move { client.upload(&local, &remote).await })
+            })
+            .await;
+
+        let _ = tokio::fs::remove_file(&local_path).await;
+        result
+            .map(|_| ())
+            .map_err(|e| anyhow!("Failed to upload companion state: {}", e))
+    }
+
+    /// Validate a remote approval token; the caller resolves the approval
+    pub fn verify_decision(&self, decision: &CompanionApprovalDecision) -> Result<()> {
+        if decision.expires_at < Utc::now().timestamp() {
+            return Err(anyhow!("Approval token expired"));
+        }
+
+        let key = self.pairing_key()?;
+        let expected =
+            hex::decode(&decision.token).map_err(|_| anyhow!("Malformed approval token"))?;
+        let mut mac = HmacSha256::new_from_slice(&key)?;
+        mac.update(decision_message(decision).as_bytes());
+        mac.verify_slice(&expected)
+            .map_err(|_| anyhow!("Invalid approval token"))
+    }
+
+    /// Compute the token a companion must attach to a decision
+    pub fn sign_decision(&self, decision: &CompanionApprovalDecision) -> Result<String> {
+        let key = self.pairing_key()?;
+        let mut mac = HmacSha256::new_from_slice(&key)?;
+        mac.update(decision_message(decision).as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// Resolve the pending approval a verified decision refers to
+    pub async fn apply_decision(
+        &self,
+        app: &AppHandle,
+        decision: &CompanionApprovalDecision,
+    ) -> Result<()> {
+        let controller = app
+            .try_state::<ApprovalController>()
+            .ok_or_else(|| anyhow!("Approvals are not available yet"))?;
+
+        // Re-authentication can only happen on this machine
+        if decision.approve
+            && controller
+                .pending_request(&decision.action_id)
+                .await
+                .is_some_and(|request| request.is_dangerous_command())
+        {
+            return Err(anyhow!(
+                "Dangerous commands can only be approved on the desktop"
+            ));
+        }
+
+        let resolution = if decision.approve {
+            ApprovalResolution::Approved { trust: false }
+        } else {
+            ApprovalResolution::Rejected {
+                reason: decision.reason.clone(),
+            }
+        };
+        controller
+            .resolve(&decision.action_id, resolution)
+            .await
+            .map_err(|e| anyhow!("Failed to resolve approval: {}", e))?;
+
+        let event = if decision.approve {
+            "approval:granted"
+        } else {
+            "approval:denied"
+        };
+        let _ = app.emit(
+            event,
+            serde_json::json!({
+                "id": decision.action_id,
+                "source": "companion",
+                "reason": decision.reason,
+            }),
+        );
+
+        // Push the updated pending list so other companion sessions drop the request
+        if let Err(e) = self.publish(app, false).await {
+            tracing::warn!("Failed to publish companion state after approval: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Apply sealed decisions arriving over the realtime channel until the app exits
+    pub fn start_inbound(self: Arc<Self>, app: AppHandle) {
+        let mut inbox = self.realtime.subscribe_companion();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let inbound = match inbox.recv().await {
+                    Ok(inbound) => inbound,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Dropped {} companion messages", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if let Err(e) = self.receive(&app, inbound).await {
+                    tracing::warn!("Rejected companion approval: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn receive(&self, app: &AppHandle, inbound: CompanionInbound) -> Result<()> {
+        // Only the session the state is published to may answer it
+        match self.channel().await {
+            Some(CompanionChannelConfig::Realtime { user_id }) if user_id == inbound.user_id => {}
+            _ => return Err(anyhow!("Approval arrived from an unexpected session")),
+        }
+        let blob: SealedCompanionBlob = serde_json::from_value(inbound.blob)?;
+        let decision = self.open_decision(&blob)?;
+        self.apply_decision(app, &decision).await
+    }
+
+    /// Periodically publish state changes until the app exits
+    pub fn start_auto_publish(self: Arc<Self>, app: AppHandle, interval: Duration) {
+        tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.publish(&app, false).await {
+                    tracing::warn!("Companion publish failed: {}", e);
+                }
+            }
+        });
+    }
+
+    fn pairing_key(&self) -> Result<Vec<u8>> {
+        let encoded = self
+            .secrets
+            .get_secret(PAIRING_KEY_SECRET)
+            .map_err(|_| anyhow!("Companion device is not paired"))?;
+        general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| anyhow!("Stored pairing key is corrupt"))
+    }
+}
+
+async fn collect_tasks(manager: &TaskManager) -> Vec<CompanionTaskProgress> {
+    let filter = TaskFilter {
+        limit: Some(MAX_TASKS),
+        ..TaskFilter::default()
+    };
+
+    match manager.list(filter).await {
+        Ok(tasks) => tasks
+            .into_iter()
+            .filter(|task| {
+                matches!(
+                    task.status,
+                    TaskStatus::Queued | TaskStatus::Running | TaskStatus::Paused
+                )
+            })
+            .map(|task| CompanionTaskProgress {
+                task_id: task.id,
+                name: task.name,
+                status: task.status,
+                progress: task.progress,
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to list tasks for companion: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn decision_message(decision: &CompanionApprovalDecision) -> String {
+    let verdict = if decision.approve {
+        "approve"
+    } else {
+        "reject"
+    };
+    format!("{}:{}:{}", decision.action_id, verdict, decision.expires_at)
+}
+
+/// Digest of the snapshot contents, ignoring the generation timestamp
+fn snapshot_digest(snapshot: &CompanionSnapshot) -> Result<String> {
+    let content = serde_json::to_vec(&(
+        &snapshot.pending_approvals,
+        &snapshot.tasks,
+        &snapshot.notifications,
+    ))?;
+    Ok(hex::encode(Sha256::digest(content)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::PresenceManager;
+    use rusqlite::Connection;
+
+    fn create_sync() -> CompanionSync {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        let secrets = Arc::new(SecretManager::new(Arc::new(std::sync::Mutex::new(conn))));
+        let pool = crate::db::Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let presence = Arc::new(PresenceManager::new(pool));
+        CompanionSync::new(
+            secrets,
+            Arc::new(CloudStorageManager::new()),
+            Arc::new(RealtimeServer::new(presence)),
+        )
+    }
+
+    #[test]
+    fn test_seal_roundtrip() {
+        let sync = create_sync();
+        sync.pair().unwrap();
+
+        let snapshot = CompanionSnapshot {
+            device_id: "device-1".to_string(),
+            generated_at: 42,
+            pending_approvals: Vec::new(),
+            tasks: Vec::new(),
+            notifications: Vec::new(),
+        };
+
+        let blob = sync.seal(&snapshot).unwrap();
+        assert!(!blob.ciphertext.contains("device-1"));
+        assert_eq!(sync.open(&blob).unwrap().generated_at, 42);
+    }
+
+    #[test]
+    fn test_decision_token_validation() {
+        let sync = create_sync();
+        sync.pair().unwrap();
+
+        let mut decision = CompanionApprovalDecision {
+            action_id: "action-1".to_string(),
+            approve: true,
+            expires_at: Utc::now().timestamp() + 300,
+            token: String::new(),
+            reason: None,
+        };
+        decision.token = sync.sign_decision(&decision).unwrap();
+        assert!(sync.verify_decision(&decision).is_ok());
+
+        // Flipping the verdict invalidates the token
+        decision.approve = false;
+        assert!(sync.verify_decision(&decision).is_err());
+    }
+
+    #[test]
+    fn test_sealed_decision_must_carry_a_valid_token() {
+        let sync = create_sync();
+        sync.pair().unwrap();
+
+        let mut decision = CompanionApprovalDecision {
+            action_id: "action-1".to_string(),
+            approve: true,
+            expires_at: Utc::now().timestamp() + 300,
+            token: String::new(),
+            reason: None,
+        };
+        decision.token = sync.sign_decision(&decision).unwrap();
+        let blob = sync.seal_decision(&decision).unwrap();
+        assert!(!blob.ciphertext.contains("action-1"));
+        assert_eq!(sync.open_decision(&blob).unwrap().action_id, "action-1");
+
+        let mut elsewhere = blob.clone();
+        elsewhere.device_id = "another-device".to_string();
+        assert!(sync.open_decision(&elsewhere).is_err());
+
+        decision.token = "00".repeat(32);
+        let forged = sync.seal_decision(&decision).unwrap();
+        assert!(sync.open_decision(&forged).is_err());
+    }
+}
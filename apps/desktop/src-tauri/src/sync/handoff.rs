@@ -0,0 +1,656 @@
+//! Session handoff: moves a live conversation from one machine to another.
+//!
+//! A handoff bundle captures a conversation, its messages and attached context
+//! items, checkpoints, screen captures and the unfinished background tasks
+//! started from it.
+//! The snapshot is hashed with SHA-256 and sealed with a passphrase-derived
+//! AES-256-GCM key. Importing verifies the digest and re-inserts everything
+//! under fresh local IDs, rewriting references between rows as it goes.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::security::{decrypt_with_password, encrypt_with_password};
+use crate::tasks::persistence::TaskPersistence;
+use crate::tasks::types::{Task, TaskFilter, TaskStatus};
+
+const BUNDLE_MAGIC: &[u8; 8] = b"AGIHOFF1";
+const BUNDLE_VERSION: u32 = 1;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffConversation {
+    pub id: i64,
+    pub title: String,
+    pub created_at: String,
+}
+
+/// A message row including the enhanced context columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffMessage {
+    pub id: i64,
+    pub role: String,
+    pub content: String,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub tokens: Option<i64>,
+    pub cost: Option<f64>,
+    pub context_items: Option<String>,
+    pub images: Option<String>,
+    pub tool_calls: Option<String>,
+    pub artifacts: Option<String>,
+    pub timeline_events: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffContextItem {
+    pub id: String,
+    pub message_id: i64,
+    pub item_type: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub path: Option<String>,
+    pub url: Option<String>,
+    pub content: Option<String>,
+    pub metadata: Option<String>,
+    pub tokens: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffCheckpoint {
+    pub id: String,
+    pub checkpoint_name: String,
+    pub description: Option<String>,
+    pub message_count: i64,
+    pub messages_snapshot: String,
+    pub context_snapshot: Option<String>,
+    pub metadata: Option<String>,
+    pub parent_checkpoint_id: Option<String>,
+    pub branch_name: Option<String>,
+    pub created_at: i64,
+}
+
+/// Capture metadata; the image files themselves stay on the source machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffCapture {
+    pub id: String,
+    pub capture_type: String,
+    pub file_path: String,
+    pub thumbnail_path: Option<String>,
+    pub ocr_text: Option<String>,
+    pub ocr_confidence: Option<f64>,
+    pub metadata: Option<String>,
+    pub created_at: i64,
+}
+
+/// Everything needed to resume a session elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub conversation: HandoffConversation,
+    pub messages: Vec<HandoffMessage>,
+    pub context_items: Vec<HandoffContextItem>,
+    pub checkpoints: Vec<HandoffCheckpoint>,
+    pub captures: Vec<HandoffCapture>,
+    pub tasks: Vec<Task>,
+}
+
+/// Plaintext bundle contents; the payload is kept as raw JSON so the digest
+/// is computed over exactly the bytes that were exported
+#[derive(Debug, Serialize, Deserialize)]
+struct HandoffEnvelope {
+    version: u32,
+    exported_at: i64,
+    payload_sha256: String,
+    payload: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffExportSummary {
+    pub conversation_id: i64,
+    pub messages: usize,
+    pub checkpoints: usize,
+    pub captures: usize,
+    pub tasks: usize,
+    pub payload_sha256: String,
+    pub bundle_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffImportSummary {
+    pub source_conversation_id: i64,
+    pub conversation_id: i64,
+    pub message_ids: HashMap<i64, i64>,
+    pub checkpoint_ids: HashMap<String, String>,
+    pub task_ids: HashMap<String, String>,
+    pub captures: usize,
+    pub exported_at: i64,
+}
+
+/// Collect a snapshot of a conversation and the unfinished background tasks
+pub fn snapshot_session(conn: &Connection, conversation_id: i64) -> Result<SessionSnapshot> {
+    let conversation = conn
+        .query_row(
+            "SELECT id, title, created_at FROM conversations WHERE id = ?1",
+            params![conversation_id],
+            |row| {
+                Ok(HandoffConversation {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            },
+        )
+        .optional()?
+        .ok_or_else(|| anyhow!("Conversation {} not found", conversation_id))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, role, content, provider, model, tokens, cost, context_items,
+                images, tool_calls, artifacts, timeline_events, created_at
+         FROM messages
+         WHERE conversation_id = ?1
+         ORDER BY created_at ASC, id ASC",
+    )?;
+    let messages = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(HandoffMessage {
+                id: row.get(0)?,
+                role: row.get(1)?,
+                content: row.get(2)?,
+                provider: row.get(3)?,
+                model: row.get(4)?,
+                tokens: row.get(5)?,
+                cost: row.get(6)?,
+                context_items: row.get(7)?,
+                images: row.get(8)?,
+                tool_calls: row.get(9)?,
+                artifacts: row.get(10)?,
+                timeline_events: row.get(11)?,
+                created_at: row.get(12)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.message_id, c.type, c.name, c.description, c.path, c.url,
+                c.content, c.metadata, c.tokens, c.created_at
+         FROM context_items c
+         JOIN messages m ON m.id = c.message_id
+         WHERE m.conversation_id = ?1",
+    )?;
+    let context_items = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(HandoffContextItem {
+                id: row.get(0)?,
+                message_id: row.get(1)?,
+                item_type: row.get(2)?,
+                name: row.get(3)?,
+                description: row.get(4)?,
+                path: row.get(5)?,
+                url: row.get(6)?,
+                content: row.get(7)?,
+                metadata: row.get(8)?,
+                tokens: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, checkpoint_name, description, message_count, messages_snapshot,
+                context_snapshot, metadata, parent_checkpoint_id, branch_name, created_at
+         FROM conversation_checkpoints
+         WHERE conversation_id = ?1
+         ORDER BY created_at ASC",
+    )?;
+    let checkpoints = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(HandoffCheckpoint {
+                id: row.get(0)?,
+                checkpoint_name: row.get(1)?,
+                description: row.get(2)?,
+                message_count: row.get(3)?,
+                messages_snapshot: row.get(4)?,
+                context_snapshot: row.get(5)?,
+                metadata: row.get(6)?,
+                parent_checkpoint_id: row.get(7)?,
+                branch_name: row.get(8)?,
+                created_at: row.get(9)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, capture_type, file_path, thumbnail_path, ocr_text, ocr_confidence,
+                metadata, created_at
+         FROM captures
+         WHERE conversation_id = ?1
+         ORDER BY created_at ASC",
+    )?;
+    let captures = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(HandoffCapture {
+                id: row.get(0)?,
+                capture_type: row.get(1)?,
+                file_path: row.get(2)?,
+                thumbnail_path: row.get(3)?,
+                ocr_text: row.get(4)?,
+                ocr_confidence: row.get(5)?,
+                metadata: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(SessionSnapshot {
+        conversation,
+        messages,
+        context_items,
+        checkpoints,
+        captures,
+        tasks: Vec::new(),
+    })
+}
+
+/// Load the conversation's queued, running and paused tasks so they can
+/// travel with the session
+pub async fn unfinished_tasks(
+    persistence: &TaskPersistence,
+    conversation_id: i64,
+) -> Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    for status in [TaskStatus::Running, TaskStatus::Paused, TaskStatus::Queued] {
+        let filter = TaskFilter {
+            status: Some(status),
+            conversation_id: Some(conversation_id),
+            ..Default::default()
+        };
+        tasks.extend(persistence.list(&filter).await?);
+    }
+    Ok(tasks)
+}
+
+/// Hash and encrypt a snapshot into a portable bundle
+pub fn seal_bundle(snapshot: &SessionSnapshot, passphrase: &str) -> Result<(Vec<u8>, String)> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        bail!(
+            "Handoff passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        );
+    }
+
+    let payload = serde_json::to_string(snapshot).context("Failed to serialize session")?;
+    let digest = hex::encode(Sha256::digest(payload.as_bytes()));
+
+    let envelope = HandoffEnvelope {
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now().timestamp(),
+        payload_sha256: digest.clone(),
+        payload,
+    };
+    let plaintext = serde_json::to_vec(&envelope).context("Failed to serialize bundle")?;
+    let sealed = encrypt_with_password(&plaintext, passphrase).map_err(|e| anyhow!(e))?;
+
+    let mut bundle = Vec::with_capacity(BUNDLE_MAGIC.len() + sealed.len());
+    bundle.extend_from_slice(BUNDLE_MAGIC);
+    bundle.extend_from_slice(&sealed);
+    Ok((bundle, digest))
+}
+
+/// Decrypt a bundle and verify its integrity digest
+pub fn open_bundle(bundle: &[u8], passphrase: &str) -> Result<(SessionSnapshot, i64)> {
+    let sealed = bundle
+        .strip_prefix(BUNDLE_MAGIC.as_slice())
+        .ok_or_else(|| anyhow!("Not a session handoff bundle"))?;
+
+    let plaintext = decrypt_with_password(sealed, passphrase).map_err(|e| anyhow!(e))?;
+    let envelope: HandoffEnvelope =
+        serde_json::from_slice(&plaintext).context("Malformed handoff bundle")?;
+
+    if envelope.version != BUNDLE_VERSION {
+        bail!(
+            "Unsupported handoff bundle version {} (expected {})",
+            envelope.version,
+            BUNDLE_VERSION
+        );
+    }
+
+    let digest = hex::encode(Sha256::digest(envelope.payload.as_bytes()));
+    if digest != envelope.payload_sha256 {
+        bail!("Handoff bundle failed integrity check");
+    }
+
+    let snapshot: SessionSnapshot =
+        serde_json::from_str(&envelope.payload).context("Malformed session snapshot")?;
+    Ok((snapshot, envelope.exported_at))
+}
+
+/// Insert a snapshot under new local IDs. Imported tasks are paused so the
+/// user can decide when to resume them on this machine.
+pub fn restore_session(
+    conn: &Connection,
+    snapshot: &SessionSnapshot,
+    exported_at: i64,
+) -> Result<HandoffImportSummary> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute(
+        "INSERT INTO conversations (title, created_at) VALUES (?1, ?2)",
+        params![
+            snapshot.conversation.title,
+            snapshot.conversation.created_at
+        ],
+    )?;
+    let conversation_id = tx.last_insert_rowid();
+
+    let mut message_ids = HashMap::new();
+    for message in &snapshot.messages {
+        tx.execute(
+            "INSERT INTO messages (
+                conversation_id, role, content, provider, model, tokens, cost,
+                context_items, images, tool_calls, artifacts, timeline_events, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                conversation_id,
+                message.role,
+                message.content,
+                message.provider,
+                message.model,
+                message.tokens,
+                message.cost,
+                message.context_items,
+                message.images,
+                message.tool_calls,
+                message.artifacts,
+                message.timeline_events,
+                message.created_at,
+            ],
+        )?;
+        message_ids.insert(message.id, tx.last_insert_rowid());
+    }
+
+    for item in &snapshot.context_items {
+        let Some(message_id) = message_ids.get(&item.message_id) else {
+            continue;
+        };
+        tx.execute(
+            "INSERT INTO context_items (
+                id, message_id, type, name, description, path, url, content,
+                metadata, tokens, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                Uuid::new_v4().to_string(),
+                message_id,
+                item.item_type,
+                item.name,
+                item.description,
+                item.path,
+                item.url,
+                item.content,
+                item.metadata,
+                item.tokens,
+                item.created_at,
+            ],
+        )?;
+    }
+
+    // Assign IDs up front so parent links can point at checkpoints in any order
+    let checkpoint_ids: HashMap<String, String> = snapshot
+        .checkpoints
+        .iter()
+        .map(|c| (c.id.clone(), Uuid::new_v4().to_string()))
+        .collect();
+
+    for checkpoint in &snapshot.checkpoints {
+        let messages_snapshot =
+            remap_messages_snapshot(&checkpoint.messages_snapshot, conversation_id, &message_ids)?;
+        let parent = checkpoint
+            .parent_checkpoint_id
+            .as_ref()
+            .and_then(|id| checkpoint_ids.get(id));
+
+        tx.execute(
+            "INSERT INTO conversation_checkpoints (
+                id, conversation_id, checkpoint_name, description,
+                message_count, messages_snapshot, context_snapshot,
+                metadata, parent_checkpoint_id, branch_name, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                checkpoint_ids[&checkpoint.id],
+                conversation_id,
+                checkpoint.checkpoint_name,
+                checkpoint.description,
+                checkpoint.message_count,
+                messages_snapshot,
+                checkpoint.context_snapshot,
+                checkpoint.metadata,
+                parent,
+                checkpoint.branch_name,
+                checkpoint.created_at,
+            ],
+        )?;
+    }
+
+    for capture in &snapshot.captures {
+        tx.execute(
+            "INSERT INTO captures (
+                id, conversation_id, capture_type, file_path, thumbnail_path,
+                ocr_text, ocr_confidence, metadata, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                Uuid::new_v4().to_string(),
+                conversation_id,
+                capture.capture_type,
+                capture.file_path,
+                capture.thumbnail_path,
+                capture.ocr_text,
+                capture.ocr_confidence,
+                capture.metadata,
+                capture.created_at,
+            ],
+        )?;
+    }
+
+    let mut task_ids = HashMap::new();
+    for task in &snapshot.tasks {
+        let new_id = Uuid::new_v4().to_string();
+        let result_json = task
+            .result
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("Failed to serialize task result")?;
+
+        tx.execute(
+            "INSERT INTO tasks (
+                id, name, description, priority, status, progress,
+                created_at, started_at, completed_at, result, payload,
+                conversation_id
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                new_id,
+                task.name,
+                task.description,
+                i32::from(task.priority),
+                TaskStatus::Paused.to_string(),
+                task.progress,
+                task.created_at.timestamp(),
+                task.started_at.map(|t| t.timestamp()),
+                task.completed_at.map(|t| t.timestamp()),
+                result_json,
+                task.payload,
+                conversation_id,
+            ],
+        )?;
+        task_ids.insert(task.id.clone(), new_id);
+    }
+
+    tx.commit()?;
+
+    Ok(HandoffImportSummary {
+        source_conversation_id: snapshot.conversation.id,
+        conversation_id,
+        message_ids,
+        checkpoint_ids,
+        task_ids,
+        captures: snapshot.captures.len(),
+        exported_at,
+    })
+}
+
+/// Checkpoint restores re-insert messages with their recorded IDs, so the
+/// snapshot must reference the imported rows rather than the source ones
+fn remap_messages_snapshot(
+    snapshot: &str,
+    conversation_id: i64,
+    message_ids: &HashMap<i64, i64>,
+) -> Result<String> {
+    let mut messages: Vec<serde_json::Value> =
+        serde_json::from_str(snapshot).context("Malformed checkpoint messages snapshot")?;
+
+    for message in messages.iter_mut() {
+        let Some(obj) = message.as_object_mut() else {
+            continue;
+        };
+        let new_id = obj
+            .get("id")
+            .and_then(|v| v.as_i64())
+            .and_then(|id| message_ids.get(&id).copied());
+        // Messages deleted before export have no local counterpart; let
+        // SQLite assign a fresh ID if the checkpoint is ever restored
+        obj.insert("id".to_string(), serde_json::json!(new_id));
+        obj.insert(
+            "conversation_id".to_string(),
+            serde_json::json!(conversation_id),
+        );
+    }
+
+    Ok(serde_json::to_string(&messages)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::types::Priority;
+
+    fn setup_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn seed_session(conn: &Connection) -> i64 {
+        conn.execute("INSERT INTO conversations (title) VALUES ('Plan')", [])
+            .unwrap();
+        let conversation_id = conn.last_insert_rowid();
+        for (role, content) in [("user", "hello"), ("assistant", "hi there")] {
+            conn.execute(
+                "INSERT INTO messages (conversation_id, role, content) VALUES (?1, ?2, ?3)",
+                params![conversation_id, role, content],
+            )
+            .unwrap();
+        }
+        let first_message: i64 = conn
+            .query_row(
+                "SELECT MIN(id) FROM messages WHERE conversation_id = ?1",
+                params![conversation_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        conn.execute(
+            "INSERT INTO conversation_checkpoints (
+                id, conversation_id, checkpoint_name, message_count, messages_snapshot, created_at
+            ) VALUES ('cp-1', ?1, 'start', 1, ?2, 0)",
+            params![
+                conversation_id,
+                format!(
+                    r#"[{{"id":{},"conversation_id":{},"role":"user","content":"hello"}}]"#,
+                    first_message, conversation_id
+                )
+            ],
+        )
+        .unwrap();
+        conversation_id
+    }
+
+    #[test]
+    fn test_handoff_roundtrip_remaps_ids() {
+        let source = setup_conn();
+        let conversation_id = seed_session(&source);
+        let snapshot = snapshot_session(&source, conversation_id).unwrap();
+
+        let (bundle, _) = seal_bundle(&snapshot, "correct horse battery").unwrap();
+        let (opened, exported_at) = open_bundle(&bundle, "correct horse battery").unwrap();
+
+        // The target already has a conversation, so IDs must not be reused
+        let target = setup_conn();
+        seed_session(&target);
+        let summary = restore_session(&target, &opened, exported_at).unwrap();
+
+        assert_ne!(summary.conversation_id, conversation_id);
+        assert_eq!(summary.message_ids.len(), 2);
+
+        let count: i64 = target
+            .query_row(
+                "SELECT COUNT(*) FROM messages WHERE conversation_id = ?1",
+                params![summary.conversation_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let new_checkpoint = &summary.checkpoint_ids["cp-1"];
+        let messages_snapshot: String = target
+            .query_row(
+                "SELECT messages_snapshot FROM conversation_checkpoints WHERE id = ?1",
+                params![new_checkpoint],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let messages: Vec<serde_json::Value> = serde_json::from_str(&messages_snapshot).unwrap();
+        let remapped_id = messages[0]["id"].as_i64().unwrap();
+        assert!(summary.message_ids.values().any(|id| *id == remapped_id));
+        assert_eq!(
+            messages[0]["conversation_id"].as_i64(),
+            Some(summary.conversation_id)
+        );
+    }
+
+    #[test]
+    fn test_tampered_bundle_rejected() {
+        let conn = setup_conn();
+        let conversation_id = seed_session(&conn);
+        let snapshot = snapshot_session(&conn, conversation_id).unwrap();
+        let (mut bundle, _) = seal_bundle(&snapshot, "correct horse battery").unwrap();
+
+        assert!(open_bundle(&bundle, "wrong passphrase").is_err());
+
+        let last = bundle.len() - 1;
+        bundle[last] ^= 0xff;
+        assert!(open_bundle(&bundle, "correct horse battery").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_only_the_conversations_tasks_are_exported() {
+        let pool = crate::db::Pool::in_memory().unwrap();
+        crate::db::migrations::run_migrations(&pool.get().unwrap()).unwrap();
+        let persistence = TaskPersistence::new(pool);
+
+        let ours = Task::new("Summarize".to_string(), None, Priority::Normal).with_conversation(7);
+        let other = Task::new("Index".to_string(), None, Priority::Normal).with_conversation(8);
+        let unlinked = Task::new("Backup".to_string(), None, Priority::Normal);
+        for task in [&ours, &other, &unlinked] {
+            persistence.save(task).await.unwrap();
+        }
+
+        let tasks = unfinished_tasks(&persistence, 7).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, ours.id);
+    }
+}
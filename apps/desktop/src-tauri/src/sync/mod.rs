@@ -1,9 +1,13 @@
 pub mod cloud;
+pub mod companion;
 pub mod conflict;
+pub mod handoff;
 pub mod manager;
 pub mod queue;
 
 pub use cloud::*;
+pub use companion::*;
 pub use conflict::*;
+pub use handoff::*;
 pub use manager::*;
 pub use queue::*;
@@ -12,11 +12,12 @@ pub mod persistence;
 pub mod queue;
 pub mod types;
 
+use crate::db::Pool;
+use crate::notifications::{Notification, NotificationCategory};
 use anyhow::Context;
 use executor::{TaskExecutor, TaskExecutorFn};
 use persistence::{TaskPersistence, TaskStats};
 use queue::TaskQueue;
-use rusqlite::Connection;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
@@ -34,11 +35,7 @@ pub struct TaskManager {
 }
 
 impl TaskManager {
-    pub fn new(
-        conn: Arc<std::sync::Mutex<Connection>>,
-        app_handle: AppHandle,
-        max_concurrent: usize,
-    ) -> Self {
+    pub fn new(conn: Pool, app_handle: AppHandle, max_concurrent: usize) -> Self {
         Self {
             queue: Arc::new(TaskQueue::new()),
             executor: Arc::new(TaskExecutor::new(max_concurrent)),
@@ -63,16 +60,21 @@ impl TaskManager {
         priority: Priority,
         payload: Option<String>,
     ) -> anyhow::Result<String> {
-        let mut task = Task::new(name.clone(), description, priority);
+        let mut task = Task::new(name, description, priority);
         if let Some(payload) = payload {
             task = task.with_payload(payload);
         }
+        self.submit_task(task).await
+    }
 
+    /// Submit a task built by the caller, e.g. one tied to a conversation
+    pub async fn submit_task(&self, task: Task) -> anyhow::Result<String> {
         let task_id = task.id.clone();
 
         // Save to database
         self.persistence
             .save(&task)
+            .await
             .context("Failed to persist task")?;
 
         // Add to tasks map
@@ -89,12 +91,17 @@ impl TaskManager {
 
         // Try to process queue
         self.process_queue().await?;
+        self.refresh_tray().await;
 
         Ok(task_id)
     }
 
     /// Process the queue and start tasks if executor has capacity
     async fn process_queue(&self) -> anyhow::Result<()> {
+        if crate::kill_switch::is_engaged() {
+            return Ok(());
+        }
+
         while self.executor.can_accept().await && !self.queue.is_empty().await {
             if let Some(mut task) = self.queue.dequeue().await {
                 let task_id = task.id.clone();
@@ -113,7 +120,7 @@ impl TaskManager {
                         let mut tasks = self.tasks.write().await;
                         tasks.insert(task_id.clone(), task.clone());
                     }
-                    self.persistence.save(&task)?;
+                    self.persistence.save(&task).await?;
                     self.emit_event("task:started", &task)?;
 
                     // Execute task
@@ -140,8 +147,9 @@ impl TaskManager {
                 let mut tasks = self.tasks.write().await;
                 tasks.insert(task_id.to_string(), task.clone());
             }
-            self.persistence.save(&task)?;
+            self.persistence.save(&task).await?;
             self.emit_event("task:cancelled", &task)?;
+            self.refresh_tray().await;
 
             return Ok(());
         }
@@ -154,8 +162,9 @@ impl TaskManager {
                 let mut tasks = self.tasks.write().await;
                 tasks.insert(task_id.to_string(), task.clone());
             }
-            self.persistence.save(&task)?;
+            self.persistence.save(&task).await?;
             self.emit_event("task:cancelled", &task)?;
+            self.refresh_tray().await;
 
             return Ok(());
         }
@@ -166,6 +175,32 @@ impl TaskManager {
         ))
     }
 
+    /// Cancel every queued, running or paused task; returns how many
+    pub async fn cancel_all(&self) -> usize {
+        let pending: Vec<String> = {
+            let tasks = self.tasks.read().await;
+            tasks
+                .values()
+                .filter(|task| {
+                    matches!(
+                        task.status,
+                        TaskStatus::Queued | TaskStatus::Running | TaskStatus::Paused
+                    )
+                })
+                .map(|task| task.id.clone())
+                .collect()
+        };
+
+        let mut cancelled = 0;
+        for task_id in pending {
+            match self.cancel(&task_id).await {
+                Ok(()) => cancelled += 1,
+                Err(e) => tracing::warn!("Failed to cancel task {}: {}", task_id, e),
+            }
+        }
+        cancelled
+    }
+
     /// Pause a running task
     pub async fn pause(&self, task_id: &str) -> anyhow::Result<()> {
         self.executor.pause(task_id).await?;
@@ -175,8 +210,9 @@ impl TaskManager {
                 let mut tasks = self.tasks.write().await;
                 tasks.insert(task_id.to_string(), task.clone());
             }
-            self.persistence.save(&task)?;
+            self.persistence.save(&task).await?;
         }
+        self.refresh_tray().await;
 
         Ok(())
     }
@@ -190,12 +226,46 @@ impl TaskManager {
                 let mut tasks = self.tasks.write().await;
                 tasks.insert(task_id.to_string(), task.clone());
             }
-            self.persistence.save(&task)?;
+            self.persistence.save(&task).await?;
         }
+        self.refresh_tray().await;
 
         Ok(())
     }
 
+    /// Pause every running task; returns how many were paused
+    pub async fn pause_all(&self) -> usize {
+        let mut paused = 0;
+        for task_id in self.task_ids_in(TaskStatus::Running).await {
+            match self.pause(&task_id).await {
+                Ok(()) => paused += 1,
+                Err(e) => tracing::warn!("Failed to pause task {}: {}", task_id, e),
+            }
+        }
+        paused
+    }
+
+    /// Resume every paused task; returns how many were resumed
+    pub async fn resume_all(&self) -> usize {
+        let mut resumed = 0;
+        for task_id in self.task_ids_in(TaskStatus::Paused).await {
+            match self.resume(&task_id).await {
+                Ok(()) => resumed += 1,
+                Err(e) => tracing::warn!("Failed to resume task {}: {}", task_id, e),
+            }
+        }
+        resumed
+    }
+
+    async fn task_ids_in(&self, status: TaskStatus) -> Vec<String> {
+        let tasks = self.tasks.read().await;
+        tasks
+            .values()
+            .filter(|task| task.status == status)
+            .map(|task| task.id.clone())
+            .collect()
+    }
+
     /// Get task status
     pub async fn get_status(&self, task_id: &str) -> anyhow::Result<Task> {
         let tasks = self.tasks.read().await;
@@ -235,26 +305,29 @@ impl TaskManager {
     }
 
     /// Get persisted task statistics
-    pub fn stats(&self) -> anyhow::Result<TaskStats> {
-        self.persistence.get_stats()
+    pub async fn stats(&self) -> anyhow::Result<TaskStats> {
+        self.persistence.get_stats().await
     }
 
     /// Poll for completed tasks and update their status
     pub async fn poll_completions(&self) -> anyhow::Result<()> {
         let completions = self.executor.poll_completions().await;
+        let finished = !completions.is_empty();
 
         for (task_id, result) in completions {
             if let Some(task) = self.tasks.write().await.get_mut(&task_id) {
                 match result {
                     Ok(output) => {
                         task.complete(TaskResult::success(output));
-                        self.persistence.save(task)?;
+                        self.persistence.save(task).await?;
                         self.emit_event("task:completed", task)?;
+                        self.notify_finished(task, "Task completed", task.name.clone());
                     }
                     Err(e) => {
                         task.fail(e.to_string());
-                        self.persistence.save(task)?;
+                        self.persistence.save(task).await?;
                         self.emit_event("task:failed", task)?;
+                        self.notify_finished(task, "Task failed", format!("{}: {}", task.name, e));
                     }
                 }
             }
@@ -262,6 +335,9 @@ impl TaskManager {
 
         // Try to process more tasks from queue
         self.process_queue().await?;
+        if finished {
+            self.refresh_tray().await;
+        }
 
         Ok(())
     }
@@ -273,7 +349,7 @@ impl TaskManager {
         for update in updates {
             if let Some(task) = self.tasks.write().await.get_mut(&update.task_id) {
                 task.update_progress(update.progress);
-                self.persistence.save(task)?;
+                self.persistence.save(task).await?;
 
                 // Emit progress event
                 self.app_handle
@@ -298,7 +374,7 @@ impl TaskManager {
             ..Default::default()
         };
 
-        let queued_tasks = self.persistence.list(&filter)?;
+        let queued_tasks = self.persistence.list(&filter).await?;
 
         for task in queued_tasks {
             let task_id = task.id.clone();
@@ -315,7 +391,7 @@ impl TaskManager {
             ..Default::default()
         };
 
-        let running_tasks = self.persistence.list(&filter)?;
+        let running_tasks = self.persistence.list(&filter).await?;
 
         for mut task in running_tasks {
             // Mark as queued again since they were interrupted
@@ -325,13 +401,26 @@ impl TaskManager {
                 let mut tasks = self.tasks.write().await;
                 tasks.insert(task_id, task.clone());
             }
-            self.persistence.save(&task)?;
+            self.persistence.save(&task).await?;
             self.queue.enqueue(task).await?;
         }
+        self.refresh_tray().await;
 
         Ok(())
     }
 
+    /// Report the number of queued and running tasks to the system tray
+    async fn refresh_tray(&self) {
+        let active = self
+            .tasks
+            .read()
+            .await
+            .values()
+            .filter(|task| matches!(task.status, TaskStatus::Queued | TaskStatus::Running))
+            .count();
+        crate::tray::update_tray(&self.app_handle, |status| status.background_tasks = active);
+    }
+
     /// Emit a task event
     fn emit_event(&self, event: &str, task: &Task) -> anyhow::Result<()> {
         self.app_handle
@@ -340,6 +429,12 @@ impl TaskManager {
         Ok(())
     }
 
+    fn notify_finished(&self, task: &Task, title: &str, body: String) {
+        let notification = Notification::new(NotificationCategory::TaskCompletion, title, body)
+            .with_target(task.id.clone());
+        crate::notifications::notify(&self.app_handle, notification);
+    }
+
     /// Shutdown the task manager
     pub async fn shutdown(&self) {
         self.executor.shutdown().await;
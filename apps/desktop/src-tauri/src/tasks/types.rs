@@ -116,6 +116,9 @@ pub struct Task {
     pub completed_at: Option<DateTime<Utc>>,
     pub result: Option<TaskResult>,
     pub payload: Option<String>, // JSON payload for task data
+    /// Conversation the task was started from, so it can travel with a handoff
+    #[serde(default)]
+    pub conversation_id: Option<i64>,
 }
 
 impl Task {
@@ -132,6 +135,7 @@ impl Task {
             completed_at: None,
             result: None,
             payload: None,
+            conversation_id: None,
         }
     }
 
@@ -140,6 +144,11 @@ impl Task {
         self
     }
 
+    pub fn with_conversation(mut self, conversation_id: i64) -> Self {
+        self.conversation_id = Some(conversation_id);
+        self
+    }
+
     pub fn start(&mut self) {
         self.status = TaskStatus::Running;
         self.started_at = Some(Utc::now());
@@ -200,6 +209,8 @@ impl Task {
 pub struct TaskFilter {
     pub status: Option<TaskStatus>,
     pub priority: Option<Priority>,
+    #[serde(default)]
+    pub conversation_id: Option<i64>,
     pub limit: Option<usize>,
 }
 
@@ -208,6 +219,7 @@ impl Default for TaskFilter {
         Self {
             status: None,
             priority: None,
+            conversation_id: None,
             limit: Some(100),
         }
     }
@@ -1,3 +1,4 @@
+use crate::realtime::PresencePolicy;
 use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
@@ -23,6 +24,9 @@ pub struct TeamSettings {
     pub require_approval_for_automations: bool,
     pub enable_activity_notifications: bool,
     pub max_members: Option<usize>,
+    /// Presence visibility for members without a rule of their own
+    #[serde(default)]
+    pub presence: PresencePolicy,
 }
 
 impl Default for TeamSettings {
@@ -33,6 +37,7 @@ impl Default for TeamSettings {
             require_approval_for_automations: true,
             enable_activity_notifications: true,
             max_members: Some(10),
+            presence: PresencePolicy::default(),
         }
     }
 }
@@ -0,0 +1,692 @@
+//! Per-workspace environment variables.
+//!
+//! Reads `.env` and `.env.local` from a workspace root and edits them in place
+//! without disturbing comments, ordering or quoting of untouched lines. Values
+//! that look like secrets are kept out of the files: they are stored through
+//! [`SecretManager`] and the file keeps a `secret://KEY` reference instead.
+//! The resolved environment is injected into terminal sessions and agent shell
+//! commands, and [`EnvMasker`] scrubs secret values from anything we log.
+
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::security::{SecretError, SecretManager};
+
+/// Env files read from a workspace root, in override order
+pub const ENV_FILES: [&str; 2] = [".env", ".env.local"];
+
+/// Value written to an env file in place of a secret held by the secret store
+pub const SECRET_REF_PREFIX: &str = "secret://";
+
+const MASK: &str = "********";
+
+/// Secret values shorter than this are not masked in free text; they would
+/// match too much unrelated output to be useful
+const MIN_MASKED_LEN: usize = 4;
+
+const SECRET_KEY_TOKENS: &[&str] = &[
+    "SECRET",
+    "SECRETS",
+    "TOKEN",
+    "TOKENS",
+    "PASSWORD",
+    "PASSWD",
+    "PASS",
+    "APIKEY",
+    "CREDENTIAL",
+    "CREDENTIALS",
+    "PRIVATE",
+    "DSN",
+];
+
+const SECRET_VALUE_PREFIXES: &[&str] = &[
+    "sk-",
+    "sk_live_",
+    "rk_live_",
+    "ghp_",
+    "gho_",
+    "ghs_",
+    "github_pat_",
+    "glpat-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "AIza",
+    "-----BEGIN",
+];
+
+static URL_WITH_PASSWORD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*://[^/\s:@]+:[^/\s@]+@").unwrap());
+
+/// Whether a variable should be treated as a secret, judged by its name and
+/// by well-known credential formats in its value
+pub fn is_secret(key: &str, value: &str) -> bool {
+    if value.starts_with(SECRET_REF_PREFIX) {
+        return true;
+    }
+
+    let upper = key.to_ascii_uppercase();
+    let tokens: Vec<&str> = upper.split('_').filter(|t| !t.is_empty()).collect();
+    // NEXT_PUBLIC_*, VITE_PUBLIC_*, STRIPE_PUBLISHABLE_KEY and friends are
+    // shipped to clients by design
+    if tokens.iter().any(|t| *t == "PUBLIC" || *t == "PUBLISHABLE") {
+        return false;
+    }
+    if tokens.iter().any(|t| SECRET_KEY_TOKENS.contains(t)) || tokens.last() == Some(&"KEY") {
+        return true;
+    }
+
+    SECRET_VALUE_PREFIXES.iter().any(|p| value.starts_with(p)) || URL_WITH_PASSWORD.is_match(value)
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct EnvEntry {
+    key: String,
+    value: String,
+    export: bool,
+    /// Original text, kept so untouched entries are written back verbatim
+    raw: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum EnvLine {
+    Entry(EnvEntry),
+    /// Blank lines, comments and anything we don't understand
+    Other(String),
+}
+
+/// A parsed `.env` file that round-trips everything it doesn't edit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvFile {
+    lines: Vec<EnvLine>,
+}
+
+impl EnvFile {
+    pub fn parse(content: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut iter = content.lines();
+
+        while let Some(line) = iter.next() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                lines.push(EnvLine::Other(line.to_string()));
+                continue;
+            }
+
+            let (export, rest) = match trimmed.strip_prefix("export ") {
+                Some(rest) => (true, rest.trim_start()),
+                None => (false, trimmed),
+            };
+            let Some((key, value)) = rest.split_once('=') else {
+                lines.push(EnvLine::Other(line.to_string()));
+                continue;
+            };
+            let key = key.trim();
+            if !is_valid_key(key) {
+                lines.push(EnvLine::Other(line.to_string()));
+                continue;
+            }
+
+            let value = value.trim_start();
+            let mut raw = line.to_string();
+            let parsed = if let Some(body) = value.strip_prefix('"') {
+                // Double-quoted values may span lines
+                let mut body = body.to_string();
+                loop {
+                    if let Some(end) = closing_quote(&body) {
+                        break Some(unescape(&body[..end]));
+                    }
+                    match iter.next() {
+                        Some(next) => {
+                            raw.push('\n');
+                            raw.push_str(next);
+                            body.push('\n');
+                            body.push_str(next);
+                        }
+                        None => break None,
+                    }
+                }
+            } else if let Some(body) = value.strip_prefix('\'') {
+                body.find('\'').map(|end| body[..end].to_string())
+            } else {
+                Some(strip_inline_comment(value).trim_end().to_string())
+            };
+
+            lines.push(match parsed {
+                Some(value) => EnvLine::Entry(EnvEntry {
+                    key: key.to_string(),
+                    value,
+                    export,
+                    raw: Some(raw),
+                }),
+                None => EnvLine::Other(raw),
+            });
+        }
+
+        Self { lines }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Self::parse(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Write through a temporary file so a crash never leaves a half-written
+    /// env file behind
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&tmp, self.render())
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            anyhow!("Failed to replace {}: {}", path.display(), e)
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            match line {
+                EnvLine::Other(raw) => out.push_str(raw),
+                EnvLine::Entry(entry) => match &entry.raw {
+                    Some(raw) => out.push_str(raw),
+                    None => {
+                        if entry.export {
+                            out.push_str("export ");
+                        }
+                        out.push_str(&entry.key);
+                        out.push('=');
+                        out.push_str(&quote(&entry.value));
+                    }
+                },
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The effective value of `key`; later duplicates win, as with dotenv
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries()
+            .filter(|e| e.key == key)
+            .last()
+            .map(|e| e.value.as_str())
+    }
+
+    /// Effective variables in first-appearance order
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = Vec::new();
+        for entry in self.entries() {
+            match vars.iter_mut().find(|(k, _)| *k == entry.key) {
+                Some((_, value)) => *value = entry.value.clone(),
+                None => vars.push((entry.key.clone(), entry.value.clone())),
+            }
+        }
+        vars
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        let existing = self.lines.iter_mut().rev().find_map(|line| match line {
+            EnvLine::Entry(entry) if entry.key == key => Some(entry),
+            _ => None,
+        });
+        match existing {
+            Some(entry) => {
+                if entry.value != value {
+                    entry.value = value.to_string();
+                    entry.raw = None;
+                }
+            }
+            None => self.lines.push(EnvLine::Entry(EnvEntry {
+                key: key.to_string(),
+                value: value.to_string(),
+                export: false,
+                raw: None,
+            })),
+        }
+    }
+
+    /// Remove every occurrence of `key`, returning whether any was present
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(line, EnvLine::Entry(entry) if entry.key == key));
+        self.lines.len() != before
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &EnvEntry> {
+        self.lines.iter().filter_map(|line| match line {
+            EnvLine::Entry(entry) => Some(entry),
+            EnvLine::Other(_) => None,
+        })
+    }
+}
+
+fn closing_quote(body: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unescape(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(c @ ('"' | '\\')) => out.push(c),
+            Some(c) => {
+                out.push('\\');
+                out.push(c);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn strip_inline_comment(value: &str) -> &str {
+    value
+        .char_indices()
+        .find(|&(i, c)| c == '#' && i > 0 && value[..i].ends_with(|p: char| p == ' ' || p == '\t'))
+        .map_or(value, |(i, _)| &value[..i])
+}
+
+fn quote(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,/:@+%".contains(c))
+    {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Replaces known secret values in free text before it is logged or emitted
+#[derive(Debug, Clone, Default)]
+pub struct EnvMasker {
+    values: Vec<String>,
+}
+
+impl EnvMasker {
+    pub fn add(&mut self, value: &str) {
+        if value.len() >= MIN_MASKED_LEN && !self.values.iter().any(|v| v == value) {
+            self.values.push(value.to_string());
+            // Longest first so a secret containing another is masked whole
+            self.values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn mask(&self, text: &str) -> String {
+        let mut masked = text.to_string();
+        for value in &self.values {
+            if masked.contains(value.as_str()) {
+                masked = masked.replace(value.as_str(), MASK);
+            }
+        }
+        masked
+    }
+}
+
+/// The environment resolved for a workspace, ready to inject into a process
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedEnv {
+    pub root: PathBuf,
+    pub vars: HashMap<String, String>,
+    pub masker: EnvMasker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvValueSource {
+    File,
+    SecretStore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvVarInfo {
+    pub key: String,
+    /// Masked for secrets unless explicitly revealed; `None` when the value
+    /// references a secret that is missing from the secret store
+    pub value: Option<String>,
+    pub secret: bool,
+    pub masked: bool,
+    pub source: EnvValueSource,
+    /// The env file the effective value comes from
+    pub file: String,
+}
+
+/// Where `workspace_env_set` writes and how the value is classified
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnvSetOptions {
+    /// Overrides automatic secret detection
+    pub secret: Option<bool>,
+    /// One of [`ENV_FILES`]; defaults to the file currently defining the key,
+    /// or `.env`
+    pub file: Option<String>,
+}
+
+/// Reads, edits and resolves workspace env files
+pub struct WorkspaceEnvManager {
+    secrets: Arc<SecretManager>,
+    write_lock: parking_lot::Mutex<()>,
+}
+
+impl WorkspaceEnvManager {
+    pub fn new(secrets: Arc<SecretManager>) -> Self {
+        Self {
+            secrets,
+            write_lock: parking_lot::Mutex::new(()),
+        }
+    }
+
+    pub fn list(&self, root: &Path) -> Result<Vec<EnvVarInfo>> {
+        let mut vars: Vec<EnvVarInfo> = Vec::new();
+        for (file, key, value) in load_all(root)? {
+            let info = self.describe(root, file, &key, &value, false);
+            match vars.iter_mut().find(|v| v.key == key) {
+                Some(existing) => *existing = info,
+                None => vars.push(info),
+            }
+        }
+        Ok(vars)
+    }
+
+    pub fn get(&self, root: &Path, key: &str, reveal: bool) -> Result<Option<EnvVarInfo>> {
+        Ok(load_all(root)?
+            .into_iter()
+            .filter(|(_, k, _)| k == key)
+            .last()
+            .map(|(file, key, value)| self.describe(root, file, &key, &value, reveal)))
+    }
+
+    /// Set `key`, or remove it from every env file and the secret store when
+    /// `value` is `None`. Secrets go to the secret store and the file keeps a
+    /// reference
+    pub fn set(
+        &self,
+        root: &Path,
+        key: &str,
+        value: Option<&str>,
+        options: EnvSetOptions,
+    ) -> Result<Option<EnvVarInfo>> {
+        if !is_valid_key(key) {
+            bail!("Invalid environment variable name: {}", key);
+        }
+        if let Some(file) = &options.file {
+            if !ENV_FILES.contains(&file.as_str()) {
+                bail!(
+                    "Unsupported env file {}; expected one of {:?}",
+                    file,
+                    ENV_FILES
+                );
+            }
+        }
+        if !root.is_dir() {
+            bail!("Workspace {} does not exist", root.display());
+        }
+
+        let _guard = self.write_lock.lock();
+        let secret_name = secret_name(root, key);
+
+        let Some(value) = value else {
+            for file in ENV_FILES {
+                let path = root.join(file);
+                let mut env = EnvFile::load(&path)?;
+                if env.remove(key) {
+                    env.save(&path)?;
+                }
+            }
+            self.delete_secret(&secret_name);
+            return Ok(None);
+        };
+
+        let file = match options.file {
+            Some(file) => ENV_FILES
+                .iter()
+                .copied()
+                .find(|f| *f == file)
+                .unwrap_or(ENV_FILES[0]),
+            None => load_all(root)?
+                .into_iter()
+                .filter(|(_, k, _)| k == key)
+                .last()
+                .map_or(ENV_FILES[0], |(file, _, _)| file),
+        };
+        let path = root.join(file);
+        let mut env = EnvFile::load(&path)?;
+
+        if options.secret.unwrap_or_else(|| is_secret(key, value)) {
+            self.secrets
+                .store_secret(&secret_name, value)
+                .map_err(|e| anyhow!("Failed to store secret for {}: {}", key, e))?;
+            env.set(key, &format!("{}{}", SECRET_REF_PREFIX, key));
+        } else {
+            env.set(key, value);
+            self.delete_secret(&secret_name);
+        }
+        env.save(&path)?;
+
+        self.get(root, key, false)
+    }
+
+    /// Resolve the workspace's environment, reading secret references from
+    /// the secret store. Unresolvable references are skipped with a warning
+    pub fn resolve(&self, root: &Path) -> Result<ResolvedEnv> {
+        let mut resolved = ResolvedEnv {
+            root: root.to_path_buf(),
+            ..Default::default()
+        };
+        for (_, key, value) in load_all(root)? {
+            match value.strip_prefix(SECRET_REF_PREFIX) {
+                Some(name) => match self.secrets.get_secret(&secret_name(root, name)) {
+                    Ok(secret) => {
+                        resolved.masker.add(&secret);
+                        resolved.vars.insert(key, secret);
+                    }
+                    Err(_) => {
+                        tracing::warn!(
+                            "Env var {} in {} references a missing secret",
+                            key,
+                            root.display()
+                        );
+                    }
+                },
+                None => {
+                    if is_secret(&key, &value) {
+                        resolved.masker.add(&value);
+                    }
+                    resolved.vars.insert(key, value);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Environment to inject for a process started in `cwd`, if `cwd` sits
+    /// inside a workspace with env files
+    pub fn resolve_for(&self, cwd: &Path) -> Option<ResolvedEnv> {
+        let root = find_env_root(cwd)?;
+        match self.resolve(&root) {
+            Ok(resolved) => Some(resolved),
+            Err(e) => {
+                tracing::warn!("Failed to load env files from {}: {}", root.display(), e);
+                None
+            }
+        }
+    }
+
+    fn describe(
+        &self,
+        root: &Path,
+        file: &str,
+        key: &str,
+        value: &str,
+        reveal: bool,
+    ) -> EnvVarInfo {
+        let secret = is_secret(key, value);
+        let (value, source) = match value.strip_prefix(SECRET_REF_PREFIX) {
+            Some(name) => {
+                let stored = match self.secrets.get_secret(&secret_name(root, name)) {
+                    Ok(secret) => Some(secret),
+                    Err(SecretError::SecretNotFound) => None,
+                    Err(e) => {
+                        tracing::warn!("Failed to read secret for env var {}: {}", key, e);
+                        None
+                    }
+                };
+                (stored, EnvValueSource::SecretStore)
+            }
+            None => (Some(value.to_string()), EnvValueSource::File),
+        };
+        let masked = secret && !reveal;
+        EnvVarInfo {
+            key: key.to_string(),
+            value: value.map(|v| if masked { MASK.to_string() } else { v }),
+            secret,
+            masked,
+            source,
+            file: file.to_string(),
+        }
+    }
+
+    fn delete_secret(&self, name: &str) {
+        if let Err(e) = self.secrets.delete_secret(name) {
+            tracing::warn!("Failed to remove stored env secret: {}", e);
+        }
+    }
+}
+
+/// Every entry across the workspace's env files, in override order
+fn load_all(root: &Path) -> Result<Vec<(&'static str, String, String)>> {
+    let mut entries = Vec::new();
+    for file in ENV_FILES {
+        for (key, value) in EnvFile::load(&root.join(file))?.vars() {
+            entries.push((file, key, value));
+        }
+    }
+    Ok(entries)
+}
+
+/// Nearest directory at or above `cwd` holding an env file, without leaving
+/// the enclosing git repository
+pub fn find_env_root(cwd: &Path) -> Option<PathBuf> {
+    for dir in cwd.ancestors() {
+        if ENV_FILES.iter().any(|f| dir.join(f).is_file()) {
+            return Some(dir.to_path_buf());
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+fn secret_name(root: &Path, key: &str) -> String {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+    format!("workspace_env.{}.{}", &hex::encode(digest)[..16], key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_untouched_lines_and_edits_in_place() {
+        let source = "# database\nexport DB_HOST=localhost # local only\nDB_PORT = 5432\n\nGREETING='hello world'\nMULTI=\"line one\nline two\"\nnot a variable\n";
+        let mut env = EnvFile::parse(source);
+        assert_eq!(env.render(), source);
+
+        assert_eq!(env.get("DB_HOST"), Some("localhost"));
+        assert_eq!(env.get("DB_PORT"), Some("5432"));
+        assert_eq!(env.get("GREETING"), Some("hello world"));
+        assert_eq!(env.get("MULTI"), Some("line one\nline two"));
+
+        env.set("DB_PORT", "6543");
+        env.set("NEW_VALUE", "has \"quotes\" and spaces");
+        assert!(env.remove("GREETING"));
+        assert_eq!(
+            env.render(),
+            "# database\nexport DB_HOST=localhost # local only\nDB_PORT=6543\n\nMULTI=\"line one\nline two\"\nnot a variable\nNEW_VALUE=\"has \\\"quotes\\\" and spaces\"\n"
+        );
+        assert_eq!(
+            EnvFile::parse(&env.render()).get("NEW_VALUE"),
+            Some("has \"quotes\" and spaces")
+        );
+    }
+
+    #[test]
+    fn classifies_secrets_by_name_and_value() {
+        assert!(is_secret("OPENAI_API_KEY", "anything"));
+        assert!(is_secret("GITHUB_TOKEN", "x"));
+        assert!(is_secret("DB_PASSWORD", "hunter2"));
+        assert!(is_secret(
+            "DATABASE_URL",
+            "postgres://app:s3cret@db:5432/app"
+        ));
+        assert!(is_secret("UPSTREAM", "sk-abc123"));
+        assert!(is_secret("ANYTHING", "secret://ANYTHING"));
+
+        assert!(!is_secret("NEXT_PUBLIC_API_KEY", "pk_123"));
+        assert!(!is_secret("KEYBOARD_LAYOUT", "us"));
+        assert!(!is_secret("DATABASE_URL", "postgres://localhost/app"));
+        assert!(!is_secret("NODE_ENV", "production"));
+    }
+
+    #[test]
+    fn masks_longest_secret_first() {
+        let mut masker = EnvMasker::default();
+        masker.add("abc");
+        masker.add("token-1");
+        masker.add("token-12345");
+        assert_eq!(
+            masker.mask("auth=token-12345 other=token-1 short=abc"),
+            "auth=******** other=******** short=abc"
+        );
+    }
+}
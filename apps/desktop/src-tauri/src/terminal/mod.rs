@@ -1,4 +1,5 @@
 pub mod ai_assistant;
+pub mod env;
 pub mod pty;
 pub mod session_manager;
 pub mod shells;
@@ -7,6 +8,7 @@ pub mod shells;
 mod tests;
 
 pub use ai_assistant::TerminalAI;
+pub use env::{EnvMasker, ResolvedEnv, WorkspaceEnvManager};
 pub use pty::{PtySession, ShellType};
 pub use session_manager::{SessionContext, SessionManager};
 pub use shells::{detect_available_shells, get_default_shell, ShellInfo};
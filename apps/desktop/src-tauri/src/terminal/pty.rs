@@ -1,4 +1,5 @@
 use crate::error::{Error, Result};
+use crate::terminal::env::{EnvMasker, ResolvedEnv};
 use portable_pty::{CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
 use std::io::{Read, Write};
 
@@ -17,10 +18,16 @@ pub struct PtySession {
     pub master: Box<dyn MasterPty + Send>,
     pub child: Box<dyn portable_pty::Child + Send + Sync>,
     pub cwd: String,
+    /// Masks secrets injected from the workspace env before input is logged
+    pub masker: EnvMasker,
 }
 
 impl PtySession {
-    pub fn new(shell_type: ShellType, cwd: Option<String>) -> Result<Self> {
+    pub fn new(
+        shell_type: ShellType,
+        cwd: Option<String>,
+        env: Option<ResolvedEnv>,
+    ) -> Result<Self> {
         let pty_system = NativePtySystem::default();
 
         // Create PTY with default size (80 cols x 24 rows)
@@ -41,6 +48,12 @@ impl PtySession {
             cmd.cwd(dir);
         }
 
+        // Inject the workspace's env file variables
+        let env = env.unwrap_or_default();
+        for (key, value) in &env.vars {
+            cmd.env(key, value);
+        }
+
         // Spawn the shell
         let child = pair
             .slave
@@ -65,6 +78,7 @@ impl PtySession {
             master,
             child,
             cwd: current_dir,
+            masker: env.masker,
         })
     }
 
@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use crate::terminal::{PtySession, ShellType};
+use crate::terminal::{PtySession, ShellType, WorkspaceEnvManager};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{Emitter, Manager};
@@ -30,7 +30,12 @@ impl SessionManager {
         shell_type: ShellType,
         cwd: Option<String>,
     ) -> Result<String> {
-        let session = PtySession::new(shell_type, cwd)?;
+        let env = cwd.as_deref().and_then(|dir| {
+            self.app_handle
+                .try_state::<WorkspaceEnvManager>()
+                .and_then(|manager| manager.resolve_for(std::path::Path::new(dir)))
+        });
+        let session = PtySession::new(shell_type, cwd, env)?;
         let session_id = session.id.clone();
 
         // Store session in Arc<Mutex> for thread-safe access
@@ -55,12 +60,13 @@ impl SessionManager {
         if let Some(session_arc) = sessions.get(session_id) {
             let mut session = session_arc.lock().await;
             session.write(data)?;
+            let logged = session.masker.mask(data);
 
-            tracing::debug!("Sent input to session {}: {:?}", session_id, data);
+            tracing::debug!("Sent input to session {}: {:?}", session_id, logged);
 
             // Log command to database if it's a complete command (ends with \r\n or \n)
-            if data.ends_with('\n') || data.ends_with("\r\n") {
-                let command = data.trim();
+            if logged.ends_with('\n') || logged.ends_with("\r\n") {
+                let command = logged.trim();
                 if !command.is_empty() {
                     // Spawn a task to log the command asynchronously
                     let session_id = session_id.to_string();
@@ -1,12 +1,184 @@
-use crate::{state::AppState, window};
+//! System tray icon and menu.
+//!
+//! The tray shows live automation state: a status line with the number of
+//! running agents and background tasks, a coloured badge on the icon (green
+//! while working, amber while paused, red once the kill switch is engaged)
+//! and quick actions. Subsystems report their counts through
+//! [`update_tray`]; the tray re-renders only when something changed.
+
+use crate::state::{AppState, DockPosition};
+use crate::window::{self, DockOptions};
 use anyhow::Result;
+use parking_lot::Mutex;
+use serde::Serialize;
 use tauri::{
+    image::Image,
     menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, AppHandle, Emitter, Manager,
+    App, AppHandle, Emitter, Manager, Wry,
 };
 
+const TRAY_ID: &str = "main";
+
+const ACTIVE_BADGE: [u8; 3] = [34, 197, 94];
+const PAUSED_BADGE: [u8; 3] = [245, 158, 11];
+const HALTED_BADGE: [u8; 3] = [239, 68, 68];
+
+/// What the tray currently shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayStatus {
+    pub running_agents: usize,
+    pub background_tasks: usize,
+    /// Agents and tasks were paused from the tray
+    pub paused: bool,
+    /// The automation kill switch is engaged
+    pub halted: bool,
+    pub unread: u32,
+}
+
+impl TrayStatus {
+    pub fn summary(&self) -> String {
+        let mut work = Vec::new();
+        if self.running_agents > 0 {
+            work.push(plural(self.running_agents, "agent"));
+        }
+        if self.background_tasks > 0 {
+            work.push(plural(self.background_tasks, "task"));
+        }
+
+        let mut summary = if self.halted {
+            "Automation stopped".to_string()
+        } else if self.paused {
+            "Paused".to_string()
+        } else if work.is_empty() {
+            "Idle".to_string()
+        } else {
+            format!("{} running", work.join(", "))
+        };
+        if self.unread > 0 {
+            summary.push_str(&format!(" \u{b7} {} unread", self.unread));
+        }
+        summary
+    }
+
+    fn badge(&self) -> Option<[u8; 3]> {
+        if self.halted {
+            Some(HALTED_BADGE)
+        } else if self.paused {
+            Some(PAUSED_BADGE)
+        } else if self.running_agents + self.background_tasks > 0 {
+            Some(ACTIVE_BADGE)
+        } else {
+            None
+        }
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Tray menu items that change with the status
+pub struct TrayState {
+    status: Mutex<TrayStatus>,
+    status_item: MenuItem<Wry>,
+    pause_item: MenuItem<Wry>,
+    icon: Option<Image<'static>>,
+}
+
+impl TrayState {
+    fn render(&self, app: &AppHandle, status: &TrayStatus) -> Result<()> {
+        let summary = status.summary();
+        self.status_item.set_text(&summary)?;
+        self.pause_item.set_text(if status.paused {
+            "Resume All Agents"
+        } else {
+            "Pause All Agents"
+        })?;
+
+        if let Some(tray) = app.tray_by_id(TRAY_ID) {
+            tray.set_tooltip(Some(format!("AGI Workforce \u{2014} {}", summary)))?;
+            if let Some(icon) = &self.icon {
+                let rgba = match status.badge() {
+                    Some(color) => badge_icon(icon.rgba(), icon.width(), icon.height(), color),
+                    None => icon.rgba().to_vec(),
+                };
+                tray.set_icon(Some(Image::new_owned(rgba, icon.width(), icon.height())))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Change the tray status and re-render it if anything changed. A no-op
+/// before the tray exists, so callers need not care about startup order.
+pub fn update_tray(app: &AppHandle, update: impl FnOnce(&mut TrayStatus)) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let status = {
+        let mut current = state.status.lock();
+        let before = *current;
+        update(&mut current);
+        if *current == before {
+            return;
+        }
+        *current
+    };
+    if let Err(err) = state.render(app, &status) {
+        tracing::warn!("[tray] failed to refresh status: {err:?}");
+    }
+}
+
+pub fn tray_status(app: &AppHandle) -> TrayStatus {
+    app.try_state::<TrayState>()
+        .map(|state| *state.status.lock())
+        .unwrap_or_default()
+}
+
+/// Draw a filled status dot with a light ring in the bottom-right corner
+fn badge_icon(rgba: &[u8], width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+    let mut out = rgba.to_vec();
+    let radius = (width.min(height) as f64 * 0.22).max(2.0);
+    let ring = radius + (radius * 0.25).max(1.0);
+    let cx = width as f64 - ring;
+    let cy = height as f64 - ring;
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f64 + 0.5 - cx;
+            let dy = y as f64 + 0.5 - cy;
+            let distance = (dx * dx + dy * dy).sqrt();
+            let pixel = match distance {
+                d if d <= radius => [color[0], color[1], color[2], 255],
+                d if d <= ring => [255, 255, 255, 255],
+                _ => continue,
+            };
+            let offset = ((y * width + x) * 4) as usize;
+            if let Some(target) = out.get_mut(offset..offset + 4) {
+                target.copy_from_slice(&pixel);
+            }
+        }
+    }
+    out
+}
+
 pub fn build_system_tray(app: &mut App) -> Result<()> {
+    let status = TrayStatus {
+        halted: crate::kill_switch::is_engaged(),
+        ..TrayStatus::default()
+    };
+    let status_item = MenuItem::with_id(app, "status", status.summary(), false, None::<&str>)?;
+    let pause_all = MenuItem::with_id(app, "pause_all", "Pause All Agents", true, None::<&str>)?;
+    let open_dashboard =
+        MenuItem::with_id(app, "open_dashboard", "Open Dashboard", true, None::<&str>)?;
+    let toggle_dock = MenuItem::with_id(app, "toggle_dock", "Toggle Docking", true, None::<&str>)?;
+    let sep0 = PredefinedMenuItem::separator(app)?;
     let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
     let hide = MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?;
     let new_conversation = MenuItem::with_id(
@@ -27,11 +199,24 @@ pub fn build_system_tray(app: &mut App) -> Result<()> {
         None::<&str>,
     )?;
     let sep2 = PredefinedMenuItem::separator(app)?;
+    let kill_switch = MenuItem::with_id(
+        app,
+        "kill_switch",
+        "Stop All Automation",
+        true,
+        None::<&str>,
+    )?;
+    let sep3 = PredefinedMenuItem::separator(app)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
     let menu = Menu::with_items(
         app,
         &[
+            &status_item,
+            &pause_all,
+            &open_dashboard,
+            &toggle_dock,
+            &sep0,
             &show,
             &hide,
             &new_conversation,
@@ -40,15 +225,34 @@ pub fn build_system_tray(app: &mut App) -> Result<()> {
             &pin,
             &always_on_top,
             &sep2,
+            &kill_switch,
+            &sep3,
             &quit,
         ],
     )?;
 
-    let _tray = TrayIconBuilder::new()
+    let icon = app
+        .default_window_icon()
+        .map(|icon| Image::new_owned(icon.rgba().to_vec(), icon.width(), icon.height()));
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
+        .tooltip(format!("AGI Workforce \u{2014} {}", status.summary()))
         .on_menu_event(handle_menu_event)
-        .on_tray_icon_event(handle_tray_icon_event)
-        .build(app)?;
+        .on_tray_icon_event(handle_tray_icon_event);
+    if let Some(icon) = &icon {
+        builder = builder.icon(icon.clone());
+    }
+    let _tray = builder.build(app)?;
+
+    let state = TrayState {
+        status: Mutex::new(TrayStatus::default()),
+        status_item,
+        pause_item: pause_all,
+        icon,
+    };
+    app.manage(state);
+    // Render the initial status, e.g. a kill switch engaged before startup
+    update_tray(app.handle(), |current| *current = status);
 
     Ok(())
 }
@@ -126,6 +330,40 @@ fn handle_menu_click(app: &AppHandle, id: &str) -> Result<()> {
                 window.emit("tray://open-settings", ())?;
             }
         }
+        "kill_switch" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::kill_switch::engage(&app, "tray", None).await;
+            });
+        }
+        "pause_all" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let paused = !tray_status(&app).paused;
+                set_automation_paused(&app, paused).await;
+            });
+        }
+        "open_dashboard" => {
+            if let Some(window) = app.get_webview_window("main") {
+                window::show_window(&window)?;
+                window.emit("tray://open-dashboard", ())?;
+            }
+        }
+        "toggle_dock" => {
+            let state = app.state::<AppState>().clone();
+            if let Some(window) = app.get_webview_window("main") {
+                if state.snapshot().dock.is_some() {
+                    window::undock(&window, &state)?;
+                } else {
+                    window::apply_dock(
+                        &window,
+                        &state,
+                        DockPosition::Right,
+                        DockOptions::default(),
+                    )?;
+                }
+            }
+        }
         "quit" => {
             app.exit(0);
         }
@@ -133,3 +371,60 @@ fn handle_menu_click(app: &AppHandle, id: &str) -> Result<()> {
     }
     Ok(())
 }
+
+/// Pause or resume every orchestrator agent and background task
+pub async fn set_automation_paused(app: &AppHandle, paused: bool) {
+    // Errors only when the orchestrator was never initialised, i.e. no agents
+    let agents = if paused {
+        crate::commands::orchestrator_pause_all().await
+    } else {
+        crate::commands::orchestrator_resume_all().await
+    }
+    .unwrap_or(0);
+
+    let tasks = match app.try_state::<crate::commands::TaskManagerState>() {
+        Some(state) if paused => state.0.pause_all().await,
+        Some(state) => state.0.resume_all().await,
+        None => 0,
+    };
+
+    tracing::info!(paused, agents, tasks, "[tray] automation pause toggled");
+    update_tray(app, |status| status.paused = paused);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_summary_and_badge() {
+        let mut status = TrayStatus::default();
+        assert_eq!(status.summary(), "Idle");
+        assert_eq!(status.badge(), None);
+
+        status.running_agents = 1;
+        status.background_tasks = 3;
+        assert_eq!(status.summary(), "1 agent, 3 tasks running");
+        assert_eq!(status.badge(), Some(ACTIVE_BADGE));
+
+        status.paused = true;
+        assert_eq!(status.badge(), Some(PAUSED_BADGE));
+        status.halted = true;
+        assert_eq!(status.summary(), "Automation stopped");
+        assert_eq!(status.badge(), Some(HALTED_BADGE));
+    }
+
+    #[test]
+    fn test_badge_drawn_in_corner() {
+        let (width, height) = (32, 32);
+        let icon = vec![10u8; (width * height * 4) as usize];
+        let badged = badge_icon(&icon, width, height, ACTIVE_BADGE);
+
+        let pixel = |x: u32, y: u32| {
+            let offset = ((y * width + x) * 4) as usize;
+            badged[offset..offset + 4].to_vec()
+        };
+        assert_eq!(pixel(0, 0), vec![10, 10, 10, 10]);
+        assert_eq!(pixel(width - 7, height - 7), vec![34, 197, 94, 255]);
+    }
+}
@@ -0,0 +1,437 @@
+//! A forgiving HTML parser that builds just enough of a tree for article
+//! extraction. Malformed markup is accepted the way browsers mostly accept
+//! it: stray closing tags are ignored and unclosed elements end with their
+//! parent.
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub enum NodeData {
+    Element {
+        tag: String,
+        attrs: Vec<(String, String)>,
+    },
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub data: NodeData,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+}
+
+/// Elements that never have children
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+/// Elements whose content is text up to the matching closing tag
+const RAW_TEXT: &[&str] = &["script", "style", "textarea", "title"];
+/// Elements closed by an opening tag of the same kind, as in `<li>a<li>b`
+const SELF_CLOSING_SIBLINGS: &[&str] = &["p", "li", "dt", "dd", "tr", "td", "th", "option"];
+/// Block elements, which end an open paragraph
+pub const BLOCK: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+pub struct Document {
+    nodes: Vec<Node>,
+}
+
+impl Document {
+    pub const ROOT: NodeId = 0;
+
+    pub fn parse(html: &str) -> Self {
+        let mut doc = Document {
+            nodes: vec![Node {
+                data: NodeData::Element {
+                    tag: "#root".to_string(),
+                    attrs: Vec::new(),
+                },
+                parent: None,
+                children: Vec::new(),
+            }],
+        };
+        let mut stack = vec![Self::ROOT];
+        let mut pos = 0;
+
+        while pos < html.len() {
+            let top = *stack.last().unwrap_or(&Self::ROOT);
+            let Some(offset) = html[pos..].find('<') else {
+                doc.push_text(top, &html[pos..]);
+                break;
+            };
+            if offset > 0 {
+                doc.push_text(top, &html[pos..pos + offset]);
+                pos += offset;
+            }
+
+            let rest = &html[pos..];
+            if rest.starts_with("<!--") {
+                pos += rest.find("-->").map(|end| end + 3).unwrap_or(rest.len());
+            } else if let Some(closing) = rest.strip_prefix("</") {
+                let end = rest.find('>').map(|end| end + 1).unwrap_or(rest.len());
+                let name = tag_name(closing);
+                if let Some(i) = stack
+                    .iter()
+                    .rposition(|&id| doc.tag(id) == Some(name.as_str()))
+                {
+                    if i > 0 {
+                        stack.truncate(i);
+                    }
+                }
+                pos += end;
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                pos += rest.find('>').map(|end| end + 1).unwrap_or(rest.len());
+            } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                let end = tag_end(rest);
+                let (name, attrs, self_closing) = parse_tag(&rest[1..end]);
+                pos += end;
+
+                let top_tag = doc.tag(top).unwrap_or_default();
+                if (SELF_CLOSING_SIBLINGS.contains(&name.as_str()) && top_tag == name)
+                    || (top_tag == "p" && BLOCK.contains(&name.as_str()))
+                {
+                    stack.pop();
+                }
+                let parent = *stack.last().unwrap_or(&Self::ROOT);
+                let id = doc.push(
+                    parent,
+                    NodeData::Element {
+                        tag: name.clone(),
+                        attrs,
+                    },
+                );
+
+                if RAW_TEXT.contains(&name.as_str()) {
+                    let len = closing_tag_start(&html[pos..], &name);
+                    let text = &html[pos..pos + len];
+                    if matches!(name.as_str(), "title" | "textarea") {
+                        doc.push_text(id, text);
+                    } else {
+                        doc.push(id, NodeData::Text(text.to_string()));
+                    }
+                    pos += len;
+                    if pos < html.len() {
+                        pos += html[pos..].find('>').map(|end| end + 1).unwrap_or(0);
+                    }
+                } else if !self_closing && !VOID.contains(&name.as_str()) {
+                    stack.push(id);
+                }
+            } else {
+                doc.push_text(top, "<");
+                pos += 1;
+            }
+        }
+        doc
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() <= 1
+    }
+
+    pub fn tag(&self, id: NodeId) -> Option<&str> {
+        match &self.nodes[id].data {
+            NodeData::Element { tag, .. } => Some(tag),
+            NodeData::Text(_) => None,
+        }
+    }
+
+    pub fn attr(&self, id: NodeId, name: &str) -> Option<&str> {
+        match &self.nodes[id].data {
+            NodeData::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str()),
+            NodeData::Text(_) => None,
+        }
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    /// `id` and everything below it, in document order
+    pub fn descendants(&self, id: NodeId) -> Vec<NodeId> {
+        let mut out = Vec::new();
+        let mut pending = vec![id];
+        while let Some(next) = pending.pop() {
+            out.push(next);
+            pending.extend(self.nodes[next].children.iter().rev());
+        }
+        out
+    }
+
+    /// First element named `tag`, in document order
+    pub fn find(&self, tag: &str) -> Option<NodeId> {
+        self.descendants(Self::ROOT)
+            .into_iter()
+            .find(|&id| self.tag(id) == Some(tag))
+    }
+
+    /// Text content of `id`, script and style contents excluded
+    pub fn text(&self, id: NodeId) -> String {
+        let mut out = String::new();
+        self.collect_text(id, &mut out);
+        out
+    }
+
+    fn collect_text(&self, id: NodeId, out: &mut String) {
+        match &self.nodes[id].data {
+            NodeData::Text(text) => out.push_str(text),
+            NodeData::Element { tag, .. } if tag == "script" || tag == "style" => {}
+            NodeData::Element { .. } => {
+                for &child in &self.nodes[id].children {
+                    self.collect_text(child, out);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, parent: NodeId, data: NodeData) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            data,
+            parent: Some(parent),
+            children: Vec::new(),
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    fn push_text(&mut self, parent: NodeId, raw: &str) {
+        let text = decode_entities(raw);
+        if let Some(&last) = self.nodes[parent].children.last() {
+            if let NodeData::Text(existing) = &mut self.nodes[last].data {
+                existing.push_str(&text);
+                return;
+            }
+        }
+        self.push(parent, NodeData::Text(text));
+    }
+}
+
+fn tag_name(s: &str) -> String {
+    s.chars()
+        .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Length of the tag at the start of `s`, through its `>`. Quoted attribute
+/// values may contain `>`.
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return i + 1,
+            None => {}
+        }
+    }
+    s.len()
+}
+
+/// Name, attributes and whether the tag closes itself, from the inside of
+/// an opening tag
+fn parse_tag(tag: &str) -> (String, Vec<(String, String)>, bool) {
+    let inner = tag.strip_suffix('>').unwrap_or(tag);
+    let self_closing = inner.trim_end().ends_with('/');
+    let name = tag_name(inner);
+    let mut attrs = Vec::new();
+
+    let mut rest = &inner[name.len().min(inner.len())..];
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        let key_len = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = rest[..key_len].to_ascii_lowercase();
+        rest = rest[key_len..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            if let Some(q) = after.chars().next().filter(|c| *c == '"' || *c == '\'') {
+                let body = &after[1..];
+                let end = body.find(q).unwrap_or(body.len());
+                value = decode_entities(&body[..end]);
+                rest = body.get(end + 1..).unwrap_or("");
+            } else {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                value = decode_entities(&after[..end]);
+                rest = &after[end..];
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        } else {
+            break;
+        }
+    }
+    (name, attrs, self_closing)
+}
+
+/// Offset of `</name` in `s`, ignoring case, or the end of `s`
+fn closing_tag_start(s: &str, name: &str) -> usize {
+    let needle = format!("</{}", name);
+    let bytes = s.as_bytes();
+    let mut from = 0;
+    while let Some(i) = s[from..].find("</") {
+        let at = from + i;
+        if bytes.len() >= at + needle.len()
+            && s[at..at + needle.len()].eq_ignore_ascii_case(&needle)
+        {
+            return at;
+        }
+        from = at + 2;
+    }
+    s.len()
+}
+
+/// Replace character references with the characters they stand for
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end > 0 && end <= 10)
+            .and_then(|end| entity(&rest[1..end + 1]).map(|c| (c, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "middot" => '·',
+        "bull" => '•',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        "euro" => '€',
+        "pound" => '£',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tolerates_broken_markup() {
+        let doc = Document::parse(
+            "<ul><li>One<li>Two &amp; <b>three</ul></span><p class=lead data-x='a>b'>Tail<p>Next",
+        );
+        let ul = doc.find("ul").unwrap();
+        assert_eq!(doc.children(ul).len(), 2);
+        assert_eq!(doc.text(ul), "OneTwo & three");
+
+        let p = doc.find("p").unwrap();
+        assert_eq!(doc.attr(p, "class"), Some("lead"));
+        assert_eq!(doc.attr(p, "data-x"), Some("a>b"));
+        // The second paragraph closes the first rather than nesting in it
+        assert_eq!(doc.text(p), "Tail");
+    }
+
+    #[test]
+    fn test_raw_text_elements() {
+        let doc = Document::parse(
+            "<title>A &amp; B</title><script>if (a < b) { x('</div>') }</SCRIPT><p>Body</p>",
+        );
+        assert_eq!(doc.text(doc.find("title").unwrap()), "A & B");
+        assert_eq!(doc.text(Document::ROOT), "A & BBody");
+        assert_eq!(decode_entities("&#8217;&#x41;&bogus;"), "’A&bogus;");
+    }
+}
@@ -0,0 +1,641 @@
+//! Readability-style article extraction: paragraphs are scored, their
+//! scores flow up to the containers holding them, and the best container
+//! (plus siblings that look like part of the same article) is rendered as
+//! Markdown. Navigation, footers, comments and ads are left out.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::dom::{Document, NodeData, NodeId, BLOCK};
+
+/// Never part of the readable content
+const NON_CONTENT: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object",
+    "embed", "form", "button", "select", "input", "textarea", "nav", "footer", "aside",
+];
+/// Elements whose text scores their ancestors
+const SCORED: &[&str] = &["p", "pre", "td", "blockquote"];
+/// Paragraphs shorter than this carry no weight
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+static UNLIKELY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)banner|breadcrumb|combx|comment|community|cookie|disqus|extra|footer|gdpr|header|legends|menu|modal|nav|newsletter|pager|pagination|popup|promo|related|remark|replies|rss|share|shoutbox|sidebar|skyscraper|social|sponsor|subscribe|tags|tool|widget|ad-break|advert",
+    )
+    .expect("valid regex")
+});
+static MAYBE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)and|article|body|column|content|main|shadow").expect("valid regex")
+});
+static POSITIVE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)article|body|content|entry|hentry|h-entry|main|page|post|text|blog|story")
+        .expect("valid regex")
+});
+static NEGATIVE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)hidden|banner|combx|comment|com-|contact|foot|footer|footnote|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|tool|widget",
+    )
+    .expect("valid regex")
+});
+static WHITESPACE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t\r\f\v\n]+").expect("valid regex"));
+static BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n{3,}").expect("valid regex"));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Article {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    /// The page's own summary, from its description metadata
+    pub excerpt: Option<String>,
+    pub site_name: Option<String>,
+    pub markdown: String,
+    pub word_count: usize,
+}
+
+/// Pull the main content out of an HTML page. Relative links and images are
+/// resolved against `base`.
+pub fn extract_article(html: &str, base: Option<&Url>) -> Article {
+    let doc = Document::parse(html);
+    let skip = skipped_nodes(&doc);
+    let content = content_nodes(&doc, &skip);
+
+    let renderer = Renderer {
+        doc: &doc,
+        skip: &skip,
+        base,
+    };
+    let blocks: Vec<String> = content
+        .into_iter()
+        .flat_map(|id| renderer.blocks_of(id, true))
+        .collect();
+    let markdown = BLANK_LINES
+        .replace_all(blocks.join("\n\n").trim(), "\n\n")
+        .into_owned();
+
+    let meta = Metadata::read(&doc);
+    Article {
+        title: meta.title,
+        byline: meta.byline,
+        excerpt: meta.excerpt,
+        site_name: meta.site_name,
+        word_count: markdown.split_whitespace().count(),
+        markdown,
+    }
+}
+
+struct Metadata {
+    title: Option<String>,
+    byline: Option<String>,
+    excerpt: Option<String>,
+    site_name: Option<String>,
+}
+
+impl Metadata {
+    fn read(doc: &Document) -> Self {
+        let mut meta: HashMap<String, String> = HashMap::new();
+        for id in doc.descendants(Document::ROOT) {
+            if doc.tag(id) != Some("meta") {
+                continue;
+            }
+            let key = doc
+                .attr(id, "property")
+                .or_else(|| doc.attr(id, "name"))
+                .map(str::to_ascii_lowercase);
+            if let (Some(key), Some(content)) = (key, doc.attr(id, "content")) {
+                let content = collapse(content);
+                if !content.is_empty() {
+                    meta.entry(key).or_insert(content);
+                }
+            }
+        }
+        let first = |keys: &[&str]| keys.iter().find_map(|key| meta.get(*key).cloned());
+
+        let title = first(&["og:title", "twitter:title"])
+            .or_else(|| {
+                doc.find("title")
+                    .map(|id| collapse(&doc.text(id)))
+                    .filter(|title| !title.is_empty())
+            })
+            .or_else(|| {
+                doc.find("h1")
+                    .map(|id| collapse(&doc.text(id)))
+                    .filter(|title| !title.is_empty())
+            });
+        let byline = first(&["author", "article:author", "byl"])
+            .filter(|author| !author.starts_with("http"));
+
+        Self {
+            title,
+            byline,
+            excerpt: first(&["og:description", "description", "twitter:description"]),
+            site_name: first(&["og:site_name"]),
+        }
+    }
+}
+
+/// Nodes left out of the content, along with everything below them
+fn skipped_nodes(doc: &Document) -> Vec<bool> {
+    let mut skip = vec![false; doc.len()];
+    for id in doc.descendants(Document::ROOT) {
+        if doc.parent(id).is_some_and(|parent| skip[parent]) {
+            skip[id] = true;
+            continue;
+        }
+        let Some(tag) = doc.tag(id) else {
+            continue;
+        };
+        let hint = class_and_id(doc, id);
+        let hidden = doc.attr(id, "hidden").is_some()
+            || doc.attr(id, "aria-hidden") == Some("true")
+            || doc.attr(id, "style").is_some_and(|style| {
+                let style = style.replace(' ', "").to_ascii_lowercase();
+                style.contains("display:none") || style.contains("visibility:hidden")
+            });
+        let unlikely = !matches!(tag, "html" | "body" | "article" | "main" | "a")
+            && UNLIKELY.is_match(&hint)
+            && !MAYBE.is_match(&hint);
+        skip[id] = NON_CONTENT.contains(&tag) || hidden || unlikely;
+    }
+    skip
+}
+
+fn class_and_id(doc: &Document, id: NodeId) -> String {
+    format!(
+        "{} {}",
+        doc.attr(id, "class").unwrap_or_default(),
+        doc.attr(id, "id").unwrap_or_default()
+    )
+}
+
+/// The container most likely to hold the article, with the siblings that
+/// belong with it; the page body when nothing scores
+fn content_nodes(doc: &Document, skip: &[bool]) -> Vec<NodeId> {
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    for id in doc.descendants(Document::ROOT) {
+        if skip[id] || !doc.tag(id).is_some_and(|tag| SCORED.contains(&tag)) {
+            continue;
+        }
+        let text = collapse(&visible_text(doc, skip, id));
+        if text.chars().count() < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let score =
+            1.0 + text.matches(',').count() as f64 + (text.chars().count() as f64 / 100.0).min(3.0);
+
+        let parent = doc.parent(id).filter(|&p| p != Document::ROOT);
+        let grandparent = parent
+            .and_then(|p| doc.parent(p))
+            .filter(|&g| g != Document::ROOT);
+        for (ancestor, share) in [(parent, 1.0), (grandparent, 0.5)] {
+            if let Some(ancestor) = ancestor {
+                *scores
+                    .entry(ancestor)
+                    .or_insert_with(|| initial_score(doc, ancestor)) += score * share;
+            }
+        }
+    }
+
+    let best = scores
+        .iter()
+        .map(|(&id, &score)| (id, score * (1.0 - link_density(doc, skip, id))))
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+    let Some((top, top_score)) = best.filter(|(_, score)| *score > 0.0) else {
+        let fallback = ["article", "main", "body"]
+            .iter()
+            .find_map(|tag| doc.find(tag))
+            .unwrap_or(Document::ROOT);
+        return vec![fallback];
+    };
+
+    let Some(parent) = doc.parent(top) else {
+        return vec![top];
+    };
+    let threshold = (top_score * 0.2).max(10.0);
+    doc.children(parent)
+        .iter()
+        .copied()
+        .filter(|&sibling| {
+            if sibling == top {
+                return true;
+            }
+            if skip[sibling] {
+                return false;
+            }
+            let bonus = if class_and_id(doc, sibling) == class_and_id(doc, top)
+                && doc.attr(top, "class").is_some()
+            {
+                top_score * 0.2
+            } else {
+                0.0
+            };
+            if scores
+                .get(&sibling)
+                .is_some_and(|score| score + bonus >= threshold)
+            {
+                return true;
+            }
+            if doc.tag(sibling) != Some("p") {
+                return false;
+            }
+            let text = collapse(&visible_text(doc, skip, sibling));
+            let density = link_density(doc, skip, sibling);
+            let len = text.chars().count();
+            (len > 80 && density < 0.25) || (len > 0 && density == 0.0 && text.contains(". "))
+        })
+        .collect()
+}
+
+fn initial_score(doc: &Document, id: NodeId) -> f64 {
+    let base = match doc.tag(id).unwrap_or_default() {
+        "div" | "article" | "main" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let hint = class_and_id(doc, id);
+    let mut weight = 0.0;
+    if POSITIVE.is_match(&hint) {
+        weight += 25.0;
+    }
+    if NEGATIVE.is_match(&hint) {
+        weight -= 25.0;
+    }
+    base + weight
+}
+
+/// Share of the text of `id` that sits inside links
+fn link_density(doc: &Document, skip: &[bool], id: NodeId) -> f64 {
+    let total = collapse(&visible_text(doc, skip, id)).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = doc
+        .descendants(id)
+        .into_iter()
+        .filter(|&node| doc.tag(node) == Some("a") && !skip[node])
+        .map(|a| collapse(&visible_text(doc, skip, a)).chars().count())
+        .sum();
+    linked as f64 / total as f64
+}
+
+fn visible_text(doc: &Document, skip: &[bool], id: NodeId) -> String {
+    let mut out = String::new();
+    for node in doc.descendants(id) {
+        if let NodeData::Text(text) = &doc.node(node).data {
+            if !skip[node] && doc.parent(node).is_none_or(|p| !is_raw(doc, p)) {
+                out.push_str(text);
+                out.push(' ');
+            }
+        }
+    }
+    out
+}
+
+fn is_raw(doc: &Document, id: NodeId) -> bool {
+    matches!(doc.tag(id), Some("script" | "style" | "title"))
+}
+
+fn collapse(text: &str) -> String {
+    WHITESPACE.replace_all(text, " ").trim().to_string()
+}
+
+struct Renderer<'a> {
+    doc: &'a Document,
+    skip: &'a [bool],
+    base: Option<&'a Url>,
+}
+
+impl Renderer<'_> {
+    /// Markdown blocks for `id`. The root of the content is rendered even
+    /// when it is an inline element.
+    fn blocks_of(&self, id: NodeId, is_root: bool) -> Vec<String> {
+        if self.skip[id] {
+            return Vec::new();
+        }
+        let doc = self.doc;
+        match doc.tag(id) {
+            None => vec![normalize_inline(&self.inline(id))]
+                .into_iter()
+                .filter(|text| !text.is_empty())
+                .collect(),
+            Some(tag) if is_root || !BLOCK.contains(&tag) => {
+                if BLOCK.contains(&tag) {
+                    self.block(id, tag)
+                } else {
+                    self.container(id)
+                }
+            }
+            Some(tag) => self.block(id, tag),
+        }
+    }
+
+    fn block(&self, id: NodeId, tag: &str) -> Vec<String> {
+        let block = match tag {
+            "p" => normalize_inline(&self.inline(id)),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let text = normalize_inline(&self.inline(id)).replace('\n', " ");
+                if text.is_empty() {
+                    return Vec::new();
+                }
+                let level = tag[1..].parse().unwrap_or(1);
+                format!("{} {}", "#".repeat(level), text)
+            }
+            "ul" | "ol" => self.list(id, tag == "ol"),
+            "pre" => {
+                let code = self.doc.text(id);
+                let code = code.trim_matches('\n');
+                if code.trim().is_empty() {
+                    return Vec::new();
+                }
+                format!("```\n{}\n```", code.trim_end())
+            }
+            "blockquote" => {
+                let inner = self.container(id).join("\n\n");
+                inner
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            "table" => return self.table(id),
+            "hr" => "---".to_string(),
+            _ => return self.container(id),
+        };
+        if block.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![block]
+        }
+    }
+
+    /// Blocks of a container's children; runs of inline content become
+    /// paragraphs
+    fn container(&self, id: NodeId) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut inline = String::new();
+        for &child in self.doc.children(id) {
+            if self.skip[child] {
+                continue;
+            }
+            match self.doc.tag(child) {
+                Some(tag) if BLOCK.contains(&tag) => {
+                    push_paragraph(&mut blocks, &mut inline);
+                    blocks.extend(self.block(child, tag));
+                }
+                _ => inline.push_str(&self.inline_node(child)),
+            }
+        }
+        push_paragraph(&mut blocks, &mut inline);
+        blocks
+    }
+
+    fn list(&self, id: NodeId, ordered: bool) -> String {
+        let mut items = Vec::new();
+        for &child in self.doc.children(id) {
+            if self.skip[child] || self.doc.tag(child) != Some("li") {
+                continue;
+            }
+            let body = self.container(child).join("\n");
+            if body.trim().is_empty() {
+                continue;
+            }
+            let marker = if ordered {
+                format!("{}. ", items.len() + 1)
+            } else {
+                "- ".to_string()
+            };
+            let indent = " ".repeat(marker.len());
+            let item = body
+                .lines()
+                .enumerate()
+                .map(|(i, line)| {
+                    if i == 0 {
+                        format!("{}{}", marker, line)
+                    } else if line.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{}{}", indent, line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            items.push(item);
+        }
+        items.join("\n")
+    }
+
+    fn table(&self, id: NodeId) -> Vec<String> {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut pending = vec![id];
+        while let Some(node) = pending.pop() {
+            for &child in self.doc.children(node).iter().rev() {
+                match self.doc.tag(child) {
+                    Some("thead" | "tbody" | "tfoot") => pending.push(child),
+                    Some("tr") => {
+                        let cells = self
+                            .doc
+                            .children(child)
+                            .iter()
+                            .filter(|&&cell| matches!(self.doc.tag(cell), Some("td" | "th")))
+                            .map(|&cell| {
+                                normalize_inline(&self.inline(cell))
+                                    .replace('\n', " ")
+                                    .replace('|', "\\|")
+                            })
+                            .collect::<Vec<_>>();
+                        rows.push(cells);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        rows.reverse();
+        rows.retain(|row| row.iter().any(|cell| !cell.is_empty()));
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns <= 1 {
+            // A layout table; its cells are just content
+            return rows
+                .into_iter()
+                .flatten()
+                .filter(|c| !c.is_empty())
+                .collect();
+        }
+        let line = |row: &[String]| {
+            let mut cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            cells.resize(columns, "");
+            format!("| {} |", cells.join(" | "))
+        };
+        let mut out = vec![line(&rows[0]), line(&vec!["---".to_string(); columns])];
+        out.extend(rows[1..].iter().map(|row| line(row)));
+        vec![out.join("\n")]
+    }
+
+    /// Inline Markdown for the children of `id`
+    fn inline(&self, id: NodeId) -> String {
+        self.doc
+            .children(id)
+            .iter()
+            .filter(|&&child| !self.skip[child])
+            .map(|&child| self.inline_node(child))
+            .collect()
+    }
+
+    fn inline_node(&self, id: NodeId) -> String {
+        let doc = self.doc;
+        let tag = match &doc.node(id).data {
+            NodeData::Text(text) => return WHITESPACE.replace_all(text, " ").into_owned(),
+            NodeData::Element { tag, .. } => tag.as_str(),
+        };
+        match tag {
+            "br" => "\n".to_string(),
+            "a" => {
+                let text = normalize_inline(&self.inline(id)).replace('\n', " ");
+                match doc.attr(id, "href").and_then(|href| self.resolve(href)) {
+                    Some(href) if !text.is_empty() => format!("[{}]({})", text, href),
+                    _ => text,
+                }
+            }
+            "strong" | "b" => wrap(&self.inline(id), "**"),
+            "em" | "i" => wrap(&self.inline(id), "*"),
+            "code" | "kbd" | "samp" => wrap(&doc.text(id), "`"),
+            "img" => {
+                let alt = collapse(doc.attr(id, "alt").unwrap_or_default());
+                match doc
+                    .attr(id, "src")
+                    .filter(|src| !src.starts_with("data:"))
+                    .and_then(|src| self.resolve(src))
+                {
+                    Some(src) => format!("![{}]({})", alt, src),
+                    None => String::new(),
+                }
+            }
+            "script" | "style" | "title" => String::new(),
+            _ if BLOCK.contains(&tag) => format!("\n{}\n", self.inline(id)),
+            _ => self.inline(id),
+        }
+    }
+
+    /// Absolute form of a link; `None` for script and same-page anchors
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+        match self.base {
+            Some(base) => base.join(href).ok().map(String::from),
+            None => Some(href.to_string()),
+        }
+    }
+}
+
+/// `text` inside a Markdown delimiter, keeping surrounding spaces outside
+fn wrap(text: &str, delimiter: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let lead = if text.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trail = if text.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", lead, delimiter, trimmed, delimiter, trail)
+}
+
+fn normalize_inline(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn push_paragraph(blocks: &mut Vec<String>, inline: &mut String) {
+    let paragraph = normalize_inline(inline);
+    if !paragraph.is_empty() {
+        blocks.push(paragraph);
+    }
+    inline.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><head>
+  <title>Fallback title</title>
+  <meta property="og:title" content="Why Rust">
+  <meta name="author" content="Ada Lovelace">
+  <meta name="description" content="A short case for Rust.">
+  <style>body { color: red }</style>
+</head><body>
+  <nav class="site-nav"><a href="/">Home</a> <a href="/blog">Blog</a></nav>
+  <div id="sidebar"><p>Subscribe to our newsletter for weekly updates and offers, it is free.</p></div>
+  <div class="post-content">
+    <h1>Why Rust</h1>
+    <p>Rust gives you <strong>memory safety</strong> without a garbage collector, which matters for systems work.</p>
+    <p>It also has a <a href="/docs/book">great book</a>, helpful compiler errors, and a friendly community.</p>
+    <ul><li>Fast</li><li>Safe<ul><li>No data races</li></ul></li></ul>
+    <pre><code>fn main() {
+    println!("hi");
+}</code></pre>
+    <table><tr><th>Year</th><th>Release</th></tr><tr><td>2015</td><td>1.0</td></tr></table>
+  </div>
+  <div class="comments"><p>First! This is a long comment that nobody needs to read, really.</p></div>
+  <footer><p>Copyright 2024, all rights reserved, and then some more words.</p></footer>
+  <script>track()</script>
+</body></html>"#;
+
+    #[test]
+    fn test_extracts_article_as_markdown() {
+        let base = Url::parse("https://example.com/posts/why-rust").unwrap();
+        let article = extract_article(PAGE, Some(&base));
+
+        assert_eq!(article.title.as_deref(), Some("Why Rust"));
+        assert_eq!(article.byline.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(article.excerpt.as_deref(), Some("A short case for Rust."));
+
+        let md = &article.markdown;
+        assert!(md.starts_with("# Why Rust\n\nRust gives you **memory safety** without"));
+        assert!(md.contains("a [great book](https://example.com/docs/book), helpful"));
+        assert!(md.contains("- Fast\n- Safe\n  - No data races"));
+        assert!(md.contains("```\nfn main() {\n    println!(\"hi\");\n}\n```"));
+        assert!(md.contains("| Year | Release |\n| --- | --- |\n| 2015 | 1.0 |"));
+        for boilerplate in ["Home", "newsletter", "First!", "Copyright", "track()"] {
+            assert!(
+                !md.contains(boilerplate),
+                "{} leaked into {}",
+                boilerplate,
+                md
+            );
+        }
+    }
+
+    #[test]
+    fn test_falls_back_to_body_without_paragraphs() {
+        let html = "<html><head><title>T</title><style>p{}</style></head>\
+                    <body><p>Hello &amp; welcome</p><script>x()</script><p>Bye</p></body></html>";
+        let article = extract_article(html, None);
+        assert_eq!(article.title.as_deref(), Some("T"));
+        assert_eq!(article.markdown, "Hello & welcome\n\nBye");
+        assert_eq!(article.word_count, 4);
+    }
+}
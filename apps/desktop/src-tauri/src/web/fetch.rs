@@ -0,0 +1,314 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use url::{Host, Url};
+
+use super::extract::extract_article;
+use super::robots::RobotsRules;
+
+/// Product token matched against robots.txt groups
+pub const USER_AGENT_TOKEN: &str = "AGIWorkforce";
+const USER_AGENT: &str = concat!("AGIWorkforce/", env!("CARGO_PKG_VERSION"), " (+web_fetch)");
+/// Largest body a caller may ask for
+pub const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
+/// How long robots.txt rules are reused for an origin
+const ROBOTS_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+static ROBOTS_CACHE: Lazy<Mutex<HashMap<String, (Instant, RobotsRules)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchOptions {
+    /// Pages with a larger body are refused; capped at [`MAX_BODY_BYTES`]
+    pub max_bytes: usize,
+    pub timeout_secs: u64,
+    pub respect_robots: bool,
+    /// Allow localhost and private network addresses
+    pub allow_private_network: bool,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 2 * 1024 * 1024,
+            timeout_secs: 30,
+            respect_robots: true,
+            allow_private_network: false,
+        }
+    }
+}
+
+/// A fetched page reduced to its main content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebPage {
+    /// The URL that was asked for
+    pub url: String,
+    /// Where redirects ended up; cite this one
+    pub final_url: String,
+    pub title: String,
+    pub byline: Option<String>,
+    pub excerpt: Option<String>,
+    pub site_name: Option<String>,
+    pub markdown: String,
+    pub word_count: usize,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Fetch `url` and extract its article as Markdown, honouring robots.txt
+/// and the size limit in `options`
+pub async fn fetch_page(url: &str, options: &FetchOptions) -> Result<WebPage> {
+    let parsed = Url::parse(url.trim()).with_context(|| format!("Invalid URL: {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("Only http and https URLs can be fetched");
+    }
+    if !options.allow_private_network {
+        check_public(&parsed).await?;
+    }
+
+    let allow_private = options.allow_private_network;
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(options.timeout_secs.max(1)))
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if !allow_private && is_private_host(attempt.url()) {
+                attempt.error("Redirect to a private address was blocked")
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()?;
+
+    if options.respect_robots && !robots_allow(&client, &parsed).await {
+        bail!(
+            "{} disallows fetching {}",
+            robots_origin(&parsed),
+            parsed.path()
+        );
+    }
+
+    let max_bytes = options.max_bytes.clamp(1, MAX_BODY_BYTES);
+    let mut response = client
+        .get(parsed.clone())
+        .header(
+            reqwest::header::ACCEPT,
+            "text/html, text/plain;q=0.9, */*;q=0.1",
+        )
+        .send()
+        .await?
+        .error_for_status()?;
+    let final_url = response.url().clone();
+    if options.respect_robots
+        && final_url.origin() != parsed.origin()
+        && !robots_allow(&client, &final_url).await
+    {
+        bail!(
+            "{} disallows fetching {}",
+            robots_origin(&final_url),
+            final_url.path()
+        );
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+    let is_html = content_type.contains("html") || content_type.contains("xml");
+    if !is_html && !content_type.starts_with("text/") {
+        bail!("Cannot extract text from {} content", content_type);
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > max_bytes)
+    {
+        bail!("Page is larger than {} bytes", max_bytes);
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            bail!("Page is larger than {} bytes", max_bytes);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8_lossy(&body);
+
+    let page = if is_html {
+        let article = extract_article(&body, Some(&final_url));
+        WebPage {
+            url: url.to_string(),
+            final_url: final_url.to_string(),
+            title: article.title.unwrap_or_else(|| final_url.to_string()),
+            byline: article.byline,
+            excerpt: article.excerpt,
+            site_name: article.site_name,
+            word_count: article.word_count,
+            markdown: article.markdown,
+            fetched_at: Utc::now(),
+        }
+    } else {
+        let markdown = body.trim().to_string();
+        WebPage {
+            url: url.to_string(),
+            final_url: final_url.to_string(),
+            title: final_url.to_string(),
+            byline: None,
+            excerpt: None,
+            site_name: None,
+            word_count: markdown.split_whitespace().count(),
+            markdown,
+            fetched_at: Utc::now(),
+        }
+    };
+    if page.markdown.is_empty() {
+        bail!("No readable content found at {}", page.final_url);
+    }
+    Ok(page)
+}
+
+/// Whether robots.txt lets us fetch `url`. Missing or unreadable rules allow
+/// everything; rules are cached per origin for an hour.
+async fn robots_allow(client: &reqwest::Client, url: &Url) -> bool {
+    let origin = robots_origin(url);
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let cached = ROBOTS_CACHE
+        .lock()
+        .get(&origin)
+        .filter(|(at, _)| at.elapsed() < ROBOTS_TTL)
+        .map(|(_, rules)| rules.clone());
+    let rules = match cached {
+        Some(rules) => rules,
+        None => {
+            let rules = fetch_robots(client, &origin).await;
+            ROBOTS_CACHE
+                .lock()
+                .insert(origin, (Instant::now(), rules.clone()));
+            rules
+        }
+    };
+    rules.is_allowed(&path)
+}
+
+async fn fetch_robots(client: &reqwest::Client, origin: &str) -> RobotsRules {
+    let response = match client.get(format!("{}/robots.txt", origin)).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::debug!("No robots.txt for {}: {}", origin, e);
+            return RobotsRules::allow_all();
+        }
+    };
+    if !response.status().is_success() {
+        return RobotsRules::allow_all();
+    }
+    match response.bytes().await {
+        Ok(body) => {
+            let body = &body[..body.len().min(MAX_ROBOTS_BYTES)];
+            RobotsRules::parse(&String::from_utf8_lossy(body), USER_AGENT_TOKEN)
+        }
+        Err(_) => RobotsRules::allow_all(),
+    }
+}
+
+fn robots_origin(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+/// Refuse hosts that are, or resolve to, loopback or private addresses
+async fn check_public(url: &Url) -> Result<()> {
+    if is_private_host(url) {
+        bail!("Fetching local or private network addresses is not allowed");
+    }
+    if let Some(Host::Domain(domain)) = url.host() {
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addrs = tokio::net::lookup_host((domain, port))
+            .await
+            .with_context(|| format!("Could not resolve {}", domain))?;
+        for addr in addrs {
+            if is_private_ip(addr.ip()) {
+                bail!("{} resolves to a private network address", domain);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_private_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost") || domain.ends_with(".local")
+        }
+        Some(Host::Ipv4(ip)) => is_private_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_private_ip(IpAddr::V6(ip)),
+        None => true,
+    }
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_hosts_are_refused() {
+        for url in [
+            "http://localhost:8080/",
+            "http://127.0.0.1/",
+            "http://10.1.2.3/",
+            "http://192.168.0.1/admin",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://printer.local/",
+        ] {
+            assert!(is_private_host(&Url::parse(url).unwrap()), "{}", url);
+        }
+        for url in [
+            "https://example.com/",
+            "http://93.184.216.34/",
+            "http://[2606:4700::1]/",
+        ] {
+            assert!(!is_private_host(&Url::parse(url).unwrap()), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Fetching web pages for agents and knowledge collections: the page is
+//! downloaded within robots.txt rules and a size limit, its boilerplate is
+//! stripped and the remaining article is converted to Markdown.
+
+pub mod dom;
+pub mod extract;
+pub mod fetch;
+pub mod robots;
+
+pub use extract::{extract_article, Article};
+pub use fetch::{fetch_page, FetchOptions, WebPage};
+pub use robots::RobotsRules;
@@ -0,0 +1,146 @@
+//! robots.txt rules for a single user agent, per RFC 9309: the group naming
+//! the agent applies, otherwise the `*` group; the longest matching rule
+//! wins, and `Allow` wins a tie.
+
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    /// `(allow, pattern)` pairs
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Rules that allow everything, for sites without a robots.txt
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// The rules in `body` that apply to `agent`, a product token such as
+    /// `AGIWorkforce`
+    pub fn parse(body: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut found_specific = false;
+
+        // User agents of the group being read, and whether its rules have
+        // started (a user-agent line after a rule starts a new group)
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        group_agents.clear();
+                        in_rules = false;
+                    }
+                    group_agents.push(value.to_ascii_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // An empty Disallow allows everything
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (key == "allow", value.to_string());
+                    if group_agents
+                        .iter()
+                        .any(|ua| ua != "*" && agent.starts_with(ua.as_str()))
+                    {
+                        found_specific = true;
+                        specific.push(rule);
+                    } else if group_agents.iter().any(|ua| ua == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if found_specific { specific } else { wildcard },
+        }
+    }
+
+    /// Whether `path` (with its query string) may be fetched
+    pub fn is_allowed(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// Whether a robots.txt `pattern` matches the start of `path`. `*` matches
+/// any run of characters and a trailing `$` anchors the end.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+# Example
+User-agent: *
+Disallow: /private/
+Disallow: /*.pdf$
+Allow: /private/press/
+
+User-agent: BadBot
+User-agent: AGIWorkforce
+Disallow: /drafts
+Disallow:
+";
+
+    #[test]
+    fn test_specific_group_replaces_wildcard() {
+        let rules = RobotsRules::parse(ROBOTS, "AGIWorkforce");
+        assert!(!rules.is_allowed("/drafts/one"));
+        assert!(rules.is_allowed("/private/page"));
+        assert!(rules.is_allowed("/robots.txt"));
+    }
+
+    #[test]
+    fn test_longest_match_and_wildcards() {
+        let rules = RobotsRules::parse(ROBOTS, "SomeOtherBot");
+        assert!(!rules.is_allowed("/private/page"));
+        assert!(rules.is_allowed("/private/press/release"));
+        assert!(!rules.is_allowed("/files/report.pdf"));
+        assert!(rules.is_allowed("/files/report.pdf?download=1"));
+        assert!(rules.is_allowed("/drafts/one"));
+        assert!(RobotsRules::allow_all().is_allowed("/anything"));
+    }
+}
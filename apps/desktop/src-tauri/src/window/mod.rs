@@ -13,6 +13,9 @@ const WINDOW_DEFAULT_HEIGHT: f64 = 850.0; // Match tauri.conf.json height
 const WINDOW_MIN_HEIGHT: f64 = 700.0; // Match tauri.conf.json minHeight
 const WINDOW_DOCK_MIN_WIDTH: f64 = 360.0; // Minimum width when docked
 const WINDOW_DEFAULT_MAX_WIDTH: f64 = 480.0; // Used for docking only
+const WINDOW_DOCK_MIN_HEIGHT: f64 = 240.0; // Minimum height when docked top/bottom
+const WINDOW_DOCK_DEFAULT_HEIGHT: f64 = 320.0; // Default height when docked top/bottom
+const WINDOW_DOCK_MAX_FRACTION: f64 = 0.5; // Docks never cover more than half the work area
 const DOCK_THRESHOLD: f64 = 32.0;
 const DOCKING_ENABLED: bool = false;
 
@@ -32,6 +35,64 @@ pub struct DockPreviewEvent {
     pub preview: Option<DockPosition>,
 }
 
+/// Per-call overrides for [`apply_dock`]. Unset fields fall back to the
+/// persisted dock size and monitor.
+#[derive(Debug, Clone, Default)]
+pub struct DockOptions {
+    /// Width for left/right docks, height for top/bottom docks
+    pub size: Option<f64>,
+    /// Monitor name as reported by [`Monitor::name`]
+    pub monitor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub id: Option<String>,
+    pub primary: bool,
+    pub scale_factor: f64,
+    pub work_area: WorkArea,
+}
+
+/// Usable part of a monitor in logical pixels: the monitor bounds minus
+/// taskbars and other app bars, wherever they sit and whether or not they
+/// auto-hide.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WorkArea {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WorkArea {
+    fn from_physical(x: i32, y: i32, width: u32, height: u32, scale_factor: f64) -> Self {
+        let position: LogicalPosition<f64> = PhysicalPosition::new(x, y).to_logical(scale_factor);
+        let size: LogicalSize<f64> = PhysicalSize::new(width, height).to_logical(scale_factor);
+        Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    fn contains(&self, geometry: &WindowGeometry) -> bool {
+        geometry.x >= self.x
+            && geometry.y >= self.y
+            && geometry.x + geometry.width <= self.right()
+            && geometry.y + geometry.height <= self.bottom()
+    }
+}
+
 pub fn set_pinned(window: &WebviewWindow, app_state: &AppState, pinned: bool) -> Result<()> {
     app_state.update(|state| {
         if state.pinned != pinned {
@@ -93,9 +154,7 @@ pub fn initialize_window(window: &WebviewWindow) -> Result<()> {
     // ALWAYS start in normal windowed mode (not docked) to prevent taskbar overlap
     // Users can manually dock the window after startup if desired
     let monitor = resolve_monitor(window)?;
-    let scale_factor = monitor.scale_factor();
-    let monitor_size: LogicalSize<f64> = monitor.size().to_logical(scale_factor);
-    let monitor_position: LogicalPosition<f64> = monitor.position().to_logical(scale_factor);
+    let area = work_area(&monitor);
 
     // Clear any saved docking state on startup
     app_state.update(|state| {
@@ -112,19 +171,16 @@ pub fn initialize_window(window: &WebviewWindow) -> Result<()> {
         // Validate saved geometry
         if saved_geometry.width >= WINDOW_MIN_WIDTH
             && saved_geometry.height >= WINDOW_MIN_HEIGHT
-            && saved_geometry.x >= monitor_position.x
-            && saved_geometry.y >= monitor_position.y
-            && saved_geometry.x + saved_geometry.width <= monitor_position.x + monitor_size.width
-            && saved_geometry.y + saved_geometry.height <= monitor_position.y + monitor_size.height
+            && area.contains(&saved_geometry)
         {
             saved_geometry
         } else {
             // Invalid geometry - calculate default centered position
-            calculate_default_geometry(&monitor)?
+            calculate_default_geometry(&area)
         }
     } else {
         // No saved geometry - calculate default centered position
-        calculate_default_geometry(&monitor)?
+        calculate_default_geometry(&area)
     };
 
     apply_geometry(window, &app_state, &geometry)?;
@@ -140,24 +196,25 @@ pub fn apply_dock(
     window: &WebviewWindow,
     app_state: &AppState,
     position: DockPosition,
+    options: DockOptions,
 ) -> Result<()> {
     if !DOCKING_ENABLED {
         emit_preview(window, None)?;
         return Ok(());
     }
 
-    let monitor = resolve_monitor(window)?;
-    let scale_factor = monitor.scale_factor();
-    let monitor_size = monitor.size().to_logical(scale_factor);
-    let monitor_position = monitor.position().to_logical(scale_factor);
-
-    let dock_width = WINDOW_DEFAULT_MAX_WIDTH;
-    let height = monitor_size.height;
-    let x = match position {
-        DockPosition::Left => monitor_position.x,
-        DockPosition::Right => monitor_position.x + monitor_size.width - dock_width,
+    let snapshot = app_state.snapshot();
+    let size = options.size.or(snapshot.dock_size);
+    let requested_monitor = options.monitor.or(snapshot.dock_monitor);
+
+    let monitor = resolve_dock_monitor(window, requested_monitor.as_deref())?;
+    let area = work_area(&monitor);
+    let geometry = dock_geometry(&area, &position, size);
+    let dock_size = if position.is_side() {
+        geometry.width
+    } else {
+        geometry.height
     };
-    let y = monitor_position.y;
 
     app_state.update(|state| {
         if state.dock.is_none() {
@@ -165,21 +222,27 @@ pub fn apply_dock(
         }
 
         state.dock = Some(position.clone());
-        state.geometry = Some(WindowGeometry {
-            x,
-            y,
-            width: dock_width,
-            height,
-        });
+        state.geometry = Some(geometry.clone());
+        // Only remember an explicit size so the default keeps tracking the screen
+        if options.size.is_some() {
+            state.dock_size = Some(dock_size);
+        }
+        // Keep a requested monitor that is only temporarily disconnected
+        if requested_monitor.is_none() || monitor.name() == requested_monitor.as_ref() {
+            state.dock_monitor = monitor.name().cloned();
+        }
         true
     })?;
 
     app_state.suppress_events(|| {
         window.set_size(LogicalSize::<f64> {
-            width: dock_width,
-            height,
+            width: geometry.width,
+            height: geometry.height,
         })?;
-        window.set_position(LogicalPosition::<f64> { x, y })
+        window.set_position(LogicalPosition::<f64> {
+            x: geometry.x,
+            y: geometry.y,
+        })
     })?;
 
     emit_state(window, app_state)?;
@@ -233,12 +296,18 @@ fn handle_move_event(
 
     // Skip dock detection if disabled or the window is maximized
     if DOCKING_ENABLED && !is_maximized {
-        let dock_candidate = detect_dock_candidate(&monitor, &logical_position, outer_size.width);
+        let area = work_area(&monitor);
+        let dock_candidate = detect_dock_candidate(&area, &logical_position, &outer_size);
         match dock_candidate {
             Some(position) => {
                 emit_preview(window, Some(position.clone()))?;
                 if app_state.with_state(|state| state.dock.clone()) != Some(position.clone()) {
-                    apply_dock(window, app_state, position)?;
+                    // Dragging docks to the monitor the window was dropped on
+                    let options = DockOptions {
+                        size: None,
+                        monitor: monitor.name().cloned(),
+                    };
+                    apply_dock(window, app_state, position, options)?;
                 }
             }
             None => {
@@ -271,7 +340,8 @@ fn handle_resize_event(
 
     // Check if window is maximized or docked
     let is_maximized = window.is_maximized()?;
-    let is_docked = app_state.with_state(|state| state.dock.is_some());
+    let dock = app_state.with_state(|state| state.dock.clone());
+    let is_docked = dock.is_some();
 
     if !is_maximized && !is_docked {
         // Only enforce minimum width and height when not maximized and not docked
@@ -290,13 +360,18 @@ fn handle_resize_event(
         if needs_resize {
             app_state.suppress_events(|| window.set_size(tauri::Size::Logical(logical)))?;
         }
-    } else if is_docked && !is_maximized {
-        // When docked, clamp width to dock width (use dock-specific minimum)
-        let clamped_width = logical
-            .width
-            .clamp(WINDOW_DOCK_MIN_WIDTH, WINDOW_DEFAULT_MAX_WIDTH);
-        if (clamped_width - logical.width).abs() > f64::EPSILON {
-            logical.width = clamped_width;
+    } else if let Some(position) = dock.as_ref().filter(|_| !is_maximized) {
+        // When docked, only the docked dimension is resizable, within dock limits
+        let area = work_area(&resolve_monitor(window)?);
+        let (min, max) = dock_size_limits(&area, position);
+        let extent = if position.is_side() {
+            &mut logical.width
+        } else {
+            &mut logical.height
+        };
+        let clamped = extent.clamp(min, max);
+        if (clamped - *extent).abs() > f64::EPSILON {
+            *extent = clamped;
             app_state.suppress_events(|| window.set_size(tauri::Size::Logical(logical)))?;
         }
     }
@@ -306,6 +381,14 @@ fn handle_resize_event(
         if state.maximized != is_maximized {
             state.maximized = is_maximized;
         }
+        // Resizing a docked window changes the remembered dock size
+        match &state.dock {
+            Some(position) if !is_maximized && position.is_side() => {
+                state.dock_size = Some(logical.width)
+            }
+            Some(_) if !is_maximized => state.dock_size = Some(logical.height),
+            _ => {}
+        }
         let geometry = state.geometry.get_or_insert_with(WindowGeometry::default);
         geometry.width = logical.width;
         geometry.height = logical.height;
@@ -317,48 +400,94 @@ fn handle_resize_event(
 }
 
 fn detect_dock_candidate(
-    monitor: &Monitor,
+    area: &WorkArea,
     position: &LogicalPosition<f64>,
-    width: f64,
+    size: &LogicalSize<f64>,
 ) -> Option<DockPosition> {
-    let monitor_position: LogicalPosition<f64> =
-        monitor.position().to_logical(monitor.scale_factor());
-    let monitor_size: LogicalSize<f64> = monitor.size().to_logical(monitor.scale_factor());
-
-    if (position.x - monitor_position.x).abs() <= DOCK_THRESHOLD {
+    if (position.x - area.x).abs() <= DOCK_THRESHOLD {
         Some(DockPosition::Left)
-    } else if ((position.x + width) - (monitor_position.x + monitor_size.width)).abs()
-        <= DOCK_THRESHOLD
-    {
+    } else if ((position.x + size.width) - area.right()).abs() <= DOCK_THRESHOLD {
         Some(DockPosition::Right)
+    } else if (position.y - area.y).abs() <= DOCK_THRESHOLD {
+        Some(DockPosition::Top)
+    } else if ((position.y + size.height) - area.bottom()).abs() <= DOCK_THRESHOLD {
+        Some(DockPosition::Bottom)
     } else {
         None
     }
 }
 
+/// Allowed docked width (left/right) or height (top/bottom) on `area`
+fn dock_size_limits(area: &WorkArea, position: &DockPosition) -> (f64, f64) {
+    let (min, available) = if position.is_side() {
+        (WINDOW_DOCK_MIN_WIDTH, area.width)
+    } else {
+        (WINDOW_DOCK_MIN_HEIGHT, area.height)
+    };
+    let max = (available * WINDOW_DOCK_MAX_FRACTION)
+        .max(min)
+        .min(available);
+    (min.min(max), max)
+}
+
+/// Docked geometry flush against `position`'s edge of the work area
+fn dock_geometry(area: &WorkArea, position: &DockPosition, size: Option<f64>) -> WindowGeometry {
+    let (min, max) = dock_size_limits(area, position);
+    let default = if position.is_side() {
+        WINDOW_DEFAULT_MAX_WIDTH
+    } else {
+        WINDOW_DOCK_DEFAULT_HEIGHT
+    };
+    let extent = size.unwrap_or(default).clamp(min, max);
+
+    match position {
+        DockPosition::Left => WindowGeometry {
+            x: area.x,
+            y: area.y,
+            width: extent,
+            height: area.height,
+        },
+        DockPosition::Right => WindowGeometry {
+            x: area.right() - extent,
+            y: area.y,
+            width: extent,
+            height: area.height,
+        },
+        DockPosition::Top => WindowGeometry {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: extent,
+        },
+        DockPosition::Bottom => WindowGeometry {
+            x: area.x,
+            y: area.bottom() - extent,
+            width: area.width,
+            height: extent,
+        },
+    }
+}
+
 fn apply_geometry(
     window: &WebviewWindow,
     app_state: &AppState,
     geometry: &WindowGeometry,
 ) -> Result<()> {
-    let is_docked = app_state.with_state(|state| state.dock.is_some());
+    let dock = app_state.with_state(|state| state.dock.clone());
     let is_maximized = app_state.with_state(|state| state.maximized);
 
-    let width = if is_docked {
-        // When docked, clamp to dock width (use dock-specific minimum)
-        geometry
-            .width
-            .clamp(WINDOW_DOCK_MIN_WIDTH, WINDOW_DEFAULT_MAX_WIDTH)
-    } else if is_maximized {
+    let (width, height) = match dock {
+        // When docked, the geometry already fits the work area
+        Some(_) => (geometry.width, geometry.height),
         // When maximized, use geometry width as-is
-        geometry.width
-    } else {
+        None if is_maximized => (geometry.width, geometry.height.max(WINDOW_MIN_HEIGHT)),
         // When not docked and not maximized, only enforce minimum
-        geometry.width.max(WINDOW_MIN_WIDTH)
+        None => (
+            geometry.width.max(WINDOW_MIN_WIDTH),
+            geometry.height.max(WINDOW_MIN_HEIGHT),
+        ),
     };
 
-    let height = geometry.height.max(WINDOW_MIN_HEIGHT);
-
     let logical_size = LogicalSize::<f64> { width, height };
     let logical_position = LogicalPosition::<f64> {
         x: geometry.x,
@@ -386,21 +515,171 @@ fn resolve_monitor(window: &WebviewWindow) -> Result<Monitor> {
     monitors.pop().context("no monitor information available")
 }
 
-fn calculate_default_geometry(monitor: &Monitor) -> Result<WindowGeometry> {
+/// Monitor named `name`, or the window's current monitor when `name` is unset
+/// or that monitor is no longer connected
+fn resolve_dock_monitor(window: &WebviewWindow, name: Option<&str>) -> Result<Monitor> {
+    if let Some(name) = name {
+        let monitors = window
+            .available_monitors()
+            .context("failed to enumerate monitors")?;
+        if let Some(monitor) = monitors
+            .into_iter()
+            .find(|monitor| monitor.name().map(String::as_str) == Some(name))
+        {
+            return Ok(monitor);
+        }
+        warn!("Dock monitor {name:?} not found, docking on the current monitor");
+    }
+    resolve_monitor(window)
+}
+
+/// Connected monitors with the ids accepted as dock targets
+pub fn list_monitors(window: &WebviewWindow) -> Result<Vec<MonitorInfo>> {
+    let primary = window.primary_monitor()?.and_then(|m| m.name().cloned());
+    let monitors = window
+        .available_monitors()
+        .context("failed to enumerate monitors")?;
+    Ok(monitors
+        .iter()
+        .map(|monitor| MonitorInfo {
+            id: monitor.name().cloned(),
+            primary: primary.is_some() && monitor.name() == primary.as_ref(),
+            scale_factor: monitor.scale_factor(),
+            work_area: work_area(monitor),
+        })
+        .collect())
+}
+
+/// Work area of `monitor`. Falls back to the full monitor bounds only when
+/// the platform reports nothing usable.
+pub fn work_area(monitor: &Monitor) -> WorkArea {
     let scale_factor = monitor.scale_factor();
-    let monitor_size: LogicalSize<f64> = monitor.size().to_logical(scale_factor);
-    let monitor_position: LogicalPosition<f64> = monitor.position().to_logical(scale_factor);
-
-    // Calculate centered position
-    let x: f64 = monitor_position.x + (monitor_size.width - WINDOW_DEFAULT_WIDTH) / 2.0;
-    let y: f64 = monitor_position.y + (monitor_size.height - WINDOW_DEFAULT_HEIGHT) / 2.0;
-
-    Ok(WindowGeometry {
-        x: x.max(monitor_position.x), // Ensure not negative
-        y: y.max(monitor_position.y), // Ensure not negative
-        width: WINDOW_DEFAULT_WIDTH,
-        height: WINDOW_DEFAULT_HEIGHT,
-    })
+
+    #[cfg(windows)]
+    if let Some((x, y, width, height)) = win32_work_area(monitor) {
+        return WorkArea::from_physical(x, y, width, height, scale_factor);
+    }
+
+    let rect = monitor.work_area();
+    if rect.size.width > 0 && rect.size.height > 0 {
+        WorkArea::from_physical(
+            rect.position.x,
+            rect.position.y,
+            rect.size.width,
+            rect.size.height,
+            scale_factor,
+        )
+    } else {
+        let position = monitor.position();
+        let size = monitor.size();
+        WorkArea::from_physical(
+            position.x,
+            position.y,
+            size.width,
+            size.height,
+            scale_factor,
+        )
+    }
+}
+
+/// `rcWork` of `monitor`, looked up by its centre point so any monitor can
+/// be targeted, not just the one the window is on. `rcWork` leaves out only
+/// app bars that reserve space; an auto-hide taskbar slides out over it, so
+/// the edge it hides on is inset by the bar's thickness.
+#[cfg(windows)]
+fn win32_work_area(monitor: &Monitor) -> Option<(i32, i32, u32, u32)> {
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::UI::Shell::{ABE_LEFT, ABE_RIGHT, ABE_TOP};
+
+    let position = monitor.position();
+    let size = monitor.size();
+    let centre = POINT {
+        x: position.x + (size.width / 2) as i32,
+        y: position.y + (size.height / 2) as i32,
+    };
+
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    // SAFETY: `info.cbSize` is set and the handle comes straight from the OS
+    let found = unsafe {
+        let handle = MonitorFromPoint(centre, MONITOR_DEFAULTTONEAREST);
+        GetMonitorInfoW(handle, &mut info).as_bool()
+    };
+    if !found {
+        return None;
+    }
+
+    let bounds = info.rcMonitor;
+    let mut rect = info.rcWork;
+    for (edge, thickness) in win32_auto_hide_bars(bounds) {
+        match edge {
+            ABE_LEFT => rect.left = rect.left.max(bounds.left + thickness),
+            ABE_TOP => rect.top = rect.top.max(bounds.top + thickness),
+            ABE_RIGHT => rect.right = rect.right.min(bounds.right - thickness),
+            _ => rect.bottom = rect.bottom.min(bounds.bottom - thickness),
+        }
+    }
+    let width = rect.right.checked_sub(rect.left).filter(|w| *w > 0)?;
+    let height = rect.bottom.checked_sub(rect.top).filter(|h| *h > 0)?;
+    Some((rect.left, rect.top, width as u32, height as u32))
+}
+
+/// Auto-hide app bars on the edges of the monitor with bounds `monitor`,
+/// as `ABE_*` edge and thickness when shown
+#[cfg(windows)]
+fn win32_auto_hide_bars(monitor: windows::Win32::Foundation::RECT) -> Vec<(u32, i32)> {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::UI::Shell::{
+        SHAppBarMessage, ABE_BOTTOM, ABE_LEFT, ABE_RIGHT, ABE_TOP, ABM_GETAUTOHIDEBAREX, APPBARDATA,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+
+    [ABE_LEFT, ABE_TOP, ABE_RIGHT, ABE_BOTTOM]
+        .into_iter()
+        .filter_map(|edge| {
+            let mut data = APPBARDATA {
+                cbSize: std::mem::size_of::<APPBARDATA>() as u32,
+                uEdge: edge,
+                rc: monitor,
+                ..Default::default()
+            };
+            let mut bar = RECT::default();
+            // SAFETY: `data.cbSize` is set and the bar handle comes from the shell
+            unsafe {
+                let handle = SHAppBarMessage(ABM_GETAUTOHIDEBAREX, &mut data);
+                if handle == 0 {
+                    return None;
+                }
+                GetWindowRect(HWND(handle as isize), &mut bar).ok()?;
+            }
+            // A hidden bar keeps its size, mostly off screen
+            let thickness = if edge == ABE_LEFT || edge == ABE_RIGHT {
+                bar.right - bar.left
+            } else {
+                bar.bottom - bar.top
+            };
+            Some((edge, thickness.max(0)))
+        })
+        .collect()
+}
+
+/// Default size centred in the work area, shrunk to fit small or heavily
+/// reserved screens
+fn calculate_default_geometry(area: &WorkArea) -> WindowGeometry {
+    let width = WINDOW_DEFAULT_WIDTH.min(area.width);
+    let height = WINDOW_DEFAULT_HEIGHT.min(area.height);
+
+    WindowGeometry {
+        x: area.x + (area.width - width) / 2.0,
+        y: area.y + (area.height - height) / 2.0,
+        width,
+        height,
+    }
 }
 
 fn emit_state(window: &WebviewWindow, app_state: &AppState) -> Result<()> {
@@ -467,3 +746,76 @@ fn register_event_handlers(window: &WebviewWindow, app_state: &AppState) -> Resu
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_geometry_respects_side_taskbar() {
+        // 1920x1080 monitor with a 62px taskbar docked on the left
+        let area = WorkArea {
+            x: 62.0,
+            y: 0.0,
+            width: 1858.0,
+            height: 1080.0,
+        };
+        let geometry = calculate_default_geometry(&area);
+        assert!(area.contains(&geometry));
+        assert_eq!(geometry.width, WINDOW_DEFAULT_WIDTH);
+        assert_eq!(geometry.x, 62.0 + (1858.0 - WINDOW_DEFAULT_WIDTH) / 2.0);
+
+        let size = LogicalSize::new(400.0, 600.0);
+        assert_eq!(
+            detect_dock_candidate(&area, &LogicalPosition::new(70.0, 200.0), &size),
+            Some(DockPosition::Left)
+        );
+        assert_eq!(
+            detect_dock_candidate(&area, &LogicalPosition::new(0.0, 200.0), &size),
+            None
+        );
+    }
+
+    #[test]
+    fn test_top_and_bottom_docking() {
+        // Secondary 2560x1440 monitor to the right of a 1920 primary, bottom taskbar
+        let area = WorkArea {
+            x: 1920.0,
+            y: 0.0,
+            width: 2560.0,
+            height: 1392.0,
+        };
+        let size = LogicalSize::new(1200.0, 700.0);
+        assert_eq!(
+            detect_dock_candidate(&area, &LogicalPosition::new(2500.0, 10.0), &size),
+            Some(DockPosition::Top)
+        );
+        assert_eq!(
+            detect_dock_candidate(&area, &LogicalPosition::new(2500.0, 700.0), &size),
+            Some(DockPosition::Bottom)
+        );
+
+        let bottom = dock_geometry(&area, &DockPosition::Bottom, None);
+        assert!(area.contains(&bottom));
+        assert_eq!(bottom.width, area.width);
+        assert_eq!(bottom.height, WINDOW_DOCK_DEFAULT_HEIGHT);
+        assert_eq!(bottom.y + bottom.height, area.bottom());
+
+        // Requested sizes are clamped to the dock limits
+        let right = dock_geometry(&area, &DockPosition::Right, Some(5000.0));
+        assert_eq!(right.width, area.width * WINDOW_DOCK_MAX_FRACTION);
+        assert_eq!(right.x + right.width, area.right());
+        let top = dock_geometry(&area, &DockPosition::Top, Some(10.0));
+        assert_eq!(top.height, WINDOW_DOCK_MIN_HEIGHT);
+    }
+
+    #[test]
+    fn test_default_geometry_shrinks_on_small_scaled_screen() {
+        // 1366x768 physical at 125% with a top taskbar
+        let area = WorkArea::from_physical(0, 40, 1366, 728, 1.25);
+        let geometry = calculate_default_geometry(&area);
+        assert!(area.contains(&geometry));
+        assert!((area.y - 32.0).abs() < f64::EPSILON);
+        assert!(geometry.height <= area.height);
+    }
+}
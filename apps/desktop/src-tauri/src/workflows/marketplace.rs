@@ -1,7 +1,6 @@
+use crate::db::Pool;
 use crate::workflows::publishing::{PublishedWorkflow, WorkflowCategory};
-use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
 
 /// Workflow filters for search
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,337 +51,341 @@ impl std::fmt::Display for SortOption {
 
 /// Workflow marketplace for discovery
 pub struct WorkflowMarketplace {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
 }
 
 impl WorkflowMarketplace {
-    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self { db }
     }
 
     /// Get featured workflows (editor's picks and top-rated)
-    pub fn get_featured_workflows(&self, limit: usize) -> Result<Vec<PublishedWorkflow>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows
-             WHERE is_featured = 1 OR (avg_rating >= 4.5 AND rating_count >= 10)
-             ORDER BY is_featured DESC, avg_rating DESC, clone_count DESC
-             LIMIT ?1",
-            )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let workflows = stmt
-            .query_map(
-                rusqlite::params![limit as i64],
-                Self::row_to_published_workflow,
-            )
-            .map_err(|e| format!("Failed to query workflows: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect workflows: {}", e))?;
-
-        Ok(workflows)
+    pub async fn get_featured_workflows(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<PublishedWorkflow>, String> {
+        self.db
+            .run(move |conn| {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows
+                     WHERE is_featured = 1 OR (avg_rating >= 4.5 AND rating_count >= 10)
+                     ORDER BY is_featured DESC, avg_rating DESC, clone_count DESC
+                     LIMIT ?1",
+                    )
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let workflows = stmt
+                    .query_map(
+                        rusqlite::params![limit as i64],
+                        Self::row_to_published_workflow,
+                    )
+                    .map_err(|e| format!("Failed to query workflows: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect workflows: {}", e))?;
+
+                Ok(workflows)
+            })
+            .await
     }
 
     /// Get trending workflows (most cloned in last 7 days)
-    pub fn get_trending_workflows(&self, limit: usize) -> Result<Vec<PublishedWorkflow>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let seven_days_ago = chrono::Utc::now().timestamp() - (7 * 24 * 60 * 60);
-
-        let mut stmt = conn.prepare(
-            "SELECT pw.id, pw.title, pw.description, pw.category, pw.creator_id, pw.creator_name,
-                    pw.workflow_definition, pw.thumbnail_url, pw.share_url, pw.clone_count,
-                    pw.view_count, pw.favorite_count, pw.avg_rating, pw.rating_count,
-                    pw.tags, pw.estimated_time_saved, pw.estimated_cost_saved,
-                    pw.is_verified, pw.is_featured, pw.created_at, pw.updated_at,
-                    COUNT(wc.id) as recent_clones
-             FROM published_workflows pw
-             LEFT JOIN workflow_clones wc ON pw.id = wc.workflow_id AND wc.cloned_at > ?1
-             GROUP BY pw.id
-             ORDER BY recent_clones DESC, pw.clone_count DESC
-             LIMIT ?2"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let workflows = stmt
-            .query_map(rusqlite::params![seven_days_ago, limit as i64], |row| {
-                Self::row_to_published_workflow(row)
+    pub async fn get_trending_workflows(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<PublishedWorkflow>, String> {
+        self.db
+            .run(move |conn| {
+                let seven_days_ago = chrono::Utc::now().timestamp() - (7 * 24 * 60 * 60);
+
+                let mut stmt = conn.prepare(
+                    "SELECT pw.id, pw.title, pw.description, pw.category, pw.creator_id, pw.creator_name,
+                            pw.workflow_definition, pw.thumbnail_url, pw.share_url, pw.clone_count,
+                            pw.view_count, pw.favorite_count, pw.avg_rating, pw.rating_count,
+                            pw.tags, pw.estimated_time_saved, pw.estimated_cost_saved,
+                            pw.is_verified, pw.is_featured, pw.created_at, pw.updated_at,
+                            COUNT(wc.id) as recent_clones
+                     FROM published_workflows pw
+                     LEFT JOIN workflow_clones wc ON pw.id = wc.workflow_id AND wc.cloned_at > ?1
+                     GROUP BY pw.id
+                     ORDER BY recent_clones DESC, pw.clone_count DESC
+                     LIMIT ?2"
+                ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let workflows = stmt
+                    .query_map(rusqlite::params![seven_days_ago, limit as i64], |row| {
+                        Self::row_to_published_workflow(row)
+                    })
+                    .map_err(|e| format!("Failed to query workflows: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect workflows: {}", e))?;
+
+                Ok(workflows)
             })
-            .map_err(|e| format!("Failed to query workflows: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect workflows: {}", e))?;
-
-        Ok(workflows)
+            .await
     }
 
     /// Search workflows with filters
-    pub fn search_workflows(
+    pub async fn search_workflows(
         &self,
         filters: WorkflowFilters,
         limit: usize,
         offset: usize,
     ) -> Result<Vec<PublishedWorkflow>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        // Build dynamic query
-        let mut query = String::from(
-            "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows WHERE 1=1",
-        );
-
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-        // Add filters
-        if let Some(category) = &filters.category {
-            query.push_str(" AND category = ?");
-            params.push(Box::new(category.to_string()));
-        }
+        self.db
+            .run(move |conn| {
+                // Build dynamic query
+                let mut query = String::from(
+                    "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows WHERE 1=1",
+                );
+
+                let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+                // Add filters
+                if let Some(category) = &filters.category {
+                    query.push_str(" AND category = ?");
+                    params.push(Box::new(category.to_string()));
+                }
 
-        if let Some(min_rating) = filters.min_rating {
-            query.push_str(" AND avg_rating >= ?");
-            params.push(Box::new(min_rating));
-        }
+                if let Some(min_rating) = filters.min_rating {
+                    query.push_str(" AND avg_rating >= ?");
+                    params.push(Box::new(min_rating));
+                }
 
-        if filters.verified_only {
-            query.push_str(" AND is_verified = 1");
-        }
+                if filters.verified_only {
+                    query.push_str(" AND is_verified = 1");
+                }
 
-        if let Some(search) = &filters.search_query {
-            query.push_str(" AND (title LIKE ? OR description LIKE ? OR tags LIKE ?)");
-            let search_pattern = format!("%{}%", search);
-            params.push(Box::new(search_pattern.clone()));
-            params.push(Box::new(search_pattern.clone()));
-            params.push(Box::new(search_pattern));
-        }
+                if let Some(search) = &filters.search_query {
+                    query.push_str(" AND (title LIKE ? OR description LIKE ? OR tags LIKE ?)");
+                    let search_pattern = format!("%{}%", search);
+                    params.push(Box::new(search_pattern.clone()));
+                    params.push(Box::new(search_pattern.clone()));
+                    params.push(Box::new(search_pattern));
+                }
 
-        // Add tag filters
-        for tag in &filters.tags {
-            query.push_str(" AND tags LIKE ?");
-            params.push(Box::new(format!("%\"{}\"% ", tag)));
-        }
+                // Add tag filters
+                for tag in &filters.tags {
+                    query.push_str(" AND tags LIKE ?");
+                    params.push(Box::new(format!("%\"{}\"% ", tag)));
+                }
 
-        // Add sorting
-        query.push_str(&format!(" ORDER BY {}", filters.sort_by));
+                // Add sorting
+                query.push_str(&format!(" ORDER BY {}", filters.sort_by));
 
-        // Add pagination
-        query.push_str(" LIMIT ? OFFSET ?");
-        params.push(Box::new(limit as i64));
-        params.push(Box::new(offset as i64));
+                // Add pagination
+                query.push_str(" LIMIT ? OFFSET ?");
+                params.push(Box::new(limit as i64));
+                params.push(Box::new(offset as i64));
 
-        let mut stmt = conn
-            .prepare(&query)
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+                let mut stmt = conn
+                    .prepare(&query)
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
-        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+                let param_refs: Vec<&dyn rusqlite::ToSql> =
+                    params.iter().map(|p| p.as_ref()).collect();
 
-        let workflows = stmt
-            .query_map(&*param_refs, Self::row_to_published_workflow)
-            .map_err(|e| format!("Failed to query workflows: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect workflows: {}", e))?;
+                let workflows = stmt
+                    .query_map(&*param_refs, Self::row_to_published_workflow)
+                    .map_err(|e| format!("Failed to query workflows: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect workflows: {}", e))?;
 
-        Ok(workflows)
+                Ok(workflows)
+            })
+            .await
     }
 
     /// Get workflow by share URL
-    pub fn get_workflow_by_share_url(&self, share_url: &str) -> Result<PublishedWorkflow, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let workflow = conn
-            .query_row(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows WHERE share_url = ?1",
-                rusqlite::params![share_url],
-                Self::row_to_published_workflow,
-            )
-            .map_err(|e| format!("Workflow not found: {}", e))?;
-
-        Ok(workflow)
+    pub async fn get_workflow_by_share_url(
+        &self,
+        share_url: &str,
+    ) -> Result<PublishedWorkflow, String> {
+        let share_url = share_url.to_string();
+        self.db
+            .run(move |conn| {
+                let workflow = conn
+                    .query_row(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows WHERE share_url = ?1",
+                        rusqlite::params![share_url],
+                        Self::row_to_published_workflow,
+                    )
+                    .map_err(|e| format!("Workflow not found: {}", e))?;
+
+                Ok(workflow)
+            })
+            .await
     }
 
     /// Get workflow by id
-    pub fn get_workflow_by_id(&self, workflow_id: &str) -> Result<PublishedWorkflow, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let workflow = conn
-            .query_row(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows WHERE id = ?1",
-                rusqlite::params![workflow_id],
-                Self::row_to_published_workflow,
-            )
-            .map_err(|e| format!("Workflow not found: {}", e))?;
-
-        Ok(workflow)
+    pub async fn get_workflow_by_id(&self, workflow_id: &str) -> Result<PublishedWorkflow, String> {
+        let workflow_id = workflow_id.to_string();
+        self.db
+            .run(move |conn| {
+                let workflow = conn
+                    .query_row(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows WHERE id = ?1",
+                        rusqlite::params![workflow_id],
+                        Self::row_to_published_workflow,
+                    )
+                    .map_err(|e| format!("Workflow not found: {}", e))?;
+
+                Ok(workflow)
+            })
+            .await
     }
 
     /// Get workflows by creator
-    pub fn get_creator_workflows(
+    pub async fn get_creator_workflows(
         &self,
         creator_id: &str,
     ) -> Result<Vec<PublishedWorkflow>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows
-             WHERE creator_id = ?1
-             ORDER BY created_at DESC",
-            )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let workflows = stmt
-            .query_map(
-                rusqlite::params![creator_id],
-                Self::row_to_published_workflow,
-            )
-            .map_err(|e| format!("Failed to query workflows: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect workflows: {}", e))?;
-
-        Ok(workflows)
+        let creator_id = creator_id.to_string();
+        self.db
+            .run(move |conn| {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows
+                     WHERE creator_id = ?1
+                     ORDER BY created_at DESC",
+                    )
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let workflows = stmt
+                    .query_map(
+                        rusqlite::params![creator_id],
+                        Self::row_to_published_workflow,
+                    )
+                    .map_err(|e| format!("Failed to query workflows: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect workflows: {}", e))?;
+
+                Ok(workflows)
+            })
+            .await
     }
 
     /// Get workflows by category
-    pub fn get_workflows_by_category(
+    pub async fn get_workflows_by_category(
         &self,
         category: WorkflowCategory,
         limit: usize,
     ) -> Result<Vec<PublishedWorkflow>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows
-             WHERE category = ?1
-             ORDER BY clone_count DESC, avg_rating DESC
-             LIMIT ?2",
-            )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let workflows = stmt
-            .query_map(
-                rusqlite::params![category.to_string(), limit as i64],
-                Self::row_to_published_workflow,
-            )
-            .map_err(|e| format!("Failed to query workflows: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect workflows: {}", e))?;
-
-        Ok(workflows)
+        self.db
+            .run(move |conn| {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows
+                     WHERE category = ?1
+                     ORDER BY clone_count DESC, avg_rating DESC
+                     LIMIT ?2",
+                    )
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let workflows = stmt
+                    .query_map(
+                        rusqlite::params![category.to_string(), limit as i64],
+                        Self::row_to_published_workflow,
+                    )
+                    .map_err(|e| format!("Failed to query workflows: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect workflows: {}", e))?;
+
+                Ok(workflows)
+            })
+            .await
     }
 
     /// Get category counts for navigation
-    pub fn get_category_counts(&self) -> Result<Vec<(WorkflowCategory, u64)>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let mut stmt = conn
-            .prepare(
-                "SELECT category, COUNT(*) as count FROM published_workflows GROUP BY category",
-            )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let counts = stmt
-            .query_map([], |row| {
-                let category_str: String = row.get(0)?;
-                let count: i64 = row.get(1)?;
-                Ok((WorkflowCategory::from_str(&category_str), count as u64))
+    pub async fn get_category_counts(&self) -> Result<Vec<(WorkflowCategory, u64)>, String> {
+        self.db
+            .run(move |conn| {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT category, COUNT(*) as count FROM published_workflows GROUP BY category",
+                    )
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let counts = stmt
+                    .query_map([], |row| {
+                        let category_str: String = row.get(0)?;
+                        let count: i64 = row.get(1)?;
+                        Ok((WorkflowCategory::from_str(&category_str), count as u64))
+                    })
+                    .map_err(|e| format!("Failed to query counts: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect counts: {}", e))?;
+
+                Ok(counts)
             })
-            .map_err(|e| format!("Failed to query counts: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect counts: {}", e))?;
-
-        Ok(counts)
+            .await
     }
 
     /// Get popular tags
-    pub fn get_popular_tags(&self, limit: usize) -> Result<Vec<(String, u64)>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        // This is a simplified approach - in production, you'd want a separate tags table
-        let mut stmt = conn
-            .prepare("SELECT tags FROM published_workflows")
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let mut tag_counts: std::collections::HashMap<String, u64> =
-            std::collections::HashMap::new();
-
-        let rows = stmt
-            .query_map([], |row| {
-                let tags_json: String = row.get(0)?;
-                Ok(tags_json)
-            })
-            .map_err(|e| format!("Failed to query tags: {}", e))?;
-
-        for row_result in rows {
-            let tags_json = row_result.map_err(|e| format!("Failed to get row: {}", e))?;
-            if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
-                for tag in tags {
-                    *tag_counts.entry(tag).or_insert(0) += 1;
+    pub async fn get_popular_tags(&self, limit: usize) -> Result<Vec<(String, u64)>, String> {
+        self.db
+            .run(move |conn| {
+                // This is a simplified approach - in production, you'd want a separate tags table
+                let mut stmt = conn
+                    .prepare("SELECT tags FROM published_workflows")
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let mut tag_counts: std::collections::HashMap<String, u64> =
+                    std::collections::HashMap::new();
+
+                let rows = stmt
+                    .query_map([], |row| {
+                        let tags_json: String = row.get(0)?;
+                        Ok(tags_json)
+                    })
+                    .map_err(|e| format!("Failed to query tags: {}", e))?;
+
+                for row_result in rows {
+                    let tags_json = row_result.map_err(|e| format!("Failed to get row: {}", e))?;
+                    if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
+                        for tag in tags {
+                            *tag_counts.entry(tag).or_insert(0) += 1;
+                        }
+                    }
                 }
-            }
-        }
 
-        let mut tags_vec: Vec<(String, u64)> = tag_counts.into_iter().collect();
-        tags_vec.sort_by(|a, b| b.1.cmp(&a.1));
-        tags_vec.truncate(limit);
+                let mut tags_vec: Vec<(String, u64)> = tag_counts.into_iter().collect();
+                tags_vec.sort_by(|a, b| b.1.cmp(&a.1));
+                tags_vec.truncate(limit);
 
-        Ok(tags_vec)
+                Ok(tags_vec)
+            })
+            .await
     }
 
     /// Helper to convert database row to PublishedWorkflow
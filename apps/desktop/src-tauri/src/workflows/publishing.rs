@@ -1,8 +1,7 @@
+use crate::db::Pool;
 use crate::orchestration::workflow_engine::WorkflowDefinition;
 use chrono::Utc;
-use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 /// Request object for publishing a workflow
@@ -97,293 +96,298 @@ impl WorkflowCategory {
 
 /// Workflow publisher for managing public workflows
 pub struct WorkflowPublisher {
-    db: Arc<Mutex<Connection>>,
+    db: Pool,
 }
 
 impl WorkflowPublisher {
-    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(db: Pool) -> Self {
         Self { db }
     }
 
     /// Publish a workflow to the marketplace
     /// Publish a workflow to the marketplace
-    pub fn publish_workflow(
+    pub async fn publish_workflow(
         &self,
         request: PublishWorkflowRequest,
     ) -> Result<PublishedWorkflow, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let published_id = Uuid::new_v4().to_string();
-        let share_url = Self::generate_share_url(&published_id);
-        let now = Utc::now().timestamp();
-
-        // Serialize workflow definition
-        let workflow_json = serde_json::to_string(&request.workflow)
-            .map_err(|e| format!("Failed to serialize workflow: {}", e))?;
-
-        // Serialize tags
-        let tags_json = serde_json::to_string(&request.tags)
-            .map_err(|e| format!("Failed to serialize tags: {}", e))?;
-
-        conn.execute(
-            "INSERT INTO published_workflows (
-                id, title, description, category, creator_id, creator_name,
-                workflow_definition, thumbnail_url, share_url, clone_count,
-                view_count, favorite_count, avg_rating, rating_count,
-                tags, estimated_time_saved, estimated_cost_saved,
-                is_verified, is_featured, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
-            rusqlite::params![
-                &published_id,
-                &request.workflow.name,
-                &request.workflow.description.as_deref().unwrap_or(""),
-                request.category.to_string(),
-                &request.publisher_id,
-                &request.publisher_name,
-                &workflow_json,
-                &request.thumbnail_url,
-                &share_url,
-                0_u64,  // clone_count
-                0_u64,  // view_count
-                0_u64,  // favorite_count
-                0.0_f64, // avg_rating
-                0_u64,  // rating_count
-                &tags_json,
-                request.estimated_time_saved as i64,
-                request.estimated_cost_saved,
-                false, // is_verified
-                false, // is_featured
-                now,
-                now,
-            ],
-        ).map_err(|e| format!("Failed to insert published workflow: {}", e))?;
-
-        Ok(PublishedWorkflow {
-            id: published_id,
-            title: request.workflow.name,
-            description: request.workflow.description.unwrap_or_default(),
-            category: request.category,
-            creator_id: request.publisher_id,
-            creator_name: request.publisher_name,
-            creator_avatar: None,
-            thumbnail_url: request.thumbnail_url,
-            share_url,
-            clone_count: 0,
-            view_count: 0,
-            favorite_count: 0,
-            rating: 0.0,
-            rating_count: 0,
-            tags: request.tags,
-            estimated_time_saved: request.estimated_time_saved,
-            estimated_cost_saved: request.estimated_cost_saved,
-            is_verified: false,
-            is_featured: false,
-            workflow_definition: workflow_json,
-            created_at: now,
-            updated_at: now,
-        })
+        self.db
+            .run(move |conn| {
+                let published_id = Uuid::new_v4().to_string();
+                let share_url = Self::generate_share_url(&published_id);
+                let now = Utc::now().timestamp();
+
+                // Serialize workflow definition
+                let workflow_json = serde_json::to_string(&request.workflow)
+                    .map_err(|e| format!("Failed to serialize workflow: {}", e))?;
+
+                // Serialize tags
+                let tags_json = serde_json::to_string(&request.tags)
+                    .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+
+                conn.execute(
+                    "INSERT INTO published_workflows (
+                        id, title, description, category, creator_id, creator_name,
+                        workflow_definition, thumbnail_url, share_url, clone_count,
+                        view_count, favorite_count, avg_rating, rating_count,
+                        tags, estimated_time_saved, estimated_cost_saved,
+                        is_verified, is_featured, created_at, updated_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
+                    rusqlite::params![
+                        &published_id,
+                        &request.workflow.name,
+                        &request.workflow.description.as_deref().unwrap_or(""),
+                        request.category.to_string(),
+                        &request.publisher_id,
+                        &request.publisher_name,
+                        &workflow_json,
+                        &request.thumbnail_url,
+                        &share_url,
+                        0_u64,  // clone_count
+                        0_u64,  // view_count
+                        0_u64,  // favorite_count
+                        0.0_f64, // avg_rating
+                        0_u64,  // rating_count
+                        &tags_json,
+                        request.estimated_time_saved as i64,
+                        request.estimated_cost_saved,
+                        false, // is_verified
+                        false, // is_featured
+                        now,
+                        now,
+                    ],
+                ).map_err(|e| format!("Failed to insert published workflow: {}", e))?;
+
+                Ok(PublishedWorkflow {
+                    id: published_id,
+                    title: request.workflow.name,
+                    description: request.workflow.description.unwrap_or_default(),
+                    category: request.category,
+                    creator_id: request.publisher_id,
+                    creator_name: request.publisher_name,
+                    creator_avatar: None,
+                    thumbnail_url: request.thumbnail_url,
+                    share_url,
+                    clone_count: 0,
+                    view_count: 0,
+                    favorite_count: 0,
+                    rating: 0.0,
+                    rating_count: 0,
+                    tags: request.tags,
+                    estimated_time_saved: request.estimated_time_saved,
+                    estimated_cost_saved: request.estimated_cost_saved,
+                    is_verified: false,
+                    is_featured: false,
+                    workflow_definition: workflow_json,
+                    created_at: now,
+                    updated_at: now,
+                })
+            })
+            .await
     }
 
     /// Unpublish a workflow from the marketplace
-    pub fn unpublish_workflow(&self, workflow_id: &str, user_id: &str) -> Result<(), String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        // Verify ownership
-        let creator_id: String = conn
-            .query_row(
-                "SELECT creator_id FROM published_workflows WHERE id = ?1",
-                rusqlite::params![workflow_id],
-                |row| row.get(0),
-            )
-            .map_err(|e| format!("Workflow not found: {}", e))?;
-
-        if creator_id != user_id {
-            return Err("Not authorized to unpublish this workflow".to_string());
-        }
-
-        conn.execute(
-            "DELETE FROM published_workflows WHERE id = ?1",
-            rusqlite::params![workflow_id],
-        )
-        .map_err(|e| format!("Failed to delete workflow: {}", e))?;
-
-        Ok(())
+    pub async fn unpublish_workflow(&self, workflow_id: &str, user_id: &str) -> Result<(), String> {
+        let workflow_id = workflow_id.to_string();
+        let user_id = user_id.to_string();
+        self.db
+            .run(move |conn| {
+                // Verify ownership
+                let creator_id: String = conn
+                    .query_row(
+                        "SELECT creator_id FROM published_workflows WHERE id = ?1",
+                        rusqlite::params![workflow_id],
+                        |row| row.get(0),
+                    )
+                    .map_err(|e| format!("Workflow not found: {}", e))?;
+
+                if creator_id != user_id {
+                    return Err("Not authorized to unpublish this workflow".to_string());
+                }
+
+                conn.execute(
+                    "DELETE FROM published_workflows WHERE id = ?1",
+                    rusqlite::params![workflow_id],
+                )
+                .map_err(|e| format!("Failed to delete workflow: {}", e))?;
+
+                Ok(())
+            })
+            .await
     }
 
     /// Clone a published workflow to user's workspace
-    pub fn clone_workflow(
+    pub async fn clone_workflow(
         &self,
         workflow_id: &str,
         user_id: &str,
         user_name: &str,
     ) -> Result<String, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        // Get published workflow
-        let (workflow_json, title): (String, String) = conn
-            .query_row(
-                "SELECT workflow_definition, title FROM published_workflows WHERE id = ?1",
-                rusqlite::params![workflow_id],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .map_err(|e| format!("Workflow not found: {}", e))?;
-
-        // Parse workflow definition
-        let mut workflow: WorkflowDefinition = serde_json::from_str(&workflow_json)
-            .map_err(|e| format!("Failed to parse workflow: {}", e))?;
-
-        // Generate new ID and update user_id
-        let cloned_id = Uuid::new_v4().to_string();
-        workflow.id = cloned_id.clone();
-        workflow.user_id = user_id.to_string();
-        workflow.name = format!("{} (cloned)", title);
-        let now = Utc::now().timestamp();
-        workflow.created_at = now;
-        workflow.updated_at = now;
-
-        // Serialize individual fields for database insertion (before serializing entire workflow)
-        let nodes_json = serde_json::to_string(&workflow.nodes).unwrap_or_default();
-        let edges_json = serde_json::to_string(&workflow.edges).unwrap_or_default();
-        let triggers_json = serde_json::to_string(&workflow.triggers).unwrap_or_default();
-        let metadata_json = serde_json::to_string(&workflow.metadata).unwrap_or_default();
-
-        // Insert into workflow_definitions
-        conn.execute(
-            "INSERT INTO workflow_definitions (id, user_id, name, description, nodes, edges, triggers, metadata, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            rusqlite::params![
-                &workflow.id,
-                &workflow.user_id,
-                &workflow.name,
-                &workflow.description,
-                &nodes_json,
-                &edges_json,
-                &triggers_json,
-                &metadata_json,
-                workflow.created_at,
-                workflow.updated_at,
-            ],
-        ).map_err(|e| format!("Failed to insert cloned workflow: {}", e))?;
-
-        // Record the clone
-        let clone_record_id = Uuid::new_v4().to_string();
-        conn.execute(
-            "INSERT INTO workflow_clones (id, workflow_id, cloner_id, cloner_name, cloned_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![&clone_record_id, workflow_id, user_id, user_name, now,],
-        )
-        .map_err(|e| format!("Failed to record clone: {}", e))?;
-
-        // Increment clone count
-        conn.execute(
-            "UPDATE published_workflows SET clone_count = clone_count + 1 WHERE id = ?1",
-            rusqlite::params![workflow_id],
-        )
-        .map_err(|e| format!("Failed to increment clone count: {}", e))?;
-
-        Ok(cloned_id)
+        let workflow_id = workflow_id.to_string();
+        let user_id = user_id.to_string();
+        let user_name = user_name.to_string();
+        self.db
+            .run(move |conn| {
+                // Get published workflow
+                let (workflow_json, title): (String, String) = conn
+                    .query_row(
+                        "SELECT workflow_definition, title FROM published_workflows WHERE id = ?1",
+                        rusqlite::params![workflow_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .map_err(|e| format!("Workflow not found: {}", e))?;
+
+                // Parse workflow definition
+                let mut workflow: WorkflowDefinition = serde_json::from_str(&workflow_json)
+                    .map_err(|e| format!("Failed to parse workflow: {}", e))?;
+
+                // Generate new ID and update user_id
+                let cloned_id = Uuid::new_v4().to_string();
+                workflow.id = cloned_id.clone();
+                workflow.user_id = user_id.to_string();
+                workflow.name = format!("{} (cloned)", title);
+                let now = Utc::now().timestamp();
+                workflow.created_at = now;
+                workflow.updated_at = now;
+
+                // Serialize individual fields for database insertion (before serializing entire workflow)
+                let nodes_json = serde_json::to_string(&workflow.nodes).unwrap_or_default();
+                let edges_json = serde_json::to_string(&workflow.edges).unwrap_or_default();
+                let triggers_json = serde_json::to_string(&workflow.triggers).unwrap_or_default();
+                let metadata_json = serde_json::to_string(&workflow.metadata).unwrap_or_default();
+
+                // Insert into workflow_definitions
+                conn.execute(
+                    "INSERT INTO workflow_definitions (id, user_id, name, description, nodes, edges, triggers, metadata, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    rusqlite::params![
+                        &workflow.id,
+                        &workflow.user_id,
+                        &workflow.name,
+                        &workflow.description,
+                        &nodes_json,
+                        &edges_json,
+                        &triggers_json,
+                        &metadata_json,
+                        workflow.created_at,
+                        workflow.updated_at,
+                    ],
+                ).map_err(|e| format!("Failed to insert cloned workflow: {}", e))?;
+
+                // Record the clone
+                let clone_record_id = Uuid::new_v4().to_string();
+                conn.execute(
+                    "INSERT INTO workflow_clones (id, workflow_id, cloner_id, cloner_name, cloned_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![&clone_record_id, workflow_id, user_id, user_name, now,],
+                )
+                .map_err(|e| format!("Failed to record clone: {}", e))?;
+
+                // Increment clone count
+                conn.execute(
+                    "UPDATE published_workflows SET clone_count = clone_count + 1 WHERE id = ?1",
+                    rusqlite::params![workflow_id],
+                )
+                .map_err(|e| format!("Failed to increment clone count: {}", e))?;
+
+                Ok(cloned_id)
+            })
+            .await
     }
 
     /// Fork a workflow (create editable copy with link to original)
-    pub fn fork_workflow(
+    pub async fn fork_workflow(
         &self,
         workflow_id: &str,
         user_id: &str,
         user_name: &str,
     ) -> Result<String, String> {
         // Forking is similar to cloning but preserves original reference
-        let cloned_id = self.clone_workflow(workflow_id, user_id, user_name)?;
+        let cloned_id = self.clone_workflow(workflow_id, user_id, user_name).await?;
 
         // Add fork metadata to the cloned workflow
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        conn.execute(
-            "UPDATE workflow_definitions SET metadata = json_set(metadata, '$.forked_from', ?1) WHERE id = ?2",
-            rusqlite::params![workflow_id, &cloned_id],
-        ).map_err(|e| format!("Failed to update fork metadata: {}", e))?;
-
-        Ok(cloned_id)
+        let workflow_id = workflow_id.to_string();
+        self.db
+            .run(move |conn| {
+                conn.execute(
+                    "UPDATE workflow_definitions SET metadata = json_set(metadata, '$.forked_from', ?1) WHERE id = ?2",
+                    rusqlite::params![workflow_id, &cloned_id],
+                ).map_err(|e| format!("Failed to update fork metadata: {}", e))?;
+
+                Ok(cloned_id)
+            })
+            .await
     }
 
     /// Get a published workflow by ID
-    pub fn get_published_workflow(&self, workflow_id: &str) -> Result<PublishedWorkflow, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let workflow = conn
-            .query_row(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows WHERE id = ?1",
-                rusqlite::params![workflow_id],
-                Self::row_to_published_workflow,
-            )
-            .map_err(|e| format!("Failed to get workflow: {}", e))?;
-
-        Ok(workflow)
+    pub async fn get_published_workflow(
+        &self,
+        workflow_id: &str,
+    ) -> Result<PublishedWorkflow, String> {
+        let workflow_id = workflow_id.to_string();
+        self.db
+            .run(move |conn| {
+                let workflow = conn
+                    .query_row(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows WHERE id = ?1",
+                        rusqlite::params![workflow_id],
+                        Self::row_to_published_workflow,
+                    )
+                    .map_err(|e| format!("Failed to get workflow: {}", e))?;
+
+                Ok(workflow)
+            })
+            .await
     }
 
     /// Get user's published workflows
-    pub fn get_user_published_workflows(
+    pub async fn get_user_published_workflows(
         &self,
         user_id: &str,
     ) -> Result<Vec<PublishedWorkflow>, String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, title, description, category, creator_id, creator_name,
-                    workflow_definition, thumbnail_url, share_url, clone_count,
-                    view_count, favorite_count, avg_rating, rating_count,
-                    tags, estimated_time_saved, estimated_cost_saved,
-                    is_verified, is_featured, created_at, updated_at
-             FROM published_workflows WHERE creator_id = ?1 ORDER BY created_at DESC",
-            )
-            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let workflows = stmt
-            .query_map(rusqlite::params![user_id], Self::row_to_published_workflow)
-            .map_err(|e| format!("Failed to query workflows: {}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect workflows: {}", e))?;
-
-        Ok(workflows)
+        let user_id = user_id.to_string();
+        self.db
+            .run(move |conn| {
+                let mut stmt = conn
+                    .prepare(
+                        "SELECT id, title, description, category, creator_id, creator_name,
+                            workflow_definition, thumbnail_url, share_url, clone_count,
+                            view_count, favorite_count, avg_rating, rating_count,
+                            tags, estimated_time_saved, estimated_cost_saved,
+                            is_verified, is_featured, created_at, updated_at
+                     FROM published_workflows WHERE creator_id = ?1 ORDER BY created_at DESC",
+                    )
+                    .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+                let workflows = stmt
+                    .query_map(rusqlite::params![user_id], Self::row_to_published_workflow)
+                    .map_err(|e| format!("Failed to query workflows: {}", e))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Failed to collect workflows: {}", e))?;
+
+                Ok(workflows)
+            })
+            .await
     }
 
     /// Increment view count for a workflow
-    pub fn increment_view_count(&self, workflow_id: &str) -> Result<(), String> {
-        let conn = self
-            .db
-            .lock()
-            .map_err(|e| format!("Failed to lock database: {}", e))?;
-
-        conn.execute(
-            "UPDATE published_workflows SET view_count = view_count + 1 WHERE id = ?1",
-            rusqlite::params![workflow_id],
-        )
-        .map_err(|e| format!("Failed to increment view count: {}", e))?;
-
-        Ok(())
+    pub async fn increment_view_count(&self, workflow_id: &str) -> Result<(), String> {
+        let workflow_id = workflow_id.to_string();
+        self.db
+            .run(move |conn| {
+                conn.execute(
+                    "UPDATE published_workflows SET view_count = view_count + 1 WHERE id = ?1",
+                    rusqlite::params![workflow_id],
+                )
+                .map_err(|e| format!("Failed to increment view count: {}", e))?;
+
+                Ok(())
+            })
+            .await
     }
 
     /// Generate a unique share URL
@@ -0,0 +1,183 @@
+// Per-migration tests against a production-shaped database
+//
+// Each migration is applied to a copy of a database that already holds
+// conversations, messages, settings and automation history, then checked
+// for integrity, foreign keys and data loss. Reversible migrations are also
+// reverted and re-applied on the copy.
+
+use agiworkforce_desktop::db::migrations::{self, MigrationDirection};
+use rusqlite::backup::Backup;
+use rusqlite::{params, Connection};
+
+const CONVERSATIONS: i64 = 40;
+const MESSAGES_PER_CONVERSATION: i64 = 25;
+
+/// Schema v1 filled with the kind of data a long-lived install has
+fn production_shaped_db() -> Connection {
+    let conn = Connection::open_in_memory().expect("Failed to open database");
+    migrations::migrate_to(&conn, Some(1), false).expect("Failed to create v1 schema");
+    conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+
+    let tx = conn.unchecked_transaction().unwrap();
+    for c in 0..CONVERSATIONS {
+        let day = format!("2024-{:02}-{:02} 09:00:00", c % 12 + 1, c % 28 + 1);
+        tx.execute(
+            "INSERT INTO conversations (title, created_at, updated_at) VALUES (?1, ?2, ?2)",
+            params![format!("Conversation {} – naïve ünïcode 🚀", c), day],
+        )
+        .unwrap();
+        let conversation_id = tx.last_insert_rowid();
+        for m in 0..MESSAGES_PER_CONVERSATION {
+            let role = match m % 3 {
+                0 => "user",
+                1 => "assistant",
+                _ => "system",
+            };
+            tx.execute(
+                "INSERT INTO messages (conversation_id, role, content, tokens, cost, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, datetime(?6, ?7))",
+                params![
+                    conversation_id,
+                    role,
+                    format!(
+                        "Message {} of {}: {}",
+                        m,
+                        c,
+                        "lorem ipsum ".repeat(m as usize)
+                    ),
+                    m * 17,
+                    m as f64 * 0.0003,
+                    day,
+                    format!("+{} seconds", m)
+                ],
+            )
+            .unwrap();
+        }
+    }
+    for key in ["theme", "default_provider", "api_key_openai"] {
+        tx.execute(
+            "INSERT INTO settings (key, value, encrypted) VALUES (?1, ?2, ?3)",
+            params![key, format!("{}-value", key), key.starts_with("api_key")],
+        )
+        .unwrap();
+    }
+    for i in 0..30 {
+        tx.execute(
+            "INSERT INTO automation_history (task_type, success, error, duration_ms, cost)
+             VALUES ('browser_automation', ?1, ?2, ?3, 0.01)",
+            params![i % 4 != 0, (i % 4 == 0).then_some("timeout"), 100 + i],
+        )
+        .unwrap();
+    }
+    tx.commit().unwrap();
+    conn
+}
+
+fn copy_of(conn: &Connection) -> Connection {
+    let mut copy = Connection::open_in_memory().unwrap();
+    Backup::new(conn, &mut copy)
+        .unwrap()
+        .run_to_completion(256, std::time::Duration::ZERO, None)
+        .unwrap();
+    copy.execute("PRAGMA foreign_keys = ON", []).unwrap();
+    copy
+}
+
+/// Row counts and a content checksum for the seeded tables
+fn fingerprint(conn: &Connection) -> (i64, i64, i64, i64, String) {
+    let count = |table: &str| -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    };
+    let content: String = conn
+        .query_row(
+            "SELECT group_concat(id || ':' || content, '|') FROM (SELECT id, content FROM messages ORDER BY id)",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    (
+        count("conversations"),
+        count("messages"),
+        count("settings"),
+        count("automation_history"),
+        content,
+    )
+}
+
+fn assert_healthy(conn: &Connection, context: &str) {
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(integrity, "ok", "{}: integrity check failed", context);
+
+    let violations: i64 = conn
+        .query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(violations, 0, "{}: foreign key violations", context);
+}
+
+#[test]
+fn test_each_migration_on_production_shaped_copy() {
+    let mut current = production_shaped_db();
+    let seeded = fingerprint(&current);
+    assert_eq!(seeded.1, CONVERSATIONS * MESSAGES_PER_CONVERSATION);
+
+    for migration in migrations::registry().iter().skip(1) {
+        let context = format!("v{} ({})", migration.version, migration.name);
+        let copy = copy_of(&current);
+
+        let plan = migrations::migrate_to(&copy, Some(migration.version), false)
+            .unwrap_or_else(|e| panic!("{}: failed to apply: {:#}", context, e));
+        assert_eq!(plan.steps.len(), 1, "{}", context);
+        assert_eq!(plan.steps[0].direction, MigrationDirection::Up);
+        assert_eq!(
+            migrations::current_version(&copy).unwrap(),
+            migration.version,
+            "{}",
+            context
+        );
+        assert_healthy(&copy, &context);
+        assert_eq!(
+            fingerprint(&copy),
+            seeded,
+            "{}: seeded data changed",
+            context
+        );
+
+        if migration.is_reversible() {
+            let previous = migration.version - 1;
+            migrations::migrate_to(&copy, Some(previous), false)
+                .unwrap_or_else(|e| panic!("{}: failed to revert: {:#}", context, e));
+            assert_eq!(migrations::current_version(&copy).unwrap(), previous);
+            assert_healthy(&copy, &format!("{} reverted", context));
+            assert_eq!(fingerprint(&copy), seeded, "{}: revert lost data", context);
+
+            migrations::migrate_to(&copy, Some(migration.version), false)
+                .unwrap_or_else(|e| panic!("{}: failed to re-apply: {:#}", context, e));
+        }
+
+        current = copy;
+    }
+
+    assert_eq!(
+        migrations::current_version(&current).unwrap(),
+        migrations::latest_version()
+    );
+}
+
+#[test]
+fn test_dry_run_leaves_production_shaped_db_untouched() {
+    let mut conn = production_shaped_db();
+    let before = fingerprint(&conn);
+
+    let plan = migrations::preview_migrations(&mut conn, None).unwrap();
+    assert_eq!(plan.steps.len() as i32, migrations::latest_version() - 1);
+    assert!(plan.steps.iter().all(|step| !step.sql.is_empty()));
+    assert_eq!(migrations::current_version(&conn).unwrap(), 1);
+    assert_eq!(fingerprint(&conn), before);
+}
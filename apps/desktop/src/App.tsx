@@ -10,6 +10,7 @@ import { initializeAgentStatusListener, useUnifiedChatStore } from './stores/uni
 import { CircleUserRound, Maximize2, Minimize2, Moon, Plus, RefreshCcw, Sun } from 'lucide-react';
 import ErrorBoundary from './components/ErrorBoundary';
 import ErrorToastContainer from './components/errors/ErrorToast';
+import { ReauthPrompt } from './components/Security/ReauthPrompt';
 import { Spinner } from './components/ui/Spinner';
 import { errorReportingService } from './services/errorReporting';
 import useErrorStore from './stores/errorStore';
@@ -19,6 +20,7 @@ const VisualizationLayer = lazy(() =>
     default: m.VisualizationLayer,
   })),
 );
+const QuickLauncher = lazy(() => import('./components/Launcher/QuickLauncher'));
 const OnboardingWizard = lazy(() =>
   import('./components/onboarding/OnboardingWizardNew').then((m) => ({
     default: m.OnboardingWizardNew,
@@ -315,6 +317,7 @@ const DesktopShell = () => {
       <Suspense fallback={null}>
         <BillingPageDialog open={billingPageOpen} onOpenChange={setBillingPageOpen} />
       </Suspense>
+      <ReauthPrompt />
       <ErrorToastContainer position="top-right" />
     </div>
   );
@@ -322,23 +325,29 @@ const DesktopShell = () => {
 
 const App = () => {
   // Updated Nov 16, 2025: Added proper URL parameter validation for security
-  const isOverlayMode = (() => {
-    if (typeof window === 'undefined') return false;
+  const mode = (() => {
+    if (typeof window === 'undefined') return null;
 
     try {
       const params = new URLSearchParams(window.location.search);
       const mode = params.get('mode');
       // Only accept specific allowed values
-      return mode === 'overlay';
+      return mode === 'overlay' || mode === 'launcher' ? mode : null;
     } catch {
-      return false;
+      return null;
     }
   })();
 
   return (
     <ErrorBoundary>
       <Suspense fallback={<LoadingFallback />}>
-        {isOverlayMode ? <VisualizationLayer /> : <DesktopShell />}
+        {mode === 'overlay' ? (
+          <VisualizationLayer />
+        ) : mode === 'launcher' ? (
+          <QuickLauncher />
+        ) : (
+          <DesktopShell />
+        )}
       </Suspense>
     </ErrorBoundary>
   );
@@ -0,0 +1,49 @@
+/**
+ * Approval Policies API
+ * Rules for agent approval requests: auto-approve lower-risk tool categories,
+ * time out undecided requests (failing or skipping the action), escalate
+ * with a second notification, and decide several requests at once.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export type ApprovalCategory = 'terminal' | 'filesystem' | 'browser' | 'ui' | 'mcp' | 'unknown';
+export type TimeoutAction = 'fail' | 'skip';
+export type ApprovalDecision = 'approve' | 'reject' | 'skip';
+
+export interface ApprovalPolicy {
+  id: string;
+  /** Every category when unset */
+  category?: ApprovalCategory | null;
+  /** Wins over category policies */
+  toolName?: string | null;
+  /** Never applies to high-risk requests or dangerous shell commands */
+  autoApprove?: boolean;
+  /** Waits indefinitely when unset */
+  timeoutSecs?: number | null;
+  onTimeout?: TimeoutAction;
+  escalateAfterSecs?: number | null;
+}
+
+/** Most specific first */
+export async function listApprovalPolicies(): Promise<ApprovalPolicy[]> {
+  return invoke<ApprovalPolicy[]>('approval_list_policies');
+}
+
+/** Adds the policy or replaces the one with the same id. Admin only. */
+export async function setApprovalPolicy(policy: ApprovalPolicy): Promise<void> {
+  return invoke<void>('approval_set_policy', { policy });
+}
+
+export async function deleteApprovalPolicy(id: string): Promise<boolean> {
+  return invoke<boolean>('approval_delete_policy', { id });
+}
+
+/** Decides every pending approval when no ids are given; returns the ids resolved */
+export async function resolveApprovalBatch(
+  decision: ApprovalDecision,
+  approvalIds?: string[],
+  reason?: string,
+): Promise<string[]> {
+  return invoke<string[]>('approval_resolve_batch', { approvalIds, decision, reason });
+}
@@ -0,0 +1,28 @@
+/**
+ * Archive Ingestion API
+ * Add every document in a zip archive to a project's knowledge base
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { ArchiveIngestProgress, ArchiveIngestReport } from '../types/document';
+
+/**
+ * Unpack a zip in a temporary sandbox and ingest its PDF, DOCX, Markdown, text
+ * and HTML files. All resulting chunks share the report's source_group.
+ */
+export async function ingestArchive(
+  projectId: string,
+  archivePath: string,
+): Promise<ArchiveIngestReport> {
+  return invoke<ArchiveIngestReport>('document_ingest_archive', { projectId, archivePath });
+}
+
+/** Called once per archive entry while an ingestion runs */
+export async function onArchiveIngestProgress(
+  handler: (progress: ArchiveIngestProgress) => void,
+): Promise<UnlistenFn> {
+  return listen<ArchiveIngestProgress>('knowledge:ingest-progress', (event) =>
+    handler(event.payload),
+  );
+}
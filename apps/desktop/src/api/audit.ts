@@ -0,0 +1,60 @@
+/**
+ * Audit API
+ * Append-only record of automation, filesystem writes, email sends,
+ * payments and permission grants, with query and compliance export.
+ * Admin only.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export type ActorKind = 'user' | 'agent' | 'employee' | 'system';
+export type AuditCategory =
+  | 'automation'
+  | 'filesystem'
+  | 'email'
+  | 'payment'
+  | 'permission'
+  | 'remote_api';
+export type AuditOutcome = 'success' | 'failure' | 'denied';
+export type AuditExportFormat = 'jsonl' | 'csv';
+
+export interface AuditRecord {
+  id: number;
+  createdAt: string;
+  actorKind: ActorKind;
+  actorId: string | null;
+  category: AuditCategory;
+  action: string;
+  target: string | null;
+  /** SHA-256 of the call parameters; the parameters themselves are not stored */
+  paramsHash: string | null;
+  outcome: AuditOutcome;
+  detail: string | null;
+}
+
+export interface AuditQuery {
+  actorKind?: ActorKind;
+  actorId?: string;
+  category?: AuditCategory;
+  action?: string;
+  /** Substring of the target */
+  target?: string;
+  outcome?: AuditOutcome;
+  since?: string;
+  until?: string;
+  limit?: number;
+  offset?: number;
+}
+
+/** Newest first */
+export async function queryAudit(filters?: AuditQuery): Promise<AuditRecord[]> {
+  return invoke<AuditRecord[]>('audit_query', { filters });
+}
+
+/** Every matching record unless a limit is given */
+export async function exportAudit(
+  format: AuditExportFormat,
+  filters?: AuditQuery,
+): Promise<string> {
+  return invoke<string>('audit_export', { filters, format });
+}
@@ -0,0 +1,41 @@
+/**
+ * Autocomplete API
+ * Suggestions for the chat input: slash commands, workflows, employees, files and contacts
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type SuggestionKind = 'slash_command' | 'workflow' | 'employee' | 'file' | 'contact';
+
+export interface Suggestion {
+  kind: SuggestionKind;
+  /** Command name, row id, relative path or email */
+  id: string;
+  label: string;
+  detail: string | null;
+  /** Text that replaces the typed prefix */
+  insertText: string;
+  score: number;
+  /** Times the user picked this suggestion */
+  usageCount: number;
+  /** Char indices in `label` to highlight */
+  matches: number[];
+}
+
+/**
+ * Suggestions for the word being typed. Without `kinds`, a leading `/`
+ * completes commands, `@` mentions contacts and employees, and anything else
+ * completes workflows and files.
+ */
+export async function autocompleteQuery(
+  prefix: string,
+  kinds?: SuggestionKind[],
+  limit?: number,
+): Promise<Suggestion[]> {
+  return invoke<Suggestion[]>('autocomplete_query', { prefix, kinds, limit });
+}
+
+/** Call when a suggestion is accepted so it ranks higher next time */
+export async function autocompleteRecordUsage(kind: SuggestionKind, id: string): Promise<void> {
+  return invoke('autocomplete_record_usage', { kind, id });
+}
@@ -0,0 +1,72 @@
+/**
+ * Blackboard API
+ * Entries and artifacts shared by the agents of an orchestrated run, so one
+ * agent can hand intermediate results to another. Changes are emitted as
+ * `blackboard:changed`.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export const BLACKBOARD_CHANGED_EVENT = 'blackboard:changed';
+
+/** `replace`: last write wins; `append`: the entry becomes a list of values */
+export type WriteMode = 'replace' | 'append';
+
+export interface BlackboardEntry {
+  key: string;
+  value: unknown;
+  version: number;
+  /** Goal id of the writing agent, or `user` */
+  writtenBy: string;
+  updatedAt: string;
+}
+
+export interface ArtifactInfo {
+  name: string;
+  contentType: string;
+  size: number;
+  version: number;
+  writtenBy: string;
+  updatedAt: string;
+}
+
+export interface Artifact extends Omit<ArtifactInfo, 'size'> {
+  content: string;
+}
+
+export interface BlackboardSnapshot {
+  runId: string;
+  entries: BlackboardEntry[];
+  artifacts: ArtifactInfo[];
+}
+
+export interface BlackboardChange {
+  runId: string;
+  kind: 'entry' | 'artifact';
+  key: string;
+  version: number;
+  writtenBy: string;
+}
+
+export async function getBlackboard(runId: string): Promise<BlackboardSnapshot> {
+  return invoke<BlackboardSnapshot>('blackboard_get', { runId });
+}
+
+export async function getBlackboardArtifact(runId: string, name: string): Promise<Artifact> {
+  return invoke<Artifact>('blackboard_get_artifact', { runId, name });
+}
+
+/** Fails on a version conflict when `expectedVersion` is given */
+export async function writeBlackboard(
+  runId: string,
+  key: string,
+  value: unknown,
+  mode?: WriteMode,
+  expectedVersion?: number,
+): Promise<BlackboardEntry> {
+  return invoke<BlackboardEntry>('blackboard_write', { runId, key, value, mode, expectedVersion });
+}
+
+export async function closeBlackboard(runId: string): Promise<boolean> {
+  return invoke<boolean>('blackboard_close', { runId });
+}
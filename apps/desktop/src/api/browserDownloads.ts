@@ -0,0 +1,77 @@
+/**
+ * Browser Downloads API
+ * Files downloaded in automated tabs are saved to a directory per tab and
+ * checksummed; with a project they are also added to its knowledge base.
+ */
+
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { invoke } from '../lib/authInvoke';
+
+export type DownloadState = 'in_progress' | 'completed' | 'canceled' | 'failed';
+
+export interface DownloadRecord {
+  /** The browser's download GUID */
+  id: string;
+  tab_id: string;
+  url: string;
+  file_name: string;
+  /** Set once the file is complete */
+  path: string | null;
+  state: DownloadState;
+  received_bytes: number;
+  /** 0 when the server did not send a length */
+  total_bytes: number;
+  sha256: string | null;
+  /** Knowledge base document ID, or the source group of an ingested archive */
+  knowledge_id: string | null;
+  ingest_error: string | null;
+  error: string | null;
+  started_at: number;
+  finished_at: number | null;
+}
+
+export interface DownloadSessionInfo {
+  tab_id: string;
+  directory: string;
+  project_id: string | null;
+  downloads: DownloadRecord[];
+}
+
+export interface WaitForDownload {
+  /** Defaults to 60 seconds */
+  timeout_ms?: number;
+  /** Only downloads whose file name contains this, ignoring case */
+  file_name?: string;
+  /** Expected SHA-256 in hex; a mismatch fails the wait */
+  sha256?: string;
+}
+
+export async function enableBrowserDownloads(
+  tabId: string,
+  directory?: string,
+  projectId?: string,
+): Promise<DownloadSessionInfo> {
+  return invoke<DownloadSessionInfo>('browser_enable_downloads', { tabId, directory, projectId });
+}
+
+export async function listBrowserDownloads(tabId: string): Promise<DownloadSessionInfo> {
+  return invoke<DownloadSessionInfo>('browser_list_downloads', { tabId });
+}
+
+export async function waitForBrowserDownload(
+  tabId: string,
+  options?: WaitForDownload,
+): Promise<DownloadRecord> {
+  return invoke<DownloadRecord>('browser_wait_for_download', { tabId, options });
+}
+
+export async function disableBrowserDownloads(tabId: string): Promise<void> {
+  return invoke<void>('browser_disable_downloads', { tabId });
+}
+
+/** Progress, completion and failure of every captured download */
+export async function subscribeToBrowserDownloads(
+  onProgress: (download: DownloadRecord) => void,
+): Promise<UnlistenFn> {
+  return listen<DownloadRecord>('browser:download-progress', (e) => onProgress(e.payload));
+}
@@ -0,0 +1,37 @@
+/**
+ * Browser Pool API
+ * Parallel orchestrator agents each get an isolated headless browser context;
+ * these stats show the pool's contexts, leases and queued requests.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export interface PooledContextStats {
+  agent_id: string;
+  /** Page target; usable as `tab_id` */
+  target_id: string;
+  active_leases: number;
+  total_leases: number;
+  created_at: number;
+  last_used_at: number;
+  idle_secs: number;
+}
+
+export interface BrowserPoolStats {
+  /** Browser operations that may run at once across all agents */
+  max_concurrency: number;
+  idle_timeout_secs: number;
+  browser_running: boolean;
+  active_leases: number;
+  /** Lease requests waiting for a free slot, per agent */
+  waiting: Record<string, number>;
+  contexts: PooledContextStats[];
+  leases_granted: number;
+  /** Leases that had to wait for a slot */
+  leases_queued: number;
+  contexts_reaped: number;
+}
+
+export async function getBrowserPoolStats(): Promise<BrowserPoolStats> {
+  return invoke<BrowserPoolStats>('browser_pool_stats');
+}
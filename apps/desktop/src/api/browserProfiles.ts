@@ -0,0 +1,80 @@
+/**
+ * Browser Profiles API
+ * Named Chromium profiles with their own cookies, storage, extensions and
+ * proxy. A profile's browser keeps running when the app quits and is
+ * re-attached on the next start, so web apps stay logged in.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export interface ProxySettings {
+  /** e.g. `http://proxy.corp:8080` or `socks5://127.0.0.1:1080` */
+  server: string;
+  /** Hosts that skip the proxy, e.g. `localhost` or `*.internal` */
+  bypass?: string[];
+}
+
+export interface ProfileSession {
+  pid: number;
+  debug_port: number;
+  started_at: number;
+}
+
+export interface BrowserProfile {
+  id: string;
+  name: string;
+  user_data_dir: string;
+  proxy: ProxySettings | null;
+  /** Unpacked extension directories */
+  extensions: string[];
+  created_at: number;
+  last_used_at: number | null;
+  /** Last known browser; it may have been closed since */
+  session: ProfileSession | null;
+}
+
+export interface BrowserProfileInput {
+  name: string;
+  proxy?: ProxySettings | null;
+  extensions?: string[];
+}
+
+export interface BrowserHandle {
+  id: string;
+  browser_type: 'chromium' | 'firefox' | 'webkit';
+  ws_endpoint: string;
+  profile_id: string | null;
+}
+
+export async function createBrowserProfile(input: BrowserProfileInput): Promise<BrowserProfile> {
+  return invoke<BrowserProfile>('browser_create_profile', { input });
+}
+
+export async function listBrowserProfiles(): Promise<BrowserProfile[]> {
+  return invoke<BrowserProfile[]>('browser_list_profiles');
+}
+
+/** Applies from the profile's next launch; pass null to clear */
+export async function setBrowserProfileProxy(
+  profileId: string,
+  proxy: ProxySettings | null,
+): Promise<BrowserProfile> {
+  return invoke<BrowserProfile>('browser_set_profile_proxy', { profileId, proxy });
+}
+
+/** Also removes the profile's cookies and storage */
+export async function deleteBrowserProfile(profileId: string): Promise<void> {
+  return invoke<void>('browser_delete_profile', { profileId });
+}
+
+/** Attaches to the profile's browser when it is already running */
+export async function launchBrowserProfile(
+  profileId: string,
+  headless?: boolean,
+): Promise<BrowserHandle> {
+  return invoke<BrowserHandle>('browser_launch_profile', { profileId, headless });
+}
+
+export async function stopBrowserProfile(profileId: string): Promise<void> {
+  return invoke<void>('browser_stop_profile', { profileId });
+}
@@ -0,0 +1,105 @@
+/**
+ * Browser Recorder API
+ * Capture what the user does in a controlled tab as a replayable script,
+ * then turn it into a skill or a draft workflow. Replays heal selectors that
+ * stop matching; healing statistics show how often that happens.
+ */
+
+import { invoke } from '../lib/authInvoke';
+import type { WorkflowDefinition } from '../types/workflow';
+import type { Skill } from './skills';
+
+/** Another way to find an element; serialized as e.g. `{ Role: ['button', 'Export'] }` */
+export type SelectorStrategy =
+  | { DataTestId: string }
+  | { AriaLabel: string }
+  | { Role: [string, string] }
+  | { Text: string }
+  | { Placeholder: string }
+  | { Css: string }
+  | { XPath: string };
+
+export interface RecordedTarget {
+  selector: string;
+  /** Most reliable first */
+  fallbacks: SelectorStrategy[];
+  /** e.g. `button "Export"` */
+  description: string;
+}
+
+export type RecordedAction =
+  | { action: 'navigate'; url: string }
+  /** Navigation caused by the step before it */
+  | { action: 'wait_for_navigation'; url: string }
+  | { action: 'click'; target: RecordedTarget }
+  /** `text` is empty for password fields */
+  | { action: 'type'; target: RecordedTarget; text: string; secret: boolean }
+  | { action: 'select'; target: RecordedTarget; value: string }
+  | { action: 'check'; target: RecordedTarget; checked: boolean }
+  | { action: 'press_key'; target: RecordedTarget | null; key: string };
+
+/** Unix milliseconds */
+export type RecordedStep = { at: number } & RecordedAction;
+
+export interface RecordedScript {
+  id: string;
+  tab_id: string;
+  start_url: string;
+  steps: RecordedStep[];
+  started_at: number;
+  finished_at: number | null;
+}
+
+/** Returns the recording ID */
+export async function startBrowserRecording(tabId: string): Promise<string> {
+  return invoke<string>('browser_start_recording', { tabId });
+}
+
+export async function getBrowserRecording(recordingId: string): Promise<RecordedScript> {
+  return invoke<RecordedScript>('browser_recording_snapshot', { recordingId });
+}
+
+export async function stopBrowserRecording(recordingId: string): Promise<RecordedScript> {
+  return invoke<RecordedScript>('browser_stop_recording', { recordingId });
+}
+
+/**
+ * Typed text becomes skill parameters; passwords have no default and must be
+ * supplied on each run. The skill is not saved; store it with `saveSkill`.
+ */
+export async function recordingToSkill(
+  script: RecordedScript,
+  name: string,
+  description: string,
+): Promise<Skill> {
+  return invoke<Skill>('browser_recording_to_skill', { script, name, description });
+}
+
+/** The draft is not saved; store it with `create_workflow` after editing */
+export async function recordingToWorkflow(
+  script: RecordedScript,
+  name: string,
+): Promise<WorkflowDefinition> {
+  return invoke<WorkflowDefinition>('browser_recording_to_workflow', { script, name });
+}
+
+export interface HealingStats {
+  /** null for calls made outside a skill or workflow */
+  workflow_id: string | null;
+  lookups: number;
+  found: number;
+  healed: number;
+  failed: number;
+  /** Share of lookups that reached the element, healed or not */
+  success_rate: number;
+  /** Share of lookups whose stored selector had gone stale */
+  heal_rate: number;
+  /** Successful heals per strategy, e.g. `{ role: 3, nearby_label: 1 }` */
+  strategies: Record<string, number>;
+  last_healed_at: number | null;
+}
+
+/** Selector healing per workflow, or for one workflow (a skill ID) */
+export async function getSelectorHealingStats(workflowId?: string): Promise<HealingStats[]> {
+  return invoke<HealingStats[]>('browser_healing_stats', { workflowId: workflowId ?? null });
+}
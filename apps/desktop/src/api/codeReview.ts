@@ -0,0 +1,64 @@
+/**
+ * Code Review API
+ * Review git diffs and post the results to GitHub pull requests
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type ReviewRange =
+  | { kind: 'uncommitted' }
+  | { kind: 'staged' }
+  | { kind: 'commits'; base: string; head?: string | null }
+  | { kind: 'pull_request'; number: number; base?: string | null };
+
+export type ReviewSeverity = 'critical' | 'high' | 'medium' | 'low' | 'info';
+
+export interface ReviewFinding {
+  severity: ReviewSeverity;
+  source: 'rule' | 'llm';
+  category: string;
+  file: string;
+  line: number | null;
+  title: string;
+  message: string;
+  suggestion: string | null;
+}
+
+export interface ReviewedFile {
+  path: string;
+  status: 'added' | 'modified' | 'deleted' | 'renamed';
+  additions: number;
+  deletions: number;
+}
+
+export interface ReviewReport {
+  range: ReviewRange;
+  files: ReviewedFile[];
+  findings: ReviewFinding[];
+  counts: Record<ReviewSeverity, number>;
+  llm_reviewed: boolean;
+  notes: string[];
+  created_at: number;
+}
+
+export async function runCodeReview(
+  repoPath: string,
+  range: ReviewRange = { kind: 'uncommitted' },
+  useLlm = true,
+): Promise<ReviewReport> {
+  return invoke<ReviewReport>('code_review_run', { repoPath, range, useLlm });
+}
+
+export async function codeReviewToMarkdown(report: ReviewReport): Promise<string> {
+  return invoke<string>('code_review_to_markdown', { report });
+}
+
+/** Returns the URL of the posted review */
+export async function postCodeReviewToGitHub(
+  repoPath: string,
+  pullRequest: number,
+  report: ReviewReport,
+  token?: string,
+): Promise<string> {
+  return invoke<string>('code_review_post_github', { repoPath, pullRequest, report, token });
+}
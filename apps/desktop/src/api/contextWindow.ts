@@ -0,0 +1,25 @@
+/**
+ * Context Window API
+ * How much of the model's context window a conversation uses, and how much has been summarized
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface ContextUsage {
+  conversation_id: number;
+  model: string;
+  context_limit: number;
+  /** Tokens of the rolling summary plus the messages after it */
+  used_tokens: number;
+  summary_tokens: number;
+  /** Older messages folded into the summary */
+  summarized_messages: number;
+  recent_messages: number;
+}
+
+export async function getContextUsage(
+  conversationId: number,
+  model?: string,
+): Promise<ContextUsage> {
+  return invoke<ContextUsage>('chat_get_context_usage', { conversationId, model });
+}
@@ -0,0 +1,56 @@
+/**
+ * Conversation Branches API
+ * Regenerate assistant replies as sibling branches and switch between them
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { Message } from '../types/chat';
+
+export interface MessageDiff {
+  base_message_id: number;
+  lines_added: number;
+  lines_removed: number;
+  /** Every line of both messages, prefixed with `+ `, `- ` or two spaces */
+  preview: string;
+}
+
+export interface ConversationBranch {
+  branch_id: number;
+  /** Message the branch continues from; null when it starts the conversation */
+  fork_message_id: number | null;
+  first_message_id: number;
+  leaf_message_id: number;
+  message_count: number;
+  preview: string;
+  created_at: string;
+  active: boolean;
+  /** How the first message differs from the earlier reply; null for the original branch */
+  diff: MessageDiff | null;
+}
+
+export interface RegenerateMessageResponse {
+  message: Message;
+  branch_id: number;
+  /** The conversation's messages with the new reply on the active branch */
+  messages: Message[];
+}
+
+export async function regenerateMessage(
+  messageId: number,
+  options: { provider?: string; model?: string } = {},
+): Promise<RegenerateMessageResponse> {
+  return invoke<RegenerateMessageResponse>('chat_regenerate_message', {
+    messageId,
+    provider: options.provider,
+    model: options.model,
+  });
+}
+
+/** Returns the messages of the branch switched to */
+export async function switchBranch(conversationId: number, branchId: number): Promise<Message[]> {
+  return invoke<Message[]>('chat_switch_branch', { conversationId, branchId });
+}
+
+export async function listBranches(conversationId: number): Promise<ConversationBranch[]> {
+  return invoke<ConversationBranch[]>('chat_list_branches', { conversationId });
+}
@@ -0,0 +1,36 @@
+/**
+ * Conversation Workflow API
+ * Draft a reusable workflow from the tool calls made in a chat
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { WorkflowDefinition } from '../types/workflow';
+
+export interface WorkflowParameter {
+  /** Referenced from node inputs as `{{name}}` */
+  name: string;
+  /** The literal the conversation used */
+  default: unknown;
+  description: string;
+  /** IDs of the nodes that reference it */
+  used_by: string[];
+}
+
+export interface SkippedToolCall {
+  tool_call_id: string;
+  tool_name: string;
+  reason: string;
+}
+
+export interface ConversationWorkflowDraft {
+  workflow: WorkflowDefinition;
+  parameters: WorkflowParameter[];
+  skipped: SkippedToolCall[];
+}
+
+/** The draft is not saved; store it with `create_workflow` after editing */
+export async function convertConversationToWorkflow(
+  conversationId: number,
+): Promise<ConversationWorkflowDraft> {
+  return invoke<ConversationWorkflowDraft>('chat_convert_to_workflow', { conversationId });
+}
@@ -0,0 +1,65 @@
+/**
+ * Credential Health API
+ * Expiry and last-check status of connected integration accounts
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type CredentialSource =
+  | 'calendar'
+  | 'cloud'
+  | 'productivity'
+  | 'messaging'
+  | 'github'
+  | 'health_check';
+
+export type CredentialStatus = 'healthy' | 'expiring_soon' | 'expired' | 'failing' | 'unknown';
+
+export interface CredentialHealth {
+  source: CredentialSource;
+  account_id: string;
+  provider: string;
+  label: string | null;
+  status: CredentialStatus;
+  /** Unix seconds; only set for credentials that cannot be refreshed automatically */
+  expires_at: number | null;
+  /** Whole days until expiry, negative once expired */
+  days_remaining: number | null;
+  last_success_at: number | null;
+  last_failure_at: number | null;
+  last_error: string | null;
+  last_checked_at: number;
+}
+
+export interface CredentialHealthOverview {
+  /** Accounts needing attention first */
+  accounts: CredentialHealth[];
+  healthy: number;
+  expiring_soon: number;
+  expired: number;
+  failing: number;
+  unknown: number;
+  last_checked_at: number | null;
+}
+
+export function needsAttention(status: CredentialStatus): boolean {
+  return status === 'expiring_soon' || status === 'expired' || status === 'failing';
+}
+
+export async function getCredentialHealth(): Promise<CredentialHealthOverview> {
+  return invoke<CredentialHealthOverview>('credentials_health_overview');
+}
+
+/** Re-check every account now instead of waiting for the next scheduled check */
+export async function checkCredentialHealth(): Promise<CredentialHealthOverview> {
+  return invoke<CredentialHealthOverview>('credentials_health_check');
+}
+
+export function onCredentialHealth(
+  handler: (overview: CredentialHealthOverview) => void,
+): Promise<UnlistenFn> {
+  return listen<CredentialHealthOverview>('credentials://health', (event) =>
+    handler(event.payload),
+  );
+}
@@ -0,0 +1,48 @@
+/**
+ * Database Backup API
+ * Back up, restore and check the local app database
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface BackupInfo {
+  path: string;
+  /** `manual`, `scheduled`, `pre-migration` or `pre-restore` */
+  label: string;
+  createdAt: string;
+  sizeBytes: number;
+  compressed: boolean;
+}
+
+export interface BackupSchedule {
+  /** Hours between automatic backups; 0 turns them off */
+  intervalHours: number;
+  compress: boolean;
+}
+
+export async function createBackup(compress = true): Promise<BackupInfo> {
+  return invoke<BackupInfo>('db_backup_create', { compress });
+}
+
+/** Newest first */
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke<BackupInfo[]>('db_backup_list');
+}
+
+/** Resolves to the backup taken of the current data just before restoring */
+export async function restoreBackup(path: string): Promise<BackupInfo> {
+  return invoke<BackupInfo>('db_backup_restore', { path });
+}
+
+/** Problems found by SQLite's integrity check; empty when the database is sound */
+export async function checkIntegrity(): Promise<string[]> {
+  return invoke<string[]>('db_integrity_check');
+}
+
+export async function getBackupSchedule(): Promise<BackupSchedule> {
+  return invoke<BackupSchedule>('db_backup_get_schedule');
+}
+
+export async function setBackupSchedule(schedule: BackupSchedule): Promise<void> {
+  return invoke('db_backup_set_schedule', { schedule });
+}
@@ -0,0 +1,98 @@
+/**
+ * Dependency Scan API
+ * Check workspace dependencies for known vulnerabilities and license conflicts
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+import type { ReviewSeverity } from './codeReview';
+
+export type Ecosystem = 'crates.io' | 'npm' | 'PyPI';
+
+export type LicenseVerdict = 'allowed' | 'review' | 'denied';
+
+export interface Dependency {
+  name: string;
+  ecosystem: Ecosystem;
+  version: string | null;
+  requirement: string | null;
+  resolved: boolean;
+  direct: boolean;
+  dev: boolean;
+  license: string | null;
+  source: string;
+}
+
+export interface VulnerabilityFinding {
+  package: string;
+  ecosystem: Ecosystem;
+  version: string;
+  direct: boolean;
+  advisory_id: string;
+  aliases: string[];
+  summary: string;
+  severity: ReviewSeverity;
+  fixed_version: string | null;
+  url: string;
+  unresolved: boolean;
+}
+
+export interface LicenseFinding {
+  package: string;
+  ecosystem: Ecosystem;
+  version: string | null;
+  direct: boolean;
+  license: string | null;
+  verdict: LicenseVerdict;
+  reason: string;
+}
+
+export interface LicensePolicy {
+  /** Detected from the workspace's own license when omitted */
+  proprietary?: boolean | null;
+  allow?: string[];
+  deny?: string[];
+  ignore_unknown?: boolean;
+}
+
+export interface DependencyScanReport {
+  root: string;
+  manifests: string[];
+  project_license: string | null;
+  proprietary: boolean;
+  dependencies: Dependency[];
+  vulnerabilities: VulnerabilityFinding[];
+  licenses: LicenseFinding[];
+  counts: Record<ReviewSeverity, number>;
+  advisory_db_updated_at: number | null;
+  notes: string[];
+  scanned_at: number;
+}
+
+export interface AdvisoryDatabaseStatus {
+  path: string;
+  updated_at: number | null;
+  advisories: number;
+  refreshed: number;
+}
+
+export async function scanDependencies(
+  workspacePath: string,
+  policy?: LicensePolicy,
+): Promise<DependencyScanReport> {
+  return invoke<DependencyScanReport>('workspace_scan_dependencies', { workspacePath, policy });
+}
+
+/**
+ * Refresh the offline advisory database, from an OSV JSON or zip export when
+ * `source` is given, otherwise from osv.dev for the workspace's packages
+ */
+export async function refreshAdvisories(options: {
+  workspacePath?: string;
+  source?: string;
+}): Promise<AdvisoryDatabaseStatus> {
+  return invoke<AdvisoryDatabaseStatus>('workspace_refresh_advisories', {
+    workspacePath: options.workspacePath,
+    source: options.source,
+  });
+}
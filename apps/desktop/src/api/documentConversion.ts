@@ -0,0 +1,20 @@
+/**
+ * Document Conversion API
+ * Convert between Word, PDF and Markdown
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { DocumentConversion } from '../types/document';
+
+/** Formats are detected from the file extensions, e.g. report.docx → report.md */
+export async function convertDocument(
+  inputPath: string,
+  outputPath: string,
+): Promise<DocumentConversion> {
+  return invoke<DocumentConversion>('document_convert', { inputPath, outputPath });
+}
+
+/** Headings, lists and tables of a Word, PDF or Markdown file as Markdown */
+export async function documentToMarkdown(filePath: string): Promise<string> {
+  return invoke<string>('document_to_markdown', { filePath });
+}
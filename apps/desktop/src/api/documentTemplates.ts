@@ -0,0 +1,31 @@
+/**
+ * Document Templates API
+ * Render .docx/.xlsx templates with {{placeholders}} into Word, Excel or PDF
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { DocumentTemplate } from '../types/document';
+
+export async function saveDocumentTemplate(
+  name: string,
+  sourcePath: string,
+): Promise<DocumentTemplate> {
+  return invoke<DocumentTemplate>('document_template_save', { name, sourcePath });
+}
+
+export async function listDocumentTemplates(): Promise<DocumentTemplate[]> {
+  return invoke<DocumentTemplate[]>('document_template_list');
+}
+
+export async function deleteDocumentTemplate(name: string): Promise<void> {
+  return invoke<void>('document_template_delete', { name });
+}
+
+/** `template` is a stored template name or a file path; the output format follows `outputPath` */
+export async function renderDocumentTemplate(
+  template: string,
+  data: Record<string, unknown>,
+  outputPath: string,
+): Promise<string> {
+  return invoke<string>('document_render_template', { template, data, outputPath });
+}
@@ -70,11 +70,80 @@ export interface SearchResult {
   similarity: number;
 }
 
+export type SearchMode = 'semantic' | 'keyword' | 'hybrid';
+
+export interface SearchOptions {
+  mode?: SearchMode;
+  limit?: number;
+  /** Candidates fetched from each retriever before fusion */
+  candidates?: number;
+  /** Reciprocal rank fusion constant */
+  rrf_k?: number;
+  semantic_weight?: number;
+  keyword_weight?: number;
+  /** Rerank the top results with the LLM router */
+  rerank?: boolean;
+  rerank_top_n?: number;
+}
+
+export interface MatchExplanation {
+  semantic_rank?: number | null;
+  similarity?: number | null;
+  keyword_rank?: number | null;
+  keyword_score?: number | null;
+  matched_terms: string[];
+  rerank_score?: number | null;
+  summary: string;
+}
+
+export interface HybridSearchResult {
+  metadata: EmbeddingMetadata;
+  score: number;
+  explanation: MatchExplanation;
+}
+
 export interface EmbeddingStats {
   total_embeddings: number;
   cache_hits: number;
   cache_misses: number;
   cache_size: number;
+  /** Chunks in the approximate index; null while searches scan every chunk */
+  ann_index_size: number | null;
+}
+
+export type EmbeddingModel =
+  | 'nomic-embed-text'
+  | 'mxbai-embed-large'
+  | 'bge-small-en-v1.5'
+  | 'text-embedding-3-small'
+  | 'text-embedding-3-large'
+  | 'all-minilm-l6-v2';
+
+export interface IndexModel {
+  /** Model id, or 'unknown' for stores that predate the record */
+  model: string;
+  dimensions: number;
+  /** Model the store is being re-embedded with */
+  pending_model: string | null;
+  updated_at: number;
+}
+
+export interface ReembedStatus {
+  from_model: string;
+  to_model: string;
+  total: number;
+  done: number;
+  running: boolean;
+  error: string | null;
+}
+
+export interface EmbeddingModelStatus {
+  /** Model used for queries and new chunks */
+  active_model: EmbeddingModel;
+  /** Model the stored vectors came from */
+  index: IndexModel | null;
+  reembed: ReembedStatus | null;
+  available: { model: EmbeddingModel; dimensions: number }[];
 }
 
 export interface IndexingProgress {
@@ -103,6 +172,28 @@ export async function semanticSearchCodebase(
   }
 }
 
+/**
+ * Search the codebase combining semantic similarity and keyword matches
+ */
+export async function hybridSearchCodebase(
+  query: string,
+  options?: SearchOptions,
+): Promise<HybridSearchResult[]> {
+  try {
+    validateNonEmpty(query, 'search query');
+    if (options?.limit !== undefined && (!Number.isInteger(options.limit) || options.limit <= 0)) {
+      throw new Error(`Invalid limit: ${options.limit}`);
+    }
+    return await invokeWithTimeout<HybridSearchResult[]>(
+      'hybrid_search_codebase',
+      { query, options },
+      options?.rerank ? EMBEDDINGS_GENERATE_TIMEOUT_MS : EMBEDDINGS_TIMEOUT_MS,
+    );
+  } catch (error) {
+    throw new Error(`Failed to search codebase: ${error}`);
+  }
+}
+
 /**
  * Generate embeddings for a file
  * Updated Nov 16, 2025: Added validation, error handling, and extended timeout
@@ -140,6 +231,45 @@ export async function getEmbeddingStats(): Promise<EmbeddingStats> {
   }
 }
 
+/**
+ * Rebuild the approximate nearest neighbor index from every stored chunk
+ * @returns Number of indexed chunks
+ */
+export async function rebuildEmbeddingIndex(): Promise<number> {
+  try {
+    return await invokeWithTimeout<number>(
+      'rebuild_embedding_index',
+      undefined,
+      EMBEDDINGS_INDEX_TIMEOUT_MS,
+    );
+  } catch (error) {
+    throw new Error(`Failed to rebuild vector index: ${error}`);
+  }
+}
+
+/**
+ * Current embedding model, the model of the stored index and re-embedding progress
+ */
+export async function getEmbeddingModelStatus(): Promise<EmbeddingModelStatus> {
+  try {
+    return await invokeWithTimeout<EmbeddingModelStatus>('get_embedding_model_status');
+  } catch (error) {
+    throw new Error(`Failed to get embedding model status: ${error}`);
+  }
+}
+
+/**
+ * Switch embedding models. The index is re-embedded in the background and
+ * searches keep using the previous model until that finishes.
+ */
+export async function setEmbeddingModel(model: EmbeddingModel): Promise<void> {
+  try {
+    await invokeWithTimeout<void>('set_embedding_model', { model }, EMBEDDINGS_GENERATE_TIMEOUT_MS);
+  } catch (error) {
+    throw new Error(`Failed to switch embedding model: ${error}`);
+  }
+}
+
 /**
  * Index the entire workspace
  * Updated Nov 16, 2025: Added error handling and extended timeout
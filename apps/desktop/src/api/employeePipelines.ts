@@ -0,0 +1,128 @@
+/**
+ * Employee Pipelines API
+ * Chain hired AI employees into multi-stage pipelines
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface StageBudget {
+  max_duration_secs?: number | null;
+  /** Checked against the `cost_usd` the stage's task reports */
+  max_cost_usd?: number | null;
+}
+
+export interface PipelineStage {
+  /** Unique within the pipeline; referenced as `$stages.<name>` */
+  name: string;
+  user_employee_id: string;
+  task_type: string;
+  /**
+   * Task input key -> `$input.<path>`, `$previous.<path>`, `$stages.<name>.<path>`
+   * or a literal. Empty hands over the previous stage's output as-is.
+   */
+  input_mapping?: Record<string, unknown>;
+  budget?: StageBudget;
+  /** Pause the run before this stage until approved */
+  requires_approval?: boolean;
+}
+
+export interface EmployeePipeline {
+  id: string;
+  user_id: string;
+  name: string;
+  description: string | null;
+  stages: PipelineStage[];
+  created_at: number;
+  updated_at: number;
+}
+
+export type PipelineRunStatus =
+  | 'running'
+  | 'awaiting_approval'
+  | 'completed'
+  | 'failed'
+  | 'rejected';
+
+export type StageRunStatus =
+  | 'pending'
+  | 'awaiting_approval'
+  | 'running'
+  | 'completed'
+  | 'failed'
+  | 'rejected'
+  | 'skipped';
+
+export interface StageRun {
+  name: string;
+  status: StageRunStatus;
+  task_id: string | null;
+  input: Record<string, unknown> | null;
+  output: Record<string, unknown> | null;
+  approval: { approved: boolean; note: string | null; decided_at: number } | null;
+  started_at: number | null;
+  completed_at: number | null;
+  execution_time_seconds: number;
+  time_saved_minutes: number;
+  cost_saved_usd: number;
+  cost_usd: number | null;
+  budget_exceeded: string | null;
+  error: string | null;
+}
+
+export interface PipelineRunMetrics {
+  stages_completed: number;
+  stages_total: number;
+  execution_time_seconds: number;
+  time_saved_minutes: number;
+  cost_saved_usd: number;
+  cost_usd: number;
+}
+
+export interface PipelineRun {
+  id: string;
+  pipeline_id: string;
+  status: PipelineRunStatus;
+  input: Record<string, unknown>;
+  /** Index of the stage running, awaiting approval, or where the run stopped */
+  current_stage: number;
+  stages: StageRun[];
+  metrics: PipelineRunMetrics;
+  error: string | null;
+  started_at: number;
+  updated_at: number;
+  completed_at: number | null;
+}
+
+export async function createEmployeePipeline(
+  userId: string,
+  name: string,
+  stages: PipelineStage[],
+  description?: string,
+): Promise<EmployeePipeline> {
+  return invoke<EmployeePipeline>('employee_pipelines_create', {
+    userId,
+    name,
+    description,
+    stages,
+  });
+}
+
+/** Resolves once the run completes, fails or reaches an approval gate */
+export async function executeEmployeePipeline(
+  pipelineId: string,
+  input: Record<string, unknown> = {},
+): Promise<PipelineRun> {
+  return invoke<PipelineRun>('employee_pipelines_execute', { pipelineId, input });
+}
+
+export async function getEmployeePipelineStatus(runId: string): Promise<PipelineRun> {
+  return invoke<PipelineRun>('employee_pipelines_status', { runId });
+}
+
+export async function approveEmployeePipelineStage(
+  runId: string,
+  approved: boolean,
+  note?: string,
+): Promise<PipelineRun> {
+  return invoke<PipelineRun>('employee_pipelines_approve', { runId, approved, note });
+}
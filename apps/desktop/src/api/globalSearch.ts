@@ -0,0 +1,36 @@
+/**
+ * Global Search API
+ * Full-text search across chat messages, documents, emails and workflows
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type SearchEntityType = 'message' | 'document' | 'email' | 'workflow';
+
+export interface GlobalSearchResult {
+  entity_type: SearchEntityType;
+  /** Message id, document path, email id or workflow id */
+  entity_id: string;
+  title: string | null;
+  /** Matching text with terms wrapped in <mark> */
+  snippet: string;
+  /** Conversation id for messages, folder for emails */
+  parent_id: string | null;
+  /** Unix seconds */
+  updated_at: number | null;
+  /** Higher is more relevant */
+  score: number;
+}
+
+/** Best matches first; the last word is matched as a prefix */
+export async function searchGlobal(
+  query: string,
+  params: { types?: SearchEntityType[]; limit?: number; offset?: number } = {},
+): Promise<GlobalSearchResult[]> {
+  return invoke<GlobalSearchResult[]>('search_global', {
+    query,
+    types: params.types,
+    limit: params.limit,
+    offset: params.offset,
+  });
+}
@@ -0,0 +1,53 @@
+/**
+ * Guardrails API
+ * Prompt-injection screening of tool outputs and blocking of secrets sent
+ * to external services, with a report per agent run.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export type InjectionAction = 'flag' | 'strip';
+
+export interface GuardrailConfig {
+  enabled: boolean;
+  injectionAction: InjectionAction;
+  blockSecretEgress: boolean;
+}
+
+export interface GuardrailFinding {
+  runId: string;
+  toolName: string;
+  kind: 'prompt_injection' | 'secret_egress';
+  action: 'flagged' | 'stripped' | 'blocked';
+  /** Matched rules or secret kinds, never the matched text */
+  details: string[];
+  at: string;
+}
+
+export interface GuardrailReport {
+  runId: string;
+  startedAt: string;
+  outputsScanned: number;
+  callsChecked: number;
+  findings: GuardrailFinding[];
+}
+
+export const GUARDRAIL_FINDING_EVENT = 'guardrails:finding';
+
+export async function getGuardrailConfig(): Promise<GuardrailConfig> {
+  return invoke<GuardrailConfig>('guardrails_get_config');
+}
+
+/** Admin only */
+export async function setGuardrailConfig(config: GuardrailConfig): Promise<void> {
+  return invoke('guardrails_set_config', { config });
+}
+
+/** Newest first */
+export async function listGuardrailReports(): Promise<GuardrailReport[]> {
+  return invoke<GuardrailReport[]>('guardrails_list_reports');
+}
+
+export async function getGuardrailReport(runId: string): Promise<GuardrailReport | null> {
+  return invoke<GuardrailReport | null>('guardrails_get_report', { runId });
+}
@@ -0,0 +1,70 @@
+/**
+ * Health Checks API
+ * Declarative HTTP checks for custom integrations, reported in credential health
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type HttpMethod = 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE' | 'HEAD' | 'OPTIONS';
+
+export type AssertionOp =
+  | 'exists'
+  | 'equals'
+  | 'not_equals'
+  | 'contains'
+  | 'greater_than'
+  | 'less_than';
+
+export interface JsonPathAssertion {
+  /** Dotted path such as `data.services[0].state` */
+  path: string;
+  op: AssertionOp;
+  /** Unused for `exists`; must be a number for `greater_than` and `less_than` */
+  value?: unknown;
+}
+
+export interface NewHealthCheck {
+  name: string;
+  method?: HttpMethod;
+  url: string;
+  headers?: Record<string, string>;
+  body?: string | null;
+  /** Accepted status codes; empty accepts any 2xx */
+  expected_status?: number[];
+  assertions?: JsonPathAssertion[];
+  /** At least 30, defaults to 300 */
+  interval_secs?: number;
+  timeout_ms?: number;
+  enabled?: boolean;
+}
+
+export interface HealthCheck {
+  id: string;
+  name: string;
+  method: HttpMethod;
+  url: string;
+  headers: Record<string, string>;
+  body: string | null;
+  expected_status: number[];
+  assertions: JsonPathAssertion[];
+  interval_secs: number;
+  timeout_ms: number;
+  enabled: boolean;
+  created_at: number;
+  last_run_at: number | null;
+  last_status: number | null;
+  last_latency_ms: number | null;
+  last_error: string | null;
+}
+
+export async function createHealthCheck(definition: NewHealthCheck): Promise<HealthCheck> {
+  return invoke<HealthCheck>('healthchecks_create', { definition });
+}
+
+export async function listHealthChecks(): Promise<HealthCheck[]> {
+  return invoke<HealthCheck[]>('healthchecks_list');
+}
+
+export async function deleteHealthCheck(id: string): Promise<void> {
+  return invoke<void>('healthchecks_delete', { id });
+}
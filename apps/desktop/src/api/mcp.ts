@@ -1,13 +1,43 @@
 // Updated Nov 16, 2025: Added comprehensive error handling, input validation, timeout handling, and retry logic
 import { invoke } from '@tauri-apps/api/core';
-import type { McpServerInfo, McpToolInfo, McpServersConfig, McpServerConfig } from '../types/mcp';
+import type {
+  McpServerInfo,
+  McpToolInfo,
+  McpServersConfig,
+  McpServerConfig,
+  McpResultProcessingSettings,
+  McpCatalogListing,
+  McpCatalogInstallResult,
+  McpServerStatusEvent,
+  McpResource,
+  McpResourceReadResult,
+  McpPrompt,
+  McpPromptGetResult,
+  McpRemoteServerAdded,
+  McpOAuthStart,
+} from '../types/mcp';
 
 /**
  * MCP API Client - TypeScript bindings for MCP Tauri commands
  */
 
 // Re-export types for convenience
-export type { McpServerInfo, McpToolInfo, McpServersConfig, McpServerConfig };
+export type {
+  McpServerInfo,
+  McpToolInfo,
+  McpServersConfig,
+  McpServerConfig,
+  McpResultProcessingSettings,
+  McpCatalogListing,
+  McpCatalogInstallResult,
+  McpServerStatusEvent,
+  McpResource,
+  McpResourceReadResult,
+  McpPrompt,
+  McpPromptGetResult,
+  McpRemoteServerAdded,
+  McpOAuthStart,
+};
 
 // Updated Nov 16, 2025: Configurable timeouts for different MCP operations
 const MCP_TIMEOUT_MS = 30000; // 30 seconds for most operations
@@ -251,6 +281,28 @@ export async function mcpUpdateConfig(config: McpServersConfig): Promise<string>
   }
 }
 
+/**
+ * Get the rules that trim tool results before they reach the model
+ */
+export async function mcpGetResultProcessing(): Promise<McpResultProcessingSettings> {
+  try {
+    return await invokeWithTimeout<McpResultProcessingSettings>('mcp_get_result_processing');
+  } catch (error) {
+    throw new Error(`Failed to get MCP result processing settings: ${error}`);
+  }
+}
+
+/**
+ * Replace and persist the tool result processing rules
+ */
+export async function mcpSetResultProcessing(settings: McpResultProcessingSettings): Promise<void> {
+  try {
+    await invokeWithTimeout<void>('mcp_set_result_processing', { settings });
+  } catch (error) {
+    throw new Error(`Failed to update MCP result processing settings: ${error}`);
+  }
+}
+
 /**
  * Get server statistics (tool counts)
  * Updated Nov 16, 2025: Added error handling and timeout
@@ -296,6 +348,188 @@ export async function mcpGetToolSchemas(): Promise<unknown[]> {
   }
 }
 
+/**
+ * Servers that crashed recently, are waiting to restart or are quarantined
+ */
+export async function mcpGetServerStatuses(): Promise<McpServerStatusEvent[]> {
+  try {
+    return await invokeWithTimeout<McpServerStatusEvent[]>('mcp_get_server_statuses');
+  } catch (error) {
+    throw new Error(`Failed to get MCP server statuses: ${error}`);
+  }
+}
+
+/**
+ * List curated MCP servers that install in one step
+ */
+export async function mcpCatalogList(): Promise<McpCatalogListing[]> {
+  try {
+    return await invokeWithTimeout<McpCatalogListing[]>('mcp_catalog_list');
+  } catch (error) {
+    throw new Error(`Failed to list MCP catalog: ${error}`);
+  }
+}
+
+/**
+ * Install a catalog server and connect to it. Resolves with
+ * `needs_credentials` when required credentials are missing; call again
+ * with them to install.
+ */
+export async function mcpCatalogInstall(
+  id: string,
+  credentials?: Record<string, string>,
+): Promise<McpCatalogInstallResult> {
+  try {
+    validateNonEmpty(id, 'catalog id');
+    // npm and uv downloads can take a few minutes
+    return await invokeWithTimeout<McpCatalogInstallResult>(
+      'mcp_catalog_install',
+      { id, credentials },
+      MCP_INIT_TIMEOUT_MS * 5,
+    );
+  } catch (error) {
+    throw new Error(`Failed to install MCP server '${id}': ${error}`);
+  }
+}
+
+/**
+ * List resources of one server, or of every connected server
+ */
+export async function mcpListResources(serverName?: string): Promise<McpResource[]> {
+  try {
+    return await invokeWithTimeout<McpResource[]>('mcp_list_resources', { serverName });
+  } catch (error) {
+    throw new Error(`Failed to list MCP resources: ${error}`);
+  }
+}
+
+export async function mcpReadResource(
+  serverName: string,
+  uri: string,
+): Promise<McpResourceReadResult> {
+  try {
+    validateNonEmpty(serverName, 'server name');
+    validateNonEmpty(uri, 'resource uri');
+    return await invokeWithTimeout<McpResourceReadResult>('mcp_read_resource', {
+      serverName,
+      uri,
+    });
+  } catch (error) {
+    throw new Error(`Failed to read MCP resource '${uri}': ${error}`);
+  }
+}
+
+/**
+ * Subscribe to changes of a resource; updates arrive as
+ * `mcp://resource-updated` events
+ */
+export async function mcpSubscribeResource(serverName: string, uri: string): Promise<void> {
+  try {
+    validateNonEmpty(serverName, 'server name');
+    validateNonEmpty(uri, 'resource uri');
+    await invokeWithTimeout<void>('mcp_subscribe_resource', { serverName, uri });
+  } catch (error) {
+    throw new Error(`Failed to subscribe to MCP resource '${uri}': ${error}`);
+  }
+}
+
+export async function mcpUnsubscribeResource(serverName: string, uri: string): Promise<void> {
+  try {
+    validateNonEmpty(serverName, 'server name');
+    validateNonEmpty(uri, 'resource uri');
+    await invokeWithTimeout<void>('mcp_unsubscribe_resource', { serverName, uri });
+  } catch (error) {
+    throw new Error(`Failed to unsubscribe from MCP resource '${uri}': ${error}`);
+  }
+}
+
+/**
+ * List prompts of one server, or of every connected server
+ */
+export async function mcpListPrompts(serverName?: string): Promise<McpPrompt[]> {
+  try {
+    return await invokeWithTimeout<McpPrompt[]>('mcp_list_prompts', { serverName });
+  } catch (error) {
+    throw new Error(`Failed to list MCP prompts: ${error}`);
+  }
+}
+
+/**
+ * Render a prompt with its arguments
+ */
+export async function mcpGetPrompt(
+  serverName: string,
+  name: string,
+  args?: Record<string, string>,
+): Promise<McpPromptGetResult> {
+  try {
+    validateNonEmpty(serverName, 'server name');
+    validateNonEmpty(name, 'prompt name');
+    return await invokeWithTimeout<McpPromptGetResult>('mcp_get_prompt', {
+      serverName,
+      name,
+      arguments: args,
+    });
+  } catch (error) {
+    throw new Error(`Failed to get MCP prompt '${name}': ${error}`);
+  }
+}
+
+/**
+ * Add a remote server reached over Streamable HTTP and try to connect.
+ * Servers that need sign-in resolve with `authorization_required` set.
+ */
+export async function mcpAddRemoteServer(name: string, url: string): Promise<McpRemoteServerAdded> {
+  try {
+    validateNonEmpty(name, 'server name');
+    validateNonEmpty(url, 'server URL');
+    return await invokeWithTimeout<McpRemoteServerAdded>('mcp_add_remote_server', { name, url });
+  } catch (error) {
+    throw new Error(`Failed to add remote MCP server '${name}': ${error}`);
+  }
+}
+
+/**
+ * Begin signing in to a remote server; open `auth_url` in the browser
+ */
+export async function mcpStartOAuth(
+  serverName: string,
+  redirectUri: string,
+): Promise<McpOAuthStart> {
+  try {
+    validateNonEmpty(serverName, 'server name');
+    validateNonEmpty(redirectUri, 'redirect URI');
+    return await invokeWithTimeout<McpOAuthStart>('mcp_start_oauth', { serverName, redirectUri });
+  } catch (error) {
+    throw new Error(`Failed to start sign-in for server '${serverName}': ${error}`);
+  }
+}
+
+/**
+ * Finish signing in with the code from the redirect and connect the server
+ */
+export async function mcpCompleteOAuth(state: string, code: string): Promise<string> {
+  try {
+    validateNonEmpty(state, 'state');
+    validateNonEmpty(code, 'code');
+    return await invokeWithTimeout<string>('mcp_complete_oauth', { request: { state, code } });
+  } catch (error) {
+    throw new Error(`Failed to complete MCP sign-in: ${error}`);
+  }
+}
+
+/**
+ * Forget a remote server's tokens and disconnect it
+ */
+export async function mcpSignOut(serverName: string): Promise<void> {
+  try {
+    validateNonEmpty(serverName, 'server name');
+    await invokeWithTimeout<void>('mcp_sign_out', { serverName });
+  } catch (error) {
+    throw new Error(`Failed to sign out of server '${serverName}': ${error}`);
+  }
+}
+
 /**
  * MCP Client - React hook-friendly wrapper
  * Updated Nov 16, 2025: All methods now use error-handled functions
@@ -378,6 +612,20 @@ export class McpClient {
     return mcpUpdateConfig(config);
   }
 
+  /**
+   * Get tool result processing rules
+   */
+  static async getResultProcessing(): Promise<McpResultProcessingSettings> {
+    return mcpGetResultProcessing();
+  }
+
+  /**
+   * Update tool result processing rules
+   */
+  static async setResultProcessing(settings: McpResultProcessingSettings): Promise<void> {
+    return mcpSetResultProcessing(settings);
+  }
+
   /**
    * Get server statistics
    */
@@ -0,0 +1,77 @@
+/**
+ * MCP Server API
+ * Serves this app's filesystem, browser, document and calendar tools to
+ * outside MCP clients such as Claude Desktop or an IDE, over local SSE or a
+ * stdio relay. Each client has its own token and permission profile.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export interface McpServerClient {
+  id: string;
+  name: string;
+  /** Permission profile every call of the client is checked against */
+  profile_id: string;
+  /** Exposed tools the client may call; null for all of them */
+  tools: string[] | null;
+  enabled: boolean;
+  created_at: number;
+  updated_at: number;
+  last_used_at: number | null;
+}
+
+export interface McpServerClientInput {
+  name: string;
+  profile_id: string;
+  tools?: string[] | null;
+  enabled?: boolean;
+}
+
+export interface McpServerClientCreated {
+  client: McpServerClient;
+  /** Only returned once; stored hashed */
+  token: string;
+  /** Entry for a client's `mcpServers` config that starts the stdio relay */
+  stdio_config: { command: string; args: string[]; env: Record<string, string> };
+  /** Clients send the token as `Authorization: Bearer` */
+  sse_url: string;
+}
+
+export interface McpServerStatus {
+  listening: boolean;
+  port: number;
+  sse_url: string;
+  clients: McpServerClient[];
+}
+
+export interface ExposedMcpTool {
+  name: string;
+  description?: string;
+  inputSchema: Record<string, unknown>;
+}
+
+export async function getMcpServerStatus(): Promise<McpServerStatus> {
+  return invoke<McpServerStatus>('mcp_server_status');
+}
+
+export async function listMcpServerTools(): Promise<ExposedMcpTool[]> {
+  return invoke<ExposedMcpTool[]>('mcp_server_list_tools');
+}
+
+export async function createMcpServerClient(
+  input: McpServerClientInput,
+): Promise<McpServerClientCreated> {
+  return invoke<McpServerClientCreated>('mcp_server_create_client', { input });
+}
+
+/** The client keeps its token */
+export async function updateMcpServerClient(
+  id: string,
+  input: McpServerClientInput,
+): Promise<McpServerClient> {
+  return invoke<McpServerClient>('mcp_server_update_client', { id, input });
+}
+
+export async function deleteMcpServerClient(id: string): Promise<boolean> {
+  return invoke<boolean>('mcp_server_delete_client', { id });
+}
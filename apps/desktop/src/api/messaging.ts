@@ -0,0 +1,104 @@
+/**
+ * Messaging API
+ * Send and poll messages across Slack, Teams and WhatsApp connections
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type MessagingPlatformName = 'slack' | 'teams' | 'whatsapp';
+
+/** Serialized form of the backend's MessagingPlatform enum */
+export type MessagingPlatform = 'Slack' | 'Teams' | 'WhatsApp';
+
+export interface UnifiedMessage {
+  id: string;
+  platform: MessagingPlatform;
+  channel_id: string;
+  sender_id: string;
+  sender_name: string | null;
+  text: string;
+  timestamp: number;
+  attachments: unknown[];
+  /** Includes `connection_id`, and `thread_id` for threaded messages */
+  metadata: Record<string, string>;
+}
+
+export interface MessagingSendRequest {
+  /** Connection (account) to send from */
+  connection_id: string;
+  /** Slack channel ID, Teams `team_id/channel_id`, or WhatsApp phone number */
+  channel_id: string;
+  text: string;
+  thread_id?: string;
+}
+
+export interface MessagingSendResult {
+  connection_id: string;
+  platform: MessagingPlatform;
+  channel_id: string;
+  message_id: string;
+  /** Pass back as `thread_id` to continue the conversation in a thread */
+  thread_id: string | null;
+  timestamp: number;
+}
+
+export interface MessagingThreadSummary {
+  connection_id: string;
+  platform: MessagingPlatform;
+  channel_id: string;
+  thread_id: string | null;
+  last_message: string;
+  last_sender: string | null;
+  last_direction: 'inbound' | 'outbound';
+  last_message_at: number;
+  message_count: number;
+  awaiting_reply: boolean;
+}
+
+export interface MessagingPollRequest {
+  connection_id: string;
+  /** Required to fetch new Slack or Teams messages from the platform */
+  channel_id?: string;
+  thread_id?: string;
+  /** `cursor` from the previous poll; omit to get the latest messages */
+  cursor?: number;
+  limit?: number;
+}
+
+export interface MessagingPollResult {
+  connection_id: string;
+  platform: MessagingPlatform;
+  /** Oldest first */
+  messages: UnifiedMessage[];
+  cursor: number;
+}
+
+export async function sendMessage(request: MessagingSendRequest): Promise<MessagingSendResult> {
+  return invoke<MessagingSendResult>('messaging_send', { request });
+}
+
+export async function listMessagingThreads(options?: {
+  connectionId?: string;
+  platform?: MessagingPlatformName;
+  limit?: number;
+}): Promise<MessagingThreadSummary[]> {
+  return invoke<MessagingThreadSummary[]>('messaging_list_threads', {
+    connectionId: options?.connectionId,
+    platform: options?.platform,
+    limit: options?.limit,
+  });
+}
+
+export async function pollMessages(request: MessagingPollRequest): Promise<MessagingPollResult> {
+  return invoke<MessagingPollResult>('messaging_poll', { request });
+}
+
+/** Forward a WhatsApp Business webhook delivery; resolves to the number of new messages */
+export async function recordWhatsAppWebhook(payload: unknown): Promise<number> {
+  return invoke<number>('messaging_whatsapp_webhook', { payload });
+}
+
+export function onMessage(handler: (message: UnifiedMessage) => void): Promise<UnlistenFn> {
+  return listen<UnifiedMessage>('messaging://message', (event) => handler(event.payload));
+}
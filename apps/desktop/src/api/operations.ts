@@ -0,0 +1,31 @@
+/**
+ * Operations API
+ * Cancel long-running commands started with an `operationId`
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export const OPERATION_STARTED_EVENT = 'operation:started';
+export const OPERATION_FINISHED_EVENT = 'operation:finished';
+
+export interface OperationInfo {
+  id: string;
+  /** Command the operation runs, e.g. `document_extract_text` */
+  kind: string;
+  startedAt: string;
+  cancelled: boolean;
+}
+
+/** ID to pass as `operationId` so the command can be cancelled later */
+export function newOperationId(): string {
+  return crypto.randomUUID();
+}
+
+/** Resolves to false when no operation with that ID is running */
+export async function cancelOperation(id: string): Promise<boolean> {
+  return invoke<boolean>('operation_cancel', { id });
+}
+
+export async function listOperations(): Promise<OperationInfo[]> {
+  return invoke<OperationInfo[]>('operation_list');
+}
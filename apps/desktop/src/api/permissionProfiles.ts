@@ -0,0 +1,88 @@
+/**
+ * Permission Profiles API
+ * Which kinds of agent tool calls are allowed, denied or need approval,
+ * per AI employee and per agent run
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type ToolCategory =
+  | 'filesystem_write'
+  | 'shell'
+  | 'browser'
+  | 'ui_automation'
+  | 'email_send'
+  | 'messaging'
+  | 'integrations'
+  | 'database_write'
+  | 'payments';
+
+export type ToolDecision = 'allow' | 'deny' | 'ask';
+
+export interface PermissionProfile {
+  /** Built-in ids: `read_only_analyst`, `developer`, `full_automation` */
+  id: string;
+  name: string;
+  description: string;
+  /** Categories left out are asked about */
+  rules: Partial<Record<ToolCategory, ToolDecision>>;
+  builtIn: boolean;
+}
+
+export interface ToolDecisionRecord {
+  id: number;
+  /** Goal id of the agent run */
+  runId: string;
+  userEmployeeId: string | null;
+  profileId: string;
+  toolName: string;
+  category: ToolCategory;
+  outcome: 'allowed' | 'denied' | 'approved' | 'rejected';
+  reason: string | null;
+  createdAt: string;
+}
+
+export async function listPermissionProfiles(): Promise<PermissionProfile[]> {
+  return invoke<PermissionProfile[]>('permission_profiles_list');
+}
+
+/** Create or replace a custom profile; built-in profiles cannot be changed */
+export async function savePermissionProfile(profile: PermissionProfile): Promise<void> {
+  return invoke('permission_profiles_save', { profile });
+}
+
+/** Resolves to whether the profile existed */
+export async function deletePermissionProfile(profileId: string): Promise<boolean> {
+  return invoke<boolean>('permission_profiles_delete', { profileId });
+}
+
+export async function getDefaultPermissionProfile(): Promise<string> {
+  return invoke<string>('permission_profiles_get_default');
+}
+
+export async function setDefaultPermissionProfile(profileId: string): Promise<void> {
+  return invoke('permission_profiles_set_default', { profileId });
+}
+
+/** `null` when the employee uses the default profile */
+export async function getEmployeePermissionProfile(userEmployeeId: string): Promise<string | null> {
+  return invoke<string | null>('ai_employees_get_permission_profile', { userEmployeeId });
+}
+
+export async function setEmployeePermissionProfile(
+  userEmployeeId: string,
+  profileId: string | null,
+): Promise<void> {
+  return invoke('ai_employees_set_permission_profile', { userEmployeeId, profileId });
+}
+
+/** Newest first */
+export async function getPermissionDecisions(
+  runId?: string,
+  limit?: number,
+): Promise<ToolDecisionRecord[]> {
+  return invoke<ToolDecisionRecord[]>('permission_decisions_log', {
+    runId: runId ?? null,
+    limit: limit ?? null,
+  });
+}
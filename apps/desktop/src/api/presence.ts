@@ -0,0 +1,35 @@
+/**
+ * Presence API
+ * Per-team rules for who can see a member's presence and how much of it
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** Who in the team may see the member: everyone, only admins and the owner, or nobody */
+export type PresenceVisibility = 'team' | 'admins' | 'nobody';
+
+/** Online/offline only, the full status, or the status plus the current task */
+export type ActivityDetail = 'online_only' | 'status' | 'current_task';
+
+export interface PresencePolicy {
+  visibility: PresenceVisibility;
+  detail: ActivityDetail;
+}
+
+/** The member's rule, or the team default when they have none */
+export async function getPresencePolicy(teamId: string, userId: string): Promise<PresencePolicy> {
+  return invoke<PresencePolicy>('get_presence_policy', { teamId, userId });
+}
+
+/**
+ * Set a member's rule; `null` falls back to the team default. Members may set
+ * their own rule, admins and the owner anyone's.
+ */
+export async function setPresencePolicy(
+  actorId: string,
+  teamId: string,
+  userId: string,
+  policy: PresencePolicy | null,
+): Promise<void> {
+  return invoke('set_presence_policy', { actorId, teamId, userId, policy });
+}
@@ -0,0 +1,50 @@
+/**
+ * Privacy API
+ * Data retention settings and "delete my data" purges
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** Days each kind of data is kept; `null` keeps it forever */
+export interface RetentionPolicy {
+  messagesDays: number | null;
+  telemetryDays: number | null;
+  automationHistoryDays: number | null;
+  overlayEventsDays: number | null;
+}
+
+export interface PurgeReport {
+  messages: number;
+  /** Conversations left empty by the message purge */
+  conversations: number;
+  telemetryEvents: number;
+  automationHistory: number;
+  overlayEvents: number;
+}
+
+export interface PrivacyPurgeReport {
+  databaseRows: number;
+  embeddings: number;
+  secrets: number;
+  directories: number;
+  /** Steps that failed; everything else was still removed */
+  errors: string[];
+}
+
+export async function getRetentionPolicy(): Promise<RetentionPolicy> {
+  return invoke<RetentionPolicy>('retention_get_policy');
+}
+
+export async function setRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+  return invoke('retention_set_policy', { policy });
+}
+
+/** Apply the retention policy now instead of waiting for the daily purge */
+export async function purgeExpiredData(): Promise<PurgeReport> {
+  return invoke<PurgeReport>('retention_purge_now');
+}
+
+/** Irreversibly delete all user data, including API keys and other secrets */
+export async function purgeAllUserData(): Promise<PrivacyPurgeReport> {
+  return invoke<PrivacyPurgeReport>('privacy_purge_all');
+}
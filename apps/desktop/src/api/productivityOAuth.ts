@@ -0,0 +1,36 @@
+/**
+ * Productivity OAuth API
+ * Browser consent flows for Notion, Trello and Asana
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  ProductivityAccount,
+  ProductivityAuthorization,
+  ProductivityOAuthConfig,
+  ProductivityOAuthResult,
+} from '../types/productivity';
+
+export async function startProductivityOAuth(
+  config: ProductivityOAuthConfig,
+): Promise<ProductivityAuthorization> {
+  return invoke<ProductivityAuthorization>('productivity_start_oauth', { config });
+}
+
+/** For Trello, pass the token from the return URL fragment as `code` */
+export async function completeProductivityOAuth(
+  state: string,
+  code: string,
+): Promise<ProductivityOAuthResult> {
+  return invoke<ProductivityOAuthResult>('productivity_complete_oauth', {
+    request: { state, code },
+  });
+}
+
+export async function listProductivityAccounts(): Promise<ProductivityAccount[]> {
+  return invoke<ProductivityAccount[]>('productivity_list_accounts');
+}
+
+export async function disconnectProductivity(accountId: string): Promise<void> {
+  return invoke<void>('productivity_disconnect', { accountId });
+}
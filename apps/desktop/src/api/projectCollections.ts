@@ -0,0 +1,92 @@
+/**
+ * Project Collections API
+ * Knowledge bases built from files, folders and web pages, queried with citations
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type CollectionSourceKind = 'file' | 'folder' | 'url';
+
+export interface ProjectCollection {
+  id: string;
+  project_id: string;
+  name: string;
+  description: string | null;
+  /** Set by the first source; queries need the same active model */
+  embedding_model: string | null;
+  dimensions: number | null;
+  created_at: string;
+}
+
+export interface CollectionSource {
+  id: string;
+  collection_id: string;
+  kind: CollectionSourceKind;
+  /** File or folder path, or URL */
+  location: string;
+  status: 'pending' | 'ready' | 'failed';
+  chunk_count: number;
+  error: string | null;
+  added_at: string;
+}
+
+export interface Citation {
+  source_id: string;
+  /** Path or URL of the document the chunk came from */
+  document: string;
+  title: string;
+  chunk_index: number;
+}
+
+export interface CollectionMatch {
+  chunk_id: string;
+  content: string;
+  similarity: number;
+  citation: Citation;
+}
+
+export async function createProjectCollection(
+  projectId: string,
+  name: string,
+  description?: string,
+): Promise<ProjectCollection> {
+  return invoke<ProjectCollection>('project_collection_create', { projectId, name, description });
+}
+
+export async function listProjectCollections(projectId: string): Promise<ProjectCollection[]> {
+  return invoke<ProjectCollection[]>('project_collection_list', { projectId });
+}
+
+export async function listCollectionSources(collectionId: string): Promise<CollectionSource[]> {
+  return invoke<CollectionSource[]>('project_collection_list_sources', { collectionId });
+}
+
+export async function deleteProjectCollection(collectionId: string): Promise<void> {
+  return invoke('project_collection_delete', { collectionId });
+}
+
+/**
+ * Chunk and embed a file, every supported document in a folder, or a web
+ * page. Resolves once the source is indexed; a failed source is kept with its
+ * error.
+ */
+export async function addCollectionSource(
+  collectionId: string,
+  kind: CollectionSourceKind,
+  location: string,
+): Promise<CollectionSource> {
+  return invoke<CollectionSource>('project_collection_add_source', {
+    collectionId,
+    kind,
+    location,
+  });
+}
+
+/** The chunks most relevant to `query`, best first */
+export async function queryProjectCollection(
+  collectionId: string,
+  query: string,
+  topK?: number,
+): Promise<CollectionMatch[]> {
+  return invoke<CollectionMatch[]>('project_collection_query', { collectionId, query, topK });
+}
@@ -0,0 +1,78 @@
+/**
+ * Project Tasks API
+ * Detect a workspace's build/test/run tasks and run them with streamed output
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type TaskKind = 'build' | 'test' | 'run' | 'lint' | 'other';
+
+export type TaskSource = 'cargo' | 'npm' | 'make' | 'gradle';
+
+export interface ProjectTask {
+  /** Unique within the workspace, e.g. `cargo:test` or `web/npm:dev` */
+  name: string;
+  kind: TaskKind;
+  source: TaskSource;
+  program: string;
+  args: string[];
+  /** Relative to the workspace root */
+  cwd: string;
+  description: string | null;
+}
+
+export type TaskRunStatus = 'succeeded' | 'failed' | 'cancelled' | 'timed_out';
+
+export interface TaskRun {
+  run_id: string;
+  task: ProjectTask;
+  status: TaskRunStatus;
+  exit_code: number | null;
+  duration_ms: number;
+  output_tail: string[];
+}
+
+export interface TaskStartedEvent {
+  run_id: string;
+  task: ProjectTask;
+}
+
+export interface TaskOutputEvent {
+  run_id: string;
+  stream: 'stdout' | 'stderr';
+  line: string;
+}
+
+export async function detectProjectTasks(workspacePath: string): Promise<ProjectTask[]> {
+  return invoke<ProjectTask[]>('project_detect_tasks', { workspacePath });
+}
+
+/**
+ * Run a task by name, or the first task of a kind ('build', 'test', 'run',
+ * 'lint'). Resolves when the task exits
+ */
+export async function runProjectTask(
+  workspacePath: string,
+  name: string,
+  timeoutSecs?: number,
+): Promise<TaskRun> {
+  return invoke<TaskRun>('project_run_task', { workspacePath, name, timeoutSecs });
+}
+
+export async function cancelProjectTask(runId: string): Promise<boolean> {
+  return invoke<boolean>('project_cancel_task', { runId });
+}
+
+export async function subscribeToProjectTasks(handlers: {
+  onStarted?: (event: TaskStartedEvent) => void;
+  onOutput?: (event: TaskOutputEvent) => void;
+  onFinished?: (run: TaskRun) => void;
+}): Promise<UnlistenFn> {
+  const unlisteners = await Promise.all([
+    listen<TaskStartedEvent>('project://task-started', (e) => handlers.onStarted?.(e.payload)),
+    listen<TaskOutputEvent>('project://task-output', (e) => handlers.onOutput?.(e.payload)),
+    listen<TaskRun>('project://task-finished', (e) => handlers.onFinished?.(e.payload)),
+  ]);
+  return () => unlisteners.forEach((unlisten) => unlisten());
+}
@@ -0,0 +1,38 @@
+/**
+ * Readiness API
+ * Which backend subsystems came up, so unavailable features can be grayed out
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type ReadinessState = 'initializing' | 'ready' | 'degraded' | 'failed' | 'disabled';
+
+export interface SubsystemReadiness {
+  name: string;
+  state: ReadinessState;
+  /** Set for degraded, failed and disabled subsystems */
+  reason: string | null;
+  updatedAt: number;
+}
+
+export interface ReadinessReport {
+  /** No subsystem is still initializing */
+  settled: boolean;
+  subsystems: Record<string, SubsystemReadiness>;
+}
+
+/** Ready and degraded subsystems can still serve requests */
+export function isUsable(state: ReadinessState | undefined): boolean {
+  return state === 'ready' || state === 'degraded';
+}
+
+export async function getReadiness(): Promise<ReadinessReport> {
+  return invoke<ReadinessReport>('app_get_readiness');
+}
+
+export function onReadinessChange(
+  handler: (subsystem: SubsystemReadiness) => void,
+): Promise<UnlistenFn> {
+  return listen<SubsystemReadiness>('app://readiness', (event) => handler(event.payload));
+}
@@ -0,0 +1,64 @@
+/**
+ * Re-authentication API
+ * Windows Hello or a password check before sensitive actions. Protected
+ * commands ask by themselves; when Windows Hello is unavailable the backend
+ * emits `reauth:password_required` and waits for `submitReauthPassword`.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type SensitiveAction =
+  | 'reveal_api_key'
+  | 'approve_dangerous_command'
+  | 'change_billing'
+  | 'export_user_data';
+
+/** `create_local` asks the user to choose the local re-authentication password */
+export type PasswordKind = 'account' | 'local' | 'create_local';
+
+export interface PasswordPrompt {
+  requestId: string;
+  action: SensitiveAction;
+  message: string;
+  kind: PasswordKind;
+}
+
+export interface ReauthStatus {
+  windowsHelloAvailable: boolean;
+  localPasswordSet: boolean;
+  gracePeriodSecs: number;
+}
+
+export const PASSWORD_REQUIRED_EVENT = 'reauth:password_required';
+
+/** Confirm identity ahead of an action; resolves at once within the grace period */
+export async function confirmIdentity(action: SensitiveAction): Promise<void> {
+  return invoke('reauth_confirm', { action });
+}
+
+/** Pass `null` to cancel the prompt */
+export async function submitReauthPassword(
+  requestId: string,
+  password: string | null,
+): Promise<void> {
+  return invoke('reauth_submit_password', { requestId, password });
+}
+
+export async function getReauthStatus(): Promise<ReauthStatus> {
+  return invoke<ReauthStatus>('reauth_status');
+}
+
+/** `0` asks on every sensitive action; at most one hour */
+export async function setReauthGracePeriod(seconds: number): Promise<void> {
+  return invoke('reauth_set_grace_period', { seconds });
+}
+
+export async function setReauthPassword(
+  newPassword: string,
+  currentPassword?: string,
+): Promise<void> {
+  return invoke('reauth_set_password', {
+    currentPassword: currentPassword ?? null,
+    newPassword,
+  });
+}
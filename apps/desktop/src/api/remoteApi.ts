@@ -0,0 +1,75 @@
+/**
+ * Remote API
+ * An HTTP API under `/v1` for scripts, CI jobs and other devices: submit
+ * goals, run workflows, read status and metrics, and follow goal events over
+ * a WebSocket. Each client has its own token, scopes and permission profile.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export type ApiScope = 'goals' | 'workflows' | 'status' | 'metrics';
+
+export interface ApiClient {
+  id: string;
+  name: string;
+  /** Permission profile the tool calls of the client's goals are checked against */
+  profile_id: string;
+  /** Account whose metrics the client may read */
+  user_id: string | null;
+  scopes: ApiScope[];
+  enabled: boolean;
+  created_at: number;
+  updated_at: number;
+  last_used_at: number | null;
+}
+
+export interface ApiClientInput {
+  name: string;
+  profile_id: string;
+  user_id?: string | null;
+  scopes: ApiScope[];
+  enabled?: boolean;
+}
+
+export interface ApiClientCreated {
+  client: ApiClient;
+  /** Only returned once; sent as `Authorization: Bearer`, or `?token=` on the events WebSocket */
+  token: string;
+}
+
+export interface RemoteApiConfig {
+  enabled: boolean;
+  port: number;
+  /** Listen on every network interface instead of only 127.0.0.1 */
+  allow_remote?: boolean;
+}
+
+export interface RemoteApiStatus {
+  config: RemoteApiConfig;
+  /** `host:port` the API is listening on */
+  address: string | null;
+  clients: ApiClient[];
+  /** Set while the API is exposed to the network, which it serves without TLS */
+  warning: string | null;
+}
+
+export async function getRemoteApiStatus(): Promise<RemoteApiStatus> {
+  return invoke<RemoteApiStatus>('remote_api_status');
+}
+
+export async function configureRemoteApi(config: RemoteApiConfig): Promise<RemoteApiStatus> {
+  return invoke<RemoteApiStatus>('remote_api_configure', { config });
+}
+
+export async function createApiClient(input: ApiClientInput): Promise<ApiClientCreated> {
+  return invoke<ApiClientCreated>('remote_api_create_client', { input });
+}
+
+/** The client keeps its token */
+export async function updateApiClient(id: string, input: ApiClientInput): Promise<ApiClient> {
+  return invoke<ApiClient>('remote_api_update_client', { id, input });
+}
+
+export async function deleteApiClient(id: string): Promise<boolean> {
+  return invoke<boolean>('remote_api_delete_client', { id });
+}
@@ -0,0 +1,157 @@
+/**
+ * Run Traces API
+ * Recorded agent runs (plan, tool calls, screenshots, LLM calls with token
+ * counts and costs) and replay of a run for debugging failed automations.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export type RunStatus = 'running' | 'completed' | 'failed';
+
+/** Limits a run may reach before it pauses to ask whether to continue */
+export interface RunBudget {
+  /** LLM spend in USD */
+  maxCost: number | null;
+  maxToolCalls: number | null;
+  maxDurationSecs: number | null;
+}
+
+export interface BudgetUsage {
+  cost: number;
+  toolCalls: number;
+  elapsedSecs: number;
+}
+
+export type BudgetLimit = 'cost' | 'tool_calls' | 'duration';
+
+export interface BudgetOverrun {
+  limit: BudgetLimit;
+  budget: RunBudget;
+  usage: BudgetUsage;
+}
+
+export interface BudgetMetrics {
+  runs: number;
+  budgetedRuns: number;
+  overBudgetRuns: number;
+  totalCost: number;
+  totalToolCalls: number;
+  avgCostPerRun: number;
+}
+
+export type TraceEvent =
+  | { kind: 'plan'; steps: unknown }
+  | {
+      kind: 'tool_call';
+      stepId: string;
+      toolName: string;
+      input: unknown;
+      output: unknown | null;
+      error: string | null;
+      durationMs: number;
+      /** A replay returned the recorded output instead of running the tool */
+      mocked: boolean;
+      screenshot: string | null;
+    }
+  | {
+      kind: 'llm_call';
+      provider: string | null;
+      model: string;
+      request: unknown;
+      response: string | null;
+      error: string | null;
+      promptTokens: number;
+      completionTokens: number;
+      cost: number;
+    }
+  | { kind: 'budget'; overrun: BudgetOverrun; approved: boolean };
+
+export type TraceEntry = TraceEvent & { seq: number; at: string };
+
+export interface RunSummary {
+  runId: string;
+  goal: unknown;
+  replayOf: string | null;
+  status: RunStatus;
+  error: string | null;
+  startedAt: string;
+  finishedAt: string | null;
+  budget: RunBudget;
+  usage: BudgetUsage;
+  /** Times the run reached a limit */
+  budgetOverruns: number;
+}
+
+export interface RunTrace extends RunSummary {
+  events: TraceEntry[];
+  promptTokens: number;
+  completionTokens: number;
+  cost: number;
+}
+
+export interface ReplayRunRequest {
+  /** Return every step's recorded output instead of running its tool */
+  mock_all_tools?: boolean;
+  /** Tools whose recorded output is returned instead of running them */
+  mock_tools?: string[];
+  permission_profile?: string;
+  user_employee_id?: string;
+}
+
+/** Newest first */
+export async function listRuns(limit?: number): Promise<RunSummary[]> {
+  return invoke<RunSummary[]>('agent_list_runs', { limit });
+}
+
+export async function getRunTrace(runId: string): Promise<RunTrace | null> {
+  return invoke<RunTrace | null>('agent_get_run_trace', { runId });
+}
+
+/** Starts the replay as a new run and returns its id */
+export async function replayRun(runId: string, request?: ReplayRunRequest): Promise<string> {
+  const response = await invoke<{ goal_id: string }>('agent_replay_run', { runId, request });
+  return response.goal_id;
+}
+
+export type FileChangeKind = 'created' | 'modified' | 'deleted' | 'unchanged';
+
+export interface FileChange {
+  path: string;
+  kind: FileChangeKind;
+  /** Tools that changed the file, in order */
+  tools: string[];
+  /** Unified diff for text files */
+  diff: string | null;
+  rolledBack: boolean;
+}
+
+export interface RollbackReport {
+  runId: string;
+  restored: string[];
+  /** Files the run created */
+  removed: string[];
+}
+
+/** Files a run wrote or deleted, compared with their content before the run */
+export async function getRunFileChanges(runId: string): Promise<FileChange[]> {
+  return invoke<FileChange[]>('agent_get_run_file_changes', { runId });
+}
+
+/** Restores every file the run touched; all or nothing */
+export async function rollbackRun(runId: string): Promise<RollbackReport> {
+  return invoke<RollbackReport>('agent_rollback_run', { runId });
+}
+
+/** Budget runs get unless submitted with their own */
+export async function getDefaultRunBudget(): Promise<RunBudget> {
+  return invoke<RunBudget>('agent_get_default_run_budget');
+}
+
+export async function setDefaultRunBudget(budget: RunBudget): Promise<void> {
+  await invoke('agent_set_default_run_budget', { budget });
+}
+
+/** Spend and budget overruns of runs from the last `days` days (default 30) */
+export async function getBudgetMetrics(days?: number): Promise<BudgetMetrics> {
+  return invoke<BudgetMetrics>('agent_get_budget_metrics', { days });
+}
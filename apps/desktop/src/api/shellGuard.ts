@@ -0,0 +1,56 @@
+/**
+ * Shell guard API
+ * Allow/deny rules for shell commands run by agents, and dry runs that show
+ * what a command would execute as without running it.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export interface ShellPolicy {
+  /** Regexes over the whole command line; a match is refused */
+  denyPatterns: string[];
+  /** Regexes over the whole command line; a match needs approval */
+  approvalPatterns: string[];
+  /** Regexes that skip the approval prompt (refusals still apply) */
+  allowPatterns: string[];
+  allowlistOnly: boolean;
+  allowedPrograms: string[];
+  /** Commands must run inside one of these directories; empty allows any */
+  allowedRoots: string[];
+  envPassthrough: string[];
+}
+
+export type ShellVerdict =
+  | { decision: 'allow' }
+  | { decision: 'needs_approval'; reasons: string[] }
+  | { decision: 'deny'; reason: string };
+
+export type ShellName = 'powershell' | 'cmd' | 'bash' | 'wsl';
+
+export interface ShellPlan {
+  command: string;
+  shell: string;
+  program: string;
+  args: string[];
+  cwd: string;
+  programs: string[];
+  envKeys: string[];
+  verdict: ShellVerdict;
+}
+
+export async function getShellPolicy(): Promise<ShellPolicy> {
+  return invoke<ShellPolicy>('shell_guard_get_policy');
+}
+
+/** Admin only */
+export async function setShellPolicy(policy: ShellPolicy): Promise<void> {
+  return invoke('shell_guard_set_policy', { policy });
+}
+
+export async function dryRunShellCommand(
+  command: string,
+  shell: ShellName = 'powershell',
+  cwd?: string,
+): Promise<ShellPlan> {
+  return invoke<ShellPlan>('shell_guard_check', { command, shell, cwd: cwd ?? null });
+}
@@ -0,0 +1,77 @@
+/**
+ * Skills API
+ * Reusable, parameterized step sequences saved from successful agent runs.
+ * The planner finds relevant skills by meaning and runs each as one step.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+export interface SkillParameter {
+  name: string;
+  description?: string | null;
+  /** Required when unset */
+  default?: unknown;
+}
+
+export interface SkillStep {
+  id: string;
+  tool_id: string;
+  description: string;
+  /** Skill parameters appear as `{{name}}` */
+  parameters: Record<string, unknown>;
+  estimated_resources: { cpu_percent: number; memory_mb: number; network_mb: number };
+  dependencies: string[];
+}
+
+export interface Skill {
+  id: string;
+  name: string;
+  description: string;
+  parameters: SkillParameter[];
+  steps: SkillStep[];
+  preconditions: string[];
+  /** Integrations that must be connected, e.g. "salesforce" */
+  integrations: string[];
+  sourceRun: string | null;
+  useCount: number;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface SkillMatch {
+  skill: Skill;
+  score: number;
+}
+
+export interface SaveSkillFromRunRequest {
+  runId: string;
+  name: string;
+  description: string;
+  /** Each default is a value the run used; it is replaced by the parameter */
+  parameters?: SkillParameter[];
+  preconditions?: string[];
+  integrations?: string[];
+}
+
+/** Most used first */
+export async function listSkills(): Promise<Skill[]> {
+  return invoke<Skill[]>('skill_list');
+}
+
+export async function searchSkills(query: string, limit?: number): Promise<SkillMatch[]> {
+  return invoke<SkillMatch[]>('skill_search', { query, limit });
+}
+
+/** Saves the run's successful steps */
+export async function saveSkillFromRun(request: SaveSkillFromRunRequest): Promise<Skill> {
+  return invoke<Skill>('skill_save_from_run', { request });
+}
+
+/** Creates the skill, or updates the one with the same id */
+export async function saveSkill(skill: Skill): Promise<Skill> {
+  return invoke<Skill>('skill_save', { skill });
+}
+
+export async function deleteSkill(id: string): Promise<boolean> {
+  return invoke<boolean>('skill_delete', { id });
+}
@@ -0,0 +1,50 @@
+/**
+ * Slack Events API
+ * Socket Mode listeners that surface Slack messages and mentions
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type SlackEventKind = 'message' | 'mention' | 'direct_message';
+
+export interface SlackInboundEvent {
+  connection_id: string;
+  team_id: string | null;
+  kind: SlackEventKind;
+  channel: string;
+  user: string | null;
+  text: string;
+  ts: string;
+  /** Set when the message is a reply in a thread */
+  thread_ts: string | null;
+  event_id: string | null;
+  received_at: number;
+}
+
+export type SlackListenerState = 'connecting' | 'connected' | 'offline';
+
+export interface SlackListenerStatus {
+  connection_id: string;
+  team_id: string | null;
+  state: SlackListenerState;
+  connected_at: number | null;
+  last_event_at: number | null;
+  last_error: string | null;
+}
+
+export async function startSlackEvents(connectionId: string): Promise<void> {
+  return invoke('slack_events_start', { connectionId });
+}
+
+export async function stopSlackEvents(connectionId: string): Promise<boolean> {
+  return invoke<boolean>('slack_events_stop', { connectionId });
+}
+
+export async function getSlackEventsStatus(): Promise<SlackListenerStatus[]> {
+  return invoke<SlackListenerStatus[]>('slack_events_status');
+}
+
+export function onSlackEvent(handler: (event: SlackInboundEvent) => void): Promise<UnlistenFn> {
+  return listen<SlackInboundEvent>('messaging://slack-event', (event) => handler(event.payload));
+}
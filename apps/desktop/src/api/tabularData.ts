@@ -0,0 +1,38 @@
+/**
+ * Tabular Data API
+ * Preview CSV/Parquet exports and import them into SQLite
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  ImportMode,
+  ImportSummary,
+  TabularPreview,
+  TabularReadOptions,
+} from '../types/document';
+
+/** First rows and inferred schema; CSV dialect is sniffed unless set in options */
+export async function readTabular(
+  filePath: string,
+  options?: TabularReadOptions,
+): Promise<TabularPreview> {
+  return invoke<TabularPreview>('document_read_tabular', { filePath, options });
+}
+
+/**
+ * Import a CSV or Parquet file into a SQLite table. Without a connectionId the
+ * table is created in the app's imports.db.
+ */
+export async function importTabularFile(
+  filePath: string,
+  tableName: string,
+  params: { mode?: ImportMode; options?: TabularReadOptions; connectionId?: string } = {},
+): Promise<ImportSummary> {
+  return invoke<ImportSummary>('db_import_file', {
+    filePath,
+    tableName,
+    mode: params.mode,
+    options: params.options,
+    connectionId: params.connectionId,
+  });
+}
@@ -0,0 +1,22 @@
+/**
+ * Tool Costs API
+ * Prices for tool calls, charged per call, per second and per metered unit
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface ToolPricing {
+  per_call: number;
+  per_second: number;
+  /** Price per unit a tool reports, e.g. `{ pages: 0.0015 }` */
+  per_unit: Record<string, number>;
+}
+
+export async function getToolPricing(): Promise<Record<string, ToolPricing>> {
+  return invoke<Record<string, ToolPricing>>('chat_get_tool_pricing');
+}
+
+/** Pass `null` to stop charging for a tool */
+export async function setToolPricing(toolId: string, pricing: ToolPricing | null): Promise<void> {
+  return invoke('chat_set_tool_pricing', { toolId, pricing });
+}
@@ -0,0 +1,22 @@
+/**
+ * Unified Tasks API
+ * Search and board views across Notion, Trello and Asana
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { UnifiedBoard, UnifiedTaskFilter, UnifiedTaskSearch } from '../types/productivity';
+
+/** Served from the local cache; pass `refresh` to force a sync with every provider */
+export async function searchAllTasks(
+  filter?: UnifiedTaskFilter,
+  refresh?: boolean,
+): Promise<UnifiedTaskSearch> {
+  return invoke<UnifiedTaskSearch>('productivity_search_all', { filter, refresh });
+}
+
+export async function getUnifiedBoard(
+  filter?: UnifiedTaskFilter,
+  refresh?: boolean,
+): Promise<UnifiedBoard> {
+  return invoke<UnifiedBoard>('productivity_get_unified_board', { filter, refresh });
+}
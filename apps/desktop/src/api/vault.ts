@@ -0,0 +1,56 @@
+/**
+ * Vault API
+ * API keys, integration credentials and account tokens, without their values
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type SecretNamespace = 'provider' | 'integration' | 'account';
+
+/** e.g. `{ namespace: 'provider', name: 'openai' }` */
+export interface SecretId {
+  namespace: SecretNamespace;
+  name: string;
+}
+
+export interface SecretInfo {
+  id: SecretId;
+  /** `database` when the OS keyring would not take the secret */
+  storage: 'keyring' | 'database';
+  /** Incremented on every write */
+  version: number;
+  createdAt: string;
+  updatedAt: string;
+  rotatedAt: string | null;
+  lastAccessedAt: string | null;
+}
+
+export interface VaultAccess {
+  id: number;
+  secret: SecretId;
+  action: 'read' | 'write' | 'rotate' | 'delete';
+  /** What asked for the secret, e.g. `settings` or `mcp` */
+  actor: string;
+  success: boolean;
+  error: string | null;
+  createdAt: string;
+}
+
+export async function listSecrets(namespace?: SecretNamespace): Promise<SecretInfo[]> {
+  return invoke<SecretInfo[]>('vault_list_secrets', { namespace: namespace ?? null });
+}
+
+/** Resolves to the new version */
+export async function rotateSecret(id: SecretId, value: string): Promise<number> {
+  return invoke<number>('vault_rotate_secret', { id, value });
+}
+
+/** Resolves to whether the secret existed */
+export async function deleteSecret(id: SecretId): Promise<boolean> {
+  return invoke<boolean>('vault_delete_secret', { id });
+}
+
+/** Newest first */
+export async function getVaultAccessLog(id?: SecretId, limit?: number): Promise<VaultAccess[]> {
+  return invoke<VaultAccess[]>('vault_access_log', { id: id ?? null, limit: limit ?? null });
+}
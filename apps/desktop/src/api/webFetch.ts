@@ -0,0 +1,44 @@
+/**
+ * Web Fetch API
+ * Fetch a web page as readable Markdown, optionally indexing it into a project collection
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { CollectionSource } from './projectCollections';
+
+export interface FetchOptions {
+  /** Pages with a larger body are refused; at most 10 MB */
+  max_bytes?: number;
+  timeout_secs?: number;
+  respect_robots?: boolean;
+  /** Allow localhost and private network addresses */
+  allow_private_network?: boolean;
+}
+
+export interface WebPage {
+  /** The URL that was asked for */
+  url: string;
+  /** Where redirects ended up; cite this one */
+  final_url: string;
+  title: string;
+  byline: string | null;
+  excerpt: string | null;
+  site_name: string | null;
+  markdown: string;
+  word_count: number;
+  fetched_at: string;
+}
+
+export interface WebFetchResult {
+  page: WebPage;
+  /** Set when the page was indexed into a collection */
+  source: CollectionSource | null;
+}
+
+export async function fetchWebPage(
+  url: string,
+  options?: FetchOptions,
+  collectionId?: string,
+): Promise<WebFetchResult> {
+  return invoke<WebFetchResult>('web_fetch', { url, options, collectionId });
+}
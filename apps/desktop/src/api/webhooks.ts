@@ -0,0 +1,134 @@
+/**
+ * Webhooks API
+ * Signed inbound webhooks that let GitHub, Stripe, Zapier and other services
+ * run a workflow or queue a background task. Endpoints are webhook workflow
+ * triggers: each listens at `POST /hooks/{path_token}` on the configured port
+ * and checks the body's HMAC-SHA256 signature.
+ */
+
+import { invoke } from '../lib/authInvoke';
+
+/**
+ * - `generic`: `X-Signature-Timestamp: <unix>` and `X-Signature: sha256=<hex>`,
+ *   the HMAC-SHA256 of `<timestamp>.<body>`
+ * - `github`: `X-Hub-Signature-256: sha256=<hex>` over the body
+ * - `stripe`: `Stripe-Signature: t=<unix>,v1=<hex>` over `<t>.<body>`
+ *
+ * Timestamps more than five minutes away from now are refused.
+ */
+export type SignatureScheme = 'generic' | 'github' | 'stripe';
+
+export interface WebhookTask {
+  name: string;
+  /** Defaults to 'Normal' */
+  priority?: 'Low' | 'Normal' | 'High' | null;
+}
+
+export type WebhookTarget =
+  | { kind: 'workflow'; workflow_id: string }
+  | ({ kind: 'task' } & WebhookTask);
+
+export interface WebhookEndpoint {
+  /** Id of the endpoint's workflow trigger */
+  id: string;
+  name: string;
+  url: string;
+  /** Last segment of the endpoint's URL */
+  path_token: string;
+  scheme: SignatureScheme;
+  target: WebhookTarget;
+  enabled: boolean;
+  created_at: number;
+  updated_at: number;
+  last_delivery_at: number | null;
+}
+
+export interface WebhookEndpointInput {
+  name: string;
+  scheme?: SignatureScheme;
+  target: WebhookTarget;
+  /** Signing secret the sender uses; generated when unset on create, kept when unset on update */
+  secret?: string | null;
+  /** Custom last URL segment; random when unset on create, kept when unset on update */
+  path_token?: string | null;
+  enabled?: boolean;
+}
+
+export interface WebhookEndpointCreated {
+  endpoint: WebhookEndpoint;
+  /** Only returned once */
+  secret: string;
+}
+
+export type DeliveryStatus = 'accepted' | 'rejected' | 'failed';
+
+/** A request a webhook endpoint received */
+export interface WebhookDelivery {
+  id: string;
+  trigger_id: string;
+  received_at: number;
+  /** Lower-cased request headers, without credentials */
+  headers: Record<string, string>;
+  body: string;
+  verified: boolean;
+  status: DeliveryStatus;
+  error: string | null;
+  /** Workflow execution or background task the delivery started */
+  execution_id: string | null;
+  /** Delivery this one replayed */
+  replay_of: string | null;
+}
+
+export interface WebhookConfig {
+  port: number;
+  /** Listen on every network interface instead of only 127.0.0.1 */
+  allow_remote?: boolean;
+}
+
+export interface WebhookStatus {
+  config: WebhookConfig;
+  /** `host:port` the listener is on; it only listens while an endpoint is enabled */
+  address: string | null;
+  endpoints: WebhookEndpoint[];
+}
+
+export async function getWebhookStatus(): Promise<WebhookStatus> {
+  return invoke<WebhookStatus>('webhook_status');
+}
+
+export async function configureWebhooks(config: WebhookConfig): Promise<WebhookStatus> {
+  return invoke<WebhookStatus>('webhook_configure', { config });
+}
+
+export async function createWebhookEndpoint(
+  input: WebhookEndpointInput,
+): Promise<WebhookEndpointCreated> {
+  return invoke<WebhookEndpointCreated>('webhook_create_endpoint', { input });
+}
+
+export async function updateWebhookEndpoint(
+  id: string,
+  input: WebhookEndpointInput,
+): Promise<WebhookEndpoint> {
+  return invoke<WebhookEndpoint>('webhook_update_endpoint', { id, input });
+}
+
+export async function deleteWebhookEndpoint(id: string): Promise<boolean> {
+  return invoke<boolean>('webhook_delete_endpoint', { id });
+}
+
+/** Newest first, of one endpoint or all of them */
+export async function listWebhookDeliveries(
+  endpointId?: string,
+  limit?: number,
+): Promise<WebhookDelivery[]> {
+  return invoke<WebhookDelivery[]>('webhook_list_deliveries', {
+    endpointId: endpointId ?? null,
+    limit: limit ?? null,
+  });
+}
+
+/** Only deliveries with a valid signature to an enabled endpoint can be replayed */
+export async function replayWebhookDelivery(deliveryId: string): Promise<WebhookDelivery> {
+  return invoke<WebhookDelivery>('webhook_replay_delivery', { deliveryId });
+}
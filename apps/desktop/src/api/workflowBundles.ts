@@ -0,0 +1,69 @@
+/**
+ * Workflow Bundles API
+ * Export a workflow with its sub-workflows, agent templates, required
+ * integrations and credential placeholders as one signed JSON or YAML file,
+ * and import such files with a report of what this install is missing.
+ */
+
+import { invoke } from '../lib/authInvoke';
+import type { MissingRequirement } from '../types/templates';
+
+export type BundleEncoding = 'json' | 'yaml';
+
+/** `unknown_signer` means the bundle was exported by another install */
+export type SignatureStatus = 'verified' | 'unsigned' | 'unknown_signer';
+
+export interface CredentialPlaceholder {
+  /** Appears in the setting as `{{credential:<name>}}` */
+  name: string;
+  workflow_id: string;
+  node_id: string;
+  field: string;
+}
+
+export interface IntegrationAccount {
+  id: string;
+  label: string;
+}
+
+export interface IntegrationMapping {
+  integration: string;
+  account_id: string | null;
+  accounts: IntegrationAccount[];
+}
+
+export interface BundleImportOptions {
+  /** Local account id per integration; one connected account is used by default */
+  accounts?: Record<string, string>;
+  /** Secret per credential placeholder name */
+  credentials?: Record<string, string>;
+  /** Only validate the bundle and report what is missing */
+  check_only?: boolean;
+}
+
+export interface BundleImportReport {
+  /** Null when only checking */
+  workflow_id: string | null;
+  name: string;
+  signature: SignatureStatus;
+  integrations: IntegrationMapping[];
+  missing: MissingRequirement[];
+  missing_credentials: CredentialPlaceholder[];
+  templates_added: string[];
+}
+
+export async function exportWorkflowBundle(
+  workflowId: string,
+  encoding: BundleEncoding = 'json',
+): Promise<string> {
+  return invoke<string>('workflow_export_bundle', { workflowId, encoding });
+}
+
+/** Rejects bundles that fail validation or were changed after this install signed them */
+export async function importWorkflowBundle(
+  contents: string,
+  userId: string,
+  options?: BundleImportOptions,
+): Promise<BundleImportReport> {
+  return invoke<BundleImportReport>('workflow_import_bundle', { contents, userId, options });
+}
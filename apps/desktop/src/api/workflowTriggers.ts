@@ -0,0 +1,116 @@
+/**
+ * Workflow Triggers API
+ * Sources that start a workflow on their own: a watched folder, new mail
+ * matching conditions, a signed POST to the local webhook listener, copied
+ * text matching a pattern, or a global hotkey. Each trigger debounces bursts
+ * of events.
+ *
+ * Webhook requests are signed with the trigger's `webhook_secret`; see
+ * `./webhooks` for the listener, signing schemes and deliveries.
+ */
+
+import { invoke } from '../lib/authInvoke';
+import type { SignatureScheme, WebhookTask } from './webhooks';
+
+export type FileChangeKind = 'created' | 'modified' | 'deleted';
+
+export interface EmailTriggerConditions {
+  /** Case-insensitive substring of the sender's address or name */
+  from?: string | null;
+  /** Case-insensitive regex over the subject */
+  subject_regex?: string | null;
+  has_attachment?: boolean | null;
+}
+
+export type TriggerSource =
+  | {
+      type: 'file_change';
+      path: string;
+      recursive?: boolean;
+      /** Glob over file names, e.g. "*.pdf" */
+      pattern?: string | null;
+      /** Defaults to created and modified */
+      events?: FileChangeKind[];
+    }
+  | { type: 'email'; account_id?: number | null; conditions: EmailTriggerConditions }
+  /**
+   * Defaults to the generic scheme and a random path token; with a `task` a
+   * delivery queues that background task instead of running the workflow
+   */
+  | {
+      type: 'webhook';
+      scheme?: SignatureScheme;
+      path_token?: string | null;
+      task?: WebhookTask | null;
+    }
+  /** Regex over copied text; its groups are passed to the workflow */
+  | { type: 'clipboard'; pattern: string }
+  | { type: 'hotkey'; key: string };
+
+export interface WorkflowTriggerInput {
+  /** May be empty for a webhook trigger that queues a task */
+  workflow_id: string;
+  name: string;
+  source: TriggerSource;
+  enabled?: boolean;
+  /** Defaults to 1000 for folders, 500 for the clipboard and 0 otherwise */
+  debounce_ms?: number | null;
+  /**
+   * Signing secret of a webhook trigger, such as Stripe's `whsec_...`; the
+   * current one is kept, or one generated, when unset
+   */
+  secret?: string | null;
+}
+
+export interface RegisteredTrigger {
+  id: string;
+  workflow_id: string;
+  name: string;
+  source: TriggerSource;
+  enabled: boolean;
+  debounce_ms: number;
+  fire_count: number;
+  last_fired_at: number | null;
+  created_at: number;
+  updated_at: number;
+  /** URL to POST to for a webhook trigger, from this machine */
+  webhook_url: string | null;
+  /** Key to sign a webhook trigger's requests with */
+  webhook_secret: string | null;
+}
+
+/** Payload of the `workflow://trigger-fired` event */
+export interface TriggerFiredEvent {
+  trigger_id: string;
+  workflow_id: string;
+  execution_id: string;
+}
+
+export async function listWorkflowTriggers(workflowId?: string): Promise<RegisteredTrigger[]> {
+  return invoke<RegisteredTrigger[]>('workflow_trigger_list', { workflowId });
+}
+
+export async function createWorkflowTrigger(
+  trigger: WorkflowTriggerInput,
+): Promise<RegisteredTrigger> {
+  return invoke<RegisteredTrigger>('workflow_trigger_create', { trigger });
+}
+
+/** A webhook trigger keeps its path token and secret unless given new ones */
+export async function updateWorkflowTrigger(
+  id: string,
+  trigger: WorkflowTriggerInput,
+): Promise<RegisteredTrigger> {
+  return invoke<RegisteredTrigger>('workflow_trigger_update', { id, trigger });
+}
+
+export async function setWorkflowTriggerEnabled(
+  id: string,
+  enabled: boolean,
+): Promise<RegisteredTrigger> {
+  return invoke<RegisteredTrigger>('workflow_trigger_set_enabled', { id, enabled });
+}
+
+export async function deleteWorkflowTrigger(id: string): Promise<boolean> {
+  return invoke<boolean>('workflow_trigger_delete', { id });
+}
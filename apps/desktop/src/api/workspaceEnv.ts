@@ -0,0 +1,49 @@
+/**
+ * Workspace Env API
+ * Read and edit a workspace's .env files; secret values live in the secret store
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type EnvFileName = '.env' | '.env.local';
+
+export type EnvValueSource = 'file' | 'secret_store';
+
+export interface EnvVarInfo {
+  key: string;
+  /** Masked for secrets unless revealed; null when a referenced secret is missing */
+  value: string | null;
+  secret: boolean;
+  masked: boolean;
+  source: EnvValueSource;
+  file: EnvFileName;
+}
+
+export interface EnvSetOptions {
+  /** Overrides automatic secret detection */
+  secret?: boolean;
+  /** Defaults to the file currently defining the key, or .env */
+  file?: EnvFileName;
+}
+
+export async function listWorkspaceEnv(workspacePath: string): Promise<EnvVarInfo[]> {
+  return invoke<EnvVarInfo[]>('workspace_env_list', { workspacePath });
+}
+
+export async function getWorkspaceEnv(
+  workspacePath: string,
+  key: string,
+  reveal = false,
+): Promise<EnvVarInfo | null> {
+  return invoke<EnvVarInfo | null>('workspace_env_get', { workspacePath, key, reveal });
+}
+
+/** Set a variable, or remove it from every env file when `value` is null */
+export async function setWorkspaceEnv(
+  workspacePath: string,
+  key: string,
+  value: string | null,
+  options?: EnvSetOptions,
+): Promise<EnvVarInfo | null> {
+  return invoke<EnvVarInfo | null>('workspace_env_set', { workspacePath, key, value, options });
+}
@@ -11,12 +11,13 @@ import {
   Upload,
 } from 'lucide-react';
 import { Button } from '../ui/Button';
+import { Checkbox } from '../ui/Checkbox';
 import { Input } from '../ui/Input';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '../ui/Select';
 import { ScrollArea } from '../ui/ScrollArea';
 import { Separator } from '../ui/Separator';
 import { useCloudStore } from '../../stores/cloudStore';
-import type { CloudProvider } from '../../types/cloud';
+import type { CloudProvider, WebDavAuth } from '../../types/cloud';
 import { formatBytes } from '../../lib/utils';
 import { cn } from '../../lib/utils';
 import { useConfirm } from '../ui/ConfirmDialog';
@@ -27,12 +28,14 @@ const PROVIDER_LABELS: Record<CloudProvider, string> = {
   google_drive: 'Google Drive',
   dropbox: 'Dropbox',
   one_drive: 'OneDrive',
+  webdav: 'WebDAV',
 };
 
 const PROVIDER_OPTIONS: { value: CloudProvider; label: string }[] = [
   { value: 'google_drive', label: 'Google Drive' },
   { value: 'dropbox', label: 'Dropbox' },
   { value: 'one_drive', label: 'OneDrive' },
+  { value: 'webdav', label: 'WebDAV (Nextcloud, ownCloud, Synology)' },
 ];
 
 export function CloudStoragePanel() {
@@ -50,6 +53,7 @@ export function CloudStoragePanel() {
     listFiles,
     beginConnect,
     completeConnect,
+    connectWebDav,
     uploadFile,
     downloadFile,
     deleteEntry,
@@ -62,6 +66,11 @@ export function CloudStoragePanel() {
   const [clientId, setClientId] = useState('');
   const [clientSecret, setClientSecret] = useState('');
   const [redirectUri, setRedirectUri] = useState('');
+  const [webdavUrl, setWebdavUrl] = useState('');
+  const [webdavUsername, setWebdavUsername] = useState('');
+  const [webdavPassword, setWebdavPassword] = useState('');
+  const [webdavAuth, setWebdavAuth] = useState<WebDavAuth>('basic');
+  const [acceptInvalidCerts, setAcceptInvalidCerts] = useState(false);
   const [oauthState, setOauthState] = useState('');
   const [oauthCode, setOauthCode] = useState('');
   const [searchTerm, setSearchTerm] = useState('');
@@ -109,6 +118,22 @@ export function CloudStoragePanel() {
   };
 
   const handleConnect = async () => {
+    if (provider === 'webdav') {
+      if (!webdavUrl.trim() || !webdavUsername.trim()) {
+        toast.error('Server URL and username are required.');
+        return;
+      }
+      await connectWebDav({
+        url: webdavUrl.trim(),
+        username: webdavUsername.trim(),
+        password: webdavPassword,
+        auth: webdavAuth,
+        acceptInvalidCerts,
+      });
+      setWebdavPassword('');
+      return;
+    }
+
     await beginConnect(provider, {
       clientId,
       clientSecret,
@@ -255,33 +280,90 @@ export function CloudStoragePanel() {
           </Select>
         </div>
 
-        <div className="flex flex-1 flex-col gap-2">
-          <label className="text-xs font-medium text-muted-foreground">Client ID</label>
-          <Input
-            value={clientId}
-            onChange={(event) => setClientId(event.target.value)}
-            placeholder="OAuth client ID"
-          />
-        </div>
+        {provider === 'webdav' ? (
+          <>
+            <div className="flex flex-1 flex-col gap-2 min-w-[260px]">
+              <label className="text-xs font-medium text-muted-foreground">Server URL</label>
+              <Input
+                value={webdavUrl}
+                onChange={(event) => setWebdavUrl(event.target.value)}
+                placeholder="https://cloud.example.com/remote.php/dav/files/me/"
+              />
+            </div>
 
-        <div className="flex flex-1 flex-col gap-2">
-          <label className="text-xs font-medium text-muted-foreground">Client Secret</label>
-          <Input
-            value={clientSecret}
-            type="password"
-            onChange={(event) => setClientSecret(event.target.value)}
-            placeholder="OAuth client secret"
-          />
-        </div>
+            <div className="flex flex-1 flex-col gap-2">
+              <label className="text-xs font-medium text-muted-foreground">Username</label>
+              <Input
+                value={webdavUsername}
+                onChange={(event) => setWebdavUsername(event.target.value)}
+              />
+            </div>
 
-        <div className="flex flex-1 flex-col gap-2 min-w-[220px]">
-          <label className="text-xs font-medium text-muted-foreground">Redirect URI</label>
-          <Input
-            value={redirectUri}
-            onChange={(event) => setRedirectUri(event.target.value)}
-            placeholder="http://localhost:3000/oauth/callback"
-          />
-        </div>
+            <div className="flex flex-1 flex-col gap-2">
+              <label className="text-xs font-medium text-muted-foreground">Password</label>
+              <Input
+                value={webdavPassword}
+                type="password"
+                onChange={(event) => setWebdavPassword(event.target.value)}
+                placeholder="Password or app password"
+              />
+            </div>
+
+            <div className="flex flex-col gap-2">
+              <label className="text-xs font-medium text-muted-foreground">Authentication</label>
+              <Select
+                value={webdavAuth}
+                onValueChange={(value: WebDavAuth) => setWebdavAuth(value)}
+              >
+                <SelectTrigger className="w-28">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="basic">Basic</SelectItem>
+                  <SelectItem value="digest">Digest</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+
+            <label className="flex items-center gap-2 pb-2 text-xs text-muted-foreground">
+              <Checkbox
+                checked={acceptInvalidCerts}
+                onCheckedChange={(checked) => setAcceptInvalidCerts(checked === true)}
+              />
+              Trust self-signed certificate
+            </label>
+          </>
+        ) : (
+          <>
+            <div className="flex flex-1 flex-col gap-2">
+              <label className="text-xs font-medium text-muted-foreground">Client ID</label>
+              <Input
+                value={clientId}
+                onChange={(event) => setClientId(event.target.value)}
+                placeholder="OAuth client ID"
+              />
+            </div>
+
+            <div className="flex flex-1 flex-col gap-2">
+              <label className="text-xs font-medium text-muted-foreground">Client Secret</label>
+              <Input
+                value={clientSecret}
+                type="password"
+                onChange={(event) => setClientSecret(event.target.value)}
+                placeholder="OAuth client secret"
+              />
+            </div>
+
+            <div className="flex flex-1 flex-col gap-2 min-w-[220px]">
+              <label className="text-xs font-medium text-muted-foreground">Redirect URI</label>
+              <Input
+                value={redirectUri}
+                onChange={(event) => setRedirectUri(event.target.value)}
+                placeholder="http://localhost:3000/oauth/callback"
+              />
+            </div>
+          </>
+        )}
 
         <div className="flex items-center gap-2">
           <Button onClick={handleConnect} disabled={loading}>
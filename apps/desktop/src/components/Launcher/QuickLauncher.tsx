@@ -0,0 +1,134 @@
+import { useCallback, useEffect, useRef, useState } from 'react';
+import { invoke } from '../../lib/tauri-mock';
+
+type PaletteKind = 'command' | 'employee' | 'workflow' | 'conversation';
+
+interface PaletteResult {
+  kind: PaletteKind;
+  id: string;
+  title: string;
+  subtitle: string | null;
+  score: number;
+  matches: number[];
+}
+
+const KIND_LABELS: Record<PaletteKind, string> = {
+  command: 'Command',
+  employee: 'AI Employee',
+  workflow: 'Workflow',
+  conversation: 'Conversation',
+};
+
+const Highlighted = ({ text, matches }: { text: string; matches: number[] }) => {
+  const marked = new Set(matches);
+  return (
+    <>
+      {Array.from(text).map((char, index) =>
+        marked.has(index) ? (
+          <mark key={index} className="bg-transparent font-semibold text-primary">
+            {char}
+          </mark>
+        ) : (
+          <span key={index}>{char}</span>
+        ),
+      )}
+    </>
+  );
+};
+
+export const QuickLauncher = () => {
+  const [query, setQuery] = useState('');
+  const [results, setResults] = useState<PaletteResult[]>([]);
+  const [selected, setSelected] = useState(0);
+  const inputRef = useRef<HTMLInputElement>(null);
+  const requestRef = useRef(0);
+
+  useEffect(() => {
+    const request = ++requestRef.current;
+    invoke<PaletteResult[]>('palette_query', { query, limit: 12 })
+      .then((next) => {
+        // Drop responses that arrive after a newer keystroke
+        if (request === requestRef.current) {
+          setResults(next);
+          setSelected(0);
+        }
+      })
+      .catch((error) => console.error('Palette query failed', error));
+  }, [query]);
+
+  useEffect(() => {
+    const focus = () => inputRef.current?.focus();
+    focus();
+    window.addEventListener('focus', focus);
+    return () => window.removeEventListener('focus', focus);
+  }, []);
+
+  const open = useCallback(async (result: PaletteResult | undefined) => {
+    if (!result) return;
+    try {
+      await invoke('palette_open', { kind: result.kind, id: result.id });
+      setQuery('');
+    } catch (error) {
+      console.error('Failed to open palette item', error);
+    }
+  }, []);
+
+  const onKeyDown = (event: React.KeyboardEvent<HTMLInputElement>) => {
+    if (event.key === 'ArrowDown') {
+      event.preventDefault();
+      setSelected((index) => Math.min(index + 1, results.length - 1));
+    } else if (event.key === 'ArrowUp') {
+      event.preventDefault();
+      setSelected((index) => Math.max(index - 1, 0));
+    } else if (event.key === 'Enter') {
+      event.preventDefault();
+      void open(results[selected]);
+    } else if (event.key === 'Escape') {
+      event.preventDefault();
+      setQuery('');
+      void invoke('launcher_hide');
+    }
+  };
+
+  return (
+    <div className="flex h-screen w-screen flex-col overflow-hidden rounded-xl border border-border bg-background/95 shadow-2xl">
+      <input
+        ref={inputRef}
+        value={query}
+        onChange={(event) => setQuery(event.target.value)}
+        onKeyDown={onKeyDown}
+        placeholder="Search commands, employees, workflows, conversations…"
+        className="w-full border-b border-border bg-transparent px-4 py-3 text-base outline-none"
+        autoFocus
+      />
+      <ul className="flex-1 overflow-y-auto py-1" role="listbox">
+        {results.map((result, index) => (
+          <li
+            key={`${result.kind}:${result.id}`}
+            role="option"
+            aria-selected={index === selected}
+            onMouseEnter={() => setSelected(index)}
+            onClick={() => void open(result)}
+            className={`flex cursor-pointer items-center justify-between px-4 py-2 text-sm ${
+              index === selected ? 'bg-accent text-accent-foreground' : ''
+            }`}
+          >
+            <div className="min-w-0">
+              <div className="truncate">
+                <Highlighted text={result.title} matches={result.matches} />
+              </div>
+              {result.subtitle && (
+                <div className="truncate text-xs text-muted-foreground">{result.subtitle}</div>
+              )}
+            </div>
+            <span className="ml-3 shrink-0 text-xs text-muted-foreground">
+              {KIND_LABELS[result.kind]}
+            </span>
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+};
+
+export default QuickLauncher;
@@ -55,6 +55,10 @@ const describeWorkflowNode = (node: WorkflowNode): string => {
       return `Run ${node.data.language} script`;
     case 'tool':
       return `Tool: ${node.data.tool_name || 'custom invocation'}`;
+    case 'try':
+      return 'Try with error handler';
+    case 'sub_workflow':
+      return `Sub-workflow: ${node.data.label}`;
     default:
       return 'Workflow node';
   }
@@ -55,13 +55,17 @@ const extractNotionTitle = (page: any): string | null => {
 
 export function ProductivityWorkspace({ className }: ProductivityWorkspaceProps) {
   const {
+    accounts,
     connectedProviders,
     selectedProvider,
+    selectedAccountId,
     tasks,
     loading,
     error,
+    loadAccounts,
     connect,
     selectProvider,
+    selectAccount,
     refreshTasks,
     createTask,
     // Notion actions
@@ -87,6 +91,7 @@ export function ProductivityWorkspace({ className }: ProductivityWorkspaceProps)
     token: '',
     apiKey: '',
     workspaceId: '',
+    label: '',
   });
 
   const [createTaskOpen, setCreateTaskOpen] = useState(false);
@@ -105,6 +110,10 @@ export function ProductivityWorkspace({ className }: ProductivityWorkspaceProps)
     return PROVIDER_OPTIONS.find((p) => p.value === selectedProvider)?.label ?? null;
   }, [selectedProvider]);
 
+  useEffect(() => {
+    void loadAccounts();
+  }, [loadAccounts]);
+
   useEffect(() => {
     if (error) {
       console.error('[productivity]', error);
@@ -145,9 +154,9 @@ export function ProductivityWorkspace({ className }: ProductivityWorkspaceProps)
               }
             : { token: credentialsForm.token };
 
-      await connect(connectProvider, credentials);
+      await connect(connectProvider, credentials, credentialsForm.label.trim() || undefined);
       setConnectOpen(false);
-      setCredentialsForm({ token: '', apiKey: '', workspaceId: '' });
+      setCredentialsForm({ token: '', apiKey: '', workspaceId: '', label: '' });
     } catch {
       // Handled in store
     }
@@ -368,6 +377,19 @@ export function ProductivityWorkspace({ className }: ProductivityWorkspaceProps)
                     placeholder={`${connectProvider === 'notion' ? 'Notion' : connectProvider === 'trello' ? 'Trello' : 'Asana'} token`}
                   />
                 </div>
+
+                <div>
+                  <label className="block text-xs font-medium text-muted-foreground">
+                    Account Label
+                  </label>
+                  <Input
+                    value={credentialsForm.label}
+                    onChange={(e) =>
+                      setCredentialsForm((prev) => ({ ...prev, label: e.target.value }))
+                    }
+                    placeholder="e.g. Work or Personal (optional)"
+                  />
+                </div>
               </div>
               <DialogFooter>
                 <Button variant="outline" onClick={() => setConnectOpen(false)}>
@@ -387,26 +409,45 @@ export function ProductivityWorkspace({ className }: ProductivityWorkspaceProps)
             {PROVIDER_OPTIONS.map((provider) => {
               const isConnected = connectedProviders.has(provider.value);
               const isSelected = selectedProvider === provider.value;
+              const providerAccounts = accounts.filter(
+                (account) => account.provider === provider.value,
+              );
 
               return (
-                <button
-                  key={provider.value}
-                  onClick={() => isConnected && selectProvider(provider.value)}
-                  disabled={!isConnected}
-                  className={cn(
-                    'flex w-full items-center justify-between gap-2 rounded-md px-3 py-2 text-sm transition-colors',
-                    isSelected
-                      ? 'bg-primary/10 text-primary font-medium'
-                      : isConnected
-                        ? 'hover:bg-accent text-foreground'
-                        : 'text-muted-foreground opacity-50 cursor-not-allowed',
-                  )}
-                >
-                  <span>{provider.label}</span>
-                  {isConnected && (
-                    <span className="h-2 w-2 rounded-full bg-green-500" title="Connected" />
-                  )}
-                </button>
+                <div key={provider.value}>
+                  <button
+                    onClick={() => isConnected && selectProvider(provider.value)}
+                    disabled={!isConnected}
+                    className={cn(
+                      'flex w-full items-center justify-between gap-2 rounded-md px-3 py-2 text-sm transition-colors',
+                      isSelected
+                        ? 'bg-primary/10 text-primary font-medium'
+                        : isConnected
+                          ? 'hover:bg-accent text-foreground'
+                          : 'text-muted-foreground opacity-50 cursor-not-allowed',
+                    )}
+                  >
+                    <span>{provider.label}</span>
+                    {isConnected && (
+                      <span className="h-2 w-2 rounded-full bg-green-500" title="Connected" />
+                    )}
+                  </button>
+                  {providerAccounts.length > 1 &&
+                    providerAccounts.map((account) => (
+                      <button
+                        key={account.account_id}
+                        onClick={() => selectAccount(account.account_id)}
+                        className={cn(
+                          'ml-3 flex w-[calc(100%-0.75rem)] items-center rounded-md px-3 py-1 text-xs transition-colors',
+                          selectedAccountId === account.account_id
+                            ? 'bg-primary/10 text-primary'
+                            : 'text-muted-foreground hover:bg-accent',
+                        )}
+                      >
+                        {account.label ?? account.remote_id ?? account.account_id.slice(0, 8)}
+                      </button>
+                    ))}
+                </div>
               );
             })}
           </div>
@@ -0,0 +1,106 @@
+/**
+ * Password prompt for re-authentication, shown when the backend needs the
+ * user to confirm their identity and Windows Hello is unavailable
+ */
+
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { useEffect, useState } from 'react';
+import {
+  PASSWORD_REQUIRED_EVENT,
+  submitReauthPassword,
+  type PasswordPrompt,
+} from '../../api/reauth';
+import { isTauri } from '../../lib/tauri-mock';
+import { Button } from '../ui/Button';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '../ui/Dialog';
+import { Input } from '../ui/Input';
+import { Label } from '../ui/Label';
+
+const PASSWORD_LABELS: Record<PasswordPrompt['kind'], string> = {
+  account: 'Account password',
+  local: 'Re-authentication password',
+  create_local: 'Choose a re-authentication password (at least 8 characters)',
+};
+
+export function ReauthPrompt() {
+  const [prompt, setPrompt] = useState<PasswordPrompt | null>(null);
+  const [password, setPassword] = useState('');
+
+  useEffect(() => {
+    if (!isTauri) return;
+    let active = true;
+    let unlisten: UnlistenFn | undefined;
+
+    listen<PasswordPrompt>(PASSWORD_REQUIRED_EVENT, (event) => {
+      if (active) {
+        setPassword('');
+        setPrompt(event.payload);
+      }
+    }).then((fn) => {
+      unlisten = fn;
+    });
+
+    return () => {
+      active = false;
+      unlisten?.();
+    };
+  }, []);
+
+  const answer = (value: string | null) => {
+    if (!prompt) return;
+    submitReauthPassword(prompt.requestId, value).catch((error) =>
+      console.error('Failed to answer re-authentication prompt:', error),
+    );
+    setPrompt(null);
+    setPassword('');
+  };
+
+  return (
+    <Dialog
+      open={prompt !== null}
+      onOpenChange={(open) => {
+        if (!open) answer(null);
+      }}
+    >
+      <DialogContent>
+        <form
+          onSubmit={(e) => {
+            e.preventDefault();
+            if (password) answer(password);
+          }}
+        >
+          <DialogHeader>
+            <DialogTitle>Confirm it's you</DialogTitle>
+            <DialogDescription>{prompt?.message}</DialogDescription>
+          </DialogHeader>
+          <div className="grid gap-2 py-4">
+            <Label htmlFor="reauth-password">{prompt && PASSWORD_LABELS[prompt.kind]}</Label>
+            <Input
+              id="reauth-password"
+              type="password"
+              autoFocus
+              autoComplete={prompt?.kind === 'create_local' ? 'new-password' : 'current-password'}
+              value={password}
+              onChange={(e) => setPassword(e.target.value)}
+            />
+          </div>
+          <DialogFooter>
+            <Button type="button" variant="outline" onClick={() => answer(null)}>
+              Cancel
+            </Button>
+            <Button type="submit" disabled={!password}>
+              Confirm
+            </Button>
+          </DialogFooter>
+        </form>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -47,7 +47,7 @@ interface APIKeyFieldProps {
 }
 
 function APIKeyField({ provider, label, placeholder }: APIKeyFieldProps) {
-  const { apiKeys, setAPIKey, testAPIKey, loading } = useSettingsStore();
+  const { apiKeys, setAPIKey, revealAPIKey, testAPIKey, loading } = useSettingsStore();
   const [showKey, setShowKey] = useState(false);
   const [localKey, setLocalKey] = useState('');
   const [testing, setTesting] = useState(false);
@@ -57,6 +57,20 @@ function APIKeyField({ provider, label, placeholder }: APIKeyFieldProps) {
     setLocalKey(apiKeys[provider as keyof typeof apiKeys] || '');
   }, [apiKeys, provider]);
 
+  const handleToggleShow = async () => {
+    const savedKey = apiKeys[provider as keyof typeof apiKeys];
+    // Showing a saved key needs Windows Hello or a password
+    if (!showKey && savedKey && localKey === savedKey) {
+      try {
+        setLocalKey(await revealAPIKey(provider));
+      } catch (error) {
+        useSettingsStore.setState({ error: String(error) });
+        return;
+      }
+    }
+    setShowKey(!showKey);
+  };
+
   const handleSave = async () => {
     if (!localKey.trim()) return;
     try {
@@ -116,7 +130,7 @@ function APIKeyField({ provider, label, placeholder }: APIKeyFieldProps) {
           />
           <button
             type="button"
-            onClick={() => setShowKey(!showKey)}
+            onClick={handleToggleShow}
             className="absolute right-3 top-1/2 -translate-y-1/2 text-muted-foreground hover:text-foreground"
           >
             {showKey ? <EyeOff className="h-4 w-4" /> : <Eye className="h-4 w-4" />}
@@ -128,6 +128,10 @@ export const OrchestrationCanvas: React.FC<OrchestrationCanvasProps> = ({
                 return '#ef4444';
               case 'tool':
                 return '#6366f1';
+              case 'try':
+                return '#f43f5e';
+              case 'sub_workflow':
+                return '#14b8a6';
               default:
                 return '#9ca3af';
             }
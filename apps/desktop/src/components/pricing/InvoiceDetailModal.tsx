@@ -57,7 +57,7 @@ AGI Workforce Team`;
 
       // Use Tauri to open default email client with pre-filled invoice
       if (typeof window !== 'undefined' && 'tauri' in window) {
-        const { invoke } = await import('@tauri-apps/api/core');
+        const { invoke } = await import('@/lib/authInvoke');
         await invoke('send_invoice_email', {
           invoiceId: selectedInvoice.id,
           recipientEmail: customerEmail,
@@ -55,7 +55,7 @@ export function PaymentMethodsTab() {
 
     setLoading(true);
     try {
-      const { invoke } = await import('@tauri-apps/api/core');
+      const { invoke } = await import('@/lib/authInvoke');
       const methods = await invoke<PaymentMethodInfo[]>('stripe_get_payment_methods', {
         customerStripeId: customer.stripe_customer_id,
       });
@@ -75,7 +75,7 @@ export function PaymentMethodsTab() {
     }
 
     try {
-      const { invoke } = await import('@tauri-apps/api/core');
+      const { invoke } = await import('@/lib/authInvoke');
       const clientSecret = await invoke<string>('stripe_create_setup_intent', {
         customerStripeId: customer.stripe_customer_id,
       });
@@ -109,7 +109,7 @@ export function PaymentMethodsTab() {
     if (!customer) return;
 
     try {
-      const { invoke } = await import('@tauri-apps/api/core');
+      const { invoke } = await import('@/lib/authInvoke');
       await invoke('stripe_set_default_payment_method', {
         customerStripeId: customer.stripe_customer_id,
         paymentMethodId: methodId,
@@ -126,7 +126,7 @@ export function PaymentMethodsTab() {
     if (!methodToDelete) return;
 
     try {
-      const { invoke } = await import('@tauri-apps/api/core');
+      const { invoke } = await import('@/lib/authInvoke');
       await invoke('stripe_delete_payment_method', {
         paymentMethodId: methodToDelete.stripe_payment_method_id,
       });
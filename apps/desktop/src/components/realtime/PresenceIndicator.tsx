@@ -4,6 +4,8 @@ import { UserPresence, websocketClient } from '../../services/websocketClient';
 
 interface PresenceIndicatorProps {
   teamId: string;
+  /** The signed-in user; presence is shown as their team's rules allow */
+  viewerId: string;
 }
 
 const statusColors = {
@@ -20,17 +22,17 @@ const statusLabels = {
   Offline: 'Offline',
 };
 
-export const PresenceIndicator: React.FC<PresenceIndicatorProps> = ({ teamId }) => {
+export const PresenceIndicator: React.FC<PresenceIndicatorProps> = ({ teamId, viewerId }) => {
   const [presence, setPresence] = useState<UserPresence[]>([]);
 
   const loadPresence = useCallback(async () => {
     try {
-      const teamPresence = await invoke<UserPresence[]>('get_team_presence', { teamId });
+      const teamPresence = await invoke<UserPresence[]>('get_team_presence', { teamId, viewerId });
       setPresence(teamPresence);
     } catch (error) {
       console.error('Failed to load team presence:', error);
     }
-  }, [teamId]);
+  }, [teamId, viewerId]);
 
   useEffect(() => {
     loadPresence();
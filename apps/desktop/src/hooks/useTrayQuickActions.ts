@@ -5,29 +5,33 @@ import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 interface TrayQuickActionsOptions {
   onNewConversation: () => void | Promise<void>;
   onOpenSettings: () => void | Promise<void>;
+  onOpenDashboard?: () => void | Promise<void>;
   unreadCount: number;
 }
 
 /**
  * Bridges native tray menu events into React handlers and keeps the tray badge
- * state in sync with the current unread count (placeholder for future updates).
+ * state in sync with the current unread count.
  *
  * Updated Nov 16, 2025: Fixed missing dependencies by using useRef for callbacks
  */
 export function useTrayQuickActions({
   onNewConversation,
   onOpenSettings,
+  onOpenDashboard,
   unreadCount,
 }: TrayQuickActionsOptions) {
   // Store callbacks in refs to avoid recreating event listeners
   const onNewConversationRef = useRef(onNewConversation);
   const onOpenSettingsRef = useRef(onOpenSettings);
+  const onOpenDashboardRef = useRef(onOpenDashboard);
 
   // Keep refs up to date
   useEffect(() => {
     onNewConversationRef.current = onNewConversation;
     onOpenSettingsRef.current = onOpenSettings;
-  }, [onNewConversation, onOpenSettings]);
+    onOpenDashboardRef.current = onOpenDashboard;
+  }, [onNewConversation, onOpenSettings, onOpenDashboard]);
 
   useEffect(() => {
     let isMounted = true;
@@ -50,6 +54,14 @@ export function useTrayQuickActions({
           await onOpenSettingsRef.current();
         });
         cleaners.push(unlistenOpenSettings);
+
+        const unlistenOpenDashboard = await listen('tray://open-dashboard', async () => {
+          if (!isMounted) {
+            return;
+          }
+          await onOpenDashboardRef.current?.();
+        });
+        cleaners.push(unlistenOpenDashboard);
       } catch (error) {
         console.error('[tray] failed to register quick action listeners', error);
       }
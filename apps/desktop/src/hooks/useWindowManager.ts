@@ -1,7 +1,14 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from 'react';
 import { invoke, isTauri, listen } from '../lib/tauri-mock';
 
-export type DockPosition = 'left' | 'right';
+export type DockPosition = 'left' | 'right' | 'top' | 'bottom';
+
+export interface DockOptions {
+  /** Width for left/right docks, height for top/bottom docks */
+  size?: number;
+  /** Monitor id from `window_list_monitors`; defaults to the last one used */
+  monitor?: string;
+}
 
 interface BackendWindowState {
   pinned: boolean;
@@ -36,7 +43,7 @@ export interface WindowActions {
   togglePinned: () => Promise<void>;
   setAlwaysOnTop: (value: boolean) => Promise<void>;
   toggleAlwaysOnTop: () => Promise<void>;
-  dock: (position: DockPosition | null) => Promise<void>;
+  dock: (position: DockPosition | null, options?: DockOptions) => Promise<void>;
   minimize: () => Promise<void>;
   toggleMaximize: () => Promise<void>;
   hide: () => Promise<void>;
@@ -122,9 +129,13 @@ export function useWindowManager(): { state: WindowState; actions: WindowActions
     };
   }, []);
 
-  const dock = useCallback(async (position: DockPosition | null) => {
+  const dock = useCallback(async (position: DockPosition | null, options?: DockOptions) => {
     try {
-      await invoke('window_dock', { position });
+      await invoke('window_dock', {
+        position,
+        size: options?.size ?? null,
+        monitor: options?.monitor ?? null,
+      });
     } catch (error) {
       console.error('Failed to dock window', error);
     }
@@ -0,0 +1,42 @@
+/**
+ * Tauri invoke that sends the signed-in user's access token
+ *
+ * Protected commands read it from the `Authorization: Bearer` invoke header
+ * and reject calls without a valid session with `{ code: 'AUTH_REQUIRED' }`
+ * (or `{ code: 'FORBIDDEN' }` when the role is not enough).
+ */
+
+import { invoke as tauriInvoke, type InvokeArgs } from '@tauri-apps/api/core';
+
+/** Same key AuthService stores the token under */
+const TOKEN_STORAGE_KEY = 'auth_token';
+
+export interface CommandAuthError {
+  code: 'AUTH_REQUIRED' | 'FORBIDDEN';
+  message: string;
+}
+
+function accessToken(): string | null {
+  if (typeof localStorage === 'undefined') {
+    return null;
+  }
+  try {
+    const stored = localStorage.getItem(TOKEN_STORAGE_KEY);
+    return stored ? (JSON.parse(stored).access_token ?? null) : null;
+  } catch {
+    return null;
+  }
+}
+
+export async function invoke<T>(command: string, args?: InvokeArgs): Promise<T> {
+  const token = accessToken();
+  if (!token) {
+    return tauriInvoke<T>(command, args);
+  }
+  return tauriInvoke<T>(command, args, { headers: { Authorization: `Bearer ${token}` } });
+}
+
+export function isCommandAuthError(error: unknown): error is CommandAuthError {
+  const code = (error as { code?: unknown } | null)?.code;
+  return code === 'AUTH_REQUIRED' || code === 'FORBIDDEN';
+}
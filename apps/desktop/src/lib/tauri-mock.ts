@@ -28,7 +28,7 @@ const MOCK_DATA = {
 export async function invoke<T>(command: string, args?: Record<string, unknown>): Promise<T> {
   if (isTauri) {
     // Dynamically import Tauri API only in Tauri context
-    const { invoke: tauriInvoke } = await import('@tauri-apps/api/core');
+    const { invoke: tauriInvoke } = await import('./authInvoke');
     return tauriInvoke<T>(command, args);
   }
 
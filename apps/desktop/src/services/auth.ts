@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { invoke as authInvoke } from '../lib/authInvoke';
 import { useAuthStore } from '../stores/authStore';
 
 export interface AuthToken {
@@ -43,7 +44,8 @@ export class AuthService {
   }
 
   /**
-   * Register a new user
+   * Register a new user. Once an account exists only a signed-in admin can
+   * add another; the first account becomes an admin.
    */
   async register(
     email: string,
@@ -51,7 +53,7 @@ export class AuthService {
     role: UserRole = UserRole.Editor,
   ): Promise<string> {
     try {
-      const userId = await invoke<string>('auth_register', {
+      const userId = await authInvoke<string>('auth_register', {
         email,
         password,
         role,
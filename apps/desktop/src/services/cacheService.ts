@@ -10,6 +10,7 @@ import type {
   CacheSettings,
   CacheAnalytics,
   CacheType,
+  WarmupRun,
 } from '../types/cache';
 
 /**
@@ -58,11 +59,11 @@ export async function configureCache(settings: CacheSettings): Promise<void> {
 }
 
 /**
- * Warm up cache with common queries
- * @param queries - Array of query strings to pre-cache
+ * Warm up cache with common queries and any recurring usage patterns that are due
+ * @param queries - Optional query strings to pre-cache
  */
-export async function warmupCache(queries: string[]): Promise<void> {
-  return invoke('cache_warmup', { queries });
+export async function warmupCache(queries?: string[]): Promise<WarmupRun> {
+  return invoke<WarmupRun>('cache_warmup', { queries });
 }
 
 /**
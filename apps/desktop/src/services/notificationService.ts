@@ -0,0 +1,82 @@
+/**
+ * Notification Service
+ *
+ * Frontend service for native notification preferences and actions.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type NotificationCategory =
+  | 'task_completion'
+  | 'approval_request'
+  | 'budget_warning'
+  | 'email_rule'
+  | 'credential_expiry'
+  | 'integration_health';
+export type NotificationAction = 'approve' | 'reject' | 'open';
+
+export interface NotificationPreferences {
+  taskCompletion: boolean;
+  approvalRequests: boolean;
+  budgetWarnings: boolean;
+  emailRules: boolean;
+  credentialExpiry: boolean;
+  integrationHealth: boolean;
+  showWhenFocused: boolean;
+}
+
+export interface AppNotification {
+  category: NotificationCategory;
+  title: string;
+  body: string;
+  target: string | null;
+  actions: NotificationAction[];
+}
+
+export interface NotificationOpenEvent {
+  category: NotificationCategory;
+  target: string | null;
+}
+
+export async function getNotificationPreferences(): Promise<NotificationPreferences> {
+  return invoke<NotificationPreferences>('notifications_get_preferences');
+}
+
+export async function setNotificationPreferences(
+  preferences: NotificationPreferences,
+): Promise<void> {
+  return invoke('notifications_set_preferences', { preferences });
+}
+
+/**
+ * Run a notification button from the in-app list (native notifications only
+ * have buttons on Windows)
+ */
+export async function dispatchNotificationAction(
+  notification: AppNotification,
+  action: NotificationAction,
+): Promise<void> {
+  return invoke('notifications_dispatch_action', {
+    activation: {
+      category: notification.category,
+      action,
+      target: notification.target,
+    },
+  });
+}
+
+export function onNotificationShown(
+  handler: (notification: AppNotification) => void,
+): Promise<UnlistenFn> {
+  return listen<AppNotification>('notification://shown', (event) => handler(event.payload));
+}
+
+/**
+ * Fired when the user clicks a native notification or its Open button
+ */
+export function onNotificationOpen(
+  handler: (event: NotificationOpenEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<NotificationOpenEvent>('notification://open', (event) => handler(event.payload));
+}
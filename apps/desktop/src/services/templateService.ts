@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { AgentTemplate, TemplateCategory } from '../types/templates';
+import type { AgentTemplate, RequirementsReport, TemplateCategory } from '../types/templates';
 
 /**
  * Template Service - Wrapper for Tauri commands
@@ -35,6 +35,15 @@ export class TemplateService {
     return await invoke<void>('install_template', { template_id: templateId });
   }
 
+  /**
+   * Check which declared integrations, tools and permissions are missing
+   */
+  static async checkRequirements(templateId: string): Promise<RequirementsReport> {
+    return await invoke<RequirementsReport>('template_check_requirements', {
+      template_id: templateId,
+    });
+  }
+
   /**
    * Uninstall a template
    */
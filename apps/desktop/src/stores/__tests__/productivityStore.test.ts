@@ -1,6 +1,6 @@
 import { beforeEach, describe, expect, it, vi } from 'vitest';
 import { useProductivityStore } from '../productivityStore';
-import type { Task } from '../../types/productivity';
+import type { ProductivityAccount, Task } from '../../types/productivity';
 
 const invokeMock = vi.fn();
 
@@ -13,8 +13,10 @@ const flushPromises = () => new Promise((resolve) => setTimeout(resolve, 0));
 beforeEach(() => {
   invokeMock.mockReset();
   useProductivityStore.setState({
+    accounts: [],
     connectedProviders: new Set(),
     selectedProvider: null,
+    selectedAccountId: null,
     tasks: [],
     selectedTaskId: null,
     notionPages: [],
@@ -48,7 +50,11 @@ describe('useProductivityStore', () => {
     invokeMock.mockImplementation(async (command: string) => {
       switch (command) {
         case 'productivity_connect':
-          return { account_id: 'acct-1', success: true };
+          return {
+            account_id: 'acct-1',
+            account: { account_id: 'acct-1', provider: 'notion', label: null, remote_id: 'bot' },
+            success: true,
+          };
         case 'productivity_list_tasks':
           return tasks;
         case 'productivity_notion_list_pages':
@@ -64,14 +70,24 @@ describe('useProductivityStore', () => {
     const state = useProductivityStore.getState();
     expect(Array.from(state.connectedProviders)).toContain('notion');
     expect(state.selectedProvider).toBe('notion');
+    expect(state.selectedAccountId).toBe('acct-1');
+    expect(state.accounts).toHaveLength(1);
     expect(state.tasks).toEqual(tasks);
     expect(state.notionPages).toHaveLength(1);
   });
 
   it('selectProvider loads trello boards and tasks', async () => {
-    invokeMock.mockImplementation(async (command: string) => {
+    const trelloAccount: ProductivityAccount = {
+      account_id: 'acct-trello',
+      provider: 'trello',
+      label: 'Roadmap',
+      remote_id: null,
+    };
+
+    invokeMock.mockImplementation(async (command: string, payload: unknown) => {
       switch (command) {
         case 'productivity_list_tasks':
+          expect(payload).toEqual({ accountId: 'acct-trello' });
           return [
             {
               id: 'card-1',
@@ -94,6 +110,7 @@ describe('useProductivityStore', () => {
     });
 
     useProductivityStore.setState({
+      accounts: [trelloAccount],
       connectedProviders: new Set(['trello']),
     });
 
@@ -102,6 +119,7 @@ describe('useProductivityStore', () => {
 
     const state = useProductivityStore.getState();
     expect(state.selectedProvider).toBe('trello');
+    expect(state.selectedAccountId).toBe('acct-trello');
     expect(state.tasks).toHaveLength(1);
     expect(state.trelloBoards).toHaveLength(1);
   });
@@ -109,12 +127,14 @@ describe('useProductivityStore', () => {
   it('setAsanaWorkspace persists workspace ID and fetches projects', async () => {
     invokeMock.mockImplementation(async (command: string, payload: unknown) => {
       if (command === 'productivity_asana_list_projects') {
-        expect(payload).toEqual({ workspace_id: 'workspace-123' });
+        expect(payload).toEqual({ accountId: 'acct-asana', workspace_id: 'workspace-123' });
         return [{ gid: 'proj-1', name: 'Marketing Ops' }];
       }
       throw new Error(`Unexpected invoke command: ${command}`);
     });
 
+    useProductivityStore.setState({ selectedAccountId: 'acct-asana' });
+
     await useProductivityStore.getState().setAsanaWorkspace('workspace-123');
     await flushPromises();
 
@@ -1,4 +1,5 @@
 import { create } from 'zustand';
+import { invoke } from '../lib/tauri-mock';
 import {
   automationOcr,
   automationScreenshot,
@@ -402,6 +403,11 @@ export const useAutomationStore = create<AutomationState>((set, get) => ({
 
   handleShortcutAction: (action) => {
     set({ lastTriggeredShortcut: action });
+    if (action === 'toggle_launcher') {
+      invoke('launcher_toggle').catch((error) =>
+        console.error('[AutomationStore] Failed to toggle launcher:', error),
+      );
+    }
   },
 
   handleShortcutRegistered: (shortcut) => {
@@ -9,6 +9,7 @@ import type {
   OAuthCredentials,
   PendingAuthorization,
   ShareLink,
+  WebDavCredentials,
 } from '../types/cloud';
 
 type Account = {
@@ -35,6 +36,7 @@ interface CloudState {
   ) => Promise<void>;
   beginConnect: (provider: CloudProvider, credentials: OAuthCredentials) => Promise<void>;
   completeConnect: (state: string, code: string) => Promise<void>;
+  connectWebDav: (credentials: WebDavCredentials) => Promise<void>;
   uploadFile: (localPath: string, remotePath: string) => Promise<void>;
   downloadFile: (remotePath: string, localPath: string) => Promise<void>;
   deleteEntry: (remotePath: string) => Promise<void>;
@@ -243,6 +245,28 @@ export const useCloudStore = create<CloudState>((set, get) => {
       }
     },
 
+    connectWebDav: async (credentials) => {
+      set({ loading: true, error: null });
+
+      try {
+        await invoke<{ account_id: string }>('cloud_connect_webdav', {
+          request: {
+            url: credentials.url,
+            username: credentials.username,
+            password: credentials.password,
+            auth: credentials.auth,
+            accept_invalid_certs: credentials.acceptInvalidCerts,
+            label: credentials.label || null,
+          },
+        });
+        set({ loading: false });
+        await get().refreshAccounts();
+      } catch (error) {
+        console.error('[cloud] failed to connect WebDAV server', error);
+        set({ error: (error as Error).message, loading: false });
+      }
+    },
+
     uploadFile: async (localPath, remotePath) => {
       const { activeAccountId } = get();
       if (!activeAccountId) {
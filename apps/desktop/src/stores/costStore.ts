@@ -1,6 +1,11 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { CostAnalyticsResponse, CostOverviewResponse } from '../types/chat';
+import type {
+  CostAnalyticsResponse,
+  CostEstimateRequest,
+  CostForecast,
+  CostOverviewResponse,
+} from '../types/chat';
 
 interface CostFilters {
   days: number;
@@ -18,6 +23,7 @@ interface CostState {
   loadOverview: () => Promise<void>;
   loadAnalytics: (overrides?: Partial<CostFilters>) => Promise<void>;
   setMonthlyBudget: (amount?: number) => Promise<void>;
+  estimateCost: (request: CostEstimateRequest) => Promise<CostForecast>;
 }
 
 const DEFAULT_FILTERS: CostFilters = {
@@ -101,4 +107,13 @@ export const useCostStore = create<CostState>((set, get) => ({
       throw error;
     }
   },
+
+  estimateCost: (request) =>
+    invoke<CostForecast>('chat_estimate_cost', {
+      conversationId: request.conversationId ?? null,
+      draftMessage: request.draftMessage,
+      model: request.model ?? null,
+      provider: request.provider ?? null,
+      enableTools: request.enableTools ?? null,
+    }),
 }));
@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { toast } from 'sonner';
 
 import type {
@@ -9,6 +10,12 @@ import type {
   EmailMessage,
   EmailProviderConfig,
   Contact,
+  EmailRule,
+  EmailRuleInput,
+  NewMessageEvent,
+  OutboxEntry,
+  OutboxEvent,
+  OutboxStatus,
 } from '../types/email';
 
 const DEFAULT_FILTER: EmailFilter = {
@@ -33,6 +40,8 @@ export interface SendEmailPayload {
   body_text?: string | null;
   body_html?: string | null;
   attachments?: string[];
+  /** Unix timestamp to send at; sends right away when omitted */
+  send_at?: number | null;
 }
 
 interface EmailState {
@@ -46,6 +55,8 @@ interface EmailState {
   error: string | null;
   filter: EmailFilter;
   contacts: Contact[];
+  rules: EmailRule[];
+  outbox: OutboxEntry[];
 
   refreshAccounts: () => Promise<void>;
   connectAccount: (payload: ConnectAccountPayload) => Promise<void>;
@@ -60,14 +71,24 @@ interface EmailState {
   selectEmail: (emailId: string | null) => void;
   markRead: (uid: number, read: boolean) => Promise<void>;
   deleteEmail: (uid: number) => Promise<void>;
-  sendEmail: (payload: SendEmailPayload) => Promise<string>;
+  sendEmail: (payload: SendEmailPayload) => Promise<OutboxEntry>;
   setFilter: (partial: Partial<EmailFilter>) => void;
   downloadAttachment: (message: EmailMessage, attachmentIndex: number) => Promise<string>;
+  subscribeToNewMessages: () => Promise<UnlistenFn>;
   clearError: () => void;
 
   refreshContacts: () => Promise<void>;
   saveContact: (contact: Partial<Contact> & { email: string }) => Promise<void>;
   deleteContact: (id: number) => Promise<void>;
+
+  refreshRules: (accountId?: number) => Promise<void>;
+  saveRule: (rule: EmailRuleInput, id?: string) => Promise<EmailRule>;
+  deleteRule: (id: string) => Promise<void>;
+  testRule: (rule: EmailRuleInput, accountId: number) => Promise<EmailMessage[]>;
+
+  refreshOutbox: (options?: { accountId?: number; status?: OutboxStatus }) => Promise<void>;
+  cancelOutboxEntry: (id: string) => Promise<boolean>;
+  subscribeToOutbox: () => Promise<UnlistenFn>;
 }
 
 function mergeFilter(current: EmailFilter, partial?: Partial<EmailFilter>): EmailFilter {
@@ -92,6 +113,8 @@ export const useEmailStore = create<EmailState>((set, get) => ({
   error: null,
   filter: DEFAULT_FILTER,
   contacts: [],
+  rules: [],
+  outbox: [],
 
   refreshAccounts: async () => {
     try {
@@ -228,10 +251,36 @@ export const useEmailStore = create<EmailState>((set, get) => ({
       }
     } catch (error) {
       console.error('[email] fetch failed', error);
+      // Fall back to messages cached by background sync while offline
+      try {
+        const cached = await invoke<EmailMessage[]>('email_list_cached', {
+          account_id: accountId,
+          folder,
+          limit: 100,
+        });
+        if (cached.length > 0) {
+          set({ emails: cached, selectedFolder: folder });
+        }
+      } catch (cacheError) {
+        console.error('[email] cache read failed', cacheError);
+      }
       set({ error: (error as Error).message, loading: false });
     }
   },
 
+  subscribeToNewMessages: () =>
+    listen<NewMessageEvent>('email://new-message', (event) => {
+      const { account_id, email } = event.payload;
+      const { selectedAccountId, selectedFolder, emails } = get();
+      if (account_id !== selectedAccountId || email.folder !== selectedFolder) {
+        return;
+      }
+      if (emails.some((message) => message.id === email.id)) {
+        return;
+      }
+      set({ emails: [email, ...emails] });
+    }),
+
   selectEmail: (emailId) => {
     if (!emailId) {
       set({ selectedEmail: null });
@@ -299,7 +348,7 @@ export const useEmailStore = create<EmailState>((set, get) => ({
 
   sendEmail: async (payload) => {
     try {
-      const messageId = await invoke<string>('email_send', {
+      const entry = await invoke<OutboxEntry>('email_send', {
         request: {
           account_id: payload.account_id,
           to: payload.to,
@@ -310,11 +359,13 @@ export const useEmailStore = create<EmailState>((set, get) => ({
           body_text: payload.body_text ?? null,
           body_html: payload.body_html ?? null,
           attachments: payload.attachments ?? [],
+          send_at: payload.send_at ?? null,
         },
       });
 
-      toast.success('Email sent');
-      return messageId;
+      set((state) => ({ outbox: [entry, ...state.outbox] }));
+      toast.success(payload.send_at ? 'Email scheduled' : 'Email queued');
+      return entry;
     } catch (error) {
       console.error('[email] send failed', error);
       set({ error: (error as Error).message });
@@ -432,4 +483,106 @@ export const useEmailStore = create<EmailState>((set, get) => ({
       throw error;
     }
   },
+
+  refreshRules: async (accountId) => {
+    try {
+      const rules = await invoke<EmailRule[]>('email_rules_list', {
+        account_id: accountId ?? null,
+      });
+      set({ rules });
+    } catch (error) {
+      console.error('[email] failed to load rules', error);
+      set({ error: (error as Error).message });
+    }
+  },
+
+  saveRule: async (rule, id) => {
+    try {
+      const saved = id
+        ? await invoke<EmailRule>('email_rules_update', { id, rule })
+        : await invoke<EmailRule>('email_rules_create', { rule });
+      set((state) => ({
+        rules: [...state.rules.filter((existing) => existing.id !== saved.id), saved].sort(
+          (a, b) => a.position - b.position,
+        ),
+      }));
+      return saved;
+    } catch (error) {
+      console.error('[email] save rule failed', error);
+      const messageText = (error as Error).message ?? 'Failed to save rule';
+      set({ error: messageText });
+      toast.error(messageText);
+      throw error;
+    }
+  },
+
+  deleteRule: async (id) => {
+    try {
+      await invoke('email_rules_delete', { id });
+      set((state) => ({
+        rules: state.rules.filter((rule) => rule.id !== id),
+      }));
+    } catch (error) {
+      console.error('[email] delete rule failed', error);
+      set({ error: (error as Error).message });
+      throw error;
+    }
+  },
+
+  testRule: async (rule, accountId) => {
+    return invoke<EmailMessage[]>('email_rules_test', { rule, account_id: accountId });
+  },
+
+  refreshOutbox: async (options) => {
+    try {
+      const outbox = await invoke<OutboxEntry[]>('email_outbox_list', {
+        account_id: options?.accountId ?? null,
+        status: options?.status ?? null,
+        limit: 100,
+      });
+      set({ outbox });
+    } catch (error) {
+      console.error('[email] failed to load outbox', error);
+      set({ error: (error as Error).message });
+    }
+  },
+
+  cancelOutboxEntry: async (id) => {
+    try {
+      const cancelled = await invoke<boolean>('email_outbox_cancel', { id });
+      if (!cancelled) {
+        toast.error('Email is already being sent');
+      }
+      return cancelled;
+    } catch (error) {
+      console.error('[email] cancel outbox entry failed', error);
+      set({ error: (error as Error).message });
+      throw error;
+    }
+  },
+
+  subscribeToOutbox: () =>
+    listen<OutboxEvent>('email://outbox', (event) => {
+      const update = event.payload;
+      set((state) => ({
+        outbox: state.outbox.map((entry) =>
+          entry.id === update.id
+            ? {
+                ...entry,
+                status: update.status,
+                attempts: update.attempts,
+                next_attempt_at: update.next_attempt_at ?? entry.next_attempt_at,
+                last_error: update.error,
+                message_id: update.message_id,
+              }
+            : entry,
+        ),
+      }));
+
+      if (update.status === 'sent') {
+        toast.success(`Email sent: ${update.subject}`);
+      } else if (update.status === 'failed') {
+        toast.error(`Email failed: ${update.subject}${update.error ? ` (${update.error})` : ''}`);
+      }
+    }),
 }));
@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../lib/authInvoke';
 import { create } from 'zustand';
 
 export interface FileMetadata {
@@ -4,10 +4,12 @@ import type {
   WorkflowDefinition,
   WorkflowExecution,
   WorkflowExecutionLog,
+  WorkflowArtifact,
   WorkflowNode,
   WorkflowEdge,
   NodeLibraryItem,
   ScheduledWorkflow,
+  WorkflowSimulation,
 } from '../types/workflow';
 
 interface OrchestrationState {
@@ -16,6 +18,8 @@ interface OrchestrationState {
   selectedWorkflow: WorkflowDefinition | null;
   currentExecution: WorkflowExecution | null;
   executionLogs: WorkflowExecutionLog[];
+  executionArtifacts: WorkflowArtifact[];
+  simulation: WorkflowSimulation | null;
   loadingWorkflows: boolean;
   loadingExecution: boolean;
   error: string | null;
@@ -41,11 +45,16 @@ interface OrchestrationState {
 
   // Actions - Execution
   executeWorkflow: (workflowId: string, inputs: Record<string, any>) => Promise<string | null>;
+  simulateWorkflow: (
+    workflowId: string,
+    inputs: Record<string, any>,
+  ) => Promise<WorkflowSimulation | null>;
   pauseWorkflow: (executionId: string) => Promise<void>;
   resumeWorkflow: (executionId: string) => Promise<void>;
   cancelWorkflow: (executionId: string) => Promise<void>;
   getExecutionStatus: (executionId: string) => Promise<void>;
   getExecutionLogs: (executionId: string) => Promise<void>;
+  getExecutionArtifacts: (executionId: string) => Promise<void>;
 
   // Actions - Scheduling
   scheduleWorkflow: (workflowId: string, cronExpr: string, timezone?: string) => Promise<void>;
@@ -123,6 +132,8 @@ export const useOrchestrationStore = create<OrchestrationState>((set, get) => ({
   selectedWorkflow: null,
   currentExecution: null,
   executionLogs: [],
+  executionArtifacts: [],
+  simulation: null,
   loadingWorkflows: false,
   loadingExecution: false,
   error: null,
@@ -238,6 +249,22 @@ export const useOrchestrationStore = create<OrchestrationState>((set, get) => ({
     }
   },
 
+  simulateWorkflow: async (workflowId: string, inputs: Record<string, any>) => {
+    set({ error: null });
+    try {
+      const simulation = await invoke<WorkflowSimulation>('execute_workflow', {
+        workflowId,
+        inputs,
+        simulate: true,
+      });
+      set({ simulation });
+      return simulation;
+    } catch (error) {
+      set({ error: String(error) });
+      return null;
+    }
+  },
+
   pauseWorkflow: async (executionId: string) => {
     set({ error: null });
     try {
@@ -295,6 +322,18 @@ export const useOrchestrationStore = create<OrchestrationState>((set, get) => ({
     }
   },
 
+  getExecutionArtifacts: async (executionId: string) => {
+    set({ error: null });
+    try {
+      const artifacts = await invoke<WorkflowArtifact[]>('workflow_get_artifacts', {
+        executionId,
+      });
+      set({ executionArtifacts: artifacts });
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
+
   // Scheduling actions
   scheduleWorkflow: async (workflowId: string, cronExpr: string, timezone?: string) => {
     set({ error: null });
@@ -366,6 +405,8 @@ export const useOrchestrationStore = create<OrchestrationState>((set, get) => ({
       selectedWorkflow: null,
       currentExecution: null,
       executionLogs: [],
+      executionArtifacts: [],
+      simulation: null,
       loadingWorkflows: false,
       loadingExecution: false,
       error: null,
@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { toast } from 'sonner';
 
 import type {
+  ProductivityAccount,
   ProductivityProvider,
   Task,
   CreateTaskRequest,
@@ -25,8 +26,10 @@ import type {
 
 interface ProductivityState {
   // Connection state
+  accounts: ProductivityAccount[];
   connectedProviders: Set<ProductivityProvider>;
   selectedProvider: ProductivityProvider | null;
+  selectedAccountId: string | null;
 
   // Tasks
   tasks: Task[];
@@ -45,8 +48,15 @@ interface ProductivityState {
   error: string | null;
 
   // Connection actions
-  connect: (provider: ProductivityProvider, credentials: ProductivityCredentials) => Promise<void>;
+  loadAccounts: () => Promise<void>;
+  connect: (
+    provider: ProductivityProvider,
+    credentials: ProductivityCredentials,
+    label?: string,
+  ) => Promise<void>;
+  disconnect: (accountId: string) => Promise<void>;
   selectProvider: (provider: ProductivityProvider | null) => void;
+  selectAccount: (accountId: string) => void;
   setAsanaWorkspace: (workspaceId: string) => Promise<void>;
 
   // Task actions
@@ -77,10 +87,20 @@ interface ProductivityState {
   clearError: () => void;
 }
 
+/** Account the provider-specific commands run against */
+function requireAccount(accountId: string | null): string {
+  if (!accountId) {
+    throw new Error('No productivity account selected');
+  }
+  return accountId;
+}
+
 export const useProductivityStore = create<ProductivityState>((set, get) => ({
   // Initial state
+  accounts: [],
   connectedProviders: new Set(),
   selectedProvider: null,
+  selectedAccountId: null,
   tasks: [],
   selectedTaskId: null,
   notionPages: [],
@@ -93,18 +113,37 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
   error: null,
 
   // Connection actions
-  connect: async (provider, credentials) => {
+  loadAccounts: async () => {
+    try {
+      const accounts = await invoke<ProductivityAccount[]>('productivity_list_accounts');
+      set({
+        accounts,
+        connectedProviders: new Set(accounts.map((account) => account.provider)),
+      });
+    } catch (error) {
+      console.error('[productivity] failed to list accounts', error);
+      set({ error: (error as Error).message });
+    }
+  },
+
+  connect: async (provider, credentials, label) => {
     try {
       set({ loading: true, error: null });
 
-      await invoke<{ account_id: string }>('productivity_connect', {
-        provider,
-        credentials,
-      });
+      const { account } = await invoke<{ account_id: string; account: ProductivityAccount }>(
+        'productivity_connect',
+        {
+          provider,
+          credentials,
+          label,
+        },
+      );
 
       const nextState: Partial<ProductivityState> = {
+        accounts: [...get().accounts, account],
         connectedProviders: new Set([...get().connectedProviders, provider]),
         selectedProvider: provider,
+        selectedAccountId: account.account_id,
         loading: false,
       };
 
@@ -144,9 +183,46 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
     }
   },
 
+  disconnect: async (accountId) => {
+    try {
+      await invoke('productivity_disconnect', { accountId });
+      const accounts = get().accounts.filter((account) => account.account_id !== accountId);
+      set({
+        accounts,
+        connectedProviders: new Set(accounts.map((account) => account.provider)),
+        ...(get().selectedAccountId === accountId
+          ? { selectedAccountId: null, selectedProvider: null, tasks: [] }
+          : {}),
+      });
+    } catch (error) {
+      console.error('[productivity] failed to disconnect', error);
+      const errorMessage = (error as Error).message;
+      set({ error: errorMessage });
+      toast.error(`Failed to disconnect: ${errorMessage}`);
+      throw error;
+    }
+  },
+
+  selectAccount: (accountId) => {
+    const account = get().accounts.find((entry) => entry.account_id === accountId);
+    if (!account) {
+      return;
+    }
+    set({ selectedAccountId: accountId });
+    get().selectProvider(account.provider);
+  },
+
   selectProvider: (provider) => {
+    const { accounts, selectedAccountId } = get();
+    const current = accounts.find((account) => account.account_id === selectedAccountId);
+    const accountId =
+      current?.provider === provider
+        ? selectedAccountId
+        : (accounts.find((account) => account.provider === provider)?.account_id ?? null);
+
     set({
       selectedProvider: provider,
+      selectedAccountId: accountId,
       tasks: [],
       selectedTaskId: null,
       trelloCards: [],
@@ -180,8 +256,8 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
 
   // Task actions
   refreshTasks: async () => {
-    const { selectedProvider } = get();
-    if (!selectedProvider) {
+    const { selectedAccountId } = get();
+    if (!selectedAccountId) {
       return;
     }
 
@@ -189,7 +265,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const tasks = await invoke<Task[]>('productivity_list_tasks', {
-        provider: selectedProvider,
+        accountId: selectedAccountId,
       });
 
       set({ tasks, loading: false });
@@ -200,17 +276,17 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
   },
 
   createTask: async (request) => {
-    const { selectedProvider } = get();
-    if (!selectedProvider) {
-      toast.error('Select a provider before creating tasks');
-      throw new Error('No provider selected');
+    const { selectedAccountId } = get();
+    if (!selectedAccountId) {
+      toast.error('Select an account before creating tasks');
+      throw new Error('No account selected');
     }
 
     try {
       set({ loading: true, error: null });
 
       const taskId = await invoke<string>('productivity_create_task', {
-        provider: selectedProvider,
+        accountId: selectedAccountId,
         task: request,
       });
 
@@ -237,7 +313,9 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
     try {
       set({ loading: true, error: null });
 
-      const pages = await invoke<NotionPage[]>('productivity_notion_list_pages');
+      const pages = await invoke<NotionPage[]>('productivity_notion_list_pages', {
+        accountId: requireAccount(get().selectedAccountId),
+      });
 
       set({ notionPages: pages, loading: false });
     } catch (error) {
@@ -251,6 +329,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const results = await invoke<any[]>('productivity_notion_query_database', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -269,6 +348,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const pageId = await invoke<string>('productivity_notion_create_database_row', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -289,7 +369,9 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
     try {
       set({ loading: true, error: null });
 
-      const boards = await invoke<TrelloBoard[]>('productivity_trello_list_boards');
+      const boards = await invoke<TrelloBoard[]>('productivity_trello_list_boards', {
+        accountId: requireAccount(get().selectedAccountId),
+      });
 
       set({ trelloBoards: boards, loading: false });
     } catch (error) {
@@ -303,6 +385,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const cards = await invoke<TrelloCard[]>('productivity_trello_list_cards', {
+        accountId: requireAccount(get().selectedAccountId),
         board_id: boardId,
       });
 
@@ -318,6 +401,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const cardId = await invoke<string>('productivity_trello_create_card', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -338,6 +422,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       await invoke('productivity_trello_move_card', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -357,6 +442,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const commentId = await invoke<string>('productivity_trello_add_comment', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -384,6 +470,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const projects = await invoke<AsanaProject[]>('productivity_asana_list_projects', {
+        accountId: requireAccount(get().selectedAccountId),
         workspace_id: workspaceId,
       });
 
@@ -399,6 +486,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const tasks = await invoke<AsanaTask[]>('productivity_asana_list_project_tasks', {
+        accountId: requireAccount(get().selectedAccountId),
         project_id: projectId,
       });
 
@@ -414,6 +502,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       const taskId = await invoke<string>('productivity_asana_create_task', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -434,6 +523,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       await invoke('productivity_asana_assign_task', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
@@ -453,6 +543,7 @@ export const useProductivityStore = create<ProductivityState>((set, get) => ({
       set({ loading: true, error: null });
 
       await invoke('productivity_asana_mark_complete', {
+        accountId: requireAccount(get().selectedAccountId),
         request,
       });
 
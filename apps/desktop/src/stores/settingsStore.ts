@@ -73,6 +73,8 @@ interface SettingsState {
   // API Key Management
   setAPIKey: (provider: Provider, key: string) => Promise<void>;
   getAPIKey: (provider: Provider) => Promise<string>;
+  /** Saved key for display; the backend confirms the user's identity first */
+  revealAPIKey: (provider: Provider) => Promise<string>;
   testAPIKey: (provider: Provider) => Promise<boolean>;
 
   // LLM Configuration
@@ -237,6 +239,10 @@ export const useSettingsStore = create<SettingsState>()(
         }
       },
 
+      revealAPIKey: async (provider: Provider) => {
+        return invoke<string>('settings_reveal_api_key', { provider });
+      },
+
       // Updated Nov 16, 2025: Improved error handling
       testAPIKey: async (provider: Provider) => {
         set({ loading: true, error: null });
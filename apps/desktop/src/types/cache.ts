@@ -35,6 +35,8 @@ export interface CacheSettings {
   ttl_seconds?: number;
   max_entries?: number;
   enabled?: boolean;
+  semantic_threshold?: number;
+  warmup_enabled?: boolean;
 }
 
 /**
@@ -67,6 +69,62 @@ export interface CacheAnalytics {
   provider_breakdown: ProviderCacheBreakdown[];
   total_cost_saved: number;
   total_tokens_saved: number;
+  hit_rate: CacheHitRate;
+  warmup: WarmupReport;
+}
+
+/**
+ * LLM cache lookups by outcome
+ */
+export interface CacheHitRate {
+  lookups: number;
+  exactHits: number;
+  semanticHits: number;
+  misses: number;
+  hitRate: number;
+  semanticHitRate: number;
+  semanticThreshold: number;
+}
+
+/**
+ * A prompt or tool call that keeps recurring
+ */
+export interface UsagePattern {
+  kind: 'llm' | 'tool';
+  key: string;
+  label: string;
+  uses: number;
+  activeDays: number;
+  peakHour: number;
+  lastUsedAt: number;
+  lastWarmedAt: number | null;
+  warmHits: number;
+}
+
+/**
+ * Effect of cache warmup on hit rates
+ */
+export interface WarmupReport {
+  enabled: boolean;
+  trackedPatterns: number;
+  frequentPatterns: UsagePattern[];
+  lastWarmedAt: number | null;
+  warmedEntries: number;
+  llmWarmHits: number;
+  toolWarmHits: number;
+  hitRateWithoutWarmup: number;
+  hitRateWithWarmup: number;
+  hitRateImprovement: number;
+}
+
+/**
+ * Outcome of a cache warmup run
+ */
+export interface WarmupRun {
+  llmWarmed: number;
+  toolsWarmed: number;
+  alreadyFresh: number;
+  failed: number;
 }
 
 /**
@@ -2,6 +2,7 @@
 
 import type { ToolCallUI, ToolResultUI, ToolExecutionWorkflow } from './toolCalling';
 import type { TaskMetadata } from '../lib/taskMetadata';
+import type { McpResourceRef } from './mcp';
 
 export type MessageRole = 'user' | 'assistant' | 'system';
 
@@ -106,6 +107,12 @@ export interface ChatSendMessageRequest extends ChatRoutingPreferences {
   stream?: boolean;
   workflowHash?: string;
   taskMetadata?: TaskMetadata;
+  /** Project collection whose best-matching excerpts are added as context */
+  collectionId?: string;
+  /** MCP resources whose contents are added as context */
+  mcpResources?: McpResourceRef[];
+  /** Lets the reply be stopped with `cancelOperation` */
+  operationId?: string;
   providerOverride?: string;
   modelOverride?: string;
   enableTools?: boolean;
@@ -137,12 +144,43 @@ export interface ConversationUI extends Conversation {
 }
 
 export interface CostOverviewResponse {
+  /** LLM and tool spend together */
   today_total: number;
   month_total: number;
+  /** The part of the totals spent by tool calls */
+  tool_today_total: number;
+  tool_month_total: number;
   monthly_budget?: number | null;
   remaining_budget?: number | null;
 }
 
+export interface CostForecastSection {
+  section: 'system' | 'tools' | 'history' | 'draft';
+  tokens: number;
+}
+
+export interface CostForecast {
+  provider: string;
+  model: string;
+  sections: CostForecastSection[];
+  inputTokens: number;
+  outputTokensMin: number;
+  outputTokensMax: number;
+  cachedTokens: number;
+  inputCost: number;
+  costMin: number;
+  costMax: number;
+  cachedSavings: number;
+}
+
+export interface CostEstimateRequest {
+  conversationId?: number | null;
+  draftMessage: string;
+  model?: string | null;
+  provider?: string | null;
+  enableTools?: boolean;
+}
+
 export interface CostTimeseriesPoint {
   date: string;
   total_cost: number;
@@ -159,10 +197,23 @@ export interface ConversationCostBreakdown {
   total_cost: number;
 }
 
+export interface ToolCostBreakdown {
+  tool_id: string;
+  calls: number;
+  failures: number;
+  total_cost: number;
+  avg_duration_ms: number;
+  /** Metered units such as pages or images, summed over the calls */
+  units: Record<string, number>;
+}
+
 export interface CostAnalyticsResponse {
   timeseries: CostTimeseriesPoint[];
   providers: ProviderCostBreakdown[];
   top_conversations: ConversationCostBreakdown[];
+  /** Daily tool spend; provider and model filters don't apply */
+  tool_timeseries: CostTimeseriesPoint[];
+  tools: ToolCostBreakdown[];
 }
 
 export interface ChatStreamStartPayload {
@@ -1,4 +1,6 @@
-export type CloudProvider = 'google_drive' | 'dropbox' | 'one_drive';
+export type CloudProvider = 'google_drive' | 'dropbox' | 'one_drive' | 'webdav';
+
+export type WebDavAuth = 'basic' | 'digest';
 
 export interface CloudAccount {
   account_id: string;
@@ -30,6 +32,15 @@ export interface OAuthCredentials {
   redirectUri: string;
 }
 
+export interface WebDavCredentials {
+  url: string;
+  username: string;
+  password: string;
+  auth: WebDavAuth;
+  acceptInvalidCerts: boolean;
+  label?: string;
+}
+
 export interface PendingAuthorization {
   provider: CloudProvider;
   state: string;
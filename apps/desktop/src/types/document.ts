@@ -17,9 +17,21 @@ export interface DocumentMetadata {
   word_count?: number;
 }
 
+export type PageTextSource = 'embedded' | 'ocr' | 'unreadable';
+
+export interface PageContent {
+  page: number;
+  text: string;
+  source: PageTextSource;
+  /** Mean OCR confidence from 0 to 100, for OCR pages */
+  confidence: number | null;
+}
+
 export interface DocumentContent {
   text: string;
   metadata: DocumentMetadata;
+  /** Per-page text for PDFs, showing which pages were recovered by OCR */
+  pages?: PageContent[];
 }
 
 export interface SearchResult {
@@ -29,6 +41,93 @@ export interface SearchResult {
   match_text: string;
 }
 
+export interface DocumentTemplate {
+  name: string;
+  file_path: string;
+  document_type: DocumentType;
+  /** Paths referenced by the template's {{tags}} */
+  placeholders: string[];
+  file_size: number;
+}
+
+export type ConversionFormat = 'markdown' | 'word' | 'pdf';
+
+export interface DocumentConversion {
+  input_format: ConversionFormat;
+  output_format: ConversionFormat;
+  output_path: string;
+}
+
+export type TabularFormat = 'csv' | 'parquet';
+
+export type ColumnType = 'integer' | 'real' | 'boolean' | 'date' | 'timestamp' | 'text';
+
+export interface ColumnSchema {
+  name: string;
+  column_type: ColumnType;
+  nullable: boolean;
+}
+
+export interface CsvDialect {
+  delimiter: string;
+  encoding: string;
+  has_header: boolean;
+}
+
+export interface TabularReadOptions {
+  delimiter?: string;
+  /** Any WHATWG label, e.g. 'utf-8', 'windows-1252', 'shift_jis' */
+  encoding?: string;
+  has_header?: boolean;
+  preview_rows?: number;
+}
+
+export interface TabularPreview {
+  format: TabularFormat;
+  columns: ColumnSchema[];
+  rows: unknown[][];
+  total_rows: number;
+  dialect?: CsvDialect | null;
+}
+
+export type ImportMode = 'create' | 'replace' | 'append';
+
+export interface ImportSummary {
+  database_path: string;
+  table: string;
+  columns: string[];
+  rows_imported: number;
+}
+
+export type ArchiveFileStatus = 'ingested' | 'skipped' | 'failed';
+
+export interface ArchiveFileResult {
+  /** Path inside the archive */
+  entry: string;
+  status: ArchiveFileStatus;
+  document_id?: string | null;
+  chunks: number;
+  /** Skip reason or failure message */
+  error?: string | null;
+}
+
+/** Payload of the `knowledge:ingest-progress` event */
+export interface ArchiveIngestProgress {
+  source_group: string;
+  index: number;
+  total: number;
+  file: ArchiveFileResult;
+}
+
+export interface ArchiveIngestReport {
+  source_group: string;
+  archive: string;
+  files: ArchiveFileResult[];
+  ingested: number;
+  skipped: number;
+  failed: number;
+}
+
 export interface DocumentState {
   currentDocument: DocumentContent | null;
   searchResults: SearchResult[];
@@ -68,6 +68,21 @@ export interface EmailMessage {
   size: number;
 }
 
+export type EmailSyncState = 'connecting' | 'syncing' | 'idle' | 'offline';
+
+export interface EmailSyncStatus {
+  account_id: number;
+  state: EmailSyncState;
+  last_sync?: number | null;
+  last_error?: string | null;
+}
+
+/** Payload of the `email://new-message` event */
+export interface NewMessageEvent {
+  account_id: number;
+  email: EmailMessage;
+}
+
 export interface Contact {
   id: number;
   email: string;
@@ -80,3 +95,98 @@ export interface Contact {
   created_at: number;
   updated_at: number;
 }
+
+export interface RuleConditions {
+  /** Substring of the sender address or name */
+  from?: string | null;
+  subject_regex?: string | null;
+  has_attachment?: boolean | null;
+  /** Category assigned by AI triage */
+  category?: string | null;
+}
+
+export type RuleAction =
+  | { type: 'move'; folder: string }
+  | { type: 'label'; label: string }
+  | { type: 'mark_read' }
+  | { type: 'auto_reply'; subject?: string | null; body: string }
+  | { type: 'create_task'; provider: 'notion' | 'trello' | 'asana'; project_id?: string | null }
+  | { type: 'notify'; title?: string | null };
+
+export interface EmailRule {
+  id: string;
+  /** `null` applies the rule to every account */
+  account_id: number | null;
+  name: string;
+  enabled: boolean;
+  position: number;
+  conditions: RuleConditions;
+  actions: RuleAction[];
+  stop_processing: boolean;
+  match_count: number;
+  last_matched_at: number | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface EmailRuleInput {
+  account_id?: number | null;
+  name: string;
+  enabled?: boolean;
+  position?: number | null;
+  conditions: RuleConditions;
+  actions: RuleAction[];
+  stop_processing?: boolean;
+}
+
+/** Payload of the `email://rule-matched` event */
+export interface RuleMatchedEvent {
+  rule_id: string;
+  rule_name: string;
+  account_id: number;
+  email_id: string;
+  category: string | null;
+  errors: string[];
+}
+
+export type OutboxStatus = 'queued' | 'sending' | 'sent' | 'failed' | 'cancelled';
+
+export interface OutboxRequest {
+  account_id: number;
+  to: EmailAddress[];
+  cc: EmailAddress[];
+  bcc: EmailAddress[];
+  reply_to: EmailAddress | null;
+  subject: string;
+  body_text: string | null;
+  body_html: string | null;
+  attachments: string[];
+  send_at: number | null;
+}
+
+export interface OutboxEntry {
+  id: string;
+  account_id: number;
+  request: OutboxRequest;
+  status: OutboxStatus;
+  send_at: number;
+  next_attempt_at: number;
+  attempts: number;
+  last_error: string | null;
+  message_id: string | null;
+  created_at: number;
+  updated_at: number;
+  sent_at: number | null;
+}
+
+/** Payload of the `email://outbox` event */
+export interface OutboxEvent {
+  id: string;
+  account_id: number;
+  subject: string;
+  status: OutboxStatus;
+  attempts: number;
+  next_attempt_at: number | null;
+  error: string | null;
+  message_id: string | null;
+}
@@ -8,8 +8,136 @@ export interface McpServerInfo {
   connected: boolean;
   tool_count: number;
   command?: string;
+  /** Endpoint of a remote (Streamable HTTP) server */
+  url?: string;
 }
 
+export type McpCatalogPackage =
+  | { type: 'npm'; package: string }
+  | { type: 'uvx'; package: string }
+  | { type: 'binary'; downloads: Record<string, { url: string; sha256: string }> };
+
+export interface McpCatalogCredential {
+  /** Environment variable the server reads */
+  key: string;
+  label: string;
+  description?: string | null;
+  required: boolean;
+}
+
+export interface McpCatalogEntry {
+  id: string;
+  name: string;
+  description: string;
+  category: string;
+  homepage?: string | null;
+  package: McpCatalogPackage;
+  args: string[];
+  env: Record<string, string>;
+  credentials: McpCatalogCredential[];
+}
+
+export interface McpCatalogListing extends McpCatalogEntry {
+  installed: boolean;
+  connected: boolean;
+  /** Required credential keys not yet in the vault */
+  missing_credentials: string[];
+}
+
+export interface McpServerHealth {
+  server_name: string;
+  status: 'healthy' | 'degraded' | 'unhealthy' | 'unknown';
+  last_check: string;
+  response_time_ms: number | null;
+  error_message: string | null;
+  tool_count: number;
+  consecutive_failures: number;
+}
+
+/** Payload of the `mcp://server-status` event */
+export interface McpServerStatusEvent {
+  server_name: string;
+  /** `quarantined` servers stay down until reconnected by hand */
+  state: 'running' | 'restarting' | 'quarantined';
+  /** Crashes since the server last ran stably */
+  crashes: number;
+  retry_at: string | null;
+  error: string | null;
+}
+
+/** Resource listed by a connected server */
+export interface McpResource {
+  server_name: string;
+  uri: string;
+  name: string;
+  description?: string;
+  mimeType?: string;
+}
+
+/** One entry of a resources/read result; blobs are base64 */
+export type McpResourceContent =
+  | { uri: string; mimeType?: string; text: string }
+  | { uri: string; mimeType?: string; blob: string };
+
+export interface McpResourceReadResult {
+  contents: McpResourceContent[];
+}
+
+/** Resource attached to a chat message as context */
+export interface McpResourceRef {
+  serverName: string;
+  uri: string;
+}
+
+export interface McpPrompt {
+  server_name: string;
+  name: string;
+  description?: string;
+  arguments?: { name: string; description?: string; required?: boolean }[];
+}
+
+export interface McpPromptMessage {
+  role: 'user' | 'assistant';
+  content:
+    | { type: 'text'; text: string }
+    | { type: 'image'; data: string; mimeType: string }
+    | { type: 'resource'; resource: { uri: string; mimeType?: string; text?: string } };
+}
+
+/** A prompt rendered with its arguments */
+export interface McpPromptGetResult {
+  description?: string;
+  messages: McpPromptMessage[];
+}
+
+/** Payload of the `mcp://resource-updated` event */
+export interface McpResourceUpdatedEvent {
+  server_name: string;
+  uri: string;
+}
+
+/** Payload of the `mcp://authorization-required` event */
+export interface McpAuthorizationRequiredEvent {
+  server_name: string;
+}
+
+export interface McpRemoteServerAdded {
+  server_name: string;
+  connected: boolean;
+  /** Sign in with `mcpStartOAuth` to connect */
+  authorization_required: boolean;
+  error?: string | null;
+}
+
+export interface McpOAuthStart {
+  auth_url: string;
+  state: string;
+}
+
+export type McpCatalogInstallResult =
+  | { status: 'needs_credentials'; missing: McpCatalogCredential[] }
+  | { status: 'installed'; server_name: string; health: McpServerHealth; error: string | null };
+
 export interface McpToolInfo {
   id: string;
   name: string;
@@ -20,11 +148,34 @@ export interface McpToolInfo {
 
 export interface McpServersConfig {
   mcpServers: Record<string, McpServerConfig>;
+  resultProcessing?: McpResultProcessingSettings;
+}
+
+/** Unset fields inherit from the server rule, then the defaults */
+export interface McpResultProcessingRule {
+  /** Maximum characters per text result; 0 disables the limit */
+  maxChars?: number;
+  /** Fraction of maxChars kept from the start when truncating */
+  headRatio?: number;
+  /** JSONPath expressions selecting the fields to keep */
+  projection?: string[];
+  /** Summarize oversized results with the LLM instead of truncating */
+  summarize?: boolean;
+}
+
+export interface McpResultProcessingSettings {
+  defaults: McpResultProcessingRule;
+  /** Keyed by server name */
+  servers: Record<string, McpResultProcessingRule>;
+  /** Keyed by `<server>/<tool>` */
+  tools: Record<string, McpResultProcessingRule>;
 }
 
 export interface McpServerConfig {
   command: string;
   args: string[];
+  /** Set for remote servers, which have no command */
+  url?: string;
   env: Record<string, string>;
   enabled: boolean;
 }
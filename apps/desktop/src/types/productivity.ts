@@ -28,6 +28,88 @@ export interface CreateTaskRequest {
   tags?: string[];
 }
 
+export interface ProductivityAccount {
+  account_id: string;
+  provider: ProductivityProvider;
+  /** User-facing name, e.g. the workspace it belongs to */
+  label: string | null;
+  /** User or bot ID reported by the provider */
+  remote_id: string | null;
+}
+
+// OAuth consent flow types
+
+export interface ProductivityOAuthConfig {
+  provider: ProductivityProvider;
+  /** OAuth client ID, or the API key for Trello */
+  client_id: string;
+  /** Required for Notion and Asana */
+  client_secret?: string;
+  redirect_uri: string;
+  /** Application name shown on Trello's consent screen */
+  app_name?: string;
+  /** Display name for the connected account */
+  label?: string;
+}
+
+export interface ProductivityAuthorization {
+  auth_url: string;
+  state: string;
+}
+
+export interface ProductivityOAuthResult {
+  provider: ProductivityProvider;
+  account_id: string;
+  account: ProductivityAccount;
+}
+
+// Unified cross-provider types
+
+export interface UnifiedTask extends Task {
+  provider: ProductivityProvider;
+  account_id: string;
+  /** Content hash used to detect changes between syncs */
+  etag: string;
+  synced_at: number;
+}
+
+export interface UnifiedTaskFilter {
+  /** Case-insensitive match against title and description */
+  query?: string;
+  providers?: ProductivityProvider[];
+  account_ids?: string[];
+  statuses?: TaskStatus[];
+  assignee?: string;
+  /** RFC 3339 */
+  due_before?: string;
+  due_after?: string;
+  limit?: number;
+}
+
+export interface ProviderSyncReport {
+  provider: ProductivityProvider;
+  account_id: string;
+  fetched: number;
+  changed: number;
+  removed: number;
+  /** Cached tasks from the last successful sync are still returned */
+  error: string | null;
+}
+
+export interface UnifiedTaskSearch {
+  tasks: UnifiedTask[];
+  last_synced_at: number | null;
+  /** Empty when served from the cache */
+  sync: ProviderSyncReport[];
+}
+
+export interface UnifiedBoard {
+  columns: { status: TaskStatus; tasks: UnifiedTask[] }[];
+  total: number;
+  last_synced_at: number | null;
+  sync: ProviderSyncReport[];
+}
+
 // Notion-specific types
 
 export interface NotionPage {
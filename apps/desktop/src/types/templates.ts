@@ -53,6 +53,25 @@ export interface AgentTemplate {
   difficulty_level: DifficultyLevel;
   install_count: number;
   created_at: number;
+  requirements?: TemplateRequirements;
+}
+
+export interface TemplateRequirements {
+  integrations: string[];
+  tools: string[];
+  permissions: string[];
+}
+
+export interface MissingRequirement {
+  kind: 'integration' | 'tool' | 'permission';
+  name: string;
+  hint: string;
+}
+
+export interface RequirementsReport {
+  templateId: string;
+  satisfied: boolean;
+  missing: MissingRequirement[];
 }
 
 export interface TemplateExecutionParams {
@@ -18,7 +18,9 @@ export type WorkflowNode =
   | ParallelNode
   | WaitNode
   | ScriptNode
-  | ToolNode;
+  | ToolNode
+  | TryNode
+  | SubWorkflowNode;
 
 export interface NodePosition {
   x: number;
@@ -137,6 +139,41 @@ export interface ToolNodeData {
   timeout_seconds?: number;
 }
 
+export interface TryNode {
+  type: 'try';
+  id: string;
+  position: NodePosition;
+  data: TryNodeData;
+}
+
+export interface TryNodeData {
+  label: string;
+  /** Variable the error is stored in before the catch branch runs; default `error` */
+  error_variable?: string;
+}
+
+export interface SubWorkflowNode {
+  type: 'sub_workflow';
+  id: string;
+  position: NodePosition;
+  data: SubWorkflowNodeData;
+}
+
+export interface SubWorkflowNodeData {
+  label: string;
+  workflow_id: string;
+  /** Sub-workflow input name -> variable of this workflow */
+  input_mapping?: Record<string, string>;
+  /** Sub-workflow variable -> variable of this workflow */
+  output_mapping?: Record<string, string>;
+}
+
+/**
+ * Edge `source_handle`s of control-flow nodes: decision branches, the loop
+ * body run once per iteration, and a try node's protected and catch branches
+ */
+export type BranchHandle = 'true' | 'false' | 'body' | 'try' | 'catch';
+
 export interface WorkflowEdge {
   id: string;
   source: string;
@@ -201,7 +238,59 @@ export interface WorkflowExecutionLog {
   timestamp: number;
 }
 
-export type LogEventType = 'started' | 'completed' | 'failed' | 'skipped';
+export type LogEventType = 'started' | 'completed' | 'failed' | 'skipped' | 'branch';
+
+/** Where a dry run took a step's outputs from */
+export type OutputSource = 'cached' | 'sample';
+
+export interface SimulatedStep {
+  node_id: string;
+  label: string;
+  node_type: WorkflowNode['type'];
+  /** Differs from the simulated workflow for steps of a sub-workflow */
+  workflow_id: string;
+  iteration: number | null;
+  branch: string | null;
+  /** Iterations a loop node is predicted to run */
+  iterations: number | null;
+  outputs: Record<string, any>;
+  output_source: OutputSource | null;
+  estimated_duration_ms: number;
+  estimated_cost: number;
+  /** Reasons the prediction may not hold */
+  notes: string[];
+}
+
+/** Predicted run from `execute_workflow` with `simulate: true` */
+export interface WorkflowSimulation {
+  workflow_id: string;
+  steps: SimulatedStep[];
+  estimated_duration_ms: number;
+  estimated_cost: number;
+  unreached_nodes: string[];
+  error: string | null;
+  failed_node_id: string | null;
+  variables: Record<string, any>;
+}
+
+export type ArtifactKind = 'report' | 'export' | 'screenshot' | 'file';
+
+export interface WorkflowArtifact {
+  id: string;
+  execution_id: string;
+  workflow_id: string;
+  node_id: string;
+  log_id: string | null;
+  name: string;
+  kind: ArtifactKind;
+  mime_type: string | null;
+  size_bytes: number;
+  content_hash: string;
+  metadata: Record<string, any>;
+  path: string;
+  created_at: number;
+  expires_at: number | null;
+}
 
 export interface ScheduledWorkflow {
   workflow_id: string;
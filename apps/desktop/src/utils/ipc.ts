@@ -1,4 +1,4 @@
-import { invoke as tauriInvoke } from '@tauri-apps/api/core';
+import { invoke as tauriInvoke } from '../lib/authInvoke';
 
 type Json = Record<string, unknown> | unknown[] | string | number | boolean | null;
 